@@ -0,0 +1,46 @@
+//! JWT 签名密钥轮换（`rotate-jwt-secret`）
+//!
+//! 生成新的随机密钥（32 字节，十六进制编码）。写入 `--output` 指定的文件后，
+//! 将 `EMS_JWT_SECRET_FILE` 指向该文件并重启服务即可生效；
+//! 旧密钥签发的 access / refresh token 随之失效，用户需重新登录。
+
+use std::path::Path;
+
+use rand_core::{OsRng, RngCore};
+
+/// 密钥字节数
+const SECRET_BYTES: usize = 32;
+
+/// 生成新密钥
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; SECRET_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// 写入密钥文件（Unix 下权限为 0600）
+pub fn write_secret(path: &Path, secret: &str) -> std::io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    std::io::Write::write_all(&mut file, format!("{secret}\n").as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_secrets_are_random_hex() {
+        let first = generate_secret();
+        let second = generate_secret();
+        assert_eq!(first.len(), SECRET_BYTES * 2);
+        assert!(first.chars().all(|ch| ch.is_ascii_hexdigit()));
+        assert_ne!(first, second);
+    }
+}
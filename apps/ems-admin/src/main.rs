@@ -0,0 +1,213 @@
+//! # EMS 管理命令行工具（ems-admin）
+//!
+//! 常见运维操作的命令行入口，替代手写 SQL：
+//!
+//! ```text
+//! ems-admin migrate                                  执行数据库迁移（顺序同 scripts/db-init.sh）
+//! ems-admin seed-demo                                写入演示数据（002_seed.sql + 演示租户/资产/历史数据）
+//! ems-admin create-tenant <tenant_id> <name>         创建租户及 admin 角色
+//! ems-admin create-admin <tenant_id> <username>      创建租户管理员账号
+//! ems-admin reset-password <tenant_id> <username>    重置口令并撤销 refresh token
+//! ems-admin rotate-jwt-secret [--output <path>]      生成新的 JWT 签名密钥
+//! ```
+//!
+//! 数据库连接串与服务端一致（`EMS_DATABASE_URL`、`EMS_CONFIG_FILE` 等，支持 `.env`）。
+//! 口令从 `EMS_ADMIN_PASSWORD` 读取，未设置时从标准输入读取一行（避免出现在 shell 历史中）。
+
+mod jwt;
+mod migrations;
+mod seed;
+mod tenant;
+
+use std::io::BufRead;
+use std::path::PathBuf;
+
+use ems_config::AppConfig;
+use ems_storage::connect_pool;
+use sqlx::PgPool;
+
+/// 口令环境变量
+const PASSWORD_ENV: &str = "EMS_ADMIN_PASSWORD";
+
+const USAGE: &str = "usage: ems-admin <command>
+
+commands:
+  migrate                                  apply database migrations
+  seed-demo                                insert demo tenants, assets, history and commands
+  create-tenant <tenant_id> <name>         create a tenant with an admin role
+  create-admin <tenant_id> <username>      create a tenant administrator
+  reset-password <tenant_id> <username>    reset a password and revoke refresh tokens
+  rotate-jwt-secret [--output <path>]      generate a new JWT signing secret
+
+passwords are read from EMS_ADMIN_PASSWORD, or from stdin when unset";
+
+/// 子命令
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+    Migrate,
+    SeedDemo,
+    CreateTenant { tenant_id: String, name: String },
+    CreateAdmin { tenant_id: String, username: String },
+    ResetPassword { tenant_id: String, username: String },
+    RotateJwtSecret { output: Option<PathBuf> },
+}
+
+fn parse(args: &[String]) -> Result<Command, String> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        ["migrate"] => Ok(Command::Migrate),
+        ["seed-demo"] => Ok(Command::SeedDemo),
+        ["create-tenant", tenant_id, name] => Ok(Command::CreateTenant {
+            tenant_id: tenant_id.to_string(),
+            name: name.to_string(),
+        }),
+        ["create-admin", tenant_id, username] => Ok(Command::CreateAdmin {
+            tenant_id: tenant_id.to_string(),
+            username: username.to_string(),
+        }),
+        ["reset-password", tenant_id, username] => Ok(Command::ResetPassword {
+            tenant_id: tenant_id.to_string(),
+            username: username.to_string(),
+        }),
+        ["rotate-jwt-secret"] => Ok(Command::RotateJwtSecret { output: None }),
+        ["rotate-jwt-secret", "--output", path] => Ok(Command::RotateJwtSecret {
+            output: Some(PathBuf::from(path)),
+        }),
+        [] => Err("missing command".to_string()),
+        [command, ..] => Err(format!("invalid arguments for `{command}`")),
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    dotenvy::dotenv().ok();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let command = match parse(&args) {
+        Ok(command) => command,
+        Err(err) => {
+            eprintln!("error: {err}\n\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+    if let Err(err) = run(command).await {
+        eprintln!("error: {err}");
+        std::process::exit(1);
+    }
+}
+
+async fn run(command: Command) -> Result<(), String> {
+    match command {
+        Command::Migrate => migrations::migrate(&connect().await?)
+            .await
+            .map_err(|err| err.to_string()),
+        Command::SeedDemo => {
+            let pool = connect().await?;
+            migrations::seed_demo(&pool)
+                .await
+                .map_err(|err| err.to_string())?;
+            seed::seed_demo(pool).await
+        }
+        Command::CreateTenant { tenant_id, name } => {
+            tenant::create_tenant(&connect().await?, &tenant_id, &name).await?;
+            println!("tenant {tenant_id} created");
+            Ok(())
+        }
+        Command::CreateAdmin {
+            tenant_id,
+            username,
+        } => {
+            let password = read_password()?;
+            let user_id =
+                tenant::create_admin(&connect().await?, &tenant_id, &username, &password).await?;
+            println!("admin {username} created (user_id {user_id})");
+            Ok(())
+        }
+        Command::ResetPassword {
+            tenant_id,
+            username,
+        } => {
+            let password = read_password()?;
+            tenant::reset_password(&connect().await?, &tenant_id, &username, &password).await?;
+            println!("password for {username} reset; refresh tokens revoked");
+            Ok(())
+        }
+        Command::RotateJwtSecret { output } => {
+            let secret = jwt::generate_secret();
+            match output {
+                Some(path) => {
+                    jwt::write_secret(&path, &secret).map_err(|err| err.to_string())?;
+                    println!(
+                        "new JWT secret written to {}; point EMS_JWT_SECRET_FILE at it and restart ems-api",
+                        path.display()
+                    );
+                }
+                None => println!("{secret}"),
+            }
+            Ok(())
+        }
+    }
+}
+
+/// 按服务端配置连接数据库
+async fn connect() -> Result<PgPool, String> {
+    let config = AppConfig::load_unchecked().map_err(|err| err.to_string())?;
+    connect_pool(&config.database_url)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// 读取口令：优先 `EMS_ADMIN_PASSWORD`，否则读取标准输入第一行
+fn read_password() -> Result<String, String> {
+    let password = match std::env::var(PASSWORD_ENV) {
+        Ok(value) => value,
+        Err(_) => {
+            eprintln!("password (from stdin):");
+            let mut line = String::new();
+            std::io::stdin()
+                .lock()
+                .read_line(&mut line)
+                .map_err(|err| err.to_string())?;
+            line.trim_end_matches(['\r', '\n']).to_string()
+        }
+    };
+    if password.trim().is_empty() {
+        return Err("password is empty".to_string());
+    }
+    Ok(password)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_commands_and_reject_bad_arguments() {
+        assert_eq!(parse(&args(&["migrate"])), Ok(Command::Migrate));
+        assert_eq!(
+            parse(&args(&["create-tenant", "tenant-3", "Third Tenant"])),
+            Ok(Command::CreateTenant {
+                tenant_id: "tenant-3".to_string(),
+                name: "Third Tenant".to_string(),
+            })
+        );
+        assert_eq!(
+            parse(&args(&[
+                "rotate-jwt-secret",
+                "--output",
+                "/run/secrets/jwt"
+            ])),
+            Ok(Command::RotateJwtSecret {
+                output: Some(PathBuf::from("/run/secrets/jwt")),
+            })
+        );
+        assert!(parse(&args(&[])).is_err());
+        assert_eq!(
+            parse(&args(&["create-admin", "tenant-3"])),
+            Err("invalid arguments for `create-admin`".to_string())
+        );
+    }
+}
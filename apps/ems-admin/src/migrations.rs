@@ -0,0 +1,215 @@
+//! 数据库迁移（`ems-admin migrate` / `ems-admin seed-demo`）
+//!
+//! 迁移文件编译期嵌入，执行顺序与 `scripts/db-init.sh` 一致；
+//! 所有迁移均为幂等脚本（`IF NOT EXISTS` / `ON CONFLICT DO NOTHING`），可重复执行。
+
+use sqlx::PgPool;
+
+/// 迁移文件（按执行顺序）
+pub const MIGRATIONS: &[(&str, &str)] = &[
+    (
+        "001_init.sql",
+        include_str!("../../../migrations/001_init.sql"),
+    ),
+    (
+        "003_assets.sql",
+        include_str!("../../../migrations/003_assets.sql"),
+    ),
+    (
+        "004_timescale.sql",
+        include_str!("../../../migrations/004_timescale.sql"),
+    ),
+    (
+        "005_control.sql",
+        include_str!("../../../migrations/005_control.sql"),
+    ),
+    (
+        "006_rbac.sql",
+        include_str!("../../../migrations/006_rbac.sql"),
+    ),
+    (
+        "007_auth_sessions.sql",
+        include_str!("../../../migrations/007_auth_sessions.sql"),
+    ),
+    (
+        "008_building_hierarchy_protocol.sql",
+        include_str!("../../../migrations/008_building_hierarchy_protocol.sql"),
+    ),
+    (
+        "009_device_templates.sql",
+        include_str!("../../../migrations/009_device_templates.sql"),
+    ),
+    (
+        "010_gateway_configs.sql",
+        include_str!("../../../migrations/010_gateway_configs.sql"),
+    ),
+    (
+        "011_point_tags.sql",
+        include_str!("../../../migrations/011_point_tags.sql"),
+    ),
+    (
+        "012_webhooks.sql",
+        include_str!("../../../migrations/012_webhooks.sql"),
+    ),
+    (
+        "013_idempotency_keys.sql",
+        include_str!("../../../migrations/013_idempotency_keys.sql"),
+    ),
+    (
+        "014_ops_metrics_permission.sql",
+        include_str!("../../../migrations/014_ops_metrics_permission.sql"),
+    ),
+    (
+        "015_ops_config_permission.sql",
+        include_str!("../../../migrations/015_ops_config_permission.sql"),
+    ),
+    (
+        "016_feature_flags.sql",
+        include_str!("../../../migrations/016_feature_flags.sql"),
+    ),
+    (
+        "017_device_shadows.sql",
+        include_str!("../../../migrations/017_device_shadows.sql"),
+    ),
+    (
+        "018_automation_rules.sql",
+        include_str!("../../../migrations/018_automation_rules.sql"),
+    ),
+    (
+        "019_control_schedules.sql",
+        include_str!("../../../migrations/019_control_schedules.sql"),
+    ),
+    (
+        "020_demand_response.sql",
+        include_str!("../../../migrations/020_demand_response.sql"),
+    ),
+    (
+        "021_firmware.sql",
+        include_str!("../../../migrations/021_firmware.sql"),
+    ),
+    (
+        "022_maintenance.sql",
+        include_str!("../../../migrations/022_maintenance.sql"),
+    ),
+    (
+        "023_anomalies.sql",
+        include_str!("../../../migrations/023_anomalies.sql"),
+    ),
+    (
+        "024_emission_factors.sql",
+        include_str!("../../../migrations/024_emission_factors.sql"),
+    ),
+    (
+        "025_usage_quotas.sql",
+        include_str!("../../../migrations/025_usage_quotas.sql"),
+    ),
+    (
+        "026_portfolios.sql",
+        include_str!("../../../migrations/026_portfolios.sql"),
+    ),
+    (
+        "027_share_tokens.sql",
+        include_str!("../../../migrations/027_share_tokens.sql"),
+    ),
+    (
+        "028_data_write_permission.sql",
+        include_str!("../../../migrations/028_data_write_permission.sql"),
+    ),
+    (
+        "029_audit_chain.sql",
+        include_str!("../../../migrations/029_audit_chain.sql"),
+    ),
+    (
+        "030_point_mapping_unique_address.sql",
+        include_str!("../../../migrations/030_point_mapping_unique_address.sql"),
+    ),
+    (
+        "031_device_offline_threshold.sql",
+        include_str!("../../../migrations/031_device_offline_threshold.sql"),
+    ),
+    (
+        "032_gateway_tokens.sql",
+        include_str!("../../../migrations/032_gateway_tokens.sql"),
+    ),
+    (
+        "033_device_events.sql",
+        include_str!("../../../migrations/033_device_events.sql"),
+    ),
+    (
+        "034_asset_updated_at.sql",
+        include_str!("../../../migrations/034_asset_updated_at.sql"),
+    ),
+    (
+        "035_jobs.sql",
+        include_str!("../../../migrations/035_jobs.sql"),
+    ),
+    (
+        "036_gateway_command_format.sql",
+        include_str!("../../../migrations/036_gateway_command_format.sql"),
+    ),
+    (
+        "037_measurement_primary_key.sql",
+        include_str!("../../../migrations/037_measurement_primary_key.sql"),
+    ),
+    (
+        "038_idempotency_response_headers.sql",
+        include_str!("../../../migrations/038_idempotency_response_headers.sql"),
+    ),
+    (
+        "039_revoke_ops_config_permission.sql",
+        include_str!("../../../migrations/039_revoke_ops_config_permission.sql"),
+    ),
+    (
+        "040_revoke_feature_flag_write.sql",
+        include_str!("../../../migrations/040_revoke_feature_flag_write.sql"),
+    ),
+    (
+        "041_revoke_usage_quota_write.sql",
+        include_str!("../../../migrations/041_revoke_usage_quota_write.sql"),
+    ),
+];
+
+/// 演示数据（默认租户、项目、管理员账号与角色权限）
+pub const SEED: (&str, &str) = (
+    "002_seed.sql",
+    include_str!("../../../migrations/002_seed.sql"),
+);
+
+/// 按顺序执行全部迁移
+pub async fn migrate(pool: &PgPool) -> Result<(), sqlx::Error> {
+    for (name, sql) in MIGRATIONS {
+        apply(pool, name, sql).await?;
+    }
+    Ok(())
+}
+
+/// 写入演示数据（需先执行迁移）
+pub async fn seed_demo(pool: &PgPool) -> Result<(), sqlx::Error> {
+    let (name, sql) = SEED;
+    apply(pool, name, sql).await
+}
+
+async fn apply(pool: &PgPool, name: &str, sql: &str) -> Result<(), sqlx::Error> {
+    sqlx::raw_sql(sql).execute(pool).await?;
+    println!("[ok]   {name}");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 迁移顺序与 scripts/db-init.sh 保持一致（种子数据最后执行）
+    #[test]
+    fn migrations_follow_db_init_script() {
+        let script = include_str!("../../../scripts/db-init.sh");
+        let scripted: Vec<&str> = script
+            .lines()
+            .filter_map(|line| line.split("-f migrations/").nth(1))
+            .map(str::trim)
+            .collect();
+        let mut expected: Vec<&str> = MIGRATIONS.iter().map(|(name, _)| *name).collect();
+        expected.push(SEED.0);
+        assert_eq!(scripted, expected);
+    }
+}
@@ -0,0 +1,38 @@
+//! 演示数据生成（`seed-demo`）：以 Postgres 存储调用 `ems-seed`。
+
+use std::sync::Arc;
+
+use ems_seed::{DEMO_PASSWORD, DEMO_TENANT_ID, DEMO_USERNAME, SeedOptions, SeedStores};
+use ems_storage::{
+    PgCommandStore, PgDeviceStore, PgGatewayStore, PgMeasurementStore, PgPointMappingStore,
+    PgPointStore, PgProjectStore, PgTenantStore, PgUserStore,
+};
+use sqlx::PgPool;
+
+/// 写入演示租户、资产、历史数据与示例命令（演示项目已存在时跳过）
+pub async fn seed_demo(pool: PgPool) -> Result<(), String> {
+    let stores = SeedStores {
+        tenant_store: Arc::new(PgTenantStore::new(pool.clone())),
+        rbac_store: Arc::new(PgUserStore::new(pool.clone())),
+        project_store: Arc::new(PgProjectStore::new(pool.clone())),
+        gateway_store: Arc::new(PgGatewayStore::new(pool.clone())),
+        device_store: Arc::new(PgDeviceStore::new(pool.clone())),
+        point_store: Arc::new(PgPointStore::new(pool.clone())),
+        point_mapping_store: Arc::new(PgPointMappingStore::new(pool.clone())),
+        measurement_store: Arc::new(PgMeasurementStore::new(pool.clone())),
+        command_store: Arc::new(PgCommandStore::new(pool)),
+    };
+    let summary = ems_seed::seed_demo(&stores, &SeedOptions::default())
+        .await
+        .map_err(|err| err.to_string())?;
+    if !summary.created {
+        println!("[skip] demo project already exists");
+        return Ok(());
+    }
+    println!(
+        "[ok]   demo data: {} gateways, {} devices, {} points, {} measurements, {} commands",
+        summary.gateways, summary.devices, summary.points, summary.measurements, summary.commands
+    );
+    println!("login: tenant {DEMO_TENANT_ID}, user {DEMO_USERNAME} / {DEMO_PASSWORD}");
+    Ok(())
+}
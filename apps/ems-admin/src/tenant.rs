@@ -0,0 +1,107 @@
+//! 租户与账号管理（`create-tenant` / `create-admin` / `reset-password`）
+//!
+//! 租户、用户与角色均通过存储层 `TenantStore` / `RbacStore` / `UserStore` 操作。
+
+use domain::{TenantContext, permissions};
+use ems_auth::hash_password;
+use ems_storage::{
+    PgTenantStore, PgUserStore, RbacRoleCreate, RbacStore, RbacUserCreate, TenantStore, UserStore,
+};
+use sqlx::PgPool;
+
+/// 新租户默认创建的管理员角色
+const ADMIN_ROLE_NAME: &str = "Administrator";
+
+/// 以租户管理员身份构造上下文（管理工具不经过登录）
+fn admin_ctx(tenant_id: &str) -> TenantContext {
+    TenantContext::new(
+        tenant_id,
+        "ems-admin",
+        vec![permissions::ROLE_ADMIN.to_string()],
+        permissions::PERMISSION_CODES
+            .iter()
+            .map(|code| code.to_string())
+            .collect(),
+        None,
+    )
+}
+
+/// 创建租户及其 admin 角色（拥有全部权限码）
+pub async fn create_tenant(pool: &PgPool, tenant_id: &str, name: &str) -> Result<(), String> {
+    let ctx = admin_ctx(tenant_id);
+    let created = PgTenantStore::new(pool.clone())
+        .create_tenant(&ctx, name)
+        .await
+        .map_err(|err| err.to_string())?;
+    if !created {
+        return Err(format!("tenant {tenant_id} already exists"));
+    }
+
+    let store = PgUserStore::new(pool.clone());
+    store
+        .create_role(
+            &ctx,
+            RbacRoleCreate {
+                tenant_id: tenant_id.to_string(),
+                role_code: permissions::ROLE_ADMIN.to_string(),
+                name: ADMIN_ROLE_NAME.to_string(),
+                permissions: ctx.permissions.clone(),
+            },
+        )
+        .await
+        .map_err(|err| err.to_string())?;
+    Ok(())
+}
+
+/// 创建租户管理员账号，返回 user_id
+pub async fn create_admin(
+    pool: &PgPool,
+    tenant_id: &str,
+    username: &str,
+    password: &str,
+) -> Result<String, String> {
+    let store = PgUserStore::new(pool.clone());
+    let ctx = admin_ctx(tenant_id);
+    let password_hash = hash_password(password).map_err(|err| err.to_string())?;
+    let created = store
+        .create_user(
+            &ctx,
+            RbacUserCreate {
+                user_id: uuid::Uuid::new_v4().to_string(),
+                tenant_id: tenant_id.to_string(),
+                username: username.to_string(),
+                password: password_hash,
+                status: "active".to_string(),
+                roles: vec![permissions::ROLE_ADMIN.to_string()],
+            },
+        )
+        .await
+        .map_err(|err| err.to_string())?;
+    Ok(created.user_id)
+}
+
+/// 重置口令并撤销该用户的 refresh token
+pub async fn reset_password(
+    pool: &PgPool,
+    tenant_id: &str,
+    username: &str,
+    password: &str,
+) -> Result<(), String> {
+    let store = PgUserStore::new(pool.clone());
+    let ctx = admin_ctx(tenant_id);
+    let user = store
+        .find_by_username(&ctx, username)
+        .await
+        .map_err(|err| err.to_string())?
+        .ok_or_else(|| format!("user {username} not found in tenant {tenant_id}"))?;
+    let password_hash = hash_password(password).map_err(|err| err.to_string())?;
+    store
+        .update_password_hash(&ctx, &user.user_id, &password_hash)
+        .await
+        .map_err(|err| err.to_string())?;
+    store
+        .set_refresh_jti(&ctx, &user.user_id, None)
+        .await
+        .map_err(|err| err.to_string())?;
+    Ok(())
+}
@@ -0,0 +1,18 @@
+//! 构建脚本：由 proto/ems/v1/ems.proto 生成 gRPC 服务端代码。
+//!
+//! 使用 protoc-bin-vendored 内置的 protoc，构建环境无需安装 protobuf 编译器；
+//! 如需使用系统 protoc，可显式设置 `PROTOC` 环境变量覆盖。
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    if std::env::var_os("PROTOC").is_none() {
+        // SAFETY: 构建脚本为单线程进程，设置环境变量不存在并发读写。
+        unsafe {
+            std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+        }
+    }
+    println!("cargo:rerun-if-changed=proto/ems/v1/ems.proto");
+    tonic_build::configure()
+        .build_client(false)
+        .compile_protos(&["proto/ems/v1/ems.proto"], &["proto"])?;
+    Ok(())
+}
@@ -0,0 +1,93 @@
+//! 启动前配置自检（`ems-api --check-config`）
+//!
+//! 部署前在目标环境执行，一次性列出全部问题后退出，不启动任何监听：
+//! - 配置读取（配置文件 / 环境变量）与语义校验（端口、QoS、TTL、必填项）
+//! - Postgres / Redis 连通性
+//! - 启用采集或控制时的 MQTT Broker TCP 连通性
+//!
+//! 全部通过返回退出码 0，否则返回 1。
+
+use std::time::Duration;
+
+use ems_config::AppConfig;
+use ems_storage::{check_postgres, check_redis};
+use tokio::net::TcpStream;
+
+/// 命令行开关
+pub const CHECK_CONFIG_FLAG: &str = "--check-config";
+
+/// 连通性探测超时
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 单项检查结果
+struct CheckResult {
+    name: &'static str,
+    error: Option<String>,
+}
+
+impl CheckResult {
+    fn new(name: &'static str, result: Result<(), String>) -> Self {
+        Self {
+            name,
+            error: result.err(),
+        }
+    }
+}
+
+/// 执行自检并打印报告，返回是否全部通过
+pub async fn run() -> bool {
+    let results = match AppConfig::load_unchecked() {
+        Ok(config) => check(&config).await,
+        Err(err) => vec![CheckResult::new("config", Err(err.to_string()))],
+    };
+    let mut passed = true;
+    for result in &results {
+        match &result.error {
+            None => println!("[ok]   {}", result.name),
+            Some(error) => {
+                passed = false;
+                println!("[fail] {}: {error}", result.name);
+            }
+        }
+    }
+    passed
+}
+
+async fn check(config: &AppConfig) -> Vec<CheckResult> {
+    let problems = config.validate();
+    let mut results = vec![CheckResult::new(
+        "config",
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems.join("; "))
+        },
+    )];
+
+    results.push(CheckResult::new(
+        "postgres",
+        probe(check_postgres(&config.database_url)).await,
+    ));
+    results.push(CheckResult::new(
+        "redis",
+        probe(check_redis(&config.redis_url)).await,
+    ));
+    if config.ingest_enabled || config.control_enabled {
+        let addr = format!("{}:{}", config.mqtt_host, config.mqtt_port);
+        results.push(CheckResult::new(
+            "mqtt",
+            probe(async { TcpStream::connect(&addr).await.map(|_| ()) }).await,
+        ));
+    }
+    results
+}
+
+async fn probe<E: std::fmt::Display>(
+    future: impl std::future::Future<Output = Result<(), E>>,
+) -> Result<(), String> {
+    match tokio::time::timeout(PROBE_TIMEOUT, future).await {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(err)) => Err(err.to_string()),
+        Err(_) => Err(format!("timed out after {}s", PROBE_TIMEOUT.as_secs())),
+    }
+}
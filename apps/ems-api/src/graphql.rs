@@ -0,0 +1,513 @@
+//! GraphQL schema（资产层级 + 数据导航）
+//!
+//! 面向看板搭建场景，一次往返按需获取：
+//! project → gateways → devices → points → lastValue / measurements
+//!
+//! 权限模型：
+//! - 请求级：Bearer token 认证（handler 中完成），TenantContext 注入 schema data
+//! - 项目级：进入 project 时校验项目归属，子节点沿用带 project_scope 的上下文
+//! - 字段级：每个字段通过 `RequirePermission` guard 校验权限码，
+//!   无权限时字段返回 null 并附带 `AUTH.FORBIDDEN` 错误，其余字段不受影响
+
+use crate::AppState;
+use crate::middleware::has_permission;
+use api_contract::error_codes;
+use async_graphql::{
+    Context, EmptyMutation, EmptySubscription, Enum, ErrorExtensions, Guard, Object, Result,
+    Schema, SimpleObject,
+};
+use domain::{TenantContext, permissions};
+use ems_storage::{
+    DeviceRecord, GatewayRecord, MeasurementAggFn, MeasurementAggregation,
+    MeasurementsQueryOptions, PointRecord, ProjectRecord, StorageError, StorageErrorKind,
+    TimeOrder,
+};
+use std::sync::OnceLock;
+
+/// 查询最大嵌套深度
+const MAX_DEPTH: usize = 10;
+/// 查询最大复杂度（字段数加权）
+const MAX_COMPLEXITY: usize = 2000;
+/// measurements 默认/最大返回条数
+const DEFAULT_MEASUREMENT_LIMIT: i32 = 1000;
+const MAX_MEASUREMENT_LIMIT: i32 = 5000;
+
+pub type EmsSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// 全局 schema（无状态，按请求注入 AppState 与 TenantContext）
+pub fn schema() -> &'static EmsSchema {
+    static SCHEMA: OnceLock<EmsSchema> = OnceLock::new();
+    SCHEMA.get_or_init(|| {
+        Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+            .limit_depth(MAX_DEPTH)
+            .limit_complexity(MAX_COMPLEXITY)
+            .finish()
+    })
+}
+
+/// 字段级权限 guard
+struct RequirePermission(&'static str);
+
+impl Guard for RequirePermission {
+    async fn check(&self, ctx: &Context<'_>) -> Result<()> {
+        let tenant = ctx.data::<TenantContext>()?;
+        if has_permission(tenant, self.0) {
+            Ok(())
+        } else {
+            Err(forbidden())
+        }
+    }
+}
+
+fn forbidden() -> async_graphql::Error {
+    async_graphql::Error::new("forbidden")
+        .extend_with(|_, e| e.set("code", error_codes::AUTH_FORBIDDEN))
+}
+
+fn invalid(message: &str) -> async_graphql::Error {
+    async_graphql::Error::new(message)
+        .extend_with(|_, e| e.set("code", error_codes::INVALID_REQUEST))
+}
+
+fn storage_failure(err: StorageError) -> async_graphql::Error {
+    tracing::error!(error = %err, "storage error");
+    let (message, code) = match err.kind() {
+        StorageErrorKind::NotFound => ("not found", error_codes::RESOURCE_NOT_FOUND),
+        StorageErrorKind::Conflict => ("resource already exists", error_codes::RESOURCE_CONFLICT),
+        StorageErrorKind::Forbidden => ("forbidden", error_codes::AUTH_FORBIDDEN),
+        StorageErrorKind::Unavailable => ("service unavailable", error_codes::SERVICE_UNAVAILABLE),
+        StorageErrorKind::Internal => ("internal error", error_codes::INTERNAL_ERROR),
+    };
+    async_graphql::Error::new(message).extend_with(|_, e| e.set("code", code))
+}
+
+/// 子节点使用的项目级上下文（项目归属已在 project 解析时校验）
+fn scoped_ctx(ctx: &Context<'_>, project_id: &str) -> Result<TenantContext> {
+    let mut tenant = ctx.data::<TenantContext>()?.clone();
+    tenant.project_scope = Some(project_id.to_string());
+    Ok(tenant)
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// 当前租户下的项目列表
+    #[graphql(guard = "RequirePermission(permissions::PROJECT_READ)")]
+    async fn projects(&self, ctx: &Context<'_>) -> Result<Vec<Project>> {
+        let state = ctx.data::<AppState>()?;
+        let tenant = ctx.data::<TenantContext>()?;
+        let projects = state
+            .project_store
+            .list_projects(tenant)
+            .await
+            .map_err(storage_failure)?;
+        Ok(projects.into_iter().map(Project).collect())
+    }
+
+    /// 按 ID 查询项目（校验项目归属）
+    #[graphql(guard = "RequirePermission(permissions::PROJECT_READ)")]
+    async fn project(&self, ctx: &Context<'_>, project_id: String) -> Result<Option<Project>> {
+        let state = ctx.data::<AppState>()?;
+        let tenant = ctx.data::<TenantContext>()?;
+        let belongs = state
+            .project_store
+            .project_belongs_to_tenant(tenant, &project_id)
+            .await
+            .map_err(storage_failure)?;
+        if !belongs {
+            return Err(forbidden());
+        }
+        let project = state
+            .project_store
+            .find_project(tenant, &project_id)
+            .await
+            .map_err(storage_failure)?;
+        Ok(project.map(Project))
+    }
+}
+
+pub struct Project(ProjectRecord);
+
+#[Object]
+impl Project {
+    async fn project_id(&self) -> &str {
+        &self.0.project_id
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn timezone(&self) -> &str {
+        &self.0.timezone
+    }
+
+    #[graphql(guard = "RequirePermission(permissions::ASSET_GATEWAY_READ)")]
+    async fn gateways(&self, ctx: &Context<'_>) -> Result<Vec<Gateway>> {
+        let state = ctx.data::<AppState>()?;
+        let tenant = scoped_ctx(ctx, &self.0.project_id)?;
+        let gateways = state
+            .gateway_store
+            .list_gateways(&tenant, &self.0.project_id)
+            .await
+            .map_err(storage_failure)?;
+        Ok(gateways.into_iter().map(Gateway).collect())
+    }
+
+    #[graphql(guard = "RequirePermission(permissions::ASSET_DEVICE_READ)")]
+    async fn devices(&self, ctx: &Context<'_>) -> Result<Vec<Device>> {
+        load_devices(ctx, &self.0.project_id, None).await
+    }
+
+    /// 项目内点位，可按设备 / 标签过滤
+    #[graphql(guard = "RequirePermission(permissions::ASSET_POINT_READ)")]
+    async fn points(
+        &self,
+        ctx: &Context<'_>,
+        device_id: Option<String>,
+        tag: Option<String>,
+    ) -> Result<Vec<Point>> {
+        load_points(
+            ctx,
+            &self.0.project_id,
+            device_id.as_deref(),
+            tag.as_deref(),
+        )
+        .await
+    }
+}
+
+pub struct Gateway(GatewayRecord);
+
+#[Object]
+impl Gateway {
+    async fn gateway_id(&self) -> &str {
+        &self.0.gateway_id
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn status(&self) -> &str {
+        &self.0.status
+    }
+
+    async fn protocol_type(&self) -> &str {
+        &self.0.protocol_type
+    }
+
+    #[graphql(guard = "RequirePermission(permissions::ASSET_DEVICE_READ)")]
+    async fn devices(&self, ctx: &Context<'_>) -> Result<Vec<Device>> {
+        load_devices(ctx, &self.0.project_id, Some(&self.0.gateway_id)).await
+    }
+}
+
+pub struct Device(DeviceRecord);
+
+#[Object]
+impl Device {
+    async fn device_id(&self) -> &str {
+        &self.0.device_id
+    }
+
+    async fn gateway_id(&self) -> &str {
+        &self.0.gateway_id
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn model(&self) -> Option<&str> {
+        self.0.model.as_deref()
+    }
+
+    async fn room_id(&self) -> Option<&str> {
+        self.0.room_id.as_deref()
+    }
+
+    #[graphql(guard = "RequirePermission(permissions::ASSET_POINT_READ)")]
+    async fn points(&self, ctx: &Context<'_>, tag: Option<String>) -> Result<Vec<Point>> {
+        load_points(
+            ctx,
+            &self.0.project_id,
+            Some(&self.0.device_id),
+            tag.as_deref(),
+        )
+        .await
+    }
+}
+
+pub struct Point(PointRecord);
+
+#[Object]
+impl Point {
+    async fn point_id(&self) -> &str {
+        &self.0.point_id
+    }
+
+    async fn device_id(&self) -> &str {
+        &self.0.device_id
+    }
+
+    async fn key(&self) -> &str {
+        &self.0.key
+    }
+
+    async fn data_type(&self) -> &str {
+        &self.0.data_type
+    }
+
+    async fn unit(&self) -> Option<&str> {
+        self.0.unit.as_deref()
+    }
+
+    async fn tags(&self) -> &[String] {
+        &self.0.tags
+    }
+
+    /// 最新值
+    #[graphql(guard = "RequirePermission(permissions::DATA_REALTIME_READ)")]
+    async fn last_value(&self, ctx: &Context<'_>) -> Result<Option<DataValue>> {
+        let state = ctx.data::<AppState>()?;
+        let tenant = scoped_ctx(ctx, &self.0.project_id)?;
+        let record = state
+            .realtime_store
+            .get_last_value(&tenant, &self.0.project_id, &self.0.point_id)
+            .await
+            .map_err(storage_failure)?;
+        Ok(record.map(|record| DataValue {
+            ts_ms: record.ts_ms,
+            value: record.value,
+            quality: record.quality,
+        }))
+    }
+
+    /// 历史序列（提供 bucketMs 时返回聚合结果，tsMs 为桶起始）
+    #[graphql(guard = "RequirePermission(permissions::DATA_MEASUREMENTS_READ)")]
+    async fn measurements(
+        &self,
+        ctx: &Context<'_>,
+        from: Option<i64>,
+        to: Option<i64>,
+        limit: Option<i32>,
+        bucket_ms: Option<i64>,
+        agg: Option<AggFn>,
+        order: Option<SortOrder>,
+    ) -> Result<Vec<DataValue>> {
+        if let (Some(from), Some(to)) = (from, to) {
+            if from > to {
+                return Err(invalid("from must be <= to"));
+            }
+        }
+        let limit = limit.unwrap_or(DEFAULT_MEASUREMENT_LIMIT);
+        if limit <= 0 || limit > MAX_MEASUREMENT_LIMIT {
+            return Err(invalid("limit out of range"));
+        }
+        let aggregation = match bucket_ms {
+            Some(bucket_ms) if bucket_ms <= 0 => return Err(invalid("bucketMs must be > 0")),
+            Some(bucket_ms) => Some(MeasurementAggregation {
+                bucket_ms,
+                func: agg.unwrap_or(AggFn::Avg).into(),
+                calendar: None,
+            }),
+            None if agg.is_some() => return Err(invalid("agg requires bucketMs")),
+            None => None,
+        };
+        let state = ctx.data::<AppState>()?;
+        let tenant = scoped_ctx(ctx, &self.0.project_id)?;
+        let items = state
+            .measurement_store
+            .query_measurements(
+                &tenant,
+                &self.0.project_id,
+                &self.0.point_id,
+                MeasurementsQueryOptions {
+                    from_ms: from,
+                    to_ms: to,
+                    cursor_ts_ms: None,
+                    order: match order.unwrap_or(SortOrder::Asc) {
+                        SortOrder::Asc => TimeOrder::Asc,
+                        SortOrder::Desc => TimeOrder::Desc,
+                    },
+                    limit: i64::from(limit),
+                    aggregation,
+                    timezone: chrono_tz::Tz::UTC,
+                },
+            )
+            .await
+            .map_err(storage_failure)?;
+        Ok(items
+            .into_iter()
+            .map(|record| DataValue {
+                ts_ms: record.ts_ms,
+                value: record.value,
+                quality: record.quality,
+            })
+            .collect())
+    }
+}
+
+/// 时间点上的测点值（最新值或历史序列元素）
+#[derive(SimpleObject)]
+pub struct DataValue {
+    ts_ms: i64,
+    value: String,
+    quality: Option<String>,
+}
+
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+pub enum AggFn {
+    Avg,
+    Min,
+    Max,
+    Sum,
+    Count,
+}
+
+impl From<AggFn> for MeasurementAggFn {
+    fn from(value: AggFn) -> Self {
+        match value {
+            AggFn::Avg => MeasurementAggFn::Avg,
+            AggFn::Min => MeasurementAggFn::Min,
+            AggFn::Max => MeasurementAggFn::Max,
+            AggFn::Sum => MeasurementAggFn::Sum,
+            AggFn::Count => MeasurementAggFn::Count,
+        }
+    }
+}
+
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+async fn load_devices(
+    ctx: &Context<'_>,
+    project_id: &str,
+    gateway_id: Option<&str>,
+) -> Result<Vec<Device>> {
+    let state = ctx.data::<AppState>()?;
+    let tenant = scoped_ctx(ctx, project_id)?;
+    let devices = state
+        .device_store
+        .list_devices(&tenant, project_id)
+        .await
+        .map_err(storage_failure)?;
+    Ok(devices
+        .into_iter()
+        .filter(|device| gateway_id.is_none_or(|gateway_id| device.gateway_id == gateway_id))
+        .map(Device)
+        .collect())
+}
+
+async fn load_points(
+    ctx: &Context<'_>,
+    project_id: &str,
+    device_id: Option<&str>,
+    tag: Option<&str>,
+) -> Result<Vec<Point>> {
+    let state = ctx.data::<AppState>()?;
+    let tenant = scoped_ctx(ctx, project_id)?;
+    let points = state
+        .point_store
+        .list_points(&tenant, project_id)
+        .await
+        .map_err(storage_failure)?;
+    Ok(points
+        .into_iter()
+        .filter(|point| device_id.is_none_or(|device_id| point.device_id == device_id))
+        .filter(|point| tag.is_none_or(|tag| point.tags.iter().any(|item| item == tag)))
+        .map(Point)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{auth_headers, build_state, project_ctx, response_json};
+    use axum::extract::State;
+    use axum::http::StatusCode;
+    use domain::{PointValue, PointValueData, TenantContext};
+
+    /// 测试：GraphQL 按需查询与字段级权限
+    ///
+    /// 验证一次查询可拿到项目 → 点位 → 最新值，且缺少权限码的字段返回 null + AUTH.FORBIDDEN。
+    #[tokio::test]
+    async fn graphql_queries_hierarchy_with_field_permissions() {
+        let state = build_state();
+        let ctx = project_ctx();
+        state
+            .point_store
+            .create_point(
+                &ctx,
+                ems_storage::PointRecord {
+                    point_id: "point-1".to_string(),
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    device_id: "device-1".to_string(),
+                    key: "power".to_string(),
+                    data_type: "f64".to_string(),
+                    unit: None,
+                    tags: vec!["energy".to_string()],
+                },
+            )
+            .await
+            .expect("create point");
+        state
+            .realtime_store
+            .upsert_last_value(
+                &ctx,
+                &PointValue {
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    point_id: "point-1".to_string(),
+                    ts_ms: 1_700_000_000_100,
+                    value: PointValueData::F64(23.45),
+                    quality: None,
+                },
+            )
+            .await
+            .expect("upsert last value");
+
+        let query = r#"{ project(projectId: "project-1") { name points(tag: "energy") { pointId lastValue { value } } } }"#;
+
+        // 具备全部权限：一次往返拿到层级与最新值
+        let headers = auth_headers(&state).await;
+        let response = crate::handlers::graphql_query(
+            State(state.clone()),
+            headers,
+            axum::Json(async_graphql::Request::new(query)),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        assert!(json.get("errors").is_none());
+        assert_eq!(
+            json["data"]["project"]["points"][0]["lastValue"]["value"],
+            "23.45"
+        );
+
+        // 缺少 DATA.REALTIME.READ：仅 lastValue 字段被拒绝
+        let limited = TenantContext::new(
+            "tenant-1".to_string(),
+            "user-1".to_string(),
+            Vec::new(),
+            vec![
+                domain::permissions::PROJECT_READ.to_string(),
+                domain::permissions::ASSET_POINT_READ.to_string(),
+            ],
+            None,
+        );
+        let response = schema()
+            .execute(async_graphql::Request::new(query).data(state).data(limited))
+            .await;
+        let json = serde_json::to_value(&response).expect("json");
+        assert_eq!(json["data"]["project"]["points"][0]["pointId"], "point-1");
+        assert!(json["data"]["project"]["points"][0]["lastValue"].is_null());
+        assert_eq!(json["errors"][0]["extensions"]["code"], "AUTH.FORBIDDEN");
+    }
+}
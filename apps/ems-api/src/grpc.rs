@@ -0,0 +1,604 @@
+//! gRPC 服务（与 Axum 并行运行）
+//!
+//! 面向需要二进制流式接入的集成方，提供：
+//! - WritePoints：批量写入测点值（与 REST `points/values` 共用采集流水线，需 DATA.WRITE）
+//! - StreamRealtime：服务端流式推送实时值变化
+//! - IssueCommand：下发控制命令
+//!
+//! WritePoints 写入的值刷新所属设备及其网关的在线状态（`EMS_ONLINE_FROM_DATA`）。
+//! WritePoints / IssueCommand 计入租户当日 `measurements` / `commands` 用量，
+//! 超出配额返回 `RESOURCE_EXHAUSTED`；租户关闭 `control` 开关时 IssueCommand 返回 `PERMISSION_DENIED`。
+//! 内部错误统一返回 `INTERNAL: internal error`（细节只写日志）；StreamRealtime 在 token 过期时
+//! 以 `UNAUTHENTICATED` 结束订阅。
+//!
+//! 认证与 REST 一致：metadata `authorization: Bearer <token>`，
+//! 每个 RPC 校验项目归属与权限码（租户隔离复用存储层 TenantContext）。
+//!
+//! 通过 `EMS_GRPC_ADDR` 启用，未配置时不启动。
+
+use crate::AppState;
+use crate::handlers::{WriteValuesError, write_values_to_pipeline};
+use crate::middleware::has_permission;
+use domain::{PointValue, PointValueData, TenantContext, permissions, usage};
+use ems_auth::AuthError;
+use ems_control::{CommandRequest, ControlError};
+use ems_pipeline::PipelineError;
+use ems_storage::{CommandRecord, RealtimeRecord};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::metadata::MetadataMap;
+use tonic::{Request, Response, Status};
+use tracing::{info, warn};
+
+/// 由 proto/ems/v1/ems.proto 生成的消息与服务定义
+pub mod pb {
+    #![allow(clippy::enum_variant_names)]
+    tonic::include_proto!("ems.v1");
+}
+
+use pb::ems_service_server::{EmsService, EmsServiceServer};
+use pb::point_sample::Value as SampleValue;
+
+/// 单次 WritePoints 允许的最大点位数
+const MAX_WRITE_POINTS: usize = 5000;
+/// StreamRealtime 默认/最小轮询间隔（毫秒）
+const DEFAULT_STREAM_INTERVAL_MS: u64 = 1000;
+const MIN_STREAM_INTERVAL_MS: u64 = 200;
+/// StreamRealtime 发送缓冲
+const STREAM_BUFFER: usize = 256;
+
+/// gRPC 服务实现，复用 HTTP 侧的 AppState。
+pub struct GrpcService {
+    state: AppState,
+}
+
+impl GrpcService {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+
+    /// 校验 token 与项目归属，返回带 project_scope 的租户上下文
+    async fn authorize(
+        &self,
+        metadata: &MetadataMap,
+        project_id: &str,
+        permission: &str,
+    ) -> Result<TenantContext, Status> {
+        self.authorize_with_expiry(metadata, project_id, permission)
+            .await
+            .map(|(ctx, _)| ctx)
+    }
+
+    /// 同 [`Self::authorize`]，另返回 token 过期时间（Unix 秒），供流式接口到期断开
+    async fn authorize_with_expiry(
+        &self,
+        metadata: &MetadataMap,
+        project_id: &str,
+        permission: &str,
+    ) -> Result<(TenantContext, u64), Status> {
+        if project_id.trim().is_empty() {
+            return Err(Status::invalid_argument("projectId is required"));
+        }
+        let token = metadata
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| Status::unauthenticated("unauthorized"))?;
+        let (mut ctx, expires_at) = match self.state.auth.verify_access_token_with_expiry(token) {
+            Ok(verified) => verified,
+            Err(AuthError::TokenInvalid | AuthError::TokenExpired) => {
+                return Err(Status::unauthenticated("unauthorized"));
+            }
+            Err(err) => {
+                tracing::error!(error = ?err, "internal auth error");
+                return Err(Status::internal("internal error"));
+            }
+        };
+        match self
+            .state
+            .project_store
+            .project_belongs_to_tenant(&ctx, project_id)
+            .await
+        {
+            Ok(true) => ctx.project_scope = Some(project_id.to_string()),
+            Ok(false) => return Err(Status::permission_denied("forbidden")),
+            Err(err) => return Err(storage_status(err)),
+        }
+        if !has_permission(&ctx, permission) {
+            return Err(Status::permission_denied("forbidden"));
+        }
+        Ok((ctx, expires_at))
+    }
+}
+
+#[tonic::async_trait]
+impl EmsService for GrpcService {
+    async fn write_points(
+        &self,
+        request: Request<pb::WritePointsRequest>,
+    ) -> Result<Response<pb::WritePointsResponse>, Status> {
+        let (metadata, _, req) = request.into_parts();
+        let ctx = self
+            .authorize(&metadata, &req.project_id, permissions::DATA_WRITE)
+            .await?;
+        if req.points.is_empty() {
+            return Ok(Response::new(pb::WritePointsResponse { written: 0 }));
+        }
+        if req.points.len() > MAX_WRITE_POINTS {
+            return Err(Status::invalid_argument("too many points"));
+        }
+
+        // 仅允许写入项目内已登记的点位，避免跨项目写入
+        let known: HashSet<String> = self
+            .state
+            .point_store
+            .list_points(&ctx, &req.project_id)
+            .await
+            .map_err(storage_status)?
+            .into_iter()
+            .map(|point| point.point_id)
+            .collect();
+        let mut values = Vec::with_capacity(req.points.len());
+        for sample in req.points {
+            if !known.contains(&sample.point_id) {
+                return Err(Status::not_found(format!(
+                    "point not found: {}",
+                    sample.point_id
+                )));
+            }
+            let value = match sample.value {
+                Some(SampleValue::IntValue(v)) => PointValueData::I64(v),
+                Some(SampleValue::DoubleValue(v)) => PointValueData::F64(v),
+                Some(SampleValue::BoolValue(v)) => PointValueData::Bool(v),
+                Some(SampleValue::StringValue(v)) => PointValueData::String(v),
+                None => return Err(Status::invalid_argument("value is required")),
+            };
+            values.push(PointValue {
+                tenant_id: ctx.tenant_id.clone(),
+                project_id: req.project_id.clone(),
+                point_id: sample.point_id,
+                ts_ms: sample.ts_ms,
+                value,
+                quality: sample.quality,
+            });
+        }
+
+        // 与 REST `points/values` 共用流水线：校验、去重、用量计量与在线状态刷新
+        let data =
+            write_values_to_pipeline(&self.state, &ctx, &req.project_id, values, now_epoch_ms())
+                .await
+                .map_err(write_values_status)?;
+        let written = data.accepted as u32;
+        Ok(Response::new(pb::WritePointsResponse { written }))
+    }
+
+    type StreamRealtimeStream = ReceiverStream<Result<pb::RealtimeValue, Status>>;
+
+    async fn stream_realtime(
+        &self,
+        request: Request<pb::StreamRealtimeRequest>,
+    ) -> Result<Response<Self::StreamRealtimeStream>, Status> {
+        let (metadata, _, req) = request.into_parts();
+        let (ctx, expires_at) = self
+            .authorize_with_expiry(&metadata, &req.project_id, permissions::DATA_REALTIME_READ)
+            .await?;
+        let interval_ms = req
+            .interval_ms
+            .map(u64::from)
+            .unwrap_or(DEFAULT_STREAM_INTERVAL_MS)
+            .max(MIN_STREAM_INTERVAL_MS);
+
+        // 订阅开始时解析点位集合；None 表示订阅项目内全部点位
+        let point_ids = if !req.point_ids.is_empty() {
+            Some(req.point_ids)
+        } else if req.device_id.is_some() || req.tag.is_some() {
+            let points = self
+                .state
+                .point_store
+                .list_points(&ctx, &req.project_id)
+                .await
+                .map_err(storage_status)?;
+            Some(
+                points
+                    .into_iter()
+                    .filter(|point| {
+                        req.device_id
+                            .as_deref()
+                            .is_none_or(|device_id| point.device_id == device_id)
+                    })
+                    .filter(|point| req.tag.as_ref().is_none_or(|tag| point.tags.contains(tag)))
+                    .map(|point| point.point_id)
+                    .collect(),
+            )
+        } else {
+            None
+        };
+
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+        let realtime_store = self.state.realtime_store.clone();
+        let project_id = req.project_id;
+        // token 到期即结束订阅（客户端需携带新 token 重新订阅）
+        let expires_in = Duration::from_secs(expires_at)
+            .saturating_sub(Duration::from_millis(now_epoch_ms() as u64));
+        tokio::spawn(async move {
+            info!(target: "ems.grpc", project_id = %project_id, "stream_realtime_started");
+            let mut last_sent: HashMap<String, i64> = HashMap::new();
+            let mut ticker = tokio::time::interval(Duration::from_millis(interval_ms));
+            let token_expired = tokio::time::sleep(expires_in);
+            tokio::pin!(token_expired);
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {}
+                    _ = &mut token_expired => {
+                        info!(target: "ems.grpc", project_id = %project_id, "stream_realtime_token_expired");
+                        let _ = tx.send(Err(Status::unauthenticated("token expired"))).await;
+                        return;
+                    }
+                }
+                let records = match &point_ids {
+                    Some(point_ids) => {
+                        realtime_store
+                            .get_last_values(&ctx, &project_id, point_ids)
+                            .await
+                    }
+                    None => realtime_store.list_last_values(&ctx, &project_id).await,
+                };
+                let records = match records {
+                    Ok(records) => records,
+                    Err(err) => {
+                        warn!(target: "ems.grpc", error = %err, "stream_realtime_read_failed");
+                        let _ = tx.send(Err(storage_status(err))).await;
+                        break;
+                    }
+                };
+                for record in records {
+                    if last_sent.get(&record.point_id) == Some(&record.ts_ms) {
+                        continue;
+                    }
+                    last_sent.insert(record.point_id.clone(), record.ts_ms);
+                    if tx.send(Ok(realtime_to_pb(record))).await.is_err() {
+                        // 客户端已断开
+                        info!(target: "ems.grpc", project_id = %project_id, "stream_realtime_closed");
+                        return;
+                    }
+                }
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn issue_command(
+        &self,
+        request: Request<pb::IssueCommandRequest>,
+    ) -> Result<Response<pb::CommandReply>, Status> {
+        let (metadata, _, req) = request.into_parts();
+        let ctx = self
+            .authorize(
+                &metadata,
+                &req.project_id,
+                permissions::CONTROL_COMMAND_ISSUE,
+            )
+            .await?;
+        let target = req.target.trim().to_string();
+        if target.is_empty() {
+            return Err(Status::invalid_argument("target is required"));
+        }
+        let payload = serde_json::from_str(&req.payload_json)
+            .map_err(|_| Status::invalid_argument("payloadJson must be valid JSON"))?;
+        let request = CommandRequest {
+            project_id: req.project_id,
+            target,
+            payload,
+            issued_at_ms: now_epoch_ms(),
+        };
+        match self
+            .state
+            .command_service
+            .issue_command(&ctx, request)
+            .await
+        {
+            Ok(command) => Ok(Response::new(command_to_pb(command))),
+            Err(err) => Err(control_status(err)),
+        }
+    }
+}
+
+/// 启动 gRPC 服务（后台任务）
+pub fn spawn_grpc_server(
+    addr: std::net::SocketAddr,
+    state: AppState,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        info!("EMS gRPC 服务已启动，监听地址: {}", addr);
+        let result = tonic::transport::Server::builder()
+            .add_service(EmsServiceServer::new(GrpcService::new(state)))
+            .serve(addr)
+            .await;
+        if let Err(err) = result {
+            warn!(error = %err, "grpc server stopped");
+        }
+    })
+}
+
+/// 控制链路错误映射：存储错误按分类映射，下发失败等内部错误只写日志、不向客户端暴露细节
+fn control_status(err: ControlError) -> Status {
+    match err {
+        ControlError::Storage(err) => storage_status(err),
+        ControlError::Payload(message) => Status::invalid_argument(message),
+        err @ ControlError::Quota(_) => Status::resource_exhausted(err.to_string()),
+        err @ ControlError::FeatureDisabled(_) => Status::permission_denied(err.to_string()),
+        err @ ControlError::Maintenance(_) => Status::failed_precondition(err.to_string()),
+        err @ ControlError::Dispatch(_) => {
+            tracing::error!(error = %err, "grpc control error");
+            Status::internal("internal error")
+        }
+    }
+}
+
+/// 流水线写入错误映射：配额 `RESOURCE_EXHAUSTED`，缓冲区满 `UNAVAILABLE`，其余按存储错误处理
+fn write_values_status(err: WriteValuesError) -> Status {
+    match err {
+        WriteValuesError::Quota => {
+            Status::resource_exhausted(format!("quota exceeded: {}", usage::MEASUREMENTS))
+        }
+        WriteValuesError::Storage(err) => storage_status(err),
+        WriteValuesError::Pipeline(err) => {
+            tracing::error!(error = %err, "pipeline error");
+            match err {
+                PipelineError::Backpressure(_) => Status::unavailable("service unavailable"),
+                PipelineError::Writer(_) => Status::internal("internal error"),
+            }
+        }
+    }
+}
+
+fn storage_status(err: ems_storage::StorageError) -> Status {
+    use ems_storage::StorageErrorKind;
+
+    tracing::error!(error = %err, "storage error");
+    match err.kind() {
+        StorageErrorKind::NotFound => Status::not_found("not found"),
+        StorageErrorKind::Conflict => Status::already_exists("resource already exists"),
+        StorageErrorKind::Forbidden => Status::permission_denied("forbidden"),
+        StorageErrorKind::Unavailable => Status::unavailable("service unavailable"),
+        StorageErrorKind::Internal => Status::internal("internal error"),
+    }
+}
+
+fn realtime_to_pb(record: RealtimeRecord) -> pb::RealtimeValue {
+    pb::RealtimeValue {
+        project_id: record.project_id,
+        point_id: record.point_id,
+        ts_ms: record.ts_ms,
+        value: record.value,
+        quality: record.quality,
+    }
+}
+
+fn command_to_pb(record: CommandRecord) -> pb::CommandReply {
+    pb::CommandReply {
+        command_id: record.command_id,
+        project_id: record.project_id,
+        target: record.target,
+        payload_json: record.payload,
+        status: record.status,
+        issued_by: record.issued_by,
+        issued_at_ms: record.issued_at_ms,
+    }
+}
+
+fn now_epoch_ms() -> i64 {
+    let now = std::time::SystemTime::now();
+    let duration = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    duration.as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{auth_headers, build_state, project_ctx};
+    use axum::http::header;
+    use ems_auth::{AuthService, JwtManager};
+    use std::sync::Arc;
+
+    /// 测试：gRPC WritePoints 经采集流水线写入最新值并校验认证
+    #[tokio::test]
+    async fn grpc_write_points_updates_realtime() {
+        use super::pb::ems_service_server::EmsService;
+        use super::pb::{PointSample, WritePointsRequest, point_sample::Value as SampleValue};
+
+        let state = build_state();
+        let ctx = project_ctx();
+        state
+            .point_store
+            .create_point(
+                &ctx,
+                ems_storage::PointRecord {
+                    point_id: "point-1".to_string(),
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    device_id: "device-1".to_string(),
+                    key: "power".to_string(),
+                    data_type: "f64".to_string(),
+                    unit: None,
+                    tags: Vec::new(),
+                },
+            )
+            .await
+            .expect("create point");
+        let headers = auth_headers(&state).await;
+        let token = headers[header::AUTHORIZATION]
+            .to_str()
+            .expect("token")
+            .to_string();
+        let service = GrpcService::new(state.clone());
+        let build_request = |point_id: &str, token: Option<&str>| {
+            let mut request = tonic::Request::new(WritePointsRequest {
+                project_id: "project-1".to_string(),
+                points: vec![PointSample {
+                    point_id: point_id.to_string(),
+                    ts_ms: 1_700_000_000_000,
+                    value: Some(SampleValue::DoubleValue(12.5)),
+                    quality: None,
+                }],
+            });
+            if let Some(token) = token {
+                request
+                    .metadata_mut()
+                    .insert("authorization", token.parse().expect("metadata"));
+            }
+            request
+        };
+
+        // 未携带 token
+        let err = service
+            .write_points(build_request("point-1", None))
+            .await
+            .expect_err("unauthenticated");
+        assert_eq!(err.code(), tonic::Code::Unauthenticated);
+
+        // 未登记的点位
+        let err = service
+            .write_points(build_request("point-x", Some(&token)))
+            .await
+            .expect_err("unknown point");
+        assert_eq!(err.code(), tonic::Code::NotFound);
+
+        let response = service
+            .write_points(build_request("point-1", Some(&token)))
+            .await
+            .expect("write points");
+        assert_eq!(response.into_inner().written, 1);
+        let record = state
+            .realtime_store
+            .get_last_value(&ctx, "project-1", "point-1")
+            .await
+            .expect("last value")
+            .expect("record");
+        assert_eq!(record.value, "12.5");
+
+        // 与 REST 共用流水线：重复值被去重，不再计为写入
+        let response = service
+            .write_points(build_request("point-1", Some(&token)))
+            .await
+            .expect("write duplicate");
+        assert_eq!(response.into_inner().written, 0);
+    }
+
+    /// 测试：gRPC StreamRealtime 在 token 过期时以 UNAUTHENTICATED 结束订阅
+    #[tokio::test]
+    async fn grpc_stream_realtime_ends_when_token_expires() {
+        use super::pb::StreamRealtimeRequest;
+        use super::pb::ems_service_server::EmsService;
+        use tokio_stream::StreamExt;
+
+        let mut state = build_state();
+        // access token 有效期 1 秒
+        state.auth = Arc::new(AuthService::new(
+            Arc::new(ems_storage::InMemoryUserStore::with_default_admin()),
+            JwtManager::new("test-secret".to_string(), 1, 7200),
+        ));
+        let headers = auth_headers(&state).await;
+        let token = headers[header::AUTHORIZATION]
+            .to_str()
+            .expect("token")
+            .to_string();
+        let service = GrpcService::new(state.clone());
+        let mut request = tonic::Request::new(StreamRealtimeRequest {
+            project_id: "project-1".to_string(),
+            point_ids: vec!["point-1".to_string()],
+            device_id: None,
+            tag: None,
+            interval_ms: Some(200),
+        });
+        request
+            .metadata_mut()
+            .insert("authorization", token.parse().expect("metadata"));
+        let mut stream = service
+            .stream_realtime(request)
+            .await
+            .expect("stream")
+            .into_inner();
+
+        let item = tokio::time::timeout(std::time::Duration::from_secs(5), stream.next())
+            .await
+            .expect("stream ends before timeout")
+            .expect("stream item");
+        let err = item.expect_err("token expired");
+        assert_eq!(err.code(), tonic::Code::Unauthenticated);
+        let end = tokio::time::timeout(std::time::Duration::from_secs(1), stream.next())
+            .await
+            .expect("stream closed");
+        assert!(end.is_none());
+    }
+
+    /// 测试：租户关闭 control 开关后 gRPC IssueCommand 被拒绝且不创建命令
+    #[tokio::test]
+    async fn grpc_issue_command_rejects_disabled_control_feature() {
+        use super::pb::IssueCommandRequest;
+        use super::pb::ems_service_server::EmsService;
+
+        let state = build_state();
+        let ctx = project_ctx();
+        let headers = auth_headers(&state).await;
+        let token = headers[header::AUTHORIZATION]
+            .to_str()
+            .expect("token")
+            .to_string();
+        let service = GrpcService::new(state.clone());
+        let build_request = || {
+            let mut request = tonic::Request::new(IssueCommandRequest {
+                project_id: "project-1".to_string(),
+                target: "device-1".to_string(),
+                payload_json: r#"{"on":true}"#.to_string(),
+            });
+            request
+                .metadata_mut()
+                .insert("authorization", token.parse().expect("metadata"));
+            request
+        };
+
+        state
+            .feature_flag_store
+            .upsert_feature_flag(
+                &ctx,
+                ems_storage::FeatureFlagRecord {
+                    tenant_id: "tenant-1".to_string(),
+                    flag_key: domain::features::CONTROL.to_string(),
+                    enabled: false,
+                    variant: None,
+                    updated_at_ms: 0,
+                },
+            )
+            .await
+            .expect("disable control");
+        let err = service
+            .issue_command(build_request())
+            .await
+            .expect_err("control disabled");
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+        let commands = state
+            .command_store
+            .list_commands(&ctx, "project-1", Default::default())
+            .await
+            .expect("commands");
+        assert!(commands.is_empty());
+
+        // 重新开启后正常下发
+        state
+            .feature_flag_store
+            .delete_feature_flag(&ctx, domain::features::CONTROL)
+            .await
+            .expect("enable control");
+        let reply = service
+            .issue_command(build_request())
+            .await
+            .expect("issue command")
+            .into_inner();
+        assert_eq!(reply.target, "device-1");
+    }
+}
@@ -0,0 +1,161 @@
+//! 用能异常 handlers
+//!
+//! 异常由后台检测任务（`ems-analytics`）写入，这里只提供查询：
+//! - GET /projects/{id}/anomalies - 按小时桶倒序列出异常（可按 pointId / deviceId / from / to 过滤）
+//!
+//! 权限要求：DATA.MEASUREMENTS.READ
+
+use crate::AppState;
+use crate::middleware::{require_permission, require_project_scope};
+use crate::utils::response::{anomaly_to_dto, bad_request_error, storage_error};
+use api_contract::{AnomalyDto, AnomalyQuery, ApiResponse};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use domain::permissions;
+
+/// 默认返回条数
+const DEFAULT_ANOMALY_LIMIT: i64 = 100;
+/// 最大返回条数
+const MAX_ANOMALY_LIMIT: i64 = 1000;
+
+#[derive(serde::Deserialize)]
+pub struct AnomalyProjectPath {
+    project_id: String,
+}
+
+/// 列出项目下的用能异常
+pub async fn list_anomalies(
+    State(state): State<AppState>,
+    Path(path): Path<AnomalyProjectPath>,
+    Query(query): Query<AnomalyQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::DATA_MEASUREMENTS_READ) {
+        return response;
+    }
+    if let (Some(from), Some(to)) = (query.from, query.to)
+        && from > to
+    {
+        return bad_request_error("from must be <= to");
+    }
+    let options = ems_storage::AnomalyQuery {
+        point_id: query.point_id,
+        device_id: query.device_id,
+        from_ms: query.from,
+        to_ms: query.to,
+        limit: query
+            .limit
+            .unwrap_or(DEFAULT_ANOMALY_LIMIT)
+            .clamp(1, MAX_ANOMALY_LIMIT),
+    };
+    match state
+        .anomaly_store
+        .list_anomalies(&ctx, &path.project_id, options)
+        .await
+    {
+        Ok(items) => {
+            let data: Vec<AnomalyDto> = items.into_iter().map(anomaly_to_dto).collect();
+            (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{api_router, auth_headers, build_state, json_request, response_json};
+    use axum::http::StatusCode;
+    use domain::TenantContext;
+    use tower::ServiceExt;
+
+    /// 测试：用能异常按点位/时间过滤并按小时桶倒序返回
+    #[tokio::test]
+    async fn anomalies_listed_with_filters() {
+        let state = build_state();
+        let headers = auth_headers(&state).await;
+        let ctx = TenantContext::new(
+            "tenant-1".to_string(),
+            "anomaly-detector".to_string(),
+            Vec::new(),
+            Vec::new(),
+            Some("project-1".to_string()),
+        );
+        let hour_ms = 3_600_000;
+        for (index, (point_id, bucket_start_ms)) in [
+            ("power", 1_704_067_200_000_i64),
+            ("power", 1_704_067_200_000 + hour_ms),
+            ("gas", 1_704_067_200_000),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let inserted = state
+                .anomaly_store
+                .insert_anomaly(
+                    &ctx,
+                    ems_storage::AnomalyRecord {
+                        tenant_id: "tenant-1".to_string(),
+                        project_id: "project-1".to_string(),
+                        anomaly_id: format!("anomaly-{index}"),
+                        point_id: point_id.to_string(),
+                        device_id: "device-1".to_string(),
+                        bucket_start_ms,
+                        hour_of_week: 0,
+                        actual_value: 180.0,
+                        baseline_value: 100.0,
+                        baseline_samples: 4,
+                        deviation_pct: 80.0,
+                        threshold_pct: 50.0,
+                        detected_at_ms: bucket_start_ms + hour_ms,
+                    },
+                )
+                .await
+                .expect("insert");
+            assert!(inserted);
+        }
+
+        let app = api_router(state.clone());
+        let request = |uri: &str| {
+            json_request(
+                &headers,
+                "GET",
+                &format!("/api/v1/projects/project-1/anomalies{uri}"),
+                None,
+            )
+        };
+
+        let response = app
+            .clone()
+            .oneshot(request("?pointId=power"))
+            .await
+            .expect("list");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        let items = json["data"].as_array().cloned().unwrap_or_default();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0]["bucketStartMs"], 1_704_067_200_000_i64 + hour_ms);
+        assert_eq!(items[0]["deviationPct"], 80.0);
+
+        let response = app
+            .clone()
+            .oneshot(request("?to=1704070800000"))
+            .await
+            .expect("list");
+        let json = response_json(response).await;
+        assert_eq!(json["data"].as_array().map(Vec::len), Some(2));
+
+        let response = app
+            .oneshot(request("?from=1704070800000&to=1704067200000"))
+            .await
+            .expect("list");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
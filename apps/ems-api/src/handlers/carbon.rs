@@ -0,0 +1,533 @@
+//! 碳排放 handlers
+//!
+//! 排放因子按能源类型（electricity / gas / diesel）配置，项目覆盖值优先于租户默认值：
+//! - GET /carbon/emission-factors - 列出租户默认因子
+//! - PUT /carbon/emission-factors/{source} - 设置租户默认因子
+//! - DELETE /carbon/emission-factors/{source} - 删除租户默认因子
+//! - GET /projects/{id}/carbon/emission-factors - 列出项目生效因子（合并租户默认值，`scope` 标注来源）
+//! - PUT /projects/{id}/carbon/emission-factors/{source} - 设置项目覆盖因子
+//! - DELETE /projects/{id}/carbon/emission-factors/{source} - 删除项目覆盖因子（回落到租户默认值）
+//! - GET /projects/{id}/carbon/report - 按周期把累计量点位消耗折算为 CO₂e
+//!
+//! 权限要求：
+//! - 因子查询需要 CARBON.FACTOR.READ，写入/删除需要 CARBON.FACTOR.WRITE
+//! - 报表需要 CARBON.FACTOR.READ 与 DATA.MEASUREMENTS.READ
+
+use crate::AppState;
+use crate::handlers::measurements::project_timezone;
+use crate::middleware::{require_permission, require_project_scope, require_tenant_context};
+use crate::utils::response::{bad_request_error, not_found_error, storage_error};
+use crate::utils::validation::normalize_optional;
+use api_contract::{
+    ApiResponse, CarbonPeriodDto, CarbonReportDto, CarbonReportQuery, CarbonSourceUsageDto,
+    EmissionFactorDto, UpdateEmissionFactorRequest,
+};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use domain::{TenantContext, permissions};
+use ems_analytics::{
+    CarbonReportWindow, CarbonReporter, CarbonSourceUsage, MAX_REPORT_PERIODS, default_unit,
+    estimated_periods, is_valid_energy_source,
+};
+use ems_storage::{CalendarBucket, EmissionFactorRecord};
+
+#[derive(serde::Deserialize)]
+pub struct EmissionFactorPath {
+    energy_source: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct CarbonProjectPath {
+    project_id: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct ProjectEmissionFactorPath {
+    project_id: String,
+    energy_source: String,
+}
+
+/// 列出租户默认排放因子
+pub async fn list_tenant_emission_factors(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_tenant_context(&state, &headers) {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::CARBON_FACTOR_READ) {
+        return response;
+    }
+    match state
+        .emission_factor_store
+        .list_emission_factors(&ctx, None)
+        .await
+    {
+        Ok(records) => {
+            let data: Vec<EmissionFactorDto> =
+                records.into_iter().map(emission_factor_to_dto).collect();
+            (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 设置租户默认排放因子
+pub async fn update_tenant_emission_factor(
+    State(state): State<AppState>,
+    Path(path): Path<EmissionFactorPath>,
+    headers: HeaderMap,
+    Json(req): Json<UpdateEmissionFactorRequest>,
+) -> Response {
+    let ctx = match require_tenant_context(&state, &headers) {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::CARBON_FACTOR_WRITE) {
+        return response;
+    }
+    upsert_emission_factor(&state, &ctx, None, path.energy_source, req).await
+}
+
+/// 删除租户默认排放因子
+pub async fn delete_tenant_emission_factor(
+    State(state): State<AppState>,
+    Path(path): Path<EmissionFactorPath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_tenant_context(&state, &headers) {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::CARBON_FACTOR_WRITE) {
+        return response;
+    }
+    delete_emission_factor(&state, &ctx, None, &path.energy_source).await
+}
+
+/// 列出项目生效的排放因子（项目覆盖 + 租户默认值）
+pub async fn list_project_emission_factors(
+    State(state): State<AppState>,
+    Path(path): Path<CarbonProjectPath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::CARBON_FACTOR_READ) {
+        return response;
+    }
+    match carbon_reporter(&state)
+        .effective_factors(&ctx, &path.project_id)
+        .await
+    {
+        Ok(factors) => {
+            let data: Vec<EmissionFactorDto> =
+                factors.into_values().map(emission_factor_to_dto).collect();
+            (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 设置项目覆盖排放因子
+pub async fn update_project_emission_factor(
+    State(state): State<AppState>,
+    Path(path): Path<ProjectEmissionFactorPath>,
+    headers: HeaderMap,
+    Json(req): Json<UpdateEmissionFactorRequest>,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::CARBON_FACTOR_WRITE) {
+        return response;
+    }
+    upsert_emission_factor(&state, &ctx, Some(path.project_id), path.energy_source, req).await
+}
+
+/// 删除项目覆盖排放因子
+pub async fn delete_project_emission_factor(
+    State(state): State<AppState>,
+    Path(path): Path<ProjectEmissionFactorPath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::CARBON_FACTOR_WRITE) {
+        return response;
+    }
+    delete_emission_factor(&state, &ctx, Some(&path.project_id), &path.energy_source).await
+}
+
+/// 碳排放报表
+pub async fn get_carbon_report(
+    State(state): State<AppState>,
+    Path(path): Path<CarbonProjectPath>,
+    Query(query): Query<CarbonReportQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::CARBON_FACTOR_READ) {
+        return response;
+    }
+    if let Err(response) = require_permission(&ctx, permissions::DATA_MEASUREMENTS_READ) {
+        return response;
+    }
+    let (Some(from), Some(to)) = (query.from, query.to) else {
+        return bad_request_error("from and to are required");
+    };
+    if from >= to {
+        return bad_request_error("from must be < to");
+    }
+    let bucket_name = query.bucket.unwrap_or_else(|| "1mo".to_string());
+    let Some(bucket) = CalendarBucket::parse(&bucket_name) else {
+        return bad_request_error("bucket must be 1h|1d|1mo");
+    };
+    if estimated_periods(bucket, from, to) > MAX_REPORT_PERIODS {
+        return bad_request_error(format!(
+            "window too large: at most {MAX_REPORT_PERIODS} periods"
+        ));
+    }
+    let timezone = match project_timezone(&state, &ctx, &path.project_id).await {
+        Ok(timezone) => timezone,
+        Err(response) => return response,
+    };
+    let window = CarbonReportWindow {
+        from_ms: from,
+        to_ms: to,
+        bucket,
+        timezone,
+    };
+    match carbon_reporter(&state)
+        .report(&ctx, &path.project_id, window)
+        .await
+    {
+        Ok(report) => {
+            let data = CarbonReportDto {
+                project_id: path.project_id,
+                from,
+                to,
+                bucket: bucket_name.trim().to_ascii_lowercase(),
+                timezone: timezone.name().to_string(),
+                periods: report
+                    .periods
+                    .into_iter()
+                    .map(|period| CarbonPeriodDto {
+                        period_start_ms: period.period_start_ms,
+                        sources: period
+                            .sources
+                            .into_iter()
+                            .map(source_usage_to_dto)
+                            .collect(),
+                        co2e_kg: period.co2e_kg,
+                    })
+                    .collect(),
+                totals: report.totals.into_iter().map(source_usage_to_dto).collect(),
+                total_co2e_kg: report.total_co2e_kg,
+                missing_factors: report.missing_factors,
+            };
+            (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+fn carbon_reporter(state: &AppState) -> CarbonReporter {
+    CarbonReporter::new(
+        state.point_store.clone(),
+        state.measurement_store.clone(),
+        state.emission_factor_store.clone(),
+    )
+}
+
+async fn upsert_emission_factor(
+    state: &AppState,
+    ctx: &TenantContext,
+    project_id: Option<String>,
+    energy_source: String,
+    req: UpdateEmissionFactorRequest,
+) -> Response {
+    if !is_valid_energy_source(&energy_source) {
+        return bad_request_error("energy source must be electricity|gas|diesel");
+    }
+    if !req.kg_co2e_per_unit.is_finite() || req.kg_co2e_per_unit < 0.0 {
+        return bad_request_error("kgCo2ePerUnit must be >= 0");
+    }
+    let unit = match normalize_optional(req.unit, "unit") {
+        Ok(value) => value,
+        Err(response) => return response,
+    };
+    let record = EmissionFactorRecord {
+        tenant_id: ctx.tenant_id.clone(),
+        project_id,
+        unit: unit
+            .or_else(|| default_unit(&energy_source).map(str::to_string))
+            .unwrap_or_default(),
+        energy_source,
+        kg_co2e_per_unit: req.kg_co2e_per_unit,
+        updated_at_ms: now_epoch_ms(),
+    };
+    match state
+        .emission_factor_store
+        .upsert_emission_factor(ctx, record)
+        .await
+    {
+        Ok(record) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(emission_factor_to_dto(record))),
+        )
+            .into_response(),
+        Err(err) => storage_error(err),
+    }
+}
+
+async fn delete_emission_factor(
+    state: &AppState,
+    ctx: &TenantContext,
+    project_id: Option<&str>,
+    energy_source: &str,
+) -> Response {
+    match state
+        .emission_factor_store
+        .delete_emission_factor(ctx, project_id, energy_source)
+        .await
+    {
+        Ok(true) => (StatusCode::OK, Json(ApiResponse::success(()))).into_response(),
+        Ok(false) => not_found_error(),
+        Err(err) => storage_error(err),
+    }
+}
+
+fn emission_factor_to_dto(record: EmissionFactorRecord) -> EmissionFactorDto {
+    EmissionFactorDto {
+        energy_source: record.energy_source,
+        kg_co2e_per_unit: record.kg_co2e_per_unit,
+        unit: record.unit,
+        scope: if record.project_id.is_some() {
+            "project"
+        } else {
+            "tenant"
+        }
+        .to_string(),
+        project_id: record.project_id,
+        updated_at_ms: record.updated_at_ms,
+    }
+}
+
+fn source_usage_to_dto(usage: CarbonSourceUsage) -> CarbonSourceUsageDto {
+    CarbonSourceUsageDto {
+        energy_source: usage.energy_source,
+        consumption: usage.consumption,
+        unit: usage.unit,
+        kg_co2e_per_unit: usage.kg_co2e_per_unit,
+        co2e_kg: usage.co2e_kg,
+    }
+}
+
+fn now_epoch_ms() -> i64 {
+    let now = std::time::SystemTime::now();
+    let duration = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    duration.as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{
+        api_router, auth_headers, build_state, json_request, project_ctx, response_json,
+    };
+    use axum::http::StatusCode;
+    use domain::{PointValue, PointValueData};
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    /// 测试：排放因子项目覆盖与租户默认值合并，碳排放报表按日折算累计量消耗
+    #[tokio::test]
+    async fn carbon_report_converts_counter_consumption() {
+        let state = build_state();
+        let headers = auth_headers(&state).await;
+        let ctx = project_ctx();
+        // 2024-01-01T00:00:00Z
+        let day_start_ms: i64 = 1_704_067_200_000;
+        let day_ms: i64 = 24 * 3_600_000;
+        for (point_id, energy_source, readings) in [
+            (
+                "meter-kwh",
+                "electricity",
+                vec![
+                    (0, 100.0),
+                    (12 * 3_600_000, 150.0),
+                    (day_ms + 3_600_000, 200.0),
+                ],
+            ),
+            ("meter-gas", "gas", vec![(0, 10.0), (3_600_000, 12.0)]),
+        ] {
+            state
+                .point_store
+                .create_point(
+                    &ctx,
+                    ems_storage::PointRecord {
+                        point_id: point_id.to_string(),
+                        tenant_id: "tenant-1".to_string(),
+                        project_id: "project-1".to_string(),
+                        device_id: "device-1".to_string(),
+                        key: point_id.to_string(),
+                        data_type: "float".to_string(),
+                        unit: None,
+                        tags: vec!["counter".to_string(), energy_source.to_string()],
+                    },
+                )
+                .await
+                .expect("point");
+            for (offset_ms, value) in readings {
+                state
+                    .measurement_store
+                    .write_measurement(
+                        &ctx,
+                        &PointValue {
+                            tenant_id: "tenant-1".to_string(),
+                            project_id: "project-1".to_string(),
+                            point_id: point_id.to_string(),
+                            ts_ms: day_start_ms + offset_ms,
+                            value: PointValueData::F64(value),
+                            quality: None,
+                        },
+                    )
+                    .await
+                    .expect("measurement");
+            }
+        }
+
+        let app = api_router(state.clone());
+        let request = |method: &str, uri: &str, body: Option<Value>| {
+            json_request(&headers, method, &format!("/api/v1{uri}"), body)
+        };
+
+        // 未知能源类型返回 400
+        let response = app
+            .clone()
+            .oneshot(request(
+                "PUT",
+                "/carbon/emission-factors/coal",
+                Some(serde_json::json!({ "kgCo2ePerUnit": 1.0 })),
+            ))
+            .await
+            .expect("factor");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        for (uri, factor) in [
+            ("/carbon/emission-factors/electricity", 0.5),
+            ("/carbon/emission-factors/gas", 1.0),
+            ("/projects/project-1/carbon/emission-factors/gas", 2.0),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(request(
+                    "PUT",
+                    uri,
+                    Some(serde_json::json!({ "kgCo2ePerUnit": factor })),
+                ))
+                .await
+                .expect("factor");
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                "GET",
+                "/projects/project-1/carbon/emission-factors",
+                None,
+            ))
+            .await
+            .expect("factors");
+        let json = response_json(response).await;
+        let factors = json["data"].as_array().cloned().unwrap_or_default();
+        assert_eq!(factors.len(), 2);
+        assert_eq!(factors[0]["energySource"], "electricity");
+        assert_eq!(factors[0]["scope"], "tenant");
+        assert_eq!(factors[0]["unit"], "kWh");
+        assert_eq!(factors[1]["kgCo2ePerUnit"], 2.0);
+        assert_eq!(factors[1]["scope"], "project");
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                "GET",
+                &format!(
+                    "/projects/project-1/carbon/report?from={day_start_ms}&to={}&bucket=1d",
+                    day_start_ms + 2 * day_ms
+                ),
+                None,
+            ))
+            .await
+            .expect("report");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        let periods = json["data"]["periods"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        assert_eq!(periods.len(), 2);
+        // 第一天：电 50 kWh × 0.5 + 气 2 m3 × 2.0
+        assert_eq!(periods[0]["periodStartMs"], day_start_ms);
+        assert_eq!(periods[0]["co2eKg"], 29.0);
+        // 第二天：电 200 − 150 kWh（含跨日增量）
+        assert_eq!(periods[1]["co2eKg"], 25.0);
+        assert_eq!(json["data"]["totalCo2eKg"], 54.0);
+        assert_eq!(
+            json["data"]["missingFactors"].as_array().map(Vec::len),
+            Some(0)
+        );
+
+        // 删除项目覆盖后回落到租户默认值
+        let response = app
+            .clone()
+            .oneshot(request(
+                "DELETE",
+                "/projects/project-1/carbon/emission-factors/gas",
+                None,
+            ))
+            .await
+            .expect("delete");
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app
+            .clone()
+            .oneshot(request(
+                "GET",
+                &format!(
+                    "/projects/project-1/carbon/report?from={day_start_ms}&to={}",
+                    day_start_ms + 2 * day_ms
+                ),
+                None,
+            ))
+            .await
+            .expect("report");
+        let json = response_json(response).await;
+        assert_eq!(json["data"]["bucket"], "1mo");
+        assert_eq!(json["data"]["totalCo2eKg"], 52.0);
+
+        let response = app
+            .oneshot(request(
+                "GET",
+                &format!("/projects/project-1/carbon/report?from={day_start_ms}&to={day_start_ms}"),
+                None,
+            ))
+            .await
+            .expect("report");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -1,23 +1,40 @@
 //! 控制命令 handlers
 //!
-//! - GET /projects/{id}/commands
-//! - POST /projects/{id}/commands
+//! - GET /projects/{id}/commands（支持 status/target/issuedBy/from/to 过滤与游标分页）
+//! - POST /projects/{id}/commands（租户关闭 `control` 功能开关时返回 403 FEATURE.DISABLED；
+//!   目标处于维护窗口时照常下发，响应附带 `warning`；超出当日 `commands` 配额返回 429 QUOTA.EXCEEDED；
+//!   `dryRun: true` 时只解析目标并渲染主题与载荷，不创建命令、不计入配额、不发布；
+//!   目标不是项目内网关或设备时返回 404）
+//! - GET /projects/{id}/commands/stats（时间窗口内按状态计数）
+//! - POST /projects/{id}/commands/{cid}/receipts（网关回调令牌鉴权，供只能经 HTTPS 回调的设备上报回执；
+//!   payload 形式与 MQTT 回执一致，重复回执幂等返回已有记录）
 
 use crate::AppState;
-use crate::middleware::{require_any_permission, require_permission, require_project_scope};
-use crate::utils::response::{command_receipt_to_dto, command_to_dto, storage_error};
-use crate::utils::validation::normalize_required;
+use crate::middleware::{
+    require_any_permission, require_feature, require_gateway_token, require_permission,
+    require_project_scope,
+};
+use crate::utils::response::{
+    bad_request_error, command_receipt_to_dto, command_to_dto, control_error, forbidden_error,
+    not_found_error, storage_error,
+};
+use crate::utils::validation::{normalize_optional, normalize_required};
 use api_contract::{
-    ApiResponse, CommandDto, CommandQuery, CommandReceiptDto, CreateCommandRequest,
+    ApiResponse, CommandDryRunDto, CommandDto, CommandQuery, CommandReceiptDto, CommandStatsDto,
+    CommandStatsQuery, CommandStatusCountDto, CreateCommandRequest,
 };
 use axum::{
     Json,
+    body::Bytes,
     extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
     response::{IntoResponse, Response},
 };
-use domain::permissions;
-use ems_control::CommandRequest;
+use domain::{features, permissions};
+use ems_control::{
+    CommandReceiptProcessor, CommandRequest, maintenance_notice, parse_receipt_payload,
+};
+use ems_storage::CommandQueryOptions;
 
 #[derive(serde::Deserialize)]
 pub struct ProjectPath {
@@ -47,10 +64,39 @@ pub async fn list_commands(
     ) {
         return response;
     }
-    let limit = query.limit.unwrap_or(100).max(0);
+    if let (Some(from), Some(to)) = (query.from, query.to) {
+        if from > to {
+            return bad_request_error("from must be <= to");
+        }
+    }
+    if query.cursor_command_id.is_some() && query.cursor_ts_ms.is_none() {
+        return bad_request_error("cursorCommandId requires cursorTsMs");
+    }
+    let status = match normalize_optional(query.status, "status") {
+        Ok(value) => value.map(|value| value.to_ascii_lowercase()),
+        Err(response) => return response,
+    };
+    let target = match normalize_optional(query.target, "target") {
+        Ok(value) => value,
+        Err(response) => return response,
+    };
+    let issued_by = match normalize_optional(query.issued_by, "issuedBy") {
+        Ok(value) => value,
+        Err(response) => return response,
+    };
+    let options = CommandQueryOptions {
+        status,
+        target,
+        issued_by,
+        from_ms: query.from,
+        to_ms: query.to,
+        cursor_ts_ms: query.cursor_ts_ms,
+        cursor_command_id: query.cursor_command_id,
+        limit: query.limit.unwrap_or(100).max(0),
+    };
     match state
         .command_store
-        .list_commands(&ctx, &path.project_id, limit)
+        .list_commands(&ctx, &path.project_id, options)
         .await
     {
         Ok(items) => {
@@ -75,24 +121,103 @@ pub async fn create_command(
     if let Err(response) = require_permission(&ctx, permissions::CONTROL_COMMAND_ISSUE) {
         return response;
     }
+    if let Err(response) = require_feature(&state, &ctx, features::CONTROL).await {
+        return response;
+    }
     let target = match normalize_required(req.target, "target") {
         Ok(value) => value,
         Err(response) => return response,
     };
     let now_ms = now_epoch_ms();
+    // 人工命令不受维护窗口拦截，仅提示（覆盖审计由命令服务记录）
+    let warning = match state
+        .maintenance_service
+        .active_window_for_target(&ctx, &path.project_id, &target, now_ms)
+        .await
+    {
+        Ok(window) => window.as_ref().map(maintenance_notice),
+        Err(err) => return storage_error(err),
+    };
     let request = CommandRequest {
         project_id: path.project_id,
         target,
         payload: req.payload,
         issued_at_ms: now_ms,
     };
+    if req.dry_run {
+        return dry_run_command(&state, &ctx, request, warning).await;
+    }
     match state.command_service.issue_command(&ctx, request).await {
-        Ok(command) => (
-            StatusCode::OK,
-            Json(ApiResponse::success(command_to_dto(command))),
-        )
-            .into_response(),
-        Err(err) => storage_error(ems_storage::StorageError::new(err.to_string())),
+        Ok(command) => {
+            let mut dto = command_to_dto(command);
+            dto.warning = warning;
+            (StatusCode::OK, Json(ApiResponse::success(dto))).into_response()
+        }
+        Err(err) => control_error(err),
+    }
+}
+
+/// 命令 dry-run：解析目标所属网关（未知目标 404），按下发器配置渲染主题与载荷
+async fn dry_run_command(
+    state: &AppState,
+    ctx: &domain::TenantContext,
+    request: CommandRequest,
+    warning: Option<String>,
+) -> Response {
+    let (target_type, gateway_id) =
+        match resolve_command_target(state, ctx, &request.project_id, &request.target).await {
+            Ok(value) => value,
+            Err(response) => return response,
+        };
+    let project_id = request.project_id.clone();
+    let target = request.target.clone();
+    let issued_at_ms = request.issued_at_ms;
+    match state.command_service.preview_command(ctx, request).await {
+        Ok(preview) => {
+            let dto = CommandDryRunDto {
+                dry_run: true,
+                command_id: preview.command_id,
+                project_id,
+                target,
+                target_type: target_type.to_string(),
+                gateway_id,
+                topic: preview.topic,
+                payload_format: preview.payload_format,
+                payload_hex: hex::encode(&preview.payload),
+                payload: String::from_utf8(preview.payload).ok(),
+                issued_at_ms,
+                warning,
+            };
+            (StatusCode::OK, Json(ApiResponse::success(dto))).into_response()
+        }
+        Err(err) => control_error(err),
+    }
+}
+
+/// 命令目标类型与所属网关：网关 ID 为其自身，设备 ID 为设备所属网关，其余返回 404
+async fn resolve_command_target(
+    state: &AppState,
+    ctx: &domain::TenantContext,
+    project_id: &str,
+    target: &str,
+) -> Result<(&'static str, String), Response> {
+    if state
+        .gateway_store
+        .find_gateway(ctx, project_id, target)
+        .await
+        .map_err(storage_error)?
+        .is_some()
+    {
+        return Ok(("gateway", target.to_string()));
+    }
+    match state
+        .device_store
+        .find_device(ctx, project_id, target)
+        .await
+        .map_err(storage_error)?
+    {
+        Some(device) => Ok(("device", device.gateway_id)),
+        None => Err(not_found_error()),
     }
 }
 
@@ -126,6 +251,113 @@ pub async fn list_command_receipts(
     }
 }
 
+/// 经 HTTP 上报命令回执（网关回调令牌鉴权）
+///
+/// 命令目标须为令牌所属网关或其下设备；写入与 MQTT 回执走同一处理流程
+/// （稳定回执 ID 幂等、更新命令状态、写审计、发布 command.completed）。
+pub async fn create_command_receipt(
+    State(state): State<AppState>,
+    Path(path): Path<CommandPath>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let (ctx, gateway) = match require_gateway_token(&state, &headers, &path.project_id).await {
+        Ok(value) => value,
+        Err(response) => return response,
+    };
+    let payload = match parse_receipt_payload(&body) {
+        Ok(payload) => payload,
+        Err(err) => return bad_request_error(format!("invalid receipt payload: {}", err)),
+    };
+    let command = match state
+        .command_store
+        .find_command(&ctx, &path.project_id, &path.command_id)
+        .await
+    {
+        Ok(Some(command)) => command,
+        Ok(None) => return not_found_error(),
+        Err(err) => return storage_error(err),
+    };
+    if command.target != gateway.gateway_id {
+        match state
+            .device_store
+            .find_device(&ctx, &path.project_id, &command.target)
+            .await
+        {
+            Ok(Some(device)) if device.gateway_id == gateway.gateway_id => {}
+            Ok(_) => return forbidden_error(),
+            Err(err) => return storage_error(err),
+        }
+    }
+    let processor = CommandReceiptProcessor::new(
+        state.command_store.clone(),
+        state.command_receipt_store.clone(),
+        state.audit_log_store.clone(),
+        state.event_bus.clone(),
+    );
+    match processor
+        .process(&ctx, &path.project_id, &path.command_id, payload, &ctx.user_id)
+        .await
+    {
+        Ok(written) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(command_receipt_to_dto(written.record))),
+        )
+            .into_response(),
+        Err(err) => control_error(err),
+    }
+}
+
+/// 命令状态统计
+///
+/// 返回时间窗口（按下发时间）内各状态的命令数量，供控制面板展示。
+pub async fn get_command_stats(
+    State(state): State<AppState>,
+    Path(path): Path<ProjectPath>,
+    Query(query): Query<CommandStatsQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_any_permission(
+        &ctx,
+        &[permissions::CONTROL_COMMAND_READ, permissions::CONTROL_COMMAND_ISSUE],
+    ) {
+        return response;
+    }
+    if let (Some(from), Some(to)) = (query.from, query.to) {
+        if from > to {
+            return bad_request_error("from must be <= to");
+        }
+    }
+    match state
+        .command_store
+        .count_commands_by_status(&ctx, &path.project_id, query.from, query.to)
+        .await
+    {
+        Ok(items) => {
+            let total = items.iter().map(|item| item.count).sum();
+            let by_status = items
+                .into_iter()
+                .map(|item| CommandStatusCountDto {
+                    status: item.status,
+                    count: item.count,
+                })
+                .collect();
+            let data = CommandStatsDto {
+                from: query.from,
+                to: query.to,
+                total,
+                by_status,
+            };
+            (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
 fn now_epoch_ms() -> i64 {
     let now = std::time::SystemTime::now();
     let duration = now
@@ -133,3 +365,263 @@ fn now_epoch_ms() -> i64 {
         .unwrap_or_default();
     duration.as_millis() as i64
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{
+        api_router, auth_headers, build_state, json_request, project_ctx, response_json,
+    };
+    use axum::http::{StatusCode, header};
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    /// 测试：网关回调令牌经 HTTP 上报命令回执（幂等、只能回执本网关及其设备的命令）
+    #[tokio::test]
+    async fn http_command_receipt_requires_gateway_token() {
+        let state = build_state();
+        let headers = auth_headers(&state).await;
+        let app = api_router(state.clone());
+        let request = |method: &str, uri: &str, body: Value| {
+            json_request(
+                &headers,
+                method,
+                &format!("/api/v1/projects/project-1{uri}"),
+                Some(body),
+            )
+        };
+        let receipt = |command_id: &str, token: &str, body: &str| {
+            axum::http::Request::builder()
+                .method("POST")
+                .uri(format!(
+                    "/api/v1/projects/project-1/commands/{command_id}/receipts"
+                ))
+                .header(header::AUTHORIZATION, format!("Bearer {token}"))
+                .body(axum::body::Body::from(body.to_string()))
+                .expect("request")
+        };
+
+        let mut gateway_ids = Vec::new();
+        for name in ["gw-a", "gw-b"] {
+            let response = app
+                .clone()
+                .oneshot(request(
+                    "POST",
+                    "/gateways",
+                    serde_json::json!({ "name": name, "protocolType": "mqtt" }),
+                ))
+                .await
+                .expect("response");
+            assert_eq!(response.status(), StatusCode::OK);
+            let json = response_json(response).await;
+            gateway_ids.push(json["data"]["gatewayId"].as_str().expect("id").to_string());
+        }
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/devices",
+                serde_json::json!({ "gatewayId": gateway_ids[0], "name": "meter" }),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        let device_id = json["data"]["deviceId"]
+            .as_str()
+            .expect("device id")
+            .to_string();
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                &format!("/gateways/{}/token", gateway_ids[0]),
+                serde_json::json!({}),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        let token = json["data"]["token"].as_str().expect("token").to_string();
+        assert!(token.starts_with(domain::gateway_token::GATEWAY_TOKEN_PREFIX));
+
+        let ctx = project_ctx();
+        for (command_id, target) in [("cmd-1", device_id.as_str()), ("cmd-2", &gateway_ids[1])] {
+            state
+                .command_store
+                .create_command(
+                    &ctx,
+                    ems_storage::CommandRecord {
+                        command_id: command_id.to_string(),
+                        tenant_id: "tenant-1".to_string(),
+                        project_id: "project-1".to_string(),
+                        target: target.to_string(),
+                        payload: "{}".to_string(),
+                        status: "issued".to_string(),
+                        issued_by: "user-1".to_string(),
+                        issued_at_ms: 1,
+                    },
+                )
+                .await
+                .expect("command");
+        }
+
+        let body = r#"{"status":"success","message":"ok","tsMs":1000}"#;
+        let response = app
+            .clone()
+            .oneshot(receipt("cmd-1", &token, body))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        let receipt_id = json["data"]["receiptId"].as_str().expect("id").to_string();
+        assert_eq!(json["data"]["status"], "success");
+
+        // 重复回执幂等：返回同一条记录，不重复写入
+        let response = app
+            .clone()
+            .oneshot(receipt("cmd-1", &token, body))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        assert_eq!(json["data"]["receiptId"], receipt_id.as_str());
+        let receipts = state
+            .command_receipt_store
+            .list_receipts(&ctx, "project-1", "cmd-1")
+            .await
+            .expect("receipts");
+        assert_eq!(receipts.len(), 1);
+        let command = state
+            .command_store
+            .find_command(&ctx, "project-1", "cmd-1")
+            .await
+            .expect("find")
+            .expect("command");
+        assert_eq!(command.status, "success");
+
+        let response = app
+            .clone()
+            .oneshot(receipt("cmd-2", &token, "success"))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response = app
+            .clone()
+            .oneshot(receipt("cmd-404", &token, "success"))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let response = app
+            .clone()
+            .oneshot(receipt("cmd-1", "ems_gw_invalid", "success"))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        // 吊销后令牌失效
+        let response = app
+            .clone()
+            .oneshot(request(
+                "DELETE",
+                &format!("/gateways/{}/token", gateway_ids[0]),
+                serde_json::json!({}),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app
+            .clone()
+            .oneshot(receipt("cmd-1", &token, "success"))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    /// 测试：命令 dry-run 返回目标解析与渲染载荷，不创建命令
+    #[tokio::test]
+    async fn command_dry_run_renders_without_dispatch() {
+        let state = build_state();
+        let headers = auth_headers(&state).await;
+        let ctx = project_ctx();
+        state
+            .device_store
+            .create_device(
+                &ctx,
+                ems_storage::DeviceRecord {
+                    device_id: "device-1".to_string(),
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    gateway_id: "gateway-1".to_string(),
+                    name: "PLC".to_string(),
+                    model: None,
+                    room_id: None,
+                    address_config: None,
+                    offline_after_seconds: None,
+                },
+            )
+            .await
+            .expect("device");
+
+        let app = api_router(state.clone());
+        let request = |method: &str, body: Option<Value>| {
+            json_request(
+                &headers,
+                method,
+                "/api/v1/projects/project-1/commands",
+                body,
+            )
+        };
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                Some(serde_json::json!({
+                    "target": "device-1",
+                    "payload": { "switch": "on" },
+                    "dryRun": true
+                })),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        let data = &json["data"];
+        assert_eq!(data["dryRun"], true);
+        assert_eq!(data["targetType"], "device");
+        assert_eq!(data["gatewayId"], "gateway-1");
+        // 测试环境未启用 MQTT 下发：无主题，载荷为默认信封
+        assert!(data["topic"].is_null());
+        assert_eq!(data["payloadFormat"], "envelope");
+        let envelope: Value =
+            serde_json::from_str(data["payload"].as_str().expect("payload")).expect("envelope");
+        assert_eq!(envelope["commandId"], data["commandId"]);
+        assert_eq!(envelope["payload"]["switch"], "on");
+        assert_eq!(
+            hex::decode(data["payloadHex"].as_str().expect("hex")).expect("decode"),
+            data["payload"].as_str().expect("payload").as_bytes()
+        );
+
+        // 目标不是项目内网关或设备时 404
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                Some(serde_json::json!({ "target": "meter-9", "payload": {}, "dryRun": true })),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        // dry-run 不创建命令
+        let response = app
+            .clone()
+            .oneshot(request("GET", None))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        assert_eq!(json["data"].as_array().map(Vec::len), Some(0));
+    }
+}
@@ -0,0 +1,602 @@
+//! 需求响应 handlers
+//!
+//! 租户按项目登记可削减负荷，并按目标削减量创建需求响应事件；
+//! 后台编排器在事件窗口内按优先级削减负荷、跟踪实际削减量，窗口结束后恢复负荷：
+//! - GET /projects/{id}/demand-response/loads - 列出可削减负荷
+//! - PUT /projects/{id}/demand-response/loads/{deviceId} - 登记或替换设备的可削减负荷配置
+//! - DELETE /projects/{id}/demand-response/loads/{deviceId} - 移除可削减负荷
+//! - GET /projects/{id}/demand-response/events - 列出事件
+//! - POST /projects/{id}/demand-response/events - 创建事件（窗口不得与未结束的事件重叠）
+//! - GET /projects/{id}/demand-response/events/{eid} - 查询事件（含已选负荷与实际削减量）
+//! - POST /projects/{id}/demand-response/events/{eid}/cancel - 取消事件（进行中的事件会恢复负荷）
+//!
+//! 权限要求：
+//! - 查询需要 CONTROL.DEMAND_RESPONSE.READ
+//! - 写入需要 CONTROL.DEMAND_RESPONSE.WRITE 与 CONTROL.COMMAND.ISSUE，且租户开启 `control` 功能开关
+
+use crate::AppState;
+use crate::middleware::{require_feature, require_permission, require_project_scope};
+use crate::utils::response::{
+    bad_request_error, demand_response_event_to_dto, not_found_error, sheddable_load_to_dto,
+    storage_error,
+};
+use api_contract::{
+    ApiResponse, CreateDemandResponseEventRequest, DemandResponseEventDto,
+    DemandResponseEventQuery, SheddableLoadDto, UpsertSheddableLoadRequest,
+};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use domain::{TenantContext, features, permissions};
+use ems_demand::{STATUS_SCHEDULED, is_open, validate_event, validate_load};
+use ems_storage::{DemandResponseEventRecord, DemandResponseEventUpdate, SheddableLoadRecord};
+
+/// 事件默认/最大返回条数
+const DEFAULT_EVENT_LIMIT: i64 = 50;
+const MAX_EVENT_LIMIT: i64 = 500;
+/// 未指定功率点位时按该标签查找设备点位
+const POWER_POINT_TAG: &str = "power";
+
+#[derive(serde::Deserialize)]
+pub struct DemandResponseProjectPath {
+    project_id: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct SheddableLoadPath {
+    project_id: String,
+    device_id: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct DemandResponseEventPath {
+    project_id: String,
+    event_id: String,
+}
+
+/// 列出可削减负荷
+pub async fn list_sheddable_loads(
+    State(state): State<AppState>,
+    Path(path): Path<DemandResponseProjectPath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::CONTROL_DEMAND_RESPONSE_READ) {
+        return response;
+    }
+    match state
+        .demand_response_store
+        .list_sheddable_loads(&ctx, &path.project_id)
+        .await
+    {
+        Ok(items) => {
+            let data: Vec<SheddableLoadDto> =
+                items.into_iter().map(sheddable_load_to_dto).collect();
+            (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 登记或替换可削减负荷
+pub async fn upsert_sheddable_load(
+    State(state): State<AppState>,
+    Path(path): Path<SheddableLoadPath>,
+    headers: HeaderMap,
+    Json(req): Json<UpsertSheddableLoadRequest>,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_write_access(&state, &ctx).await {
+        return response;
+    }
+    if let Err(err) = validate_load(req.rated_kw, &req.shed_payload, &req.restore_payload) {
+        return bad_request_error(err.to_string());
+    }
+    match state
+        .device_store
+        .find_device(&ctx, &path.project_id, &path.device_id)
+        .await
+    {
+        Ok(Some(_)) => {}
+        Ok(None) => return not_found_error(),
+        Err(err) => return storage_error(err),
+    }
+    let power_point_id = match resolve_power_point(&state, &ctx, &path, req.power_point_id).await {
+        Ok(point_id) => point_id,
+        Err(response) => return response,
+    };
+
+    let now_ms = now_epoch_ms();
+    let record = SheddableLoadRecord {
+        tenant_id: ctx.tenant_id.clone(),
+        project_id: path.project_id,
+        device_id: path.device_id,
+        priority: req.priority,
+        power_point_id,
+        rated_kw: req.rated_kw,
+        shed_payload: req.shed_payload.to_string(),
+        restore_payload: req.restore_payload.to_string(),
+        enabled: req.enabled.unwrap_or(true),
+        created_at_ms: now_ms,
+        updated_at_ms: now_ms,
+    };
+    match state
+        .demand_response_store
+        .upsert_sheddable_load(&ctx, record)
+        .await
+    {
+        Ok(record) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(sheddable_load_to_dto(record))),
+        )
+            .into_response(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 移除可削减负荷
+pub async fn delete_sheddable_load(
+    State(state): State<AppState>,
+    Path(path): Path<SheddableLoadPath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_write_access(&state, &ctx).await {
+        return response;
+    }
+    match state
+        .demand_response_store
+        .delete_sheddable_load(&ctx, &path.project_id, &path.device_id)
+        .await
+    {
+        Ok(true) => (StatusCode::OK, Json(ApiResponse::success(()))).into_response(),
+        Ok(false) => not_found_error(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 列出需求响应事件
+pub async fn list_demand_response_events(
+    State(state): State<AppState>,
+    Path(path): Path<DemandResponseProjectPath>,
+    Query(query): Query<DemandResponseEventQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::CONTROL_DEMAND_RESPONSE_READ) {
+        return response;
+    }
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_EVENT_LIMIT)
+        .clamp(1, MAX_EVENT_LIMIT);
+    match state
+        .demand_response_store
+        .list_demand_response_events(&ctx, &path.project_id, limit)
+        .await
+    {
+        Ok(items) => {
+            let data: Vec<DemandResponseEventDto> = items
+                .into_iter()
+                .map(demand_response_event_to_dto)
+                .collect();
+            (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 创建需求响应事件
+pub async fn create_demand_response_event(
+    State(state): State<AppState>,
+    Path(path): Path<DemandResponseProjectPath>,
+    headers: HeaderMap,
+    Json(req): Json<CreateDemandResponseEventRequest>,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_write_access(&state, &ctx).await {
+        return response;
+    }
+    let now_ms = now_epoch_ms();
+    let start_at_ms = req.start_at_ms.unwrap_or(now_ms);
+    if let Err(err) = validate_event(req.target_kw, start_at_ms, req.end_at_ms, now_ms) {
+        return bad_request_error(err.to_string());
+    }
+    // 同一项目的负荷只能由一个事件控制，窗口不得与未结束的事件重叠
+    match state
+        .demand_response_store
+        .list_demand_response_events(&ctx, &path.project_id, MAX_EVENT_LIMIT)
+        .await
+    {
+        Ok(events) => {
+            if events.iter().any(|event| {
+                is_open(&event.status)
+                    && event.start_at_ms < req.end_at_ms
+                    && start_at_ms < event.end_at_ms
+            }) {
+                return bad_request_error("event window overlaps an open demand response event");
+            }
+        }
+        Err(err) => return storage_error(err),
+    }
+
+    let record = DemandResponseEventRecord {
+        tenant_id: ctx.tenant_id.clone(),
+        project_id: path.project_id,
+        event_id: uuid::Uuid::new_v4().to_string(),
+        target_kw: req.target_kw,
+        start_at_ms,
+        end_at_ms: req.end_at_ms,
+        status: STATUS_SCHEDULED.to_string(),
+        cancel_requested: false,
+        baseline_kw: None,
+        achieved_kw: None,
+        loads: "[]".to_string(),
+        created_by: ctx.user_id.clone(),
+        created_at_ms: now_ms,
+        updated_at_ms: now_ms,
+    };
+    match state
+        .demand_response_store
+        .create_demand_response_event(&ctx, record)
+        .await
+    {
+        Ok(record) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(demand_response_event_to_dto(record))),
+        )
+            .into_response(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 查询需求响应事件
+pub async fn get_demand_response_event(
+    State(state): State<AppState>,
+    Path(path): Path<DemandResponseEventPath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::CONTROL_DEMAND_RESPONSE_READ) {
+        return response;
+    }
+    match state
+        .demand_response_store
+        .find_demand_response_event(&ctx, &path.project_id, &path.event_id)
+        .await
+    {
+        Ok(Some(record)) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(demand_response_event_to_dto(record))),
+        )
+            .into_response(),
+        Ok(None) => not_found_error(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 取消需求响应事件（由编排器在下一次检查时恢复负荷并结束事件）
+pub async fn cancel_demand_response_event(
+    State(state): State<AppState>,
+    Path(path): Path<DemandResponseEventPath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_write_access(&state, &ctx).await {
+        return response;
+    }
+    match state
+        .demand_response_store
+        .find_demand_response_event(&ctx, &path.project_id, &path.event_id)
+        .await
+    {
+        Ok(Some(event)) if !is_open(&event.status) => {
+            return bad_request_error("demand response event already finished");
+        }
+        Ok(Some(_)) => {}
+        Ok(None) => return not_found_error(),
+        Err(err) => return storage_error(err),
+    }
+    let update = DemandResponseEventUpdate {
+        cancel_requested: Some(true),
+        updated_at_ms: now_epoch_ms(),
+        ..DemandResponseEventUpdate::default()
+    };
+    match state
+        .demand_response_store
+        .update_demand_response_event(&ctx, &path.project_id, &path.event_id, update)
+        .await
+    {
+        Ok(Some(record)) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(demand_response_event_to_dto(record))),
+        )
+            .into_response(),
+        Ok(None) => not_found_error(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 确定功率点位：显式指定时校验属于该设备，否则取设备下带 `power` 标签的点位
+async fn resolve_power_point(
+    state: &AppState,
+    ctx: &TenantContext,
+    path: &SheddableLoadPath,
+    power_point_id: Option<String>,
+) -> Result<String, Response> {
+    if let Some(point_id) = power_point_id.map(|value| value.trim().to_string()) {
+        return match state
+            .point_store
+            .find_point(ctx, &path.project_id, &point_id)
+            .await
+        {
+            Ok(Some(point)) if point.device_id == path.device_id => Ok(point.point_id),
+            Ok(_) => Err(bad_request_error(
+                "powerPointId must be a point of the device",
+            )),
+            Err(err) => Err(storage_error(err)),
+        };
+    }
+    let points = state
+        .point_store
+        .list_points(ctx, &path.project_id)
+        .await
+        .map_err(storage_error)?;
+    points
+        .into_iter()
+        .find(|point| {
+            point.device_id == path.device_id && point.tags.iter().any(|tag| tag == POWER_POINT_TAG)
+        })
+        .map(|point| point.point_id)
+        .ok_or_else(|| {
+            bad_request_error("powerPointId is required (device has no point tagged \"power\")")
+        })
+}
+
+/// 写入所需的权限与功能开关（编排器以事件身份下发命令，需在写入时校验）
+async fn require_write_access(state: &AppState, ctx: &TenantContext) -> Result<(), Response> {
+    require_permission(ctx, permissions::CONTROL_DEMAND_RESPONSE_WRITE)?;
+    require_permission(ctx, permissions::CONTROL_COMMAND_ISSUE)?;
+    require_feature(state, ctx, features::CONTROL).await
+}
+
+fn now_epoch_ms() -> i64 {
+    let now = std::time::SystemTime::now();
+    let duration = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    duration.as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{
+        api_router, auth_headers, build_state, json_request, project_ctx, response_json,
+    };
+    use axum::http::StatusCode;
+    use domain::{PointValue, PointValueData};
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    /// 测试：可削减负荷按 power 标签解析功率点位，需求响应事件削减、跟踪并恢复负荷
+    #[tokio::test]
+    async fn demand_response_event_sheds_and_restores_loads() {
+        let state = build_state();
+        let headers = auth_headers(&state).await;
+        let ctx = project_ctx();
+        for device_id in ["chiller-1", "pump-1"] {
+            state
+                .device_store
+                .create_device(
+                    &ctx,
+                    ems_storage::DeviceRecord {
+                        device_id: device_id.to_string(),
+                        tenant_id: "tenant-1".to_string(),
+                        project_id: "project-1".to_string(),
+                        gateway_id: "gateway-1".to_string(),
+                        name: device_id.to_string(),
+                        model: None,
+                        room_id: None,
+                        address_config: None,
+                        offline_after_seconds: None,
+                    },
+                )
+                .await
+                .expect("device");
+            state
+                .point_store
+                .create_point(
+                    &ctx,
+                    ems_storage::PointRecord {
+                        point_id: format!("{device_id}-kw"),
+                        tenant_id: "tenant-1".to_string(),
+                        project_id: "project-1".to_string(),
+                        device_id: device_id.to_string(),
+                        key: "active_power".to_string(),
+                        data_type: "f64".to_string(),
+                        unit: Some("kW".to_string()),
+                        tags: vec!["power".to_string()],
+                    },
+                )
+                .await
+                .expect("point");
+        }
+        let set_power = |point_id: &str, kw: f64| PointValue {
+            tenant_id: "tenant-1".to_string(),
+            project_id: "project-1".to_string(),
+            point_id: point_id.to_string(),
+            ts_ms: 1_000,
+            value: PointValueData::F64(kw),
+            quality: None,
+        };
+        state
+            .realtime_store
+            .upsert_last_value(&ctx, &set_power("chiller-1-kw", 60.0))
+            .await
+            .expect("realtime");
+
+        let app = api_router(state.clone());
+        let request = |method: &str, uri: &str, body: Option<Value>| {
+            json_request(
+                &headers,
+                method,
+                &format!("/api/v1/projects/project-1/demand-response{uri}"),
+                body,
+            )
+        };
+        let load = |priority: i32, rated_kw: f64| {
+            serde_json::json!({
+                "priority": priority,
+                "ratedKw": rated_kw,
+                "shedPayload": { "on": false },
+                "restorePayload": { "on": true }
+            })
+        };
+
+        // 设备不存在返回 404，载荷非对象返回 400
+        let response = app
+            .clone()
+            .oneshot(request("PUT", "/loads/missing", Some(load(1, 5.0))))
+            .await
+            .expect("upsert");
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let mut invalid = load(1, 5.0);
+        invalid["shedPayload"] = serde_json::json!("off");
+        let response = app
+            .clone()
+            .oneshot(request("PUT", "/loads/pump-1", Some(invalid)))
+            .await
+            .expect("upsert");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        for (device_id, priority, rated_kw) in [("pump-1", 1, 15.0), ("chiller-1", 2, 80.0)] {
+            let response = app
+                .clone()
+                .oneshot(request(
+                    "PUT",
+                    &format!("/loads/{device_id}"),
+                    Some(load(priority, rated_kw)),
+                ))
+                .await
+                .expect("upsert");
+            assert_eq!(response.status(), StatusCode::OK);
+            let json = response_json(response).await;
+            assert_eq!(json["data"]["powerPointId"], format!("{device_id}-kw"));
+        }
+
+        let now_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("clock")
+            .as_millis() as i64;
+        let body = serde_json::json!({ "targetKw": 50.0, "endAtMs": now_ms + 600_000 });
+        let response = app
+            .clone()
+            .oneshot(request("POST", "/events", Some(body.clone())))
+            .await
+            .expect("create");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        assert_eq!(json["data"]["status"], "scheduled");
+        let event_id = json["data"]["eventId"]
+            .as_str()
+            .expect("event id")
+            .to_string();
+
+        // 与未结束事件重叠的窗口返回 400
+        let response = app
+            .clone()
+            .oneshot(request("POST", "/events", Some(body)))
+            .await
+            .expect("create");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let runner = ems_demand::DemandResponseRunner::new(
+            state.demand_response_store.clone(),
+            state.realtime_store.clone(),
+            state.command_service.clone(),
+        );
+        runner.run_due(now_ms + 1_000).await;
+        state
+            .realtime_store
+            .upsert_last_value(&ctx, &set_power("chiller-1-kw", 20.0))
+            .await
+            .expect("realtime");
+        runner.run_due(now_ms + 2_000).await;
+
+        let response = app
+            .clone()
+            .oneshot(request("GET", &format!("/events/{event_id}"), None))
+            .await
+            .expect("get");
+        let json = response_json(response).await;
+        assert_eq!(json["data"]["status"], "active");
+        // pump-1 无实时值按额定功率估算：15 + 60
+        assert_eq!(json["data"]["baselineKw"], 75.0);
+        assert_eq!(json["data"]["achievedKw"], 40.0);
+        assert_eq!(json["data"]["loads"][0]["deviceId"], "pump-1");
+        assert_eq!(json["data"]["loads"][1]["deviceId"], "chiller-1");
+
+        let response = app
+            .clone()
+            .oneshot(request("POST", &format!("/events/{event_id}/cancel"), None))
+            .await
+            .expect("cancel");
+        let json = response_json(response).await;
+        assert_eq!(json["data"]["cancelRequested"], true);
+        runner.run_due(now_ms + 3_000).await;
+
+        let response = app
+            .clone()
+            .oneshot(request("GET", "/events", None))
+            .await
+            .expect("list");
+        let json = response_json(response).await;
+        assert_eq!(json["data"][0]["status"], "cancelled");
+        let commands = state
+            .command_store
+            .list_commands(
+                &ctx,
+                "project-1",
+                ems_storage::CommandQueryOptions::simple(10),
+            )
+            .await
+            .expect("commands");
+        assert_eq!(commands.len(), 4);
+        assert!(
+            commands
+                .iter()
+                .all(|command| command.issued_by == format!("demand-response:{event_id}"))
+        );
+
+        // 已结束的事件不能再取消
+        let response = app
+            .oneshot(request("POST", &format!("/events/{event_id}/cancel"), None))
+            .await
+            .expect("cancel");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
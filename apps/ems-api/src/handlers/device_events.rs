@@ -0,0 +1,257 @@
+//! 设备时间线 handlers
+//!
+//! 时间线由事件总线记录器（`ems-events`）写入，这里只提供查询：
+//! - GET /projects/{id}/devices/{did}/events - 按发生时间倒序列出设备事件
+//!   （创建、配置变更、上下线、命令下发 / 完成、告警；可按 eventType / from / to 过滤，游标分页）
+//!
+//! 权限要求：ASSET.DEVICE.READ
+
+use crate::AppState;
+use crate::middleware::{require_permission, require_project_scope};
+use crate::utils::response::{
+    bad_request_error, device_event_to_dto, not_found_error, storage_error,
+};
+use crate::utils::validation::normalize_optional;
+use api_contract::{ApiResponse, DeviceEventDto, DeviceEventQuery};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use domain::permissions;
+
+/// 默认返回条数
+const DEFAULT_DEVICE_EVENT_LIMIT: i64 = 100;
+/// 最大返回条数
+const MAX_DEVICE_EVENT_LIMIT: i64 = 1000;
+
+#[derive(serde::Deserialize)]
+pub struct DeviceEventPath {
+    project_id: String,
+    device_id: String,
+}
+
+/// 列出设备时间线
+pub async fn list_device_events(
+    State(state): State<AppState>,
+    Path(path): Path<DeviceEventPath>,
+    Query(query): Query<DeviceEventQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::ASSET_DEVICE_READ) {
+        return response;
+    }
+    if let (Some(from), Some(to)) = (query.from, query.to)
+        && from > to
+    {
+        return bad_request_error("from must be <= to");
+    }
+    if query.cursor_event_id.is_some() && query.cursor_ts_ms.is_none() {
+        return bad_request_error("cursorEventId requires cursorTsMs");
+    }
+    let event_type = match normalize_optional(query.event_type, "eventType") {
+        Ok(value) => value,
+        Err(response) => return response,
+    };
+    match state
+        .device_store
+        .find_device(&ctx, &path.project_id, &path.device_id)
+        .await
+    {
+        Ok(Some(_)) => {}
+        Ok(None) => return not_found_error(),
+        Err(err) => return storage_error(err),
+    }
+    let options = ems_storage::DeviceEventQuery {
+        event_type,
+        from_ms: query.from,
+        to_ms: query.to,
+        cursor_ts_ms: query.cursor_ts_ms,
+        cursor_event_id: query.cursor_event_id,
+        limit: query
+            .limit
+            .unwrap_or(DEFAULT_DEVICE_EVENT_LIMIT)
+            .clamp(1, MAX_DEVICE_EVENT_LIMIT),
+    };
+    match state
+        .device_event_store
+        .list_device_events(&ctx, &path.project_id, &path.device_id, options)
+        .await
+    {
+        Ok(items) => {
+            let data: Vec<DeviceEventDto> = items.into_iter().map(device_event_to_dto).collect();
+            (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{api_router, auth_headers, build_state, json_request, response_json};
+    use axum::http::StatusCode;
+    use ems_events::{DeviceEventRecorder, spawn_device_event_recorder};
+    use serde_json::Value;
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    /// 测试：设备时间线记录创建、配置变更、上下线与命令事件，并按时间倒序游标分页
+    #[tokio::test]
+    async fn device_timeline_records_lifecycle_events() {
+        let state = build_state();
+        let headers = auth_headers(&state).await;
+        let _recorder = spawn_device_event_recorder(
+            &state.event_bus,
+            Arc::new(DeviceEventRecorder::new(
+                state.device_event_store.clone(),
+                state.device_store.clone(),
+            )),
+        );
+        let app = api_router(state.clone());
+        let request = |method: &str, uri: &str, body: Option<Value>| {
+            json_request(
+                &headers,
+                method,
+                &format!("/api/v1/projects/project-1{uri}"),
+                body,
+            )
+        };
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/gateways",
+                Some(serde_json::json!({ "name": "gw", "protocolType": "mqtt" })),
+            ))
+            .await
+            .expect("response");
+        let json = response_json(response).await;
+        let gateway_id = json["data"]["gatewayId"].as_str().expect("gateway id");
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/devices",
+                Some(serde_json::json!({ "gatewayId": gateway_id, "name": "meter" })),
+            ))
+            .await
+            .expect("response");
+        let json = response_json(response).await;
+        let device_id = json["data"]["deviceId"]
+            .as_str()
+            .expect("device id")
+            .to_string();
+        let response = app
+            .clone()
+            .oneshot(request(
+                "PUT",
+                &format!("/devices/{device_id}"),
+                Some(serde_json::json!({ "name": "meter-2" })),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/commands",
+                Some(serde_json::json!({ "target": device_id, "payload": {"switch": "on"} })),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        state.event_bus.publish(ems_events::DomainEvent::new(
+            ems_events::event_types::DEVICE_OFFLINE,
+            "tenant-1",
+            "project-1",
+            serde_json::json!({ "deviceId": device_id }),
+        ));
+
+        // 记录器异步消费事件总线，等待全部写入
+        let mut items = Vec::new();
+        for _ in 0..100 {
+            let response = app
+                .clone()
+                .oneshot(request(
+                    "GET",
+                    &format!("/devices/{device_id}/events"),
+                    None,
+                ))
+                .await
+                .expect("response");
+            assert_eq!(response.status(), StatusCode::OK);
+            let json = response_json(response).await;
+            items = json["data"].as_array().cloned().unwrap_or_default();
+            if items.len() >= 4 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        let mut types: Vec<&str> = items
+            .iter()
+            .filter_map(|item| item["eventType"].as_str())
+            .collect();
+        types.sort();
+        assert_eq!(
+            types,
+            vec![
+                "command.issued",
+                "device.created",
+                "device.offline",
+                "device.updated"
+            ]
+        );
+        let updated = items
+            .iter()
+            .find(|item| item["eventType"] == "device.updated")
+            .expect("updated");
+        assert_eq!(
+            updated["data"]["changedFields"],
+            serde_json::json!(["name"])
+        );
+
+        // 游标分页：第二页从第一页最后一条之后开始
+        let response = app
+            .clone()
+            .oneshot(request(
+                "GET",
+                &format!("/devices/{device_id}/events?limit=2"),
+                None,
+            ))
+            .await
+            .expect("response");
+        let json = response_json(response).await;
+        let first_page = json["data"].as_array().cloned().expect("page");
+        assert_eq!(first_page.len(), 2);
+        let last = &first_page[1];
+        let response = app
+            .clone()
+            .oneshot(request(
+                "GET",
+                &format!(
+                    "/devices/{device_id}/events?limit=10&cursorTsMs={}&cursorEventId={}",
+                    last["occurredAtMs"],
+                    last["eventId"].as_str().expect("event id")
+                ),
+                None,
+            ))
+            .await
+            .expect("response");
+        let json = response_json(response).await;
+        assert_eq!(json["data"].as_array().map(Vec::len), Some(2));
+
+        let response = app
+            .clone()
+            .oneshot(request("GET", "/devices/device-404/events", None))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
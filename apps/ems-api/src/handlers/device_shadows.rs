@@ -0,0 +1,287 @@
+//! 设备影子 handlers
+//!
+//! - GET /projects/{id}/devices/{did}/shadow - 查询设备影子（期望 / 上报 / 差量）
+//! - PUT /projects/{id}/devices/{did}/shadow - 设置期望状态（整体替换），差量非空时以命令下发到设备
+//!
+//! 权限要求：
+//! - 查询需要 ASSET.DEVICE.READ
+//! - 设置需要 CONTROL.COMMAND.ISSUE（租户关闭 `control` 功能开关时返回 403 FEATURE.DISABLED）
+
+use crate::AppState;
+use crate::middleware::{require_feature, require_permission, require_project_scope};
+use crate::utils::response::{control_error, device_shadow_to_dto, not_found_error, storage_error};
+use api_contract::{ApiResponse, UpdateDeviceShadowRequest};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use domain::{TenantContext, features, permissions};
+
+#[derive(serde::Deserialize)]
+pub struct DeviceShadowPath {
+    project_id: String,
+    device_id: String,
+}
+
+/// 查询设备影子
+pub async fn get_device_shadow(
+    State(state): State<AppState>,
+    Path(path): Path<DeviceShadowPath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::ASSET_DEVICE_READ) {
+        return response;
+    }
+    if let Err(response) = ensure_device(&state, &ctx, &path).await {
+        return response;
+    }
+    match state
+        .shadow_service
+        .get_shadow(&ctx, &path.project_id, &path.device_id)
+        .await
+    {
+        Ok(shadow) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(device_shadow_to_dto(shadow))),
+        )
+            .into_response(),
+        Err(err) => control_error(err),
+    }
+}
+
+/// 设置设备期望状态
+pub async fn update_device_shadow(
+    State(state): State<AppState>,
+    Path(path): Path<DeviceShadowPath>,
+    headers: HeaderMap,
+    Json(req): Json<UpdateDeviceShadowRequest>,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::CONTROL_COMMAND_ISSUE) {
+        return response;
+    }
+    if let Err(response) = require_feature(&state, &ctx, features::CONTROL).await {
+        return response;
+    }
+    if let Err(response) = ensure_device(&state, &ctx, &path).await {
+        return response;
+    }
+    match state
+        .shadow_service
+        .set_desired(
+            &ctx,
+            &path.project_id,
+            &path.device_id,
+            req.desired,
+            now_epoch_ms(),
+        )
+        .await
+    {
+        Ok(shadow) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(device_shadow_to_dto(shadow))),
+        )
+            .into_response(),
+        Err(err) => control_error(err),
+    }
+}
+
+async fn ensure_device(
+    state: &AppState,
+    ctx: &TenantContext,
+    path: &DeviceShadowPath,
+) -> Result<(), Response> {
+    match state
+        .device_store
+        .find_device(ctx, &path.project_id, &path.device_id)
+        .await
+    {
+        Ok(Some(_)) => Ok(()),
+        Ok(None) => Err(not_found_error()),
+        Err(err) => Err(storage_error(err)),
+    }
+}
+
+fn now_epoch_ms() -> i64 {
+    let now = std::time::SystemTime::now();
+    let duration = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    duration.as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{
+        api_router, auth_headers, build_state, json_request, project_ctx, response_json,
+    };
+    use axum::http::StatusCode;
+    use domain::{PointValue, PointValueData};
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    /// 测试：设备影子（期望状态下发差量、回执与实时值推导上报状态）
+    #[tokio::test]
+    async fn device_shadow_publishes_delta_and_converges() {
+        let state = build_state();
+        let headers = auth_headers(&state).await;
+        let ctx = project_ctx();
+        state
+            .device_store
+            .create_device(
+                &ctx,
+                ems_storage::DeviceRecord {
+                    device_id: "device-1".to_string(),
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    gateway_id: "gateway-1".to_string(),
+                    name: "AHU".to_string(),
+                    model: None,
+                    room_id: None,
+                    address_config: None,
+                    offline_after_seconds: None,
+                },
+            )
+            .await
+            .expect("device");
+        for (point_id, key) in [("point-1", "setpoint"), ("point-2", "mode")] {
+            state
+                .point_store
+                .create_point(
+                    &ctx,
+                    ems_storage::PointRecord {
+                        point_id: point_id.to_string(),
+                        tenant_id: "tenant-1".to_string(),
+                        project_id: "project-1".to_string(),
+                        device_id: "device-1".to_string(),
+                        key: key.to_string(),
+                        data_type: "f64".to_string(),
+                        unit: None,
+                        tags: Vec::new(),
+                    },
+                )
+                .await
+                .expect("point");
+        }
+        let upsert = |point_id: &str, ts_ms: i64, value: PointValueData| PointValue {
+            tenant_id: "tenant-1".to_string(),
+            project_id: "project-1".to_string(),
+            point_id: point_id.to_string(),
+            ts_ms,
+            value,
+            quality: None,
+        };
+        state
+            .realtime_store
+            .upsert_last_value(&ctx, &upsert("point-1", 1_000, PointValueData::F64(20.0)))
+            .await
+            .expect("upsert");
+
+        let app = api_router(state.clone());
+        let request = |method: &str, body: Option<Value>| {
+            json_request(
+                &headers,
+                method,
+                "/api/v1/projects/project-1/devices/device-1/shadow",
+                body,
+            )
+        };
+
+        // 未设置期望状态：上报状态来自实时值
+        let response = app
+            .clone()
+            .oneshot(request("GET", None))
+            .await
+            .expect("get");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        assert_eq!(json["data"]["version"], 0);
+        assert_eq!(json["data"]["reported"]["setpoint"], 20.0);
+        assert_eq!(json["data"]["inSync"], true);
+
+        // 设置期望状态：差量以命令下发到设备
+        let body = serde_json::json!({ "desired": { "setpoint": 22, "mode": "eco" } });
+        let response = app
+            .clone()
+            .oneshot(request("PUT", Some(body)))
+            .await
+            .expect("put");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        assert_eq!(json["data"]["version"], 1);
+        assert_eq!(
+            json["data"]["delta"],
+            serde_json::json!({ "setpoint": 22, "mode": "eco" })
+        );
+        assert_eq!(json["data"]["lastCommandStatus"], "accepted");
+        let command_id = json["data"]["lastCommandId"]
+            .as_str()
+            .expect("command id")
+            .to_string();
+        let commands = state
+            .command_store
+            .list_commands(
+                &ctx,
+                "project-1",
+                ems_storage::CommandQueryOptions::simple(10),
+            )
+            .await
+            .expect("commands");
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].target, "device-1");
+        let payload: Value = serde_json::from_str(&commands[0].payload).expect("payload");
+        assert_eq!(payload["shadow"]["version"], 1);
+        assert_eq!(payload["shadow"]["delta"]["mode"], "eco");
+
+        // 未知点位 key 返回 400
+        let body = serde_json::json!({ "desired": { "unknown": 1 } });
+        let response = app
+            .clone()
+            .oneshot(request("PUT", Some(body)))
+            .await
+            .expect("put");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // 设备成功回执 + 新的实时值：差量收敛
+        state
+            .command_receipt_store
+            .create_receipt(
+                &ctx,
+                ems_storage::CommandReceiptRecord {
+                    receipt_id: "receipt-1".to_string(),
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    command_id,
+                    ts_ms: 3_000,
+                    status: "success".to_string(),
+                    message: None,
+                },
+            )
+            .await
+            .expect("receipt");
+        state
+            .realtime_store
+            .upsert_last_value(
+                &ctx,
+                &upsert("point-2", 5_000, PointValueData::String("eco".to_string())),
+            )
+            .await
+            .expect("upsert");
+        let response = app.oneshot(request("GET", None)).await.expect("get");
+        let json = response_json(response).await;
+        assert_eq!(json["data"]["reported"]["setpoint"], 22);
+        assert_eq!(json["data"]["reported"]["mode"], "eco");
+        assert_eq!(json["data"]["reportedAtMs"], 5_000);
+        assert_eq!(json["data"]["lastCommandStatus"], "success");
+        assert_eq!(json["data"]["inSync"], true);
+    }
+}
@@ -0,0 +1,257 @@
+//! 设备模板 handlers
+//!
+//! 提供设备模板（产品模型）的管理接口：
+//! - GET /projects/{id}/device-templates - 列出设备模板
+//! - POST /projects/{id}/device-templates - 创建设备模板（标准点位 + 映射提示）
+//! - GET /projects/{id}/device-templates/{tid} - 获取设备模板详情
+//! - DELETE /projects/{id}/device-templates/{tid} - 删除设备模板
+//!
+//! 按模板实例化设备通过 `POST /projects/{id}/devices?templateId=...` 完成。
+//!
+//! 权限要求：
+//! - 读取需要 ASSET.DEVICE.READ，写入需要 ASSET.DEVICE.WRITE
+
+use crate::AppState;
+use crate::middleware::{require_permission, require_project_scope};
+use crate::utils::response::{
+    bad_request_error, device_template_to_dto, not_found_error, storage_error,
+};
+use crate::utils::{normalize_optional, normalize_required};
+use api_contract::{ApiResponse, CreateDeviceTemplateRequest, DeviceTemplateDto};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use domain::permissions;
+use ems_storage::{
+    DeviceInstance, DeviceRecord, DeviceTemplatePoint, DeviceTemplateRecord, PointMappingRecord,
+    PointRecord,
+};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+#[derive(serde::Deserialize)]
+pub struct ProjectPath {
+    project_id: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct DeviceTemplatePath {
+    project_id: String,
+    template_id: String,
+}
+
+/// 列出设备模板
+pub async fn list_device_templates(
+    State(state): State<AppState>,
+    Path(path): Path<ProjectPath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::ASSET_DEVICE_READ) {
+        return response;
+    }
+    match state
+        .device_template_store
+        .list_device_templates(&ctx, &path.project_id)
+        .await
+    {
+        Ok(items) => {
+            let data: Vec<DeviceTemplateDto> =
+                items.into_iter().map(device_template_to_dto).collect();
+            (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 创建设备模板
+///
+/// 校验规则：
+/// - 模板至少包含一个点位，点位 key 在模板内唯一
+/// - 映射提示 `sourceType` 与 `address` 必须同时提供或同时省略
+pub async fn create_device_template(
+    State(state): State<AppState>,
+    Path(path): Path<ProjectPath>,
+    headers: HeaderMap,
+    Json(req): Json<CreateDeviceTemplateRequest>,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::ASSET_DEVICE_WRITE) {
+        return response;
+    }
+    let name = match normalize_required(req.name, "name") {
+        Ok(value) => value,
+        Err(response) => return response,
+    };
+    let model = match normalize_optional(req.model, "model") {
+        Ok(value) => value,
+        Err(response) => return response,
+    };
+    if req.points.is_empty() {
+        return bad_request_error("points required");
+    }
+    let mut keys = HashSet::new();
+    let mut points = Vec::with_capacity(req.points.len());
+    for point in req.points {
+        let key = match normalize_required(point.key, "key") {
+            Ok(value) => value,
+            Err(response) => return response,
+        };
+        let data_type = match normalize_required(point.data_type, "dataType") {
+            Ok(value) => value,
+            Err(response) => return response,
+        };
+        let source_type = match normalize_optional(point.source_type, "sourceType") {
+            Ok(value) => value,
+            Err(response) => return response,
+        };
+        let address = match normalize_optional(point.address, "address") {
+            Ok(value) => value,
+            Err(response) => return response,
+        };
+        if source_type.is_some() != address.is_some() {
+            return bad_request_error("sourceType and address must be provided together");
+        }
+        if !keys.insert(key.clone()) {
+            return bad_request_error(format!("duplicate point key: {key}"));
+        }
+        points.push(DeviceTemplatePoint {
+            key,
+            data_type,
+            unit: point.unit,
+            source_type,
+            address,
+            scale: point.scale,
+            offset: point.offset,
+            protocol_detail: point.protocol_detail,
+        });
+    }
+    let record = DeviceTemplateRecord {
+        template_id: Uuid::new_v4().to_string(),
+        tenant_id: ctx.tenant_id.clone(),
+        project_id: path.project_id,
+        name,
+        model,
+        points,
+    };
+    match state
+        .device_template_store
+        .create_device_template(&ctx, record)
+        .await
+    {
+        Ok(item) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(device_template_to_dto(item))),
+        )
+            .into_response(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 获取设备模板详情
+pub async fn get_device_template(
+    State(state): State<AppState>,
+    Path(path): Path<DeviceTemplatePath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::ASSET_DEVICE_READ) {
+        return response;
+    }
+    match state
+        .device_template_store
+        .find_device_template(&ctx, &path.project_id, &path.template_id)
+        .await
+    {
+        Ok(Some(item)) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(device_template_to_dto(item))),
+        )
+            .into_response(),
+        Ok(None) => not_found_error(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 删除设备模板
+///
+/// 已按模板创建的设备、点位与映射不受影响。
+pub async fn delete_device_template(
+    State(state): State<AppState>,
+    Path(path): Path<DeviceTemplatePath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::ASSET_DEVICE_WRITE) {
+        return response;
+    }
+    match state
+        .device_template_store
+        .delete_device_template(&ctx, &path.project_id, &path.template_id)
+        .await
+    {
+        Ok(true) => (StatusCode::OK, Json(ApiResponse::success(()))).into_response(),
+        Ok(false) => not_found_error(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 按模板展开设备实例（为设备、点位、映射分配新 ID）。
+///
+/// 设备未指定型号时沿用模板型号；仅带有映射提示的点位会生成点位映射。
+pub(crate) fn build_device_instance(
+    template: DeviceTemplateRecord,
+    mut device: DeviceRecord,
+) -> DeviceInstance {
+    if device.model.is_none() {
+        device.model = template.model;
+    }
+    let mut points = Vec::with_capacity(template.points.len());
+    let mut mappings = Vec::new();
+    for item in template.points {
+        let point_id = Uuid::new_v4().to_string();
+        if let (Some(source_type), Some(address)) = (item.source_type, item.address) {
+            mappings.push(PointMappingRecord {
+                source_id: Uuid::new_v4().to_string(),
+                tenant_id: device.tenant_id.clone(),
+                project_id: device.project_id.clone(),
+                point_id: point_id.clone(),
+                source_type,
+                address,
+                scale: item.scale,
+                offset: item.offset,
+                protocol_detail: item.protocol_detail,
+            });
+        }
+        points.push(PointRecord {
+            point_id,
+            tenant_id: device.tenant_id.clone(),
+            project_id: device.project_id.clone(),
+            device_id: device.device_id.clone(),
+            key: item.key,
+            data_type: item.data_type,
+            unit: item.unit,
+            tags: Vec::new(),
+        });
+    }
+    DeviceInstance {
+        device,
+        points,
+        mappings,
+    }
+}
@@ -2,7 +2,7 @@
 //!
 //! 提供设备资源的增删改查接口：
 //! - GET /projects/{id}/devices - 列出设备
-//! - POST /projects/{id}/devices - 创建设备（需验证网关存在）
+//! - POST /projects/{id}/devices - 创建设备（需验证网关存在；支持 `?templateId=` 按模板实例化）
 //! - GET /projects/{id}/devices/{did} - 获取设备详情
 //! - PUT /projects/{id}/devices/{did} - 更新设备
 //! - DELETE /projects/{id}/devices/{did} - 删除设备
@@ -11,20 +11,34 @@
 //! - 所有接口需要 Bearer token 认证
 //! - 需验证项目归属当前租户
 //! - 创建设备时需验证网关属于该项目
+//! - `addressConfig` 按所属网关的协议类型做字段级校验（ems_protocol）
+//! - `offlineAfterSeconds` 为离线检测阈值（须大于 0，未设置时使用全局默认值）
+//!
+//! 事件：创建发布 `device.created`，更新发布 `device.updated`（附变更字段），写入设备时间线。
 
 use crate::AppState;
-use crate::middleware::{require_permission, require_project_scope};
-use crate::utils::response::device_to_dto;
+use crate::handlers::device_templates::build_device_instance;
+use crate::middleware::{require_permission, require_point_quota, require_project_scope};
 use crate::utils::response::{bad_request_error, not_found_error, storage_error};
-use crate::utils::{normalize_optional, normalize_required};
-use api_contract::{ApiResponse, CreateDeviceRequest, DeviceDto, UpdateDeviceRequest};
+use crate::utils::response::{
+    collection_etag, device_instance_to_dto, device_to_dto, etag_matches, last_seen_fingerprint,
+    list_success, not_modified, with_etag,
+};
+use crate::utils::{check_protocol_fields, normalize_optional, normalize_required, parse_fields};
+use api_contract::{
+    ApiResponse, CreateDeviceQuery, CreateDeviceRequest, DeviceDto, FieldsQuery,
+    UpdateDeviceRequest,
+};
 use axum::{
     Json,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
     response::{IntoResponse, Response},
 };
-use domain::permissions;
+use domain::{TenantContext, permissions};
+use ems_events::{DomainEvent, event_types};
+use ems_protocol::validate_device_address;
+use ems_storage::DeviceRecord;
 use uuid::Uuid;
 
 #[derive(serde::Deserialize)]
@@ -67,6 +81,7 @@ pub struct DevicePath {
 pub async fn list_devices(
     State(state): State<AppState>,
     Path(path): Path<ProjectPath>,
+    Query(fields): Query<FieldsQuery>,
     headers: HeaderMap,
 ) -> Response {
     let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
@@ -76,6 +91,18 @@ pub async fn list_devices(
     if let Err(response) = require_permission(&ctx, permissions::ASSET_DEVICE_READ) {
         return response;
     }
+    let fields = match parse_fields(fields.fields) {
+        Ok(fields) => fields,
+        Err(response) => return response,
+    };
+    let version = match state
+        .device_store
+        .devices_version(&ctx, &path.project_id)
+        .await
+    {
+        Ok(version) => version,
+        Err(err) => return storage_error(err),
+    };
     match state
         .device_store
         .list_devices(&ctx, &path.project_id)
@@ -88,6 +115,11 @@ pub async fn list_devices(
                 .list_devices_last_seen_at_ms(&ctx, &path.project_id, &device_ids)
                 .await
                 .unwrap_or_default();
+            // 在线状态来自实时存储，变化同样使 ETag 失效
+            let etag = collection_etag(version, fields.as_deref(), &last_seen_fingerprint(&online));
+            if etag_matches(&headers, &etag) {
+                return not_modified(&etag);
+            }
             let data: Vec<DeviceDto> = items
                 .into_iter()
                 .map(|record| {
@@ -99,7 +131,7 @@ pub async fn list_devices(
                     dto
                 })
                 .collect();
-            (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+            with_etag(list_success(data, fields.as_deref()), &etag)
         }
         Err(err) => storage_error(err),
     }
@@ -113,12 +145,15 @@ pub async fn list_devices(
 ///
 /// - `state`: 应用状态，包含 `device_store` 和 `gateway_store` 存储实例
 /// - `path`: 路径参数，包含 `project_id`
+/// - `query`: 查询参数，可选 `templateId`（按设备模板实例化）
 /// - `headers`: HTTP 请求头，用于提取 Bearer token 进行认证
 /// - `req`: 请求体，包含设备创建信息（gateway_id、name、model）
 ///
 /// # 返回
 ///
 /// 成功时返回 `200 OK` 和创建的设备信息，失败时返回相应的错误响应。
+/// 提供 `templateId` 时返回 `DeviceInstanceDto`（设备 + 点位 + 点位映射），
+/// 三者在同一事务内写入。
 ///
 /// # 流程
 ///
@@ -131,13 +166,14 @@ pub async fn list_devices(
 ///
 /// # 错误处理
 ///
-/// - `400 BAD REQUEST`: 必填字段缺失或网关不存在
+/// - `400 BAD REQUEST`: 必填字段缺失、网关不存在、设备模板不存在或地址配置校验失败
 /// - `401 UNAUTHORIZED`: 认证失败
 /// - `403 FORBIDDEN`: 项目归属验证失败
 /// - `500 INTERNAL SERVER ERROR`: 存储层错误
 pub async fn create_device(
     State(state): State<AppState>,
     Path(path): Path<ProjectPath>,
+    Query(query): Query<CreateDeviceQuery>,
     headers: HeaderMap,
     Json(req): Json<CreateDeviceRequest>,
 ) -> Response {
@@ -156,14 +192,28 @@ pub async fn create_device(
         Ok(value) => value,
         Err(response) => return response,
     };
-    let exists = state
+    if req
+        .offline_after_seconds
+        .is_some_and(|seconds| seconds <= 0)
+    {
+        return bad_request_error("offlineAfterSeconds must be greater than 0");
+    }
+    let gateway = match state
         .gateway_store
         .find_gateway(&ctx, &path.project_id, &gateway_id)
-        .await;
-    match exists {
-        Ok(Some(_)) => {}
+        .await
+    {
+        Ok(Some(gateway)) => gateway,
         Ok(None) => return bad_request_error("gateway not found"),
         Err(err) => return storage_error(err),
+    };
+    if let Some(address_config) = req.address_config.as_deref()
+        && let Err(response) = check_protocol_fields(
+            "addressConfig",
+            validate_device_address(&gateway.protocol_type, address_config),
+        )
+    {
+        return response;
     }
     let record = ems_storage::DeviceRecord {
         device_id: Uuid::new_v4().to_string(),
@@ -174,13 +224,48 @@ pub async fn create_device(
         model: req.model,
         room_id: req.room_id,
         address_config: req.address_config,
+        offline_after_seconds: req.offline_after_seconds,
     };
+    if let Some(template_id) = query.template_id {
+        let template = match state
+            .device_template_store
+            .find_device_template(&ctx, &record.project_id, &template_id)
+            .await
+        {
+            Ok(Some(template)) => template,
+            Ok(None) => return bad_request_error("device template not found"),
+            Err(err) => return storage_error(err),
+        };
+        let instance = build_device_instance(template, record);
+        if let Err(response) = require_point_quota(&state, &ctx, instance.points.len() as i64).await
+        {
+            return response;
+        }
+        return match state
+            .device_template_store
+            .instantiate_device(&ctx, instance)
+            .await
+        {
+            Ok(item) => {
+                publish_device_created(&state, &ctx, &item.device, Some(&template_id));
+                (
+                    StatusCode::OK,
+                    Json(ApiResponse::success(device_instance_to_dto(item))),
+                )
+                    .into_response()
+            }
+            Err(err) => storage_error(err),
+        };
+    }
     match state.device_store.create_device(&ctx, record).await {
-        Ok(item) => (
-            StatusCode::OK,
-            Json(ApiResponse::success(device_to_dto(item))),
-        )
-            .into_response(),
+        Ok(item) => {
+            publish_device_created(&state, &ctx, &item, None);
+            (
+                StatusCode::OK,
+                Json(ApiResponse::success(device_to_dto(item))),
+            )
+                .into_response()
+        }
         Err(err) => storage_error(err),
     }
 }
@@ -274,7 +359,7 @@ pub async fn get_device(
 ///
 /// # 错误处理
 ///
-/// - `400 BAD REQUEST`: 没有提供更新字段或字段格式错误
+/// - `400 BAD REQUEST`: 没有提供更新字段、字段格式错误或地址配置校验失败
 /// - `401 UNAUTHORIZED`: 认证失败
 /// - `403 FORBIDDEN`: 项目归属验证失败
 /// - `404 NOT FOUND`: 设备不存在
@@ -302,14 +387,48 @@ pub async fn update_device(
     };
     let room_id = req.room_id;
     let address_config = req.address_config;
-    if name.is_none() && model.is_none() && room_id.is_none() && address_config.is_none() {
+    let offline_after_seconds = req.offline_after_seconds;
+    if name.is_none()
+        && model.is_none()
+        && room_id.is_none()
+        && address_config.is_none()
+        && offline_after_seconds.is_none()
+    {
         return bad_request_error("empty update");
     }
+    if offline_after_seconds.is_some_and(|seconds| seconds <= 0) {
+        return bad_request_error("offlineAfterSeconds must be greater than 0");
+    }
+    if let Some(config) = address_config.as_deref() {
+        let protocol_type =
+            match device_protocol_type(&state, &ctx, &path.project_id, &path.device_id).await {
+                Ok(Some(protocol_type)) => protocol_type,
+                Ok(None) => return not_found_error(),
+                Err(response) => return response,
+            };
+        if let Err(response) = check_protocol_fields(
+            "addressConfig",
+            validate_device_address(&protocol_type, config),
+        ) {
+            return response;
+        }
+    }
+    let changed: Vec<&str> = [
+        ("name", name.is_some()),
+        ("model", model.is_some()),
+        ("roomId", room_id.is_some()),
+        ("addressConfig", address_config.is_some()),
+        ("offlineAfterSeconds", offline_after_seconds.is_some()),
+    ]
+    .into_iter()
+    .filter_map(|(field, present)| present.then_some(field))
+    .collect();
     let update = ems_storage::DeviceUpdate {
         name,
         model,
         room_id,
         address_config,
+        offline_after_seconds,
     };
     match state
         .device_store
@@ -317,6 +436,17 @@ pub async fn update_device(
         .await
     {
         Ok(Some(item)) => {
+            state.event_bus.publish(DomainEvent::new(
+                event_types::DEVICE_UPDATED,
+                item.tenant_id.clone(),
+                item.project_id.clone(),
+                serde_json::json!({
+                    "deviceId": item.device_id,
+                    "name": item.name,
+                    "changedFields": changed,
+                    "updatedBy": ctx.user_id,
+                }),
+            ));
             let last_seen_at_ms = state
                 .online_store
                 .get_device_last_seen_at_ms(&ctx, &path.project_id, &path.device_id)
@@ -389,3 +519,134 @@ pub async fn delete_device(
         Err(err) => storage_error(err),
     }
 }
+
+/// 查询设备所属网关的协议类型（设备或网关不存在时返回 None）
+pub(crate) async fn device_protocol_type(
+    state: &AppState,
+    ctx: &TenantContext,
+    project_id: &str,
+    device_id: &str,
+) -> Result<Option<String>, Response> {
+    let device = match state
+        .device_store
+        .find_device(ctx, project_id, device_id)
+        .await
+    {
+        Ok(Some(device)) => device,
+        Ok(None) => return Ok(None),
+        Err(err) => return Err(storage_error(err)),
+    };
+    match state
+        .gateway_store
+        .find_gateway(ctx, project_id, &device.gateway_id)
+        .await
+    {
+        Ok(gateway) => Ok(gateway.map(|gateway| gateway.protocol_type)),
+        Err(err) => Err(storage_error(err)),
+    }
+}
+
+/// 发布 `device.created`（按模板实例化时附带 `templateId`）
+fn publish_device_created(
+    state: &AppState,
+    ctx: &TenantContext,
+    device: &DeviceRecord,
+    template_id: Option<&str>,
+) {
+    state.event_bus.publish(DomainEvent::new(
+        event_types::DEVICE_CREATED,
+        device.tenant_id.clone(),
+        device.project_id.clone(),
+        serde_json::json!({
+            "deviceId": device.device_id,
+            "gatewayId": device.gateway_id,
+            "name": device.name,
+            "templateId": template_id,
+            "createdBy": ctx.user_id,
+        }),
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{api_router, auth_headers, build_state, json_request, response_json};
+    use axum::http::StatusCode;
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    /// 测试：设备离线阈值在创建 / 更新时校验并随设备返回
+    #[tokio::test]
+    async fn device_offline_threshold_validated_and_returned() {
+        let state = build_state();
+        let headers = auth_headers(&state).await;
+        let app = api_router(state.clone());
+        let request = |method: &str, uri: &str, body: Value| {
+            json_request(
+                &headers,
+                method,
+                &format!("/api/v1/projects/project-1{uri}"),
+                Some(body),
+            )
+        };
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/gateways",
+                serde_json::json!({ "name": "gw", "protocolType": "mqtt" }),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        let gateway_id = json["data"]["gatewayId"].as_str().expect("gateway id");
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/devices",
+                serde_json::json!({
+                    "gatewayId": gateway_id,
+                    "name": "meter",
+                    "offlineAfterSeconds": 0,
+                }),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/devices",
+                serde_json::json!({
+                    "gatewayId": gateway_id,
+                    "name": "meter",
+                    "offlineAfterSeconds": 120,
+                }),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        assert_eq!(json["data"]["offlineAfterSeconds"], 120);
+        let device_id = json["data"]["deviceId"].as_str().expect("device id");
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                "PUT",
+                &format!("/devices/{device_id}"),
+                serde_json::json!({ "offlineAfterSeconds": 60 }),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        assert_eq!(json["data"]["offlineAfterSeconds"], 60);
+        assert_eq!(json["data"]["name"], "meter");
+    }
+}
@@ -0,0 +1,230 @@
+//! 租户功能开关 handlers
+//!
+//! 按租户灰度开放能力（内置开关默认开启，其余键默认关闭）：
+//! - GET /feature-flags - 列出生效开关（内置开关 + 已配置开关，合并默认值），需要 FEATURE.FLAG.READ
+//! - PUT /ops/tenants/{tenant_id}/feature-flags/{key}（仅运维端口）- 设置开关（启用 + 可选变体）
+//! - DELETE /ops/tenants/{tenant_id}/feature-flags/{key}（仅运维端口）- 删除配置，恢复默认值
+//!
+//! 开关由平台运维按租户开放，租户 API 只读（租户管理员不能自行打开灰度能力）。
+//!
+//! 内置开关的消费方：control（下发命令）、graphql（GraphQL 查询）、webhooks（创建订阅）。
+
+use crate::AppState;
+use crate::middleware::{require_permission, require_tenant_context};
+use crate::ops::{OpsState, operator_context};
+use crate::utils::response::{bad_request_error, not_found_error, storage_error};
+use crate::utils::validation::normalize_optional;
+use api_contract::{ApiResponse, FeatureFlagDto, UpdateFeatureFlagRequest};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use domain::{features, permissions};
+use ems_storage::FeatureFlagRecord;
+
+#[derive(serde::Deserialize)]
+pub struct TenantFeatureFlagPath {
+    pub tenant_id: String,
+    pub flag_key: String,
+}
+
+/// 列出租户生效的功能开关
+pub async fn list_feature_flags(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    let ctx = match require_tenant_context(&state, &headers) {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::FEATURE_FLAG_READ) {
+        return response;
+    }
+    let records = match state.feature_flag_store.list_feature_flags(&ctx).await {
+        Ok(records) => records,
+        Err(err) => return storage_error(err),
+    };
+
+    let mut data: Vec<FeatureFlagDto> = features::BUILTIN_FLAGS
+        .iter()
+        .filter(|flag_key| !records.iter().any(|record| record.flag_key == **flag_key))
+        .map(|flag_key| FeatureFlagDto {
+            flag_key: flag_key.to_string(),
+            enabled: features::default_enabled(flag_key),
+            variant: None,
+            builtin: true,
+            is_default: true,
+            updated_at: None,
+        })
+        .collect();
+    data.extend(records.into_iter().map(feature_flag_to_dto));
+    data.sort_by(|left, right| left.flag_key.cmp(&right.flag_key));
+    (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+}
+
+/// 设置指定租户的功能开关（仅运维端口）
+pub async fn set_tenant_feature_flag(
+    State(state): State<OpsState>,
+    Path(path): Path<TenantFeatureFlagPath>,
+    Json(req): Json<UpdateFeatureFlagRequest>,
+) -> Response {
+    let Some(store) = state.feature_flag_store() else {
+        return not_found_error();
+    };
+    if !features::is_valid_flag_key(&path.flag_key) {
+        return bad_request_error("invalid flag key");
+    }
+    let variant = match normalize_optional(req.variant, "variant") {
+        Ok(value) => value,
+        Err(response) => return response,
+    };
+    let ctx = operator_context(&path.tenant_id);
+    let record = FeatureFlagRecord {
+        tenant_id: path.tenant_id,
+        flag_key: path.flag_key,
+        enabled: req.enabled,
+        variant,
+        updated_at_ms: now_epoch_ms(),
+    };
+    match store.upsert_feature_flag(&ctx, record).await {
+        Ok(record) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(feature_flag_to_dto(record))),
+        )
+            .into_response(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 删除指定租户的功能开关（恢复默认值，仅运维端口）
+pub async fn delete_tenant_feature_flag(
+    State(state): State<OpsState>,
+    Path(path): Path<TenantFeatureFlagPath>,
+) -> Response {
+    let Some(store) = state.feature_flag_store() else {
+        return not_found_error();
+    };
+    let ctx = operator_context(&path.tenant_id);
+    match store.delete_feature_flag(&ctx, &path.flag_key).await {
+        Ok(true) => (StatusCode::OK, Json(ApiResponse::success(()))).into_response(),
+        Ok(false) => not_found_error(),
+        Err(err) => storage_error(err),
+    }
+}
+
+fn feature_flag_to_dto(record: FeatureFlagRecord) -> FeatureFlagDto {
+    FeatureFlagDto {
+        builtin: features::BUILTIN_FLAGS.contains(&record.flag_key.as_str()),
+        flag_key: record.flag_key,
+        enabled: record.enabled,
+        variant: record.variant,
+        is_default: false,
+        updated_at: Some(record.updated_at_ms),
+    }
+}
+
+fn now_epoch_ms() -> i64 {
+    let now = std::time::SystemTime::now();
+    let duration = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    duration.as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{api_router, auth_headers, build_state, json_request, response_json};
+    use axum::extract::State;
+    use axum::http::{HeaderMap, StatusCode};
+    use tower::ServiceExt;
+
+    /// 测试：租户 API 不能写开关；运维端口关闭租户 graphql 开关后 GraphQL 返回 FEATURE.DISABLED，
+    /// 删除配置后恢复默认开启
+    #[tokio::test]
+    async fn feature_flag_disables_graphql_per_tenant() {
+        let state = build_state();
+        let headers = auth_headers(&state).await;
+        let query = "{ __typename }";
+
+        let response =
+            crate::handlers::list_feature_flags(State(state.clone()), headers.clone()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        let flags = json["data"].as_array().expect("flags");
+        assert_eq!(flags.len(), domain::features::BUILTIN_FLAGS.len());
+        assert!(
+            flags
+                .iter()
+                .all(|flag| flag["enabled"] == true && flag["isDefault"] == true)
+        );
+
+        // 租户管理员不能自行修改开关
+        let response = api_router(state.clone())
+            .oneshot(json_request(
+                &headers,
+                "PUT",
+                "/api/v1/feature-flags/graphql",
+                Some(serde_json::json!({ "enabled": false })),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let ops_router = crate::ops::create_ops_router(
+            crate::ops::OpsState::new(None, serde_json::json!({}))
+                .with_feature_flag_store(state.feature_flag_store.clone()),
+        );
+        let response = ops_router
+            .clone()
+            .oneshot(json_request(
+                &HeaderMap::new(),
+                "PUT",
+                "/ops/tenants/tenant-1/feature-flags/graphql",
+                Some(serde_json::json!({ "enabled": false })),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = crate::handlers::graphql_query(
+            State(state.clone()),
+            headers.clone(),
+            axum::Json(async_graphql::Request::new(query)),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let json = response_json(response).await;
+        assert_eq!(json["error"]["code"], "FEATURE.DISABLED");
+
+        let response = ops_router
+            .clone()
+            .oneshot(json_request(
+                &HeaderMap::new(),
+                "DELETE",
+                "/ops/tenants/tenant-1/feature-flags/graphql",
+                None,
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = crate::handlers::graphql_query(
+            State(state),
+            headers,
+            axum::Json(async_graphql::Request::new(query)),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // 未配置开关存储时运维端口不提供该操作
+        let response =
+            crate::ops::create_ops_router(crate::ops::OpsState::new(None, serde_json::json!({})))
+                .oneshot(json_request(
+                    &HeaderMap::new(),
+                    "DELETE",
+                    "/ops/tenants/tenant-1/feature-flags/graphql",
+                    None,
+                ))
+                .await
+                .expect("response");
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,502 @@
+//! 网关固件升级（OTA）handlers
+//!
+//! 登记固件包（元数据 + SHA-256 校验和 + 存储引用），按批次将固件推送到一组网关，
+//! 网关经 MQTT 回执下载 / 安装进度：
+//! - GET /projects/{id}/firmware/packages - 列出固件包
+//! - POST /projects/{id}/firmware/packages - 登记固件包（同名同版本不可重复）
+//! - GET /projects/{id}/firmware/packages/{pid} - 查询固件包
+//! - GET /projects/{id}/firmware/campaigns - 列出升级批次（按创建时间倒序）
+//! - POST /projects/{id}/firmware/campaigns - 创建升级批次并向目标网关下发升级命令
+//! - GET /projects/{id}/firmware/campaigns/{cid} - 查询升级批次
+//! - GET /projects/{id}/firmware/campaigns/{cid}/rollouts - 查询各网关升级进度
+//!
+//! 网关进度：pending → published/failed → downloading/installing → succeeded/failed（网关回执）。
+//!
+//! 权限要求：
+//! - 查询需要 ASSET.FIRMWARE.READ，登记固件包与创建批次需要 ASSET.FIRMWARE.WRITE
+
+use crate::AppState;
+use crate::middleware::{require_permission, require_project_scope};
+use crate::utils::response::{
+    bad_request_error, control_error, firmware_campaign_to_dto, firmware_package_to_dto,
+    firmware_rollout_to_dto, not_found_error, storage_error,
+};
+use api_contract::{
+    ApiResponse, CreateFirmwareCampaignRequest, CreateFirmwarePackageRequest, FirmwareCampaignDto,
+    FirmwareCampaignQuery, FirmwarePackageDto, FirmwareRolloutDto,
+};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use domain::permissions;
+use ems_control::{FirmwareCampaignRequest, normalize_sha256};
+use ems_storage::FirmwarePackageRecord;
+
+/// 升级批次默认/最大返回条数
+const DEFAULT_CAMPAIGN_LIMIT: i64 = 50;
+const MAX_CAMPAIGN_LIMIT: i64 = 500;
+
+#[derive(serde::Deserialize)]
+pub struct FirmwareProjectPath {
+    project_id: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct FirmwarePackagePath {
+    project_id: String,
+    package_id: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct FirmwareCampaignPath {
+    project_id: String,
+    campaign_id: String,
+}
+
+/// 列出固件包
+pub async fn list_firmware_packages(
+    State(state): State<AppState>,
+    Path(path): Path<FirmwareProjectPath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::ASSET_FIRMWARE_READ) {
+        return response;
+    }
+    match state
+        .firmware_service
+        .list_packages(&ctx, &path.project_id)
+        .await
+    {
+        Ok(items) => {
+            let data: Vec<FirmwarePackageDto> =
+                items.into_iter().map(firmware_package_to_dto).collect();
+            (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 登记固件包
+///
+/// 固件文件需事先上传到对象存储等外部位置，网关按 `storageUrl` 拉取并用 `checksumSha256` 校验。
+pub async fn create_firmware_package(
+    State(state): State<AppState>,
+    Path(path): Path<FirmwareProjectPath>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateFirmwarePackageRequest>,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::ASSET_FIRMWARE_WRITE) {
+        return response;
+    }
+    let name = payload.name.trim();
+    let version = payload.version.trim();
+    let storage_url = payload.storage_url.trim();
+    if name.is_empty() || version.is_empty() {
+        return bad_request_error("name and version are required");
+    }
+    if storage_url.is_empty() {
+        return bad_request_error("storageUrl is required");
+    }
+    let Some(checksum_sha256) = normalize_sha256(&payload.checksum_sha256) else {
+        return bad_request_error("checksumSha256 must be 64 hex characters");
+    };
+    if payload.size_bytes <= 0 {
+        return bad_request_error("sizeBytes must be greater than 0");
+    }
+    let metadata = match payload.metadata {
+        Some(metadata) if !metadata.is_object() => {
+            return bad_request_error("metadata must be an object");
+        }
+        Some(metadata) => Some(metadata.to_string()),
+        None => None,
+    };
+    let record = FirmwarePackageRecord {
+        tenant_id: ctx.tenant_id.clone(),
+        project_id: path.project_id,
+        package_id: uuid::Uuid::new_v4().to_string(),
+        name: name.to_string(),
+        version: version.to_string(),
+        checksum_sha256,
+        size_bytes: payload.size_bytes,
+        storage_url: storage_url.to_string(),
+        metadata,
+        created_by: ctx.user_id.clone(),
+        created_at_ms: now_epoch_ms(),
+    };
+    match state.firmware_service.create_package(&ctx, record).await {
+        Ok(record) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(firmware_package_to_dto(record))),
+        )
+            .into_response(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 查询固件包
+pub async fn get_firmware_package(
+    State(state): State<AppState>,
+    Path(path): Path<FirmwarePackagePath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::ASSET_FIRMWARE_READ) {
+        return response;
+    }
+    match state
+        .firmware_service
+        .find_package(&ctx, &path.project_id, &path.package_id)
+        .await
+    {
+        Ok(Some(record)) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(firmware_package_to_dto(record))),
+        )
+            .into_response(),
+        Ok(None) => not_found_error(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 列出升级批次
+pub async fn list_firmware_campaigns(
+    State(state): State<AppState>,
+    Path(path): Path<FirmwareProjectPath>,
+    Query(query): Query<FirmwareCampaignQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::ASSET_FIRMWARE_READ) {
+        return response;
+    }
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_CAMPAIGN_LIMIT)
+        .clamp(0, MAX_CAMPAIGN_LIMIT);
+    match state
+        .firmware_service
+        .list_campaigns(&ctx, &path.project_id, limit)
+        .await
+    {
+        Ok(items) => {
+            let data: Vec<FirmwareCampaignDto> =
+                items.into_iter().map(firmware_campaign_to_dto).collect();
+            (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 创建升级批次
+///
+/// 未指定 `gatewayIds` 时推送到项目下全部网关；目标网关去重后逐个发布升级命令。
+pub async fn create_firmware_campaign(
+    State(state): State<AppState>,
+    Path(path): Path<FirmwareProjectPath>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateFirmwareCampaignRequest>,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::ASSET_FIRMWARE_WRITE) {
+        return response;
+    }
+    let package = match state
+        .firmware_service
+        .find_package(&ctx, &path.project_id, &payload.package_id)
+        .await
+    {
+        Ok(Some(package)) => package,
+        Ok(None) => return bad_request_error("packageId not found"),
+        Err(err) => return storage_error(err),
+    };
+    let gateways = match state
+        .gateway_store
+        .list_gateways(&ctx, &path.project_id)
+        .await
+    {
+        Ok(gateways) => gateways,
+        Err(err) => return storage_error(err),
+    };
+    let gateway_ids: Vec<String> = match payload.gateway_ids {
+        Some(requested) => {
+            let mut gateway_ids: Vec<String> = Vec::with_capacity(requested.len());
+            for gateway_id in requested {
+                if !gateways
+                    .iter()
+                    .any(|gateway| gateway.gateway_id == gateway_id)
+                {
+                    return bad_request_error(format!("gateway not found: {gateway_id}"));
+                }
+                if !gateway_ids.contains(&gateway_id) {
+                    gateway_ids.push(gateway_id);
+                }
+            }
+            gateway_ids
+        }
+        None => gateways
+            .into_iter()
+            .map(|gateway| gateway.gateway_id)
+            .collect(),
+    };
+    if gateway_ids.is_empty() {
+        return bad_request_error("campaign must target at least one gateway");
+    }
+    let name = payload
+        .name
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| format!("{} {}", package.name, package.version));
+    let request = FirmwareCampaignRequest {
+        project_id: path.project_id,
+        package,
+        name,
+        gateway_ids,
+        created_at_ms: now_epoch_ms(),
+    };
+    match state.firmware_service.start_campaign(&ctx, request).await {
+        Ok(record) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(firmware_campaign_to_dto(record))),
+        )
+            .into_response(),
+        Err(err) => control_error(err),
+    }
+}
+
+/// 查询升级批次
+pub async fn get_firmware_campaign(
+    State(state): State<AppState>,
+    Path(path): Path<FirmwareCampaignPath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::ASSET_FIRMWARE_READ) {
+        return response;
+    }
+    match state
+        .firmware_service
+        .find_campaign(&ctx, &path.project_id, &path.campaign_id)
+        .await
+    {
+        Ok(Some(record)) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(firmware_campaign_to_dto(record))),
+        )
+            .into_response(),
+        Ok(None) => not_found_error(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 查询升级批次下各网关的升级进度
+pub async fn list_firmware_rollouts(
+    State(state): State<AppState>,
+    Path(path): Path<FirmwareCampaignPath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::ASSET_FIRMWARE_READ) {
+        return response;
+    }
+    match state
+        .firmware_service
+        .find_campaign(&ctx, &path.project_id, &path.campaign_id)
+        .await
+    {
+        Ok(Some(_)) => {}
+        Ok(None) => return not_found_error(),
+        Err(err) => return storage_error(err),
+    }
+    match state
+        .firmware_service
+        .list_rollouts(&ctx, &path.project_id, &path.campaign_id)
+        .await
+    {
+        Ok(items) => {
+            let data: Vec<FirmwareRolloutDto> =
+                items.into_iter().map(firmware_rollout_to_dto).collect();
+            (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+fn now_epoch_ms() -> i64 {
+    let now = std::time::SystemTime::now();
+    let duration = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    duration.as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{
+        api_router, auth_headers, build_state, json_request, project_ctx, response_json,
+    };
+    use axum::http::StatusCode;
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    /// 测试：登记固件包并创建升级批次，默认推送到项目下全部网关
+    #[tokio::test]
+    async fn firmware_campaign_targets_project_gateways() {
+        let state = build_state();
+        let headers = auth_headers(&state).await;
+        let ctx = project_ctx();
+        for gateway_id in ["gw-a", "gw-b"] {
+            state
+                .gateway_store
+                .create_gateway(
+                    &ctx,
+                    ems_storage::GatewayRecord {
+                        gateway_id: gateway_id.to_string(),
+                        tenant_id: "tenant-1".to_string(),
+                        project_id: "project-1".to_string(),
+                        name: gateway_id.to_string(),
+                        status: "online".to_string(),
+                        protocol_type: "mqtt".to_string(),
+                        protocol_config: None,
+                    },
+                )
+                .await
+                .expect("gateway");
+        }
+
+        let app = api_router(state.clone());
+        let request = |method: &str, uri: &str, body: Option<Value>| {
+            json_request(
+                &headers,
+                method,
+                &format!("/api/v1/projects/project-1/firmware{uri}"),
+                body,
+            )
+        };
+        let package = |checksum: &str| {
+            serde_json::json!({
+                "name": "gw-fw",
+                "version": "2.1.0",
+                "checksumSha256": checksum,
+                "sizeBytes": 2048,
+                "storageUrl": "https://files.example.com/gw-fw-2.1.0.bin",
+                "metadata": { "model": "gw-100" }
+            })
+        };
+
+        // 校验和格式错误返回 400，同名同版本重复登记返回 409
+        let response = app
+            .clone()
+            .oneshot(request("POST", "/packages", Some(package("1234"))))
+            .await
+            .expect("create");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let checksum = "AB".repeat(32);
+        let response = app
+            .clone()
+            .oneshot(request("POST", "/packages", Some(package(&checksum))))
+            .await
+            .expect("create");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        assert_eq!(json["data"]["checksumSha256"], "ab".repeat(32));
+        assert_eq!(json["data"]["metadata"]["model"], "gw-100");
+        let package_id = json["data"]["packageId"]
+            .as_str()
+            .expect("package id")
+            .to_string();
+        let response = app
+            .clone()
+            .oneshot(request("POST", "/packages", Some(package(&checksum))))
+            .await
+            .expect("create");
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        // 未知网关返回 400；未指定网关时推送到全部网关
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/campaigns",
+                Some(serde_json::json!({ "packageId": package_id, "gatewayIds": ["gw-x"] })),
+            ))
+            .await
+            .expect("campaign");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/campaigns",
+                Some(serde_json::json!({ "packageId": package_id })),
+            ))
+            .await
+            .expect("campaign");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        assert_eq!(json["data"]["name"], "gw-fw 2.1.0");
+        assert_eq!(json["data"]["status"], "in_progress");
+        let campaign_id = json["data"]["campaignId"]
+            .as_str()
+            .expect("campaign id")
+            .to_string();
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                "GET",
+                &format!("/campaigns/{campaign_id}/rollouts"),
+                None,
+            ))
+            .await
+            .expect("rollouts");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        let rollouts = json["data"].as_array().expect("rollouts");
+        assert_eq!(rollouts.len(), 2);
+        assert_eq!(rollouts[0]["gatewayId"], "gw-a");
+        assert!(
+            rollouts
+                .iter()
+                .all(|rollout| rollout["status"] == "published")
+        );
+
+        let response = app
+            .clone()
+            .oneshot(request("GET", "/campaigns/missing/rollouts", None))
+            .await
+            .expect("rollouts");
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let response = app
+            .oneshot(request("GET", "/campaigns", None))
+            .await
+            .expect("campaigns");
+        let json = response_json(response).await;
+        assert_eq!(json["data"].as_array().map(Vec::len), Some(1));
+    }
+}
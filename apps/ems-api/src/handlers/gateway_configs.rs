@@ -0,0 +1,202 @@
+//! 网关配置下发 handlers
+//!
+//! 将网关下的设备、点位与映射打包为配置文档，按版本下发给边缘网关：
+//! - POST /projects/{id}/gateways/{gid}/config/push - 生成并下发新版本配置
+//! - GET /projects/{id}/gateways/{gid}/config/pushes - 列出下发记录（按版本倒序）
+//!
+//! 下发状态：pending → published/failed → applied/failed（网关回执）。
+//!
+//! 权限要求：
+//! - 下发需要 ASSET.GATEWAY.WRITE，查询需要 ASSET.GATEWAY.READ
+
+use crate::AppState;
+use crate::middleware::{require_permission, require_project_scope};
+use crate::utils::response::{
+    control_error, gateway_config_push_to_dto, not_found_error, storage_error,
+};
+use api_contract::{ApiResponse, GatewayConfigPushDto, GatewayConfigPushQuery};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use domain::{TenantContext, permissions};
+use ems_control::GatewayConfigRequest;
+use ems_storage::{DeviceRecord, GatewayRecord, PointMappingRecord, PointRecord, StorageError};
+use serde_json::{Value, json};
+
+#[derive(serde::Deserialize)]
+pub struct GatewayConfigPath {
+    project_id: String,
+    gateway_id: String,
+}
+
+/// 下发网关配置
+///
+/// 以当前设备/点位/映射数据生成配置文档，写入新版本并发布到配置 topic。
+pub async fn push_gateway_config(
+    State(state): State<AppState>,
+    Path(path): Path<GatewayConfigPath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::ASSET_GATEWAY_WRITE) {
+        return response;
+    }
+    let gateway = match state
+        .gateway_store
+        .find_gateway(&ctx, &path.project_id, &path.gateway_id)
+        .await
+    {
+        Ok(Some(gateway)) => gateway,
+        Ok(None) => return not_found_error(),
+        Err(err) => return storage_error(err),
+    };
+    let document = match load_gateway_config_document(&state, &ctx, gateway).await {
+        Ok(document) => document,
+        Err(err) => return storage_error(err),
+    };
+    let request = GatewayConfigRequest {
+        project_id: path.project_id,
+        gateway_id: path.gateway_id,
+        document,
+        pushed_at_ms: now_epoch_ms(),
+    };
+    match state
+        .gateway_config_service
+        .push_config(&ctx, request)
+        .await
+    {
+        Ok(record) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(gateway_config_push_to_dto(record))),
+        )
+            .into_response(),
+        Err(err) => control_error(err),
+    }
+}
+
+/// 列出网关配置下发记录
+pub async fn list_gateway_config_pushes(
+    State(state): State<AppState>,
+    Path(path): Path<GatewayConfigPath>,
+    Query(query): Query<GatewayConfigPushQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::ASSET_GATEWAY_READ) {
+        return response;
+    }
+    let limit = query.limit.unwrap_or(20).max(0);
+    match state
+        .gateway_config_service
+        .list_config_pushes(&ctx, &path.project_id, &path.gateway_id, limit)
+        .await
+    {
+        Ok(items) => {
+            let data: Vec<GatewayConfigPushDto> =
+                items.into_iter().map(gateway_config_push_to_dto).collect();
+            (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+        }
+        Err(err) => control_error(err),
+    }
+}
+
+async fn load_gateway_config_document(
+    state: &AppState,
+    ctx: &TenantContext,
+    gateway: GatewayRecord,
+) -> Result<Value, StorageError> {
+    let project_id = gateway.project_id.clone();
+    let devices = state.device_store.list_devices(ctx, &project_id).await?;
+    let points = state.point_store.list_points(ctx, &project_id).await?;
+    let mappings = state
+        .point_mapping_store
+        .list_point_mappings(ctx, &project_id)
+        .await?;
+    Ok(build_gateway_config_document(
+        gateway, devices, points, mappings,
+    ))
+}
+
+/// 组装网关配置文档：仅包含挂在该网关下的设备及其点位、映射。
+///
+/// JSON 字符串字段（协议配置、地址配置、协议细节）尽量解析为对象，解析失败时保留原文。
+pub(crate) fn build_gateway_config_document(
+    gateway: GatewayRecord,
+    devices: Vec<DeviceRecord>,
+    points: Vec<PointRecord>,
+    mappings: Vec<PointMappingRecord>,
+) -> Value {
+    let devices: Vec<Value> = devices
+        .into_iter()
+        .filter(|device| device.gateway_id == gateway.gateway_id)
+        .map(|device| {
+            let device_points: Vec<Value> = points
+                .iter()
+                .filter(|point| point.device_id == device.device_id)
+                .map(|point| {
+                    let point_mappings: Vec<Value> = mappings
+                        .iter()
+                        .filter(|mapping| mapping.point_id == point.point_id)
+                        .map(|mapping| {
+                            json!({
+                                "sourceId": mapping.source_id,
+                                "sourceType": mapping.source_type,
+                                "address": mapping.address,
+                                "scale": mapping.scale,
+                                "offset": mapping.offset,
+                                "protocolDetail": parse_json_text(mapping.protocol_detail.as_deref()),
+                            })
+                        })
+                        .collect();
+                    json!({
+                        "pointId": point.point_id,
+                        "key": point.key,
+                        "dataType": point.data_type,
+                        "unit": point.unit,
+                        "mappings": point_mappings,
+                    })
+                })
+                .collect();
+            json!({
+                "deviceId": device.device_id,
+                "name": device.name,
+                "model": device.model,
+                "addressConfig": parse_json_text(device.address_config.as_deref()),
+                "points": device_points,
+            })
+        })
+        .collect();
+    json!({
+        "gatewayId": gateway.gateway_id,
+        "name": gateway.name,
+        "protocolType": gateway.protocol_type,
+        "protocolConfig": parse_json_text(gateway.protocol_config.as_deref()),
+        "devices": devices,
+    })
+}
+
+fn parse_json_text(value: Option<&str>) -> Value {
+    match value {
+        Some(text) => {
+            serde_json::from_str(text).unwrap_or_else(|_| Value::String(text.to_string()))
+        }
+        None => Value::Null,
+    }
+}
+
+fn now_epoch_ms() -> i64 {
+    let now = std::time::SystemTime::now();
+    let duration = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    duration.as_millis() as i64
+}
@@ -6,11 +6,17 @@
 //! - GET /projects/{id}/gateways/{gid} - 获取网关详情
 //! - PUT /projects/{id}/gateways/{gid} - 更新网关
 //! - DELETE /projects/{id}/gateways/{gid} - 删除网关
+//! - POST /projects/{id}/gateways/{gid}/token - 签发/轮换网关回调令牌（返回令牌明文，仅此一次）
+//! - DELETE /projects/{id}/gateways/{gid}/token - 吊销网关回调令牌
+//! - GET/PUT/DELETE /projects/{id}/gateways/{gid}/command-format - 命令载荷格式（查询 / 设置 / 恢复默认信封）
 //!
 //! 权限要求：
 //! - 所有接口需要 Bearer token 认证
 //! - 需验证项目归属当前租户（require_project_scope）
 //!
+//! 配置校验：
+//! - `protocolType` 须为支持的协议类型，`protocolConfig` 按协议类型做字段级校验（ems_protocol）
+//!
 //! 数据隔离：
 //! - 所有操作都通过 TenantContext 进行多租户隔离
 //! - 存储层会根据 tenant_id 和 project_id 过滤数据
@@ -20,18 +26,27 @@
 //! - tenant_id 字段在 DTO 中被排除，确保租户信息不泄露
 
 use crate::AppState;
-use crate::middleware::{require_permission, require_project_scope};
-use crate::utils::response::gateway_to_dto;
+use crate::middleware::{gateway_token_hash, require_permission, require_project_scope};
 use crate::utils::response::{bad_request_error, not_found_error, storage_error};
-use crate::utils::{normalize_optional, normalize_required};
-use api_contract::{ApiResponse, CreateGatewayRequest, GatewayDto, UpdateGatewayRequest};
+use crate::utils::response::{
+    collection_etag, etag_matches, gateway_to_dto, last_seen_fingerprint, list_success,
+    not_modified, with_etag,
+};
+use crate::utils::{check_protocol_fields, normalize_optional, normalize_required, parse_fields};
+use api_contract::{
+    ApiResponse, CreateGatewayRequest, FieldsQuery, GatewayCommandFormatDto, GatewayDto,
+    GatewayTokenDto, SetGatewayCommandFormatRequest, UpdateGatewayRequest,
+};
 use axum::{
     Json,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
     response::{IntoResponse, Response},
 };
-use domain::permissions;
+use domain::{gateway_token, permissions};
+use ems_control::CommandPayloadFormat;
+use ems_events::{DomainEvent, event_types};
+use ems_protocol::{validate_gateway_config, validate_protocol_type};
 use uuid::Uuid;
 
 /// 项目路径参数
@@ -102,6 +117,7 @@ pub struct GatewayPath {
 pub async fn list_gateways(
     State(state): State<AppState>,
     Path(path): Path<ProjectPath>,
+    Query(fields): Query<FieldsQuery>,
     headers: HeaderMap,
 ) -> Response {
     // 步骤 1: 验证项目归属，获取增强的租户上下文
@@ -115,6 +131,18 @@ pub async fn list_gateways(
     if let Err(response) = require_permission(&ctx, permissions::ASSET_GATEWAY_READ) {
         return response;
     }
+    let fields = match parse_fields(fields.fields) {
+        Ok(fields) => fields,
+        Err(response) => return response,
+    };
+    let version = match state
+        .gateway_store
+        .gateways_version(&ctx, &path.project_id)
+        .await
+    {
+        Ok(version) => version,
+        Err(err) => return storage_error(err),
+    };
 
     // 步骤 2: 查询网关列表
     // - 存储层会根据 ctx.tenant_id 和 project_id 自动过滤数据
@@ -132,6 +160,11 @@ pub async fn list_gateways(
                 .list_gateways_last_seen_at_ms(&ctx, &path.project_id, &gateway_ids)
                 .await
                 .unwrap_or_default();
+            // 在线状态来自实时存储，变化同样使 ETag 失效
+            let etag = collection_etag(version, fields.as_deref(), &last_seen_fingerprint(&online));
+            if etag_matches(&headers, &etag) {
+                return not_modified(&etag);
+            }
             let data: Vec<GatewayDto> = items
                 .into_iter()
                 .map(|record| {
@@ -143,8 +176,8 @@ pub async fn list_gateways(
                     dto
                 })
                 .collect();
-            // 步骤 4: 返回成功响应
-            (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+            // 步骤 4: 返回成功响应（按 fields 裁剪字段，附带 ETag）
+            with_etag(list_success(data, fields.as_deref()), &etag)
         }
         Err(err) => storage_error(err),
     }
@@ -190,7 +223,7 @@ pub async fn list_gateways(
 /// ```
 ///
 /// # 错误响应
-/// - `400 BAD REQUEST`：name 字段为空或仅包含空格
+/// - `400 BAD REQUEST`：name 字段为空或仅包含空格；协议类型不支持或协议配置校验失败（`data` 为字段级错误）
 /// - `401 UNAUTHORIZED`：Bearer token 无效或缺失
 /// - `403 FORBIDDEN`：项目不属于当前租户
 /// - `500 INTERNAL SERVER ERROR`：存储层创建失败
@@ -218,28 +251,46 @@ pub async fn create_gateway(
     // 步骤 3: 处理可选字段 status，默认值为 "offline"
     let status = req.status.unwrap_or_else(|| "offline".to_string());
 
+    // 步骤 3.1: 校验协议类型（默认 mqtt）与协议配置
+    let protocol_type = req.protocol_type.unwrap_or_else(|| "mqtt".to_string());
+    if let Err(response) = check_gateway_protocol(&protocol_type, req.protocol_config.as_deref()) {
+        return response;
+    }
+
     // 步骤 4: 构建网关记录
     // - gateway_id: 自动生成 UUID v4
     // - tenant_id: 从上下文获取（多租户隔离）
     // - project_id: 从路径参数获取
-    // - protocol_type: 默认 mqtt
     let record = ems_storage::GatewayRecord {
         gateway_id: Uuid::new_v4().to_string(),
         tenant_id: ctx.tenant_id.clone(),
         project_id: path.project_id,
         name,
         status,
-        protocol_type: req.protocol_type.unwrap_or_else(|| "mqtt".to_string()),
+        protocol_type,
         protocol_config: req.protocol_config,
     };
 
-    // 步骤 5: 创建网关并返回
+    // 步骤 5: 创建网关，发布 gateway.created 事件并返回
     match state.gateway_store.create_gateway(&ctx, record).await {
-        Ok(item) => (
-            StatusCode::OK,
-            Json(ApiResponse::success(gateway_to_dto(item))),
-        )
-            .into_response(),
+        Ok(item) => {
+            state.event_bus.publish(DomainEvent::new(
+                event_types::GATEWAY_CREATED,
+                item.tenant_id.clone(),
+                item.project_id.clone(),
+                serde_json::json!({
+                    "gatewayId": item.gateway_id,
+                    "name": item.name,
+                    "protocolType": item.protocol_type,
+                    "createdBy": ctx.user_id,
+                }),
+            ));
+            (
+                StatusCode::OK,
+                Json(ApiResponse::success(gateway_to_dto(item))),
+            )
+                .into_response()
+        }
         Err(err) => storage_error(err),
     }
 }
@@ -358,7 +409,7 @@ pub async fn get_gateway(
 /// ```
 ///
 /// # 错误响应
-/// - `400 BAD REQUEST`：未提供任何更新字段，或字段为空
+/// - `400 BAD REQUEST`：未提供任何更新字段，或字段为空；协议类型 / 协议配置校验失败
 /// - `401 UNAUTHORIZED`：Bearer token 无效或缺失
 /// - `403 FORBIDDEN`：项目不属于当前租户
 /// - `404 NOT FOUND`：网关不存在或不属于当前租户/项目
@@ -397,6 +448,33 @@ pub async fn update_gateway(
         return bad_request_error("empty update");
     }
 
+    // 步骤 4.1: 修改协议类型或协议配置时，按更新后的组合校验（未提供的一方取当前值）
+    if protocol_type.is_some() || protocol_config.is_some() {
+        let current = if protocol_type.is_some() && protocol_config.is_some() {
+            None
+        } else {
+            match state
+                .gateway_store
+                .find_gateway(&ctx, &path.project_id, &path.gateway_id)
+                .await
+            {
+                Ok(Some(item)) => Some(item),
+                Ok(None) => return not_found_error(),
+                Err(err) => return storage_error(err),
+            }
+        };
+        let effective_type = protocol_type
+            .as_deref()
+            .or(current.as_ref().map(|item| item.protocol_type.as_str()))
+            .unwrap_or("mqtt");
+        let effective_config = protocol_config.as_deref().or(current
+            .as_ref()
+            .and_then(|item| item.protocol_config.as_deref()));
+        if let Err(response) = check_gateway_protocol(effective_type, effective_config) {
+            return response;
+        }
+    }
+
     // 步骤 5: 构建更新对象
     let update = ems_storage::GatewayUpdate {
         name,
@@ -489,3 +567,628 @@ pub async fn delete_gateway(
         Err(err) => storage_error(err),
     }
 }
+
+/// 签发网关回调令牌
+///
+/// 每个网关只保留一个有效令牌，重复签发即轮换（旧令牌立即失效）。
+pub async fn issue_gateway_token(
+    State(state): State<AppState>,
+    Path(path): Path<GatewayPath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::ASSET_GATEWAY_WRITE) {
+        return response;
+    }
+    let token = format!(
+        "{}{}{}",
+        gateway_token::GATEWAY_TOKEN_PREFIX,
+        Uuid::new_v4().simple(),
+        Uuid::new_v4().simple()
+    );
+    match state
+        .gateway_store
+        .set_gateway_token_hash(
+            &ctx,
+            &path.project_id,
+            &path.gateway_id,
+            Some(&gateway_token_hash(&token)),
+        )
+        .await
+    {
+        Ok(true) => {
+            let dto = GatewayTokenDto {
+                gateway_id: path.gateway_id,
+                project_id: path.project_id,
+                token,
+            };
+            (StatusCode::OK, Json(ApiResponse::success(dto))).into_response()
+        }
+        Ok(false) => not_found_error(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 吊销网关回调令牌
+pub async fn revoke_gateway_token(
+    State(state): State<AppState>,
+    Path(path): Path<GatewayPath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::ASSET_GATEWAY_WRITE) {
+        return response;
+    }
+    match state
+        .gateway_store
+        .set_gateway_token_hash(&ctx, &path.project_id, &path.gateway_id, None)
+        .await
+    {
+        Ok(true) => (StatusCode::OK, Json(ApiResponse::success(()))).into_response(),
+        Ok(false) => not_found_error(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 查询网关命令载荷格式（未设置时为默认 `envelope`）
+pub async fn get_gateway_command_format(
+    State(state): State<AppState>,
+    Path(path): Path<GatewayPath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::ASSET_GATEWAY_READ) {
+        return response;
+    }
+    match state
+        .gateway_store
+        .find_gateway(&ctx, &path.project_id, &path.gateway_id)
+        .await
+    {
+        Ok(Some(_)) => {}
+        Ok(None) => return not_found_error(),
+        Err(err) => return storage_error(err),
+    }
+    let format = match state
+        .gateway_store
+        .find_gateway_command_format(&ctx, &path.project_id, &path.gateway_id)
+        .await
+    {
+        Ok(Some(json)) => CommandPayloadFormat::from_json(&json).unwrap_or_default(),
+        Ok(None) => CommandPayloadFormat::default(),
+        Err(err) => return storage_error(err),
+    };
+    let dto = command_format_to_dto(path.gateway_id, &format);
+    (StatusCode::OK, Json(ApiResponse::success(dto))).into_response()
+}
+
+/// 设置网关命令载荷格式
+///
+/// 保存前校验模板（未知格式、hex 模板含非十六进制文本等返回 400），下发时按命令 target 所属网关生效。
+pub async fn set_gateway_command_format(
+    State(state): State<AppState>,
+    Path(path): Path<GatewayPath>,
+    headers: HeaderMap,
+    Json(req): Json<SetGatewayCommandFormatRequest>,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::ASSET_GATEWAY_WRITE) {
+        return response;
+    }
+    let format = match CommandPayloadFormat::parse(
+        req.format.trim(),
+        req.template.as_deref(),
+        req.checksum.as_deref(),
+    ) {
+        Ok(format) => format,
+        Err(err) => return bad_request_error(err.to_string()),
+    };
+    match state
+        .gateway_store
+        .set_gateway_command_format(
+            &ctx,
+            &path.project_id,
+            &path.gateway_id,
+            Some(&format.to_json()),
+        )
+        .await
+    {
+        Ok(true) => {
+            let dto = command_format_to_dto(path.gateway_id, &format);
+            (StatusCode::OK, Json(ApiResponse::success(dto))).into_response()
+        }
+        Ok(false) => not_found_error(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 恢复网关默认命令载荷格式（JSON 信封）
+pub async fn clear_gateway_command_format(
+    State(state): State<AppState>,
+    Path(path): Path<GatewayPath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::ASSET_GATEWAY_WRITE) {
+        return response;
+    }
+    match state
+        .gateway_store
+        .set_gateway_command_format(&ctx, &path.project_id, &path.gateway_id, None)
+        .await
+    {
+        Ok(true) => (StatusCode::OK, Json(ApiResponse::success(()))).into_response(),
+        Ok(false) => not_found_error(),
+        Err(err) => storage_error(err),
+    }
+}
+
+fn command_format_to_dto(
+    gateway_id: String,
+    format: &CommandPayloadFormat,
+) -> GatewayCommandFormatDto {
+    GatewayCommandFormatDto {
+        gateway_id,
+        format: format.name().to_string(),
+        template: format.template().map(str::to_string),
+        checksum: format
+            .checksum()
+            .map(|checksum| checksum.as_str().to_string()),
+    }
+}
+
+/// 校验协议类型与协议配置（字段级错误返回 400）
+fn check_gateway_protocol(
+    protocol_type: &str,
+    protocol_config: Option<&str>,
+) -> Result<(), Response> {
+    if let Err(error) = validate_protocol_type(protocol_type) {
+        return check_protocol_fields("protocolType", vec![error]);
+    }
+    match protocol_config {
+        Some(config) => check_protocol_fields(
+            "protocolConfig",
+            validate_gateway_config(protocol_type, config),
+        ),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{
+        api_router, auth_headers, build_state, json_request, project_ctx, response_json,
+    };
+    use axum::http::{HeaderValue, StatusCode, header};
+    use http_body_util::BodyExt;
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    /// 测试：资产列表返回弱 ETag，未变更时 304，变更 / 字段选择 / 在线状态变化后重新返回 200
+    #[tokio::test]
+    async fn asset_list_etag_returns_not_modified() {
+        let state = build_state();
+        let headers = auth_headers(&state).await;
+        let app = api_router(state.clone());
+        let request = |method: &str, uri: &str, etag: Option<&str>, body: Option<Value>| {
+            let mut request = json_request(
+                &headers,
+                method,
+                &format!("/api/v1/projects/project-1{uri}"),
+                body,
+            );
+            if let Some(etag) = etag {
+                request.headers_mut().insert(
+                    header::IF_NONE_MATCH,
+                    HeaderValue::from_str(etag).expect("etag"),
+                );
+            }
+            request
+        };
+        let etag_of = |response: &axum::response::Response| {
+            response.headers()[header::ETAG]
+                .to_str()
+                .expect("etag")
+                .to_string()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(request("GET", "/gateways", None, None))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = etag_of(&response);
+        assert!(etag.starts_with("W/\""));
+
+        // 未变更：304 且无响应体
+        let response = app
+            .clone()
+            .oneshot(request("GET", "/gateways", Some(&etag), None))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(etag_of(&response), etag);
+        let body = response
+            .into_body()
+            .collect()
+            .await
+            .expect("body")
+            .to_bytes();
+        assert!(body.is_empty());
+
+        // 新建网关后旧 ETag 失效
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/gateways",
+                None,
+                Some(serde_json::json!({ "name": "gw", "protocolType": "mqtt" })),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let gateway_id = response_json(response).await["data"]["gatewayId"]
+            .as_str()
+            .expect("gateway id")
+            .to_string();
+        let response = app
+            .clone()
+            .oneshot(request("GET", "/gateways", Some(&etag), None))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = etag_of(&response);
+
+        // 字段选择得到不同的表示
+        let response = app
+            .clone()
+            .oneshot(request(
+                "GET",
+                "/gateways?fields=gatewayId",
+                Some(&etag),
+                None,
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_ne!(etag_of(&response), etag);
+
+        // 在线状态变化同样使 ETag 失效
+        let ctx = project_ctx();
+        state
+            .online_store
+            .touch_gateway(&ctx, "project-1", &gateway_id, 1_700_000_000_000)
+            .await
+            .expect("touch");
+        let response = app
+            .clone()
+            .oneshot(request("GET", "/gateways", Some(&etag), None))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        assert_eq!(json["data"][0]["online"], true);
+
+        // 其它资产列表同样支持
+        let response = app
+            .clone()
+            .oneshot(request("GET", "/points", None, None))
+            .await
+            .expect("response");
+        let etag = etag_of(&response);
+        let response = app
+            .oneshot(request("GET", "/points", Some(&etag), None))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    }
+
+    /// 测试：网关命令载荷格式的设置 / 查询 / 恢复默认，设备命令按所属网关格式编码
+    #[tokio::test]
+    async fn gateway_command_format_applies_to_device_commands() {
+        let state = build_state();
+        let headers = auth_headers(&state).await;
+        let ctx = project_ctx();
+        state
+            .gateway_store
+            .create_gateway(
+                &ctx,
+                ems_storage::GatewayRecord {
+                    gateway_id: "gateway-1".to_string(),
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    name: "legacy".to_string(),
+                    status: "online".to_string(),
+                    protocol_type: "mqtt".to_string(),
+                    protocol_config: None,
+                },
+            )
+            .await
+            .expect("gateway");
+        state
+            .device_store
+            .create_device(
+                &ctx,
+                ems_storage::DeviceRecord {
+                    device_id: "device-1".to_string(),
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    gateway_id: "gateway-1".to_string(),
+                    name: "PLC".to_string(),
+                    model: None,
+                    room_id: None,
+                    address_config: None,
+                    offline_after_seconds: None,
+                },
+            )
+            .await
+            .expect("device");
+
+        let app = api_router(state.clone());
+        let request = |method: &str, body: Option<Value>| {
+            json_request(
+                &headers,
+                method,
+                "/api/v1/projects/project-1/gateways/gateway-1/command-format",
+                body,
+            )
+        };
+
+        // 未设置时为默认信封
+        let response = app
+            .clone()
+            .oneshot(request("GET", None))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response_json(response).await["data"]["format"], "envelope");
+
+        // hex 模板含非十六进制文本时拒绝
+        let response = app
+            .clone()
+            .oneshot(request(
+                "PUT",
+                Some(serde_json::json!({ "format": "hex", "template": "01 GG" })),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                "PUT",
+                Some(serde_json::json!({
+                    "format": "hex",
+                    "template": "01 06 {payload.register:u16} {payload.value:u16}",
+                    "checksum": "crc16_modbus",
+                })),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app
+            .clone()
+            .oneshot(request("GET", None))
+            .await
+            .expect("response");
+        let json = response_json(response).await;
+        assert_eq!(json["data"]["format"], "hex");
+        assert_eq!(json["data"]["checksum"], "crc16_modbus");
+
+        let formats = ems_control::GatewayPayloadFormats::new(
+            state.gateway_store.clone(),
+            state.device_store.clone(),
+        );
+        let format = formats
+            .format_for_target("tenant-1", "project-1", "device-1")
+            .await
+            .expect("format");
+        let frame = format
+            .encode(&ems_control::CommandDispatch {
+                command_id: "cmd-1".to_string(),
+                tenant_id: "tenant-1".to_string(),
+                project_id: "project-1".to_string(),
+                target: "device-1".to_string(),
+                payload: r#"{"register":1,"value":3}"#.to_string(),
+                issued_at_ms: 1_000,
+            })
+            .expect("frame");
+        assert_eq!(frame, vec![0x01, 0x06, 0x00, 0x01, 0x00, 0x03, 0x98, 0x0B]);
+        // 未知目标使用默认信封
+        let format = formats
+            .format_for_target("tenant-1", "project-1", "unknown")
+            .await
+            .expect("format");
+        assert_eq!(format, ems_control::CommandPayloadFormat::Envelope);
+
+        let response = app
+            .clone()
+            .oneshot(request("DELETE", None))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let format = formats
+            .format_for_target("tenant-1", "project-1", "gateway-1")
+            .await
+            .expect("format");
+        assert_eq!(format, ems_control::CommandPayloadFormat::Envelope);
+    }
+
+    /// 测试：按协议类型校验网关 / 设备 / 映射配置，返回字段级错误
+    #[tokio::test]
+    async fn protocol_configs_validated_on_save() {
+        let state = build_state();
+        let headers = auth_headers(&state).await;
+        let ctx = project_ctx();
+        let app = api_router(state.clone());
+        let request = |uri: &str, body: Value| {
+            json_request(
+                &headers,
+                "POST",
+                &format!("/api/v1/projects/project-1{uri}"),
+                Some(body),
+            )
+        };
+
+        // 未知协议类型
+        let response = app
+            .clone()
+            .oneshot(request(
+                "/gateways",
+                serde_json::json!({ "name": "gw", "protocolType": "modbus-tcp" }),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let json = response_json(response).await;
+        assert_eq!(json["data"][0]["field"], "protocolType");
+
+        // 字段拼写错误与越界值逐项返回
+        let response = app
+            .clone()
+            .oneshot(request(
+                "/gateways",
+                serde_json::json!({
+                    "name": "gw",
+                    "protocolType": "modbus_tcp",
+                    "protocolConfig": r#"{"hots": "10.0.0.1", "port": 70000}"#,
+                }),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let json = response_json(response).await;
+        let fields: Vec<&str> = json["data"]
+            .as_array()
+            .expect("errors")
+            .iter()
+            .filter_map(|item| item["field"].as_str())
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                "protocolConfig.host",
+                "protocolConfig.port",
+                "protocolConfig.hots"
+            ]
+        );
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                "/gateways",
+                serde_json::json!({
+                    "name": "gw",
+                    "protocolType": "modbus_tcp",
+                    "protocolConfig": r#"{"host": "10.0.0.1", "port": 502}"#,
+                }),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        let gateway_id = json["data"]["gatewayId"].as_str().expect("gateway id");
+
+        // 设备地址按网关协议校验
+        let response = app
+            .clone()
+            .oneshot(request(
+                "/devices",
+                serde_json::json!({
+                    "gatewayId": gateway_id,
+                    "name": "meter",
+                    "addressConfig": r#"{"slave_address": 1}"#,
+                }),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let json = response_json(response).await;
+        assert_eq!(json["data"][0]["field"], "addressConfig.slave_id");
+        assert_eq!(json["data"][1]["field"], "addressConfig.slave_address");
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                "/devices",
+                serde_json::json!({
+                    "gatewayId": gateway_id,
+                    "name": "meter",
+                    "addressConfig": r#"{"slave_id": 1}"#,
+                }),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        let device_id = json["data"]["deviceId"].as_str().expect("device id");
+
+        // 映射协议细节按点位所属设备的网关协议校验
+        state
+            .point_store
+            .create_point(
+                &ctx,
+                ems_storage::PointRecord {
+                    point_id: "point-modbus".to_string(),
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    device_id: device_id.to_string(),
+                    key: "power".to_string(),
+                    data_type: "float".to_string(),
+                    unit: None,
+                    tags: Vec::new(),
+                },
+            )
+            .await
+            .expect("point");
+        let response = app
+            .clone()
+            .oneshot(request(
+                "/point-mappings",
+                serde_json::json!({
+                    "pointId": "point-modbus",
+                    "sourceType": "modbus",
+                    "address": "1:100",
+                    "protocolDetail": r#"{"register_address": 100, "data_type": "float"}"#,
+                }),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let json = response_json(response).await;
+        assert_eq!(json["data"][0]["field"], "protocolDetail.data_type");
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                "/point-mappings",
+                serde_json::json!({
+                    "pointId": "point-modbus",
+                    "sourceType": "modbus",
+                    "address": "1:100",
+                    "protocolDetail": r#"{"register_address": 100, "register_count": 2, "data_type": "float32"}"#,
+                }),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
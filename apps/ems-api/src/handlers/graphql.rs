@@ -0,0 +1,36 @@
+//! GraphQL handlers
+//!
+//! - POST /graphql - 执行 GraphQL 查询（资产层级、最新值、历史序列）
+//!
+//! 请求体为标准 GraphQL JSON（query / variables / operationName），
+//! 响应为标准 GraphQL 响应（data / errors），不使用 ApiResponse 封装。
+//! 认证失败直接返回 401；字段级权限不足体现在 errors 中（code = AUTH.FORBIDDEN）。
+//! 租户关闭 `graphql` 功能开关时返回 403（code = FEATURE.DISABLED）。
+
+use crate::AppState;
+use crate::graphql::schema;
+use crate::middleware::{require_feature, require_tenant_context};
+use axum::{
+    Json,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use domain::features;
+
+/// 执行 GraphQL 查询
+pub async fn graphql_query(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<async_graphql::Request>,
+) -> Response {
+    let ctx = match require_tenant_context(&state, &headers) {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_feature(&state, &ctx, features::GRAPHQL).await {
+        return response;
+    }
+    let response = schema().execute(request.data(state).data(ctx)).await;
+    (StatusCode::OK, Json(response)).into_response()
+}
@@ -0,0 +1,389 @@
+//! InfluxDB 行协议写入 handlers
+//!
+//! 兼容 InfluxDB 写入接口，现场已有的 Telegraf 只需把输出地址指向本服务即可上报数据：
+//! - POST /projects/{id}/influx/write - 1.x 写入接口（Telegraf `outputs.influxdb`）
+//! - POST /projects/{id}/influx/api/v2/write - 2.x 写入接口（Telegraf `outputs.influxdb_v2`）
+//!
+//! 字段按 `point_id` 标签（或 `{measurement}.{field}`）映射为点位，经与 HTTP 写入相同的流水线处理；
+//! 未登记的点位与被流水线拒绝的值跳过，接受 / 跳过数量在 `x-ems-accepted` / `x-ems-rejected` 响应头返回。
+//! 请求体可为 gzip 压缩（`Content-Encoding: gzip`）。
+//!
+//! 认证：`Authorization: Bearer` 或 `Authorization: Token`（2.x 风格）
+//! - 登录令牌：需要 DATA.WRITE，可写入项目内全部点位
+//! - 网关令牌（`ems_gw_` 前缀）：只能写入该网关下设备的点位
+
+use crate::AppState;
+use crate::handlers::write_values_to_pipeline;
+use crate::middleware::{
+    bearer_token, require_gateway_token, require_permission, require_project_scope,
+};
+use crate::utils::response::{bad_request_error, storage_error};
+use api_contract::{InfluxWriteQuery, RejectedPointValueDto};
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use domain::{PointValue, gateway_token, permissions};
+use ems_normalize::{TimestampPrecision, parse_line_protocol};
+use std::collections::HashSet;
+use tracing::warn;
+
+/// 单次写入允许的最大值数量（与点位值批量写入一致）
+const MAX_WRITE_VALUES: usize = 5000;
+
+#[derive(serde::Deserialize)]
+pub struct InfluxWritePath {
+    project_id: String,
+}
+
+/// 写入 InfluxDB 行协议数据
+pub async fn write_influx_line_protocol(
+    State(state): State<AppState>,
+    Path(path): Path<InfluxWritePath>,
+    Query(query): Query<InfluxWriteQuery>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let headers = influx_auth_headers(headers);
+    // 网关令牌只允许写入该网关下设备的点位
+    let (ctx, gateway_id) = if bearer_token(&headers).is_some_and(gateway_token::is_gateway_token) {
+        match require_gateway_token(&state, &headers, &path.project_id).await {
+            Ok((ctx, gateway)) => (ctx, Some(gateway.gateway_id)),
+            Err(response) => return response,
+        }
+    } else {
+        let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+            Ok(ctx) => ctx,
+            Err(response) => return response,
+        };
+        if let Err(response) = require_permission(&ctx, permissions::DATA_WRITE) {
+            return response;
+        }
+        (ctx, None)
+    };
+
+    let precision = match query.precision.as_deref() {
+        None => TimestampPrecision::default(),
+        Some(value) => match TimestampPrecision::parse(value) {
+            Some(precision) => precision,
+            None => return bad_request_error(format!("invalid precision: {value}")),
+        },
+    };
+    let Ok(text) = std::str::from_utf8(&body) else {
+        return bad_request_error("body must be UTF-8 line protocol");
+    };
+    let lines = match parse_line_protocol(text) {
+        Ok(lines) if lines.is_empty() => return bad_request_error("no data"),
+        Ok(lines) => lines,
+        Err(err) => return bad_request_error(err.to_string()),
+    };
+
+    let known = match writable_points(&state, &ctx, &path.project_id, gateway_id.as_deref()).await {
+        Ok(known) => known,
+        Err(response) => return response,
+    };
+    let now_ms = now_epoch_ms();
+    let mut values = Vec::new();
+    let mut skipped = Vec::new();
+    for line in lines {
+        let ts_ms = match line.timestamp {
+            None => now_ms,
+            Some(timestamp) => match precision.to_ms(timestamp) {
+                Some(ts_ms) => ts_ms,
+                None => return bad_request_error(format!("timestamp out of range: {timestamp}")),
+            },
+        };
+        let quality = line.quality();
+        for (point_id, value) in line.point_samples() {
+            if !known.contains(&point_id) {
+                skipped.push(RejectedPointValueDto {
+                    point_id,
+                    ts_ms,
+                    reason: "point_not_found".to_string(),
+                });
+                continue;
+            }
+            values.push(PointValue {
+                tenant_id: ctx.tenant_id.clone(),
+                project_id: path.project_id.clone(),
+                point_id,
+                ts_ms,
+                value,
+                quality: quality.clone(),
+            });
+        }
+    }
+    if values.len() > MAX_WRITE_VALUES {
+        return bad_request_error("too many values");
+    }
+
+    let mut accepted = 0;
+    let mut rejected = skipped;
+    if !values.is_empty() {
+        match write_values_to_pipeline(&state, &ctx, &path.project_id, values, now_ms).await {
+            Ok(data) => {
+                accepted = data.accepted;
+                rejected.extend(data.rejected);
+            }
+            Err(err) => return err.into_response(),
+        }
+    }
+    if let Some(first) = rejected.first() {
+        warn!(
+            target: "ems.influx",
+            project_id = %path.project_id,
+            rejected = rejected.len(),
+            point_id = %first.point_id,
+            reason = %first.reason,
+            "influx_write_values_rejected"
+        );
+    }
+
+    // 与 InfluxDB 一致返回 204；Telegraf 1.x 输出插件只把 204 视为写入成功
+    let mut response = StatusCode::NO_CONTENT.into_response();
+    response
+        .headers_mut()
+        .insert("x-ems-accepted", HeaderValue::from(accepted));
+    response
+        .headers_mut()
+        .insert("x-ems-rejected", HeaderValue::from(rejected.len()));
+    response
+}
+
+/// 可写入的点位：登录令牌为项目内全部点位，网关令牌为该网关下设备的点位
+async fn writable_points(
+    state: &AppState,
+    ctx: &domain::TenantContext,
+    project_id: &str,
+    gateway_id: Option<&str>,
+) -> Result<HashSet<String>, Response> {
+    let points = state
+        .point_store
+        .list_points(ctx, project_id)
+        .await
+        .map_err(storage_error)?;
+    let Some(gateway_id) = gateway_id else {
+        return Ok(points.into_iter().map(|point| point.point_id).collect());
+    };
+    let devices: HashSet<String> = state
+        .device_store
+        .list_devices(ctx, project_id)
+        .await
+        .map_err(storage_error)?
+        .into_iter()
+        .filter(|device| device.gateway_id == gateway_id)
+        .map(|device| device.device_id)
+        .collect();
+    Ok(points
+        .into_iter()
+        .filter(|point| devices.contains(&point.device_id))
+        .map(|point| point.point_id)
+        .collect())
+}
+
+/// 2.x 客户端使用 `Authorization: Token <token>`，统一转换为 Bearer 头后按常规流程鉴权
+fn influx_auth_headers(mut headers: HeaderMap) -> HeaderMap {
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Token "))
+        .and_then(|token| HeaderValue::from_str(&format!("Bearer {token}")).ok());
+    if let Some(value) = token {
+        headers.insert(header::AUTHORIZATION, value);
+    }
+    headers
+}
+
+fn now_epoch_ms() -> i64 {
+    let now = std::time::SystemTime::now();
+    let duration = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    duration.as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{
+        api_router, auth_headers, build_state, json_request, project_ctx, response_json,
+    };
+    use axum::http::{StatusCode, header};
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    /// 测试：InfluxDB 行协议写入按标签 / 字段映射点位，网关令牌只能写入本网关设备
+    #[tokio::test]
+    async fn influx_line_protocol_write_maps_points() {
+        use std::io::Write;
+
+        let state = build_state();
+        let headers = auth_headers(&state).await;
+        let app = api_router(state.clone());
+        let request = |uri: &str, body: Value| {
+            json_request(
+                &headers,
+                "POST",
+                &format!("/api/v1/projects/project-1{uri}"),
+                Some(body),
+            )
+        };
+        let write = |uri: &str, authorization: &str, body: axum::body::Body| {
+            axum::http::Request::builder()
+                .method("POST")
+                .uri(format!("/api/v1/projects/project-1/influx{uri}"))
+                .header(header::AUTHORIZATION, authorization)
+                .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+                .body(body)
+                .expect("request")
+        };
+        let counts = |response: &axum::response::Response| {
+            (
+                response.headers()["x-ems-accepted"]
+                    .to_str()
+                    .expect("accepted")
+                    .to_string(),
+                response.headers()["x-ems-rejected"]
+                    .to_str()
+                    .expect("rejected")
+                    .to_string(),
+            )
+        };
+
+        let ctx = project_ctx();
+        let mut gateway_ids = Vec::new();
+        for name in ["gw-a", "gw-b"] {
+            let response = app
+                .clone()
+                .oneshot(request(
+                    "/gateways",
+                    serde_json::json!({ "name": name, "protocolType": "mqtt" }),
+                ))
+                .await
+                .expect("response");
+            let json = response_json(response).await;
+            let gateway_id = json["data"]["gatewayId"].as_str().expect("id").to_string();
+            let response = app
+                .clone()
+                .oneshot(request(
+                    "/devices",
+                    serde_json::json!({ "gatewayId": gateway_id, "name": name }),
+                ))
+                .await
+                .expect("response");
+            let json = response_json(response).await;
+            let device_id = json["data"]["deviceId"].as_str().expect("device id");
+            let point_ids: &[&str] = if name == "gw-a" {
+                &["meter-1.kw", "meter-1.kw.voltage"]
+            } else {
+                &["cpu.usage_idle"]
+            };
+            for point_id in point_ids {
+                state
+                    .point_store
+                    .create_point(
+                        &ctx,
+                        ems_storage::PointRecord {
+                            point_id: point_id.to_string(),
+                            tenant_id: "tenant-1".to_string(),
+                            project_id: "project-1".to_string(),
+                            device_id: device_id.to_string(),
+                            key: point_id.to_string(),
+                            data_type: "float".to_string(),
+                            unit: None,
+                            tags: Vec::new(),
+                        },
+                    )
+                    .await
+                    .expect("point");
+            }
+            gateway_ids.push(gateway_id);
+        }
+        let bearer = headers[header::AUTHORIZATION]
+            .to_str()
+            .expect("authorization")
+            .to_string();
+
+        // 登录令牌：point_id 标签 + value 字段，其余字段与无标签行按名称映射；未登记点位跳过
+        let response = app
+            .clone()
+            .oneshot(write(
+                "/write?db=telegraf",
+                &bearer,
+                axum::body::Body::from(
+                    "power,point_id=meter-1.kw,quality=good value=12.5,voltage=230i 1000000000\n\
+                     cpu usage_idle=97.5,usage_user=2.5 2000000000\n",
+                ),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(counts(&response), ("3".to_string(), "1".to_string()));
+        let last = state
+            .realtime_store
+            .get_last_value(&ctx, "project-1", "meter-1.kw")
+            .await
+            .expect("last value")
+            .expect("meter-1.kw");
+        assert_eq!(last.ts_ms, 1_000);
+        assert_eq!(last.quality.as_deref(), Some("good"));
+
+        // 网关令牌（2.x Token 头、gzip 请求体）：其他网关设备的点位被拒绝
+        let response = app
+            .clone()
+            .oneshot(request(
+                &format!("/gateways/{}/token", gateway_ids[0]),
+                serde_json::json!({}),
+            ))
+            .await
+            .expect("response");
+        let json = response_json(response).await;
+        let token = json["data"]["token"].as_str().expect("token").to_string();
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder
+            .write_all(b"power,point_id=meter-1.kw value=13 3000\ncpu usage_idle=90 3000\n")
+            .expect("gzip");
+        let mut gzip_request = write(
+            "/api/v2/write?org=site&bucket=ems&precision=ms",
+            &format!("Token {token}"),
+            axum::body::Body::from(encoder.finish().expect("gzip")),
+        );
+        gzip_request
+            .headers_mut()
+            .insert(header::CONTENT_ENCODING, "gzip".parse().expect("encoding"));
+        let response = app.clone().oneshot(gzip_request).await.expect("response");
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(counts(&response), ("1".to_string(), "1".to_string()));
+        let last = state
+            .realtime_store
+            .get_last_value(&ctx, "project-1", "meter-1.kw")
+            .await
+            .expect("last value")
+            .expect("meter-1.kw");
+        assert_eq!(last.ts_ms, 3_000);
+
+        // 格式错误与无效精度返回 400，未认证返回 401
+        for (uri, body) in [
+            ("/write", "power value=\n"),
+            ("/write?precision=h", "power value=1 1\n"),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(write(uri, &bearer, axum::body::Body::from(body)))
+                .await
+                .expect("response");
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+        let response = app
+            .clone()
+            .oneshot(write(
+                "/write",
+                "Token ems_gw_invalid",
+                axum::body::Body::from("power value=1\n"),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,278 @@
+//! 后台任务 handlers
+//!
+//! 长耗时操作以后台任务执行（由各业务接口提交，如电能质量报表任务），这里提供查询与取消：
+//! - GET /jobs - 列出租户的后台任务（可按 projectId / kind / status 过滤，按创建时间倒序）
+//! - GET /jobs/{id} - 获取任务状态、进度与结果
+//! - POST /jobs/{id}/cancel - 取消任务（排队中直接取消，运行中在下一次进度上报时停止）
+//!
+//! 权限要求：查询需要 JOB.READ，取消需要 JOB.WRITE
+
+use crate::AppState;
+use crate::middleware::{require_permission, require_tenant_context};
+use crate::utils::response::{bad_request_error, job_to_dto, not_found_error, storage_error};
+use crate::utils::validation::normalize_optional;
+use api_contract::{ApiResponse, JobDto, JobQuery};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use domain::permissions;
+use ems_jobs::{JOB_STATUSES, is_finished};
+
+/// 默认返回条数
+const DEFAULT_JOB_LIMIT: i64 = 50;
+/// 最大返回条数
+const MAX_JOB_LIMIT: i64 = 500;
+
+#[derive(serde::Deserialize)]
+pub struct JobPath {
+    job_id: String,
+}
+
+/// 列出后台任务
+pub async fn list_jobs(
+    State(state): State<AppState>,
+    Query(query): Query<JobQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_tenant_context(&state, &headers) {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::JOB_READ) {
+        return response;
+    }
+    let project_id = match normalize_optional(query.project_id, "projectId") {
+        Ok(value) => value,
+        Err(response) => return response,
+    };
+    let kind = match normalize_optional(query.kind, "kind") {
+        Ok(value) => value,
+        Err(response) => return response,
+    };
+    let status = match normalize_optional(query.status, "status") {
+        Ok(value) => value,
+        Err(response) => return response,
+    };
+    if let Some(status) = status.as_deref()
+        && !JOB_STATUSES.contains(&status)
+    {
+        return bad_request_error(format!(
+            "status must be one of: {}",
+            JOB_STATUSES.join(", ")
+        ));
+    }
+    let options = ems_storage::JobQuery {
+        project_id,
+        kind,
+        status,
+        limit: query
+            .limit
+            .unwrap_or(DEFAULT_JOB_LIMIT)
+            .clamp(1, MAX_JOB_LIMIT),
+    };
+    match state.job_store.list_jobs(&ctx, options).await {
+        Ok(items) => {
+            let data: Vec<JobDto> = items.into_iter().map(job_to_dto).collect();
+            (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 获取后台任务
+pub async fn get_job(
+    State(state): State<AppState>,
+    Path(path): Path<JobPath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_tenant_context(&state, &headers) {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::JOB_READ) {
+        return response;
+    }
+    match state.job_store.find_job(&ctx, &path.job_id).await {
+        Ok(Some(record)) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(job_to_dto(record))),
+        )
+            .into_response(),
+        Ok(None) => not_found_error(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 取消后台任务
+pub async fn cancel_job(
+    State(state): State<AppState>,
+    Path(path): Path<JobPath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_tenant_context(&state, &headers) {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::JOB_WRITE) {
+        return response;
+    }
+    match state.job_store.find_job(&ctx, &path.job_id).await {
+        Ok(Some(job)) if is_finished(&job.status) => {
+            return bad_request_error("job already finished");
+        }
+        Ok(Some(_)) => {}
+        Ok(None) => return not_found_error(),
+        Err(err) => return storage_error(err),
+    }
+    match state.job_runner.cancel(&ctx, &path.job_id).await {
+        Ok(Some(record)) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(job_to_dto(record))),
+        )
+            .into_response(),
+        Ok(None) => not_found_error(),
+        Err(err) => storage_error(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{
+        api_router, auth_headers, build_state, json_request, project_ctx, response_json,
+    };
+    use axum::http::StatusCode;
+    use domain::{PointValue, PointValueData};
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    /// 测试：电能质量报表后台任务提交后执行完成，可按状态查询，已结束的任务不能取消
+    #[tokio::test]
+    async fn power_quality_report_job_runs_and_reports_result() {
+        let state = build_state();
+        let headers = auth_headers(&state).await;
+        let ctx = project_ctx();
+        // 2024-01-01T00:00:00Z
+        let start_ms: i64 = 1_704_067_200_000;
+        let sub_ms: i64 = 5 * 60_000;
+        state
+            .point_store
+            .create_point(
+                &ctx,
+                ems_storage::PointRecord {
+                    point_id: "device-1-kw".to_string(),
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    device_id: "device-1".to_string(),
+                    key: "device-1-kw".to_string(),
+                    data_type: "float".to_string(),
+                    unit: None,
+                    tags: vec!["kw".to_string()],
+                },
+            )
+            .await
+            .expect("point");
+        for (index, value) in [80.0, 100.0, 120.0].into_iter().enumerate() {
+            state
+                .measurement_store
+                .write_measurement(
+                    &ctx,
+                    &PointValue {
+                        tenant_id: "tenant-1".to_string(),
+                        project_id: "project-1".to_string(),
+                        point_id: "device-1-kw".to_string(),
+                        ts_ms: start_ms + index as i64 * sub_ms,
+                        value: PointValueData::F64(value),
+                        quality: None,
+                    },
+                )
+                .await
+                .expect("measurement");
+        }
+
+        let app = api_router(state.clone());
+        let request = |method: &str, uri: String, body: Option<Value>| {
+            json_request(&headers, method, &format!("/api/v1{uri}"), body)
+        };
+        let to_ms = start_ms + 3 * sub_ms;
+
+        // 参数与同步报表相同的校验
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/projects/project-1/reports/power-quality/jobs".to_string(),
+                Some(serde_json::json!({ "from": to_ms, "to": start_ms })),
+            ))
+            .await
+            .expect("submit");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/projects/project-1/reports/power-quality/jobs".to_string(),
+                Some(serde_json::json!({ "from": start_ms, "to": to_ms })),
+            ))
+            .await
+            .expect("submit");
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let json = response_json(response).await;
+        assert_eq!(json["data"]["kind"], "report.power_quality");
+        assert_eq!(json["data"]["projectId"], "project-1");
+        assert_eq!(json["data"]["params"]["windowMinutes"], 15);
+        let job_id = json["data"]["jobId"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+
+        let mut job = Value::Null;
+        for _ in 0..100 {
+            let response = app
+                .clone()
+                .oneshot(request("GET", format!("/jobs/{job_id}"), None))
+                .await
+                .expect("job");
+            assert_eq!(response.status(), StatusCode::OK);
+            job = response_json(response).await["data"].clone();
+            if job["status"] == "succeeded" || job["status"] == "failed" {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(job["status"], "succeeded");
+        assert_eq!(job["progress"], 100);
+        assert_eq!(job["result"]["devices"][0]["deviceId"], "device-1");
+        assert_eq!(job["result"]["devices"][0]["samples"], 3);
+
+        let response = app
+            .clone()
+            .oneshot(request("GET", "/jobs?status=succeeded".to_string(), None))
+            .await
+            .expect("list");
+        let json = response_json(response).await;
+        assert_eq!(json["data"].as_array().map(Vec::len), Some(1));
+        let response = app
+            .clone()
+            .oneshot(request("GET", "/jobs?status=done".to_string(), None))
+            .await
+            .expect("list");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = app
+            .clone()
+            .oneshot(request("POST", format!("/jobs/{job_id}/cancel"), None))
+            .await
+            .expect("cancel");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = app
+            .clone()
+            .oneshot(request("GET", "/jobs/missing".to_string(), None))
+            .await
+            .expect("job");
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
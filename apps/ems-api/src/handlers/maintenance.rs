@@ -0,0 +1,522 @@
+//! 设备 / 网关维护模式 handlers
+//!
+//! 维护窗口内抑制设备离线告警、拦截自动化命令（规则 / 控制计划 / 需求响应）；
+//! 人工命令仍可下发，响应附带 `warning` 并记录覆盖审计：
+//! - GET /projects/{id}/maintenance - 列出项目下的维护窗口（含已到期未清除的窗口）
+//! - GET /projects/{id}/devices/{did}/maintenance - 查询设备维护窗口
+//! - PUT /projects/{id}/devices/{did}/maintenance - 设置设备维护窗口（覆盖原窗口）
+//! - DELETE /projects/{id}/devices/{did}/maintenance - 清除设备维护窗口
+//! - GET/PUT/DELETE /projects/{id}/gateways/{gid}/maintenance - 网关维护窗口（覆盖其下全部设备）
+//!
+//! 权限要求：
+//! - 列表需要 ASSET.DEVICE.READ 或 ASSET.GATEWAY.READ
+//! - 设备窗口查询需要 ASSET.DEVICE.READ，设置 / 清除需要 ASSET.DEVICE.WRITE
+//! - 网关窗口查询需要 ASSET.GATEWAY.READ，设置 / 清除需要 ASSET.GATEWAY.WRITE
+
+use crate::AppState;
+use crate::middleware::{require_any_permission, require_permission, require_project_scope};
+use crate::utils::response::{
+    bad_request_error, maintenance_window_to_dto, not_found_error, storage_error,
+};
+use api_contract::{ApiResponse, MaintenanceWindowDto, SetMaintenanceWindowRequest};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use domain::{TenantContext, permissions};
+use ems_control::{MAINTENANCE_TARGET_DEVICE, MAINTENANCE_TARGET_GATEWAY};
+use ems_storage::MaintenanceWindowRecord;
+
+/// 维护原因最大长度
+const MAX_REASON_LEN: usize = 500;
+
+#[derive(serde::Deserialize)]
+pub struct MaintenanceProjectPath {
+    project_id: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct DeviceMaintenancePath {
+    project_id: String,
+    device_id: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct GatewayMaintenancePath {
+    project_id: String,
+    gateway_id: String,
+}
+
+/// 列出项目下的维护窗口
+pub async fn list_maintenance_windows(
+    State(state): State<AppState>,
+    Path(path): Path<MaintenanceProjectPath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_any_permission(
+        &ctx,
+        &[
+            permissions::ASSET_DEVICE_READ,
+            permissions::ASSET_GATEWAY_READ,
+        ],
+    ) {
+        return response;
+    }
+    match state
+        .maintenance_service
+        .list_windows(&ctx, &path.project_id)
+        .await
+    {
+        Ok(items) => {
+            let now_ms = now_epoch_ms();
+            let data: Vec<MaintenanceWindowDto> = items
+                .into_iter()
+                .map(|record| maintenance_window_to_dto(record, now_ms))
+                .collect();
+            (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 查询设备维护窗口
+pub async fn get_device_maintenance(
+    State(state): State<AppState>,
+    Path(path): Path<DeviceMaintenancePath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::ASSET_DEVICE_READ) {
+        return response;
+    }
+    if let Err(response) = ensure_device(&state, &ctx, &path).await {
+        return response;
+    }
+    get_window(
+        &state,
+        &ctx,
+        &path.project_id,
+        MAINTENANCE_TARGET_DEVICE,
+        &path.device_id,
+    )
+    .await
+}
+
+/// 设置设备维护窗口
+pub async fn set_device_maintenance(
+    State(state): State<AppState>,
+    Path(path): Path<DeviceMaintenancePath>,
+    headers: HeaderMap,
+    Json(req): Json<SetMaintenanceWindowRequest>,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::ASSET_DEVICE_WRITE) {
+        return response;
+    }
+    if let Err(response) = ensure_device(&state, &ctx, &path).await {
+        return response;
+    }
+    set_window(
+        &state,
+        &ctx,
+        &path.project_id,
+        MAINTENANCE_TARGET_DEVICE,
+        &path.device_id,
+        req,
+    )
+    .await
+}
+
+/// 清除设备维护窗口
+pub async fn clear_device_maintenance(
+    State(state): State<AppState>,
+    Path(path): Path<DeviceMaintenancePath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::ASSET_DEVICE_WRITE) {
+        return response;
+    }
+    clear_window(
+        &state,
+        &ctx,
+        &path.project_id,
+        MAINTENANCE_TARGET_DEVICE,
+        &path.device_id,
+    )
+    .await
+}
+
+/// 查询网关维护窗口
+pub async fn get_gateway_maintenance(
+    State(state): State<AppState>,
+    Path(path): Path<GatewayMaintenancePath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::ASSET_GATEWAY_READ) {
+        return response;
+    }
+    if let Err(response) = ensure_gateway(&state, &ctx, &path).await {
+        return response;
+    }
+    get_window(
+        &state,
+        &ctx,
+        &path.project_id,
+        MAINTENANCE_TARGET_GATEWAY,
+        &path.gateway_id,
+    )
+    .await
+}
+
+/// 设置网关维护窗口
+pub async fn set_gateway_maintenance(
+    State(state): State<AppState>,
+    Path(path): Path<GatewayMaintenancePath>,
+    headers: HeaderMap,
+    Json(req): Json<SetMaintenanceWindowRequest>,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::ASSET_GATEWAY_WRITE) {
+        return response;
+    }
+    if let Err(response) = ensure_gateway(&state, &ctx, &path).await {
+        return response;
+    }
+    set_window(
+        &state,
+        &ctx,
+        &path.project_id,
+        MAINTENANCE_TARGET_GATEWAY,
+        &path.gateway_id,
+        req,
+    )
+    .await
+}
+
+/// 清除网关维护窗口
+pub async fn clear_gateway_maintenance(
+    State(state): State<AppState>,
+    Path(path): Path<GatewayMaintenancePath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::ASSET_GATEWAY_WRITE) {
+        return response;
+    }
+    clear_window(
+        &state,
+        &ctx,
+        &path.project_id,
+        MAINTENANCE_TARGET_GATEWAY,
+        &path.gateway_id,
+    )
+    .await
+}
+
+async fn get_window(
+    state: &AppState,
+    ctx: &TenantContext,
+    project_id: &str,
+    target_type: &str,
+    target_id: &str,
+) -> Response {
+    match state
+        .maintenance_service
+        .find_window(ctx, project_id, target_type, target_id)
+        .await
+    {
+        Ok(Some(record)) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(maintenance_window_to_dto(
+                record,
+                now_epoch_ms(),
+            ))),
+        )
+            .into_response(),
+        Ok(None) => not_found_error(),
+        Err(err) => storage_error(err),
+    }
+}
+
+async fn set_window(
+    state: &AppState,
+    ctx: &TenantContext,
+    project_id: &str,
+    target_type: &str,
+    target_id: &str,
+    req: SetMaintenanceWindowRequest,
+) -> Response {
+    let now_ms = now_epoch_ms();
+    let starts_at_ms = req.starts_at_ms.unwrap_or(now_ms);
+    if req.ends_at_ms <= starts_at_ms {
+        return bad_request_error("endsAtMs must be greater than startsAtMs");
+    }
+    if req.ends_at_ms <= now_ms {
+        return bad_request_error("endsAtMs must be in the future");
+    }
+    let reason = req
+        .reason
+        .map(|reason| reason.trim().to_string())
+        .filter(|reason| !reason.is_empty());
+    if reason
+        .as_ref()
+        .is_some_and(|reason| reason.chars().count() > MAX_REASON_LEN)
+    {
+        return bad_request_error(format!(
+            "reason must be at most {MAX_REASON_LEN} characters"
+        ));
+    }
+    let record = MaintenanceWindowRecord {
+        tenant_id: ctx.tenant_id.clone(),
+        project_id: project_id.to_string(),
+        target_type: target_type.to_string(),
+        target_id: target_id.to_string(),
+        starts_at_ms,
+        ends_at_ms: req.ends_at_ms,
+        reason,
+        created_by: ctx.user_id.clone(),
+        created_at_ms: now_ms,
+    };
+    match state.maintenance_service.set_window(ctx, record).await {
+        Ok(record) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(maintenance_window_to_dto(
+                record, now_ms,
+            ))),
+        )
+            .into_response(),
+        Err(err) => storage_error(err),
+    }
+}
+
+async fn clear_window(
+    state: &AppState,
+    ctx: &TenantContext,
+    project_id: &str,
+    target_type: &str,
+    target_id: &str,
+) -> Response {
+    match state
+        .maintenance_service
+        .clear_window(ctx, project_id, target_type, target_id, now_epoch_ms())
+        .await
+    {
+        Ok(true) => (StatusCode::OK, Json(ApiResponse::success(()))).into_response(),
+        Ok(false) => not_found_error(),
+        Err(err) => storage_error(err),
+    }
+}
+
+async fn ensure_device(
+    state: &AppState,
+    ctx: &TenantContext,
+    path: &DeviceMaintenancePath,
+) -> Result<(), Response> {
+    match state
+        .device_store
+        .find_device(ctx, &path.project_id, &path.device_id)
+        .await
+    {
+        Ok(Some(_)) => Ok(()),
+        Ok(None) => Err(not_found_error()),
+        Err(err) => Err(storage_error(err)),
+    }
+}
+
+async fn ensure_gateway(
+    state: &AppState,
+    ctx: &TenantContext,
+    path: &GatewayMaintenancePath,
+) -> Result<(), Response> {
+    match state
+        .gateway_store
+        .find_gateway(ctx, &path.project_id, &path.gateway_id)
+        .await
+    {
+        Ok(Some(_)) => Ok(()),
+        Ok(None) => Err(not_found_error()),
+        Err(err) => Err(storage_error(err)),
+    }
+}
+
+fn now_epoch_ms() -> i64 {
+    let now = std::time::SystemTime::now();
+    let duration = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    duration.as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{
+        api_router, auth_headers, build_state, json_request, project_ctx, response_json,
+    };
+    use axum::http::StatusCode;
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    /// 测试：网关维护窗口覆盖其下设备，人工命令照常下发并附带提示与覆盖审计
+    #[tokio::test]
+    async fn gateway_maintenance_warns_manual_commands() {
+        let state = build_state();
+        let headers = auth_headers(&state).await;
+        let ctx = project_ctx();
+        state
+            .gateway_store
+            .create_gateway(
+                &ctx,
+                ems_storage::GatewayRecord {
+                    gateway_id: "gw-a".to_string(),
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    name: "gw-a".to_string(),
+                    status: "online".to_string(),
+                    protocol_type: "mqtt".to_string(),
+                    protocol_config: None,
+                },
+            )
+            .await
+            .expect("gateway");
+        state
+            .device_store
+            .create_device(
+                &ctx,
+                ems_storage::DeviceRecord {
+                    device_id: "device-1".to_string(),
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    gateway_id: "gw-a".to_string(),
+                    name: "Inverter".to_string(),
+                    model: None,
+                    room_id: None,
+                    address_config: None,
+                    offline_after_seconds: None,
+                },
+            )
+            .await
+            .expect("device");
+
+        let app = api_router(state.clone());
+        let request = |method: &str, uri: &str, body: Option<Value>| {
+            json_request(
+                &headers,
+                method,
+                &format!("/api/v1/projects/project-1{uri}"),
+                body,
+            )
+        };
+        let now_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as i64;
+
+        // 结束时间已过返回 400
+        let response = app
+            .clone()
+            .oneshot(request(
+                "PUT",
+                "/gateways/gw-a/maintenance",
+                Some(serde_json::json!({ "endsAtMs": now_ms - 1_000 })),
+            ))
+            .await
+            .expect("maintenance");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = app
+            .clone()
+            .oneshot(request(
+                "PUT",
+                "/gateways/gw-a/maintenance",
+                Some(serde_json::json!({ "endsAtMs": now_ms + 3_600_000, "reason": "firmware swap" })),
+            ))
+            .await
+            .expect("maintenance");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        assert_eq!(json["data"]["targetType"], "gateway");
+        assert_eq!(json["data"]["active"], true);
+
+        let response = app
+            .clone()
+            .oneshot(request("GET", "/maintenance", None))
+            .await
+            .expect("list");
+        let json = response_json(response).await;
+        assert_eq!(json["data"].as_array().map(Vec::len), Some(1));
+
+        // 网关下设备的人工命令照常下发并附带提示
+        let command = serde_json::json!({ "target": "device-1", "payload": { "switch": "off" } });
+        let response = app
+            .clone()
+            .oneshot(request("POST", "/commands", Some(command.clone())))
+            .await
+            .expect("command");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        assert_eq!(json["data"]["status"], "accepted");
+        assert!(
+            json["data"]["warning"]
+                .as_str()
+                .is_some_and(|warning| warning.contains("gw-a"))
+        );
+        let logs = state
+            .audit_log_store
+            .list_audit_logs(&ctx, "project-1", None, None, 50)
+            .await
+            .expect("audit");
+        assert!(
+            logs.iter()
+                .any(|log| log.action == "ASSET.GATEWAY.MAINTENANCE.SET")
+        );
+        assert!(
+            logs.iter()
+                .any(|log| log.action == "CONTROL.COMMAND.MAINTENANCE_OVERRIDE")
+        );
+
+        // 清除后不再提示；重复清除返回 404
+        let response = app
+            .clone()
+            .oneshot(request("DELETE", "/gateways/gw-a/maintenance", None))
+            .await
+            .expect("clear");
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app
+            .clone()
+            .oneshot(request("DELETE", "/gateways/gw-a/maintenance", None))
+            .await
+            .expect("clear");
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let response = app
+            .oneshot(request("POST", "/commands", Some(command)))
+            .await
+            .expect("command");
+        let json = response_json(response).await;
+        assert!(json["data"].get("warning").is_none());
+    }
+}
@@ -1,39 +1,88 @@
 //! 历史查询 handlers
 //!
-//! - GET /projects/{id}/measurements
+//! - GET /projects/{id}/measurements（可使用项目分享令牌免登录访问）
+//! - GET /projects/{id}/measurements/export - 流式导出（CSV / NDJSON，按页查询边读边写，
+//!   内存占用与导出总量无关；同样可使用分享令牌）
+//! - GET /projects/{id}/points/{pid}/coverage - 数据覆盖率与缺失区间
 
 use crate::AppState;
-use crate::middleware::{require_permission, require_project_scope};
-use crate::utils::normalize_required;
-use crate::utils::response::{bad_request_error, storage_error};
-use api_contract::{ApiResponse, MeasurementValueDto, MeasurementsQuery};
+use crate::middleware::{require_permission, require_project_read_access, require_project_scope};
+use crate::utils::response::{bad_request_error, not_found_error, storage_error};
+use crate::utils::{list_success, normalize_required, parse_fields};
+use api_contract::{
+    ApiResponse, CoverageGapDto, FieldsQuery, MeasurementExportQuery, MeasurementValueDto,
+    MeasurementsQuery, PointCoverageDto, PointCoverageQuery, ShareTokenQuery,
+};
 use axum::{
     Json,
+    body::{Body, Bytes},
     extract::{Path, Query, State},
-    http::{HeaderMap, StatusCode},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
     response::{IntoResponse, Response},
 };
-use domain::permissions;
-use ems_storage::{MeasurementAggFn, MeasurementAggregation, MeasurementsQueryOptions, TimeOrder};
+use chrono_tz::Tz;
+use domain::{TenantContext, permissions};
+use ems_storage::{
+    CalendarBucket, MeasurementAggFn, MeasurementAggregation, MeasurementCoverageOptions,
+    MeasurementRecord, MeasurementStore, MeasurementsQueryOptions, StorageError, TimeOrder,
+};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+/// 单次覆盖率查询允许的最大桶数量，避免过小的间隔扫描超长窗口。
+const MAX_COVERAGE_BUCKETS: i64 = 1_000_000;
+/// 单次返回的最大缺失区间数量。
+const MAX_COVERAGE_GAPS: i64 = 500;
+/// 导出时每页查询的条数（写出后再查询下一页）。
+const EXPORT_PAGE_SIZE: i64 = 5000;
+/// 导出通道中最多缓冲的页数，客户端读取较慢时查询随之暂停。
+const EXPORT_BUFFERED_PAGES: usize = 2;
+
+/// 导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExportFormat {
+    Csv,
+    Ndjson,
+}
 
 #[derive(serde::Deserialize)]
 pub struct ProjectPath {
     pub(crate) project_id: String,
 }
 
+#[derive(serde::Deserialize)]
+pub struct PointCoveragePath {
+    project_id: String,
+    point_id: String,
+}
+
 pub async fn list_measurements(
     State(state): State<AppState>,
     Path(path): Path<ProjectPath>,
     Query(query): Query<MeasurementsQuery>,
+    Query(share): Query<ShareTokenQuery>,
+    Query(fields): Query<FieldsQuery>,
     headers: HeaderMap,
 ) -> Response {
-    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+    let ctx = match require_project_read_access(
+        &state,
+        &headers,
+        share.share_token.as_deref(),
+        &path.project_id,
+    )
+    .await
+    {
         Ok(ctx) => ctx,
         Err(response) => return response,
     };
     if let Err(response) = require_permission(&ctx, permissions::DATA_MEASUREMENTS_READ) {
         return response;
     }
+    let fields = match parse_fields(fields.fields) {
+        Ok(fields) => fields,
+        Err(response) => return response,
+    };
     let point_id = match normalize_required(query.point_id, "pointId") {
         Ok(value) => value,
         Err(response) => return response,
@@ -51,10 +100,23 @@ pub async fn list_measurements(
         Ok(order) => order,
         Err(response) => return response,
     };
-    let aggregation = match parse_aggregation(query.bucket_ms, query.agg.as_deref()) {
+    let aggregation = match parse_aggregation(
+        query.bucket_ms,
+        query.bucket.as_deref(),
+        query.agg.as_deref(),
+    ) {
         Ok(aggregation) => aggregation,
         Err(response) => return response,
     };
+    // 日历桶按项目时区的本地时间对齐
+    let timezone = if aggregation.is_some_and(|aggregation| aggregation.calendar.is_some()) {
+        match project_timezone(&state, &ctx, &path.project_id).await {
+            Ok(timezone) => timezone,
+            Err(response) => return response,
+        }
+    } else {
+        Tz::UTC
+    };
     match state
         .measurement_store
         .query_measurements(
@@ -68,6 +130,7 @@ pub async fn list_measurements(
                 order,
                 limit,
                 aggregation,
+                timezone,
             },
         )
         .await
@@ -83,6 +146,238 @@ pub async fn list_measurements(
                     quality: record.quality,
                 })
                 .collect();
+            list_success(data, fields.as_deref())
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 流式导出测点历史数据
+///
+/// 按时间升序以 keyset 分页查询，每页写出后再查询下一页；中途查询失败时中断响应体，
+/// 客户端据此识别导出不完整。
+pub async fn export_measurements(
+    State(state): State<AppState>,
+    Path(path): Path<ProjectPath>,
+    Query(query): Query<MeasurementExportQuery>,
+    Query(share): Query<ShareTokenQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_read_access(
+        &state,
+        &headers,
+        share.share_token.as_deref(),
+        &path.project_id,
+    )
+    .await
+    {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::DATA_MEASUREMENTS_READ) {
+        return response;
+    }
+    let point_id = match normalize_required(query.point_id, "pointId") {
+        Ok(value) => value,
+        Err(response) => return response,
+    };
+    if let (Some(from), Some(to)) = (query.from, query.to)
+        && from > to
+    {
+        return bad_request_error("from must be <= to");
+    }
+    let format = match query.format.as_deref().map(str::trim) {
+        None | Some("") | Some("csv") => ExportFormat::Csv,
+        Some("ndjson") => ExportFormat::Ndjson,
+        Some(_) => return bad_request_error("format must be csv|ndjson"),
+    };
+    let (content_type, extension) = match format {
+        ExportFormat::Csv => ("text/csv; charset=utf-8", "csv"),
+        ExportFormat::Ndjson => ("application/x-ndjson", "ndjson"),
+    };
+    let disposition = format!("attachment; filename=\"measurements-{point_id}.{extension}\"");
+
+    let (sender, receiver) = mpsc::channel(EXPORT_BUFFERED_PAGES);
+    let options = MeasurementsQueryOptions::simple(query.from, query.to, EXPORT_PAGE_SIZE);
+    tokio::spawn(write_export(
+        state.measurement_store.clone(),
+        ctx,
+        path.project_id,
+        point_id,
+        options,
+        format,
+        sender,
+    ));
+    let mut response = Body::from_stream(ReceiverStream::new(receiver)).into_response();
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    if let Ok(value) = HeaderValue::from_str(&disposition) {
+        response
+            .headers_mut()
+            .insert(header::CONTENT_DISPOSITION, value);
+    }
+    response
+}
+
+/// 逐页查询并写入导出通道；客户端断开（通道关闭）时停止查询
+async fn write_export(
+    store: Arc<dyn MeasurementStore>,
+    ctx: TenantContext,
+    project_id: String,
+    point_id: String,
+    mut options: MeasurementsQueryOptions,
+    format: ExportFormat,
+    sender: mpsc::Sender<Result<Bytes, std::io::Error>>,
+) {
+    if format == ExportFormat::Csv {
+        let header_line = Bytes::from_static(b"pointId,tsMs,value,quality\n");
+        if sender.send(Ok(header_line)).await.is_err() {
+            return;
+        }
+    }
+    loop {
+        let page = match store
+            .query_measurements(&ctx, &project_id, &point_id, options)
+            .await
+        {
+            Ok(page) => page,
+            Err(err) => {
+                tracing::warn!(
+                    project_id = %project_id,
+                    point_id = %point_id,
+                    error = %err,
+                    "measurement export failed"
+                );
+                let _ = sender
+                    .send(Err(std::io::Error::other(err.to_string())))
+                    .await;
+                return;
+            }
+        };
+        let Some(last_ts_ms) = page.last().map(|record| record.ts_ms) else {
+            return;
+        };
+        let mut chunk = String::new();
+        for record in &page {
+            write_export_line(&mut chunk, record, format);
+        }
+        if sender.send(Ok(Bytes::from(chunk))).await.is_err() {
+            return;
+        }
+        if (page.len() as i64) < options.limit {
+            return;
+        }
+        options.cursor_ts_ms = Some(last_ts_ms);
+    }
+}
+
+fn write_export_line(out: &mut String, record: &MeasurementRecord, format: ExportFormat) {
+    match format {
+        ExportFormat::Csv => {
+            out.push_str(&csv_field(&record.point_id));
+            out.push(',');
+            out.push_str(&record.ts_ms.to_string());
+            out.push(',');
+            out.push_str(&csv_field(&record.value));
+            out.push(',');
+            out.push_str(&csv_field(record.quality.as_deref().unwrap_or_default()));
+        }
+        ExportFormat::Ndjson => {
+            let line = MeasurementValueDto {
+                project_id: record.project_id.clone(),
+                point_id: record.point_id.clone(),
+                ts_ms: record.ts_ms,
+                value: record.value.clone(),
+                quality: record.quality.clone(),
+            };
+            if let Ok(line) = serde_json::to_string(&line) {
+                out.push_str(&line);
+            }
+        }
+    }
+    out.push('\n');
+}
+
+/// CSV 字段转义：含逗号、引号或换行时加引号，引号加倍
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// 查询测点数据覆盖率
+///
+/// 按 expectedIntervalMs 将 `[from, to)` 切桶，统计有数据的桶占比并列出连续缺失区间。
+pub async fn get_point_coverage(
+    State(state): State<AppState>,
+    Path(path): Path<PointCoveragePath>,
+    Query(query): Query<PointCoverageQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::DATA_MEASUREMENTS_READ) {
+        return response;
+    }
+    let (Some(from), Some(to)) = (query.from, query.to) else {
+        return bad_request_error("from and to are required");
+    };
+    if from >= to {
+        return bad_request_error("from must be < to");
+    }
+    let Some(expected_interval_ms) = query.expected_interval_ms else {
+        return bad_request_error("expectedIntervalMs is required");
+    };
+    if expected_interval_ms <= 0 {
+        return bad_request_error("expectedIntervalMs must be > 0");
+    }
+    let options = MeasurementCoverageOptions {
+        from_ms: from,
+        to_ms: to,
+        expected_interval_ms,
+        gap_limit: MAX_COVERAGE_GAPS,
+    };
+    if options.expected_buckets() > MAX_COVERAGE_BUCKETS {
+        return bad_request_error("expectedIntervalMs too small for window");
+    }
+    match state
+        .point_store
+        .find_point(&ctx, &path.project_id, &path.point_id)
+        .await
+    {
+        Ok(Some(_)) => {}
+        Ok(None) => return not_found_error(),
+        Err(err) => return storage_error(err),
+    }
+    match state
+        .measurement_store
+        .measurement_coverage(&ctx, &path.project_id, &path.point_id, options)
+        .await
+    {
+        Ok(coverage) => {
+            let data = PointCoverageDto {
+                project_id: path.project_id,
+                point_id: path.point_id,
+                from,
+                to,
+                expected_interval_ms,
+                expected_buckets: coverage.expected_buckets,
+                covered_buckets: coverage.covered_buckets,
+                completeness_pct: coverage.completeness_pct,
+                gaps: coverage
+                    .gaps
+                    .into_iter()
+                    .map(|gap| CoverageGapDto {
+                        from_ms: gap.from_ms,
+                        to_ms: gap.to_ms,
+                    })
+                    .collect(),
+            };
             (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
         }
         Err(err) => storage_error(err),
@@ -99,19 +394,56 @@ fn parse_order(value: Option<&str>) -> Result<TimeOrder, Response> {
     }
 }
 
+/// 读取项目时区（IANA 名称，如 `Asia/Shanghai`）
+pub(crate) async fn project_timezone(
+    state: &AppState,
+    ctx: &TenantContext,
+    project_id: &str,
+) -> Result<Tz, Response> {
+    let project = match state.project_store.find_project(ctx, project_id).await {
+        Ok(Some(project)) => project,
+        Ok(None) => return Err(not_found_error()),
+        Err(err) => return Err(storage_error(err)),
+    };
+    project.timezone.trim().parse::<Tz>().map_err(|_| {
+        // 时区由项目配置写入，解析失败属于数据错误而非客户端请求错误
+        storage_error(StorageError::new(format!(
+            "invalid project timezone: {}",
+            project.timezone
+        )))
+    })
+}
+
 fn parse_aggregation(
     bucket_ms: Option<i64>,
+    bucket: Option<&str>,
     agg: Option<&str>,
 ) -> Result<Option<MeasurementAggregation>, Response> {
-    let Some(bucket_ms) = bucket_ms else {
-        if agg.is_some() {
-            return Err(bad_request_error("agg requires bucketMs"));
+    let calendar = match bucket {
+        None => None,
+        Some(_) if bucket_ms.is_some() => {
+            return Err(bad_request_error(
+                "bucket and bucketMs are mutually exclusive",
+            ));
+        }
+        Some(value) => match CalendarBucket::parse(value) {
+            Some(calendar) => Some(calendar),
+            None => return Err(bad_request_error("bucket must be 1h|1d|1mo")),
+        },
+    };
+    let bucket_ms = match (bucket_ms, calendar) {
+        (Some(bucket_ms), _) if bucket_ms <= 0 => {
+            return Err(bad_request_error("bucketMs must be > 0"));
+        }
+        (Some(bucket_ms), _) => bucket_ms,
+        (None, Some(_)) => 0,
+        (None, None) => {
+            if agg.is_some() {
+                return Err(bad_request_error("agg requires bucketMs or bucket"));
+            }
+            return Ok(None);
         }
-        return Ok(None);
     };
-    if bucket_ms <= 0 {
-        return Err(bad_request_error("bucketMs must be > 0"));
-    }
     let func = match agg.map(|value| value.trim().to_ascii_lowercase()) {
         None => MeasurementAggFn::Avg,
         Some(value) if value.is_empty() => MeasurementAggFn::Avg,
@@ -122,5 +454,282 @@ fn parse_aggregation(
         Some(value) if value == "count" => MeasurementAggFn::Count,
         Some(_) => return Err(bad_request_error("agg must be avg|min|max|sum|count")),
     };
-    Ok(Some(MeasurementAggregation { bucket_ms, func }))
+    Ok(Some(MeasurementAggregation {
+        bucket_ms,
+        func,
+        calendar,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{
+        api_router, auth_headers, build_state, json_request, project_ctx, response_json,
+    };
+    use api_contract::{FieldsQuery, MeasurementsQuery, ShareTokenQuery};
+    use axum::extract::{Path, Query, State};
+    use axum::http::{StatusCode, header};
+    use domain::{PointValue, PointValueData};
+    use ems_storage::ProjectUpdate;
+    use http_body_util::BodyExt;
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    /// 测试：获取历史测量数据（GET /projects/{project_id}/measurements）
+    ///
+    /// 验证历史数据 API 能够正确返回存储的测点历史值。
+    ///
+    /// ## 测试步骤
+    ///
+    /// 1. 创建测试 AppState（内存存储）
+    /// 2. 向测量存储中写入一条历史数据
+    /// 3. 调用 `list_measurements` 处理器
+    /// 4. 验证响应状态码为 200 OK
+    /// 5. 验证响应体包含正确的数据
+    #[tokio::test]
+    async fn measurements_returns_values() {
+        // 准备测试环境
+        let state = build_state();
+
+        // 创建租户上下文
+        let ctx = project_ctx();
+
+        // 创建测试数据：一条历史测量值
+        let value = PointValue {
+            tenant_id: "tenant-1".to_string(),
+            project_id: "project-1".to_string(),
+            point_id: "point-1".to_string(),
+            ts_ms: 1_700_000_000_100,          // 时间戳（毫秒）
+            value: PointValueData::F64(23.45), // 浮点数值
+            quality: Some("good".to_string()), // 质量标识：良好
+        };
+
+        // 写入历史存储
+        state
+            .measurement_store
+            .write_measurement(&ctx, &value)
+            .await
+            .expect("write measurement");
+
+        // 调用处理器并验证响应
+        let headers = auth_headers(&state).await;
+        let query = |fields: Option<&str>| {
+            (
+                Query(MeasurementsQuery {
+                    point_id: "point-1".to_string(), // 指定测点 ID
+                    from: None,                      // 起始时间（不限）
+                    to: None,                        // 结束时间（不限）
+                    limit: Some(100),                // 最多返回 100 条
+                    cursor_ts_ms: None,              // 游标（分页用）
+                    order: None,                     // 排序方式（默认）
+                    bucket_ms: None,                 // 聚合桶大小（不聚合）
+                    bucket: None,                    // 日历聚合桶（不聚合）
+                    agg: None,                       // 聚合函数（不聚合）
+                }),
+                Query(FieldsQuery {
+                    fields: fields.map(str::to_string), // 字段选择（None 表示不裁剪）
+                }),
+            )
+        };
+        let (measurements, fields) = query(None);
+        let response = list_measurements(
+            State(state.clone()),
+            Path(crate::handlers::measurements::ProjectPath {
+                project_id: "project-1".to_string(),
+            }),
+            measurements,
+            Query(ShareTokenQuery { share_token: None }),
+            fields,
+            headers.clone(),
+        )
+        .await;
+
+        // 验证 HTTP 状态码
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // 验证响应体内容
+        let json = response_json(response).await;
+        assert_eq!(json["success"], true);
+        assert_eq!(json["data"].as_array().map(|v| v.len()), Some(1));
+        assert_eq!(json["data"][0]["quality"], "good");
+
+        // 字段选择：只返回 tsMs 与 value
+        let (measurements, fields) = query(Some("tsMs, value"));
+        let response = list_measurements(
+            State(state.clone()),
+            Path(crate::handlers::measurements::ProjectPath {
+                project_id: "project-1".to_string(),
+            }),
+            measurements,
+            Query(ShareTokenQuery { share_token: None }),
+            fields,
+            headers.clone(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        assert_eq!(
+            json["data"],
+            serde_json::json!([{"tsMs": 1_700_000_000_100i64, "value": "23.45"}])
+        );
+
+        // 空字段列表返回 400
+        let (measurements, fields) = query(Some(" , "));
+        let response = list_measurements(
+            State(state.clone()),
+            Path(crate::handlers::measurements::ProjectPath {
+                project_id: "project-1".to_string(),
+            }),
+            measurements,
+            Query(ShareTokenQuery { share_token: None }),
+            fields,
+            headers.clone(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // 项目存储的时区无效属于服务端数据错误：日历聚合返回 500 而非 400
+        state
+            .project_store
+            .update_project(
+                &ctx,
+                "project-1",
+                ProjectUpdate {
+                    name: None,
+                    timezone: Some("Mars/Olympus".to_string()),
+                },
+            )
+            .await
+            .expect("update project");
+        let (Query(mut measurements), fields) = query(None);
+        measurements.bucket = Some("1d".to_string());
+        let response = list_measurements(
+            State(state),
+            Path(crate::handlers::measurements::ProjectPath {
+                project_id: "project-1".to_string(),
+            }),
+            Query(measurements),
+            Query(ShareTokenQuery { share_token: None }),
+            fields,
+            headers,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    /// 测试：历史数据流式导出 CSV / NDJSON，压缩层按 Accept-Encoding 返回 gzip
+    #[tokio::test]
+    async fn measurement_export_streams_csv_and_ndjson() {
+        let state = build_state();
+        let ctx = project_ctx();
+        for (ts_ms, value) in [(1_000, 1.5), (2_000, 2.5), (3_000, 3.5)] {
+            let value = PointValue {
+                tenant_id: "tenant-1".to_string(),
+                project_id: "project-1".to_string(),
+                point_id: "point-1".to_string(),
+                ts_ms,
+                value: PointValueData::F64(value),
+                quality: Some("good".to_string()),
+            };
+            state
+                .measurement_store
+                .write_measurement(&ctx, &value)
+                .await
+                .expect("write measurement");
+        }
+        let headers = auth_headers(&state).await;
+        let app = api_router(state.clone()).layer(tower_http::compression::CompressionLayer::new());
+        let request = |query: &str, encoding: Option<&str>| {
+            let mut request = json_request(
+                &headers,
+                "GET",
+                &format!("/api/v1/projects/project-1/measurements/export?pointId=point-1{query}"),
+                None,
+            );
+            if let Some(encoding) = encoding {
+                request.headers_mut().insert(
+                    header::ACCEPT_ENCODING,
+                    HeaderValue::from_str(encoding).expect("accept encoding"),
+                );
+            }
+            request
+        };
+
+        // 默认 CSV，按时间升序
+        let response = app
+            .clone()
+            .oneshot(request("", None))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(
+            response.headers()[header::CONTENT_TYPE]
+                .to_str()
+                .expect("content type")
+                .starts_with("text/csv")
+        );
+        assert!(
+            response.headers()[header::CONTENT_DISPOSITION]
+                .to_str()
+                .expect("disposition")
+                .contains("measurements-point-1.csv")
+        );
+        let body = response
+            .into_body()
+            .collect()
+            .await
+            .expect("body")
+            .to_bytes();
+        let text = String::from_utf8(body.to_vec()).expect("utf8");
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], "pointId,tsMs,value,quality");
+        assert_eq!(lines[1], "point-1,1000,1.5,good");
+
+        // NDJSON：每行一个测量值对象
+        let response = app
+            .clone()
+            .oneshot(request("&format=ndjson&from=2000", None))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response
+            .into_body()
+            .collect()
+            .await
+            .expect("body")
+            .to_bytes();
+        let text = String::from_utf8(body.to_vec()).expect("utf8");
+        let items: Vec<Value> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("json line"))
+            .collect();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0]["tsMs"], 2_000);
+
+        // 客户端声明 gzip 时响应体被压缩
+        let response = app
+            .clone()
+            .oneshot(request("", Some("gzip")))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+        let body = response
+            .into_body()
+            .collect()
+            .await
+            .expect("body")
+            .to_bytes();
+        assert_eq!(&body[..2], &[0x1f, 0x8b]);
+
+        // 不支持的格式
+        let response = app
+            .clone()
+            .oneshot(request("&format=xlsx", None))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
 }
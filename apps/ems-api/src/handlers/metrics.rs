@@ -1,12 +1,13 @@
 //! Telemetry 指标快照（MVP）。
 //!
-//! - GET /metrics
+//! - GET /metrics：需要 `OPS.METRICS.READ`，只返回调用方所属租户的指标（不含全局计数）
+//! - GET /metrics（运维端口）：免鉴权的 Prometheus 文本指标，仅含全局计数
 
-use api_contract::{ApiResponse, MetricsSnapshotDto};
+use api_contract::{ApiResponse, TenantMetricsDto};
 use axum::{
     Json,
     extract::State,
-    http::{HeaderMap, StatusCode},
+    http::{HeaderMap, StatusCode, header},
     response::{IntoResponse, Response},
 };
 use ems_telemetry::metrics;
@@ -19,34 +20,104 @@ pub async fn get_metrics(State(state): State<AppState>, headers: HeaderMap) -> R
         Ok(ctx) => ctx,
         Err(response) => return response,
     };
-    if let Err(response) = require_permission(&ctx, permissions::SYSTEM_METRICS_READ) {
+    if let Err(response) = require_permission(&ctx, permissions::OPS_METRICS_READ) {
         return response;
     }
 
-    let snapshot = metrics().snapshot();
+    // 全局计数（含其他租户的流量）只在运维端口输出
+    let tenant = metrics().tenant_snapshot(&ctx.tenant_id);
     (
         StatusCode::OK,
-        Json(ApiResponse::success(MetricsSnapshotDto {
-        raw_events: snapshot.raw_events,
-        normalized_values: snapshot.normalized_values,
-        write_success: snapshot.write_success,
-        write_failure: snapshot.write_failure,
-        dropped_duplicate: snapshot.dropped_duplicate,
-        dropped_invalid: snapshot.dropped_invalid,
-        dropped_stale: snapshot.dropped_stale,
-        dropped_unmapped: snapshot.dropped_unmapped,
-        backpressure: snapshot.backpressure,
-        write_latency_ms_total: snapshot.write_latency_ms_total,
-        write_latency_ms_count: snapshot.write_latency_ms_count,
-        end_to_end_latency_ms_total: snapshot.end_to_end_latency_ms_total,
-        end_to_end_latency_ms_count: snapshot.end_to_end_latency_ms_count,
-        commands_issued: snapshot.commands_issued,
-        command_dispatch_success: snapshot.command_dispatch_success,
-        command_dispatch_failure: snapshot.command_dispatch_failure,
-        command_issue_latency_ms_total: snapshot.command_issue_latency_ms_total,
-        command_issue_latency_ms_count: snapshot.command_issue_latency_ms_count,
-        receipts_processed: snapshot.receipts_processed,
+        Json(ApiResponse::success(TenantMetricsDto {
+            tenant_id: ctx.tenant_id.clone(),
+            raw_events: tenant.raw_events,
+            write_success: tenant.write_success,
+            commands_issued: tenant.commands_issued,
+            receipts_processed: tenant.receipts_processed,
         })),
     )
         .into_response()
 }
+
+/// 运维端口的指标（Prometheus 文本格式：存活指标 + 全局计数，不含租户标记的指标）。
+pub async fn get_metrics_text() -> Response {
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics().snapshot().to_prometheus(),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{auth_headers, build_state, response_json};
+    use axum::extract::State;
+    use axum::http::StatusCode;
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+
+    /// 测试：指标快照只返回调用方租户的指标；全局计数与其他运维端点只在运维端口提供
+    #[tokio::test]
+    async fn metrics_snapshot_scoped_to_tenant() {
+        let state = build_state();
+        let headers = auth_headers(&state).await;
+        ems_telemetry::record_raw_event("tenant-1");
+        ems_telemetry::record_command_issued("tenant-2");
+
+        let response = crate::handlers::get_metrics(State(state), headers).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        assert_eq!(json["data"]["tenantId"], "tenant-1");
+        assert!(json["data"]["rawEvents"].as_u64().unwrap_or(0) >= 1);
+        // 全局计数（写入失败、丢弃等）不在业务端口返回
+        assert!(json["data"].get("writeFailure").is_none());
+
+        // 运维端口免鉴权，但只输出全局计数
+        let ops_router = crate::ops::create_ops_router(crate::ops::OpsState::new(
+            None,
+            serde_json::json!({ "jwt_secret": "***" }),
+        ));
+        let response = ops_router
+            .clone()
+            .oneshot(
+                axum::http::Request::builder()
+                    .uri("/metrics")
+                    .body(axum::body::Body::empty())
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = response
+            .into_body()
+            .collect()
+            .await
+            .expect("collect body")
+            .to_bytes();
+        let body = String::from_utf8_lossy(&bytes);
+        assert!(body.contains("ems_up 1"));
+        assert!(body.contains("ems_raw_events_total "));
+        assert!(!body.contains("tenant"));
+
+        for (uri, field) in [
+            ("/healthz", "ok"),
+            ("/debug/runtime", "workers"),
+            ("/config", "jwt_secret"),
+        ] {
+            let response = ops_router
+                .clone()
+                .oneshot(
+                    axum::http::Request::builder()
+                        .uri(uri)
+                        .body(axum::body::Body::empty())
+                        .expect("request"),
+                )
+                .await
+                .expect("response");
+            assert_eq!(response.status(), StatusCode::OK);
+            let json = response_json(response).await;
+            assert!(json.get(field).is_some(), "{uri} missing {field}");
+        }
+    }
+}
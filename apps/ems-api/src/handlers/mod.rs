@@ -1,27 +1,73 @@
 //! Handlers 模块
 
+pub mod anomalies;
 pub mod audit;
 pub mod auth;
+pub mod carbon;
 pub mod commands;
+pub mod demand_response;
+pub mod device_events;
+pub mod device_shadows;
+pub mod device_templates;
 pub mod devices;
+pub mod feature_flags;
+pub mod firmware;
+pub mod gateway_configs;
 pub mod gateways;
+pub mod graphql;
+pub mod influx;
+pub mod jobs;
+pub mod maintenance;
 pub mod measurements;
 pub mod metrics;
+pub mod ops_config;
 pub mod point_mappings;
+pub mod point_values;
 pub mod points;
+pub mod portfolios;
+pub mod project_clone;
 pub mod projects;
 pub mod rbac;
 pub mod realtime;
+pub mod reports;
+pub mod rules;
+pub mod schedules;
+pub mod share_tokens;
+pub mod usage;
+pub mod webhooks;
 
+pub use anomalies::*;
 pub use audit::*;
 pub use auth::*;
+pub use carbon::*;
 pub use commands::*;
+pub use demand_response::*;
+pub use device_events::*;
+pub use device_shadows::*;
+pub use device_templates::*;
 pub use devices::*;
+pub use feature_flags::*;
+pub use firmware::*;
+pub use gateway_configs::*;
 pub use gateways::*;
+pub use graphql::*;
+pub use influx::*;
+pub use jobs::*;
+pub use maintenance::*;
 pub use measurements::*;
 pub use metrics::*;
+pub use ops_config::*;
 pub use point_mappings::*;
+pub use point_values::*;
 pub use points::*;
+pub use portfolios::*;
+pub use project_clone::*;
 pub use projects::*;
 pub use rbac::*;
 pub use realtime::*;
+pub use reports::*;
+pub use rules::*;
+pub use schedules::*;
+pub use share_tokens::*;
+pub use usage::*;
+pub use webhooks::*;
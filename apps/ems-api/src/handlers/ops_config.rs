@@ -0,0 +1,86 @@
+//! 生效配置快照。
+//!
+//! - GET /ops/config（仅运维端口）：返回启动时生效的配置（密钥与连接串口令已脱敏），
+//!   逐项标注来源（环境变量 / 密钥文件 / 外部密钥服务 / 配置文件 / 默认值）；
+//!   平台配置不属于任何租户，业务端口不提供该路由
+
+use api_contract::ConfigEntryDto;
+use axum::{
+    Json,
+    extract::State,
+    response::{IntoResponse, Response},
+};
+use ems_config::ConfigOrigin;
+
+use crate::ops::OpsState;
+
+pub async fn get_ops_config(State(state): State<OpsState>) -> Response {
+    let entries = state
+        .config_entries()
+        .iter()
+        .map(|entry| ConfigEntryDto {
+            field: entry.field.clone(),
+            env: entry.env.clone(),
+            value: entry.value.clone(),
+            source: entry.origin.as_str().to_string(),
+            is_default: entry.origin == ConfigOrigin::Default,
+        })
+        .collect::<Vec<_>>();
+    Json(entries).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{api_router, auth_headers, build_state, json_request, response_json};
+    use axum::http::StatusCode;
+    use tower::ServiceExt;
+
+    /// 测试：生效配置快照只在运维端口提供，逐项返回脱敏值与来源
+    #[tokio::test]
+    async fn ops_config_lists_redacted_entries_with_sources() {
+        // 业务端口（含管理员 token）没有该路由
+        let state = build_state();
+        let headers = auth_headers(&state).await;
+        let response = api_router(state)
+            .oneshot(json_request(&headers, "GET", "/api/v1/ops/config", None))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let ops_router = crate::ops::create_ops_router(
+            crate::ops::OpsState::new(None, serde_json::json!({})).with_config_entries(vec![
+                ems_config::ConfigEntry {
+                    field: "jwt_secret".to_string(),
+                    env: "EMS_JWT_SECRET".to_string(),
+                    value: serde_json::json!(ems_config::REDACTED),
+                    origin: ems_config::ConfigOrigin::Env,
+                },
+                ems_config::ConfigEntry {
+                    field: "mqtt_port".to_string(),
+                    env: "EMS_MQTT_PORT".to_string(),
+                    value: serde_json::json!(1883),
+                    origin: ems_config::ConfigOrigin::Default,
+                },
+            ]),
+        );
+        let response = ops_router
+            .oneshot(
+                axum::http::Request::builder()
+                    .uri("/ops/config")
+                    .body(axum::body::Body::empty())
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        let entries = json.as_array().expect("entries");
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["env"], "EMS_JWT_SECRET");
+        assert_eq!(entries[0]["value"], "***");
+        assert_eq!(entries[0]["source"], "env");
+        assert_eq!(entries[0]["isDefault"], false);
+        assert_eq!(entries[1]["field"], "mqtt_port");
+        assert_eq!(entries[1]["isDefault"], true);
+    }
+}
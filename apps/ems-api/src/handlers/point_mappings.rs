@@ -6,28 +6,50 @@
 //! - GET /projects/{id}/point-mappings/{sid} - 获取点映射详情
 //! - PUT /projects/{id}/point-mappings/{sid} - 更新点映射
 //! - DELETE /projects/{id}/point-mappings/{sid} - 删除点映射
+//! - POST /projects/{id}/point-mappings/validate - 预检地址冲突（不写入）
+//! - GET /projects/{id}/point-mappings/duplicates - 列出已存在的重复映射（修复报告）
+//! - POST /projects/{id}/point-mappings/test - 映射试运行：按采集链路规范化样例报文（不写入）
+//!
+//! 同一项目内 (sourceType, address) 只能对应一个映射，否则规范化结果不确定；
+//! 创建 / 更新时冲突返回 409。
+//! `protocolDetail` 按点位所属设备的网关协议类型做字段级校验（ems_protocol）。
 //!
 //! 权限要求：
 //! - 所有接口需要 Bearer token 认证
 //! - 需验证项目归属当前租户
 //! - 创建点映射时需验证点属于该项目
 
+use super::devices::device_protocol_type;
 use crate::AppState;
 use crate::middleware::{require_permission, require_project_scope};
-use crate::utils::response::{bad_request_error, not_found_error, storage_error};
-use crate::utils::{normalize_optional, normalize_required, point_mapping_to_dto};
+use crate::utils::response::{bad_request_error, conflict_error, not_found_error, storage_error};
+use crate::utils::{
+    check_protocol_fields, collection_etag, etag_matches, list_success, normalize_optional,
+    normalize_required, not_modified, parse_fields, point_mapping_to_dto, with_etag,
+};
 use api_contract::{
-    ApiResponse, CreatePointMappingRequest, PointMappingDto, UpdatePointMappingRequest,
+    ApiResponse, CreatePointMappingRequest, FieldsQuery, PointMappingConflictDto, PointMappingDto,
+    PointMappingDuplicateDto, PointMappingTestDto, PointMappingValidationDto, RealtimeValueDto,
+    TestPointMappingRequest, UpdatePointMappingRequest, ValidatePointMappingsRequest,
 };
 use axum::{
     Json,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
     response::{IntoResponse, Response},
 };
-use domain::permissions;
+use domain::{TenantContext, permissions};
+use ems_normalize::{
+    NormalizeError, PointMappingProvider, StoragePointMappingProvider, parse_payload,
+};
+use ems_protocol::{FieldError, MODBUS_SERVER_SOURCE_TYPE, validate_point_detail};
+use ems_storage::PointMappingRecord;
+use std::collections::HashMap;
 use uuid::Uuid;
 
+/// 单次预检允许的最大候选数量
+const MAX_VALIDATE_MAPPINGS: usize = 5000;
+
 #[derive(serde::Deserialize)]
 pub struct ProjectPath {
     project_id: String,
@@ -43,6 +65,7 @@ pub struct PointMappingPath {
 pub async fn list_point_mappings(
     State(state): State<AppState>,
     Path(path): Path<ProjectPath>,
+    Query(fields): Query<FieldsQuery>,
     headers: HeaderMap,
 ) -> Response {
     let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
@@ -52,6 +75,23 @@ pub async fn list_point_mappings(
     if let Err(response) = require_permission(&ctx, permissions::ASSET_POINT_READ) {
         return response;
     }
+    let fields = match parse_fields(fields.fields) {
+        Ok(fields) => fields,
+        Err(response) => return response,
+    };
+    // 集合未变更时返回 304，省去重复的列表负载
+    let version = match state
+        .point_mapping_store
+        .point_mappings_version(&ctx, &path.project_id)
+        .await
+    {
+        Ok(version) => version,
+        Err(err) => return storage_error(err),
+    };
+    let etag = collection_etag(version, fields.as_deref(), "");
+    if etag_matches(&headers, &etag) {
+        return not_modified(&etag);
+    }
     match state
         .point_mapping_store
         .list_point_mappings(&ctx, &path.project_id)
@@ -59,7 +99,7 @@ pub async fn list_point_mappings(
     {
         Ok(items) => {
             let data: Vec<PointMappingDto> = items.into_iter().map(point_mapping_to_dto).collect();
-            (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+            with_etag(list_success(data, fields.as_deref()), &etag)
         }
         Err(err) => storage_error(err),
     }
@@ -91,14 +131,37 @@ pub async fn create_point_mapping(
         Ok(value) => value,
         Err(response) => return response,
     };
-    let exists = state
+    let point = match state
         .point_store
         .find_point(&ctx, &path.project_id, &point_id)
-        .await;
-    match exists {
-        Ok(Some(_)) => {}
+        .await
+    {
+        Ok(Some(point)) => point,
         Ok(None) => return bad_request_error("point not found"),
         Err(err) => return storage_error(err),
+    };
+    if let Err(response) = check_point_detail(
+        &state,
+        &ctx,
+        &path.project_id,
+        &point.device_id,
+        &source_type,
+        req.protocol_detail.as_deref(),
+    )
+    .await
+    {
+        return response;
+    }
+    let existing = match state
+        .point_mapping_store
+        .list_point_mappings(&ctx, &path.project_id)
+        .await
+    {
+        Ok(items) => items,
+        Err(err) => return storage_error(err),
+    };
+    if let Some(item) = find_address_conflict(&existing, &source_type, &address, None) {
+        return address_conflict_error(item);
     }
     let record = ems_storage::PointMappingRecord {
         source_id: Uuid::new_v4().to_string(),
@@ -191,6 +254,73 @@ pub async fn update_point_mapping(
     {
         return bad_request_error("empty update");
     }
+    // 改为 modbus_server 来源时，已有的 protocolDetail 也必须是合法的寄存器定义
+    if protocol_detail.is_some() || update.source_type.as_deref() == Some(MODBUS_SERVER_SOURCE_TYPE)
+    {
+        let current = match state
+            .point_mapping_store
+            .find_point_mapping(&ctx, &path.project_id, &path.source_id)
+            .await
+        {
+            Ok(Some(item)) => item,
+            Ok(None) => return not_found_error(),
+            Err(err) => return storage_error(err),
+        };
+        let device_id = match state
+            .point_store
+            .find_point(&ctx, &path.project_id, &current.point_id)
+            .await
+        {
+            Ok(Some(point)) => point.device_id,
+            Ok(None) => return not_found_error(),
+            Err(err) => return storage_error(err),
+        };
+        let source_type = update
+            .source_type
+            .as_deref()
+            .unwrap_or(&current.source_type);
+        let detail = protocol_detail
+            .as_deref()
+            .or(current.protocol_detail.as_deref());
+        if let Err(response) = check_point_detail(
+            &state,
+            &ctx,
+            &path.project_id,
+            &device_id,
+            source_type,
+            detail,
+        )
+        .await
+        {
+            return response;
+        }
+    }
+    if update.source_type.is_some() || update.address.is_some() {
+        let existing = match state
+            .point_mapping_store
+            .list_point_mappings(&ctx, &path.project_id)
+            .await
+        {
+            Ok(items) => items,
+            Err(err) => return storage_error(err),
+        };
+        let Some(current) = existing
+            .iter()
+            .find(|item| item.source_id == path.source_id)
+        else {
+            return not_found_error();
+        };
+        let source_type = update
+            .source_type
+            .as_deref()
+            .unwrap_or(&current.source_type);
+        let address = update.address.as_deref().unwrap_or(&current.address);
+        if let Some(item) =
+            find_address_conflict(&existing, source_type, address, Some(&path.source_id))
+        {
+            return address_conflict_error(item);
+        }
+    }
     match state
         .point_mapping_store
         .update_point_mapping(&ctx, &path.project_id, &path.source_id, update)
@@ -229,3 +359,495 @@ pub async fn delete_point_mapping(
         Err(err) => storage_error(err),
     }
 }
+
+/// 预检点位映射地址冲突
+///
+/// 对候选 (sourceType, address) 逐条检查：是否已被项目内其他映射占用，
+/// 以及是否与请求中更早的候选项重复。仅检查，不写入。
+pub async fn validate_point_mappings(
+    State(state): State<AppState>,
+    Path(path): Path<ProjectPath>,
+    headers: HeaderMap,
+    Json(req): Json<ValidatePointMappingsRequest>,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::ASSET_POINT_READ) {
+        return response;
+    }
+    if req.mappings.is_empty() {
+        return bad_request_error("mappings is empty");
+    }
+    if req.mappings.len() > MAX_VALIDATE_MAPPINGS {
+        return bad_request_error("too many mappings");
+    }
+    let existing = match state
+        .point_mapping_store
+        .list_point_mappings(&ctx, &path.project_id)
+        .await
+    {
+        Ok(items) => items,
+        Err(err) => return storage_error(err),
+    };
+    let mut conflicts = Vec::new();
+    let mut seen: HashMap<(String, String), usize> = HashMap::new();
+    for (index, candidate) in req.mappings.into_iter().enumerate() {
+        let source_type = candidate.source_type.trim().to_string();
+        let address = candidate.address.trim().to_string();
+        if source_type.is_empty() || address.is_empty() {
+            return bad_request_error(format!("sourceType and address are required: {index}"));
+        }
+        let conflict = find_address_conflict(
+            &existing,
+            &source_type,
+            &address,
+            candidate.source_id.as_deref(),
+        );
+        let key = (source_type.clone(), address.clone());
+        if let Some(item) = conflict {
+            conflicts.push(PointMappingConflictDto {
+                index,
+                source_type,
+                address,
+                reason: "existing".to_string(),
+                existing_source_id: Some(item.source_id.clone()),
+                existing_point_id: Some(item.point_id.clone()),
+                duplicate_of_index: None,
+            });
+        } else if let Some(first) = seen.get(&key) {
+            conflicts.push(PointMappingConflictDto {
+                index,
+                source_type,
+                address,
+                reason: "duplicate_in_request".to_string(),
+                existing_source_id: None,
+                existing_point_id: None,
+                duplicate_of_index: Some(*first),
+            });
+        }
+        seen.entry(key).or_insert(index);
+    }
+    let data = PointMappingValidationDto {
+        valid: conflicts.is_empty(),
+        conflicts,
+    };
+    (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+}
+
+/// 重复点位映射修复报告
+///
+/// 列出项目内 (sourceType, address) 被多个映射占用的分组，按地址排序；
+/// 清理后即可重新执行 `030_point_mapping_unique_address.sql` 建立唯一索引。
+pub async fn list_duplicate_point_mappings(
+    State(state): State<AppState>,
+    Path(path): Path<ProjectPath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::ASSET_POINT_READ) {
+        return response;
+    }
+    let existing = match state
+        .point_mapping_store
+        .list_point_mappings(&ctx, &path.project_id)
+        .await
+    {
+        Ok(items) => items,
+        Err(err) => return storage_error(err),
+    };
+    let mut groups: HashMap<(String, String), Vec<PointMappingRecord>> = HashMap::new();
+    for item in existing {
+        groups
+            .entry((item.source_type.clone(), item.address.clone()))
+            .or_default()
+            .push(item);
+    }
+    let mut data: Vec<PointMappingDuplicateDto> = groups
+        .into_iter()
+        .filter(|(_, items)| items.len() > 1)
+        .map(|((source_type, address), mut items)| {
+            items.sort_by(|a, b| a.source_id.cmp(&b.source_id));
+            PointMappingDuplicateDto {
+                source_type,
+                address,
+                mappings: items.into_iter().map(point_mapping_to_dto).collect(),
+            }
+        })
+        .collect();
+    data.sort_by(|a, b| {
+        (a.source_type.as_str(), a.address.as_str())
+            .cmp(&(b.source_type.as_str(), b.address.as_str()))
+    });
+    (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+}
+
+/// 映射试运行
+///
+/// 与采集链路使用相同的映射匹配（`StoragePointMappingProvider`）与换算步骤
+/// （`parse_payload` + `PointMapping::apply`），返回命中的映射、中间值与最终点位值；
+/// 不写入历史库、最新值，也不计入用量。未命中映射时 `matched=false`。
+pub async fn test_point_mapping(
+    State(state): State<AppState>,
+    Path(path): Path<ProjectPath>,
+    headers: HeaderMap,
+    Json(req): Json<TestPointMappingRequest>,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::ASSET_POINT_READ) {
+        return response;
+    }
+    let address = match normalize_required(req.address, "address") {
+        Ok(value) => value,
+        Err(response) => return response,
+    };
+    let source_id = req.source_id.unwrap_or_default().trim().to_string();
+    let provider = StoragePointMappingProvider::new(state.point_mapping_store.clone());
+    let mapping = match provider
+        .find_mapping(&ctx.tenant_id, &path.project_id, &source_id, &address)
+        .await
+    {
+        Ok(mapping) => mapping,
+        Err(NormalizeError::MappingProvider(err)) => return storage_error(err),
+        Err(err) => return bad_request_error(err.to_string()),
+    };
+    let mut data = PointMappingTestDto {
+        matched: mapping.is_some(),
+        mapping: None,
+        raw_value: None,
+        scaled_value: None,
+        point_value: None,
+        error: None,
+    };
+    let Some(mapping) = mapping else {
+        return (StatusCode::OK, Json(ApiResponse::success(data))).into_response();
+    };
+    match state
+        .point_mapping_store
+        .find_point_mapping(&ctx, &path.project_id, &mapping.source_id)
+        .await
+    {
+        Ok(record) => data.mapping = record.map(point_mapping_to_dto),
+        Err(err) => return storage_error(err),
+    }
+    match parse_payload(req.payload.as_bytes()) {
+        Ok(raw) => {
+            let value = mapping.apply(raw);
+            data.raw_value = Some(raw);
+            data.scaled_value = Some(mapping.scale.map_or(raw, |scale| raw * scale));
+            data.point_value = Some(RealtimeValueDto {
+                project_id: path.project_id,
+                point_id: mapping.point_id,
+                ts_ms: req.received_at_ms.unwrap_or_else(now_epoch_ms),
+                value: value.to_string(),
+                quality: None,
+            });
+        }
+        Err(err) => data.error = Some(err.to_string()),
+    }
+    (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+}
+
+/// 查找占用相同 (source_type, address) 的其他映射
+fn find_address_conflict<'a>(
+    mappings: &'a [PointMappingRecord],
+    source_type: &str,
+    address: &str,
+    exclude_source_id: Option<&str>,
+) -> Option<&'a PointMappingRecord> {
+    mappings.iter().find(|item| {
+        Some(item.source_id.as_str()) != exclude_source_id
+            && item.source_type == source_type
+            && item.address == address
+    })
+}
+
+fn address_conflict_error(item: &PointMappingRecord) -> Response {
+    conflict_error(format!(
+        "address already mapped: {} {} (sourceId {}, pointId {})",
+        item.source_type, item.address, item.source_id, item.point_id
+    ))
+}
+
+fn now_epoch_ms() -> i64 {
+    let now = std::time::SystemTime::now();
+    let duration = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    duration.as_millis() as i64
+}
+
+/// 按点位所属设备的网关协议类型校验 `protocolDetail`
+///
+/// 设备或网关已不存在时无法确定协议类型，只校验为 JSON 对象。
+/// `modbus_server` 映射描述对外暴露的寄存器，必须提供 `protocolDetail` 并按寄存器定义校验。
+async fn check_point_detail(
+    state: &AppState,
+    ctx: &TenantContext,
+    project_id: &str,
+    device_id: &str,
+    source_type: &str,
+    detail: Option<&str>,
+) -> Result<(), Response> {
+    if source_type == MODBUS_SERVER_SOURCE_TYPE {
+        let errors = match detail {
+            Some(detail) => validate_point_detail(MODBUS_SERVER_SOURCE_TYPE, detail),
+            None => vec![FieldError {
+                field: String::new(),
+                message: "required for modbus_server mappings".to_string(),
+            }],
+        };
+        return check_protocol_fields("protocolDetail", errors);
+    }
+    let Some(detail) = detail else {
+        return Ok(());
+    };
+    let protocol_type = device_protocol_type(state, ctx, project_id, device_id)
+        .await?
+        .unwrap_or_default();
+    check_protocol_fields(
+        "protocolDetail",
+        validate_point_detail(&protocol_type, detail),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{
+        api_router, auth_headers, build_state, json_request, project_ctx, response_json,
+    };
+    use axum::http::StatusCode;
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    /// 测试：映射试运行按采集链路匹配并换算样例报文，且不写入任何数据
+    #[tokio::test]
+    async fn point_mapping_test_normalizes_without_writing() {
+        let state = build_state();
+        let headers = auth_headers(&state).await;
+        let ctx = project_ctx();
+        state
+            .point_mapping_store
+            .create_point_mapping(
+                &ctx,
+                ems_storage::PointMappingRecord {
+                    source_id: "source-1".to_string(),
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    point_id: "point-1".to_string(),
+                    source_type: "mqtt".to_string(),
+                    address: "temp".to_string(),
+                    scale: Some(0.5),
+                    offset: Some(-40.0),
+                    protocol_detail: None,
+                },
+            )
+            .await
+            .expect("mapping");
+
+        let app = api_router(state.clone());
+        let request = |body: Value| {
+            json_request(
+                &headers,
+                "POST",
+                "/api/v1/projects/project-1/point-mappings/test",
+                Some(body),
+            )
+        };
+
+        let response = app
+            .clone()
+            .oneshot(request(serde_json::json!({
+                "address": "temp",
+                "payload": " 130 ",
+                "receivedAtMs": 1_000,
+            })))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        assert_eq!(json["data"]["matched"], true);
+        assert_eq!(json["data"]["mapping"]["sourceId"], "source-1");
+        assert_eq!(json["data"]["rawValue"], 130.0);
+        assert_eq!(json["data"]["scaledValue"], 65.0);
+        assert_eq!(json["data"]["pointValue"]["pointId"], "point-1");
+        assert_eq!(json["data"]["pointValue"]["tsMs"], 1_000);
+        assert_eq!(json["data"]["pointValue"]["value"], "25");
+
+        // 报文无法解析时返回原因
+        let response = app
+            .clone()
+            .oneshot(request(
+                serde_json::json!({ "address": "temp", "payload": "n/a" }),
+            ))
+            .await
+            .expect("response");
+        let json = response_json(response).await;
+        assert_eq!(json["data"]["matched"], true);
+        assert!(json["data"]["pointValue"].is_null());
+        assert!(json["data"]["error"].as_str().is_some());
+
+        // 未命中映射
+        let response = app
+            .clone()
+            .oneshot(request(
+                serde_json::json!({ "address": "humidity", "payload": "1" }),
+            ))
+            .await
+            .expect("response");
+        let json = response_json(response).await;
+        assert_eq!(json["data"]["matched"], false);
+        assert!(json["data"]["mapping"].is_null());
+
+        let last = state
+            .realtime_store
+            .get_last_value(&ctx, "project-1", "point-1")
+            .await
+            .expect("last value");
+        assert!(last.is_none());
+    }
+
+    /// 测试：同一项目内映射地址唯一，创建 / 更新冲突 409，预检报告冲突位置
+    #[tokio::test]
+    async fn point_mapping_address_conflicts_detected() {
+        let state = build_state();
+        let headers = auth_headers(&state).await;
+        let ctx = project_ctx();
+        for point_id in ["point-1", "point-2"] {
+            state
+                .point_store
+                .create_point(
+                    &ctx,
+                    ems_storage::PointRecord {
+                        point_id: point_id.to_string(),
+                        tenant_id: "tenant-1".to_string(),
+                        project_id: "project-1".to_string(),
+                        device_id: "device-1".to_string(),
+                        key: point_id.to_string(),
+                        data_type: "float".to_string(),
+                        unit: None,
+                        tags: Vec::new(),
+                    },
+                )
+                .await
+                .expect("point");
+        }
+
+        let app = api_router(state.clone());
+        let request = |method: &str, uri: &str, body: Option<Value>| {
+            json_request(&headers, method, &format!("/api/v1{uri}"), body)
+        };
+
+        let mut source_ids = Vec::new();
+        for (point_id, address) in [("point-1", "40001"), ("point-2", "40002")] {
+            let response = app
+                .clone()
+                .oneshot(request(
+                    "POST",
+                    "/projects/project-1/point-mappings",
+                    Some(serde_json::json!({
+                        "pointId": point_id,
+                        "sourceType": "modbus",
+                        "address": address,
+                    })),
+                ))
+                .await
+                .expect("response");
+            assert_eq!(response.status(), StatusCode::OK);
+            let json = response_json(response).await;
+            source_ids.push(
+                json["data"]["sourceId"]
+                    .as_str()
+                    .expect("sourceId")
+                    .to_string(),
+            );
+        }
+
+        // 相同类型与地址再次创建、或更新为已占用地址均返回 409
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/projects/project-1/point-mappings",
+                Some(serde_json::json!({
+                    "pointId": "point-2",
+                    "sourceType": "modbus",
+                    "address": "40001",
+                })),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let response = app
+            .clone()
+            .oneshot(request(
+                "PUT",
+                &format!("/projects/project-1/point-mappings/{}", source_ids[1]),
+                Some(serde_json::json!({ "address": "40001" })),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        // 不同协议类型可使用相同地址
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/projects/project-1/point-mappings",
+                Some(serde_json::json!({
+                    "pointId": "point-2",
+                    "sourceType": "mqtt",
+                    "address": "40001",
+                })),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/projects/project-1/point-mappings/validate",
+                Some(serde_json::json!({ "mappings": [
+                    { "sourceType": "modbus", "address": "40001" },
+                    { "sourceType": "modbus", "address": "40003" },
+                    { "sourceType": "modbus", "address": "40003" },
+                    { "sourceId": source_ids[1], "sourceType": "modbus", "address": "40002" },
+                ] })),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        assert_eq!(json["data"]["valid"], false);
+        let conflicts = json["data"]["conflicts"].as_array().expect("conflicts");
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts[0]["index"], 0);
+        assert_eq!(conflicts[0]["reason"], "existing");
+        assert_eq!(conflicts[0]["existingSourceId"], source_ids[0].as_str());
+        assert_eq!(conflicts[1]["index"], 2);
+        assert_eq!(conflicts[1]["reason"], "duplicate_in_request");
+        assert_eq!(conflicts[1]["duplicateOfIndex"], 1);
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                "GET",
+                "/projects/project-1/point-mappings/duplicates",
+                None,
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        assert_eq!(json["data"].as_array().map(Vec::len), Some(0));
+    }
+}
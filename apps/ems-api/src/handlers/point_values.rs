@@ -0,0 +1,425 @@
+//! 点位值写入 handlers
+//!
+//! 供可信集成（第三方云平台、计算服务）不经 MQTT 直接写入点位值：
+//! - POST /projects/{id}/points/values - 批量写入（历史库 + 最新值）
+//!
+//! 写入经过与采集链路相同的流水线校验与去重（时间戳、非有限数值、过期、重复值），
+//! 被拒绝的值逐条返回原因；整批计入租户当日 `measurements` 用量，超出配额返回 429。
+//! 写入的值同样刷新所属设备及其网关的在线状态（`EMS_ONLINE_FROM_DATA`）。
+//!
+//! 权限要求：DATA.WRITE
+
+use crate::AppState;
+use crate::middleware::{require_permission, require_project_scope};
+use crate::utils::response::{
+    bad_request_error, pipeline_error, quota_exceeded_error, storage_error,
+};
+use api_contract::{
+    ApiResponse, RejectedPointValueDto, WritePointValuesDto, WritePointValuesRequest,
+};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use domain::{PointValue, PointValueData, TenantContext, permissions, usage};
+use ems_pipeline::PipelineError;
+use ems_storage::StorageError;
+use std::collections::HashSet;
+
+/// 单次写入允许的最大值数量（与 gRPC WritePoints 一致）
+pub(crate) const MAX_WRITE_VALUES: usize = 5000;
+
+#[derive(serde::Deserialize)]
+pub struct PointValuesPath {
+    project_id: String,
+}
+
+/// 批量写入点位值
+pub async fn write_point_values(
+    State(state): State<AppState>,
+    Path(path): Path<PointValuesPath>,
+    headers: HeaderMap,
+    Json(req): Json<WritePointValuesRequest>,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::DATA_WRITE) {
+        return response;
+    }
+    if req.values.is_empty() {
+        return bad_request_error("values is empty");
+    }
+    if req.values.len() > MAX_WRITE_VALUES {
+        return bad_request_error("too many values");
+    }
+
+    // 仅允许写入项目内已登记的点位
+    let known: HashSet<String> = match state.point_store.list_points(&ctx, &path.project_id).await {
+        Ok(points) => points.into_iter().map(|point| point.point_id).collect(),
+        Err(err) => return storage_error(err),
+    };
+    let now_ms = now_epoch_ms();
+    let mut values = Vec::with_capacity(req.values.len());
+    for item in req.values {
+        if !known.contains(&item.point_id) {
+            return bad_request_error(format!("point not found: {}", item.point_id));
+        }
+        let Some(value) = json_to_point_value(&item.value) else {
+            return bad_request_error(format!(
+                "value must be a number, boolean or string: {}",
+                item.point_id
+            ));
+        };
+        values.push(PointValue {
+            tenant_id: ctx.tenant_id.clone(),
+            project_id: path.project_id.clone(),
+            point_id: item.point_id,
+            ts_ms: item.ts_ms.unwrap_or(now_ms),
+            value,
+            quality: item.quality,
+        });
+    }
+
+    match write_values_to_pipeline(&state, &ctx, &path.project_id, values, now_ms).await {
+        Ok(data) => (StatusCode::OK, Json(ApiResponse::success(data))).into_response(),
+        Err(err) => err.into_response(),
+    }
+}
+
+/// 流水线写入失败原因（HTTP 与 gRPC 各自映射为响应 / 状态码）
+#[derive(Debug)]
+pub(crate) enum WriteValuesError {
+    /// 超出当日 `measurements` 配额
+    Quota,
+    Storage(StorageError),
+    Pipeline(PipelineError),
+}
+
+impl IntoResponse for WriteValuesError {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Quota => quota_exceeded_error(usage::MEASUREMENTS),
+            Self::Storage(err) => storage_error(err),
+            Self::Pipeline(err) => pipeline_error(err),
+        }
+    }
+}
+
+/// 经采集流水线写入一批点位值（HTTP、InfluxDB 行协议与 gRPC WritePoints 共用）
+///
+/// 整批按缓存的当日测量值用量检查配额，只有被流水线接受（未被去重 / 过滤）的值计入用量；
+/// 逐条返回被流水线拒绝的原因，并刷新所属设备及其网关的在线状态。
+pub(crate) async fn write_values_to_pipeline(
+    state: &AppState,
+    ctx: &TenantContext,
+    project_id: &str,
+    values: Vec<PointValue>,
+    now_ms: i64,
+) -> Result<WritePointValuesDto, WriteValuesError> {
+    // 整批检查当日测量值配额，超出配额时整批拒绝
+    let period_start_ms = usage::day_start_ms(now_ms);
+    match state
+        .usage_meter
+        .has_quota(
+            ctx,
+            usage::MEASUREMENTS,
+            period_start_ms,
+            values.len() as i64,
+        )
+        .await
+    {
+        Ok(true) => {}
+        Ok(false) => return Err(WriteValuesError::Quota),
+        Err(err) => return Err(WriteValuesError::Storage(err)),
+    }
+
+    let mut accepted = 0;
+    let mut rejected = Vec::new();
+    let mut touched = Vec::new();
+    for value in values {
+        let point_id = value.point_id.clone();
+        let ts_ms = value.ts_ms;
+        match state.point_value_pipeline.handle(value).await {
+            // queued 表示已进入批次，随后统一刷盘
+            Ok(result) if result.written || result.reason.as_deref() == Some("queued") => {
+                accepted += 1;
+                touched.push((point_id, ts_ms));
+            }
+            Ok(result) => rejected.push(RejectedPointValueDto {
+                point_id,
+                ts_ms,
+                reason: result.reason.unwrap_or_default(),
+            }),
+            Err(err) => return Err(WriteValuesError::Pipeline(err)),
+        }
+    }
+    if let Err(err) = state.point_value_pipeline.flush().await {
+        return Err(WriteValuesError::Pipeline(err));
+    }
+    state
+        .usage_meter
+        .record(ctx, usage::MEASUREMENTS, period_start_ms, accepted as i64);
+    // 在线状态刷新失败不影响写入结果
+    let _ = state
+        .online_tracker
+        .touch_points(
+            ctx,
+            project_id,
+            touched
+                .iter()
+                .map(|(point_id, ts_ms)| (point_id.as_str(), *ts_ms)),
+        )
+        .await;
+    Ok(WritePointValuesDto { accepted, rejected })
+}
+
+/// JSON 标量转换为点位值：整数保持整数，其余数字按浮点处理
+fn json_to_point_value(value: &serde_json::Value) -> Option<PointValueData> {
+    match value {
+        serde_json::Value::Bool(value) => Some(PointValueData::Bool(*value)),
+        serde_json::Value::String(value) => Some(PointValueData::String(value.clone())),
+        serde_json::Value::Number(number) => match number.as_i64() {
+            Some(value) => Some(PointValueData::I64(value)),
+            None => number.as_f64().map(PointValueData::F64),
+        },
+        _ => None,
+    }
+}
+
+fn now_epoch_ms() -> i64 {
+    let now = std::time::SystemTime::now();
+    let duration = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    duration.as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{
+        api_router, auth_headers, build_state, json_request, project_ctx, response_json,
+    };
+    use axum::http::{HeaderMap, StatusCode};
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    /// 测试：点位值批量写入经流水线校验与去重，写入最新值，去重后的值计入测量值配额
+    #[tokio::test]
+    async fn point_values_written_through_pipeline() {
+        let state = build_state();
+        let headers = auth_headers(&state).await;
+        let ctx = project_ctx();
+        for point_id in ["point-1", "point-2"] {
+            state
+                .point_store
+                .create_point(
+                    &ctx,
+                    ems_storage::PointRecord {
+                        point_id: point_id.to_string(),
+                        tenant_id: "tenant-1".to_string(),
+                        project_id: "project-1".to_string(),
+                        device_id: "device-1".to_string(),
+                        key: point_id.to_string(),
+                        data_type: "float".to_string(),
+                        unit: None,
+                        tags: Vec::new(),
+                    },
+                )
+                .await
+                .expect("point");
+        }
+
+        let app = api_router(state.clone());
+        let request = |method: &str, uri: &str, body: Option<Value>| {
+            json_request(&headers, method, &format!("/api/v1{uri}"), body)
+        };
+
+        // 未登记点位与非标量值整批拒绝
+        for values in [
+            serde_json::json!([{ "pointId": "point-x", "tsMs": 1_000, "value": 1 }]),
+            serde_json::json!([{ "pointId": "point-1", "tsMs": 1_000, "value": { "v": 1 } }]),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(request(
+                    "POST",
+                    "/projects/project-1/points/values",
+                    Some(serde_json::json!({ "values": values })),
+                ))
+                .await
+                .expect("response");
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/projects/project-1/points/values",
+                Some(serde_json::json!({ "values": [
+                    { "pointId": "point-1", "tsMs": 1_000, "value": 21.5 },
+                    { "pointId": "point-1", "tsMs": 1_000, "value": 21.5 },
+                    { "pointId": "point-2", "tsMs": 0, "value": 3 },
+                    { "pointId": "point-2", "tsMs": 2_000, "value": true, "quality": "good" },
+                ] })),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        assert_eq!(json["data"]["accepted"], 2);
+        let reasons: Vec<&str> = json["data"]["rejected"]
+            .as_array()
+            .expect("rejected")
+            .iter()
+            .filter_map(|item| item["reason"].as_str())
+            .collect();
+        assert_eq!(reasons, vec!["duplicate", "invalid_ts"]);
+
+        // 请求返回时已刷盘：最新值与历史库均可读
+        let last = state
+            .realtime_store
+            .get_last_value(&ctx, "project-1", "point-1")
+            .await
+            .expect("last value")
+            .expect("point-1");
+        assert_eq!(last.ts_ms, 1_000);
+        let response = app
+            .clone()
+            .oneshot(request(
+                "GET",
+                "/projects/project-1/measurements?pointId=point-2",
+                None,
+            ))
+            .await
+            .expect("response");
+        let json = response_json(response).await;
+        assert_eq!(json["data"].as_array().map(Vec::len), Some(1));
+        assert_eq!(json["data"][0]["quality"], "good");
+
+        // 只有被接受的值计入 measurements 用量（重复、无效时间戳不计）
+        let response = app
+            .clone()
+            .oneshot(request("GET", "/usage", None))
+            .await
+            .expect("response");
+        let json = response_json(response).await;
+        let measurements = json["data"]["metrics"]
+            .as_array()
+            .and_then(|metrics| metrics.iter().find(|item| item["metric"] == "measurements"))
+            .cloned()
+            .unwrap_or_default();
+        assert_eq!(measurements["current"], 2);
+
+        // 整批按当前用量检查配额，超出配额返回 429（配额由运维端口设置）
+        let response = crate::ops::create_ops_router(
+            crate::ops::OpsState::new(None, serde_json::json!({}))
+                .with_usage(state.usage_store.clone(), state.usage_meter.clone()),
+        )
+        .oneshot(json_request(
+            &HeaderMap::new(),
+            "PUT",
+            "/ops/tenants/tenant-1/quotas/measurements",
+            Some(serde_json::json!({ "limit": 3 })),
+        ))
+        .await
+        .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/projects/project-1/points/values",
+                Some(serde_json::json!({ "values": [
+                    { "pointId": "point-1", "tsMs": 3_000, "value": 22 },
+                    { "pointId": "point-2", "tsMs": 3_000, "value": false },
+                ] })),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    /// 测试：HTTP 写入的测量值刷新所属设备及其网关的在线状态（同批取最大时间戳）
+    #[tokio::test]
+    async fn point_values_refresh_device_and_gateway_online() {
+        let state = build_state();
+        let headers = auth_headers(&state).await;
+        let ctx = project_ctx();
+        state
+            .device_store
+            .create_device(
+                &ctx,
+                ems_storage::DeviceRecord {
+                    device_id: "device-1".to_string(),
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    gateway_id: "gateway-1".to_string(),
+                    name: "meter".to_string(),
+                    model: None,
+                    room_id: None,
+                    address_config: None,
+                    offline_after_seconds: None,
+                },
+            )
+            .await
+            .expect("device");
+        for point_id in ["point-1", "point-2"] {
+            state
+                .point_store
+                .create_point(
+                    &ctx,
+                    ems_storage::PointRecord {
+                        point_id: point_id.to_string(),
+                        tenant_id: "tenant-1".to_string(),
+                        project_id: "project-1".to_string(),
+                        device_id: "device-1".to_string(),
+                        key: point_id.to_string(),
+                        data_type: "float".to_string(),
+                        unit: None,
+                        tags: Vec::new(),
+                    },
+                )
+                .await
+                .expect("point");
+        }
+
+        let now_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("clock")
+            .as_millis() as i64;
+        let body = serde_json::json!({
+            "values": [
+                { "pointId": "point-1", "value": 1.5, "tsMs": now_ms - 1_000 },
+                { "pointId": "point-2", "value": 2.5, "tsMs": now_ms },
+            ]
+        });
+        let response = api_router(state.clone())
+            .oneshot(json_request(
+                &headers,
+                "POST",
+                "/api/v1/projects/project-1/points/values",
+                Some(body),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let device_seen = state
+            .online_store
+            .get_device_last_seen_at_ms(&ctx, "project-1", "device-1")
+            .await
+            .expect("device online");
+        assert_eq!(device_seen, Some(now_ms));
+        let gateway_seen = state
+            .online_store
+            .get_gateway_last_seen_at_ms(&ctx, "project-1", "gateway-1")
+            .await
+            .expect("gateway online");
+        assert_eq!(gateway_seen, Some(now_ms));
+    }
+}
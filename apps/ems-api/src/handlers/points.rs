@@ -2,7 +2,7 @@
 //!
 //! 提供点资源的增删改查接口：
 //! - GET /projects/{id}/points - 列出点
-//! - POST /projects/{id}/points - 创建点（需验证设备存在）
+//! - POST /projects/{id}/points - 创建点（需验证设备存在；超出 `points` 配额返回 429 QUOTA.EXCEEDED）
 //! - GET /projects/{id}/points/{pid} - 获取点详情
 //! - PUT /projects/{id}/points/{pid} - 更新点
 //! - DELETE /projects/{id}/points/{pid} - 删除点
@@ -13,13 +13,16 @@
 //! - 创建点时需验证设备属于该项目
 
 use crate::AppState;
-use crate::middleware::{require_permission, require_project_scope};
+use crate::middleware::{require_permission, require_point_quota, require_project_scope};
 use crate::utils::response::{bad_request_error, not_found_error, storage_error};
-use crate::utils::{normalize_optional, normalize_required, point_to_dto};
-use api_contract::{ApiResponse, CreatePointRequest, PointDto, UpdatePointRequest};
+use crate::utils::{
+    collection_etag, etag_matches, list_success, normalize_optional, normalize_required,
+    normalize_tags, not_modified, parse_fields, point_to_dto, with_etag,
+};
+use api_contract::{ApiResponse, CreatePointRequest, FieldsQuery, PointDto, UpdatePointRequest};
 use axum::{
     Json,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
     response::{IntoResponse, Response},
 };
@@ -41,6 +44,7 @@ pub struct PointPath {
 pub async fn list_points(
     State(state): State<AppState>,
     Path(path): Path<ProjectPath>,
+    Query(fields): Query<FieldsQuery>,
     headers: HeaderMap,
 ) -> Response {
     let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
@@ -50,10 +54,27 @@ pub async fn list_points(
     if let Err(response) = require_permission(&ctx, permissions::ASSET_POINT_READ) {
         return response;
     }
+    let fields = match parse_fields(fields.fields) {
+        Ok(fields) => fields,
+        Err(response) => return response,
+    };
+    // 集合未变更时返回 304，省去重复的列表负载
+    let version = match state
+        .point_store
+        .points_version(&ctx, &path.project_id)
+        .await
+    {
+        Ok(version) => version,
+        Err(err) => return storage_error(err),
+    };
+    let etag = collection_etag(version, fields.as_deref(), "");
+    if etag_matches(&headers, &etag) {
+        return not_modified(&etag);
+    }
     match state.point_store.list_points(&ctx, &path.project_id).await {
         Ok(items) => {
             let data: Vec<PointDto> = items.into_iter().map(point_to_dto).collect();
-            (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+            with_etag(list_success(data, fields.as_deref()), &etag)
         }
         Err(err) => storage_error(err),
     }
@@ -85,6 +106,10 @@ pub async fn create_point(
         Ok(value) => value,
         Err(response) => return response,
     };
+    let tags = match normalize_tags(req.tags.unwrap_or_default(), "tag") {
+        Ok(value) => value,
+        Err(response) => return response,
+    };
     let exists = state
         .device_store
         .find_device(&ctx, &path.project_id, &device_id)
@@ -94,6 +119,9 @@ pub async fn create_point(
         Ok(None) => return bad_request_error("device not found"),
         Err(err) => return storage_error(err),
     }
+    if let Err(response) = require_point_quota(&state, &ctx, 1).await {
+        return response;
+    }
     let record = ems_storage::PointRecord {
         point_id: Uuid::new_v4().to_string(),
         tenant_id: ctx.tenant_id.clone(),
@@ -102,6 +130,7 @@ pub async fn create_point(
         key,
         data_type,
         unit: req.unit,
+        tags,
     };
     match state.point_store.create_point(&ctx, record).await {
         Ok(item) => (
@@ -167,13 +196,21 @@ pub async fn update_point(
         Ok(value) => value,
         Err(response) => return response,
     };
-    if key.is_none() && data_type.is_none() && unit.is_none() {
+    let tags = match req.tags {
+        Some(tags) => match normalize_tags(tags, "tag") {
+            Ok(value) => Some(value),
+            Err(response) => return response,
+        },
+        None => None,
+    };
+    if key.is_none() && data_type.is_none() && unit.is_none() && tags.is_none() {
         return bad_request_error("empty update");
     }
     let update = ems_storage::PointUpdate {
         key,
         data_type,
         unit,
+        tags,
     };
     match state
         .point_store
@@ -0,0 +1,703 @@
+//! 项目组合 handlers
+//!
+//! 项目组合是项目之上的可选分组层（多站点客户按区域 / 业态查看站点群）：
+//! - GET /portfolios - 列出项目组合
+//! - POST /portfolios - 创建项目组合
+//! - GET /portfolios/{id} - 获取项目组合
+//! - PUT /portfolios/{id} - 更新项目组合（`projectIds` 整体替换成员）
+//! - DELETE /portfolios/{id} - 删除项目组合（不影响成员项目）
+//! - GET /portfolios/{id}/overview - 组合概览：各成员项目与合计的用能、告警数、网关在线率
+//!
+//! 成员项目需归属当前租户；已删除的成员项目在概览中跳过。
+//! 用能按带 `counter` 与能源类型标签的累计量点位计算（同碳排放报表），
+//! 告警数为窗口内记录的用能异常数，在线率为当前在线网关数 / 网关总数。
+//!
+//! 权限要求：查询需要 PORTFOLIO.READ（概览另需 DATA.MEASUREMENTS.READ），
+//! 写入/删除需要 PORTFOLIO.WRITE
+
+use std::collections::BTreeMap;
+
+use crate::AppState;
+use crate::middleware::{require_permission, require_tenant_context};
+use crate::utils::response::{bad_request_error, not_found_error, storage_error};
+use crate::utils::{normalize_optional, normalize_required, normalize_tags};
+use api_contract::{
+    ApiResponse, CreatePortfolioRequest, EnergyConsumptionDto, PortfolioDto, PortfolioOverviewDto,
+    PortfolioOverviewQuery, PortfolioProjectOverviewDto, UpdatePortfolioRequest,
+};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use chrono_tz::Tz;
+use domain::{TenantContext, permissions};
+use ems_analytics::{CarbonReportWindow, CarbonReporter, MAX_REPORT_PERIODS, estimated_periods};
+use ems_storage::{
+    AnomalyQuery, CalendarBucket, PortfolioRecord, PortfolioUpdate, ProjectRecord, StorageError,
+};
+use uuid::Uuid;
+
+/// 单个组合最多包含的项目数（限制概览的聚合开销）
+const MAX_PORTFOLIO_PROJECTS: usize = 100;
+
+#[derive(serde::Deserialize)]
+pub struct PortfolioPath {
+    portfolio_id: String,
+}
+
+/// 列出项目组合
+pub async fn list_portfolios(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    let ctx = match require_tenant_context(&state, &headers) {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::PORTFOLIO_READ) {
+        return response;
+    }
+    match state.portfolio_store.list_portfolios(&ctx).await {
+        Ok(records) => {
+            let data: Vec<PortfolioDto> = records.into_iter().map(portfolio_to_dto).collect();
+            (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 创建项目组合
+pub async fn create_portfolio(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<CreatePortfolioRequest>,
+) -> Response {
+    let ctx = match require_tenant_context(&state, &headers) {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::PORTFOLIO_WRITE) {
+        return response;
+    }
+    let name = match normalize_required(req.name, "name") {
+        Ok(value) => value,
+        Err(response) => return response,
+    };
+    let description = match normalize_optional(req.description, "description") {
+        Ok(value) => value,
+        Err(response) => return response,
+    };
+    let project_ids = match validate_project_ids(&state, &ctx, req.project_ids).await {
+        Ok(value) => value,
+        Err(response) => return response,
+    };
+    let now_ms = now_epoch_ms();
+    let record = PortfolioRecord {
+        tenant_id: ctx.tenant_id.clone(),
+        portfolio_id: Uuid::new_v4().to_string(),
+        name,
+        description,
+        project_ids,
+        created_at_ms: now_ms,
+        updated_at_ms: now_ms,
+    };
+    match state.portfolio_store.create_portfolio(&ctx, record).await {
+        Ok(record) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(portfolio_to_dto(record))),
+        )
+            .into_response(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 获取项目组合
+pub async fn get_portfolio(
+    State(state): State<AppState>,
+    Path(path): Path<PortfolioPath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_tenant_context(&state, &headers) {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::PORTFOLIO_READ) {
+        return response;
+    }
+    match state
+        .portfolio_store
+        .find_portfolio(&ctx, &path.portfolio_id)
+        .await
+    {
+        Ok(Some(record)) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(portfolio_to_dto(record))),
+        )
+            .into_response(),
+        Ok(None) => not_found_error(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 更新项目组合
+pub async fn update_portfolio(
+    State(state): State<AppState>,
+    Path(path): Path<PortfolioPath>,
+    headers: HeaderMap,
+    Json(req): Json<UpdatePortfolioRequest>,
+) -> Response {
+    let ctx = match require_tenant_context(&state, &headers) {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::PORTFOLIO_WRITE) {
+        return response;
+    }
+    let name = match normalize_optional(req.name, "name") {
+        Ok(value) => value,
+        Err(response) => return response,
+    };
+    let description = match normalize_optional(req.description, "description") {
+        Ok(value) => value,
+        Err(response) => return response,
+    };
+    let project_ids = match req.project_ids {
+        Some(project_ids) => match validate_project_ids(&state, &ctx, project_ids).await {
+            Ok(value) => Some(value),
+            Err(response) => return response,
+        },
+        None => None,
+    };
+    let update = PortfolioUpdate {
+        name,
+        description,
+        project_ids,
+        updated_at_ms: now_epoch_ms(),
+    };
+    match state
+        .portfolio_store
+        .update_portfolio(&ctx, &path.portfolio_id, update)
+        .await
+    {
+        Ok(Some(record)) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(portfolio_to_dto(record))),
+        )
+            .into_response(),
+        Ok(None) => not_found_error(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 删除项目组合
+pub async fn delete_portfolio(
+    State(state): State<AppState>,
+    Path(path): Path<PortfolioPath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_tenant_context(&state, &headers) {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::PORTFOLIO_WRITE) {
+        return response;
+    }
+    match state
+        .portfolio_store
+        .delete_portfolio(&ctx, &path.portfolio_id)
+        .await
+    {
+        Ok(true) => (StatusCode::OK, Json(ApiResponse::success(()))).into_response(),
+        Ok(false) => not_found_error(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 项目组合概览
+pub async fn get_portfolio_overview(
+    State(state): State<AppState>,
+    Path(path): Path<PortfolioPath>,
+    Query(query): Query<PortfolioOverviewQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_tenant_context(&state, &headers) {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::PORTFOLIO_READ) {
+        return response;
+    }
+    if let Err(response) = require_permission(&ctx, permissions::DATA_MEASUREMENTS_READ) {
+        return response;
+    }
+    let (Some(from), Some(to)) = (query.from, query.to) else {
+        return bad_request_error("from and to are required");
+    };
+    if from >= to {
+        return bad_request_error("from must be < to");
+    }
+    if estimated_periods(CalendarBucket::Month, from, to) > MAX_REPORT_PERIODS {
+        return bad_request_error(format!(
+            "window too large: at most {MAX_REPORT_PERIODS} months"
+        ));
+    }
+    let portfolio = match state
+        .portfolio_store
+        .find_portfolio(&ctx, &path.portfolio_id)
+        .await
+    {
+        Ok(Some(record)) => record,
+        Ok(None) => return not_found_error(),
+        Err(err) => return storage_error(err),
+    };
+
+    let mut projects = Vec::with_capacity(portfolio.project_ids.len());
+    for project_id in &portfolio.project_ids {
+        let project = match state.project_store.find_project(&ctx, project_id).await {
+            Ok(Some(project)) => project,
+            Ok(None) => continue,
+            Err(err) => return storage_error(err),
+        };
+        match project_overview(&state, &ctx, project, from, to).await {
+            Ok(overview) => projects.push(overview),
+            Err(err) => return storage_error(err),
+        }
+    }
+
+    let mut consumption: BTreeMap<String, EnergyConsumptionDto> = BTreeMap::new();
+    for item in projects.iter().flat_map(|project| &project.consumption) {
+        consumption
+            .entry(item.energy_source.clone())
+            .or_insert_with(|| EnergyConsumptionDto {
+                energy_source: item.energy_source.clone(),
+                consumption: 0.0,
+                unit: item.unit.clone(),
+            })
+            .consumption += item.consumption;
+    }
+    let gateways = projects.iter().map(|project| project.gateways).sum();
+    let gateways_online = projects.iter().map(|project| project.gateways_online).sum();
+    let data = PortfolioOverviewDto {
+        portfolio_id: portfolio.portfolio_id,
+        from,
+        to,
+        consumption: consumption.into_values().collect(),
+        alarm_count: projects.iter().map(|project| project.alarm_count).sum(),
+        gateways,
+        gateways_online,
+        online_rate: online_rate(gateways_online, gateways),
+        projects,
+    };
+    (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+}
+
+/// 计算单个成员项目的概览
+async fn project_overview(
+    state: &AppState,
+    ctx: &TenantContext,
+    project: ProjectRecord,
+    from: i64,
+    to: i64,
+) -> Result<PortfolioProjectOverviewDto, StorageError> {
+    // 项目已确认归属当前租户，按成员项目收窄作用域
+    let mut scoped = ctx.clone();
+    scoped.project_scope = Some(project.project_id.clone());
+    let project_id = project.project_id.as_str();
+
+    let window = CarbonReportWindow {
+        from_ms: from,
+        to_ms: to,
+        bucket: CalendarBucket::Month,
+        timezone: project.timezone.trim().parse::<Tz>().unwrap_or(Tz::UTC),
+    };
+    let report = CarbonReporter::new(
+        state.point_store.clone(),
+        state.measurement_store.clone(),
+        state.emission_factor_store.clone(),
+    )
+    .report(&scoped, project_id, window)
+    .await?;
+
+    let alarm_count = state
+        .anomaly_store
+        .count_anomalies(
+            &scoped,
+            project_id,
+            AnomalyQuery {
+                from_ms: Some(from),
+                to_ms: Some(to),
+                ..AnomalyQuery::default()
+            },
+        )
+        .await?;
+
+    let gateway_ids: Vec<String> = state
+        .gateway_store
+        .list_gateways(&scoped, project_id)
+        .await?
+        .into_iter()
+        .map(|gateway| gateway.gateway_id)
+        .collect();
+    let gateways_online = state
+        .online_store
+        .list_gateways_last_seen_at_ms(&scoped, project_id, &gateway_ids)
+        .await
+        .map(|online| online.len())
+        .unwrap_or_default();
+
+    Ok(PortfolioProjectOverviewDto {
+        project_id: project.project_id,
+        name: project.name,
+        consumption: report
+            .totals
+            .into_iter()
+            .map(|usage| EnergyConsumptionDto {
+                energy_source: usage.energy_source,
+                consumption: usage.consumption,
+                unit: usage.unit,
+            })
+            .collect(),
+        alarm_count,
+        gateways: gateway_ids.len(),
+        gateways_online,
+        online_rate: online_rate(gateways_online, gateway_ids.len()),
+    })
+}
+
+/// 校验成员项目：去空格、去重，且均需归属当前租户
+async fn validate_project_ids(
+    state: &AppState,
+    ctx: &TenantContext,
+    project_ids: Vec<String>,
+) -> Result<Vec<String>, Response> {
+    let project_ids = normalize_tags(project_ids, "projectIds")?;
+    if project_ids.len() > MAX_PORTFOLIO_PROJECTS {
+        return Err(bad_request_error(format!(
+            "projectIds: at most {MAX_PORTFOLIO_PROJECTS} projects"
+        )));
+    }
+    for project_id in &project_ids {
+        match state
+            .project_store
+            .project_belongs_to_tenant(ctx, project_id)
+            .await
+        {
+            Ok(true) => {}
+            Ok(false) => return Err(bad_request_error(format!("unknown project: {project_id}"))),
+            Err(err) => return Err(storage_error(err)),
+        }
+    }
+    Ok(project_ids)
+}
+
+fn online_rate(online: usize, total: usize) -> Option<f64> {
+    (total > 0).then(|| online as f64 / total as f64)
+}
+
+fn portfolio_to_dto(record: PortfolioRecord) -> PortfolioDto {
+    PortfolioDto {
+        portfolio_id: record.portfolio_id,
+        name: record.name,
+        description: record.description,
+        project_ids: record.project_ids,
+        created_at_ms: record.created_at_ms,
+        updated_at_ms: record.updated_at_ms,
+    }
+}
+
+fn now_epoch_ms() -> i64 {
+    let now = std::time::SystemTime::now();
+    let duration = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    duration.as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{api_router, auth_headers, build_state, json_request, response_json};
+    use axum::http::StatusCode;
+    use domain::{PointValue, PointValueData, TenantContext};
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    /// 测试：项目组合 CRUD 与概览汇总各成员项目的用能、告警数与网关在线率
+    #[tokio::test]
+    async fn portfolio_overview_aggregates_member_projects() {
+        let state = build_state();
+        let headers = auth_headers(&state).await;
+        let tenant_ctx = TenantContext::new(
+            "tenant-1".to_string(),
+            "user-1".to_string(),
+            Vec::new(),
+            Vec::new(),
+            None,
+        );
+        state
+            .project_store
+            .create_project(
+                &tenant_ctx,
+                ems_storage::ProjectRecord {
+                    project_id: "project-2".to_string(),
+                    tenant_id: "tenant-1".to_string(),
+                    name: "Building B".to_string(),
+                    timezone: "UTC".to_string(),
+                },
+            )
+            .await
+            .expect("project");
+        // 2024-01-01T00:00:00Z
+        let day_start_ms: i64 = 1_704_067_200_000;
+        let hour_ms: i64 = 3_600_000;
+        for (project_id, kwh) in [("project-1", 30.0), ("project-2", 12.5)] {
+            let ctx = TenantContext::new(
+                "tenant-1".to_string(),
+                "user-1".to_string(),
+                Vec::new(),
+                Vec::new(),
+                Some(project_id.to_string()),
+            );
+            for (index, gateway_id) in [format!("{project_id}-gw-1"), format!("{project_id}-gw-2")]
+                .iter()
+                .enumerate()
+            {
+                state
+                    .gateway_store
+                    .create_gateway(
+                        &ctx,
+                        ems_storage::GatewayRecord {
+                            gateway_id: gateway_id.clone(),
+                            tenant_id: "tenant-1".to_string(),
+                            project_id: project_id.to_string(),
+                            name: "GW".to_string(),
+                            status: "offline".to_string(),
+                            protocol_type: "mqtt".to_string(),
+                            protocol_config: None,
+                        },
+                    )
+                    .await
+                    .expect("gateway");
+                // project-1 两台网关在线，project-2 仅一台在线
+                if index == 0 || project_id == "project-1" {
+                    state
+                        .online_store
+                        .touch_gateway(&ctx, project_id, gateway_id, day_start_ms)
+                        .await
+                        .expect("touch");
+                }
+            }
+            let point_id = format!("{project_id}-kwh");
+            state
+                .point_store
+                .create_point(
+                    &ctx,
+                    ems_storage::PointRecord {
+                        point_id: point_id.clone(),
+                        tenant_id: "tenant-1".to_string(),
+                        project_id: project_id.to_string(),
+                        device_id: "device-1".to_string(),
+                        key: "kwh".to_string(),
+                        data_type: "float".to_string(),
+                        unit: Some("kWh".to_string()),
+                        tags: vec!["counter".to_string(), "electricity".to_string()],
+                    },
+                )
+                .await
+                .expect("point");
+            for (offset_ms, value) in [(0, 100.0), (hour_ms, 100.0 + kwh)] {
+                state
+                    .measurement_store
+                    .write_measurement(
+                        &ctx,
+                        &PointValue {
+                            tenant_id: "tenant-1".to_string(),
+                            project_id: project_id.to_string(),
+                            point_id: point_id.clone(),
+                            ts_ms: day_start_ms + offset_ms,
+                            value: PointValueData::F64(value),
+                            quality: None,
+                        },
+                    )
+                    .await
+                    .expect("measurement");
+            }
+        }
+        let project_2_ctx = TenantContext::new(
+            "tenant-1".to_string(),
+            "user-1".to_string(),
+            Vec::new(),
+            Vec::new(),
+            Some("project-2".to_string()),
+        );
+        // 窗口内两条异常，窗口外一条
+        for (index, bucket_start_ms) in
+            [day_start_ms, day_start_ms + hour_ms, day_start_ms - hour_ms]
+                .into_iter()
+                .enumerate()
+        {
+            state
+                .anomaly_store
+                .insert_anomaly(
+                    &project_2_ctx,
+                    ems_storage::AnomalyRecord {
+                        tenant_id: "tenant-1".to_string(),
+                        project_id: "project-2".to_string(),
+                        anomaly_id: format!("anomaly-{index}"),
+                        point_id: "project-2-kwh".to_string(),
+                        device_id: "device-1".to_string(),
+                        bucket_start_ms,
+                        hour_of_week: 0,
+                        actual_value: 20.0,
+                        baseline_value: 10.0,
+                        baseline_samples: 4,
+                        deviation_pct: 100.0,
+                        threshold_pct: 50.0,
+                        detected_at_ms: bucket_start_ms + hour_ms,
+                    },
+                )
+                .await
+                .expect("anomaly");
+        }
+
+        let app = api_router(state.clone());
+        let request = |method: &str, uri: &str, body: Option<Value>| {
+            json_request(&headers, method, &format!("/api/v1{uri}"), body)
+        };
+
+        // 成员项目需归属当前租户
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/portfolios",
+                Some(serde_json::json!({ "name": "East", "projectIds": ["project-x"] })),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/portfolios",
+                Some(serde_json::json!({
+                    "name": "East",
+                    "description": "East coast sites",
+                    "projectIds": ["project-1", " project-2 ", "project-1"]
+                })),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let created = response_json(response).await;
+        let portfolio_id = created["data"]["portfolioId"]
+            .as_str()
+            .expect("portfolio id")
+            .to_string();
+        assert_eq!(
+            created["data"]["projectIds"],
+            serde_json::json!(["project-1", "project-2"])
+        );
+
+        let response = app
+            .clone()
+            .oneshot(request("GET", "/portfolios", None))
+            .await
+            .expect("response");
+        let listed = response_json(response).await;
+        assert_eq!(listed["data"].as_array().map(Vec::len), Some(1));
+
+        // from / to 必填
+        let response = app
+            .clone()
+            .oneshot(request(
+                "GET",
+                &format!("/portfolios/{portfolio_id}/overview"),
+                None,
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let overview_uri = format!(
+            "/portfolios/{portfolio_id}/overview?from={day_start_ms}&to={}",
+            day_start_ms + 24 * hour_ms
+        );
+        let response = app
+            .clone()
+            .oneshot(request("GET", &overview_uri, None))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let overview = response_json(response).await;
+        let data = &overview["data"];
+        assert_eq!(data["projects"].as_array().map(Vec::len), Some(2));
+        assert_eq!(data["projects"][0]["projectId"], "project-1");
+        assert_eq!(data["projects"][0]["consumption"][0]["consumption"], 30.0);
+        assert_eq!(data["projects"][0]["alarmCount"], 0);
+        assert_eq!(data["projects"][0]["onlineRate"], 1.0);
+        assert_eq!(data["projects"][1]["name"], "Building B");
+        assert_eq!(data["projects"][1]["alarmCount"], 2);
+        assert_eq!(data["projects"][1]["onlineRate"], 0.5);
+        assert_eq!(data["consumption"][0]["energySource"], "electricity");
+        assert_eq!(data["consumption"][0]["consumption"], 42.5);
+        assert_eq!(data["consumption"][0]["unit"], "kWh");
+        assert_eq!(data["alarmCount"], 2);
+        assert_eq!(data["gateways"], 4);
+        assert_eq!(data["gatewaysOnline"], 3);
+        assert_eq!(data["onlineRate"], 0.75);
+
+        // 整体替换成员
+        let response = app
+            .clone()
+            .oneshot(request(
+                "PUT",
+                &format!("/portfolios/{portfolio_id}"),
+                Some(serde_json::json!({ "projectIds": ["project-2"] })),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let updated = response_json(response).await;
+        assert_eq!(
+            updated["data"]["projectIds"],
+            serde_json::json!(["project-2"])
+        );
+        assert_eq!(updated["data"]["name"], "East");
+
+        let response = app
+            .clone()
+            .oneshot(request("GET", &overview_uri, None))
+            .await
+            .expect("response");
+        let overview = response_json(response).await;
+        assert_eq!(overview["data"]["gateways"], 2);
+        assert_eq!(overview["data"]["consumption"][0]["consumption"], 12.5);
+
+        // 删除组合不影响成员项目
+        let response = app
+            .clone()
+            .oneshot(request(
+                "DELETE",
+                &format!("/portfolios/{portfolio_id}"),
+                None,
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app
+            .clone()
+            .oneshot(request("GET", &format!("/portfolios/{portfolio_id}"), None))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert!(
+            state
+                .project_store
+                .find_project(&tenant_ctx, "project-2")
+                .await
+                .expect("find")
+                .is_some()
+        );
+    }
+}
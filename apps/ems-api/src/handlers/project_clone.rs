@@ -0,0 +1,543 @@
+//! 项目克隆 handler
+//!
+//! - POST /projects/{id}/clone - 把源项目的资产树复制到新项目（标准站点模板推广到新楼宇）
+//!
+//! 复制范围：网关（含命令载荷格式）、设备、点位、点位映射、设备模板、自动化规则（可选）。
+//! 所有资源重新分配 ID，设备所属网关、点位所属设备、映射目标点位、规则中的
+//! `pointId` / `deviceId` / 命令 `target` 按新 ID 重写；以下内容不复制
+//! （响应 `notCopied` 同步列出）：
+//! - 看板（前端本地配置，不是平台资源）与看板嵌入用的分享令牌
+//! - 时序数据、实时值、命令与审计等运行数据
+//! - 设备所属房间（楼宇层级不随项目复制，`roomId` 置空）
+//!
+//! 复制出的网关状态为 `offline`，规则均为停用状态（调试完成后再启用）。
+//! 新增点位计入租户 `points` 配额。
+//!
+//! 权限要求：PROJECT.WRITE（源项目需归属当前租户）
+
+use std::collections::HashMap;
+
+use crate::AppState;
+use crate::middleware::{require_permission, require_point_quota, require_project_scope};
+use crate::utils::response::{not_found_error, storage_error};
+use crate::utils::{normalize_optional, normalize_required, project_to_dto};
+use api_contract::{ApiResponse, CloneProjectRequest, ProjectCloneDto};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use domain::{TenantContext, permissions};
+use ems_storage::{ProjectClone, ProjectRecord, RuleRecord, StorageError};
+use serde_json::Value;
+use uuid::Uuid;
+
+/// 不随项目复制的内容（随响应返回，便于调用方提示人工补齐）
+const NOT_COPIED: &[&str] = &[
+    "dashboards",
+    "shareTokens",
+    "measurements",
+    "realtime",
+    "commands",
+    "audit",
+    "rooms",
+];
+
+#[derive(serde::Deserialize)]
+pub struct CloneProjectPath {
+    project_id: String,
+}
+
+/// 克隆项目
+pub async fn clone_project(
+    State(state): State<AppState>,
+    Path(path): Path<CloneProjectPath>,
+    headers: HeaderMap,
+    Json(req): Json<CloneProjectRequest>,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::PROJECT_WRITE) {
+        return response;
+    }
+    let name = match normalize_required(req.name, "name") {
+        Ok(value) => value,
+        Err(response) => return response,
+    };
+    let timezone = match normalize_optional(req.timezone, "timezone") {
+        Ok(value) => value,
+        Err(response) => return response,
+    };
+    let source = match state
+        .project_store
+        .find_project(&ctx, &path.project_id)
+        .await
+    {
+        Ok(Some(project)) => project,
+        Ok(None) => return not_found_error(),
+        Err(err) => return storage_error(err),
+    };
+    let target = ProjectRecord {
+        project_id: Uuid::new_v4().to_string(),
+        tenant_id: ctx.tenant_id.clone(),
+        name,
+        timezone: timezone.unwrap_or(source.timezone),
+    };
+    let clone = match build_clone(
+        &state,
+        &ctx,
+        &path.project_id,
+        target,
+        req.include_rules.unwrap_or(true),
+    )
+    .await
+    {
+        Ok(clone) => clone,
+        Err(err) => return storage_error(err),
+    };
+    if let Err(response) = require_point_quota(&state, &ctx, clone.points.len() as i64).await {
+        return response;
+    }
+    match state
+        .project_clone_store
+        .create_project_clone(&ctx, clone)
+        .await
+    {
+        Ok(clone) => {
+            let data = ProjectCloneDto {
+                source_project_id: path.project_id,
+                gateways: clone.gateways.len(),
+                gateway_command_formats: clone.gateway_command_formats.len(),
+                devices: clone.devices.len(),
+                points: clone.points.len(),
+                point_mappings: clone.mappings.len(),
+                device_templates: clone.device_templates.len(),
+                rules: clone.rules.len(),
+                project: project_to_dto(clone.project),
+                not_copied: NOT_COPIED.iter().map(|item| item.to_string()).collect(),
+            };
+            (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 读取源项目资产树并重新分配 ID
+async fn build_clone(
+    state: &AppState,
+    ctx: &TenantContext,
+    source_project_id: &str,
+    project: ProjectRecord,
+    include_rules: bool,
+) -> Result<ProjectClone, StorageError> {
+    let gateways = state
+        .gateway_store
+        .list_gateways(ctx, source_project_id)
+        .await?;
+    let devices = state
+        .device_store
+        .list_devices(ctx, source_project_id)
+        .await?;
+    let points = state
+        .point_store
+        .list_points(ctx, source_project_id)
+        .await?;
+    let mappings = state
+        .point_mapping_store
+        .list_point_mappings(ctx, source_project_id)
+        .await?;
+    let device_templates = state
+        .device_template_store
+        .list_device_templates(ctx, source_project_id)
+        .await?;
+    let rules = if include_rules {
+        state.rule_store.list_rules(ctx, source_project_id).await?
+    } else {
+        Vec::new()
+    };
+
+    // 旧 ID → 新 ID（网关、设备、点位共用一张表，供规则引用重写）
+    let mut ids: HashMap<String, String> = HashMap::new();
+    for id in gateways
+        .iter()
+        .map(|item| &item.gateway_id)
+        .chain(devices.iter().map(|item| &item.device_id))
+        .chain(points.iter().map(|item| &item.point_id))
+    {
+        ids.insert(id.clone(), Uuid::new_v4().to_string());
+    }
+    let remap = |id: &str| ids.get(id).cloned().unwrap_or_else(|| id.to_string());
+    let mut gateway_command_formats = Vec::new();
+    for gateway in &gateways {
+        if let Some(format) = state
+            .gateway_store
+            .find_gateway_command_format(ctx, source_project_id, &gateway.gateway_id)
+            .await?
+        {
+            gateway_command_formats.push((remap(&gateway.gateway_id), format));
+        }
+    }
+    let tenant_id = project.tenant_id.clone();
+    let project_id = project.project_id.clone();
+    let now_ms = now_epoch_ms();
+
+    let gateways = gateways
+        .into_iter()
+        .map(|mut gateway| {
+            gateway.gateway_id = remap(&gateway.gateway_id);
+            gateway.tenant_id = tenant_id.clone();
+            gateway.project_id = project_id.clone();
+            gateway.status = "offline".to_string();
+            gateway
+        })
+        .collect();
+    let devices = devices
+        .into_iter()
+        .map(|mut device| {
+            device.device_id = remap(&device.device_id);
+            device.gateway_id = remap(&device.gateway_id);
+            device.tenant_id = tenant_id.clone();
+            device.project_id = project_id.clone();
+            device.room_id = None;
+            device
+        })
+        .collect();
+    let points = points
+        .into_iter()
+        .map(|mut point| {
+            point.point_id = remap(&point.point_id);
+            point.device_id = remap(&point.device_id);
+            point.tenant_id = tenant_id.clone();
+            point.project_id = project_id.clone();
+            point
+        })
+        .collect();
+    let mappings = mappings
+        .into_iter()
+        .map(|mut mapping| {
+            mapping.source_id = Uuid::new_v4().to_string();
+            mapping.point_id = remap(&mapping.point_id);
+            mapping.tenant_id = tenant_id.clone();
+            mapping.project_id = project_id.clone();
+            mapping
+        })
+        .collect();
+    let device_templates = device_templates
+        .into_iter()
+        .map(|mut template| {
+            template.template_id = Uuid::new_v4().to_string();
+            template.tenant_id = tenant_id.clone();
+            template.project_id = project_id.clone();
+            template
+        })
+        .collect();
+    let rules = rules
+        .into_iter()
+        .map(|rule| RuleRecord {
+            tenant_id: tenant_id.clone(),
+            project_id: project_id.clone(),
+            rule_id: Uuid::new_v4().to_string(),
+            name: rule.name,
+            enabled: false,
+            trigger: remap_rule_json(&rule.trigger, &ids),
+            actions: remap_rule_json(&rule.actions, &ids),
+            created_by: ctx.user_id.clone(),
+            created_at_ms: now_ms,
+            updated_at_ms: now_ms,
+        })
+        .collect();
+
+    Ok(ProjectClone {
+        project,
+        gateways,
+        gateway_command_formats,
+        devices,
+        points,
+        mappings,
+        device_templates,
+        rules,
+    })
+}
+
+/// 重写规则触发条件 / 动作 JSON 中引用的资源 ID
+///
+/// 触发条件的 `pointId` / `deviceId` 与命令动作的 `target` 命中映射表时替换为新 ID，
+/// 其余字段（以及无法解析的 JSON）原样保留。
+fn remap_rule_json(raw: &str, ids: &HashMap<String, String>) -> String {
+    let Ok(mut value) = serde_json::from_str::<Value>(raw) else {
+        return raw.to_string();
+    };
+    match &mut value {
+        Value::Array(actions) => {
+            for action in actions.iter_mut() {
+                if action.get("type").and_then(Value::as_str) == Some("command") {
+                    remap_field(action, "target", ids);
+                }
+            }
+        }
+        trigger => {
+            remap_field(trigger, "pointId", ids);
+            remap_field(trigger, "deviceId", ids);
+        }
+    }
+    value.to_string()
+}
+
+fn remap_field(value: &mut Value, field: &str, ids: &HashMap<String, String>) {
+    if let Some(slot) = value.get_mut(field)
+        && let Some(new_id) = slot.as_str().and_then(|id| ids.get(id))
+    {
+        *slot = Value::String(new_id.clone());
+    }
+}
+
+fn now_epoch_ms() -> i64 {
+    let now = std::time::SystemTime::now();
+    let duration = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    duration.as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{
+        api_router, auth_headers, build_state, json_request, project_ctx, response_json,
+    };
+    use axum::http::StatusCode;
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    /// 测试：项目克隆复制资产树并重写引用 ID，规则停用，点位计入配额
+    #[tokio::test]
+    async fn project_clone_copies_asset_tree_with_new_ids() {
+        let state = build_state();
+        let headers = auth_headers(&state).await;
+        let ctx = project_ctx();
+        state
+            .gateway_store
+            .create_gateway(
+                &ctx,
+                ems_storage::GatewayRecord {
+                    gateway_id: "gateway-1".to_string(),
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    name: "GW".to_string(),
+                    status: "online".to_string(),
+                    protocol_type: "mqtt".to_string(),
+                    protocol_config: None,
+                },
+            )
+            .await
+            .expect("gateway");
+        state
+            .device_store
+            .create_device(
+                &ctx,
+                ems_storage::DeviceRecord {
+                    device_id: "device-1".to_string(),
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    gateway_id: "gateway-1".to_string(),
+                    name: "Meter".to_string(),
+                    model: Some("PM5000".to_string()),
+                    room_id: Some("room-1".to_string()),
+                    address_config: None,
+                    offline_after_seconds: None,
+                },
+            )
+            .await
+            .expect("device");
+        state
+            .point_store
+            .create_point(
+                &ctx,
+                ems_storage::PointRecord {
+                    point_id: "point-1".to_string(),
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    device_id: "device-1".to_string(),
+                    key: "kw".to_string(),
+                    data_type: "float".to_string(),
+                    unit: Some("kW".to_string()),
+                    tags: vec!["kw".to_string()],
+                },
+            )
+            .await
+            .expect("point");
+        state
+            .point_mapping_store
+            .create_point_mapping(
+                &ctx,
+                ems_storage::PointMappingRecord {
+                    source_id: "source-1".to_string(),
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    point_id: "point-1".to_string(),
+                    source_type: "mqtt".to_string(),
+                    address: "meter/kw".to_string(),
+                    scale: Some(0.1),
+                    offset: None,
+                    protocol_detail: None,
+                },
+            )
+            .await
+            .expect("mapping");
+        state
+            .rule_store
+            .create_rule(
+                &ctx,
+                ems_storage::RuleRecord {
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    rule_id: "rule-1".to_string(),
+                    name: "High load".to_string(),
+                    enabled: true,
+                    trigger: serde_json::json!({ "type": "point", "pointId": "point-1", "op": "gt", "value": 100.0 }).to_string(),
+                    actions: serde_json::json!([{ "type": "command", "target": "device-1", "payload": { "shed": true } }]).to_string(),
+                    created_by: "user-1".to_string(),
+                    created_at_ms: 0,
+                    updated_at_ms: 0,
+                },
+            )
+            .await
+            .expect("rule");
+        state
+            .gateway_store
+            .set_gateway_command_format(&ctx, "project-1", "gateway-1", Some(r#"{"format":"raw"}"#))
+            .await
+            .expect("command format");
+
+        let app = api_router(state.clone());
+        let request = |body: Value| {
+            json_request(
+                &headers,
+                "POST",
+                "/api/v1/projects/project-1/clone",
+                Some(body),
+            )
+        };
+
+        // 点位配额不足时整体拒绝
+        state
+            .usage_store
+            .upsert_quota(
+                &ctx,
+                ems_storage::QuotaRecord {
+                    tenant_id: "tenant-1".to_string(),
+                    metric: "points".to_string(),
+                    limit: 1,
+                    updated_at_ms: 0,
+                },
+            )
+            .await
+            .expect("quota");
+        let response = app
+            .clone()
+            .oneshot(request(serde_json::json!({ "name": "Site B" })))
+            .await
+            .expect("clone");
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        state
+            .usage_store
+            .delete_quota(&ctx, "points")
+            .await
+            .expect("delete quota");
+
+        let response = app
+            .clone()
+            .oneshot(request(serde_json::json!({ "name": "Site B" })))
+            .await
+            .expect("clone");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        assert_eq!(json["data"]["gateways"], 1);
+        assert_eq!(json["data"]["devices"], 1);
+        assert_eq!(json["data"]["points"], 1);
+        assert_eq!(json["data"]["pointMappings"], 1);
+        assert_eq!(json["data"]["rules"], 1);
+        assert_eq!(json["data"]["gatewayCommandFormats"], 1);
+        assert!(
+            json["data"]["notCopied"]
+                .as_array()
+                .is_some_and(|items| items.iter().any(|item| item == "dashboards"))
+        );
+        assert_eq!(json["data"]["project"]["timezone"], "UTC");
+        let new_project_id = json["data"]["project"]["projectId"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        assert_ne!(new_project_id, "project-1");
+
+        let mut new_ctx = ctx.clone();
+        new_ctx.project_scope = Some(new_project_id.clone());
+        let gateways = state
+            .gateway_store
+            .list_gateways(&new_ctx, &new_project_id)
+            .await
+            .expect("gateways");
+        assert_eq!(gateways[0].status, "offline");
+        assert_ne!(gateways[0].gateway_id, "gateway-1");
+        let format = state
+            .gateway_store
+            .find_gateway_command_format(&new_ctx, &new_project_id, &gateways[0].gateway_id)
+            .await
+            .expect("command format");
+        assert_eq!(format.as_deref(), Some(r#"{"format":"raw"}"#));
+        let devices = state
+            .device_store
+            .list_devices(&new_ctx, &new_project_id)
+            .await
+            .expect("devices");
+        assert_eq!(devices[0].gateway_id, gateways[0].gateway_id);
+        assert_eq!(devices[0].model.as_deref(), Some("PM5000"));
+        assert!(devices[0].room_id.is_none());
+        let points = state
+            .point_store
+            .list_points(&new_ctx, &new_project_id)
+            .await
+            .expect("points");
+        assert_eq!(points[0].device_id, devices[0].device_id);
+        let mappings = state
+            .point_mapping_store
+            .list_point_mappings(&new_ctx, &new_project_id)
+            .await
+            .expect("mappings");
+        assert_eq!(mappings[0].point_id, points[0].point_id);
+        assert_eq!(mappings[0].address, "meter/kw");
+        let rules = state
+            .rule_store
+            .list_rules(&new_ctx, &new_project_id)
+            .await
+            .expect("rules");
+        assert!(!rules[0].enabled);
+        let trigger: Value = serde_json::from_str(&rules[0].trigger).expect("trigger");
+        assert_eq!(trigger["pointId"], points[0].point_id.as_str());
+        let actions: Value = serde_json::from_str(&rules[0].actions).expect("actions");
+        assert_eq!(actions[0]["target"], devices[0].device_id.as_str());
+
+        // 源项目不受影响
+        let source_points = state
+            .point_store
+            .list_points(&ctx, "project-1")
+            .await
+            .expect("points");
+        assert_eq!(source_points.len(), 1);
+        assert_eq!(source_points[0].point_id, "point-1");
+
+        // 不包含规则
+        let response = app
+            .clone()
+            .oneshot(request(
+                serde_json::json!({ "name": "Site C", "timezone": "Asia/Shanghai", "includeRules": false }),
+            ))
+            .await
+            .expect("clone");
+        let json = response_json(response).await;
+        assert_eq!(json["data"]["rules"], 0);
+        assert_eq!(json["data"]["project"]["timezone"], "Asia/Shanghai");
+    }
+}
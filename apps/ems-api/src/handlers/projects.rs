@@ -14,11 +14,16 @@
 use crate::AppState;
 use crate::middleware::{require_permission, require_tenant_context};
 use crate::utils::response::{bad_request_error, not_found_error, storage_error};
-use crate::utils::{normalize_optional, normalize_required, project_to_dto};
-use api_contract::{ApiResponse, CreateProjectRequest, ProjectDto, UpdateProjectRequest};
+use crate::utils::{
+    collection_etag, etag_matches, list_success, normalize_optional, normalize_required,
+    not_modified, parse_fields, project_to_dto, with_etag,
+};
+use api_contract::{
+    ApiResponse, CreateProjectRequest, FieldsQuery, ProjectDto, UpdateProjectRequest,
+};
 use axum::{
     Json,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
     response::{IntoResponse, Response},
 };
@@ -31,7 +36,11 @@ pub struct ProjectPath {
 }
 
 /// 列出项目
-pub async fn list_projects(State(state): State<AppState>, headers: HeaderMap) -> Response {
+pub async fn list_projects(
+    State(state): State<AppState>,
+    Query(fields): Query<FieldsQuery>,
+    headers: HeaderMap,
+) -> Response {
     let ctx = match require_tenant_context(&state, &headers) {
         Ok(ctx) => ctx,
         Err(response) => return response,
@@ -39,10 +48,23 @@ pub async fn list_projects(State(state): State<AppState>, headers: HeaderMap) ->
     if let Err(response) = require_permission(&ctx, permissions::PROJECT_READ) {
         return response;
     }
+    let fields = match parse_fields(fields.fields) {
+        Ok(fields) => fields,
+        Err(response) => return response,
+    };
+    // 集合未变更时返回 304，省去重复的列表负载
+    let version = match state.project_store.projects_version(&ctx).await {
+        Ok(version) => version,
+        Err(err) => return storage_error(err),
+    };
+    let etag = collection_etag(version, fields.as_deref(), "");
+    if etag_matches(&headers, &etag) {
+        return not_modified(&etag);
+    }
     match state.project_store.list_projects(&ctx).await {
         Ok(projects) => {
             let data: Vec<ProjectDto> = projects.into_iter().map(project_to_dto).collect();
-            (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+            with_etag(list_success(data, fields.as_deref()), &etag)
         }
         Err(err) => storage_error(err),
     }
@@ -179,109 +201,28 @@ pub async fn delete_project(
 mod tests {
     use super::*;
     use crate::middleware::require_project_scope;
-    use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
-    use ems_auth::{AuthService, JwtManager};
-    use ems_storage::{InMemoryProjectStore, InMemoryUserStore, ProjectStore};
-    use std::sync::Arc;
+    use crate::test_support::{auth_headers, build_state, context_headers};
+    use axum::http::StatusCode;
 
     #[tokio::test]
     async fn projects_list_requires_permission() {
-        let user_store = Arc::new(InMemoryUserStore::with_default_admin());
-        let jwt = JwtManager::new("secret".to_string(), 3600, 3600);
-        let auth = Arc::new(AuthService::new(user_store.clone(), jwt));
-        let project_store: Arc<dyn ProjectStore> =
-            Arc::new(InMemoryProjectStore::with_default_project());
-        let command_store: Arc<dyn ems_storage::CommandStore> =
-            Arc::new(ems_storage::InMemoryCommandStore::new());
-        let command_receipt_store: Arc<dyn ems_storage::CommandReceiptStore> =
-            Arc::new(ems_storage::InMemoryCommandReceiptStore::new());
-        let audit_log_store: Arc<dyn ems_storage::AuditLogStore> =
-            Arc::new(ems_storage::InMemoryAuditLogStore::new());
-        let dispatcher = Arc::new(ems_control::NoopDispatcher::default());
-        let command_service = Arc::new(ems_control::CommandService::new(
-            command_store.clone(),
-            audit_log_store.clone(),
-            dispatcher,
+        let state = build_state();
+        let headers = context_headers(&domain::TenantContext::new(
+            "tenant-1".to_string(),
+            "user-1".to_string(),
+            Vec::new(),
+            Vec::new(),
+            None,
         ));
-        let state = AppState {
-            auth,
-            db_pool: None,
-            rbac_store: user_store,
-            project_store,
-            gateway_store: Arc::new(ems_storage::InMemoryGatewayStore::new()),
-            device_store: Arc::new(ems_storage::InMemoryDeviceStore::new()),
-            point_store: Arc::new(ems_storage::InMemoryPointStore::new()),
-            point_mapping_store: Arc::new(ems_storage::InMemoryPointMappingStore::new()),
-            measurement_store: Arc::new(ems_storage::InMemoryMeasurementStore::new()),
-            realtime_store: Arc::new(ems_storage::InMemoryRealtimeStore::new()),
-            online_store: Arc::new(ems_storage::InMemoryOnlineStore::new()),
-            command_store,
-            command_receipt_store,
-            audit_log_store,
-            command_service,
-        };
-
-        let jwt = JwtManager::new("secret".to_string(), 3600, 3600);
-        let tokens = jwt
-            .issue_tokens(&domain::TenantContext::new(
-                "tenant-1".to_string(),
-                "user-1".to_string(),
-                Vec::new(),
-                Vec::new(),
-                None,
-            ))
-            .expect("token");
-        let mut headers = HeaderMap::new();
-        headers.insert(
-            header::AUTHORIZATION,
-            HeaderValue::from_str(&format!("Bearer {}", tokens.access_token)).expect("header"),
-        );
-        let response = list_projects(State(state), headers).await;
+        let response =
+            list_projects(State(state), Query(FieldsQuery { fields: None }), headers).await;
         assert_eq!(response.status(), StatusCode::FORBIDDEN);
     }
 
     #[tokio::test]
     async fn project_scope_sets_context() {
-        let user_store = Arc::new(InMemoryUserStore::with_default_admin());
-        let jwt = JwtManager::new("secret".to_string(), 3600, 3600);
-        let auth = Arc::new(AuthService::new(user_store.clone(), jwt));
-        let project_store: Arc<dyn ProjectStore> =
-            Arc::new(InMemoryProjectStore::with_default_project());
-        let command_store: Arc<dyn ems_storage::CommandStore> =
-            Arc::new(ems_storage::InMemoryCommandStore::new());
-        let command_receipt_store: Arc<dyn ems_storage::CommandReceiptStore> =
-            Arc::new(ems_storage::InMemoryCommandReceiptStore::new());
-        let audit_log_store: Arc<dyn ems_storage::AuditLogStore> =
-            Arc::new(ems_storage::InMemoryAuditLogStore::new());
-        let dispatcher = Arc::new(ems_control::NoopDispatcher::default());
-        let command_service = Arc::new(ems_control::CommandService::new(
-            command_store.clone(),
-            audit_log_store.clone(),
-            dispatcher,
-        ));
-        let state = AppState {
-            auth,
-            db_pool: None,
-            rbac_store: user_store,
-            project_store,
-            gateway_store: Arc::new(ems_storage::InMemoryGatewayStore::new()),
-            device_store: Arc::new(ems_storage::InMemoryDeviceStore::new()),
-            point_store: Arc::new(ems_storage::InMemoryPointStore::new()),
-            point_mapping_store: Arc::new(ems_storage::InMemoryPointMappingStore::new()),
-            measurement_store: Arc::new(ems_storage::InMemoryMeasurementStore::new()),
-            realtime_store: Arc::new(ems_storage::InMemoryRealtimeStore::new()),
-            online_store: Arc::new(ems_storage::InMemoryOnlineStore::new()),
-            command_store,
-            command_receipt_store,
-            audit_log_store,
-            command_service,
-        };
-        let (_, tokens) = state.auth.login("admin", "admin123").await.expect("login");
-        let mut headers = HeaderMap::new();
-        headers.insert(
-            header::AUTHORIZATION,
-            HeaderValue::from_str(&format!("Bearer {}", tokens.access_token)).expect("header"),
-        );
+        let state = build_state();
+        let headers = auth_headers(&state).await;
         let ctx = require_project_scope(&state, &headers, "project-1")
             .await
             .expect("scope");
@@ -290,46 +231,8 @@ mod tests {
 
     #[tokio::test]
     async fn project_scope_rejects_mismatch() {
-        let user_store = Arc::new(InMemoryUserStore::with_default_admin());
-        let jwt = JwtManager::new("secret".to_string(), 3600, 3600);
-        let auth = Arc::new(AuthService::new(user_store.clone(), jwt));
-        let project_store: Arc<dyn ProjectStore> =
-            Arc::new(InMemoryProjectStore::with_default_project());
-        let command_store: Arc<dyn ems_storage::CommandStore> =
-            Arc::new(ems_storage::InMemoryCommandStore::new());
-        let command_receipt_store: Arc<dyn ems_storage::CommandReceiptStore> =
-            Arc::new(ems_storage::InMemoryCommandReceiptStore::new());
-        let audit_log_store: Arc<dyn ems_storage::AuditLogStore> =
-            Arc::new(ems_storage::InMemoryAuditLogStore::new());
-        let dispatcher = Arc::new(ems_control::NoopDispatcher::default());
-        let command_service = Arc::new(ems_control::CommandService::new(
-            command_store.clone(),
-            audit_log_store.clone(),
-            dispatcher,
-        ));
-        let state = AppState {
-            auth,
-            db_pool: None,
-            rbac_store: user_store,
-            project_store,
-            gateway_store: Arc::new(ems_storage::InMemoryGatewayStore::new()),
-            device_store: Arc::new(ems_storage::InMemoryDeviceStore::new()),
-            point_store: Arc::new(ems_storage::InMemoryPointStore::new()),
-            point_mapping_store: Arc::new(ems_storage::InMemoryPointMappingStore::new()),
-            measurement_store: Arc::new(ems_storage::InMemoryMeasurementStore::new()),
-            realtime_store: Arc::new(ems_storage::InMemoryRealtimeStore::new()),
-            online_store: Arc::new(ems_storage::InMemoryOnlineStore::new()),
-            command_store,
-            command_receipt_store,
-            audit_log_store,
-            command_service,
-        };
-        let (_, tokens) = state.auth.login("admin", "admin123").await.expect("login");
-        let mut headers = HeaderMap::new();
-        headers.insert(
-            header::AUTHORIZATION,
-            HeaderValue::from_str(&format!("Bearer {}", tokens.access_token)).expect("header"),
-        );
+        let state = build_state();
+        let headers = auth_headers(&state).await;
         let response = require_project_scope(&state, &headers, "project-2")
             .await
             .expect_err("forbidden");
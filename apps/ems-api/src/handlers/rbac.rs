@@ -390,69 +390,18 @@ pub async fn list_rbac_permissions(State(state): State<AppState>, headers: Heade
 #[cfg(test)]
 mod tests {
     use super::*;
-    use axum::http::{HeaderValue, header};
-    use ems_auth::{AuthService, JwtManager};
-    use std::sync::Arc;
-
-    fn build_state() -> AppState {
-        let user_store: Arc<ems_storage::InMemoryUserStore> =
-            Arc::new(ems_storage::InMemoryUserStore::with_default_admin());
-        let jwt = JwtManager::new("secret".to_string(), 3600, 3600);
-        let auth: Arc<AuthService> = Arc::new(AuthService::new(user_store.clone(), jwt));
-        let rbac_store: Arc<dyn ems_storage::RbacStore> = user_store;
-
-        let project_store: Arc<dyn ems_storage::ProjectStore> =
-            Arc::new(ems_storage::InMemoryProjectStore::with_default_project());
-        let command_store: Arc<dyn ems_storage::CommandStore> =
-            Arc::new(ems_storage::InMemoryCommandStore::new());
-        let command_receipt_store: Arc<dyn ems_storage::CommandReceiptStore> =
-            Arc::new(ems_storage::InMemoryCommandReceiptStore::new());
-        let audit_log_store: Arc<dyn ems_storage::AuditLogStore> =
-            Arc::new(ems_storage::InMemoryAuditLogStore::new());
-        let dispatcher = Arc::new(ems_control::NoopDispatcher::default());
-        let command_service = Arc::new(ems_control::CommandService::new(
-            command_store.clone(),
-            audit_log_store.clone(),
-            dispatcher,
-        ));
-
-        AppState {
-            auth,
-            db_pool: None,
-            rbac_store,
-            project_store,
-            gateway_store: Arc::new(ems_storage::InMemoryGatewayStore::new()),
-            device_store: Arc::new(ems_storage::InMemoryDeviceStore::new()),
-            point_store: Arc::new(ems_storage::InMemoryPointStore::new()),
-            point_mapping_store: Arc::new(ems_storage::InMemoryPointMappingStore::new()),
-            measurement_store: Arc::new(ems_storage::InMemoryMeasurementStore::new()),
-            realtime_store: Arc::new(ems_storage::InMemoryRealtimeStore::new()),
-            online_store: Arc::new(ems_storage::InMemoryOnlineStore::new()),
-            command_store,
-            command_receipt_store,
-            audit_log_store,
-            command_service,
-        }
-    }
+    use crate::test_support::{build_state, context_headers};
 
     #[tokio::test]
     async fn list_users_requires_permission() {
         let state = build_state();
-        let jwt = JwtManager::new("secret".to_string(), 3600, 3600);
-        let tokens = jwt
-            .issue_tokens(&domain::TenantContext::new(
-                "tenant-1".to_string(),
-                "user-1".to_string(),
-                Vec::new(),
-                Vec::new(),
-                None,
-            ))
-            .expect("token");
-        let mut headers = HeaderMap::new();
-        headers.insert(
-            header::AUTHORIZATION,
-            HeaderValue::from_str(&format!("Bearer {}", tokens.access_token)).expect("header"),
-        );
+        let headers = context_headers(&domain::TenantContext::new(
+            "tenant-1".to_string(),
+            "user-1".to_string(),
+            Vec::new(),
+            Vec::new(),
+            None,
+        ));
         let response = list_rbac_users(State(state), headers).await;
         assert_eq!(response.status(), StatusCode::FORBIDDEN);
     }
@@ -1,19 +1,37 @@
 //! 实时查询 handlers
 //!
-//! - GET /projects/{id}/realtime
+//! - GET /projects/{id}/realtime（支持 pointId，或 deviceId/tag 组合过滤）
+//! - GET /projects/{id}/realtime/ws（WebSocket 订阅：按间隔轮询最新值，仅推送 tsMs 变化的点位）
+//!
+//! 两个接口均可使用项目分享令牌（`?shareToken=` 或 Bearer）免登录访问。
+//! WebSocket 连接在凭据到期（JWT `exp` / 分享令牌 `expiresAt`）时关闭，
+//! 分享令牌另在每个推送间隔复查，撤销后立即断开。
 
 use crate::AppState;
-use crate::middleware::{require_permission, require_project_scope};
+use crate::middleware::{
+    StreamCredential, require_permission, require_project_read_access,
+    require_project_stream_access,
+};
 use crate::utils::normalize_optional;
-use crate::utils::response::storage_error;
-use api_contract::{ApiResponse, RealtimeQuery, RealtimeValueDto};
+use crate::utils::response::{bad_request_error, storage_error};
+use api_contract::{
+    ApiResponse, RealtimeQuery, RealtimeStreamQuery, RealtimeValueDto, ShareTokenQuery,
+};
 use axum::{
     Json,
+    extract::ws::{CloseFrame, Message, WebSocket, WebSocketUpgrade, close_code},
     extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
     response::{IntoResponse, Response},
 };
-use domain::permissions;
+use domain::{TenantContext, permissions};
+use ems_storage::{PointRecord, RealtimeRecord, StorageError};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// WebSocket 订阅默认/最小轮询间隔（毫秒，与 gRPC StreamRealtime 一致）
+const DEFAULT_STREAM_INTERVAL_MS: u64 = 1000;
+const MIN_STREAM_INTERVAL_MS: u64 = 200;
 
 #[derive(serde::Deserialize)]
 pub struct ProjectPath {
@@ -24,9 +42,17 @@ pub async fn get_realtime(
     State(state): State<AppState>,
     Path(path): Path<ProjectPath>,
     Query(query): Query<RealtimeQuery>,
+    Query(share): Query<ShareTokenQuery>,
     headers: HeaderMap,
 ) -> Response {
-    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+    let ctx = match require_project_read_access(
+        &state,
+        &headers,
+        share.share_token.as_deref(),
+        &path.project_id,
+    )
+    .await
+    {
         Ok(ctx) => ctx,
         Err(response) => return response,
     };
@@ -37,6 +63,17 @@ pub async fn get_realtime(
         Ok(value) => value,
         Err(response) => return response,
     };
+    let device_id = match normalize_optional(query.device_id, "deviceId") {
+        Ok(value) => value,
+        Err(response) => return response,
+    };
+    let tag = match normalize_optional(query.tag, "tag") {
+        Ok(value) => value,
+        Err(response) => return response,
+    };
+    if point_id.is_some() && (device_id.is_some() || tag.is_some()) {
+        return bad_request_error("pointId cannot be combined with deviceId or tag");
+    }
     let records = if let Some(point_id) = point_id {
         match state
             .realtime_store
@@ -47,6 +84,21 @@ pub async fn get_realtime(
             Ok(None) => Vec::new(),
             Err(err) => return storage_error(err),
         }
+    } else if device_id.is_some() || tag.is_some() {
+        // 先在点位表中解析出目标点位集合，再批量读取最新值
+        let points = match state.point_store.list_points(&ctx, &path.project_id).await {
+            Ok(points) => points,
+            Err(err) => return storage_error(err),
+        };
+        let point_ids = filter_point_ids(points, device_id.as_deref(), tag.as_deref());
+        match state
+            .realtime_store
+            .get_last_values(&ctx, &path.project_id, &point_ids)
+            .await
+        {
+            Ok(items) => items,
+            Err(err) => return storage_error(err),
+        }
     } else {
         match state
             .realtime_store
@@ -57,15 +109,536 @@ pub async fn get_realtime(
             Err(err) => return storage_error(err),
         }
     };
-    let data: Vec<RealtimeValueDto> = records
-        .into_iter()
-        .map(|record| RealtimeValueDto {
-            project_id: record.project_id,
-            point_id: record.point_id,
-            ts_ms: record.ts_ms,
-            value: record.value,
-            quality: record.quality,
-        })
-        .collect();
+    let data: Vec<RealtimeValueDto> = records.into_iter().map(realtime_to_dto).collect();
     (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
 }
+
+/// WebSocket 实时订阅
+///
+/// 订阅开始时解析点位集合，之后每个间隔读取一次最新值，
+/// 以文本帧推送 `RealtimeValueDto` JSON；读取失败时以 1011 + `internal error` 关闭连接（细节只写日志）。
+/// 凭据到期或分享令牌被撤销时以 1008 + `token expired` / `token revoked` 关闭连接。
+pub async fn stream_realtime_ws(
+    State(state): State<AppState>,
+    Path(path): Path<ProjectPath>,
+    Query(query): Query<RealtimeStreamQuery>,
+    Query(share): Query<ShareTokenQuery>,
+    headers: HeaderMap,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let (ctx, credential) = match require_project_stream_access(
+        &state,
+        &headers,
+        share.share_token.as_deref(),
+        &path.project_id,
+    )
+    .await
+    {
+        Ok(verified) => verified,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::DATA_REALTIME_READ) {
+        return response;
+    }
+    let device_id = match normalize_optional(query.device_id, "deviceId") {
+        Ok(value) => value,
+        Err(response) => return response,
+    };
+    let tag = match normalize_optional(query.tag, "tag") {
+        Ok(value) => value,
+        Err(response) => return response,
+    };
+    let point_ids: Option<Vec<String>> = query
+        .point_ids
+        .map(|value| {
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|point_id| !point_id.is_empty())
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        })
+        .filter(|point_ids| !point_ids.is_empty());
+    if point_ids.is_some() && (device_id.is_some() || tag.is_some()) {
+        return bad_request_error("pointIds cannot be combined with deviceId or tag");
+    }
+    // None 表示订阅项目内全部点位
+    let point_ids = if point_ids.is_some() {
+        point_ids
+    } else if device_id.is_some() || tag.is_some() {
+        match state.point_store.list_points(&ctx, &path.project_id).await {
+            Ok(points) => Some(filter_point_ids(
+                points,
+                device_id.as_deref(),
+                tag.as_deref(),
+            )),
+            Err(err) => return storage_error(err),
+        }
+    } else {
+        None
+    };
+    let interval_ms = query
+        .interval_ms
+        .unwrap_or(DEFAULT_STREAM_INTERVAL_MS)
+        .max(MIN_STREAM_INTERVAL_MS);
+    ws.on_upgrade(move |socket| {
+        push_realtime(
+            socket,
+            state,
+            ctx,
+            credential,
+            path.project_id,
+            point_ids,
+            interval_ms,
+        )
+    })
+}
+
+/// 推送循环：客户端关闭、发送失败或凭据失效时退出
+async fn push_realtime(
+    mut socket: WebSocket,
+    state: AppState,
+    ctx: TenantContext,
+    credential: StreamCredential,
+    project_id: String,
+    point_ids: Option<Vec<String>>,
+    interval_ms: u64,
+) {
+    let mut last_sent: HashMap<String, i64> = HashMap::new();
+    let mut ticker = tokio::time::interval(Duration::from_millis(interval_ms));
+    let expires_in = Duration::from_millis(
+        u64::try_from(credential.expires_at_ms().saturating_sub(now_epoch_ms())).unwrap_or(0),
+    );
+    let token_expired = tokio::time::sleep(expires_in);
+    tokio::pin!(token_expired);
+    loop {
+        tokio::select! {
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => continue,
+            },
+            _ = &mut token_expired => {
+                tracing::info!(target: "ems.realtime", project_id = %project_id, "realtime_ws_token_expired");
+                close_policy(&mut socket, "token expired").await;
+                return;
+            }
+            _ = ticker.tick() => {}
+        }
+        match credential_close_reason(&state, &credential).await {
+            Ok(None) => {}
+            Ok(Some(reason)) => {
+                tracing::info!(target: "ems.realtime", project_id = %project_id, reason, "realtime_ws_token_inactive");
+                close_policy(&mut socket, reason).await;
+                return;
+            }
+            Err(err) => {
+                tracing::warn!(
+                    target: "ems.realtime",
+                    project_id = %project_id,
+                    error = %err,
+                    "realtime_ws_token_check_failed"
+                );
+                let frame = CloseFrame {
+                    code: close_code::ERROR,
+                    reason: "internal error".into(),
+                };
+                let _ = socket.send(Message::Close(Some(frame))).await;
+                return;
+            }
+        }
+        let records = match &point_ids {
+            Some(point_ids) => {
+                state
+                    .realtime_store
+                    .get_last_values(&ctx, &project_id, point_ids)
+                    .await
+            }
+            None => {
+                state
+                    .realtime_store
+                    .list_last_values(&ctx, &project_id)
+                    .await
+            }
+        };
+        let records = match records {
+            Ok(records) => records,
+            Err(err) => {
+                // 底层错误只写日志，关闭帧只带固定原因
+                tracing::warn!(
+                    target: "ems.realtime",
+                    project_id = %project_id,
+                    error = %err,
+                    "realtime_ws_read_failed"
+                );
+                let frame = CloseFrame {
+                    code: close_code::ERROR,
+                    reason: "internal error".into(),
+                };
+                let _ = socket.send(Message::Close(Some(frame))).await;
+                return;
+            }
+        };
+        for record in records {
+            if last_sent.get(&record.point_id) == Some(&record.ts_ms) {
+                continue;
+            }
+            last_sent.insert(record.point_id.clone(), record.ts_ms);
+            let Ok(text) = serde_json::to_string(&realtime_to_dto(record)) else {
+                continue;
+            };
+            if socket.send(Message::Text(text)).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// 复核连接凭据，失效时返回关闭原因
+///
+/// 分享令牌重新按摘要查询，须仍存在且未撤销、未过期；登录令牌无法撤销，只依赖到期计时器。
+async fn credential_close_reason(
+    state: &AppState,
+    credential: &StreamCredential,
+) -> Result<Option<&'static str>, StorageError> {
+    let StreamCredential::ShareToken { token_hash, .. } = credential else {
+        return Ok(None);
+    };
+    let record = state
+        .share_token_store
+        .find_share_token_by_hash(token_hash)
+        .await?;
+    Ok(match record {
+        Some(record) if record.is_active(now_epoch_ms()) => None,
+        Some(record) if record.revoked_at_ms.is_none() => Some("token expired"),
+        _ => Some("token revoked"),
+    })
+}
+
+/// 以 1008（策略违规）关闭连接
+async fn close_policy(socket: &mut WebSocket, reason: &'static str) {
+    let frame = CloseFrame {
+        code: close_code::POLICY,
+        reason: reason.into(),
+    };
+    let _ = socket.send(Message::Close(Some(frame))).await;
+}
+
+/// 按设备 / 标签筛选点位 ID
+fn filter_point_ids(
+    points: Vec<PointRecord>,
+    device_id: Option<&str>,
+    tag: Option<&str>,
+) -> Vec<String> {
+    points
+        .into_iter()
+        .filter(|point| device_id.is_none_or(|device_id| point.device_id == device_id))
+        .filter(|point| tag.is_none_or(|tag| point.tags.iter().any(|item| item == tag)))
+        .map(|point| point.point_id)
+        .collect()
+}
+
+fn realtime_to_dto(record: RealtimeRecord) -> RealtimeValueDto {
+    RealtimeValueDto {
+        project_id: record.project_id,
+        point_id: record.point_id,
+        ts_ms: record.ts_ms,
+        value: record.value,
+        quality: record.quality,
+    }
+}
+
+fn now_epoch_ms() -> i64 {
+    let now = std::time::SystemTime::now();
+    let duration = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    duration.as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{auth_headers, build_state, project_ctx, response_json};
+    use crate::{middleware, routes};
+    use api_contract::{RealtimeQuery, ShareTokenQuery};
+    use axum::extract::{Path, Query, State};
+    use axum::http::StatusCode;
+    use domain::{PointValue, PointValueData};
+
+    /// 测试：获取实时数据（GET /projects/{project_id}/realtime）
+    ///
+    /// 验证实时数据 API 能够正确返回存储的测点最新值。
+    ///
+    /// ## 测试步骤
+    ///
+    /// 1. 创建测试 AppState（内存存储）
+    /// 2. 向实时存储中写入一条测点数据
+    /// 3. 调用 `get_realtime` 处理器
+    /// 4. 验证响应状态码为 200 OK
+    /// 5. 验证响应体包含正确的数据
+    #[tokio::test]
+    async fn realtime_returns_values() {
+        // 准备测试环境
+        let state = build_state();
+
+        // 创建租户上下文（模拟已认证用户的请求上下文）
+        let ctx = project_ctx();
+
+        // 创建测试数据：一条测点值
+        let value = PointValue {
+            tenant_id: "tenant-1".to_string(),
+            project_id: "project-1".to_string(),
+            point_id: "point-1".to_string(),
+            ts_ms: 1_700_000_000_000,          // 时间戳（毫秒）
+            value: PointValueData::F64(12.34), // 浮点数值
+            quality: None,                     // 质量标识（无）
+        };
+
+        // 写入实时存储
+        state
+            .realtime_store
+            .upsert_last_value(&ctx, &value)
+            .await
+            .expect("upsert last value");
+
+        // 调用处理器并验证响应
+        let headers = auth_headers(&state).await;
+        let response = get_realtime(
+            State(state),
+            Path(crate::handlers::realtime::ProjectPath {
+                project_id: "project-1".to_string(),
+            }),
+            Query(RealtimeQuery {
+                point_id: None,
+                device_id: None,
+                tag: None,
+            }), // 查询所有测点
+            Query(ShareTokenQuery { share_token: None }),
+            headers,
+        )
+        .await;
+
+        // 验证 HTTP 状态码
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // 验证响应体内容
+        let json = response_json(response).await;
+        assert_eq!(json["success"], true);
+        assert_eq!(json["data"].as_array().map(|v| v.len()), Some(1));
+    }
+
+    /// 测试：按设备与标签查询实时数据
+    ///
+    /// 验证 `deviceId` / `tag` 过滤会先解析点位集合，再批量返回其最新值。
+    #[tokio::test]
+    async fn realtime_filters_by_device_and_tag() {
+        let state = build_state();
+        let ctx = project_ctx();
+
+        // 两个设备各一个点位，仅 point-1 带有 energy 标签
+        for (point_id, device_id, tags) in [
+            ("point-1", "device-1", vec!["energy".to_string()]),
+            ("point-2", "device-2", Vec::new()),
+        ] {
+            state
+                .point_store
+                .create_point(
+                    &ctx,
+                    ems_storage::PointRecord {
+                        point_id: point_id.to_string(),
+                        tenant_id: "tenant-1".to_string(),
+                        project_id: "project-1".to_string(),
+                        device_id: device_id.to_string(),
+                        key: point_id.to_string(),
+                        data_type: "f64".to_string(),
+                        unit: None,
+                        tags,
+                    },
+                )
+                .await
+                .expect("create point");
+            let value = PointValue {
+                tenant_id: "tenant-1".to_string(),
+                project_id: "project-1".to_string(),
+                point_id: point_id.to_string(),
+                ts_ms: 1_700_000_000_000,
+                value: PointValueData::F64(1.0),
+                quality: None,
+            };
+            state
+                .realtime_store
+                .upsert_last_value(&ctx, &value)
+                .await
+                .expect("upsert last value");
+        }
+
+        let headers = auth_headers(&state).await;
+        for (device_id, tag, expected) in [
+            (Some("device-2"), None, "point-2"),
+            (None, Some("energy"), "point-1"),
+        ] {
+            let response = get_realtime(
+                State(state.clone()),
+                Path(crate::handlers::realtime::ProjectPath {
+                    project_id: "project-1".to_string(),
+                }),
+                Query(RealtimeQuery {
+                    point_id: None,
+                    device_id: device_id.map(str::to_string),
+                    tag: tag.map(str::to_string),
+                }),
+                Query(ShareTokenQuery { share_token: None }),
+                headers.clone(),
+            )
+            .await;
+            assert_eq!(response.status(), StatusCode::OK);
+            let json = response_json(response).await;
+            let data = json["data"].as_array().expect("data");
+            assert_eq!(data.len(), 1);
+            assert_eq!(data[0]["pointId"], expected);
+        }
+    }
+
+    /// 测试：ems-client 经 WebSocket 订阅实时数据（登录、握手与推送端到端）
+    #[tokio::test]
+    async fn realtime_ws_streams_values_to_client() {
+        let state = build_state();
+        let app = routes::create_api_router(middleware::ApiVersionPolicy::new(None))
+            .with_state(state.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind");
+        let addr = listener.local_addr().expect("addr");
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("serve");
+        });
+
+        let ctx = project_ctx();
+        state
+            .realtime_store
+            .upsert_last_value(
+                &ctx,
+                &PointValue {
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    point_id: "point-1".to_string(),
+                    ts_ms: 1_700_000_000_000,
+                    value: PointValueData::F64(12.34),
+                    quality: None,
+                },
+            )
+            .await
+            .expect("upsert last value");
+
+        let client = ems_client::EmsClient::new(format!("http://{addr}"));
+        client.login("admin", "admin123").await.expect("login");
+        let mut subscription = client
+            .subscribe_realtime(
+                "project-1",
+                &api_contract::RealtimeStreamQuery {
+                    point_ids: Some("point-1".to_string()),
+                    device_id: None,
+                    tag: None,
+                    interval_ms: Some(200),
+                },
+            )
+            .await
+            .expect("subscribe");
+        let value = subscription.next().await.expect("value").expect("decode");
+        assert_eq!(value.point_id, "point-1");
+        assert_eq!(value.ts_ms, 1_700_000_000_000);
+        subscription.close().await.expect("close");
+    }
+
+    /// 测试：分享令牌订阅在撤销或到期后被服务端以 1008 关闭
+    #[tokio::test]
+    async fn realtime_ws_closes_when_share_token_revoked_or_expired() {
+        let state = build_state();
+        let app = routes::create_api_router(middleware::ApiVersionPolicy::new(None))
+            .with_state(state.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind");
+        let addr = listener.local_addr().expect("addr");
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("serve");
+        });
+
+        let ctx = project_ctx();
+        state
+            .realtime_store
+            .upsert_last_value(
+                &ctx,
+                &PointValue {
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    point_id: "point-1".to_string(),
+                    ts_ms: 1_700_000_000_000,
+                    value: PointValueData::F64(12.34),
+                    quality: None,
+                },
+            )
+            .await
+            .expect("upsert last value");
+
+        let now_ms = now_epoch_ms();
+        for (token_id, expires_at_ms, revoke, expected) in [
+            ("share-1", now_ms + 3_600_000, true, "token revoked"),
+            ("share-2", now_ms + 1_000, false, "token expired"),
+        ] {
+            let token = format!("{}{token_id}", domain::share::SHARE_TOKEN_PREFIX);
+            state
+                .share_token_store
+                .create_share_token(
+                    &ctx,
+                    ems_storage::ShareTokenRecord {
+                        tenant_id: "tenant-1".to_string(),
+                        project_id: "project-1".to_string(),
+                        token_id: token_id.to_string(),
+                        token_hash: middleware::share_token_hash(&token),
+                        name: token_id.to_string(),
+                        scopes: vec![domain::share::REALTIME.to_string()],
+                        created_by: "user-1".to_string(),
+                        expires_at_ms,
+                        revoked_at_ms: None,
+                        created_at_ms: now_ms,
+                    },
+                )
+                .await
+                .expect("create share token");
+
+            let client =
+                ems_client::EmsClient::new(format!("http://{addr}")).with_tokens(token, None);
+            let mut subscription = client
+                .subscribe_realtime(
+                    "project-1",
+                    &api_contract::RealtimeStreamQuery {
+                        point_ids: Some("point-1".to_string()),
+                        device_id: None,
+                        tag: None,
+                        interval_ms: Some(200),
+                    },
+                )
+                .await
+                .expect("subscribe");
+            let value = subscription.next().await.expect("value").expect("decode");
+            assert_eq!(value.point_id, "point-1");
+
+            if revoke {
+                state
+                    .share_token_store
+                    .revoke_share_token(&ctx, "project-1", token_id, now_epoch_ms())
+                    .await
+                    .expect("revoke share token");
+            }
+            let err = tokio::time::timeout(Duration::from_secs(5), subscription.next())
+                .await
+                .expect("closed before timeout")
+                .expect("close frame")
+                .expect_err("policy close");
+            let message = err.to_string();
+            assert!(message.contains("1008"), "{message}");
+            assert!(message.contains(expected), "{message}");
+        }
+    }
+}
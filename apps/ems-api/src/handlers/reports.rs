@@ -0,0 +1,334 @@
+//! 报表 handlers
+//!
+//! - GET /projects/{id}/reports/power-quality - 按设备计算功率因数、负荷率与峰值需量
+//! - POST /projects/{id}/reports/power-quality/jobs - 以后台任务计算同一报表（长时间范围 / 设备较多时使用），
+//!   返回 202 与任务记录，结果经 `GET /jobs/{id}` 获取
+//!
+//! 点位角色由标签配置：`kw`（或 `power`）为有功功率，`kva` 为视在功率，`kwh` 为有功电量累计值。
+//!
+//! 权限要求：DATA.MEASUREMENTS.READ；提交后台任务另需 JOB.WRITE
+
+use crate::AppState;
+use crate::jobs::POWER_QUALITY_REPORT_JOB;
+use crate::middleware::{require_permission, require_project_scope};
+use crate::utils::response::{bad_request_error, job_to_dto, storage_error};
+use api_contract::{ApiResponse, DevicePowerQualityDto, PowerQualityQuery, PowerQualityReportDto};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use domain::permissions;
+use ems_analytics::{
+    DEFAULT_DEMAND_SUBINTERVAL_MINUTES, DEFAULT_DEMAND_WINDOW_MINUTES, DemandWindow,
+    DevicePowerQuality, MAX_DEMAND_SUBINTERVALS, PowerQualityAnalyzer, estimated_subintervals,
+};
+use ems_jobs::JobError;
+
+#[derive(serde::Deserialize)]
+pub struct ReportProjectPath {
+    project_id: String,
+}
+
+/// 电能质量报表
+pub async fn get_power_quality_report(
+    State(state): State<AppState>,
+    Path(path): Path<ReportProjectPath>,
+    Query(query): Query<PowerQualityQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::DATA_MEASUREMENTS_READ) {
+        return response;
+    }
+    let PowerQualityParams {
+        from,
+        to,
+        device_id,
+        window,
+    } = match PowerQualityParams::parse(query) {
+        Ok(params) => params,
+        Err(message) => return bad_request_error(message),
+    };
+    let analyzer =
+        PowerQualityAnalyzer::new(state.point_store.clone(), state.measurement_store.clone());
+    match analyzer
+        .analyze(
+            &ctx,
+            &path.project_id,
+            device_id.as_deref(),
+            from,
+            to,
+            window,
+        )
+        .await
+    {
+        Ok(devices) => {
+            let data = PowerQualityReportDto {
+                project_id: path.project_id,
+                from,
+                to,
+                window_minutes: window.window_minutes,
+                subinterval_minutes: window.subinterval_minutes,
+                devices: devices
+                    .into_iter()
+                    .map(device_power_quality_to_dto)
+                    .collect(),
+            };
+            (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 提交电能质量报表后台任务
+pub async fn submit_power_quality_report_job(
+    State(state): State<AppState>,
+    Path(path): Path<ReportProjectPath>,
+    headers: HeaderMap,
+    Json(req): Json<PowerQualityQuery>,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::DATA_MEASUREMENTS_READ) {
+        return response;
+    }
+    if let Err(response) = require_permission(&ctx, permissions::JOB_WRITE) {
+        return response;
+    }
+    let params = match PowerQualityParams::parse(req) {
+        Ok(params) => params,
+        Err(message) => return bad_request_error(message),
+    };
+    let params = match serde_json::to_value(params.to_query()) {
+        Ok(params) => params,
+        Err(err) => return bad_request_error(err.to_string()),
+    };
+    match state
+        .job_runner
+        .submit(
+            &ctx,
+            Some(&path.project_id),
+            POWER_QUALITY_REPORT_JOB,
+            params,
+        )
+        .await
+    {
+        Ok(record) => (
+            StatusCode::ACCEPTED,
+            Json(ApiResponse::success(job_to_dto(record))),
+        )
+            .into_response(),
+        Err(JobError::Storage(err)) => storage_error(err),
+        Err(err) => bad_request_error(err.to_string()),
+    }
+}
+
+/// 已校验的电能质量报表参数
+pub(crate) struct PowerQualityParams {
+    pub(crate) from: i64,
+    pub(crate) to: i64,
+    pub(crate) device_id: Option<String>,
+    pub(crate) window: DemandWindow,
+}
+
+impl PowerQualityParams {
+    /// 校验报表参数：`from` / `to` 必填且 `from < to`，需量窗口合法且子区间数不超过上限
+    pub(crate) fn parse(query: PowerQualityQuery) -> Result<Self, String> {
+        let (Some(from), Some(to)) = (query.from, query.to) else {
+            return Err("from and to are required".to_string());
+        };
+        if from >= to {
+            return Err("from must be < to".to_string());
+        }
+        let window = DemandWindow {
+            window_minutes: query
+                .window_minutes
+                .unwrap_or(DEFAULT_DEMAND_WINDOW_MINUTES),
+            subinterval_minutes: query
+                .subinterval_minutes
+                .unwrap_or(DEFAULT_DEMAND_SUBINTERVAL_MINUTES),
+        };
+        window.validate().map_err(|message| message.to_string())?;
+        if estimated_subintervals(window.subinterval_ms(), from, to) > MAX_DEMAND_SUBINTERVALS {
+            return Err(format!(
+                "window too large: at most {MAX_DEMAND_SUBINTERVALS} subintervals"
+            ));
+        }
+        let device_id = match query.device_id {
+            Some(value) if value.trim().is_empty() => return Err("deviceId required".to_string()),
+            Some(value) => Some(value.trim().to_string()),
+            None => None,
+        };
+        Ok(Self {
+            from,
+            to,
+            device_id,
+            window,
+        })
+    }
+
+    /// 转回查询参数（补齐默认窗口），作为后台任务参数保存
+    pub(crate) fn to_query(&self) -> PowerQualityQuery {
+        PowerQualityQuery {
+            from: Some(self.from),
+            to: Some(self.to),
+            device_id: self.device_id.clone(),
+            window_minutes: Some(self.window.window_minutes),
+            subinterval_minutes: Some(self.window.subinterval_minutes),
+        }
+    }
+}
+
+pub(crate) fn device_power_quality_to_dto(item: DevicePowerQuality) -> DevicePowerQualityDto {
+    DevicePowerQualityDto {
+        device_id: item.device_id,
+        kw_point_id: item.kw_point_id,
+        kva_point_id: item.kva_point_id,
+        kwh_point_id: item.kwh_point_id,
+        samples: item.samples,
+        energy_kwh: item.energy_kwh,
+        average_demand_kw: item.average_demand_kw,
+        peak_demand_kw: item.peak_demand.map(|peak| peak.kw),
+        peak_demand_at_ms: item.peak_demand.map(|peak| peak.window_start_ms),
+        load_factor: item.load_factor,
+        power_factor: item.power_factor,
+        min_power_factor: item.min_power_factor,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{
+        api_router, auth_headers, build_state, json_request, project_ctx, response_json,
+    };
+    use axum::http::StatusCode;
+    use domain::{PointValue, PointValueData};
+    use tower::ServiceExt;
+
+    /// 测试：电能质量报表按角色点位计算功率因数、负荷率与滑动窗口峰值需量
+    #[tokio::test]
+    async fn power_quality_report_derives_metrics_from_point_roles() {
+        let state = build_state();
+        let headers = auth_headers(&state).await;
+        let ctx = project_ctx();
+        // 2024-01-01T00:00:00Z
+        let start_ms: i64 = 1_704_067_200_000;
+        let sub_ms: i64 = 5 * 60_000;
+        for (point_id, device_id, role, readings) in [
+            (
+                "device-1-kw",
+                "device-1",
+                "kw",
+                vec![80.0, 100.0, 120.0, 40.0],
+            ),
+            (
+                "device-1-kva",
+                "device-1",
+                "kva",
+                vec![100.0, 125.0, 150.0, 50.0],
+            ),
+            ("device-2-kwh", "device-2", "kwh", vec![100.0, 110.0, 120.0]),
+        ] {
+            state
+                .point_store
+                .create_point(
+                    &ctx,
+                    ems_storage::PointRecord {
+                        point_id: point_id.to_string(),
+                        tenant_id: "tenant-1".to_string(),
+                        project_id: "project-1".to_string(),
+                        device_id: device_id.to_string(),
+                        key: point_id.to_string(),
+                        data_type: "float".to_string(),
+                        unit: None,
+                        tags: vec![role.to_string()],
+                    },
+                )
+                .await
+                .expect("point");
+            for (index, value) in readings.into_iter().enumerate() {
+                state
+                    .measurement_store
+                    .write_measurement(
+                        &ctx,
+                        &PointValue {
+                            tenant_id: "tenant-1".to_string(),
+                            project_id: "project-1".to_string(),
+                            point_id: point_id.to_string(),
+                            ts_ms: start_ms + index as i64 * sub_ms,
+                            value: PointValueData::F64(value),
+                            quality: None,
+                        },
+                    )
+                    .await
+                    .expect("measurement");
+            }
+        }
+
+        let app = api_router(state.clone());
+        let request = |uri: String| json_request(&headers, "GET", &format!("/api/v1{uri}"), None);
+        let to_ms = start_ms + 4 * sub_ms;
+
+        // 窗口不是子区间整数倍时返回 400
+        let response = app
+            .clone()
+            .oneshot(request(format!(
+                "/projects/project-1/reports/power-quality?from={start_ms}&to={to_ms}&windowMinutes=12"
+            )))
+            .await
+            .expect("report");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = app
+            .clone()
+            .oneshot(request(format!(
+                "/projects/project-1/reports/power-quality?from={start_ms}&to={to_ms}&windowMinutes=10"
+            )))
+            .await
+            .expect("report");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        let devices = json["data"]["devices"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        assert_eq!(devices.len(), 2);
+        let metered = &devices[0];
+        assert_eq!(metered["deviceId"], "device-1");
+        assert_eq!(metered["samples"], 4);
+        assert_eq!(metered["averageDemandKw"], 85.0);
+        assert_eq!(metered["peakDemandKw"], 110.0);
+        assert_eq!(metered["peakDemandAtMs"], start_ms + sub_ms);
+        assert_eq!(metered["powerFactor"], 0.8);
+        assert_eq!(metered["minPowerFactor"], 0.8);
+        let load_factor = metered["loadFactor"].as_f64().unwrap_or_default();
+        assert!((load_factor - 85.0 / 110.0).abs() < 1e-9);
+        assert!(metered["kwhPointId"].is_null());
+
+        // 只有电量累计值的设备由增量推算需量
+        let counter = &devices[1];
+        assert_eq!(counter["deviceId"], "device-2");
+        assert_eq!(counter["energyKwh"], 20.0);
+        assert_eq!(counter["peakDemandKw"], 120.0);
+        assert_eq!(counter["loadFactor"], 1.0);
+        assert!(counter["powerFactor"].is_null());
+
+        let response = app
+            .clone()
+            .oneshot(request(format!(
+                "/projects/project-1/reports/power-quality?from={start_ms}&to={to_ms}&deviceId=device-2"
+            )))
+            .await
+            .expect("report");
+        let json = response_json(response).await;
+        assert_eq!(json["data"]["devices"].as_array().map(Vec::len), Some(1));
+        assert_eq!(json["data"]["windowMinutes"], 15);
+    }
+}
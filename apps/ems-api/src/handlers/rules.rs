@@ -0,0 +1,487 @@
+//! 自动化规则 handlers
+//!
+//! 租户按项目定义 if-this-then-that 规则，由后台规则引擎评估并执行：
+//! - GET /projects/{id}/rules - 列出规则
+//! - POST /projects/{id}/rules - 创建规则
+//! - GET /projects/{id}/rules/{rid} - 查询规则
+//! - PUT /projects/{id}/rules/{rid} - 更新规则（未传字段保持不变）
+//! - DELETE /projects/{id}/rules/{rid} - 删除规则（连同执行记录）
+//! - POST /projects/{id}/rules/{rid}/enable | disable - 启用 / 停用规则
+//! - GET /projects/{id}/rules/{rid}/executions - 执行记录
+//!
+//! 权限要求：
+//! - 查询需要 AUTOMATION.RULE.READ，写入需要 AUTOMATION.RULE.WRITE
+//! - 含命令动作的规则还需要 CONTROL.COMMAND.ISSUE 且租户开启 `control` 功能开关；
+//!   含 Webhook 动作的规则需要租户开启 `webhooks` 功能开关
+
+use crate::AppState;
+use crate::middleware::{require_feature, require_permission, require_project_scope};
+use crate::utils::response::{
+    bad_request_error, not_found_error, rule_execution_to_dto, rule_to_dto, storage_error,
+};
+use api_contract::{
+    ApiResponse, CreateRuleRequest, RuleDto, RuleExecutionDto, RuleExecutionQuery,
+    UpdateRuleRequest,
+};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use domain::{TenantContext, features, permissions};
+use ems_rules::{RuleAction, parse_actions, parse_trigger};
+use ems_storage::{RuleRecord, RuleUpdate};
+
+/// 执行记录默认/最大返回条数
+const DEFAULT_EXECUTION_LIMIT: i64 = 50;
+const MAX_EXECUTION_LIMIT: i64 = 500;
+
+#[derive(serde::Deserialize)]
+pub struct RuleProjectPath {
+    project_id: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct RulePath {
+    project_id: String,
+    rule_id: String,
+}
+
+/// 列出规则
+pub async fn list_rules(
+    State(state): State<AppState>,
+    Path(path): Path<RuleProjectPath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::AUTOMATION_RULE_READ) {
+        return response;
+    }
+    match state.rule_store.list_rules(&ctx, &path.project_id).await {
+        Ok(items) => {
+            let data: Vec<RuleDto> = items.into_iter().map(rule_to_dto).collect();
+            (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 创建规则
+pub async fn create_rule(
+    State(state): State<AppState>,
+    Path(path): Path<RuleProjectPath>,
+    headers: HeaderMap,
+    Json(req): Json<CreateRuleRequest>,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::AUTOMATION_RULE_WRITE) {
+        return response;
+    }
+    let name = req.name.trim().to_string();
+    if name.is_empty() {
+        return bad_request_error("name is required");
+    }
+    if let Err(err) = parse_trigger(&req.trigger) {
+        return bad_request_error(err.to_string());
+    }
+    let actions = match parse_actions(&req.actions) {
+        Ok(actions) => actions,
+        Err(err) => return bad_request_error(err.to_string()),
+    };
+    if let Err(response) = require_action_access(&state, &ctx, &actions).await {
+        return response;
+    }
+
+    let now_ms = now_epoch_ms();
+    let record = RuleRecord {
+        tenant_id: ctx.tenant_id.clone(),
+        project_id: path.project_id,
+        rule_id: uuid::Uuid::new_v4().to_string(),
+        name,
+        enabled: req.enabled.unwrap_or(true),
+        trigger: req.trigger.to_string(),
+        actions: serde_json::Value::Array(req.actions).to_string(),
+        created_by: ctx.user_id.clone(),
+        created_at_ms: now_ms,
+        updated_at_ms: now_ms,
+    };
+    match state.rule_store.create_rule(&ctx, record).await {
+        Ok(record) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(rule_to_dto(record))),
+        )
+            .into_response(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 查询规则
+pub async fn get_rule(
+    State(state): State<AppState>,
+    Path(path): Path<RulePath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::AUTOMATION_RULE_READ) {
+        return response;
+    }
+    match state
+        .rule_store
+        .find_rule(&ctx, &path.project_id, &path.rule_id)
+        .await
+    {
+        Ok(Some(record)) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(rule_to_dto(record))),
+        )
+            .into_response(),
+        Ok(None) => not_found_error(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 更新规则
+pub async fn update_rule(
+    State(state): State<AppState>,
+    Path(path): Path<RulePath>,
+    headers: HeaderMap,
+    Json(req): Json<UpdateRuleRequest>,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::AUTOMATION_RULE_WRITE) {
+        return response;
+    }
+    let name = match req.name.map(|value| value.trim().to_string()) {
+        Some(name) if name.is_empty() => return bad_request_error("name is empty"),
+        other => other,
+    };
+    if let Some(trigger) = req.trigger.as_ref() {
+        if let Err(err) = parse_trigger(trigger) {
+            return bad_request_error(err.to_string());
+        }
+    }
+    if let Some(actions) = req.actions.as_deref() {
+        let actions = match parse_actions(actions) {
+            Ok(actions) => actions,
+            Err(err) => return bad_request_error(err.to_string()),
+        };
+        if let Err(response) = require_action_access(&state, &ctx, &actions).await {
+            return response;
+        }
+    }
+    let update = RuleUpdate {
+        name,
+        enabled: req.enabled,
+        trigger: req.trigger.map(|value| value.to_string()),
+        actions: req
+            .actions
+            .map(|values| serde_json::Value::Array(values).to_string()),
+        updated_at_ms: now_epoch_ms(),
+    };
+    save_update(&state, &ctx, &path, update).await
+}
+
+/// 删除规则
+pub async fn delete_rule(
+    State(state): State<AppState>,
+    Path(path): Path<RulePath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::AUTOMATION_RULE_WRITE) {
+        return response;
+    }
+    match state
+        .rule_store
+        .delete_rule(&ctx, &path.project_id, &path.rule_id)
+        .await
+    {
+        Ok(true) => (StatusCode::OK, Json(ApiResponse::success(()))).into_response(),
+        Ok(false) => not_found_error(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 启用规则
+pub async fn enable_rule(
+    State(state): State<AppState>,
+    Path(path): Path<RulePath>,
+    headers: HeaderMap,
+) -> Response {
+    set_rule_enabled(state, path, headers, true).await
+}
+
+/// 停用规则
+pub async fn disable_rule(
+    State(state): State<AppState>,
+    Path(path): Path<RulePath>,
+    headers: HeaderMap,
+) -> Response {
+    set_rule_enabled(state, path, headers, false).await
+}
+
+/// 查询规则执行记录
+pub async fn list_rule_executions(
+    State(state): State<AppState>,
+    Path(path): Path<RulePath>,
+    Query(query): Query<RuleExecutionQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::AUTOMATION_RULE_READ) {
+        return response;
+    }
+    match state
+        .rule_store
+        .find_rule(&ctx, &path.project_id, &path.rule_id)
+        .await
+    {
+        Ok(Some(_)) => {}
+        Ok(None) => return not_found_error(),
+        Err(err) => return storage_error(err),
+    }
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_EXECUTION_LIMIT)
+        .clamp(1, MAX_EXECUTION_LIMIT);
+    match state
+        .rule_store
+        .list_rule_executions(&ctx, &path.project_id, &path.rule_id, limit)
+        .await
+    {
+        Ok(items) => {
+            let data: Vec<RuleExecutionDto> =
+                items.into_iter().map(rule_execution_to_dto).collect();
+            (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+async fn set_rule_enabled(
+    state: AppState,
+    path: RulePath,
+    headers: HeaderMap,
+    enabled: bool,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::AUTOMATION_RULE_WRITE) {
+        return response;
+    }
+    let update = RuleUpdate {
+        enabled: Some(enabled),
+        updated_at_ms: now_epoch_ms(),
+        ..RuleUpdate::default()
+    };
+    save_update(&state, &ctx, &path, update).await
+}
+
+async fn save_update(
+    state: &AppState,
+    ctx: &TenantContext,
+    path: &RulePath,
+    update: RuleUpdate,
+) -> Response {
+    match state
+        .rule_store
+        .update_rule(ctx, &path.project_id, &path.rule_id, update)
+        .await
+    {
+        Ok(Some(record)) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(rule_to_dto(record))),
+        )
+            .into_response(),
+        Ok(None) => not_found_error(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 动作所需的额外权限与功能开关（规则执行时以规则身份下发命令，需在保存时校验）
+async fn require_action_access(
+    state: &AppState,
+    ctx: &TenantContext,
+    actions: &[RuleAction],
+) -> Result<(), Response> {
+    if actions
+        .iter()
+        .any(|action| matches!(action, RuleAction::Command { .. }))
+    {
+        require_permission(ctx, permissions::CONTROL_COMMAND_ISSUE)?;
+        require_feature(state, ctx, features::CONTROL).await?;
+    }
+    if actions
+        .iter()
+        .any(|action| matches!(action, RuleAction::Webhook { .. }))
+    {
+        require_feature(state, ctx, features::WEBHOOKS).await?;
+    }
+    Ok(())
+}
+
+fn now_epoch_ms() -> i64 {
+    let now = std::time::SystemTime::now();
+    let duration = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    duration.as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{
+        api_router, auth_headers, build_state, json_request, project_ctx, response_json,
+    };
+    use axum::http::StatusCode;
+    use domain::{PointValue, PointValueData};
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    /// 测试：自动化规则 CRUD、启停与引擎触发后的执行记录
+    #[tokio::test]
+    async fn automation_rule_fires_and_records_executions() {
+        let state = build_state();
+        let headers = auth_headers(&state).await;
+        let ctx = project_ctx();
+        let app = api_router(state.clone());
+        let request = |method: &str, uri: &str, body: Option<Value>| {
+            json_request(
+                &headers,
+                method,
+                &format!("/api/v1/projects/project-1/rules{uri}"),
+                body,
+            )
+        };
+
+        // 非法触发条件返回 400
+        let body = serde_json::json!({
+            "name": "bad",
+            "trigger": { "type": "point", "pointId": "point-1", "op": "between", "value": 1 },
+            "actions": [{ "type": "alarm", "message": "x" }],
+        });
+        let response = app
+            .clone()
+            .oneshot(request("POST", "", Some(body)))
+            .await
+            .expect("create");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = serde_json::json!({
+            "name": "High temperature",
+            "trigger": { "type": "point", "pointId": "point-1", "op": "gt", "value": 30 },
+            "actions": [{ "type": "command", "target": "fan-1", "payload": { "on": true } }],
+        });
+        let response = app
+            .clone()
+            .oneshot(request("POST", "", Some(body)))
+            .await
+            .expect("create");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        assert_eq!(json["data"]["enabled"], true);
+        assert_eq!(json["data"]["trigger"]["op"], "gt");
+        let rule_id = json["data"]["ruleId"]
+            .as_str()
+            .expect("rule id")
+            .to_string();
+
+        // 停用后引擎不评估
+        let response = app
+            .clone()
+            .oneshot(request("POST", &format!("/{rule_id}/disable"), None))
+            .await
+            .expect("disable");
+        assert_eq!(response_json(response).await["data"]["enabled"], false);
+        state
+            .realtime_store
+            .upsert_last_value(
+                &ctx,
+                &PointValue {
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    point_id: "point-1".to_string(),
+                    ts_ms: 1_000,
+                    value: PointValueData::F64(35.0),
+                    quality: None,
+                },
+            )
+            .await
+            .expect("upsert");
+        let engine = ems_rules::RuleEngine::new(
+            state.rule_store.clone(),
+            state.realtime_store.clone(),
+            state.online_store.clone(),
+            state.command_service.clone(),
+            state.event_bus.clone(),
+            &ems_rules::RuleEngineConfig::default(),
+        );
+        assert!(engine.evaluate(1_000).await.is_empty());
+
+        let response = app
+            .clone()
+            .oneshot(request("POST", &format!("/{rule_id}/enable"), None))
+            .await
+            .expect("enable");
+        assert_eq!(response_json(response).await["data"]["enabled"], true);
+        let executions = engine.evaluate(2_000).await;
+        assert_eq!(executions.len(), 1);
+
+        let response = app
+            .clone()
+            .oneshot(request("GET", &format!("/{rule_id}/executions"), None))
+            .await
+            .expect("executions");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        assert_eq!(json["data"][0]["status"], "success");
+        assert_eq!(json["data"][0]["trigger"]["value"], 35.0);
+        assert_eq!(json["data"][0]["results"][0]["type"], "command");
+        let commands = state
+            .command_store
+            .list_commands(
+                &ctx,
+                "project-1",
+                ems_storage::CommandQueryOptions::simple(10),
+            )
+            .await
+            .expect("commands");
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].target, "fan-1");
+        assert_eq!(commands[0].issued_by, format!("rule:{rule_id}"));
+
+        // 删除后查询返回 404
+        let response = app
+            .clone()
+            .oneshot(request("DELETE", &format!("/{rule_id}"), None))
+            .await
+            .expect("delete");
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app
+            .oneshot(request("GET", &format!("/{rule_id}"), None))
+            .await
+            .expect("get");
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,486 @@
+//! 控制计划 handlers
+//!
+//! 租户按项目定义周期性控制动作（预定义命令或设定值曲线），由后台计划执行器按项目时区执行：
+//! - GET /projects/{id}/schedules - 列出计划
+//! - POST /projects/{id}/schedules - 创建计划（时区默认取项目时区）
+//! - GET /projects/{id}/schedules/{sid} - 查询计划
+//! - PUT /projects/{id}/schedules/{sid} - 更新计划（未传字段保持不变）
+//! - DELETE /projects/{id}/schedules/{sid} - 删除计划（连同执行记录）
+//! - POST /projects/{id}/schedules/{sid}/enable | disable - 启用 / 停用计划
+//! - GET /projects/{id}/schedules/{sid}/executions - 执行记录
+//!
+//! 权限要求：
+//! - 查询需要 AUTOMATION.SCHEDULE.READ
+//! - 写入需要 AUTOMATION.SCHEDULE.WRITE 与 CONTROL.COMMAND.ISSUE，且租户开启 `control` 功能开关
+
+use crate::AppState;
+use crate::middleware::{require_feature, require_permission, require_project_scope};
+use crate::utils::response::{
+    bad_request_error, not_found_error, schedule_execution_to_dto, schedule_to_dto, storage_error,
+};
+use api_contract::{
+    ApiResponse, CreateScheduleRequest, ScheduleDto, ScheduleExecutionDto, ScheduleExecutionQuery,
+    UpdateScheduleRequest,
+};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use domain::{TenantContext, features, permissions};
+use ems_schedule::{MissedRunPolicy, parse_spec, parse_timezone};
+use ems_storage::{ScheduleRecord, ScheduleUpdate};
+
+/// 执行记录默认/最大返回条数
+const DEFAULT_EXECUTION_LIMIT: i64 = 50;
+const MAX_EXECUTION_LIMIT: i64 = 500;
+
+#[derive(serde::Deserialize)]
+pub struct ScheduleProjectPath {
+    project_id: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct SchedulePath {
+    project_id: String,
+    schedule_id: String,
+}
+
+/// 列出计划
+pub async fn list_schedules(
+    State(state): State<AppState>,
+    Path(path): Path<ScheduleProjectPath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::AUTOMATION_SCHEDULE_READ) {
+        return response;
+    }
+    let now_ms = now_epoch_ms();
+    match state
+        .schedule_store
+        .list_schedules(&ctx, &path.project_id)
+        .await
+    {
+        Ok(items) => {
+            let data: Vec<ScheduleDto> = items
+                .into_iter()
+                .map(|record| schedule_to_dto(record, now_ms))
+                .collect();
+            (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 创建计划
+pub async fn create_schedule(
+    State(state): State<AppState>,
+    Path(path): Path<ScheduleProjectPath>,
+    headers: HeaderMap,
+    Json(req): Json<CreateScheduleRequest>,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_write_access(&state, &ctx).await {
+        return response;
+    }
+    let name = req.name.trim().to_string();
+    if name.is_empty() {
+        return bad_request_error("name is required");
+    }
+    if let Err(err) = parse_spec(&req.spec) {
+        return bad_request_error(err.to_string());
+    }
+    let policy = match req.missed_run_policy.as_deref().map(MissedRunPolicy::parse) {
+        Some(Ok(policy)) => policy,
+        Some(Err(err)) => return bad_request_error(err.to_string()),
+        None => MissedRunPolicy::default(),
+    };
+    let timezone = match req.timezone {
+        Some(timezone) => timezone,
+        None => match state
+            .project_store
+            .find_project(&ctx, &path.project_id)
+            .await
+        {
+            Ok(Some(project)) => project.timezone,
+            Ok(None) => return not_found_error(),
+            Err(err) => return storage_error(err),
+        },
+    };
+    if let Err(err) = parse_timezone(&timezone) {
+        return bad_request_error(err.to_string());
+    }
+
+    let now_ms = now_epoch_ms();
+    let record = ScheduleRecord {
+        tenant_id: ctx.tenant_id.clone(),
+        project_id: path.project_id,
+        schedule_id: uuid::Uuid::new_v4().to_string(),
+        name,
+        enabled: req.enabled.unwrap_or(true),
+        timezone,
+        spec: req.spec.to_string(),
+        missed_run_policy: policy.as_str().to_string(),
+        last_evaluated_at_ms: now_ms,
+        created_by: ctx.user_id.clone(),
+        created_at_ms: now_ms,
+        updated_at_ms: now_ms,
+    };
+    match state.schedule_store.create_schedule(&ctx, record).await {
+        Ok(record) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(schedule_to_dto(record, now_ms))),
+        )
+            .into_response(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 查询计划
+pub async fn get_schedule(
+    State(state): State<AppState>,
+    Path(path): Path<SchedulePath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::AUTOMATION_SCHEDULE_READ) {
+        return response;
+    }
+    match state
+        .schedule_store
+        .find_schedule(&ctx, &path.project_id, &path.schedule_id)
+        .await
+    {
+        Ok(Some(record)) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(schedule_to_dto(
+                record,
+                now_epoch_ms(),
+            ))),
+        )
+            .into_response(),
+        Ok(None) => not_found_error(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 更新计划
+pub async fn update_schedule(
+    State(state): State<AppState>,
+    Path(path): Path<SchedulePath>,
+    headers: HeaderMap,
+    Json(req): Json<UpdateScheduleRequest>,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_write_access(&state, &ctx).await {
+        return response;
+    }
+    let name = match req.name.map(|value| value.trim().to_string()) {
+        Some(name) if name.is_empty() => return bad_request_error("name is empty"),
+        other => other,
+    };
+    if let Some(spec) = req.spec.as_ref()
+        && let Err(err) = parse_spec(spec)
+    {
+        return bad_request_error(err.to_string());
+    }
+    if let Some(timezone) = req.timezone.as_deref()
+        && let Err(err) = parse_timezone(timezone)
+    {
+        return bad_request_error(err.to_string());
+    }
+    let policy = match req.missed_run_policy.as_deref().map(MissedRunPolicy::parse) {
+        Some(Ok(policy)) => Some(policy.as_str().to_string()),
+        Some(Err(err)) => return bad_request_error(err.to_string()),
+        None => None,
+    };
+    let now_ms = now_epoch_ms();
+    // 启用或修改计划时刻时重置游标，不补跑修改前错过的时刻
+    let reset_cursor = req.enabled == Some(true) || req.spec.is_some() || req.timezone.is_some();
+    let update = ScheduleUpdate {
+        name,
+        enabled: req.enabled,
+        timezone: req.timezone,
+        spec: req.spec.map(|value| value.to_string()),
+        missed_run_policy: policy,
+        last_evaluated_at_ms: reset_cursor.then_some(now_ms),
+        updated_at_ms: now_ms,
+    };
+    save_update(&state, &ctx, &path, update).await
+}
+
+/// 删除计划
+pub async fn delete_schedule(
+    State(state): State<AppState>,
+    Path(path): Path<SchedulePath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::AUTOMATION_SCHEDULE_WRITE) {
+        return response;
+    }
+    match state
+        .schedule_store
+        .delete_schedule(&ctx, &path.project_id, &path.schedule_id)
+        .await
+    {
+        Ok(true) => (StatusCode::OK, Json(ApiResponse::success(()))).into_response(),
+        Ok(false) => not_found_error(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 启用计划
+pub async fn enable_schedule(
+    State(state): State<AppState>,
+    Path(path): Path<SchedulePath>,
+    headers: HeaderMap,
+) -> Response {
+    set_schedule_enabled(state, path, headers, true).await
+}
+
+/// 停用计划
+pub async fn disable_schedule(
+    State(state): State<AppState>,
+    Path(path): Path<SchedulePath>,
+    headers: HeaderMap,
+) -> Response {
+    set_schedule_enabled(state, path, headers, false).await
+}
+
+/// 查询计划执行记录
+pub async fn list_schedule_executions(
+    State(state): State<AppState>,
+    Path(path): Path<SchedulePath>,
+    Query(query): Query<ScheduleExecutionQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::AUTOMATION_SCHEDULE_READ) {
+        return response;
+    }
+    match state
+        .schedule_store
+        .find_schedule(&ctx, &path.project_id, &path.schedule_id)
+        .await
+    {
+        Ok(Some(_)) => {}
+        Ok(None) => return not_found_error(),
+        Err(err) => return storage_error(err),
+    }
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_EXECUTION_LIMIT)
+        .clamp(1, MAX_EXECUTION_LIMIT);
+    match state
+        .schedule_store
+        .list_schedule_executions(&ctx, &path.project_id, &path.schedule_id, limit)
+        .await
+    {
+        Ok(items) => {
+            let data: Vec<ScheduleExecutionDto> =
+                items.into_iter().map(schedule_execution_to_dto).collect();
+            (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+async fn set_schedule_enabled(
+    state: AppState,
+    path: SchedulePath,
+    headers: HeaderMap,
+    enabled: bool,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_write_access(&state, &ctx).await {
+        return response;
+    }
+    let now_ms = now_epoch_ms();
+    let update = ScheduleUpdate {
+        enabled: Some(enabled),
+        last_evaluated_at_ms: enabled.then_some(now_ms),
+        updated_at_ms: now_ms,
+        ..ScheduleUpdate::default()
+    };
+    save_update(&state, &ctx, &path, update).await
+}
+
+async fn save_update(
+    state: &AppState,
+    ctx: &TenantContext,
+    path: &SchedulePath,
+    update: ScheduleUpdate,
+) -> Response {
+    let now_ms = update.updated_at_ms;
+    match state
+        .schedule_store
+        .update_schedule(ctx, &path.project_id, &path.schedule_id, update)
+        .await
+    {
+        Ok(Some(record)) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(schedule_to_dto(record, now_ms))),
+        )
+            .into_response(),
+        Ok(None) => not_found_error(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 计划以计划身份下发命令，保存时需校验下发权限与功能开关
+async fn require_write_access(state: &AppState, ctx: &TenantContext) -> Result<(), Response> {
+    require_permission(ctx, permissions::AUTOMATION_SCHEDULE_WRITE)?;
+    require_permission(ctx, permissions::CONTROL_COMMAND_ISSUE)?;
+    require_feature(state, ctx, features::CONTROL).await
+}
+
+fn now_epoch_ms() -> i64 {
+    let now = std::time::SystemTime::now();
+    let duration = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    duration.as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{
+        api_router, auth_headers, build_state, json_request, project_ctx, response_json,
+    };
+    use axum::http::StatusCode;
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    /// 测试：控制计划创建校验、默认项目时区、执行器按计划时刻下发命令并记录执行
+    #[tokio::test]
+    async fn control_schedule_runs_due_commands() {
+        let state = build_state();
+        let headers = auth_headers(&state).await;
+        let ctx = project_ctx();
+        let app = api_router(state.clone());
+        let request = |method: &str, uri: &str, body: Option<Value>| {
+            json_request(
+                &headers,
+                method,
+                &format!("/api/v1/projects/project-1/schedules{uri}"),
+                body,
+            )
+        };
+        let spec = serde_json::json!({
+            "type": "setpoint_profile",
+            "target": "ahu-1",
+            "days": "1-5",
+            "steps": [
+                { "at": "07:30", "payload": { "setpoint": 22 } },
+                { "at": "18:00", "payload": { "setpoint": 26 } }
+            ]
+        });
+
+        // 非法 cron / 时区 / 策略返回 400
+        for body in [
+            serde_json::json!({ "name": "bad", "spec": { "type": "commands", "cron": "61 * * * *", "commands": [{ "target": "d", "payload": {} }] } }),
+            serde_json::json!({ "name": "bad", "spec": spec, "timezone": "Mars/Base" }),
+            serde_json::json!({ "name": "bad", "spec": spec, "missedRunPolicy": "later" }),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(request("POST", "", Some(body)))
+                .await
+                .expect("create");
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+
+        let body = serde_json::json!({ "name": "Weekday HVAC", "spec": spec });
+        let response = app
+            .clone()
+            .oneshot(request("POST", "", Some(body)))
+            .await
+            .expect("create");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        assert_eq!(json["data"]["timezone"], "UTC");
+        assert_eq!(json["data"]["missedRunPolicy"], "skip");
+        let schedule_id = json["data"]["scheduleId"]
+            .as_str()
+            .expect("schedule id")
+            .to_string();
+        let next_run_at_ms = json["data"]["nextRunAtMs"].as_i64().expect("next run");
+
+        let runner = ems_schedule::ScheduleRunner::new(
+            state.schedule_store.clone(),
+            state.command_service.clone(),
+            &ems_schedule::ScheduleRunnerConfig::default(),
+        );
+        assert!(runner.run_due(next_run_at_ms - 1_000).await.is_empty());
+        let executions = runner.run_due(next_run_at_ms + 1_000).await;
+        assert_eq!(executions.len(), 1);
+
+        let response = app
+            .clone()
+            .oneshot(request("GET", &format!("/{schedule_id}/executions"), None))
+            .await
+            .expect("executions");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        assert_eq!(json["data"][0]["status"], "success");
+        assert_eq!(json["data"][0]["scheduledAtMs"], next_run_at_ms);
+        assert_eq!(json["data"][0]["results"][0]["target"], "ahu-1");
+        let commands = state
+            .command_store
+            .list_commands(
+                &ctx,
+                "project-1",
+                ems_storage::CommandQueryOptions::simple(10),
+            )
+            .await
+            .expect("commands");
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].issued_by, format!("schedule:{schedule_id}"));
+
+        // 停用后没有下一个计划时刻
+        let response = app
+            .clone()
+            .oneshot(request("POST", &format!("/{schedule_id}/disable"), None))
+            .await
+            .expect("disable");
+        let json = response_json(response).await;
+        assert_eq!(json["data"]["enabled"], false);
+        assert!(json["data"]["nextRunAtMs"].is_null());
+
+        let response = app
+            .clone()
+            .oneshot(request("DELETE", &format!("/{schedule_id}"), None))
+            .await
+            .expect("delete");
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app
+            .oneshot(request("GET", &format!("/{schedule_id}"), None))
+            .await
+            .expect("get");
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
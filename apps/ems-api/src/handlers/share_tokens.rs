@@ -0,0 +1,365 @@
+//! 数据分享令牌 handlers
+//!
+//! 为看板嵌入、大屏展示签发只读分享令牌（无需登录）：
+//! - POST /projects/{id}/share-tokens - 创建令牌（返回令牌明文，仅此一次）
+//! - GET /projects/{id}/share-tokens - 列出令牌（含已撤销/已过期）
+//! - DELETE /projects/{id}/share-tokens/{tid} - 撤销令牌
+//!
+//! 令牌只能访问所属项目的 realtime（含 WebSocket）与 measurements 接口，
+//! 通过 `?shareToken=` 或 `Authorization: Bearer ems_share_...` 传入。
+//!
+//! 权限要求：
+//! - 创建/撤销需要 SHARE.TOKEN.WRITE，查询需要 SHARE.TOKEN.READ
+//! - 创建者须自身具备所授范围对应的数据读取权限
+
+use crate::AppState;
+use crate::middleware::{
+    has_permission, require_permission, require_project_scope, share_token_hash,
+};
+use crate::utils::normalize_required;
+use crate::utils::response::{bad_request_error, forbidden_error, not_found_error, storage_error};
+use api_contract::{ApiResponse, CreateShareTokenRequest, ShareTokenDto};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use domain::{permissions, share};
+use ems_storage::ShareTokenRecord;
+
+/// 默认/最长有效期（秒）
+const DEFAULT_EXPIRES_IN_SECONDS: i64 = 30 * 24 * 3600;
+const MAX_EXPIRES_IN_SECONDS: i64 = 365 * 24 * 3600;
+
+#[derive(serde::Deserialize)]
+pub struct ShareTokenProjectPath {
+    project_id: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct ShareTokenPath {
+    project_id: String,
+    token_id: String,
+}
+
+/// 创建分享令牌
+pub async fn create_share_token(
+    State(state): State<AppState>,
+    Path(path): Path<ShareTokenProjectPath>,
+    headers: HeaderMap,
+    Json(req): Json<CreateShareTokenRequest>,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::SHARE_TOKEN_WRITE) {
+        return response;
+    }
+    let name = match normalize_required(req.name, "name") {
+        Ok(value) => value,
+        Err(response) => return response,
+    };
+    let scopes = match req.scopes {
+        Some(values) => {
+            let mut scopes: Vec<String> = Vec::new();
+            for scope in values {
+                let scope = scope.trim().to_string();
+                if !share::SCOPES.contains(&scope.as_str()) {
+                    return bad_request_error(format!("unsupported scope: {}", scope));
+                }
+                if !scopes.contains(&scope) {
+                    scopes.push(scope);
+                }
+            }
+            scopes
+        }
+        None => share::SCOPES
+            .iter()
+            .map(|scope| scope.to_string())
+            .collect(),
+    };
+    if scopes.is_empty() {
+        return bad_request_error("scopes is empty");
+    }
+    // 不允许通过分享令牌授出创建者自身没有的读取权限
+    if scopes.iter().any(|scope| {
+        share::scope_permission(scope).is_none_or(|permission| !has_permission(&ctx, permission))
+    }) {
+        return forbidden_error();
+    }
+    let expires_in_seconds = req.expires_in_seconds.unwrap_or(DEFAULT_EXPIRES_IN_SECONDS);
+    if expires_in_seconds <= 0 || expires_in_seconds > MAX_EXPIRES_IN_SECONDS {
+        return bad_request_error("expiresInSeconds out of range");
+    }
+
+    let token = format!(
+        "{}{}{}",
+        share::SHARE_TOKEN_PREFIX,
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    );
+    let now_ms = now_epoch_ms();
+    let record = ShareTokenRecord {
+        tenant_id: ctx.tenant_id.clone(),
+        project_id: path.project_id,
+        token_id: uuid::Uuid::new_v4().to_string(),
+        token_hash: share_token_hash(&token),
+        name,
+        scopes,
+        created_by: ctx.user_id.clone(),
+        expires_at_ms: now_ms + expires_in_seconds * 1000,
+        revoked_at_ms: None,
+        created_at_ms: now_ms,
+    };
+    match state
+        .share_token_store
+        .create_share_token(&ctx, record)
+        .await
+    {
+        Ok(record) => {
+            let dto = ShareTokenDto {
+                token: Some(token),
+                ..share_token_to_dto(record)
+            };
+            (StatusCode::OK, Json(ApiResponse::success(dto))).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 列出分享令牌
+pub async fn list_share_tokens(
+    State(state): State<AppState>,
+    Path(path): Path<ShareTokenProjectPath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::SHARE_TOKEN_READ) {
+        return response;
+    }
+    match state
+        .share_token_store
+        .list_share_tokens(&ctx, &path.project_id)
+        .await
+    {
+        Ok(items) => {
+            let data: Vec<ShareTokenDto> = items.into_iter().map(share_token_to_dto).collect();
+            (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 撤销分享令牌（立即失效，记录保留）
+pub async fn revoke_share_token(
+    State(state): State<AppState>,
+    Path(path): Path<ShareTokenPath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::SHARE_TOKEN_WRITE) {
+        return response;
+    }
+    match state
+        .share_token_store
+        .revoke_share_token(&ctx, &path.project_id, &path.token_id, now_epoch_ms())
+        .await
+    {
+        Ok(Some(record)) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(share_token_to_dto(record))),
+        )
+            .into_response(),
+        Ok(None) => not_found_error(),
+        Err(err) => storage_error(err),
+    }
+}
+
+fn share_token_to_dto(record: ShareTokenRecord) -> ShareTokenDto {
+    ShareTokenDto {
+        token_id: record.token_id,
+        project_id: record.project_id,
+        name: record.name,
+        scopes: record.scopes,
+        expires_at_ms: record.expires_at_ms,
+        revoked_at_ms: record.revoked_at_ms,
+        token: None,
+        created_by: record.created_by,
+        created_at_ms: record.created_at_ms,
+    }
+}
+
+fn now_epoch_ms() -> i64 {
+    let now = std::time::SystemTime::now();
+    let duration = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    duration.as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{
+        api_router, auth_headers, build_state, json_request, project_ctx, response_json,
+    };
+    use axum::http::{StatusCode, header};
+    use domain::{PointValue, PointValueData};
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    /// 测试：分享令牌免登录读取实时数据，受范围、项目与撤销限制
+    #[tokio::test]
+    async fn share_token_grants_scoped_read_only_access() {
+        let state = build_state();
+        let headers = auth_headers(&state).await;
+        let ctx = project_ctx();
+        state
+            .realtime_store
+            .upsert_last_value(
+                &ctx,
+                &PointValue {
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    point_id: "point-1".to_string(),
+                    ts_ms: 1_000,
+                    value: PointValueData::F64(42.0),
+                    quality: None,
+                },
+            )
+            .await
+            .expect("upsert last value");
+
+        let app = api_router(state.clone());
+        let request = |method: &str, uri: &str, body: Option<Value>| {
+            json_request(&headers, method, &format!("/api/v1{uri}"), body)
+        };
+        // 免登录请求（不带 Authorization 头）
+        let anonymous = |uri: &str| {
+            axum::http::Request::builder()
+                .uri(format!("/api/v1{uri}"))
+                .body(axum::body::Body::empty())
+                .expect("request")
+        };
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/projects/project-1/share-tokens",
+                Some(serde_json::json!({ "name": "Lobby", "scopes": ["alarms"] })),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/projects/project-1/share-tokens",
+                Some(serde_json::json!({ "name": "Lobby", "scopes": ["realtime"] })),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        let token_id = json["data"]["tokenId"]
+            .as_str()
+            .expect("token id")
+            .to_string();
+        let token = json["data"]["token"].as_str().expect("token").to_string();
+        assert!(token.starts_with(domain::share::SHARE_TOKEN_PREFIX));
+
+        let response = app
+            .clone()
+            .oneshot(anonymous(&format!(
+                "/projects/project-1/realtime?shareToken={token}"
+            )))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        assert_eq!(json["data"][0]["pointId"], "point-1");
+
+        // Bearer 方式同样有效
+        let response = app
+            .clone()
+            .oneshot(
+                axum::http::Request::builder()
+                    .uri("/api/v1/projects/project-1/realtime")
+                    .header(header::AUTHORIZATION, format!("Bearer {token}"))
+                    .body(axum::body::Body::empty())
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // 未授权范围、其他项目与管理接口均不可访问
+        let response = app
+            .clone()
+            .oneshot(anonymous(&format!(
+                "/projects/project-1/measurements?pointId=point-1&shareToken={token}"
+            )))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response = app
+            .clone()
+            .oneshot(anonymous(&format!(
+                "/projects/project-2/realtime?shareToken={token}"
+            )))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response = app
+            .clone()
+            .oneshot(anonymous(&format!(
+                "/projects/project-1/points?shareToken={token}"
+            )))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        // 列表不返回令牌明文
+        let response = app
+            .clone()
+            .oneshot(request("GET", "/projects/project-1/share-tokens", None))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        assert_eq!(json["data"].as_array().map(Vec::len), Some(1));
+        assert!(json["data"][0].get("token").is_none());
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                "DELETE",
+                &format!("/projects/project-1/share-tokens/{token_id}"),
+                None,
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        assert!(json["data"]["revokedAtMs"].is_i64());
+
+        let response = app
+            .clone()
+            .oneshot(anonymous(&format!(
+                "/projects/project-1/realtime?shareToken={token}"
+            )))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
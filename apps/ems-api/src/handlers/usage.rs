@@ -0,0 +1,431 @@
+//! 租户用量与配额 handlers
+//!
+//! 用量按租户计量：`measurements` / `api_calls` / `commands` 为 UTC 自然日计数，
+//! `points` 为当前点位数；配额按指标配置，未配置即不限制：
+//! - GET /usage - 用量报表（按日明细 + 当前值 + 配额）
+//! - GET /usage/quotas - 列出已配置的配额
+//! - PUT /ops/tenants/{tenant_id}/quotas/{metric}（仅运维端口）- 设置配额
+//! - DELETE /ops/tenants/{tenant_id}/quotas/{metric}（仅运维端口）- 删除配额（恢复不限制）
+//!
+//! 权限要求：查询需要 USAGE.QUOTA.READ；配额由平台运维设置，租户 API 只读（租户不能自行放宽配额）
+
+use std::collections::HashMap;
+
+use crate::AppState;
+use crate::middleware::{require_permission, require_tenant_context};
+use crate::ops::{OpsState, operator_context};
+use crate::utils::response::{bad_request_error, not_found_error, storage_error};
+use api_contract::{
+    ApiResponse, QuotaDto, UpdateQuotaRequest, UsageDailyDto, UsageMetricDto, UsageQuery,
+    UsageReportDto,
+};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use domain::{permissions, usage};
+use ems_storage::QuotaRecord;
+
+/// 用量报表最大查询窗口（天）
+const MAX_USAGE_DAYS: i64 = 366;
+
+#[derive(serde::Deserialize)]
+pub struct TenantQuotaPath {
+    tenant_id: String,
+    metric: String,
+}
+
+/// 租户用量报表
+pub async fn get_usage_report(
+    State(state): State<AppState>,
+    Query(query): Query<UsageQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_tenant_context(&state, &headers) {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::USAGE_QUOTA_READ) {
+        return response;
+    }
+    let today = usage::day_start_ms(now_epoch_ms());
+    let from = query.from.unwrap_or(today);
+    let to = query.to.unwrap_or(today + usage::DAY_MS);
+    if from >= to {
+        return bad_request_error("from must be < to");
+    }
+    if to - from > MAX_USAGE_DAYS * usage::DAY_MS {
+        return bad_request_error(format!("window too large: at most {MAX_USAGE_DAYS} days"));
+    }
+    // 先写入本实例未刷盘的 api_calls / measurements 增量
+    state.usage_meter.flush_tenant(&ctx.tenant_id).await;
+    // 按日计数以自然日起始时刻存储，窗口起点对齐到所在自然日
+    let records = match state
+        .usage_store
+        .list_usage(&ctx, usage::day_start_ms(from), to)
+        .await
+    {
+        Ok(records) => records,
+        Err(err) => return storage_error(err),
+    };
+    let today_records = match state
+        .usage_store
+        .list_usage(&ctx, today, today + usage::DAY_MS)
+        .await
+    {
+        Ok(records) => records,
+        Err(err) => return storage_error(err),
+    };
+    let limits: HashMap<String, i64> = match state.usage_store.list_quotas(&ctx).await {
+        Ok(quotas) => quotas
+            .into_iter()
+            .map(|quota| (quota.metric, quota.limit))
+            .collect(),
+        Err(err) => return storage_error(err),
+    };
+    let points = match state.point_store.count_points(&ctx).await {
+        Ok(count) => count,
+        Err(err) => return storage_error(err),
+    };
+
+    let mut metrics = Vec::with_capacity(usage::METRICS.len());
+    for metric in usage::DAILY_METRICS {
+        let daily: Vec<UsageDailyDto> = records
+            .iter()
+            .filter(|record| record.metric == metric)
+            .map(|record| UsageDailyDto {
+                period_start_ms: record.period_start_ms,
+                count: record.count,
+            })
+            .collect();
+        let current = today_records
+            .iter()
+            .filter(|record| record.metric == metric)
+            .map(|record| record.count)
+            .sum();
+        metrics.push(UsageMetricDto {
+            metric: metric.to_string(),
+            total: daily.iter().map(|item| item.count).sum(),
+            current,
+            limit: limits.get(metric).copied(),
+            daily,
+        });
+    }
+    metrics.push(UsageMetricDto {
+        metric: usage::POINTS.to_string(),
+        total: points,
+        current: points,
+        limit: limits.get(usage::POINTS).copied(),
+        daily: Vec::new(),
+    });
+    let data = UsageReportDto {
+        tenant_id: ctx.tenant_id,
+        from,
+        to,
+        metrics,
+    };
+    (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+}
+
+/// 列出租户配额
+pub async fn list_quotas(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    let ctx = match require_tenant_context(&state, &headers) {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::USAGE_QUOTA_READ) {
+        return response;
+    }
+    match state.usage_store.list_quotas(&ctx).await {
+        Ok(records) => {
+            let data: Vec<QuotaDto> = records.into_iter().map(quota_to_dto).collect();
+            (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 设置指定租户的配额（仅运维端口）
+pub async fn set_tenant_quota(
+    State(state): State<OpsState>,
+    Path(path): Path<TenantQuotaPath>,
+    Json(req): Json<UpdateQuotaRequest>,
+) -> Response {
+    let Some((usage_store, usage_meter)) = state.usage() else {
+        return not_found_error();
+    };
+    if !usage::is_valid_metric(&path.metric) {
+        return bad_request_error("metric must be points|measurements|api_calls|commands");
+    }
+    if req.limit < 0 {
+        return bad_request_error("limit must be >= 0");
+    }
+    let ctx = operator_context(&path.tenant_id);
+    let record = QuotaRecord {
+        tenant_id: path.tenant_id,
+        metric: path.metric,
+        limit: req.limit,
+        updated_at_ms: now_epoch_ms(),
+    };
+    match usage_store.upsert_quota(&ctx, record).await {
+        Ok(record) => {
+            usage_meter.invalidate(&ctx.tenant_id, &record.metric);
+            (
+                StatusCode::OK,
+                Json(ApiResponse::success(quota_to_dto(record))),
+            )
+                .into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 删除指定租户的配额（恢复不限制，仅运维端口）
+pub async fn delete_tenant_quota(
+    State(state): State<OpsState>,
+    Path(path): Path<TenantQuotaPath>,
+) -> Response {
+    let Some((usage_store, usage_meter)) = state.usage() else {
+        return not_found_error();
+    };
+    let ctx = operator_context(&path.tenant_id);
+    match usage_store.delete_quota(&ctx, &path.metric).await {
+        Ok(true) => {
+            usage_meter.invalidate(&ctx.tenant_id, &path.metric);
+            (StatusCode::OK, Json(ApiResponse::success(()))).into_response()
+        }
+        Ok(false) => not_found_error(),
+        Err(err) => storage_error(err),
+    }
+}
+
+fn quota_to_dto(record: QuotaRecord) -> QuotaDto {
+    QuotaDto {
+        metric: record.metric,
+        limit: record.limit,
+        updated_at_ms: record.updated_at_ms,
+    }
+}
+
+fn now_epoch_ms() -> i64 {
+    let now = std::time::SystemTime::now();
+    let duration = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    duration.as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::middleware;
+    use crate::test_support::{
+        api_router, auth_headers, build_state, json_request, project_ctx, response_json,
+    };
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::middleware as axum_middleware;
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    /// 测试：租户配额限制点位创建、命令下发与 API 调用，用量报表汇总当日计数
+    #[tokio::test]
+    async fn usage_quotas_reject_requests_beyond_limits() {
+        let state = build_state();
+        let headers = auth_headers(&state).await;
+        let ctx = project_ctx();
+        state
+            .device_store
+            .create_device(
+                &ctx,
+                ems_storage::DeviceRecord {
+                    device_id: "device-1".to_string(),
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    gateway_id: "gateway-1".to_string(),
+                    name: "Meter".to_string(),
+                    model: None,
+                    room_id: None,
+                    address_config: None,
+                    offline_after_seconds: None,
+                },
+            )
+            .await
+            .expect("device");
+        let policy = middleware::UsagePolicy::new(state.auth.clone(), state.usage_meter.clone());
+        let app = api_router(state.clone()).layer(axum_middleware::from_fn_with_state(
+            policy,
+            middleware::api_usage_meter,
+        ));
+        let request = |method: &str, uri: &str, body: Option<Value>| {
+            json_request(&headers, method, &format!("/api/v1{uri}"), body)
+        };
+        // 配额只能由平台运维在运维端口按租户设置
+        let ops_router = crate::ops::create_ops_router(
+            crate::ops::OpsState::new(None, serde_json::json!({}))
+                .with_usage(state.usage_store.clone(), state.usage_meter.clone()),
+        );
+        let set_quota = |metric: &str, limit: i64| {
+            json_request(
+                &HeaderMap::new(),
+                "PUT",
+                &format!("/ops/tenants/tenant-1/quotas/{metric}"),
+                Some(serde_json::json!({ "limit": limit })),
+            )
+        };
+
+        // 未知指标与负数配额返回 400
+        let response = ops_router
+            .clone()
+            .oneshot(set_quota("bogus", 1))
+            .await
+            .expect("quota");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = ops_router
+            .clone()
+            .oneshot(set_quota("points", -1))
+            .await
+            .expect("quota");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        for (metric, limit) in [("points", 1), ("commands", 1)] {
+            let response = ops_router
+                .clone()
+                .oneshot(set_quota(metric, limit))
+                .await
+                .expect("quota");
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        // 点位配额：第二个点位返回 429
+        let point = |key: &str| serde_json::json!({ "deviceId": "device-1", "key": key, "dataType": "float" });
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/projects/project-1/points",
+                Some(point("p1")),
+            ))
+            .await
+            .expect("point");
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/projects/project-1/points",
+                Some(point("p2")),
+            ))
+            .await
+            .expect("point");
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let json = response_json(response).await;
+        assert_eq!(json["error"]["code"], "QUOTA.EXCEEDED");
+
+        // 命令配额：当日第二条命令返回 429
+        let command = serde_json::json!({ "target": "device-1", "payload": { "switch": "on" } });
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/projects/project-1/commands",
+                Some(command.clone()),
+            ))
+            .await
+            .expect("command");
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/projects/project-1/commands",
+                Some(command),
+            ))
+            .await
+            .expect("command");
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        let response = app
+            .clone()
+            .oneshot(request("GET", "/usage", None))
+            .await
+            .expect("usage");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        let metrics = json["data"]["metrics"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        let metric = |name: &str| {
+            metrics
+                .iter()
+                .find(|item| item["metric"] == name)
+                .cloned()
+                .unwrap_or_default()
+        };
+        assert_eq!(metric("points")["current"], 1);
+        assert_eq!(metric("points")["limit"], 1);
+        assert_eq!(metric("commands")["current"], 1);
+        assert_eq!(
+            metric("commands")["daily"].as_array().map(Vec::len),
+            Some(1)
+        );
+        let api_calls = metric("api_calls")["current"].as_i64().unwrap_or_default();
+        assert_eq!(api_calls, 5);
+        assert!(metric("api_calls")["limit"].is_null());
+
+        // API 调用配额：已超出当日用量后所有已认证请求返回 429
+        let response = ops_router
+            .clone()
+            .oneshot(set_quota("api_calls", api_calls + 1))
+            .await
+            .expect("quota");
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app
+            .clone()
+            .oneshot(request("GET", "/usage/quotas", None))
+            .await
+            .expect("quotas");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        assert_eq!(json["data"].as_array().map(Vec::len), Some(3));
+        let response = app
+            .clone()
+            .oneshot(request("GET", "/usage", None))
+            .await
+            .expect("usage");
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let json = response_json(response).await;
+        assert_eq!(json["error"]["code"], "QUOTA.EXCEEDED");
+
+        // 运维端口删除配额后立即恢复（失效计量缓存）
+        let response = ops_router
+            .clone()
+            .oneshot(json_request(
+                &HeaderMap::new(),
+                "DELETE",
+                "/ops/tenants/tenant-1/quotas/api_calls",
+                None,
+            ))
+            .await
+            .expect("delete quota");
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app
+            .clone()
+            .oneshot(request("GET", "/usage", None))
+            .await
+            .expect("usage");
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // 租户 API 不能修改自身配额
+        let response = app
+            .clone()
+            .oneshot(request(
+                "PUT",
+                "/usage/quotas/points",
+                Some(serde_json::json!({ "limit": 100 })),
+            ))
+            .await
+            .expect("quota");
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,364 @@
+//! Webhook 订阅 handlers
+//!
+//! 租户按项目注册 Webhook 地址并选择事件类型，事件发生时由后台推送器签名推送：
+//! - POST /projects/{id}/webhooks - 创建订阅（返回签名密钥，仅此一次）
+//! - GET /projects/{id}/webhooks - 列出订阅
+//! - DELETE /projects/{id}/webhooks/{sid} - 删除订阅
+//! - GET /projects/{id}/webhooks/deliveries - 推送日志（可按订阅、状态过滤）
+//!
+//! 权限要求：
+//! - 创建/删除需要 PROJECT.WRITE，查询需要 PROJECT.READ
+//! - 租户关闭 `webhooks` 功能开关时不能创建订阅（403 FEATURE.DISABLED）
+//!
+//! URL 必须为 http/https；未开启 `EMS_WEBHOOK_ALLOW_PRIVATE_TARGETS` 时拒绝
+//! 回环 / 内网 / 链路本地的字面量地址与 localhost（推送时还会按解析结果再次校验）
+
+use crate::AppState;
+use crate::middleware::{require_feature, require_permission, require_project_scope};
+use crate::utils::response::{
+    bad_request_error, not_found_error, storage_error, webhook_delivery_to_dto,
+    webhook_subscription_to_dto,
+};
+use api_contract::{
+    ApiResponse, CreateWebhookRequest, WebhookDeliveryDto, WebhookDeliveryQuery,
+    WebhookSubscriptionDto,
+};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use domain::{features, permissions};
+use ems_events::{WebhookTargetError, event_types, validate_webhook_url};
+use ems_storage::WebhookSubscriptionRecord;
+
+/// 推送日志默认/最大返回条数
+const DEFAULT_DELIVERY_LIMIT: i64 = 50;
+const MAX_DELIVERY_LIMIT: i64 = 500;
+
+#[derive(serde::Deserialize)]
+pub struct WebhookProjectPath {
+    project_id: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct WebhookPath {
+    project_id: String,
+    subscription_id: String,
+}
+
+/// 创建 Webhook 订阅
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    Path(path): Path<WebhookProjectPath>,
+    headers: HeaderMap,
+    Json(req): Json<CreateWebhookRequest>,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::PROJECT_WRITE) {
+        return response;
+    }
+    if let Err(response) = require_feature(&state, &ctx, features::WEBHOOKS).await {
+        return response;
+    }
+    let url = req.url.trim().to_string();
+    match validate_webhook_url(&url) {
+        Ok(_) => {}
+        Err(WebhookTargetError::Blocked(_)) if state.webhook_allow_private_targets => {}
+        Err(err) => return bad_request_error(err.to_string()),
+    }
+    let mut event_types: Vec<String> = Vec::new();
+    for event_type in req.event_types {
+        let event_type = event_type.trim().to_string();
+        if !event_types::is_supported(&event_type) {
+            return bad_request_error(format!("unsupported event type: {}", event_type));
+        }
+        if !event_types.contains(&event_type) {
+            event_types.push(event_type);
+        }
+    }
+    if event_types.is_empty() {
+        return bad_request_error("eventTypes is required");
+    }
+    let secret = match req.secret.map(|value| value.trim().to_string()) {
+        Some(secret) if secret.is_empty() => return bad_request_error("secret is empty"),
+        Some(secret) => secret,
+        None => format!("whsec_{}", uuid::Uuid::new_v4().simple()),
+    };
+
+    let record = WebhookSubscriptionRecord {
+        subscription_id: uuid::Uuid::new_v4().to_string(),
+        tenant_id: ctx.tenant_id.clone(),
+        project_id: path.project_id,
+        url,
+        event_types,
+        secret,
+        enabled: req.enabled.unwrap_or(true),
+        created_by: ctx.user_id.clone(),
+        created_at_ms: now_epoch_ms(),
+    };
+    match state
+        .webhook_store
+        .create_webhook_subscription(&ctx, record)
+        .await
+    {
+        Ok(record) => {
+            let secret = record.secret.clone();
+            let dto = WebhookSubscriptionDto {
+                secret: Some(secret),
+                ..webhook_subscription_to_dto(record)
+            };
+            (StatusCode::OK, Json(ApiResponse::success(dto))).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 列出 Webhook 订阅
+pub async fn list_webhooks(
+    State(state): State<AppState>,
+    Path(path): Path<WebhookProjectPath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::PROJECT_READ) {
+        return response;
+    }
+    match state
+        .webhook_store
+        .list_webhook_subscriptions(&ctx, &path.project_id)
+        .await
+    {
+        Ok(items) => {
+            let data: Vec<WebhookSubscriptionDto> =
+                items.into_iter().map(webhook_subscription_to_dto).collect();
+            (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 删除 Webhook 订阅
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    Path(path): Path<WebhookPath>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::PROJECT_WRITE) {
+        return response;
+    }
+    match state
+        .webhook_store
+        .delete_webhook_subscription(&ctx, &path.project_id, &path.subscription_id)
+        .await
+    {
+        Ok(true) => (StatusCode::OK, Json(ApiResponse::success(()))).into_response(),
+        Ok(false) => not_found_error(),
+        Err(err) => storage_error(err),
+    }
+}
+
+/// 查询 Webhook 推送日志
+pub async fn list_webhook_deliveries(
+    State(state): State<AppState>,
+    Path(path): Path<WebhookProjectPath>,
+    Query(query): Query<WebhookDeliveryQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = match require_project_scope(&state, &headers, &path.project_id).await {
+        Ok(ctx) => ctx,
+        Err(response) => return response,
+    };
+    if let Err(response) = require_permission(&ctx, permissions::PROJECT_READ) {
+        return response;
+    }
+    if let Some(status) = query.status.as_deref() {
+        if !matches!(status, "success" | "failed") {
+            return bad_request_error("status must be success or failed");
+        }
+    }
+    let options = ems_storage::WebhookDeliveryQuery {
+        subscription_id: query.subscription_id,
+        status: query.status,
+        limit: query
+            .limit
+            .unwrap_or(DEFAULT_DELIVERY_LIMIT)
+            .clamp(1, MAX_DELIVERY_LIMIT),
+    };
+    match state
+        .webhook_store
+        .list_webhook_deliveries(&ctx, &path.project_id, options)
+        .await
+    {
+        Ok(items) => {
+            let data: Vec<WebhookDeliveryDto> =
+                items.into_iter().map(webhook_delivery_to_dto).collect();
+            (StatusCode::OK, Json(ApiResponse::success(data))).into_response()
+        }
+        Err(err) => storage_error(err),
+    }
+}
+
+fn now_epoch_ms() -> i64 {
+    let now = std::time::SystemTime::now();
+    let duration = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    duration.as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{api_router, auth_headers, build_state, json_request, response_json};
+    use axum::http::StatusCode;
+    use ems_events::{WebhookDispatcherConfig, spawn_webhook_dispatcher};
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    /// 测试：Webhook 订阅管理、gateway.created 事件推送与失败推送日志
+    #[tokio::test]
+    async fn webhook_subscription_receives_gateway_created() {
+        let state = build_state();
+        let headers = auth_headers(&state).await;
+        let _dispatcher = spawn_webhook_dispatcher(
+            &state.event_bus,
+            state.webhook_store.clone(),
+            WebhookDispatcherConfig {
+                max_attempts: 2,
+                backoff_ms: 0,
+                timeout_ms: 1000,
+                allow_private_targets: false,
+            },
+        )
+        .expect("webhook dispatcher");
+        let mut events = state.event_bus.subscribe();
+        let app = api_router(state);
+        let request = |method: &str, uri: &str, body: Option<Value>| {
+            json_request(&headers, method, uri, body)
+        };
+
+        // 不支持的事件类型
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/api/v1/projects/project-1/webhooks",
+                Some(serde_json::json!({"url": "http://127.0.0.1:1/hook", "eventTypes": ["device.deleted"]})),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // 回环 / 云元数据等内网地址不允许
+        for url in ["http://127.0.0.1:1/hook", "http://169.254.169.254/latest"] {
+            let response = app
+                .clone()
+                .oneshot(request(
+                    "POST",
+                    "/api/v1/projects/project-1/webhooks",
+                    Some(serde_json::json!({"url": url, "eventTypes": ["gateway.created"]})),
+                ))
+                .await
+                .expect("response");
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+
+        // 创建订阅：密钥仅在创建时返回（.invalid 域名无法解析，推送必然失败）
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/api/v1/projects/project-1/webhooks",
+                Some(serde_json::json!({"url": "http://webhook.invalid/hook", "eventTypes": ["gateway.created"]})),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let json = response_json(response).await;
+        let subscription_id = json["data"]["subscriptionId"]
+            .as_str()
+            .expect("subscription id")
+            .to_string();
+        assert!(
+            json["data"]["secret"]
+                .as_str()
+                .is_some_and(|secret| secret.starts_with("whsec_"))
+        );
+
+        let response = app
+            .clone()
+            .oneshot(request("GET", "/api/v1/projects/project-1/webhooks", None))
+            .await
+            .expect("response");
+        let json = response_json(response).await;
+        assert_eq!(json["data"][0]["eventTypes"][0], "gateway.created");
+        assert!(json["data"][0].get("secret").is_none());
+
+        // 创建网关发布 gateway.created
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/api/v1/projects/project-1/gateways",
+                Some(serde_json::json!({"name": "gw-1"})),
+            ))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let event = events.recv().await.expect("event");
+        assert_eq!(event.event_type, "gateway.created");
+        assert_eq!(event.data["name"], "gw-1");
+
+        // 推送失败（重试后）写入推送日志
+        let mut deliveries = Value::Null;
+        for _ in 0..50 {
+            let response = app
+                .clone()
+                .oneshot(request(
+                    "GET",
+                    "/api/v1/projects/project-1/webhooks/deliveries?status=failed",
+                    None,
+                ))
+                .await
+                .expect("response");
+            deliveries = response_json(response).await;
+            if deliveries["data"]
+                .as_array()
+                .is_some_and(|items| !items.is_empty())
+            {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        assert_eq!(
+            deliveries["data"][0]["subscriptionId"],
+            subscription_id.as_str()
+        );
+        assert_eq!(deliveries["data"][0]["eventId"], event.event_id.as_str());
+        assert_eq!(deliveries["data"][0]["attempts"], 2);
+
+        let uri = format!("/api/v1/projects/project-1/webhooks/{}", subscription_id);
+        let response = app
+            .clone()
+            .oneshot(request("DELETE", &uri, None))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app
+            .oneshot(request("DELETE", &uri, None))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -3,23 +3,33 @@
 //! 该模块负责将数据采集的各个组件（数据源、规整器、处理流水线、存储层）组装在一起，
 //! 构建完整的数据处理链路。它定义了如何从数据源（如 MQTT）接收原始数据，
 //! 经过标准化处理后，通过流水线写入存储，并同步更新设备的在线状态。
+//! 进入流水线前按租户累计当日测量值用量，超出 `measurements` 配额的数据直接丢弃（限流）。
+//!
+//! 许多网关从不单独上报心跳，因此到达的测量值即视为所属设备及其网关的活跃信号：
+//! [`OnlineTracker`] 由采集链路与 HTTP / gRPC 点位写入共用（`EMS_ONLINE_FROM_DATA`，默认开启）。
 
 use ems_config::AppConfig;
 use ems_ingest::{IngestError, MqttSource, MqttSourceConfig, NoopSource, RawEventHandler, Source};
 use ems_normalize::{Normalizer, StoragePointMappingProvider};
-use ems_pipeline::{Pipeline, PipelineError, StoragePointValueWriter};
+use ems_pipeline::{Pipeline, PipelineConfig, PipelineError, StoragePointValueWriter};
 use ems_storage::{
     DeviceStore, MeasurementStore, OnlineStore, PointMappingStore, PointStore, RealtimeStore,
+    StorageError,
 };
 use ems_telemetry::{
     record_backpressure, record_dropped_duplicate, record_dropped_invalid, record_dropped_stale,
     record_dropped_unmapped, record_end_to_end_latency_ms, record_normalized_value,
     record_raw_event, record_write_failure, record_write_latency_ms, record_write_success,
 };
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
+use tokio::sync::watch;
 use tracing::{info, warn};
 
+use crate::reload::RuntimeSettings;
+use crate::usage_meter::UsageMeter;
+
 /// 流水线处理器
 ///
 /// 实现了 `RawEventHandler` 接口，负责处理从采集源接收到的原始事件。
@@ -29,12 +39,10 @@ struct PipelineHandler {
     normalizer: Normalizer,
     /// 数据流水线，负责点位值的后续处理和持久化写入
     pipeline: Pipeline,
-    /// 点位存储，用于查询点位详情
-    point_store: Arc<dyn PointStore>,
-    /// 设备存储，用于查询设备详情
-    device_store: Arc<dyn DeviceStore>,
-    /// 在线状态存储，用于记录设备和网关的活跃状态
-    online_store: Arc<dyn OnlineStore>,
+    /// 数据活跃在线跟踪，用于刷新设备和网关的活跃状态
+    online_tracker: OnlineTracker,
+    /// 用量计量缓冲，用于检查测量值配额并累计去重后的用量
+    usage_meter: Arc<UsageMeter>,
 }
 
 #[async_trait::async_trait]
@@ -42,7 +50,7 @@ impl RawEventHandler for PipelineHandler {
     /// 处理接收到的原始采集事件
     async fn handle(&self, event: domain::RawEvent) -> Result<(), IngestError> {
         // 记录原始事件指标
-        record_raw_event();
+        record_raw_event(&event.tenant_id);
         info!(
             target: "ems.ingest",
             tenant_id = %event.tenant_id,
@@ -94,32 +102,57 @@ impl RawEventHandler for PipelineHandler {
             "point_value_normalized"
         );
 
-        // 2. 流水线处理：负责过滤、去重并最终写入存储
+        let ctx = domain::TenantContext::new(
+            tenant_id.clone(),
+            "system".to_string(),
+            Vec::new(),
+            Vec::new(),
+            Some(project_id.clone()),
+        );
+
+        // 2. 配额：按缓存的租户当日测量值用量检查，超出配额时丢弃（计量失败时放行，不阻断采集）
+        let period_start_ms = domain::usage::day_start_ms(now_epoch_ms());
+        match self
+            .usage_meter
+            .has_quota(&ctx, domain::usage::MEASUREMENTS, period_start_ms, 1)
+            .await
+        {
+            Ok(true) => {}
+            Ok(false) => {
+                warn!(
+                    target: "ems.ingest",
+                    tenant_id = %tenant_id,
+                    project_id = %project_id,
+                    point_id = %point_id,
+                    ts_ms = ts_ms,
+                    "measurement_quota_exceeded"
+                );
+                return Ok(());
+            }
+            Err(err) => {
+                warn!(target: "ems.ingest", tenant_id = %tenant_id, error = %err, "usage_meter_failed");
+            }
+        }
+
+        // 3. 流水线处理：负责过滤、去重并最终写入存储
         let write_started_at = Instant::now();
         match self.pipeline.handle(value).await {
             Ok(result) => {
-                // 3. 更新在线状态：根据成功处理的点位，更新设备和网关的最后活跃时间
-                let ctx = domain::TenantContext::new(
-                    tenant_id.clone(),
-                    "system".to_string(),
-                    Vec::new(),
-                    Vec::new(),
-                    Some(project_id.clone()),
-                );
-                let _ = touch_online_from_point(
-                    &ctx,
-                    &project_id,
-                    &point_id,
-                    ts_ms,
-                    self.point_store.clone(),
-                    self.device_store.clone(),
-                    self.online_store.clone(),
-                )
-                .await;
+                // 去重、过滤后实际进入写入的测量值才计入用量
+                if result.written || result.reason.as_deref() == Some("queued") {
+                    self.usage_meter
+                        .record(&ctx, domain::usage::MEASUREMENTS, period_start_ms, 1);
+                }
+
+                // 4. 更新在线状态：根据成功处理的点位，更新设备和网关的最后活跃时间
+                let _ = self
+                    .online_tracker
+                    .touch_points(&ctx, &project_id, [(point_id.as_str(), ts_ms)])
+                    .await;
 
                 // 物理写入成功后记录各类指标
                 if result.written {
-                    record_write_success();
+                    record_write_success(&tenant_id);
                     record_write_latency_ms(write_started_at.elapsed().as_millis() as u64);
                     if let Some(latency_ms) = end_to_end_latency_ms(ts_ms) {
                         record_end_to_end_latency_ms(latency_ms);
@@ -197,51 +230,81 @@ fn now_epoch_ms() -> i64 {
     duration.as_millis() as i64
 }
 
+/// 由应用配置构造流水线参数（EMS_PIPELINE_*）
+pub fn pipeline_config(config: &AppConfig) -> PipelineConfig {
+    PipelineConfig {
+        batch_size: config.pipeline_batch_size as usize,
+        max_buffer_size: config.pipeline_max_buffer_size as usize,
+        max_retries: config.pipeline_max_retries as usize,
+        dedup_cache_size: config.pipeline_dedup_cache_size as usize,
+        max_age_ms: config.pipeline_max_age_ms.map(|value| value as i64),
+    }
+}
+
+/// 采集任务依赖的存储
+pub struct IngestStores {
+    /// 点位映射规则存储
+    pub point_mapping_store: Arc<dyn PointMappingStore>,
+    /// 历史时序数据存储
+    pub measurement_store: Arc<dyn MeasurementStore>,
+    /// 实时点位值存储
+    pub realtime_store: Arc<dyn RealtimeStore>,
+    /// 数据活跃在线跟踪（与 HTTP / gRPC 写入共用归属缓存）
+    pub online_tracker: OnlineTracker,
+    /// 用量计量缓冲（测量值配额，与 HTTP / gRPC 写入共用）
+    pub usage_meter: Arc<UsageMeter>,
+}
+
 /// 启动采集任务
 ///
 /// 该函数负责初始化规整器、流水线、数据源，并启动后台任务。
 ///
 /// # 参数
 /// - `config`: 应用程序统一配置
-/// - `point_mapping_store`: 点位映射规则存储
-/// - `point_store`: 点位元数据存储
-/// - `device_store`: 设备元数据存储
-/// - `measurement_store`: 历史时序数据存储
-/// - `realtime_store`: 实时点位值存储
-/// - `online_store`: 在线状态存储
+/// - `stores`: 采集依赖的存储（映射、时序、实时值、在线跟踪、用量）
+/// - `settings`: 热加载设置（流水线批量大小、刷盘间隔）
 pub fn spawn_ingest(
     config: &AppConfig,
-    point_mapping_store: Arc<dyn PointMappingStore>,
-    point_store: Arc<dyn PointStore>,
-    device_store: Arc<dyn DeviceStore>,
-    measurement_store: Arc<dyn MeasurementStore>,
-    realtime_store: Arc<dyn RealtimeStore>,
-    online_store: Arc<dyn OnlineStore>,
+    stores: IngestStores,
+    settings: watch::Receiver<RuntimeSettings>,
 ) -> tokio::task::JoinHandle<()> {
     // 初始化规整化服务
-    let provider = StoragePointMappingProvider::new(point_mapping_store);
+    let provider = StoragePointMappingProvider::new(stores.point_mapping_store);
     let normalizer = Normalizer::new(Arc::new(provider));
 
     // 初始化流水线写入器
-    let writer = StoragePointValueWriter::new(measurement_store, realtime_store);
-    let pipeline = Pipeline::new(Arc::new(writer));
+    let writer = StoragePointValueWriter::new(stores.measurement_store, stores.realtime_store);
+    let pipeline = Pipeline::with_config(Arc::new(writer), pipeline_config(config));
 
     // 创建全局唯一的流水线处理器
     let handler = Arc::new(PipelineHandler {
         normalizer,
         pipeline,
-        point_store,
-        device_store,
-        online_store,
+        online_tracker: stores.online_tracker,
+        usage_meter: stores.usage_meter,
     });
 
-    // 1. 如果启用了采集，启动流水线定时刷盘任务
+    // 1. 如果启用了采集，启动热加载订阅与流水线定时刷盘任务
     if config.ingest_enabled {
+        let pipeline = handler.pipeline.clone();
+        let mut updates = settings.clone();
+        tokio::spawn(async move {
+            // 批量大小变更时重配流水线（已缓冲数据保留）
+            while updates.changed().await.is_ok() {
+                let batch_size = updates.borrow_and_update().pipeline_batch_size;
+                pipeline.reconfigure(PipelineConfig {
+                    batch_size,
+                    ..pipeline.config()
+                });
+            }
+        });
+
         let pipeline = handler.pipeline.clone();
         tokio::spawn(async move {
             loop {
-                // 每秒触发一次刷新，确保缓冲的数据能够及时写入
-                tokio::time::sleep(Duration::from_secs(1)).await;
+                // 按刷盘间隔（默认 1 秒，可热加载）触发刷新，确保缓冲的数据能够及时写入
+                let interval_ms = settings.borrow().pipeline_flush_interval_ms.max(1);
+                tokio::time::sleep(Duration::from_millis(interval_ms)).await;
                 match pipeline.flush().await {
                     Ok(pairs) => {
                         if pairs.is_empty() {
@@ -257,7 +320,7 @@ pub fn spawn_ingest(
 
                             // 记录批量写入成功的延迟指标
                             if result.written {
-                                record_write_success();
+                                record_write_success(&tenant_id);
                                 if let Some(latency_ms) = end_to_end_latency_ms(ts_ms) {
                                     record_end_to_end_latency_ms(latency_ms);
                                 }
@@ -312,37 +375,330 @@ pub fn spawn_ingest(
     })
 }
 
-/// 更新设备和网关的在线状态
+/// 点位归属缓存有效期：设备改挂网关、点位改挂设备后最迟一个周期生效
+const OWNER_CACHE_TTL: Duration = Duration::from_secs(60);
+/// 未登记点位的负缓存有效期：期间不再为同一点位查询元数据，新建点位最迟一个周期生效
+const UNKNOWN_OWNER_CACHE_TTL: Duration = Duration::from_secs(10);
+/// 点位归属缓存容量上限（超过后先清理过期项，仍超限则整体清空）
+const OWNER_CACHE_CAPACITY: usize = 100_000;
+
+/// 点位所属的设备与网关
+#[derive(Clone)]
+struct PointOwner {
+    device_id: String,
+    gateway_id: String,
+}
+
+/// 归属缓存项：`owner` 为空表示点位未登记或已删除（负缓存）
+#[derive(Clone)]
+struct CachedOwner {
+    owner: Option<PointOwner>,
+    cached_at: Instant,
+}
+
+impl CachedOwner {
+    fn is_fresh(&self) -> bool {
+        let ttl = if self.owner.is_some() {
+            OWNER_CACHE_TTL
+        } else {
+            UNKNOWN_OWNER_CACHE_TTL
+        };
+        self.cached_at.elapsed() < ttl
+    }
+}
+
+/// 数据活跃在线跟踪
 ///
-/// 根据上报点位所属的设备信息，向 在线状态存储 发送一个 "活跃" 信号。
-async fn touch_online_from_point(
-    ctx: &domain::TenantContext,
-    project_id: &str,
-    point_id: &str,
-    ts_ms: i64,
+/// 任何到达的测量值都会刷新所属设备及其父网关在 `OnlineStore` 中的最后活跃时间，
+/// 点位 → (设备, 网关) 的归属经缓存解析，避免每个测量值都查询元数据存储；
+/// 未登记的点位短期负缓存，持续上报未知点位时不会反复整项目重扫。
+/// 关闭（`EMS_ONLINE_FROM_DATA=off`）时只依赖显式心跳。
+#[derive(Clone)]
+pub struct OnlineTracker {
+    enabled: bool,
     point_store: Arc<dyn PointStore>,
     device_store: Arc<dyn DeviceStore>,
     online_store: Arc<dyn OnlineStore>,
-) -> Result<(), ems_storage::StorageError> {
-    // 查找点位，获取所属设备 ID
-    let point = point_store.find_point(ctx, project_id, point_id).await?;
-    let Some(point) = point else {
-        return Ok(());
-    };
-    // 查找设备，获取所属网关 ID
-    let device = device_store
-        .find_device(ctx, project_id, &point.device_id)
-        .await?;
-    let Some(device) = device else {
-        return Ok(());
+    owners: Arc<Mutex<HashMap<String, CachedOwner>>>,
+}
+
+impl OnlineTracker {
+    pub fn new(
+        enabled: bool,
+        point_store: Arc<dyn PointStore>,
+        device_store: Arc<dyn DeviceStore>,
+        online_store: Arc<dyn OnlineStore>,
+    ) -> Self {
+        Self {
+            enabled,
+            point_store,
+            device_store,
+            online_store,
+            owners: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// 按测量值刷新设备与网关在线状态
+    ///
+    /// 同一批内每个设备 / 网关只刷新一次，取最大时间戳；未登记或已删除的点位忽略。
+    pub async fn touch_points<'a>(
+        &self,
+        ctx: &domain::TenantContext,
+        project_id: &str,
+        points: impl IntoIterator<Item = (&'a str, i64)>,
+    ) -> Result<(), StorageError> {
+        if !self.enabled {
+            return Ok(());
+        }
+        let points: Vec<(&str, i64)> = points.into_iter().collect();
+        let owners = self
+            .resolve_owners(
+                ctx,
+                project_id,
+                points.iter().map(|(point_id, _)| *point_id),
+            )
+            .await?;
+        let mut devices: HashMap<&str, i64> = HashMap::new();
+        let mut gateways: HashMap<&str, i64> = HashMap::new();
+        for (point_id, ts_ms) in &points {
+            let Some(owner) = owners.get(*point_id) else {
+                continue;
+            };
+            let device = devices.entry(owner.device_id.as_str()).or_insert(*ts_ms);
+            *device = (*device).max(*ts_ms);
+            let gateway = gateways.entry(owner.gateway_id.as_str()).or_insert(*ts_ms);
+            *gateway = (*gateway).max(*ts_ms);
+        }
+        for (device_id, ts_ms) in devices {
+            self.online_store
+                .touch_device(ctx, project_id, device_id, ts_ms)
+                .await?;
+        }
+        for (gateway_id, ts_ms) in gateways {
+            self.online_store
+                .touch_gateway(ctx, project_id, gateway_id, ts_ms)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// 解析点位归属：先查缓存；仅一个未命中时按 ID 查询，多个未命中时整项目加载一次；
+    /// 加载后仍未找到的点位写入负缓存
+    async fn resolve_owners<'a>(
+        &self,
+        ctx: &domain::TenantContext,
+        project_id: &str,
+        point_ids: impl Iterator<Item = &'a str>,
+    ) -> Result<HashMap<String, PointOwner>, StorageError> {
+        let mut owners = HashMap::new();
+        let mut missing: Vec<&str> = Vec::new();
+        let mut seen = HashSet::new();
+        if let Ok(cache) = self.owners.lock() {
+            for point_id in point_ids {
+                if !seen.insert(point_id) {
+                    continue;
+                }
+                match cache.get(&owner_key(&ctx.tenant_id, project_id, point_id)) {
+                    Some(cached) if cached.is_fresh() => {
+                        if let Some(owner) = &cached.owner {
+                            owners.insert(point_id.to_string(), owner.clone());
+                        }
+                    }
+                    _ => missing.push(point_id),
+                }
+            }
+        }
+        let loaded: HashMap<String, PointOwner> = match missing.as_slice() {
+            [] => return Ok(owners),
+            [point_id] => self.load_owner(ctx, project_id, point_id).await?,
+            _ => {
+                let missing: HashSet<&str> = missing.iter().copied().collect();
+                self.load_project_owners(ctx, project_id, &missing).await?
+            }
+        }
+        .into_iter()
+        .collect();
+        if let Ok(mut cache) = self.owners.lock() {
+            if cache.len() + missing.len() > OWNER_CACHE_CAPACITY {
+                cache.retain(|_, cached| cached.is_fresh());
+                if cache.len() + missing.len() > OWNER_CACHE_CAPACITY {
+                    cache.clear();
+                }
+            }
+            let now = Instant::now();
+            for point_id in &missing {
+                cache.insert(
+                    owner_key(&ctx.tenant_id, project_id, point_id),
+                    CachedOwner {
+                        owner: loaded.get(*point_id).cloned(),
+                        cached_at: now,
+                    },
+                );
+            }
+        }
+        owners.extend(loaded);
+        Ok(owners)
+    }
+
+    async fn load_owner(
+        &self,
+        ctx: &domain::TenantContext,
+        project_id: &str,
+        point_id: &str,
+    ) -> Result<Vec<(String, PointOwner)>, StorageError> {
+        // 查找点位，获取所属设备 ID
+        let Some(point) = self
+            .point_store
+            .find_point(ctx, project_id, point_id)
+            .await?
+        else {
+            return Ok(Vec::new());
+        };
+        // 查找设备，获取所属网关 ID
+        let Some(device) = self
+            .device_store
+            .find_device(ctx, project_id, &point.device_id)
+            .await?
+        else {
+            return Ok(Vec::new());
+        };
+        Ok(vec![(
+            point.point_id,
+            PointOwner {
+                device_id: device.device_id,
+                gateway_id: device.gateway_id,
+            },
+        )])
+    }
+
+    async fn load_project_owners(
+        &self,
+        ctx: &domain::TenantContext,
+        project_id: &str,
+        point_ids: &HashSet<&str>,
+    ) -> Result<Vec<(String, PointOwner)>, StorageError> {
+        let gateways: HashMap<String, String> = self
+            .device_store
+            .list_devices(ctx, project_id)
+            .await?
+            .into_iter()
+            .map(|device| (device.device_id, device.gateway_id))
+            .collect();
+        Ok(self
+            .point_store
+            .list_points(ctx, project_id)
+            .await?
+            .into_iter()
+            .filter(|point| point_ids.contains(&point.point_id.as_str()))
+            .filter_map(|point| {
+                let gateway_id = gateways.get(&point.device_id)?.clone();
+                Some((
+                    point.point_id,
+                    PointOwner {
+                        device_id: point.device_id,
+                        gateway_id,
+                    },
+                ))
+            })
+            .collect())
+    }
+}
+
+fn owner_key(tenant_id: &str, project_id: &str, point_id: &str) -> String {
+    format!("{}:{}:{}", tenant_id, project_id, point_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ems_storage::{
+        DeviceRecord, InMemoryDeviceStore, InMemoryOnlineStore, InMemoryPointStore, PointRecord,
     };
-    // 更新设备心跳
-    online_store
-        .touch_device(ctx, project_id, &device.device_id, ts_ms)
-        .await?;
-    // 更新网关心跳
-    online_store
-        .touch_gateway(ctx, project_id, &device.gateway_id, ts_ms)
-        .await?;
-    Ok(())
+
+    fn point(point_id: &str) -> PointRecord {
+        PointRecord {
+            point_id: point_id.to_string(),
+            tenant_id: "tenant-1".to_string(),
+            project_id: "project-1".to_string(),
+            device_id: "device-1".to_string(),
+            key: point_id.to_string(),
+            data_type: "float".to_string(),
+            unit: None,
+            tags: Vec::new(),
+        }
+    }
+
+    /// 测试：未登记点位负缓存，有效期内新建的点位不触发重新加载
+    #[tokio::test]
+    async fn unknown_points_are_negatively_cached() {
+        let ctx = domain::TenantContext::new(
+            "tenant-1".to_string(),
+            "user-1".to_string(),
+            Vec::new(),
+            Vec::new(),
+            Some("project-1".to_string()),
+        );
+        let point_store: Arc<dyn PointStore> = Arc::new(InMemoryPointStore::new());
+        let device_store: Arc<dyn DeviceStore> = Arc::new(InMemoryDeviceStore::new());
+        let online_store: Arc<dyn OnlineStore> = Arc::new(InMemoryOnlineStore::new());
+        device_store
+            .create_device(
+                &ctx,
+                DeviceRecord {
+                    device_id: "device-1".to_string(),
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    gateway_id: "gateway-1".to_string(),
+                    name: "meter".to_string(),
+                    model: None,
+                    room_id: None,
+                    address_config: None,
+                    offline_after_seconds: None,
+                },
+            )
+            .await
+            .expect("device");
+        let tracker = OnlineTracker::new(
+            true,
+            point_store.clone(),
+            device_store,
+            online_store.clone(),
+        );
+
+        // 两个未知点位：整项目加载一次，结果写入负缓存
+        tracker
+            .touch_points(&ctx, "project-1", [("point-1", 1_000), ("point-2", 1_000)])
+            .await
+            .expect("touch");
+        for point_id in ["point-1", "point-2"] {
+            point_store
+                .create_point(&ctx, point(point_id))
+                .await
+                .expect("point");
+        }
+        tracker
+            .touch_points(&ctx, "project-1", [("point-1", 2_000), ("point-2", 2_000)])
+            .await
+            .expect("touch");
+        let device_seen = online_store
+            .get_device_last_seen_at_ms(&ctx, "project-1", "device-1")
+            .await
+            .expect("online");
+        assert_eq!(device_seen, None);
+
+        // 未被负缓存的点位照常解析
+        point_store
+            .create_point(&ctx, point("point-3"))
+            .await
+            .expect("point");
+        tracker
+            .touch_points(&ctx, "project-1", [("point-3", 3_000)])
+            .await
+            .expect("touch");
+        let device_seen = online_store
+            .get_device_last_seen_at_ms(&ctx, "project-1", "device-1")
+            .await
+            .expect("online");
+        assert_eq!(device_seen, Some(3_000));
+    }
 }
@@ -0,0 +1,102 @@
+//! 后台任务类型
+//!
+//! 在 `JobRunner` 上注册 API 提供的任务类型：
+//! - `report.power_quality`：电能质量报表（参数同 `GET /projects/{id}/reports/power-quality`），
+//!   逐台设备计算并上报进度，结果为 `PowerQualityReportDto`
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use api_contract::{PowerQualityQuery, PowerQualityReportDto};
+use ems_analytics::{PowerQualityAnalyzer, point_role};
+use ems_jobs::{JobContext, JobHandler, JobRunner, JobRunnerConfig};
+use ems_storage::{JobStore, MeasurementStore, PointStore};
+
+use crate::handlers::{PowerQualityParams, device_power_quality_to_dto};
+
+/// 任务类型：电能质量报表
+pub const POWER_QUALITY_REPORT_JOB: &str = "report.power_quality";
+
+/// 创建任务执行器并注册全部任务类型
+pub fn build_job_runner(
+    job_store: Arc<dyn JobStore>,
+    point_store: Arc<dyn PointStore>,
+    measurement_store: Arc<dyn MeasurementStore>,
+    config: &JobRunnerConfig,
+) -> JobRunner {
+    let mut runner = JobRunner::new(job_store, config);
+    runner.register(
+        POWER_QUALITY_REPORT_JOB,
+        Arc::new(PowerQualityReportJob {
+            analyzer: PowerQualityAnalyzer::new(point_store.clone(), measurement_store),
+            point_store,
+        }),
+    );
+    runner
+}
+
+/// 电能质量报表任务
+struct PowerQualityReportJob {
+    analyzer: PowerQualityAnalyzer,
+    point_store: Arc<dyn PointStore>,
+}
+
+#[async_trait::async_trait]
+impl JobHandler for PowerQualityReportJob {
+    async fn run(&self, job: &JobContext) -> Result<serde_json::Value, String> {
+        let ctx = job.tenant_context();
+        let project_id = job
+            .record()
+            .project_id
+            .clone()
+            .ok_or_else(|| "project required".to_string())?;
+        let params = PowerQualityParams::parse(job.params::<PowerQualityQuery>()?)?;
+        let points = self
+            .point_store
+            .list_points(ctx, &project_id)
+            .await
+            .map_err(|err| err.to_string())?;
+        // 只计算带角色标签点位的设备，按设备 ID 排序以稳定进度与结果顺序
+        let device_ids: BTreeSet<String> = points
+            .into_iter()
+            .filter(|point| point_role(point).is_some())
+            .filter(|point| {
+                params
+                    .device_id
+                    .as_deref()
+                    .is_none_or(|device_id| point.device_id == device_id)
+            })
+            .map(|point| point.device_id)
+            .collect();
+        let total = device_ids.len().max(1);
+        let mut devices = Vec::with_capacity(device_ids.len());
+        for (index, device_id) in device_ids.iter().enumerate() {
+            let items = self
+                .analyzer
+                .analyze(
+                    ctx,
+                    &project_id,
+                    Some(device_id),
+                    params.from,
+                    params.to,
+                    params.window,
+                )
+                .await
+                .map_err(|err| err.to_string())?;
+            devices.extend(items.into_iter().map(device_power_quality_to_dto));
+            let progress = ((index + 1) * 100 / total) as i32;
+            if !job.set_progress(progress).await {
+                return Err("cancelled".to_string());
+            }
+        }
+        let report = PowerQualityReportDto {
+            project_id,
+            from: params.from,
+            to: params.to,
+            window_minutes: params.window.window_minutes,
+            subinterval_minutes: params.window.subinterval_minutes,
+            devices,
+        };
+        serde_json::to_value(report).map_err(|err| err.to_string())
+    }
+}
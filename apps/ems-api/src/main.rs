@@ -87,16 +87,41 @@
 //! - [`ems_config`]: 应用配置管理
 //! - [`ems_storage`]: 存储层抽象和实现（PostgreSQL、Redis）
 //! - [`ems_control`]: 设备控制服务（MQTT 指令分发）
+//! - [`ems_analytics`]: 用能基线与异常检测（后台检测任务）
 //! - [`ems_telemetry`]: 遥测和日志系统
 
 // ============================================================================
 // 本地模块声明
 // ============================================================================
 
+/// 启动前自检模块
+/// `--check-config`：校验配置并探测数据库 / Redis / MQTT 连通性后退出
+mod check;
+
 /// HTTP 请求处理器模块
 /// 包含所有 API 端点的具体处理逻辑（登录、项目管理、设备管理等）
 mod handlers;
 
+/// GraphQL schema 模块
+/// 资产层级 + 最新值 + 历史序列的按需查询（字段级权限 guard）
+mod graphql;
+
+/// gRPC 服务模块
+/// 提供 WritePoints / StreamRealtime / IssueCommand RPC（通过 EMS_GRPC_ADDR 启用）
+mod grpc;
+
+/// 后台任务类型模块
+/// 在任务执行器上注册 API 提供的任务类型（如电能质量报表）
+mod jobs;
+
+/// Modbus TCP 从站模块
+/// 为 modbus_server 网关启动只读从站，寄存器值取点位最新值（通过 EMS_MODBUS_SERVER_ENABLED 启用）
+mod modbus_server;
+
+/// 运维监听模块
+/// 独立端口提供指标、健康检查、运行时统计与脱敏配置（通过 EMS_OPS_ADDR 启用）
+mod ops;
+
 /// 数据采集模块
 /// 负责从 MQTT 接收遥测数据并写入存储层
 mod ingest;
@@ -105,20 +130,33 @@ mod ingest;
 /// 包含请求上下文注入、认证校验等中间件
 mod middleware;
 
+/// 运行时配置热加载模块
+/// SIGHUP / POST /ops/reload 重新读取日志级别与流水线参数，经 watch 通道推送给子系统
+mod reload;
+
 /// 路由配置模块
 /// 定义所有 API 路由及其对应的处理器
 mod routes;
 
+/// 高频用量计量缓冲模块
+/// API 调用与测量值用量在内存累计、定期批量写入用量表，配额按缓存计数检查
+mod usage_meter;
+
 /// 工具函数模块
 /// 包含通用的辅助函数和工具类
 mod utils;
 
+/// 测试辅助模块
+/// 各处理器测试共用的内存 AppState、认证请求头与请求构造
+#[cfg(test)]
+mod test_support;
+
 // ============================================================================
 // 外部依赖导入
 // ============================================================================
 
 // Axum Web 框架 —— 高性能异步 HTTP 服务器框架
-use axum::{Router, middleware as axum_middleware};
+use axum::middleware as axum_middleware;
 
 // 认证模块 —— JWT 令牌管理和用户认证服务
 use ems_auth::{AuthService, JwtManager};
@@ -128,28 +166,86 @@ use ems_config::AppConfig;
 
 // 控制模块 —— 设备控制指令发送和回执处理
 use ems_control::{
-    CommandService,            // 控制指令服务（封装指令创建、分发、重试逻辑）
-    CommandServiceConfig,      // 控制服务配置（重试次数、超时等）
-    MqttDispatcher,            // MQTT 指令分发器（通过 MQTT 发送控制指令）
-    MqttDispatcherConfig,      // MQTT 分发器配置（连接信息、主题前缀等）
-    MqttReceiptListenerConfig, // MQTT 回执监听器配置
-    NoopDispatcher,            // 空操作分发器（用于禁用控制功能时）
-    spawn_receipt_listener,    // 启动回执监听后台任务
+    CommandService,                  // 控制指令服务（封装指令创建、分发、重试逻辑）
+    CommandServiceConfig,            // 控制服务配置（重试次数、超时等）
+    DeviceShadowService,             // 设备影子服务（期望状态 + 上报推导 + 差量下发）
+    FirmwareService,                 // 网关固件升级服务（升级批次 + 发布 + 进度回执）
+    GatewayConfigService,            // 网关配置下发服务（版本化配置 + 发布 + 回执）
+    GatewayPayloadFormats,           // 网关命令载荷格式（按命令 target 所属网关编码载荷）
+    MaintenanceService,              // 设备 / 网关维护模式服务（维护窗口 + 命令拦截判断）
+    MqttDispatcher,                  // MQTT 指令分发器（通过 MQTT 发送控制指令）
+    MqttDispatcherConfig,            // MQTT 分发器配置（连接信息、主题前缀等）
+    MqttReceiptListenerConfig,       // MQTT 回执监听器配置
+    NoopConfigPublisher,             // 空操作配置发布器（用于禁用控制功能时）
+    NoopDispatcher,                  // 空操作分发器（用于禁用控制功能时）
+    NoopFirmwarePublisher,           // 空操作固件升级发布器（用于禁用控制功能时）
+    spawn_config_receipt_listener,   // 启动网关配置回执监听后台任务
+    spawn_firmware_receipt_listener, // 启动固件升级回执监听后台任务
+    spawn_receipt_listener,          // 启动回执监听后台任务
+};
+
+// 事件模块 —— 领域事件总线与 Webhook 推送
+use ems_events::{
+    DeviceEventRecorder, EventBus, WebhookDispatcherConfig, spawn_device_event_recorder,
+    spawn_webhook_dispatcher,
+};
+
+// 规则模块 —— 自动化规则引擎（触发条件评估 + 动作执行）
+use ems_rules::{RuleEngine, RuleEngineConfig, spawn_rule_engine};
+
+// 分析模块 —— 用能基线与异常检测（周内同时段基线 → 异常记录 / 告警）
+use ems_analytics::{AnomalyDetector, AnomalyDetectorConfig, spawn_anomaly_detector};
+use ems_presence::{PresenceWatcher, PresenceWatcherConfig, spawn_presence_watcher};
+
+// 计划模块 —— 控制计划执行器（cron + 项目时区 → 周期性命令）
+use ems_demand::{DemandResponseRunner, DemandResponseRunnerConfig, spawn_demand_response_runner};
+use ems_schedule::{ScheduleRunner, ScheduleRunnerConfig, spawn_schedule_runner};
+
+// 后台任务模块 —— 长耗时操作（报表等）异步执行，记录状态、进度与结果
+use ems_jobs::{JobRunner, JobRunnerConfig};
+
+// 导出模块 —— 已持久化测量值流式发布到 Kafka / NATS
+use ems_export::{
+    ExporterConfig, ExportingMeasurementStore, build_publisher, spawn_exporter,
+    validate_topic_template,
 };
 
+// 演示数据模块 —— EMS_SEED_DEMO=on 时写入演示租户与数据
+use ems_seed::{SeedOptions, SeedStores, seed_demo};
+
 // 存储模块 —— 数据持久化层实现
 use ems_storage::{
     // PostgreSQL 存储实现
-    PgAuditLogStore,       // 审计日志存储（记录用户操作）
-    PgCommandReceiptStore, // 控制指令回执存储
-    PgCommandStore,        // 控制指令存储
-    PgDeviceStore,         // 设备信息存储
-    PgGatewayStore,        // 网关信息存储
-    PgMeasurementStore,    // 历史测量数据存储（时序数据）
-    PgPointMappingStore,   // 测点映射存储（外部标识 → 内部 ID）
-    PgPointStore,          // 测点定义存储
-    PgProjectStore,        // 项目信息存储
-    PgUserStore,           // 用户信息存储
+    PgAnomalyStore,             // 用能异常存储（检测任务写入）
+    PgAuditLogStore,            // 审计日志存储（记录用户操作）
+    PgCommandReceiptStore,      // 控制指令回执存储
+    PgCommandStore,             // 控制指令存储
+    PgDemandResponseStore,      // 需求响应可削减负荷与事件存储
+    PgDeviceEventStore,         // 设备时间线存储（事件总线记录器写入）
+    PgDeviceShadowStore,        // 设备影子存储（期望状态 + 版本）
+    PgDeviceStore,              // 设备信息存储
+    PgDeviceTemplateStore,      // 设备模板存储（产品模型）
+    PgEmissionFactorStore,      // 碳排放因子存储（租户默认值 + 项目覆盖）
+    PgFeatureFlagStore,         // 租户功能开关存储
+    PgFirmwareStore,            // 固件包、升级批次与网关升级进度存储
+    PgGatewayConfigStore,       // 网关配置下发记录存储（版本 + 状态）
+    PgGatewayStore,             // 网关信息存储
+    PgIdempotencyStore,         // POST 幂等键存储（请求摘要 + 首次响应）
+    PgJobStore,                 // 后台任务存储（状态、进度、结果）
+    PgMaintenanceStore,         // 设备 / 网关维护窗口存储
+    PgMeasurementStore,         // 历史测量数据存储（时序数据）
+    PgPointMappingStore,        // 测点映射存储（外部标识 → 内部 ID）
+    PgPointStore,               // 测点定义存储
+    PgPortfolioStore,           // 项目组合（多站点分组）存储
+    PgProjectCloneStore,        // 项目克隆（资产树整体写入新项目）
+    PgProjectStore,             // 项目信息存储
+    PgRuleStore,                // 自动化规则与执行记录存储
+    PgScheduleStore,            // 控制计划与执行记录存储
+    PgShareTokenStore,          // 只读数据分享令牌存储
+    PgTenantStore,              // 租户存储（演示数据）
+    PgUsageStore,               // 租户用量计量与配额存储
+    PgUserStore,                // 用户信息存储
+    PgWebhookSubscriptionStore, // Webhook 订阅与推送日志存储
     // Redis 存储实现
     RedisOnlineStore,   // 设备在线状态缓存
     RedisRealtimeStore, // 实时数据缓存（最新值）
@@ -158,7 +254,7 @@ use ems_storage::{
 };
 
 // 遥测模块 —— 日志和追踪系统初始化
-use ems_telemetry::init_tracing;
+use ems_telemetry::{init_tracing, set_log_filter};
 
 // 标准库
 use std::sync::Arc; // 原子引用计数（线程安全的共享所有权）
@@ -167,6 +263,9 @@ use std::{env, path::PathBuf}; // 环境变量访问和文件路径处理
 // Tokio 异步运行时
 use tokio::process::Command; // 异步子进程管理（用于启动前端）
 
+// HTTP 响应压缩（按 Accept-Encoding 选择 gzip / brotli）
+use tower_http::compression::CompressionLayer;
+
 // Tracing 日志宏
 use tracing::{info, warn}; // 结构化日志输出
 
@@ -254,17 +353,25 @@ fn spawn_web_admin() -> Result<tokio::process::Child, std::io::Error> {
 /// │  ┌── 认证与权限 ──┐    ┌── 资产管理 ──┐    ┌── 数据采集 ──┐       │
 /// │  │ auth           │    │ project_store │    │ measurement  │       │
 /// │  │ rbac_store     │    │ gateway_store │    │ realtime     │       │
-/// │  └────────────────┘    │ device_store  │    │ online       │       │
-/// │                        │ point_store   │    │ point_mapping│       │
+/// │  │ usage_store    │    │ device_store  │    │ online       │       │
+/// │  │ share_token    │    │ point_store   │    │ point_mapping│       │
+/// │  └────────────────┘    │ project_clone │    │ anomaly      │       │
+/// │                        │ portfolio     │    │ carbon       │       │
 /// │                        └───────────────┘    └──────────────┘       │
 /// │                                                                     │
 /// │  ┌── 设备控制 ────────────────────────────────────────────┐        │
 /// │  │ command_store / command_receipt_store / command_service │        │
+/// │  │ gateway_config_service                                  │        │
+/// │  │ firmware_service                                        │        │
+/// │  │ maintenance_service                                     │        │
+/// │  │ shadow_service                                          │        │
+/// │  │ demand_response_store                                   │        │
 /// │  └────────────────────────────────────────────────────────┘        │
 /// │                                                                     │
-/// │  ┌── 审计日志 ──┐                                                  │
-/// │  │ audit_log    │                                                  │
-/// │  └──────────────┘                                                  │
+/// │  ┌── 审计日志 ──┐    ┌── 事件推送 ───┐    ┌── 自动化规则 ──┐      │
+/// │  │ audit_log    │    │ event_bus     │    │ rule_store     │      │
+/// │  └──────────────┘    │ webhook_store │    │ schedule_store │      │
+/// │                      └───────────────┘    └────────────────┘      │
 /// │                                                                     │
 /// └─────────────────────────────────────────────────────────────────────┘
 /// ```
@@ -324,6 +431,23 @@ struct AppState {
     /// 用于数据上报时根据网关上报的标识查找对应的测点。
     point_mapping_store: Arc<dyn ems_storage::PointMappingStore>,
 
+    /// 设备模板存储
+    ///
+    /// 管理设备模板（产品模型）：标准点位集合与映射提示。
+    /// 按模板创建设备时，设备、点位、映射在同一事务内写入。
+    device_template_store: Arc<dyn ems_storage::DeviceTemplateStore>,
+
+    /// 项目克隆存储
+    ///
+    /// 把源项目的资产树（网关、设备、点位、映射、设备模板、规则）重新分配 ID 后
+    /// 在同一事务内写入新项目。
+    project_clone_store: Arc<dyn ems_storage::ProjectCloneStore>,
+
+    /// 项目组合存储
+    ///
+    /// 项目之上的可选分组层（组合 + 成员项目），用于多站点概览。
+    portfolio_store: Arc<dyn ems_storage::PortfolioStore>,
+
     // ========================================================================
     // 数据采集模块
     // ========================================================================
@@ -333,6 +457,21 @@ struct AppState {
     /// 后端使用 PostgreSQL + TimescaleDB 扩展实现高效的时序存储。
     measurement_store: Arc<dyn ems_storage::MeasurementStore>,
 
+    /// 用能异常存储
+    ///
+    /// 由后台异常检测任务写入（小时值偏离周内同时段基线），供异常查询接口读取。
+    anomaly_store: Arc<dyn ems_storage::AnomalyStore>,
+
+    /// 设备时间线存储
+    ///
+    /// 由事件总线记录器写入与设备相关的事件（创建、配置变更、上下线、命令、告警），供时间线接口读取。
+    device_event_store: Arc<dyn ems_storage::DeviceEventStore>,
+
+    /// 碳排放因子存储
+    ///
+    /// 按能源类型保存租户默认值与项目覆盖值，供碳排放报表折算 CO₂e。
+    emission_factor_store: Arc<dyn ems_storage::EmissionFactorStore>,
+
     /// 实时数据存储
     ///
     /// 存储测点的最新值（Last Value），用于实时监控场景。
@@ -345,6 +484,18 @@ struct AppState {
     /// 后端使用 Redis 实现，设备需周期性发送心跳刷新状态。
     online_store: Arc<dyn ems_storage::OnlineStore>,
 
+    /// API 点位值写入流水线
+    ///
+    /// `POST /projects/{id}/points/values` 使用，与采集链路相同的校验与去重参数，
+    /// 每次请求结束时刷盘（写入历史库与最新值）。
+    point_value_pipeline: ems_pipeline::Pipeline,
+
+    /// 数据活跃在线跟踪
+    ///
+    /// 采集链路与 HTTP / gRPC 点位写入共用：测量值到达时刷新所属设备及其网关的在线状态
+    /// （`EMS_ONLINE_FROM_DATA`，默认开启）。
+    online_tracker: ingest::OnlineTracker,
+
     // ========================================================================
     // 设备控制模块
     // ========================================================================
@@ -375,6 +526,108 @@ struct AppState {
     /// - 处理重试逻辑和超时
     /// - 记录审计日志
     command_service: Arc<CommandService>,
+
+    /// 网关配置下发服务
+    ///
+    /// 将网关下的设备/点位/映射打包为版本化配置文档，
+    /// 通过 MQTT 发布到配置主题，并跟踪网关的应用回执。
+    gateway_config_service: Arc<GatewayConfigService>,
+
+    /// 网关固件升级服务
+    ///
+    /// 登记固件包元数据，按升级批次经 MQTT 向目标网关下发升级命令，
+    /// 并根据网关回执跟踪每个网关的下载 / 安装进度。
+    firmware_service: Arc<FirmwareService>,
+
+    /// 设备 / 网关维护模式服务
+    ///
+    /// 管理维护窗口并记录审计；窗口内拦截自动化命令、抑制设备离线告警，
+    /// 人工命令照常下发并附带提示。
+    maintenance_service: Arc<MaintenanceService>,
+
+    /// 设备影子服务
+    ///
+    /// 保存设备期望状态，由实时值与差量命令回执推导上报状态，
+    /// 差量非空时经控制指令服务下发到设备。
+    shadow_service: Arc<DeviceShadowService>,
+    /// 需求响应存储
+    ///
+    /// 管理可削减负荷与需求响应事件，事件由后台编排器按优先级削减负荷、
+    /// 跟踪实际削减量并在窗口结束后恢复负荷。
+    demand_response_store: Arc<dyn ems_storage::DemandResponseStore>,
+
+    // ========================================================================
+    // 事件推送模块
+    // ========================================================================
+    /// 领域事件总线
+    ///
+    /// 业务处理器在状态变化时发布领域事件（如 gateway.created），
+    /// 由 Webhook 推送器等后台任务订阅消费。
+    event_bus: EventBus,
+
+    /// Webhook 订阅存储
+    ///
+    /// 管理租户注册的 Webhook 地址、订阅的事件类型与推送日志。
+    webhook_store: Arc<dyn ems_storage::WebhookSubscriptionStore>,
+
+    /// 是否允许 Webhook 指向回环 / 内网 / 链路本地地址
+    ///
+    /// 对应 `EMS_WEBHOOK_ALLOW_PRIVATE_TARGETS`，创建订阅时据此校验 URL。
+    webhook_allow_private_targets: bool,
+
+    // ========================================================================
+    // 自动化规则模块
+    // ========================================================================
+    /// 自动化规则存储
+    ///
+    /// 管理租户定义的规则（触发条件 + 动作）与执行记录，
+    /// 规则由后台规则引擎评估执行。
+    rule_store: Arc<dyn ems_storage::RuleStore>,
+    /// 控制计划存储
+    ///
+    /// 管理周期性控制动作（预定义命令 / 设定值曲线）与执行记录，
+    /// 计划由后台计划执行器按项目时区执行。
+    schedule_store: Arc<dyn ems_storage::ScheduleStore>,
+
+    // ========================================================================
+    // 功能开关模块
+    // ========================================================================
+    /// 租户功能开关存储
+    ///
+    /// 按租户启用/关闭能力（control、graphql、webhooks 及灰度功能），
+    /// 由对应 handler 通过 `require_feature` 校验。
+    feature_flag_store: Arc<dyn ems_storage::FeatureFlagStore>,
+
+    /// 租户用量计量与配额存储
+    ///
+    /// 按日累计 API 调用、命令与测量值写入，保存各指标配额；
+    /// 超出配额时拒绝请求 / 命令 / 点位创建，采集链路丢弃测量值。
+    usage_store: Arc<dyn ems_storage::UsageStore>,
+
+    /// 高频用量计量缓冲
+    ///
+    /// API 调用与测量值用量先在内存累计、定期批量写入 `usage_store`，
+    /// 配额按缓存计数检查；配额修改后失效缓存，用量报表读取前刷盘。
+    usage_meter: Arc<usage_meter::UsageMeter>,
+
+    /// 只读数据分享令牌存储
+    ///
+    /// 项目级分享令牌（仅保存摘要），允许免登录读取实时与历史数据，
+    /// 用于看板嵌入与大屏展示。
+    share_token_store: Arc<dyn ems_storage::ShareTokenStore>,
+
+    // ========================================================================
+    // 后台任务模块
+    // ========================================================================
+    /// 后台任务存储
+    ///
+    /// 保存任务状态、进度、参数与结果，供任务查询 / 取消接口读取。
+    job_store: Arc<dyn ems_storage::JobStore>,
+
+    /// 后台任务执行器
+    ///
+    /// 业务接口提交长耗时操作（如电能质量报表），在并发上限内后台执行并记录进度。
+    job_runner: Arc<JobRunner>,
 }
 
 /// 主函数：EMS API 服务的入口点
@@ -396,11 +649,30 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // 1. 加载 .env 文件中的环境变量（忽略错误）
     dotenvy::dotenv().ok();
 
-    // 2. 从环境变量读取应用配置
-    let config = AppConfig::from_env()?;
+    // `--check-config`：仅做配置与连通性自检，不启动服务
+    if env::args().any(|arg| arg == check::CHECK_CONFIG_FLAG) {
+        if !check::run().await {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // 2. 读取并校验应用配置（EMS_CONFIG_FILE 配置文件 + 环境变量覆盖）
+    let config = AppConfig::load()?;
 
-    // 3. 初始化 tracing 日志系统
+    // 3. 初始化 tracing 日志系统（EMS_LOG_LEVEL 优先于 RUST_LOG）
     init_tracing();
+    if let Some(log_level) = config.log_level.as_deref() {
+        if let Err(err) = set_log_filter(Some(log_level)) {
+            warn!(error = %err, "invalid EMS_LOG_LEVEL, keeping default filter");
+        }
+    }
+
+    // 热加载：SIGHUP / 运维端口 POST /ops/reload 重新读取配置，经 watch 通道推送给日志与采集链路
+    let reloader = reload::Reloader::new(reload::RuntimeSettings::from(&config));
+    let _log_level_handle = reload::spawn_log_level_watcher(reloader.subscribe());
+    #[cfg(unix)]
+    let _sighup_handle = reload::spawn_sighup_listener(reloader.clone());
 
     // 4. 处理 Web Admin 启动逻辑
     let web_admin_mode = WebAdminMode::from_env();
@@ -482,11 +754,53 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // 测点映射存储：外部标识 → 内部 ID 的映射
     let point_mapping_store: Arc<dyn ems_storage::PointMappingStore> =
         Arc::new(PgPointMappingStore::new(pool.clone()));
+    // 设备模板存储：产品模型与按模板实例化设备
+    let device_template_store: Arc<dyn ems_storage::DeviceTemplateStore> =
+        Arc::new(PgDeviceTemplateStore::new(pool.clone()));
+    // 项目克隆存储：资产树整体写入新项目（单事务）
+    let project_clone_store: Arc<dyn ems_storage::ProjectCloneStore> =
+        Arc::new(PgProjectCloneStore::new(pool.clone()));
+    // 项目组合存储：多站点分组与成员项目
+    let portfolio_store: Arc<dyn ems_storage::PortfolioStore> =
+        Arc::new(PgPortfolioStore::new(pool.clone()));
 
     // --- 数据采集存储 ---
     // 历史测量数据存储（PostgreSQL + TimescaleDB）
-    let measurement_store: Arc<dyn ems_storage::MeasurementStore> =
-        Arc::new(PgMeasurementStore::new(pool.clone()));
+    // 同一点位同一时间戳重复写入（如网关断线重放）按 EMS_MEASUREMENT_DEDUP 策略处理
+    let measurement_dedup = ems_storage::MeasurementDedupStrategy::parse(&config.measurement_dedup)
+        .ok_or_else(|| {
+            format!(
+                "invalid EMS_MEASUREMENT_DEDUP: {}",
+                config.measurement_dedup
+            )
+        })?;
+    let mut measurement_store: Arc<dyn ems_storage::MeasurementStore> =
+        Arc::new(PgMeasurementStore::new(pool.clone()).with_dedup_strategy(measurement_dedup));
+    // 流式导出：配置 EMS_EXPORT_SINK 时，写入成功的测量值同时发布到 Kafka / NATS
+    if let (Some(sink), Some(url)) = (config.export_sink.as_deref(), config.export_url.as_deref()) {
+        validate_topic_template(&config.export_topic)?;
+        let publisher = build_publisher(sink, url, std::time::Duration::from_secs(5))?;
+        let (exporter, _) = spawn_exporter(
+            publisher,
+            ExporterConfig {
+                topic_template: config.export_topic.clone(),
+                buffer_size: config.export_buffer_size as usize,
+                batch_size: config.export_batch_size as usize,
+                ..ExporterConfig::default()
+            },
+        );
+        measurement_store = Arc::new(ExportingMeasurementStore::new(measurement_store, exporter));
+        info!(sink = sink, topic = %config.export_topic, "measurement export enabled");
+    }
+    // 用能异常存储：后台检测任务写入的异常记录
+    let anomaly_store: Arc<dyn ems_storage::AnomalyStore> =
+        Arc::new(PgAnomalyStore::new(pool.clone()));
+    // 设备时间线存储：事件总线记录器写入的设备事件
+    let device_event_store: Arc<dyn ems_storage::DeviceEventStore> =
+        Arc::new(PgDeviceEventStore::new(pool.clone()));
+    // 碳排放因子存储：碳排放报表使用的折算因子
+    let emission_factor_store: Arc<dyn ems_storage::EmissionFactorStore> =
+        Arc::new(PgEmissionFactorStore::new(pool.clone()));
     // 实时数据缓存（Redis）：存储测点的最新值
     let realtime_store: Arc<dyn ems_storage::RealtimeStore> =
         Arc::new(RedisRealtimeStore::connect_with_ttl(
@@ -498,6 +812,21 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         &config.redis_url,
         config.redis_online_ttl_seconds, // 在线状态的过期时间（秒）
     )?);
+    // API 点位值写入流水线：复用采集链路的校验与去重参数
+    let point_value_pipeline = ems_pipeline::Pipeline::with_config(
+        Arc::new(ems_pipeline::StoragePointValueWriter::new(
+            measurement_store.clone(),
+            realtime_store.clone(),
+        )),
+        ingest::pipeline_config(&config),
+    );
+    // 数据活跃在线跟踪：测量值到达即刷新设备及其网关在线状态（点位归属带缓存）
+    let online_tracker = ingest::OnlineTracker::new(
+        config.online_from_data,
+        point_store.clone(),
+        device_store.clone(),
+        online_store.clone(),
+    );
 
     // --- 设备控制存储（PostgreSQL） ---
     // 控制指令存储：记录下发的控制指令
@@ -509,6 +838,112 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // 审计日志存储：记录用户操作日志
     let audit_log_store: Arc<dyn ems_storage::AuditLogStore> =
         Arc::new(PgAuditLogStore::new(pool.clone()));
+    // 网关配置下发记录存储：记录每个网关的配置版本与应用状态
+    let gateway_config_store: Arc<dyn ems_storage::GatewayConfigStore> =
+        Arc::new(PgGatewayConfigStore::new(pool.clone()));
+    // 设备影子存储：记录设备期望状态与版本
+    let device_shadow_store: Arc<dyn ems_storage::DeviceShadowStore> =
+        Arc::new(PgDeviceShadowStore::new(pool.clone()));
+    // 固件升级存储：记录固件包、升级批次与每个网关的升级进度
+    let firmware_store: Arc<dyn ems_storage::FirmwareStore> =
+        Arc::new(PgFirmwareStore::new(pool.clone()));
+    // 维护窗口存储：记录设备 / 网关的维护时间窗口
+    let maintenance_store: Arc<dyn ems_storage::MaintenanceStore> =
+        Arc::new(PgMaintenanceStore::new(pool.clone()));
+
+    // --- 事件推送存储（PostgreSQL） ---
+    // Webhook 订阅与推送日志存储
+    let webhook_store: Arc<dyn ems_storage::WebhookSubscriptionStore> =
+        Arc::new(PgWebhookSubscriptionStore::new(pool.clone()));
+
+    // --- 自动化规则存储（PostgreSQL） ---
+    // 规则定义与执行记录
+    let rule_store: Arc<dyn ems_storage::RuleStore> = Arc::new(PgRuleStore::new(pool.clone()));
+
+    // --- 控制计划存储（PostgreSQL） ---
+    // 计划定义、执行器游标与执行记录
+    let schedule_store: Arc<dyn ems_storage::ScheduleStore> =
+        Arc::new(PgScheduleStore::new(pool.clone()));
+
+    // --- 需求响应存储（PostgreSQL） ---
+    // 可削减负荷配置与事件（已选负荷、实际削减量）
+    let demand_response_store: Arc<dyn ems_storage::DemandResponseStore> =
+        Arc::new(PgDemandResponseStore::new(pool.clone()));
+
+    // --- 功能开关存储（PostgreSQL） ---
+    let feature_flag_store: Arc<dyn ems_storage::FeatureFlagStore> =
+        Arc::new(PgFeatureFlagStore::new(pool.clone()));
+
+    // --- 用量与配额存储（PostgreSQL） ---
+    let usage_store: Arc<dyn ems_storage::UsageStore> = Arc::new(PgUsageStore::new(pool.clone()));
+    // API 调用与测量值用量在内存累计，后台任务定期批量刷盘
+    let usage_meter = Arc::new(usage_meter::UsageMeter::new(usage_store.clone()));
+    let _usage_flush_handle = usage_meter::spawn_usage_flusher(usage_meter.clone());
+
+    // --- 数据分享令牌存储（PostgreSQL） ---
+    let share_token_store: Arc<dyn ems_storage::ShareTokenStore> =
+        Arc::new(PgShareTokenStore::new(pool.clone()));
+
+    // --- 后台任务存储与执行器（PostgreSQL） ---
+    // 任务在提交它的实例内执行；上次停机时未结束的任务在启动时标记为失败
+    let job_store: Arc<dyn ems_storage::JobStore> = Arc::new(PgJobStore::new(pool.clone()));
+    let job_runner = Arc::new(jobs::build_job_runner(
+        job_store.clone(),
+        point_store.clone(),
+        measurement_store.clone(),
+        &JobRunnerConfig {
+            max_concurrency: usize::try_from(config.job_max_concurrency).unwrap_or(usize::MAX),
+        },
+    ));
+    if config.job_recover_interrupted {
+        job_runner.fail_interrupted().await?;
+    }
+
+    // --- 演示数据（EMS_SEED_DEMO=on；演示项目已存在时跳过） ---
+    if config.seed_demo {
+        let seed_stores = SeedStores {
+            tenant_store: Arc::new(PgTenantStore::new(pool.clone())),
+            rbac_store: rbac_store.clone(),
+            project_store: project_store.clone(),
+            gateway_store: gateway_store.clone(),
+            device_store: device_store.clone(),
+            point_store: point_store.clone(),
+            point_mapping_store: point_mapping_store.clone(),
+            measurement_store: measurement_store.clone(),
+            command_store: command_store.clone(),
+        };
+        let summary = seed_demo(&seed_stores, &SeedOptions::default()).await?;
+        if summary.created {
+            info!(
+                points = summary.points,
+                measurements = summary.measurements,
+                "demo data seeded (tenant-demo / demo)"
+            );
+        } else {
+            info!("demo data already present, seeding skipped");
+        }
+    }
+
+    // 领域事件总线 + Webhook 推送器（签名、重试、推送日志）
+    let event_bus = EventBus::default();
+    let _webhook_handle = spawn_webhook_dispatcher(
+        &event_bus,
+        webhook_store.clone(),
+        WebhookDispatcherConfig {
+            max_attempts: config.webhook_max_attempts, // 单个事件最大请求次数
+            backoff_ms: config.webhook_backoff_ms,     // 重试退避（毫秒）
+            timeout_ms: config.webhook_timeout_ms,     // 单次请求超时（毫秒）
+            allow_private_targets: config.webhook_allow_private_targets, // 允许内网目标
+        },
+    )?;
+    // 设备时间线记录器：与设备相关的事件写入 device_events
+    let _device_event_handle = spawn_device_event_recorder(
+        &event_bus,
+        Arc::new(DeviceEventRecorder::new(
+            device_event_store.clone(),
+            device_store.clone(),
+        )),
+    );
 
     // ========================================================================
     // 8. 初始化设备控制服务（MQTT 分发器）
@@ -517,6 +952,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // 根据配置决定是否启用设备控制功能：
     // - 启用时：连接 MQTT Broker，通过 MQTT 发送控制指令
     // - 禁用时：使用空操作分发器（NoopDispatcher），不发送任何指令
+    // 网关配置发布器：控制功能禁用时使用空操作发布器
+    let mut config_publisher: Arc<dyn ems_control::GatewayConfigPublisher> =
+        Arc::new(NoopConfigPublisher);
+    // 固件升级发布器：控制功能禁用时使用空操作发布器
+    let mut firmware_publisher: Arc<dyn ems_control::FirmwarePublisher> =
+        Arc::new(NoopFirmwarePublisher);
     let (dispatcher, _dispatch_handle): (
         Arc<dyn ems_control::CommandDispatcher>,
         Option<tokio::task::JoinHandle<()>>,
@@ -531,24 +972,205 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             include_target_in_topic: config.mqtt_command_topic_include_target, // 是否在主题中包含目标
             qos: config.mqtt_command_qos,                                      // 消息服务质量等级
         })?;
+        // 网关配置发布器复用指令分发器的 MQTT 连接
+        config_publisher =
+            Arc::new(mqtt_dispatcher.config_publisher(config.mqtt_config_topic_prefix.clone()));
+        // 固件升级发布器同样复用该连接
+        firmware_publisher =
+            Arc::new(mqtt_dispatcher.firmware_publisher(config.mqtt_firmware_topic_prefix.clone()));
+        // 按命令 target 所属网关的载荷格式编码（未配置的网关使用 JSON 信封）
+        let mqtt_dispatcher = mqtt_dispatcher.with_payload_formats(Arc::new(
+            GatewayPayloadFormats::new(gateway_store.clone(), device_store.clone()),
+        ));
         (Arc::new(mqtt_dispatcher), Some(handle))
     } else {
         // 控制功能禁用，使用空操作分发器
         (Arc::new(NoopDispatcher::default()), None)
     };
 
+    // 创建维护模式服务（维护窗口 + 审计；命令服务与规则引擎据此拦截 / 抑制）
+    let maintenance_service = Arc::new(MaintenanceService::new(
+        maintenance_store,
+        device_store.clone(),
+        audit_log_store.clone(),
+    ));
+
     // 创建控制指令服务（封装指令创建、分发、重试逻辑）
-    let command_service = Arc::new(CommandService::new_with_config(
-        command_store.clone(),
+    // 命令进入终态（下发失败 / 回执超时）时发布 command.completed
+    // 目标处于维护窗口时拦截自动化命令，人工命令记录覆盖审计
+    // 按租户每日累计命令数，超出 commands 配额时拒绝下发
+    // 租户关闭 control 开关时拒绝一切命令下发（含 gRPC 与自动化引擎）
+    let command_service = Arc::new(
+        CommandService::new_with_config(
+            command_store.clone(),
+            audit_log_store.clone(),
+            dispatcher.clone(),
+            CommandServiceConfig {
+                dispatch_max_retries: config.control_dispatch_max_retries, // 最大重试次数
+                dispatch_backoff_ms: config.control_dispatch_backoff_ms,   // 重试退避时间（毫秒）
+                receipt_timeout_ms: config.control_receipt_timeout_seconds.saturating_mul(1000), // 回执超时（毫秒）
+            },
+        )
+        .with_event_bus(event_bus.clone())
+        .with_maintenance(maintenance_service.clone())
+        .with_usage_store(usage_store.clone())
+        .with_feature_flags(feature_flag_store.clone()),
+    );
+
+    // 创建网关配置下发服务（配置版本记录 + 发布 + 审计）
+    let gateway_config_service = Arc::new(GatewayConfigService::new(
+        gateway_config_store.clone(),
         audit_log_store.clone(),
-        dispatcher.clone(),
-        CommandServiceConfig {
-            dispatch_max_retries: config.control_dispatch_max_retries, // 最大重试次数
-            dispatch_backoff_ms: config.control_dispatch_backoff_ms,   // 重试退避时间（毫秒）
-            receipt_timeout_ms: config.control_receipt_timeout_seconds.saturating_mul(1000), // 回执超时（毫秒）
-        },
+        config_publisher,
+    ));
+
+    // 创建网关固件升级服务（升级批次记录 + 发布 + 审计）
+    let firmware_service = Arc::new(FirmwareService::new(
+        firmware_store.clone(),
+        audit_log_store.clone(),
+        firmware_publisher,
     ));
 
+    // 创建设备影子服务（期望状态 + 上报推导 + 差量经命令下发）
+    let shadow_service = Arc::new(DeviceShadowService::new(
+        device_shadow_store,
+        point_store.clone(),
+        realtime_store.clone(),
+        command_receipt_store.clone(),
+        command_service.clone(),
+    ));
+
+    // 启动自动化规则引擎（EMS_RULES_TICK_MS=0 时不启动）
+    // 按间隔评估点位 / 定时 / 设备离线规则，并订阅事件总线处理告警规则
+    let _rule_engine_handle = if config.rules_tick_ms > 0 {
+        let rule_engine_config = RuleEngineConfig {
+            tick_ms: config.rules_tick_ms,                 // 评估间隔（毫秒）
+            webhook_timeout_ms: config.webhook_timeout_ms, // Webhook 动作请求超时（毫秒）
+        };
+        // 处于维护窗口的设备不触发离线规则
+        let rule_engine = Arc::new(
+            RuleEngine::new(
+                rule_store.clone(),
+                realtime_store.clone(),
+                online_store.clone(),
+                command_service.clone(),
+                event_bus.clone(),
+                &rule_engine_config,
+            )
+            .with_maintenance(maintenance_service.clone()),
+        );
+        Some(spawn_rule_engine(
+            rule_engine,
+            &event_bus,
+            &rule_engine_config,
+        ))
+    } else {
+        None
+    };
+
+    // 启动控制计划执行器（EMS_SCHEDULE_TICK_MS=0 时不启动）
+    // 按项目时区检查到期的计划时刻并下发命令，停机期间错过的时刻按计划的错过执行策略处理
+    let _schedule_runner_handle = if config.schedule_tick_ms > 0 {
+        let schedule_runner_config = ScheduleRunnerConfig {
+            tick_ms: config.schedule_tick_ms,   // 检查间隔（毫秒）
+            grace_ms: config.schedule_grace_ms, // 宽限期（毫秒）
+        };
+        let schedule_runner = Arc::new(ScheduleRunner::new(
+            schedule_store.clone(),
+            command_service.clone(),
+            &schedule_runner_config,
+        ));
+        Some(spawn_schedule_runner(
+            schedule_runner,
+            &schedule_runner_config,
+        ))
+    } else {
+        None
+    };
+
+    // 启动需求响应编排器（EMS_DEMAND_RESPONSE_TICK_MS=0 时不启动）
+    // 到达开始时间的事件按优先级削减负荷，进行中的事件按实时功率更新实际削减量，窗口结束后恢复负荷
+    let _demand_response_runner_handle = if config.demand_response_tick_ms > 0 {
+        let demand_response_runner_config = DemandResponseRunnerConfig {
+            tick_ms: config.demand_response_tick_ms, // 检查间隔（毫秒）
+        };
+        let demand_response_runner = Arc::new(DemandResponseRunner::new(
+            demand_response_store.clone(),
+            realtime_store.clone(),
+            command_service.clone(),
+        ));
+        Some(spawn_demand_response_runner(
+            demand_response_runner,
+            &demand_response_runner_config,
+        ))
+    } else {
+        None
+    };
+
+    // 启动用能异常检测任务（EMS_ANOMALY_TICK_MS=0 时不启动）
+    // 带 energy 标签的点位按周内同时段基线检测最近一个完整小时，偏差超过阈值时记录异常并发布告警
+    let _anomaly_detector_handle = if config.anomaly_tick_ms > 0 {
+        let anomaly_detector_config = AnomalyDetectorConfig {
+            tick_ms: config.anomaly_tick_ms, // 检测间隔（毫秒）
+            deviation_pct: config.anomaly_deviation_pct as f64, // 偏差阈值（百分比）
+            baseline_weeks: u32::try_from(config.anomaly_baseline_weeks).unwrap_or(u32::MAX), // 基线回看周数
+        };
+        let anomaly_detector = Arc::new(
+            AnomalyDetector::new(
+                point_store.clone(),
+                project_store.clone(),
+                measurement_store.clone(),
+                anomaly_store.clone(),
+                &anomaly_detector_config,
+            )
+            .with_event_bus(event_bus.clone()),
+        );
+        Some(spawn_anomaly_detector(
+            anomaly_detector,
+            &anomaly_detector_config,
+        ))
+    } else {
+        None
+    };
+
+    // 启动设备 / 网关离线检测任务（EMS_PRESENCE_TICK_MS=0 时不启动）
+    // 超过阈值未上报时发布 device.offline / gateway.offline 与告警，持续恢复上报后发布 online 事件
+    let _presence_watcher_handle = if config.presence_tick_ms > 0 {
+        let presence_watcher_config = PresenceWatcherConfig {
+            tick_ms: config.presence_tick_ms, // 检测间隔（毫秒）
+            offline_after_seconds: config.presence_offline_after_seconds, // 默认离线阈值（秒）
+            recovery_seconds: config.presence_recovery_seconds, // 恢复滞回时长（秒）
+        };
+        let presence_watcher = Arc::new(
+            PresenceWatcher::new(
+                device_store.clone(),
+                gateway_store.clone(),
+                online_store.clone(),
+                event_bus.clone(),
+                &presence_watcher_config,
+            )
+            .with_maintenance(maintenance_service.clone()),
+        );
+        Some(spawn_presence_watcher(
+            presence_watcher,
+            &presence_watcher_config,
+        ))
+    } else {
+        None
+    };
+
+    // 启动 Modbus TCP 从站（EMS_MODBUS_SERVER_ENABLED=true 时启用）
+    // 每个 modbus_server 网关一个只读从站，SCADA 按寄存器读取点位最新值；只在启动时读取网关列表
+    if config.modbus_server_enabled {
+        let started = modbus_server::spawn_modbus_servers(
+            gateway_store.clone(),
+            point_mapping_store.clone(),
+            realtime_store.clone(),
+        )
+        .await;
+        info!("modbus servers started: {}", started);
+    }
+
     // 启动 MQTT 回执监听器（如果控制功能启用）
     // 回执监听器会订阅回执主题，接收设备执行结果并更新指令状态
     let _receipt_handle = if config.control_enabled {
@@ -560,10 +1182,52 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 password: config.mqtt_password.clone(),
                 receipt_topic_prefix: config.mqtt_receipt_topic_prefix.clone(), // 回执主题前缀
                 qos: config.mqtt_receipt_qos,
+                strict: config.mqtt_receipt_strict, // 严格模式：校验命令归属与 target
             },
             command_store.clone(),
             command_receipt_store.clone(),
             audit_log_store.clone(),
+            event_bus.clone(), // 回执为终态时发布 command.completed
+        ))
+    } else {
+        None
+    };
+
+    // 启动网关配置回执监听器（如果控制功能启用）
+    // 网关应用配置后回执 applied/failed，监听器据此更新对应版本的状态
+    let _config_receipt_handle = if config.control_enabled {
+        Some(spawn_config_receipt_listener(
+            MqttReceiptListenerConfig {
+                host: config.mqtt_host.clone(),
+                port: config.mqtt_port,
+                username: config.mqtt_username.clone(),
+                password: config.mqtt_password.clone(),
+                receipt_topic_prefix: config.mqtt_config_receipt_topic_prefix.clone(), // 配置回执主题前缀
+                qos: config.mqtt_receipt_qos,
+                strict: false, // 严格模式仅用于命令回执
+            },
+            gateway_config_store.clone(),
+            audit_log_store.clone(),
+        ))
+    } else {
+        None
+    };
+
+    // 启动固件升级回执监听器（如果控制功能启用）
+    // 网关回执下载 / 安装进度，监听器据此更新网关升级进度与批次状态
+    let _firmware_receipt_handle = if config.control_enabled {
+        Some(spawn_firmware_receipt_listener(
+            MqttReceiptListenerConfig {
+                host: config.mqtt_host.clone(),
+                port: config.mqtt_port,
+                username: config.mqtt_username.clone(),
+                password: config.mqtt_password.clone(),
+                receipt_topic_prefix: config.mqtt_firmware_receipt_topic_prefix.clone(), // 固件回执主题前缀
+                qos: config.mqtt_receipt_qos,
+                strict: false, // 严格模式仅用于命令回执
+            },
+            firmware_store.clone(),
+            audit_log_store.clone(),
         ))
     } else {
         None
@@ -580,12 +1244,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // 4. 更新设备在线状态
     let _ingest_handle = ingest::spawn_ingest(
         &config,
-        point_mapping_store.clone(),
-        point_store.clone(),
-        device_store.clone(),
-        measurement_store.clone(),
-        realtime_store.clone(),
-        online_store.clone(),
+        ingest::IngestStores {
+            point_mapping_store: point_mapping_store.clone(),
+            measurement_store: measurement_store.clone(),
+            realtime_store: realtime_store.clone(),
+            online_tracker: online_tracker.clone(),
+            usage_meter: usage_meter.clone(),
+        },
+        reloader.subscribe(),
     );
 
     // ========================================================================
@@ -604,13 +1270,37 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         device_store,
         point_store,
         point_mapping_store,
+        device_template_store,
+        project_clone_store,
+        portfolio_store,
         measurement_store,
+        anomaly_store,
+        device_event_store,
+        emission_factor_store,
         realtime_store,
         online_store,
+        point_value_pipeline,
+        online_tracker,
         command_store,
         command_receipt_store,
         audit_log_store,
         command_service,
+        gateway_config_service,
+        firmware_service,
+        maintenance_service,
+        shadow_service,
+        demand_response_store,
+        event_bus,
+        webhook_store,
+        webhook_allow_private_targets: config.webhook_allow_private_targets,
+        rule_store,
+        schedule_store,
+        feature_flag_store,
+        usage_store,
+        usage_meter,
+        share_token_store,
+        job_store,
+        job_runner,
     };
 
     // ========================================================================
@@ -618,16 +1308,51 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // ========================================================================
     //
     // 路由器配置说明：
-    // - `routes::create_api_router()`: 创建包含所有 API 端点的路由器
-    // - `.merge(api.clone())`: 在根路径 `/` 下挂载 API（向后兼容）
-    // - `.nest("/api", api)`: 在 `/api` 前缀下也挂载 API（推荐前缀）
+    // - `routes::create_api_router(policy)`: 创建包含所有 API 端点的路由器，
+    //   挂载 `/api/v1`（推荐）以及已弃用的 `/`、`/api` 旧路径
     // - `.with_state(state)`: 注入应用状态
-    // - `.layer(...)`: 添加请求上下文中间件（注入 request_id/trace_id）
-    let api = routes::create_api_router();
-    let app = Router::new()
-        .merge(api.clone()) // 在根路径挂载 API
-        .nest("/api", api) // 在 /api 前缀下也挂载 API
+    // - `.layer(...)`: 添加幂等中间件（POST + Idempotency-Key 只执行一次）、
+    //   API 调用计量中间件（租户 `api_calls` 配额）、响应压缩（gzip / br，流式响应体边压缩边发送）
+    //   与请求上下文中间件（注入 request_id/trace_id）
+    // 可选：gRPC 服务与 HTTP 共用 AppState（认证、租户隔离一致）
+    let _grpc_handle = match config.grpc_addr.as_deref() {
+        Some(addr) => Some(grpc::spawn_grpc_server(addr.parse()?, state.clone())),
+        None => None,
+    };
+    // 可选：运维端口（仅内网绑定，不经过公网入口）
+    let _ops_handle = match config.ops_addr.as_deref() {
+        Some(addr) => Some(ops::spawn_ops_server(
+            addr.parse()?,
+            ops::OpsState::new(
+                Some(pool.clone()),
+                serde_json::to_value(config.redacted()).unwrap_or_default(),
+            )
+            .with_config_entries(config.annotated())
+            .with_reloader(reloader.clone())
+            .with_feature_flag_store(state.feature_flag_store.clone())
+            .with_usage(state.usage_store.clone(), state.usage_meter.clone()),
+        )),
+        None => None,
+    };
+
+    let version_policy = middleware::ApiVersionPolicy::new(config.api_legacy_sunset.clone());
+    let idempotency_policy = middleware::IdempotencyPolicy::new(
+        state.auth.clone(),
+        Arc::new(PgIdempotencyStore::new(pool.clone())),
+        config.idempotency_ttl_seconds, // 幂等键有效期（秒）
+    );
+    let usage_policy = middleware::UsagePolicy::new(state.auth.clone(), state.usage_meter.clone());
+    let app = routes::create_api_router(version_policy)
         .with_state(state) // 注入应用状态
+        .layer(axum_middleware::from_fn_with_state(
+            idempotency_policy,
+            middleware::idempotency_guard,
+        )) // 添加幂等中间件
+        .layer(axum_middleware::from_fn_with_state(
+            usage_policy,
+            middleware::api_usage_meter,
+        )) // 添加 API 调用计量中间件
+        .layer(CompressionLayer::new()) // 添加响应压缩（位于幂等中间件外层，重放记录保存未压缩响应）
         .layer(axum_middleware::from_fn(middleware::request_context)); // 添加请求追踪中间件
 
     // ========================================================================
@@ -642,291 +1367,3 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     axum::serve(listener, app).await?;
     Ok(())
 }
-
-// ============================================================================
-// 单元测试模块
-// ============================================================================
-//
-// 本模块包含 EMS API 的单元测试，使用内存存储替代真实数据库，
-// 以实现快速、隔离的测试执行。
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::handlers::{get_realtime, list_measurements};
-    use api_contract::{MeasurementsQuery, RealtimeQuery};
-    use axum::extract::{Path, Query, State};
-    use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
-    use domain::{PointValue, PointValueData, TenantContext};
-    use http_body_util::BodyExt;
-    use serde_json::Value;
-    use std::sync::Arc;
-
-    // ========================================================================
-    // 测试辅助函数
-    // ========================================================================
-
-    /// 构建测试用的 AppState（使用内存存储）
-    ///
-    /// 创建一个完整的 AppState 实例，但所有存储层都使用内存实现（InMemory*），
-    /// 这样测试可以快速运行，不依赖外部数据库或 Redis。
-    ///
-    /// ## 默认数据
-    ///
-    /// - 用户：admin/admin123（通过 `InMemoryUserStore::with_default_admin()`）
-    /// - 项目：默认项目（通过 `InMemoryProjectStore::with_default_project()`）
-    ///
-    /// ## 返回值
-    ///
-    /// 返回完全初始化的 AppState，可直接用于测试 HTTP 处理器。
-    fn build_state() -> AppState {
-        // --- 认证模块 ---
-        // 创建内存用户存储，预置默认管理员账户
-        let user_store: Arc<ems_storage::InMemoryUserStore> =
-            Arc::new(ems_storage::InMemoryUserStore::with_default_admin());
-        // 创建 JWT 管理器（测试用密钥和较长的 TTL）
-        let jwt = JwtManager::new("test-secret".to_string(), 3600, 7200);
-        // 创建认证服务
-        let auth: Arc<AuthService> = Arc::new(AuthService::new(user_store.clone(), jwt));
-        // RBAC 存储复用用户存储
-        let rbac_store: Arc<dyn ems_storage::RbacStore> = user_store.clone();
-
-        // --- 资产管理存储（内存实现） ---
-        let project_store: Arc<dyn ems_storage::ProjectStore> =
-            Arc::new(ems_storage::InMemoryProjectStore::with_default_project());
-        let gateway_store: Arc<dyn ems_storage::GatewayStore> =
-            Arc::new(ems_storage::InMemoryGatewayStore::new());
-        let device_store: Arc<dyn ems_storage::DeviceStore> =
-            Arc::new(ems_storage::InMemoryDeviceStore::new());
-        let point_store: Arc<dyn ems_storage::PointStore> =
-            Arc::new(ems_storage::InMemoryPointStore::new());
-        let point_mapping_store: Arc<dyn ems_storage::PointMappingStore> =
-            Arc::new(ems_storage::InMemoryPointMappingStore::new());
-
-        // --- 数据采集存储（内存实现） ---
-        let measurement_store: Arc<dyn ems_storage::MeasurementStore> =
-            Arc::new(ems_storage::InMemoryMeasurementStore::new());
-        let realtime_store: Arc<dyn ems_storage::RealtimeStore> =
-            Arc::new(ems_storage::InMemoryRealtimeStore::new());
-        let online_store: Arc<dyn ems_storage::OnlineStore> =
-            Arc::new(ems_storage::InMemoryOnlineStore::new());
-
-        // --- 设备控制存储（内存实现） ---
-        let command_store: Arc<dyn ems_storage::CommandStore> =
-            Arc::new(ems_storage::InMemoryCommandStore::new());
-        let command_receipt_store: Arc<dyn ems_storage::CommandReceiptStore> =
-            Arc::new(ems_storage::InMemoryCommandReceiptStore::new());
-        let audit_log_store: Arc<dyn ems_storage::AuditLogStore> =
-            Arc::new(ems_storage::InMemoryAuditLogStore::new());
-
-        // 使用空操作分发器（测试环境不发送实际 MQTT 消息）
-        let dispatcher = Arc::new(ems_control::NoopDispatcher::default());
-        let command_service = Arc::new(ems_control::CommandService::new(
-            command_store.clone(),
-            audit_log_store.clone(),
-            dispatcher,
-        ));
-
-        // 组装并返回 AppState
-        AppState {
-            auth,
-            db_pool: None, // 测试环境不使用真实数据库连接池
-            rbac_store,
-            project_store,
-            gateway_store,
-            device_store,
-            point_store,
-            point_mapping_store,
-            measurement_store,
-            realtime_store,
-            online_store,
-            command_store,
-            command_receipt_store,
-            audit_log_store,
-            command_service,
-        }
-    }
-
-    /// 生成认证请求头（Bearer Token）
-    ///
-    /// 使用默认管理员账户（admin/admin123）登录，获取 JWT 令牌，
-    /// 并返回包含 Authorization 头的 HeaderMap。
-    ///
-    /// ## 参数
-    ///
-    /// - `state`: 应用状态，用于执行登录操作
-    ///
-    /// ## 返回值
-    ///
-    /// 返回包含 `Authorization: Bearer <token>` 头的 HeaderMap。
-    async fn auth_headers(state: &AppState) -> HeaderMap {
-        // 使用默认管理员账户登录
-        let (_, tokens) = state.auth.login("admin", "admin123").await.expect("login");
-        let mut headers = HeaderMap::new();
-        // 构造 Bearer Token 格式的 Authorization 头
-        let value = format!("Bearer {}", tokens.access_token);
-        headers.insert(
-            header::AUTHORIZATION,
-            HeaderValue::from_str(&value).expect("auth header"),
-        );
-        headers
-    }
-
-    /// 将 HTTP 响应体解析为 JSON
-    ///
-    /// ## 参数
-    ///
-    /// - `response`: Axum 的 HTTP 响应
-    ///
-    /// ## 返回值
-    ///
-    /// 返回解析后的 `serde_json::Value`。
-    ///
-    /// ## Panic
-    ///
-    /// 如果响应体读取或 JSON 解析失败，会 panic。
-    async fn response_json(response: axum::response::Response) -> Value {
-        let body = response.into_body();
-        let bytes = body.collect().await.expect("collect body").to_bytes();
-        serde_json::from_slice(&bytes).expect("json body")
-    }
-
-    // ========================================================================
-    // 测试用例
-    // ========================================================================
-
-    /// 测试：获取实时数据（GET /projects/{project_id}/realtime）
-    ///
-    /// 验证实时数据 API 能够正确返回存储的测点最新值。
-    ///
-    /// ## 测试步骤
-    ///
-    /// 1. 创建测试 AppState（内存存储）
-    /// 2. 向实时存储中写入一条测点数据
-    /// 3. 调用 `get_realtime` 处理器
-    /// 4. 验证响应状态码为 200 OK
-    /// 5. 验证响应体包含正确的数据
-    #[tokio::test]
-    async fn realtime_returns_values() {
-        // 准备测试环境
-        let state = build_state();
-
-        // 创建租户上下文（模拟已认证用户的请求上下文）
-        let ctx = TenantContext::new(
-            "tenant-1".to_string(),
-            "user-1".to_string(),
-            Vec::new(),
-            Vec::new(),
-            Some("project-1".to_string()),
-        );
-
-        // 创建测试数据：一条测点值
-        let value = PointValue {
-            tenant_id: "tenant-1".to_string(),
-            project_id: "project-1".to_string(),
-            point_id: "point-1".to_string(),
-            ts_ms: 1_700_000_000_000,          // 时间戳（毫秒）
-            value: PointValueData::F64(12.34), // 浮点数值
-            quality: None,                     // 质量标识（无）
-        };
-
-        // 写入实时存储
-        state
-            .realtime_store
-            .upsert_last_value(&ctx, &value)
-            .await
-            .expect("upsert last value");
-
-        // 调用处理器并验证响应
-        let headers = auth_headers(&state).await;
-        let response = get_realtime(
-            State(state),
-            Path(crate::handlers::realtime::ProjectPath {
-                project_id: "project-1".to_string(),
-            }),
-            Query(RealtimeQuery { point_id: None }), // 查询所有测点
-            headers,
-        )
-        .await;
-
-        // 验证 HTTP 状态码
-        assert_eq!(response.status(), StatusCode::OK);
-
-        // 验证响应体内容
-        let json = response_json(response).await;
-        assert_eq!(json["success"], true);
-        assert_eq!(json["data"].as_array().map(|v| v.len()), Some(1));
-    }
-
-    /// 测试：获取历史测量数据（GET /projects/{project_id}/measurements）
-    ///
-    /// 验证历史数据 API 能够正确返回存储的测点历史值。
-    ///
-    /// ## 测试步骤
-    ///
-    /// 1. 创建测试 AppState（内存存储）
-    /// 2. 向测量存储中写入一条历史数据
-    /// 3. 调用 `list_measurements` 处理器
-    /// 4. 验证响应状态码为 200 OK
-    /// 5. 验证响应体包含正确的数据
-    #[tokio::test]
-    async fn measurements_returns_values() {
-        // 准备测试环境
-        let state = build_state();
-
-        // 创建租户上下文
-        let ctx = TenantContext::new(
-            "tenant-1".to_string(),
-            "user-1".to_string(),
-            Vec::new(),
-            Vec::new(),
-            Some("project-1".to_string()),
-        );
-
-        // 创建测试数据：一条历史测量值
-        let value = PointValue {
-            tenant_id: "tenant-1".to_string(),
-            project_id: "project-1".to_string(),
-            point_id: "point-1".to_string(),
-            ts_ms: 1_700_000_000_100,          // 时间戳（毫秒）
-            value: PointValueData::F64(23.45), // 浮点数值
-            quality: Some("good".to_string()), // 质量标识：良好
-        };
-
-        // 写入历史存储
-        state
-            .measurement_store
-            .write_measurement(&ctx, &value)
-            .await
-            .expect("write measurement");
-
-        // 调用处理器并验证响应
-        let headers = auth_headers(&state).await;
-        let response = list_measurements(
-            State(state),
-            Path(crate::handlers::measurements::ProjectPath {
-                project_id: "project-1".to_string(),
-            }),
-            Query(MeasurementsQuery {
-                point_id: "point-1".to_string(), // 指定测点 ID
-                from: None,                      // 起始时间（不限）
-                to: None,                        // 结束时间（不限）
-                limit: Some(100),                // 最多返回 100 条
-                cursor_ts_ms: None,              // 游标（分页用）
-                order: None,                     // 排序方式（默认）
-                bucket_ms: None,                 // 聚合桶大小（不聚合）
-                agg: None,                       // 聚合函数（不聚合）
-            }),
-            headers,
-        )
-        .await;
-
-        // 验证 HTTP 状态码
-        assert_eq!(response.status(), StatusCode::OK);
-
-        // 验证响应体内容
-        let json = response_json(response).await;
-        assert_eq!(json["success"], true);
-        assert_eq!(json["data"].as_array().map(|v| v.len()), Some(1));
-    }
-}
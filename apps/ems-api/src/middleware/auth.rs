@@ -5,6 +5,12 @@
 //! - bearer_token：从 Authorization 头提取 Bearer token
 //! - require_tenant_context：验证 token 并提取租户上下文
 //! - require_project_scope：验证项目归属（带租户上下文）
+//! - require_project_read_access：只读数据接口鉴权（登录令牌或项目分享令牌）
+//! - require_project_stream_access：同上，另返回凭据到期时间，供长连接到期断开与复核
+//! - share_token_hash：分享令牌摘要（存储与查找只使用摘要）
+//! - require_gateway_token：网关回调令牌鉴权（HTTP 回执等设备侧回调）
+//! - require_feature：校验租户功能开关（未配置时取默认值）
+//! - require_point_quota：校验租户点位总数配额
 //!
 //! 认证流程：
 //! 1. request_context：在所有请求前注入追踪 ID
@@ -21,11 +27,15 @@ use axum::{
 };
 use ems_auth::AuthError;
 use ems_telemetry::new_request_ids;
+use sha2::{Digest, Sha256};
 use tracing::{Instrument, info_span};
 
 use crate::AppState;
-use crate::utils::response::{auth_error, forbidden_error, storage_error};
-use domain::TenantContext;
+use crate::utils::response::{
+    auth_error, feature_disabled_error, forbidden_error, quota_exceeded_error, storage_error,
+};
+use domain::{TenantContext, gateway_token, share};
+use ems_storage::GatewayRecord;
 
 pub fn has_permission(ctx: &TenantContext, permission: &str) -> bool {
     ctx.permissions.iter().any(|item| item == permission)
@@ -50,6 +60,52 @@ pub fn require_any_permission(ctx: &TenantContext, permissions: &[&str]) -> Resu
     }
 }
 
+/// 校验租户功能开关：已关闭返回 403 FEATURE.DISABLED，未配置时按默认值处理
+pub async fn require_feature(
+    state: &AppState,
+    ctx: &TenantContext,
+    flag_key: &str,
+) -> Result<(), Response> {
+    let enabled = match state
+        .feature_flag_store
+        .get_feature_flag(ctx, flag_key)
+        .await
+    {
+        Ok(Some(flag)) => flag.enabled,
+        Ok(None) => domain::features::default_enabled(flag_key),
+        Err(err) => return Err(storage_error(err)),
+    };
+    if enabled {
+        Ok(())
+    } else {
+        Err(feature_disabled_error(flag_key))
+    }
+}
+
+/// 校验点位配额：新增 `additional` 个点位后超出 `points` 配额时返回 429 QUOTA.EXCEEDED
+pub async fn require_point_quota(
+    state: &AppState,
+    ctx: &TenantContext,
+    additional: i64,
+) -> Result<(), Response> {
+    let quota = match state
+        .usage_store
+        .get_quota(ctx, domain::usage::POINTS)
+        .await
+    {
+        Ok(Some(quota)) => quota,
+        Ok(None) => return Ok(()),
+        Err(err) => return Err(storage_error(err)),
+    };
+    match state.point_store.count_points(ctx).await {
+        Ok(count) if count.saturating_add(additional) > quota.limit => {
+            Err(quota_exceeded_error(domain::usage::POINTS))
+        }
+        Ok(_) => Ok(()),
+        Err(err) => Err(storage_error(err)),
+    }
+}
+
 /// 请求上下文中间件：注入 request_id/trace_id
 pub async fn request_context(mut req: Request<Body>, next: Next) -> Response {
     let ids = new_request_ids();
@@ -89,12 +145,20 @@ pub fn require_tenant_context(
     state: &AppState,
     headers: &HeaderMap,
 ) -> Result<TenantContext, Response> {
+    require_tenant_context_with_expiry(state, headers).map(|(ctx, _)| ctx)
+}
+
+/// 同 [`require_tenant_context`]，另返回 token 过期时间（Unix 秒），供长连接到期断开
+pub fn require_tenant_context_with_expiry(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> Result<(TenantContext, u64), Response> {
     let token = match bearer_token(headers) {
         Some(token) => token,
         None => return Err(auth_error(axum::http::StatusCode::UNAUTHORIZED)),
     };
-    match state.auth.verify_access_token(token) {
-        Ok(ctx) => Ok(ctx),
+    match state.auth.verify_access_token_with_expiry(token) {
+        Ok(verified) => Ok(verified),
         Err(AuthError::TokenInvalid | AuthError::TokenExpired) => {
             Err(auth_error(axum::http::StatusCode::UNAUTHORIZED))
         }
@@ -108,8 +172,19 @@ pub async fn require_project_scope(
     headers: &HeaderMap,
     project_id: &str,
 ) -> Result<TenantContext, Response> {
-    let mut ctx = match require_tenant_context(state, headers) {
-        Ok(ctx) => ctx,
+    require_project_scope_with_expiry(state, headers, project_id)
+        .await
+        .map(|(ctx, _)| ctx)
+}
+
+/// 同 [`require_project_scope`]，另返回 token 过期时间（Unix 秒）
+pub async fn require_project_scope_with_expiry(
+    state: &AppState,
+    headers: &HeaderMap,
+    project_id: &str,
+) -> Result<(TenantContext, u64), Response> {
+    let (mut ctx, expires_at) = match require_tenant_context_with_expiry(state, headers) {
+        Ok(verified) => verified,
         Err(response) => return Err(response),
     };
     match state
@@ -119,9 +194,149 @@ pub async fn require_project_scope(
     {
         Ok(true) => {
             ctx.project_scope = Some(project_id.to_string());
-            Ok(ctx)
+            Ok((ctx, expires_at))
         }
         Ok(false) => Err(forbidden_error()),
         Err(err) => Err(storage_error(err)),
     }
 }
+
+/// 只读数据接口鉴权
+///
+/// 分享令牌可通过 `shareToken` 查询参数或 Bearer 头传入（`ems_share_` 前缀）：
+/// 令牌须未撤销、未过期且属于该项目，得到的上下文仅包含令牌范围对应的读取权限。
+/// 其余情况按登录令牌走 `require_project_scope`。
+pub async fn require_project_read_access(
+    state: &AppState,
+    headers: &HeaderMap,
+    share_token: Option<&str>,
+    project_id: &str,
+) -> Result<TenantContext, Response> {
+    require_project_stream_access(state, headers, share_token, project_id)
+        .await
+        .map(|(ctx, _)| ctx)
+}
+
+/// 长连接持有的凭据，用于到期断开与推送期间复核
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamCredential {
+    /// 登录令牌，`expires_at_ms` 取自 JWT `exp`
+    AccessToken { expires_at_ms: i64 },
+    /// 分享令牌，推送期间按摘要重新查询以感知撤销
+    ShareToken {
+        token_hash: String,
+        expires_at_ms: i64,
+    },
+}
+
+impl StreamCredential {
+    pub fn expires_at_ms(&self) -> i64 {
+        match self {
+            Self::AccessToken { expires_at_ms } | Self::ShareToken { expires_at_ms, .. } => {
+                *expires_at_ms
+            }
+        }
+    }
+}
+
+/// 同 [`require_project_read_access`]，另返回凭据信息，供 WebSocket 等长连接使用
+pub async fn require_project_stream_access(
+    state: &AppState,
+    headers: &HeaderMap,
+    share_token: Option<&str>,
+    project_id: &str,
+) -> Result<(TenantContext, StreamCredential), Response> {
+    let token = share_token
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .or_else(|| bearer_token(headers).filter(|token| share::is_share_token(token)));
+    let Some(token) = token else {
+        let (ctx, expires_at) =
+            require_project_scope_with_expiry(state, headers, project_id).await?;
+        let expires_at_ms = i64::try_from(expires_at.saturating_mul(1000)).unwrap_or(i64::MAX);
+        return Ok((ctx, StreamCredential::AccessToken { expires_at_ms }));
+    };
+    let token_hash = share_token_hash(token);
+    let record = match state
+        .share_token_store
+        .find_share_token_by_hash(&token_hash)
+        .await
+    {
+        Ok(Some(record)) if record.is_active(now_epoch_ms()) => record,
+        Ok(_) => return Err(auth_error(axum::http::StatusCode::UNAUTHORIZED)),
+        Err(err) => return Err(storage_error(err)),
+    };
+    if record.project_id != project_id {
+        return Err(forbidden_error());
+    }
+    let permissions = record
+        .scopes
+        .iter()
+        .filter_map(|scope| share::scope_permission(scope))
+        .map(str::to_string)
+        .collect();
+    let credential = StreamCredential::ShareToken {
+        token_hash,
+        expires_at_ms: record.expires_at_ms,
+    };
+    let ctx = TenantContext::new(
+        record.tenant_id,
+        format!("share:{}", record.token_id),
+        Vec::new(),
+        permissions,
+        Some(record.project_id),
+    );
+    Ok((ctx, credential))
+}
+
+/// 分享令牌摘要（SHA-256 十六进制）
+pub fn share_token_hash(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// 网关回调令牌鉴权
+///
+/// 只接受 Bearer 头中的网关令牌（`ems_gw_` 前缀），令牌须属于该项目的网关；
+/// 得到的上下文不含任何角色与权限，仅用于以网关身份写入回调数据。
+pub async fn require_gateway_token(
+    state: &AppState,
+    headers: &HeaderMap,
+    project_id: &str,
+) -> Result<(TenantContext, GatewayRecord), Response> {
+    let Some(token) = bearer_token(headers).filter(|token| gateway_token::is_gateway_token(token))
+    else {
+        return Err(auth_error(axum::http::StatusCode::UNAUTHORIZED));
+    };
+    let gateway = match state
+        .gateway_store
+        .find_gateway_by_token_hash(&gateway_token_hash(token))
+        .await
+    {
+        Ok(Some(gateway)) => gateway,
+        Ok(None) => return Err(auth_error(axum::http::StatusCode::UNAUTHORIZED)),
+        Err(err) => return Err(storage_error(err)),
+    };
+    if gateway.project_id != project_id {
+        return Err(forbidden_error());
+    }
+    let ctx = TenantContext::new(
+        gateway.tenant_id.clone(),
+        format!("gateway:{}", gateway.gateway_id),
+        Vec::new(),
+        Vec::new(),
+        Some(gateway.project_id.clone()),
+    );
+    Ok((ctx, gateway))
+}
+
+/// 网关回调令牌摘要（与分享令牌相同的 SHA-256 十六进制）
+pub fn gateway_token_hash(token: &str) -> String {
+    share_token_hash(token)
+}
+
+fn now_epoch_ms() -> i64 {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    now.as_millis() as i64
+}
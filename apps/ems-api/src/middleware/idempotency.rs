@@ -0,0 +1,404 @@
+//! Idempotency-Key 中间件
+//!
+//! 现场网络不稳定时客户端会重试 POST，为避免重复创建资源或重复下发命令：
+//! - 携带 `Idempotency-Key` 的已认证 POST 请求按 (租户, 幂等键) 记录请求摘要与首次响应
+//! - 有效期内的重试直接返回首次响应（状态码、响应头与响应体，附带 `Idempotent-Replayed: true`），
+//!   不再执行 handler
+//! - 同一幂等键但请求（方法 + 路径 + 请求体）不同：422 + `IDEMPOTENCY.KEY_REUSED`
+//! - 首次请求仍在处理中：409 + `IDEMPOTENCY.IN_PROGRESS`
+//! - 只记录 2xx 与确定性的校验失败（400 / 422）；其余响应（409 冲突、429 限流 / 配额、
+//!   5xx 等可能随重试改变的结果）不记录，释放幂等键以便客户端重试
+//! - 未携带幂等键或未认证（由 handler 返回 401）的请求直接放行
+
+use std::sync::Arc;
+
+use axum::{
+    body::{Body, Bytes, to_bytes},
+    extract::{Request, State},
+    http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode, header},
+    middleware::Next,
+    response::Response,
+};
+use domain::TenantContext;
+use ems_auth::AuthService;
+use ems_storage::{IdempotencyRecord, IdempotencyStore};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use super::bearer_token;
+use crate::utils::response::{
+    bad_request_error, idempotency_in_progress_error, idempotency_key_reused_error, storage_error,
+};
+
+/// 幂等键请求头
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+/// 重放响应标记头
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// 幂等键最大长度
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+/// 参与摘要计算的请求体上限（与 axum Json 默认上限一致）
+const MAX_REQUEST_BODY_BYTES: usize = 2 * 1024 * 1024;
+/// 不随重放恢复的响应头（由重放响应重新生成或与连接相关）
+const SKIPPED_REPLAY_HEADERS: [header::HeaderName; 4] = [
+    header::CONTENT_LENGTH,
+    header::TRANSFER_ENCODING,
+    header::CONNECTION,
+    header::DATE,
+];
+
+/// 幂等策略（认证服务用于解析租户，存储用于记录请求与响应）
+#[derive(Clone)]
+pub struct IdempotencyPolicy {
+    auth: Arc<AuthService>,
+    store: Arc<dyn IdempotencyStore>,
+    ttl_ms: i64,
+}
+
+impl IdempotencyPolicy {
+    pub fn new(auth: Arc<AuthService>, store: Arc<dyn IdempotencyStore>, ttl_seconds: u64) -> Self {
+        Self {
+            auth,
+            store,
+            ttl_ms: ttl_seconds.max(1).saturating_mul(1000) as i64,
+        }
+    }
+}
+
+/// 幂等中间件：POST + Idempotency-Key 的请求只执行一次
+pub async fn idempotency_guard(
+    State(policy): State<IdempotencyPolicy>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    if req.method() != Method::POST {
+        return next.run(req).await;
+    }
+    let Some(key) = req
+        .headers()
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_string())
+    else {
+        return next.run(req).await;
+    };
+    if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LEN {
+        return bad_request_error("invalid idempotency key");
+    }
+    let ctx = match bearer_token(req.headers()).map(|token| policy.auth.verify_access_token(token))
+    {
+        Some(Ok(ctx)) => ctx,
+        _ => return next.run(req).await,
+    };
+
+    let (parts, body) = req.into_parts();
+    let body = match to_bytes(body, MAX_REQUEST_BODY_BYTES).await {
+        Ok(body) => body,
+        Err(_) => return bad_request_error("request body too large"),
+    };
+    let path = parts
+        .uri
+        .path_and_query()
+        .map(|value| value.as_str())
+        .unwrap_or("/");
+    let request_hash = request_hash(parts.method.as_str(), path, &body);
+    let now_ms = now_epoch_ms();
+    let record = IdempotencyRecord {
+        tenant_id: ctx.tenant_id.clone(),
+        idempotency_key: key.clone(),
+        request_hash: request_hash.clone(),
+        status_code: None,
+        response_headers: None,
+        response_body: None,
+        created_at_ms: now_ms,
+        expires_at_ms: now_ms.saturating_add(policy.ttl_ms),
+    };
+    match policy.store.reserve_idempotency_key(&ctx, record).await {
+        Ok(None) => {}
+        Ok(Some(existing)) => return replay(existing, &request_hash),
+        Err(err) => return storage_error(err),
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+    record_response(&policy, &ctx, &key, response).await
+}
+
+/// 记录首次响应；不可缓存的状态码或无法读取的响应释放幂等键
+async fn record_response(
+    policy: &IdempotencyPolicy,
+    ctx: &TenantContext,
+    key: &str,
+    response: Response,
+) -> Response {
+    let (parts, body) = response.into_parts();
+    let body = match to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(err) => {
+            warn!(error = %err, "idempotent_response_read_failed");
+            release(policy, ctx, key).await;
+            return Response::from_parts(parts, Body::empty());
+        }
+    };
+    match std::str::from_utf8(&body) {
+        Ok(text) if is_cacheable(parts.status) => {
+            if let Err(err) = policy
+                .store
+                .complete_idempotency_key(
+                    ctx,
+                    key,
+                    i32::from(parts.status.as_u16()),
+                    encode_headers(&parts.headers),
+                    text.to_string(),
+                )
+                .await
+            {
+                warn!(error = %err, "idempotent_response_store_failed");
+            }
+        }
+        _ => release(policy, ctx, key).await,
+    }
+    Response::from_parts(parts, Body::from(body))
+}
+
+/// 可重放的状态码：2xx 与请求本身导致的校验失败（400 / 422），重试结果不会改变
+fn is_cacheable(status: StatusCode) -> bool {
+    status.is_success()
+        || status == StatusCode::BAD_REQUEST
+        || status == StatusCode::UNPROCESSABLE_ENTITY
+}
+
+/// 响应头编码为 JSON 数组 `[[name, value], ...]`（保留重复头，跳过非 UTF-8 值）
+fn encode_headers(headers: &HeaderMap) -> String {
+    let pairs: Vec<(&str, &str)> = headers
+        .iter()
+        .filter(|(name, _)| !SKIPPED_REPLAY_HEADERS.contains(name))
+        .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?)))
+        .collect();
+    serde_json::to_string(&pairs).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// 恢复首次响应头（无法解析的记录忽略）
+fn restore_headers(headers: &mut HeaderMap, encoded: Option<&str>) {
+    let Some(pairs) =
+        encoded.and_then(|encoded| serde_json::from_str::<Vec<(String, String)>>(encoded).ok())
+    else {
+        return;
+    };
+    for (name, value) in pairs {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(&value),
+        ) {
+            headers.append(name, value);
+        }
+    }
+}
+
+async fn release(policy: &IdempotencyPolicy, ctx: &TenantContext, key: &str) {
+    if let Err(err) = policy.store.release_idempotency_key(ctx, key).await {
+        warn!(error = %err, "idempotency_key_release_failed");
+    }
+}
+
+/// 根据已有记录生成响应：请求不一致 422，处理中 409，否则重放首次响应（含响应头）
+fn replay(existing: IdempotencyRecord, request_hash: &str) -> Response {
+    if existing.request_hash != request_hash {
+        return idempotency_key_reused_error();
+    }
+    let (Some(status_code), Some(body)) = (existing.status_code, existing.response_body) else {
+        return idempotency_in_progress_error();
+    };
+    let status = u16::try_from(status_code)
+        .ok()
+        .and_then(|value| StatusCode::from_u16(value).ok())
+        .unwrap_or(StatusCode::OK);
+    let mut response = Response::new(Body::from(body));
+    *response.status_mut() = status;
+    let headers = response.headers_mut();
+    restore_headers(headers, existing.response_headers.as_deref());
+    if !headers.contains_key(header::CONTENT_TYPE) {
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        );
+    }
+    headers.insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+    response
+}
+
+/// 请求摘要：SHA-256(方法 \n 路径 \n 请求体)
+pub fn request_hash(method: &str, path: &str, body: &Bytes) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(path.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(body);
+    hex::encode(hasher.finalize())
+}
+
+fn now_epoch_ms() -> i64 {
+    let now = std::time::SystemTime::now();
+    let duration = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    duration.as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{api_router, auth_headers, build_state, json_request, response_json};
+    use axum::http::{StatusCode, header};
+    use axum::middleware as axum_middleware;
+    use tower::ServiceExt;
+
+    #[test]
+    fn request_hash_covers_method_path_and_body() {
+        let body = Bytes::from_static(b"{\"name\":\"p1\"}");
+        let hash = request_hash("POST", "/projects", &body);
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, request_hash("POST", "/projects", &body));
+        assert_ne!(hash, request_hash("POST", "/api/v1/projects", &body));
+        assert_ne!(
+            hash,
+            request_hash("POST", "/projects", &Bytes::from_static(b"{}"))
+        );
+    }
+
+    #[test]
+    fn only_success_and_validation_failures_are_cacheable() {
+        assert!(is_cacheable(StatusCode::OK));
+        assert!(is_cacheable(StatusCode::CREATED));
+        assert!(is_cacheable(StatusCode::BAD_REQUEST));
+        assert!(is_cacheable(StatusCode::UNPROCESSABLE_ENTITY));
+        assert!(!is_cacheable(StatusCode::CONFLICT));
+        assert!(!is_cacheable(StatusCode::TOO_MANY_REQUESTS));
+        assert!(!is_cacheable(StatusCode::INTERNAL_SERVER_ERROR));
+    }
+
+    #[test]
+    fn replay_restores_original_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::LOCATION, HeaderValue::from_static("/projects/p-1"));
+        headers.insert(header::ETAG, HeaderValue::from_static("W/\"1\""));
+        headers.insert(header::CONTENT_LENGTH, HeaderValue::from_static("2"));
+        let existing = IdempotencyRecord {
+            tenant_id: "tenant-1".to_string(),
+            idempotency_key: "key-1".to_string(),
+            request_hash: "hash".to_string(),
+            status_code: Some(201),
+            response_headers: Some(encode_headers(&headers)),
+            response_body: Some("{}".to_string()),
+            created_at_ms: 0,
+            expires_at_ms: 1,
+        };
+        let response = replay(existing, "hash");
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let headers = response.headers();
+        assert_eq!(headers[header::LOCATION], "/projects/p-1");
+        assert_eq!(headers[header::ETAG], "W/\"1\"");
+        assert_eq!(headers[header::CONTENT_TYPE], "application/json");
+        assert_eq!(headers[IDEMPOTENT_REPLAYED_HEADER], "true");
+        assert!(!headers.contains_key(header::CONTENT_LENGTH));
+    }
+
+    /// 测试：Idempotency-Key 重试返回首次结果，不重复创建
+    ///
+    /// 验证同键同请求重放首次响应（`Idempotent-Replayed: true`），
+    /// 同键不同请求返回 422，不带幂等键的请求照常执行。
+    #[tokio::test]
+    async fn idempotent_post_replays_first_response() {
+        let state = build_state();
+        let headers = auth_headers(&state).await;
+        let policy = crate::middleware::IdempotencyPolicy::new(
+            state.auth.clone(),
+            Arc::new(ems_storage::InMemoryIdempotencyStore::new()),
+            60,
+        );
+        let app = api_router(state).layer(axum_middleware::from_fn_with_state(
+            policy,
+            crate::middleware::idempotency_guard,
+        ));
+        let request = |key: Option<&str>, name: &str| {
+            let mut request = json_request(
+                &headers,
+                "POST",
+                "/api/v1/projects/project-1/gateways",
+                Some(serde_json::json!({ "name": name })),
+            );
+            if let Some(key) = key {
+                request.headers_mut().insert(
+                    crate::middleware::IDEMPOTENCY_KEY_HEADER,
+                    HeaderValue::from_str(key).expect("idempotency key"),
+                );
+            }
+            request
+        };
+
+        let response = app
+            .clone()
+            .oneshot(request(Some("key-1"), "gw-1"))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(
+            response
+                .headers()
+                .get(crate::middleware::IDEMPOTENT_REPLAYED_HEADER)
+                .is_none()
+        );
+        let first = response_json(response).await;
+
+        // 重试：返回首次结果
+        let response = app
+            .clone()
+            .oneshot(request(Some("key-1"), "gw-1"))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response
+                .headers()
+                .get(crate::middleware::IDEMPOTENT_REPLAYED_HEADER)
+                .and_then(|value| value.to_str().ok()),
+            Some("true")
+        );
+        let replayed = response_json(response).await;
+        assert_eq!(replayed["data"]["gatewayId"], first["data"]["gatewayId"]);
+
+        // 同键不同请求
+        let response = app
+            .clone()
+            .oneshot(request(Some("key-1"), "gw-2"))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let json = response_json(response).await;
+        assert_eq!(json["error"]["code"], "IDEMPOTENCY.KEY_REUSED");
+
+        // 不带幂等键：照常创建
+        let response = app
+            .clone()
+            .oneshot(request(None, "gw-1"))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .oneshot(
+                axum::http::Request::builder()
+                    .uri("/api/v1/projects/project-1/gateways")
+                    .header(
+                        header::AUTHORIZATION,
+                        headers.get(header::AUTHORIZATION).expect("auth").clone(),
+                    )
+                    .body(axum::body::Body::empty())
+                    .expect("request"),
+            )
+            .await
+            .expect("response");
+        let json = response_json(response).await;
+        assert_eq!(json["data"].as_array().map(|items| items.len()), Some(2));
+    }
+}
@@ -1,5 +1,11 @@
 //! 中间件模块
 
 pub mod auth;
+pub mod idempotency;
+pub mod usage;
+pub mod versioning;
 
 pub use auth::*;
+pub use idempotency::*;
+pub use usage::*;
+pub use versioning::*;
@@ -0,0 +1,71 @@
+//! API 调用用量计量中间件
+//!
+//! - 已认证请求按租户计入当日（UTC）`api_calls` 用量（内存累计，定期批量写入用量表）
+//! - 超出租户配额（按缓存计数检查）：429 + `QUOTA.EXCEEDED`，不再执行 handler
+//! - 未认证请求直接放行（由 handler 返回 401）
+//! - 用量存储异常时放行并记录告警，避免计量故障阻断业务
+
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use domain::usage;
+use ems_auth::AuthService;
+use tracing::warn;
+
+use super::bearer_token;
+use crate::usage_meter::UsageMeter;
+use crate::utils::response::quota_exceeded_error;
+
+/// 用量计量策略（认证服务用于解析租户，计量缓冲用于累计用量）
+#[derive(Clone)]
+pub struct UsagePolicy {
+    auth: Arc<AuthService>,
+    meter: Arc<UsageMeter>,
+}
+
+impl UsagePolicy {
+    pub fn new(auth: Arc<AuthService>, meter: Arc<UsageMeter>) -> Self {
+        Self { auth, meter }
+    }
+}
+
+/// 计量中间件：已认证请求计入 `api_calls`，超出配额返回 429
+pub async fn api_usage_meter(
+    State(policy): State<UsagePolicy>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let ctx = match bearer_token(req.headers()).map(|token| policy.auth.verify_access_token(token))
+    {
+        Some(Ok(ctx)) => ctx,
+        _ => return next.run(req).await,
+    };
+    match policy
+        .meter
+        .try_consume(
+            &ctx,
+            usage::API_CALLS,
+            usage::day_start_ms(now_epoch_ms()),
+            1,
+        )
+        .await
+    {
+        Ok(true) => {}
+        Ok(false) => return quota_exceeded_error(usage::API_CALLS),
+        Err(err) => warn!(error = %err, tenant_id = %ctx.tenant_id, "usage_meter_failed"),
+    }
+    next.run(req).await
+}
+
+fn now_epoch_ms() -> i64 {
+    let now = std::time::SystemTime::now();
+    let duration = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    duration.as_millis() as i64
+}
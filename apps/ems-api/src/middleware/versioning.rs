@@ -0,0 +1,198 @@
+//! API 版本协商与旧路径弃用中间件
+//!
+//! 版本策略：
+//! - `/api/v1/*` 为显式版本命名空间，新 DTO 演进只在新版本下发生
+//! - `/` 与 `/api/*` 为旧路径，行为等同当前稳定版本（v1），响应附带
+//!   `Deprecation` / `Sunset` / `Link: rel="successor-version"` 头
+//! - 客户端可通过 `X-API-Version: v1` 或 `Accept: application/vnd.ems.v1+json`
+//!   声明期望版本；不支持的版本返回 406 + `API.VERSION_UNSUPPORTED`
+//! - 所有响应回写 `X-API-Version`，标明实际处理请求的版本
+
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{HeaderMap, HeaderValue, header},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::utils::response::unsupported_version_error;
+
+/// 版本请求/响应头
+pub const API_VERSION_HEADER: &str = "x-api-version";
+/// 当前稳定版本（旧路径也按此版本处理）
+pub const CURRENT_API_VERSION: &str = "v1";
+/// 服务端支持的版本列表
+pub const SUPPORTED_API_VERSIONS: &[&str] = &["v1"];
+
+const VENDOR_MEDIA_PREFIX: &str = "application/vnd.ems.";
+
+/// 版本策略（旧路径的下线时间等）
+#[derive(Debug, Clone, Default)]
+pub struct ApiVersionPolicy {
+    /// 旧路径下线时间（HTTP-date，如 `Wed, 31 Dec 2025 23:59:59 GMT`）；未配置则不输出 Sunset 头
+    pub legacy_sunset: Option<String>,
+}
+
+impl ApiVersionPolicy {
+    pub fn new(legacy_sunset: Option<String>) -> Self {
+        Self { legacy_sunset }
+    }
+}
+
+/// 版本协商中间件：校验客户端声明的版本并回写 X-API-Version
+pub async fn negotiate_api_version(req: Request<Body>, next: Next) -> Response {
+    if let Some(requested) = requested_api_version(req.headers()) {
+        if !SUPPORTED_API_VERSIONS.contains(&requested.as_str()) {
+            return unsupported_version_error(&requested);
+        }
+    }
+    let mut response = next.run(req).await;
+    response.headers_mut().insert(
+        API_VERSION_HEADER,
+        HeaderValue::from_static(CURRENT_API_VERSION),
+    );
+    response
+}
+
+/// 旧路径弃用中间件：附带 Deprecation/Sunset/Link 头
+///
+/// 挂载在嵌套路由内部，此时 uri 已去掉 `/api` 前缀，可直接拼接后继路径。
+pub async fn deprecate_legacy_api(
+    State(policy): State<ApiVersionPolicy>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let successor = format!(
+        "</api/{CURRENT_API_VERSION}{}>; rel=\"successor-version\"",
+        req.uri().path()
+    );
+    let mut response = next.run(req).await;
+    let headers = response.headers_mut();
+    headers.insert("deprecation", HeaderValue::from_static("true"));
+    if let Some(sunset) = policy.legacy_sunset.as_deref() {
+        if let Ok(value) = HeaderValue::from_str(sunset) {
+            headers.insert("sunset", value);
+        }
+    }
+    if let Ok(value) = HeaderValue::from_str(&successor) {
+        headers.append(header::LINK, value);
+    }
+    response
+}
+
+/// 解析客户端声明的版本：优先 X-API-Version，其次 Accept 中的厂商媒体类型
+///
+/// 版本号统一为 `v<N>` 形式（`1` 与 `v1` 等价）。
+pub fn requested_api_version(headers: &HeaderMap) -> Option<String> {
+    if let Some(value) = headers
+        .get(API_VERSION_HEADER)
+        .and_then(|value| value.to_str().ok())
+    {
+        let value = value.trim();
+        if !value.is_empty() {
+            return Some(normalize_version(value));
+        }
+    }
+    let accept = headers.get(header::ACCEPT)?.to_str().ok()?;
+    accept.split(',').find_map(|item| {
+        let media = item.split(';').next()?.trim().to_ascii_lowercase();
+        let rest = media.strip_prefix(VENDOR_MEDIA_PREFIX)?;
+        let version = rest.split('+').next()?;
+        (!version.is_empty()).then(|| normalize_version(version))
+    })
+}
+
+fn normalize_version(value: &str) -> String {
+    let value = value.trim().to_ascii_lowercase();
+    if value.starts_with('v') {
+        value
+    } else {
+        format!("v{value}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{build_state, response_json};
+    use axum::http::{StatusCode, header};
+    use tower::ServiceExt;
+
+    #[test]
+    fn requested_version_prefers_header() {
+        let mut headers = HeaderMap::new();
+        headers.insert(API_VERSION_HEADER, HeaderValue::from_static("1"));
+        headers.insert(
+            header::ACCEPT,
+            HeaderValue::from_static("application/vnd.ems.v2+json"),
+        );
+        assert_eq!(requested_api_version(&headers), Some("v1".to_string()));
+    }
+
+    #[test]
+    fn requested_version_from_accept_media_type() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::ACCEPT,
+            HeaderValue::from_static("text/html, application/vnd.ems.v2+json;q=0.9"),
+        );
+        assert_eq!(requested_api_version(&headers), Some("v2".to_string()));
+
+        headers.insert(header::ACCEPT, HeaderValue::from_static("application/json"));
+        assert_eq!(requested_api_version(&headers), None);
+    }
+
+    /// 测试：版本命名空间与旧路径弃用头
+    ///
+    /// 验证 `/api/v1` 回写 X-API-Version、旧路径附带 Deprecation/Sunset/Link 头，
+    /// 以及不支持的版本返回 406。
+    #[tokio::test]
+    async fn versioned_and_legacy_routes() {
+        let app = crate::routes::create_api_router(crate::middleware::ApiVersionPolicy::new(Some(
+            "Wed, 31 Dec 2025 23:59:59 GMT".to_string(),
+        )))
+        .with_state(build_state());
+        let request = |uri: &str| {
+            axum::http::Request::builder()
+                .uri(uri)
+                .body(axum::body::Body::empty())
+                .expect("request")
+        };
+
+        // 版本命名空间：无弃用头
+        let response = app
+            .clone()
+            .oneshot(request("/api/v1/health"))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["x-api-version"], "v1");
+        assert!(response.headers().get("deprecation").is_none());
+
+        // 旧路径：附带弃用与后继版本链接
+        for (uri, successor) in [
+            ("/health", "</api/v1/health>; rel=\"successor-version\""),
+            ("/api/livez", "</api/v1/livez>; rel=\"successor-version\""),
+        ] {
+            let response = app.clone().oneshot(request(uri)).await.expect("response");
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.headers()["deprecation"], "true");
+            assert_eq!(
+                response.headers()["sunset"],
+                "Wed, 31 Dec 2025 23:59:59 GMT"
+            );
+            assert_eq!(response.headers()[header::LINK], successor);
+        }
+
+        // 不支持的版本
+        let mut unsupported = request("/api/v1/health");
+        unsupported
+            .headers_mut()
+            .insert("x-api-version", HeaderValue::from_static("v9"));
+        let response = app.oneshot(unsupported).await.expect("response");
+        assert_eq!(response.status(), StatusCode::NOT_ACCEPTABLE);
+        let json = response_json(response).await;
+        assert_eq!(json["error"]["code"], "API.VERSION_UNSUPPORTED");
+    }
+}
@@ -0,0 +1,274 @@
+//! Modbus TCP 从站
+//!
+//! 每个 `modbus_server` 网关启动一个只读从站（`protocolConfig` 见 [`ModbusServerConfig`]），
+//! 寄存器表取自该网关所属项目中 `sourceType = "modbus_server"` 的点位映射，
+//! 寄存器值为点位最新值（按映射的 scale / offset 反算）。
+//!
+//! 只在启动时读取网关列表：新增或修改 `modbus_server` 网关后需重启服务。
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use domain::TenantContext;
+use ems_protocol::{
+    MODBUS_SERVER_SOURCE_TYPE, ModbusServer, ModbusServerConfig, ProtocolError, RegisterBinding,
+    RegisterValueSource,
+};
+use ems_storage::{GatewayStore, PointMappingStore, RealtimeStore};
+use tracing::{error, info, warn};
+
+/// 网关协议类型：Modbus TCP 从站
+const MODBUS_SERVER_PROTOCOL: &str = "modbus_server";
+
+/// 为全部 `modbus_server` 网关启动从站，返回启动的从站数量
+pub async fn spawn_modbus_servers(
+    gateway_store: Arc<dyn GatewayStore>,
+    point_mapping_store: Arc<dyn PointMappingStore>,
+    realtime_store: Arc<dyn RealtimeStore>,
+) -> usize {
+    let gateways = match gateway_store.list_all_gateways().await {
+        Ok(items) => items,
+        Err(err) => {
+            warn!(target: "ems.modbus_server", error = %err, "modbus_server_gateways_read_failed");
+            return 0;
+        }
+    };
+    let mut started = 0;
+    for gateway in gateways
+        .into_iter()
+        .filter(|gateway| gateway.protocol_type == MODBUS_SERVER_PROTOCOL)
+    {
+        let config =
+            match ModbusServerConfig::from_json(gateway.protocol_config.as_deref().unwrap_or("{}"))
+            {
+                Ok(config) => config,
+                Err(err) => {
+                    warn!(
+                        target: "ems.modbus_server",
+                        gateway_id = %gateway.gateway_id,
+                        error = %err,
+                        "modbus_server_config_invalid"
+                    );
+                    continue;
+                }
+            };
+        let source = register_source(
+            &gateway.tenant_id,
+            &gateway.project_id,
+            point_mapping_store.clone(),
+            realtime_store.clone(),
+        );
+        info!(
+            target: "ems.modbus_server",
+            gateway_id = %gateway.gateway_id,
+            project_id = %gateway.project_id,
+            listen_port = config.listen_port,
+            "modbus_server_starting"
+        );
+        let server = Arc::new(ModbusServer::new(config, source));
+        let gateway_id = gateway.gateway_id;
+        tokio::spawn(async move {
+            if let Err(err) = server.run().await {
+                error!(
+                    target: "ems.modbus_server",
+                    gateway_id = %gateway_id,
+                    error = %err,
+                    "modbus_server_stopped"
+                );
+            }
+        });
+        started += 1;
+    }
+    started
+}
+
+/// 项目的寄存器表与点位值来源（`modbus_server` 点位映射 + 点位最新值）
+pub(crate) fn register_source(
+    tenant_id: &str,
+    project_id: &str,
+    point_mapping_store: Arc<dyn PointMappingStore>,
+    realtime_store: Arc<dyn RealtimeStore>,
+) -> Arc<dyn RegisterValueSource> {
+    Arc::new(StoreRegisterSource {
+        ctx: server_context(tenant_id, project_id),
+        project_id: project_id.to_string(),
+        point_mapping_store,
+        realtime_store,
+    })
+}
+
+/// 从存储读取寄存器表与点位最新值
+struct StoreRegisterSource {
+    ctx: TenantContext,
+    project_id: String,
+    point_mapping_store: Arc<dyn PointMappingStore>,
+    realtime_store: Arc<dyn RealtimeStore>,
+}
+
+#[async_trait::async_trait]
+impl RegisterValueSource for StoreRegisterSource {
+    async fn register_map(&self) -> Result<Vec<RegisterBinding>, ProtocolError> {
+        let mappings = self
+            .point_mapping_store
+            .list_point_mappings(&self.ctx, &self.project_id)
+            .await
+            .map_err(|err| ProtocolError::Connection(format!("storage: {err}")))?;
+        let mut bindings = Vec::new();
+        for mapping in mappings
+            .iter()
+            .filter(|mapping| mapping.source_type == MODBUS_SERVER_SOURCE_TYPE)
+        {
+            let detail = mapping.protocol_detail.as_deref().unwrap_or("{}");
+            match RegisterBinding::from_point_detail(
+                &mapping.point_id,
+                detail,
+                mapping.scale,
+                mapping.offset,
+            ) {
+                Ok(binding) => bindings.push(binding),
+                Err(err) => warn!(
+                    target: "ems.modbus_server",
+                    source_id = %mapping.source_id,
+                    error = %err,
+                    "modbus_server_mapping_invalid"
+                ),
+            }
+        }
+        Ok(bindings)
+    }
+
+    async fn point_values(
+        &self,
+        point_ids: &[String],
+    ) -> Result<HashMap<String, f64>, ProtocolError> {
+        let records = self
+            .realtime_store
+            .get_last_values(&self.ctx, &self.project_id, point_ids)
+            .await
+            .map_err(|err| ProtocolError::Connection(format!("storage: {err}")))?;
+        Ok(records
+            .into_iter()
+            .filter_map(|record| {
+                register_value(&record.value).map(|value| (record.point_id, value))
+            })
+            .collect())
+    }
+}
+
+/// 最新值（字符串存储）转换为数值；布尔值按 1 / 0，非数值返回 None
+fn register_value(value: &str) -> Option<f64> {
+    match value {
+        "true" => Some(1.0),
+        "false" => Some(0.0),
+        _ => value.parse::<f64>().ok(),
+    }
+}
+
+fn server_context(tenant_id: &str, project_id: &str) -> TenantContext {
+    TenantContext::new(
+        tenant_id.to_string(),
+        "modbus-server".to_string(),
+        Vec::new(),
+        Vec::new(),
+        Some(project_id.to_string()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{api_router, auth_headers, build_state, json_request, project_ctx};
+    use axum::http::StatusCode;
+    use domain::{PointValue, PointValueData};
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    /// 测试：modbus_server 映射必须提供合法寄存器定义，从站按映射返回点位最新值
+    #[tokio::test]
+    async fn modbus_server_mapping_serves_last_value() {
+        let state = build_state();
+        let headers = auth_headers(&state).await;
+        let ctx = project_ctx();
+        state
+            .point_store
+            .create_point(
+                &ctx,
+                ems_storage::PointRecord {
+                    point_id: "point-1".to_string(),
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    device_id: "device-1".to_string(),
+                    key: "power".to_string(),
+                    data_type: "float".to_string(),
+                    unit: None,
+                    tags: Vec::new(),
+                },
+            )
+            .await
+            .expect("point");
+
+        let app = api_router(state.clone());
+        let create = |detail: Option<&str>| {
+            let mut body = serde_json::json!({
+                "pointId": "point-1",
+                "sourceType": "modbus_server",
+                "address": "scada-power",
+            });
+            if let Some(detail) = detail {
+                body["protocolDetail"] = Value::from(detail);
+            }
+            json_request(
+                &headers,
+                "POST",
+                "/api/v1/projects/project-1/point-mappings",
+                Some(body),
+            )
+        };
+
+        // 缺少寄存器定义或功能码不支持时拒绝
+        for detail in [
+            None,
+            Some(r#"{"register_address": 100, "function_code": 6}"#),
+        ] {
+            let response = app.clone().oneshot(create(detail)).await.expect("response");
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+        let response = app
+            .clone()
+            .oneshot(create(Some(
+                r#"{"register_address": 100, "data_type": "float32"}"#,
+            )))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+
+        state
+            .realtime_store
+            .upsert_last_value(
+                &ctx,
+                &PointValue {
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    point_id: "point-1".to_string(),
+                    ts_ms: 1_700_000_000_000,
+                    value: PointValueData::F64(12.5),
+                    quality: None,
+                },
+            )
+            .await
+            .expect("upsert last value");
+
+        let source = register_source(
+            "tenant-1",
+            "project-1",
+            state.point_mapping_store.clone(),
+            state.realtime_store.clone(),
+        );
+        let config = ems_protocol::ModbusServerConfig::from_json(r#"{"listen_port": 1502}"#)
+            .expect("config");
+        let server = ems_protocol::ModbusServer::new(config, source);
+        // 读保持寄存器 100 起 2 个：float32 12.5 = 0x41480000
+        let response = server.handle_pdu(1, &[0x03, 0x00, 0x64, 0x00, 0x02]).await;
+        assert_eq!(response, vec![0x03, 0x04, 0x41, 0x48, 0x00, 0x00]);
+    }
+}
@@ -0,0 +1,230 @@
+//! 运维监听（EMS_OPS_ADDR）
+//!
+//! 与业务 HTTP 端口分离，运维端点不经过公网入口（建议仅绑定内网地址）：
+//! - GET /metrics：Prometheus 文本指标（存活 + 全局计数，不含租户数据）
+//! - GET /healthz：存活与依赖检查（Postgres）
+//! - GET /debug/runtime：进程与 Tokio 运行时统计
+//! - GET /config：生效配置（已脱敏）
+//! - GET /ops/config：生效配置逐项列表（已脱敏，标注环境变量名与来源）
+//! - POST /ops/reload：重新读取可热加载的设置（日志级别、流水线参数），等同 SIGHUP；
+//!   只挂在运维监听上，业务端口没有该路由
+//! - PUT/DELETE /ops/tenants/{tenant_id}/feature-flags/{key}：为指定租户设置 / 删除功能开关
+//!   （平台运维操作，租户 API 只读）
+//! - PUT/DELETE /ops/tenants/{tenant_id}/quotas/{metric}：为指定租户设置 / 删除配额（租户 API 只读）
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use axum::{
+    Json, Router,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post, put},
+};
+use domain::TenantContext;
+use sqlx::PgPool;
+use tracing::{info, warn};
+
+use crate::handlers::{
+    delete_tenant_feature_flag, delete_tenant_quota, get_metrics_text, get_ops_config,
+    set_tenant_feature_flag, set_tenant_quota,
+};
+use crate::reload::Reloader;
+use crate::usage_meter::UsageMeter;
+
+/// 运维端口状态（与业务 AppState 分离，不含认证与存储）
+#[derive(Clone)]
+pub struct OpsState {
+    db_pool: Option<PgPool>,
+    config: Arc<serde_json::Value>,
+    config_entries: Arc<Vec<ems_config::ConfigEntry>>,
+    reloader: Option<Reloader>,
+    feature_flag_store: Option<Arc<dyn ems_storage::FeatureFlagStore>>,
+    usage: Option<(Arc<dyn ems_storage::UsageStore>, Arc<UsageMeter>)>,
+    started_at: Instant,
+}
+
+impl OpsState {
+    /// `config` 为脱敏后的配置快照
+    pub fn new(db_pool: Option<PgPool>, config: serde_json::Value) -> Self {
+        Self {
+            db_pool,
+            config: Arc::new(config),
+            config_entries: Arc::new(Vec::new()),
+            reloader: None,
+            feature_flag_store: None,
+            usage: None,
+            started_at: Instant::now(),
+        }
+    }
+
+    /// `GET /ops/config` 返回的逐项配置（`AppConfig::annotated()`）
+    pub fn with_config_entries(mut self, entries: Vec<ems_config::ConfigEntry>) -> Self {
+        self.config_entries = Arc::new(entries);
+        self
+    }
+
+    pub(crate) fn config_entries(&self) -> &[ems_config::ConfigEntry] {
+        &self.config_entries
+    }
+
+    /// 启用 `POST /ops/reload`
+    pub fn with_reloader(mut self, reloader: Reloader) -> Self {
+        self.reloader = Some(reloader);
+        self
+    }
+
+    /// 启用租户功能开关管理（`/ops/tenants/{tenant_id}/feature-flags/{key}`）
+    pub fn with_feature_flag_store(
+        mut self,
+        store: Arc<dyn ems_storage::FeatureFlagStore>,
+    ) -> Self {
+        self.feature_flag_store = Some(store);
+        self
+    }
+
+    pub(crate) fn feature_flag_store(&self) -> Option<&Arc<dyn ems_storage::FeatureFlagStore>> {
+        self.feature_flag_store.as_ref()
+    }
+
+    /// 启用租户配额管理（`/ops/tenants/{tenant_id}/quotas/{metric}`）；
+    /// 配额修改后失效 `usage_meter` 中该租户指标的缓存
+    pub fn with_usage(
+        mut self,
+        usage_store: Arc<dyn ems_storage::UsageStore>,
+        usage_meter: Arc<UsageMeter>,
+    ) -> Self {
+        self.usage = Some((usage_store, usage_meter));
+        self
+    }
+
+    pub(crate) fn usage(&self) -> Option<&(Arc<dyn ems_storage::UsageStore>, Arc<UsageMeter>)> {
+        self.usage.as_ref()
+    }
+}
+
+/// 平台运维对指定租户操作时使用的上下文（不带租户角色与权限，运维端口不做 token 鉴权）
+pub(crate) fn operator_context(tenant_id: &str) -> TenantContext {
+    TenantContext::new(
+        tenant_id.to_string(),
+        "ops".to_string(),
+        Vec::new(),
+        Vec::new(),
+        None,
+    )
+}
+
+/// 运维路由（无鉴权，依赖监听地址做网段隔离）
+pub fn create_ops_router(state: OpsState) -> Router {
+    Router::new()
+        .route("/metrics", get(get_metrics_text))
+        .route("/healthz", get(healthz))
+        .route("/debug/runtime", get(runtime_stats))
+        .route("/config", get(config_dump))
+        .route("/ops/config", get(get_ops_config))
+        .route("/ops/reload", post(reload_settings))
+        .route(
+            "/ops/tenants/:tenant_id/feature-flags/:flag_key",
+            put(set_tenant_feature_flag).delete(delete_tenant_feature_flag),
+        )
+        .route(
+            "/ops/tenants/:tenant_id/quotas/:metric",
+            put(set_tenant_quota).delete(delete_tenant_quota),
+        )
+        .with_state(state)
+}
+
+/// 启动运维监听
+pub fn spawn_ops_server(
+    addr: std::net::SocketAddr,
+    state: OpsState,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let listener = match tokio::net::TcpListener::bind(addr).await {
+            Ok(listener) => listener,
+            Err(err) => {
+                warn!(error = %err, "ops listener bind failed");
+                return;
+            }
+        };
+        info!("EMS 运维端口已启动，监听地址: {}", addr);
+        if let Err(err) = axum::serve(listener, create_ops_router(state)).await {
+            warn!(error = %err, "ops server stopped");
+        }
+    })
+}
+
+/// 健康检查：进程存活且 Postgres 可用时返回 200
+async fn healthz(State(state): State<OpsState>) -> Response {
+    let database = match state.db_pool.as_ref() {
+        Some(pool) => match sqlx::query_scalar::<_, i32>("select 1")
+            .fetch_one(pool)
+            .await
+        {
+            Ok(_) => "ok",
+            Err(err) => {
+                warn!(error = %err, "healthz database check failed");
+                "unavailable"
+            }
+        },
+        None => "disabled",
+    };
+    let ok = database != "unavailable";
+    let status = if ok {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (
+        status,
+        Json(serde_json::json!({ "ok": ok, "database": database })),
+    )
+        .into_response()
+}
+
+/// 运行时统计（类似 pprof 的轻量快照）
+async fn runtime_stats(State(state): State<OpsState>) -> Response {
+    let runtime = tokio::runtime::Handle::current().metrics();
+    Json(serde_json::json!({
+        "pid": std::process::id(),
+        "uptimeSeconds": state.started_at.elapsed().as_secs(),
+        "workers": runtime.num_workers(),
+        "aliveTasks": runtime.num_alive_tasks(),
+        "globalQueueDepth": runtime.global_queue_depth(),
+    }))
+    .into_response()
+}
+
+/// 生效配置（密钥与连接串口令已脱敏）
+async fn config_dump(State(state): State<OpsState>) -> Response {
+    Json(state.config.as_ref().clone()).into_response()
+}
+
+/// 热加载：重新读取配置并推送可热加载的设置；配置无效时保持原设置并返回 400
+async fn reload_settings(State(state): State<OpsState>) -> Response {
+    let Some(reloader) = state.reloader.as_ref() else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "ok": false, "error": "reload disabled" })),
+        )
+            .into_response();
+    };
+    match reloader.reload() {
+        Ok(settings) => {
+            Json(serde_json::json!({ "ok": true, "settings": settings })).into_response()
+        }
+        Err(err) => {
+            warn!(error = %err, "config reload failed");
+            (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({
+                    "ok": false,
+                    "error": err.to_string(),
+                    "settings": reloader.current(),
+                })),
+            )
+                .into_response()
+        }
+    }
+}
@@ -0,0 +1,161 @@
+//! 运行时配置热加载
+//!
+//! 收到 SIGHUP 或运维端口 `POST /ops/reload` 时重新读取配置（配置文件 + 环境变量），
+//! 通过 watch 通道把可热加载的设置推送给各子系统，无需重启进程：
+//! - 日志过滤指令（EMS_LOG_LEVEL）：由日志订阅任务替换 tracing 过滤器
+//! - 流水线批量大小 / 刷盘间隔（EMS_PIPELINE_BATCH_SIZE / EMS_PIPELINE_FLUSH_INTERVAL_MS）：由采集链路消费
+//!
+//! 其余配置（监听地址、连接串、密钥等）仍需重启生效；重新读取的配置未通过校验时保持原设置。
+
+use std::sync::Arc;
+
+use ems_config::{AppConfig, ConfigError};
+use ems_telemetry::set_log_filter;
+use serde::Serialize;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+/// 可热加载的运行时设置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeSettings {
+    pub log_level: Option<String>,
+    pub pipeline_batch_size: usize,
+    pub pipeline_flush_interval_ms: u64,
+}
+
+impl From<&AppConfig> for RuntimeSettings {
+    fn from(config: &AppConfig) -> Self {
+        Self {
+            log_level: config.log_level.clone(),
+            pipeline_batch_size: config.pipeline_batch_size as usize,
+            pipeline_flush_interval_ms: config.pipeline_flush_interval_ms,
+        }
+    }
+}
+
+/// 热加载入口（持有 watch 发送端，子系统通过 [`Reloader::subscribe`] 订阅）
+#[derive(Clone)]
+pub struct Reloader {
+    sender: Arc<watch::Sender<RuntimeSettings>>,
+}
+
+impl Reloader {
+    pub fn new(initial: RuntimeSettings) -> Self {
+        let (sender, _) = watch::channel(initial);
+        Self {
+            sender: Arc::new(sender),
+        }
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<RuntimeSettings> {
+        self.sender.subscribe()
+    }
+
+    /// 当前生效的设置
+    pub fn current(&self) -> RuntimeSettings {
+        self.sender.borrow().clone()
+    }
+
+    /// 重新读取并校验配置，推送新设置
+    pub fn reload(&self) -> Result<RuntimeSettings, ConfigError> {
+        let config = AppConfig::load()?;
+        Ok(self.apply(RuntimeSettings::from(&config)))
+    }
+
+    /// 推送设置（与当前相同时不通知订阅方）
+    pub fn apply(&self, settings: RuntimeSettings) -> RuntimeSettings {
+        let changed = self.sender.send_if_modified(|current| {
+            if *current == settings {
+                return false;
+            }
+            *current = settings.clone();
+            true
+        });
+        info!(changed, settings = ?settings, "runtime settings reloaded");
+        settings
+    }
+}
+
+/// 日志订阅任务：设置变更时替换 tracing 过滤器
+pub fn spawn_log_level_watcher(
+    mut settings: watch::Receiver<RuntimeSettings>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        while settings.changed().await.is_ok() {
+            let log_level = settings.borrow_and_update().log_level.clone();
+            if let Err(err) = set_log_filter(log_level.as_deref()) {
+                warn!(error = %err, "log filter reload failed");
+            }
+        }
+    })
+}
+
+/// SIGHUP 监听：收到信号时重新读取配置
+#[cfg(unix)]
+pub fn spawn_sighup_listener(reloader: Reloader) -> tokio::task::JoinHandle<()> {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    tokio::spawn(async move {
+        let mut hangup = match signal(SignalKind::hangup()) {
+            Ok(hangup) => hangup,
+            Err(err) => {
+                warn!(error = %err, "SIGHUP listener install failed");
+                return;
+            }
+        };
+        while hangup.recv().await.is_some() {
+            if let Err(err) = reloader.reload() {
+                warn!(error = %err, "config reload on SIGHUP failed");
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use tower::ServiceExt;
+
+    /// 测试：热加载设置变更时通知订阅方，相同设置不重复通知；未启用热加载时 POST /ops/reload 返回 404
+    #[tokio::test]
+    async fn runtime_settings_reload_notifies_subscribers() {
+        let initial = RuntimeSettings {
+            log_level: None,
+            pipeline_batch_size: 100,
+            pipeline_flush_interval_ms: 1000,
+        };
+        let reloader = Reloader::new(initial.clone());
+        let mut settings = reloader.subscribe();
+
+        reloader.apply(initial.clone());
+        assert!(!settings.has_changed().expect("sender alive"));
+
+        reloader.apply(RuntimeSettings {
+            log_level: Some("debug".to_string()),
+            pipeline_batch_size: 10,
+            ..initial
+        });
+        assert!(settings.has_changed().expect("sender alive"));
+        let current = settings.borrow_and_update().clone();
+        assert_eq!(current.log_level.as_deref(), Some("debug"));
+        assert_eq!(current.pipeline_batch_size, 10);
+        assert_eq!(reloader.current(), current);
+
+        let response =
+            crate::ops::create_ops_router(crate::ops::OpsState::new(None, serde_json::json!({})))
+                .oneshot(
+                    axum::http::Request::builder()
+                        .method("POST")
+                        .uri("/ops/reload")
+                        .body(axum::body::Body::empty())
+                        .expect("request"),
+                )
+                .await
+                .expect("response");
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let json = crate::test_support::response_json(response).await;
+        assert_eq!(json["error"], "reload disabled");
+    }
+}
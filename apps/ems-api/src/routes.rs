@@ -1,33 +1,88 @@
 //! 路由定义
 //!
 //! 集中管理所有 API 路由，将路径映射到对应的 handlers。
+//! 版本前缀：/api/v1（推荐），/ 与 /api 为已弃用的旧路径。
 //! 路由包括：
 //! - 健康检查：/health
 //! - 认证接口：/login, /refresh-token, /get-async-routes
-//! - 项目管理：/projects/*
-//! - 网关管理：/projects/{id}/gateways/*
-//! - 设备管理：/projects/{id}/devices/*
-//! - 点管理：/projects/{id}/points/*
-//! - 点映射管理：/projects/{id}/point-mappings/*
-//! - 控制命令：/projects/{id}/commands/*
-//! - 审计日志：/projects/{id}/audit
+//! - 项目管理：/projects/*（含克隆 projects/{id}/clone）
+//! - 项目组合：/portfolios/*（含概览 portfolios/{id}/overview）
+//! - 网关管理：/projects/{id}/gateways/*（含配置下发 config/push、config/pushes，维护窗口 maintenance，回调令牌 token，命令载荷格式 command-format）
+//! - 固件升级：/projects/{id}/firmware/*（固件包 packages、升级批次 campaigns 与网关进度 rollouts）
+//! - 设备管理：/projects/{id}/devices/*（含设备影子 devices/{did}/shadow、设备时间线 devices/{did}/events、维护窗口 devices/{did}/maintenance）
+//! - 维护模式：/projects/{id}/maintenance（项目下全部维护窗口）
+//! - 设备模板：/projects/{id}/device-templates/*
+//! - 点管理：/projects/{id}/points/*（含数据覆盖率 points/{pid}/coverage、批量写入点位值 points/values）
+//! - InfluxDB 行协议写入：/projects/{id}/influx/write、/projects/{id}/influx/api/v2/write（兼容 Telegraf）
+//! - 用能异常：/projects/{id}/anomalies（后台检测任务写入，只读）
+//! - 碳排放：/projects/{id}/carbon/*（项目排放因子 emission-factors、报表 report）
+//! - 报表：/projects/{id}/reports/*（电能质量 power-quality，含后台任务 power-quality/jobs）
+//! - 点映射管理：/projects/{id}/point-mappings/*（含地址冲突预检 validate、重复映射报告 duplicates、试运行 test）
+//! - 控制命令：/projects/{id}/commands/*（含 HTTP 回执 commands/{cid}/receipts，网关回调令牌鉴权）
+//! - 审计日志：/projects/{id}/audit、/audit/verify（租户哈希链校验）
+//! - Webhook 订阅：/projects/{id}/webhooks/*（含推送日志 webhooks/deliveries）
+//! - 自动化规则：/projects/{id}/rules/*（含启停 enable/disable、执行记录 executions）
+//! - 控制计划：/projects/{id}/schedules/*（含启停 enable/disable、执行记录 executions）
+//! - 需求响应：/projects/{id}/demand-response/*（可削减负荷 loads、事件 events 与取消 cancel）
+//! - 实时数据：/projects/{id}/realtime（含 WebSocket 订阅 realtime/ws）
+//! - 历史数据：/projects/{id}/measurements（含流式导出 measurements/export）
+//! - 数据分享：/projects/{id}/share-tokens/*（只读分享令牌，可访问 realtime 与 measurements）
+//! - GraphQL：/graphql
+//! - 功能开关：/feature-flags（只读，写入在运维端口）
+//! - 租户排放因子：/carbon/emission-factors/*
+//! - 用量与配额：/usage（用量报表）、/usage/quotas（只读，配额写入在运维端口）
+//! - 后台任务：/jobs/*（状态查询与取消 cancel）
 
 use super::AppState;
 use super::handlers::*;
+use super::middleware::{ApiVersionPolicy, deprecate_legacy_api, negotiate_api_version};
 use axum::{
     Router,
+    middleware::{from_fn, from_fn_with_state},
     routing::{get, post},
 };
+use tower_http::decompression::RequestDecompressionLayer;
 
 /// 创建 API 路由
 ///
-/// 返回包含所有 API 端点的 Router，支持 / 和 /api/ 两种前缀
-pub fn create_api_router() -> Router<AppState> {
+/// 同一组路由挂载在三个前缀下：
+/// - `/api/v1`：显式版本命名空间（推荐）
+/// - `/api`、`/`：旧路径（向后兼容），响应附带 Deprecation/Sunset 头
+///
+/// 所有前缀都经过版本协商（X-API-Version / Accept 厂商媒体类型）。
+pub fn create_api_router(policy: ApiVersionPolicy) -> Router<AppState> {
+    let v1 = create_v1_routes().layer(from_fn(negotiate_api_version));
+    let legacy = v1
+        .clone()
+        .layer(from_fn_with_state(policy, deprecate_legacy_api));
+    Router::new()
+        .nest("/api/v1", v1)
+        .nest("/api", legacy.clone())
+        .merge(legacy)
+}
+
+/// v1 路由表
+fn create_v1_routes() -> Router<AppState> {
     Router::new()
         .route("/health", get(health))
         .route("/livez", get(livez))
         .route("/readyz", get(readyz))
         .route("/metrics", get(get_metrics))
+        .route("/feature-flags", get(list_feature_flags))
+        .route(
+            "/carbon/emission-factors",
+            get(list_tenant_emission_factors),
+        )
+        .route(
+            "/carbon/emission-factors/:energy_source",
+            axum::routing::put(update_tenant_emission_factor).delete(delete_tenant_emission_factor),
+        )
+        .route("/audit/verify", get(verify_audit_chain))
+        .route("/usage", get(get_usage_report))
+        .route("/usage/quotas", get(list_quotas))
+        .route("/jobs", get(list_jobs))
+        .route("/jobs/:job_id", get(get_job))
+        .route("/jobs/:job_id/cancel", post(cancel_job))
         .route("/login", post(login))
         .route("/refresh-token", post(refresh_token))
         .route("/get-async-routes", get(get_async_routes))
@@ -55,6 +110,18 @@ pub fn create_api_router() -> Router<AppState> {
             "/projects/:project_id",
             get(get_project).put(update_project).delete(delete_project),
         )
+        .route("/projects/:project_id/clone", post(clone_project))
+        .route("/portfolios", get(list_portfolios).post(create_portfolio))
+        .route(
+            "/portfolios/:portfolio_id",
+            get(get_portfolio)
+                .put(update_portfolio)
+                .delete(delete_portfolio),
+        )
+        .route(
+            "/portfolios/:portfolio_id/overview",
+            get(get_portfolio_overview),
+        )
         .route(
             "/projects/:project_id/gateways",
             get(list_gateways).post(create_gateway),
@@ -63,6 +130,50 @@ pub fn create_api_router() -> Router<AppState> {
             "/projects/:project_id/gateways/:gateway_id",
             get(get_gateway).put(update_gateway).delete(delete_gateway),
         )
+        .route(
+            "/projects/:project_id/gateways/:gateway_id/token",
+            post(issue_gateway_token).delete(revoke_gateway_token),
+        )
+        .route(
+            "/projects/:project_id/gateways/:gateway_id/command-format",
+            get(get_gateway_command_format)
+                .put(set_gateway_command_format)
+                .delete(clear_gateway_command_format),
+        )
+        .route(
+            "/projects/:project_id/gateways/:gateway_id/config/push",
+            post(push_gateway_config),
+        )
+        .route(
+            "/projects/:project_id/gateways/:gateway_id/config/pushes",
+            get(list_gateway_config_pushes),
+        )
+        .route(
+            "/projects/:project_id/gateways/:gateway_id/maintenance",
+            get(get_gateway_maintenance)
+                .put(set_gateway_maintenance)
+                .delete(clear_gateway_maintenance),
+        )
+        .route(
+            "/projects/:project_id/firmware/packages",
+            get(list_firmware_packages).post(create_firmware_package),
+        )
+        .route(
+            "/projects/:project_id/firmware/packages/:package_id",
+            get(get_firmware_package),
+        )
+        .route(
+            "/projects/:project_id/firmware/campaigns",
+            get(list_firmware_campaigns).post(create_firmware_campaign),
+        )
+        .route(
+            "/projects/:project_id/firmware/campaigns/:campaign_id",
+            get(get_firmware_campaign),
+        )
+        .route(
+            "/projects/:project_id/firmware/campaigns/:campaign_id/rollouts",
+            get(list_firmware_rollouts),
+        )
         .route(
             "/projects/:project_id/devices",
             get(list_devices).post(create_device),
@@ -71,29 +182,194 @@ pub fn create_api_router() -> Router<AppState> {
             "/projects/:project_id/devices/:device_id",
             get(get_device).put(update_device).delete(delete_device),
         )
+        .route(
+            "/projects/:project_id/devices/:device_id/events",
+            get(list_device_events),
+        )
+        .route(
+            "/projects/:project_id/devices/:device_id/shadow",
+            get(get_device_shadow).put(update_device_shadow),
+        )
+        .route(
+            "/projects/:project_id/devices/:device_id/maintenance",
+            get(get_device_maintenance)
+                .put(set_device_maintenance)
+                .delete(clear_device_maintenance),
+        )
+        .route(
+            "/projects/:project_id/maintenance",
+            get(list_maintenance_windows),
+        )
+        .route(
+            "/projects/:project_id/device-templates",
+            get(list_device_templates).post(create_device_template),
+        )
+        .route(
+            "/projects/:project_id/device-templates/:template_id",
+            get(get_device_template).delete(delete_device_template),
+        )
         .route(
             "/projects/:project_id/points",
             get(list_points).post(create_point),
         )
+        .route(
+            "/projects/:project_id/points/values",
+            post(write_point_values),
+        )
+        .route(
+            "/projects/:project_id/influx/write",
+            post(write_influx_line_protocol).layer(RequestDecompressionLayer::new()),
+        )
+        .route(
+            "/projects/:project_id/influx/api/v2/write",
+            post(write_influx_line_protocol).layer(RequestDecompressionLayer::new()),
+        )
+        .route("/graphql", post(graphql_query))
         .route("/projects/:project_id/realtime", get(get_realtime))
+        .route("/projects/:project_id/realtime/ws", get(stream_realtime_ws))
         .route("/projects/:project_id/measurements", get(list_measurements))
+        .route(
+            "/projects/:project_id/measurements/export",
+            get(export_measurements),
+        )
+        .route(
+            "/projects/:project_id/share-tokens",
+            get(list_share_tokens).post(create_share_token),
+        )
+        .route(
+            "/projects/:project_id/share-tokens/:token_id",
+            axum::routing::delete(revoke_share_token),
+        )
+        .route("/projects/:project_id/anomalies", get(list_anomalies))
+        .route(
+            "/projects/:project_id/carbon/emission-factors",
+            get(list_project_emission_factors),
+        )
+        .route(
+            "/projects/:project_id/carbon/emission-factors/:energy_source",
+            axum::routing::put(update_project_emission_factor)
+                .delete(delete_project_emission_factor),
+        )
+        .route(
+            "/projects/:project_id/carbon/report",
+            get(get_carbon_report),
+        )
+        .route(
+            "/projects/:project_id/reports/power-quality",
+            get(get_power_quality_report),
+        )
+        .route(
+            "/projects/:project_id/reports/power-quality/jobs",
+            post(submit_power_quality_report_job),
+        )
         .route(
             "/projects/:project_id/commands",
             get(list_commands).post(create_command),
         )
+        .route("/projects/:project_id/commands/stats", get(get_command_stats))
         .route(
             "/projects/:project_id/commands/:command_id/receipts",
-            get(list_command_receipts),
+            get(list_command_receipts).post(create_command_receipt),
         )
         .route("/projects/:project_id/audit", get(list_audit_logs))
+        .route(
+            "/projects/:project_id/webhooks",
+            get(list_webhooks).post(create_webhook),
+        )
+        .route(
+            "/projects/:project_id/webhooks/deliveries",
+            get(list_webhook_deliveries),
+        )
+        .route(
+            "/projects/:project_id/webhooks/:subscription_id",
+            axum::routing::delete(delete_webhook),
+        )
+        .route(
+            "/projects/:project_id/rules",
+            get(list_rules).post(create_rule),
+        )
+        .route(
+            "/projects/:project_id/rules/:rule_id",
+            get(get_rule).put(update_rule).delete(delete_rule),
+        )
+        .route(
+            "/projects/:project_id/rules/:rule_id/enable",
+            post(enable_rule),
+        )
+        .route(
+            "/projects/:project_id/rules/:rule_id/disable",
+            post(disable_rule),
+        )
+        .route(
+            "/projects/:project_id/rules/:rule_id/executions",
+            get(list_rule_executions),
+        )
+        .route(
+            "/projects/:project_id/schedules",
+            get(list_schedules).post(create_schedule),
+        )
+        .route(
+            "/projects/:project_id/schedules/:schedule_id",
+            get(get_schedule)
+                .put(update_schedule)
+                .delete(delete_schedule),
+        )
+        .route(
+            "/projects/:project_id/schedules/:schedule_id/enable",
+            post(enable_schedule),
+        )
+        .route(
+            "/projects/:project_id/schedules/:schedule_id/disable",
+            post(disable_schedule),
+        )
+        .route(
+            "/projects/:project_id/schedules/:schedule_id/executions",
+            get(list_schedule_executions),
+        )
+        .route(
+            "/projects/:project_id/demand-response/loads",
+            get(list_sheddable_loads),
+        )
+        .route(
+            "/projects/:project_id/demand-response/loads/:device_id",
+            axum::routing::put(upsert_sheddable_load).delete(delete_sheddable_load),
+        )
+        .route(
+            "/projects/:project_id/demand-response/events",
+            get(list_demand_response_events).post(create_demand_response_event),
+        )
+        .route(
+            "/projects/:project_id/demand-response/events/:event_id",
+            get(get_demand_response_event),
+        )
+        .route(
+            "/projects/:project_id/demand-response/events/:event_id/cancel",
+            post(cancel_demand_response_event),
+        )
         .route(
             "/projects/:project_id/points/:point_id",
             get(get_point).put(update_point).delete(delete_point),
         )
+        .route(
+            "/projects/:project_id/points/:point_id/coverage",
+            get(get_point_coverage),
+        )
         .route(
             "/projects/:project_id/point-mappings",
             get(list_point_mappings).post(create_point_mapping),
         )
+        .route(
+            "/projects/:project_id/point-mappings/validate",
+            post(validate_point_mappings),
+        )
+        .route(
+            "/projects/:project_id/point-mappings/duplicates",
+            get(list_duplicate_point_mappings),
+        )
+        .route(
+            "/projects/:project_id/point-mappings/test",
+            post(test_point_mapping),
+        )
         .route(
             "/projects/:project_id/point-mappings/:source_id",
             get(get_point_mapping)
@@ -0,0 +1,268 @@
+//! 测试辅助模块
+//!
+//! 各处理器测试共用的内存 AppState、登录请求头、请求构造与响应解析。
+
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::http::{HeaderMap, HeaderValue, Request, header};
+use domain::TenantContext;
+use ems_auth::{AuthService, JwtManager};
+use ems_events::EventBus;
+use ems_jobs::JobRunnerConfig;
+use http_body_util::BodyExt;
+use serde_json::Value;
+
+use crate::{AppState, middleware, routes};
+
+/// 测试 AppState 的 JWT 密钥（自行签发令牌时使用）
+pub(crate) const TEST_JWT_SECRET: &str = "test-secret";
+
+/// 构建测试用的 AppState（使用内存存储）
+///
+/// 创建一个完整的 AppState 实例，但所有存储层都使用内存实现（InMemory*），
+/// 这样测试可以快速运行，不依赖外部数据库或 Redis。
+///
+/// ## 默认数据
+///
+/// - 用户：admin/admin123（通过 `InMemoryUserStore::with_default_admin()`）
+/// - 项目：默认项目（通过 `InMemoryProjectStore::with_default_project()`）
+///
+/// ## 返回值
+///
+/// 返回完全初始化的 AppState，可直接用于测试 HTTP 处理器。
+pub(crate) fn build_state() -> AppState {
+    // --- 认证模块 ---
+    // 创建内存用户存储，预置默认管理员账户
+    let user_store: Arc<ems_storage::InMemoryUserStore> =
+        Arc::new(ems_storage::InMemoryUserStore::with_default_admin());
+    // 创建 JWT 管理器（测试用密钥和较长的 TTL）
+    let jwt = JwtManager::new(TEST_JWT_SECRET.to_string(), 3600, 7200);
+    // 创建认证服务
+    let auth: Arc<AuthService> = Arc::new(AuthService::new(user_store.clone(), jwt));
+    // RBAC 存储复用用户存储
+    let rbac_store: Arc<dyn ems_storage::RbacStore> = user_store.clone();
+
+    // --- 资产管理存储（内存实现） ---
+    let project_store: Arc<dyn ems_storage::ProjectStore> =
+        Arc::new(ems_storage::InMemoryProjectStore::with_default_project());
+    let gateway_store: Arc<dyn ems_storage::GatewayStore> =
+        Arc::new(ems_storage::InMemoryGatewayStore::new());
+    let device_store: Arc<dyn ems_storage::DeviceStore> =
+        Arc::new(ems_storage::InMemoryDeviceStore::new());
+    let point_store: Arc<dyn ems_storage::PointStore> =
+        Arc::new(ems_storage::InMemoryPointStore::new());
+    let point_mapping_store: Arc<dyn ems_storage::PointMappingStore> =
+        Arc::new(ems_storage::InMemoryPointMappingStore::new());
+    let device_template_store: Arc<dyn ems_storage::DeviceTemplateStore> =
+        Arc::new(ems_storage::InMemoryDeviceTemplateStore::new(
+            device_store.clone(),
+            point_store.clone(),
+            point_mapping_store.clone(),
+        ));
+    let rule_store: Arc<dyn ems_storage::RuleStore> =
+        Arc::new(ems_storage::InMemoryRuleStore::new());
+    let project_clone_store: Arc<dyn ems_storage::ProjectCloneStore> =
+        Arc::new(ems_storage::InMemoryProjectCloneStore::new(
+            project_store.clone(),
+            gateway_store.clone(),
+            device_store.clone(),
+            point_store.clone(),
+            point_mapping_store.clone(),
+            device_template_store.clone(),
+            rule_store.clone(),
+        ));
+
+    // --- 数据采集存储（内存实现） ---
+    let measurement_store: Arc<dyn ems_storage::MeasurementStore> =
+        Arc::new(ems_storage::InMemoryMeasurementStore::new());
+    let realtime_store: Arc<dyn ems_storage::RealtimeStore> =
+        Arc::new(ems_storage::InMemoryRealtimeStore::new());
+    let online_store: Arc<dyn ems_storage::OnlineStore> =
+        Arc::new(ems_storage::InMemoryOnlineStore::new());
+    let point_value_pipeline =
+        ems_pipeline::Pipeline::new(Arc::new(ems_pipeline::StoragePointValueWriter::new(
+            measurement_store.clone(),
+            realtime_store.clone(),
+        )));
+    let online_tracker = crate::ingest::OnlineTracker::new(
+        true,
+        point_store.clone(),
+        device_store.clone(),
+        online_store.clone(),
+    );
+
+    // --- 设备控制存储（内存实现） ---
+    let command_store: Arc<dyn ems_storage::CommandStore> =
+        Arc::new(ems_storage::InMemoryCommandStore::new());
+    let command_receipt_store: Arc<dyn ems_storage::CommandReceiptStore> =
+        Arc::new(ems_storage::InMemoryCommandReceiptStore::new());
+    let audit_log_store: Arc<dyn ems_storage::AuditLogStore> =
+        Arc::new(ems_storage::InMemoryAuditLogStore::new());
+
+    let maintenance_service = Arc::new(ems_control::MaintenanceService::new(
+        Arc::new(ems_storage::InMemoryMaintenanceStore::new()),
+        device_store.clone(),
+        audit_log_store.clone(),
+    ));
+
+    // 使用空操作分发器（测试环境不发送实际 MQTT 消息）
+    let dispatcher = Arc::new(ems_control::NoopDispatcher::default());
+    let usage_store: Arc<dyn ems_storage::UsageStore> =
+        Arc::new(ems_storage::InMemoryUsageStore::new());
+    let feature_flag_store: Arc<dyn ems_storage::FeatureFlagStore> =
+        Arc::new(ems_storage::InMemoryFeatureFlagStore::new());
+    let event_bus = EventBus::default();
+    let command_service = Arc::new(
+        ems_control::CommandService::new(
+            command_store.clone(),
+            audit_log_store.clone(),
+            dispatcher,
+        )
+        .with_event_bus(event_bus.clone())
+        .with_maintenance(maintenance_service.clone())
+        .with_usage_store(usage_store.clone())
+        .with_feature_flags(feature_flag_store.clone()),
+    );
+    let gateway_config_service = Arc::new(ems_control::GatewayConfigService::new(
+        Arc::new(ems_storage::InMemoryGatewayConfigStore::new()),
+        audit_log_store.clone(),
+        Arc::new(ems_control::NoopConfigPublisher),
+    ));
+    let firmware_service = Arc::new(ems_control::FirmwareService::new(
+        Arc::new(ems_storage::InMemoryFirmwareStore::new()),
+        audit_log_store.clone(),
+        Arc::new(ems_control::NoopFirmwarePublisher),
+    ));
+    let shadow_service = Arc::new(ems_control::DeviceShadowService::new(
+        Arc::new(ems_storage::InMemoryDeviceShadowStore::new()),
+        point_store.clone(),
+        realtime_store.clone(),
+        command_receipt_store.clone(),
+        command_service.clone(),
+    ));
+
+    let job_store: Arc<dyn ems_storage::JobStore> = Arc::new(ems_storage::InMemoryJobStore::new());
+    let job_runner = Arc::new(crate::jobs::build_job_runner(
+        job_store.clone(),
+        point_store.clone(),
+        measurement_store.clone(),
+        &JobRunnerConfig::default(),
+    ));
+
+    // 组装并返回 AppState
+    AppState {
+        auth,
+        db_pool: None, // 测试环境不使用真实数据库连接池
+        rbac_store,
+        project_store,
+        gateway_store,
+        device_store,
+        point_store,
+        point_mapping_store,
+        device_template_store,
+        project_clone_store,
+        portfolio_store: Arc::new(ems_storage::InMemoryPortfolioStore::new()),
+        measurement_store,
+        anomaly_store: Arc::new(ems_storage::InMemoryAnomalyStore::new()),
+        device_event_store: Arc::new(ems_storage::InMemoryDeviceEventStore::new()),
+        emission_factor_store: Arc::new(ems_storage::InMemoryEmissionFactorStore::new()),
+        realtime_store,
+        online_store,
+        point_value_pipeline,
+        online_tracker,
+        command_store,
+        command_receipt_store,
+        audit_log_store,
+        command_service,
+        gateway_config_service,
+        firmware_service,
+        maintenance_service,
+        shadow_service,
+        event_bus,
+        webhook_store: Arc::new(ems_storage::InMemoryWebhookSubscriptionStore::new()),
+        webhook_allow_private_targets: false,
+        rule_store,
+        schedule_store: Arc::new(ems_storage::InMemoryScheduleStore::new()),
+        demand_response_store: Arc::new(ems_storage::InMemoryDemandResponseStore::new()),
+        feature_flag_store,
+        usage_meter: Arc::new(crate::usage_meter::UsageMeter::new(usage_store.clone())),
+        usage_store,
+        share_token_store: Arc::new(ems_storage::InMemoryShareTokenStore::new()),
+        job_store,
+        job_runner,
+    }
+}
+
+/// 构建挂载测试 AppState 的 API 路由（默认版本策略）
+pub(crate) fn api_router(state: AppState) -> axum::Router {
+    routes::create_api_router(middleware::ApiVersionPolicy::default()).with_state(state)
+}
+
+/// 生成认证请求头（Bearer Token）
+///
+/// 使用默认管理员账户（admin/admin123）登录，获取 JWT 令牌，
+/// 并返回包含 Authorization 头的 HeaderMap。
+pub(crate) async fn auth_headers(state: &AppState) -> HeaderMap {
+    let (_, tokens) = state.auth.login("admin", "admin123").await.expect("login");
+    bearer_headers(&tokens.access_token)
+}
+
+/// 为指定租户上下文签发令牌并返回认证请求头（不经过登录，权限由上下文决定）
+pub(crate) fn context_headers(ctx: &TenantContext) -> HeaderMap {
+    let jwt = JwtManager::new(TEST_JWT_SECRET.to_string(), 3600, 7200);
+    let tokens = jwt.issue_tokens(ctx).expect("token");
+    bearer_headers(&tokens.access_token)
+}
+
+fn bearer_headers(token: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::AUTHORIZATION,
+        HeaderValue::from_str(&format!("Bearer {token}")).expect("auth header"),
+    );
+    headers
+}
+
+/// 构造 API 请求：复制 `headers`，有请求体时以 JSON 发送
+pub(crate) fn json_request(
+    headers: &HeaderMap,
+    method: &str,
+    uri: &str,
+    body: Option<Value>,
+) -> Request<Body> {
+    let mut builder = Request::builder().method(method).uri(uri);
+    for (name, value) in headers.iter() {
+        builder = builder.header(name, value);
+    }
+    let body = match body {
+        Some(body) => {
+            builder = builder.header(header::CONTENT_TYPE, "application/json");
+            Body::from(body.to_string())
+        }
+        None => Body::empty(),
+    };
+    builder.body(body).expect("request")
+}
+
+/// 将 HTTP 响应体解析为 JSON（读取或解析失败时 panic）
+pub(crate) async fn response_json(response: axum::response::Response) -> Value {
+    let bytes = response
+        .into_body()
+        .collect()
+        .await
+        .expect("collect body")
+        .to_bytes();
+    serde_json::from_slice(&bytes).expect("json body")
+}
+
+/// tenant-1 / user-1 在 project-1 下的租户上下文（无角色与权限）
+pub(crate) fn project_ctx() -> TenantContext {
+    TenantContext::new(
+        "tenant-1".to_string(),
+        "user-1".to_string(),
+        Vec::new(),
+        Vec::new(),
+        Some("project-1".to_string()),
+    )
+}
@@ -0,0 +1,333 @@
+//! 高频用量计量缓冲
+//!
+//! API 调用（`api_calls`）与测量值（`measurements`）不再逐次写用量表：
+//! - 按 (租户, 指标, 周期) 在内存中累计，后台任务每 `USAGE_FLUSH_INTERVAL` 批量写入 `UsageStore`
+//! - 配额检查使用缓存：配额与已落库用量首次使用时加载，`QUOTA_REFRESH_INTERVAL` 后重新加载，
+//!   当前用量 = 已落库用量 + 未刷盘增量
+//! - 刷盘按实际用量累加（`UsageStore::add_usage`），返回的落库总量回写缓存，多实例部署据此同步
+//!
+//! 代价：进程异常退出时最多丢失一个刷盘周期的计数；多实例部署时配额可能被短暂超出一个刷盘周期的用量。
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use domain::TenantContext;
+use ems_storage::{StorageError, UsageStore};
+use tracing::warn;
+
+/// 未刷盘增量写入用量表的周期
+const USAGE_FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+/// 缓存的配额与已落库用量的有效期（配额接口修改后立即失效）
+const QUOTA_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct CounterKey {
+    tenant_id: String,
+    metric: String,
+    period_start_ms: i64,
+}
+
+/// 单个 (租户, 指标, 周期) 的计数
+#[derive(Default)]
+struct Counter {
+    /// 已落库用量（最近一次加载或刷盘返回的总量）
+    stored: i64,
+    /// 正在刷盘的增量
+    flushing: i64,
+    /// 未刷盘的增量
+    pending: i64,
+    /// 配额（未配置为 None）
+    limit: Option<i64>,
+    /// 配额与已落库用量的加载时间（None 表示需要加载）
+    loaded_at: Option<Instant>,
+}
+
+impl Counter {
+    fn is_fresh(&self) -> bool {
+        self.loaded_at
+            .is_some_and(|loaded_at| loaded_at.elapsed() < QUOTA_REFRESH_INTERVAL)
+    }
+
+    fn allows(&self, amount: i64) -> bool {
+        let used = self.stored + self.flushing + self.pending;
+        self.limit
+            .is_none_or(|limit| used.saturating_add(amount) <= limit)
+    }
+}
+
+/// 用量计量缓冲（HTTP 中间件、点位写入与 MQTT 采集共用）
+pub struct UsageMeter {
+    store: Arc<dyn UsageStore>,
+    counters: Mutex<HashMap<CounterKey, Counter>>,
+}
+
+impl UsageMeter {
+    pub fn new(store: Arc<dyn UsageStore>) -> Self {
+        Self {
+            store,
+            counters: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 检查配额并计入用量：超出配额时不计入并返回 false
+    pub async fn try_consume(
+        &self,
+        ctx: &TenantContext,
+        metric: &str,
+        period_start_ms: i64,
+        amount: i64,
+    ) -> Result<bool, StorageError> {
+        let key = self.ensure_loaded(ctx, metric, period_start_ms).await?;
+        let mut counters = self.lock();
+        let counter = counters.entry(key).or_default();
+        if !counter.allows(amount) {
+            return Ok(false);
+        }
+        counter.pending += amount;
+        Ok(true)
+    }
+
+    /// 只检查配额（不计入）：当前用量加上 `amount` 不超过配额
+    ///
+    /// 实际计入量要在处理后才知道时（如去重后的测量值）使用，随后调用 [`UsageMeter::record`]。
+    pub async fn has_quota(
+        &self,
+        ctx: &TenantContext,
+        metric: &str,
+        period_start_ms: i64,
+        amount: i64,
+    ) -> Result<bool, StorageError> {
+        let key = self.ensure_loaded(ctx, metric, period_start_ms).await?;
+        Ok(self
+            .lock()
+            .get(&key)
+            .is_none_or(|counter| counter.allows(amount)))
+    }
+
+    /// 计入用量（不检查配额），下次刷盘时写入用量表
+    pub fn record(&self, ctx: &TenantContext, metric: &str, period_start_ms: i64, amount: i64) {
+        if amount <= 0 {
+            return;
+        }
+        let key = counter_key(&ctx.tenant_id, metric, period_start_ms);
+        self.lock().entry(key).or_default().pending += amount;
+    }
+
+    /// 配额变更后丢弃租户缓存的配额，下次检查时重新加载
+    pub fn invalidate(&self, tenant_id: &str, metric: &str) {
+        for (key, counter) in self.lock().iter_mut() {
+            if key.tenant_id == tenant_id && key.metric == metric {
+                counter.loaded_at = None;
+            }
+        }
+    }
+
+    /// 把全部未刷盘增量写入用量表
+    pub async fn flush(&self) {
+        self.flush_where(|_| true).await;
+    }
+
+    /// 把指定租户的未刷盘增量写入用量表（用量报表读取前调用）
+    pub async fn flush_tenant(&self, tenant_id: &str) {
+        self.flush_where(|key| key.tenant_id == tenant_id).await;
+    }
+
+    async fn flush_where(&self, filter: impl Fn(&CounterKey) -> bool) {
+        let batch: Vec<(CounterKey, i64)> = {
+            let mut counters = self.lock();
+            // 没有待写入增量且缓存已过期的计数直接移除（含已结束周期），下次使用时重新加载
+            counters.retain(|_, counter| {
+                counter.pending > 0 || counter.flushing > 0 || counter.is_fresh()
+            });
+            counters
+                .iter_mut()
+                .filter(|(key, counter)| counter.pending > 0 && filter(key))
+                .map(|(key, counter)| {
+                    let amount = std::mem::take(&mut counter.pending);
+                    counter.flushing += amount;
+                    (key.clone(), amount)
+                })
+                .collect()
+        };
+        for (key, amount) in batch {
+            let result = self
+                .store
+                .add_usage(
+                    &system_context(&key.tenant_id),
+                    &key.metric,
+                    key.period_start_ms,
+                    amount,
+                )
+                .await;
+            let mut counters = self.lock();
+            let counter = counters.entry(key.clone()).or_default();
+            counter.flushing -= amount;
+            match result {
+                Ok(total) => counter.stored = counter.stored.max(total),
+                Err(err) => {
+                    // 写入失败的增量留待下次刷盘
+                    counter.pending += amount;
+                    warn!(
+                        tenant_id = %key.tenant_id,
+                        metric = %key.metric,
+                        amount = amount,
+                        error = %err,
+                        "usage_flush_failed"
+                    );
+                }
+            }
+        }
+    }
+
+    /// 确保计数已加载配额与已落库用量，返回计数键
+    async fn ensure_loaded(
+        &self,
+        ctx: &TenantContext,
+        metric: &str,
+        period_start_ms: i64,
+    ) -> Result<CounterKey, StorageError> {
+        let key = counter_key(&ctx.tenant_id, metric, period_start_ms);
+        if self.lock().get(&key).is_some_and(Counter::is_fresh) {
+            return Ok(key);
+        }
+        let limit = self
+            .store
+            .get_quota(ctx, metric)
+            .await?
+            .map(|quota| quota.limit);
+        let stored = self
+            .store
+            .list_usage(ctx, period_start_ms, period_start_ms + 1)
+            .await?
+            .into_iter()
+            .filter(|record| record.metric == metric)
+            .map(|record| record.count)
+            .sum::<i64>();
+        let mut counters = self.lock();
+        let counter = counters.entry(key.clone()).or_default();
+        // 同一周期内用量只增不减：加载期间完成的刷盘可能已返回更新的总量
+        counter.stored = counter.stored.max(stored);
+        counter.limit = limit;
+        counter.loaded_at = Some(Instant::now());
+        Ok(key)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<CounterKey, Counter>> {
+        self.counters
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// 启动用量刷盘后台任务
+pub fn spawn_usage_flusher(meter: Arc<UsageMeter>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(USAGE_FLUSH_INTERVAL);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            meter.flush().await;
+        }
+    })
+}
+
+fn counter_key(tenant_id: &str, metric: &str, period_start_ms: i64) -> CounterKey {
+    CounterKey {
+        tenant_id: tenant_id.to_string(),
+        metric: metric.to_string(),
+        period_start_ms,
+    }
+}
+
+fn system_context(tenant_id: &str) -> TenantContext {
+    TenantContext::new(
+        tenant_id.to_string(),
+        "system".to_string(),
+        Vec::new(),
+        Vec::new(),
+        None,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ems_storage::{InMemoryUsageStore, QuotaRecord};
+
+    fn ctx() -> TenantContext {
+        system_context("tenant-1")
+    }
+
+    /// 测试：计数先在内存累计、刷盘后写入用量表，配额按缓存计数检查
+    #[tokio::test]
+    async fn usage_is_buffered_and_checked_against_cached_counter() {
+        let store = Arc::new(InMemoryUsageStore::new());
+        store
+            .upsert_quota(
+                &ctx(),
+                QuotaRecord {
+                    tenant_id: "tenant-1".to_string(),
+                    metric: "api_calls".to_string(),
+                    limit: 3,
+                    updated_at_ms: 0,
+                },
+            )
+            .await
+            .expect("quota");
+        store
+            .add_usage(&ctx(), "api_calls", 0, 1)
+            .await
+            .expect("usage");
+        let meter = UsageMeter::new(store.clone());
+
+        assert!(
+            meter
+                .try_consume(&ctx(), "api_calls", 0, 1)
+                .await
+                .expect("consume")
+        );
+        assert!(
+            meter
+                .has_quota(&ctx(), "api_calls", 0, 1)
+                .await
+                .expect("check")
+        );
+        meter.record(&ctx(), "api_calls", 0, 1);
+        // 已落库 1 + 未刷盘 2 = 配额 3
+        assert!(
+            !meter
+                .try_consume(&ctx(), "api_calls", 0, 1)
+                .await
+                .expect("consume")
+        );
+        let stored = store.list_usage(&ctx(), 0, 1).await.expect("list");
+        assert_eq!(stored[0].count, 1);
+
+        meter.flush().await;
+        let stored = store.list_usage(&ctx(), 0, 1).await.expect("list");
+        assert_eq!(stored[0].count, 3);
+        assert!(
+            !meter
+                .has_quota(&ctx(), "api_calls", 0, 1)
+                .await
+                .expect("check")
+        );
+
+        // 配额删除后失效缓存即恢复
+        store
+            .delete_quota(&ctx(), "api_calls")
+            .await
+            .expect("delete");
+        meter.invalidate("tenant-1", "api_calls");
+        assert!(
+            meter
+                .try_consume(&ctx(), "api_calls", 0, 1)
+                .await
+                .expect("consume")
+        );
+        meter.flush_tenant("tenant-1").await;
+        let stored = store.list_usage(&ctx(), 0, 1).await.expect("list");
+        assert_eq!(stored[0].count, 4);
+    }
+}
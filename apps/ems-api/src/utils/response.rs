@@ -1,8 +1,10 @@
 //! HTTP 响应辅助函数和 DTO 转换
 //!
 //! 提供统一的错误响应构造函数和 DTO 转换函数：
-//! - 错误响应：auth_error, forbidden_error, bad_request_error, not_found_error, internal_auth_error, storage_error
-//! - DTO 转换：project_to_dto, gateway_to_dto, device_to_dto, point_to_dto, point_mapping_to_dto, command_to_dto, audit_log_to_dto
+//! - 错误响应：auth_error, forbidden_error, bad_request_error, field_errors_error, not_found_error, unsupported_version_error, idempotency_in_progress_error, idempotency_key_reused_error, feature_disabled_error, quota_exceeded_error, conflict_error, internal_auth_error, storage_error, control_error, pipeline_error
+//! - 成功响应：list_success（支持 `fields=` 字段选择）
+//! - 条件请求：collection_etag, last_seen_fingerprint, etag_matches, not_modified, with_etag（资产列表弱 ETag / 304）
+//! - DTO 转换：project_to_dto, gateway_to_dto, device_to_dto, device_shadow_to_dto, device_template_to_dto, device_instance_to_dto, gateway_config_push_to_dto, firmware_package_to_dto, firmware_campaign_to_dto, firmware_rollout_to_dto, maintenance_window_to_dto, point_to_dto, point_mapping_to_dto, command_to_dto, audit_log_to_dto, webhook_subscription_to_dto, webhook_delivery_to_dto, rule_to_dto, rule_execution_to_dto, schedule_to_dto, schedule_execution_to_dto, sheddable_load_to_dto, demand_response_event_to_dto, anomaly_to_dto, device_event_to_dto
 //!
 //! 设计原则：
 //! - 所有错误返回统一的 ApiResponse 格式
@@ -10,19 +12,32 @@
 //! - DTO 转换保持 Record 和 DTO 字段一致
 
 use api_contract::{
-    ApiResponse, AuditLogDto, CommandDto, CommandReceiptDto, DeviceDto, GatewayDto, PointDto,
-    PointMappingDto, ProjectDto, error_codes,
+    AnomalyDto, ApiError, ApiResponse, AuditLogDto, CommandDto, CommandReceiptDto,
+    DemandResponseEventDto, DeviceDto, DeviceEventDto, DeviceInstanceDto, DeviceShadowDto,
+    DeviceTemplateDto, DeviceTemplatePointDto, FieldErrorDto, FirmwareCampaignDto,
+    FirmwarePackageDto, FirmwareRolloutDto, GatewayConfigPushDto, GatewayDto, JobDto,
+    MaintenanceWindowDto, PointDto, PointMappingDto, ProjectDto, RuleDto, RuleExecutionDto,
+    ScheduleDto, ScheduleExecutionDto, SheddableLoadDto, WebhookDeliveryDto,
+    WebhookSubscriptionDto, error_codes,
 };
 use axum::{
     Json,
-    http::StatusCode,
+    http::{HeaderMap, HeaderValue, StatusCode, header},
     response::{IntoResponse, Response},
 };
 use ems_auth::AuthError;
+use ems_control::{ControlError, DeviceShadow};
+use ems_pipeline::PipelineError;
 use ems_storage::{
-    AuditLogRecord, CommandReceiptRecord, CommandRecord, DeviceRecord, GatewayRecord,
-    PointMappingRecord, PointRecord, ProjectRecord, StorageError,
+    AnomalyRecord, AuditLogRecord, CollectionVersion, CommandReceiptRecord, CommandRecord,
+    DemandResponseEventRecord, DeviceEventRecord, DeviceInstance, DeviceRecord,
+    DeviceTemplateRecord, FirmwareCampaignRecord, FirmwarePackageRecord, FirmwareRolloutRecord,
+    GatewayConfigRecord, GatewayRecord, JobRecord, MaintenanceWindowRecord, PointMappingRecord,
+    PointRecord, ProjectRecord, RuleExecutionRecord, RuleRecord, ScheduleExecutionRecord,
+    ScheduleRecord, SheddableLoadRecord, StorageError, StorageErrorKind, WebhookDeliveryRecord,
+    WebhookSubscriptionRecord,
 };
+use sha2::{Digest, Sha256};
 
 /// 认证错误响应
 pub fn auth_error(status: StatusCode) -> Response {
@@ -60,6 +75,34 @@ pub fn bad_request_error(message: impl Into<String>) -> Response {
         .into_response()
 }
 
+/// 字段级校验失败响应（400）
+///
+/// `error.message` 概述首个错误，`data` 列出全部字段错误。
+pub fn field_errors_error(errors: Vec<FieldErrorDto>) -> Response {
+    let message = match errors.first() {
+        Some(first) if errors.len() > 1 => format!(
+            "{}: {} (and {} more)",
+            first.field,
+            first.message,
+            errors.len() - 1
+        ),
+        Some(first) => format!("{}: {}", first.field, first.message),
+        None => "invalid request".to_string(),
+    };
+    (
+        StatusCode::BAD_REQUEST,
+        Json(ApiResponse {
+            success: false,
+            data: Some(errors),
+            error: Some(ApiError {
+                code: error_codes::INVALID_REQUEST.to_string(),
+                message,
+            }),
+        }),
+    )
+        .into_response()
+}
+
 /// 资源未找到错误响应
 pub fn not_found_error() -> Response {
     (
@@ -72,6 +115,78 @@ pub fn not_found_error() -> Response {
         .into_response()
 }
 
+/// 不支持的 API 版本响应
+pub fn unsupported_version_error(requested: &str) -> Response {
+    (
+        StatusCode::NOT_ACCEPTABLE,
+        Json(ApiResponse::<()>::error(
+            error_codes::API_VERSION_UNSUPPORTED,
+            format!("unsupported api version: {requested}"),
+        )),
+    )
+        .into_response()
+}
+
+/// 幂等键对应的首个请求仍在处理中
+pub fn idempotency_in_progress_error() -> Response {
+    (
+        StatusCode::CONFLICT,
+        Json(ApiResponse::<()>::error(
+            error_codes::IDEMPOTENCY_IN_PROGRESS,
+            "request with this idempotency key is in progress",
+        )),
+    )
+        .into_response()
+}
+
+/// 幂等键已用于内容不同的请求
+pub fn idempotency_key_reused_error() -> Response {
+    (
+        StatusCode::UNPROCESSABLE_ENTITY,
+        Json(ApiResponse::<()>::error(
+            error_codes::IDEMPOTENCY_KEY_REUSED,
+            "idempotency key reused with a different request",
+        )),
+    )
+        .into_response()
+}
+
+/// 租户未启用该功能
+pub fn feature_disabled_error(flag_key: &str) -> Response {
+    (
+        StatusCode::FORBIDDEN,
+        Json(ApiResponse::<()>::error(
+            error_codes::FEATURE_DISABLED,
+            format!("feature disabled: {flag_key}"),
+        )),
+    )
+        .into_response()
+}
+
+/// 租户配额超限响应（429）
+pub fn quota_exceeded_error(metric: &str) -> Response {
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        Json(ApiResponse::<()>::error(
+            error_codes::QUOTA_EXCEEDED,
+            format!("quota exceeded: {metric}"),
+        )),
+    )
+        .into_response()
+}
+
+/// 资源冲突响应（409），附带冲突说明
+pub fn conflict_error(message: impl Into<String>) -> Response {
+    (
+        StatusCode::CONFLICT,
+        Json(ApiResponse::<()>::error(
+            error_codes::RESOURCE_CONFLICT,
+            message.into(),
+        )),
+    )
+        .into_response()
+}
+
 /// 认证内部错误响应
 pub fn internal_auth_error(err: AuthError) -> Response {
     tracing::error!(error = ?err, "internal auth error");
@@ -86,16 +201,163 @@ pub fn internal_auth_error(err: AuthError) -> Response {
 }
 
 /// 存储错误响应
+///
+/// 按错误分类映射：NotFound 404、Conflict 409、Forbidden 403、Unavailable 503，其余 500。
+/// 底层错误信息只写日志，不返回给客户端。
 pub fn storage_error(err: StorageError) -> Response {
-    tracing::error!(error = %err, "storage error");
-    (
-        StatusCode::INTERNAL_SERVER_ERROR,
-        Json(ApiResponse::<()>::error(
+    let (status, code, message) = match err.kind() {
+        StorageErrorKind::NotFound => (
+            StatusCode::NOT_FOUND,
+            error_codes::RESOURCE_NOT_FOUND,
+            "not found",
+        ),
+        StorageErrorKind::Conflict => (
+            StatusCode::CONFLICT,
+            error_codes::RESOURCE_CONFLICT,
+            "resource already exists",
+        ),
+        StorageErrorKind::Forbidden => (
+            StatusCode::FORBIDDEN,
+            error_codes::AUTH_FORBIDDEN,
+            "forbidden",
+        ),
+        StorageErrorKind::Unavailable => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            error_codes::SERVICE_UNAVAILABLE,
+            "service unavailable",
+        ),
+        StorageErrorKind::Internal => (
+            StatusCode::INTERNAL_SERVER_ERROR,
             error_codes::INTERNAL_ERROR,
             "internal error",
-        )),
-    )
-        .into_response()
+        ),
+    };
+    if status.is_server_error() {
+        tracing::error!(error = %err, "storage error");
+    } else {
+        tracing::warn!(error = %err, "storage error");
+    }
+    (status, Json(ApiResponse::<()>::error(code, message))).into_response()
+}
+
+/// 控制链路错误响应
+///
+/// 存储错误按 [`storage_error`] 分类映射；载荷错误 400，配额超限 429，功能开关关闭 403，
+/// 下发失败等其余错误 500（底层错误信息只写日志）。
+pub fn control_error(err: ControlError) -> Response {
+    match err {
+        ControlError::Storage(err) => storage_error(err),
+        ControlError::Payload(message) => bad_request_error(message),
+        ControlError::Quota(metric) => quota_exceeded_error(&metric),
+        ControlError::FeatureDisabled(flag_key) => feature_disabled_error(&flag_key),
+        other => {
+            tracing::error!(error = %other, "control error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    error_codes::INTERNAL_ERROR,
+                    "internal error",
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// 写入流水线错误响应
+///
+/// 缓冲区已满（Backpressure）返回 503，写入器失败返回 500；底层错误信息只写日志。
+pub fn pipeline_error(err: PipelineError) -> Response {
+    let (status, code, message) = match err {
+        PipelineError::Backpressure(_) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            error_codes::SERVICE_UNAVAILABLE,
+            "service unavailable",
+        ),
+        PipelineError::Writer(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            error_codes::INTERNAL_ERROR,
+            "internal error",
+        ),
+    };
+    tracing::error!(error = %err, "pipeline error");
+    (status, Json(ApiResponse::<()>::error(code, message))).into_response()
+}
+
+/// 列表成功响应
+///
+/// 指定 `fields` 时每个列表项只保留所列的顶层字段（按序列化后的 camelCase 字段名匹配，
+/// 未知字段名忽略），用于移动端省略 `protocolConfig`、`quality` 等大字段。
+pub fn list_success<T: serde::Serialize>(data: Vec<T>, fields: Option<&[String]>) -> Response {
+    let Some(fields) = fields else {
+        return (StatusCode::OK, Json(ApiResponse::success(data))).into_response();
+    };
+    let items: Vec<serde_json::Value> = data
+        .into_iter()
+        .map(|item| match serde_json::to_value(item) {
+            Ok(serde_json::Value::Object(mut object)) => {
+                object.retain(|key, _| fields.iter().any(|field| field == key));
+                serde_json::Value::Object(object)
+            }
+            Ok(value) => value,
+            Err(_) => serde_json::Value::Null,
+        })
+        .collect();
+    (StatusCode::OK, Json(ApiResponse::success(items))).into_response()
+}
+
+/// 资产列表弱 ETag
+///
+/// 由存储层集合版本（条数 + 最近变更标记）与影响响应内容的附加因素
+/// （字段选择、实时在线状态等）共同决定，任一变化即生成新的 ETag。
+pub fn collection_etag(
+    version: CollectionVersion,
+    fields: Option<&[String]>,
+    extra: &str,
+) -> String {
+    let mut hasher = Sha256::new();
+    if let Some(fields) = fields {
+        hasher.update(fields.join(",").as_bytes());
+    }
+    hasher.update([0u8]);
+    hasher.update(extra.as_bytes());
+    let digest = hex::encode(&hasher.finalize()[..8]);
+    format!("W/\"{}-{}-{digest}\"", version.count, version.revision)
+}
+
+/// 在线状态指纹（按 ID 排序的 `id:lastSeenAtMs`），作为网关 / 设备列表 ETag 的附加因素
+pub fn last_seen_fingerprint(last_seen: &std::collections::HashMap<String, i64>) -> String {
+    let mut items: Vec<String> = last_seen
+        .iter()
+        .map(|(id, ts_ms)| format!("{id}:{ts_ms}"))
+        .collect();
+    items.sort();
+    items.join(",")
+}
+
+/// `If-None-Match` 是否命中 ETag（弱比较，支持逗号分隔的多个值与 `*`）
+pub fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+    let opaque = |value: &str| value.trim().trim_start_matches("W/").to_string();
+    let expected = opaque(etag);
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|value| value.trim() == "*" || opaque(value) == expected)
+}
+
+/// 304 Not Modified 响应（携带 ETag，无响应体）
+pub fn not_modified(etag: &str) -> Response {
+    with_etag(StatusCode::NOT_MODIFIED.into_response(), etag)
+}
+
+/// 为响应附加 ETag 头
+pub fn with_etag(mut response: Response, etag: &str) -> Response {
+    if let Ok(value) = HeaderValue::from_str(etag) {
+        response.headers_mut().insert(header::ETAG, value);
+    }
+    response
 }
 
 /// ProjectRecord 转 ProjectDto
@@ -121,6 +383,87 @@ pub fn gateway_to_dto(record: GatewayRecord) -> GatewayDto {
     }
 }
 
+/// GatewayConfigRecord 转 GatewayConfigPushDto
+pub fn gateway_config_push_to_dto(record: GatewayConfigRecord) -> GatewayConfigPushDto {
+    let document = serde_json::from_str(&record.document)
+        .unwrap_or_else(|_| serde_json::Value::String(record.document.clone()));
+    GatewayConfigPushDto {
+        project_id: record.project_id,
+        gateway_id: record.gateway_id,
+        version: record.version,
+        document,
+        status: record.status,
+        message: record.message,
+        pushed_by: record.pushed_by,
+        pushed_at_ms: record.pushed_at_ms,
+        updated_at_ms: record.updated_at_ms,
+    }
+}
+
+/// FirmwarePackageRecord 转 FirmwarePackageDto
+pub fn firmware_package_to_dto(record: FirmwarePackageRecord) -> FirmwarePackageDto {
+    let metadata = record.metadata.map(|metadata| {
+        serde_json::from_str(&metadata).unwrap_or(serde_json::Value::String(metadata))
+    });
+    FirmwarePackageDto {
+        package_id: record.package_id,
+        project_id: record.project_id,
+        name: record.name,
+        version: record.version,
+        checksum_sha256: record.checksum_sha256,
+        size_bytes: record.size_bytes,
+        storage_url: record.storage_url,
+        metadata,
+        created_by: record.created_by,
+        created_at_ms: record.created_at_ms,
+    }
+}
+
+/// FirmwareCampaignRecord 转 FirmwareCampaignDto
+pub fn firmware_campaign_to_dto(record: FirmwareCampaignRecord) -> FirmwareCampaignDto {
+    FirmwareCampaignDto {
+        campaign_id: record.campaign_id,
+        project_id: record.project_id,
+        package_id: record.package_id,
+        name: record.name,
+        status: record.status,
+        created_by: record.created_by,
+        created_at_ms: record.created_at_ms,
+        updated_at_ms: record.updated_at_ms,
+    }
+}
+
+/// FirmwareRolloutRecord 转 FirmwareRolloutDto
+pub fn firmware_rollout_to_dto(record: FirmwareRolloutRecord) -> FirmwareRolloutDto {
+    FirmwareRolloutDto {
+        campaign_id: record.campaign_id,
+        gateway_id: record.gateway_id,
+        status: record.status,
+        progress: record.progress,
+        message: record.message,
+        updated_at_ms: record.updated_at_ms,
+    }
+}
+
+/// MaintenanceWindowRecord 转 MaintenanceWindowDto（`active` 按 `now_ms` 计算）
+pub fn maintenance_window_to_dto(
+    record: MaintenanceWindowRecord,
+    now_ms: i64,
+) -> MaintenanceWindowDto {
+    let active = record.is_active_at(now_ms);
+    MaintenanceWindowDto {
+        project_id: record.project_id,
+        target_type: record.target_type,
+        target_id: record.target_id,
+        starts_at_ms: record.starts_at_ms,
+        ends_at_ms: record.ends_at_ms,
+        reason: record.reason,
+        created_by: record.created_by,
+        created_at_ms: record.created_at_ms,
+        active,
+    }
+}
+
 /// DeviceRecord 转 DeviceDto
 pub fn device_to_dto(record: DeviceRecord) -> DeviceDto {
     DeviceDto {
@@ -133,6 +476,62 @@ pub fn device_to_dto(record: DeviceRecord) -> DeviceDto {
         last_seen_at_ms: None,
         room_id: record.room_id,
         address_config: record.address_config,
+        offline_after_seconds: record.offline_after_seconds,
+    }
+}
+
+/// DeviceShadow 转 DeviceShadowDto
+pub fn device_shadow_to_dto(shadow: DeviceShadow) -> DeviceShadowDto {
+    DeviceShadowDto {
+        project_id: shadow.project_id,
+        device_id: shadow.device_id,
+        version: shadow.version,
+        in_sync: shadow.delta.is_empty(),
+        desired: serde_json::Value::Object(shadow.desired),
+        reported: serde_json::Value::Object(shadow.reported),
+        reported_at_ms: shadow.reported_at_ms,
+        delta: serde_json::Value::Object(shadow.delta),
+        last_command_id: shadow.last_command_id,
+        last_command_status: shadow.last_command_status,
+        updated_by: shadow.updated_by,
+        updated_at_ms: shadow.updated_at_ms,
+    }
+}
+
+/// DeviceTemplateRecord 转 DeviceTemplateDto
+pub fn device_template_to_dto(record: DeviceTemplateRecord) -> DeviceTemplateDto {
+    DeviceTemplateDto {
+        template_id: record.template_id,
+        project_id: record.project_id,
+        name: record.name,
+        model: record.model,
+        points: record
+            .points
+            .into_iter()
+            .map(|point| DeviceTemplatePointDto {
+                key: point.key,
+                data_type: point.data_type,
+                unit: point.unit,
+                source_type: point.source_type,
+                address: point.address,
+                scale: point.scale,
+                offset: point.offset,
+                protocol_detail: point.protocol_detail,
+            })
+            .collect(),
+    }
+}
+
+/// DeviceInstance 转 DeviceInstanceDto
+pub fn device_instance_to_dto(instance: DeviceInstance) -> DeviceInstanceDto {
+    DeviceInstanceDto {
+        device: device_to_dto(instance.device),
+        points: instance.points.into_iter().map(point_to_dto).collect(),
+        mappings: instance
+            .mappings
+            .into_iter()
+            .map(point_mapping_to_dto)
+            .collect(),
     }
 }
 
@@ -145,6 +544,7 @@ pub fn point_to_dto(record: PointRecord) -> PointDto {
         key: record.key,
         data_type: record.data_type,
         unit: record.unit,
+        tags: record.tags,
     }
 }
 
@@ -174,6 +574,7 @@ pub fn command_to_dto(record: CommandRecord) -> CommandDto {
         status: record.status,
         issued_by: record.issued_by,
         issued_at_ms: record.issued_at_ms,
+        warning: None,
     }
 }
 
@@ -203,6 +604,203 @@ pub fn command_receipt_to_dto(record: CommandReceiptRecord) -> CommandReceiptDto
     }
 }
 
+/// WebhookSubscriptionRecord 转 WebhookSubscriptionDto（不含签名密钥）
+pub fn webhook_subscription_to_dto(record: WebhookSubscriptionRecord) -> WebhookSubscriptionDto {
+    WebhookSubscriptionDto {
+        subscription_id: record.subscription_id,
+        project_id: record.project_id,
+        url: record.url,
+        event_types: record.event_types,
+        enabled: record.enabled,
+        secret: None,
+        created_by: record.created_by,
+        created_at_ms: record.created_at_ms,
+    }
+}
+
+/// WebhookDeliveryRecord 转 WebhookDeliveryDto
+pub fn webhook_delivery_to_dto(record: WebhookDeliveryRecord) -> WebhookDeliveryDto {
+    WebhookDeliveryDto {
+        delivery_id: record.delivery_id,
+        subscription_id: record.subscription_id,
+        event_id: record.event_id,
+        event_type: record.event_type,
+        status: record.status,
+        attempts: record.attempts,
+        response_status: record.response_status,
+        error: record.error,
+        created_at_ms: record.created_at_ms,
+    }
+}
+
+/// RuleRecord 转 RuleDto
+pub fn rule_to_dto(record: RuleRecord) -> RuleDto {
+    let trigger = serde_json::from_str(&record.trigger)
+        .unwrap_or_else(|_| serde_json::Value::String(record.trigger.clone()));
+    let actions = serde_json::from_str(&record.actions)
+        .unwrap_or_else(|_| serde_json::Value::String(record.actions.clone()));
+    RuleDto {
+        rule_id: record.rule_id,
+        project_id: record.project_id,
+        name: record.name,
+        enabled: record.enabled,
+        trigger,
+        actions,
+        created_by: record.created_by,
+        created_at_ms: record.created_at_ms,
+        updated_at_ms: record.updated_at_ms,
+    }
+}
+
+/// RuleExecutionRecord 转 RuleExecutionDto
+pub fn rule_execution_to_dto(record: RuleExecutionRecord) -> RuleExecutionDto {
+    let trigger = serde_json::from_str(&record.trigger_detail)
+        .unwrap_or_else(|_| serde_json::Value::String(record.trigger_detail.clone()));
+    let results = serde_json::from_str(&record.results)
+        .unwrap_or_else(|_| serde_json::Value::String(record.results.clone()));
+    RuleExecutionDto {
+        execution_id: record.execution_id,
+        rule_id: record.rule_id,
+        trigger,
+        status: record.status,
+        results,
+        executed_at_ms: record.executed_at_ms,
+    }
+}
+
+/// ScheduleRecord 转 ScheduleDto（`now_ms` 用于计算下一个计划时刻）
+pub fn schedule_to_dto(record: ScheduleRecord, now_ms: i64) -> ScheduleDto {
+    let next_run_at_ms = if record.enabled {
+        ems_schedule::next_run_at_ms(&record.spec, &record.timezone, now_ms)
+    } else {
+        None
+    };
+    let spec = serde_json::from_str(&record.spec)
+        .unwrap_or_else(|_| serde_json::Value::String(record.spec.clone()));
+    ScheduleDto {
+        schedule_id: record.schedule_id,
+        project_id: record.project_id,
+        name: record.name,
+        enabled: record.enabled,
+        timezone: record.timezone,
+        spec,
+        missed_run_policy: record.missed_run_policy,
+        next_run_at_ms,
+        last_evaluated_at_ms: record.last_evaluated_at_ms,
+        created_by: record.created_by,
+        created_at_ms: record.created_at_ms,
+        updated_at_ms: record.updated_at_ms,
+    }
+}
+
+/// ScheduleExecutionRecord 转 ScheduleExecutionDto
+pub fn schedule_execution_to_dto(record: ScheduleExecutionRecord) -> ScheduleExecutionDto {
+    let results = serde_json::from_str(&record.results)
+        .unwrap_or_else(|_| serde_json::Value::String(record.results.clone()));
+    ScheduleExecutionDto {
+        execution_id: record.execution_id,
+        schedule_id: record.schedule_id,
+        scheduled_at_ms: record.scheduled_at_ms,
+        status: record.status,
+        results,
+        executed_at_ms: record.executed_at_ms,
+    }
+}
+
+/// SheddableLoadRecord 转 SheddableLoadDto
+pub fn sheddable_load_to_dto(record: SheddableLoadRecord) -> SheddableLoadDto {
+    let shed_payload = serde_json::from_str(&record.shed_payload)
+        .unwrap_or_else(|_| serde_json::Value::String(record.shed_payload.clone()));
+    let restore_payload = serde_json::from_str(&record.restore_payload)
+        .unwrap_or_else(|_| serde_json::Value::String(record.restore_payload.clone()));
+    SheddableLoadDto {
+        device_id: record.device_id,
+        project_id: record.project_id,
+        priority: record.priority,
+        power_point_id: record.power_point_id,
+        rated_kw: record.rated_kw,
+        shed_payload,
+        restore_payload,
+        enabled: record.enabled,
+        created_at_ms: record.created_at_ms,
+        updated_at_ms: record.updated_at_ms,
+    }
+}
+
+/// DemandResponseEventRecord 转 DemandResponseEventDto
+pub fn demand_response_event_to_dto(record: DemandResponseEventRecord) -> DemandResponseEventDto {
+    let loads = serde_json::from_str(&record.loads)
+        .unwrap_or_else(|_| serde_json::Value::String(record.loads.clone()));
+    DemandResponseEventDto {
+        event_id: record.event_id,
+        project_id: record.project_id,
+        target_kw: record.target_kw,
+        start_at_ms: record.start_at_ms,
+        end_at_ms: record.end_at_ms,
+        status: record.status,
+        cancel_requested: record.cancel_requested,
+        baseline_kw: record.baseline_kw,
+        achieved_kw: record.achieved_kw,
+        loads,
+        created_by: record.created_by,
+        created_at_ms: record.created_at_ms,
+        updated_at_ms: record.updated_at_ms,
+    }
+}
+
+/// AnomalyRecord 转 AnomalyDto
+pub fn anomaly_to_dto(record: AnomalyRecord) -> AnomalyDto {
+    AnomalyDto {
+        anomaly_id: record.anomaly_id,
+        project_id: record.project_id,
+        point_id: record.point_id,
+        device_id: record.device_id,
+        bucket_start_ms: record.bucket_start_ms,
+        hour_of_week: record.hour_of_week,
+        actual_value: record.actual_value,
+        baseline_value: record.baseline_value,
+        baseline_samples: record.baseline_samples,
+        deviation_pct: record.deviation_pct,
+        threshold_pct: record.threshold_pct,
+        detected_at_ms: record.detected_at_ms,
+    }
+}
+
+/// DeviceEventRecord 转 DeviceEventDto（数据非合法 JSON 时返回 null）
+pub fn device_event_to_dto(record: DeviceEventRecord) -> DeviceEventDto {
+    let data = serde_json::from_str(&record.data).unwrap_or_default();
+    DeviceEventDto {
+        event_id: record.event_id,
+        project_id: record.project_id,
+        device_id: record.device_id,
+        event_type: record.event_type,
+        occurred_at_ms: record.occurred_at_ms,
+        data,
+    }
+}
+
+/// JobRecord 转 JobDto（参数 / 结果非合法 JSON 时返回 null）
+pub fn job_to_dto(record: JobRecord) -> JobDto {
+    JobDto {
+        job_id: record.job_id,
+        project_id: record.project_id,
+        kind: record.kind,
+        status: record.status,
+        params: serde_json::from_str(&record.params).unwrap_or_default(),
+        progress: record.progress,
+        result: record
+            .result
+            .map(|result| serde_json::from_str(&result).unwrap_or_default()),
+        error: record.error,
+        cancel_requested: record.cancel_requested,
+        created_by: record.created_by,
+        created_at_ms: record.created_at_ms,
+        started_at_ms: record.started_at_ms,
+        finished_at_ms: record.finished_at_ms,
+        updated_at_ms: record.updated_at_ms,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -219,6 +817,65 @@ mod tests {
         serde_json::from_slice(&bytes).expect("json body")
     }
 
+    #[tokio::test]
+    async fn list_success_selects_fields() {
+        let items = vec![
+            serde_json::json!({"pointId": "p-1", "value": 1.5, "quality": "good"}),
+            serde_json::json!({"pointId": "p-2", "value": 2.0}),
+        ];
+        let fields = vec![
+            "pointId".to_string(),
+            "value".to_string(),
+            "unknown".to_string(),
+        ];
+        let json = response_json(list_success(items.clone(), Some(&fields))).await;
+        assert_eq!(
+            json["data"],
+            serde_json::json!([{"pointId": "p-1", "value": 1.5}, {"pointId": "p-2", "value": 2.0}])
+        );
+        let json = response_json(list_success(items, None)).await;
+        assert_eq!(json["data"][0]["quality"], "good");
+    }
+
+    #[test]
+    fn collection_etag_matches_weakly() {
+        let version = CollectionVersion {
+            count: 2,
+            revision: 7,
+        };
+        let fields = vec!["name".to_string()];
+        let etag = collection_etag(version, Some(&fields), "");
+        assert!(etag.starts_with("W/\"2-7-"));
+        assert_ne!(etag, collection_etag(version, None, ""));
+        assert_ne!(etag, collection_etag(version, Some(&fields), "gw-1:1000"));
+        assert_ne!(
+            etag,
+            collection_etag(
+                CollectionVersion {
+                    count: 2,
+                    revision: 8
+                },
+                Some(&fields),
+                ""
+            )
+        );
+
+        let mut headers = HeaderMap::new();
+        assert!(!etag_matches(&headers, &etag));
+        let strong = etag.trim_start_matches("W/").to_string();
+        headers.insert(
+            header::IF_NONE_MATCH,
+            HeaderValue::from_str(&format!("\"other\", {strong}")).expect("header"),
+        );
+        assert!(etag_matches(&headers, &etag));
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_static("*"));
+        assert!(etag_matches(&headers, &etag));
+
+        let response = not_modified(&etag);
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[header::ETAG], etag.as_str());
+    }
+
     #[tokio::test]
     async fn forbidden_error_contract() {
         let response = forbidden_error();
@@ -236,4 +893,71 @@ mod tests {
         assert_eq!(json["success"], false);
         assert_eq!(json["error"]["code"], error_codes::AUTH_UNAUTHORIZED);
     }
+
+    #[tokio::test]
+    async fn storage_error_maps_kind_to_status() {
+        let cases = [
+            (
+                StorageError::not_found("missing"),
+                StatusCode::NOT_FOUND,
+                error_codes::RESOURCE_NOT_FOUND,
+            ),
+            (
+                StorageError::conflict("project exists"),
+                StatusCode::CONFLICT,
+                error_codes::RESOURCE_CONFLICT,
+            ),
+            (
+                StorageError::forbidden("tenant mismatch"),
+                StatusCode::FORBIDDEN,
+                error_codes::AUTH_FORBIDDEN,
+            ),
+            (
+                StorageError::unavailable("pool timed out"),
+                StatusCode::SERVICE_UNAVAILABLE,
+                error_codes::SERVICE_UNAVAILABLE,
+            ),
+            (
+                StorageError::new("lock failed"),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                error_codes::INTERNAL_ERROR,
+            ),
+        ];
+        for (err, status, code) in cases {
+            let response = storage_error(err);
+            assert_eq!(response.status(), status);
+            let json = response_json(response).await;
+            assert_eq!(json["error"]["code"], code);
+        }
+    }
+
+    #[tokio::test]
+    async fn control_error_keeps_storage_kind() {
+        let cases = [
+            (
+                ControlError::from(StorageError::not_found("gateway missing")),
+                StatusCode::NOT_FOUND,
+                error_codes::RESOURCE_NOT_FOUND,
+            ),
+            (
+                ControlError::from(StorageError::unavailable("pool timed out")),
+                StatusCode::SERVICE_UNAVAILABLE,
+                error_codes::SERVICE_UNAVAILABLE,
+            ),
+            (
+                ControlError::Dispatch("broker down".to_string()),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                error_codes::INTERNAL_ERROR,
+            ),
+        ];
+        for (err, status, code) in cases {
+            let response = control_error(err);
+            assert_eq!(response.status(), status);
+            let json = response_json(response).await;
+            assert_eq!(json["error"]["code"], code);
+            assert_ne!(json["error"]["message"], "broker down");
+        }
+        let response = control_error(ControlError::Payload("template is empty".to_string()));
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
 }
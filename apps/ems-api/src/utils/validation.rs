@@ -3,14 +3,19 @@
 //! 提供统一的输入验证函数：
 //! - normalize_required：验证必填字段，去除空格并检查非空
 //! - normalize_optional：验证可选字段，如果提供则去除空格并检查非空
+//! - normalize_tags：验证标签列表，逐个去除空格、检查非空并去重
+//! - parse_fields：解析 `fields=` 字段选择参数（逗号分隔）
+//! - check_protocol_fields：协议配置字段级校验结果转换为 400 响应
 //!
 //! 验证规则：
 //! - 去除首尾空格
 //! - 非空字符串才通过验证
 //! - 失败返回 bad_request_error 响应
 
-use crate::utils::response::bad_request_error;
+use crate::utils::response::{bad_request_error, field_errors_error};
+use api_contract::FieldErrorDto;
 use axum::response::Response;
+use ems_protocol::FieldError;
 
 /// 验证必填字段，去除空格并检查非空
 pub fn normalize_required(value: String, field: &str) -> Result<String, Response> {
@@ -34,3 +39,61 @@ pub fn normalize_optional(value: Option<String>, field: &str) -> Result<Option<S
         None => Ok(None),
     }
 }
+
+/// 验证标签列表，逐个去除空格、检查非空并去重（保持原顺序）
+pub fn normalize_tags(values: Vec<String>, field: &str) -> Result<Vec<String>, Response> {
+    let mut tags: Vec<String> = Vec::with_capacity(values.len());
+    for value in values {
+        let tag = normalize_required(value, field)?;
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    Ok(tags)
+}
+
+/// 解析 `fields=` 字段选择参数：逗号分隔、去除空格、去重；全部为空时返回 400
+pub fn parse_fields(value: Option<String>) -> Result<Option<Vec<String>>, Response> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    let mut fields: Vec<String> = Vec::new();
+    for field in value
+        .split(',')
+        .map(str::trim)
+        .filter(|field| !field.is_empty())
+    {
+        if !field
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+        {
+            return Err(bad_request_error(format!("invalid field: {field}")));
+        }
+        if !fields.iter().any(|item| item == field) {
+            fields.push(field.to_string());
+        }
+    }
+    if fields.is_empty() {
+        return Err(bad_request_error("fields required"));
+    }
+    Ok(Some(fields))
+}
+
+/// 协议配置字段级校验：有错误时返回 400，字段路径加上请求字段前缀（如 `protocolConfig.port`）
+pub fn check_protocol_fields(field: &str, errors: Vec<FieldError>) -> Result<(), Response> {
+    if errors.is_empty() {
+        return Ok(());
+    }
+    let errors = errors
+        .into_iter()
+        .map(|error| FieldErrorDto {
+            field: if error.field.is_empty() {
+                field.to_string()
+            } else {
+                format!("{field}.{}", error.field)
+            },
+            message: error.message,
+        })
+        .collect();
+    Err(field_errors_error(errors))
+}
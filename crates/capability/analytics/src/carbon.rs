@@ -0,0 +1,381 @@
+//! 碳排放核算。
+//!
+//! 按能源类型把累计量点位的消耗折算为 CO₂e：
+//! - 参与核算的点位需带 `counter` 标签和能源类型标签（`electricity` / `gas` / `diesel`）
+//! - 排放因子取项目覆盖值，未配置时取租户默认值；仍未配置的能源类型只报告消耗量
+//! - 周期按项目时区的日历桶（小时 / 日 / 月）对齐，周期消耗为累计量的增量
+
+use crate::COUNTER_POINT_TAG;
+use chrono_tz::Tz;
+use domain::TenantContext;
+use ems_storage::{
+    CalendarBucket, EmissionFactorRecord, EmissionFactorStore, MeasurementAggFn,
+    MeasurementAggregation, MeasurementStore, MeasurementsQueryOptions, PointRecord, PointStore,
+    StorageError, TimeOrder,
+};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// 支持的能源类型及默认计量单位
+pub const ENERGY_SOURCES: [(&str, &str); 3] =
+    [("electricity", "kWh"), ("gas", "m3"), ("diesel", "L")];
+
+/// 单次报表允许的最大周期数
+pub const MAX_REPORT_PERIODS: i64 = 1000;
+
+/// 是否为支持的能源类型
+pub fn is_valid_energy_source(energy_source: &str) -> bool {
+    ENERGY_SOURCES
+        .iter()
+        .any(|(source, _)| *source == energy_source)
+}
+
+/// 能源类型的默认计量单位
+pub fn default_unit(energy_source: &str) -> Option<&'static str> {
+    ENERGY_SOURCES
+        .iter()
+        .find(|(source, _)| *source == energy_source)
+        .map(|(_, unit)| *unit)
+}
+
+/// 点位的能源类型（需带 `counter` 标签；多个能源类型标签时取第一个）
+pub fn point_energy_source(point: &PointRecord) -> Option<&str> {
+    if !point.tags.iter().any(|tag| tag == COUNTER_POINT_TAG) {
+        return None;
+    }
+    point
+        .tags
+        .iter()
+        .map(String::as_str)
+        .find(|tag| is_valid_energy_source(tag))
+}
+
+/// 合并排放因子：项目覆盖值优先，其余取租户默认值（key 为能源类型）
+pub fn resolve_factors(
+    tenant: Vec<EmissionFactorRecord>,
+    project: Vec<EmissionFactorRecord>,
+) -> BTreeMap<String, EmissionFactorRecord> {
+    let mut factors: BTreeMap<String, EmissionFactorRecord> = tenant
+        .into_iter()
+        .map(|factor| (factor.energy_source.clone(), factor))
+        .collect();
+    for factor in project {
+        factors.insert(factor.energy_source.clone(), factor);
+    }
+    factors
+}
+
+/// 估算窗口 `[from_ms, to_ms)` 内的周期数（按最短桶长估算，偏多）
+pub fn estimated_periods(bucket: CalendarBucket, from_ms: i64, to_ms: i64) -> i64 {
+    // 夏令时切换日最短 23 小时，月最短 28 天
+    let min_bucket_ms: i64 = match bucket {
+        CalendarBucket::Hour => 3600 * 1000,
+        CalendarBucket::Day => 23 * 3600 * 1000,
+        CalendarBucket::Month => 28 * 24 * 3600 * 1000,
+    };
+    (to_ms - from_ms).max(0) / min_bucket_ms + 2
+}
+
+/// 由各周期的（周期起始, 最小值, 最大值）计算累计量在各周期的增量
+///
+/// 周期增量为本周期最大值减上一有数据周期的最大值（含跨周期边界的增量）；
+/// 首个周期或累计量回绕（本周期最大值小于上一周期）时取本周期最大值减最小值。
+pub fn counter_consumption(buckets: &[(i64, f64, f64)]) -> Vec<(i64, f64)> {
+    let mut previous_max: Option<f64> = None;
+    buckets
+        .iter()
+        .map(|(period_start_ms, min, max)| {
+            let consumption = match previous_max {
+                Some(previous) if *max >= previous => max - previous,
+                _ => max - min,
+            };
+            previous_max = Some(*max);
+            (*period_start_ms, consumption.max(0.0))
+        })
+        .collect()
+}
+
+/// 单个能源类型的消耗与排放
+#[derive(Debug, Clone, PartialEq)]
+pub struct CarbonSourceUsage {
+    pub energy_source: String,
+    pub consumption: f64,
+    pub unit: String,
+    /// 排放因子（未配置时为 None）
+    pub kg_co2e_per_unit: Option<f64>,
+    /// 排放量（kgCO₂e，未配置因子时为 0）
+    pub co2e_kg: f64,
+}
+
+/// 单个周期的排放
+#[derive(Debug, Clone, PartialEq)]
+pub struct CarbonPeriod {
+    pub period_start_ms: i64,
+    pub sources: Vec<CarbonSourceUsage>,
+    pub co2e_kg: f64,
+}
+
+/// 碳排放报表
+#[derive(Debug, Clone, PartialEq)]
+pub struct CarbonReport {
+    pub periods: Vec<CarbonPeriod>,
+    /// 窗口内各能源类型合计
+    pub totals: Vec<CarbonSourceUsage>,
+    pub total_co2e_kg: f64,
+    /// 有消耗但未配置排放因子的能源类型
+    pub missing_factors: Vec<String>,
+}
+
+/// 报表窗口：`[from_ms, to_ms)` 按项目时区的日历桶分周期
+#[derive(Debug, Clone, Copy)]
+pub struct CarbonReportWindow {
+    pub from_ms: i64,
+    pub to_ms: i64,
+    pub bucket: CalendarBucket,
+    pub timezone: Tz,
+}
+
+/// 碳排放报表生成器
+pub struct CarbonReporter {
+    point_store: Arc<dyn PointStore>,
+    measurement_store: Arc<dyn MeasurementStore>,
+    emission_factor_store: Arc<dyn EmissionFactorStore>,
+}
+
+impl CarbonReporter {
+    pub fn new(
+        point_store: Arc<dyn PointStore>,
+        measurement_store: Arc<dyn MeasurementStore>,
+        emission_factor_store: Arc<dyn EmissionFactorStore>,
+    ) -> Self {
+        Self {
+            point_store,
+            measurement_store,
+            emission_factor_store,
+        }
+    }
+
+    /// 项目生效的排放因子（项目覆盖 + 租户默认值）
+    pub async fn effective_factors(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<BTreeMap<String, EmissionFactorRecord>, StorageError> {
+        let tenant = self
+            .emission_factor_store
+            .list_emission_factors(ctx, None)
+            .await?;
+        let project = self
+            .emission_factor_store
+            .list_emission_factors(ctx, Some(project_id))
+            .await?;
+        Ok(resolve_factors(tenant, project))
+    }
+
+    /// 生成窗口内分周期的排放报表
+    pub async fn report(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        window: CarbonReportWindow,
+    ) -> Result<CarbonReport, StorageError> {
+        let factors = self.effective_factors(ctx, project_id).await?;
+        let points = self.point_store.list_points(ctx, project_id).await?;
+
+        // (周期起始, 能源类型) → 消耗量
+        let mut usage: BTreeMap<(i64, String), f64> = BTreeMap::new();
+        for point in &points {
+            let Some(energy_source) = point_energy_source(point) else {
+                continue;
+            };
+            let buckets = self.min_max_buckets(ctx, point, window).await?;
+            for (period_start_ms, consumption) in counter_consumption(&buckets) {
+                *usage
+                    .entry((period_start_ms, energy_source.to_string()))
+                    .or_default() += consumption;
+            }
+        }
+        Ok(build_report(usage, &factors))
+    }
+
+    async fn min_max_buckets(
+        &self,
+        ctx: &TenantContext,
+        point: &PointRecord,
+        window: CarbonReportWindow,
+    ) -> Result<Vec<(i64, f64, f64)>, StorageError> {
+        let min = self
+            .aggregate(ctx, point, window, MeasurementAggFn::Min)
+            .await?;
+        let max = self
+            .aggregate(ctx, point, window, MeasurementAggFn::Max)
+            .await?;
+        Ok(max
+            .into_iter()
+            .filter_map(|(ts_ms, max)| min.get(&ts_ms).map(|min| (ts_ms, *min, max)))
+            .collect())
+    }
+
+    async fn aggregate(
+        &self,
+        ctx: &TenantContext,
+        point: &PointRecord,
+        window: CarbonReportWindow,
+        func: MeasurementAggFn,
+    ) -> Result<BTreeMap<i64, f64>, StorageError> {
+        let records = self
+            .measurement_store
+            .query_measurements(
+                ctx,
+                &point.project_id,
+                &point.point_id,
+                MeasurementsQueryOptions {
+                    from_ms: Some(window.from_ms),
+                    to_ms: Some(window.to_ms),
+                    cursor_ts_ms: None,
+                    order: TimeOrder::Asc,
+                    limit: estimated_periods(window.bucket, window.from_ms, window.to_ms),
+                    aggregation: Some(MeasurementAggregation {
+                        bucket_ms: 0,
+                        func,
+                        calendar: Some(window.bucket),
+                    }),
+                    timezone: window.timezone,
+                },
+            )
+            .await?;
+        Ok(records
+            .into_iter()
+            .filter_map(|record| {
+                record
+                    .value
+                    .trim()
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|value| value.is_finite())
+                    .map(|value| (record.ts_ms, value))
+            })
+            .collect())
+    }
+}
+
+fn source_usage(
+    energy_source: &str,
+    consumption: f64,
+    factors: &BTreeMap<String, EmissionFactorRecord>,
+) -> CarbonSourceUsage {
+    let factor = factors.get(energy_source);
+    let kg_co2e_per_unit = factor.map(|factor| factor.kg_co2e_per_unit);
+    CarbonSourceUsage {
+        energy_source: energy_source.to_string(),
+        consumption,
+        unit: factor
+            .map(|factor| factor.unit.clone())
+            .or_else(|| default_unit(energy_source).map(str::to_string))
+            .unwrap_or_default(),
+        kg_co2e_per_unit,
+        co2e_kg: kg_co2e_per_unit.map_or(0.0, |factor| consumption * factor),
+    }
+}
+
+/// 按周期汇总消耗并折算排放
+pub fn build_report(
+    usage: BTreeMap<(i64, String), f64>,
+    factors: &BTreeMap<String, EmissionFactorRecord>,
+) -> CarbonReport {
+    let mut periods: Vec<CarbonPeriod> = Vec::new();
+    let mut totals: BTreeMap<String, f64> = BTreeMap::new();
+    for ((period_start_ms, energy_source), consumption) in usage {
+        *totals.entry(energy_source.clone()).or_default() += consumption;
+        let source = source_usage(&energy_source, consumption, factors);
+        match periods.last_mut() {
+            Some(period) if period.period_start_ms == period_start_ms => {
+                period.co2e_kg += source.co2e_kg;
+                period.sources.push(source);
+            }
+            _ => periods.push(CarbonPeriod {
+                period_start_ms,
+                co2e_kg: source.co2e_kg,
+                sources: vec![source],
+            }),
+        }
+    }
+    let totals: Vec<CarbonSourceUsage> = totals
+        .into_iter()
+        .map(|(energy_source, consumption)| source_usage(&energy_source, consumption, factors))
+        .collect();
+    let missing_factors = totals
+        .iter()
+        .filter(|total| total.kg_co2e_per_unit.is_none())
+        .map(|total| total.energy_source.clone())
+        .collect();
+    CarbonReport {
+        periods,
+        total_co2e_kg: totals.iter().map(|total| total.co2e_kg).sum(),
+        totals,
+        missing_factors,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn factor(project_id: Option<&str>, energy_source: &str, value: f64) -> EmissionFactorRecord {
+        EmissionFactorRecord {
+            tenant_id: "tenant-1".to_string(),
+            project_id: project_id.map(str::to_string),
+            energy_source: energy_source.to_string(),
+            kg_co2e_per_unit: value,
+            unit: default_unit(energy_source).unwrap_or_default().to_string(),
+            updated_at_ms: 0,
+        }
+    }
+
+    #[test]
+    fn project_factors_override_tenant_defaults() {
+        let factors = resolve_factors(
+            vec![factor(None, "electricity", 0.5), factor(None, "gas", 2.0)],
+            vec![factor(Some("project-1"), "electricity", 0.4)],
+        );
+        assert_eq!(factors.len(), 2);
+        assert_eq!(factors["electricity"].kg_co2e_per_unit, 0.4);
+        assert_eq!(factors["gas"].kg_co2e_per_unit, 2.0);
+    }
+
+    #[test]
+    fn counter_consumption_spans_period_boundaries_and_resets() {
+        let buckets = vec![
+            (0, 100.0, 150.0),
+            (1, 160.0, 200.0),
+            // 回绕
+            (2, 5.0, 30.0),
+            (3, 40.0, 60.0),
+        ];
+        assert_eq!(
+            counter_consumption(&buckets),
+            vec![(0, 50.0), (1, 50.0), (2, 25.0), (3, 30.0)]
+        );
+    }
+
+    #[test]
+    fn report_converts_consumption_and_lists_missing_factors() {
+        let factors = resolve_factors(vec![factor(None, "electricity", 0.5)], Vec::new());
+        let usage = BTreeMap::from([
+            ((0, "electricity".to_string()), 100.0),
+            ((0, "diesel".to_string()), 10.0),
+            ((1, "electricity".to_string()), 40.0),
+        ]);
+        let report = build_report(usage, &factors);
+        assert_eq!(report.periods.len(), 2);
+        assert_eq!(report.periods[0].co2e_kg, 50.0);
+        assert_eq!(report.periods[1].co2e_kg, 20.0);
+        assert_eq!(report.total_co2e_kg, 70.0);
+        assert_eq!(report.missing_factors, vec!["diesel".to_string()]);
+        let diesel = &report.totals[0];
+        assert_eq!(diesel.energy_source, "diesel");
+        assert_eq!(diesel.unit, "L");
+        assert_eq!(diesel.co2e_kg, 0.0);
+        assert_eq!(report.totals[1].consumption, 140.0);
+    }
+}
@@ -0,0 +1,520 @@
+//! 异常检测任务。
+//!
+//! 每次运行检测全部租户带 `energy` 标签的点位：
+//! - 取最近一个完整小时（项目时区整点对齐）的小时值
+//! - 取此前 `baseline_weeks` 周的小时值，计算同一 hour-of-week 的基线
+//! - 偏差绝对值不小于 `deviation_pct` 时写入异常并发布 `alarm.raised`（`source` 为 `anomaly`）
+//!
+//! 只检测最近一个完整小时，检测任务停机期间的小时不会补检；
+//! 多实例部署时异常按 (点位, 小时桶) 去重，只有写入成功的实例发布告警事件。
+
+use crate::{
+    ANOMALY_POINT_TAG, COUNTER_POINT_TAG, HOUR_MS, HourOfWeekBaseline, WEEK_MS, deviation_pct,
+    hour_of_week, is_anomalous,
+};
+use chrono_tz::Tz;
+use domain::TenantContext;
+use ems_events::{DomainEvent, EventBus, event_types};
+use ems_storage::{
+    AnomalyRecord, AnomalyStore, CalendarBucket, MeasurementAggFn, MeasurementAggregation,
+    MeasurementStore, MeasurementsQueryOptions, PointRecord, PointStore, ProjectStore,
+    StorageError, TimeOrder,
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+use tracing::{info, warn};
+
+/// 异常检测配置
+#[derive(Debug, Clone)]
+pub struct AnomalyDetectorConfig {
+    /// 检测间隔（毫秒）
+    pub tick_ms: u64,
+    /// 偏差阈值（百分比）
+    pub deviation_pct: f64,
+    /// 基线回看周数
+    pub baseline_weeks: u32,
+}
+
+impl Default for AnomalyDetectorConfig {
+    fn default() -> Self {
+        Self {
+            tick_ms: 300_000,
+            deviation_pct: 50.0,
+            baseline_weeks: 4,
+        }
+    }
+}
+
+/// 用能异常检测器（周内同时段基线 + 偏差阈值）
+pub struct AnomalyDetector {
+    point_store: Arc<dyn PointStore>,
+    project_store: Arc<dyn ProjectStore>,
+    measurement_store: Arc<dyn MeasurementStore>,
+    anomaly_store: Arc<dyn AnomalyStore>,
+    event_bus: Option<EventBus>,
+    deviation_pct: f64,
+    baseline_weeks: u32,
+}
+
+impl AnomalyDetector {
+    pub fn new(
+        point_store: Arc<dyn PointStore>,
+        project_store: Arc<dyn ProjectStore>,
+        measurement_store: Arc<dyn MeasurementStore>,
+        anomaly_store: Arc<dyn AnomalyStore>,
+        config: &AnomalyDetectorConfig,
+    ) -> Self {
+        Self {
+            point_store,
+            project_store,
+            measurement_store,
+            anomaly_store,
+            event_bus: None,
+            deviation_pct: config.deviation_pct,
+            baseline_weeks: config.baseline_weeks.max(1),
+        }
+    }
+
+    /// 挂载事件总线（新增异常时发布 `alarm.raised`）
+    pub fn with_event_bus(mut self, event_bus: EventBus) -> Self {
+        self.event_bus = Some(event_bus);
+        self
+    }
+
+    /// 运行一次检测，返回本次新增的异常
+    pub async fn run_once(&self, now_ms: i64) -> Vec<AnomalyRecord> {
+        let points = match self
+            .point_store
+            .list_points_with_tag(ANOMALY_POINT_TAG)
+            .await
+        {
+            Ok(points) => points,
+            Err(err) => {
+                warn!(target: "ems.analytics", error = %err, "anomaly_points_read_failed");
+                return Vec::new();
+            }
+        };
+        let mut timezones: HashMap<(String, String), Tz> = HashMap::new();
+        let mut detected = Vec::new();
+        for point in points {
+            let ctx = detector_context(&point);
+            let key = (point.tenant_id.clone(), point.project_id.clone());
+            let timezone = match timezones.get(&key) {
+                Some(timezone) => *timezone,
+                None => {
+                    let timezone = self.project_timezone(&ctx, &point.project_id).await;
+                    timezones.insert(key, timezone);
+                    timezone
+                }
+            };
+            match self.detect_point(&ctx, &point, timezone, now_ms).await {
+                Ok(Some(record)) => {
+                    info!(
+                        target: "ems.analytics",
+                        point_id = %record.point_id,
+                        deviation_pct = record.deviation_pct,
+                        "anomaly_detected"
+                    );
+                    self.publish(&record);
+                    detected.push(record);
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    warn!(
+                        target: "ems.analytics",
+                        point_id = %point.point_id,
+                        error = %err,
+                        "anomaly_detection_failed"
+                    );
+                }
+            }
+        }
+        detected
+    }
+
+    /// 检测单个点位最近一个完整小时，新增异常时返回记录
+    async fn detect_point(
+        &self,
+        ctx: &TenantContext,
+        point: &PointRecord,
+        timezone: Tz,
+        now_ms: i64,
+    ) -> Result<Option<AnomalyRecord>, StorageError> {
+        let current_start_ms = CalendarBucket::Hour.bucket_start_ms(now_ms, timezone);
+        let bucket_start_ms = CalendarBucket::Hour.bucket_start_ms(current_start_ms - 1, timezone);
+        let from_ms = bucket_start_ms - i64::from(self.baseline_weeks) * WEEK_MS;
+        let values = self
+            .hourly_values(ctx, point, from_ms, current_start_ms, timezone)
+            .await?;
+        let Some(actual) = values
+            .iter()
+            .find(|(ts_ms, _)| *ts_ms == bucket_start_ms)
+            .map(|(_, value)| *value)
+        else {
+            return Ok(None);
+        };
+        let history: Vec<(i64, f64)> = values
+            .into_iter()
+            .filter(|(ts_ms, _)| *ts_ms < bucket_start_ms)
+            .collect();
+        let slot = hour_of_week(bucket_start_ms, timezone);
+        let Some(baseline) = HourOfWeekBaseline::from_hourly(&history, timezone).get(slot) else {
+            return Ok(None);
+        };
+        let Some(deviation) = deviation_pct(actual, baseline.value) else {
+            return Ok(None);
+        };
+        if !is_anomalous(deviation, self.deviation_pct) {
+            return Ok(None);
+        }
+        let record = AnomalyRecord {
+            tenant_id: point.tenant_id.clone(),
+            project_id: point.project_id.clone(),
+            anomaly_id: uuid::Uuid::new_v4().to_string(),
+            point_id: point.point_id.clone(),
+            device_id: point.device_id.clone(),
+            bucket_start_ms,
+            hour_of_week: slot as i32,
+            actual_value: actual,
+            baseline_value: baseline.value,
+            baseline_samples: baseline.samples as i32,
+            deviation_pct: deviation,
+            threshold_pct: self.deviation_pct,
+            detected_at_ms: now_ms,
+        };
+        if self
+            .anomaly_store
+            .insert_anomaly(ctx, record.clone())
+            .await?
+        {
+            Ok(Some(record))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// 查询 `[from_ms, to_ms)` 内的小时值（小时桶起始时刻, 值）
+    ///
+    /// 瞬时量取小时平均；累计量取小时内最大值与最小值之差。
+    async fn hourly_values(
+        &self,
+        ctx: &TenantContext,
+        point: &PointRecord,
+        from_ms: i64,
+        to_ms: i64,
+        timezone: Tz,
+    ) -> Result<Vec<(i64, f64)>, StorageError> {
+        let is_counter = point.tags.iter().any(|tag| tag == COUNTER_POINT_TAG);
+        if !is_counter {
+            return self
+                .aggregate(ctx, point, from_ms, to_ms, timezone, MeasurementAggFn::Avg)
+                .await;
+        }
+        let max = self
+            .aggregate(ctx, point, from_ms, to_ms, timezone, MeasurementAggFn::Max)
+            .await?;
+        let min: HashMap<i64, f64> = self
+            .aggregate(ctx, point, from_ms, to_ms, timezone, MeasurementAggFn::Min)
+            .await?
+            .into_iter()
+            .collect();
+        Ok(max
+            .into_iter()
+            .filter_map(|(ts_ms, max)| min.get(&ts_ms).map(|min| (ts_ms, max - min)))
+            .collect())
+    }
+
+    async fn aggregate(
+        &self,
+        ctx: &TenantContext,
+        point: &PointRecord,
+        from_ms: i64,
+        to_ms: i64,
+        timezone: Tz,
+        func: MeasurementAggFn,
+    ) -> Result<Vec<(i64, f64)>, StorageError> {
+        // 夏令时切换会让本地小时数多于按 1 小时计算的数量，多留余量
+        let limit = (to_ms - from_ms) / HOUR_MS + 2;
+        let records = self
+            .measurement_store
+            .query_measurements(
+                ctx,
+                &point.project_id,
+                &point.point_id,
+                MeasurementsQueryOptions {
+                    from_ms: Some(from_ms),
+                    to_ms: Some(to_ms),
+                    cursor_ts_ms: None,
+                    order: TimeOrder::Asc,
+                    limit,
+                    aggregation: Some(MeasurementAggregation {
+                        bucket_ms: HOUR_MS,
+                        func,
+                        calendar: Some(CalendarBucket::Hour),
+                    }),
+                    timezone,
+                },
+            )
+            .await?;
+        Ok(records
+            .into_iter()
+            .filter_map(|record| {
+                record
+                    .value
+                    .parse::<f64>()
+                    .ok()
+                    .map(|value| (record.ts_ms, value))
+            })
+            .collect())
+    }
+
+    /// 项目时区（项目不存在或时区无效时按 UTC）
+    async fn project_timezone(&self, ctx: &TenantContext, project_id: &str) -> Tz {
+        match self.project_store.find_project(ctx, project_id).await {
+            Ok(Some(project)) => project.timezone.parse::<Tz>().unwrap_or(Tz::UTC),
+            Ok(None) => Tz::UTC,
+            Err(err) => {
+                warn!(
+                    target: "ems.analytics",
+                    project_id = %project_id,
+                    error = %err,
+                    "anomaly_project_read_failed"
+                );
+                Tz::UTC
+            }
+        }
+    }
+
+    fn publish(&self, record: &AnomalyRecord) {
+        let Some(event_bus) = &self.event_bus else {
+            return;
+        };
+        let direction = if record.deviation_pct >= 0.0 {
+            "above"
+        } else {
+            "below"
+        };
+        event_bus.publish(DomainEvent::new(
+            event_types::ALARM_RAISED,
+            record.tenant_id.clone(),
+            record.project_id.clone(),
+            json!({
+                "source": "anomaly",
+                "severity": "warning",
+                "message": format!(
+                    "point {} is {:.1}% {} its hour-of-week baseline",
+                    record.point_id,
+                    record.deviation_pct.abs(),
+                    direction
+                ),
+                "anomalyId": record.anomaly_id,
+                "pointId": record.point_id,
+                "deviceId": record.device_id,
+                "bucketStartMs": record.bucket_start_ms,
+                "actualValue": record.actual_value,
+                "baselineValue": record.baseline_value,
+                "deviationPct": record.deviation_pct,
+            }),
+        ));
+    }
+}
+
+/// 启动异常检测后台任务
+pub fn spawn_anomaly_detector(
+    detector: Arc<AnomalyDetector>,
+    config: &AnomalyDetectorConfig,
+) -> tokio::task::JoinHandle<()> {
+    let tick_ms = config.tick_ms.max(1);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_millis(tick_ms));
+        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        loop {
+            interval.tick().await;
+            detector.run_once(now_epoch_ms()).await;
+        }
+    })
+}
+
+/// 检测任务上下文（按点位所属租户与项目）
+fn detector_context(point: &PointRecord) -> TenantContext {
+    TenantContext::new(
+        point.tenant_id.clone(),
+        "anomaly-detector".to_string(),
+        Vec::new(),
+        Vec::new(),
+        Some(point.project_id.clone()),
+    )
+}
+
+fn now_epoch_ms() -> i64 {
+    let now = std::time::SystemTime::now();
+    let duration = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    duration.as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::{PointValue, PointValueData};
+    use ems_storage::{
+        AnomalyQuery, InMemoryAnomalyStore, InMemoryMeasurementStore, InMemoryPointStore,
+        InMemoryProjectStore,
+    };
+
+    // 2024-01-01T00:00:00Z 为周一
+    const MONDAY_UTC_MS: i64 = 1_704_067_200_000;
+
+    struct Harness {
+        detector: AnomalyDetector,
+        point_store: Arc<InMemoryPointStore>,
+        measurement_store: Arc<InMemoryMeasurementStore>,
+        anomaly_store: Arc<InMemoryAnomalyStore>,
+    }
+
+    fn harness(event_bus: EventBus) -> Harness {
+        let point_store = Arc::new(InMemoryPointStore::new());
+        let measurement_store = Arc::new(InMemoryMeasurementStore::new());
+        let anomaly_store = Arc::new(InMemoryAnomalyStore::new());
+        let detector = AnomalyDetector::new(
+            point_store.clone(),
+            Arc::new(InMemoryProjectStore::with_default_project()),
+            measurement_store.clone(),
+            anomaly_store.clone(),
+            &AnomalyDetectorConfig::default(),
+        )
+        .with_event_bus(event_bus);
+        Harness {
+            detector,
+            point_store,
+            measurement_store,
+            anomaly_store,
+        }
+    }
+
+    fn ctx() -> TenantContext {
+        TenantContext::new(
+            "tenant-1".to_string(),
+            "user-1".to_string(),
+            Vec::new(),
+            Vec::new(),
+            Some("project-1".to_string()),
+        )
+    }
+
+    async fn add_point(h: &Harness, point_id: &str, tags: &[&str]) {
+        h.point_store
+            .create_point(
+                &ctx(),
+                PointRecord {
+                    point_id: point_id.to_string(),
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    device_id: "device-1".to_string(),
+                    key: point_id.to_string(),
+                    data_type: "float".to_string(),
+                    unit: Some("kW".to_string()),
+                    tags: tags.iter().map(|tag| tag.to_string()).collect(),
+                },
+            )
+            .await
+            .expect("point");
+    }
+
+    async fn write(h: &Harness, point_id: &str, ts_ms: i64, value: f64) {
+        h.measurement_store
+            .write_measurement(
+                &ctx(),
+                &PointValue {
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    point_id: point_id.to_string(),
+                    ts_ms,
+                    value: PointValueData::F64(value),
+                    quality: None,
+                },
+            )
+            .await
+            .expect("measurement");
+    }
+
+    #[tokio::test]
+    async fn flags_hour_deviating_from_weekly_baseline() {
+        let bus = EventBus::default();
+        let mut receiver = bus.subscribe();
+        let h = harness(bus);
+        add_point(&h, "power", &["energy"]).await;
+        add_point(&h, "voltage", &["energy"]).await;
+        add_point(&h, "untagged", &[]).await;
+        for point_id in ["power", "voltage", "untagged"] {
+            for weeks in 1..=2 {
+                let bucket = MONDAY_UTC_MS - weeks * WEEK_MS;
+                write(&h, point_id, bucket, 90.0).await;
+                write(&h, point_id, bucket + 30 * 60 * 1000, 110.0).await;
+            }
+        }
+        // 检测小时：周一 00:00–01:00
+        write(&h, "power", MONDAY_UTC_MS + 10_000, 180.0).await;
+        write(&h, "voltage", MONDAY_UTC_MS + 10_000, 120.0).await;
+        write(&h, "untagged", MONDAY_UTC_MS + 10_000, 500.0).await;
+
+        let now_ms = MONDAY_UTC_MS + HOUR_MS + 5 * 60 * 1000;
+        let detected = h.detector.run_once(now_ms).await;
+        assert_eq!(detected.len(), 1);
+        let anomaly = &detected[0];
+        assert_eq!(anomaly.point_id, "power");
+        assert_eq!(anomaly.device_id, "device-1");
+        assert_eq!(anomaly.bucket_start_ms, MONDAY_UTC_MS);
+        assert_eq!(anomaly.hour_of_week, 0);
+        assert_eq!(anomaly.baseline_value, 100.0);
+        assert_eq!(anomaly.baseline_samples, 2);
+        assert_eq!(anomaly.deviation_pct, 80.0);
+
+        let event = receiver.recv().await.expect("event");
+        assert_eq!(event.event_type, event_types::ALARM_RAISED);
+        assert_eq!(event.data["source"], "anomaly");
+        assert_eq!(event.data["pointId"], "power");
+
+        // 同一小时重复检测不再记录
+        assert!(h.detector.run_once(now_ms + 60_000).await.is_empty());
+        let stored = h
+            .anomaly_store
+            .list_anomalies(&ctx(), "project-1", AnomalyQuery::default())
+            .await
+            .expect("list");
+        assert_eq!(stored.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn counter_points_use_hourly_increase() {
+        let h = harness(EventBus::default());
+        add_point(&h, "energy-import", &["energy", "counter"]).await;
+        let mut counter = 1000.0;
+        for weeks in (1..=3).rev() {
+            let bucket = MONDAY_UTC_MS - weeks * WEEK_MS;
+            write(&h, "energy-import", bucket, counter).await;
+            write(&h, "energy-import", bucket + HOUR_MS - 1, counter + 50.0).await;
+            counter += 1000.0;
+        }
+        // 小时增量 10 低于基线 50 的 80%
+        write(&h, "energy-import", MONDAY_UTC_MS, counter).await;
+        write(
+            &h,
+            "energy-import",
+            MONDAY_UTC_MS + HOUR_MS - 1,
+            counter + 10.0,
+        )
+        .await;
+
+        let detected = h.detector.run_once(MONDAY_UTC_MS + HOUR_MS).await;
+        assert_eq!(detected.len(), 1);
+        assert_eq!(detected[0].actual_value, 10.0);
+        assert_eq!(detected[0].baseline_value, 50.0);
+        assert_eq!(detected[0].baseline_samples, 3);
+        assert_eq!(detected[0].deviation_pct, -80.0);
+    }
+}
@@ -0,0 +1,167 @@
+//! 用能基线与异常检测
+//!
+//! 按点位计算周内同时段（hour-of-week）基线：过去若干周同一周内小时的小时值平均。
+//! 最近一个完整小时的值偏离基线超过阈值时记录为异常（`AnomalyStore`）并发布 `alarm.raised` 事件：
+//! - 只检测带 `energy` 标签的点位；小时按项目时区对齐
+//! - 瞬时量（功率、电流等）的小时值为小时平均；带 `counter` 标签的累计量为小时增量（最大值 − 最小值）
+//!
+//! `spawn_anomaly_detector` 按固定间隔运行检测；同一点位的同一小时只记录一次，重复检测无副作用。
+//!
+//! 碳排放核算（`CarbonReporter`）把带能源类型标签的累计量点位消耗按排放因子折算为 CO₂e。
+//!
+//! 电能质量分析（`PowerQualityAnalyzer`）按设备的 kW / kVA / kWh 角色点位计算功率因数、负荷率与滑动窗口峰值需量。
+
+use chrono::{Datelike, TimeZone, Timelike};
+use chrono_tz::Tz;
+
+mod carbon;
+mod detector;
+mod power_quality;
+pub use carbon::*;
+pub use detector::*;
+pub use power_quality::*;
+
+/// 参与异常检测的点位标签
+pub const ANOMALY_POINT_TAG: &str = "energy";
+/// 累计量点位标签（小时值取增量）
+pub const COUNTER_POINT_TAG: &str = "counter";
+/// 一周的小时数
+pub const HOURS_PER_WEEK: usize = 7 * 24;
+/// 一小时（毫秒）
+pub const HOUR_MS: i64 = 3600 * 1000;
+/// 一周（毫秒）
+pub const WEEK_MS: i64 = HOURS_PER_WEEK as i64 * HOUR_MS;
+/// 基线至少需要的历史小时数（不足时不判定异常）
+pub const MIN_BASELINE_SAMPLES: usize = 2;
+
+/// 时刻在指定时区的周内小时（周一 0 点为 0，周日 23 点为 167）
+pub fn hour_of_week(ts_ms: i64, timezone: Tz) -> usize {
+    let Some(utc) = chrono::DateTime::from_timestamp_millis(ts_ms) else {
+        return 0;
+    };
+    let local = timezone.from_utc_datetime(&utc.naive_utc());
+    local.weekday().num_days_from_monday() as usize * 24 + local.hour() as usize
+}
+
+/// 基线值
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BaselineValue {
+    pub value: f64,
+    /// 参与平均的历史小时数
+    pub samples: usize,
+}
+
+/// 周内同时段基线（按 hour-of-week 累计历史小时值）
+#[derive(Debug, Clone)]
+pub struct HourOfWeekBaseline {
+    sums: Vec<f64>,
+    counts: Vec<usize>,
+}
+
+impl HourOfWeekBaseline {
+    pub fn new() -> Self {
+        Self {
+            sums: vec![0.0; HOURS_PER_WEEK],
+            counts: vec![0; HOURS_PER_WEEK],
+        }
+    }
+
+    /// 由小时值（小时桶起始时刻, 值）构建基线；非有限值忽略
+    pub fn from_hourly(values: &[(i64, f64)], timezone: Tz) -> Self {
+        let mut baseline = Self::new();
+        for (bucket_start_ms, value) in values {
+            baseline.add(*bucket_start_ms, *value, timezone);
+        }
+        baseline
+    }
+
+    /// 累计一个小时值
+    pub fn add(&mut self, bucket_start_ms: i64, value: f64, timezone: Tz) {
+        if !value.is_finite() {
+            return;
+        }
+        let slot = hour_of_week(bucket_start_ms, timezone);
+        self.sums[slot] += value;
+        self.counts[slot] += 1;
+    }
+
+    /// 指定周内小时的基线（历史小时数不足 `MIN_BASELINE_SAMPLES` 时返回 None）
+    pub fn get(&self, hour_of_week: usize) -> Option<BaselineValue> {
+        let samples = *self.counts.get(hour_of_week)?;
+        if samples < MIN_BASELINE_SAMPLES {
+            return None;
+        }
+        Some(BaselineValue {
+            value: self.sums[hour_of_week] / samples as f64,
+            samples,
+        })
+    }
+}
+
+impl Default for HourOfWeekBaseline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 偏差百分比：(实际 − 基线) / |基线| × 100；基线为 0 时无法计算，返回 None
+pub fn deviation_pct(actual: f64, baseline: f64) -> Option<f64> {
+    if baseline.abs() < f64::EPSILON || !actual.is_finite() {
+        return None;
+    }
+    Some((actual - baseline) / baseline.abs() * 100.0)
+}
+
+/// 偏差是否超过阈值（偏高或偏低均视为异常）
+pub fn is_anomalous(deviation_pct: f64, threshold_pct: f64) -> bool {
+    deviation_pct.abs() >= threshold_pct
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-01-01T00:00:00Z 为周一
+    const MONDAY_UTC_MS: i64 = 1_704_067_200_000;
+
+    #[test]
+    fn hour_of_week_uses_local_time() {
+        assert_eq!(hour_of_week(MONDAY_UTC_MS, Tz::UTC), 0);
+        assert_eq!(hour_of_week(MONDAY_UTC_MS + 25 * HOUR_MS, Tz::UTC), 25);
+        assert_eq!(hour_of_week(MONDAY_UTC_MS - HOUR_MS, Tz::UTC), 167);
+        // 上海时间比 UTC 早 8 小时
+        assert_eq!(hour_of_week(MONDAY_UTC_MS, Tz::Asia__Shanghai), 8);
+    }
+
+    #[test]
+    fn baseline_averages_same_hour_of_week() {
+        let values = vec![
+            (MONDAY_UTC_MS - 2 * WEEK_MS, 10.0),
+            (MONDAY_UTC_MS - WEEK_MS, 20.0),
+            (MONDAY_UTC_MS - WEEK_MS + HOUR_MS, 100.0),
+            (MONDAY_UTC_MS - WEEK_MS + 2 * HOUR_MS, f64::NAN),
+        ];
+        let baseline = HourOfWeekBaseline::from_hourly(&values, Tz::UTC);
+        assert_eq!(
+            baseline.get(0),
+            Some(BaselineValue {
+                value: 15.0,
+                samples: 2
+            })
+        );
+        // 样本不足
+        assert_eq!(baseline.get(1), None);
+        assert_eq!(baseline.get(2), None);
+        assert_eq!(baseline.get(HOURS_PER_WEEK), None);
+    }
+
+    #[test]
+    fn deviation_is_relative_to_baseline_magnitude() {
+        assert_eq!(deviation_pct(150.0, 100.0), Some(50.0));
+        assert_eq!(deviation_pct(50.0, 100.0), Some(-50.0));
+        assert_eq!(deviation_pct(-15.0, -10.0), Some(-50.0));
+        assert_eq!(deviation_pct(10.0, 0.0), None);
+        assert!(is_anomalous(-50.0, 50.0));
+        assert!(!is_anomalous(49.9, 50.0));
+    }
+}
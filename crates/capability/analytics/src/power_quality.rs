@@ -0,0 +1,503 @@
+//! 电能质量派生指标。
+//!
+//! 按设备汇总点位角色（由点位标签配置）计算：
+//! - `kw`（或 `power`）：有功功率 kW；`kva`：视在功率 kVA；`kwh`：有功电量累计值 kWh
+//! - 需量：按子区间（默认 5 分钟）平均功率，滑动窗口（默认 15 分钟）内子区间平均的均值；峰值需量取最大窗口
+//! - 平均需量与负荷率（平均需量 / 峰值需量）
+//! - 功率因数：对齐子区间的 Σ|kW| / ΣkVA，并给出最低子区间功率因数
+//!
+//! 设备没有 kW 点位时由 kWh 累计值的子区间增量推算功率；有 kWh 点位时电量取累计增量，否则由功率积分。
+
+use crate::counter_consumption;
+use chrono_tz::Tz;
+use domain::TenantContext;
+use ems_storage::{
+    MeasurementAggFn, MeasurementAggregation, MeasurementStore, MeasurementsQueryOptions,
+    PointRecord, PointStore, StorageError, TimeOrder,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// 有功功率点位标签（kW）
+pub const KW_POINT_TAG: &str = "kw";
+/// 有功功率点位标签别名（与需求响应共用）
+pub const POWER_POINT_TAG: &str = "power";
+/// 视在功率点位标签（kVA）
+pub const KVA_POINT_TAG: &str = "kva";
+/// 有功电量累计点位标签（kWh）
+pub const KWH_POINT_TAG: &str = "kwh";
+
+/// 默认需量窗口（分钟）
+pub const DEFAULT_DEMAND_WINDOW_MINUTES: i64 = 15;
+/// 默认需量子区间（分钟）
+pub const DEFAULT_DEMAND_SUBINTERVAL_MINUTES: i64 = 5;
+/// 单次计算允许的最大子区间数
+pub const MAX_DEMAND_SUBINTERVALS: i64 = 20_000;
+
+const MINUTE_MS: i64 = 60 * 1000;
+const HOUR_MS_F64: f64 = 3600.0 * 1000.0;
+
+/// 点位角色
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointRole {
+    Kw,
+    Kva,
+    Kwh,
+}
+
+/// 点位的角色（按标签识别；同时带多个角色标签时按 kW → kVA → kWh 取第一个）
+pub fn point_role(point: &PointRecord) -> Option<PointRole> {
+    let has = |tag: &str| point.tags.iter().any(|value| value == tag);
+    if has(KW_POINT_TAG) || has(POWER_POINT_TAG) {
+        Some(PointRole::Kw)
+    } else if has(KVA_POINT_TAG) {
+        Some(PointRole::Kva)
+    } else if has(KWH_POINT_TAG) {
+        Some(PointRole::Kwh)
+    } else {
+        None
+    }
+}
+
+/// 需量窗口配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DemandWindow {
+    /// 滑动窗口长度（分钟）
+    pub window_minutes: i64,
+    /// 子区间长度（分钟），窗口需为子区间的整数倍
+    pub subinterval_minutes: i64,
+}
+
+impl Default for DemandWindow {
+    fn default() -> Self {
+        Self {
+            window_minutes: DEFAULT_DEMAND_WINDOW_MINUTES,
+            subinterval_minutes: DEFAULT_DEMAND_SUBINTERVAL_MINUTES,
+        }
+    }
+}
+
+impl DemandWindow {
+    /// 校验窗口配置
+    pub fn validate(&self) -> Result<(), String> {
+        if self.subinterval_minutes <= 0 || self.window_minutes <= 0 {
+            return Err("demand window and subinterval must be > 0".to_string());
+        }
+        if self.window_minutes % self.subinterval_minutes != 0 {
+            return Err("demand window must be a multiple of the subinterval".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn subinterval_ms(&self) -> i64 {
+        self.subinterval_minutes * MINUTE_MS
+    }
+
+    /// 窗口包含的子区间数
+    pub fn subintervals(&self) -> usize {
+        (self.window_minutes / self.subinterval_minutes).max(1) as usize
+    }
+}
+
+/// `[from_ms, to_ms)` 覆盖的子区间数（含首尾不完整的子区间）
+pub fn estimated_subintervals(subinterval_ms: i64, from_ms: i64, to_ms: i64) -> i64 {
+    if subinterval_ms <= 0 || to_ms <= from_ms {
+        return 0;
+    }
+    (to_ms - from_ms) / subinterval_ms + 2
+}
+
+/// 峰值需量
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PeakDemand {
+    pub kw: f64,
+    /// 峰值窗口起始时刻
+    pub window_start_ms: i64,
+}
+
+/// 滑动窗口峰值需量：`series` 为按时间升序的（子区间起始, 平均功率），只计算子区间连续完整的窗口
+pub fn rolling_peak_demand(
+    series: &[(i64, f64)],
+    subinterval_ms: i64,
+    subintervals: usize,
+) -> Option<PeakDemand> {
+    if subintervals == 0 || series.len() < subintervals {
+        return None;
+    }
+    let span_ms = (subintervals as i64 - 1) * subinterval_ms;
+    let mut peak: Option<PeakDemand> = None;
+    for window in series.windows(subintervals) {
+        let start_ms = window[0].0;
+        if window[subintervals - 1].0 - start_ms != span_ms {
+            continue;
+        }
+        let kw = window.iter().map(|(_, value)| value).sum::<f64>() / subintervals as f64;
+        if peak.is_none_or(|peak| kw > peak.kw) {
+            peak = Some(PeakDemand {
+                kw,
+                window_start_ms: start_ms,
+            });
+        }
+    }
+    peak
+}
+
+/// 由 kWh 累计值各子区间最大值推算子区间平均功率（只使用相邻子区间，累计值回绕时跳过）
+pub fn demand_from_counter(max_series: &[(i64, f64)], subinterval_ms: i64) -> Vec<(i64, f64)> {
+    let hours = subinterval_ms as f64 / HOUR_MS_F64;
+    max_series
+        .windows(2)
+        .filter(|pair| pair[1].0 - pair[0].0 == subinterval_ms && pair[1].1 >= pair[0].1)
+        .map(|pair| (pair[1].0, (pair[1].1 - pair[0].1) / hours))
+        .collect()
+}
+
+/// 功率因数：返回（对齐子区间的 Σ|kW| / ΣkVA，最低子区间功率因数）
+pub fn power_factor(kw: &[(i64, f64)], kva: &[(i64, f64)]) -> Option<(f64, f64)> {
+    let kva: HashMap<i64, f64> = kva.iter().copied().collect();
+    let mut sum_kw = 0.0;
+    let mut sum_kva = 0.0;
+    let mut min_pf: Option<f64> = None;
+    for (ts_ms, kw) in kw {
+        let Some(kva) = kva.get(ts_ms).copied().filter(|kva| *kva > 0.0) else {
+            continue;
+        };
+        let pf = (kw.abs() / kva).min(1.0);
+        sum_kw += kw.abs();
+        sum_kva += kva;
+        min_pf = Some(min_pf.map_or(pf, |min: f64| min.min(pf)));
+    }
+    let min_pf = min_pf?;
+    Some(((sum_kw / sum_kva).min(1.0), min_pf))
+}
+
+/// 单台设备的电能质量指标
+#[derive(Debug, Clone, PartialEq)]
+pub struct DevicePowerQuality {
+    pub device_id: String,
+    pub kw_point_id: Option<String>,
+    pub kva_point_id: Option<String>,
+    pub kwh_point_id: Option<String>,
+    /// 参与计算的功率子区间数
+    pub samples: usize,
+    /// 电量（kWh 累计增量，或功率积分）
+    pub energy_kwh: Option<f64>,
+    pub average_demand_kw: Option<f64>,
+    pub peak_demand: Option<PeakDemand>,
+    /// 负荷率（平均需量 / 峰值需量）
+    pub load_factor: Option<f64>,
+    pub power_factor: Option<f64>,
+    pub min_power_factor: Option<f64>,
+}
+
+/// 由子区间序列汇总设备指标
+pub fn summarize_device(
+    device_id: &str,
+    points: &DeviceRolePoints,
+    demand: &[(i64, f64)],
+    kva: &[(i64, f64)],
+    counter_energy_kwh: Option<f64>,
+    window: DemandWindow,
+) -> DevicePowerQuality {
+    let subinterval_ms = window.subinterval_ms();
+    let average_demand_kw = (!demand.is_empty())
+        .then(|| demand.iter().map(|(_, kw)| kw).sum::<f64>() / demand.len() as f64);
+    let peak_demand = rolling_peak_demand(demand, subinterval_ms, window.subintervals());
+    let load_factor = match (average_demand_kw, peak_demand) {
+        (Some(average), Some(peak)) if peak.kw > 0.0 => Some(average / peak.kw),
+        _ => None,
+    };
+    let energy_kwh = counter_energy_kwh.or_else(|| {
+        (!demand.is_empty()).then(|| {
+            demand.iter().map(|(_, kw)| kw).sum::<f64>() * subinterval_ms as f64 / HOUR_MS_F64
+        })
+    });
+    let power_factor = power_factor(demand, kva);
+    DevicePowerQuality {
+        device_id: device_id.to_string(),
+        kw_point_id: points.kw.as_ref().map(|point| point.point_id.clone()),
+        kva_point_id: points.kva.as_ref().map(|point| point.point_id.clone()),
+        kwh_point_id: points.kwh.as_ref().map(|point| point.point_id.clone()),
+        samples: demand.len(),
+        energy_kwh,
+        average_demand_kw,
+        peak_demand,
+        load_factor,
+        power_factor: power_factor.map(|(average, _)| average),
+        min_power_factor: power_factor.map(|(_, min)| min),
+    }
+}
+
+/// 设备下各角色的点位（同一角色多个点位时取 point_id 最小者）
+#[derive(Debug, Clone, Default)]
+pub struct DeviceRolePoints {
+    pub kw: Option<PointRecord>,
+    pub kva: Option<PointRecord>,
+    pub kwh: Option<PointRecord>,
+}
+
+/// 按设备归集角色点位（key 为 device_id）
+pub fn group_role_points(points: Vec<PointRecord>) -> BTreeMap<String, DeviceRolePoints> {
+    let mut devices: BTreeMap<String, DeviceRolePoints> = BTreeMap::new();
+    for point in points {
+        let Some(role) = point_role(&point) else {
+            continue;
+        };
+        let entry = devices.entry(point.device_id.clone()).or_default();
+        let slot = match role {
+            PointRole::Kw => &mut entry.kw,
+            PointRole::Kva => &mut entry.kva,
+            PointRole::Kwh => &mut entry.kwh,
+        };
+        if slot
+            .as_ref()
+            .is_none_or(|current| point.point_id < current.point_id)
+        {
+            *slot = Some(point);
+        }
+    }
+    devices
+}
+
+/// 电能质量分析器
+pub struct PowerQualityAnalyzer {
+    point_store: Arc<dyn PointStore>,
+    measurement_store: Arc<dyn MeasurementStore>,
+}
+
+impl PowerQualityAnalyzer {
+    pub fn new(
+        point_store: Arc<dyn PointStore>,
+        measurement_store: Arc<dyn MeasurementStore>,
+    ) -> Self {
+        Self {
+            point_store,
+            measurement_store,
+        }
+    }
+
+    /// 计算 `[from_ms, to_ms)` 内各设备的指标（`device_id` 为 None 时计算项目下全部带角色点位的设备）
+    pub async fn analyze(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        device_id: Option<&str>,
+        from_ms: i64,
+        to_ms: i64,
+        window: DemandWindow,
+    ) -> Result<Vec<DevicePowerQuality>, StorageError> {
+        let points = self.point_store.list_points(ctx, project_id).await?;
+        let devices = group_role_points(
+            points
+                .into_iter()
+                .filter(|point| device_id.is_none_or(|device_id| point.device_id == device_id))
+                .collect(),
+        );
+        let subinterval_ms = window.subinterval_ms();
+        let mut results = Vec::with_capacity(devices.len());
+        for (device_id, role_points) in &devices {
+            let kwh_max = match &role_points.kwh {
+                Some(point) => {
+                    self.aggregate(
+                        ctx,
+                        point,
+                        from_ms,
+                        to_ms,
+                        subinterval_ms,
+                        MeasurementAggFn::Max,
+                    )
+                    .await?
+                }
+                None => Vec::new(),
+            };
+            let counter_energy_kwh = match &role_points.kwh {
+                Some(point) => {
+                    let min: HashMap<i64, f64> = self
+                        .aggregate(
+                            ctx,
+                            point,
+                            from_ms,
+                            to_ms,
+                            subinterval_ms,
+                            MeasurementAggFn::Min,
+                        )
+                        .await?
+                        .into_iter()
+                        .collect();
+                    let buckets: Vec<(i64, f64, f64)> = kwh_max
+                        .iter()
+                        .filter_map(|(ts_ms, max)| min.get(ts_ms).map(|min| (*ts_ms, *min, *max)))
+                        .collect();
+                    (!buckets.is_empty()).then(|| {
+                        counter_consumption(&buckets)
+                            .iter()
+                            .map(|(_, consumption)| consumption)
+                            .sum()
+                    })
+                }
+                None => None,
+            };
+            let demand = match &role_points.kw {
+                Some(point) => {
+                    self.aggregate(
+                        ctx,
+                        point,
+                        from_ms,
+                        to_ms,
+                        subinterval_ms,
+                        MeasurementAggFn::Avg,
+                    )
+                    .await?
+                }
+                None => demand_from_counter(&kwh_max, subinterval_ms),
+            };
+            let kva = match &role_points.kva {
+                Some(point) => {
+                    self.aggregate(
+                        ctx,
+                        point,
+                        from_ms,
+                        to_ms,
+                        subinterval_ms,
+                        MeasurementAggFn::Avg,
+                    )
+                    .await?
+                }
+                None => Vec::new(),
+            };
+            results.push(summarize_device(
+                device_id,
+                role_points,
+                &demand,
+                &kva,
+                counter_energy_kwh,
+                window,
+            ));
+        }
+        Ok(results)
+    }
+
+    async fn aggregate(
+        &self,
+        ctx: &TenantContext,
+        point: &PointRecord,
+        from_ms: i64,
+        to_ms: i64,
+        subinterval_ms: i64,
+        func: MeasurementAggFn,
+    ) -> Result<Vec<(i64, f64)>, StorageError> {
+        let records = self
+            .measurement_store
+            .query_measurements(
+                ctx,
+                &point.project_id,
+                &point.point_id,
+                MeasurementsQueryOptions {
+                    from_ms: Some(from_ms),
+                    to_ms: Some(to_ms),
+                    cursor_ts_ms: None,
+                    order: TimeOrder::Asc,
+                    limit: estimated_subintervals(subinterval_ms, from_ms, to_ms),
+                    aggregation: Some(MeasurementAggregation {
+                        bucket_ms: subinterval_ms,
+                        func,
+                        calendar: None,
+                    }),
+                    timezone: Tz::UTC,
+                },
+            )
+            .await?;
+        Ok(records
+            .into_iter()
+            .filter_map(|record| {
+                record
+                    .value
+                    .trim()
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|value| value.is_finite())
+                    .map(|value| (record.ts_ms, value))
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUB_MS: i64 = 5 * MINUTE_MS;
+
+    #[test]
+    fn rolling_peak_requires_contiguous_subintervals() {
+        let series = vec![
+            (0, 10.0),
+            (SUB_MS, 20.0),
+            (2 * SUB_MS, 30.0),
+            // 缺少 3 × SUB_MS
+            (4 * SUB_MS, 100.0),
+            (5 * SUB_MS, 100.0),
+            (6 * SUB_MS, 40.0),
+        ];
+        let peak = rolling_peak_demand(&series, SUB_MS, 3).expect("peak");
+        assert_eq!(peak.window_start_ms, 4 * SUB_MS);
+        assert_eq!(peak.kw, 80.0);
+        assert_eq!(rolling_peak_demand(&series[..2], SUB_MS, 3), None);
+    }
+
+    #[test]
+    fn counter_demand_uses_adjacent_increments() {
+        // 5 分钟 10 kWh → 120 kW
+        let max_series = vec![
+            (0, 100.0),
+            (SUB_MS, 110.0),
+            (2 * SUB_MS, 115.0),
+            (4 * SUB_MS, 200.0),
+            (5 * SUB_MS, 3.0),
+        ];
+        assert_eq!(
+            demand_from_counter(&max_series, SUB_MS),
+            vec![(SUB_MS, 120.0), (2 * SUB_MS, 60.0)]
+        );
+    }
+
+    #[test]
+    fn power_factor_aligns_subintervals() {
+        let kw = vec![(0, 80.0), (SUB_MS, -45.0), (2 * SUB_MS, 50.0)];
+        let kva = vec![(0, 100.0), (SUB_MS, 50.0), (3 * SUB_MS, 60.0)];
+        let (average, min) = power_factor(&kw, &kva).expect("pf");
+        assert!((average - 125.0 / 150.0).abs() < 1e-9);
+        assert_eq!(min, 0.8);
+        assert_eq!(power_factor(&kw, &[]), None);
+    }
+
+    #[test]
+    fn summary_derives_load_factor_and_energy() {
+        let demand = vec![(0, 10.0), (SUB_MS, 30.0), (2 * SUB_MS, 20.0)];
+        let summary = summarize_device(
+            "device-1",
+            &DeviceRolePoints::default(),
+            &demand,
+            &[],
+            None,
+            DemandWindow {
+                window_minutes: 10,
+                subinterval_minutes: 5,
+            },
+        );
+        assert_eq!(summary.average_demand_kw, Some(20.0));
+        assert_eq!(summary.peak_demand.map(|peak| peak.kw), Some(25.0));
+        assert_eq!(summary.load_factor, Some(0.8));
+        // 60 kW·子区间 × 5 分钟 = 5 kWh
+        assert_eq!(summary.energy_kwh, Some(5.0));
+        assert_eq!(summary.power_factor, None);
+        assert!(
+            DemandWindow {
+                window_minutes: 15,
+                subinterval_minutes: 10
+            }
+            .validate()
+            .is_err()
+        );
+    }
+}
@@ -62,6 +62,14 @@ impl JwtManager {
         self.decode(token, ACCESS_TOKEN_TYPE)
     }
 
+    /// 解析 access token，同时返回过期时间（Unix 秒），供长连接在到期时断开。
+    pub fn decode_access_with_expiry(
+        &self,
+        token: &str,
+    ) -> Result<(TenantContext, u64), AuthError> {
+        self.decode_with_expiry(token, ACCESS_TOKEN_TYPE)
+    }
+
     /// 解析 refresh token。
     pub fn decode_refresh(&self, token: &str) -> Result<TenantContext, AuthError> {
         self.decode(token, REFRESH_TOKEN_TYPE)
@@ -111,17 +119,28 @@ impl JwtManager {
 
     /// 内部解码逻辑，校验 token 类型。
     fn decode(&self, token: &str, expected_type: &str) -> Result<TenantContext, AuthError> {
+        self.decode_with_expiry(token, expected_type)
+            .map(|(ctx, _)| ctx)
+    }
+
+    /// 内部解码逻辑，校验 token 类型并返回过期时间（Unix 秒）。
+    fn decode_with_expiry(
+        &self,
+        token: &str,
+        expected_type: &str,
+    ) -> Result<(TenantContext, u64), AuthError> {
         let decoded = self.decode_claims(token)?;
         if decoded.token_type != expected_type {
             return Err(AuthError::TokenInvalid);
         }
-        Ok(TenantContext::new(
+        let ctx = TenantContext::new(
             decoded.tenant_id,
             decoded.sub,
             decoded.roles,
             decoded.permissions,
             None,
-        ))
+        );
+        Ok((ctx, decoded.exp as u64))
     }
 }
 
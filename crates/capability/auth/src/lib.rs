@@ -90,6 +90,14 @@ impl AuthService {
         self.jwt.decode_access(token)
     }
 
+    /// 校验 access token，提取 TenantContext 与过期时间（Unix 秒）。
+    pub fn verify_access_token_with_expiry(
+        &self,
+        token: &str,
+    ) -> Result<(TenantContext, u64), AuthError> {
+        self.jwt.decode_access_with_expiry(token)
+    }
+
     /// 使用 refresh token 换取新 token。
     pub async fn refresh(&self, token: &str) -> Result<AuthTokens, AuthError> {
         let (ctx, jti) = self.jwt.decode_refresh_with_jti(token)?;
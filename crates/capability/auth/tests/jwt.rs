@@ -18,4 +18,13 @@ fn jwt_issue_and_decode() {
 
     assert_eq!(access_ctx.tenant_id, "tenant-1");
     assert_eq!(refresh_ctx.user_id, "user-1");
+
+    let (_, expires_at) = jwt
+        .decode_access_with_expiry(&tokens.access_token)
+        .expect("access with expiry");
+    assert!(expires_at.abs_diff(tokens.expires_at) <= 1);
+    assert!(
+        jwt.decode_access_with_expiry(&tokens.refresh_token)
+            .is_err()
+    );
 }
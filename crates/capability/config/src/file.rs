@@ -0,0 +1,317 @@
+//! 配置文件解析（TOML / YAML）。
+//!
+//! 文件按子系统分节（`[http]`、`[storage]`、`[mqtt]`、`[control]` …），
+//! 解析结果展开为 `节.键` → 字符串值，再由 [`FILE_KEYS`] 映射到对应的环境变量名。
+//! YAML 仅支持配置所需的子集：嵌套映射 + 标量值（不支持序列、锚点与多文档）。
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::ConfigError;
+
+/// 配置文件键路径 → 环境变量名。
+pub(crate) const FILE_KEYS: &[(&str, &str)] = &[
+    ("http.addr", "EMS_HTTP_ADDR"),
+    ("http.api_legacy_sunset", "EMS_API_LEGACY_SUNSET"),
+    ("http.grpc_addr", "EMS_GRPC_ADDR"),
+    ("http.ops_addr", "EMS_OPS_ADDR"),
+    ("storage.database_url", "EMS_DATABASE_URL"),
+    ("storage.require_timescale", "EMS_REQUIRE_TIMESCALE"),
+    ("storage.measurement_dedup", "EMS_MEASUREMENT_DEDUP"),
+    ("storage.redis_url", "EMS_REDIS_URL"),
+    (
+        "storage.redis_last_value_ttl_seconds",
+        "EMS_REDIS_LAST_VALUE_TTL_SECONDS",
+    ),
+    (
+        "storage.redis_online_ttl_seconds",
+        "EMS_REDIS_ONLINE_TTL_SECONDS",
+    ),
+    ("mqtt.host", "EMS_MQTT_HOST"),
+    ("mqtt.port", "EMS_MQTT_PORT"),
+    ("mqtt.username", "EMS_MQTT_USERNAME"),
+    ("mqtt.password", "EMS_MQTT_PASSWORD"),
+    ("mqtt.topic_prefix", "EMS_MQTT_TOPIC_PREFIX"),
+    ("mqtt.data_topic_prefix", "EMS_MQTT_DATA_TOPIC_PREFIX"),
+    (
+        "mqtt.data_topic_has_source_id",
+        "EMS_MQTT_DATA_TOPIC_HAS_SOURCE_ID",
+    ),
+    ("mqtt.command_topic_prefix", "EMS_MQTT_COMMAND_TOPIC_PREFIX"),
+    (
+        "mqtt.command_topic_include_target",
+        "EMS_MQTT_COMMAND_TOPIC_INCLUDE_TARGET",
+    ),
+    ("mqtt.receipt_topic_prefix", "EMS_MQTT_RECEIPT_TOPIC_PREFIX"),
+    ("mqtt.config_topic_prefix", "EMS_MQTT_CONFIG_TOPIC_PREFIX"),
+    (
+        "mqtt.config_receipt_topic_prefix",
+        "EMS_MQTT_CONFIG_RECEIPT_TOPIC_PREFIX",
+    ),
+    (
+        "mqtt.firmware_topic_prefix",
+        "EMS_MQTT_FIRMWARE_TOPIC_PREFIX",
+    ),
+    (
+        "mqtt.firmware_receipt_topic_prefix",
+        "EMS_MQTT_FIRMWARE_RECEIPT_TOPIC_PREFIX",
+    ),
+    ("mqtt.command_qos", "EMS_MQTT_COMMAND_QOS"),
+    ("mqtt.receipt_qos", "EMS_MQTT_RECEIPT_QOS"),
+    ("mqtt.receipt_strict", "EMS_MQTT_RECEIPT_STRICT"),
+    ("ingest.enabled", "EMS_INGEST"),
+    ("ingest.online_from_data", "EMS_ONLINE_FROM_DATA"),
+    ("control.enabled", "EMS_CONTROL"),
+    (
+        "control.dispatch_max_retries",
+        "EMS_CONTROL_DISPATCH_MAX_RETRIES",
+    ),
+    (
+        "control.dispatch_backoff_ms",
+        "EMS_CONTROL_DISPATCH_BACKOFF_MS",
+    ),
+    (
+        "control.receipt_timeout_seconds",
+        "EMS_CONTROL_RECEIPT_TIMEOUT_SECONDS",
+    ),
+    ("webhook.max_attempts", "EMS_WEBHOOK_MAX_ATTEMPTS"),
+    ("webhook.backoff_ms", "EMS_WEBHOOK_BACKOFF_MS"),
+    ("webhook.timeout_ms", "EMS_WEBHOOK_TIMEOUT_MS"),
+    (
+        "webhook.allow_private_targets",
+        "EMS_WEBHOOK_ALLOW_PRIVATE_TARGETS",
+    ),
+    ("rules.tick_ms", "EMS_RULES_TICK_MS"),
+    ("schedule.tick_ms", "EMS_SCHEDULE_TICK_MS"),
+    ("schedule.grace_ms", "EMS_SCHEDULE_GRACE_MS"),
+    ("demand_response.tick_ms", "EMS_DEMAND_RESPONSE_TICK_MS"),
+    ("anomaly.tick_ms", "EMS_ANOMALY_TICK_MS"),
+    ("anomaly.deviation_pct", "EMS_ANOMALY_DEVIATION_PCT"),
+    ("anomaly.baseline_weeks", "EMS_ANOMALY_BASELINE_WEEKS"),
+    ("presence.tick_ms", "EMS_PRESENCE_TICK_MS"),
+    (
+        "presence.offline_after_seconds",
+        "EMS_PRESENCE_OFFLINE_AFTER_SECONDS",
+    ),
+    ("presence.recovery_seconds", "EMS_PRESENCE_RECOVERY_SECONDS"),
+    ("jobs.max_concurrency", "EMS_JOB_MAX_CONCURRENCY"),
+    ("jobs.recover_interrupted", "EMS_JOB_RECOVER_INTERRUPTED"),
+    ("export.sink", "EMS_EXPORT_SINK"),
+    ("export.url", "EMS_EXPORT_URL"),
+    ("export.topic", "EMS_EXPORT_TOPIC"),
+    ("export.buffer_size", "EMS_EXPORT_BUFFER_SIZE"),
+    ("export.batch_size", "EMS_EXPORT_BATCH_SIZE"),
+    ("modbus_server.enabled", "EMS_MODBUS_SERVER_ENABLED"),
+    ("idempotency.ttl_seconds", "EMS_IDEMPOTENCY_TTL_SECONDS"),
+    ("log.level", "EMS_LOG_LEVEL"),
+    ("pipeline.batch_size", "EMS_PIPELINE_BATCH_SIZE"),
+    (
+        "pipeline.flush_interval_ms",
+        "EMS_PIPELINE_FLUSH_INTERVAL_MS",
+    ),
+    ("pipeline.max_buffer_size", "EMS_PIPELINE_MAX_BUFFER_SIZE"),
+    ("pipeline.max_retries", "EMS_PIPELINE_MAX_RETRIES"),
+    ("pipeline.dedup_cache_size", "EMS_PIPELINE_DEDUP_CACHE_SIZE"),
+    ("pipeline.max_age_ms", "EMS_PIPELINE_MAX_AGE_MS"),
+    ("seed.demo", "EMS_SEED_DEMO"),
+    ("jwt.secret", "EMS_JWT_SECRET"),
+    ("jwt.access_ttl_seconds", "EMS_JWT_ACCESS_TTL_SECONDS"),
+    ("jwt.refresh_ttl_seconds", "EMS_JWT_REFRESH_TTL_SECONDS"),
+];
+
+/// 配置文件中的一个取值（保留键路径用于报错）。
+#[derive(Debug, Clone)]
+pub(crate) struct FileValue {
+    pub key_path: String,
+    pub value: String,
+}
+
+/// 读取配置文件，返回 环境变量名 → 取值。
+pub(crate) fn load_file(path: &Path) -> Result<HashMap<&'static str, FileValue>, ConfigError> {
+    let display = path.display().to_string();
+    let content = std::fs::read_to_string(path)
+        .map_err(|err| ConfigError::File(display.clone(), err.to_string()))?;
+    let extension = path
+        .extension()
+        .and_then(|value| value.to_str())
+        .map(|value| value.to_ascii_lowercase());
+    let entries = match extension.as_deref() {
+        Some("toml") => parse_toml(&content),
+        Some("yaml") | Some("yml") => parse_yaml(&content),
+        _ => Err("unsupported config file extension (expected .toml/.yaml/.yml)".to_string()),
+    }
+    .map_err(|err| ConfigError::File(display.clone(), err))?;
+
+    let mut values = HashMap::new();
+    for (key_path, value) in entries {
+        let Some((_, env_key)) = FILE_KEYS.iter().find(|(path, _)| *path == key_path) else {
+            return Err(ConfigError::UnknownKey(display, key_path));
+        };
+        values.insert(*env_key, FileValue { key_path, value });
+    }
+    Ok(values)
+}
+
+/// 解析 TOML，展开为 (键路径, 标量字符串)。
+pub(crate) fn parse_toml(content: &str) -> Result<Vec<(String, String)>, String> {
+    let document = content
+        .parse::<toml_edit::DocumentMut>()
+        .map_err(|err| err.to_string())?;
+    let mut entries = Vec::new();
+    flatten_toml_table(document.as_table(), "", &mut entries)?;
+    Ok(entries)
+}
+
+fn flatten_toml_table(
+    table: &toml_edit::Table,
+    prefix: &str,
+    entries: &mut Vec<(String, String)>,
+) -> Result<(), String> {
+    for (key, item) in table.iter() {
+        let path = join_path(prefix, key);
+        match item {
+            toml_edit::Item::Table(child) => flatten_toml_table(child, &path, entries)?,
+            toml_edit::Item::Value(toml_edit::Value::InlineTable(child)) => {
+                for (child_key, value) in child.iter() {
+                    entries.push((
+                        join_path(&path, child_key),
+                        toml_scalar(&join_path(&path, child_key), value)?,
+                    ));
+                }
+            }
+            toml_edit::Item::Value(value) => {
+                entries.push((path.clone(), toml_scalar(&path, value)?))
+            }
+            toml_edit::Item::ArrayOfTables(_) => {
+                return Err(format!("{path}: arrays of tables are not supported"));
+            }
+            toml_edit::Item::None => {}
+        }
+    }
+    Ok(())
+}
+
+fn toml_scalar(path: &str, value: &toml_edit::Value) -> Result<String, String> {
+    match value {
+        toml_edit::Value::String(value) => Ok(value.value().clone()),
+        toml_edit::Value::Integer(value) => Ok(value.value().to_string()),
+        toml_edit::Value::Float(value) => Ok(value.value().to_string()),
+        toml_edit::Value::Boolean(value) => Ok(value.value().to_string()),
+        toml_edit::Value::Datetime(value) => Ok(value.value().to_string()),
+        toml_edit::Value::Array(_) | toml_edit::Value::InlineTable(_) => {
+            Err(format!("{path}: expected a scalar value"))
+        }
+    }
+}
+
+/// 解析 YAML 子集（嵌套映射 + 标量），展开为 (键路径, 标量字符串)。
+pub(crate) fn parse_yaml(content: &str) -> Result<Vec<(String, String)>, String> {
+    let mut entries = Vec::new();
+    // (缩进, 键) 栈，表示当前所在的映射路径
+    let mut stack: Vec<(usize, String)> = Vec::new();
+    for (index, raw_line) in content.lines().enumerate() {
+        let line_no = index + 1;
+        let line = strip_yaml_comment(raw_line).trim_end();
+        if line.trim().is_empty() || (line == "---" && entries.is_empty() && stack.is_empty()) {
+            continue;
+        }
+        if line.trim_start_matches(' ').starts_with('\t') {
+            return Err(format!(
+                "line {line_no}: tabs are not allowed for indentation"
+            ));
+        }
+        let indent = line.len() - line.trim_start_matches(' ').len();
+        let text = line.trim();
+        if text.starts_with("- ") || text == "-" {
+            return Err(format!("line {line_no}: sequences are not supported"));
+        }
+        let Some((key, value)) = text.split_once(':') else {
+            return Err(format!("line {line_no}: expected `key: value`"));
+        };
+        let key = unquote(key.trim());
+        if key.is_empty() {
+            return Err(format!("line {line_no}: empty key"));
+        }
+        while stack.last().is_some_and(|(level, _)| *level >= indent) {
+            stack.pop();
+        }
+        let prefix = stack
+            .iter()
+            .map(|(_, key)| key.as_str())
+            .collect::<Vec<_>>()
+            .join(".");
+        let path = join_path(&prefix, &key);
+        let value = value.trim();
+        if value.is_empty() {
+            stack.push((indent, key));
+            continue;
+        }
+        if value.starts_with('[') || value.starts_with('{') || value.starts_with('&') {
+            return Err(format!("line {line_no}: {path}: expected a scalar value"));
+        }
+        if matches!(value, "~" | "null") {
+            continue;
+        }
+        entries.push((path, unquote(value)));
+    }
+    Ok(entries)
+}
+
+fn strip_yaml_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut previous = ' ';
+    for (index, ch) in line.char_indices() {
+        match (quote, ch) {
+            (None, '"' | '\'') => quote = Some(ch),
+            (Some(open), _) if ch == open => quote = None,
+            (None, '#') if previous.is_whitespace() => return &line[..index],
+            _ => {}
+        }
+        previous = ch;
+    }
+    line
+}
+
+fn unquote(value: &str) -> String {
+    let quoted = value.len() >= 2
+        && ((value.starts_with('"') && value.ends_with('"'))
+            || (value.starts_with('\'') && value.ends_with('\'')));
+    if quoted {
+        value[1..value.len() - 1].to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+fn join_path(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{prefix}.{key}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toml_and_yaml_flatten_to_same_paths() {
+        let toml =
+            "[http]\naddr = \"0.0.0.0:8080\"\n\n[mqtt]\nport = 1884\nhost = \"broker\" # 注释\n";
+        let yaml = "http:\n  addr: \"0.0.0.0:8080\"\nmqtt:\n  port: 1884\n  host: broker # 注释\n";
+        let expected = vec![
+            ("http.addr".to_string(), "0.0.0.0:8080".to_string()),
+            ("mqtt.port".to_string(), "1884".to_string()),
+            ("mqtt.host".to_string(), "broker".to_string()),
+        ];
+        assert_eq!(parse_toml(toml).expect("toml"), expected);
+        assert_eq!(parse_yaml(yaml).expect("yaml"), expected);
+    }
+
+    #[test]
+    fn yaml_rejects_sequences_with_line_number() {
+        let err = parse_yaml("mqtt:\n  hosts:\n    - a\n").expect_err("sequence");
+        assert_eq!(err, "line 3: sequences are not supported");
+    }
+}
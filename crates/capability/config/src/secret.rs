@@ -0,0 +1,131 @@
+//! 密钥来源：`*_FILE` 挂载文件与外部密钥服务（Vault / KMS）。
+//!
+//! 对 [`SECRET_KEYS`] 中的每个键，按以下顺序取值（均高于配置文件）：
+//! 1. 环境变量本身（如 `EMS_JWT_SECRET`）
+//! 2. `<KEY>_FILE` 指向的文件内容（docker / k8s secrets 挂载，去除首尾空白）
+//! 3. [`SecretProvider`]（如 `EMS_SECRETS_COMMAND` 调用 vault / 云 KMS CLI）
+
+use std::collections::HashMap;
+use std::process::Command;
+
+use crate::{ConfigError, ConfigOrigin, read_optional};
+
+/// 支持 `*_FILE` 与外部密钥服务的配置键。
+pub const SECRET_KEYS: &[&str] = &[
+    "EMS_DATABASE_URL",
+    "EMS_REDIS_URL",
+    "EMS_MQTT_PASSWORD",
+    "EMS_JWT_SECRET",
+];
+
+/// 外部密钥命令环境变量（见 [`CommandSecretProvider`]）。
+pub const SECRETS_COMMAND_ENV: &str = "EMS_SECRETS_COMMAND";
+
+/// 外部密钥获取钩子（Vault、KMS 等）；返回 `Ok(None)` 表示该键未托管。
+pub trait SecretProvider: Send + Sync {
+    fn get_secret(&self, key: &str) -> Result<Option<String>, String>;
+}
+
+/// 通过 shell 命令获取密钥：命令以 `sh -c` 执行，键名通过 `EMS_SECRET_KEY` 传入，
+/// 标准输出（去除首尾空白）即密钥，输出为空表示未托管。
+///
+/// 例：`EMS_SECRETS_COMMAND='vault kv get -field="$EMS_SECRET_KEY" secret/ems'`
+#[derive(Debug, Clone)]
+pub struct CommandSecretProvider {
+    command: String,
+}
+
+impl CommandSecretProvider {
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+        }
+    }
+
+    /// 读取 `EMS_SECRETS_COMMAND`，未设置时返回 `None`。
+    pub fn from_env() -> Option<Self> {
+        read_optional(SECRETS_COMMAND_ENV).map(Self::new)
+    }
+}
+
+impl SecretProvider for CommandSecretProvider {
+    fn get_secret(&self, key: &str) -> Result<Option<String>, String> {
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(&self.command)
+            .env("EMS_SECRET_KEY", key)
+            .output()
+            .map_err(|err| err.to_string())?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("{}: {}", output.status, stderr.trim()));
+        }
+        let value = String::from_utf8(output.stdout).map_err(|err| err.to_string())?;
+        let value = value.trim();
+        Ok((!value.is_empty()).then(|| value.to_string()))
+    }
+}
+
+/// 解析密钥键的 `*_FILE` / 外部来源，返回 键 → (报错标签, 取值, 来源)；环境变量已直接设置的键跳过。
+pub(crate) fn resolve_secrets(
+    provider: Option<&dyn SecretProvider>,
+) -> Result<HashMap<&'static str, (String, String, ConfigOrigin)>, ConfigError> {
+    let mut secrets = HashMap::new();
+    for key in SECRET_KEYS {
+        if read_optional(key).is_some() {
+            continue;
+        }
+        let file_key = format!("{key}_FILE");
+        if let Some(path) = read_optional(&file_key) {
+            let content = std::fs::read_to_string(&path)
+                .map_err(|err| ConfigError::Secret(file_key.clone(), format!("{path}: {err}")))?;
+            let value = content.trim().to_string();
+            if !value.is_empty() {
+                secrets.insert(*key, (file_key, value, ConfigOrigin::SecretFile));
+            }
+            continue;
+        }
+        if let Some(provider) = provider {
+            let value = provider
+                .get_secret(key)
+                .map_err(|err| ConfigError::Secret(key.to_string(), err))?;
+            if let Some(value) = value {
+                secrets.insert(
+                    *key,
+                    (
+                        format!("{key} (secret provider)"),
+                        value,
+                        ConfigOrigin::SecretProvider,
+                    ),
+                );
+            }
+        }
+    }
+    Ok(secrets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_provider_passes_key_and_trims_output() {
+        let provider = CommandSecretProvider::new("printf '%s-value\\n' \"$EMS_SECRET_KEY\"");
+        assert_eq!(
+            provider.get_secret("EMS_JWT_SECRET").expect("secret"),
+            Some("EMS_JWT_SECRET-value".to_string())
+        );
+        assert_eq!(
+            CommandSecretProvider::new("true")
+                .get_secret("EMS_JWT_SECRET")
+                .expect("empty"),
+            None
+        );
+        assert!(
+            CommandSecretProvider::new("echo denied >&2; exit 3")
+                .get_secret("EMS_JWT_SECRET")
+                .expect_err("failure")
+                .contains("denied")
+        );
+    }
+}
@@ -0,0 +1,733 @@
+//! 网关固件升级（OTA 批次 + MQTT 下发 + 进度回执）。
+//!
+//! 流程：
+//! 1. `FirmwareService::start_campaign` 写入升级批次及每个目标网关的升级进度（状态 `pending`）
+//! 2. 通过 `FirmwarePublisher` 向每个网关发布升级命令到 `{firmware_prefix}/{tenant}/{project}/{gateway_id}`
+//! 3. 发布成功流转为 `published`，失败流转为 `failed`
+//! 4. 网关下载 / 安装过程中向 `{firmware_receipt_prefix}/{tenant}/{project}/{gateway_id}` 回执进度，
+//!    `spawn_firmware_receipt_listener` 将状态流转为 `downloading` / `installing` → `succeeded` / `failed`
+//! 5. 每次进度变化后按全部网关状态重新计算批次状态（`in_progress` / `completed` / `failed`）
+
+use crate::gateway_config::extract_config_receipt_scope;
+use crate::{ControlError, MqttDispatcher, MqttReceiptListenerConfig, now_epoch_ms, qos_from_u8};
+use async_trait::async_trait;
+use domain::TenantContext;
+use ems_storage::{
+    AuditLogRecord, AuditLogStore, FirmwareCampaignRecord, FirmwarePackageRecord,
+    FirmwareRolloutRecord, FirmwareRolloutUpdate, FirmwareStore, StorageError,
+};
+use rumqttc::{AsyncClient, Event, MqttOptions, Packet, QoS};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// 固件升级批次请求。
+#[derive(Debug, Clone)]
+pub struct FirmwareCampaignRequest {
+    pub project_id: String,
+    pub package: FirmwarePackageRecord,
+    pub name: String,
+    /// 目标网关（调用方负责去重并确认网关存在）
+    pub gateway_ids: Vec<String>,
+    pub created_at_ms: i64,
+}
+
+/// 单个网关的固件升级命令。
+#[derive(Debug, Clone)]
+pub struct FirmwareDispatch {
+    pub tenant_id: String,
+    pub project_id: String,
+    pub gateway_id: String,
+    pub campaign_id: String,
+    pub package_id: String,
+    pub name: String,
+    pub version: String,
+    pub checksum_sha256: String,
+    pub size_bytes: i64,
+    pub storage_url: String,
+    pub issued_at_ms: i64,
+}
+
+/// 固件升级命令发布器抽象。
+#[async_trait]
+pub trait FirmwarePublisher: Send + Sync {
+    async fn publish(&self, dispatch: &FirmwareDispatch) -> Result<(), ControlError>;
+}
+
+/// 空发布器（用于占位）。
+#[derive(Debug, Default)]
+pub struct NoopFirmwarePublisher;
+
+#[async_trait]
+impl FirmwarePublisher for NoopFirmwarePublisher {
+    async fn publish(&self, _dispatch: &FirmwareDispatch) -> Result<(), ControlError> {
+        Ok(())
+    }
+}
+
+/// MQTT 固件升级命令发布器（复用命令下发的 MQTT 连接）。
+#[derive(Clone)]
+pub struct MqttFirmwarePublisher {
+    client: AsyncClient,
+    firmware_topic_prefix: String,
+    qos: QoS,
+}
+
+impl MqttDispatcher {
+    /// 基于当前连接创建固件升级命令发布器。
+    pub fn firmware_publisher(
+        &self,
+        firmware_topic_prefix: impl Into<String>,
+    ) -> MqttFirmwarePublisher {
+        MqttFirmwarePublisher {
+            client: self.client.clone(),
+            firmware_topic_prefix: firmware_topic_prefix.into(),
+            qos: self.qos,
+        }
+    }
+}
+
+#[async_trait]
+impl FirmwarePublisher for MqttFirmwarePublisher {
+    async fn publish(&self, dispatch: &FirmwareDispatch) -> Result<(), ControlError> {
+        let topic = format!(
+            "{}/{}/{}/{}",
+            self.firmware_topic_prefix.trim_end_matches('/'),
+            dispatch.tenant_id,
+            dispatch.project_id,
+            dispatch.gateway_id
+        );
+        let payload = mqtt_firmware_payload(dispatch)?;
+        info!(
+            target: "ems.control",
+            tenant_id = %dispatch.tenant_id,
+            project_id = %dispatch.project_id,
+            gateway_id = %dispatch.gateway_id,
+            campaign_id = %dispatch.campaign_id,
+            version = %dispatch.version,
+            topic = %topic,
+            "firmware_publish"
+        );
+        // 与配置下发一致使用 retain：离线网关上线后仍能收到最近一次升级命令，
+        // 网关按版本号判断是否已升级
+        self.client
+            .publish(topic, self.qos, true, payload)
+            .await
+            .map_err(|err| ControlError::Dispatch(err.to_string()))?;
+        Ok(())
+    }
+}
+
+/// 网关固件升级服务（批次记录 + 发布 + 审计）。
+#[derive(Clone)]
+pub struct FirmwareService {
+    firmware_store: Arc<dyn FirmwareStore>,
+    audit_store: Arc<dyn AuditLogStore>,
+    publisher: Arc<dyn FirmwarePublisher>,
+}
+
+impl FirmwareService {
+    pub fn new(
+        firmware_store: Arc<dyn FirmwareStore>,
+        audit_store: Arc<dyn AuditLogStore>,
+        publisher: Arc<dyn FirmwarePublisher>,
+    ) -> Self {
+        Self {
+            firmware_store,
+            audit_store,
+            publisher,
+        }
+    }
+
+    /// 创建升级批次并向每个目标网关发布升级命令。
+    ///
+    /// 单个网关发布失败只将该网关置为 `failed`，不影响其他网关。
+    pub async fn start_campaign(
+        &self,
+        ctx: &TenantContext,
+        request: FirmwareCampaignRequest,
+    ) -> Result<FirmwareCampaignRecord, ControlError> {
+        let campaign_id = uuid::Uuid::new_v4().to_string();
+        let campaign = FirmwareCampaignRecord {
+            tenant_id: ctx.tenant_id.clone(),
+            project_id: request.project_id.clone(),
+            campaign_id: campaign_id.clone(),
+            package_id: request.package.package_id.clone(),
+            name: request.name,
+            status: "in_progress".to_string(),
+            created_by: ctx.user_id.clone(),
+            created_at_ms: request.created_at_ms,
+            updated_at_ms: request.created_at_ms,
+        };
+        let rollouts = request
+            .gateway_ids
+            .iter()
+            .map(|gateway_id| FirmwareRolloutRecord {
+                tenant_id: ctx.tenant_id.clone(),
+                project_id: request.project_id.clone(),
+                campaign_id: campaign_id.clone(),
+                gateway_id: gateway_id.clone(),
+                status: "pending".to_string(),
+                progress: 0,
+                message: None,
+                updated_at_ms: request.created_at_ms,
+            })
+            .collect();
+        let campaign = self
+            .firmware_store
+            .create_firmware_campaign(ctx, campaign, rollouts)
+            .await?;
+
+        let package = &request.package;
+        let mut published = 0usize;
+        for gateway_id in &request.gateway_ids {
+            let dispatch = FirmwareDispatch {
+                tenant_id: campaign.tenant_id.clone(),
+                project_id: campaign.project_id.clone(),
+                gateway_id: gateway_id.clone(),
+                campaign_id: campaign.campaign_id.clone(),
+                package_id: package.package_id.clone(),
+                name: package.name.clone(),
+                version: package.version.clone(),
+                checksum_sha256: package.checksum_sha256.clone(),
+                size_bytes: package.size_bytes,
+                storage_url: package.storage_url.clone(),
+                issued_at_ms: campaign.created_at_ms,
+            };
+            let (status, message) = match self.publisher.publish(&dispatch).await {
+                Ok(()) => {
+                    published += 1;
+                    ("published", None)
+                }
+                Err(err) => ("failed", Some(err.to_string())),
+            };
+            self.firmware_store
+                .update_firmware_rollout(
+                    ctx,
+                    &campaign.project_id,
+                    &campaign.campaign_id,
+                    gateway_id,
+                    FirmwareRolloutUpdate {
+                        status: status.to_string(),
+                        progress: None,
+                        message,
+                        updated_at_ms: now_epoch_ms(),
+                    },
+                )
+                .await?;
+        }
+        let total = request.gateway_ids.len();
+        info!(
+            target: "ems.control",
+            tenant_id = %campaign.tenant_id,
+            project_id = %campaign.project_id,
+            campaign_id = %campaign.campaign_id,
+            version = %package.version,
+            published = published,
+            total = total,
+            "firmware_campaign_started"
+        );
+        let updated = refresh_campaign_status(
+            self.firmware_store.as_ref(),
+            ctx,
+            &campaign.project_id,
+            &campaign.campaign_id,
+        )
+        .await?;
+        let campaign = updated.unwrap_or(campaign);
+
+        let audit = AuditLogRecord {
+            audit_id: uuid::Uuid::new_v4().to_string(),
+            tenant_id: ctx.tenant_id.clone(),
+            project_id: Some(campaign.project_id.clone()),
+            actor: ctx.user_id.clone(),
+            action: "ASSET.FIRMWARE.CAMPAIGN.START".to_string(),
+            resource: format!("firmware-campaign:{}", campaign.campaign_id),
+            result: if published > 0 { "success" } else { "failed" }.to_string(),
+            detail: Some(format!(
+                "{} {}: {published}/{total} gateways published",
+                package.name, package.version
+            )),
+            ts_ms: campaign.created_at_ms,
+        };
+        let _ = self.audit_store.create_audit_log(ctx, audit).await;
+        Ok(campaign)
+    }
+
+    /// 登记固件包并记录审计。
+    pub async fn create_package(
+        &self,
+        ctx: &TenantContext,
+        record: FirmwarePackageRecord,
+    ) -> Result<FirmwarePackageRecord, StorageError> {
+        let record = self
+            .firmware_store
+            .create_firmware_package(ctx, record)
+            .await?;
+        let audit = AuditLogRecord {
+            audit_id: uuid::Uuid::new_v4().to_string(),
+            tenant_id: ctx.tenant_id.clone(),
+            project_id: Some(record.project_id.clone()),
+            actor: ctx.user_id.clone(),
+            action: "ASSET.FIRMWARE.PACKAGE.CREATE".to_string(),
+            resource: format!("firmware-package:{}", record.package_id),
+            result: "success".to_string(),
+            detail: Some(format!("{} {}", record.name, record.version)),
+            ts_ms: record.created_at_ms,
+        };
+        let _ = self.audit_store.create_audit_log(ctx, audit).await;
+        Ok(record)
+    }
+
+    pub async fn list_packages(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<Vec<FirmwarePackageRecord>, StorageError> {
+        self.firmware_store
+            .list_firmware_packages(ctx, project_id)
+            .await
+    }
+
+    pub async fn find_package(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        package_id: &str,
+    ) -> Result<Option<FirmwarePackageRecord>, StorageError> {
+        self.firmware_store
+            .find_firmware_package(ctx, project_id, package_id)
+            .await
+    }
+
+    pub async fn list_campaigns(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        limit: i64,
+    ) -> Result<Vec<FirmwareCampaignRecord>, StorageError> {
+        self.firmware_store
+            .list_firmware_campaigns(ctx, project_id, limit)
+            .await
+    }
+
+    pub async fn find_campaign(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        campaign_id: &str,
+    ) -> Result<Option<FirmwareCampaignRecord>, StorageError> {
+        self.firmware_store
+            .find_firmware_campaign(ctx, project_id, campaign_id)
+            .await
+    }
+
+    pub async fn list_rollouts(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        campaign_id: &str,
+    ) -> Result<Vec<FirmwareRolloutRecord>, StorageError> {
+        self.firmware_store
+            .list_firmware_rollouts(ctx, project_id, campaign_id)
+            .await
+    }
+}
+
+/// 按全部网关进度计算批次状态：存在未结束的网关为 `in_progress`，
+/// 全部成功为 `completed`，全部结束且存在失败为 `failed`。
+pub fn campaign_status(rollouts: &[FirmwareRolloutRecord]) -> &'static str {
+    if rollouts
+        .iter()
+        .any(|rollout| rollout.status != "succeeded" && rollout.status != "failed")
+    {
+        "in_progress"
+    } else if rollouts.iter().all(|rollout| rollout.status == "succeeded") {
+        "completed"
+    } else {
+        "failed"
+    }
+}
+
+/// 校验并规范化 SHA-256 校验和（64 位十六进制，统一为小写）。
+pub fn normalize_sha256(value: &str) -> Option<String> {
+    let value = value.trim();
+    if value.len() == 64 && value.chars().all(|ch| ch.is_ascii_hexdigit()) {
+        Some(value.to_ascii_lowercase())
+    } else {
+        None
+    }
+}
+
+async fn refresh_campaign_status(
+    store: &dyn FirmwareStore,
+    ctx: &TenantContext,
+    project_id: &str,
+    campaign_id: &str,
+) -> Result<Option<FirmwareCampaignRecord>, StorageError> {
+    let rollouts = store
+        .list_firmware_rollouts(ctx, project_id, campaign_id)
+        .await?;
+    store
+        .update_firmware_campaign_status(
+            ctx,
+            project_id,
+            campaign_id,
+            campaign_status(&rollouts),
+            now_epoch_ms(),
+        )
+        .await
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FirmwareReceiptPayload {
+    campaign_id: String,
+    #[serde(alias = "result", alias = "state")]
+    status: String,
+    #[serde(default)]
+    progress: Option<i32>,
+    #[serde(alias = "msg", alias = "detail")]
+    message: Option<String>,
+}
+
+/// 启动固件升级回执监听（复用回执监听配置，`receipt_topic_prefix` 为固件回执前缀）。
+pub fn spawn_firmware_receipt_listener(
+    config: MqttReceiptListenerConfig,
+    firmware_store: Arc<dyn FirmwareStore>,
+    audit_store: Arc<dyn AuditLogStore>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let client_id = format!("ems-control-firmware-receipt-{}", uuid::Uuid::new_v4());
+        let mut options = MqttOptions::new(client_id, config.host, config.port);
+        options.set_keep_alive(Duration::from_secs(30));
+        if let (Some(username), Some(password)) = (config.username, config.password) {
+            options.set_credentials(username, password);
+        }
+        let (client, mut eventloop) = AsyncClient::new(options, 10);
+        let topic = format!("{}/#", config.receipt_topic_prefix.trim_end_matches('/'));
+        if let Err(err) = client.subscribe(topic, qos_from_u8(config.qos)).await {
+            warn!(target: "ems.control", "mqtt firmware receipt subscribe error: {}", err);
+            return;
+        }
+
+        loop {
+            match eventloop.poll().await {
+                Ok(Event::Incoming(Packet::Publish(publish))) => {
+                    let Some((tenant_id, project_id, gateway_id)) =
+                        extract_config_receipt_scope(&config.receipt_topic_prefix, &publish.topic)
+                    else {
+                        warn!(target: "ems.control", "firmware receipt topic skipped: {}", publish.topic);
+                        continue;
+                    };
+                    let payload: FirmwareReceiptPayload = match serde_json::from_slice(
+                        &publish.payload,
+                    ) {
+                        Ok(payload) => payload,
+                        Err(err) => {
+                            warn!(target: "ems.control", "firmware receipt payload invalid: {}", err);
+                            continue;
+                        }
+                    };
+                    let ctx = TenantContext::new(
+                        tenant_id.clone(),
+                        "system".to_string(),
+                        Vec::new(),
+                        Vec::new(),
+                        Some(project_id.clone()),
+                    );
+                    if let Err(err) = apply_firmware_receipt(
+                        firmware_store.as_ref(),
+                        audit_store.as_ref(),
+                        &ctx,
+                        &project_id,
+                        &gateway_id,
+                        payload,
+                    )
+                    .await
+                    {
+                        warn!(target: "ems.control", "firmware receipt write failed: {}", err);
+                    }
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    warn!(target: "ems.control", "mqtt firmware receipt eventloop error: {}", err);
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+            }
+        }
+    })
+}
+
+/// 写入一条网关进度回执并刷新批次状态；网关结束升级（成功 / 失败）时记录审计。
+async fn apply_firmware_receipt(
+    firmware_store: &dyn FirmwareStore,
+    audit_store: &dyn AuditLogStore,
+    ctx: &TenantContext,
+    project_id: &str,
+    gateway_id: &str,
+    payload: FirmwareReceiptPayload,
+) -> Result<(), StorageError> {
+    let status = normalize_firmware_status(&payload.status);
+    let progress = match status {
+        "succeeded" => Some(100),
+        _ => payload.progress.map(|progress| progress.clamp(0, 100)),
+    };
+    let ts_ms = now_epoch_ms();
+    let updated = firmware_store
+        .update_firmware_rollout(
+            ctx,
+            project_id,
+            &payload.campaign_id,
+            gateway_id,
+            FirmwareRolloutUpdate {
+                status: status.to_string(),
+                progress,
+                message: payload.message.clone(),
+                updated_at_ms: ts_ms,
+            },
+        )
+        .await?;
+    if updated.is_none() {
+        warn!(
+            target: "ems.control",
+            tenant_id = %ctx.tenant_id,
+            project_id = %project_id,
+            gateway_id = %gateway_id,
+            campaign_id = %payload.campaign_id,
+            "firmware_receipt_unknown_campaign"
+        );
+        return Ok(());
+    }
+    refresh_campaign_status(firmware_store, ctx, project_id, &payload.campaign_id).await?;
+    if status == "succeeded" || status == "failed" {
+        let audit = AuditLogRecord {
+            audit_id: uuid::Uuid::new_v4().to_string(),
+            tenant_id: ctx.tenant_id.clone(),
+            project_id: Some(project_id.to_string()),
+            actor: "system".to_string(),
+            action: "ASSET.FIRMWARE.RECEIPT".to_string(),
+            resource: format!(
+                "gateway:{}:firmware-campaign:{}",
+                gateway_id, payload.campaign_id
+            ),
+            result: status.to_string(),
+            detail: payload.message.clone(),
+            ts_ms,
+        };
+        let _ = audit_store.create_audit_log(ctx, audit).await;
+    }
+    info!(
+        target: "ems.control",
+        tenant_id = %ctx.tenant_id,
+        project_id = %project_id,
+        gateway_id = %gateway_id,
+        campaign_id = %payload.campaign_id,
+        status = %status,
+        progress = ?progress,
+        "firmware_receipt_processed"
+    );
+    Ok(())
+}
+
+fn normalize_firmware_status(value: &str) -> &'static str {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "downloading" | "download" => "downloading",
+        "installing" | "install" | "upgrading" => "installing",
+        "succeeded" | "success" | "ok" | "completed" | "applied" => "succeeded",
+        _ => "failed",
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FirmwareMqttEnvelope<'a> {
+    gateway_id: &'a str,
+    campaign_id: &'a str,
+    package_id: &'a str,
+    name: &'a str,
+    version: &'a str,
+    url: &'a str,
+    checksum_sha256: &'a str,
+    size_bytes: i64,
+    issued_at_ms: i64,
+}
+
+fn mqtt_firmware_payload(dispatch: &FirmwareDispatch) -> Result<Vec<u8>, ControlError> {
+    let envelope = FirmwareMqttEnvelope {
+        gateway_id: &dispatch.gateway_id,
+        campaign_id: &dispatch.campaign_id,
+        package_id: &dispatch.package_id,
+        name: &dispatch.name,
+        version: &dispatch.version,
+        url: &dispatch.storage_url,
+        checksum_sha256: &dispatch.checksum_sha256,
+        size_bytes: dispatch.size_bytes,
+        issued_at_ms: dispatch.issued_at_ms,
+    };
+    serde_json::to_vec(&envelope).map_err(|err| ControlError::Payload(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rollout(status: &str) -> FirmwareRolloutRecord {
+        FirmwareRolloutRecord {
+            tenant_id: "tenant-1".to_string(),
+            project_id: "project-1".to_string(),
+            campaign_id: "campaign-1".to_string(),
+            gateway_id: "gw-1".to_string(),
+            status: status.to_string(),
+            progress: 0,
+            message: None,
+            updated_at_ms: 0,
+        }
+    }
+
+    #[test]
+    fn campaign_status_follows_rollouts() {
+        assert_eq!(
+            campaign_status(&[rollout("succeeded"), rollout("installing")]),
+            "in_progress"
+        );
+        assert_eq!(
+            campaign_status(&[rollout("succeeded"), rollout("succeeded")]),
+            "completed"
+        );
+        assert_eq!(
+            campaign_status(&[rollout("succeeded"), rollout("failed")]),
+            "failed"
+        );
+    }
+
+    #[test]
+    fn firmware_receipt_payload_parses() {
+        let payload: FirmwareReceiptPayload =
+            serde_json::from_slice(br#"{"campaignId":"c-1","state":"Downloading","progress":40}"#)
+                .expect("payload");
+        assert_eq!(payload.campaign_id, "c-1");
+        assert_eq!(payload.progress, Some(40));
+        assert_eq!(normalize_firmware_status(&payload.status), "downloading");
+        assert_eq!(normalize_firmware_status("OK"), "succeeded");
+        assert_eq!(normalize_firmware_status("checksum mismatch"), "failed");
+    }
+
+    #[tokio::test]
+    async fn campaign_tracks_receipts_per_gateway() {
+        let store: Arc<dyn FirmwareStore> = Arc::new(ems_storage::InMemoryFirmwareStore::new());
+        let audit_store = Arc::new(ems_storage::InMemoryAuditLogStore::new());
+        let service = FirmwareService::new(
+            store.clone(),
+            audit_store.clone(),
+            Arc::new(NoopFirmwarePublisher),
+        );
+        let ctx = TenantContext::new(
+            "tenant-1".to_string(),
+            "user-1".to_string(),
+            Vec::new(),
+            Vec::new(),
+            Some("project-1".to_string()),
+        );
+        let package = service
+            .create_package(
+                &ctx,
+                FirmwarePackageRecord {
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    package_id: "pkg-1".to_string(),
+                    name: "gw-fw".to_string(),
+                    version: "2.0.0".to_string(),
+                    checksum_sha256: "ab".repeat(32),
+                    size_bytes: 1024,
+                    storage_url: "s3://firmware/gw-fw-2.0.0.bin".to_string(),
+                    metadata: None,
+                    created_by: "user-1".to_string(),
+                    created_at_ms: 1,
+                },
+            )
+            .await
+            .expect("package");
+        let campaign = service
+            .start_campaign(
+                &ctx,
+                FirmwareCampaignRequest {
+                    project_id: "project-1".to_string(),
+                    package,
+                    name: "rollout".to_string(),
+                    gateway_ids: vec!["gw-1".to_string(), "gw-2".to_string()],
+                    created_at_ms: 2,
+                },
+            )
+            .await
+            .expect("campaign");
+        assert_eq!(campaign.status, "in_progress");
+        let rollouts = service
+            .list_rollouts(&ctx, "project-1", &campaign.campaign_id)
+            .await
+            .expect("rollouts");
+        assert!(rollouts.iter().all(|rollout| rollout.status == "published"));
+
+        let receipt = |status: &str, progress: Option<i32>| FirmwareReceiptPayload {
+            campaign_id: campaign.campaign_id.clone(),
+            status: status.to_string(),
+            progress,
+            message: None,
+        };
+        for (gateway_id, payload) in [
+            ("gw-1", receipt("downloading", Some(40))),
+            ("gw-1", receipt("success", None)),
+            ("gw-2", receipt("installing", Some(80))),
+        ] {
+            apply_firmware_receipt(
+                store.as_ref(),
+                audit_store.as_ref(),
+                &ctx,
+                "project-1",
+                gateway_id,
+                payload,
+            )
+            .await
+            .expect("receipt");
+        }
+        let rollouts = service
+            .list_rollouts(&ctx, "project-1", &campaign.campaign_id)
+            .await
+            .expect("rollouts");
+        assert_eq!(rollouts[0].status, "succeeded");
+        assert_eq!(rollouts[0].progress, 100);
+        assert_eq!(rollouts[1].status, "installing");
+        assert_eq!(rollouts[1].progress, 80);
+        let current = service
+            .find_campaign(&ctx, "project-1", &campaign.campaign_id)
+            .await
+            .expect("campaign")
+            .expect("exists");
+        assert_eq!(current.status, "in_progress");
+
+        apply_firmware_receipt(
+            store.as_ref(),
+            audit_store.as_ref(),
+            &ctx,
+            "project-1",
+            "gw-2",
+            receipt("checksum mismatch", None),
+        )
+        .await
+        .expect("receipt");
+        let current = service
+            .find_campaign(&ctx, "project-1", &campaign.campaign_id)
+            .await
+            .expect("campaign")
+            .expect("exists");
+        assert_eq!(current.status, "failed");
+    }
+
+    #[test]
+    fn sha256_is_validated_and_lowercased() {
+        let upper = "AB".repeat(32);
+        assert_eq!(normalize_sha256(&upper), Some("ab".repeat(32)));
+        assert!(normalize_sha256("abc").is_none());
+        assert!(normalize_sha256(&"zz".repeat(32)).is_none());
+    }
+}
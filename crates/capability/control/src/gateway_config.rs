@@ -0,0 +1,432 @@
+//! 网关配置下发（版本化配置文档 + MQTT 发布 + 回执）。
+//!
+//! 流程：
+//! 1. `GatewayConfigService::push_config` 写入配置记录（存储分配递增版本号，状态 `pending`）
+//! 2. 通过 `GatewayConfigPublisher` 发布到 `{config_prefix}/{tenant}/{project}/{gateway_id}`
+//! 3. 发布成功流转为 `published`，失败流转为 `failed`
+//! 4. 网关应用后向 `{config_receipt_prefix}/{tenant}/{project}/{gateway_id}` 回执，
+//!    `spawn_config_receipt_listener` 将状态流转为 `applied` / `failed`
+
+use crate::{ControlError, MqttDispatcher, MqttReceiptListenerConfig, now_epoch_ms, qos_from_u8};
+use async_trait::async_trait;
+use domain::TenantContext;
+use ems_storage::{AuditLogRecord, AuditLogStore, GatewayConfigRecord, GatewayConfigStore};
+use rumqttc::{AsyncClient, Event, MqttOptions, Packet, QoS};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// 网关配置下发请求。
+#[derive(Debug, Clone)]
+pub struct GatewayConfigRequest {
+    pub project_id: String,
+    pub gateway_id: String,
+    pub document: serde_json::Value,
+    pub pushed_at_ms: i64,
+}
+
+/// 网关配置发布数据（版本已由存储分配）。
+#[derive(Debug, Clone)]
+pub struct GatewayConfigDispatch {
+    pub tenant_id: String,
+    pub project_id: String,
+    pub gateway_id: String,
+    pub version: i64,
+    pub document: String,
+    pub pushed_at_ms: i64,
+}
+
+/// 网关配置发布器抽象。
+#[async_trait]
+pub trait GatewayConfigPublisher: Send + Sync {
+    async fn publish(&self, config: &GatewayConfigDispatch) -> Result<(), ControlError>;
+}
+
+/// 空发布器（用于占位）。
+#[derive(Debug, Default)]
+pub struct NoopConfigPublisher;
+
+#[async_trait]
+impl GatewayConfigPublisher for NoopConfigPublisher {
+    async fn publish(&self, _config: &GatewayConfigDispatch) -> Result<(), ControlError> {
+        Ok(())
+    }
+}
+
+/// MQTT 配置发布器（复用命令下发的 MQTT 连接）。
+#[derive(Clone)]
+pub struct MqttConfigPublisher {
+    client: AsyncClient,
+    config_topic_prefix: String,
+    qos: QoS,
+}
+
+impl MqttDispatcher {
+    /// 基于当前连接创建网关配置发布器。
+    pub fn config_publisher(&self, config_topic_prefix: impl Into<String>) -> MqttConfigPublisher {
+        MqttConfigPublisher {
+            client: self.client.clone(),
+            config_topic_prefix: config_topic_prefix.into(),
+            qos: self.qos,
+        }
+    }
+}
+
+#[async_trait]
+impl GatewayConfigPublisher for MqttConfigPublisher {
+    async fn publish(&self, config: &GatewayConfigDispatch) -> Result<(), ControlError> {
+        let topic = format!(
+            "{}/{}/{}/{}",
+            self.config_topic_prefix.trim_end_matches('/'),
+            config.tenant_id,
+            config.project_id,
+            config.gateway_id
+        );
+        let payload = mqtt_config_payload(config)?;
+        info!(
+            target: "ems.control",
+            tenant_id = %config.tenant_id,
+            project_id = %config.project_id,
+            gateway_id = %config.gateway_id,
+            version = config.version,
+            topic = %topic,
+            payload_size = payload.len(),
+            "gateway_config_publish"
+        );
+        // 配置为最新状态语义，使用 retain 让离线网关上线后也能拿到最新版本
+        self.client
+            .publish(topic, self.qos, true, payload)
+            .await
+            .map_err(|err| ControlError::Dispatch(err.to_string()))?;
+        Ok(())
+    }
+}
+
+/// 网关配置服务（记录版本 + 发布 + 审计）。
+#[derive(Clone)]
+pub struct GatewayConfigService {
+    config_store: Arc<dyn GatewayConfigStore>,
+    audit_store: Arc<dyn AuditLogStore>,
+    publisher: Arc<dyn GatewayConfigPublisher>,
+}
+
+impl GatewayConfigService {
+    pub fn new(
+        config_store: Arc<dyn GatewayConfigStore>,
+        audit_store: Arc<dyn AuditLogStore>,
+        publisher: Arc<dyn GatewayConfigPublisher>,
+    ) -> Self {
+        Self {
+            config_store,
+            audit_store,
+            publisher,
+        }
+    }
+
+    pub async fn push_config(
+        &self,
+        ctx: &TenantContext,
+        request: GatewayConfigRequest,
+    ) -> Result<GatewayConfigRecord, ControlError> {
+        let document = serde_json::to_string(&request.document)
+            .map_err(|err| ControlError::Payload(err.to_string()))?;
+        let record = GatewayConfigRecord {
+            tenant_id: ctx.tenant_id.clone(),
+            project_id: request.project_id,
+            gateway_id: request.gateway_id,
+            version: 0,
+            document: document.clone(),
+            status: "pending".to_string(),
+            message: None,
+            pushed_by: ctx.user_id.clone(),
+            pushed_at_ms: request.pushed_at_ms,
+            updated_at_ms: request.pushed_at_ms,
+        };
+        let record = self.config_store.create_gateway_config(ctx, record).await?;
+
+        let dispatch = GatewayConfigDispatch {
+            tenant_id: record.tenant_id.clone(),
+            project_id: record.project_id.clone(),
+            gateway_id: record.gateway_id.clone(),
+            version: record.version,
+            document,
+            pushed_at_ms: record.pushed_at_ms,
+        };
+        let (status, result, detail) = match self.publisher.publish(&dispatch).await {
+            Ok(()) => ("published", "success", None),
+            Err(err) => ("failed", "failed", Some(err.to_string())),
+        };
+        info!(
+            target: "ems.control",
+            tenant_id = %record.tenant_id,
+            project_id = %record.project_id,
+            gateway_id = %record.gateway_id,
+            version = record.version,
+            status = %status,
+            detail = ?detail,
+            "gateway_config_pushed"
+        );
+        let updated = self
+            .config_store
+            .update_gateway_config_status(
+                ctx,
+                &record.project_id,
+                &record.gateway_id,
+                record.version,
+                status,
+                detail.clone(),
+                now_epoch_ms(),
+            )
+            .await?;
+        let record = updated.unwrap_or_else(|| GatewayConfigRecord {
+            status: status.to_string(),
+            message: detail.clone(),
+            ..record
+        });
+
+        let audit = AuditLogRecord {
+            audit_id: uuid::Uuid::new_v4().to_string(),
+            tenant_id: ctx.tenant_id.clone(),
+            project_id: Some(record.project_id.clone()),
+            actor: ctx.user_id.clone(),
+            action: "ASSET.GATEWAY.CONFIG.PUSH".to_string(),
+            resource: format!("gateway:{}:config:{}", record.gateway_id, record.version),
+            result: result.to_string(),
+            detail,
+            ts_ms: record.pushed_at_ms,
+        };
+        let _ = self.audit_store.create_audit_log(ctx, audit).await;
+        Ok(record)
+    }
+
+    pub async fn list_config_pushes(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        gateway_id: &str,
+        limit: i64,
+    ) -> Result<Vec<GatewayConfigRecord>, ControlError> {
+        self.config_store
+            .list_gateway_configs(ctx, project_id, gateway_id, limit)
+            .await
+            .map_err(ControlError::from)
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConfigReceiptPayload {
+    version: i64,
+    #[serde(alias = "result", alias = "state")]
+    status: String,
+    #[serde(alias = "msg", alias = "detail")]
+    message: Option<String>,
+}
+
+/// 启动网关配置回执监听（复用回执监听配置，`receipt_topic_prefix` 为配置回执前缀）。
+pub fn spawn_config_receipt_listener(
+    config: MqttReceiptListenerConfig,
+    config_store: Arc<dyn GatewayConfigStore>,
+    audit_store: Arc<dyn AuditLogStore>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let client_id = format!("ems-control-config-receipt-{}", uuid::Uuid::new_v4());
+        let mut options = MqttOptions::new(client_id, config.host, config.port);
+        options.set_keep_alive(Duration::from_secs(30));
+        if let (Some(username), Some(password)) = (config.username, config.password) {
+            options.set_credentials(username, password);
+        }
+        let (client, mut eventloop) = AsyncClient::new(options, 10);
+        let topic = format!("{}/#", config.receipt_topic_prefix.trim_end_matches('/'));
+        if let Err(err) = client.subscribe(topic, qos_from_u8(config.qos)).await {
+            warn!(target: "ems.control", "mqtt config receipt subscribe error: {}", err);
+            return;
+        }
+
+        loop {
+            match eventloop.poll().await {
+                Ok(Event::Incoming(Packet::Publish(publish))) => {
+                    let Some((tenant_id, project_id, gateway_id)) =
+                        extract_config_receipt_scope(&config.receipt_topic_prefix, &publish.topic)
+                    else {
+                        warn!(target: "ems.control", "config receipt topic skipped: {}", publish.topic);
+                        continue;
+                    };
+                    let payload: ConfigReceiptPayload = match serde_json::from_slice(
+                        &publish.payload,
+                    ) {
+                        Ok(payload) => payload,
+                        Err(err) => {
+                            warn!(target: "ems.control", "config receipt payload invalid: {}", err);
+                            continue;
+                        }
+                    };
+                    let status = normalize_config_status(&payload.status);
+                    let ctx = TenantContext::new(
+                        tenant_id.clone(),
+                        "system".to_string(),
+                        Vec::new(),
+                        Vec::new(),
+                        Some(project_id.clone()),
+                    );
+                    let ts_ms = now_epoch_ms();
+                    let updated = match config_store
+                        .update_gateway_config_status(
+                            &ctx,
+                            &project_id,
+                            &gateway_id,
+                            payload.version,
+                            status,
+                            payload.message.clone(),
+                            ts_ms,
+                        )
+                        .await
+                    {
+                        Ok(updated) => updated,
+                        Err(err) => {
+                            warn!(target: "ems.control", "config receipt write failed: {}", err);
+                            continue;
+                        }
+                    };
+                    if updated.is_none() {
+                        warn!(
+                            target: "ems.control",
+                            tenant_id = %tenant_id,
+                            project_id = %project_id,
+                            gateway_id = %gateway_id,
+                            version = payload.version,
+                            "config_receipt_unknown_version"
+                        );
+                        continue;
+                    }
+                    let audit = AuditLogRecord {
+                        audit_id: uuid::Uuid::new_v4().to_string(),
+                        tenant_id: tenant_id.clone(),
+                        project_id: Some(project_id.clone()),
+                        actor: "system".to_string(),
+                        action: "ASSET.GATEWAY.CONFIG.RECEIPT".to_string(),
+                        resource: format!("gateway:{}:config:{}", gateway_id, payload.version),
+                        result: status.to_string(),
+                        detail: payload.message.clone(),
+                        ts_ms,
+                    };
+                    let _ = audit_store.create_audit_log(&ctx, audit).await;
+                    info!(
+                        target: "ems.control",
+                        tenant_id = %tenant_id,
+                        project_id = %project_id,
+                        gateway_id = %gateway_id,
+                        version = payload.version,
+                        status = %status,
+                        message = ?payload.message,
+                        "config_receipt_processed"
+                    );
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    warn!(target: "ems.control", "mqtt config receipt eventloop error: {}", err);
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+            }
+        }
+    })
+}
+
+/// 解析配置回执 topic：`{prefix}/{tenant}/{project}/{gateway_id}`（固件升级回执复用同一格式）。
+pub(crate) fn extract_config_receipt_scope(
+    prefix: &str,
+    topic: &str,
+) -> Option<(String, String, String)> {
+    let prefix = prefix.trim_matches('/');
+    let topic = topic.trim_matches('/');
+    let rest = if prefix.is_empty() {
+        topic
+    } else {
+        topic.strip_prefix(prefix)?
+    };
+    let parts: Vec<&str> = rest.split('/').filter(|part| !part.is_empty()).collect();
+    if parts.len() != 3 {
+        return None;
+    }
+    Some((
+        parts[0].to_string(),
+        parts[1].to_string(),
+        parts[2].to_string(),
+    ))
+}
+
+fn normalize_config_status(value: &str) -> &'static str {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "applied" | "success" | "ok" => "applied",
+        _ => "failed",
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ConfigMqttEnvelope<'a> {
+    gateway_id: &'a str,
+    version: i64,
+    pushed_at_ms: i64,
+    config: serde_json::Value,
+}
+
+fn mqtt_config_payload(config: &GatewayConfigDispatch) -> Result<Vec<u8>, ControlError> {
+    let document: serde_json::Value = serde_json::from_str(&config.document)
+        .map_err(|err| ControlError::Payload(err.to_string()))?;
+    let envelope = ConfigMqttEnvelope {
+        gateway_id: &config.gateway_id,
+        version: config.version,
+        pushed_at_ms: config.pushed_at_ms,
+        config: document,
+    };
+    serde_json::to_vec(&envelope).map_err(|err| ControlError::Payload(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_receipt_topic_scope() {
+        let scope = extract_config_receipt_scope(
+            "ems/config-receipts",
+            "ems/config-receipts/tenant-1/project-1/gw-1",
+        )
+        .expect("scope");
+        assert_eq!(scope.0, "tenant-1");
+        assert_eq!(scope.1, "project-1");
+        assert_eq!(scope.2, "gw-1");
+        assert!(
+            extract_config_receipt_scope("ems/config-receipts", "ems/config-receipts/t/p")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn config_receipt_payload_parses() {
+        let payload: ConfigReceiptPayload =
+            serde_json::from_slice(br#"{"version":3,"status":"OK","message":"done"}"#)
+                .expect("payload");
+        assert_eq!(payload.version, 3);
+        assert_eq!(normalize_config_status(&payload.status), "applied");
+        assert_eq!(normalize_config_status("error"), "failed");
+    }
+
+    #[test]
+    fn config_envelope_embeds_document() {
+        let dispatch = GatewayConfigDispatch {
+            tenant_id: "tenant-1".to_string(),
+            project_id: "project-1".to_string(),
+            gateway_id: "gw-1".to_string(),
+            version: 2,
+            document: r#"{"devices":[]}"#.to_string(),
+            pushed_at_ms: 1,
+        };
+        let payload = mqtt_config_payload(&dispatch).expect("payload");
+        let value: serde_json::Value = serde_json::from_slice(&payload).expect("json");
+        assert_eq!(value["version"], 2);
+        assert_eq!(value["gatewayId"], "gw-1");
+        assert!(value["config"]["devices"].is_array());
+    }
+}
@@ -1,18 +1,31 @@
 use async_trait::async_trait;
 use domain::TenantContext;
+use ems_events::{DomainEvent, EventBus, event_types};
 use ems_telemetry::{
     record_command_dispatch_failure, record_command_dispatch_success, record_command_issue_latency_ms,
-    record_command_issued, record_receipt_processed,
+    record_command_issued, record_receipt_processed, record_receipt_rejected,
 };
 use ems_storage::{
     AuditLogRecord, AuditLogStore, CommandReceiptRecord, CommandReceiptStore, CommandRecord,
-    CommandReceiptWriteResult, CommandStore,
+    CommandReceiptWriteResult, CommandStore, FeatureFlagStore, MaintenanceWindowRecord,
+    UsageStore,
 };
 use rumqttc::{AsyncClient, Event, MqttOptions, Packet, QoS};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tracing::{info, warn};
 
+mod firmware;
+mod gateway_config;
+mod maintenance;
+mod payload_format;
+mod shadow;
+pub use firmware::*;
+pub use gateway_config::*;
+pub use maintenance::*;
+pub use payload_format::*;
+pub use shadow::*;
+
 /// 命令下发请求。
 #[derive(Debug, Clone)]
 pub struct CommandRequest {
@@ -33,21 +46,52 @@ pub struct CommandDispatch {
     pub issued_at_ms: i64,
 }
 
+/// 命令下发预览（dry-run）：将要发布的主题与载荷。
+#[derive(Debug, Clone)]
+pub struct CommandPreview {
+    pub command_id: String,
+    /// 发布主题；下发器不经 MQTT（NoopDispatcher）时为 None
+    pub topic: Option<String>,
+    /// 载荷格式名（envelope / raw / template / hex）
+    pub payload_format: String,
+    pub payload: Vec<u8>,
+}
+
 /// 控制链路错误。
 #[derive(Debug, thiserror::Error)]
 pub enum ControlError {
     #[error("storage error: {0}")]
-    Storage(String),
+    Storage(#[from] ems_storage::StorageError),
     #[error("dispatch error: {0}")]
     Dispatch(String),
     #[error("payload error: {0}")]
     Payload(String),
+    /// 目标处于维护窗口，自动化命令被拦截
+    #[error("maintenance: {0}")]
+    Maintenance(String),
+    /// 租户当日命令数超出配额
+    #[error("quota exceeded: {0}")]
+    Quota(String),
+    /// 租户功能开关关闭（`control`），命令被拒绝
+    #[error("feature disabled: {0}")]
+    FeatureDisabled(String),
 }
 
 /// 命令下发器抽象。
 #[async_trait]
 pub trait CommandDispatcher: Send + Sync {
     async fn dispatch(&self, command: &CommandDispatch) -> Result<(), ControlError>;
+
+    /// 渲染将要发布的主题与载荷，不实际发布；默认无主题、载荷为 JSON 信封
+    async fn preview(&self, command: &CommandDispatch) -> Result<CommandPreview, ControlError> {
+        let format = CommandPayloadFormat::default();
+        Ok(CommandPreview {
+            command_id: command.command_id.clone(),
+            topic: None,
+            payload_format: format.name().to_string(),
+            payload: format.encode(command)?,
+        })
+    }
 }
 
 /// 空下发器（用于占位）。
@@ -84,6 +128,7 @@ pub struct MqttDispatcher {
     command_topic_prefix: String,
     include_target_in_topic: bool,
     qos: QoS,
+    payload_formats: Option<Arc<GatewayPayloadFormats>>,
 }
 
 impl MqttDispatcher {
@@ -111,11 +156,32 @@ impl MqttDispatcher {
                 command_topic_prefix: config.command_topic_prefix,
                 include_target_in_topic: config.include_target_in_topic,
                 qos: qos_from_u8(config.qos),
+                payload_formats: None,
             },
             handle,
         ))
     }
 
+    /// 挂载网关载荷格式：按命令 target 所属网关的配置编码载荷（未挂载时统一使用 JSON 信封）
+    pub fn with_payload_formats(mut self, payload_formats: Arc<GatewayPayloadFormats>) -> Self {
+        self.payload_formats = Some(payload_formats);
+        self
+    }
+
+    async fn format_for(
+        &self,
+        command: &CommandDispatch,
+    ) -> Result<CommandPayloadFormat, ControlError> {
+        match &self.payload_formats {
+            Some(formats) => {
+                formats
+                    .format_for_target(&command.tenant_id, &command.project_id, &command.target)
+                    .await
+            }
+            None => Ok(CommandPayloadFormat::default()),
+        }
+    }
+
     fn topic_for(&self, tenant_id: &str, project_id: &str, target: &str, command_id: &str) -> String {
         let prefix = self.command_topic_prefix.trim_end_matches('/');
         if self.include_target_in_topic {
@@ -136,7 +202,7 @@ impl CommandDispatcher for MqttDispatcher {
             &command.target,
             &command.command_id,
         );
-        let payload = mqtt_command_payload(command)?;
+        let payload = self.format_for(command).await?.encode(command)?;
         info!(
             target: "ems.control",
             tenant_id = %command.tenant_id,
@@ -153,6 +219,21 @@ impl CommandDispatcher for MqttDispatcher {
             .map_err(|err| ControlError::Dispatch(err.to_string()))?;
         Ok(())
     }
+
+    async fn preview(&self, command: &CommandDispatch) -> Result<CommandPreview, ControlError> {
+        let format = self.format_for(command).await?;
+        Ok(CommandPreview {
+            command_id: command.command_id.clone(),
+            topic: Some(self.topic_for(
+                &command.tenant_id,
+                &command.project_id,
+                &command.target,
+                &command.command_id,
+            )),
+            payload_format: format.name().to_string(),
+            payload: format.encode(command)?,
+        })
+    }
 }
 
 /// MQTT 回执监听配置。
@@ -164,6 +245,11 @@ pub struct MqttReceiptListenerConfig {
     pub password: Option<String>,
     pub receipt_topic_prefix: String,
     pub qos: u8,
+    /// 严格模式（仅命令回执监听器生效）。
+    ///
+    /// 开启后写入回执前校验命令存在且属于主题中的租户 / 项目；
+    /// 主题在 tenant/project 与 command_id 之间带有额外段时，须与命令 target 一致。
+    pub strict: bool,
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -188,7 +274,10 @@ pub fn spawn_receipt_listener(
     command_store: Arc<dyn CommandStore>,
     receipt_store: Arc<dyn CommandReceiptStore>,
     audit_store: Arc<dyn AuditLogStore>,
+    event_bus: EventBus,
 ) -> tokio::task::JoinHandle<()> {
+    let processor =
+        CommandReceiptProcessor::new(command_store.clone(), receipt_store, audit_store, event_bus);
     tokio::spawn(async move {
         let client_id = format!("ems-control-receipt-{}", uuid::Uuid::new_v4());
         let mut options = MqttOptions::new(client_id, config.host, config.port);
@@ -206,21 +295,27 @@ pub fn spawn_receipt_listener(
         loop {
             match eventloop.poll().await {
                 Ok(Event::Incoming(Packet::Publish(publish))) => {
-                    let Some((tenant_id, project_id, command_id)) =
+                    let Some(scope) =
                         extract_receipt_scope(&config.receipt_topic_prefix, &publish.topic)
                     else {
+                        record_receipt_rejected();
                         warn!(target: "ems.control", "receipt topic skipped: {}", publish.topic);
                         continue;
                     };
                     let payload = match parse_receipt_payload(&publish.payload) {
                         Ok(payload) => payload,
                         Err(err) => {
+                            record_receipt_rejected();
                             warn!(target: "ems.control", "receipt payload invalid: {}", err);
                             continue;
                         }
                     };
-                    let ts_ms = payload.ts_ms.unwrap_or_else(now_epoch_ms);
-                    let status = normalize_status(&payload.status);
+                    let ReceiptScope {
+                        tenant_id,
+                        project_id,
+                        target,
+                        command_id,
+                    } = scope;
                     let ctx = TenantContext::new(
                         tenant_id.clone(),
                         "system".to_string(),
@@ -228,67 +323,35 @@ pub fn spawn_receipt_listener(
                         Vec::new(),
                         Some(project_id.clone()),
                     );
-                    let receipt = CommandReceiptRecord {
-                        receipt_id: stable_receipt_id(
-                            &tenant_id,
+                    if config.strict {
+                        if let Err(reason) = check_receipt_command(
+                            command_store.as_ref(),
+                            &ctx,
                             &project_id,
                             &command_id,
-                            ts_ms,
-                            &status,
-                            payload.message.as_deref(),
-                        ),
-                        tenant_id: tenant_id.clone(),
-                        project_id: project_id.clone(),
-                        command_id: command_id.clone(),
-                        ts_ms,
-                        status: status.clone(),
-                        message: payload.message.clone(),
-                    };
-                    let written: CommandReceiptWriteResult =
-                        match receipt_store.create_receipt(&ctx, receipt).await {
-                            Ok(result) => result,
-                            Err(err) => {
-                                warn!(target: "ems.control", "receipt write failed: {}", err);
-                                continue;
-                            }
-                        };
-                    if !written.inserted {
-                        info!(
-                            target: "ems.control",
-                            tenant_id = %tenant_id,
-                            project_id = %project_id,
-                            command_id = %command_id,
-                            receipt_id = %written.record.receipt_id,
-                            "receipt_duplicate_ignored"
-                        );
-                        continue;
+                            target.as_deref(),
+                        )
+                        .await
+                        {
+                            record_receipt_rejected();
+                            warn!(
+                                target: "ems.control",
+                                tenant_id = %tenant_id,
+                                project_id = %project_id,
+                                command_id = %command_id,
+                                topic = %publish.topic,
+                                reason = %reason,
+                                "receipt_rejected"
+                            );
+                            continue;
+                        }
+                    }
+                    if let Err(err) = processor
+                        .process(&ctx, &project_id, &command_id, payload, "system")
+                        .await
+                    {
+                        warn!(target: "ems.control", "receipt write failed: {}", err);
                     }
-                    record_receipt_processed();
-                    let _ = command_store
-                        .update_command_status(&ctx, &project_id, &command_id, &status)
-                        .await;
-                    let audit = AuditLogRecord {
-                        audit_id: stable_audit_id_for_receipt(&written.record.receipt_id),
-                        tenant_id: tenant_id.clone(),
-                        project_id: Some(project_id.clone()),
-                        actor: "system".to_string(),
-                        action: "CONTROL.COMMAND.RECEIPT".to_string(),
-                        resource: format!("command:{}", command_id),
-                        result: status.clone(),
-                        detail: payload.message.clone(),
-                        ts_ms,
-                    };
-                    let _ = audit_store.create_audit_log(&ctx, audit).await;
-                    info!(
-                        target: "ems.control",
-                        tenant_id = %tenant_id,
-                        project_id = %project_id,
-                        command_id = %command_id,
-                        status = %status,
-                        message = ?payload.message,
-                        ts_ms = ts_ms,
-                        "receipt_processed"
-                    );
                 }
                 Ok(_) => {}
                 Err(err) => {
@@ -300,6 +363,117 @@ pub fn spawn_receipt_listener(
     })
 }
 
+/// 命令回执处理器（MQTT 回执监听器与 HTTP 回执接口共用）。
+///
+/// 回执 ID 由 (tenant, project, command, ts, status, message) 派生，重复回执只写入一次；
+/// 新回执更新命令状态、写入审计 `CONTROL.COMMAND.RECEIPT`，终态时发布 `command.completed`。
+#[derive(Clone)]
+pub struct CommandReceiptProcessor {
+    command_store: Arc<dyn CommandStore>,
+    receipt_store: Arc<dyn CommandReceiptStore>,
+    audit_store: Arc<dyn AuditLogStore>,
+    event_bus: EventBus,
+}
+
+impl CommandReceiptProcessor {
+    pub fn new(
+        command_store: Arc<dyn CommandStore>,
+        receipt_store: Arc<dyn CommandReceiptStore>,
+        audit_store: Arc<dyn AuditLogStore>,
+        event_bus: EventBus,
+    ) -> Self {
+        Self {
+            command_store,
+            receipt_store,
+            audit_store,
+            event_bus,
+        }
+    }
+
+    /// 写入一条回执（`actor` 记入审计，MQTT 回执为 `system`）
+    pub async fn process(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        command_id: &str,
+        payload: ParsedReceiptPayload,
+        actor: &str,
+    ) -> Result<CommandReceiptWriteResult, ControlError> {
+        let tenant_id = ctx.tenant_id.as_str();
+        let ts_ms = payload.ts_ms.unwrap_or_else(now_epoch_ms);
+        let status = normalize_status(&payload.status);
+        let receipt = CommandReceiptRecord {
+            receipt_id: stable_receipt_id(
+                tenant_id,
+                project_id,
+                command_id,
+                ts_ms,
+                &status,
+                payload.message.as_deref(),
+            ),
+            tenant_id: tenant_id.to_string(),
+            project_id: project_id.to_string(),
+            command_id: command_id.to_string(),
+            ts_ms,
+            status: status.clone(),
+            message: payload.message.clone(),
+        };
+        let written = self.receipt_store.create_receipt(ctx, receipt).await?;
+        if !written.inserted {
+            info!(
+                target: "ems.control",
+                tenant_id = %tenant_id,
+                project_id = %project_id,
+                command_id = %command_id,
+                receipt_id = %written.record.receipt_id,
+                "receipt_duplicate_ignored"
+            );
+            return Ok(written);
+        }
+        record_receipt_processed(tenant_id);
+        let target = self
+            .command_store
+            .update_command_status(ctx, project_id, command_id, &status)
+            .await
+            .ok()
+            .flatten()
+            .map(|command| command.target);
+        let audit = AuditLogRecord {
+            audit_id: stable_audit_id_for_receipt(&written.record.receipt_id),
+            tenant_id: tenant_id.to_string(),
+            project_id: Some(project_id.to_string()),
+            actor: actor.to_string(),
+            action: "CONTROL.COMMAND.RECEIPT".to_string(),
+            resource: format!("command:{}", command_id),
+            result: status.clone(),
+            detail: payload.message.clone(),
+            ts_ms,
+        };
+        let _ = self.audit_store.create_audit_log(ctx, audit).await;
+        publish_command_completed(
+            &self.event_bus,
+            tenant_id,
+            project_id,
+            command_id,
+            target.as_deref(),
+            &status,
+            payload.message.as_deref(),
+        );
+        info!(
+            target: "ems.control",
+            tenant_id = %tenant_id,
+            project_id = %project_id,
+            command_id = %command_id,
+            status = %status,
+            message = ?payload.message,
+            ts_ms = ts_ms,
+            actor = %actor,
+            "receipt_processed"
+        );
+        Ok(written)
+    }
+}
+
 /// 命令服务（创建 + 下发 + 审计）。
 #[derive(Clone)]
 pub struct CommandService {
@@ -307,6 +481,10 @@ pub struct CommandService {
     audit_store: Arc<dyn AuditLogStore>,
     dispatcher: Arc<dyn CommandDispatcher>,
     config: CommandServiceConfig,
+    event_bus: Option<EventBus>,
+    maintenance: Option<Arc<MaintenanceService>>,
+    usage_store: Option<Arc<dyn UsageStore>>,
+    feature_flag_store: Option<Arc<dyn FeatureFlagStore>>,
 }
 
 #[derive(Debug, Clone)]
@@ -347,18 +525,81 @@ impl CommandService {
             audit_store,
             dispatcher,
             config,
+            event_bus: None,
+            maintenance: None,
+            usage_store: None,
+            feature_flag_store: None,
         }
     }
 
+    /// 挂载事件总线：命令进入终态（下发失败 / 超时）时发布 `command.completed`
+    pub fn with_event_bus(mut self, event_bus: EventBus) -> Self {
+        self.event_bus = Some(event_bus);
+        self
+    }
+
+    /// 挂载维护模式：目标处于维护窗口时拦截自动化命令，人工命令照常下发并记录覆盖审计
+    pub fn with_maintenance(mut self, maintenance: Arc<MaintenanceService>) -> Self {
+        self.maintenance = Some(maintenance);
+        self
+    }
+
+    /// 挂载用量计量：按租户每日累计命令数，超出 `commands` 配额时拒绝下发
+    pub fn with_usage_store(mut self, usage_store: Arc<dyn UsageStore>) -> Self {
+        self.usage_store = Some(usage_store);
+        self
+    }
+
+    /// 挂载功能开关：租户关闭 `control` 时拒绝一切命令下发（人工、gRPC 与规则 / 计划 / 需求响应）
+    pub fn with_feature_flags(mut self, feature_flag_store: Arc<dyn FeatureFlagStore>) -> Self {
+        self.feature_flag_store = Some(feature_flag_store);
+        self
+    }
+
+    /// 命令 dry-run：序列化 payload 并渲染主题与载荷，不创建命令、不计入配额、不发布
+    pub async fn preview_command(
+        &self,
+        ctx: &TenantContext,
+        request: CommandRequest,
+    ) -> Result<CommandPreview, ControlError> {
+        let payload = serde_json::to_string(&request.payload)
+            .map_err(|err| ControlError::Payload(err.to_string()))?;
+        let dispatch = CommandDispatch {
+            command_id: uuid::Uuid::new_v4().to_string(),
+            tenant_id: ctx.tenant_id.clone(),
+            project_id: request.project_id,
+            target: request.target,
+            payload,
+            issued_at_ms: request.issued_at_ms,
+        };
+        let preview = self.dispatcher.preview(&dispatch).await?;
+        info!(
+            target: "ems.control",
+            tenant_id = %dispatch.tenant_id,
+            project_id = %dispatch.project_id,
+            command_id = %dispatch.command_id,
+            actor = %ctx.user_id,
+            command_target = %dispatch.target,
+            topic = ?preview.topic,
+            payload_format = %preview.payload_format,
+            payload_size = preview.payload.len(),
+            "command_dry_run"
+        );
+        Ok(preview)
+    }
+
     pub async fn issue_command(
         &self,
         ctx: &TenantContext,
         request: CommandRequest,
     ) -> Result<CommandRecord, ControlError> {
-        record_command_issued();
+        record_command_issued(&ctx.tenant_id);
         let started_at = Instant::now();
         let payload = serde_json::to_string(&request.payload)
             .map_err(|err| ControlError::Payload(err.to_string()))?;
+        self.ensure_control_enabled(ctx).await?;
+        let maintenance_window = self.maintenance_window(ctx, &request).await?;
+        self.consume_command_quota(ctx, request.issued_at_ms).await?;
         let command_id = uuid::Uuid::new_v4().to_string();
         info!(
             target: "ems.control",
@@ -381,11 +622,7 @@ impl CommandService {
             issued_by: ctx.user_id.clone(),
             issued_at_ms: request.issued_at_ms,
         };
-        let record = self
-            .command_store
-            .create_command(ctx, record)
-            .await
-            .map_err(|err| ControlError::Storage(err.to_string()))?;
+        let record = self.command_store.create_command(ctx, record).await?;
         info!(
             target: "ems.control",
             tenant_id = %record.tenant_id,
@@ -433,8 +670,7 @@ impl CommandService {
         let updated = self
             .command_store
             .update_command_status(ctx, &record.project_id, &record.command_id, status)
-            .await
-            .map_err(|err| ControlError::Storage(err.to_string()))?;
+            .await?;
         let record = updated.unwrap_or_else(|| CommandRecord {
             status: status.to_string(),
             ..record
@@ -445,11 +681,37 @@ impl CommandService {
             spawn_command_timeout_task(
                 self.command_store.clone(),
                 self.audit_store.clone(),
+                self.event_bus.clone(),
                 ctx.clone(),
                 record.clone(),
                 self.config.receipt_timeout_ms,
             );
         }
+        if let Some(event_bus) = &self.event_bus {
+            event_bus.publish(DomainEvent::new(
+                event_types::COMMAND_ISSUED,
+                record.tenant_id.clone(),
+                record.project_id.clone(),
+                serde_json::json!({
+                    "commandId": record.command_id,
+                    "target": record.target,
+                    "status": status,
+                    "issuedBy": record.issued_by,
+                    "message": detail,
+                }),
+            ));
+            if status == "failed" {
+                publish_command_completed(
+                    event_bus,
+                    &record.tenant_id,
+                    &record.project_id,
+                    &record.command_id,
+                    Some(&record.target),
+                    status,
+                    detail.as_deref(),
+                );
+            }
+        }
 
         let audit = AuditLogRecord {
             audit_id: uuid::Uuid::new_v4().to_string(),
@@ -463,13 +725,140 @@ impl CommandService {
             ts_ms: record.issued_at_ms,
         };
         let _ = self.audit_store.create_audit_log(ctx, audit).await;
+        if let Some(window) = maintenance_window {
+            let audit = AuditLogRecord {
+                audit_id: uuid::Uuid::new_v4().to_string(),
+                tenant_id: ctx.tenant_id.clone(),
+                project_id: Some(record.project_id.clone()),
+                actor: ctx.user_id.clone(),
+                action: "CONTROL.COMMAND.MAINTENANCE_OVERRIDE".to_string(),
+                resource: format!("command:{}", record.command_id),
+                result: result.to_string(),
+                detail: Some(maintenance_notice(&window)),
+                ts_ms: record.issued_at_ms,
+            };
+            let _ = self.audit_store.create_audit_log(ctx, audit).await;
+        }
         Ok(record)
     }
+
+    /// 租户关闭 `control` 开关时返回 `ControlError::FeatureDisabled`（不创建命令）
+    async fn ensure_control_enabled(&self, ctx: &TenantContext) -> Result<(), ControlError> {
+        let Some(feature_flag_store) = &self.feature_flag_store else {
+            return Ok(());
+        };
+        let enabled = match feature_flag_store
+            .get_feature_flag(ctx, domain::features::CONTROL)
+            .await?
+        {
+            Some(flag) => flag.enabled,
+            None => domain::features::default_enabled(domain::features::CONTROL),
+        };
+        if !enabled {
+            warn!(
+                target: "ems.control",
+                tenant_id = %ctx.tenant_id,
+                actor = %ctx.user_id,
+                "command_feature_disabled"
+            );
+            return Err(ControlError::FeatureDisabled(
+                domain::features::CONTROL.to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// 累计当日命令数；超出配额时返回 `ControlError::Quota`（不创建命令）
+    async fn consume_command_quota(
+        &self,
+        ctx: &TenantContext,
+        issued_at_ms: i64,
+    ) -> Result<(), ControlError> {
+        let Some(usage_store) = &self.usage_store else {
+            return Ok(());
+        };
+        let consumed = usage_store
+            .consume_usage(
+                ctx,
+                domain::usage::COMMANDS,
+                domain::usage::day_start_ms(issued_at_ms),
+                1,
+            )
+            .await?;
+        if consumed.is_none() {
+            warn!(
+                target: "ems.control",
+                tenant_id = %ctx.tenant_id,
+                actor = %ctx.user_id,
+                "command_quota_exceeded"
+            );
+            return Err(ControlError::Quota(domain::usage::COMMANDS.to_string()));
+        }
+        Ok(())
+    }
+
+    /// 命令目标生效中的维护窗口：自动化命令直接拦截（记录审计），人工命令返回窗口供覆盖审计
+    async fn maintenance_window(
+        &self,
+        ctx: &TenantContext,
+        request: &CommandRequest,
+    ) -> Result<Option<MaintenanceWindowRecord>, ControlError> {
+        let Some(maintenance) = &self.maintenance else {
+            return Ok(None);
+        };
+        let Some(window) = maintenance
+            .active_window_for_target(
+                ctx,
+                &request.project_id,
+                &request.target,
+                request.issued_at_ms,
+            )
+            .await?
+        else {
+            return Ok(None);
+        };
+        let notice = maintenance_notice(&window);
+        if !is_automated_actor(&ctx.user_id) {
+            warn!(
+                target: "ems.control",
+                tenant_id = %ctx.tenant_id,
+                project_id = %request.project_id,
+                actor = %ctx.user_id,
+                command_target = %request.target,
+                notice = %notice,
+                "command_maintenance_override"
+            );
+            return Ok(Some(window));
+        }
+        warn!(
+            target: "ems.control",
+            tenant_id = %ctx.tenant_id,
+            project_id = %request.project_id,
+            actor = %ctx.user_id,
+            command_target = %request.target,
+            notice = %notice,
+            "command_blocked_by_maintenance"
+        );
+        let audit = AuditLogRecord {
+            audit_id: uuid::Uuid::new_v4().to_string(),
+            tenant_id: ctx.tenant_id.clone(),
+            project_id: Some(request.project_id.clone()),
+            actor: ctx.user_id.clone(),
+            action: "CONTROL.COMMAND.BLOCKED".to_string(),
+            resource: format!("{}:{}", window.target_type, window.target_id),
+            result: "blocked".to_string(),
+            detail: Some(notice.clone()),
+            ts_ms: request.issued_at_ms,
+        };
+        let _ = self.audit_store.create_audit_log(ctx, audit).await;
+        Err(ControlError::Maintenance(notice))
+    }
 }
 
 fn spawn_command_timeout_task(
     command_store: Arc<dyn CommandStore>,
     audit_store: Arc<dyn AuditLogStore>,
+    event_bus: Option<EventBus>,
     ctx: TenantContext,
     command: CommandRecord,
     timeout_ms: u64,
@@ -517,6 +906,17 @@ fn spawn_command_timeout_task(
             ts_ms,
         };
         let _ = audit_store.create_audit_log(&ctx, audit).await;
+        if let Some(event_bus) = &event_bus {
+            publish_command_completed(
+                event_bus,
+                &ctx.tenant_id,
+                &command.project_id,
+                &command.command_id,
+                Some(&command.target),
+                "timeout",
+                None,
+            );
+        }
         info!(
             target: "ems.control",
             tenant_id = %ctx.tenant_id,
@@ -528,7 +928,45 @@ fn spawn_command_timeout_task(
     });
 }
 
-fn extract_receipt_scope(prefix: &str, topic: &str) -> Option<(String, String, String)> {
+/// 命令进入终态（success / failed / timeout）时发布 `command.completed`
+///
+/// `target` 为命令目标（设备或网关），供设备时间线等消费者关联设备。
+fn publish_command_completed(
+    event_bus: &EventBus,
+    tenant_id: &str,
+    project_id: &str,
+    command_id: &str,
+    target: Option<&str>,
+    status: &str,
+    message: Option<&str>,
+) {
+    if !matches!(status, "success" | "failed" | "timeout") {
+        return;
+    }
+    event_bus.publish(DomainEvent::new(
+        event_types::COMMAND_COMPLETED,
+        tenant_id,
+        project_id,
+        serde_json::json!({
+            "commandId": command_id,
+            "target": target,
+            "status": status,
+            "message": message,
+        }),
+    ));
+}
+
+/// 回执主题解析结果：`{prefix}/{tenant}/{project}[/{target...}]/{command_id}`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ReceiptScope {
+    tenant_id: String,
+    project_id: String,
+    /// tenant/project 与 command_id 之间的额外段（可包含多段）
+    target: Option<String>,
+    command_id: String,
+}
+
+fn extract_receipt_scope(prefix: &str, topic: &str) -> Option<ReceiptScope> {
     let prefix = prefix.trim_matches('/');
     let topic = topic.trim_matches('/');
     let rest = if prefix.is_empty() {
@@ -544,20 +982,48 @@ fn extract_receipt_scope(prefix: &str, topic: &str) -> Option<(String, String, S
     if parts.len() < 3 {
         return None;
     }
-    let tenant_id = parts[0];
-    let project_id = parts[1];
-    let command_id = parts[parts.len() - 1];
-    Some((tenant_id.to_string(), project_id.to_string(), command_id.to_string()))
+    let middle = &parts[2..parts.len() - 1];
+    Some(ReceiptScope {
+        tenant_id: parts[0].to_string(),
+        project_id: parts[1].to_string(),
+        target: (!middle.is_empty()).then(|| middle.join("/")),
+        command_id: parts[parts.len() - 1].to_string(),
+    })
+}
+
+/// 严格模式校验：命令须存在于主题所属租户 / 项目，主题携带的 target 须与命令一致
+async fn check_receipt_command(
+    command_store: &dyn CommandStore,
+    ctx: &TenantContext,
+    project_id: &str,
+    command_id: &str,
+    target: Option<&str>,
+) -> Result<(), String> {
+    let command = command_store
+        .find_command(ctx, project_id, command_id)
+        .await
+        .map_err(|err| format!("command lookup failed: {}", err))?
+        .ok_or_else(|| "command not found".to_string())?;
+    if let Some(target) = target {
+        if target != command.target.trim_matches('/') {
+            return Err(format!("target mismatch: {}", target));
+        }
+    }
+    Ok(())
 }
 
+/// 回执解析结果。
 #[derive(Debug, Clone)]
-struct ParsedReceiptPayload {
-    status: String,
-    message: Option<String>,
-    ts_ms: Option<i64>,
+pub struct ParsedReceiptPayload {
+    /// 设备上报的原始状态（写入前按 accepted/success/failed/timeout 归一）
+    pub status: String,
+    pub message: Option<String>,
+    /// 回执时间（毫秒）；缺省时使用接收时间
+    pub ts_ms: Option<i64>,
 }
 
-fn parse_receipt_payload(payload: &[u8]) -> Result<ParsedReceiptPayload, String> {
+/// 解析回执 payload：JSON 对象、JSON 字符串或纯文本状态（如 `success`）。
+pub fn parse_receipt_payload(payload: &[u8]) -> Result<ParsedReceiptPayload, String> {
     if payload.is_empty() {
         return Err("empty payload".to_string());
     }
@@ -630,27 +1096,6 @@ fn normalize_status(value: &str) -> String {
     }
 }
 
-#[derive(Debug, serde::Serialize)]
-#[serde(rename_all = "camelCase")]
-struct CommandMqttEnvelope<'a> {
-    command_id: &'a str,
-    target: &'a str,
-    issued_at_ms: i64,
-    payload: serde_json::Value,
-}
-
-fn mqtt_command_payload(command: &CommandDispatch) -> Result<Vec<u8>, ControlError> {
-    let payload_value: serde_json::Value = serde_json::from_str(&command.payload)
-        .unwrap_or_else(|_| serde_json::Value::String(command.payload.clone()));
-    let envelope = CommandMqttEnvelope {
-        command_id: &command.command_id,
-        target: &command.target,
-        issued_at_ms: command.issued_at_ms,
-        payload: payload_value,
-    };
-    serde_json::to_vec(&envelope).map_err(|err| ControlError::Payload(err.to_string()))
-}
-
 fn stable_receipt_id(
     tenant_id: &str,
     project_id: &str,
@@ -685,9 +1130,66 @@ mod tests {
         let prefix = "ems/receipts";
         let topic = "ems/receipts/tenant-1/project-1/demo-target/cmd-1";
         let scope = extract_receipt_scope(prefix, topic).expect("scope");
-        assert_eq!(scope.0, "tenant-1");
-        assert_eq!(scope.1, "project-1");
-        assert_eq!(scope.2, "cmd-1");
+        assert_eq!(scope.tenant_id, "tenant-1");
+        assert_eq!(scope.project_id, "project-1");
+        assert_eq!(scope.target.as_deref(), Some("demo-target"));
+        assert_eq!(scope.command_id, "cmd-1");
+
+        let scope = extract_receipt_scope(prefix, "ems/receipts/tenant-1/project-1/cmd-1")
+            .expect("scope");
+        assert!(scope.target.is_none());
+        assert!(extract_receipt_scope(prefix, "ems/receipts/tenant-1/cmd-1").is_none());
+    }
+
+    #[tokio::test]
+    async fn strict_receipt_check_requires_matching_command() {
+        let store = ems_storage::InMemoryCommandStore::new();
+        let ctx = TenantContext::new(
+            "tenant-1".to_string(),
+            "system".to_string(),
+            Vec::new(),
+            Vec::new(),
+            Some("project-1".to_string()),
+        );
+        store
+            .create_command(
+                &ctx,
+                CommandRecord {
+                    command_id: "cmd-1".to_string(),
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    target: "site-a/meter-1".to_string(),
+                    payload: "{}".to_string(),
+                    status: "accepted".to_string(),
+                    issued_by: "user-1".to_string(),
+                    issued_at_ms: 0,
+                },
+            )
+            .await
+            .expect("command");
+
+        assert!(check_receipt_command(&store, &ctx, "project-1", "cmd-1", None).await.is_ok());
+        assert!(
+            check_receipt_command(&store, &ctx, "project-1", "cmd-1", Some("site-a/meter-1"))
+                .await
+                .is_ok()
+        );
+        assert!(
+            check_receipt_command(&store, &ctx, "project-1", "cmd-1", Some("meter-1"))
+                .await
+                .is_err()
+        );
+        assert!(check_receipt_command(&store, &ctx, "project-1", "cmd-2", None).await.is_err());
+
+        // 其他租户的主题不能写入该命令的回执
+        let other = TenantContext::new(
+            "tenant-2".to_string(),
+            "system".to_string(),
+            Vec::new(),
+            Vec::new(),
+            Some("project-1".to_string()),
+        );
+        assert!(check_receipt_command(&store, &other, "project-1", "cmd-1", None).await.is_err());
     }
 
     #[test]
@@ -706,6 +1208,73 @@ mod tests {
         assert!(parsed.message.is_none());
         assert!(parsed.ts_ms.is_none());
     }
+
+    #[test]
+    fn command_completed_published_only_for_terminal_status() {
+        let bus = EventBus::default();
+        let mut receiver = bus.subscribe();
+        publish_command_completed(&bus, "tenant-1", "project-1", "cmd-1", None, "accepted", None);
+        publish_command_completed(
+            &bus,
+            "tenant-1",
+            "project-1",
+            "cmd-1",
+            Some("dev-1"),
+            "success",
+            Some("ok"),
+        );
+        let event = receiver.try_recv().expect("event");
+        assert_eq!(event.event_type, event_types::COMMAND_COMPLETED);
+        assert_eq!(event.data["status"], "success");
+        assert_eq!(event.data["commandId"], "cmd-1");
+        assert_eq!(event.data["target"], "dev-1");
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn preview_command_does_not_create_command() {
+        let command_store = Arc::new(ems_storage::InMemoryCommandStore::new());
+        let service = CommandService::new(
+            command_store.clone(),
+            Arc::new(ems_storage::InMemoryAuditLogStore::new()),
+            Arc::new(NoopDispatcher),
+        );
+        let ctx = TenantContext::new(
+            "tenant-1".to_string(),
+            "user-1".to_string(),
+            Vec::new(),
+            Vec::new(),
+            Some("project-1".to_string()),
+        );
+        let preview = service
+            .preview_command(
+                &ctx,
+                CommandRequest {
+                    project_id: "project-1".to_string(),
+                    target: "device-1".to_string(),
+                    payload: serde_json::json!({ "setpoint": 5 }),
+                    issued_at_ms: 1_000,
+                },
+            )
+            .await
+            .expect("preview");
+        assert!(preview.topic.is_none());
+        assert_eq!(preview.payload_format, "envelope");
+        let envelope: serde_json::Value =
+            serde_json::from_slice(&preview.payload).expect("envelope");
+        assert_eq!(envelope["commandId"], preview.command_id.as_str());
+        assert_eq!(envelope["payload"]["setpoint"], 5);
+
+        let commands = command_store
+            .list_commands(
+                &ctx,
+                "project-1",
+                ems_storage::CommandQueryOptions::default(),
+            )
+            .await
+            .expect("commands");
+        assert!(commands.is_empty());
+    }
 }
 
 async fn dispatch_with_retry(
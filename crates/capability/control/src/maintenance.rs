@@ -0,0 +1,400 @@
+//! 设备 / 网关维护模式。
+//!
+//! - 维护窗口按目标（设备或网关）设置，窗口内：
+//!   - 自动化命令（规则 / 控制计划 / 需求响应）被拦截，记录 `CONTROL.COMMAND.BLOCKED` 审计
+//!   - 人工命令照常下发，记录 `CONTROL.COMMAND.MAINTENANCE_OVERRIDE` 审计并在响应中提示
+//!   - 设备离线规则不触发告警
+//! - 网关处于维护时，其下全部设备视为处于维护
+//! - 设置 / 清除窗口记录 `ASSET.DEVICE.MAINTENANCE.*` / `ASSET.GATEWAY.MAINTENANCE.*` 审计
+
+use domain::TenantContext;
+use ems_storage::{
+    AuditLogRecord, AuditLogStore, DeviceStore, MaintenanceStore, MaintenanceWindowRecord,
+    StorageError,
+};
+use std::sync::Arc;
+use tracing::info;
+
+/// 维护目标类型：设备
+pub const MAINTENANCE_TARGET_DEVICE: &str = "device";
+/// 维护目标类型：网关
+pub const MAINTENANCE_TARGET_GATEWAY: &str = "gateway";
+
+/// 自动化执行者前缀（规则引擎 / 控制计划执行器 / 需求响应编排器下发命令时的 actor）
+const AUTOMATED_ACTOR_PREFIXES: [&str; 3] = ["rule:", "schedule:", "demand-response:"];
+
+/// 是否为自动化执行者（维护窗口内其命令会被拦截）。
+pub fn is_automated_actor(actor: &str) -> bool {
+    AUTOMATED_ACTOR_PREFIXES
+        .iter()
+        .any(|prefix| actor.starts_with(prefix))
+}
+
+/// 维护窗口的提示信息（用于命令拦截错误与人工覆盖提示）。
+pub fn maintenance_notice(window: &MaintenanceWindowRecord) -> String {
+    format!(
+        "{} {} is under maintenance until {}",
+        window.target_type, window.target_id, window.ends_at_ms
+    )
+}
+
+/// 维护模式服务（窗口设置 + 审计 + 生效判断）。
+pub struct MaintenanceService {
+    maintenance_store: Arc<dyn MaintenanceStore>,
+    device_store: Arc<dyn DeviceStore>,
+    audit_store: Arc<dyn AuditLogStore>,
+}
+
+impl MaintenanceService {
+    pub fn new(
+        maintenance_store: Arc<dyn MaintenanceStore>,
+        device_store: Arc<dyn DeviceStore>,
+        audit_store: Arc<dyn AuditLogStore>,
+    ) -> Self {
+        Self {
+            maintenance_store,
+            device_store,
+            audit_store,
+        }
+    }
+
+    pub async fn list_windows(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<Vec<MaintenanceWindowRecord>, StorageError> {
+        self.maintenance_store
+            .list_maintenance_windows(ctx, project_id)
+            .await
+    }
+
+    pub async fn find_window(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        target_type: &str,
+        target_id: &str,
+    ) -> Result<Option<MaintenanceWindowRecord>, StorageError> {
+        self.maintenance_store
+            .find_maintenance_window(ctx, project_id, target_type, target_id)
+            .await
+    }
+
+    /// 设置维护窗口（覆盖原窗口）并记录审计。
+    pub async fn set_window(
+        &self,
+        ctx: &TenantContext,
+        record: MaintenanceWindowRecord,
+    ) -> Result<MaintenanceWindowRecord, StorageError> {
+        let record = self
+            .maintenance_store
+            .upsert_maintenance_window(ctx, record)
+            .await?;
+        info!(
+            target: "ems.control",
+            tenant_id = %record.tenant_id,
+            project_id = %record.project_id,
+            target_type = %record.target_type,
+            target_id = %record.target_id,
+            starts_at_ms = record.starts_at_ms,
+            ends_at_ms = record.ends_at_ms,
+            "maintenance_window_set"
+        );
+        let detail = match &record.reason {
+            Some(reason) => format!("{}..{}: {reason}", record.starts_at_ms, record.ends_at_ms),
+            None => format!("{}..{}", record.starts_at_ms, record.ends_at_ms),
+        };
+        let audit = AuditLogRecord {
+            audit_id: uuid::Uuid::new_v4().to_string(),
+            tenant_id: ctx.tenant_id.clone(),
+            project_id: Some(record.project_id.clone()),
+            actor: ctx.user_id.clone(),
+            action: audit_action(&record.target_type, "SET"),
+            resource: format!("{}:{}", record.target_type, record.target_id),
+            result: "success".to_string(),
+            detail: Some(detail),
+            ts_ms: record.created_at_ms,
+        };
+        let _ = self.audit_store.create_audit_log(ctx, audit).await;
+        Ok(record)
+    }
+
+    /// 清除维护窗口，存在时记录审计；返回是否存在。
+    pub async fn clear_window(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        target_type: &str,
+        target_id: &str,
+        ts_ms: i64,
+    ) -> Result<bool, StorageError> {
+        let removed = self
+            .maintenance_store
+            .delete_maintenance_window(ctx, project_id, target_type, target_id)
+            .await?;
+        if !removed {
+            return Ok(false);
+        }
+        info!(
+            target: "ems.control",
+            tenant_id = %ctx.tenant_id,
+            project_id = %project_id,
+            target_type = %target_type,
+            target_id = %target_id,
+            "maintenance_window_cleared"
+        );
+        let audit = AuditLogRecord {
+            audit_id: uuid::Uuid::new_v4().to_string(),
+            tenant_id: ctx.tenant_id.clone(),
+            project_id: Some(project_id.to_string()),
+            actor: ctx.user_id.clone(),
+            action: audit_action(target_type, "CLEAR"),
+            resource: format!("{target_type}:{target_id}"),
+            result: "success".to_string(),
+            detail: None,
+            ts_ms,
+        };
+        let _ = self.audit_store.create_audit_log(ctx, audit).await;
+        Ok(true)
+    }
+
+    /// 查询设备在指定时刻生效的维护窗口（设备自身或其所属网关）。
+    pub async fn active_window_for_device(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        device_id: &str,
+        ts_ms: i64,
+    ) -> Result<Option<MaintenanceWindowRecord>, StorageError> {
+        let windows = self.active_windows(ctx, project_id, ts_ms).await?;
+        if let Some(window) = windows.iter().find(|window| {
+            window.target_type == MAINTENANCE_TARGET_DEVICE && window.target_id == device_id
+        }) {
+            return Ok(Some(window.clone()));
+        }
+        self.gateway_window_for_device(ctx, project_id, device_id, windows)
+            .await
+    }
+
+    /// 查询命令目标在指定时刻生效的维护窗口。
+    ///
+    /// 命令 target 可能是设备 ID 或网关 ID：先按 ID 直接匹配，再按设备所属网关匹配。
+    pub async fn active_window_for_target(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        target: &str,
+        ts_ms: i64,
+    ) -> Result<Option<MaintenanceWindowRecord>, StorageError> {
+        let windows = self.active_windows(ctx, project_id, ts_ms).await?;
+        if let Some(window) = windows.iter().find(|window| window.target_id == target) {
+            return Ok(Some(window.clone()));
+        }
+        self.gateway_window_for_device(ctx, project_id, target, windows)
+            .await
+    }
+
+    async fn active_windows(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        ts_ms: i64,
+    ) -> Result<Vec<MaintenanceWindowRecord>, StorageError> {
+        let windows = self
+            .maintenance_store
+            .list_maintenance_windows(ctx, project_id)
+            .await?;
+        Ok(windows
+            .into_iter()
+            .filter(|window| window.is_active_at(ts_ms))
+            .collect())
+    }
+
+    /// 设备所属网关处于维护时返回网关窗口（无生效的网关窗口时不查询设备）
+    async fn gateway_window_for_device(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        device_id: &str,
+        windows: Vec<MaintenanceWindowRecord>,
+    ) -> Result<Option<MaintenanceWindowRecord>, StorageError> {
+        if !windows
+            .iter()
+            .any(|window| window.target_type == MAINTENANCE_TARGET_GATEWAY)
+        {
+            return Ok(None);
+        }
+        let Some(device) = self
+            .device_store
+            .find_device(ctx, project_id, device_id)
+            .await?
+        else {
+            return Ok(None);
+        };
+        Ok(windows.into_iter().find(|window| {
+            window.target_type == MAINTENANCE_TARGET_GATEWAY
+                && window.target_id == device.gateway_id
+        }))
+    }
+}
+
+fn audit_action(target_type: &str, verb: &str) -> String {
+    format!(
+        "ASSET.{}.MAINTENANCE.{verb}",
+        target_type.to_ascii_uppercase()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ems_storage::{DeviceRecord, InMemoryDeviceStore, InMemoryMaintenanceStore};
+
+    fn ctx() -> TenantContext {
+        TenantContext::new(
+            "tenant-1".to_string(),
+            "user-1".to_string(),
+            Vec::new(),
+            Vec::new(),
+            Some("project-1".to_string()),
+        )
+    }
+
+    fn window(target_type: &str, target_id: &str) -> MaintenanceWindowRecord {
+        MaintenanceWindowRecord {
+            tenant_id: "tenant-1".to_string(),
+            project_id: "project-1".to_string(),
+            target_type: target_type.to_string(),
+            target_id: target_id.to_string(),
+            starts_at_ms: 1_000,
+            ends_at_ms: 2_000,
+            reason: Some("inverter swap".to_string()),
+            created_by: "user-1".to_string(),
+            created_at_ms: 500,
+        }
+    }
+
+    #[test]
+    fn automated_actors_are_recognized() {
+        assert!(is_automated_actor("rule:r-1"));
+        assert!(is_automated_actor("schedule:s-1"));
+        assert!(is_automated_actor("demand-response:e-1"));
+        assert!(!is_automated_actor("user-1"));
+    }
+
+    #[tokio::test]
+    async fn gateway_window_covers_its_devices() {
+        let ctx = ctx();
+        let device_store = Arc::new(InMemoryDeviceStore::new());
+        device_store
+            .create_device(
+                &ctx,
+                DeviceRecord {
+                    device_id: "device-1".to_string(),
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    gateway_id: "gw-1".to_string(),
+                    name: "Meter".to_string(),
+                    model: None,
+                    room_id: None,
+                    address_config: None,
+                    offline_after_seconds: None,
+                },
+            )
+            .await
+            .expect("device");
+        let audit_store = Arc::new(ems_storage::InMemoryAuditLogStore::new());
+        let service = MaintenanceService::new(
+            Arc::new(InMemoryMaintenanceStore::new()),
+            device_store,
+            audit_store.clone(),
+        );
+        service
+            .set_window(&ctx, window(MAINTENANCE_TARGET_GATEWAY, "gw-1"))
+            .await
+            .expect("set");
+
+        let active = service
+            .active_window_for_device(&ctx, "project-1", "device-1", 1_500)
+            .await
+            .expect("active");
+        assert_eq!(
+            active.map(|window| window.target_id),
+            Some("gw-1".to_string())
+        );
+        let target = service
+            .active_window_for_target(&ctx, "project-1", "gw-1", 1_500)
+            .await
+            .expect("target");
+        assert!(target.is_some());
+        let expired = service
+            .active_window_for_device(&ctx, "project-1", "device-1", 2_000)
+            .await
+            .expect("expired");
+        assert!(expired.is_none());
+
+        assert!(
+            service
+                .clear_window(&ctx, "project-1", MAINTENANCE_TARGET_GATEWAY, "gw-1", 1_600)
+                .await
+                .expect("clear")
+        );
+        let logs = audit_store
+            .list_audit_logs(&ctx, "project-1", None, None, 10)
+            .await
+            .expect("audit");
+        let actions: Vec<&str> = logs.iter().map(|log| log.action.as_str()).collect();
+        assert!(actions.contains(&"ASSET.GATEWAY.MAINTENANCE.SET"));
+        assert!(actions.contains(&"ASSET.GATEWAY.MAINTENANCE.CLEAR"));
+    }
+
+    #[tokio::test]
+    async fn automated_commands_are_blocked_and_manual_ones_audited() {
+        let audit_store = Arc::new(ems_storage::InMemoryAuditLogStore::new());
+        let maintenance = Arc::new(MaintenanceService::new(
+            Arc::new(InMemoryMaintenanceStore::new()),
+            Arc::new(InMemoryDeviceStore::new()),
+            audit_store.clone(),
+        ));
+        maintenance
+            .set_window(&ctx(), window(MAINTENANCE_TARGET_DEVICE, "device-1"))
+            .await
+            .expect("set");
+        let service = crate::CommandService::new(
+            Arc::new(ems_storage::InMemoryCommandStore::new()),
+            audit_store.clone(),
+            Arc::new(crate::NoopDispatcher),
+        )
+        .with_maintenance(maintenance);
+        let request = crate::CommandRequest {
+            project_id: "project-1".to_string(),
+            target: "device-1".to_string(),
+            payload: serde_json::json!({"switch": "off"}),
+            issued_at_ms: 1_500,
+        };
+
+        let rule_ctx = TenantContext::new(
+            "tenant-1".to_string(),
+            "rule:rule-1".to_string(),
+            Vec::new(),
+            Vec::new(),
+            Some("project-1".to_string()),
+        );
+        let blocked = service.issue_command(&rule_ctx, request.clone()).await;
+        assert!(matches!(blocked, Err(crate::ControlError::Maintenance(_))));
+
+        let command = service
+            .issue_command(&ctx(), request)
+            .await
+            .expect("manual override");
+        assert_eq!(command.status, "accepted");
+
+        let logs = audit_store
+            .list_audit_logs(&ctx(), "project-1", None, None, 10)
+            .await
+            .expect("audit");
+        let actions: Vec<&str> = logs.iter().map(|log| log.action.as_str()).collect();
+        assert!(actions.contains(&"CONTROL.COMMAND.BLOCKED"));
+        assert!(actions.contains(&"CONTROL.COMMAND.MAINTENANCE_OVERRIDE"));
+    }
+}
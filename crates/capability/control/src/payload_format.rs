@@ -0,0 +1,595 @@
+//! 命令载荷格式（按网关配置，下发时由 MqttDispatcher 编码）。
+//!
+//! - `envelope`（默认）：`{ commandId, target, issuedAtMs, payload }` JSON 信封
+//! - `raw`：原样透传命令 payload（JSON 字符串取其内容，其余为 JSON 文本）
+//! - `template`：厂商文本模板，占位符替换后发布
+//! - `hex`：十六进制帧模板，占位符替换后解码为字节，可追加校验
+//!
+//! 占位符：`{command_id}`、`{target}`、`{issued_at_ms}`、`{payload}`（整个 payload）、
+//! `{payload.a.b}`（payload 对象字段，字符串不带引号）。其余花括号按原文保留，JSON 模板无需转义。
+//! `hex` 模板中的占位符可带宽度 `{payload.value:u16}`（`u8` / `i8` / `u16` / `i16` / `u32` / `i32`，
+//! 默认大端，`u16le` 等为小端），数值按宽度编码为十六进制；不带宽度时替换内容须为十六进制文本。
+//!
+//! 命令 target 为网关 ID 时取该网关的格式，为设备 ID 时取设备所属网关的格式；均未配置时使用 `envelope`。
+
+use crate::{CommandDispatch, ControlError};
+use domain::TenantContext;
+use ems_storage::{DeviceStore, GatewayStore};
+use std::sync::Arc;
+
+/// `hex` 帧校验。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HexChecksum {
+    /// Modbus RTU CRC16（低字节在前）
+    Crc16Modbus,
+    /// 逐字节累加和取低 8 位
+    Sum8,
+}
+
+impl HexChecksum {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Crc16Modbus => "crc16_modbus",
+            Self::Sum8 => "sum8",
+        }
+    }
+
+    fn append(self, frame: &mut Vec<u8>) {
+        match self {
+            Self::Crc16Modbus => {
+                let crc = crc16_modbus(frame);
+                frame.extend_from_slice(&crc.to_le_bytes());
+            }
+            Self::Sum8 => {
+                let sum = frame.iter().fold(0u8, |acc, byte| acc.wrapping_add(*byte));
+                frame.push(sum);
+            }
+        }
+    }
+}
+
+/// 命令载荷格式。
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "format", rename_all = "snake_case")]
+pub enum CommandPayloadFormat {
+    #[default]
+    Envelope,
+    Raw,
+    Template {
+        template: String,
+    },
+    Hex {
+        template: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        checksum: Option<HexChecksum>,
+    },
+}
+
+impl CommandPayloadFormat {
+    /// 按格式名与参数构造并校验模板
+    pub fn parse(
+        format: &str,
+        template: Option<&str>,
+        checksum: Option<&str>,
+    ) -> Result<Self, ControlError> {
+        let template = template.map(str::to_string);
+        let value = match format {
+            "envelope" | "raw" | "template" => serde_json::json!({
+                "format": format,
+                "template": template,
+            }),
+            "hex" => serde_json::json!({
+                "format": format,
+                "template": template,
+                "checksum": checksum,
+            }),
+            other => {
+                return Err(ControlError::Payload(format!(
+                    "unknown payload format {other}, expected envelope, raw, template or hex"
+                )));
+            }
+        };
+        if checksum.is_some() && format != "hex" {
+            return Err(ControlError::Payload(
+                "checksum is only supported by hex format".to_string(),
+            ));
+        }
+        let parsed: Self =
+            serde_json::from_value(value).map_err(|err| ControlError::Payload(err.to_string()))?;
+        parsed.validate()?;
+        Ok(parsed)
+    }
+
+    /// 从存储的 JSON 文本解析
+    pub fn from_json(json: &str) -> Result<Self, ControlError> {
+        let parsed: Self =
+            serde_json::from_str(json).map_err(|err| ControlError::Payload(err.to_string()))?;
+        parsed.validate()?;
+        Ok(parsed)
+    }
+
+    /// 序列化为存储的 JSON 文本
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// 格式名
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Envelope => "envelope",
+            Self::Raw => "raw",
+            Self::Template { .. } => "template",
+            Self::Hex { .. } => "hex",
+        }
+    }
+
+    /// 模板（`template` / `hex`）
+    pub fn template(&self) -> Option<&str> {
+        match self {
+            Self::Template { template } | Self::Hex { template, .. } => Some(template),
+            _ => None,
+        }
+    }
+
+    /// 帧校验（`hex`）
+    pub fn checksum(&self) -> Option<HexChecksum> {
+        match self {
+            Self::Hex { checksum, .. } => *checksum,
+            _ => None,
+        }
+    }
+
+    fn validate(&self) -> Result<(), ControlError> {
+        match self {
+            Self::Envelope | Self::Raw => Ok(()),
+            Self::Template { template } => {
+                if template.is_empty() {
+                    return Err(ControlError::Payload("template is empty".to_string()));
+                }
+                for segment in parse_template(template) {
+                    if let Segment::Placeholder { width: Some(_), .. } = segment {
+                        return Err(ControlError::Payload(
+                            "placeholder width is only supported by hex format".to_string(),
+                        ));
+                    }
+                }
+                Ok(())
+            }
+            Self::Hex { template, .. } => {
+                let segments = parse_template(template);
+                if segments.is_empty() {
+                    return Err(ControlError::Payload("template is empty".to_string()));
+                }
+                for segment in segments {
+                    if let Segment::Literal(text) = segment
+                        && !text
+                            .chars()
+                            .all(|ch| ch.is_ascii_hexdigit() || ch.is_ascii_whitespace())
+                    {
+                        return Err(ControlError::Payload(format!(
+                            "hex template contains non-hex text {text:?}"
+                        )));
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// 编码命令载荷
+    pub fn encode(&self, command: &CommandDispatch) -> Result<Vec<u8>, ControlError> {
+        let payload = payload_value(command);
+        match self {
+            Self::Envelope => envelope_payload(command, payload),
+            Self::Raw => Ok(match payload {
+                serde_json::Value::String(text) => text.into_bytes(),
+                other => other.to_string().into_bytes(),
+            }),
+            Self::Template { template } => {
+                render(template, command, &payload, false).map(String::into_bytes)
+            }
+            Self::Hex { template, checksum } => {
+                let text = render(template, command, &payload, true)?;
+                let mut frame = decode_hex(&text)?;
+                if let Some(checksum) = checksum {
+                    checksum.append(&mut frame);
+                }
+                Ok(frame)
+            }
+        }
+    }
+}
+
+/// 按命令 target 查找所属网关的载荷格式
+pub struct GatewayPayloadFormats {
+    gateway_store: Arc<dyn GatewayStore>,
+    device_store: Arc<dyn DeviceStore>,
+}
+
+impl GatewayPayloadFormats {
+    pub fn new(gateway_store: Arc<dyn GatewayStore>, device_store: Arc<dyn DeviceStore>) -> Self {
+        Self {
+            gateway_store,
+            device_store,
+        }
+    }
+
+    /// target 为网关 ID 时取该网关的格式，为设备 ID 时取设备所属网关的格式，否则为默认格式
+    pub async fn format_for_target(
+        &self,
+        tenant_id: &str,
+        project_id: &str,
+        target: &str,
+    ) -> Result<CommandPayloadFormat, ControlError> {
+        let ctx = TenantContext::new(
+            tenant_id.to_string(),
+            "system".to_string(),
+            Vec::new(),
+            Vec::new(),
+            Some(project_id.to_string()),
+        );
+        let gateway_id = if self
+            .gateway_store
+            .find_gateway(&ctx, project_id, target)
+            .await?
+            .is_some()
+        {
+            target.to_string()
+        } else {
+            match self
+                .device_store
+                .find_device(&ctx, project_id, target)
+                .await?
+            {
+                Some(device) => device.gateway_id,
+                None => return Ok(CommandPayloadFormat::default()),
+            }
+        };
+        match self
+            .gateway_store
+            .find_gateway_command_format(&ctx, project_id, &gateway_id)
+            .await?
+        {
+            Some(json) => CommandPayloadFormat::from_json(&json),
+            None => Ok(CommandPayloadFormat::default()),
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CommandMqttEnvelope<'a> {
+    command_id: &'a str,
+    target: &'a str,
+    issued_at_ms: i64,
+    payload: serde_json::Value,
+}
+
+fn envelope_payload(
+    command: &CommandDispatch,
+    payload: serde_json::Value,
+) -> Result<Vec<u8>, ControlError> {
+    let envelope = CommandMqttEnvelope {
+        command_id: &command.command_id,
+        target: &command.target,
+        issued_at_ms: command.issued_at_ms,
+        payload,
+    };
+    serde_json::to_vec(&envelope).map_err(|err| ControlError::Payload(err.to_string()))
+}
+
+fn payload_value(command: &CommandDispatch) -> serde_json::Value {
+    serde_json::from_str(&command.payload)
+        .unwrap_or_else(|_| serde_json::Value::String(command.payload.clone()))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Width {
+    bytes: usize,
+    signed: bool,
+    little_endian: bool,
+}
+
+impl Width {
+    fn parse(spec: &str) -> Option<Self> {
+        let (spec, little_endian) = match spec.strip_suffix("le") {
+            Some(spec) => (spec, true),
+            None => (spec, false),
+        };
+        let (signed, bits) = match spec.split_at_checked(1)? {
+            ("u", bits) => (false, bits),
+            ("i", bits) => (true, bits),
+            _ => return None,
+        };
+        let bytes = match bits {
+            "8" if !little_endian => 1,
+            "16" => 2,
+            "32" => 4,
+            _ => return None,
+        };
+        Some(Self {
+            bytes,
+            signed,
+            little_endian,
+        })
+    }
+
+    fn encode(self, value: i64) -> Option<String> {
+        let bits = (self.bytes * 8) as u32;
+        let (min, max) = if self.signed {
+            (-(1i64 << (bits - 1)), (1i64 << (bits - 1)) - 1)
+        } else {
+            (0, (1i64 << bits) - 1)
+        };
+        if value < min || value > max {
+            return None;
+        }
+        let raw = (value as u64) & ((1u64 << bits) - 1);
+        let mut bytes: Vec<u8> = (0..self.bytes)
+            .rev()
+            .map(|index| (raw >> (index * 8)) as u8)
+            .collect();
+        if self.little_endian {
+            bytes.reverse();
+        }
+        Some(bytes.iter().map(|byte| format!("{byte:02X}")).collect())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Segment<'a> {
+    Literal(&'a str),
+    Placeholder { name: &'a str, width: Option<Width> },
+}
+
+/// 拆分模板；只有形如 `{name}` / `{name:width}` 且名称合法的片段视为占位符
+fn parse_template(template: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut literal_start = 0;
+    let mut pos = 0;
+    while let Some(offset) = template[pos..].find('{') {
+        let open = pos + offset;
+        let Some(close) = template[open..].find('}').map(|index| open + index) else {
+            break;
+        };
+        let inner = &template[open + 1..close];
+        let (name, width) = match inner.split_once(':') {
+            Some((name, spec)) => (name, Width::parse(spec).map(Some)),
+            None => (inner, Some(None)),
+        };
+        match width {
+            Some(width) if is_placeholder_name(name) => {
+                if literal_start < open {
+                    segments.push(Segment::Literal(&template[literal_start..open]));
+                }
+                segments.push(Segment::Placeholder { name, width });
+                literal_start = close + 1;
+                pos = close + 1;
+            }
+            _ => pos = open + 1,
+        }
+    }
+    if literal_start < template.len() {
+        segments.push(Segment::Literal(&template[literal_start..]));
+    }
+    segments
+}
+
+fn is_placeholder_name(name: &str) -> bool {
+    match name {
+        "command_id" | "target" | "issued_at_ms" | "payload" => true,
+        _ => name.strip_prefix("payload.").is_some_and(|path| {
+            path.split('.').all(|key| {
+                !key.is_empty()
+                    && key
+                        .chars()
+                        .all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+            })
+        }),
+    }
+}
+
+fn render(
+    template: &str,
+    command: &CommandDispatch,
+    payload: &serde_json::Value,
+    hex: bool,
+) -> Result<String, ControlError> {
+    let mut output = String::new();
+    for segment in parse_template(template) {
+        match segment {
+            Segment::Literal(text) => output.push_str(text),
+            Segment::Placeholder { name, width } => {
+                let value = placeholder_value(name, command, payload)?;
+                match width {
+                    Some(width) => {
+                        let number = integer_value(&value).ok_or_else(|| {
+                            ControlError::Payload(format!("{name} is not an integer"))
+                        })?;
+                        let encoded = width.encode(number).ok_or_else(|| {
+                            ControlError::Payload(format!("{name} out of range: {number}"))
+                        })?;
+                        output.push_str(&encoded);
+                    }
+                    None => {
+                        let text = match value {
+                            serde_json::Value::String(text) => text,
+                            other => other.to_string(),
+                        };
+                        if hex
+                            && !text
+                                .chars()
+                                .all(|ch| ch.is_ascii_hexdigit() || ch.is_ascii_whitespace())
+                        {
+                            return Err(ControlError::Payload(format!(
+                                "{name} is not hex text: {text:?}"
+                            )));
+                        }
+                        output.push_str(&text);
+                    }
+                }
+            }
+        }
+    }
+    Ok(output)
+}
+
+fn placeholder_value(
+    name: &str,
+    command: &CommandDispatch,
+    payload: &serde_json::Value,
+) -> Result<serde_json::Value, ControlError> {
+    let value = match name {
+        "command_id" => serde_json::Value::from(command.command_id.as_str()),
+        "target" => serde_json::Value::from(command.target.as_str()),
+        "issued_at_ms" => serde_json::Value::from(command.issued_at_ms),
+        "payload" => payload.clone(),
+        _ => {
+            let path = name.trim_start_matches("payload.");
+            let mut current = payload;
+            for key in path.split('.') {
+                current = current.get(key).ok_or_else(|| {
+                    ControlError::Payload(format!("payload field {path} is missing"))
+                })?;
+            }
+            current.clone()
+        }
+    };
+    Ok(value)
+}
+
+fn integer_value(value: &serde_json::Value) -> Option<i64> {
+    match value {
+        serde_json::Value::Bool(flag) => Some(i64::from(*flag)),
+        serde_json::Value::Number(number) => number.as_i64().or_else(|| {
+            number
+                .as_f64()
+                .filter(|value| value.fract() == 0.0 && value.abs() < 9.0e15)
+                .map(|value| value as i64)
+        }),
+        serde_json::Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn decode_hex(text: &str) -> Result<Vec<u8>, ControlError> {
+    let digits: Vec<u8> = text
+        .bytes()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .collect();
+    if !digits.len().is_multiple_of(2) {
+        return Err(ControlError::Payload(
+            "hex frame has an odd number of digits".to_string(),
+        ));
+    }
+    digits
+        .chunks(2)
+        .map(|pair| {
+            std::str::from_utf8(pair)
+                .ok()
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(|| ControlError::Payload("invalid hex digit".to_string()))
+        })
+        .collect()
+}
+
+fn crc16_modbus(bytes: &[u8]) -> u16 {
+    let mut crc = 0xFFFFu16;
+    for byte in bytes {
+        crc ^= u16::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xA001
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(payload: &str) -> CommandDispatch {
+        CommandDispatch {
+            command_id: "cmd-1".to_string(),
+            tenant_id: "tenant-1".to_string(),
+            project_id: "project-1".to_string(),
+            target: "device-1".to_string(),
+            payload: payload.to_string(),
+            issued_at_ms: 1_000,
+        }
+    }
+
+    #[test]
+    fn envelope_raw_and_template_formats() {
+        let cmd = command(r#"{"action":"start","setpoint":42}"#);
+        let envelope = CommandPayloadFormat::default()
+            .encode(&cmd)
+            .expect("envelope");
+        let value: serde_json::Value = serde_json::from_slice(&envelope).expect("json");
+        assert_eq!(value["commandId"], "cmd-1");
+        assert_eq!(value["payload"]["setpoint"], 42);
+
+        let raw = CommandPayloadFormat::Raw.encode(&cmd).expect("raw");
+        assert_eq!(raw, br#"{"action":"start","setpoint":42}"#);
+        let raw = CommandPayloadFormat::Raw
+            .encode(&command(r#""RUN 1""#))
+            .expect("raw string");
+        assert_eq!(raw, b"RUN 1");
+
+        let template = CommandPayloadFormat::parse(
+            "template",
+            Some(r#"{"id":"{command_id}","op":"{payload.action}","sp":{payload.setpoint},"x":{}}"#),
+            None,
+        )
+        .expect("template");
+        assert_eq!(
+            template.encode(&cmd).expect("render"),
+            br#"{"id":"cmd-1","op":"start","sp":42,"x":{}}"#
+        );
+        let err = CommandPayloadFormat::parse("template", Some("{payload.missing}"), None)
+            .expect("template")
+            .encode(&cmd)
+            .expect_err("missing field");
+        assert!(err.to_string().contains("payload field missing is missing"));
+    }
+
+    #[test]
+    fn hex_frames_with_width_and_checksum() {
+        // Modbus RTU 写单个寄存器：从站 1，寄存器 1，值 3
+        let format = CommandPayloadFormat::parse(
+            "hex",
+            Some("01 06 {payload.register:u16} {payload.value:u16}"),
+            Some("crc16_modbus"),
+        )
+        .expect("hex");
+        let frame = format
+            .encode(&command(r#"{"register":1,"value":3}"#))
+            .expect("frame");
+        assert_eq!(frame, vec![0x01, 0x06, 0x00, 0x01, 0x00, 0x03, 0x98, 0x0B]);
+
+        let format = CommandPayloadFormat::parse("hex", Some("AA{payload.v:i16le}"), Some("sum8"))
+            .expect("hex");
+        let frame = format.encode(&command(r#"{"v":-2}"#)).expect("frame");
+        assert_eq!(frame, vec![0xAA, 0xFE, 0xFF, 0xA7]);
+
+        let err = format
+            .encode(&command(r#"{"v":40000}"#))
+            .expect_err("out of range");
+        assert!(err.to_string().contains("out of range"));
+        assert!(CommandPayloadFormat::parse("hex", Some("01 ZZ"), None).is_err());
+        assert!(CommandPayloadFormat::parse("template", Some("{payload:u8}"), None).is_err());
+        assert!(CommandPayloadFormat::parse("raw", None, Some("sum8")).is_err());
+        assert!(CommandPayloadFormat::parse("modbus", None, None).is_err());
+
+        let stored = format.to_json();
+        assert_eq!(
+            CommandPayloadFormat::from_json(&stored).expect("roundtrip"),
+            format
+        );
+    }
+}
@@ -0,0 +1,402 @@
+//! 设备影子（期望状态 vs 上报状态）。
+//!
+//! - 期望状态（desired）：`点位 key → 期望值`，经 API 设置，按设备版本化保存
+//! - 上报状态（reported）：由设备点位的实时值推导；最近一次差量命令收到 `success` 回执后，
+//!   回执时间之后尚无新实时值的点位按期望值视为已上报
+//! - 差量（delta）：期望值与上报值不一致（或尚未上报）的点位
+//!
+//! 设置期望状态后若差量非空，经 `CommandService` 以命令形式下发到设备（target 为设备 ID），
+//! 命令载荷为 `{"shadow": {"version": n, "delta": {...}}}`，下发 / 回执 / 超时沿用命令链路。
+
+use crate::{CommandRequest, CommandService, ControlError};
+use domain::TenantContext;
+use ems_storage::{
+    CommandReceiptStore, DeviceShadowRecord, DeviceShadowStore, PointRecord, PointStore,
+    RealtimeRecord, RealtimeStore,
+};
+use serde_json::{Map, Value, json};
+use std::sync::Arc;
+use tracing::info;
+
+/// 设备影子视图（期望 + 上报 + 差量）。
+#[derive(Debug, Clone)]
+pub struct DeviceShadow {
+    pub project_id: String,
+    pub device_id: String,
+    /// 期望状态版本（未设置过为 0）
+    pub version: i64,
+    pub desired: Map<String, Value>,
+    pub reported: Map<String, Value>,
+    /// 上报状态中最新的时间戳
+    pub reported_at_ms: Option<i64>,
+    pub delta: Map<String, Value>,
+    /// 最近一次下发差量的命令 ID
+    pub last_command_id: Option<String>,
+    /// 最近一次差量命令的最新回执状态
+    pub last_command_status: Option<String>,
+    pub updated_by: Option<String>,
+    pub updated_at_ms: Option<i64>,
+}
+
+/// 设备影子服务（期望状态存储 + 上报推导 + 差量下发）。
+#[derive(Clone)]
+pub struct DeviceShadowService {
+    shadow_store: Arc<dyn DeviceShadowStore>,
+    point_store: Arc<dyn PointStore>,
+    realtime_store: Arc<dyn RealtimeStore>,
+    receipt_store: Arc<dyn CommandReceiptStore>,
+    command_service: Arc<CommandService>,
+}
+
+impl DeviceShadowService {
+    pub fn new(
+        shadow_store: Arc<dyn DeviceShadowStore>,
+        point_store: Arc<dyn PointStore>,
+        realtime_store: Arc<dyn RealtimeStore>,
+        receipt_store: Arc<dyn CommandReceiptStore>,
+        command_service: Arc<CommandService>,
+    ) -> Self {
+        Self {
+            shadow_store,
+            point_store,
+            realtime_store,
+            receipt_store,
+            command_service,
+        }
+    }
+
+    /// 查询设备影子（设备存在性由调用方校验）。
+    pub async fn get_shadow(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        device_id: &str,
+    ) -> Result<DeviceShadow, ControlError> {
+        let record = self
+            .shadow_store
+            .get_device_shadow(ctx, project_id, device_id)
+            .await?;
+        let points = self.device_points(ctx, project_id, device_id).await?;
+        self.build_shadow(ctx, project_id, device_id, record, &points)
+            .await
+    }
+
+    /// 设置期望状态（整体替换），差量非空时下发到设备。
+    ///
+    /// `desired` 必须为 JSON 对象，键为设备点位 key，值为数值 / 布尔 / 字符串。
+    pub async fn set_desired(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        device_id: &str,
+        desired: Value,
+        updated_at_ms: i64,
+    ) -> Result<DeviceShadow, ControlError> {
+        let points = self.device_points(ctx, project_id, device_id).await?;
+        let desired = validate_desired(desired, &points)?;
+        let document = serde_json::to_string(&Value::Object(desired))
+            .map_err(|err| ControlError::Payload(err.to_string()))?;
+        let record = DeviceShadowRecord {
+            tenant_id: ctx.tenant_id.clone(),
+            project_id: project_id.to_string(),
+            device_id: device_id.to_string(),
+            desired: document,
+            version: 0,
+            last_command_id: None,
+            updated_by: ctx.user_id.clone(),
+            updated_at_ms,
+        };
+        let record = self.shadow_store.put_desired_state(ctx, record).await?;
+        let version = record.version;
+        let mut shadow = self
+            .build_shadow(ctx, project_id, device_id, Some(record), &points)
+            .await?;
+        info!(
+            target: "ems.control",
+            tenant_id = %ctx.tenant_id,
+            project_id = %project_id,
+            device_id = %device_id,
+            version = version,
+            delta_keys = shadow.delta.len(),
+            "device_shadow_desired_updated"
+        );
+        if shadow.delta.is_empty() {
+            return Ok(shadow);
+        }
+
+        let request = CommandRequest {
+            project_id: project_id.to_string(),
+            target: device_id.to_string(),
+            payload: json!({
+                "shadow": {
+                    "version": version,
+                    "delta": Value::Object(shadow.delta.clone()),
+                }
+            }),
+            issued_at_ms: updated_at_ms,
+        };
+        let command = self.command_service.issue_command(ctx, request).await?;
+        self.shadow_store
+            .set_shadow_command(ctx, project_id, device_id, version, &command.command_id)
+            .await?;
+        shadow.last_command_id = Some(command.command_id);
+        shadow.last_command_status = Some(command.status);
+        Ok(shadow)
+    }
+
+    async fn device_points(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        device_id: &str,
+    ) -> Result<Vec<PointRecord>, ControlError> {
+        let points = self.point_store.list_points(ctx, project_id).await?;
+        Ok(points
+            .into_iter()
+            .filter(|point| point.device_id == device_id)
+            .collect())
+    }
+
+    async fn build_shadow(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        device_id: &str,
+        record: Option<DeviceShadowRecord>,
+        points: &[PointRecord],
+    ) -> Result<DeviceShadow, ControlError> {
+        let desired = match &record {
+            Some(record) => parse_desired(&record.desired)?,
+            None => Map::new(),
+        };
+        let point_ids: Vec<String> = points.iter().map(|point| point.point_id.clone()).collect();
+        let values = if point_ids.is_empty() {
+            Vec::new()
+        } else {
+            self.realtime_store
+                .get_last_values(ctx, project_id, &point_ids)
+                .await?
+        };
+        let last_command_id = record
+            .as_ref()
+            .and_then(|record| record.last_command_id.clone());
+        let mut last_command_status = None;
+        let mut acked_at_ms = None;
+        if let Some(command_id) = &last_command_id {
+            let receipts = self
+                .receipt_store
+                .list_receipts(ctx, project_id, command_id)
+                .await?;
+            if let Some(latest) = receipts.into_iter().max_by_key(|receipt| receipt.ts_ms) {
+                if latest.status == "success" {
+                    acked_at_ms = Some(latest.ts_ms);
+                }
+                last_command_status = Some(latest.status);
+            }
+        }
+        let (reported, reported_at_ms) = reported_state(points, &values, &desired, acked_at_ms);
+        let delta = compute_delta(&desired, &reported);
+        Ok(DeviceShadow {
+            project_id: project_id.to_string(),
+            device_id: device_id.to_string(),
+            version: record.as_ref().map(|record| record.version).unwrap_or(0),
+            desired,
+            reported,
+            reported_at_ms,
+            delta,
+            last_command_id,
+            last_command_status,
+            updated_by: record.as_ref().map(|record| record.updated_by.clone()),
+            updated_at_ms: record.as_ref().map(|record| record.updated_at_ms),
+        })
+    }
+}
+
+fn parse_desired(document: &str) -> Result<Map<String, Value>, ControlError> {
+    match serde_json::from_str(document) {
+        Ok(Value::Object(map)) => Ok(map),
+        Ok(_) => Err(ControlError::Payload(
+            "desired must be an object".to_string(),
+        )),
+        Err(err) => Err(ControlError::Payload(err.to_string())),
+    }
+}
+
+/// 校验期望状态：JSON 对象，键为设备点位 key，值为标量。
+fn validate_desired(
+    desired: Value,
+    points: &[PointRecord],
+) -> Result<Map<String, Value>, ControlError> {
+    let Value::Object(desired) = desired else {
+        return Err(ControlError::Payload(
+            "desired must be an object".to_string(),
+        ));
+    };
+    for (key, value) in &desired {
+        if !points.iter().any(|point| &point.key == key) {
+            return Err(ControlError::Payload(format!("unknown point key: {key}")));
+        }
+        if !matches!(value, Value::Number(_) | Value::Bool(_) | Value::String(_)) {
+            return Err(ControlError::Payload(format!(
+                "desired value for {key} must be a number, boolean or string"
+            )));
+        }
+    }
+    Ok(desired)
+}
+
+/// 推导上报状态：按点位 key 取实时值；差量命令已成功回执且回执之后无新实时值的期望键，
+/// 按期望值视为已上报。返回上报状态与其中最新的时间戳。
+fn reported_state(
+    points: &[PointRecord],
+    values: &[RealtimeRecord],
+    desired: &Map<String, Value>,
+    acked_at_ms: Option<i64>,
+) -> (Map<String, Value>, Option<i64>) {
+    let mut reported = Map::new();
+    let mut reported_at_ms: Option<i64> = None;
+    for point in points {
+        let value = values.iter().find(|value| value.point_id == point.point_id);
+        let acked = match (acked_at_ms, desired.get(&point.key)) {
+            (Some(acked_at_ms), Some(desired)) => match value {
+                Some(value) if value.ts_ms >= acked_at_ms => None,
+                _ => Some((desired.clone(), acked_at_ms)),
+            },
+            _ => None,
+        };
+        let entry = match (acked, value) {
+            (Some(acked), _) => acked,
+            (None, Some(value)) => (parse_reported(&value.value), value.ts_ms),
+            (None, None) => continue,
+        };
+        reported.insert(point.key.clone(), entry.0);
+        reported_at_ms = Some(reported_at_ms.map_or(entry.1, |ts| ts.max(entry.1)));
+    }
+    (reported, reported_at_ms)
+}
+
+/// 实时值文本尽量还原为数值 / 布尔，其余保留为字符串。
+fn parse_reported(value: &str) -> Value {
+    match serde_json::from_str::<Value>(value.trim()) {
+        Ok(parsed @ (Value::Number(_) | Value::Bool(_))) => parsed,
+        _ => Value::String(value.to_string()),
+    }
+}
+
+/// 计算差量：期望值与上报值不一致或尚未上报的键。
+fn compute_delta(
+    desired: &Map<String, Value>,
+    reported: &Map<String, Value>,
+) -> Map<String, Value> {
+    desired
+        .iter()
+        .filter(|(key, value)| {
+            reported
+                .get(key.as_str())
+                .is_none_or(|reported| !values_match(value, reported))
+        })
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
+/// 比较期望值与上报值：数值（含布尔与数字字符串）按相对误差 1e-6 比较，其余按文本比较。
+fn values_match(desired: &Value, reported: &Value) -> bool {
+    match (as_number(desired), as_number(reported)) {
+        (Some(a), Some(b)) => (a - b).abs() <= 1e-6 * a.abs().max(b.abs()).max(1.0),
+        _ => as_text(desired) == as_text(reported),
+    }
+}
+
+fn as_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::Bool(flag) => Some(if *flag { 1.0 } else { 0.0 }),
+        Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn as_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.trim().to_string(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(point_id: &str, key: &str) -> PointRecord {
+        PointRecord {
+            point_id: point_id.to_string(),
+            tenant_id: "tenant-1".to_string(),
+            project_id: "project-1".to_string(),
+            device_id: "device-1".to_string(),
+            key: key.to_string(),
+            data_type: "f64".to_string(),
+            unit: None,
+            tags: Vec::new(),
+        }
+    }
+
+    fn realtime(point_id: &str, ts_ms: i64, value: &str) -> RealtimeRecord {
+        RealtimeRecord {
+            tenant_id: "tenant-1".to_string(),
+            project_id: "project-1".to_string(),
+            point_id: point_id.to_string(),
+            ts_ms,
+            value: value.to_string(),
+            quality: None,
+        }
+    }
+
+    fn object(value: Value) -> Map<String, Value> {
+        match value {
+            Value::Object(map) => map,
+            _ => panic!("object"),
+        }
+    }
+
+    #[test]
+    fn delta_compares_numbers_with_tolerance() {
+        let points = vec![
+            point("p1", "setpoint"),
+            point("p2", "enabled"),
+            point("p3", "mode"),
+        ];
+        let values = vec![
+            realtime("p1", 10, "21.0000001"),
+            realtime("p2", 20, "1"),
+            realtime("p3", 30, "eco"),
+        ];
+        let desired = object(json!({"setpoint": 21, "enabled": true, "mode": "comfort"}));
+        let (reported, reported_at_ms) = reported_state(&points, &values, &desired, None);
+        assert_eq!(reported_at_ms, Some(30));
+        assert_eq!(reported["mode"], "eco");
+        let delta = compute_delta(&desired, &reported);
+        assert_eq!(Value::Object(delta), json!({"mode": "comfort"}));
+    }
+
+    #[test]
+    fn success_receipt_counts_as_reported_until_newer_value() {
+        let points = vec![point("p1", "setpoint"), point("p2", "mode")];
+        let desired = object(json!({"setpoint": 22, "mode": "eco"}));
+        let values = vec![realtime("p1", 10, "20"), realtime("p2", 200, "comfort")];
+        let (reported, _) = reported_state(&points, &values, &desired, Some(100));
+        // p1 的实时值早于回执：视为已应用；p2 回执后有新值：以实时值为准
+        assert_eq!(reported["setpoint"], json!(22));
+        assert_eq!(reported["mode"], "comfort");
+        let delta = compute_delta(&desired, &reported);
+        assert_eq!(Value::Object(delta), json!({"mode": "eco"}));
+    }
+
+    #[test]
+    fn desired_rejects_unknown_keys_and_nested_values() {
+        let points = vec![point("p1", "setpoint")];
+        assert!(validate_desired(json!({"setpoint": 20}), &points).is_ok());
+        assert!(validate_desired(json!({"other": 1}), &points).is_err());
+        assert!(validate_desired(json!({"setpoint": {"v": 1}}), &points).is_err());
+        assert!(validate_desired(json!([1]), &points).is_err());
+    }
+}
@@ -0,0 +1,224 @@
+//! 需求响应（负荷削减）编排
+//!
+//! 给定目标削减量（kW），按优先级从可削减负荷中选择设备并下发削减命令，
+//! 事件窗口内按设备功率点位的实时值跟踪实际削减量，窗口结束或取消后下发恢复命令。
+//!
+//! 可削减负荷与事件保存在 `DemandResponseStore` 中，由本 crate 校验；
+//! `spawn_demand_response_runner` 按固定间隔推进事件状态。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+mod runner;
+pub use runner::*;
+
+/// 事件状态：等待开始
+pub const STATUS_SCHEDULED: &str = "scheduled";
+/// 事件状态：已削减，窗口进行中
+pub const STATUS_ACTIVE: &str = "active";
+/// 事件状态：窗口结束，负荷已恢复
+pub const STATUS_COMPLETED: &str = "completed";
+/// 事件状态：已取消（进行中的事件会先恢复负荷）
+pub const STATUS_CANCELLED: &str = "cancelled";
+/// 事件状态：窗口结束前未能启动（如编排器停机）
+pub const STATUS_EXPIRED: &str = "expired";
+
+/// 单个事件窗口的最大时长（毫秒）
+pub const MAX_EVENT_DURATION_MS: i64 = 24 * 3600 * 1000;
+
+/// 需求响应定义错误。
+#[derive(Debug, thiserror::Error)]
+pub enum DemandResponseError {
+    #[error("invalid load: {0}")]
+    Load(String),
+    #[error("invalid event: {0}")]
+    Event(String),
+}
+
+/// 事件是否未结束（scheduled / active）
+pub fn is_open(status: &str) -> bool {
+    status == STATUS_SCHEDULED || status == STATUS_ACTIVE
+}
+
+/// 校验可削减负荷配置（额定功率非负，削减 / 恢复载荷为 JSON 对象）
+pub fn validate_load(
+    rated_kw: f64,
+    shed_payload: &Value,
+    restore_payload: &Value,
+) -> Result<(), DemandResponseError> {
+    if !rated_kw.is_finite() || rated_kw < 0.0 {
+        return Err(DemandResponseError::Load(
+            "ratedKw must be a non-negative number".to_string(),
+        ));
+    }
+    if !shed_payload.is_object() {
+        return Err(DemandResponseError::Load(
+            "shedPayload must be an object".to_string(),
+        ));
+    }
+    if !restore_payload.is_object() {
+        return Err(DemandResponseError::Load(
+            "restorePayload must be an object".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// 校验事件（目标削减量为正，窗口有效、未结束且不超过 `MAX_EVENT_DURATION_MS`）
+pub fn validate_event(
+    target_kw: f64,
+    start_at_ms: i64,
+    end_at_ms: i64,
+    now_ms: i64,
+) -> Result<(), DemandResponseError> {
+    if !target_kw.is_finite() || target_kw <= 0.0 {
+        return Err(DemandResponseError::Event(
+            "targetKw must be greater than 0".to_string(),
+        ));
+    }
+    if end_at_ms <= start_at_ms {
+        return Err(DemandResponseError::Event(
+            "endAtMs must be after startAtMs".to_string(),
+        ));
+    }
+    if end_at_ms <= now_ms {
+        return Err(DemandResponseError::Event(
+            "endAtMs must be in the future".to_string(),
+        ));
+    }
+    if end_at_ms - start_at_ms > MAX_EVENT_DURATION_MS {
+        return Err(DemandResponseError::Event(format!(
+            "event window must not exceed {MAX_EVENT_DURATION_MS} ms"
+        )));
+    }
+    Ok(())
+}
+
+/// 参与选择的候选负荷。
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadCandidate {
+    pub device_id: String,
+    pub priority: i32,
+    pub power_point_id: String,
+    /// 当前功率（kW，实时值缺失时为额定功率）
+    pub current_kw: f64,
+}
+
+/// 按优先级选择负荷：优先级升序、同优先级功率大者优先，累计功率达到目标即停止；
+/// 功率不大于 0 的负荷不参与选择。可削减负荷不足时返回全部可选负荷。
+pub fn select_loads(mut candidates: Vec<LoadCandidate>, target_kw: f64) -> Vec<LoadCandidate> {
+    candidates.retain(|candidate| candidate.current_kw > 0.0);
+    candidates.sort_by(|a, b| {
+        a.priority
+            .cmp(&b.priority)
+            .then_with(|| b.current_kw.total_cmp(&a.current_kw))
+            .then_with(|| a.device_id.cmp(&b.device_id))
+    });
+    let mut selected = Vec::new();
+    let mut total_kw = 0.0;
+    for candidate in candidates {
+        if total_kw >= target_kw {
+            break;
+        }
+        total_kw += candidate.current_kw;
+        selected.push(candidate);
+    }
+    selected
+}
+
+/// 事件中的已选负荷（以 JSON 数组保存在事件的 `loads` 中）。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventLoad {
+    pub device_id: String,
+    pub priority: i32,
+    pub power_point_id: String,
+    /// 削减前功率（kW）
+    pub baseline_kw: f64,
+    /// 最近一次读取的实时功率（kW）
+    #[serde(default)]
+    pub current_kw: Option<f64>,
+    #[serde(default)]
+    pub shed_command_id: Option<String>,
+    #[serde(default)]
+    pub restore_command_id: Option<String>,
+    /// 削减或恢复命令下发失败的原因
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 实际削减量：已下发削减命令的负荷 Σ(削减前功率 − 最近实时功率)，无实时值的负荷不计入
+pub fn achieved_reduction_kw(loads: &[EventLoad]) -> f64 {
+    loads
+        .iter()
+        .filter(|load| load.shed_command_id.is_some())
+        .filter_map(|load| load.current_kw.map(|current| load.baseline_kw - current))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn candidate(device_id: &str, priority: i32, current_kw: f64) -> LoadCandidate {
+        LoadCandidate {
+            device_id: device_id.to_string(),
+            priority,
+            power_point_id: format!("{device_id}-power"),
+            current_kw,
+        }
+    }
+
+    #[test]
+    fn select_loads_by_priority_until_target() {
+        let candidates = vec![
+            candidate("chiller", 2, 50.0),
+            candidate("lighting", 1, 8.0),
+            candidate("fountain", 1, 12.0),
+            candidate("idle-pump", 0, 0.0),
+            candidate("ev-charger", 3, 40.0),
+        ];
+        let selected: Vec<String> = select_loads(candidates.clone(), 25.0)
+            .into_iter()
+            .map(|load| load.device_id)
+            .collect();
+        assert_eq!(selected, vec!["fountain", "lighting", "chiller"]);
+
+        // 目标超过全部可削减负荷时全部选中（零功率负荷除外）
+        assert_eq!(select_loads(candidates, 500.0).len(), 4);
+    }
+
+    #[test]
+    fn achieved_reduction_counts_shed_loads_with_readings() {
+        let load = |shed: bool, baseline_kw: f64, current_kw: Option<f64>| EventLoad {
+            device_id: "d".to_string(),
+            priority: 1,
+            power_point_id: "p".to_string(),
+            baseline_kw,
+            current_kw,
+            shed_command_id: shed.then(|| "cmd".to_string()),
+            restore_command_id: None,
+            error: None,
+        };
+        let loads = vec![
+            load(true, 10.0, Some(2.0)),
+            load(true, 5.0, None),
+            load(false, 20.0, Some(0.0)),
+        ];
+        assert_eq!(achieved_reduction_kw(&loads), 8.0);
+    }
+
+    #[test]
+    fn validation_rejects_invalid_definitions() {
+        assert!(validate_load(5.0, &json!({"on": false}), &json!({"on": true})).is_ok());
+        assert!(validate_load(-1.0, &json!({}), &json!({})).is_err());
+        assert!(validate_load(1.0, &json!("off"), &json!({})).is_err());
+
+        assert!(validate_event(10.0, 1_000, 2_000, 0).is_ok());
+        assert!(validate_event(0.0, 1_000, 2_000, 0).is_err());
+        assert!(validate_event(10.0, 2_000, 1_000, 0).is_err());
+        assert!(validate_event(10.0, 1_000, 2_000, 3_000).is_err());
+        assert!(validate_event(10.0, 0, MAX_EVENT_DURATION_MS + 1, 0).is_err());
+    }
+}
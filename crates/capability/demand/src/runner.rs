@@ -0,0 +1,628 @@
+//! 需求响应编排器。
+//!
+//! 每次检查推进全部未结束的事件：
+//! - `scheduled`：到达开始时间后选择负荷、下发削减命令并转为 `active`；
+//!   已请求取消的转为 `cancelled`，窗口已结束仍未启动的转为 `expired`
+//! - `active`：读取已选负荷的实时功率并更新实际削减量；
+//!   到达结束时间（或已请求取消）后下发恢复命令并转为 `completed`（`cancelled`）
+//!
+//! 多实例部署时每个实例都会独立推进，需只在一个实例上启用编排器。
+
+use crate::{
+    EventLoad, LoadCandidate, STATUS_ACTIVE, STATUS_CANCELLED, STATUS_COMPLETED, STATUS_EXPIRED,
+    STATUS_SCHEDULED, achieved_reduction_kw, select_loads,
+};
+use domain::TenantContext;
+use ems_control::{CommandRequest, CommandService};
+use ems_storage::{
+    DemandResponseEventRecord, DemandResponseEventUpdate, DemandResponseStore, RealtimeStore,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+use tracing::{info, warn};
+
+/// 需求响应编排器配置
+#[derive(Debug, Clone)]
+pub struct DemandResponseRunnerConfig {
+    /// 检查间隔（毫秒）
+    pub tick_ms: u64,
+}
+
+impl Default for DemandResponseRunnerConfig {
+    fn default() -> Self {
+        Self { tick_ms: 5000 }
+    }
+}
+
+/// 需求响应编排器（选择负荷、下发削减 / 恢复命令、跟踪实际削减量）
+pub struct DemandResponseRunner {
+    store: Arc<dyn DemandResponseStore>,
+    realtime_store: Arc<dyn RealtimeStore>,
+    command_service: Arc<CommandService>,
+}
+
+impl DemandResponseRunner {
+    pub fn new(
+        store: Arc<dyn DemandResponseStore>,
+        realtime_store: Arc<dyn RealtimeStore>,
+        command_service: Arc<CommandService>,
+    ) -> Self {
+        Self {
+            store,
+            realtime_store,
+            command_service,
+        }
+    }
+
+    /// 推进一次全部未结束的事件，返回本次更新后的事件
+    pub async fn run_due(&self, now_ms: i64) -> Vec<DemandResponseEventRecord> {
+        let events = match self.store.list_open_demand_response_events().await {
+            Ok(events) => events,
+            Err(err) => {
+                warn!(target: "ems.demand", error = %err, "demand_response_events_read_failed");
+                return Vec::new();
+            }
+        };
+        let mut updated = Vec::new();
+        for event in events {
+            let update = if event.status == STATUS_SCHEDULED {
+                if event.cancel_requested {
+                    Some(finish_without_loads(STATUS_CANCELLED, now_ms))
+                } else if now_ms >= event.end_at_ms {
+                    Some(finish_without_loads(STATUS_EXPIRED, now_ms))
+                } else if now_ms >= event.start_at_ms {
+                    Some(self.activate(&event, now_ms).await)
+                } else {
+                    None
+                }
+            } else if event.status == STATUS_ACTIVE {
+                if event.cancel_requested {
+                    Some(self.restore(&event, STATUS_CANCELLED, now_ms).await)
+                } else if now_ms >= event.end_at_ms {
+                    Some(self.restore(&event, STATUS_COMPLETED, now_ms).await)
+                } else {
+                    Some(self.track(&event, now_ms).await)
+                }
+            } else {
+                None
+            };
+            let Some(update) = update else {
+                continue;
+            };
+            if let Some(status) = update.status.as_deref() {
+                info!(
+                    target: "ems.demand",
+                    tenant_id = %event.tenant_id,
+                    event_id = %event.event_id,
+                    status = status,
+                    achieved_kw = ?update.achieved_kw,
+                    "demand_response_event_transition"
+                );
+            }
+            let ctx = event_context(&event);
+            match self
+                .store
+                .update_demand_response_event(&ctx, &event.project_id, &event.event_id, update)
+                .await
+            {
+                Ok(Some(record)) => updated.push(record),
+                Ok(None) => {}
+                Err(err) => {
+                    warn!(target: "ems.demand", event_id = %event.event_id, error = %err, "demand_response_event_update_failed");
+                }
+            }
+        }
+        updated
+    }
+
+    /// 选择负荷并下发削减命令（单条命令失败不影响其他负荷）
+    async fn activate(
+        &self,
+        event: &DemandResponseEventRecord,
+        now_ms: i64,
+    ) -> DemandResponseEventUpdate {
+        let ctx = event_context(event);
+        let loads = match self
+            .store
+            .list_sheddable_loads(&ctx, &event.project_id)
+            .await
+        {
+            Ok(loads) => loads,
+            Err(err) => {
+                warn!(target: "ems.demand", event_id = %event.event_id, error = %err, "sheddable_loads_read_failed");
+                Vec::new()
+            }
+        };
+        let loads: Vec<_> = loads.into_iter().filter(|load| load.enabled).collect();
+        let point_ids: Vec<String> = loads
+            .iter()
+            .map(|load| load.power_point_id.clone())
+            .collect();
+        let readings = self.read_power(&ctx, &event.project_id, &point_ids).await;
+        let candidates = loads
+            .iter()
+            .map(|load| LoadCandidate {
+                device_id: load.device_id.clone(),
+                priority: load.priority,
+                power_point_id: load.power_point_id.clone(),
+                current_kw: readings
+                    .get(&load.power_point_id)
+                    .copied()
+                    .unwrap_or(load.rated_kw),
+            })
+            .collect();
+
+        let mut event_loads = Vec::new();
+        for candidate in select_loads(candidates, event.target_kw) {
+            let mut event_load = EventLoad {
+                device_id: candidate.device_id.clone(),
+                priority: candidate.priority,
+                power_point_id: candidate.power_point_id.clone(),
+                baseline_kw: candidate.current_kw,
+                current_kw: None,
+                shed_command_id: None,
+                restore_command_id: None,
+                error: None,
+            };
+            let payload = loads
+                .iter()
+                .find(|load| load.device_id == candidate.device_id)
+                .map(|load| load.shed_payload.as_str())
+                .unwrap_or("{}");
+            match self
+                .issue(
+                    &ctx,
+                    &event.project_id,
+                    &candidate.device_id,
+                    payload,
+                    now_ms,
+                )
+                .await
+            {
+                Ok(command_id) => event_load.shed_command_id = Some(command_id),
+                Err(err) => event_load.error = Some(err),
+            }
+            event_loads.push(event_load);
+        }
+        if event_loads.iter().any(|load| load.error.is_some()) {
+            warn!(target: "ems.demand", event_id = %event.event_id, "demand_response_shed_partially_failed");
+        }
+        let baseline_kw = event_loads
+            .iter()
+            .filter(|load| load.shed_command_id.is_some())
+            .map(|load| load.baseline_kw)
+            .sum();
+        DemandResponseEventUpdate {
+            status: Some(STATUS_ACTIVE.to_string()),
+            baseline_kw: Some(baseline_kw),
+            achieved_kw: Some(0.0),
+            loads: Some(encode_loads(&event_loads)),
+            updated_at_ms: now_ms,
+            ..DemandResponseEventUpdate::default()
+        }
+    }
+
+    /// 读取已选负荷的实时功率并更新实际削减量
+    async fn track(
+        &self,
+        event: &DemandResponseEventRecord,
+        now_ms: i64,
+    ) -> DemandResponseEventUpdate {
+        let mut loads = decode_loads(&event.loads);
+        self.refresh(event, &mut loads).await;
+        DemandResponseEventUpdate {
+            achieved_kw: Some(achieved_reduction_kw(&loads)),
+            loads: Some(encode_loads(&loads)),
+            updated_at_ms: now_ms,
+            ..DemandResponseEventUpdate::default()
+        }
+    }
+
+    /// 记录最终削减量后下发恢复命令
+    async fn restore(
+        &self,
+        event: &DemandResponseEventRecord,
+        status: &str,
+        now_ms: i64,
+    ) -> DemandResponseEventUpdate {
+        let ctx = event_context(event);
+        let mut loads = decode_loads(&event.loads);
+        self.refresh(event, &mut loads).await;
+        let achieved_kw = achieved_reduction_kw(&loads);
+        let stored = match self
+            .store
+            .list_sheddable_loads(&ctx, &event.project_id)
+            .await
+        {
+            Ok(stored) => stored,
+            Err(err) => {
+                warn!(target: "ems.demand", event_id = %event.event_id, error = %err, "sheddable_loads_read_failed");
+                Vec::new()
+            }
+        };
+        for load in loads
+            .iter_mut()
+            .filter(|load| load.shed_command_id.is_some() && load.restore_command_id.is_none())
+        {
+            // 负荷配置已删除时无法获知恢复载荷，记录失败
+            let Some(payload) = stored
+                .iter()
+                .find(|item| item.device_id == load.device_id)
+                .map(|item| item.restore_payload.clone())
+            else {
+                load.error = Some("sheddable load not found".to_string());
+                continue;
+            };
+            match self
+                .issue(&ctx, &event.project_id, &load.device_id, &payload, now_ms)
+                .await
+            {
+                Ok(command_id) => load.restore_command_id = Some(command_id),
+                Err(err) => load.error = Some(err),
+            }
+        }
+        if loads.iter().any(|load| load.error.is_some()) {
+            warn!(target: "ems.demand", event_id = %event.event_id, "demand_response_restore_partially_failed");
+        }
+        DemandResponseEventUpdate {
+            status: Some(status.to_string()),
+            achieved_kw: Some(achieved_kw),
+            loads: Some(encode_loads(&loads)),
+            updated_at_ms: now_ms,
+            ..DemandResponseEventUpdate::default()
+        }
+    }
+
+    /// 用实时值刷新已削减负荷的当前功率（无新值时保留上次读数）
+    async fn refresh(&self, event: &DemandResponseEventRecord, loads: &mut [EventLoad]) {
+        let ctx = event_context(event);
+        let point_ids: Vec<String> = loads
+            .iter()
+            .filter(|load| load.shed_command_id.is_some())
+            .map(|load| load.power_point_id.clone())
+            .collect();
+        if point_ids.is_empty() {
+            return;
+        }
+        let readings = self.read_power(&ctx, &event.project_id, &point_ids).await;
+        for load in loads.iter_mut() {
+            if let Some(current_kw) = readings.get(&load.power_point_id) {
+                load.current_kw = Some(*current_kw);
+            }
+        }
+    }
+
+    /// 读取功率点位实时值（非数值的点位忽略）
+    async fn read_power(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        point_ids: &[String],
+    ) -> HashMap<String, f64> {
+        if point_ids.is_empty() {
+            return HashMap::new();
+        }
+        match self
+            .realtime_store
+            .get_last_values(ctx, project_id, point_ids)
+            .await
+        {
+            Ok(records) => records
+                .into_iter()
+                .filter_map(|record| {
+                    let value = record.value.trim().parse::<f64>().ok()?;
+                    value.is_finite().then_some((record.point_id, value))
+                })
+                .collect(),
+            Err(err) => {
+                warn!(target: "ems.demand", project_id = %project_id, error = %err, "demand_response_realtime_read_failed");
+                HashMap::new()
+            }
+        }
+    }
+
+    async fn issue(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        device_id: &str,
+        payload: &str,
+        now_ms: i64,
+    ) -> Result<String, String> {
+        let payload: serde_json::Value =
+            serde_json::from_str(payload).map_err(|err| err.to_string())?;
+        self.command_service
+            .issue_command(
+                ctx,
+                CommandRequest {
+                    project_id: project_id.to_string(),
+                    target: device_id.to_string(),
+                    payload,
+                    issued_at_ms: now_ms,
+                },
+            )
+            .await
+            .map(|issued| issued.command_id)
+            .map_err(|err| err.to_string())
+    }
+}
+
+/// 启动需求响应编排器后台任务
+pub fn spawn_demand_response_runner(
+    runner: Arc<DemandResponseRunner>,
+    config: &DemandResponseRunnerConfig,
+) -> tokio::task::JoinHandle<()> {
+    let tick_ms = config.tick_ms.max(1);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_millis(tick_ms));
+        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        loop {
+            interval.tick().await;
+            runner.run_due(now_epoch_ms()).await;
+        }
+    })
+}
+
+fn finish_without_loads(status: &str, now_ms: i64) -> DemandResponseEventUpdate {
+    DemandResponseEventUpdate {
+        status: Some(status.to_string()),
+        updated_at_ms: now_ms,
+        ..DemandResponseEventUpdate::default()
+    }
+}
+
+fn decode_loads(value: &str) -> Vec<EventLoad> {
+    serde_json::from_str(value).unwrap_or_default()
+}
+
+fn encode_loads(loads: &[EventLoad]) -> String {
+    serde_json::to_string(loads).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// 事件执行上下文（命令与审计记录中的操作人为 `demand-response:{event_id}`）
+fn event_context(event: &DemandResponseEventRecord) -> TenantContext {
+    TenantContext::new(
+        event.tenant_id.clone(),
+        format!("demand-response:{}", event.event_id),
+        Vec::new(),
+        Vec::new(),
+        Some(event.project_id.clone()),
+    )
+}
+
+fn now_epoch_ms() -> i64 {
+    let now = std::time::SystemTime::now();
+    let duration = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    duration.as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::{PointValue, PointValueData};
+    use ems_control::NoopDispatcher;
+    use ems_storage::{
+        CommandQueryOptions, CommandStore, InMemoryAuditLogStore, InMemoryCommandStore,
+        InMemoryDemandResponseStore, InMemoryRealtimeStore, SheddableLoadRecord,
+    };
+    use serde_json::json;
+
+    struct Harness {
+        runner: DemandResponseRunner,
+        store: Arc<InMemoryDemandResponseStore>,
+        realtime_store: Arc<InMemoryRealtimeStore>,
+        command_store: Arc<InMemoryCommandStore>,
+    }
+
+    fn harness() -> Harness {
+        let store = Arc::new(InMemoryDemandResponseStore::new());
+        let realtime_store = Arc::new(InMemoryRealtimeStore::new());
+        let command_store = Arc::new(InMemoryCommandStore::new());
+        let command_service = Arc::new(CommandService::new(
+            command_store.clone(),
+            Arc::new(InMemoryAuditLogStore::new()),
+            Arc::new(NoopDispatcher),
+        ));
+        let runner =
+            DemandResponseRunner::new(store.clone(), realtime_store.clone(), command_service);
+        Harness {
+            runner,
+            store,
+            realtime_store,
+            command_store,
+        }
+    }
+
+    fn ctx() -> TenantContext {
+        TenantContext::new(
+            "tenant-1".to_string(),
+            "user-1".to_string(),
+            Vec::new(),
+            Vec::new(),
+            Some("project-1".to_string()),
+        )
+    }
+
+    async fn add_load(h: &Harness, device_id: &str, priority: i32, rated_kw: f64) {
+        h.store
+            .upsert_sheddable_load(
+                &ctx(),
+                SheddableLoadRecord {
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    device_id: device_id.to_string(),
+                    priority,
+                    power_point_id: format!("{device_id}-power"),
+                    rated_kw,
+                    shed_payload: json!({"on": false}).to_string(),
+                    restore_payload: json!({"on": true}).to_string(),
+                    enabled: true,
+                    created_at_ms: 0,
+                    updated_at_ms: 0,
+                },
+            )
+            .await
+            .expect("load");
+    }
+
+    async fn set_power(h: &Harness, device_id: &str, kw: f64) {
+        h.realtime_store
+            .upsert_last_value(
+                &ctx(),
+                &PointValue {
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    point_id: format!("{device_id}-power"),
+                    ts_ms: 0,
+                    value: PointValueData::F64(kw),
+                    quality: None,
+                },
+            )
+            .await
+            .expect("realtime");
+    }
+
+    async fn add_event(h: &Harness, event_id: &str, target_kw: f64, start_at_ms: i64) {
+        h.store
+            .create_demand_response_event(
+                &ctx(),
+                DemandResponseEventRecord {
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    event_id: event_id.to_string(),
+                    target_kw,
+                    start_at_ms,
+                    end_at_ms: start_at_ms + 60_000,
+                    status: STATUS_SCHEDULED.to_string(),
+                    cancel_requested: false,
+                    baseline_kw: None,
+                    achieved_kw: None,
+                    loads: "[]".to_string(),
+                    created_by: "user-1".to_string(),
+                    created_at_ms: 0,
+                    updated_at_ms: 0,
+                },
+            )
+            .await
+            .expect("event");
+    }
+
+    async fn event(h: &Harness, event_id: &str) -> DemandResponseEventRecord {
+        h.store
+            .find_demand_response_event(&ctx(), "project-1", event_id)
+            .await
+            .expect("find")
+            .expect("event")
+    }
+
+    async fn commands(h: &Harness) -> Vec<(String, String)> {
+        let mut items: Vec<(String, String)> = h
+            .command_store
+            .list_commands(&ctx(), "project-1", CommandQueryOptions::simple(50))
+            .await
+            .expect("commands")
+            .into_iter()
+            .map(|command| (command.target, command.payload))
+            .collect();
+        items.sort();
+        items
+    }
+
+    #[tokio::test]
+    async fn event_sheds_by_priority_tracks_reduction_and_restores() {
+        let h = harness();
+        add_load(&h, "lighting", 1, 10.0).await;
+        add_load(&h, "chiller", 2, 80.0).await;
+        add_load(&h, "ev-charger", 3, 40.0).await;
+        set_power(&h, "lighting", 12.0).await;
+        set_power(&h, "chiller", 45.0).await;
+        add_event(&h, "dr-1", 30.0, 1_000).await;
+
+        // 未到开始时间
+        assert!(h.runner.run_due(500).await.is_empty());
+
+        let updated = h.runner.run_due(1_000).await;
+        assert_eq!(updated.len(), 1);
+        let active = event(&h, "dr-1").await;
+        assert_eq!(active.status, STATUS_ACTIVE);
+        assert_eq!(active.baseline_kw, Some(57.0));
+        let loads: Vec<EventLoad> = serde_json::from_str(&active.loads).expect("loads");
+        let devices: Vec<&str> = loads.iter().map(|load| load.device_id.as_str()).collect();
+        assert_eq!(devices, vec!["lighting", "chiller"]);
+        assert!(loads.iter().all(|load| load.shed_command_id.is_some()));
+
+        // 削减后功率下降，按实时值计算实际削减量
+        set_power(&h, "lighting", 2.0).await;
+        set_power(&h, "chiller", 15.0).await;
+        h.runner.run_due(30_000).await;
+        assert_eq!(event(&h, "dr-1").await.achieved_kw, Some(40.0));
+
+        // 窗口结束后恢复负荷
+        h.runner.run_due(61_000).await;
+        let completed = event(&h, "dr-1").await;
+        assert_eq!(completed.status, STATUS_COMPLETED);
+        assert_eq!(completed.achieved_kw, Some(40.0));
+        let loads: Vec<EventLoad> = serde_json::from_str(&completed.loads).expect("loads");
+        assert!(loads.iter().all(|load| load.restore_command_id.is_some()));
+
+        let issued = commands(&h).await;
+        assert_eq!(issued.len(), 4);
+        assert_eq!(
+            issued
+                .iter()
+                .filter(|(target, _)| target == "ev-charger")
+                .count(),
+            0
+        );
+        assert!(h.runner.run_due(62_000).await.is_empty());
+
+        let history = h
+            .command_store
+            .list_commands(&ctx(), "project-1", CommandQueryOptions::simple(1))
+            .await
+            .expect("commands");
+        assert_eq!(history[0].issued_by, "demand-response:dr-1");
+    }
+
+    #[tokio::test]
+    async fn cancel_and_expiry() {
+        let h = harness();
+        add_load(&h, "lighting", 1, 10.0).await;
+        add_event(&h, "scheduled", 5.0, 10_000).await;
+        add_event(&h, "running", 5.0, 0).await;
+        add_event(&h, "missed", 5.0, -120_000).await;
+
+        h.runner.run_due(1_000).await;
+        assert_eq!(event(&h, "running").await.status, STATUS_ACTIVE);
+        assert_eq!(event(&h, "missed").await.status, STATUS_EXPIRED);
+
+        for event_id in ["scheduled", "running"] {
+            h.store
+                .update_demand_response_event(
+                    &ctx(),
+                    "project-1",
+                    event_id,
+                    DemandResponseEventUpdate {
+                        cancel_requested: Some(true),
+                        updated_at_ms: 2_000,
+                        ..DemandResponseEventUpdate::default()
+                    },
+                )
+                .await
+                .expect("cancel");
+        }
+        h.runner.run_due(3_000).await;
+        assert_eq!(event(&h, "scheduled").await.status, STATUS_CANCELLED);
+        assert_eq!(event(&h, "running").await.status, STATUS_CANCELLED);
+
+        // 只有进行中的事件下发了削减与恢复命令
+        let issued = commands(&h).await;
+        assert_eq!(issued.len(), 2);
+        assert!(issued.iter().any(|(_, payload)| payload.contains("false")));
+        assert!(issued.iter().any(|(_, payload)| payload.contains("true")));
+    }
+}
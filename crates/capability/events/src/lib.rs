@@ -0,0 +1,184 @@
+//! 领域事件总线
+//!
+//! 进程内广播领域事件（设备 / 网关离线与恢复、命令完成、告警、网关创建等），
+//! 由 Webhook 推送器等消费者订阅：
+//! - `EventBus`：基于 tokio broadcast 的发布/订阅
+//! - `DomainEvent`：事件信封（事件 ID、类型、租户/项目、发生时间、数据）
+//! - `spawn_webhook_dispatcher`：按订阅推送事件（签名 + 重试 + 推送日志）
+//! - `spawn_device_event_recorder`：与设备相关的事件写入设备时间线
+//!
+//! 发布方不感知消费者；没有订阅者时事件直接丢弃。
+
+use domain::TenantContext;
+use serde::Serialize;
+use tokio::sync::broadcast;
+use tracing::debug;
+
+mod timeline;
+mod webhook;
+pub use timeline::*;
+pub use webhook::*;
+
+/// 事件类型常量
+pub mod event_types {
+    /// 设备离线
+    pub const DEVICE_OFFLINE: &str = "device.offline";
+    /// 设备恢复在线
+    pub const DEVICE_ONLINE: &str = "device.online";
+    /// 网关离线
+    pub const GATEWAY_OFFLINE: &str = "gateway.offline";
+    /// 网关恢复在线
+    pub const GATEWAY_ONLINE: &str = "gateway.online";
+    /// 命令进入终态（success / failed / timeout）
+    pub const COMMAND_COMPLETED: &str = "command.completed";
+    /// 告警触发
+    pub const ALARM_RAISED: &str = "alarm.raised";
+    /// 网关创建
+    pub const GATEWAY_CREATED: &str = "gateway.created";
+    /// 设备创建
+    pub const DEVICE_CREATED: &str = "device.created";
+    /// 设备配置变更
+    pub const DEVICE_UPDATED: &str = "device.updated";
+    /// 命令下发（含下发结果 accepted / failed）
+    pub const COMMAND_ISSUED: &str = "command.issued";
+
+    /// 支持订阅的全部事件类型
+    pub const ALL: &[&str] = &[
+        DEVICE_OFFLINE,
+        DEVICE_ONLINE,
+        GATEWAY_OFFLINE,
+        GATEWAY_ONLINE,
+        COMMAND_COMPLETED,
+        ALARM_RAISED,
+        GATEWAY_CREATED,
+        DEVICE_CREATED,
+        DEVICE_UPDATED,
+        COMMAND_ISSUED,
+    ];
+
+    /// 是否为支持的事件类型
+    pub fn is_supported(event_type: &str) -> bool {
+        ALL.contains(&event_type)
+    }
+}
+
+/// 默认广播缓冲（慢消费者落后超过该数量会丢失最旧的事件）
+const DEFAULT_BUS_CAPACITY: usize = 1024;
+
+/// 领域事件（同时作为 Webhook 推送的请求体）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DomainEvent {
+    pub event_id: String,
+    pub event_type: String,
+    pub tenant_id: String,
+    pub project_id: String,
+    pub occurred_at_ms: i64,
+    pub data: serde_json::Value,
+}
+
+impl DomainEvent {
+    /// 创建事件（自动生成事件 ID，发生时间取当前时间）
+    pub fn new(
+        event_type: &str,
+        tenant_id: impl Into<String>,
+        project_id: impl Into<String>,
+        data: serde_json::Value,
+    ) -> Self {
+        Self {
+            event_id: uuid::Uuid::new_v4().to_string(),
+            event_type: event_type.to_string(),
+            tenant_id: tenant_id.into(),
+            project_id: project_id.into(),
+            occurred_at_ms: now_epoch_ms(),
+            data,
+        }
+    }
+}
+
+/// 进程内事件总线
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<DomainEvent>,
+}
+
+impl EventBus {
+    /// 创建指定缓冲大小的事件总线
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self { sender }
+    }
+
+    /// 发布事件（无订阅者时丢弃）
+    pub fn publish(&self, event: DomainEvent) {
+        let event_type = event.event_type.clone();
+        if self.sender.send(event).is_err() {
+            debug!(target: "ems.events", event_type = %event_type, "event_dropped_no_subscriber");
+        }
+    }
+
+    /// 订阅事件
+    pub fn subscribe(&self) -> broadcast::Receiver<DomainEvent> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_BUS_CAPACITY)
+    }
+}
+
+/// 后台消费者写入存储时使用的系统上下文（限定在事件所属项目）
+fn system_context(event: &DomainEvent) -> TenantContext {
+    TenantContext::new(
+        event.tenant_id.clone(),
+        "system".to_string(),
+        Vec::new(),
+        Vec::new(),
+        Some(event.project_id.clone()),
+    )
+}
+
+fn now_epoch_ms() -> i64 {
+    let now = std::time::SystemTime::now();
+    let duration = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    duration.as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn bus_delivers_to_subscribers() {
+        let bus = EventBus::default();
+        let mut receiver = bus.subscribe();
+        bus.publish(DomainEvent::new(
+            event_types::GATEWAY_CREATED,
+            "tenant-1",
+            "project-1",
+            serde_json::json!({"gatewayId": "gw-1"}),
+        ));
+        let event = receiver.recv().await.expect("event");
+        assert_eq!(event.event_type, "gateway.created");
+        assert_eq!(event.data["gatewayId"], "gw-1");
+    }
+
+    #[test]
+    fn event_serializes_as_camel_case() {
+        let event = DomainEvent::new(
+            event_types::COMMAND_COMPLETED,
+            "tenant-1",
+            "project-1",
+            serde_json::json!({}),
+        );
+        let value = serde_json::to_value(&event).expect("json");
+        assert!(value.get("eventType").is_some());
+        assert!(value.get("occurredAtMs").is_some());
+        assert!(event_types::is_supported("device.offline"));
+        assert!(!event_types::is_supported("device.deleted"));
+    }
+}
@@ -0,0 +1,194 @@
+//! 设备时间线
+//!
+//! 订阅事件总线，把与设备相关的领域事件写入 `DeviceEventStore`：
+//! - `device.created` / `device.updated` / `device.online` / `device.offline` / `alarm.raised`：
+//!   取 `data.deviceId`（告警只记录带 `deviceId` 的）
+//! - `command.issued` / `command.completed`：`data.target` 为项目内设备时记录
+//!
+//! 事件 ID 与领域事件一致，重复写入由存储忽略；写入失败只记日志，不影响其它消费者。
+
+use crate::{DomainEvent, EventBus, event_types, system_context};
+use ems_storage::{DeviceEventRecord, DeviceEventStore, DeviceStore, StorageError};
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::warn;
+
+/// 写入设备时间线的事件类型
+pub const DEVICE_TIMELINE_EVENT_TYPES: &[&str] = &[
+    event_types::DEVICE_CREATED,
+    event_types::DEVICE_UPDATED,
+    event_types::DEVICE_ONLINE,
+    event_types::DEVICE_OFFLINE,
+    event_types::COMMAND_ISSUED,
+    event_types::COMMAND_COMPLETED,
+    event_types::ALARM_RAISED,
+];
+
+/// 设备时间线记录器
+pub struct DeviceEventRecorder {
+    event_store: Arc<dyn DeviceEventStore>,
+    device_store: Arc<dyn DeviceStore>,
+}
+
+impl DeviceEventRecorder {
+    pub fn new(event_store: Arc<dyn DeviceEventStore>, device_store: Arc<dyn DeviceStore>) -> Self {
+        Self {
+            event_store,
+            device_store,
+        }
+    }
+
+    /// 记录单个事件，返回是否新增（与设备无关或已记录时返回 false）
+    pub async fn record(&self, event: &DomainEvent) -> Result<bool, StorageError> {
+        if !DEVICE_TIMELINE_EVENT_TYPES.contains(&event.event_type.as_str()) {
+            return Ok(false);
+        }
+        let ctx = system_context(event);
+        let device_id = match event.data.get("deviceId").and_then(|value| value.as_str()) {
+            Some(device_id) => device_id.to_string(),
+            None => {
+                // 命令目标可能是网关，只有目标为项目内设备时才记入时间线
+                let is_command = event.event_type == event_types::COMMAND_ISSUED
+                    || event.event_type == event_types::COMMAND_COMPLETED;
+                let Some(target) = event
+                    .data
+                    .get("target")
+                    .and_then(|value| value.as_str())
+                    .filter(|_| is_command)
+                else {
+                    return Ok(false);
+                };
+                match self
+                    .device_store
+                    .find_device(&ctx, &event.project_id, target)
+                    .await?
+                {
+                    Some(device) => device.device_id,
+                    None => return Ok(false),
+                }
+            }
+        };
+        let record = DeviceEventRecord {
+            tenant_id: event.tenant_id.clone(),
+            project_id: event.project_id.clone(),
+            device_id,
+            event_id: event.event_id.clone(),
+            event_type: event.event_type.clone(),
+            occurred_at_ms: event.occurred_at_ms,
+            data: event.data.to_string(),
+        };
+        self.event_store.insert_device_event(&ctx, record).await
+    }
+}
+
+/// 启动设备时间线记录后台任务
+pub fn spawn_device_event_recorder(
+    bus: &EventBus,
+    recorder: Arc<DeviceEventRecorder>,
+) -> tokio::task::JoinHandle<()> {
+    let mut receiver = bus.subscribe();
+    tokio::spawn(async move {
+        loop {
+            let event = match receiver.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!(target: "ems.events", skipped = skipped, "device_event_recorder_lagged");
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            if let Err(err) = recorder.record(&event).await {
+                warn!(
+                    target: "ems.events",
+                    event_id = %event.event_id,
+                    event_type = %event.event_type,
+                    error = %err,
+                    "device_event_record_failed"
+                );
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::TenantContext;
+    use ems_storage::{
+        DeviceEventQuery, DeviceRecord, InMemoryDeviceEventStore, InMemoryDeviceStore,
+    };
+
+    fn ctx() -> TenantContext {
+        TenantContext::new(
+            "tenant-1".to_string(),
+            "user-1".to_string(),
+            Vec::new(),
+            Vec::new(),
+            Some("project-1".to_string()),
+        )
+    }
+
+    #[tokio::test]
+    async fn records_device_related_events_only() {
+        let event_store = Arc::new(InMemoryDeviceEventStore::new());
+        let device_store = Arc::new(InMemoryDeviceStore::new());
+        device_store
+            .create_device(
+                &ctx(),
+                DeviceRecord {
+                    device_id: "dev-1".to_string(),
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    gateway_id: "gw-1".to_string(),
+                    name: "meter".to_string(),
+                    model: None,
+                    room_id: None,
+                    address_config: None,
+                    offline_after_seconds: None,
+                },
+            )
+            .await
+            .expect("device");
+        let recorder = DeviceEventRecorder::new(event_store.clone(), device_store);
+        let event = |event_type: &str, data: serde_json::Value| {
+            DomainEvent::new(event_type, "tenant-1", "project-1", data)
+        };
+
+        let offline = event(
+            event_types::DEVICE_OFFLINE,
+            serde_json::json!({"deviceId": "dev-1"}),
+        );
+        assert!(recorder.record(&offline).await.expect("record"));
+        // 同一事件重复投递只记录一次
+        assert!(!recorder.record(&offline).await.expect("record"));
+        let command = event(
+            event_types::COMMAND_ISSUED,
+            serde_json::json!({"commandId": "cmd-1", "target": "dev-1"}),
+        );
+        assert!(recorder.record(&command).await.expect("record"));
+        // 目标为网关的命令、不带设备的告警与网关事件不进入设备时间线
+        let gateway_command = event(
+            event_types::COMMAND_COMPLETED,
+            serde_json::json!({"commandId": "cmd-2", "target": "gw-1"}),
+        );
+        assert!(!recorder.record(&gateway_command).await.expect("record"));
+        let rule_alarm = event(
+            event_types::ALARM_RAISED,
+            serde_json::json!({"ruleId": "rule-1"}),
+        );
+        assert!(!recorder.record(&rule_alarm).await.expect("record"));
+        let gateway_offline = event(
+            event_types::GATEWAY_OFFLINE,
+            serde_json::json!({"gatewayId": "gw-1", "deviceId": "dev-1"}),
+        );
+        assert!(!recorder.record(&gateway_offline).await.expect("record"));
+
+        let items = event_store
+            .list_device_events(&ctx(), "project-1", "dev-1", DeviceEventQuery::default())
+            .await
+            .expect("list");
+        let mut types: Vec<&str> = items.iter().map(|item| item.event_type.as_str()).collect();
+        types.sort();
+        assert_eq!(types, vec!["command.issued", "device.offline"]);
+    }
+}
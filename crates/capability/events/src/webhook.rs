@@ -0,0 +1,503 @@
+//! Webhook 推送
+//!
+//! 订阅事件总线，按项目内的 Webhook 订阅推送事件：
+//! - 请求体为 `DomainEvent` JSON
+//! - 签名：`X-EMS-Signature: sha256=<hex>`，内容为 `HMAC-SHA256(secret, "{timestamp}.{body}")`，
+//!   时间戳通过 `X-EMS-Timestamp` 传递（毫秒），接收方可据此拒绝重放
+//! - 非 2xx 或连接失败按线性退避重试，最终结果写入推送日志
+//! - 防 SSRF：每次推送时解析目标主机，拒绝回环 / 内网 / 链路本地 / 未指定地址（不重试）；
+//!   客户端的 DNS 解析同样过滤这些地址（防 DNS rebinding），且不跟随重定向
+
+use crate::{DomainEvent, EventBus, system_context};
+use ems_storage::{WebhookDeliveryRecord, WebhookSubscriptionRecord, WebhookSubscriptionStore};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{info, warn};
+
+/// 签名头
+pub const SIGNATURE_HEADER: &str = "x-ems-signature";
+/// 签名时间戳头（毫秒）
+pub const TIMESTAMP_HEADER: &str = "x-ems-timestamp";
+/// 事件类型头
+pub const EVENT_TYPE_HEADER: &str = "x-ems-event";
+/// 事件 ID 头（接收方去重用）
+pub const EVENT_ID_HEADER: &str = "x-ems-event-id";
+
+/// 推送配置
+#[derive(Debug, Clone)]
+pub struct WebhookDispatcherConfig {
+    /// 单个事件的最大请求次数（含首次）
+    pub max_attempts: u64,
+    /// 重试退避（毫秒，按次数线性递增）
+    pub backoff_ms: u64,
+    /// 单次请求超时（毫秒）
+    pub timeout_ms: u64,
+    /// 允许推送到回环 / 内网 / 链路本地地址（默认关闭，仅用于内网部署与测试）
+    pub allow_private_targets: bool,
+}
+
+impl Default for WebhookDispatcherConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            backoff_ms: 1000,
+            timeout_ms: 5000,
+            allow_private_targets: false,
+        }
+    }
+}
+
+/// 推送目标校验失败
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebhookTargetError {
+    /// URL 无法解析、非 http/https 或缺少主机
+    Invalid(String),
+    /// 目标为回环 / 内网 / 链路本地 / 未指定地址
+    Blocked(IpAddr),
+    /// 主机名解析失败（可重试）
+    Unresolved(String),
+}
+
+impl fmt::Display for WebhookTargetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid(message) => write!(f, "invalid webhook url: {}", message),
+            Self::Blocked(ip) => write!(f, "webhook target address {} is not allowed", ip),
+            Self::Unresolved(message) => write!(f, "webhook host resolution failed: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for WebhookTargetError {}
+
+/// 是否为允许推送的公网地址（拒绝回环、内网、链路本地、未指定、组播等地址）
+pub fn is_public_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let octets = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                // 0.0.0.0/8 与运营商级 NAT 100.64.0.0/10
+                || octets[0] == 0
+                || (octets[0] == 100 && (octets[1] & 0xc0) == 64))
+        }
+        IpAddr::V6(ip) => {
+            if let Some(mapped) = ip.to_ipv4_mapped() {
+                return is_public_address(IpAddr::V4(mapped));
+            }
+            let first = ip.segments()[0];
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                // 唯一本地地址 fc00::/7 与链路本地地址 fe80::/10
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+/// 校验 Webhook URL 格式：http/https、带主机，字面量 IP 或 localhost 必须为公网地址
+///
+/// 创建订阅时调用，给出早期反馈；推送时仍以 [`check_delivery_target`] 的解析结果为准。
+pub fn validate_webhook_url(url: &str) -> Result<reqwest::Url, WebhookTargetError> {
+    let parsed =
+        reqwest::Url::parse(url).map_err(|err| WebhookTargetError::Invalid(err.to_string()))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(WebhookTargetError::Invalid(
+            "url must start with http:// or https://".to_string(),
+        ));
+    }
+    match target_host(&parsed)? {
+        TargetHost::Ip(ip) => ensure_public(ip)?,
+        TargetHost::Domain(domain) => {
+            let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+            if domain == "localhost" || domain.ends_with(".localhost") {
+                return Err(WebhookTargetError::Blocked(IpAddr::from([127, 0, 0, 1])));
+            }
+        }
+    }
+    Ok(parsed)
+}
+
+/// URL 中的主机：字面量 IP 或域名
+enum TargetHost<'a> {
+    Ip(IpAddr),
+    Domain(&'a str),
+}
+
+fn target_host(url: &reqwest::Url) -> Result<TargetHost<'_>, WebhookTargetError> {
+    let host = url
+        .host_str()
+        .filter(|host| !host.is_empty())
+        .ok_or_else(|| WebhookTargetError::Invalid("url has no host".to_string()))?;
+    // IPv6 字面量带方括号
+    let literal = host.trim_start_matches('[').trim_end_matches(']');
+    Ok(match literal.parse::<IpAddr>() {
+        Ok(ip) => TargetHost::Ip(ip),
+        Err(_) => TargetHost::Domain(host),
+    })
+}
+
+/// 推送前解析目标主机，任一解析地址不是公网地址即拒绝
+pub async fn check_delivery_target(url: &str) -> Result<(), WebhookTargetError> {
+    let parsed = validate_webhook_url(url)?;
+    let TargetHost::Domain(domain) = target_host(&parsed)? else {
+        return Ok(());
+    };
+    let port = parsed.port_or_known_default().unwrap_or(80);
+    let addrs = resolve_public((domain, port)).await?;
+    if addrs.is_empty() {
+        return Err(WebhookTargetError::Unresolved(format!(
+            "{} has no address",
+            domain
+        )));
+    }
+    Ok(())
+}
+
+fn ensure_public(ip: IpAddr) -> Result<(), WebhookTargetError> {
+    if is_public_address(ip) {
+        Ok(())
+    } else {
+        Err(WebhookTargetError::Blocked(ip))
+    }
+}
+
+/// 解析主机并过滤：任一地址不是公网地址即整体拒绝
+async fn resolve_public(host: (&str, u16)) -> Result<Vec<SocketAddr>, WebhookTargetError> {
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host(host)
+        .await
+        .map_err(|err| WebhookTargetError::Unresolved(err.to_string()))?
+        .collect();
+    for addr in &addrs {
+        ensure_public(addr.ip())?;
+    }
+    Ok(addrs)
+}
+
+/// 推送客户端的 DNS 解析器：连接建立时再次过滤内网地址，防止校验后 DNS 记录被改写
+struct PublicAddressResolver;
+
+impl reqwest::dns::Resolve for PublicAddressResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addrs = resolve_public((host.as_str(), 0)).await?;
+            Ok(Box::new(addrs.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+/// 计算签名：`sha256=<hex(HMAC-SHA256(secret, "{timestamp}.{body}"))>`
+pub fn sign_payload(secret: &str, timestamp_ms: i64, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("hmac accepts any key length");
+    mac.update(timestamp_ms.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// 启动 Webhook 推送后台任务
+///
+/// HTTP 客户端构建失败时返回错误（不退化为无内网过滤的默认客户端），由调用方在启动阶段终止。
+pub fn spawn_webhook_dispatcher(
+    bus: &EventBus,
+    store: Arc<dyn WebhookSubscriptionStore>,
+    config: WebhookDispatcherConfig,
+) -> Result<tokio::task::JoinHandle<()>, reqwest::Error> {
+    let mut receiver = bus.subscribe();
+    let client = build_client(&config)?;
+    Ok(tokio::spawn(async move {
+        loop {
+            let event = match receiver.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!(target: "ems.events", skipped = skipped, "webhook_dispatcher_lagged");
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            let ctx = system_context(&event);
+            let subscriptions = match store
+                .list_webhook_subscriptions_for_event(&ctx, &event.project_id, &event.event_type)
+                .await
+            {
+                Ok(subscriptions) => subscriptions,
+                Err(err) => {
+                    warn!(target: "ems.events", error = %err, "webhook_subscriptions_read_failed");
+                    continue;
+                }
+            };
+            for subscription in subscriptions {
+                // 每个订阅独立推送，慢端点不阻塞其它订阅与后续事件
+                let client = client.clone();
+                let store = store.clone();
+                let config = config.clone();
+                let event = event.clone();
+                tokio::spawn(async move {
+                    deliver_event(&client, store.as_ref(), &subscription, &event, &config).await;
+                });
+            }
+        }
+    }))
+}
+
+/// 向单个订阅推送事件（含重试），并写入推送日志
+pub async fn deliver_event(
+    client: &reqwest::Client,
+    store: &dyn WebhookSubscriptionStore,
+    subscription: &WebhookSubscriptionRecord,
+    event: &DomainEvent,
+    config: &WebhookDispatcherConfig,
+) -> WebhookDeliveryRecord {
+    let body = serde_json::to_string(event).unwrap_or_else(|_| "{}".to_string());
+    let max_attempts = config.max_attempts.max(1);
+    let mut attempts = 0;
+    let mut response_status = None;
+    let mut error = None;
+    let mut delivered = false;
+    while attempts < max_attempts {
+        if attempts > 0 && config.backoff_ms > 0 {
+            tokio::time::sleep(Duration::from_millis(
+                config.backoff_ms.saturating_mul(attempts),
+            ))
+            .await;
+        }
+        attempts += 1;
+        if !config.allow_private_targets {
+            match check_delivery_target(&subscription.url).await {
+                Ok(()) => {}
+                Err(err @ WebhookTargetError::Unresolved(_)) => {
+                    response_status = None;
+                    error = Some(err.to_string());
+                    continue;
+                }
+                // 目标地址不允许：不发请求、不重试
+                Err(err) => {
+                    response_status = None;
+                    error = Some(err.to_string());
+                    break;
+                }
+            }
+        }
+        let timestamp_ms = crate::now_epoch_ms();
+        let result = client
+            .post(&subscription.url)
+            .header("content-type", "application/json")
+            .header(
+                SIGNATURE_HEADER,
+                sign_payload(&subscription.secret, timestamp_ms, &body),
+            )
+            .header(TIMESTAMP_HEADER, timestamp_ms.to_string())
+            .header(EVENT_TYPE_HEADER, &event.event_type)
+            .header(EVENT_ID_HEADER, &event.event_id)
+            .body(body.clone())
+            .send()
+            .await;
+        match result {
+            Ok(response) => {
+                let status = response.status();
+                response_status = Some(i32::from(status.as_u16()));
+                if status.is_success() {
+                    delivered = true;
+                    error = None;
+                    break;
+                }
+                error = Some(format!("unexpected status {}", status.as_u16()));
+            }
+            Err(err) => {
+                response_status = None;
+                error = Some(err.to_string());
+            }
+        }
+    }
+
+    let record = WebhookDeliveryRecord {
+        delivery_id: uuid::Uuid::new_v4().to_string(),
+        tenant_id: event.tenant_id.clone(),
+        project_id: event.project_id.clone(),
+        subscription_id: subscription.subscription_id.clone(),
+        event_id: event.event_id.clone(),
+        event_type: event.event_type.clone(),
+        status: if delivered { "success" } else { "failed" }.to_string(),
+        attempts: attempts as i32,
+        response_status,
+        error,
+        created_at_ms: crate::now_epoch_ms(),
+    };
+    if delivered {
+        info!(
+            target: "ems.events",
+            subscription_id = %record.subscription_id,
+            event_id = %record.event_id,
+            event_type = %record.event_type,
+            attempts = record.attempts,
+            "webhook_delivered"
+        );
+    } else {
+        warn!(
+            target: "ems.events",
+            subscription_id = %record.subscription_id,
+            event_id = %record.event_id,
+            event_type = %record.event_type,
+            attempts = record.attempts,
+            error = ?record.error,
+            "webhook_delivery_failed"
+        );
+    }
+    let ctx = system_context(event);
+    if let Err(err) = store.create_webhook_delivery(&ctx, record.clone()).await {
+        warn!(target: "ems.events", error = %err, "webhook_delivery_log_failed");
+    }
+    record
+}
+
+/// 构建推送用 HTTP 客户端（不跟随重定向；未允许内网目标时过滤解析出的内网地址）
+pub fn build_client(config: &WebhookDispatcherConfig) -> Result<reqwest::Client, reqwest::Error> {
+    let builder = reqwest::Client::builder()
+        .timeout(Duration::from_millis(config.timeout_ms.max(1)))
+        .redirect(reqwest::redirect::Policy::none());
+    let builder = if config.allow_private_targets {
+        builder
+    } else {
+        builder.dns_resolver(Arc::new(PublicAddressResolver))
+    };
+    builder.build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_types;
+    use ems_storage::{InMemoryWebhookSubscriptionStore, WebhookDeliveryQuery};
+
+    #[test]
+    fn signature_is_hmac_sha256_of_timestamp_and_body() {
+        // HMAC-SHA256("secret", "1700000000000.{}")
+        let signature = sign_payload("secret", 1_700_000_000_000, "{}");
+        assert_eq!(
+            signature,
+            "sha256=8399216d111287e3bb28e25c0f4f31dffdf831c68c9ee2b96c2f67c9b81d341b"
+        );
+        assert_ne!(signature, sign_payload("other", 1_700_000_000_000, "{}"));
+        assert_ne!(signature, sign_payload("secret", 1_700_000_000_001, "{}"));
+    }
+
+    #[tokio::test]
+    async fn failed_delivery_is_retried_and_logged() {
+        let store = InMemoryWebhookSubscriptionStore::new();
+        let subscription = WebhookSubscriptionRecord {
+            subscription_id: "sub-1".to_string(),
+            tenant_id: "tenant-1".to_string(),
+            project_id: "project-1".to_string(),
+            // 端口 1 无监听，连接立即失败
+            url: "http://127.0.0.1:1/hook".to_string(),
+            event_types: vec![event_types::GATEWAY_CREATED.to_string()],
+            secret: "secret".to_string(),
+            enabled: true,
+            created_by: "user-1".to_string(),
+            created_at_ms: 0,
+        };
+        let event = DomainEvent::new(
+            event_types::GATEWAY_CREATED,
+            "tenant-1",
+            "project-1",
+            serde_json::json!({"gatewayId": "gw-1"}),
+        );
+        let config = WebhookDispatcherConfig {
+            max_attempts: 2,
+            backoff_ms: 0,
+            timeout_ms: 1000,
+            allow_private_targets: true,
+        };
+        let client = build_client(&config).expect("webhook client");
+        let record = deliver_event(&client, &store, &subscription, &event, &config).await;
+        assert_eq!(record.status, "failed");
+        assert_eq!(record.attempts, 2);
+        assert!(record.response_status.is_none());
+        assert!(record.error.is_some());
+
+        let ctx = system_context(&event);
+        let logged = store
+            .list_webhook_deliveries(
+                &ctx,
+                "project-1",
+                WebhookDeliveryQuery {
+                    status: Some("failed".to_string()),
+                    limit: 10,
+                    ..WebhookDeliveryQuery::default()
+                },
+            )
+            .await
+            .expect("deliveries");
+        assert_eq!(logged.len(), 1);
+        assert_eq!(logged[0].event_id, event.event_id);
+
+        // 默认不允许内网目标：不发请求、不重试
+        let config = WebhookDispatcherConfig {
+            allow_private_targets: false,
+            ..config
+        };
+        let record = deliver_event(&client, &store, &subscription, &event, &config).await;
+        assert_eq!(record.status, "failed");
+        assert_eq!(record.attempts, 1);
+        assert_eq!(
+            record.error.as_deref(),
+            Some("webhook target address 127.0.0.1 is not allowed")
+        );
+    }
+
+    #[test]
+    fn private_and_local_addresses_are_rejected() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "0.0.0.0",
+            "100.64.0.1",
+            "::1",
+            "::",
+            "fe80::1",
+            "fd00::1",
+            "::ffff:127.0.0.1",
+        ] {
+            let ip: IpAddr = ip.parse().expect("ip");
+            assert!(!is_public_address(ip), "{}", ip);
+        }
+        assert!(is_public_address("93.184.216.34".parse().expect("ip")));
+        assert!(is_public_address("2606:4700::1".parse().expect("ip")));
+
+        assert!(validate_webhook_url("https://example.com/hook").is_ok());
+        assert!(matches!(
+            validate_webhook_url("ftp://example.com/hook"),
+            Err(WebhookTargetError::Invalid(_))
+        ));
+        for url in [
+            "http://127.0.0.1:8080/hook",
+            "http://[::1]/hook",
+            "http://169.254.169.254/latest/meta-data",
+            "http://localhost/hook",
+        ] {
+            assert!(
+                matches!(
+                    validate_webhook_url(url),
+                    Err(WebhookTargetError::Blocked(_))
+                ),
+                "{}",
+                url
+            );
+        }
+    }
+}
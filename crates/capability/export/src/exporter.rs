@@ -0,0 +1,357 @@
+//! 导出队列与后台发布任务。
+
+use crate::{PointValueRecord, StreamPublisher, render_topic};
+use domain::{PointValue, TenantContext};
+use ems_storage::{
+    MeasurementCoverage, MeasurementCoverageOptions, MeasurementRecord, MeasurementStore,
+    MeasurementsQueryOptions, StorageError,
+};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::warn;
+
+/// 导出参数
+#[derive(Debug, Clone)]
+pub struct ExporterConfig {
+    /// 主题模板（`{tenant_id}` / `{project_id}` 占位符）
+    pub topic_template: String,
+    /// 队列容量（条）；队列满时丢弃新数据
+    pub buffer_size: usize,
+    /// 单次发布的最大条数
+    pub batch_size: usize,
+    /// 发布失败重试次数
+    pub max_retries: u32,
+    /// 重试退避基数（毫秒，按次数倍增）
+    pub backoff_ms: u64,
+}
+
+impl Default for ExporterConfig {
+    fn default() -> Self {
+        Self {
+            topic_template: crate::DEFAULT_TOPIC_TEMPLATE.to_string(),
+            buffer_size: 10_000,
+            batch_size: 500,
+            max_retries: 3,
+            backoff_ms: 500,
+        }
+    }
+}
+
+/// 导出计数
+#[derive(Debug, Default)]
+pub struct ExportStats {
+    /// 已发布条数
+    pub published: AtomicU64,
+    /// 丢弃条数（队列满或重试耗尽）
+    pub dropped: AtomicU64,
+}
+
+/// 导出入口（可克隆，投递不阻塞调用方）
+#[derive(Clone)]
+pub struct Exporter {
+    sender: mpsc::Sender<PointValue>,
+    stats: Arc<ExportStats>,
+}
+
+impl Exporter {
+    /// 投递测量值；队列满或导出任务已停止时丢弃并计数
+    pub fn export(&self, values: &[PointValue]) {
+        for value in values {
+            if self.sender.try_send(value.clone()).is_err() {
+                let dropped = self.stats.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+                // 每 1000 条记录一次，避免日志刷屏
+                if dropped % 1000 == 1 {
+                    warn!(target: "ems.export", dropped = dropped, "export_queue_full");
+                }
+            }
+        }
+    }
+
+    pub fn stats(&self) -> &ExportStats {
+        &self.stats
+    }
+}
+
+/// 启动导出后台任务，返回投递入口
+pub fn spawn_exporter(
+    publisher: Arc<dyn StreamPublisher>,
+    config: ExporterConfig,
+) -> (Exporter, tokio::task::JoinHandle<()>) {
+    let (sender, mut receiver) = mpsc::channel::<PointValue>(config.buffer_size.max(1));
+    let stats = Arc::new(ExportStats::default());
+    let exporter = Exporter {
+        sender,
+        stats: stats.clone(),
+    };
+    let batch_size = config.batch_size.max(1);
+    let handle = tokio::spawn(async move {
+        let mut batch = Vec::with_capacity(batch_size);
+        loop {
+            // 阻塞等待第一条，之后取走已排队的数据凑批
+            if receiver.recv_many(&mut batch, batch_size).await == 0 {
+                break;
+            }
+            let mut topics: BTreeMap<String, Vec<PointValueRecord>> = BTreeMap::new();
+            for value in batch.drain(..) {
+                topics
+                    .entry(render_topic(
+                        &config.topic_template,
+                        &value.tenant_id,
+                        &value.project_id,
+                    ))
+                    .or_default()
+                    .push(PointValueRecord::from_point_value(&value));
+            }
+            for (topic, records) in topics {
+                publish_with_retry(publisher.as_ref(), &topic, &records, &config, &stats).await;
+            }
+        }
+    });
+    (exporter, handle)
+}
+
+async fn publish_with_retry(
+    publisher: &dyn StreamPublisher,
+    topic: &str,
+    records: &[PointValueRecord],
+    config: &ExporterConfig,
+    stats: &ExportStats,
+) {
+    let mut attempt = 0;
+    loop {
+        match publisher.publish(topic, records).await {
+            Ok(()) => {
+                stats
+                    .published
+                    .fetch_add(records.len() as u64, Ordering::Relaxed);
+                return;
+            }
+            Err(err) if attempt < config.max_retries => {
+                attempt += 1;
+                warn!(target: "ems.export", topic = %topic, attempt = attempt, error = %err, "export_publish_retry");
+                tokio::time::sleep(Duration::from_millis(
+                    config.backoff_ms.saturating_mul(u64::from(attempt)),
+                ))
+                .await;
+            }
+            Err(err) => {
+                stats
+                    .dropped
+                    .fetch_add(records.len() as u64, Ordering::Relaxed);
+                warn!(target: "ems.export", topic = %topic, count = records.len(), error = %err, "export_publish_failed");
+                return;
+            }
+        }
+    }
+}
+
+/// 写入成功后导出测量值的存储包装（其余方法直接转发）
+pub struct ExportingMeasurementStore {
+    inner: Arc<dyn MeasurementStore>,
+    exporter: Exporter,
+}
+
+impl ExportingMeasurementStore {
+    pub fn new(inner: Arc<dyn MeasurementStore>, exporter: Exporter) -> Self {
+        Self { inner, exporter }
+    }
+}
+
+#[async_trait::async_trait]
+impl MeasurementStore for ExportingMeasurementStore {
+    async fn write_measurement(
+        &self,
+        ctx: &TenantContext,
+        value: &PointValue,
+    ) -> Result<(), StorageError> {
+        self.inner.write_measurement(ctx, value).await?;
+        self.exporter.export(std::slice::from_ref(value));
+        Ok(())
+    }
+
+    async fn write_measurements(
+        &self,
+        ctx: &TenantContext,
+        values: &[PointValue],
+    ) -> Result<usize, StorageError> {
+        let written = self.inner.write_measurements(ctx, values).await?;
+        self.exporter.export(values);
+        Ok(written)
+    }
+
+    async fn query_measurements(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        point_id: &str,
+        options: MeasurementsQueryOptions,
+    ) -> Result<Vec<MeasurementRecord>, StorageError> {
+        self.inner
+            .query_measurements(ctx, project_id, point_id, options)
+            .await
+    }
+
+    async fn measurement_coverage(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        point_id: &str,
+        options: MeasurementCoverageOptions,
+    ) -> Result<MeasurementCoverage, StorageError> {
+        self.inner
+            .measurement_coverage(ctx, project_id, point_id, options)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ExportError;
+    use domain::PointValueData;
+    use ems_storage::InMemoryMeasurementStore;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingPublisher {
+        published: Mutex<Vec<(String, Vec<String>)>>,
+        failures: AtomicU64,
+    }
+
+    #[async_trait::async_trait]
+    impl StreamPublisher for RecordingPublisher {
+        async fn publish(
+            &self,
+            topic: &str,
+            records: &[PointValueRecord],
+        ) -> Result<(), ExportError> {
+            if self.failures.load(Ordering::SeqCst) > 0 {
+                self.failures.fetch_sub(1, Ordering::SeqCst);
+                return Err(ExportError::Publish("unavailable".to_string()));
+            }
+            let points = records
+                .iter()
+                .map(|record| record.point_id.clone())
+                .collect();
+            self.published
+                .lock()
+                .expect("lock")
+                .push((topic.to_string(), points));
+            Ok(())
+        }
+    }
+
+    fn value(project_id: &str, point_id: &str, ts_ms: i64) -> PointValue {
+        PointValue {
+            tenant_id: "tenant-1".to_string(),
+            project_id: project_id.to_string(),
+            point_id: point_id.to_string(),
+            ts_ms,
+            value: PointValueData::F64(1.0),
+            quality: None,
+        }
+    }
+
+    fn ctx(project_id: &str) -> TenantContext {
+        TenantContext::new(
+            "tenant-1",
+            "system",
+            vec![],
+            vec![],
+            Some(project_id.to_string()),
+        )
+    }
+
+    async fn wait_published(exporter: &Exporter, expected: u64) {
+        for _ in 0..200 {
+            if exporter.stats().published.load(Ordering::Relaxed) >= expected {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn persisted_values_are_routed_by_project_topic() {
+        let publisher = Arc::new(RecordingPublisher::default());
+        publisher.failures.store(1, Ordering::SeqCst);
+        let (exporter, _handle) = spawn_exporter(
+            publisher.clone(),
+            ExporterConfig {
+                backoff_ms: 1,
+                ..ExporterConfig::default()
+            },
+        );
+        let store = ExportingMeasurementStore::new(
+            Arc::new(InMemoryMeasurementStore::new()),
+            exporter.clone(),
+        );
+        store
+            .write_measurements(
+                &ctx("project-1"),
+                &[
+                    value("project-1", "p-1", 1_000),
+                    value("project-1", "p-2", 1_000),
+                ],
+            )
+            .await
+            .expect("write");
+        store
+            .write_measurement(&ctx("project-2"), &value("project-2", "p-3", 1_000))
+            .await
+            .expect("write");
+        wait_published(&exporter, 3).await;
+
+        let mut published = publisher.published.lock().expect("lock").clone();
+        published.sort();
+        let topics: Vec<&str> = published.iter().map(|(topic, _)| topic.as_str()).collect();
+        assert!(topics.contains(&"ems.tenant-1.project-1.measurements"));
+        assert!(topics.contains(&"ems.tenant-1.project-2.measurements"));
+        let total: usize = published.iter().map(|(_, points)| points.len()).sum();
+        assert_eq!(total, 3);
+        assert_eq!(exporter.stats().dropped.load(Ordering::Relaxed), 0);
+    }
+
+    #[tokio::test]
+    async fn failed_writes_are_not_exported_and_retries_exhaust_to_drop() {
+        let publisher = Arc::new(RecordingPublisher::default());
+        publisher.failures.store(10, Ordering::SeqCst);
+        let (exporter, _handle) = spawn_exporter(
+            publisher.clone(),
+            ExporterConfig {
+                max_retries: 1,
+                backoff_ms: 1,
+                ..ExporterConfig::default()
+            },
+        );
+        let store = ExportingMeasurementStore::new(
+            Arc::new(InMemoryMeasurementStore::new()),
+            exporter.clone(),
+        );
+        // 租户不匹配，写入失败，不导出
+        let mut foreign = value("project-1", "p-1", 1_000);
+        foreign.tenant_id = "tenant-2".to_string();
+        assert!(
+            store
+                .write_measurement(&ctx("project-1"), &foreign)
+                .await
+                .is_err()
+        );
+        store
+            .write_measurement(&ctx("project-1"), &value("project-1", "p-1", 1_000))
+            .await
+            .expect("write");
+        for _ in 0..200 {
+            if exporter.stats().dropped.load(Ordering::Relaxed) > 0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        assert_eq!(exporter.stats().dropped.load(Ordering::Relaxed), 1);
+        assert_eq!(publisher.failures.load(Ordering::SeqCst), 8);
+        assert!(publisher.published.lock().expect("lock").is_empty());
+    }
+}
@@ -0,0 +1,176 @@
+//! 测量数据流式导出
+//!
+//! 把已持久化的测点值发布到 Kafka / NATS，供下游分析消费（无需轮询 API）：
+//! - 消息体：每条测点值一个 JSON 对象，结构见 `schema/point_value.v1.json`（[`POINT_VALUE_JSON_SCHEMA`]）
+//! - 主题：按模板 `{tenant_id}` / `{project_id}` 占位符路由（默认 `ems.{tenant_id}.{project_id}.measurements`）
+//! - 投递：`ExportingMeasurementStore` 在写入成功后把测量值放入有界队列，后台任务按主题批量发布；
+//!   队列满或发布重试耗尽时丢弃并计数，导出故障不影响写入链路
+//!
+//! 传输：NATS 使用核心协议（PUB，至多一次）；Kafka 经 REST Proxy（Confluent REST API v2）写入。
+
+mod exporter;
+mod publisher;
+
+pub use exporter::*;
+pub use publisher::*;
+
+use domain::{PointValue, PointValueData};
+use serde::Serialize;
+
+/// 消息结构版本（写入每条消息的 `schema` 字段）
+pub const POINT_VALUE_SCHEMA: &str = "ems.point_value.v1";
+
+/// 消息结构（JSON Schema）
+pub const POINT_VALUE_JSON_SCHEMA: &str = include_str!("../schema/point_value.v1.json");
+
+/// 默认主题模板
+pub const DEFAULT_TOPIC_TEMPLATE: &str = "ems.{tenant_id}.{project_id}.measurements";
+
+/// 导出错误
+#[derive(Debug, thiserror::Error)]
+pub enum ExportError {
+    #[error("invalid export config: {0}")]
+    Config(String),
+    #[error("publish failed: {0}")]
+    Publish(String),
+}
+
+/// 导出消息（对应 `ems.point_value.v1`）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PointValueRecord {
+    pub schema: &'static str,
+    pub tenant_id: String,
+    pub project_id: String,
+    pub point_id: String,
+    pub ts_ms: i64,
+    /// `i64` / `f64` / `bool` / `string`
+    pub value_type: &'static str,
+    pub value: serde_json::Value,
+    pub quality: Option<String>,
+}
+
+impl PointValueRecord {
+    pub fn from_point_value(value: &PointValue) -> Self {
+        let (value_type, data) = match &value.value {
+            PointValueData::I64(v) => ("i64", serde_json::Value::from(*v)),
+            PointValueData::F64(v) => ("f64", serde_json::Value::from(*v)),
+            PointValueData::Bool(v) => ("bool", serde_json::Value::from(*v)),
+            PointValueData::String(v) => ("string", serde_json::Value::from(v.as_str())),
+        };
+        Self {
+            schema: POINT_VALUE_SCHEMA,
+            tenant_id: value.tenant_id.clone(),
+            project_id: value.project_id.clone(),
+            point_id: value.point_id.clone(),
+            ts_ms: value.ts_ms,
+            value_type,
+            value: data,
+            quality: value.quality.clone(),
+        }
+    }
+}
+
+/// 按模板生成主题：`{tenant_id}` / `{project_id}` 替换为对应 ID
+///
+/// ID 中 `[A-Za-z0-9_-]` 以外的字符替换为 `_`，保证 Kafka 主题名与 NATS 主题段合法。
+pub fn render_topic(template: &str, tenant_id: &str, project_id: &str) -> String {
+    template
+        .replace("{tenant_id}", &topic_token(tenant_id))
+        .replace("{project_id}", &topic_token(project_id))
+}
+
+fn topic_token(value: &str) -> String {
+    value
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// 校验主题模板：非空，且只含主题合法字符与占位符
+pub fn validate_topic_template(template: &str) -> Result<(), ExportError> {
+    let rendered = render_topic(template, "t", "p");
+    if rendered.is_empty() {
+        return Err(ExportError::Config("topic template is empty".to_string()));
+    }
+    if let Some(ch) = rendered
+        .chars()
+        .find(|ch| !(ch.is_ascii_alphanumeric() || matches!(ch, '.' | '-' | '_')))
+    {
+        return Err(ExportError::Config(format!(
+            "topic template contains invalid character {ch:?}"
+        )));
+    }
+    if rendered.starts_with('.') || rendered.ends_with('.') || rendered.contains("..") {
+        return Err(ExportError::Config(
+            "topic template has an empty segment".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_follows_schema_fields() {
+        let record = PointValueRecord::from_point_value(&PointValue {
+            tenant_id: "tenant-1".to_string(),
+            project_id: "project-1".to_string(),
+            point_id: "point-1".to_string(),
+            ts_ms: 1_000,
+            value: PointValueData::F64(12.5),
+            quality: Some("good".to_string()),
+        });
+        let json = serde_json::to_value(&record).expect("json");
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "schema": "ems.point_value.v1",
+                "tenantId": "tenant-1",
+                "projectId": "project-1",
+                "pointId": "point-1",
+                "tsMs": 1000,
+                "valueType": "f64",
+                "value": 12.5,
+                "quality": "good",
+            })
+        );
+        let schema: serde_json::Value =
+            serde_json::from_str(POINT_VALUE_JSON_SCHEMA).expect("schema");
+        let mut required: Vec<&str> = schema["required"]
+            .as_array()
+            .expect("required")
+            .iter()
+            .filter_map(|value| value.as_str())
+            .collect();
+        required.sort();
+        let mut fields: Vec<&str> = json
+            .as_object()
+            .expect("object")
+            .keys()
+            .map(String::as_str)
+            .collect();
+        fields.sort();
+        assert_eq!(required, fields);
+    }
+
+    #[test]
+    fn topic_routing_sanitizes_ids() {
+        assert_eq!(
+            render_topic(DEFAULT_TOPIC_TEMPLATE, "tenant-1", "site.a b"),
+            "ems.tenant-1.site_a_b.measurements"
+        );
+        assert!(validate_topic_template(DEFAULT_TOPIC_TEMPLATE).is_ok());
+        assert!(validate_topic_template("ems.{tenant_id}.*").is_err());
+        assert!(validate_topic_template("ems..{project_id}").is_err());
+        assert!(validate_topic_template("").is_err());
+    }
+}
@@ -0,0 +1,48 @@
+//! 后台任务（Job）框架
+//!
+//! 长耗时操作（报表、清理、导入、汇总等）提交为后台任务异步执行，调用方立即得到任务 ID，
+//! 之后通过任务记录查询状态、进度与结果：
+//! - 状态：`queued` → `running` → `succeeded` / `failed` / `cancelled`
+//! - 进度：0-100，由任务处理器在执行过程中上报
+//! - 取消：排队中的任务直接取消；运行中的任务标记取消请求，处理器在下一次进度上报时停止
+//!
+//! 任务记录保存在 `JobStore` 中；任务类型由 `JobHandler` 实现并在 `JobRunner` 上注册。
+
+use ems_storage::StorageError;
+
+mod runner;
+pub use runner::*;
+
+/// 任务状态：排队中
+pub const STATUS_QUEUED: &str = "queued";
+/// 任务状态：执行中
+pub const STATUS_RUNNING: &str = "running";
+/// 任务状态：执行成功
+pub const STATUS_SUCCEEDED: &str = "succeeded";
+/// 任务状态：执行失败（含服务停机中断）
+pub const STATUS_FAILED: &str = "failed";
+/// 任务状态：已取消
+pub const STATUS_CANCELLED: &str = "cancelled";
+
+/// 全部任务状态
+pub const JOB_STATUSES: &[&str] = &[
+    STATUS_QUEUED,
+    STATUS_RUNNING,
+    STATUS_SUCCEEDED,
+    STATUS_FAILED,
+    STATUS_CANCELLED,
+];
+
+/// 任务是否已结束（succeeded / failed / cancelled）
+pub fn is_finished(status: &str) -> bool {
+    status != STATUS_QUEUED && status != STATUS_RUNNING
+}
+
+/// 任务提交错误。
+#[derive(Debug, thiserror::Error)]
+pub enum JobError {
+    #[error("unknown job kind: {0}")]
+    UnknownKind(String),
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+}
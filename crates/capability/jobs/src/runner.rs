@@ -0,0 +1,456 @@
+//! 后台任务执行器。
+//!
+//! 提交时写入 `queued` 任务记录并在后台执行：取得并发许可后转为 `running`，
+//! 调用对应类型的 `JobHandler`，按返回值写入 `succeeded`（结果）或 `failed`（错误）；
+//! 处理器 panic 时记为 `failed`。
+//!
+//! 任务在提交它的实例内执行；服务停机时未结束的任务由下次启动时的
+//! `fail_interrupted` 标记为 `failed`，多实例部署时需只在一个实例上执行回收。
+
+use crate::{JobError, STATUS_CANCELLED, STATUS_FAILED, STATUS_QUEUED, STATUS_SUCCEEDED};
+use domain::TenantContext;
+use ems_storage::{JobFinish, JobRecord, JobStore, StorageError};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Semaphore;
+use tracing::{info, warn};
+
+/// 停机中断任务的错误描述
+pub const INTERRUPTED_ERROR: &str = "interrupted by service restart";
+
+/// 任务执行器配置
+#[derive(Debug, Clone)]
+pub struct JobRunnerConfig {
+    /// 同时执行的任务数上限（其余任务保持 `queued`）
+    pub max_concurrency: usize,
+}
+
+impl Default for JobRunnerConfig {
+    fn default() -> Self {
+        Self { max_concurrency: 4 }
+    }
+}
+
+/// 任务处理器（一种任务类型的执行逻辑）
+#[async_trait::async_trait]
+pub trait JobHandler: Send + Sync {
+    /// 执行任务，返回结果（JSON）；失败时返回错误描述
+    ///
+    /// 长耗时处理器应定期调用 [`JobContext::set_progress`]，返回 false 时尽快停止。
+    async fn run(&self, job: &JobContext) -> Result<serde_json::Value, String>;
+}
+
+/// 任务执行上下文（传给处理器）
+pub struct JobContext {
+    record: JobRecord,
+    ctx: TenantContext,
+    store: Arc<dyn JobStore>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl JobContext {
+    /// 任务记录（开始执行时的快照）
+    pub fn record(&self) -> &JobRecord {
+        &self.record
+    }
+
+    /// 提交任务时的租户上下文
+    pub fn tenant_context(&self) -> &TenantContext {
+        &self.ctx
+    }
+
+    /// 解析任务参数
+    pub fn params<T: DeserializeOwned>(&self) -> Result<T, String> {
+        serde_json::from_str(&self.record.params).map_err(|err| format!("invalid params: {err}"))
+    }
+
+    /// 是否已请求取消
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// 上报进度（0-100），返回是否应继续执行
+    ///
+    /// 同时读取任务记录上的取消请求（可能来自其它实例），任务已不在运行时同样返回 false。
+    pub async fn set_progress(&self, progress: i32) -> bool {
+        match self
+            .store
+            .update_job_progress(&self.ctx, &self.record.job_id, progress, now_epoch_ms())
+            .await
+        {
+            Ok(Some(record)) if record.cancel_requested => {
+                self.cancelled.store(true, Ordering::SeqCst);
+            }
+            Ok(Some(_)) => {}
+            Ok(None) => self.cancelled.store(true, Ordering::SeqCst),
+            // 进度写入失败不影响执行
+            Err(err) => {
+                warn!(target: "ems.jobs", job_id = %self.record.job_id, error = %err, "job_progress_update_failed");
+            }
+        }
+        !self.is_cancelled()
+    }
+}
+
+/// 后台任务执行器
+pub struct JobRunner {
+    store: Arc<dyn JobStore>,
+    handlers: HashMap<String, Arc<dyn JobHandler>>,
+    permits: Arc<Semaphore>,
+    /// 本实例内未结束任务的取消标记
+    running: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl JobRunner {
+    pub fn new(store: Arc<dyn JobStore>, config: &JobRunnerConfig) -> Self {
+        Self {
+            store,
+            handlers: HashMap::new(),
+            permits: Arc::new(Semaphore::new(config.max_concurrency.max(1))),
+            running: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 注册任务类型（同名类型覆盖）
+    pub fn register(&mut self, kind: &str, handler: Arc<dyn JobHandler>) {
+        self.handlers.insert(kind.to_string(), handler);
+    }
+
+    /// 已注册的任务类型（按名称排序）
+    pub fn kinds(&self) -> Vec<&str> {
+        let mut kinds: Vec<&str> = self.handlers.keys().map(String::as_str).collect();
+        kinds.sort();
+        kinds
+    }
+
+    /// 提交任务：写入 `queued` 记录并在后台执行
+    pub async fn submit(
+        self: &Arc<Self>,
+        ctx: &TenantContext,
+        project_id: Option<&str>,
+        kind: &str,
+        params: serde_json::Value,
+    ) -> Result<JobRecord, JobError> {
+        let Some(handler) = self.handlers.get(kind).cloned() else {
+            return Err(JobError::UnknownKind(kind.to_string()));
+        };
+        let now_ms = now_epoch_ms();
+        let record = JobRecord {
+            job_id: uuid::Uuid::new_v4().to_string(),
+            tenant_id: ctx.tenant_id.clone(),
+            project_id: project_id.map(str::to_string),
+            kind: kind.to_string(),
+            status: STATUS_QUEUED.to_string(),
+            params: params.to_string(),
+            progress: 0,
+            result: None,
+            error: None,
+            cancel_requested: false,
+            created_by: ctx.user_id.clone(),
+            created_at_ms: now_ms,
+            started_at_ms: None,
+            finished_at_ms: None,
+            updated_at_ms: now_ms,
+        };
+        let record = self.store.create_job(ctx, record).await?;
+        let cancelled = Arc::new(AtomicBool::new(false));
+        if let Ok(mut running) = self.running.lock() {
+            running.insert(record.job_id.clone(), cancelled.clone());
+        }
+        info!(target: "ems.jobs", job_id = %record.job_id, kind = %record.kind, "job_queued");
+        let runner = self.clone();
+        let ctx = ctx.clone();
+        let job_id = record.job_id.clone();
+        tokio::spawn(async move {
+            runner.execute(ctx, &job_id, handler, cancelled).await;
+            if let Ok(mut running) = runner.running.lock() {
+                running.remove(&job_id);
+            }
+        });
+        Ok(record)
+    }
+
+    /// 请求取消任务，返回最新的任务记录（不存在时返回 None）
+    pub async fn cancel(
+        &self,
+        ctx: &TenantContext,
+        job_id: &str,
+    ) -> Result<Option<JobRecord>, StorageError> {
+        let record = self
+            .store
+            .request_job_cancel(ctx, job_id, now_epoch_ms())
+            .await?;
+        if record
+            .as_ref()
+            .is_some_and(|record| record.cancel_requested)
+            && let Ok(running) = self.running.lock()
+            && let Some(flag) = running.get(job_id)
+        {
+            flag.store(true, Ordering::SeqCst);
+        }
+        Ok(record)
+    }
+
+    /// 把上次停机时未结束的任务标记为 `failed`，返回数量
+    pub async fn fail_interrupted(&self) -> Result<u64, StorageError> {
+        let count = self
+            .store
+            .fail_unfinished_jobs(INTERRUPTED_ERROR, now_epoch_ms())
+            .await?;
+        if count > 0 {
+            warn!(target: "ems.jobs", count = count, "interrupted_jobs_failed");
+        }
+        Ok(count)
+    }
+
+    async fn execute(
+        &self,
+        ctx: TenantContext,
+        job_id: &str,
+        handler: Arc<dyn JobHandler>,
+        cancelled: Arc<AtomicBool>,
+    ) {
+        let Ok(_permit) = self.permits.clone().acquire_owned().await else {
+            return;
+        };
+        // 排队期间已取消的任务不再执行
+        let record = match self.store.start_job(&ctx, job_id, now_epoch_ms()).await {
+            Ok(Some(record)) => record,
+            Ok(None) => return,
+            Err(err) => {
+                warn!(target: "ems.jobs", job_id = %job_id, error = %err, "job_start_failed");
+                return;
+            }
+        };
+        let job = JobContext {
+            record,
+            ctx: ctx.clone(),
+            store: self.store.clone(),
+            cancelled: cancelled.clone(),
+        };
+        // 在独立任务中执行，处理器 panic 时仍能写入终态
+        let outcome = tokio::spawn(async move { handler.run(&job).await }).await;
+        let (status, result, error) = if cancelled.load(Ordering::SeqCst) {
+            (STATUS_CANCELLED, None, None)
+        } else {
+            match outcome {
+                Ok(Ok(value)) => (STATUS_SUCCEEDED, Some(value.to_string()), None),
+                Ok(Err(message)) => (STATUS_FAILED, None, Some(message)),
+                Err(_) => (STATUS_FAILED, None, Some("job panicked".to_string())),
+            }
+        };
+        let finish = JobFinish {
+            status: status.to_string(),
+            result,
+            error,
+            finished_at_ms: now_epoch_ms(),
+        };
+        match self.store.finish_job(&ctx, job_id, finish).await {
+            Ok(_) => info!(target: "ems.jobs", job_id = %job_id, status = status, "job_finished"),
+            Err(err) => {
+                warn!(target: "ems.jobs", job_id = %job_id, error = %err, "job_finish_failed");
+            }
+        }
+    }
+}
+
+fn now_epoch_ms() -> i64 {
+    let now = std::time::SystemTime::now();
+    let duration = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    duration.as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{STATUS_RUNNING, is_finished};
+    use ems_storage::InMemoryJobStore;
+    use std::time::Duration;
+    use tokio::sync::Notify;
+
+    fn ctx() -> TenantContext {
+        TenantContext::new(
+            "tenant-1".to_string(),
+            "user-1".to_string(),
+            Vec::new(),
+            Vec::new(),
+            None,
+        )
+    }
+
+    /// 按参数上报进度的处理器；`fail` 为 true 时返回错误
+    struct StepHandler;
+
+    #[async_trait::async_trait]
+    impl JobHandler for StepHandler {
+        async fn run(&self, job: &JobContext) -> Result<serde_json::Value, String> {
+            let params: serde_json::Value = job.params()?;
+            if params["fail"].as_bool() == Some(true) {
+                return Err("boom".to_string());
+            }
+            for progress in [25, 50, 75] {
+                if !job.set_progress(progress).await {
+                    return Ok(serde_json::Value::Null);
+                }
+            }
+            Ok(serde_json::json!({ "steps": 3 }))
+        }
+    }
+
+    /// 阻塞到收到通知后才检查取消的处理器
+    struct BlockingHandler {
+        release: Arc<Notify>,
+    }
+
+    #[async_trait::async_trait]
+    impl JobHandler for BlockingHandler {
+        async fn run(&self, job: &JobContext) -> Result<serde_json::Value, String> {
+            self.release.notified().await;
+            if !job.set_progress(10).await {
+                return Ok(serde_json::Value::Null);
+            }
+            Ok(serde_json::json!({ "done": true }))
+        }
+    }
+
+    async fn wait_for(store: &InMemoryJobStore, job_id: &str, done: fn(&str) -> bool) -> JobRecord {
+        for _ in 0..200 {
+            let job = store
+                .find_job(&ctx(), job_id)
+                .await
+                .expect("find")
+                .expect("job");
+            if done(&job.status) {
+                return job;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        panic!("job {job_id} did not reach expected status");
+    }
+
+    #[tokio::test]
+    async fn runs_jobs_to_completion() {
+        let store = Arc::new(InMemoryJobStore::new());
+        let mut runner = JobRunner::new(store.clone(), &JobRunnerConfig::default());
+        runner.register("test.step", Arc::new(StepHandler));
+        let runner = Arc::new(runner);
+
+        let err = runner
+            .submit(&ctx(), None, "test.unknown", serde_json::json!({}))
+            .await;
+        assert!(matches!(err, Err(JobError::UnknownKind(_))));
+
+        let job = runner
+            .submit(&ctx(), None, "test.step", serde_json::json!({}))
+            .await
+            .expect("submit");
+        assert_eq!(job.status, STATUS_QUEUED);
+        let job = wait_for(&store, &job.job_id, is_finished).await;
+        assert_eq!(job.status, STATUS_SUCCEEDED);
+        assert_eq!(job.progress, 100);
+        assert_eq!(job.result.as_deref(), Some(r#"{"steps":3}"#));
+        assert!(job.started_at_ms.is_some() && job.finished_at_ms.is_some());
+
+        let job = runner
+            .submit(
+                &ctx(),
+                None,
+                "test.step",
+                serde_json::json!({ "fail": true }),
+            )
+            .await
+            .expect("submit");
+        let job = wait_for(&store, &job.job_id, is_finished).await;
+        assert_eq!(job.status, STATUS_FAILED);
+        assert_eq!(job.error.as_deref(), Some("boom"));
+    }
+
+    #[tokio::test]
+    async fn cancels_queued_and_running_jobs() {
+        let store = Arc::new(InMemoryJobStore::new());
+        let release = Arc::new(Notify::new());
+        let mut runner = JobRunner::new(store.clone(), &JobRunnerConfig { max_concurrency: 1 });
+        runner.register(
+            "test.blocking",
+            Arc::new(BlockingHandler {
+                release: release.clone(),
+            }),
+        );
+        let runner = Arc::new(runner);
+
+        // 并发上限为 1：第二个任务保持排队
+        let running = runner
+            .submit(&ctx(), None, "test.blocking", serde_json::json!({}))
+            .await
+            .expect("submit");
+        let queued = runner
+            .submit(&ctx(), None, "test.blocking", serde_json::json!({}))
+            .await
+            .expect("submit");
+        wait_for(&store, &running.job_id, |status| status == STATUS_RUNNING).await;
+
+        let job = runner
+            .cancel(&ctx(), &queued.job_id)
+            .await
+            .expect("cancel")
+            .expect("job");
+        assert_eq!(job.status, STATUS_CANCELLED);
+        let job = runner
+            .cancel(&ctx(), &running.job_id)
+            .await
+            .expect("cancel")
+            .expect("job");
+        assert_eq!(job.status, STATUS_RUNNING);
+        assert!(job.cancel_requested);
+
+        release.notify_one();
+        let job = wait_for(&store, &running.job_id, is_finished).await;
+        assert_eq!(job.status, STATUS_CANCELLED);
+        assert!(job.result.is_none());
+        // 已取消的排队任务不会再开始执行
+        let job = wait_for(&store, &queued.job_id, is_finished).await;
+        assert!(job.started_at_ms.is_none());
+    }
+
+    #[tokio::test]
+    async fn fails_interrupted_jobs() {
+        let store = Arc::new(InMemoryJobStore::new());
+        let runner = JobRunner::new(store.clone(), &JobRunnerConfig::default());
+        store
+            .create_job(
+                &ctx(),
+                JobRecord {
+                    job_id: "job-1".to_string(),
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: None,
+                    kind: "test.step".to_string(),
+                    status: STATUS_RUNNING.to_string(),
+                    params: "{}".to_string(),
+                    progress: 40,
+                    result: None,
+                    error: None,
+                    cancel_requested: false,
+                    created_by: "user-1".to_string(),
+                    created_at_ms: 1_000,
+                    started_at_ms: Some(1_000),
+                    finished_at_ms: None,
+                    updated_at_ms: 1_000,
+                },
+            )
+            .await
+            .expect("create");
+        assert_eq!(runner.fail_interrupted().await.expect("recover"), 1);
+        let job = store
+            .find_job(&ctx(), "job-1")
+            .await
+            .expect("find")
+            .expect("job");
+        assert_eq!(job.status, STATUS_FAILED);
+        assert_eq!(job.error.as_deref(), Some(INTERRUPTED_ERROR));
+        assert_eq!(runner.fail_interrupted().await.expect("recover"), 0);
+    }
+}
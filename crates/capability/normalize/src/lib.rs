@@ -3,19 +3,49 @@ use domain::{PointValue, PointValueData, RawEvent, TenantContext};
 use ems_storage::PointMappingStore;
 use std::sync::Arc;
 
+pub mod line_protocol;
+
+pub use line_protocol::{LineProtocolLine, TimestampPrecision, parse_line_protocol};
+
 /// 点位映射信息。
 #[derive(Debug, Clone)]
 pub struct PointMapping {
+    /// 命中的映射 ID
+    pub source_id: String,
     pub point_id: String,
     pub scale: Option<f64>,
     pub offset: Option<f64>,
 }
 
+impl PointMapping {
+    /// 应用缩放与偏移：先乘 scale，再加 offset。
+    pub fn apply(&self, raw: f64) -> f64 {
+        let mut value = raw;
+        if let Some(scale) = self.scale {
+            value *= scale;
+        }
+        if let Some(offset) = self.offset {
+            value += offset;
+        }
+        value
+    }
+}
+
+/// 解析原始报文：UTF-8 文本去除首尾空白后按浮点数解析。
+pub fn parse_payload(payload: &[u8]) -> Result<f64, NormalizeError> {
+    let payload_str = std::str::from_utf8(payload)
+        .map_err(|err| NormalizeError::InvalidPayload(err.to_string()))?;
+    payload_str
+        .trim()
+        .parse::<f64>()
+        .map_err(|err| NormalizeError::InvalidPayload(err.to_string()))
+}
+
 /// 规范化错误。
 #[derive(Debug, thiserror::Error)]
 pub enum NormalizeError {
     #[error("mapping provider error: {0}")]
-    MappingProvider(String),
+    MappingProvider(#[from] ems_storage::StorageError),
     #[error("invalid payload: {0}")]
     InvalidPayload(String),
 }
@@ -58,19 +88,7 @@ impl Normalizer {
             None => return Ok(None),
         };
 
-        let payload_str = std::str::from_utf8(&event.payload)
-            .map_err(|err| NormalizeError::InvalidPayload(err.to_string()))?;
-        let mut value = payload_str
-            .trim()
-            .parse::<f64>()
-            .map_err(|err| NormalizeError::InvalidPayload(err.to_string()))?;
-
-        if let Some(scale) = mapping.scale {
-            value *= scale;
-        }
-        if let Some(offset) = mapping.offset {
-            value += offset;
-        }
+        let value = mapping.apply(parse_payload(&event.payload)?);
 
         Ok(Some(PointValue {
             tenant_id: event.tenant_id,
@@ -116,11 +134,11 @@ impl PointMappingProvider for StoragePointMappingProvider {
             let record = self
                 .store
                 .find_point_mapping(&ctx, project_id, source_id)
-                .await
-                .map_err(|err| NormalizeError::MappingProvider(err.to_string()))?;
+                .await?;
             if let Some(record) = record {
                 if record.address == address {
                     return Ok(Some(PointMapping {
+                        source_id: record.source_id,
                         point_id: record.point_id,
                         scale: record.scale,
                         offset: record.offset,
@@ -130,13 +148,10 @@ impl PointMappingProvider for StoragePointMappingProvider {
         }
 
         if !address.is_empty() {
-            let mappings = self
-                .store
-                .list_point_mappings(&ctx, project_id)
-                .await
-                .map_err(|err| NormalizeError::MappingProvider(err.to_string()))?;
+            let mappings = self.store.list_point_mappings(&ctx, project_id).await?;
             if let Some(record) = mappings.into_iter().find(|item| item.address == address) {
                 return Ok(Some(PointMapping {
+                    source_id: record.source_id,
                     point_id: record.point_id,
                     scale: record.scale,
                     offset: record.offset,
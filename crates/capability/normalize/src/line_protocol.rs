@@ -0,0 +1,359 @@
+//! InfluxDB 行协议解析
+//!
+//! 每行 `measurement[,tag=value...] field=value[,field=value...] [timestamp]`：
+//! - 度量名转义 `,` 与空格；标签键值、字段键转义 `,`、`=` 与空格
+//! - 字段值：浮点（`1.5`）、整数（`1i` / `1u`）、布尔（`t` / `true` / `f` / `false` 等）、字符串（`"..."`，转义 `"` 与 `\`）
+//! - 空行与 `#` 开头的注释行忽略
+//!
+//! 点位映射见 [`LineProtocolLine::point_samples`]。
+
+use crate::NormalizeError;
+use domain::PointValueData;
+use std::collections::BTreeMap;
+
+/// 指定点位 ID 的标签
+pub const POINT_ID_TAG: &str = "point_id";
+/// 指定数据质量的标签
+pub const QUALITY_TAG: &str = "quality";
+/// 写入 `point_id` 标签对应点位的字段
+pub const VALUE_FIELD: &str = "value";
+
+/// 时间戳精度（`precision` 参数）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimestampPrecision {
+    #[default]
+    Nanoseconds,
+    Microseconds,
+    Milliseconds,
+    Seconds,
+}
+
+impl TimestampPrecision {
+    /// 解析 `precision` 参数（兼容 1.x 的 `n` / `u` 与 2.x 的 `ns` / `us`）
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "n" | "ns" => Some(Self::Nanoseconds),
+            "u" | "us" => Some(Self::Microseconds),
+            "ms" => Some(Self::Milliseconds),
+            "s" => Some(Self::Seconds),
+            _ => None,
+        }
+    }
+
+    /// 换算为毫秒（向下取整）；溢出时返回 None
+    pub fn to_ms(self, timestamp: i64) -> Option<i64> {
+        match self {
+            Self::Nanoseconds => Some(timestamp.div_euclid(1_000_000)),
+            Self::Microseconds => Some(timestamp.div_euclid(1_000)),
+            Self::Milliseconds => Some(timestamp),
+            Self::Seconds => timestamp.checked_mul(1_000),
+        }
+    }
+}
+
+/// 解析后的一行数据
+#[derive(Debug, Clone)]
+pub struct LineProtocolLine {
+    pub measurement: String,
+    pub tags: BTreeMap<String, String>,
+    pub fields: Vec<(String, PointValueData)>,
+    /// 原始时间戳（按请求精度解释）
+    pub timestamp: Option<i64>,
+}
+
+impl LineProtocolLine {
+    /// 字段映射为点位值
+    ///
+    /// - 带 `point_id` 标签：`value` 字段写入该点位，其余字段写入 `{point_id}.{field}`
+    /// - 不带 `point_id` 标签：字段写入 `{measurement}.{field}`
+    pub fn point_samples(&self) -> Vec<(String, PointValueData)> {
+        let point_id = self.tags.get(POINT_ID_TAG);
+        self.fields
+            .iter()
+            .map(|(field, value)| {
+                let target = match point_id {
+                    Some(point_id) if field == VALUE_FIELD => point_id.clone(),
+                    Some(point_id) => format!("{point_id}.{field}"),
+                    None => format!("{}.{field}", self.measurement),
+                };
+                (target, value.clone())
+            })
+            .collect()
+    }
+
+    /// `quality` 标签
+    pub fn quality(&self) -> Option<String> {
+        self.tags.get(QUALITY_TAG).cloned()
+    }
+}
+
+/// 解析行协议文本；任一行格式错误时返回带行号的错误
+pub fn parse_line_protocol(text: &str) -> Result<Vec<LineProtocolLine>, NormalizeError> {
+    let mut lines = Vec::new();
+    for (index, raw) in text.lines().enumerate() {
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let parsed = parse_line(line).map_err(|message| {
+            NormalizeError::InvalidPayload(format!("line {}: {message}", index + 1))
+        })?;
+        lines.push(parsed);
+    }
+    Ok(lines)
+}
+
+fn parse_line(line: &str) -> Result<LineProtocolLine, String> {
+    let mut scanner = Scanner::new(line);
+    let measurement = scanner.read_token(&[',', ' '], &[',', ' ']);
+    if measurement.is_empty() {
+        return Err("missing measurement".to_string());
+    }
+
+    let mut tags = BTreeMap::new();
+    while scanner.eat(',') {
+        let key = scanner.read_token(&['=', ',', ' '], &[',', '=', ' ']);
+        if !scanner.eat('=') {
+            return Err(format!("missing tag value for {key:?}"));
+        }
+        let value = scanner.read_token(&[',', ' '], &[',', '=', ' ']);
+        if key.is_empty() || value.is_empty() {
+            return Err("empty tag key or value".to_string());
+        }
+        tags.insert(key, value);
+    }
+
+    if !scanner.skip_spaces() {
+        return Err("missing fields".to_string());
+    }
+    let mut fields = Vec::new();
+    loop {
+        let key = scanner.read_token(&['=', ',', ' '], &[',', '=', ' ']);
+        if key.is_empty() || !scanner.eat('=') {
+            return Err("invalid field".to_string());
+        }
+        let value = scanner.read_field_value()?;
+        fields.push((key, value));
+        if !scanner.eat(',') {
+            break;
+        }
+    }
+
+    let timestamp = if scanner.skip_spaces() && !scanner.is_end() {
+        let raw = scanner.read_token(&[' '], &[]);
+        Some(
+            raw.parse::<i64>()
+                .map_err(|_| format!("invalid timestamp {raw:?}"))?,
+        )
+    } else {
+        None
+    };
+    scanner.skip_spaces();
+    if !scanner.is_end() {
+        return Err("unexpected trailing data".to_string());
+    }
+    Ok(LineProtocolLine {
+        measurement,
+        tags,
+        fields,
+        timestamp,
+    })
+}
+
+struct Scanner {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Scanner {
+    fn new(line: &str) -> Self {
+        Self {
+            chars: line.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn is_end(&self) -> bool {
+        self.pos >= self.chars.len()
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// 跳过空格，返回是否跳过了至少一个
+    fn skip_spaces(&mut self) -> bool {
+        let start = self.pos;
+        while self.peek() == Some(' ') {
+            self.pos += 1;
+        }
+        self.pos > start
+    }
+
+    /// 读取到未转义的分隔符为止；`\` 后跟可转义字符时取该字符，否则按原样保留
+    fn read_token(&mut self, stops: &[char], escapable: &[char]) -> String {
+        let mut token = String::new();
+        while let Some(ch) = self.peek() {
+            if ch == '\\'
+                && let Some(next) = self.chars.get(self.pos + 1).copied()
+                && escapable.contains(&next)
+            {
+                token.push(next);
+                self.pos += 2;
+                continue;
+            }
+            if stops.contains(&ch) {
+                break;
+            }
+            token.push(ch);
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn read_field_value(&mut self) -> Result<PointValueData, String> {
+        if self.eat('"') {
+            let mut value = String::new();
+            loop {
+                match self.peek() {
+                    None => return Err("unterminated string field".to_string()),
+                    Some('"') => {
+                        self.pos += 1;
+                        return Ok(PointValueData::String(value));
+                    }
+                    Some('\\') if matches!(self.chars.get(self.pos + 1), Some('"' | '\\')) => {
+                        value.push(self.chars[self.pos + 1]);
+                        self.pos += 2;
+                    }
+                    Some(ch) => {
+                        value.push(ch);
+                        self.pos += 1;
+                    }
+                }
+            }
+        }
+        let raw = self.read_token(&[',', ' '], &[]);
+        parse_scalar(&raw).ok_or_else(|| format!("invalid field value {raw:?}"))
+    }
+}
+
+fn parse_scalar(raw: &str) -> Option<PointValueData> {
+    match raw {
+        "t" | "T" | "true" | "True" | "TRUE" => return Some(PointValueData::Bool(true)),
+        "f" | "F" | "false" | "False" | "FALSE" => return Some(PointValueData::Bool(false)),
+        _ => {}
+    }
+    if let Some(integer) = raw.strip_suffix('i') {
+        return integer.parse::<i64>().ok().map(PointValueData::I64);
+    }
+    if let Some(unsigned) = raw.strip_suffix('u') {
+        let value = unsigned.parse::<u64>().ok()?;
+        return i64::try_from(value).ok().map(PointValueData::I64);
+    }
+    // 浮点不接受 NaN / inf（与 InfluxDB 一致）
+    if !raw
+        .chars()
+        .all(|ch| ch.is_ascii_digit() || matches!(ch, '.' | '-' | '+' | 'e' | 'E'))
+    {
+        return None;
+    }
+    raw.parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite())
+        .map(PointValueData::F64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples(line: &LineProtocolLine) -> Vec<(String, String)> {
+        line.point_samples()
+            .into_iter()
+            .map(|(point_id, value)| (point_id, format!("{value:?}")))
+            .collect()
+    }
+
+    #[test]
+    fn parses_tags_fields_and_escapes() {
+        let lines = parse_line_protocol(
+            "# telegraf\n\
+             power,point_id=meter-1.kw,quality=good value=12.5,voltage=230i 1735689600000000000\n\
+             \n\
+             my\\ meas,site=a\\,b on=t,status=\"ok \\\"fine\\\"\",count=7u\n",
+        )
+        .expect("parse");
+        assert_eq!(lines.len(), 2);
+
+        let first = &lines[0];
+        assert_eq!(first.measurement, "power");
+        assert_eq!(first.timestamp, Some(1_735_689_600_000_000_000));
+        assert_eq!(first.quality(), Some("good".to_string()));
+        assert_eq!(
+            samples(first),
+            vec![
+                ("meter-1.kw".to_string(), "F64(12.5)".to_string()),
+                ("meter-1.kw.voltage".to_string(), "I64(230)".to_string()),
+            ]
+        );
+
+        let second = &lines[1];
+        assert_eq!(second.measurement, "my meas");
+        assert_eq!(second.tags.get("site").map(String::as_str), Some("a,b"));
+        assert_eq!(second.timestamp, None);
+        assert_eq!(
+            samples(second),
+            vec![
+                ("my meas.on".to_string(), "Bool(true)".to_string()),
+                (
+                    "my meas.status".to_string(),
+                    r#"String("ok \"fine\"")"#.to_string(),
+                ),
+                ("my meas.count".to_string(), "I64(7)".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_lines_with_line_number() {
+        for (text, expected) in [
+            ("cpu", "line 1: missing fields"),
+            ("cpu value=1\ncpu,host value=1", "line 2: missing tag value"),
+            ("cpu value=abc", "line 1: invalid field value"),
+            ("cpu value=NaN", "line 1: invalid field value"),
+            ("cpu value=\"open", "line 1: unterminated string field"),
+            ("cpu value=1 12x", "line 1: invalid timestamp"),
+            ("cpu value=1 1 2", "line 1: unexpected trailing data"),
+        ] {
+            let err = parse_line_protocol(text).expect_err(text).to_string();
+            assert!(err.contains(expected), "{text}: {err}");
+        }
+    }
+
+    #[test]
+    fn precision_converts_to_milliseconds() {
+        assert_eq!(
+            TimestampPrecision::parse("n").and_then(|p| p.to_ms(1_500_000_999)),
+            Some(1_500)
+        );
+        assert_eq!(
+            TimestampPrecision::parse("us").and_then(|p| p.to_ms(-1)),
+            Some(-1)
+        );
+        assert_eq!(
+            TimestampPrecision::Seconds.to_ms(1_700_000_000),
+            Some(1_700_000_000_000)
+        );
+        assert_eq!(TimestampPrecision::Seconds.to_ms(i64::MAX), None);
+        assert_eq!(TimestampPrecision::parse("h"), None);
+    }
+}
@@ -2,7 +2,7 @@ use async_trait::async_trait;
 use domain::{PointValue, PointValueData, TenantContext};
 use ems_storage::{MeasurementStore, RealtimeStore};
 use std::collections::{HashMap, VecDeque};
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use tokio::sync::Mutex;
 
 /// 写入结果（最小占位）。
@@ -132,7 +132,7 @@ struct PipelineState {
 
 struct PipelineInner {
     writer: Arc<dyn PointValueWriter>,
-    config: PipelineConfig,
+    config: RwLock<PipelineConfig>,
     state: Mutex<PipelineState>,
 }
 
@@ -151,7 +151,7 @@ impl Pipeline {
         let config = config.sanitized();
         let inner = PipelineInner {
             writer,
-            config: config.clone(),
+            config: RwLock::new(config.clone()),
             state: Mutex::new(PipelineState {
                 buffer: Vec::new(),
                 dedup: DedupState::new(config.dedup_cache_size),
@@ -162,10 +162,27 @@ impl Pipeline {
         }
     }
 
+    /// 当前生效的参数。
+    pub fn config(&self) -> PipelineConfig {
+        self.inner
+            .config
+            .read()
+            .map(|config| config.clone())
+            .unwrap_or_default()
+    }
+
+    /// 运行时替换参数（热加载）；已缓冲的数据保留，下一次写入起按新参数处理。
+    pub fn reconfigure(&self, config: PipelineConfig) {
+        if let Ok(mut current) = self.inner.config.write() {
+            *current = config.sanitized();
+        }
+    }
+
     pub async fn handle(&self, value: PointValue) -> Result<WriteResult, PipelineError> {
         let point_id = value.point_id.clone();
+        let config = self.config();
 
-        if let Some(reason) = validate_value(&value, self.inner.config.max_age_ms) {
+        if let Some(reason) = validate_value(&value, config.max_age_ms) {
             return Ok(WriteResult {
                 point_id,
                 written: false,
@@ -174,9 +191,10 @@ impl Pipeline {
         }
 
         let mut state = self.inner.state.lock().await;
-        if state.buffer.len() >= self.inner.config.max_buffer_size {
+        if state.buffer.len() >= config.max_buffer_size {
             return Err(PipelineError::Backpressure("buffer full".to_string()));
         }
+        state.dedup.capacity = config.dedup_cache_size;
         if state
             .dedup
             .is_duplicate(dedup_key(&value), signature_from_value(&value))
@@ -189,7 +207,7 @@ impl Pipeline {
         }
         state.buffer.push(value);
         let index = state.buffer.len().saturating_sub(1);
-        if state.buffer.len() < self.inner.config.batch_size {
+        if state.buffer.len() < config.batch_size {
             return Ok(WriteResult {
                 point_id,
                 written: false,
@@ -244,7 +262,7 @@ impl Pipeline {
                 Ok(results) => return Ok(results),
                 Err(err) => {
                     attempt += 1;
-                    if attempt > self.inner.config.max_retries {
+                    if attempt > self.config().max_retries {
                         return Err(err);
                     }
                 }
@@ -257,7 +275,7 @@ impl Pipeline {
             return Ok(());
         }
         let mut state = self.inner.state.lock().await;
-        if state.buffer.len() + values.len() > self.inner.config.max_buffer_size {
+        if state.buffer.len() + values.len() > self.config().max_buffer_size {
             return Err(PipelineError::Backpressure(
                 "buffer overflow after retry".to_string(),
             ));
@@ -496,6 +514,36 @@ mod tests {
         assert_eq!(batches.as_slice(), &[2]);
     }
 
+    #[tokio::test]
+    async fn pipeline_reconfigure_applies_new_batch_size() {
+        let writer = Arc::new(CountingWriter::default());
+        let pipeline = Pipeline::with_config(
+            writer.clone(),
+            PipelineConfig {
+                batch_size: 3,
+                max_buffer_size: 10,
+                max_retries: 1,
+                dedup_cache_size: 0,
+                max_age_ms: None,
+            },
+        );
+        let _ = pipeline
+            .handle(sample_value(1, PointValueData::I64(1)))
+            .await
+            .expect("queued");
+        pipeline.reconfigure(PipelineConfig {
+            batch_size: 2,
+            ..pipeline.config()
+        });
+        assert_eq!(pipeline.config().batch_size, 2);
+        let _ = pipeline
+            .handle(sample_value(2, PointValueData::I64(2)))
+            .await
+            .expect("written");
+        let batches = writer.batches.lock().await;
+        assert_eq!(batches.as_slice(), &[2]);
+    }
+
     #[tokio::test]
     async fn pipeline_dedup_skips_duplicate() {
         let writer = Arc::new(CountingWriter::default());
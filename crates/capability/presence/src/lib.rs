@@ -0,0 +1,185 @@
+//! 设备 / 网关离线检测。
+//!
+//! 在线状态存储只提供带 TTL 的最后活跃时间；离线检测任务定期读取全部设备与网关的
+//! 最后活跃时间，在状态变化时发布领域事件（供 Webhook 推送与告警规则消费）：
+//! - 超过阈值未上报：发布 `device.offline` / `gateway.offline`，并发布 `alarm.raised`（`source` 为 `presence`）
+//! - 离线后恢复上报：持续上报满恢复时长后发布 `device.online` / `gateway.online`
+//!
+//! 设备阈值取设备的 `offline_after_seconds`，未设置（以及网关）使用全局默认值；
+//! 恢复时长提供滞回，避免设备在阈值附近抖动时反复发布离线 / 恢复事件。
+//! 启动后首次看到的资源只建立基线（不发布事件），从未上报过的资源不判定。
+//!
+//! 评估状态只保存在进程内存中，多实例部署时需只在一个实例上启用离线检测。
+
+mod watcher;
+
+pub use watcher::*;
+
+/// 状态迁移
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    /// 在线 → 离线
+    Offline,
+    /// 离线 → 在线
+    Online,
+}
+
+/// 单个设备 / 网关的离线判定状态
+#[derive(Debug, Clone, Default)]
+pub struct PresenceState {
+    /// 当前判定（None 表示尚未建立基线）
+    online: Option<bool>,
+    /// 观察到的最大最后活跃时间（在线状态 TTL 过期后仍保留）
+    last_seen_at_ms: Option<i64>,
+    /// 离线后首次重新上报的时间
+    recovering_since_ms: Option<i64>,
+}
+
+impl PresenceState {
+    /// 当前是否判定为在线（尚未建立基线时为 None）
+    pub fn online(&self) -> Option<bool> {
+        self.online
+    }
+
+    /// 已观察到的最后活跃时间
+    pub fn last_seen_at_ms(&self) -> Option<i64> {
+        self.last_seen_at_ms
+    }
+
+    /// 合并本次读取的最后活跃时间并返回状态迁移
+    ///
+    /// - 在线：`now - last_seen > threshold_ms` 时迁移为离线
+    /// - 离线：重新上报后开始计时，持续上报跨度达到 `recovery_ms` 且未再次超时才迁移为在线
+    pub fn observe(
+        &mut self,
+        last_seen_at_ms: Option<i64>,
+        threshold_ms: i64,
+        recovery_ms: i64,
+        now_ms: i64,
+    ) -> Option<Transition> {
+        let previous = self.last_seen_at_ms;
+        if let Some(ts_ms) = last_seen_at_ms {
+            self.last_seen_at_ms = Some(previous.map_or(ts_ms, |value| value.max(ts_ms)));
+        }
+        let last_seen = self.last_seen_at_ms?;
+        let stale = now_ms - last_seen > threshold_ms;
+        match self.online {
+            None => {
+                self.online = Some(!stale);
+                None
+            }
+            Some(true) => {
+                if !stale {
+                    return None;
+                }
+                self.online = Some(false);
+                self.recovering_since_ms = None;
+                Some(Transition::Offline)
+            }
+            Some(false) => {
+                if stale {
+                    self.recovering_since_ms = None;
+                    return None;
+                }
+                let since = match self.recovering_since_ms {
+                    Some(since) => since,
+                    // 离线期间的首个新上报作为恢复起点
+                    None if previous.is_none_or(|value| last_seen > value) => {
+                        self.recovering_since_ms = Some(last_seen);
+                        last_seen
+                    }
+                    None => return None,
+                };
+                if last_seen - since < recovery_ms {
+                    return None;
+                }
+                self.online = Some(true);
+                self.recovering_since_ms = None;
+                Some(Transition::Online)
+            }
+        }
+    }
+
+    /// 推迟离线判定（维护窗口内）：恢复为在线，窗口结束后仍超时再迁移
+    fn defer_offline(&mut self) {
+        self.online = Some(true);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THRESHOLD_MS: i64 = 60_000;
+    const RECOVERY_MS: i64 = 30_000;
+
+    #[test]
+    fn first_observation_sets_baseline() {
+        let mut state = PresenceState::default();
+        assert_eq!(state.observe(None, THRESHOLD_MS, RECOVERY_MS, 0), None);
+        assert_eq!(state.online(), None);
+
+        assert_eq!(
+            state.observe(Some(0), THRESHOLD_MS, RECOVERY_MS, 100_000),
+            None
+        );
+        assert_eq!(state.online(), Some(false));
+    }
+
+    #[test]
+    fn offline_after_threshold_even_when_ttl_expired() {
+        let mut state = PresenceState::default();
+        assert_eq!(
+            state.observe(Some(0), THRESHOLD_MS, RECOVERY_MS, 1_000),
+            None
+        );
+        // 在线状态已过期（读不到最后活跃时间），按记住的时间判定
+        assert_eq!(state.observe(None, THRESHOLD_MS, RECOVERY_MS, 60_000), None);
+        assert_eq!(
+            state.observe(None, THRESHOLD_MS, RECOVERY_MS, 60_001),
+            Some(Transition::Offline)
+        );
+        assert_eq!(
+            state.observe(None, THRESHOLD_MS, RECOVERY_MS, 120_000),
+            None
+        );
+        assert_eq!(state.last_seen_at_ms(), Some(0));
+    }
+
+    #[test]
+    fn recovery_requires_sustained_reporting() {
+        let mut state = PresenceState::default();
+        state.observe(Some(0), THRESHOLD_MS, RECOVERY_MS, 0);
+        assert_eq!(
+            state.observe(None, THRESHOLD_MS, RECOVERY_MS, 100_000),
+            Some(Transition::Offline)
+        );
+        // 单次上报不恢复
+        assert_eq!(
+            state.observe(Some(100_000), THRESHOLD_MS, RECOVERY_MS, 100_000),
+            None
+        );
+        assert_eq!(
+            state.observe(Some(100_000), THRESHOLD_MS, RECOVERY_MS, 150_000),
+            None
+        );
+        // 再次超时，恢复计时重置
+        assert_eq!(
+            state.observe(None, THRESHOLD_MS, RECOVERY_MS, 170_000),
+            None
+        );
+        assert_eq!(
+            state.observe(Some(180_000), THRESHOLD_MS, RECOVERY_MS, 180_000),
+            None
+        );
+        assert_eq!(
+            state.observe(Some(200_000), THRESHOLD_MS, RECOVERY_MS, 200_000),
+            None
+        );
+        assert_eq!(
+            state.observe(Some(210_000), THRESHOLD_MS, RECOVERY_MS, 210_000),
+            Some(Transition::Online)
+        );
+        assert_eq!(state.online(), Some(true));
+    }
+}
@@ -0,0 +1,642 @@
+//! 离线检测后台任务。
+
+use crate::{PresenceState, Transition};
+use domain::TenantContext;
+use ems_control::MaintenanceService;
+use ems_events::{DomainEvent, EventBus, event_types};
+use ems_storage::{DeviceRecord, DeviceStore, GatewayRecord, GatewayStore, OnlineStore};
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+use tracing::{info, warn};
+
+/// 离线检测配置
+#[derive(Debug, Clone)]
+pub struct PresenceWatcherConfig {
+    /// 检测间隔（毫秒）
+    pub tick_ms: u64,
+    /// 默认离线阈值（秒，设备未设置阈值时及网关使用）
+    pub offline_after_seconds: u64,
+    /// 恢复滞回时长（秒，离线后需持续上报满该时长才判定恢复）
+    pub recovery_seconds: u64,
+}
+
+impl Default for PresenceWatcherConfig {
+    fn default() -> Self {
+        Self {
+            tick_ms: 10_000,
+            offline_after_seconds: 300,
+            recovery_seconds: 30,
+        }
+    }
+}
+
+/// 资源类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ResourceKind {
+    Device,
+    Gateway,
+}
+
+/// 资源状态键：(类型, 租户, 项目, 资源 ID)
+type StateKey = (ResourceKind, String, String, String);
+
+/// 待检测资源
+struct Resource {
+    kind: ResourceKind,
+    id: String,
+    name: String,
+    gateway_id: Option<String>,
+    threshold_seconds: i64,
+}
+
+/// 设备 / 网关离线检测器
+pub struct PresenceWatcher {
+    device_store: Arc<dyn DeviceStore>,
+    gateway_store: Arc<dyn GatewayStore>,
+    online_store: Arc<dyn OnlineStore>,
+    event_bus: EventBus,
+    maintenance: Option<Arc<MaintenanceService>>,
+    offline_after_seconds: i64,
+    recovery_ms: i64,
+    states: Mutex<HashMap<StateKey, PresenceState>>,
+}
+
+impl PresenceWatcher {
+    pub fn new(
+        device_store: Arc<dyn DeviceStore>,
+        gateway_store: Arc<dyn GatewayStore>,
+        online_store: Arc<dyn OnlineStore>,
+        event_bus: EventBus,
+        config: &PresenceWatcherConfig,
+    ) -> Self {
+        Self {
+            device_store,
+            gateway_store,
+            online_store,
+            event_bus,
+            maintenance: None,
+            offline_after_seconds: config.offline_after_seconds.max(1) as i64,
+            recovery_ms: config.recovery_seconds as i64 * 1000,
+            states: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 挂载维护模式（维护窗口内的设备 / 网关不判定离线，窗口结束后仍超时再发布）
+    pub fn with_maintenance(mut self, maintenance: Arc<MaintenanceService>) -> Self {
+        self.maintenance = Some(maintenance);
+        self
+    }
+
+    /// 运行一次检测，返回本次发布的离线 / 恢复事件
+    pub async fn run_once(&self, now_ms: i64) -> Vec<DomainEvent> {
+        let gateways = match self.gateway_store.list_all_gateways().await {
+            Ok(items) => items,
+            Err(err) => {
+                warn!(target: "ems.presence", error = %err, "presence_gateways_read_failed");
+                return Vec::new();
+            }
+        };
+        let devices = match self.device_store.list_all_devices().await {
+            Ok(items) => items,
+            Err(err) => {
+                warn!(target: "ems.presence", error = %err, "presence_devices_read_failed");
+                return Vec::new();
+            }
+        };
+
+        // 按 (租户, 项目) 分组，批量读取最后活跃时间
+        let mut projects: BTreeMap<(String, String), Vec<Resource>> = BTreeMap::new();
+        for gateway in gateways {
+            projects
+                .entry((gateway.tenant_id.clone(), gateway.project_id.clone()))
+                .or_default()
+                .push(self.gateway_resource(gateway));
+        }
+        for device in devices {
+            projects
+                .entry((device.tenant_id.clone(), device.project_id.clone()))
+                .or_default()
+                .push(self.device_resource(device));
+        }
+
+        let mut alive: HashSet<StateKey> = HashSet::new();
+        let mut events = Vec::new();
+        for ((tenant_id, project_id), resources) in projects {
+            let ctx = watcher_context(&tenant_id, &project_id);
+            let Some(last_seen) = self.last_seen(&ctx, &project_id, &resources).await else {
+                // 读取失败时保留状态，下次重试
+                alive.extend(
+                    resources
+                        .iter()
+                        .map(|resource| state_key(resource, &tenant_id, &project_id)),
+                );
+                continue;
+            };
+            for resource in resources {
+                let key = state_key(&resource, &tenant_id, &project_id);
+                let transition = self.observe(
+                    &key,
+                    last_seen
+                        .get(&(resource.kind, resource.id.clone()))
+                        .copied(),
+                    resource.threshold_seconds * 1000,
+                    now_ms,
+                );
+                alive.insert(key.clone());
+                let Some((transition, last_seen_at_ms)) = transition else {
+                    continue;
+                };
+                if transition == Transition::Offline
+                    && self
+                        .under_maintenance(&ctx, &project_id, &resource, now_ms)
+                        .await
+                {
+                    if let Ok(mut states) = self.states.lock()
+                        && let Some(state) = states.get_mut(&key)
+                    {
+                        state.defer_offline();
+                    }
+                    continue;
+                }
+                info!(
+                    target: "ems.presence",
+                    tenant_id = %tenant_id,
+                    project_id = %project_id,
+                    resource_id = %resource.id,
+                    transition = ?transition,
+                    "presence_changed"
+                );
+                events.extend(self.publish(
+                    &tenant_id,
+                    &project_id,
+                    &resource,
+                    transition,
+                    last_seen_at_ms,
+                ));
+            }
+        }
+
+        // 已删除的设备 / 网关不再保留状态
+        if let Ok(mut states) = self.states.lock() {
+            states.retain(|key, _| alive.contains(key));
+        }
+        events
+    }
+
+    fn gateway_resource(&self, gateway: GatewayRecord) -> Resource {
+        Resource {
+            kind: ResourceKind::Gateway,
+            id: gateway.gateway_id,
+            name: gateway.name,
+            gateway_id: None,
+            threshold_seconds: self.offline_after_seconds,
+        }
+    }
+
+    fn device_resource(&self, device: DeviceRecord) -> Resource {
+        Resource {
+            kind: ResourceKind::Device,
+            id: device.device_id,
+            name: device.name,
+            gateway_id: Some(device.gateway_id),
+            threshold_seconds: device
+                .offline_after_seconds
+                .filter(|value| *value > 0)
+                .unwrap_or(self.offline_after_seconds),
+        }
+    }
+
+    /// 批量读取项目内资源的最后活跃时间（读取失败返回 None）
+    async fn last_seen(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        resources: &[Resource],
+    ) -> Option<HashMap<(ResourceKind, String), i64>> {
+        let ids = |kind: ResourceKind| -> Vec<String> {
+            resources
+                .iter()
+                .filter(|resource| resource.kind == kind)
+                .map(|resource| resource.id.clone())
+                .collect()
+        };
+        let gateway_ids = ids(ResourceKind::Gateway);
+        let device_ids = ids(ResourceKind::Device);
+        let gateways = if gateway_ids.is_empty() {
+            HashMap::new()
+        } else {
+            self.online_store
+                .list_gateways_last_seen_at_ms(ctx, project_id, &gateway_ids)
+                .await
+                .map_err(|err| {
+                    warn!(target: "ems.presence", project_id = %project_id, error = %err, "presence_online_read_failed");
+                })
+                .ok()?
+        };
+        let devices = if device_ids.is_empty() {
+            HashMap::new()
+        } else {
+            self.online_store
+                .list_devices_last_seen_at_ms(ctx, project_id, &device_ids)
+                .await
+                .map_err(|err| {
+                    warn!(target: "ems.presence", project_id = %project_id, error = %err, "presence_online_read_failed");
+                })
+                .ok()?
+        };
+        Some(
+            gateways
+                .into_iter()
+                .map(|(id, ts_ms)| ((ResourceKind::Gateway, id), ts_ms))
+                .chain(
+                    devices
+                        .into_iter()
+                        .map(|(id, ts_ms)| ((ResourceKind::Device, id), ts_ms)),
+                )
+                .collect(),
+        )
+    }
+
+    /// 更新资源状态，返回状态迁移及迁移时的最后活跃时间
+    fn observe(
+        &self,
+        key: &StateKey,
+        last_seen_at_ms: Option<i64>,
+        threshold_ms: i64,
+        now_ms: i64,
+    ) -> Option<(Transition, Option<i64>)> {
+        let Ok(mut states) = self.states.lock() else {
+            return None;
+        };
+        let state = states.entry(key.clone()).or_default();
+        state
+            .observe(last_seen_at_ms, threshold_ms, self.recovery_ms, now_ms)
+            .map(|transition| (transition, state.last_seen_at_ms()))
+    }
+
+    /// 资源当前是否处于维护窗口（未挂载维护模式或读取失败时视为否）
+    async fn under_maintenance(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        resource: &Resource,
+        now_ms: i64,
+    ) -> bool {
+        let Some(maintenance) = &self.maintenance else {
+            return false;
+        };
+        match maintenance
+            .active_window_for_target(ctx, project_id, &resource.id, now_ms)
+            .await
+        {
+            Ok(Some(window)) => {
+                info!(
+                    target: "ems.presence",
+                    resource_id = %resource.id,
+                    maintenance_target = %window.target_id,
+                    "presence_offline_suppressed_by_maintenance"
+                );
+                true
+            }
+            Ok(None) => false,
+            Err(err) => {
+                warn!(target: "ems.presence", resource_id = %resource.id, error = %err, "presence_maintenance_read_failed");
+                false
+            }
+        }
+    }
+
+    /// 发布离线 / 恢复事件（离线同时发布 `alarm.raised`）
+    fn publish(
+        &self,
+        tenant_id: &str,
+        project_id: &str,
+        resource: &Resource,
+        transition: Transition,
+        last_seen_at_ms: Option<i64>,
+    ) -> Vec<DomainEvent> {
+        let (event_type, label, id_field) = match (resource.kind, transition) {
+            (ResourceKind::Device, Transition::Offline) => {
+                (event_types::DEVICE_OFFLINE, "device", "deviceId")
+            }
+            (ResourceKind::Device, Transition::Online) => {
+                (event_types::DEVICE_ONLINE, "device", "deviceId")
+            }
+            (ResourceKind::Gateway, Transition::Offline) => {
+                (event_types::GATEWAY_OFFLINE, "gateway", "gatewayId")
+            }
+            (ResourceKind::Gateway, Transition::Online) => {
+                (event_types::GATEWAY_ONLINE, "gateway", "gatewayId")
+            }
+        };
+        let mut data = json!({
+            id_field: resource.id,
+            "name": resource.name,
+            "lastSeenAtMs": last_seen_at_ms,
+            "offlineAfterSeconds": resource.threshold_seconds,
+        });
+        if let (Some(gateway_id), Value::Object(map)) = (&resource.gateway_id, &mut data) {
+            map.insert("gatewayId".to_string(), json!(gateway_id));
+        }
+        let mut events = vec![DomainEvent::new(
+            event_type,
+            tenant_id.to_string(),
+            project_id.to_string(),
+            data,
+        )];
+        if transition == Transition::Offline {
+            let mut alarm = json!({
+                "source": "presence",
+                "severity": "warning",
+                "message": format!(
+                    "{} {} has not reported for more than {}s",
+                    label, resource.name, resource.threshold_seconds
+                ),
+                id_field: resource.id,
+            });
+            if let (Some(gateway_id), Value::Object(map)) = (&resource.gateway_id, &mut alarm) {
+                map.insert("gatewayId".to_string(), json!(gateway_id));
+            }
+            events.push(DomainEvent::new(
+                event_types::ALARM_RAISED,
+                tenant_id.to_string(),
+                project_id.to_string(),
+                alarm,
+            ));
+        }
+        for event in &events {
+            self.event_bus.publish(event.clone());
+        }
+        events
+    }
+}
+
+/// 启动离线检测后台任务
+pub fn spawn_presence_watcher(
+    watcher: Arc<PresenceWatcher>,
+    config: &PresenceWatcherConfig,
+) -> tokio::task::JoinHandle<()> {
+    let tick_ms = config.tick_ms.max(1);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_millis(tick_ms));
+        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        loop {
+            interval.tick().await;
+            watcher.run_once(now_epoch_ms()).await;
+        }
+    })
+}
+
+fn state_key(resource: &Resource, tenant_id: &str, project_id: &str) -> StateKey {
+    (
+        resource.kind,
+        tenant_id.to_string(),
+        project_id.to_string(),
+        resource.id.clone(),
+    )
+}
+
+/// 检测任务上下文（按资源所属租户与项目）
+fn watcher_context(tenant_id: &str, project_id: &str) -> TenantContext {
+    TenantContext::new(
+        tenant_id.to_string(),
+        "presence-watcher".to_string(),
+        Vec::new(),
+        Vec::new(),
+        Some(project_id.to_string()),
+    )
+}
+
+fn now_epoch_ms() -> i64 {
+    let now = std::time::SystemTime::now();
+    let duration = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    duration.as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ems_storage::{
+        InMemoryAuditLogStore, InMemoryDeviceStore, InMemoryGatewayStore, InMemoryMaintenanceStore,
+        InMemoryOnlineStore, MaintenanceWindowRecord,
+    };
+
+    struct Harness {
+        watcher: PresenceWatcher,
+        device_store: Arc<InMemoryDeviceStore>,
+        online_store: Arc<InMemoryOnlineStore>,
+        maintenance: Arc<MaintenanceService>,
+    }
+
+    async fn harness(event_bus: EventBus) -> Harness {
+        let device_store = Arc::new(InMemoryDeviceStore::new());
+        let gateway_store = Arc::new(InMemoryGatewayStore::new());
+        let online_store = Arc::new(InMemoryOnlineStore::new());
+        gateway_store
+            .create_gateway(
+                &ctx(),
+                GatewayRecord {
+                    gateway_id: "gateway-1".to_string(),
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    name: "GW-1".to_string(),
+                    status: "active".to_string(),
+                    protocol_type: "mqtt".to_string(),
+                    protocol_config: None,
+                },
+            )
+            .await
+            .expect("gateway");
+        let maintenance = Arc::new(MaintenanceService::new(
+            Arc::new(InMemoryMaintenanceStore::new()),
+            device_store.clone(),
+            Arc::new(InMemoryAuditLogStore::new()),
+        ));
+        let watcher = PresenceWatcher::new(
+            device_store.clone(),
+            gateway_store,
+            online_store.clone(),
+            event_bus,
+            &PresenceWatcherConfig {
+                tick_ms: 1_000,
+                offline_after_seconds: 60,
+                recovery_seconds: 30,
+            },
+        )
+        .with_maintenance(maintenance.clone());
+        Harness {
+            watcher,
+            device_store,
+            online_store,
+            maintenance,
+        }
+    }
+
+    fn ctx() -> TenantContext {
+        TenantContext::new(
+            "tenant-1".to_string(),
+            "user-1".to_string(),
+            Vec::new(),
+            Vec::new(),
+            Some("project-1".to_string()),
+        )
+    }
+
+    async fn add_device(h: &Harness, device_id: &str, offline_after_seconds: Option<i64>) {
+        h.device_store
+            .create_device(
+                &ctx(),
+                DeviceRecord {
+                    device_id: device_id.to_string(),
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    gateway_id: "gateway-1".to_string(),
+                    name: device_id.to_string(),
+                    model: None,
+                    room_id: None,
+                    address_config: None,
+                    offline_after_seconds,
+                },
+            )
+            .await
+            .expect("device");
+    }
+
+    async fn report(h: &Harness, device_id: &str, ts_ms: i64) {
+        h.online_store
+            .touch_device(&ctx(), "project-1", device_id, ts_ms)
+            .await
+            .expect("touch device");
+        h.online_store
+            .touch_gateway(&ctx(), "project-1", "gateway-1", ts_ms)
+            .await
+            .expect("touch gateway");
+    }
+
+    fn types(events: &[DomainEvent]) -> Vec<&str> {
+        events
+            .iter()
+            .map(|event| event.event_type.as_str())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn emits_offline_and_online_with_hysteresis() {
+        let bus = EventBus::default();
+        let mut receiver = bus.subscribe();
+        let h = harness(bus).await;
+        add_device(&h, "device-1", None).await;
+        report(&h, "device-1", 0).await;
+
+        // 首次检测只建立基线
+        assert!(h.watcher.run_once(1_000).await.is_empty());
+        assert!(h.watcher.run_once(60_000).await.is_empty());
+
+        let events = h.watcher.run_once(61_000).await;
+        assert_eq!(
+            types(&events),
+            vec![
+                event_types::GATEWAY_OFFLINE,
+                event_types::ALARM_RAISED,
+                event_types::DEVICE_OFFLINE,
+                event_types::ALARM_RAISED,
+            ]
+        );
+        let event = receiver.recv().await.expect("event");
+        assert_eq!(event.event_type, event_types::GATEWAY_OFFLINE);
+        assert_eq!(event.data["gatewayId"], "gateway-1");
+        assert_eq!(event.data["lastSeenAtMs"], 0);
+        assert_eq!(events[2].data["deviceId"], "device-1");
+        assert_eq!(events[2].data["gatewayId"], "gateway-1");
+        assert_eq!(events[2].data["offlineAfterSeconds"], 60);
+        assert_eq!(events[3].data["source"], "presence");
+        assert_eq!(events[3].data["deviceId"], "device-1");
+
+        // 持续离线不重复发布
+        assert!(h.watcher.run_once(120_000).await.is_empty());
+
+        // 单次上报不恢复，持续上报满恢复时长后才发布 online
+        report(&h, "device-1", 130_000).await;
+        assert!(h.watcher.run_once(130_000).await.is_empty());
+        report(&h, "device-1", 150_000).await;
+        assert!(h.watcher.run_once(150_000).await.is_empty());
+        report(&h, "device-1", 160_000).await;
+        let events = h.watcher.run_once(160_000).await;
+        assert_eq!(
+            types(&events),
+            vec![event_types::GATEWAY_ONLINE, event_types::DEVICE_ONLINE]
+        );
+        assert_eq!(events[1].data["lastSeenAtMs"], 160_000);
+    }
+
+    #[tokio::test]
+    async fn device_threshold_overrides_default() {
+        let h = harness(EventBus::default()).await;
+        add_device(&h, "device-fast", Some(10)).await;
+        add_device(&h, "device-slow", None).await;
+        report(&h, "device-fast", 0).await;
+        report(&h, "device-slow", 0).await;
+
+        assert!(h.watcher.run_once(0).await.is_empty());
+        let events = h.watcher.run_once(11_000).await;
+        assert_eq!(
+            types(&events),
+            vec![event_types::DEVICE_OFFLINE, event_types::ALARM_RAISED]
+        );
+        assert_eq!(events[0].data["deviceId"], "device-fast");
+        assert_eq!(events[0].data["offlineAfterSeconds"], 10);
+    }
+
+    #[tokio::test]
+    async fn never_seen_resources_are_not_judged() {
+        let h = harness(EventBus::default()).await;
+        add_device(&h, "device-1", None).await;
+        assert!(h.watcher.run_once(0).await.is_empty());
+        assert!(h.watcher.run_once(3_600_000).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn maintenance_defers_offline_until_window_ends() {
+        let h = harness(EventBus::default()).await;
+        h.maintenance
+            .set_window(
+                &ctx(),
+                MaintenanceWindowRecord {
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    target_type: "gateway".to_string(),
+                    target_id: "gateway-1".to_string(),
+                    starts_at_ms: 0,
+                    ends_at_ms: 100_000,
+                    reason: None,
+                    created_by: "user-1".to_string(),
+                    created_at_ms: 0,
+                },
+            )
+            .await
+            .expect("window");
+        add_device(&h, "device-1", None).await;
+        report(&h, "device-1", 0).await;
+
+        assert!(h.watcher.run_once(0).await.is_empty());
+        // 网关维护窗口同时覆盖其下设备
+        assert!(h.watcher.run_once(70_000).await.is_empty());
+        assert!(h.watcher.run_once(99_000).await.is_empty());
+        // 窗口结束后仍离线，正常发布
+        let events = h.watcher.run_once(100_000).await;
+        assert_eq!(
+            types(&events),
+            vec![
+                event_types::GATEWAY_OFFLINE,
+                event_types::ALARM_RAISED,
+                event_types::DEVICE_OFFLINE,
+                event_types::ALARM_RAISED,
+            ]
+        );
+    }
+}
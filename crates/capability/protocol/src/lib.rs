@@ -4,6 +4,7 @@
 //! - **Modbus TCP**：读取从设备寄存器数据
 //! - **TCP Server**：监听端口接收设备上报数据
 //! - **TCP Client**：主动连接设备获取数据
+//! - **Modbus TCP Server**：作为从站向下游 SCADA 只读暴露点位最新值
 //!
 //! ## 架构设计
 //!
@@ -43,15 +44,39 @@
 //! // gateway.protocol_config
 //! { "listen_port": 9000, "frame_delimiter": "\n" }
 //! ```
+//!
+//! ### Modbus TCP Server（从站）
+//! ```json
+//! // gateway.protocol_config
+//! { "listen_port": 1502, "map_refresh_ms": 10000 }
+//!
+//! // point_mapping.source_type = "modbus_server"，point_mapping.protocol_detail
+//! { "unit_id": 1, "function_code": 3, "register_address": 100, "data_type": "float32" }
+//! ```
+//!
+//! ## 配置校验
+//!
+//! `validate_gateway_config` / `validate_device_address` / `validate_point_detail`
+//! 按协议类型校验上述配置（必填、类型、取值范围、未知字段），供接口层在保存时调用。
 
 mod error;
+mod modbus_server;
 mod modbus_tcp;
+mod schema;
 mod tcp_client;
 mod tcp_server;
 mod types;
 
 pub use error::ProtocolError;
+pub use modbus_server::{
+    build_register_map, ModbusServer, ModbusServerConfig, ModbusServerPointDetail, RegisterBinding,
+    RegisterValueSource, MODBUS_SERVER_SOURCE_TYPE,
+};
 pub use modbus_tcp::{ModbusTcpConfig, ModbusTcpSource};
+pub use schema::{
+    validate_device_address, validate_gateway_config, validate_point_detail,
+    validate_protocol_type, FieldError, PROTOCOL_TYPES,
+};
 pub use tcp_client::{TcpClientConfig, TcpClientSource};
 pub use tcp_server::{TcpServerConfig, TcpServerSource};
 pub use types::*;
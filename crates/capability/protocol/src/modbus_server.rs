@@ -0,0 +1,611 @@
+//! Modbus TCP 从站（服务端）实现
+//!
+//! 供下游 SCADA 主动轮询：把选定点位的最新值按寄存器表编码，只读暴露。
+//!
+//! - 寄存器表由 `source_type = "modbus_server"` 的点位映射提供（`protocol_detail` 见 [`ModbusServerPointDetail`]）
+//! - 支持功能码 03（读保持寄存器）/ 04（读输入寄存器）；写功能码与线圈读取返回异常码 01
+//! - 请求范围内没有任何已映射寄存器时返回异常码 02；未映射的空隙填 0
+//! - 点位暂无最新值时浮点类型填 NaN，整数类型填 0
+//!
+//! ## 使用示例
+//!
+//! ```rust,ignore
+//! let config = ModbusServerConfig::from_json(r#"{"listen_port": 1502}"#)?;
+//! let server = Arc::new(ModbusServer::new(config, source));
+//! server.run().await?;
+//! ```
+
+use crate::error::ProtocolError;
+use crate::types::ModbusDataType;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{RwLock, Semaphore};
+use tracing::{debug, info, warn};
+
+/// 从站寄存器表使用的点位映射来源类型
+pub const MODBUS_SERVER_SOURCE_TYPE: &str = "modbus_server";
+
+/// 单次读取的最大寄存器数（Modbus 规范）
+const MAX_READ_REGISTERS: u16 = 125;
+
+/// 异常码：非法功能
+const EXCEPTION_ILLEGAL_FUNCTION: u8 = 0x01;
+/// 异常码：非法数据地址
+const EXCEPTION_ILLEGAL_DATA_ADDRESS: u8 = 0x02;
+/// 异常码：非法数据值
+const EXCEPTION_ILLEGAL_DATA_VALUE: u8 = 0x03;
+/// 异常码：从站设备故障
+const EXCEPTION_SERVER_DEVICE_FAILURE: u8 = 0x04;
+
+/// Modbus TCP 从站配置（网关 `protocol_config`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModbusServerConfig {
+    /// 监听端口
+    pub listen_port: u16,
+    /// 监听地址（默认 0.0.0.0）
+    #[serde(default = "default_bind_host")]
+    pub bind_host: String,
+    /// 最大连接数
+    #[serde(default = "default_max_connections")]
+    pub max_connections: usize,
+    /// 寄存器表刷新间隔（毫秒）
+    #[serde(default = "default_map_refresh")]
+    pub map_refresh_ms: u64,
+}
+
+fn default_bind_host() -> String {
+    "0.0.0.0".to_string()
+}
+
+fn default_max_connections() -> usize {
+    16
+}
+
+fn default_map_refresh() -> u64 {
+    10_000
+}
+
+impl ModbusServerConfig {
+    /// 从 JSON 配置字符串解析
+    pub fn from_json(json: &str) -> Result<Self, ProtocolError> {
+        serde_json::from_str(json).map_err(|e| ProtocolError::ConfigParse(e.to_string()))
+    }
+}
+
+/// 寄存器表项协议详情（点位映射 `protocol_detail`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModbusServerPointDetail {
+    /// 从站地址（默认 1）
+    #[serde(default = "default_unit_id")]
+    pub unit_id: u8,
+    /// 功能码：3 保持寄存器（默认）/ 4 输入寄存器
+    #[serde(default = "default_function_code")]
+    pub function_code: u8,
+    /// 寄存器起始地址
+    pub register_address: u16,
+    /// 数据类型（决定占用的寄存器数）
+    #[serde(default)]
+    pub data_type: ModbusDataType,
+    /// 多寄存器类型的字序（big_endian 高字在前 / little_endian 低字在前）
+    #[serde(default = "default_byte_order")]
+    pub byte_order: String,
+}
+
+fn default_unit_id() -> u8 {
+    1
+}
+
+fn default_function_code() -> u8 {
+    3
+}
+
+fn default_byte_order() -> String {
+    "big_endian".to_string()
+}
+
+/// 寄存器表项：点位最新值编码到指定寄存器
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegisterBinding {
+    pub point_id: String,
+    pub unit_id: u8,
+    /// 功能码 3 / 4
+    pub function_code: u8,
+    pub register_address: u16,
+    pub data_type: ModbusDataType,
+    /// 低字在前
+    pub little_endian: bool,
+    /// 与采集映射相同的换算（工程值 = 寄存器值 × scale + offset），编码时反向换算
+    pub scale: Option<f64>,
+    pub offset: Option<f64>,
+}
+
+impl RegisterBinding {
+    /// 从点位映射构造表项
+    pub fn from_point_detail(
+        point_id: &str,
+        protocol_detail: &str,
+        scale: Option<f64>,
+        offset: Option<f64>,
+    ) -> Result<Self, ProtocolError> {
+        let detail: ModbusServerPointDetail = serde_json::from_str(protocol_detail)
+            .map_err(|e| ProtocolError::ConfigParse(format!("point detail: {}", e)))?;
+        if detail.function_code != 3 && detail.function_code != 4 {
+            return Err(ProtocolError::ConfigParse(format!(
+                "unsupported function code: {}",
+                detail.function_code
+            )));
+        }
+        let binding = Self {
+            point_id: point_id.to_string(),
+            unit_id: detail.unit_id,
+            function_code: detail.function_code,
+            register_address: detail.register_address,
+            data_type: detail.data_type,
+            little_endian: detail.byte_order == "little_endian",
+            scale,
+            offset,
+        };
+        if u32::from(binding.register_address) + u32::from(binding.register_count()) > 65_536 {
+            return Err(ProtocolError::ConfigParse(
+                "register range exceeds 65535".to_string(),
+            ));
+        }
+        Ok(binding)
+    }
+
+    /// 占用的寄存器数
+    pub fn register_count(&self) -> u16 {
+        match self.data_type {
+            ModbusDataType::Int16 | ModbusDataType::Uint16 => 1,
+            ModbusDataType::Int32 | ModbusDataType::Uint32 | ModbusDataType::Float32 => 2,
+            ModbusDataType::Float64 => 4,
+        }
+    }
+
+    fn end(&self) -> u32 {
+        u32::from(self.register_address) + u32::from(self.register_count())
+    }
+
+    /// 编码为寄存器值；整数类型四舍五入并截断到取值范围
+    pub fn encode(&self, value: Option<f64>) -> Vec<u16> {
+        let raw = value.map(|value| {
+            let mut raw = value - self.offset.unwrap_or(0.0);
+            if let Some(scale) = self.scale.filter(|scale| *scale != 0.0) {
+                raw /= scale;
+            }
+            raw
+        });
+        let mut words = match self.data_type {
+            ModbusDataType::Int16 => vec![raw.map_or(0, |raw| raw.round() as i16) as u16],
+            ModbusDataType::Uint16 => vec![raw.map_or(0, |raw| raw.round() as u16)],
+            ModbusDataType::Int32 => split_u32(raw.map_or(0, |raw| raw.round() as i32) as u32),
+            ModbusDataType::Uint32 => split_u32(raw.map_or(0, |raw| raw.round() as u32)),
+            ModbusDataType::Float32 => split_u32((raw.unwrap_or(f64::NAN) as f32).to_bits()),
+            ModbusDataType::Float64 => {
+                let bits = raw.unwrap_or(f64::NAN).to_bits();
+                vec![
+                    (bits >> 48) as u16,
+                    (bits >> 32) as u16,
+                    (bits >> 16) as u16,
+                    bits as u16,
+                ]
+            }
+        };
+        if self.little_endian {
+            words.reverse();
+        }
+        words
+    }
+}
+
+fn split_u32(value: u32) -> Vec<u16> {
+    vec![(value >> 16) as u16, value as u16]
+}
+
+/// 整理寄存器表：按地址排序，同一从站 / 功能码下与已有表项重叠的表项丢弃
+pub fn build_register_map(mut bindings: Vec<RegisterBinding>) -> Vec<RegisterBinding> {
+    bindings.sort_by(|a, b| {
+        (a.unit_id, a.function_code, a.register_address).cmp(&(
+            b.unit_id,
+            b.function_code,
+            b.register_address,
+        ))
+    });
+    let mut map: Vec<RegisterBinding> = Vec::with_capacity(bindings.len());
+    for binding in bindings {
+        if let Some(last) = map.last() {
+            if last.unit_id == binding.unit_id
+                && last.function_code == binding.function_code
+                && last.end() > u32::from(binding.register_address)
+            {
+                warn!(
+                    point_id = %binding.point_id,
+                    overlaps = %last.point_id,
+                    register = binding.register_address,
+                    "modbus server register overlaps, binding skipped"
+                );
+                continue;
+            }
+        }
+        map.push(binding);
+    }
+    map
+}
+
+/// 寄存器表与点位值来源
+#[async_trait]
+pub trait RegisterValueSource: Send + Sync {
+    /// 当前寄存器表
+    async fn register_map(&self) -> Result<Vec<RegisterBinding>, ProtocolError>;
+
+    /// 点位最新值（无值的点位不返回）
+    async fn point_values(
+        &self,
+        point_ids: &[String],
+    ) -> Result<HashMap<String, f64>, ProtocolError>;
+}
+
+struct CachedMap {
+    bindings: Arc<Vec<RegisterBinding>>,
+    loaded_at: Option<Instant>,
+}
+
+/// Modbus TCP 从站
+pub struct ModbusServer {
+    config: ModbusServerConfig,
+    source: Arc<dyn RegisterValueSource>,
+    map: RwLock<CachedMap>,
+}
+
+impl ModbusServer {
+    /// 创建从站
+    pub fn new(config: ModbusServerConfig, source: Arc<dyn RegisterValueSource>) -> Self {
+        Self {
+            config,
+            source,
+            map: RwLock::new(CachedMap {
+                bindings: Arc::new(Vec::new()),
+                loaded_at: None,
+            }),
+        }
+    }
+
+    /// 绑定监听地址并运行
+    pub async fn run(self: Arc<Self>) -> Result<(), ProtocolError> {
+        let addr = format!("{}:{}", self.config.bind_host, self.config.listen_port);
+        let listener = TcpListener::bind(&addr).await?;
+        info!("modbus server listening on {}", addr);
+        self.serve(listener).await
+    }
+
+    /// 在已绑定的监听器上接受连接
+    pub async fn serve(self: Arc<Self>, listener: TcpListener) -> Result<(), ProtocolError> {
+        let permits = Arc::new(Semaphore::new(self.config.max_connections.max(1)));
+        loop {
+            let (stream, peer_addr) = listener.accept().await?;
+            let Ok(permit) = Arc::clone(&permits).try_acquire_owned() else {
+                warn!(
+                    "modbus server connection limit reached, rejecting {}",
+                    peer_addr
+                );
+                continue;
+            };
+            debug!("modbus server connection from {}", peer_addr);
+            let server = Arc::clone(&self);
+            tokio::spawn(async move {
+                if let Err(e) = server.handle_connection(stream).await {
+                    debug!("modbus server connection {} closed: {}", peer_addr, e);
+                }
+                drop(permit);
+            });
+        }
+    }
+
+    /// 处理单个连接：逐帧读取 MBAP 头与 PDU 并应答
+    async fn handle_connection(&self, mut stream: TcpStream) -> Result<(), ProtocolError> {
+        let mut header = [0u8; 7];
+        loop {
+            match stream.read_exact(&mut header).await {
+                Ok(_) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(()),
+                Err(e) => return Err(e.into()),
+            }
+            let protocol_id = u16::from_be_bytes([header[2], header[3]]);
+            let length = u16::from_be_bytes([header[4], header[5]]) as usize;
+            if protocol_id != 0 || !(2..=254).contains(&length) {
+                return Err(ProtocolError::DataParse(format!(
+                    "invalid mbap header: protocol {} length {}",
+                    protocol_id, length
+                )));
+            }
+            let mut pdu = vec![0u8; length - 1];
+            stream.read_exact(&mut pdu).await?;
+
+            let response = self.handle_pdu(header[6], &pdu).await;
+            let mut frame = Vec::with_capacity(7 + response.len());
+            frame.extend_from_slice(&header[0..4]);
+            frame.extend_from_slice(&((response.len() + 1) as u16).to_be_bytes());
+            frame.push(header[6]);
+            frame.extend_from_slice(&response);
+            stream.write_all(&frame).await?;
+        }
+    }
+
+    /// 处理请求 PDU，返回应答 PDU（正常应答或异常应答）
+    pub async fn handle_pdu(&self, unit_id: u8, pdu: &[u8]) -> Vec<u8> {
+        let function_code = pdu.first().copied().unwrap_or(0);
+        match self.read_registers(unit_id, pdu).await {
+            Ok(words) => {
+                let mut response = Vec::with_capacity(2 + words.len() * 2);
+                response.push(function_code);
+                response.push((words.len() * 2) as u8);
+                for word in words {
+                    response.extend_from_slice(&word.to_be_bytes());
+                }
+                response
+            }
+            Err(code) => vec![function_code | 0x80, code],
+        }
+    }
+
+    async fn read_registers(&self, unit_id: u8, pdu: &[u8]) -> Result<Vec<u16>, u8> {
+        let function_code = pdu.first().copied().unwrap_or(0);
+        if function_code != 3 && function_code != 4 {
+            return Err(EXCEPTION_ILLEGAL_FUNCTION);
+        }
+        if pdu.len() != 5 {
+            return Err(EXCEPTION_ILLEGAL_DATA_VALUE);
+        }
+        let start = u16::from_be_bytes([pdu[1], pdu[2]]);
+        let quantity = u16::from_be_bytes([pdu[3], pdu[4]]);
+        if quantity == 0 || quantity > MAX_READ_REGISTERS {
+            return Err(EXCEPTION_ILLEGAL_DATA_VALUE);
+        }
+        let end = u32::from(start) + u32::from(quantity);
+        if end > 65_536 {
+            return Err(EXCEPTION_ILLEGAL_DATA_ADDRESS);
+        }
+
+        let map = self.bindings().await;
+        let bindings: Vec<&RegisterBinding> = map
+            .iter()
+            .filter(|binding| {
+                binding.unit_id == unit_id
+                    && binding.function_code == function_code
+                    && u32::from(binding.register_address) < end
+                    && binding.end() > u32::from(start)
+            })
+            .collect();
+        if bindings.is_empty() {
+            return Err(EXCEPTION_ILLEGAL_DATA_ADDRESS);
+        }
+        let point_ids: Vec<String> = bindings
+            .iter()
+            .map(|binding| binding.point_id.clone())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        let values = match self.source.point_values(&point_ids).await {
+            Ok(values) => values,
+            Err(e) => {
+                warn!(error = %e, "modbus server failed to read point values");
+                return Err(EXCEPTION_SERVER_DEVICE_FAILURE);
+            }
+        };
+
+        let mut words = vec![0u16; quantity as usize];
+        for binding in bindings {
+            let encoded = binding.encode(values.get(&binding.point_id).copied());
+            for (index, word) in encoded.into_iter().enumerate() {
+                let address = u32::from(binding.register_address) + index as u32;
+                if address >= u32::from(start) && address < end {
+                    words[(address - u32::from(start)) as usize] = word;
+                }
+            }
+        }
+        Ok(words)
+    }
+
+    /// 取寄存器表，超过刷新间隔时重新加载；加载失败时沿用上一次的表
+    async fn bindings(&self) -> Arc<Vec<RegisterBinding>> {
+        let refresh = Duration::from_millis(self.config.map_refresh_ms);
+        {
+            let cached = self.map.read().await;
+            if cached
+                .loaded_at
+                .is_some_and(|loaded_at| loaded_at.elapsed() < refresh)
+            {
+                return Arc::clone(&cached.bindings);
+            }
+        }
+        let mut cached = self.map.write().await;
+        match self.source.register_map().await {
+            Ok(bindings) => {
+                cached.bindings = Arc::new(build_register_map(bindings));
+            }
+            Err(e) => {
+                warn!(error = %e, "modbus server failed to load register map");
+            }
+        }
+        cached.loaded_at = Some(Instant::now());
+        Arc::clone(&cached.bindings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct StaticSource {
+        bindings: Vec<RegisterBinding>,
+        values: HashMap<String, f64>,
+    }
+
+    #[async_trait]
+    impl RegisterValueSource for StaticSource {
+        async fn register_map(&self) -> Result<Vec<RegisterBinding>, ProtocolError> {
+            Ok(self.bindings.clone())
+        }
+
+        async fn point_values(
+            &self,
+            point_ids: &[String],
+        ) -> Result<HashMap<String, f64>, ProtocolError> {
+            Ok(self
+                .values
+                .iter()
+                .filter(|(point_id, _)| point_ids.contains(point_id))
+                .map(|(point_id, value)| (point_id.clone(), *value))
+                .collect())
+        }
+    }
+
+    fn binding(point_id: &str, detail: &str) -> RegisterBinding {
+        RegisterBinding::from_point_detail(point_id, detail, None, None).unwrap()
+    }
+
+    fn server() -> ModbusServer {
+        let source = StaticSource {
+            bindings: vec![
+                binding(
+                    "power",
+                    r#"{"register_address": 100, "data_type": "float32"}"#,
+                ),
+                RegisterBinding::from_point_detail(
+                    "temp",
+                    r#"{"register_address": 103, "data_type": "int16"}"#,
+                    Some(0.1),
+                    Some(-40.0),
+                )
+                .unwrap(),
+                binding(
+                    "missing",
+                    r#"{"register_address": 104, "data_type": "uint16"}"#,
+                ),
+                // 与 power 重叠，被丢弃
+                binding("overlap", r#"{"register_address": 101}"#),
+                binding(
+                    "energy",
+                    r#"{"unit_id": 2, "function_code": 4, "register_address": 0, "data_type": "uint32", "byte_order": "little_endian"}"#,
+                ),
+            ],
+            values: HashMap::from([
+                ("power".to_string(), 12.5),
+                ("temp".to_string(), 25.0),
+                ("overlap".to_string(), 1.0),
+                ("energy".to_string(), 70_000.0),
+            ]),
+        };
+        ModbusServer::new(
+            ModbusServerConfig::from_json(r#"{"listen_port": 1502}"#).unwrap(),
+            Arc::new(source),
+        )
+    }
+
+    #[test]
+    fn test_encode_registers() {
+        let power = binding(
+            "power",
+            r#"{"register_address": 0, "data_type": "float32"}"#,
+        );
+        assert_eq!(power.encode(Some(12.5)), vec![0x4148, 0x0000]);
+        let nan = power.encode(None);
+        assert!(f32::from_bits((u32::from(nan[0]) << 16) | u32::from(nan[1])).is_nan());
+
+        let swapped = binding(
+            "power",
+            r#"{"register_address": 0, "data_type": "int32", "byte_order": "little_endian"}"#,
+        );
+        assert_eq!(swapped.encode(Some(-2.0)), vec![0xFFFE, 0xFFFF]);
+
+        let temp = RegisterBinding::from_point_detail(
+            "temp",
+            r#"{"register_address": 0, "data_type": "uint16"}"#,
+            Some(0.1),
+            Some(-40.0),
+        )
+        .unwrap();
+        assert_eq!(temp.encode(Some(25.0)), vec![650]);
+        assert_eq!(temp.encode(Some(-100.0)), vec![0]);
+        assert_eq!(
+            binding(
+                "x",
+                r#"{"register_address": 65532, "data_type": "float64"}"#
+            )
+            .register_count(),
+            4
+        );
+        assert!(RegisterBinding::from_point_detail(
+            "x",
+            r#"{"register_address": 65534, "data_type": "float64"}"#,
+            None,
+            None
+        )
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_handle_pdu() {
+        let server = server();
+        // 读 100..105：power(float32) + 空隙 + temp + missing
+        let response = server.handle_pdu(1, &[3, 0, 100, 0, 5]).await;
+        assert_eq!(
+            response,
+            vec![3, 10, 0x41, 0x48, 0, 0, 0, 0, 0x02, 0x8A, 0, 0]
+        );
+        // 输入寄存器与从站地址分开
+        let response = server.handle_pdu(2, &[4, 0, 0, 0, 2]).await;
+        assert_eq!(response, vec![4, 4, 0x11, 0x70, 0x00, 0x01]);
+        assert_eq!(
+            server.handle_pdu(1, &[4, 0, 100, 0, 1]).await,
+            vec![0x84, 2]
+        );
+        // 写功能码、数量越界
+        assert_eq!(
+            server.handle_pdu(1, &[6, 0, 100, 0, 1]).await,
+            vec![0x86, 1]
+        );
+        assert_eq!(
+            server.handle_pdu(1, &[3, 0, 100, 0, 0]).await,
+            vec![0x83, 3]
+        );
+        assert_eq!(
+            server.handle_pdu(1, &[3, 0, 100, 0, 126]).await,
+            vec![0x83, 3]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_serve_tcp_frames() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(Arc::new(server()).serve(listener));
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        // 事务 0x0102，从站 1，读保持寄存器 100 起 2 个
+        stream
+            .write_all(&[0x01, 0x02, 0, 0, 0, 6, 1, 3, 0, 100, 0, 2])
+            .await
+            .unwrap();
+        let mut response = [0u8; 13];
+        stream.read_exact(&mut response).await.unwrap();
+        assert_eq!(
+            response,
+            [0x01, 0x02, 0, 0, 0, 7, 1, 3, 4, 0x41, 0x48, 0, 0]
+        );
+
+        // 同一连接上的写请求返回异常应答
+        stream
+            .write_all(&[0x01, 0x03, 0, 0, 0, 6, 1, 6, 0, 100, 0, 1])
+            .await
+            .unwrap();
+        let mut response = [0u8; 9];
+        stream.read_exact(&mut response).await.unwrap();
+        assert_eq!(response, [0x01, 0x03, 0, 0, 0, 3, 1, 0x86, 1]);
+    }
+}
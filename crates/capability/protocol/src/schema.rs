@@ -0,0 +1,415 @@
+//! 协议配置校验
+//!
+//! 按协议类型校验网关 `protocol_config`、设备 `address_config` 与点位映射 `protocol_detail`，
+//! 规则与各采集源解析的配置结构一致（字段名、类型、取值范围），
+//! 并拒绝未知字段，使拼写错误在保存时即被发现，而不是等到采集源启动。
+//!
+//! `mqtt` 的采集参数由服务端配置决定，只要求配置为 JSON 对象；
+//! TCP 设备地址与点位详情由帧解析约定决定，同样只要求为 JSON 对象。
+//!
+//! `modbus_server` 点位映射（来源类型而非网关协议）描述的是对外暴露的寄存器，
+//! 不依赖点位所属网关的协议类型，按 [`crate::ModbusServerPointDetail`] 校验。
+
+/// 支持的网关协议类型
+pub const PROTOCOL_TYPES: &[&str] = &[
+    "mqtt",
+    "modbus_tcp",
+    "tcp_server",
+    "tcp_client",
+    "modbus_server",
+];
+
+/// Modbus 数据类型取值（与 [`crate::ModbusDataType`] 一致）
+const MODBUS_DATA_TYPES: &[&str] = &["int16", "uint16", "int32", "uint32", "float32", "float64"];
+
+/// 字段级校验错误
+///
+/// `field` 为配置文档内的字段名，整体错误（非 JSON、非对象）时为空串。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    fn new(field: &str, message: impl Into<String>) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+enum FieldKind {
+    String,
+    Integer { min: i64, max: i64 },
+    Bool,
+    Enum(&'static [&'static str]),
+}
+
+struct FieldSpec {
+    name: &'static str,
+    kind: FieldKind,
+    required: bool,
+}
+
+const fn field(name: &'static str, kind: FieldKind, required: bool) -> FieldSpec {
+    FieldSpec {
+        name,
+        kind,
+        required,
+    }
+}
+
+const PORT: FieldKind = FieldKind::Integer { min: 1, max: 65535 };
+const DURATION_MS: FieldKind = FieldKind::Integer {
+    min: 1,
+    max: 86_400_000,
+};
+
+/// [`crate::ModbusTcpConfig`]
+const MODBUS_TCP_CONFIG: &[FieldSpec] = &[
+    field("host", FieldKind::String, true),
+    field("port", PORT, false),
+    field("poll_interval_ms", DURATION_MS, false),
+    field("connect_timeout_ms", DURATION_MS, false),
+    field("read_timeout_ms", DURATION_MS, false),
+];
+
+/// [`crate::TcpServerConfig`]
+const TCP_SERVER_CONFIG: &[FieldSpec] = &[
+    field("listen_port", PORT, true),
+    field("frame_delimiter", FieldKind::String, false),
+    field(
+        "max_connections",
+        FieldKind::Integer { min: 1, max: 65535 },
+        false,
+    ),
+    field(
+        "connection_timeout_secs",
+        FieldKind::Integer {
+            min: 1,
+            max: 86_400,
+        },
+        false,
+    ),
+];
+
+/// [`crate::TcpClientConfig`]
+const TCP_CLIENT_CONFIG: &[FieldSpec] = &[
+    field("host", FieldKind::String, true),
+    field("port", PORT, true),
+    field("poll_interval_ms", DURATION_MS, false),
+    field("connect_timeout_ms", DURATION_MS, false),
+    field("request_command", FieldKind::String, false),
+    field("frame_delimiter", FieldKind::String, false),
+    field("auto_reconnect", FieldKind::Bool, false),
+    field("reconnect_interval_ms", DURATION_MS, false),
+];
+
+/// [`crate::ModbusServerConfig`]
+const MODBUS_SERVER_CONFIG: &[FieldSpec] = &[
+    field("listen_port", PORT, true),
+    field("bind_host", FieldKind::String, false),
+    field(
+        "max_connections",
+        FieldKind::Integer { min: 1, max: 1024 },
+        false,
+    ),
+    field("map_refresh_ms", DURATION_MS, false),
+];
+
+/// [`crate::ModbusDeviceAddress`]
+const MODBUS_DEVICE_ADDRESS: &[FieldSpec] = &[field(
+    "slave_id",
+    FieldKind::Integer { min: 1, max: 247 },
+    true,
+)];
+
+/// [`crate::ModbusPointDetail`]
+const MODBUS_POINT_DETAIL: &[FieldSpec] = &[
+    field(
+        "function_code",
+        FieldKind::Integer { min: 1, max: 4 },
+        false,
+    ),
+    field(
+        "register_address",
+        FieldKind::Integer { min: 0, max: 65535 },
+        true,
+    ),
+    field(
+        "register_count",
+        FieldKind::Integer { min: 1, max: 125 },
+        false,
+    ),
+    field("data_type", FieldKind::Enum(MODBUS_DATA_TYPES), false),
+    field(
+        "byte_order",
+        FieldKind::Enum(&["big_endian", "little_endian"]),
+        false,
+    ),
+];
+
+/// [`crate::ModbusServerPointDetail`]
+const MODBUS_SERVER_POINT_DETAIL: &[FieldSpec] = &[
+    field("unit_id", FieldKind::Integer { min: 0, max: 255 }, false),
+    field(
+        "function_code",
+        FieldKind::Integer { min: 3, max: 4 },
+        false,
+    ),
+    field(
+        "register_address",
+        FieldKind::Integer { min: 0, max: 65535 },
+        true,
+    ),
+    field("data_type", FieldKind::Enum(MODBUS_DATA_TYPES), false),
+    field(
+        "byte_order",
+        FieldKind::Enum(&["big_endian", "little_endian"]),
+        false,
+    ),
+];
+
+/// 校验网关协议类型
+pub fn validate_protocol_type(protocol_type: &str) -> Result<(), FieldError> {
+    if PROTOCOL_TYPES.contains(&protocol_type) {
+        Ok(())
+    } else {
+        Err(FieldError::new(
+            "",
+            format!(
+                "unsupported protocol type, expected one of: {}",
+                PROTOCOL_TYPES.join(", ")
+            ),
+        ))
+    }
+}
+
+/// 校验网关 `protocol_config`
+pub fn validate_gateway_config(protocol_type: &str, config: &str) -> Vec<FieldError> {
+    let specs = match protocol_type {
+        "modbus_tcp" => Some(MODBUS_TCP_CONFIG),
+        "tcp_server" => Some(TCP_SERVER_CONFIG),
+        "tcp_client" => Some(TCP_CLIENT_CONFIG),
+        "modbus_server" => Some(MODBUS_SERVER_CONFIG),
+        _ => None,
+    };
+    validate_document(config, specs)
+}
+
+/// 校验设备 `address_config`（协议类型取设备所属网关）
+pub fn validate_device_address(protocol_type: &str, address_config: &str) -> Vec<FieldError> {
+    let specs = match protocol_type {
+        "modbus_tcp" => Some(MODBUS_DEVICE_ADDRESS),
+        _ => None,
+    };
+    validate_document(address_config, specs)
+}
+
+/// 校验点位映射 `protocol_detail`（协议类型取点位所属设备的网关；
+/// `modbus_server` 映射传入 [`crate::MODBUS_SERVER_SOURCE_TYPE`]）
+pub fn validate_point_detail(protocol_type: &str, protocol_detail: &str) -> Vec<FieldError> {
+    let specs = match protocol_type {
+        "modbus_tcp" => Some(MODBUS_POINT_DETAIL),
+        "modbus_server" => Some(MODBUS_SERVER_POINT_DETAIL),
+        _ => None,
+    };
+    let mut errors = validate_document(protocol_detail, specs);
+    if protocol_type == "modbus_tcp" && errors.is_empty() {
+        errors.extend(check_modbus_register_count(protocol_detail));
+    }
+    if protocol_type == "modbus_server" && errors.is_empty() {
+        errors.extend(check_modbus_server_range(protocol_detail));
+    }
+    errors
+}
+
+/// 多寄存器数据类型要求足够的寄存器数量
+fn check_modbus_register_count(protocol_detail: &str) -> Option<FieldError> {
+    let value: serde_json::Value = serde_json::from_str(protocol_detail).ok()?;
+    let data_type = value.get("data_type").and_then(|item| item.as_str())?;
+    let required = match data_type {
+        "int32" | "uint32" | "float32" => 2,
+        "float64" => 4,
+        _ => 1,
+    };
+    let count = value
+        .get("register_count")
+        .and_then(|item| item.as_i64())
+        .unwrap_or(1);
+    if count < required {
+        Some(FieldError::new(
+            "register_count",
+            format!("{data_type} requires at least {required} registers"),
+        ))
+    } else {
+        None
+    }
+}
+
+/// 从站寄存器表项不能超出 65535
+fn check_modbus_server_range(protocol_detail: &str) -> Option<FieldError> {
+    let value: serde_json::Value = serde_json::from_str(protocol_detail).ok()?;
+    let address = value
+        .get("register_address")
+        .and_then(|item| item.as_i64())?;
+    let count = match value.get("data_type").and_then(|item| item.as_str()) {
+        Some("int32" | "uint32" | "float32") => 2,
+        Some("float64") => 4,
+        _ => 1,
+    };
+    if address + count > 65_536 {
+        Some(FieldError::new(
+            "register_address",
+            "register range exceeds 65535",
+        ))
+    } else {
+        None
+    }
+}
+
+/// 校验 JSON 文档：必须为对象；提供字段规则时检查必填、类型、范围与未知字段
+fn validate_document(document: &str, specs: Option<&[FieldSpec]>) -> Vec<FieldError> {
+    let value: serde_json::Value = match serde_json::from_str(document) {
+        Ok(value) => value,
+        Err(err) => return vec![FieldError::new("", format!("invalid JSON: {err}"))],
+    };
+    let Some(object) = value.as_object() else {
+        return vec![FieldError::new("", "must be a JSON object")];
+    };
+    let Some(specs) = specs else {
+        return Vec::new();
+    };
+    let mut errors = Vec::new();
+    for spec in specs {
+        match object.get(spec.name) {
+            None | Some(serde_json::Value::Null) => {
+                if spec.required {
+                    errors.push(FieldError::new(spec.name, "is required"));
+                }
+            }
+            Some(value) => {
+                if let Some(message) = check_kind(&spec.kind, value) {
+                    errors.push(FieldError::new(spec.name, message));
+                }
+            }
+        }
+    }
+    let mut unknown: Vec<&String> = object
+        .keys()
+        .filter(|key| !specs.iter().any(|spec| spec.name == key.as_str()))
+        .collect();
+    unknown.sort();
+    for key in unknown {
+        errors.push(FieldError::new(key, "unknown field"));
+    }
+    errors
+}
+
+fn check_kind(kind: &FieldKind, value: &serde_json::Value) -> Option<String> {
+    match kind {
+        FieldKind::String => match value.as_str() {
+            // 分隔符可能是空白字符（如 "\n"），只拒绝空串
+            Some(text) if !text.is_empty() => None,
+            Some(_) => Some("must not be empty".to_string()),
+            None => Some("must be a string".to_string()),
+        },
+        FieldKind::Integer { min, max } => match value.as_i64() {
+            Some(number) if number >= *min && number <= *max => None,
+            Some(_) => Some(format!("must be between {min} and {max}")),
+            None => Some("must be an integer".to_string()),
+        },
+        FieldKind::Bool => match value.as_bool() {
+            Some(_) => None,
+            None => Some("must be a boolean".to_string()),
+        },
+        FieldKind::Enum(values) => match value.as_str() {
+            Some(text) if values.contains(&text) => None,
+            _ => Some(format!("must be one of: {}", values.join(", "))),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(errors: &[FieldError]) -> Vec<&str> {
+        errors.iter().map(|error| error.field.as_str()).collect()
+    }
+
+    #[test]
+    fn test_modbus_gateway_config() {
+        let errors = validate_gateway_config(
+            "modbus_tcp",
+            r#"{"host": "192.168.1.100", "port": 502, "poll_interval_ms": 1000}"#,
+        );
+        assert!(errors.is_empty());
+
+        let errors = validate_gateway_config("modbus_tcp", r#"{"hots": "x", "port": 70000}"#);
+        assert_eq!(fields(&errors), vec!["host", "port", "hots"]);
+        assert_eq!(errors[0].message, "is required");
+        assert_eq!(errors[2].message, "unknown field");
+    }
+
+    #[test]
+    fn test_document_must_be_object() {
+        assert_eq!(fields(&validate_gateway_config("mqtt", "{")), vec![""]);
+        assert_eq!(fields(&validate_gateway_config("mqtt", "[1]")), vec![""]);
+        assert!(validate_gateway_config("mqtt", r#"{"anything": 1}"#).is_empty());
+        assert!(validate_gateway_config(
+            "tcp_server",
+            r#"{"listen_port": 9000, "frame_delimiter": "\n"}"#
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_protocol_type() {
+        assert!(validate_protocol_type("tcp_client").is_ok());
+        assert!(validate_protocol_type("modbus-tcp").is_err());
+    }
+
+    #[test]
+    fn test_modbus_device_and_point() {
+        assert!(validate_device_address("modbus_tcp", r#"{"slave_id": 1}"#).is_empty());
+        let errors = validate_device_address("modbus_tcp", r#"{"slave_address": 1}"#);
+        assert_eq!(fields(&errors), vec!["slave_id", "slave_address"]);
+
+        let detail = r#"{"function_code": 3, "register_address": 100, "register_count": 1, "data_type": "int16"}"#;
+        assert!(validate_point_detail("modbus_tcp", detail).is_empty());
+        let errors = validate_point_detail(
+            "modbus_tcp",
+            r#"{"register_address": 100, "data_type": "float"}"#,
+        );
+        assert_eq!(fields(&errors), vec!["data_type"]);
+        let errors = validate_point_detail(
+            "modbus_tcp",
+            r#"{"register_address": 100, "data_type": "float32"}"#,
+        );
+        assert_eq!(fields(&errors), vec!["register_count"]);
+    }
+
+    #[test]
+    fn test_modbus_server_config_and_point() {
+        assert!(validate_protocol_type("modbus_server").is_ok());
+        assert!(validate_gateway_config("modbus_server", r#"{"listen_port": 1502}"#).is_empty());
+        let errors = validate_gateway_config("modbus_server", r#"{"port": 1502}"#);
+        assert_eq!(fields(&errors), vec!["listen_port", "port"]);
+
+        let detail = r#"{"unit_id": 1, "function_code": 4, "register_address": 100, "data_type": "float32"}"#;
+        assert!(validate_point_detail("modbus_server", detail).is_empty());
+        let errors = validate_point_detail(
+            "modbus_server",
+            r#"{"register_address": 100, "function_code": 6}"#,
+        );
+        assert_eq!(fields(&errors), vec!["function_code"]);
+        let errors = validate_point_detail(
+            "modbus_server",
+            r#"{"register_address": 65534, "data_type": "float64"}"#,
+        );
+        assert_eq!(fields(&errors), vec!["register_address"]);
+    }
+}
@@ -0,0 +1,774 @@
+//! 规则评估与动作执行。
+//!
+//! - 点位 / 设备离线规则为边沿触发：条件由不满足变为满足时触发一次，恢复后才会再次触发
+//!   （引擎启动或规则更新后的首次评估视为此前不满足）
+//! - 定时规则从引擎首次看到规则（或规则更新）时开始计时
+//! - 告警规则订阅事件总线上的 `alarm.raised`；由规则动作产生的告警不会再触发规则，避免循环
+//! - 挂载维护模式后，设备（或其所属网关）处于维护窗口时设备离线规则不触发；
+//!   窗口结束后仍离线则按上升沿正常触发
+//!
+//! 评估状态只保存在进程内存中，多实例部署时每个实例都会独立评估。
+
+use crate::{RuleAction, RuleTrigger, parse_actions, parse_trigger};
+use domain::TenantContext;
+use ems_control::{CommandRequest, CommandService, MaintenanceService};
+use ems_events::{DomainEvent, EventBus, event_types};
+use ems_storage::{OnlineStore, RealtimeStore, RuleExecutionRecord, RuleRecord, RuleStore};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::MissedTickBehavior;
+use tracing::{info, warn};
+
+/// 规则引擎配置
+#[derive(Debug, Clone)]
+pub struct RuleEngineConfig {
+    /// 评估间隔（毫秒）
+    pub tick_ms: u64,
+    /// Webhook 动作单次请求超时（毫秒）
+    pub webhook_timeout_ms: u64,
+}
+
+impl Default for RuleEngineConfig {
+    fn default() -> Self {
+        Self {
+            tick_ms: 1000,
+            webhook_timeout_ms: 5000,
+        }
+    }
+}
+
+/// 单条规则的评估状态
+#[derive(Debug, Default)]
+struct RuleState {
+    /// 规则更新时间（变化时重置状态）
+    revision: i64,
+    /// 上次评估时条件是否满足
+    condition: bool,
+    /// 定时规则的上次触发（或开始计时）时间
+    last_fired_ms: Option<i64>,
+}
+
+type RuleKey = (String, String, String);
+
+/// 规则引擎（评估触发条件并执行动作）
+pub struct RuleEngine {
+    rule_store: Arc<dyn RuleStore>,
+    realtime_store: Arc<dyn RealtimeStore>,
+    online_store: Arc<dyn OnlineStore>,
+    command_service: Arc<CommandService>,
+    event_bus: EventBus,
+    client: reqwest::Client,
+    states: Mutex<HashMap<RuleKey, RuleState>>,
+    maintenance: Option<Arc<MaintenanceService>>,
+}
+
+impl RuleEngine {
+    pub fn new(
+        rule_store: Arc<dyn RuleStore>,
+        realtime_store: Arc<dyn RealtimeStore>,
+        online_store: Arc<dyn OnlineStore>,
+        command_service: Arc<CommandService>,
+        event_bus: EventBus,
+        config: &RuleEngineConfig,
+    ) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.webhook_timeout_ms.max(1)))
+            .build()
+            .unwrap_or_default();
+        Self {
+            rule_store,
+            realtime_store,
+            online_store,
+            command_service,
+            event_bus,
+            client,
+            states: Mutex::new(HashMap::new()),
+            maintenance: None,
+        }
+    }
+
+    /// 挂载维护模式：处于维护窗口的设备不触发离线规则
+    pub fn with_maintenance(mut self, maintenance: Arc<MaintenanceService>) -> Self {
+        self.maintenance = Some(maintenance);
+        self
+    }
+
+    /// 评估一次点位 / 定时 / 设备离线规则，返回本次触发的执行记录
+    pub async fn evaluate(&self, now_ms: i64) -> Vec<RuleExecutionRecord> {
+        let rules = match self.rule_store.list_enabled_rules().await {
+            Ok(rules) => rules,
+            Err(err) => {
+                warn!(target: "ems.rules", error = %err, "rules_read_failed");
+                return Vec::new();
+            }
+        };
+        self.retain_states(&rules);
+        let mut executions = Vec::new();
+        for rule in rules {
+            let trigger = match stored_trigger(&rule) {
+                Ok(trigger) => trigger,
+                Err(err) => {
+                    warn!(target: "ems.rules", rule_id = %rule.rule_id, error = %err, "rule_trigger_invalid");
+                    continue;
+                }
+            };
+            let ctx = rule_context(&rule);
+            let detail = match trigger {
+                RuleTrigger::Point {
+                    point_id,
+                    op,
+                    value,
+                } => {
+                    let record = match self
+                        .realtime_store
+                        .get_last_value(&ctx, &rule.project_id, &point_id)
+                        .await
+                    {
+                        Ok(record) => record,
+                        Err(err) => {
+                            warn!(target: "ems.rules", rule_id = %rule.rule_id, error = %err, "rule_point_read_failed");
+                            continue;
+                        }
+                    };
+                    let reading = record.and_then(|record| {
+                        record
+                            .value
+                            .parse::<f64>()
+                            .ok()
+                            .map(|current| (current, record.ts_ms))
+                    });
+                    let matched = reading.is_some_and(|(current, _)| op.compare(current, value));
+                    if !self.rising_edge(&rule, matched) {
+                        continue;
+                    }
+                    let (current, ts_ms) = reading.unwrap_or_default();
+                    json!({
+                        "type": "point",
+                        "pointId": point_id,
+                        "value": current,
+                        "tsMs": ts_ms,
+                    })
+                }
+                RuleTrigger::Schedule { every_seconds } => {
+                    if !self.schedule_due(&rule, every_seconds, now_ms) {
+                        continue;
+                    }
+                    json!({ "type": "schedule", "everySeconds": every_seconds })
+                }
+                RuleTrigger::DeviceOffline {
+                    device_id,
+                    offline_after_seconds,
+                } => {
+                    let last_seen_at_ms = match self
+                        .online_store
+                        .get_device_last_seen_at_ms(&ctx, &rule.project_id, &device_id)
+                        .await
+                    {
+                        Ok(last_seen_at_ms) => last_seen_at_ms,
+                        Err(err) => {
+                            warn!(target: "ems.rules", rule_id = %rule.rule_id, error = %err, "rule_online_read_failed");
+                            continue;
+                        }
+                    };
+                    let threshold_ms = offline_after_seconds.saturating_mul(1000) as i64;
+                    let offline = last_seen_at_ms
+                        .is_none_or(|seen| now_ms.saturating_sub(seen) > threshold_ms);
+                    if offline
+                        && self
+                            .under_maintenance(&ctx, &rule, &device_id, now_ms)
+                            .await
+                    {
+                        // 维护期间视为未离线，窗口结束后仍离线时再触发
+                        self.rising_edge(&rule, false);
+                        continue;
+                    }
+                    if !self.rising_edge(&rule, offline) {
+                        continue;
+                    }
+                    json!({
+                        "type": "device_offline",
+                        "deviceId": device_id,
+                        "lastSeenAtMs": last_seen_at_ms,
+                    })
+                }
+                RuleTrigger::Alarm { .. } => continue,
+            };
+            executions.push(self.execute(&rule, detail, now_ms).await);
+        }
+        executions
+    }
+
+    /// 处理领域事件（触发告警规则），返回本次触发的执行记录
+    pub async fn handle_event(&self, event: &DomainEvent) -> Vec<RuleExecutionRecord> {
+        // 规则动作产生的告警带 ruleId，不再触发规则
+        if event.event_type != event_types::ALARM_RAISED || event.data.get("ruleId").is_some() {
+            return Vec::new();
+        }
+        let rules = match self.rule_store.list_enabled_rules().await {
+            Ok(rules) => rules,
+            Err(err) => {
+                warn!(target: "ems.rules", error = %err, "rules_read_failed");
+                return Vec::new();
+            }
+        };
+        let severity = event.data.get("severity").and_then(Value::as_str);
+        let mut executions = Vec::new();
+        for rule in rules {
+            if rule.tenant_id != event.tenant_id || rule.project_id != event.project_id {
+                continue;
+            }
+            let Ok(RuleTrigger::Alarm { severity: filter }) = stored_trigger(&rule) else {
+                continue;
+            };
+            if filter.is_some() && filter.as_deref() != severity {
+                continue;
+            }
+            let detail = json!({
+                "type": "alarm",
+                "eventId": event.event_id,
+                "severity": severity,
+                "data": event.data,
+            });
+            executions.push(self.execute(&rule, detail, event.occurred_at_ms).await);
+        }
+        executions
+    }
+
+    /// 依次执行规则动作并写入执行记录（单个动作失败不影响后续动作）
+    async fn execute(&self, rule: &RuleRecord, detail: Value, now_ms: i64) -> RuleExecutionRecord {
+        let ctx = rule_context(rule);
+        let mut results = Vec::new();
+        match stored_actions(rule) {
+            Ok(actions) => {
+                for action in &actions {
+                    let mut result =
+                        match self.run_action(&ctx, rule, action, &detail, now_ms).await {
+                            Ok(mut output) => {
+                                output["status"] = json!("success");
+                                output
+                            }
+                            Err(error) => json!({ "status": "failed", "error": error }),
+                        };
+                    result["type"] = json!(action.kind());
+                    results.push(result);
+                }
+            }
+            Err(error) => results.push(json!({ "status": "failed", "error": error })),
+        }
+        let failed = results.iter().any(|result| result["status"] != "success");
+        let record = RuleExecutionRecord {
+            execution_id: uuid::Uuid::new_v4().to_string(),
+            tenant_id: rule.tenant_id.clone(),
+            project_id: rule.project_id.clone(),
+            rule_id: rule.rule_id.clone(),
+            trigger_detail: detail.to_string(),
+            status: if failed { "failed" } else { "success" }.to_string(),
+            results: Value::Array(results).to_string(),
+            executed_at_ms: now_ms,
+        };
+        if failed {
+            warn!(
+                target: "ems.rules",
+                tenant_id = %record.tenant_id,
+                rule_id = %record.rule_id,
+                results = %record.results,
+                "rule_execution_failed"
+            );
+        } else {
+            info!(
+                target: "ems.rules",
+                tenant_id = %record.tenant_id,
+                rule_id = %record.rule_id,
+                "rule_executed"
+            );
+        }
+        if let Err(err) = self
+            .rule_store
+            .create_rule_execution(&ctx, record.clone())
+            .await
+        {
+            warn!(target: "ems.rules", rule_id = %record.rule_id, error = %err, "rule_execution_log_failed");
+        }
+        record
+    }
+
+    async fn run_action(
+        &self,
+        ctx: &TenantContext,
+        rule: &RuleRecord,
+        action: &RuleAction,
+        detail: &Value,
+        now_ms: i64,
+    ) -> Result<Value, String> {
+        match action {
+            RuleAction::Command { target, payload } => {
+                let command = self
+                    .command_service
+                    .issue_command(
+                        ctx,
+                        CommandRequest {
+                            project_id: rule.project_id.clone(),
+                            target: target.clone(),
+                            payload: payload.clone(),
+                            issued_at_ms: now_ms,
+                        },
+                    )
+                    .await
+                    .map_err(|err| err.to_string())?;
+                Ok(json!({ "commandId": command.command_id }))
+            }
+            RuleAction::Alarm { severity, message } => {
+                let event = DomainEvent::new(
+                    event_types::ALARM_RAISED,
+                    rule.tenant_id.clone(),
+                    rule.project_id.clone(),
+                    json!({
+                        "ruleId": rule.rule_id,
+                        "ruleName": rule.name,
+                        "severity": severity,
+                        "message": message,
+                        "trigger": detail,
+                    }),
+                );
+                let event_id = event.event_id.clone();
+                self.event_bus.publish(event);
+                Ok(json!({ "eventId": event_id }))
+            }
+            RuleAction::Webhook { url } => {
+                let body = json!({
+                    "ruleId": rule.rule_id,
+                    "ruleName": rule.name,
+                    "tenantId": rule.tenant_id,
+                    "projectId": rule.project_id,
+                    "firedAtMs": now_ms,
+                    "trigger": detail,
+                });
+                let response = self
+                    .client
+                    .post(url)
+                    .json(&body)
+                    .send()
+                    .await
+                    .map_err(|err| err.to_string())?;
+                let status = response.status();
+                if !status.is_success() {
+                    return Err(format!("unexpected status {}", status.as_u16()));
+                }
+                Ok(json!({ "responseStatus": status.as_u16() }))
+            }
+        }
+    }
+
+    /// 设备（或其所属网关）当前是否处于维护窗口（未挂载维护模式或读取失败时视为否）
+    async fn under_maintenance(
+        &self,
+        ctx: &TenantContext,
+        rule: &RuleRecord,
+        device_id: &str,
+        now_ms: i64,
+    ) -> bool {
+        let Some(maintenance) = &self.maintenance else {
+            return false;
+        };
+        match maintenance
+            .active_window_for_device(ctx, &rule.project_id, device_id, now_ms)
+            .await
+        {
+            Ok(Some(window)) => {
+                info!(
+                    target: "ems.rules",
+                    rule_id = %rule.rule_id,
+                    device_id = %device_id,
+                    maintenance_target = %window.target_id,
+                    "rule_offline_suppressed_by_maintenance"
+                );
+                true
+            }
+            Ok(None) => false,
+            Err(err) => {
+                warn!(target: "ems.rules", rule_id = %rule.rule_id, error = %err, "rule_maintenance_read_failed");
+                false
+            }
+        }
+    }
+
+    /// 记录本次条件结果，返回是否为上升沿（不满足 → 满足）
+    fn rising_edge(&self, rule: &RuleRecord, matched: bool) -> bool {
+        let Ok(mut states) = self.states.lock() else {
+            return false;
+        };
+        let state = state_for(&mut states, rule);
+        let fire = matched && !state.condition;
+        state.condition = matched;
+        fire
+    }
+
+    /// 定时规则是否到期（首次看到规则时开始计时）
+    fn schedule_due(&self, rule: &RuleRecord, every_seconds: u64, now_ms: i64) -> bool {
+        let Ok(mut states) = self.states.lock() else {
+            return false;
+        };
+        let state = state_for(&mut states, rule);
+        let interval_ms = every_seconds.saturating_mul(1000) as i64;
+        match state.last_fired_ms {
+            Some(last) if now_ms.saturating_sub(last) >= interval_ms => {
+                state.last_fired_ms = Some(now_ms);
+                true
+            }
+            Some(_) => false,
+            None => {
+                state.last_fired_ms = Some(now_ms);
+                false
+            }
+        }
+    }
+
+    /// 丢弃已删除 / 已停用规则的状态
+    fn retain_states(&self, rules: &[RuleRecord]) {
+        if let Ok(mut states) = self.states.lock() {
+            states.retain(|key, _| rules.iter().any(|rule| rule_key(rule) == *key));
+        }
+    }
+}
+
+/// 启动规则引擎后台任务（按间隔评估 + 订阅事件总线）
+pub fn spawn_rule_engine(
+    engine: Arc<RuleEngine>,
+    bus: &EventBus,
+    config: &RuleEngineConfig,
+) -> tokio::task::JoinHandle<()> {
+    let mut receiver = bus.subscribe();
+    let tick_ms = config.tick_ms.max(1);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_millis(tick_ms));
+        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    engine.evaluate(now_epoch_ms()).await;
+                }
+                received = receiver.recv() => match received {
+                    Ok(event) => {
+                        engine.handle_event(&event).await;
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!(target: "ems.rules", skipped = skipped, "rule_engine_lagged");
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+            }
+        }
+    })
+}
+
+fn state_for<'a>(
+    states: &'a mut HashMap<RuleKey, RuleState>,
+    rule: &RuleRecord,
+) -> &'a mut RuleState {
+    let state = states.entry(rule_key(rule)).or_default();
+    if state.revision != rule.updated_at_ms {
+        *state = RuleState {
+            revision: rule.updated_at_ms,
+            ..RuleState::default()
+        };
+    }
+    state
+}
+
+fn rule_key(rule: &RuleRecord) -> RuleKey {
+    (
+        rule.tenant_id.clone(),
+        rule.project_id.clone(),
+        rule.rule_id.clone(),
+    )
+}
+
+fn stored_trigger(rule: &RuleRecord) -> Result<RuleTrigger, String> {
+    let value: Value = serde_json::from_str(&rule.trigger).map_err(|err| err.to_string())?;
+    parse_trigger(&value).map_err(|err| err.to_string())
+}
+
+fn stored_actions(rule: &RuleRecord) -> Result<Vec<RuleAction>, String> {
+    let values: Vec<Value> = serde_json::from_str(&rule.actions).map_err(|err| err.to_string())?;
+    parse_actions(&values).map_err(|err| err.to_string())
+}
+
+/// 规则执行上下文（命令与审计记录中的操作人为 `rule:{rule_id}`）
+fn rule_context(rule: &RuleRecord) -> TenantContext {
+    TenantContext::new(
+        rule.tenant_id.clone(),
+        format!("rule:{}", rule.rule_id),
+        Vec::new(),
+        Vec::new(),
+        Some(rule.project_id.clone()),
+    )
+}
+
+fn now_epoch_ms() -> i64 {
+    let now = std::time::SystemTime::now();
+    let duration = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    duration.as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::PointValue;
+    use ems_control::NoopDispatcher;
+    use ems_storage::{
+        CommandQueryOptions, CommandStore, InMemoryAuditLogStore, InMemoryCommandStore,
+        InMemoryOnlineStore, InMemoryRealtimeStore, InMemoryRuleStore,
+    };
+
+    struct Harness {
+        engine: RuleEngine,
+        rule_store: Arc<InMemoryRuleStore>,
+        realtime_store: Arc<InMemoryRealtimeStore>,
+        command_store: Arc<InMemoryCommandStore>,
+        bus: EventBus,
+    }
+
+    fn harness() -> Harness {
+        let rule_store = Arc::new(InMemoryRuleStore::new());
+        let realtime_store = Arc::new(InMemoryRealtimeStore::new());
+        let command_store = Arc::new(InMemoryCommandStore::new());
+        let command_service = Arc::new(CommandService::new(
+            command_store.clone(),
+            Arc::new(InMemoryAuditLogStore::new()),
+            Arc::new(NoopDispatcher),
+        ));
+        let bus = EventBus::default();
+        let engine = RuleEngine::new(
+            rule_store.clone(),
+            realtime_store.clone(),
+            Arc::new(InMemoryOnlineStore::new()),
+            command_service,
+            bus.clone(),
+            &RuleEngineConfig::default(),
+        );
+        Harness {
+            engine,
+            rule_store,
+            realtime_store,
+            command_store,
+            bus,
+        }
+    }
+
+    fn ctx() -> TenantContext {
+        TenantContext::new(
+            "tenant-1".to_string(),
+            "user-1".to_string(),
+            Vec::new(),
+            Vec::new(),
+            Some("project-1".to_string()),
+        )
+    }
+
+    async fn add_rule(store: &InMemoryRuleStore, rule_id: &str, trigger: Value, actions: Value) {
+        store
+            .create_rule(
+                &ctx(),
+                RuleRecord {
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    rule_id: rule_id.to_string(),
+                    name: rule_id.to_string(),
+                    enabled: true,
+                    trigger: trigger.to_string(),
+                    actions: actions.to_string(),
+                    created_by: "user-1".to_string(),
+                    created_at_ms: 1,
+                    updated_at_ms: 1,
+                },
+            )
+            .await
+            .expect("rule");
+    }
+
+    async fn write_value(store: &InMemoryRealtimeStore, value: f64, ts_ms: i64) {
+        store
+            .upsert_last_value(
+                &ctx(),
+                &PointValue {
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    point_id: "point-1".to_string(),
+                    ts_ms,
+                    value: domain::PointValueData::F64(value),
+                    quality: None,
+                },
+            )
+            .await
+            .expect("value");
+    }
+
+    #[tokio::test]
+    async fn point_rule_fires_on_rising_edge_and_issues_command() {
+        let h = harness();
+        add_rule(
+            &h.rule_store,
+            "rule-1",
+            json!({"type": "point", "pointId": "point-1", "op": "gt", "value": 30}),
+            json!([{"type": "command", "target": "device-1", "payload": {"fan": "on"}}]),
+        )
+        .await;
+
+        write_value(&h.realtime_store, 25.0, 1_000).await;
+        assert!(h.engine.evaluate(1_000).await.is_empty());
+
+        write_value(&h.realtime_store, 31.5, 2_000).await;
+        let executions = h.engine.evaluate(2_000).await;
+        assert_eq!(executions.len(), 1);
+        assert_eq!(executions[0].status, "success");
+
+        // 条件持续满足不重复触发，恢复后再次满足才触发
+        write_value(&h.realtime_store, 35.0, 3_000).await;
+        assert!(h.engine.evaluate(3_000).await.is_empty());
+        write_value(&h.realtime_store, 20.0, 4_000).await;
+        assert!(h.engine.evaluate(4_000).await.is_empty());
+        write_value(&h.realtime_store, 32.0, 5_000).await;
+        assert_eq!(h.engine.evaluate(5_000).await.len(), 1);
+
+        let commands = h
+            .command_store
+            .list_commands(&ctx(), "project-1", CommandQueryOptions::simple(10))
+            .await
+            .expect("commands");
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[0].issued_by, "rule:rule-1");
+        assert_eq!(commands[0].target, "device-1");
+
+        let history = h
+            .rule_store
+            .list_rule_executions(&ctx(), "project-1", "rule-1", 10)
+            .await
+            .expect("history");
+        assert_eq!(history.len(), 2);
+        let detail: Value = serde_json::from_str(&history[0].trigger_detail).expect("detail");
+        assert_eq!(detail["value"], 32.0);
+    }
+
+    #[tokio::test]
+    async fn schedule_and_alarm_rules_chain_without_looping() {
+        let h = harness();
+        let mut receiver = h.bus.subscribe();
+        add_rule(
+            &h.rule_store,
+            "rule-schedule",
+            json!({"type": "schedule", "everySeconds": 60}),
+            json!([{"type": "alarm", "severity": "critical", "message": "hourly check"}]),
+        )
+        .await;
+        add_rule(
+            &h.rule_store,
+            "rule-alarm",
+            json!({"type": "alarm", "severity": "critical"}),
+            json!([{"type": "alarm", "message": "echo"}]),
+        )
+        .await;
+
+        // 首次看到定时规则只开始计时
+        assert!(h.engine.evaluate(0).await.is_empty());
+        assert!(h.engine.evaluate(59_000).await.is_empty());
+        let executions = h.engine.evaluate(60_000).await;
+        assert_eq!(executions.len(), 1);
+        assert_eq!(executions[0].rule_id, "rule-schedule");
+
+        // 规则产生的告警不会触发告警规则
+        let event = receiver.recv().await.expect("event");
+        assert_eq!(event.data["ruleId"], "rule-schedule");
+        assert!(h.engine.handle_event(&event).await.is_empty());
+
+        // 外部告警按级别过滤
+        let external = DomainEvent::new(
+            event_types::ALARM_RAISED,
+            "tenant-1",
+            "project-1",
+            json!({"severity": "critical", "message": "overheat"}),
+        );
+        let executions = h.engine.handle_event(&external).await;
+        assert_eq!(executions.len(), 1);
+        assert_eq!(executions[0].rule_id, "rule-alarm");
+        let minor = DomainEvent::new(
+            event_types::ALARM_RAISED,
+            "tenant-1",
+            "project-1",
+            json!({"severity": "minor"}),
+        );
+        assert!(h.engine.handle_event(&minor).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn failed_action_marks_execution_failed() {
+        let h = harness();
+        add_rule(
+            &h.rule_store,
+            "rule-offline",
+            json!({"type": "device_offline", "deviceId": "device-1", "offlineAfterSeconds": 60}),
+            // 端口 1 无监听，连接立即失败
+            json!([
+                {"type": "webhook", "url": "http://127.0.0.1:1/hook"},
+                {"type": "alarm", "message": "device offline"}
+            ]),
+        )
+        .await;
+
+        let executions = h.engine.evaluate(1_000).await;
+        assert_eq!(executions.len(), 1);
+        assert_eq!(executions[0].status, "failed");
+        let results: Value = serde_json::from_str(&executions[0].results).expect("results");
+        assert_eq!(results[0]["type"], "webhook");
+        assert_eq!(results[0]["status"], "failed");
+        assert_eq!(results[1]["status"], "success");
+
+        // 持续离线不重复触发
+        assert!(h.engine.evaluate(2_000).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn maintenance_suppresses_offline_rules_until_window_ends() {
+        let h = harness();
+        let maintenance_store = Arc::new(ems_storage::InMemoryMaintenanceStore::new());
+        let maintenance = Arc::new(MaintenanceService::new(
+            maintenance_store,
+            Arc::new(ems_storage::InMemoryDeviceStore::new()),
+            Arc::new(InMemoryAuditLogStore::new()),
+        ));
+        maintenance
+            .set_window(
+                &ctx(),
+                ems_storage::MaintenanceWindowRecord {
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    target_type: "device".to_string(),
+                    target_id: "device-1".to_string(),
+                    starts_at_ms: 0,
+                    ends_at_ms: 10_000,
+                    reason: None,
+                    created_by: "user-1".to_string(),
+                    created_at_ms: 0,
+                },
+            )
+            .await
+            .expect("window");
+        let engine = h.engine.with_maintenance(maintenance);
+        add_rule(
+            &h.rule_store,
+            "rule-offline",
+            json!({"type": "device_offline", "deviceId": "device-1", "offlineAfterSeconds": 60}),
+            json!([{"type": "alarm", "message": "device offline"}]),
+        )
+        .await;
+
+        assert!(engine.evaluate(1_000).await.is_empty());
+        assert!(engine.evaluate(9_000).await.is_empty());
+        // 窗口结束后仍离线，正常触发
+        assert_eq!(engine.evaluate(10_000).await.len(), 1);
+    }
+}
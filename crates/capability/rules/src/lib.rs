@@ -0,0 +1,297 @@
+//! 自动化规则引擎（if-this-then-that）
+//!
+//! 租户按项目定义规则：一个触发条件 + 若干动作。
+//! - 触发条件：点位条件（实时值比较）、定时、告警事件、设备离线
+//! - 动作：下发命令、触发告警（发布 `alarm.raised`）、调用 Webhook
+//!
+//! 规则以 JSON 保存在 `RuleStore` 中，由本 crate 解析校验；
+//! `spawn_rule_engine` 按固定间隔评估规则并订阅事件总线，每次触发写一条执行记录。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+mod engine;
+pub use engine::*;
+
+/// 设备离线判定的默认阈值（秒）
+pub const DEFAULT_OFFLINE_AFTER_SECONDS: u64 = 300;
+/// 定时触发的最小间隔（秒）
+pub const MIN_SCHEDULE_SECONDS: u64 = 1;
+/// 单条规则的最大动作数
+pub const MAX_ACTIONS: usize = 10;
+
+/// 规则定义错误。
+#[derive(Debug, thiserror::Error)]
+pub enum RuleError {
+    #[error("invalid trigger: {0}")]
+    Trigger(String),
+    #[error("invalid actions: {0}")]
+    Actions(String),
+}
+
+/// 比较运算符。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompareOp {
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    Eq,
+    Ne,
+}
+
+impl CompareOp {
+    /// 计算 `left op right`
+    pub fn compare(self, left: f64, right: f64) -> bool {
+        match self {
+            CompareOp::Gt => left > right,
+            CompareOp::Gte => left >= right,
+            CompareOp::Lt => left < right,
+            CompareOp::Lte => left <= right,
+            CompareOp::Eq => left == right,
+            CompareOp::Ne => left != right,
+        }
+    }
+}
+
+/// 触发条件。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleTrigger {
+    /// 点位实时值满足比较条件（条件由不满足变为满足时触发）
+    #[serde(rename_all = "camelCase")]
+    Point {
+        point_id: String,
+        op: CompareOp,
+        value: f64,
+    },
+    /// 每隔固定秒数触发
+    #[serde(rename_all = "camelCase")]
+    Schedule { every_seconds: u64 },
+    /// 收到告警事件时触发（可按级别过滤）
+    Alarm {
+        #[serde(default)]
+        severity: Option<String>,
+    },
+    /// 设备超过阈值未上报时触发（由在线变为离线时触发）
+    #[serde(rename_all = "camelCase")]
+    DeviceOffline {
+        device_id: String,
+        #[serde(default = "default_offline_after_seconds")]
+        offline_after_seconds: u64,
+    },
+}
+
+fn default_offline_after_seconds() -> u64 {
+    DEFAULT_OFFLINE_AFTER_SECONDS
+}
+
+impl RuleTrigger {
+    /// 触发类型名（与 JSON `type` 一致）
+    pub fn kind(&self) -> &'static str {
+        match self {
+            RuleTrigger::Point { .. } => "point",
+            RuleTrigger::Schedule { .. } => "schedule",
+            RuleTrigger::Alarm { .. } => "alarm",
+            RuleTrigger::DeviceOffline { .. } => "device_offline",
+        }
+    }
+}
+
+/// 规则动作。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleAction {
+    /// 经命令链路下发命令
+    Command { target: String, payload: Value },
+    /// 发布 `alarm.raised` 事件
+    Alarm {
+        #[serde(default = "default_alarm_severity")]
+        severity: String,
+        message: String,
+    },
+    /// 以 POST JSON 调用 Webhook
+    Webhook { url: String },
+}
+
+fn default_alarm_severity() -> String {
+    "warning".to_string()
+}
+
+impl RuleAction {
+    /// 动作类型名（与 JSON `type` 一致）
+    pub fn kind(&self) -> &'static str {
+        match self {
+            RuleAction::Command { .. } => "command",
+            RuleAction::Alarm { .. } => "alarm",
+            RuleAction::Webhook { .. } => "webhook",
+        }
+    }
+}
+
+/// 解析并校验触发条件。
+pub fn parse_trigger(value: &Value) -> Result<RuleTrigger, RuleError> {
+    let trigger: RuleTrigger =
+        serde_json::from_value(value.clone()).map_err(|err| RuleError::Trigger(err.to_string()))?;
+    match &trigger {
+        RuleTrigger::Point {
+            point_id, value, ..
+        } => {
+            if point_id.trim().is_empty() {
+                return Err(RuleError::Trigger("pointId is required".to_string()));
+            }
+            if !value.is_finite() {
+                return Err(RuleError::Trigger("value must be finite".to_string()));
+            }
+        }
+        RuleTrigger::Schedule { every_seconds } => {
+            if *every_seconds < MIN_SCHEDULE_SECONDS {
+                return Err(RuleError::Trigger(format!(
+                    "everySeconds must be at least {MIN_SCHEDULE_SECONDS}"
+                )));
+            }
+        }
+        RuleTrigger::Alarm { .. } => {}
+        RuleTrigger::DeviceOffline {
+            device_id,
+            offline_after_seconds,
+        } => {
+            if device_id.trim().is_empty() {
+                return Err(RuleError::Trigger("deviceId is required".to_string()));
+            }
+            if *offline_after_seconds == 0 {
+                return Err(RuleError::Trigger(
+                    "offlineAfterSeconds must be greater than 0".to_string(),
+                ));
+            }
+        }
+    }
+    Ok(trigger)
+}
+
+/// 解析并校验动作列表（至少一个，最多 `MAX_ACTIONS` 个）。
+pub fn parse_actions(values: &[Value]) -> Result<Vec<RuleAction>, RuleError> {
+    if values.is_empty() {
+        return Err(RuleError::Actions(
+            "at least one action is required".to_string(),
+        ));
+    }
+    if values.len() > MAX_ACTIONS {
+        return Err(RuleError::Actions(format!(
+            "at most {MAX_ACTIONS} actions are allowed"
+        )));
+    }
+    let mut actions = Vec::with_capacity(values.len());
+    for (index, value) in values.iter().enumerate() {
+        let action: RuleAction = serde_json::from_value(value.clone())
+            .map_err(|err| RuleError::Actions(format!("action {index}: {err}")))?;
+        match &action {
+            RuleAction::Command { target, .. } => {
+                if target.trim().is_empty() {
+                    return Err(RuleError::Actions(format!(
+                        "action {index}: target is required"
+                    )));
+                }
+            }
+            RuleAction::Alarm { message, .. } => {
+                if message.trim().is_empty() {
+                    return Err(RuleError::Actions(format!(
+                        "action {index}: message is required"
+                    )));
+                }
+            }
+            RuleAction::Webhook { url } => {
+                if !(url.starts_with("http://") || url.starts_with("https://")) {
+                    return Err(RuleError::Actions(format!(
+                        "action {index}: url must start with http:// or https://"
+                    )));
+                }
+            }
+        }
+        actions.push(action);
+    }
+    Ok(actions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parse_trigger_accepts_each_kind() {
+        let trigger =
+            parse_trigger(&json!({"type": "point", "pointId": "p1", "op": "gt", "value": 10}))
+                .expect("point");
+        assert_eq!(
+            trigger,
+            RuleTrigger::Point {
+                point_id: "p1".to_string(),
+                op: CompareOp::Gt,
+                value: 10.0
+            }
+        );
+        let trigger =
+            parse_trigger(&json!({"type": "device_offline", "deviceId": "d1"})).expect("offline");
+        assert_eq!(
+            trigger,
+            RuleTrigger::DeviceOffline {
+                device_id: "d1".to_string(),
+                offline_after_seconds: DEFAULT_OFFLINE_AFTER_SECONDS
+            }
+        );
+        assert_eq!(
+            parse_trigger(&json!({"type": "alarm"}))
+                .expect("alarm")
+                .kind(),
+            "alarm"
+        );
+        assert_eq!(
+            parse_trigger(&json!({"type": "schedule", "everySeconds": 60}))
+                .expect("schedule")
+                .kind(),
+            "schedule"
+        );
+    }
+
+    #[test]
+    fn parse_rejects_invalid_definitions() {
+        assert!(parse_trigger(&json!({"type": "unknown"})).is_err());
+        assert!(
+            parse_trigger(&json!({"type": "point", "pointId": "p1", "op": "between", "value": 1}))
+                .is_err()
+        );
+        assert!(parse_trigger(&json!({"type": "schedule", "everySeconds": 0})).is_err());
+        assert!(parse_actions(&[]).is_err());
+        assert!(parse_actions(&[json!({"type": "webhook", "url": "ftp://x"})]).is_err());
+        assert!(
+            parse_actions(&[json!({"type": "command", "target": " ", "payload": {}})]).is_err()
+        );
+
+        let actions = parse_actions(&[
+            json!({"type": "command", "target": "device-1", "payload": {"on": true}}),
+            json!({"type": "alarm", "message": "too hot"}),
+        ])
+        .expect("actions");
+        assert_eq!(actions[1].kind(), "alarm");
+        assert_eq!(
+            actions[1],
+            RuleAction::Alarm {
+                severity: "warning".to_string(),
+                message: "too hot".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn compare_ops() {
+        assert!(CompareOp::Gt.compare(2.0, 1.0));
+        assert!(!CompareOp::Gt.compare(1.0, 1.0));
+        assert!(CompareOp::Gte.compare(1.0, 1.0));
+        assert!(CompareOp::Lt.compare(0.5, 1.0));
+        assert!(CompareOp::Lte.compare(1.0, 1.0));
+        assert!(CompareOp::Eq.compare(1.0, 1.0));
+        assert!(CompareOp::Ne.compare(1.0, 2.0));
+    }
+}
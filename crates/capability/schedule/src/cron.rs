@@ -0,0 +1,230 @@
+//! 五段式 cron 表达式（分 时 日 月 周），按指定时区计算下次触发时间。
+//!
+//! - 支持 `*`、数值、范围 `a-b`、列表 `a,b`、步长 `*/n` / `a-b/n`
+//! - 周取值 0-7（0 与 7 均为周日）；日与周同时受限时任一匹配即可（与 crontab 一致）
+//! - 夏令时：不存在的本地时刻跳过，重复的本地时刻只触发一次
+
+use chrono::{Datelike, NaiveDate, NaiveDateTime, TimeDelta, TimeZone, Timelike};
+use chrono_tz::Tz;
+
+/// 向后搜索下次触发时间的最大天数（覆盖仅在闰年 2 月 29 日触发的表达式）
+const MAX_SEARCH_DAYS: i64 = 366 * 8;
+
+/// 已解析的 cron 表达式（各字段为取值位图）。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronExpr {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// 日字段是否受限（非 `*` 开头）
+    day_restricted: bool,
+    /// 周字段是否受限（非 `*` 开头）
+    weekday_restricted: bool,
+}
+
+impl CronExpr {
+    /// 解析五段式表达式
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!("cron must have 5 fields, got {}", fields.len()));
+        }
+        let mut weekdays = parse_field(fields[4], 0, 7, "weekday")?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            minutes: parse_field(fields[0], 0, 59, "minute")?,
+            hours: parse_field(fields[1], 0, 23, "hour")?,
+            days: parse_field(fields[2], 1, 31, "day")?,
+            months: parse_field(fields[3], 1, 12, "month")?,
+            weekdays,
+            day_restricted: !fields[2].starts_with('*'),
+            weekday_restricted: !fields[4].starts_with('*'),
+        })
+    }
+
+    /// 严格晚于 `after_ms` 的下一次触发时间（毫秒），搜索范围内无匹配时返回 None
+    pub fn next_after(&self, after_ms: i64, timezone: Tz) -> Option<i64> {
+        let local = chrono::DateTime::from_timestamp_millis(after_ms)?
+            .with_timezone(&timezone)
+            .naive_local();
+        let mut candidate = local.with_second(0)?.with_nanosecond(0)? + TimeDelta::minutes(1);
+        let limit = candidate + TimeDelta::days(MAX_SEARCH_DAYS);
+        while candidate < limit {
+            if !has_bit(self.months, candidate.month()) {
+                candidate = first_of_next_month(candidate.date())?;
+                continue;
+            }
+            if !self.day_matches(candidate.date()) {
+                candidate = candidate.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if !has_bit(self.hours, candidate.hour()) {
+                candidate =
+                    candidate.date().and_hms_opt(candidate.hour(), 0, 0)? + TimeDelta::hours(1);
+                continue;
+            }
+            if has_bit(self.minutes, candidate.minute())
+                && let Some(at) = timezone.from_local_datetime(&candidate).earliest()
+            {
+                let at_ms = at.timestamp_millis();
+                if at_ms > after_ms {
+                    return Some(at_ms);
+                }
+            }
+            candidate += TimeDelta::minutes(1);
+        }
+        None
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let day = has_bit(self.days, date.day());
+        let weekday = has_bit(self.weekdays, date.weekday().num_days_from_sunday());
+        if self.day_restricted && self.weekday_restricted {
+            day || weekday
+        } else {
+            day && weekday
+        }
+    }
+}
+
+fn has_bit(mask: u64, value: u32) -> bool {
+    mask & (1 << value) != 0
+}
+
+fn first_of_next_month(date: NaiveDate) -> Option<NaiveDateTime> {
+    let (year, month) = if date.month() == 12 {
+        (date.year() + 1, 1)
+    } else {
+        (date.year(), date.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)
+}
+
+fn parse_field(field: &str, min: u32, max: u32, name: &str) -> Result<u64, String> {
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .map_err(|_| format!("invalid {name} step: {part}"))?;
+                if step == 0 {
+                    return Err(format!("invalid {name} step: {part}"));
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (
+                parse_value(start, min, max, name)?,
+                parse_value(end, min, max, name)?,
+            )
+        } else {
+            let value = parse_value(range, min, max, name)?;
+            // `a/n` 表示从 a 开始到最大值
+            (value, if step > 1 { max } else { value })
+        };
+        if start > end {
+            return Err(format!("invalid {name} range: {part}"));
+        }
+        let mut value = start;
+        while value <= end {
+            mask |= 1 << value;
+            value += step;
+        }
+    }
+    Ok(mask)
+}
+
+fn parse_value(value: &str, min: u32, max: u32, name: &str) -> Result<u32, String> {
+    let parsed: u32 = value
+        .parse()
+        .map_err(|_| format!("invalid {name} value: {value}"))?;
+    if parsed < min || parsed > max {
+        return Err(format!("{name} value {parsed} out of range {min}-{max}"));
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(tz: Tz, y: i32, mo: u32, d: u32, h: u32, mi: u32) -> i64 {
+        tz.with_ymd_and_hms(y, mo, d, h, mi, 0)
+            .earliest()
+            .expect("local time")
+            .timestamp_millis()
+    }
+
+    #[test]
+    fn parse_rejects_invalid_expressions() {
+        assert!(CronExpr::parse("* * * *").is_err());
+        assert!(CronExpr::parse("60 * * * *").is_err());
+        assert!(CronExpr::parse("*/0 * * * *").is_err());
+        assert!(CronExpr::parse("0 18-8 * * *").is_err());
+        assert!(CronExpr::parse("0 8 * * mon").is_err());
+        assert!(CronExpr::parse("0,30 8-18/2 * * 1-5").is_ok());
+    }
+
+    #[test]
+    fn next_after_uses_timezone_and_weekdays() {
+        let tz: Tz = "Asia/Shanghai".parse().expect("tz");
+        let cron = CronExpr::parse("30 7 * * 1-5").expect("cron");
+        // 2024-06-07 是周五，下一个工作日 07:30 为周一 2024-06-10
+        let friday_noon = ms(tz, 2024, 6, 7, 12, 0);
+        assert_eq!(
+            cron.next_after(friday_noon, tz),
+            Some(ms(tz, 2024, 6, 10, 7, 30))
+        );
+        // 恰好等于触发时刻时取下一次
+        let monday = ms(tz, 2024, 6, 10, 7, 30);
+        assert_eq!(
+            cron.next_after(monday, tz),
+            Some(ms(tz, 2024, 6, 11, 7, 30))
+        );
+
+        let sunday = CronExpr::parse("0 0 * * 7").expect("cron");
+        assert_eq!(
+            sunday.next_after(friday_noon, tz),
+            Some(ms(tz, 2024, 6, 9, 0, 0))
+        );
+    }
+
+    #[test]
+    fn next_after_handles_day_or_weekday_and_dst() {
+        // 每月 1 日或每周一
+        let cron = CronExpr::parse("0 0 1 * 1").expect("cron");
+        let utc = Tz::UTC;
+        assert_eq!(
+            cron.next_after(ms(utc, 2024, 6, 25, 0, 0), utc),
+            Some(ms(utc, 2024, 7, 1, 0, 0))
+        );
+        assert_eq!(
+            cron.next_after(ms(utc, 2024, 7, 1, 0, 0), utc),
+            Some(ms(utc, 2024, 7, 8, 0, 0))
+        );
+
+        // 2024-03-10 纽约 02:30 不存在，跳到次日
+        let tz: Tz = "America/New_York".parse().expect("tz");
+        let cron = CronExpr::parse("30 2 * * *").expect("cron");
+        assert_eq!(
+            cron.next_after(ms(tz, 2024, 3, 9, 12, 0), tz),
+            Some(ms(tz, 2024, 3, 11, 2, 30))
+        );
+
+        assert_eq!(
+            CronExpr::parse("0 0 30 2 *")
+                .expect("cron")
+                .next_after(0, utc),
+            None
+        );
+    }
+}
@@ -0,0 +1,295 @@
+//! 控制计划（周期性控制动作）
+//!
+//! 租户按项目定义计划，在项目时区下按 cron 表达式周期性下发命令：
+//! - `commands`：按一个 cron 表达式下发一组预定义命令
+//! - `setpoint_profile`：设定值曲线，在选定的星期按时刻向同一目标下发不同负载
+//!   （如工作日 07:30 空调设定 22℃、18:00 设定 26℃）
+//!
+//! 计划以 JSON 保存在 `ScheduleStore` 中，由本 crate 解析校验；
+//! `spawn_schedule_runner` 按固定间隔检查到期时刻，按错过执行策略处理停机期间的时刻，
+//! 每个计划时刻写一条执行记录。
+
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+mod cron;
+mod runner;
+pub use cron::CronExpr;
+pub use runner::*;
+
+/// 单个计划的最大命令数
+pub const MAX_COMMANDS: usize = 10;
+/// 设定值曲线的最大时刻数
+pub const MAX_PROFILE_STEPS: usize = 48;
+
+/// 计划定义错误。
+#[derive(Debug, thiserror::Error)]
+pub enum ScheduleError {
+    #[error("invalid spec: {0}")]
+    Spec(String),
+    #[error("invalid timezone: {0}")]
+    Timezone(String),
+    #[error("invalid missedRunPolicy: {0}")]
+    MissedRunPolicy(String),
+}
+
+/// 错过执行策略（执行器停机或落后时，早于宽限期的计划时刻如何处理）。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MissedRunPolicy {
+    /// 跳过错过的时刻（记录一条 `skipped` 执行记录）
+    #[default]
+    Skip,
+    /// 只补跑最近一次错过的时刻
+    RunOnce,
+    /// 依次补跑全部错过的时刻
+    RunAll,
+}
+
+impl MissedRunPolicy {
+    /// 策略名（与 JSON 一致）
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MissedRunPolicy::Skip => "skip",
+            MissedRunPolicy::RunOnce => "run_once",
+            MissedRunPolicy::RunAll => "run_all",
+        }
+    }
+
+    /// 解析策略名
+    pub fn parse(value: &str) -> Result<Self, ScheduleError> {
+        match value {
+            "skip" => Ok(MissedRunPolicy::Skip),
+            "run_once" => Ok(MissedRunPolicy::RunOnce),
+            "run_all" => Ok(MissedRunPolicy::RunAll),
+            other => Err(ScheduleError::MissedRunPolicy(format!(
+                "{other} (expected skip, run_once or run_all)"
+            ))),
+        }
+    }
+}
+
+/// 预定义命令。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduleCommand {
+    pub target: String,
+    pub payload: Value,
+}
+
+/// 设定值曲线中的一个时刻。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfileStep {
+    /// 本地时刻 `HH:MM`
+    pub at: String,
+    pub payload: Value,
+}
+
+/// 计划定义。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScheduleSpec {
+    /// 按 cron 表达式下发一组预定义命令
+    Commands {
+        cron: String,
+        commands: Vec<ScheduleCommand>,
+    },
+    /// 设定值曲线：`days` 为 cron 周字段语法（默认每天）
+    SetpointProfile {
+        target: String,
+        #[serde(default = "default_profile_days")]
+        days: String,
+        steps: Vec<ProfileStep>,
+    },
+}
+
+fn default_profile_days() -> String {
+    "*".to_string()
+}
+
+/// 计划中的一个触发项：cron 表达式 + 到期时下发的命令。
+#[derive(Debug, Clone)]
+pub struct ScheduleEntry {
+    pub cron: CronExpr,
+    pub commands: Vec<ScheduleCommand>,
+}
+
+impl ScheduleSpec {
+    /// 计划类型名（与 JSON `type` 一致）
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ScheduleSpec::Commands { .. } => "commands",
+            ScheduleSpec::SetpointProfile { .. } => "setpoint_profile",
+        }
+    }
+
+    /// 展开为触发项（设定值曲线的每个时刻对应一个触发项）
+    pub fn entries(&self) -> Result<Vec<ScheduleEntry>, ScheduleError> {
+        match self {
+            ScheduleSpec::Commands { cron, commands } => Ok(vec![ScheduleEntry {
+                cron: CronExpr::parse(cron).map_err(ScheduleError::Spec)?,
+                commands: commands.clone(),
+            }]),
+            ScheduleSpec::SetpointProfile {
+                target,
+                days,
+                steps,
+            } => steps
+                .iter()
+                .map(|step| {
+                    let (hour, minute) = parse_time_of_day(&step.at)?;
+                    let cron = CronExpr::parse(&format!("{minute} {hour} * * {days}"))
+                        .map_err(ScheduleError::Spec)?;
+                    Ok(ScheduleEntry {
+                        cron,
+                        commands: vec![ScheduleCommand {
+                            target: target.clone(),
+                            payload: step.payload.clone(),
+                        }],
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+/// 解析并校验计划定义。
+pub fn parse_spec(value: &Value) -> Result<ScheduleSpec, ScheduleError> {
+    let spec: ScheduleSpec = serde_json::from_value(value.clone())
+        .map_err(|err| ScheduleError::Spec(err.to_string()))?;
+    match &spec {
+        ScheduleSpec::Commands { commands, .. } => {
+            if commands.is_empty() || commands.len() > MAX_COMMANDS {
+                return Err(ScheduleError::Spec(format!(
+                    "commands must contain 1 to {MAX_COMMANDS} items"
+                )));
+            }
+            if let Some(index) = commands
+                .iter()
+                .position(|command| command.target.trim().is_empty())
+            {
+                return Err(ScheduleError::Spec(format!(
+                    "command {index}: target is required"
+                )));
+            }
+        }
+        ScheduleSpec::SetpointProfile { target, steps, .. } => {
+            if target.trim().is_empty() {
+                return Err(ScheduleError::Spec("target is required".to_string()));
+            }
+            if steps.is_empty() || steps.len() > MAX_PROFILE_STEPS {
+                return Err(ScheduleError::Spec(format!(
+                    "steps must contain 1 to {MAX_PROFILE_STEPS} items"
+                )));
+            }
+        }
+    }
+    let entries = spec.entries()?;
+    // 拒绝永远不会触发的表达式（如 2 月 30 日）
+    if entries
+        .iter()
+        .any(|entry| entry.cron.next_after(0, Tz::UTC).is_none())
+    {
+        return Err(ScheduleError::Spec("schedule never fires".to_string()));
+    }
+    Ok(spec)
+}
+
+/// 解析 IANA 时区名。
+pub fn parse_timezone(value: &str) -> Result<Tz, ScheduleError> {
+    value
+        .parse::<Tz>()
+        .map_err(|_| ScheduleError::Timezone(value.to_string()))
+}
+
+/// 计算严格晚于 `after_ms` 的下一个计划时刻（定义无效时返回 None）。
+pub fn next_run_at_ms(spec: &str, timezone: &str, after_ms: i64) -> Option<i64> {
+    let value: Value = serde_json::from_str(spec).ok()?;
+    let entries = parse_spec(&value).ok()?.entries().ok()?;
+    let timezone = parse_timezone(timezone).ok()?;
+    entries
+        .iter()
+        .filter_map(|entry| entry.cron.next_after(after_ms, timezone))
+        .min()
+}
+
+fn parse_time_of_day(value: &str) -> Result<(u32, u32), ScheduleError> {
+    let invalid = || ScheduleError::Spec(format!("invalid step time {value} (expected HH:MM)"));
+    let (hour, minute) = value.split_once(':').ok_or_else(invalid)?;
+    let hour: u32 = hour.parse().map_err(|_| invalid())?;
+    let minute: u32 = minute.parse().map_err(|_| invalid())?;
+    if hour > 23 || minute > 59 {
+        return Err(invalid());
+    }
+    Ok((hour, minute))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parse_spec_accepts_commands_and_profiles() {
+        let spec = parse_spec(&json!({
+            "type": "commands",
+            "cron": "0 2 * * *",
+            "commands": [{"target": "meter-1", "payload": {"reset": true}}]
+        }))
+        .expect("commands");
+        assert_eq!(spec.kind(), "commands");
+        assert_eq!(spec.entries().expect("entries").len(), 1);
+
+        let spec = parse_spec(&json!({
+            "type": "setpoint_profile",
+            "target": "ahu-1",
+            "days": "1-5",
+            "steps": [
+                {"at": "07:30", "payload": {"setpoint": 22}},
+                {"at": "18:00", "payload": {"setpoint": 26}}
+            ]
+        }))
+        .expect("profile");
+        let entries = spec.entries().expect("entries");
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].commands[0].target, "ahu-1");
+        assert_eq!(entries[1].commands[0].payload, json!({"setpoint": 26}));
+    }
+
+    #[test]
+    fn parse_spec_rejects_invalid_definitions() {
+        assert!(parse_spec(&json!({"type": "unknown"})).is_err());
+        assert!(parse_spec(&json!({"type": "commands", "cron": "bad", "commands": [{"target": "d", "payload": {}}]})).is_err());
+        assert!(
+            parse_spec(&json!({"type": "commands", "cron": "* * * * *", "commands": []})).is_err()
+        );
+        assert!(parse_spec(&json!({"type": "commands", "cron": "0 0 30 2 *", "commands": [{"target": "d", "payload": {}}]})).is_err());
+        assert!(parse_spec(&json!({"type": "setpoint_profile", "target": "d", "steps": [{"at": "25:00", "payload": 1}]})).is_err());
+        assert!(parse_spec(&json!({"type": "setpoint_profile", "target": "d", "days": "8", "steps": [{"at": "08:00", "payload": 1}]})).is_err());
+        assert!(parse_timezone("Mars/Base").is_err());
+        assert!(MissedRunPolicy::parse("later").is_err());
+        assert_eq!(
+            MissedRunPolicy::parse("run_once").expect("policy"),
+            MissedRunPolicy::RunOnce
+        );
+    }
+
+    #[test]
+    fn next_run_uses_earliest_profile_step() {
+        let spec = json!({
+            "type": "setpoint_profile",
+            "target": "ahu-1",
+            "steps": [
+                {"at": "18:00", "payload": 26},
+                {"at": "07:30", "payload": 22}
+            ]
+        })
+        .to_string();
+        // 1970-01-01 08:00 UTC 之后最近的是 18:00
+        assert_eq!(
+            next_run_at_ms(&spec, "UTC", 8 * 3_600_000),
+            Some(18 * 3_600_000)
+        );
+        assert_eq!(next_run_at_ms(&spec, "Mars/Base", 0), None);
+    }
+}
@@ -0,0 +1,526 @@
+//! 计划执行器。
+//!
+//! - 每次检查收集游标（`last_evaluated_at_ms`）之后、当前时间之前的全部计划时刻
+//! - 宽限期内的时刻正常执行；早于宽限期的视为错过，按计划的错过执行策略处理
+//! - 处理完成后推进游标；游标持久化在存储中，重启后可识别停机期间错过的时刻
+//!
+//! 多实例部署时每个实例都会独立执行，需只在一个实例上启用执行器。
+
+use crate::{MissedRunPolicy, ScheduleCommand, ScheduleEntry, parse_spec, parse_timezone};
+use chrono_tz::Tz;
+use domain::TenantContext;
+use ems_control::{CommandRequest, CommandService};
+use ems_storage::{ScheduleExecutionRecord, ScheduleRecord, ScheduleStore};
+use serde_json::{Value, json};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+use tracing::{info, warn};
+
+/// 单次检查最多处理的计划时刻数（超过时 `run_all` 分批补跑，其余策略只计数）
+pub const MAX_CATCH_UP_RUNS: usize = 100;
+
+/// 计划执行器配置
+#[derive(Debug, Clone)]
+pub struct ScheduleRunnerConfig {
+    /// 检查间隔（毫秒）
+    pub tick_ms: u64,
+    /// 宽限期（毫秒）：晚于计划时刻不超过该值仍视为按时执行
+    pub grace_ms: u64,
+}
+
+impl Default for ScheduleRunnerConfig {
+    fn default() -> Self {
+        Self {
+            tick_ms: 1000,
+            grace_ms: 60_000,
+        }
+    }
+}
+
+/// 到期的计划时刻
+struct DueRun {
+    scheduled_at_ms: i64,
+    commands: Vec<ScheduleCommand>,
+}
+
+/// 计划执行器（检查到期时刻并下发命令）
+pub struct ScheduleRunner {
+    schedule_store: Arc<dyn ScheduleStore>,
+    command_service: Arc<CommandService>,
+    grace_ms: i64,
+}
+
+impl ScheduleRunner {
+    pub fn new(
+        schedule_store: Arc<dyn ScheduleStore>,
+        command_service: Arc<CommandService>,
+        config: &ScheduleRunnerConfig,
+    ) -> Self {
+        Self {
+            schedule_store,
+            command_service,
+            grace_ms: config.grace_ms as i64,
+        }
+    }
+
+    /// 检查一次全部启用的计划，返回本次写入的执行记录
+    pub async fn run_due(&self, now_ms: i64) -> Vec<ScheduleExecutionRecord> {
+        let schedules = match self.schedule_store.list_enabled_schedules().await {
+            Ok(schedules) => schedules,
+            Err(err) => {
+                warn!(target: "ems.schedule", error = %err, "schedules_read_failed");
+                return Vec::new();
+            }
+        };
+        let mut executions = Vec::new();
+        for schedule in schedules {
+            let (entries, timezone, policy) = match stored_schedule(&schedule) {
+                Ok(parsed) => parsed,
+                Err(err) => {
+                    warn!(target: "ems.schedule", schedule_id = %schedule.schedule_id, error = %err, "schedule_invalid");
+                    continue;
+                }
+            };
+            let due = collect_due(&entries, timezone, schedule.last_evaluated_at_ms, now_ms);
+            if due.is_empty() {
+                continue;
+            }
+            let capped = due.len() >= MAX_CATCH_UP_RUNS;
+            let cursor_ms = match due.last() {
+                Some(last) if capped && policy == MissedRunPolicy::RunAll => last.scheduled_at_ms,
+                _ => now_ms,
+            };
+            let (missed, on_time): (Vec<DueRun>, Vec<DueRun>) = due
+                .into_iter()
+                .partition(|run| now_ms.saturating_sub(run.scheduled_at_ms) > self.grace_ms);
+
+            if !missed.is_empty() {
+                let missed_runs = missed.len();
+                match policy {
+                    MissedRunPolicy::Skip => {
+                        let last = &missed[missed_runs - 1];
+                        let results = json!([{ "status": "skipped", "missedRuns": missed_runs }]);
+                        executions.push(
+                            self.record(
+                                &schedule,
+                                last.scheduled_at_ms,
+                                "skipped",
+                                results,
+                                now_ms,
+                            )
+                            .await,
+                        );
+                    }
+                    MissedRunPolicy::RunOnce => {
+                        let last = &missed[missed_runs - 1];
+                        executions.push(self.execute(&schedule, last, now_ms).await);
+                    }
+                    MissedRunPolicy::RunAll => {
+                        for run in &missed {
+                            executions.push(self.execute(&schedule, run, now_ms).await);
+                        }
+                    }
+                }
+                warn!(
+                    target: "ems.schedule",
+                    schedule_id = %schedule.schedule_id,
+                    missed_runs = missed_runs,
+                    policy = policy.as_str(),
+                    "schedule_missed_runs"
+                );
+            }
+            for run in &on_time {
+                executions.push(self.execute(&schedule, run, now_ms).await);
+            }
+
+            let ctx = schedule_context(&schedule);
+            if let Err(err) = self
+                .schedule_store
+                .mark_schedule_evaluated(
+                    &ctx,
+                    &schedule.project_id,
+                    &schedule.schedule_id,
+                    cursor_ms,
+                )
+                .await
+            {
+                warn!(target: "ems.schedule", schedule_id = %schedule.schedule_id, error = %err, "schedule_cursor_update_failed");
+            }
+        }
+        executions
+    }
+
+    /// 依次下发计划时刻的命令并写入执行记录（单条命令失败不影响后续命令）
+    async fn execute(
+        &self,
+        schedule: &ScheduleRecord,
+        run: &DueRun,
+        now_ms: i64,
+    ) -> ScheduleExecutionRecord {
+        let ctx = schedule_context(schedule);
+        let mut results = Vec::new();
+        for command in &run.commands {
+            let result = match self
+                .command_service
+                .issue_command(
+                    &ctx,
+                    CommandRequest {
+                        project_id: schedule.project_id.clone(),
+                        target: command.target.clone(),
+                        payload: command.payload.clone(),
+                        issued_at_ms: now_ms,
+                    },
+                )
+                .await
+            {
+                Ok(issued) => json!({
+                    "target": command.target,
+                    "status": "success",
+                    "commandId": issued.command_id,
+                }),
+                Err(err) => json!({
+                    "target": command.target,
+                    "status": "failed",
+                    "error": err.to_string(),
+                }),
+            };
+            results.push(result);
+        }
+        let failed = results.iter().any(|result| result["status"] != "success");
+        let status = if failed { "failed" } else { "success" };
+        self.record(
+            schedule,
+            run.scheduled_at_ms,
+            status,
+            Value::Array(results),
+            now_ms,
+        )
+        .await
+    }
+
+    async fn record(
+        &self,
+        schedule: &ScheduleRecord,
+        scheduled_at_ms: i64,
+        status: &str,
+        results: Value,
+        now_ms: i64,
+    ) -> ScheduleExecutionRecord {
+        let record = ScheduleExecutionRecord {
+            execution_id: uuid::Uuid::new_v4().to_string(),
+            tenant_id: schedule.tenant_id.clone(),
+            project_id: schedule.project_id.clone(),
+            schedule_id: schedule.schedule_id.clone(),
+            scheduled_at_ms,
+            status: status.to_string(),
+            results: results.to_string(),
+            executed_at_ms: now_ms,
+        };
+        if status == "failed" {
+            warn!(
+                target: "ems.schedule",
+                tenant_id = %record.tenant_id,
+                schedule_id = %record.schedule_id,
+                results = %record.results,
+                "schedule_execution_failed"
+            );
+        } else {
+            info!(
+                target: "ems.schedule",
+                tenant_id = %record.tenant_id,
+                schedule_id = %record.schedule_id,
+                status = %record.status,
+                "schedule_executed"
+            );
+        }
+        let ctx = schedule_context(schedule);
+        if let Err(err) = self
+            .schedule_store
+            .create_schedule_execution(&ctx, record.clone())
+            .await
+        {
+            warn!(target: "ems.schedule", schedule_id = %record.schedule_id, error = %err, "schedule_execution_log_failed");
+        }
+        record
+    }
+}
+
+/// 启动计划执行器后台任务
+pub fn spawn_schedule_runner(
+    runner: Arc<ScheduleRunner>,
+    config: &ScheduleRunnerConfig,
+) -> tokio::task::JoinHandle<()> {
+    let tick_ms = config.tick_ms.max(1);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_millis(tick_ms));
+        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        loop {
+            interval.tick().await;
+            runner.run_due(now_epoch_ms()).await;
+        }
+    })
+}
+
+/// 收集 (after_ms, now_ms] 内的计划时刻（同一时刻的多个触发项合并），最多 `MAX_CATCH_UP_RUNS` 个
+fn collect_due(entries: &[ScheduleEntry], timezone: Tz, after_ms: i64, now_ms: i64) -> Vec<DueRun> {
+    let mut due = Vec::new();
+    let mut cursor_ms = after_ms;
+    while due.len() < MAX_CATCH_UP_RUNS {
+        let next: Vec<(i64, &ScheduleEntry)> = entries
+            .iter()
+            .filter_map(|entry| {
+                entry
+                    .cron
+                    .next_after(cursor_ms, timezone)
+                    .map(|at| (at, entry))
+            })
+            .collect();
+        let Some(at_ms) = next.iter().map(|(at, _)| *at).min() else {
+            break;
+        };
+        if at_ms > now_ms {
+            break;
+        }
+        let commands = next
+            .iter()
+            .filter(|(at, _)| *at == at_ms)
+            .flat_map(|(_, entry)| entry.commands.iter().cloned())
+            .collect();
+        due.push(DueRun {
+            scheduled_at_ms: at_ms,
+            commands,
+        });
+        cursor_ms = at_ms;
+    }
+    due
+}
+
+fn stored_schedule(
+    schedule: &ScheduleRecord,
+) -> Result<(Vec<ScheduleEntry>, Tz, MissedRunPolicy), String> {
+    let value: Value = serde_json::from_str(&schedule.spec).map_err(|err| err.to_string())?;
+    let entries = parse_spec(&value)
+        .and_then(|spec| spec.entries())
+        .map_err(|err| err.to_string())?;
+    let timezone = parse_timezone(&schedule.timezone).map_err(|err| err.to_string())?;
+    let policy =
+        MissedRunPolicy::parse(&schedule.missed_run_policy).map_err(|err| err.to_string())?;
+    Ok((entries, timezone, policy))
+}
+
+/// 计划执行上下文（命令与审计记录中的操作人为 `schedule:{schedule_id}`）
+fn schedule_context(schedule: &ScheduleRecord) -> TenantContext {
+    TenantContext::new(
+        schedule.tenant_id.clone(),
+        format!("schedule:{}", schedule.schedule_id),
+        Vec::new(),
+        Vec::new(),
+        Some(schedule.project_id.clone()),
+    )
+}
+
+fn now_epoch_ms() -> i64 {
+    let now = std::time::SystemTime::now();
+    let duration = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    duration.as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ems_control::NoopDispatcher;
+    use ems_storage::{
+        CommandQueryOptions, CommandStore, InMemoryAuditLogStore, InMemoryCommandStore,
+        InMemoryScheduleStore,
+    };
+
+    const MINUTE_MS: i64 = 60_000;
+    const HOUR_MS: i64 = 3_600_000;
+
+    struct Harness {
+        runner: ScheduleRunner,
+        schedule_store: Arc<InMemoryScheduleStore>,
+        command_store: Arc<InMemoryCommandStore>,
+    }
+
+    fn harness() -> Harness {
+        let schedule_store = Arc::new(InMemoryScheduleStore::new());
+        let command_store = Arc::new(InMemoryCommandStore::new());
+        let command_service = Arc::new(CommandService::new(
+            command_store.clone(),
+            Arc::new(InMemoryAuditLogStore::new()),
+            Arc::new(NoopDispatcher),
+        ));
+        let runner = ScheduleRunner::new(
+            schedule_store.clone(),
+            command_service,
+            &ScheduleRunnerConfig::default(),
+        );
+        Harness {
+            runner,
+            schedule_store,
+            command_store,
+        }
+    }
+
+    fn ctx() -> TenantContext {
+        TenantContext::new(
+            "tenant-1".to_string(),
+            "user-1".to_string(),
+            Vec::new(),
+            Vec::new(),
+            Some("project-1".to_string()),
+        )
+    }
+
+    async fn add_schedule(
+        store: &InMemoryScheduleStore,
+        schedule_id: &str,
+        timezone: &str,
+        spec: Value,
+        policy: &str,
+        evaluated_at_ms: i64,
+    ) {
+        store
+            .create_schedule(
+                &ctx(),
+                ScheduleRecord {
+                    tenant_id: "tenant-1".to_string(),
+                    project_id: "project-1".to_string(),
+                    schedule_id: schedule_id.to_string(),
+                    name: schedule_id.to_string(),
+                    enabled: true,
+                    timezone: timezone.to_string(),
+                    spec: spec.to_string(),
+                    missed_run_policy: policy.to_string(),
+                    last_evaluated_at_ms: evaluated_at_ms,
+                    created_by: "user-1".to_string(),
+                    created_at_ms: evaluated_at_ms,
+                    updated_at_ms: evaluated_at_ms,
+                },
+            )
+            .await
+            .expect("schedule");
+    }
+
+    fn hourly() -> Value {
+        json!({
+            "type": "commands",
+            "cron": "0 * * * *",
+            "commands": [{"target": "device-1", "payload": {"on": true}}]
+        })
+    }
+
+    #[tokio::test]
+    async fn profile_fires_at_local_times_and_advances_cursor() {
+        let h = harness();
+        // Asia/Shanghai = UTC+8：本地 08:00 为 UTC 00:00
+        add_schedule(
+            &h.schedule_store,
+            "hvac",
+            "Asia/Shanghai",
+            json!({
+                "type": "setpoint_profile",
+                "target": "ahu-1",
+                "steps": [
+                    {"at": "08:00", "payload": {"setpoint": 22}},
+                    {"at": "18:00", "payload": {"setpoint": 26}}
+                ]
+            }),
+            "skip",
+            12 * HOUR_MS,
+        )
+        .await;
+
+        // 本地 04:00 无到期时刻
+        assert!(h.runner.run_due(20 * HOUR_MS).await.is_empty());
+
+        // 1970-01-02 00:00:05 UTC = 本地 08:00:05
+        let now_ms = 24 * HOUR_MS + 5_000;
+        let executions = h.runner.run_due(now_ms).await;
+        assert_eq!(executions.len(), 1);
+        assert_eq!(executions[0].status, "success");
+        assert_eq!(executions[0].scheduled_at_ms, 24 * HOUR_MS);
+
+        // 游标已推进，不重复执行
+        assert!(h.runner.run_due(now_ms + 1_000).await.is_empty());
+
+        let commands = h
+            .command_store
+            .list_commands(&ctx(), "project-1", CommandQueryOptions::simple(10))
+            .await
+            .expect("commands");
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].target, "ahu-1");
+        assert_eq!(commands[0].issued_by, "schedule:hvac");
+        assert!(commands[0].payload.contains("22"));
+    }
+
+    #[tokio::test]
+    async fn missed_run_policies() {
+        let h = harness();
+        add_schedule(&h.schedule_store, "skip", "UTC", hourly(), "skip", 0).await;
+        add_schedule(&h.schedule_store, "once", "UTC", hourly(), "run_once", 0).await;
+        add_schedule(&h.schedule_store, "all", "UTC", hourly(), "run_all", 0).await;
+
+        // 停机 3 小时后恢复：01:00、02:00 已超过宽限期，03:00 在宽限期内
+        let now_ms = 3 * HOUR_MS + 30_000;
+        let executions = h.runner.run_due(now_ms).await;
+        let statuses = |schedule_id: &str| -> Vec<(i64, String)> {
+            executions
+                .iter()
+                .filter(|record| record.schedule_id == schedule_id)
+                .map(|record| (record.scheduled_at_ms, record.status.clone()))
+                .collect()
+        };
+        assert_eq!(
+            statuses("skip"),
+            vec![
+                (2 * HOUR_MS, "skipped".to_string()),
+                (3 * HOUR_MS, "success".to_string())
+            ]
+        );
+        assert_eq!(
+            statuses("once"),
+            vec![
+                (2 * HOUR_MS, "success".to_string()),
+                (3 * HOUR_MS, "success".to_string())
+            ]
+        );
+        assert_eq!(statuses("all").len(), 3);
+
+        let skipped = h
+            .schedule_store
+            .list_schedule_executions(&ctx(), "project-1", "skip", 10)
+            .await
+            .expect("history");
+        assert_eq!(skipped.len(), 2);
+        assert!(skipped[1].results.contains("\"missedRuns\":2"));
+
+        let commands = h
+            .command_store
+            .list_commands(&ctx(), "project-1", CommandQueryOptions::simple(20))
+            .await
+            .expect("commands");
+        assert_eq!(commands.len(), 1 + 2 + 3);
+    }
+
+    #[tokio::test]
+    async fn run_all_catches_up_in_batches() {
+        let h = harness();
+        let every_minute = json!({
+            "type": "commands",
+            "cron": "* * * * *",
+            "commands": [{"target": "device-1", "payload": {}}]
+        });
+        add_schedule(&h.schedule_store, "all", "UTC", every_minute, "run_all", 0).await;
+
+        let now_ms = 150 * MINUTE_MS;
+        assert_eq!(h.runner.run_due(now_ms).await.len(), MAX_CATCH_UP_RUNS);
+        assert_eq!(h.runner.run_due(now_ms).await.len(), 50);
+        assert!(h.runner.run_due(now_ms).await.is_empty());
+    }
+}
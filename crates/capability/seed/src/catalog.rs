@@ -0,0 +1,256 @@
+//! 演示资产清单：网关、设备、点位及其数据曲线。
+//!
+//! 所有 ID 固定，重复执行时可据此判断是否已写入；点位 key / 单位参照常见配电与暖通设备。
+
+use crate::profile::Profile;
+
+/// 演示网关
+pub(crate) struct DemoGateway {
+    pub gateway_id: &'static str,
+    pub name: &'static str,
+    pub protocol_type: &'static str,
+    pub protocol_config: Option<&'static str>,
+}
+
+/// 演示设备（点位挂在设备下）
+pub(crate) struct DemoDevice {
+    pub device_id: &'static str,
+    pub gateway_id: &'static str,
+    pub name: &'static str,
+    pub model: &'static str,
+    pub points: &'static [DemoPoint],
+}
+
+/// 演示点位（映射地址为 `设备短名/key`）
+pub(crate) struct DemoPoint {
+    pub point_id: &'static str,
+    pub key: &'static str,
+    pub unit: Option<&'static str>,
+    pub tags: &'static [&'static str],
+    pub profile: Profile,
+}
+
+/// 演示命令（目标为设备 ID）
+pub(crate) struct DemoCommand {
+    pub command_id: &'static str,
+    pub target: &'static str,
+    pub payload: &'static str,
+    pub status: &'static str,
+    /// 相对生成时刻的下发时间（分钟前）
+    pub minutes_ago: i64,
+}
+
+pub(crate) const GATEWAYS: &[DemoGateway] = &[
+    DemoGateway {
+        gateway_id: "gateway-demo-mqtt",
+        name: "Switchroom MQTT Gateway",
+        protocol_type: "mqtt",
+        protocol_config: None,
+    },
+    DemoGateway {
+        gateway_id: "gateway-demo-modbus",
+        name: "Plant Room Modbus Gateway",
+        protocol_type: "modbus_tcp",
+        protocol_config: Some(r#"{"host":"192.168.10.20","port":502,"poll_interval_ms":5000}"#),
+    },
+];
+
+/// 总进线负荷（kW），电表有功功率与累计电量共用
+const MAIN_LOAD: Profile = Profile::Daily {
+    base: 180.0,
+    amplitude: 140.0,
+    peak_hour: 14.0,
+};
+
+/// 光伏出力（kW），逆变器功率与当日发电量共用
+const PV_OUTPUT: Profile = Profile::Solar { peak: 95.0 };
+
+/// 冷机功率（kW）
+const CHILLER_LOAD: Profile = Profile::Daily {
+    base: 35.0,
+    amplitude: 85.0,
+    peak_hour: 15.0,
+};
+
+pub(crate) const DEVICES: &[DemoDevice] = &[
+    DemoDevice {
+        device_id: "device-demo-meter",
+        gateway_id: "gateway-demo-mqtt",
+        name: "Main Incomer Meter",
+        model: "PM5560",
+        points: &[
+            DemoPoint {
+                point_id: "point-demo-meter-active-power",
+                key: "active_power",
+                unit: Some("kW"),
+                tags: &["energy", "power"],
+                profile: MAIN_LOAD,
+            },
+            DemoPoint {
+                point_id: "point-demo-meter-reactive-power",
+                key: "reactive_power",
+                unit: Some("kvar"),
+                tags: &["energy", "power"],
+                profile: Profile::Daily {
+                    base: 45.0,
+                    amplitude: 30.0,
+                    peak_hour: 14.0,
+                },
+            },
+            DemoPoint {
+                point_id: "point-demo-meter-voltage-a",
+                key: "voltage_a",
+                unit: Some("V"),
+                tags: &["energy"],
+                profile: Profile::Steady {
+                    value: 230.0,
+                    jitter: 2.5,
+                },
+            },
+            DemoPoint {
+                point_id: "point-demo-meter-current-a",
+                key: "current_a",
+                unit: Some("A"),
+                tags: &["energy"],
+                profile: Profile::Daily {
+                    base: 260.0,
+                    amplitude: 200.0,
+                    peak_hour: 14.0,
+                },
+            },
+            DemoPoint {
+                point_id: "point-demo-meter-power-factor",
+                key: "power_factor",
+                unit: None,
+                tags: &["energy"],
+                profile: Profile::Steady {
+                    value: 0.95,
+                    jitter: 0.02,
+                },
+            },
+            DemoPoint {
+                point_id: "point-demo-meter-energy-import",
+                key: "energy_import",
+                unit: Some("kWh"),
+                tags: &["energy", "counter"],
+                profile: Profile::Counter {
+                    start: 1_250_000.0,
+                    rate: &MAIN_LOAD,
+                    daily_reset: false,
+                },
+            },
+        ],
+    },
+    DemoDevice {
+        device_id: "device-demo-pv",
+        gateway_id: "gateway-demo-mqtt",
+        name: "Rooftop PV Inverter",
+        model: "SUN2000-100KTL",
+        points: &[
+            DemoPoint {
+                point_id: "point-demo-pv-active-power",
+                key: "pv_power",
+                unit: Some("kW"),
+                tags: &["energy", "pv"],
+                profile: PV_OUTPUT,
+            },
+            DemoPoint {
+                point_id: "point-demo-pv-daily-yield",
+                key: "daily_yield",
+                unit: Some("kWh"),
+                tags: &["energy", "pv", "counter"],
+                profile: Profile::Counter {
+                    start: 0.0,
+                    rate: &PV_OUTPUT,
+                    daily_reset: true,
+                },
+            },
+            DemoPoint {
+                point_id: "point-demo-pv-temperature",
+                key: "inverter_temp",
+                unit: Some("°C"),
+                tags: &["pv"],
+                profile: Profile::Daily {
+                    base: 28.0,
+                    amplitude: 22.0,
+                    peak_hour: 13.0,
+                },
+            },
+        ],
+    },
+    DemoDevice {
+        device_id: "device-demo-chiller",
+        gateway_id: "gateway-demo-modbus",
+        name: "Chiller 1",
+        model: "30XA-1002",
+        points: &[
+            DemoPoint {
+                point_id: "point-demo-chiller-power",
+                key: "chiller_power",
+                unit: Some("kW"),
+                tags: &["hvac", "power"],
+                profile: CHILLER_LOAD,
+            },
+            DemoPoint {
+                point_id: "point-demo-chiller-supply-temp",
+                key: "supply_temp",
+                unit: Some("°C"),
+                tags: &["hvac"],
+                profile: Profile::Steady {
+                    value: 7.0,
+                    jitter: 0.4,
+                },
+            },
+            DemoPoint {
+                point_id: "point-demo-chiller-return-temp",
+                key: "return_temp",
+                unit: Some("°C"),
+                tags: &["hvac"],
+                profile: Profile::Daily {
+                    base: 10.5,
+                    amplitude: 2.0,
+                    peak_hour: 15.0,
+                },
+            },
+            DemoPoint {
+                point_id: "point-demo-chiller-cop",
+                key: "cop",
+                unit: None,
+                tags: &["hvac"],
+                profile: Profile::Steady {
+                    value: 5.2,
+                    jitter: 0.3,
+                },
+            },
+        ],
+    },
+];
+
+pub(crate) const COMMANDS: &[DemoCommand] = &[
+    DemoCommand {
+        command_id: "command-demo-1",
+        target: "device-demo-chiller",
+        payload: r#"{"action":"set_supply_temp","value":7.5}"#,
+        status: "success",
+        minutes_ago: 180,
+    },
+    DemoCommand {
+        command_id: "command-demo-2",
+        target: "device-demo-pv",
+        payload: r#"{"action":"limit_power","value":80}"#,
+        status: "failed",
+        minutes_ago: 90,
+    },
+    DemoCommand {
+        command_id: "command-demo-3",
+        target: "device-demo-chiller",
+        payload: r#"{"action":"start"}"#,
+        status: "issued",
+        minutes_ago: 5,
+    },
+];
+
+/// 设备短名（映射地址前缀），如 `device-demo-meter` → `meter`
+pub(crate) fn device_short_name(device_id: &str) -> &str {
+    device_id.trim_start_matches("device-demo-")
+}
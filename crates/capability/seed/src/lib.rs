@@ -0,0 +1,325 @@
+//! # 演示数据生成（ems-seed）
+//!
+//! 为上手体验与前端联调写入一套完整的演示数据：
+//! - 演示租户 `tenant-demo` 及 admin 角色、管理员账号 `demo`（口令 `demo123`）
+//! - 演示项目 `project-demo`（Asia/Shanghai）
+//! - 2 个网关（MQTT / Modbus TCP）、3 台设备（总进线电表、光伏逆变器、冷机）及其点位与 MQTT 映射
+//! - 点位历史数据（默认最近 24 小时、15 分钟间隔；日负荷 / 光伏 / 累计电量曲线）
+//! - 示例控制命令（成功 / 失败 / 已下发）
+//!
+//! 入口：`ems-admin seed-demo`，或 ems-api 启动时设置 `EMS_SEED_DEMO=on`。
+//! 以演示项目是否存在判断幂等：已存在时不做任何写入。
+//! 仅通过存储接口写入，内存存储与 Postgres 存储均可使用。
+
+mod catalog;
+mod profile;
+
+use std::sync::Arc;
+
+use domain::{TenantContext, permissions};
+use ems_storage::{
+    CommandRecord, CommandStore, DeviceRecord, DeviceStore, GatewayRecord, GatewayStore,
+    MeasurementStore, PointMappingRecord, PointMappingStore, PointRecord, PointStore,
+    ProjectRecord, ProjectStore, RbacRoleCreate, RbacStore, RbacUserCreate, StorageError,
+    TenantStore,
+};
+
+use crate::catalog::{COMMANDS, DEVICES, GATEWAYS, device_short_name};
+use crate::profile::Window;
+
+/// 演示租户 ID
+pub const DEMO_TENANT_ID: &str = "tenant-demo";
+/// 演示项目 ID
+pub const DEMO_PROJECT_ID: &str = "project-demo";
+/// 演示管理员用户名
+pub const DEMO_USERNAME: &str = "demo";
+/// 演示管理员口令（仅用于演示环境）
+pub const DEMO_PASSWORD: &str = "demo123";
+
+const DEMO_USER_ID: &str = "user-demo";
+const DEMO_TENANT_NAME: &str = "Demo Tenant";
+const DEMO_PROJECT_NAME: &str = "Demo Campus";
+const DEMO_TIMEZONE: &str = "Asia/Shanghai";
+/// Asia/Shanghai 无夏令时，固定 UTC+8
+const DEMO_UTC_OFFSET_HOURS: i64 = 8;
+const ADMIN_ROLE_NAME: &str = "Administrator";
+
+/// 演示数据生成错误
+#[derive(Debug, thiserror::Error)]
+pub enum SeedError {
+    #[error("storage error: {0}")]
+    Storage(#[from] StorageError),
+    #[error("password hash failed: {0}")]
+    Password(String),
+}
+
+/// 生成演示数据所需的存储
+#[derive(Clone)]
+pub struct SeedStores {
+    pub tenant_store: Arc<dyn TenantStore>,
+    pub rbac_store: Arc<dyn RbacStore>,
+    pub project_store: Arc<dyn ProjectStore>,
+    pub gateway_store: Arc<dyn GatewayStore>,
+    pub device_store: Arc<dyn DeviceStore>,
+    pub point_store: Arc<dyn PointStore>,
+    pub point_mapping_store: Arc<dyn PointMappingStore>,
+    pub measurement_store: Arc<dyn MeasurementStore>,
+    pub command_store: Arc<dyn CommandStore>,
+}
+
+/// 演示数据生成选项
+#[derive(Debug, Clone)]
+pub struct SeedOptions {
+    /// 历史数据时长（小时）
+    pub history_hours: u32,
+    /// 历史数据采样间隔（分钟）
+    pub interval_minutes: u32,
+    /// 生成时刻（毫秒时间戳），历史数据截止于此
+    pub now_ms: i64,
+}
+
+impl Default for SeedOptions {
+    fn default() -> Self {
+        Self {
+            history_hours: 24,
+            interval_minutes: 15,
+            now_ms: now_epoch_ms(),
+        }
+    }
+}
+
+/// 生成结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SeedSummary {
+    /// false 表示演示项目已存在，本次未写入
+    pub created: bool,
+    pub gateways: usize,
+    pub devices: usize,
+    pub points: usize,
+    pub measurements: usize,
+    pub commands: usize,
+}
+
+/// 演示租户的管理员上下文（拥有全部权限码）
+pub fn demo_ctx() -> TenantContext {
+    TenantContext::new(
+        DEMO_TENANT_ID,
+        "ems-seed",
+        vec![permissions::ROLE_ADMIN.to_string()],
+        permissions::PERMISSION_CODES
+            .iter()
+            .map(|code| code.to_string())
+            .collect(),
+        Some(DEMO_PROJECT_ID.to_string()),
+    )
+}
+
+/// 写入演示数据（演示项目已存在时跳过）
+pub async fn seed_demo(
+    stores: &SeedStores,
+    options: &SeedOptions,
+) -> Result<SeedSummary, SeedError> {
+    let ctx = demo_ctx();
+    if stores
+        .project_store
+        .find_project(&ctx, DEMO_PROJECT_ID)
+        .await?
+        .is_some()
+    {
+        return Ok(SeedSummary::default());
+    }
+
+    stores
+        .tenant_store
+        .create_tenant(&ctx, DEMO_TENANT_NAME)
+        .await?;
+    seed_admin(stores, &ctx).await?;
+    stores
+        .project_store
+        .create_project(
+            &ctx,
+            ProjectRecord {
+                project_id: DEMO_PROJECT_ID.to_string(),
+                tenant_id: DEMO_TENANT_ID.to_string(),
+                name: DEMO_PROJECT_NAME.to_string(),
+                timezone: DEMO_TIMEZONE.to_string(),
+            },
+        )
+        .await?;
+
+    let mut summary = SeedSummary {
+        created: true,
+        ..SeedSummary::default()
+    };
+    for gateway in GATEWAYS {
+        stores
+            .gateway_store
+            .create_gateway(
+                &ctx,
+                GatewayRecord {
+                    gateway_id: gateway.gateway_id.to_string(),
+                    tenant_id: DEMO_TENANT_ID.to_string(),
+                    project_id: DEMO_PROJECT_ID.to_string(),
+                    name: gateway.name.to_string(),
+                    status: "online".to_string(),
+                    protocol_type: gateway.protocol_type.to_string(),
+                    protocol_config: gateway.protocol_config.map(str::to_string),
+                },
+            )
+            .await?;
+        summary.gateways += 1;
+    }
+
+    let interval_ms = i64::from(options.interval_minutes.max(1)) * 60_000;
+    let count = (i64::from(options.history_hours) * 3_600_000 / interval_ms) as usize + 1;
+    let window = Window {
+        start_ms: options.now_ms
+            - options.now_ms.rem_euclid(interval_ms)
+            - interval_ms * (count as i64 - 1),
+        interval_ms,
+        count,
+        utc_offset_hours: DEMO_UTC_OFFSET_HOURS,
+    };
+    for device in DEVICES {
+        stores
+            .device_store
+            .create_device(
+                &ctx,
+                DeviceRecord {
+                    device_id: device.device_id.to_string(),
+                    tenant_id: DEMO_TENANT_ID.to_string(),
+                    project_id: DEMO_PROJECT_ID.to_string(),
+                    gateway_id: device.gateway_id.to_string(),
+                    name: device.name.to_string(),
+                    model: Some(device.model.to_string()),
+                    room_id: None,
+                    address_config: None,
+                    offline_after_seconds: None,
+                },
+            )
+            .await?;
+        summary.devices += 1;
+
+        for point in device.points {
+            stores
+                .point_store
+                .create_point(
+                    &ctx,
+                    PointRecord {
+                        point_id: point.point_id.to_string(),
+                        tenant_id: DEMO_TENANT_ID.to_string(),
+                        project_id: DEMO_PROJECT_ID.to_string(),
+                        device_id: device.device_id.to_string(),
+                        key: point.key.to_string(),
+                        data_type: "f64".to_string(),
+                        unit: point.unit.map(str::to_string),
+                        tags: point.tags.iter().map(|tag| tag.to_string()).collect(),
+                    },
+                )
+                .await?;
+            stores
+                .point_mapping_store
+                .create_point_mapping(
+                    &ctx,
+                    PointMappingRecord {
+                        source_id: format!("source-{}", point.point_id),
+                        tenant_id: DEMO_TENANT_ID.to_string(),
+                        project_id: DEMO_PROJECT_ID.to_string(),
+                        point_id: point.point_id.to_string(),
+                        source_type: "mqtt".to_string(),
+                        address: format!("{}/{}", device_short_name(device.device_id), point.key),
+                        scale: None,
+                        offset: None,
+                        protocol_detail: None,
+                    },
+                )
+                .await?;
+            summary.points += 1;
+
+            let values = profile::generate(
+                DEMO_TENANT_ID,
+                DEMO_PROJECT_ID,
+                point.point_id,
+                &point.profile,
+                &window,
+            );
+            summary.measurements += stores
+                .measurement_store
+                .write_measurements(&ctx, &values)
+                .await?;
+        }
+    }
+
+    for command in COMMANDS {
+        stores
+            .command_store
+            .create_command(
+                &ctx,
+                CommandRecord {
+                    command_id: command.command_id.to_string(),
+                    tenant_id: DEMO_TENANT_ID.to_string(),
+                    project_id: DEMO_PROJECT_ID.to_string(),
+                    target: command.target.to_string(),
+                    payload: command.payload.to_string(),
+                    status: command.status.to_string(),
+                    issued_by: DEMO_USER_ID.to_string(),
+                    issued_at_ms: options.now_ms - command.minutes_ago * 60_000,
+                },
+            )
+            .await?;
+        summary.commands += 1;
+    }
+    Ok(summary)
+}
+
+/// 创建 admin 角色与演示管理员（已存在时跳过）
+async fn seed_admin(stores: &SeedStores, ctx: &TenantContext) -> Result<(), SeedError> {
+    let roles = stores.rbac_store.list_roles(ctx).await?;
+    if !roles
+        .iter()
+        .any(|role| role.role_code == permissions::ROLE_ADMIN)
+    {
+        stores
+            .rbac_store
+            .create_role(
+                ctx,
+                RbacRoleCreate {
+                    tenant_id: DEMO_TENANT_ID.to_string(),
+                    role_code: permissions::ROLE_ADMIN.to_string(),
+                    name: ADMIN_ROLE_NAME.to_string(),
+                    permissions: ctx.permissions.clone(),
+                },
+            )
+            .await?;
+    }
+
+    let users = stores.rbac_store.list_users(ctx).await?;
+    if users.iter().any(|user| user.username == DEMO_USERNAME) {
+        return Ok(());
+    }
+    let password = ems_auth::hash_password(DEMO_PASSWORD)
+        .map_err(|err| SeedError::Password(err.to_string()))?;
+    stores
+        .rbac_store
+        .create_user(
+            ctx,
+            RbacUserCreate {
+                user_id: DEMO_USER_ID.to_string(),
+                tenant_id: DEMO_TENANT_ID.to_string(),
+                username: DEMO_USERNAME.to_string(),
+                password,
+                status: "active".to_string(),
+                roles: vec![permissions::ROLE_ADMIN.to_string()],
+            },
+        )
+        .await?;
+    Ok(())
+}
+
+fn now_epoch_ms() -> i64 {
+    let now = std::time::SystemTime::now();
+    let duration = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    duration.as_millis() as i64
+}
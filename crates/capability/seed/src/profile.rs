@@ -0,0 +1,125 @@
+//! 演示数据曲线：按项目本地时刻生成确定性的日变化数值（同一时刻重复生成结果一致）。
+
+use domain::{PointValue, PointValueData};
+
+/// 一小时的毫秒数
+const HOUR_MS: i64 = 3_600_000;
+
+/// 点位数据曲线
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Profile {
+    /// 日负荷：`base + amplitude × 钟形(本地时刻, peak_hour)`，夜间接近 base
+    Daily {
+        base: f64,
+        amplitude: f64,
+        peak_hour: f64,
+    },
+    /// 光伏出力：6–18 点正弦，峰值 `peak`，其余时刻为 0
+    Solar { peak: f64 },
+    /// 平稳值：`value ± jitter`
+    Steady { value: f64, jitter: f64 },
+    /// 累计量：对 `rate`（kW）积分得到 kWh，`daily_reset` 为 true 时每日零点清零
+    Counter {
+        start: f64,
+        rate: &'static Profile,
+        daily_reset: bool,
+    },
+}
+
+impl Profile {
+    /// 本地时刻 `hour`（0–24）的瞬时值；`seed` 用于确定性扰动
+    fn sample(&self, hour: f64, seed: u64) -> f64 {
+        match *self {
+            Profile::Daily {
+                base,
+                amplitude,
+                peak_hour,
+            } => {
+                let distance = (hour - peak_hour)
+                    .abs()
+                    .min(24.0 - (hour - peak_hour).abs());
+                let bell = (-(distance * distance) / 18.0).exp();
+                (base + amplitude * bell) * (1.0 + 0.03 * noise(seed))
+            }
+            Profile::Solar { peak } => {
+                if !(6.0..18.0).contains(&hour) {
+                    return 0.0;
+                }
+                let shape = (std::f64::consts::PI * (hour - 6.0) / 12.0).sin();
+                (peak * shape * (1.0 + 0.05 * noise(seed))).max(0.0)
+            }
+            Profile::Steady { value, jitter } => value + jitter * noise(seed),
+            Profile::Counter { rate, .. } => rate.sample(hour, seed),
+        }
+    }
+}
+
+/// 采样窗口：从 `start_ms` 起、间隔 `interval_ms` 的 `count` 个时刻
+pub(crate) struct Window {
+    pub start_ms: i64,
+    pub interval_ms: i64,
+    pub count: usize,
+    /// 项目时区相对 UTC 的小时偏移（曲线按本地时刻计算）
+    pub utc_offset_hours: i64,
+}
+
+/// 按窗口生成一个点位的历史样本
+pub(crate) fn generate(
+    tenant_id: &str,
+    project_id: &str,
+    point_id: &str,
+    profile: &Profile,
+    window: &Window,
+) -> Vec<PointValue> {
+    let Window {
+        start_ms,
+        interval_ms,
+        count,
+        utc_offset_hours,
+    } = *window;
+    let point_seed = point_id.bytes().fold(0u64, |acc, byte| {
+        acc.wrapping_mul(31).wrapping_add(u64::from(byte))
+    });
+    let mut total = match profile {
+        Profile::Counter { start, .. } => *start,
+        _ => 0.0,
+    };
+    let mut previous_day = None;
+    (0..count)
+        .map(|index| {
+            let ts_ms = start_ms + interval_ms * index as i64;
+            let local_ms = ts_ms + utc_offset_hours * HOUR_MS;
+            let day = local_ms.div_euclid(24 * HOUR_MS);
+            let hour = local_ms.rem_euclid(24 * HOUR_MS) as f64 / HOUR_MS as f64;
+            let seed = point_seed.wrapping_add(ts_ms as u64 / 1000);
+            let value = match profile {
+                Profile::Counter { daily_reset, .. } => {
+                    if *daily_reset && previous_day.is_some_and(|previous| previous != day) {
+                        total = 0.0;
+                    }
+                    previous_day = Some(day);
+                    total += profile.sample(hour, seed) * interval_ms as f64 / HOUR_MS as f64;
+                    total
+                }
+                _ => profile.sample(hour, seed),
+            };
+            PointValue {
+                tenant_id: tenant_id.to_string(),
+                project_id: project_id.to_string(),
+                point_id: point_id.to_string(),
+                ts_ms,
+                value: PointValueData::F64((value * 1000.0).round() / 1000.0),
+                quality: None,
+            }
+        })
+        .collect()
+}
+
+/// 确定性扰动，取值范围 [-1, 1]
+fn noise(seed: u64) -> f64 {
+    let mut x = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^= x >> 31;
+    (x % 2001) as f64 / 1000.0 - 1.0
+}
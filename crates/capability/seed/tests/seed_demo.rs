@@ -0,0 +1,118 @@
+use std::sync::Arc;
+
+use ems_seed::{DEMO_PROJECT_ID, DEMO_USERNAME, SeedOptions, SeedStores, demo_ctx, seed_demo};
+use ems_storage::{
+    CommandQueryOptions, InMemoryCommandStore, InMemoryDeviceStore, InMemoryGatewayStore,
+    InMemoryMeasurementStore, InMemoryPointMappingStore, InMemoryPointStore, InMemoryProjectStore,
+    InMemoryTenantStore, InMemoryUserStore, MeasurementsQueryOptions,
+};
+
+/// 2024-06-01 12:00:00 UTC（北京时间 20:00）
+const NOW_MS: i64 = 1_717_243_200_000;
+
+fn stores() -> SeedStores {
+    SeedStores {
+        tenant_store: Arc::new(InMemoryTenantStore::new()),
+        rbac_store: Arc::new(InMemoryUserStore::with_default_admin()),
+        project_store: Arc::new(InMemoryProjectStore::with_default_project()),
+        gateway_store: Arc::new(InMemoryGatewayStore::new()),
+        device_store: Arc::new(InMemoryDeviceStore::new()),
+        point_store: Arc::new(InMemoryPointStore::new()),
+        point_mapping_store: Arc::new(InMemoryPointMappingStore::new()),
+        measurement_store: Arc::new(InMemoryMeasurementStore::new()),
+        command_store: Arc::new(InMemoryCommandStore::new()),
+    }
+}
+
+fn options() -> SeedOptions {
+    SeedOptions {
+        history_hours: 24,
+        interval_minutes: 15,
+        now_ms: NOW_MS,
+    }
+}
+
+#[tokio::test]
+async fn seed_demo_creates_assets_history_and_commands() {
+    let stores = stores();
+    let summary = seed_demo(&stores, &options()).await.expect("seed");
+    assert!(summary.created);
+    assert_eq!(summary.gateways, 2);
+    assert_eq!(summary.devices, 3);
+    assert_eq!(summary.points, 13);
+    assert_eq!(summary.measurements, 13 * 97);
+    assert_eq!(summary.commands, 3);
+
+    let ctx = demo_ctx();
+    let users = stores.rbac_store.list_users(&ctx).await.expect("users");
+    assert!(users.iter().any(|user| user.username == DEMO_USERNAME));
+    let points = stores
+        .point_store
+        .list_points(&ctx, DEMO_PROJECT_ID)
+        .await
+        .expect("points");
+    assert_eq!(points.len(), 13);
+
+    let pv_power = stores
+        .measurement_store
+        .query_measurements(
+            &ctx,
+            DEMO_PROJECT_ID,
+            "point-demo-pv-active-power",
+            MeasurementsQueryOptions::simple(None, None, 1000),
+        )
+        .await
+        .expect("pv power");
+    assert_eq!(pv_power.len(), 97);
+    assert!(pv_power.iter().all(|record| record.ts_ms <= NOW_MS));
+    // 北京时间 20:00 无光伏出力，正午有出力
+    let value_at = |ts_ms: i64| -> f64 {
+        pv_power
+            .iter()
+            .find(|record| record.ts_ms == ts_ms)
+            .expect("sample")
+            .value
+            .parse()
+            .expect("f64")
+    };
+    assert_eq!(value_at(NOW_MS), 0.0);
+    assert!(value_at(NOW_MS - 8 * 3_600_000) > 50.0);
+
+    let energy = stores
+        .measurement_store
+        .query_measurements(
+            &ctx,
+            DEMO_PROJECT_ID,
+            "point-demo-meter-energy-import",
+            MeasurementsQueryOptions::simple(None, None, 1000),
+        )
+        .await
+        .expect("energy");
+    let mut samples: Vec<(i64, f64)> = energy
+        .iter()
+        .map(|record| (record.ts_ms, record.value.parse().expect("f64")))
+        .collect();
+    samples.sort_by_key(|(ts_ms, _)| *ts_ms);
+    assert!(samples.windows(2).all(|pair| pair[0].1 < pair[1].1));
+
+    let commands = stores
+        .command_store
+        .list_commands(&ctx, DEMO_PROJECT_ID, CommandQueryOptions::simple(10))
+        .await
+        .expect("commands");
+    let mut statuses: Vec<&str> = commands
+        .iter()
+        .map(|command| command.status.as_str())
+        .collect();
+    statuses.sort();
+    assert_eq!(statuses, vec!["failed", "issued", "success"]);
+}
+
+#[tokio::test]
+async fn seed_demo_skips_existing_project() {
+    let stores = stores();
+    seed_demo(&stores, &options()).await.expect("seed");
+    let summary = seed_demo(&stores, &options()).await.expect("reseed");
+    assert!(!summary.created);
+    assert_eq!(summary.measurements, 0);
+}
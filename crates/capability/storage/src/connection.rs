@@ -2,6 +2,7 @@
 //!
 //! 提供数据库连接池初始化功能：
 //! - connect_pool：建立 Postgres 连接池
+//! - check_postgres / check_redis：连通性探测（用于启动前自检）
 //!
 //! 设计原则：
 //! - 最大连接数限制为 8
@@ -27,3 +28,27 @@ pub async fn connect_pool(database_url: &str) -> Result<PgPool, StorageError> {
         .await?;
     Ok(pool)
 }
+
+/// 探测 Postgres 连通性（单连接 + `select 1`）
+pub async fn check_postgres(database_url: &str) -> Result<(), StorageError> {
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .acquire_timeout(std::time::Duration::from_secs(5))
+        .connect(database_url)
+        .await?;
+    sqlx::query_scalar::<_, i32>("select 1")
+        .fetch_one(&pool)
+        .await?;
+    pool.close().await;
+    Ok(())
+}
+
+/// 探测 Redis 连通性（PING）
+pub async fn check_redis(redis_url: &str) -> Result<(), StorageError> {
+    let client = redis::Client::open(redis_url)?;
+    let mut connection = client.get_multiplexed_tokio_connection().await?;
+    redis::cmd("PING")
+        .query_async::<_, String>(&mut connection)
+        .await?;
+    Ok(())
+}
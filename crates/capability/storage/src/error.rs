@@ -4,18 +4,63 @@
 //! - SQL 执行错误
 //! - 连接错误
 //! - 数据一致性错误
+//!
+//! 错误按 [`StorageErrorKind`] 分类，接口层据此映射 HTTP 状态码
+//! （404/409/403/503，其余为 500）。
+
+/// 存储错误分类
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageErrorKind {
+    /// 记录不存在
+    NotFound,
+    /// 唯一约束冲突 / 资源已存在
+    Conflict,
+    /// 租户或项目作用域不匹配
+    Forbidden,
+    /// 数据库 / 缓存不可用（连接失败、连接池超时等）
+    Unavailable,
+    /// 其他内部错误
+    Internal,
+}
 
 #[derive(Debug)]
 pub struct StorageError {
+    kind: StorageErrorKind,
     message: String,
 }
 
 impl StorageError {
+    /// 内部错误
     pub fn new(message: impl Into<String>) -> Self {
+        Self::with_kind(StorageErrorKind::Internal, message)
+    }
+
+    pub fn with_kind(kind: StorageErrorKind, message: impl Into<String>) -> Self {
         Self {
+            kind,
             message: message.into(),
         }
     }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::with_kind(StorageErrorKind::NotFound, message)
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Self::with_kind(StorageErrorKind::Conflict, message)
+    }
+
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self::with_kind(StorageErrorKind::Forbidden, message)
+    }
+
+    pub fn unavailable(message: impl Into<String>) -> Self {
+        Self::with_kind(StorageErrorKind::Unavailable, message)
+    }
+
+    pub fn kind(&self) -> StorageErrorKind {
+        self.kind
+    }
 }
 
 impl std::fmt::Display for StorageError {
@@ -28,6 +73,35 @@ impl std::error::Error for StorageError {}
 
 impl From<sqlx::Error> for StorageError {
     fn from(err: sqlx::Error) -> Self {
-        Self::new(err.to_string())
+        let kind = match &err {
+            sqlx::Error::RowNotFound => StorageErrorKind::NotFound,
+            sqlx::Error::Database(db)
+                if db.is_unique_violation() || db.is_foreign_key_violation() =>
+            {
+                StorageErrorKind::Conflict
+            }
+            sqlx::Error::Io(_)
+            | sqlx::Error::Tls(_)
+            | sqlx::Error::PoolTimedOut
+            | sqlx::Error::PoolClosed
+            | sqlx::Error::WorkerCrashed => StorageErrorKind::Unavailable,
+            _ => StorageErrorKind::Internal,
+        };
+        Self::with_kind(kind, err.to_string())
+    }
+}
+
+impl From<redis::RedisError> for StorageError {
+    fn from(err: redis::RedisError) -> Self {
+        let kind = if err.is_io_error()
+            || err.is_connection_refusal()
+            || err.is_connection_dropped()
+            || err.is_timeout()
+        {
+            StorageErrorKind::Unavailable
+        } else {
+            StorageErrorKind::Internal
+        };
+        Self::with_kind(kind, err.to_string())
     }
 }
@@ -0,0 +1,125 @@
+//! 用能异常内存存储实现
+//!
+//! 仅用于本地测试和占位。
+
+use crate::error::StorageError;
+use crate::models::AnomalyRecord;
+use crate::traits::{AnomalyQuery, AnomalyStore};
+use crate::validation::ensure_project_scope;
+use domain::TenantContext;
+use std::sync::RwLock;
+
+/// 用能异常内存存储
+pub struct InMemoryAnomalyStore {
+    anomalies: RwLock<Vec<AnomalyRecord>>,
+}
+
+impl InMemoryAnomalyStore {
+    /// 创建新的异常存储
+    pub fn new() -> Self {
+        Self {
+            anomalies: RwLock::new(Vec::new()),
+        }
+    }
+}
+
+impl Default for InMemoryAnomalyStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl AnomalyStore for InMemoryAnomalyStore {
+    async fn insert_anomaly(
+        &self,
+        ctx: &TenantContext,
+        record: AnomalyRecord,
+    ) -> Result<bool, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let mut anomalies = self
+            .anomalies
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let exists = anomalies.iter().any(|item| {
+            item.tenant_id == record.tenant_id
+                && item.project_id == record.project_id
+                && item.point_id == record.point_id
+                && item.bucket_start_ms == record.bucket_start_ms
+        });
+        if exists {
+            return Ok(false);
+        }
+        anomalies.push(record);
+        Ok(true)
+    }
+
+    async fn list_anomalies(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        options: AnomalyQuery,
+    ) -> Result<Vec<AnomalyRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let anomalies = self
+            .anomalies
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let mut items: Vec<AnomalyRecord> = anomalies
+            .iter()
+            .filter(|item| matches_query(item, &ctx.tenant_id, project_id, &options))
+            .cloned()
+            .collect();
+        items.sort_by(|a, b| {
+            b.bucket_start_ms
+                .cmp(&a.bucket_start_ms)
+                .then_with(|| a.point_id.cmp(&b.point_id))
+        });
+        if options.limit > 0 {
+            items.truncate(options.limit as usize);
+        }
+        Ok(items)
+    }
+
+    async fn count_anomalies(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        options: AnomalyQuery,
+    ) -> Result<i64, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let anomalies = self
+            .anomalies
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        Ok(anomalies
+            .iter()
+            .filter(|item| matches_query(item, &ctx.tenant_id, project_id, &options))
+            .count() as i64)
+    }
+}
+
+fn matches_query(
+    item: &AnomalyRecord,
+    tenant_id: &str,
+    project_id: &str,
+    options: &AnomalyQuery,
+) -> bool {
+    item.tenant_id == tenant_id
+        && item.project_id == project_id
+        && options
+            .point_id
+            .as_deref()
+            .is_none_or(|value| item.point_id == value)
+        && options
+            .device_id
+            .as_deref()
+            .is_none_or(|value| item.device_id == value)
+        && options
+            .from_ms
+            .is_none_or(|from| item.bucket_start_ms >= from)
+        && options.to_ms.is_none_or(|to| item.bucket_start_ms < to)
+}
@@ -3,15 +3,15 @@
 //! 仅用于本地测试和占位。
 
 use crate::error::StorageError;
-use crate::models::AuditLogRecord;
+use crate::models::{AuditChainEntry, AuditLogRecord};
 use crate::traits::AuditLogStore;
 use crate::validation::ensure_project_scope;
 use domain::TenantContext;
 use std::sync::RwLock;
 
-/// 审计日志内存存储
+/// 审计日志内存存储（按写入顺序保存哈希链节点）
 pub struct InMemoryAuditLogStore {
-    logs: RwLock<Vec<AuditLogRecord>>,
+    logs: RwLock<Vec<AuditChainEntry>>,
 }
 
 impl InMemoryAuditLogStore {
@@ -31,7 +31,7 @@ impl AuditLogStore for InMemoryAuditLogStore {
         record: AuditLogRecord,
     ) -> Result<AuditLogRecord, StorageError> {
         if record.tenant_id != ctx.tenant_id {
-            return Err(StorageError::new("tenant mismatch"));
+            return Err(StorageError::forbidden("tenant mismatch"));
         }
         if let Some(project_id) = record.project_id.as_deref() {
             ensure_project_scope(ctx, project_id)?;
@@ -40,7 +40,19 @@ impl AuditLogStore for InMemoryAuditLogStore {
             .logs
             .write()
             .map_err(|_| StorageError::new("lock failed"))?;
-        logs.push(record.clone());
+        let (seq, prev_hash) = logs
+            .iter()
+            .rev()
+            .find(|item| item.record.tenant_id == record.tenant_id)
+            .map(|item| (item.seq + 1, item.hash.clone()))
+            .unwrap_or((1, String::new()));
+        let hash = record.chain_hash(seq, &prev_hash);
+        logs.push(AuditChainEntry {
+            record: record.clone(),
+            seq,
+            prev_hash,
+            hash,
+        });
         Ok(record)
     }
 
@@ -60,6 +72,7 @@ impl AuditLogStore for InMemoryAuditLogStore {
             .map_err(|_| StorageError::new("lock failed"))?;
         let mut items: Vec<AuditLogRecord> = logs
             .iter()
+            .map(|item| &item.record)
             .filter(|item| {
                 item.tenant_id == ctx.tenant_id && item.project_id.as_deref() == Some(project_id)
             })
@@ -79,4 +92,22 @@ impl AuditLogStore for InMemoryAuditLogStore {
         }
         Ok(items)
     }
+
+    async fn list_audit_chain(
+        &self,
+        ctx: &TenantContext,
+        after_seq: i64,
+        limit: i64,
+    ) -> Result<Vec<AuditChainEntry>, StorageError> {
+        let logs = self
+            .logs
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        Ok(logs
+            .iter()
+            .filter(|item| item.record.tenant_id == ctx.tenant_id && item.seq > after_seq)
+            .take(limit.max(0) as usize)
+            .cloned()
+            .collect())
+    }
 }
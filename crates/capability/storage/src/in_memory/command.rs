@@ -3,10 +3,11 @@
 //! 仅用于本地测试和占位。
 
 use crate::error::StorageError;
-use crate::models::CommandRecord;
-use crate::traits::CommandStore;
+use crate::models::{CommandRecord, CommandStatusCount};
+use crate::traits::{CommandQueryOptions, CommandStore};
 use crate::validation::{ensure_project_scope, ensure_tenant};
 use domain::TenantContext;
+use std::collections::BTreeMap;
 use std::sync::RwLock;
 
 /// 命令内存存储
@@ -32,7 +33,7 @@ impl CommandStore for InMemoryCommandStore {
     ) -> Result<CommandRecord, StorageError> {
         ensure_project_scope(ctx, &record.project_id)?;
         if record.tenant_id != ctx.tenant_id {
-            return Err(StorageError::new("tenant mismatch"));
+            return Err(StorageError::forbidden("tenant mismatch"));
         }
         let mut commands = self
             .commands
@@ -42,6 +43,28 @@ impl CommandStore for InMemoryCommandStore {
         Ok(record)
     }
 
+    async fn find_command(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        command_id: &str,
+    ) -> Result<Option<CommandRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        ensure_tenant(ctx)?;
+        let commands = self
+            .commands
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        Ok(commands
+            .iter()
+            .find(|item| {
+                item.command_id == command_id
+                    && item.tenant_id == ctx.tenant_id
+                    && item.project_id == project_id
+            })
+            .cloned())
+    }
+
     async fn update_command_status(
         &self,
         ctx: &TenantContext,
@@ -100,10 +123,10 @@ impl CommandStore for InMemoryCommandStore {
         &self,
         ctx: &TenantContext,
         project_id: &str,
-        limit: i64,
+        options: CommandQueryOptions,
     ) -> Result<Vec<CommandRecord>, StorageError> {
         ensure_project_scope(ctx, project_id)?;
-        let limit = limit.max(0) as usize;
+        let limit = options.limit.max(0) as usize;
         let commands = self
             .commands
             .read()
@@ -111,12 +134,88 @@ impl CommandStore for InMemoryCommandStore {
         let mut items: Vec<CommandRecord> = commands
             .iter()
             .filter(|item| item.tenant_id == ctx.tenant_id && item.project_id == project_id)
+            .filter(|item| matches_options(item, &options))
             .cloned()
             .collect();
-        items.sort_by(|a, b| b.issued_at_ms.cmp(&a.issued_at_ms));
+        items.sort_by(|a, b| {
+            b.issued_at_ms
+                .cmp(&a.issued_at_ms)
+                .then_with(|| b.command_id.cmp(&a.command_id))
+        });
         if limit > 0 && items.len() > limit {
             items.truncate(limit);
         }
         Ok(items)
     }
+
+    async fn count_commands_by_status(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        from_ms: Option<i64>,
+        to_ms: Option<i64>,
+    ) -> Result<Vec<CommandStatusCount>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let commands = self
+            .commands
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let mut counts: BTreeMap<String, i64> = BTreeMap::new();
+        for item in commands.iter() {
+            if item.tenant_id != ctx.tenant_id || item.project_id != project_id {
+                continue;
+            }
+            if from_ms.is_some_and(|from| item.issued_at_ms < from)
+                || to_ms.is_some_and(|to| item.issued_at_ms > to)
+            {
+                continue;
+            }
+            *counts.entry(item.status.clone()).or_insert(0) += 1;
+        }
+        Ok(counts
+            .into_iter()
+            .map(|(status, count)| CommandStatusCount { status, count })
+            .collect())
+    }
+}
+
+fn matches_options(item: &CommandRecord, options: &CommandQueryOptions) -> bool {
+    if options
+        .status
+        .as_deref()
+        .is_some_and(|status| item.status != status)
+    {
+        return false;
+    }
+    if options
+        .target
+        .as_deref()
+        .is_some_and(|target| item.target != target)
+    {
+        return false;
+    }
+    if options
+        .issued_by
+        .as_deref()
+        .is_some_and(|issued_by| item.issued_by != issued_by)
+    {
+        return false;
+    }
+    if options.from_ms.is_some_and(|from| item.issued_at_ms < from) {
+        return false;
+    }
+    if options.to_ms.is_some_and(|to| item.issued_at_ms > to) {
+        return false;
+    }
+    if let Some(cursor_ts_ms) = options.cursor_ts_ms {
+        let cursor_id = options.cursor_command_id.as_deref().unwrap_or("");
+        let before_cursor = item.issued_at_ms < cursor_ts_ms
+            || (item.issued_at_ms == cursor_ts_ms
+                && options.cursor_command_id.is_some()
+                && item.command_id.as_str() < cursor_id);
+        if !before_cursor {
+            return false;
+        }
+    }
+    true
 }
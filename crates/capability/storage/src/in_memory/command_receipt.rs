@@ -32,7 +32,7 @@ impl CommandReceiptStore for InMemoryCommandReceiptStore {
     ) -> Result<CommandReceiptWriteResult, StorageError> {
         ensure_project_scope(ctx, &record.project_id)?;
         if record.tenant_id != ctx.tenant_id {
-            return Err(StorageError::new("tenant mismatch"));
+            return Err(StorageError::forbidden("tenant mismatch"));
         }
         let mut receipts = self
             .receipts
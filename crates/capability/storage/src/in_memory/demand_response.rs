@@ -0,0 +1,234 @@
+//! 需求响应可削减负荷与事件内存存储实现
+//!
+//! 仅用于本地测试和占位。
+
+use crate::error::StorageError;
+use crate::models::{DemandResponseEventRecord, DemandResponseEventUpdate, SheddableLoadRecord};
+use crate::traits::DemandResponseStore;
+use crate::validation::ensure_project_scope;
+use domain::TenantContext;
+use std::sync::RwLock;
+
+/// 需求响应内存存储
+pub struct InMemoryDemandResponseStore {
+    loads: RwLock<Vec<SheddableLoadRecord>>,
+    events: RwLock<Vec<DemandResponseEventRecord>>,
+}
+
+impl InMemoryDemandResponseStore {
+    /// 创建新的需求响应存储
+    pub fn new() -> Self {
+        Self {
+            loads: RwLock::new(Vec::new()),
+            events: RwLock::new(Vec::new()),
+        }
+    }
+}
+
+impl Default for InMemoryDemandResponseStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn same_event(
+    item: &DemandResponseEventRecord,
+    tenant_id: &str,
+    project_id: &str,
+    event_id: &str,
+) -> bool {
+    item.tenant_id == tenant_id && item.project_id == project_id && item.event_id == event_id
+}
+
+#[async_trait::async_trait]
+impl DemandResponseStore for InMemoryDemandResponseStore {
+    async fn list_sheddable_loads(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<Vec<SheddableLoadRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let loads = self
+            .loads
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let mut items: Vec<SheddableLoadRecord> = loads
+            .iter()
+            .filter(|item| item.tenant_id == ctx.tenant_id && item.project_id == project_id)
+            .cloned()
+            .collect();
+        items.sort_by(|a, b| {
+            a.priority
+                .cmp(&b.priority)
+                .then_with(|| a.device_id.cmp(&b.device_id))
+        });
+        Ok(items)
+    }
+
+    async fn upsert_sheddable_load(
+        &self,
+        ctx: &TenantContext,
+        record: SheddableLoadRecord,
+    ) -> Result<SheddableLoadRecord, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let mut loads = self
+            .loads
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        if let Some(existing) = loads.iter_mut().find(|item| {
+            item.tenant_id == record.tenant_id
+                && item.project_id == record.project_id
+                && item.device_id == record.device_id
+        }) {
+            let created_at_ms = existing.created_at_ms;
+            *existing = SheddableLoadRecord {
+                created_at_ms,
+                ..record
+            };
+            return Ok(existing.clone());
+        }
+        loads.push(record.clone());
+        Ok(record)
+    }
+
+    async fn delete_sheddable_load(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        device_id: &str,
+    ) -> Result<bool, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let mut loads = self
+            .loads
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let before = loads.len();
+        loads.retain(|item| {
+            !(item.tenant_id == ctx.tenant_id
+                && item.project_id == project_id
+                && item.device_id == device_id)
+        });
+        Ok(loads.len() != before)
+    }
+
+    async fn list_demand_response_events(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        limit: i64,
+    ) -> Result<Vec<DemandResponseEventRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let events = self
+            .events
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let mut items: Vec<DemandResponseEventRecord> = events
+            .iter()
+            .filter(|item| item.tenant_id == ctx.tenant_id && item.project_id == project_id)
+            .cloned()
+            .collect();
+        items.sort_by_key(|item| std::cmp::Reverse(item.start_at_ms));
+        items.truncate(limit.max(0) as usize);
+        Ok(items)
+    }
+
+    async fn find_demand_response_event(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        event_id: &str,
+    ) -> Result<Option<DemandResponseEventRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let events = self
+            .events
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        Ok(events
+            .iter()
+            .find(|item| same_event(item, &ctx.tenant_id, project_id, event_id))
+            .cloned())
+    }
+
+    async fn create_demand_response_event(
+        &self,
+        ctx: &TenantContext,
+        record: DemandResponseEventRecord,
+    ) -> Result<DemandResponseEventRecord, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let mut events = self
+            .events
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        if events.iter().any(|item| {
+            same_event(
+                item,
+                &record.tenant_id,
+                &record.project_id,
+                &record.event_id,
+            )
+        }) {
+            return Err(StorageError::conflict("demand response event exists"));
+        }
+        events.push(record.clone());
+        Ok(record)
+    }
+
+    async fn update_demand_response_event(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        event_id: &str,
+        update: DemandResponseEventUpdate,
+    ) -> Result<Option<DemandResponseEventRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let mut events = self
+            .events
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let Some(event) = events
+            .iter_mut()
+            .find(|item| same_event(item, &ctx.tenant_id, project_id, event_id))
+        else {
+            return Ok(None);
+        };
+        if let Some(status) = update.status {
+            event.status = status;
+        }
+        if let Some(cancel_requested) = update.cancel_requested {
+            event.cancel_requested = cancel_requested;
+        }
+        if let Some(baseline_kw) = update.baseline_kw {
+            event.baseline_kw = Some(baseline_kw);
+        }
+        if let Some(achieved_kw) = update.achieved_kw {
+            event.achieved_kw = Some(achieved_kw);
+        }
+        if let Some(loads) = update.loads {
+            event.loads = loads;
+        }
+        event.updated_at_ms = update.updated_at_ms;
+        Ok(Some(event.clone()))
+    }
+
+    async fn list_open_demand_response_events(
+        &self,
+    ) -> Result<Vec<DemandResponseEventRecord>, StorageError> {
+        let events = self
+            .events
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let mut items: Vec<DemandResponseEventRecord> = events
+            .iter()
+            .filter(|item| item.status == "scheduled" || item.status == "active")
+            .cloned()
+            .collect();
+        items.sort_by_key(|item| item.start_at_ms);
+        Ok(items)
+    }
+}
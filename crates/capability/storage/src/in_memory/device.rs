@@ -8,18 +8,21 @@
 //! - 租户隔离验证
 
 use crate::error::StorageError;
-use crate::models::{DeviceRecord, DeviceUpdate};
+use crate::models::{CollectionVersion, DeviceRecord, DeviceUpdate};
 use crate::traits::DeviceStore;
 use crate::validation::ensure_project_scope;
 use domain::TenantContext;
 use std::collections::HashMap;
 use std::sync::RwLock;
+use std::sync::atomic::{AtomicI64, Ordering};
 
 /// 设备内存存储
 ///
 /// 使用 RwLock + HashMap 提供线程安全的内存存储。
 pub struct InMemoryDeviceStore {
     devices: RwLock<HashMap<String, DeviceRecord>>,
+    /// 变更计数（创建 / 更新 / 删除时递增），作为集合版本的变更标记
+    revision: AtomicI64,
 }
 
 impl InMemoryDeviceStore {
@@ -27,6 +30,7 @@ impl InMemoryDeviceStore {
     pub fn new() -> Self {
         Self {
             devices: RwLock::new(HashMap::new()),
+            revision: AtomicI64::new(0),
         }
     }
 }
@@ -53,6 +57,27 @@ impl DeviceStore for InMemoryDeviceStore {
         Ok(items)
     }
 
+    /// 指定项目设备集合的版本
+    async fn devices_version(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<CollectionVersion, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let map = self
+            .devices
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let count = map
+            .values()
+            .filter(|item| item.tenant_id == ctx.tenant_id && item.project_id == project_id)
+            .count();
+        Ok(CollectionVersion {
+            count: count as i64,
+            revision: self.revision.load(Ordering::Relaxed),
+        })
+    }
+
     /// 查找指定设备
     async fn find_device(
         &self,
@@ -78,16 +103,17 @@ impl DeviceStore for InMemoryDeviceStore {
     ) -> Result<DeviceRecord, StorageError> {
         ensure_project_scope(ctx, &record.project_id)?;
         if record.tenant_id != ctx.tenant_id {
-            return Err(StorageError::new("tenant mismatch"));
+            return Err(StorageError::forbidden("tenant mismatch"));
         }
         let mut map = self
             .devices
             .write()
             .map_err(|_| StorageError::new("lock failed"))?;
         if map.contains_key(&record.device_id) {
-            return Err(StorageError::new("device exists"));
+            return Err(StorageError::conflict("device exists"));
         }
         map.insert(record.device_id.clone(), record.clone());
+        self.revision.fetch_add(1, Ordering::Relaxed);
         Ok(record)
     }
 
@@ -117,6 +143,10 @@ impl DeviceStore for InMemoryDeviceStore {
         if let Some(model) = update.model {
             device.model = Some(model);
         }
+        if let Some(offline_after_seconds) = update.offline_after_seconds {
+            device.offline_after_seconds = Some(offline_after_seconds);
+        }
+        self.revision.fetch_add(1, Ordering::Relaxed);
         Ok(Some(device.clone()))
     }
 
@@ -135,9 +165,21 @@ impl DeviceStore for InMemoryDeviceStore {
         match map.get(device_id) {
             Some(item) if item.tenant_id == ctx.tenant_id && item.project_id == project_id => {
                 map.remove(device_id);
+                self.revision.fetch_add(1, Ordering::Relaxed);
                 Ok(true)
             }
             _ => Ok(false),
         }
     }
+
+    /// 查询全部租户的设备
+    async fn list_all_devices(&self) -> Result<Vec<DeviceRecord>, StorageError> {
+        let map = self
+            .devices
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let mut items: Vec<DeviceRecord> = map.values().cloned().collect();
+        items.sort_by(|a, b| a.device_id.cmp(&b.device_id));
+        Ok(items)
+    }
 }
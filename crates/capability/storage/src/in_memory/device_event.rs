@@ -0,0 +1,117 @@
+//! 设备时间线内存存储实现
+//!
+//! 仅用于本地测试和占位。
+
+use crate::error::StorageError;
+use crate::models::DeviceEventRecord;
+use crate::traits::{DeviceEventQuery, DeviceEventStore};
+use crate::validation::ensure_project_scope;
+use domain::TenantContext;
+use std::sync::RwLock;
+
+/// 设备时间线内存存储
+pub struct InMemoryDeviceEventStore {
+    events: RwLock<Vec<DeviceEventRecord>>,
+}
+
+impl InMemoryDeviceEventStore {
+    /// 创建新的设备时间线存储
+    pub fn new() -> Self {
+        Self {
+            events: RwLock::new(Vec::new()),
+        }
+    }
+}
+
+impl Default for InMemoryDeviceEventStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl DeviceEventStore for InMemoryDeviceEventStore {
+    async fn insert_device_event(
+        &self,
+        ctx: &TenantContext,
+        record: DeviceEventRecord,
+    ) -> Result<bool, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let mut events = self
+            .events
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let exists = events.iter().any(|item| {
+            item.tenant_id == record.tenant_id
+                && item.device_id == record.device_id
+                && item.event_id == record.event_id
+        });
+        if exists {
+            return Ok(false);
+        }
+        events.push(record);
+        Ok(true)
+    }
+
+    async fn list_device_events(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        device_id: &str,
+        options: DeviceEventQuery,
+    ) -> Result<Vec<DeviceEventRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let events = self
+            .events
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let mut items: Vec<DeviceEventRecord> = events
+            .iter()
+            .filter(|item| {
+                item.tenant_id == ctx.tenant_id
+                    && item.project_id == project_id
+                    && item.device_id == device_id
+            })
+            .filter(|item| matches_query(item, &options))
+            .cloned()
+            .collect();
+        items.sort_by(|a, b| {
+            b.occurred_at_ms
+                .cmp(&a.occurred_at_ms)
+                .then_with(|| b.event_id.cmp(&a.event_id))
+        });
+        if options.limit > 0 {
+            items.truncate(options.limit as usize);
+        }
+        Ok(items)
+    }
+}
+
+fn matches_query(item: &DeviceEventRecord, options: &DeviceEventQuery) -> bool {
+    if options
+        .event_type
+        .as_deref()
+        .is_some_and(|value| item.event_type != value)
+    {
+        return false;
+    }
+    if options
+        .from_ms
+        .is_some_and(|from| item.occurred_at_ms < from)
+        || options.to_ms.is_some_and(|to| item.occurred_at_ms > to)
+    {
+        return false;
+    }
+    // 游标仅给时间戳时按时间严格截断；同时给 event_id 时按 (occurred_at, event_id) 比较
+    match (options.cursor_ts_ms, options.cursor_event_id.as_deref()) {
+        (Some(ts_ms), Some(event_id)) => {
+            item.occurred_at_ms < ts_ms
+                || (item.occurred_at_ms == ts_ms && item.event_id.as_str() < event_id)
+        }
+        (Some(ts_ms), None) => item.occurred_at_ms < ts_ms,
+        _ => true,
+    }
+}
@@ -0,0 +1,104 @@
+//! 设备影子内存存储实现
+//!
+//! 仅用于本地测试和占位。
+
+use crate::error::StorageError;
+use crate::models::DeviceShadowRecord;
+use crate::traits::DeviceShadowStore;
+use crate::validation::ensure_project_scope;
+use domain::TenantContext;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// 设备影子内存存储（键：租户 / 项目 / 设备）
+pub struct InMemoryDeviceShadowStore {
+    shadows: RwLock<HashMap<(String, String, String), DeviceShadowRecord>>,
+}
+
+impl InMemoryDeviceShadowStore {
+    /// 创建新的设备影子存储
+    pub fn new() -> Self {
+        Self {
+            shadows: RwLock::new(HashMap::new()),
+        }
+    }
+}
+
+impl Default for InMemoryDeviceShadowStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn shadow_key(tenant_id: &str, project_id: &str, device_id: &str) -> (String, String, String) {
+    (
+        tenant_id.to_string(),
+        project_id.to_string(),
+        device_id.to_string(),
+    )
+}
+
+#[async_trait::async_trait]
+impl DeviceShadowStore for InMemoryDeviceShadowStore {
+    async fn get_device_shadow(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        device_id: &str,
+    ) -> Result<Option<DeviceShadowRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let shadows = self
+            .shadows
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        Ok(shadows
+            .get(&shadow_key(&ctx.tenant_id, project_id, device_id))
+            .cloned())
+    }
+
+    async fn put_desired_state(
+        &self,
+        ctx: &TenantContext,
+        record: DeviceShadowRecord,
+    ) -> Result<DeviceShadowRecord, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let mut shadows = self
+            .shadows
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let key = shadow_key(&record.tenant_id, &record.project_id, &record.device_id);
+        let version = shadows.get(&key).map(|item| item.version).unwrap_or(0) + 1;
+        let record = DeviceShadowRecord {
+            version,
+            last_command_id: None,
+            ..record
+        };
+        shadows.insert(key, record.clone());
+        Ok(record)
+    }
+
+    async fn set_shadow_command(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        device_id: &str,
+        version: i64,
+        command_id: &str,
+    ) -> Result<Option<DeviceShadowRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let mut shadows = self
+            .shadows
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        match shadows.get_mut(&shadow_key(&ctx.tenant_id, project_id, device_id)) {
+            Some(item) if item.version == version => {
+                item.last_command_id = Some(command_id.to_string());
+                Ok(Some(item.clone()))
+            }
+            _ => Ok(None),
+        }
+    }
+}
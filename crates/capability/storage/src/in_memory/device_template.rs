@@ -0,0 +1,210 @@
+//! 设备模板内存存储实现
+//!
+//! 仅用于本地 M0 演示和测试。
+//!
+//! 功能：
+//! - 设备模板增删查
+//! - 按模板实例化设备（写入设备/点位/点位映射，失败时回滚已写入部分）
+//! - 租户隔离验证
+
+use crate::error::StorageError;
+use crate::models::{DeviceInstance, DeviceTemplateRecord};
+use crate::traits::{DeviceStore, DeviceTemplateStore, PointMappingStore, PointStore};
+use crate::validation::ensure_project_scope;
+use domain::TenantContext;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// 设备模板内存存储
+///
+/// 模板本身使用 RwLock + HashMap 存储；实例化时写入关联的资产存储。
+pub struct InMemoryDeviceTemplateStore {
+    templates: RwLock<HashMap<String, DeviceTemplateRecord>>,
+    device_store: Arc<dyn DeviceStore>,
+    point_store: Arc<dyn PointStore>,
+    point_mapping_store: Arc<dyn PointMappingStore>,
+}
+
+impl InMemoryDeviceTemplateStore {
+    /// 创建新的设备模板存储（实例化结果写入给定的资产存储）
+    pub fn new(
+        device_store: Arc<dyn DeviceStore>,
+        point_store: Arc<dyn PointStore>,
+        point_mapping_store: Arc<dyn PointMappingStore>,
+    ) -> Self {
+        Self {
+            templates: RwLock::new(HashMap::new()),
+            device_store,
+            point_store,
+            point_mapping_store,
+        }
+    }
+
+    /// 回滚已写入的部分实例（内存实现无事务，按逆序删除）。
+    async fn rollback(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        device_id: &str,
+        point_ids: &[String],
+        source_ids: &[String],
+    ) {
+        for source_id in source_ids {
+            let _ = self
+                .point_mapping_store
+                .delete_point_mapping(ctx, project_id, source_id)
+                .await;
+        }
+        for point_id in point_ids {
+            let _ = self
+                .point_store
+                .delete_point(ctx, project_id, point_id)
+                .await;
+        }
+        let _ = self
+            .device_store
+            .delete_device(ctx, project_id, device_id)
+            .await;
+    }
+}
+
+#[async_trait::async_trait]
+impl DeviceTemplateStore for InMemoryDeviceTemplateStore {
+    /// 列出指定项目的所有设备模板
+    async fn list_device_templates(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<Vec<DeviceTemplateRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let items = self
+            .templates
+            .read()
+            .map(|map| {
+                map.values()
+                    .filter(|item| item.tenant_id == ctx.tenant_id && item.project_id == project_id)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(items)
+    }
+
+    /// 查找指定设备模板
+    async fn find_device_template(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        template_id: &str,
+    ) -> Result<Option<DeviceTemplateRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let item = self
+            .templates
+            .read()
+            .ok()
+            .and_then(|map| map.get(template_id).cloned())
+            .filter(|item| item.tenant_id == ctx.tenant_id && item.project_id == project_id);
+        Ok(item)
+    }
+
+    /// 创建新设备模板
+    async fn create_device_template(
+        &self,
+        ctx: &TenantContext,
+        record: DeviceTemplateRecord,
+    ) -> Result<DeviceTemplateRecord, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let mut map = self
+            .templates
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        if map.contains_key(&record.template_id) {
+            return Err(StorageError::conflict("device template exists"));
+        }
+        map.insert(record.template_id.clone(), record.clone());
+        Ok(record)
+    }
+
+    /// 删除设备模板
+    async fn delete_device_template(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        template_id: &str,
+    ) -> Result<bool, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let mut map = self
+            .templates
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        match map.get(template_id) {
+            Some(item) if item.tenant_id == ctx.tenant_id && item.project_id == project_id => {
+                map.remove(template_id);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// 实例化设备
+    async fn instantiate_device(
+        &self,
+        ctx: &TenantContext,
+        instance: DeviceInstance,
+    ) -> Result<DeviceInstance, StorageError> {
+        let project_id = instance.device.project_id.clone();
+        ensure_project_scope(ctx, &project_id)?;
+        if instance.device.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let device = self
+            .device_store
+            .create_device(ctx, instance.device)
+            .await?;
+
+        let mut points = Vec::with_capacity(instance.points.len());
+        let mut point_ids = Vec::with_capacity(instance.points.len());
+        for point in instance.points {
+            match self.point_store.create_point(ctx, point).await {
+                Ok(point) => {
+                    point_ids.push(point.point_id.clone());
+                    points.push(point);
+                }
+                Err(err) => {
+                    self.rollback(ctx, &project_id, &device.device_id, &point_ids, &[])
+                        .await;
+                    return Err(err);
+                }
+            }
+        }
+
+        let mut mappings = Vec::with_capacity(instance.mappings.len());
+        let mut source_ids = Vec::with_capacity(instance.mappings.len());
+        for mapping in instance.mappings {
+            match self
+                .point_mapping_store
+                .create_point_mapping(ctx, mapping)
+                .await
+            {
+                Ok(mapping) => {
+                    source_ids.push(mapping.source_id.clone());
+                    mappings.push(mapping);
+                }
+                Err(err) => {
+                    self.rollback(ctx, &project_id, &device.device_id, &point_ids, &source_ids)
+                        .await;
+                    return Err(err);
+                }
+            }
+        }
+
+        Ok(DeviceInstance {
+            device,
+            points,
+            mappings,
+        })
+    }
+}
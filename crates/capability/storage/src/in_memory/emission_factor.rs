@@ -0,0 +1,107 @@
+//! 碳排放因子内存存储实现
+//!
+//! 仅用于本地测试和占位。
+
+use crate::error::StorageError;
+use crate::models::EmissionFactorRecord;
+use crate::traits::EmissionFactorStore;
+use crate::validation::{ensure_project_scope, ensure_tenant};
+use domain::TenantContext;
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+/// 排放因子 key：(tenant_id, project_id, energy_source)，租户默认值的 project_id 为 None
+type EmissionFactorKey = (String, Option<String>, String);
+
+/// 碳排放因子内存存储
+pub struct InMemoryEmissionFactorStore {
+    factors: RwLock<BTreeMap<EmissionFactorKey, EmissionFactorRecord>>,
+}
+
+impl InMemoryEmissionFactorStore {
+    /// 创建新的排放因子存储
+    pub fn new() -> Self {
+        Self {
+            factors: RwLock::new(BTreeMap::new()),
+        }
+    }
+}
+
+impl Default for InMemoryEmissionFactorStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn ensure_scope(ctx: &TenantContext, project_id: Option<&str>) -> Result<(), StorageError> {
+    match project_id {
+        Some(project_id) => ensure_project_scope(ctx, project_id),
+        None => ensure_tenant(ctx),
+    }
+}
+
+#[async_trait::async_trait]
+impl EmissionFactorStore for InMemoryEmissionFactorStore {
+    async fn list_emission_factors(
+        &self,
+        ctx: &TenantContext,
+        project_id: Option<&str>,
+    ) -> Result<Vec<EmissionFactorRecord>, StorageError> {
+        ensure_scope(ctx, project_id)?;
+        let factors = self
+            .factors
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        Ok(factors
+            .values()
+            .filter(|factor| {
+                factor.tenant_id == ctx.tenant_id && factor.project_id.as_deref() == project_id
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn upsert_emission_factor(
+        &self,
+        ctx: &TenantContext,
+        record: EmissionFactorRecord,
+    ) -> Result<EmissionFactorRecord, StorageError> {
+        ensure_scope(ctx, record.project_id.as_deref())?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let mut factors = self
+            .factors
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        factors.insert(
+            (
+                record.tenant_id.clone(),
+                record.project_id.clone(),
+                record.energy_source.clone(),
+            ),
+            record.clone(),
+        );
+        Ok(record)
+    }
+
+    async fn delete_emission_factor(
+        &self,
+        ctx: &TenantContext,
+        project_id: Option<&str>,
+        energy_source: &str,
+    ) -> Result<bool, StorageError> {
+        ensure_scope(ctx, project_id)?;
+        let mut factors = self
+            .factors
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        Ok(factors
+            .remove(&(
+                ctx.tenant_id.clone(),
+                project_id.map(str::to_string),
+                energy_source.to_string(),
+            ))
+            .is_some())
+    }
+}
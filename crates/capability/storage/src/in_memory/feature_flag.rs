@@ -0,0 +1,100 @@
+//! 功能开关内存存储实现
+//!
+//! 仅用于本地测试和占位。
+
+use crate::error::StorageError;
+use crate::models::FeatureFlagRecord;
+use crate::traits::FeatureFlagStore;
+use crate::validation::ensure_tenant;
+use domain::TenantContext;
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+/// 功能开关内存存储（key 为 (tenant_id, flag_key)）
+pub struct InMemoryFeatureFlagStore {
+    flags: RwLock<BTreeMap<(String, String), FeatureFlagRecord>>,
+}
+
+impl InMemoryFeatureFlagStore {
+    /// 创建新的功能开关存储
+    pub fn new() -> Self {
+        Self {
+            flags: RwLock::new(BTreeMap::new()),
+        }
+    }
+}
+
+impl Default for InMemoryFeatureFlagStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl FeatureFlagStore for InMemoryFeatureFlagStore {
+    async fn list_feature_flags(
+        &self,
+        ctx: &TenantContext,
+    ) -> Result<Vec<FeatureFlagRecord>, StorageError> {
+        ensure_tenant(ctx)?;
+        let flags = self
+            .flags
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        Ok(flags
+            .values()
+            .filter(|flag| flag.tenant_id == ctx.tenant_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_feature_flag(
+        &self,
+        ctx: &TenantContext,
+        flag_key: &str,
+    ) -> Result<Option<FeatureFlagRecord>, StorageError> {
+        ensure_tenant(ctx)?;
+        let flags = self
+            .flags
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        Ok(flags
+            .get(&(ctx.tenant_id.clone(), flag_key.to_string()))
+            .cloned())
+    }
+
+    async fn upsert_feature_flag(
+        &self,
+        ctx: &TenantContext,
+        record: FeatureFlagRecord,
+    ) -> Result<FeatureFlagRecord, StorageError> {
+        ensure_tenant(ctx)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let mut flags = self
+            .flags
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        flags.insert(
+            (record.tenant_id.clone(), record.flag_key.clone()),
+            record.clone(),
+        );
+        Ok(record)
+    }
+
+    async fn delete_feature_flag(
+        &self,
+        ctx: &TenantContext,
+        flag_key: &str,
+    ) -> Result<bool, StorageError> {
+        ensure_tenant(ctx)?;
+        let mut flags = self
+            .flags
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        Ok(flags
+            .remove(&(ctx.tenant_id.clone(), flag_key.to_string()))
+            .is_some())
+    }
+}
@@ -0,0 +1,252 @@
+//! 固件包、升级批次与网关升级进度内存存储实现
+//!
+//! 仅用于本地测试和占位。
+
+use crate::error::StorageError;
+use crate::models::{
+    FirmwareCampaignRecord, FirmwarePackageRecord, FirmwareRolloutRecord, FirmwareRolloutUpdate,
+};
+use crate::traits::FirmwareStore;
+use crate::validation::ensure_project_scope;
+use domain::TenantContext;
+use std::sync::RwLock;
+
+/// 固件升级内存存储
+pub struct InMemoryFirmwareStore {
+    packages: RwLock<Vec<FirmwarePackageRecord>>,
+    campaigns: RwLock<Vec<FirmwareCampaignRecord>>,
+    rollouts: RwLock<Vec<FirmwareRolloutRecord>>,
+}
+
+impl InMemoryFirmwareStore {
+    /// 创建新的固件升级存储
+    pub fn new() -> Self {
+        Self {
+            packages: RwLock::new(Vec::new()),
+            campaigns: RwLock::new(Vec::new()),
+            rollouts: RwLock::new(Vec::new()),
+        }
+    }
+}
+
+impl Default for InMemoryFirmwareStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl FirmwareStore for InMemoryFirmwareStore {
+    async fn list_firmware_packages(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<Vec<FirmwarePackageRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let packages = self
+            .packages
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let mut items: Vec<FirmwarePackageRecord> = packages
+            .iter()
+            .filter(|item| item.tenant_id == ctx.tenant_id && item.project_id == project_id)
+            .cloned()
+            .collect();
+        items.sort_by_key(|item| std::cmp::Reverse(item.created_at_ms));
+        Ok(items)
+    }
+
+    async fn find_firmware_package(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        package_id: &str,
+    ) -> Result<Option<FirmwarePackageRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let packages = self
+            .packages
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        Ok(packages
+            .iter()
+            .find(|item| {
+                item.tenant_id == ctx.tenant_id
+                    && item.project_id == project_id
+                    && item.package_id == package_id
+            })
+            .cloned())
+    }
+
+    async fn create_firmware_package(
+        &self,
+        ctx: &TenantContext,
+        record: FirmwarePackageRecord,
+    ) -> Result<FirmwarePackageRecord, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let mut packages = self
+            .packages
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        if packages.iter().any(|item| {
+            item.tenant_id == record.tenant_id
+                && item.project_id == record.project_id
+                && item.name == record.name
+                && item.version == record.version
+        }) {
+            return Err(StorageError::conflict("firmware version already exists"));
+        }
+        packages.push(record.clone());
+        Ok(record)
+    }
+
+    async fn list_firmware_campaigns(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        limit: i64,
+    ) -> Result<Vec<FirmwareCampaignRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let campaigns = self
+            .campaigns
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let mut items: Vec<FirmwareCampaignRecord> = campaigns
+            .iter()
+            .filter(|item| item.tenant_id == ctx.tenant_id && item.project_id == project_id)
+            .cloned()
+            .collect();
+        items.sort_by_key(|item| std::cmp::Reverse(item.created_at_ms));
+        items.truncate(limit.max(0) as usize);
+        Ok(items)
+    }
+
+    async fn find_firmware_campaign(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        campaign_id: &str,
+    ) -> Result<Option<FirmwareCampaignRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let campaigns = self
+            .campaigns
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        Ok(campaigns
+            .iter()
+            .find(|item| {
+                item.tenant_id == ctx.tenant_id
+                    && item.project_id == project_id
+                    && item.campaign_id == campaign_id
+            })
+            .cloned())
+    }
+
+    async fn create_firmware_campaign(
+        &self,
+        ctx: &TenantContext,
+        record: FirmwareCampaignRecord,
+        rollouts: Vec<FirmwareRolloutRecord>,
+    ) -> Result<FirmwareCampaignRecord, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id
+            || rollouts
+                .iter()
+                .any(|rollout| rollout.tenant_id != ctx.tenant_id)
+        {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let mut campaigns = self
+            .campaigns
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let mut stored = self
+            .rollouts
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        campaigns.push(record.clone());
+        stored.extend(rollouts);
+        Ok(record)
+    }
+
+    async fn update_firmware_campaign_status(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        campaign_id: &str,
+        status: &str,
+        updated_at_ms: i64,
+    ) -> Result<Option<FirmwareCampaignRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let mut campaigns = self
+            .campaigns
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let Some(item) = campaigns.iter_mut().find(|item| {
+            item.tenant_id == ctx.tenant_id
+                && item.project_id == project_id
+                && item.campaign_id == campaign_id
+        }) else {
+            return Ok(None);
+        };
+        item.status = status.to_string();
+        item.updated_at_ms = updated_at_ms;
+        Ok(Some(item.clone()))
+    }
+
+    async fn list_firmware_rollouts(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        campaign_id: &str,
+    ) -> Result<Vec<FirmwareRolloutRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let rollouts = self
+            .rollouts
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let mut items: Vec<FirmwareRolloutRecord> = rollouts
+            .iter()
+            .filter(|item| {
+                item.tenant_id == ctx.tenant_id
+                    && item.project_id == project_id
+                    && item.campaign_id == campaign_id
+            })
+            .cloned()
+            .collect();
+        items.sort_by(|a, b| a.gateway_id.cmp(&b.gateway_id));
+        Ok(items)
+    }
+
+    async fn update_firmware_rollout(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        campaign_id: &str,
+        gateway_id: &str,
+        update: FirmwareRolloutUpdate,
+    ) -> Result<Option<FirmwareRolloutRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let mut rollouts = self
+            .rollouts
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let Some(item) = rollouts.iter_mut().find(|item| {
+            item.tenant_id == ctx.tenant_id
+                && item.project_id == project_id
+                && item.campaign_id == campaign_id
+                && item.gateway_id == gateway_id
+        }) else {
+            return Ok(None);
+        };
+        item.status = update.status;
+        if let Some(progress) = update.progress {
+            item.progress = progress;
+        }
+        item.message = update.message;
+        item.updated_at_ms = update.updated_at_ms;
+        Ok(Some(item.clone()))
+    }
+}
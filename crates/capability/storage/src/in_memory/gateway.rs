@@ -8,18 +8,25 @@
 //! - 租户隔离验证
 
 use crate::error::StorageError;
-use crate::models::{GatewayRecord, GatewayUpdate};
+use crate::models::{CollectionVersion, GatewayRecord, GatewayUpdate};
 use crate::traits::GatewayStore;
 use crate::validation::ensure_project_scope;
 use domain::TenantContext;
 use std::collections::HashMap;
 use std::sync::RwLock;
+use std::sync::atomic::{AtomicI64, Ordering};
 
 /// 网关内存存储
 ///
 /// 使用 RwLock + HashMap 提供线程安全的内存存储。
 pub struct InMemoryGatewayStore {
     gateways: RwLock<HashMap<String, GatewayRecord>>,
+    /// 变更计数（创建 / 更新 / 删除时递增），作为集合版本的变更标记
+    revision: AtomicI64,
+    /// 回调令牌摘要（gateway_id → token_hash）
+    tokens: RwLock<HashMap<String, String>>,
+    /// 命令载荷格式（gateway_id → JSON 文本）
+    command_formats: RwLock<HashMap<String, String>>,
 }
 
 impl InMemoryGatewayStore {
@@ -27,8 +34,26 @@ impl InMemoryGatewayStore {
     pub fn new() -> Self {
         Self {
             gateways: RwLock::new(HashMap::new()),
+            revision: AtomicI64::new(0),
+            tokens: RwLock::new(HashMap::new()),
+            command_formats: RwLock::new(HashMap::new()),
         }
     }
+
+    /// 网关存在且属于当前租户与项目
+    fn owns_gateway(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        gateway_id: &str,
+    ) -> Result<bool, StorageError> {
+        Ok(self
+            .gateways
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?
+            .get(gateway_id)
+            .is_some_and(|item| item.tenant_id == ctx.tenant_id && item.project_id == project_id))
+    }
 }
 
 #[async_trait::async_trait]
@@ -53,6 +78,27 @@ impl GatewayStore for InMemoryGatewayStore {
         Ok(items)
     }
 
+    /// 指定项目网关集合的版本
+    async fn gateways_version(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<CollectionVersion, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let map = self
+            .gateways
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let count = map
+            .values()
+            .filter(|item| item.tenant_id == ctx.tenant_id && item.project_id == project_id)
+            .count();
+        Ok(CollectionVersion {
+            count: count as i64,
+            revision: self.revision.load(Ordering::Relaxed),
+        })
+    }
+
     /// 查找指定网关
     async fn find_gateway(
         &self,
@@ -78,16 +124,17 @@ impl GatewayStore for InMemoryGatewayStore {
     ) -> Result<GatewayRecord, StorageError> {
         ensure_project_scope(ctx, &record.project_id)?;
         if record.tenant_id != ctx.tenant_id {
-            return Err(StorageError::new("tenant mismatch"));
+            return Err(StorageError::forbidden("tenant mismatch"));
         }
         let mut map = self
             .gateways
             .write()
             .map_err(|_| StorageError::new("lock failed"))?;
         if map.contains_key(&record.gateway_id) {
-            return Err(StorageError::new("gateway exists"));
+            return Err(StorageError::conflict("gateway exists"));
         }
         map.insert(record.gateway_id.clone(), record.clone());
+        self.revision.fetch_add(1, Ordering::Relaxed);
         Ok(record)
     }
 
@@ -117,6 +164,7 @@ impl GatewayStore for InMemoryGatewayStore {
         if let Some(status) = update.status {
             gateway.status = status;
         }
+        self.revision.fetch_add(1, Ordering::Relaxed);
         Ok(Some(gateway.clone()))
     }
 
@@ -135,9 +183,123 @@ impl GatewayStore for InMemoryGatewayStore {
         match map.get(gateway_id) {
             Some(item) if item.tenant_id == ctx.tenant_id && item.project_id == project_id => {
                 map.remove(gateway_id);
+                self.revision.fetch_add(1, Ordering::Relaxed);
+                if let Ok(mut tokens) = self.tokens.write() {
+                    tokens.remove(gateway_id);
+                }
+                if let Ok(mut formats) = self.command_formats.write() {
+                    formats.remove(gateway_id);
+                }
                 Ok(true)
             }
             _ => Ok(false),
         }
     }
+
+    /// 查询全部租户的网关
+    async fn list_all_gateways(&self) -> Result<Vec<GatewayRecord>, StorageError> {
+        let map = self
+            .gateways
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let mut items: Vec<GatewayRecord> = map.values().cloned().collect();
+        items.sort_by(|a, b| a.gateway_id.cmp(&b.gateway_id));
+        Ok(items)
+    }
+
+    /// 设置网关回调令牌摘要
+    async fn set_gateway_token_hash(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        gateway_id: &str,
+        token_hash: Option<&str>,
+    ) -> Result<bool, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        if !self.owns_gateway(ctx, project_id, gateway_id)? {
+            return Ok(false);
+        }
+        let mut tokens = self
+            .tokens
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        match token_hash {
+            Some(token_hash) => {
+                tokens.insert(gateway_id.to_string(), token_hash.to_string());
+            }
+            None => {
+                tokens.remove(gateway_id);
+            }
+        }
+        Ok(true)
+    }
+
+    /// 按回调令牌摘要查找网关
+    async fn find_gateway_by_token_hash(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<GatewayRecord>, StorageError> {
+        let gateway_id = self
+            .tokens
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?
+            .iter()
+            .find(|(_, hash)| hash.as_str() == token_hash)
+            .map(|(gateway_id, _)| gateway_id.clone());
+        let Some(gateway_id) = gateway_id else {
+            return Ok(None);
+        };
+        let map = self
+            .gateways
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        Ok(map.get(&gateway_id).cloned())
+    }
+
+    /// 设置网关命令载荷格式
+    async fn set_gateway_command_format(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        gateway_id: &str,
+        format: Option<&str>,
+    ) -> Result<bool, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        if !self.owns_gateway(ctx, project_id, gateway_id)? {
+            return Ok(false);
+        }
+        let mut formats = self
+            .command_formats
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        match format {
+            Some(format) => {
+                formats.insert(gateway_id.to_string(), format.to_string());
+            }
+            None => {
+                formats.remove(gateway_id);
+            }
+        }
+        self.revision.fetch_add(1, Ordering::Relaxed);
+        Ok(true)
+    }
+
+    /// 查询网关命令载荷格式
+    async fn find_gateway_command_format(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        gateway_id: &str,
+    ) -> Result<Option<String>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        if !self.owns_gateway(ctx, project_id, gateway_id)? {
+            return Ok(None);
+        }
+        Ok(self
+            .command_formats
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?
+            .get(gateway_id)
+            .cloned())
+    }
 }
@@ -0,0 +1,122 @@
+//! 网关配置下发记录内存存储实现
+//!
+//! 仅用于本地测试和占位。
+
+use crate::error::StorageError;
+use crate::models::GatewayConfigRecord;
+use crate::traits::GatewayConfigStore;
+use crate::validation::ensure_project_scope;
+use domain::TenantContext;
+use std::sync::RwLock;
+
+/// 网关配置下发记录内存存储
+pub struct InMemoryGatewayConfigStore {
+    configs: RwLock<Vec<GatewayConfigRecord>>,
+}
+
+impl Default for InMemoryGatewayConfigStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InMemoryGatewayConfigStore {
+    /// 创建新的网关配置存储
+    pub fn new() -> Self {
+        Self {
+            configs: RwLock::new(Vec::new()),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl GatewayConfigStore for InMemoryGatewayConfigStore {
+    async fn create_gateway_config(
+        &self,
+        ctx: &TenantContext,
+        record: GatewayConfigRecord,
+    ) -> Result<GatewayConfigRecord, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let mut configs = self
+            .configs
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let latest = configs
+            .iter()
+            .filter(|item| {
+                item.tenant_id == record.tenant_id
+                    && item.project_id == record.project_id
+                    && item.gateway_id == record.gateway_id
+            })
+            .map(|item| item.version)
+            .max()
+            .unwrap_or(0);
+        let record = GatewayConfigRecord {
+            version: latest + 1,
+            ..record
+        };
+        configs.push(record.clone());
+        Ok(record)
+    }
+
+    async fn list_gateway_configs(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        gateway_id: &str,
+        limit: i64,
+    ) -> Result<Vec<GatewayConfigRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let configs = self
+            .configs
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let mut items: Vec<GatewayConfigRecord> = configs
+            .iter()
+            .filter(|item| {
+                item.tenant_id == ctx.tenant_id
+                    && item.project_id == project_id
+                    && item.gateway_id == gateway_id
+            })
+            .cloned()
+            .collect();
+        items.sort_by_key(|item| std::cmp::Reverse(item.version));
+        if limit > 0 {
+            items.truncate(limit as usize);
+        }
+        Ok(items)
+    }
+
+    async fn update_gateway_config_status(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        gateway_id: &str,
+        version: i64,
+        status: &str,
+        message: Option<String>,
+        updated_at_ms: i64,
+    ) -> Result<Option<GatewayConfigRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let mut configs = self
+            .configs
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        for item in configs.iter_mut() {
+            if item.tenant_id == ctx.tenant_id
+                && item.project_id == project_id
+                && item.gateway_id == gateway_id
+                && item.version == version
+            {
+                item.status = status.to_string();
+                item.message = message;
+                item.updated_at_ms = updated_at_ms;
+                return Ok(Some(item.clone()));
+            }
+        }
+        Ok(None)
+    }
+}
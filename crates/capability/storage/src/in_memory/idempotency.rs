@@ -0,0 +1,93 @@
+//! 幂等键内存存储实现
+//!
+//! 仅用于本地测试和占位。
+
+use crate::error::StorageError;
+use crate::models::IdempotencyRecord;
+use crate::traits::IdempotencyStore;
+use crate::validation::ensure_tenant;
+use domain::TenantContext;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// 幂等键内存存储（key 为 (tenant_id, idempotency_key)）
+pub struct InMemoryIdempotencyStore {
+    records: RwLock<HashMap<(String, String), IdempotencyRecord>>,
+}
+
+impl InMemoryIdempotencyStore {
+    /// 创建新的幂等键存储
+    pub fn new() -> Self {
+        Self {
+            records: RwLock::new(HashMap::new()),
+        }
+    }
+}
+
+impl Default for InMemoryIdempotencyStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl IdempotencyStore for InMemoryIdempotencyStore {
+    async fn reserve_idempotency_key(
+        &self,
+        ctx: &TenantContext,
+        record: IdempotencyRecord,
+    ) -> Result<Option<IdempotencyRecord>, StorageError> {
+        ensure_tenant(ctx)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let mut records = self
+            .records
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        // 顺带清理已过期的记录
+        records.retain(|_, item| item.expires_at_ms > record.created_at_ms);
+        let key = (record.tenant_id.clone(), record.idempotency_key.clone());
+        if let Some(existing) = records.get(&key) {
+            return Ok(Some(existing.clone()));
+        }
+        records.insert(key, record);
+        Ok(None)
+    }
+
+    async fn complete_idempotency_key(
+        &self,
+        ctx: &TenantContext,
+        idempotency_key: &str,
+        status_code: i32,
+        response_headers: String,
+        response_body: String,
+    ) -> Result<(), StorageError> {
+        ensure_tenant(ctx)?;
+        let mut records = self
+            .records
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let key = (ctx.tenant_id.clone(), idempotency_key.to_string());
+        if let Some(record) = records.get_mut(&key) {
+            record.status_code = Some(status_code);
+            record.response_headers = Some(response_headers);
+            record.response_body = Some(response_body);
+        }
+        Ok(())
+    }
+
+    async fn release_idempotency_key(
+        &self,
+        ctx: &TenantContext,
+        idempotency_key: &str,
+    ) -> Result<(), StorageError> {
+        ensure_tenant(ctx)?;
+        let mut records = self
+            .records
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        records.remove(&(ctx.tenant_id.clone(), idempotency_key.to_string()));
+        Ok(())
+    }
+}
@@ -0,0 +1,242 @@
+//! 后台任务内存存储实现
+//!
+//! 仅用于本地测试和占位。
+
+use crate::error::StorageError;
+use crate::models::JobRecord;
+use crate::traits::{JobFinish, JobQuery, JobStore};
+use crate::validation::{ensure_project_scope, ensure_tenant};
+use domain::TenantContext;
+use std::sync::RwLock;
+
+/// 后台任务内存存储
+pub struct InMemoryJobStore {
+    jobs: RwLock<Vec<JobRecord>>,
+}
+
+impl InMemoryJobStore {
+    /// 创建新的后台任务存储
+    pub fn new() -> Self {
+        Self {
+            jobs: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// 在写锁内修改单个可见任务；任务不存在时返回 None
+    fn modify<F>(
+        &self,
+        ctx: &TenantContext,
+        job_id: &str,
+        apply: F,
+    ) -> Result<Option<JobRecord>, StorageError>
+    where
+        F: FnOnce(&mut JobRecord) -> bool,
+    {
+        ensure_tenant(ctx)?;
+        let mut jobs = self
+            .jobs
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let Some(job) = jobs
+            .iter_mut()
+            .find(|job| job.job_id == job_id && visible(ctx, job))
+        else {
+            return Ok(None);
+        };
+        Ok(apply(job).then(|| job.clone()))
+    }
+}
+
+impl Default for InMemoryJobStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 任务是否对上下文可见（租户一致，项目作用域内只看该项目的任务）
+fn visible(ctx: &TenantContext, job: &JobRecord) -> bool {
+    job.tenant_id == ctx.tenant_id
+        && ctx
+            .project_scope
+            .as_deref()
+            .is_none_or(|scope| job.project_id.as_deref() == Some(scope))
+}
+
+fn is_unfinished(status: &str) -> bool {
+    status == "queued" || status == "running"
+}
+
+#[async_trait::async_trait]
+impl JobStore for InMemoryJobStore {
+    async fn create_job(
+        &self,
+        ctx: &TenantContext,
+        record: JobRecord,
+    ) -> Result<JobRecord, StorageError> {
+        match record.project_id.as_deref() {
+            Some(project_id) => ensure_project_scope(ctx, project_id)?,
+            None => ensure_tenant(ctx)?,
+        }
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let mut jobs = self
+            .jobs
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        jobs.push(record.clone());
+        Ok(record)
+    }
+
+    async fn find_job(
+        &self,
+        ctx: &TenantContext,
+        job_id: &str,
+    ) -> Result<Option<JobRecord>, StorageError> {
+        ensure_tenant(ctx)?;
+        let jobs = self
+            .jobs
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        Ok(jobs
+            .iter()
+            .find(|job| job.job_id == job_id && visible(ctx, job))
+            .cloned())
+    }
+
+    async fn list_jobs(
+        &self,
+        ctx: &TenantContext,
+        options: JobQuery,
+    ) -> Result<Vec<JobRecord>, StorageError> {
+        ensure_tenant(ctx)?;
+        let jobs = self
+            .jobs
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let mut items: Vec<JobRecord> = jobs
+            .iter()
+            .filter(|job| visible(ctx, job))
+            .filter(|job| {
+                options
+                    .project_id
+                    .as_deref()
+                    .is_none_or(|value| job.project_id.as_deref() == Some(value))
+                    && options
+                        .kind
+                        .as_deref()
+                        .is_none_or(|value| job.kind == value)
+                    && options
+                        .status
+                        .as_deref()
+                        .is_none_or(|value| job.status == value)
+            })
+            .cloned()
+            .collect();
+        items.sort_by(|a, b| {
+            b.created_at_ms
+                .cmp(&a.created_at_ms)
+                .then_with(|| b.job_id.cmp(&a.job_id))
+        });
+        if options.limit > 0 {
+            items.truncate(options.limit as usize);
+        }
+        Ok(items)
+    }
+
+    async fn start_job(
+        &self,
+        ctx: &TenantContext,
+        job_id: &str,
+        started_at_ms: i64,
+    ) -> Result<Option<JobRecord>, StorageError> {
+        self.modify(ctx, job_id, |job| {
+            if job.status != "queued" || job.cancel_requested {
+                return false;
+            }
+            job.status = "running".to_string();
+            job.started_at_ms = Some(started_at_ms);
+            job.updated_at_ms = started_at_ms;
+            true
+        })
+    }
+
+    async fn update_job_progress(
+        &self,
+        ctx: &TenantContext,
+        job_id: &str,
+        progress: i32,
+        updated_at_ms: i64,
+    ) -> Result<Option<JobRecord>, StorageError> {
+        self.modify(ctx, job_id, |job| {
+            if job.status != "running" {
+                return false;
+            }
+            job.progress = progress.clamp(0, 100);
+            job.updated_at_ms = updated_at_ms;
+            true
+        })
+    }
+
+    async fn finish_job(
+        &self,
+        ctx: &TenantContext,
+        job_id: &str,
+        finish: JobFinish,
+    ) -> Result<Option<JobRecord>, StorageError> {
+        self.modify(ctx, job_id, |job| {
+            if !is_unfinished(&job.status) {
+                return false;
+            }
+            if finish.status == "succeeded" {
+                job.progress = 100;
+            }
+            job.status = finish.status;
+            job.result = finish.result;
+            job.error = finish.error;
+            job.finished_at_ms = Some(finish.finished_at_ms);
+            job.updated_at_ms = finish.finished_at_ms;
+            true
+        })
+    }
+
+    async fn request_job_cancel(
+        &self,
+        ctx: &TenantContext,
+        job_id: &str,
+        requested_at_ms: i64,
+    ) -> Result<Option<JobRecord>, StorageError> {
+        self.modify(ctx, job_id, |job| {
+            if job.status == "queued" {
+                job.status = "cancelled".to_string();
+                job.cancel_requested = true;
+                job.finished_at_ms = Some(requested_at_ms);
+                job.updated_at_ms = requested_at_ms;
+            } else if job.status == "running" && !job.cancel_requested {
+                job.cancel_requested = true;
+                job.updated_at_ms = requested_at_ms;
+            }
+            true
+        })
+    }
+
+    async fn fail_unfinished_jobs(
+        &self,
+        error: &str,
+        finished_at_ms: i64,
+    ) -> Result<u64, StorageError> {
+        let mut jobs = self
+            .jobs
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let mut count = 0;
+        for job in jobs.iter_mut().filter(|job| is_unfinished(&job.status)) {
+            job.status = "failed".to_string();
+            job.error = Some(error.to_string());
+            job.finished_at_ms = Some(finished_at_ms);
+            job.updated_at_ms = finished_at_ms;
+            count += 1;
+        }
+        Ok(count)
+    }
+}
@@ -0,0 +1,131 @@
+//! 维护窗口内存存储实现
+//!
+//! 仅用于本地测试和占位。
+
+use crate::error::StorageError;
+use crate::models::MaintenanceWindowRecord;
+use crate::traits::MaintenanceStore;
+use crate::validation::ensure_project_scope;
+use domain::TenantContext;
+use std::sync::RwLock;
+
+/// 维护窗口内存存储
+pub struct InMemoryMaintenanceStore {
+    windows: RwLock<Vec<MaintenanceWindowRecord>>,
+}
+
+impl InMemoryMaintenanceStore {
+    /// 创建新的维护窗口存储
+    pub fn new() -> Self {
+        Self {
+            windows: RwLock::new(Vec::new()),
+        }
+    }
+}
+
+impl Default for InMemoryMaintenanceStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn same_target(
+    item: &MaintenanceWindowRecord,
+    tenant_id: &str,
+    project_id: &str,
+    target_type: &str,
+    target_id: &str,
+) -> bool {
+    item.tenant_id == tenant_id
+        && item.project_id == project_id
+        && item.target_type == target_type
+        && item.target_id == target_id
+}
+
+#[async_trait::async_trait]
+impl MaintenanceStore for InMemoryMaintenanceStore {
+    async fn list_maintenance_windows(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<Vec<MaintenanceWindowRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let windows = self
+            .windows
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let mut items: Vec<MaintenanceWindowRecord> = windows
+            .iter()
+            .filter(|item| item.tenant_id == ctx.tenant_id && item.project_id == project_id)
+            .cloned()
+            .collect();
+        items.sort_by(|a, b| {
+            a.target_type
+                .cmp(&b.target_type)
+                .then_with(|| a.target_id.cmp(&b.target_id))
+        });
+        Ok(items)
+    }
+
+    async fn find_maintenance_window(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        target_type: &str,
+        target_id: &str,
+    ) -> Result<Option<MaintenanceWindowRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let windows = self
+            .windows
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        Ok(windows
+            .iter()
+            .find(|item| same_target(item, &ctx.tenant_id, project_id, target_type, target_id))
+            .cloned())
+    }
+
+    async fn upsert_maintenance_window(
+        &self,
+        ctx: &TenantContext,
+        record: MaintenanceWindowRecord,
+    ) -> Result<MaintenanceWindowRecord, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let mut windows = self
+            .windows
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        windows.retain(|item| {
+            !same_target(
+                item,
+                &record.tenant_id,
+                &record.project_id,
+                &record.target_type,
+                &record.target_id,
+            )
+        });
+        windows.push(record.clone());
+        Ok(record)
+    }
+
+    async fn delete_maintenance_window(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        target_type: &str,
+        target_id: &str,
+    ) -> Result<bool, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let mut windows = self
+            .windows
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let before = windows.len();
+        windows
+            .retain(|item| !same_target(item, &ctx.tenant_id, project_id, target_type, target_id));
+        Ok(windows.len() != before)
+    }
+}
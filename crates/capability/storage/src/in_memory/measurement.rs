@@ -3,17 +3,31 @@
 //! 仅用于本地测试和占位。
 
 use crate::error::StorageError;
-use crate::models::MeasurementRecord;
+use crate::models::{MeasurementCoverage, MeasurementGap, MeasurementRecord};
 use crate::traits::{
-    MeasurementAggFn, MeasurementAggregation, MeasurementStore, MeasurementsQueryOptions, TimeOrder,
+    MeasurementAggFn, MeasurementAggregation, MeasurementCoverageOptions, MeasurementDedupStrategy,
+    MeasurementStore, MeasurementsQueryOptions, TimeOrder,
 };
 use crate::validation::ensure_project_scope;
 use domain::{PointValue, PointValueData, TenantContext};
+use std::collections::{BTreeSet, HashMap};
 use std::sync::RwLock;
 
+/// 测量值主键：(tenant_id, project_id, point_id, ts_ms)
+type MeasurementKey = (String, String, String, i64);
+
 /// 时序写入内存存储
 pub struct InMemoryMeasurementStore {
     values: RwLock<Vec<PointValue>>,
+    /// 主键 → `values` 下标，按去重策略处理重复写入（与 PG 主键一致）
+    index: RwLock<HashMap<MeasurementKey, usize>>,
+    dedup: MeasurementDedupStrategy,
+}
+
+impl Default for InMemoryMeasurementStore {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl InMemoryMeasurementStore {
@@ -21,7 +35,54 @@ impl InMemoryMeasurementStore {
     pub fn new() -> Self {
         Self {
             values: RwLock::new(Vec::new()),
+            index: RwLock::new(HashMap::new()),
+            dedup: MeasurementDedupStrategy::default(),
+        }
+    }
+
+    /// 设置重复写入的处理策略，默认 `ignore`
+    pub fn with_dedup_strategy(mut self, dedup: MeasurementDedupStrategy) -> Self {
+        self.dedup = dedup;
+        self
+    }
+
+    /// 写入一批值，返回实际写入（新增或替换）的数量
+    fn write_values(&self, values: &[PointValue]) -> Result<usize, StorageError> {
+        let mut store = self
+            .values
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let mut index = self
+            .index
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let mut written = 0;
+        for value in values {
+            let key = (
+                value.tenant_id.clone(),
+                value.project_id.clone(),
+                value.point_id.clone(),
+                value.ts_ms,
+            );
+            match index.get(&key) {
+                Some(&position) => {
+                    let existing = &mut store[position];
+                    if self
+                        .dedup
+                        .replaces(existing.quality.as_deref(), value.quality.as_deref())
+                    {
+                        *existing = value.clone();
+                        written += 1;
+                    }
+                }
+                None => {
+                    index.insert(key, store.len());
+                    store.push(value.clone());
+                    written += 1;
+                }
+            }
         }
+        Ok(written)
     }
 
     /// 获取当前累计的测点值数量（用于测试）
@@ -48,13 +109,9 @@ impl MeasurementStore for InMemoryMeasurementStore {
     ) -> Result<(), StorageError> {
         ensure_project_scope(ctx, &value.project_id)?;
         if value.tenant_id != ctx.tenant_id {
-            return Err(StorageError::new("tenant mismatch"));
+            return Err(StorageError::forbidden("tenant mismatch"));
         }
-        let mut values = self
-            .values
-            .write()
-            .map_err(|_| StorageError::new("lock failed"))?;
-        values.push(value.clone());
+        self.write_values(std::slice::from_ref(value))?;
         Ok(())
     }
 
@@ -66,15 +123,10 @@ impl MeasurementStore for InMemoryMeasurementStore {
         for value in values {
             ensure_project_scope(ctx, &value.project_id)?;
             if value.tenant_id != ctx.tenant_id {
-                return Err(StorageError::new("tenant mismatch"));
+                return Err(StorageError::forbidden("tenant mismatch"));
             }
         }
-        let mut store = self
-            .values
-            .write()
-            .map_err(|_| StorageError::new("lock failed"))?;
-        store.extend(values.iter().cloned());
-        Ok(values.len())
+        self.write_values(values)
     }
 
     async fn query_measurements(
@@ -98,15 +150,15 @@ impl MeasurementStore for InMemoryMeasurementStore {
             {
                 continue;
             }
-            if let Some(from) = options.from_ms {
-                if value.ts_ms < from {
-                    continue;
-                }
+            if let Some(from) = options.from_ms
+                && value.ts_ms < from
+            {
+                continue;
             }
-            if let Some(to) = options.to_ms {
-                if value.ts_ms > to {
-                    continue;
-                }
+            if let Some(to) = options.to_ms
+                && value.ts_ms > to
+            {
+                continue;
             }
             selected.push(value.clone());
         }
@@ -121,8 +173,7 @@ impl MeasurementStore for InMemoryMeasurementStore {
                 ctx,
                 project_id,
                 point_id,
-                options.order,
-                options.cursor_ts_ms,
+                &options,
             ));
         }
 
@@ -153,6 +204,73 @@ impl MeasurementStore for InMemoryMeasurementStore {
         }
         Ok(items)
     }
+
+    async fn measurement_coverage(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        point_id: &str,
+        options: MeasurementCoverageOptions,
+    ) -> Result<MeasurementCoverage, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let expected_buckets = options.expected_buckets();
+        if expected_buckets == 0 {
+            return Ok(MeasurementCoverage {
+                expected_buckets: 0,
+                covered_buckets: 0,
+                completeness_pct: 0.0,
+                gaps: Vec::new(),
+            });
+        }
+        let values = self
+            .values
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let mut buckets = BTreeSet::new();
+        for value in values.iter() {
+            if value.tenant_id != ctx.tenant_id
+                || value.project_id != project_id
+                || value.point_id != point_id
+                || value.ts_ms < options.from_ms
+                || value.ts_ms >= options.to_ms
+            {
+                continue;
+            }
+            buckets.insert((value.ts_ms - options.from_ms) / options.expected_interval_ms);
+        }
+        let covered_buckets = buckets.len() as i64;
+
+        // 以 -1 和 expected_buckets 作为哨兵，相邻已覆盖桶之间的空桶即缺失区间
+        let gap_limit = options.gap_limit.max(0) as usize;
+        let mut gaps = Vec::new();
+        let mut prev = -1;
+        for idx in buckets
+            .iter()
+            .copied()
+            .chain(std::iter::once(expected_buckets))
+        {
+            if idx - prev > 1 && gaps.len() < gap_limit {
+                gaps.push(MeasurementGap {
+                    from_ms: options.from_ms + (prev + 1) * options.expected_interval_ms,
+                    to_ms: (options.from_ms + idx * options.expected_interval_ms)
+                        .min(options.to_ms),
+                });
+            }
+            prev = idx;
+        }
+
+        Ok(MeasurementCoverage {
+            expected_buckets,
+            covered_buckets,
+            completeness_pct: completeness_pct(covered_buckets, expected_buckets),
+            gaps,
+        })
+    }
+}
+
+fn completeness_pct(covered_buckets: i64, expected_buckets: i64) -> f64 {
+    let pct = covered_buckets as f64 * 100.0 / expected_buckets as f64;
+    (pct * 100.0).round() / 100.0
 }
 
 fn numeric_value(value: &PointValue) -> Option<f64> {
@@ -171,17 +289,21 @@ fn aggregate_values(
     ctx: &TenantContext,
     project_id: &str,
     point_id: &str,
-    order: TimeOrder,
-    cursor_ts_ms: Option<i64>,
+    options: &MeasurementsQueryOptions,
 ) -> Vec<MeasurementRecord> {
-    if aggregation.bucket_ms <= 0 {
+    let order = options.order;
+    let cursor_ts_ms = options.cursor_ts_ms;
+    if aggregation.calendar.is_none() && aggregation.bucket_ms <= 0 {
         return Vec::new();
     }
     let bucket_ms = aggregation.bucket_ms;
     let mut buckets: std::collections::BTreeMap<i64, Vec<&PointValue>> =
         std::collections::BTreeMap::new();
     for value in values {
-        let bucket_start = value.ts_ms.div_euclid(bucket_ms) * bucket_ms;
+        let bucket_start = match aggregation.calendar {
+            Some(calendar) => calendar.bucket_start_ms(value.ts_ms, options.timezone),
+            None => value.ts_ms.div_euclid(bucket_ms) * bucket_ms,
+        };
         buckets.entry(bucket_start).or_default().push(value);
     }
 
@@ -5,33 +5,93 @@
 //! 包含以下实现：
 //! - UserStore: InMemoryUserStore
 //! - ProjectStore: InMemoryProjectStore
+//! - PortfolioStore: InMemoryPortfolioStore
 //! - GatewayStore: InMemoryGatewayStore
 //! - DeviceStore: InMemoryDeviceStore
 //! - PointStore: InMemoryPointStore
 //! - PointMappingStore: InMemoryPointMappingStore
+//! - DeviceTemplateStore: InMemoryDeviceTemplateStore
+//! - ProjectCloneStore: InMemoryProjectCloneStore
+//! - GatewayConfigStore: InMemoryGatewayConfigStore
+//! - DeviceShadowStore: InMemoryDeviceShadowStore
+//! - FirmwareStore: InMemoryFirmwareStore
+//! - MaintenanceStore: InMemoryMaintenanceStore
+//! - WebhookSubscriptionStore: InMemoryWebhookSubscriptionStore
+//! - ShareTokenStore: InMemoryShareTokenStore
+//! - RuleStore: InMemoryRuleStore
+//! - ScheduleStore: InMemoryScheduleStore
+//! - DemandResponseStore: InMemoryDemandResponseStore
+//! - AnomalyStore: InMemoryAnomalyStore
+//! - DeviceEventStore: InMemoryDeviceEventStore
+//! - EmissionFactorStore: InMemoryEmissionFactorStore
+//! - IdempotencyStore: InMemoryIdempotencyStore
+//! - FeatureFlagStore: InMemoryFeatureFlagStore
+//! - UsageStore: InMemoryUsageStore
+//! - JobStore: InMemoryJobStore
+//! - TenantStore: InMemoryTenantStore
 
+pub mod anomaly;
 pub mod audit;
 pub mod command;
 pub mod command_receipt;
+pub mod demand_response;
 pub mod device;
+pub mod device_event;
+pub mod device_shadow;
+pub mod device_template;
+pub mod emission_factor;
+pub mod feature_flag;
+pub mod firmware;
 pub mod gateway;
+pub mod gateway_config;
+pub mod idempotency;
+pub mod job;
+pub mod maintenance;
 pub mod measurement;
 pub mod online;
 pub mod point;
 pub mod point_mapping;
+pub mod portfolio;
 pub mod project;
+pub mod project_clone;
 pub mod realtime;
+pub mod rule;
+pub mod schedule;
+pub mod share_token;
+pub mod tenant;
+pub mod usage;
 pub mod user;
+pub mod webhook;
 
+pub use anomaly::*;
 pub use audit::*;
 pub use command::*;
 pub use command_receipt::*;
+pub use demand_response::*;
 pub use device::*;
+pub use device_event::*;
+pub use device_shadow::*;
+pub use device_template::*;
+pub use emission_factor::*;
+pub use feature_flag::*;
+pub use firmware::*;
 pub use gateway::*;
+pub use gateway_config::*;
+pub use idempotency::*;
+pub use job::*;
+pub use maintenance::*;
 pub use measurement::*;
 pub use online::*;
 pub use point::*;
 pub use point_mapping::*;
+pub use portfolio::*;
 pub use project::*;
+pub use project_clone::*;
 pub use realtime::*;
+pub use rule::*;
+pub use schedule::*;
+pub use share_token::*;
+pub use tenant::*;
+pub use usage::*;
 pub use user::*;
+pub use webhook::*;
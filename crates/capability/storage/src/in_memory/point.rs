@@ -8,18 +8,21 @@
 //! - 租户隔离验证
 
 use crate::error::StorageError;
-use crate::models::{PointRecord, PointUpdate};
+use crate::models::{CollectionVersion, PointRecord, PointUpdate};
 use crate::traits::PointStore;
-use crate::validation::ensure_project_scope;
+use crate::validation::{ensure_project_scope, ensure_tenant};
 use domain::TenantContext;
 use std::collections::HashMap;
 use std::sync::RwLock;
+use std::sync::atomic::{AtomicI64, Ordering};
 
 /// 点位内存存储
 ///
 /// 使用 RwLock + HashMap 提供线程安全的内存存储。
 pub struct InMemoryPointStore {
     points: RwLock<HashMap<String, PointRecord>>,
+    /// 变更计数（创建 / 更新 / 删除时递增），作为集合版本的变更标记
+    revision: AtomicI64,
 }
 
 impl InMemoryPointStore {
@@ -27,6 +30,7 @@ impl InMemoryPointStore {
     pub fn new() -> Self {
         Self {
             points: RwLock::new(HashMap::new()),
+            revision: AtomicI64::new(0),
         }
     }
 }
@@ -53,6 +57,27 @@ impl PointStore for InMemoryPointStore {
         Ok(items)
     }
 
+    /// 指定项目点集合的版本
+    async fn points_version(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<CollectionVersion, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let map = self
+            .points
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let count = map
+            .values()
+            .filter(|item| item.tenant_id == ctx.tenant_id && item.project_id == project_id)
+            .count();
+        Ok(CollectionVersion {
+            count: count as i64,
+            revision: self.revision.load(Ordering::Relaxed),
+        })
+    }
+
     /// 查找指定点
     async fn find_point(
         &self,
@@ -78,16 +103,17 @@ impl PointStore for InMemoryPointStore {
     ) -> Result<PointRecord, StorageError> {
         ensure_project_scope(ctx, &record.project_id)?;
         if record.tenant_id != ctx.tenant_id {
-            return Err(StorageError::new("tenant mismatch"));
+            return Err(StorageError::forbidden("tenant mismatch"));
         }
         let mut map = self
             .points
             .write()
             .map_err(|_| StorageError::new("lock failed"))?;
         if map.contains_key(&record.point_id) {
-            return Err(StorageError::new("point exists"));
+            return Err(StorageError::conflict("point exists"));
         }
         map.insert(record.point_id.clone(), record.clone());
+        self.revision.fetch_add(1, Ordering::Relaxed);
         Ok(record)
     }
 
@@ -120,6 +146,10 @@ impl PointStore for InMemoryPointStore {
         if let Some(unit) = update.unit {
             point.unit = Some(unit);
         }
+        if let Some(tags) = update.tags {
+            point.tags = tags;
+        }
+        self.revision.fetch_add(1, Ordering::Relaxed);
         Ok(Some(point.clone()))
     }
 
@@ -138,9 +168,38 @@ impl PointStore for InMemoryPointStore {
         match map.get(point_id) {
             Some(item) if item.tenant_id == ctx.tenant_id && item.project_id == project_id => {
                 map.remove(point_id);
+                self.revision.fetch_add(1, Ordering::Relaxed);
                 Ok(true)
             }
             _ => Ok(false),
         }
     }
+
+    /// 查询全部租户带指定标签的点位
+    async fn list_points_with_tag(&self, tag: &str) -> Result<Vec<PointRecord>, StorageError> {
+        let map = self
+            .points
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let mut items: Vec<PointRecord> = map
+            .values()
+            .filter(|item| item.tags.iter().any(|value| value == tag))
+            .cloned()
+            .collect();
+        items.sort_by(|a, b| a.point_id.cmp(&b.point_id));
+        Ok(items)
+    }
+
+    /// 统计租户点位数
+    async fn count_points(&self, ctx: &TenantContext) -> Result<i64, StorageError> {
+        ensure_tenant(ctx)?;
+        let map = self
+            .points
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        Ok(map
+            .values()
+            .filter(|item| item.tenant_id == ctx.tenant_id)
+            .count() as i64)
+    }
 }
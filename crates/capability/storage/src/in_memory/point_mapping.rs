@@ -6,20 +6,24 @@
 //! - 点映射 CRUD 操作
 //! - 项目级资源过滤
 //! - 租户隔离验证
+//! - 同一项目内 (source_type, address) 唯一（对应 Postgres 唯一索引）
 
 use crate::error::StorageError;
-use crate::models::{PointMappingRecord, PointMappingUpdate};
+use crate::models::{CollectionVersion, PointMappingRecord, PointMappingUpdate};
 use crate::traits::PointMappingStore;
 use crate::validation::ensure_project_scope;
 use domain::TenantContext;
 use std::collections::HashMap;
 use std::sync::RwLock;
+use std::sync::atomic::{AtomicI64, Ordering};
 
 /// 点位映射内存存储
 ///
 /// 使用 RwLock + HashMap 提供线程安全的内存存储。
 pub struct InMemoryPointMappingStore {
     mappings: RwLock<HashMap<String, PointMappingRecord>>,
+    /// 变更计数（创建 / 更新 / 删除时递增），作为集合版本的变更标记
+    revision: AtomicI64,
 }
 
 impl InMemoryPointMappingStore {
@@ -27,6 +31,7 @@ impl InMemoryPointMappingStore {
     pub fn new() -> Self {
         Self {
             mappings: RwLock::new(HashMap::new()),
+            revision: AtomicI64::new(0),
         }
     }
 }
@@ -53,6 +58,27 @@ impl PointMappingStore for InMemoryPointMappingStore {
         Ok(items)
     }
 
+    /// 指定项目点映射集合的版本
+    async fn point_mappings_version(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<CollectionVersion, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let map = self
+            .mappings
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let count = map
+            .values()
+            .filter(|item| item.tenant_id == ctx.tenant_id && item.project_id == project_id)
+            .count();
+        Ok(CollectionVersion {
+            count: count as i64,
+            revision: self.revision.load(Ordering::Relaxed),
+        })
+    }
+
     /// 查找指定点映射
     async fn find_point_mapping(
         &self,
@@ -78,16 +104,20 @@ impl PointMappingStore for InMemoryPointMappingStore {
     ) -> Result<PointMappingRecord, StorageError> {
         ensure_project_scope(ctx, &record.project_id)?;
         if record.tenant_id != ctx.tenant_id {
-            return Err(StorageError::new("tenant mismatch"));
+            return Err(StorageError::forbidden("tenant mismatch"));
         }
         let mut map = self
             .mappings
             .write()
             .map_err(|_| StorageError::new("lock failed"))?;
         if map.contains_key(&record.source_id) {
-            return Err(StorageError::new("mapping exists"));
+            return Err(StorageError::conflict("mapping exists"));
+        }
+        if address_taken(&map, &record, None) {
+            return Err(StorageError::conflict("mapping address exists"));
         }
         map.insert(record.source_id.clone(), record.clone());
+        self.revision.fetch_add(1, Ordering::Relaxed);
         Ok(record)
     }
 
@@ -104,13 +134,25 @@ impl PointMappingStore for InMemoryPointMappingStore {
             .mappings
             .write()
             .map_err(|_| StorageError::new("lock failed"))?;
-        let mapping = match map.get_mut(source_id) {
-            Some(mapping) => mapping,
+        let mut candidate = match map.get(source_id) {
+            Some(mapping) => mapping.clone(),
             None => return Ok(None),
         };
-        if mapping.tenant_id != ctx.tenant_id || mapping.project_id != project_id {
+        if candidate.tenant_id != ctx.tenant_id || candidate.project_id != project_id {
             return Ok(None);
         }
+        if let Some(source_type) = update.source_type.clone() {
+            candidate.source_type = source_type;
+        }
+        if let Some(address) = update.address.clone() {
+            candidate.address = address;
+        }
+        if address_taken(&map, &candidate, Some(source_id)) {
+            return Err(StorageError::conflict("mapping address exists"));
+        }
+        let Some(mapping) = map.get_mut(source_id) else {
+            return Ok(None);
+        };
         if let Some(source_type) = update.source_type {
             mapping.source_type = source_type;
         }
@@ -123,6 +165,7 @@ impl PointMappingStore for InMemoryPointMappingStore {
         if let Some(offset) = update.offset {
             mapping.offset = Some(offset);
         }
+        self.revision.fetch_add(1, Ordering::Relaxed);
         Ok(Some(mapping.clone()))
     }
 
@@ -141,9 +184,25 @@ impl PointMappingStore for InMemoryPointMappingStore {
         match map.get(source_id) {
             Some(item) if item.tenant_id == ctx.tenant_id && item.project_id == project_id => {
                 map.remove(source_id);
+                self.revision.fetch_add(1, Ordering::Relaxed);
                 Ok(true)
             }
             _ => Ok(false),
         }
     }
 }
+
+/// 同项目内是否已有其他映射占用相同的 (source_type, address)
+fn address_taken(
+    map: &HashMap<String, PointMappingRecord>,
+    record: &PointMappingRecord,
+    exclude_source_id: Option<&str>,
+) -> bool {
+    map.values().any(|item| {
+        Some(item.source_id.as_str()) != exclude_source_id
+            && item.tenant_id == record.tenant_id
+            && item.project_id == record.project_id
+            && item.source_type == record.source_type
+            && item.address == record.address
+    })
+}
@@ -0,0 +1,139 @@
+//! 项目组合内存存储实现
+//!
+//! 仅用于本地测试和占位。
+
+use crate::error::StorageError;
+use crate::models::{PortfolioRecord, PortfolioUpdate};
+use crate::traits::PortfolioStore;
+use crate::validation::ensure_tenant;
+use domain::TenantContext;
+use std::sync::RwLock;
+
+/// 项目组合内存存储
+pub struct InMemoryPortfolioStore {
+    portfolios: RwLock<Vec<PortfolioRecord>>,
+}
+
+impl InMemoryPortfolioStore {
+    /// 创建新的项目组合存储
+    pub fn new() -> Self {
+        Self {
+            portfolios: RwLock::new(Vec::new()),
+        }
+    }
+}
+
+impl Default for InMemoryPortfolioStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn same_portfolio(item: &PortfolioRecord, tenant_id: &str, portfolio_id: &str) -> bool {
+    item.tenant_id == tenant_id && item.portfolio_id == portfolio_id
+}
+
+#[async_trait::async_trait]
+impl PortfolioStore for InMemoryPortfolioStore {
+    async fn list_portfolios(
+        &self,
+        ctx: &TenantContext,
+    ) -> Result<Vec<PortfolioRecord>, StorageError> {
+        ensure_tenant(ctx)?;
+        let portfolios = self
+            .portfolios
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let mut items: Vec<PortfolioRecord> = portfolios
+            .iter()
+            .filter(|item| item.tenant_id == ctx.tenant_id)
+            .cloned()
+            .collect();
+        items.sort_by_key(|item| std::cmp::Reverse(item.created_at_ms));
+        Ok(items)
+    }
+
+    async fn find_portfolio(
+        &self,
+        ctx: &TenantContext,
+        portfolio_id: &str,
+    ) -> Result<Option<PortfolioRecord>, StorageError> {
+        ensure_tenant(ctx)?;
+        let portfolios = self
+            .portfolios
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        Ok(portfolios
+            .iter()
+            .find(|item| same_portfolio(item, &ctx.tenant_id, portfolio_id))
+            .cloned())
+    }
+
+    async fn create_portfolio(
+        &self,
+        ctx: &TenantContext,
+        record: PortfolioRecord,
+    ) -> Result<PortfolioRecord, StorageError> {
+        ensure_tenant(ctx)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let mut portfolios = self
+            .portfolios
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        if portfolios
+            .iter()
+            .any(|item| same_portfolio(item, &record.tenant_id, &record.portfolio_id))
+        {
+            return Err(StorageError::conflict("portfolio exists"));
+        }
+        portfolios.push(record.clone());
+        Ok(record)
+    }
+
+    async fn update_portfolio(
+        &self,
+        ctx: &TenantContext,
+        portfolio_id: &str,
+        update: PortfolioUpdate,
+    ) -> Result<Option<PortfolioRecord>, StorageError> {
+        ensure_tenant(ctx)?;
+        let mut portfolios = self
+            .portfolios
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let Some(portfolio) = portfolios
+            .iter_mut()
+            .find(|item| same_portfolio(item, &ctx.tenant_id, portfolio_id))
+        else {
+            return Ok(None);
+        };
+        if let Some(name) = update.name {
+            portfolio.name = name;
+        }
+        if let Some(description) = update.description {
+            portfolio.description = Some(description);
+        }
+        if let Some(project_ids) = update.project_ids {
+            portfolio.project_ids = project_ids;
+        }
+        portfolio.updated_at_ms = update.updated_at_ms;
+        Ok(Some(portfolio.clone()))
+    }
+
+    async fn delete_portfolio(
+        &self,
+        ctx: &TenantContext,
+        portfolio_id: &str,
+    ) -> Result<bool, StorageError> {
+        ensure_tenant(ctx)?;
+        let mut portfolios = self
+            .portfolios
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let before = portfolios.len();
+        portfolios.retain(|item| !same_portfolio(item, &ctx.tenant_id, portfolio_id));
+        Ok(portfolios.len() != before)
+    }
+}
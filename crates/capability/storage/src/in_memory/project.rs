@@ -8,18 +8,21 @@
 //! - 租户隔离验证
 
 use crate::error::StorageError;
-use crate::models::{ProjectRecord, ProjectUpdate};
+use crate::models::{CollectionVersion, ProjectRecord, ProjectUpdate};
 use crate::traits::ProjectStore;
 use crate::validation::ensure_tenant;
 use domain::TenantContext;
 use std::collections::HashMap;
 use std::sync::RwLock;
+use std::sync::atomic::{AtomicI64, Ordering};
 
 /// 项目内存存储
 ///
 /// 使用 RwLock + HashMap 提供线程安全的内存存储。
 pub struct InMemoryProjectStore {
     projects: RwLock<HashMap<String, ProjectRecord>>,
+    /// 变更计数（创建 / 更新 / 删除时递增），作为集合版本的变更标记
+    revision: AtomicI64,
 }
 
 impl InMemoryProjectStore {
@@ -39,6 +42,7 @@ impl InMemoryProjectStore {
         );
         Self {
             projects: RwLock::new(projects),
+            revision: AtomicI64::new(0),
         }
     }
 }
@@ -61,6 +65,26 @@ impl ProjectStore for InMemoryProjectStore {
         Ok(projects)
     }
 
+    /// 当前租户项目集合的版本
+    async fn projects_version(
+        &self,
+        ctx: &TenantContext,
+    ) -> Result<CollectionVersion, StorageError> {
+        ensure_tenant(ctx)?;
+        let map = self
+            .projects
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let count = map
+            .values()
+            .filter(|project| project.tenant_id == ctx.tenant_id)
+            .count();
+        Ok(CollectionVersion {
+            count: count as i64,
+            revision: self.revision.load(Ordering::Relaxed),
+        })
+    }
+
     /// 查找指定项目
     async fn find_project(
         &self,
@@ -85,16 +109,17 @@ impl ProjectStore for InMemoryProjectStore {
     ) -> Result<ProjectRecord, StorageError> {
         ensure_tenant(ctx)?;
         if record.tenant_id != ctx.tenant_id {
-            return Err(StorageError::new("tenant mismatch"));
+            return Err(StorageError::forbidden("tenant mismatch"));
         }
         let mut map = self
             .projects
             .write()
             .map_err(|_| StorageError::new("lock failed"))?;
         if map.contains_key(&record.project_id) {
-            return Err(StorageError::new("project exists"));
+            return Err(StorageError::conflict("project exists"));
         }
         map.insert(record.project_id.clone(), record.clone());
+        self.revision.fetch_add(1, Ordering::Relaxed);
         Ok(record)
     }
 
@@ -123,6 +148,7 @@ impl ProjectStore for InMemoryProjectStore {
         if let Some(timezone) = update.timezone {
             project.timezone = timezone;
         }
+        self.revision.fetch_add(1, Ordering::Relaxed);
         Ok(Some(project.clone()))
     }
 
@@ -140,6 +166,7 @@ impl ProjectStore for InMemoryProjectStore {
         match map.get(project_id) {
             Some(project) if project.tenant_id == ctx.tenant_id => {
                 map.remove(project_id);
+                self.revision.fetch_add(1, Ordering::Relaxed);
                 Ok(true)
             }
             _ => Ok(false),
@@ -0,0 +1,183 @@
+//! 项目克隆内存存储实现
+//!
+//! 仅用于本地 M0 演示和测试。
+//!
+//! 功能：
+//! - 依次写入新项目、网关（含命令载荷格式）、设备、点位、点位映射、设备模板与规则
+//! - 任一写入失败时按逆序删除已写入部分（内存实现无事务）
+//! - 租户隔离验证
+
+use crate::error::StorageError;
+use crate::models::ProjectClone;
+use crate::traits::{
+    DeviceStore, DeviceTemplateStore, GatewayStore, PointMappingStore, PointStore,
+    ProjectCloneStore, ProjectStore, RuleStore,
+};
+use crate::validation::ensure_tenant;
+use domain::TenantContext;
+use std::sync::Arc;
+
+/// 项目克隆内存存储
+///
+/// 自身不保存数据，克隆结果写入给定的资产存储。
+pub struct InMemoryProjectCloneStore {
+    project_store: Arc<dyn ProjectStore>,
+    gateway_store: Arc<dyn GatewayStore>,
+    device_store: Arc<dyn DeviceStore>,
+    point_store: Arc<dyn PointStore>,
+    point_mapping_store: Arc<dyn PointMappingStore>,
+    device_template_store: Arc<dyn DeviceTemplateStore>,
+    rule_store: Arc<dyn RuleStore>,
+}
+
+/// 已写入的记录 ID（用于回滚）
+#[derive(Default)]
+struct Written {
+    project: bool,
+    gateways: Vec<String>,
+    devices: Vec<String>,
+    points: Vec<String>,
+    mappings: Vec<String>,
+    templates: Vec<String>,
+    rules: Vec<String>,
+}
+
+impl InMemoryProjectCloneStore {
+    /// 创建项目克隆存储（克隆结果写入给定的资产存储）
+    pub fn new(
+        project_store: Arc<dyn ProjectStore>,
+        gateway_store: Arc<dyn GatewayStore>,
+        device_store: Arc<dyn DeviceStore>,
+        point_store: Arc<dyn PointStore>,
+        point_mapping_store: Arc<dyn PointMappingStore>,
+        device_template_store: Arc<dyn DeviceTemplateStore>,
+        rule_store: Arc<dyn RuleStore>,
+    ) -> Self {
+        Self {
+            project_store,
+            gateway_store,
+            device_store,
+            point_store,
+            point_mapping_store,
+            device_template_store,
+            rule_store,
+        }
+    }
+
+    async fn write(
+        &self,
+        ctx: &TenantContext,
+        clone: &ProjectClone,
+        written: &mut Written,
+    ) -> Result<(), StorageError> {
+        self.project_store
+            .create_project(ctx, clone.project.clone())
+            .await?;
+        written.project = true;
+        for gateway in &clone.gateways {
+            self.gateway_store
+                .create_gateway(ctx, gateway.clone())
+                .await?;
+            written.gateways.push(gateway.gateway_id.clone());
+        }
+        for (gateway_id, format) in &clone.gateway_command_formats {
+            self.gateway_store
+                .set_gateway_command_format(
+                    ctx,
+                    &clone.project.project_id,
+                    gateway_id,
+                    Some(format),
+                )
+                .await?;
+        }
+        for device in &clone.devices {
+            self.device_store.create_device(ctx, device.clone()).await?;
+            written.devices.push(device.device_id.clone());
+        }
+        for point in &clone.points {
+            self.point_store.create_point(ctx, point.clone()).await?;
+            written.points.push(point.point_id.clone());
+        }
+        for mapping in &clone.mappings {
+            self.point_mapping_store
+                .create_point_mapping(ctx, mapping.clone())
+                .await?;
+            written.mappings.push(mapping.source_id.clone());
+        }
+        for template in &clone.device_templates {
+            self.device_template_store
+                .create_device_template(ctx, template.clone())
+                .await?;
+            written.templates.push(template.template_id.clone());
+        }
+        for rule in &clone.rules {
+            self.rule_store.create_rule(ctx, rule.clone()).await?;
+            written.rules.push(rule.rule_id.clone());
+        }
+        Ok(())
+    }
+
+    /// 回滚已写入的部分（按逆序删除）。
+    async fn rollback(&self, ctx: &TenantContext, project_id: &str, written: Written) {
+        for rule_id in &written.rules {
+            let _ = self.rule_store.delete_rule(ctx, project_id, rule_id).await;
+        }
+        for template_id in &written.templates {
+            let _ = self
+                .device_template_store
+                .delete_device_template(ctx, project_id, template_id)
+                .await;
+        }
+        for source_id in &written.mappings {
+            let _ = self
+                .point_mapping_store
+                .delete_point_mapping(ctx, project_id, source_id)
+                .await;
+        }
+        for point_id in &written.points {
+            let _ = self
+                .point_store
+                .delete_point(ctx, project_id, point_id)
+                .await;
+        }
+        for device_id in &written.devices {
+            let _ = self
+                .device_store
+                .delete_device(ctx, project_id, device_id)
+                .await;
+        }
+        for gateway_id in &written.gateways {
+            let _ = self
+                .gateway_store
+                .delete_gateway(ctx, project_id, gateway_id)
+                .await;
+        }
+        if written.project {
+            let _ = self.project_store.delete_project(ctx, project_id).await;
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ProjectCloneStore for InMemoryProjectCloneStore {
+    async fn create_project_clone(
+        &self,
+        ctx: &TenantContext,
+        clone: ProjectClone,
+    ) -> Result<ProjectClone, StorageError> {
+        ensure_tenant(ctx)?;
+        if clone.project.tenant_id != ctx.tenant_id || !clone.is_scoped_to_project() {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        // 写入使用新项目作用域
+        let mut scoped = ctx.clone();
+        scoped.project_scope = Some(clone.project.project_id.clone());
+        let mut written = Written::default();
+        if let Err(err) = self.write(&scoped, &clone, &mut written).await {
+            self.rollback(&scoped, &clone.project.project_id, written)
+                .await;
+            return Err(err);
+        }
+        Ok(clone)
+    }
+}
@@ -45,7 +45,7 @@ impl RealtimeStore for InMemoryRealtimeStore {
     ) -> Result<(), StorageError> {
         ensure_project_scope(ctx, &value.project_id)?;
         if value.tenant_id != ctx.tenant_id {
-            return Err(StorageError::new("tenant mismatch"));
+            return Err(StorageError::forbidden("tenant mismatch"));
         }
         let mut values = self
             .last_values
@@ -117,4 +117,19 @@ impl RealtimeStore for InMemoryRealtimeStore {
         }
         Ok(items)
     }
+
+    async fn get_last_values(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        point_ids: &[String],
+    ) -> Result<Vec<RealtimeRecord>, StorageError> {
+        let mut items = Vec::with_capacity(point_ids.len());
+        for point_id in point_ids {
+            if let Some(item) = self.get_last_value(ctx, project_id, point_id).await? {
+                items.push(item);
+            }
+        }
+        Ok(items)
+    }
 }
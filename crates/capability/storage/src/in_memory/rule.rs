@@ -0,0 +1,213 @@
+//! 自动化规则与执行记录内存存储实现
+//!
+//! 仅用于本地测试和占位。
+
+use crate::error::StorageError;
+use crate::models::{RuleExecutionRecord, RuleRecord, RuleUpdate};
+use crate::traits::RuleStore;
+use crate::validation::ensure_project_scope;
+use domain::TenantContext;
+use std::sync::RwLock;
+
+/// 自动化规则内存存储
+pub struct InMemoryRuleStore {
+    rules: RwLock<Vec<RuleRecord>>,
+    executions: RwLock<Vec<RuleExecutionRecord>>,
+}
+
+impl InMemoryRuleStore {
+    /// 创建新的规则存储
+    pub fn new() -> Self {
+        Self {
+            rules: RwLock::new(Vec::new()),
+            executions: RwLock::new(Vec::new()),
+        }
+    }
+}
+
+impl Default for InMemoryRuleStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn same_rule(item: &RuleRecord, tenant_id: &str, project_id: &str, rule_id: &str) -> bool {
+    item.tenant_id == tenant_id && item.project_id == project_id && item.rule_id == rule_id
+}
+
+#[async_trait::async_trait]
+impl RuleStore for InMemoryRuleStore {
+    async fn list_rules(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<Vec<RuleRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let rules = self
+            .rules
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let mut items: Vec<RuleRecord> = rules
+            .iter()
+            .filter(|item| item.tenant_id == ctx.tenant_id && item.project_id == project_id)
+            .cloned()
+            .collect();
+        items.sort_by_key(|item| std::cmp::Reverse(item.created_at_ms));
+        Ok(items)
+    }
+
+    async fn find_rule(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        rule_id: &str,
+    ) -> Result<Option<RuleRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let rules = self
+            .rules
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        Ok(rules
+            .iter()
+            .find(|item| same_rule(item, &ctx.tenant_id, project_id, rule_id))
+            .cloned())
+    }
+
+    async fn create_rule(
+        &self,
+        ctx: &TenantContext,
+        record: RuleRecord,
+    ) -> Result<RuleRecord, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let mut rules = self
+            .rules
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        if rules
+            .iter()
+            .any(|item| same_rule(item, &record.tenant_id, &record.project_id, &record.rule_id))
+        {
+            return Err(StorageError::conflict("rule exists"));
+        }
+        rules.push(record.clone());
+        Ok(record)
+    }
+
+    async fn update_rule(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        rule_id: &str,
+        update: RuleUpdate,
+    ) -> Result<Option<RuleRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let mut rules = self
+            .rules
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let Some(rule) = rules
+            .iter_mut()
+            .find(|item| same_rule(item, &ctx.tenant_id, project_id, rule_id))
+        else {
+            return Ok(None);
+        };
+        if let Some(name) = update.name {
+            rule.name = name;
+        }
+        if let Some(enabled) = update.enabled {
+            rule.enabled = enabled;
+        }
+        if let Some(trigger) = update.trigger {
+            rule.trigger = trigger;
+        }
+        if let Some(actions) = update.actions {
+            rule.actions = actions;
+        }
+        rule.updated_at_ms = update.updated_at_ms;
+        Ok(Some(rule.clone()))
+    }
+
+    async fn delete_rule(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        rule_id: &str,
+    ) -> Result<bool, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let mut rules = self
+            .rules
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let before = rules.len();
+        rules.retain(|item| !same_rule(item, &ctx.tenant_id, project_id, rule_id));
+        let deleted = rules.len() != before;
+        if deleted {
+            let mut executions = self
+                .executions
+                .write()
+                .map_err(|_| StorageError::new("lock failed"))?;
+            executions.retain(|item| {
+                !(item.tenant_id == ctx.tenant_id
+                    && item.project_id == project_id
+                    && item.rule_id == rule_id)
+            });
+        }
+        Ok(deleted)
+    }
+
+    async fn list_enabled_rules(&self) -> Result<Vec<RuleRecord>, StorageError> {
+        let rules = self
+            .rules
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        Ok(rules.iter().filter(|item| item.enabled).cloned().collect())
+    }
+
+    async fn create_rule_execution(
+        &self,
+        ctx: &TenantContext,
+        record: RuleExecutionRecord,
+    ) -> Result<RuleExecutionRecord, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let mut executions = self
+            .executions
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        executions.push(record.clone());
+        Ok(record)
+    }
+
+    async fn list_rule_executions(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        rule_id: &str,
+        limit: i64,
+    ) -> Result<Vec<RuleExecutionRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let executions = self
+            .executions
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let mut items: Vec<RuleExecutionRecord> = executions
+            .iter()
+            .filter(|item| {
+                item.tenant_id == ctx.tenant_id
+                    && item.project_id == project_id
+                    && item.rule_id == rule_id
+            })
+            .cloned()
+            .collect();
+        items.sort_by_key(|item| std::cmp::Reverse(item.executed_at_ms));
+        if limit > 0 {
+            items.truncate(limit as usize);
+        }
+        Ok(items)
+    }
+}
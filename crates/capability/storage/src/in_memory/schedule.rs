@@ -0,0 +1,258 @@
+//! 控制计划与执行记录内存存储实现
+//!
+//! 仅用于本地测试和占位。
+
+use crate::error::StorageError;
+use crate::models::{ScheduleExecutionRecord, ScheduleRecord, ScheduleUpdate};
+use crate::traits::ScheduleStore;
+use crate::validation::ensure_project_scope;
+use domain::TenantContext;
+use std::sync::RwLock;
+
+/// 控制计划内存存储
+pub struct InMemoryScheduleStore {
+    schedules: RwLock<Vec<ScheduleRecord>>,
+    executions: RwLock<Vec<ScheduleExecutionRecord>>,
+}
+
+impl InMemoryScheduleStore {
+    /// 创建新的计划存储
+    pub fn new() -> Self {
+        Self {
+            schedules: RwLock::new(Vec::new()),
+            executions: RwLock::new(Vec::new()),
+        }
+    }
+}
+
+impl Default for InMemoryScheduleStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn same_schedule(
+    item: &ScheduleRecord,
+    tenant_id: &str,
+    project_id: &str,
+    schedule_id: &str,
+) -> bool {
+    item.tenant_id == tenant_id && item.project_id == project_id && item.schedule_id == schedule_id
+}
+
+#[async_trait::async_trait]
+impl ScheduleStore for InMemoryScheduleStore {
+    async fn list_schedules(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<Vec<ScheduleRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let schedules = self
+            .schedules
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let mut items: Vec<ScheduleRecord> = schedules
+            .iter()
+            .filter(|item| item.tenant_id == ctx.tenant_id && item.project_id == project_id)
+            .cloned()
+            .collect();
+        items.sort_by_key(|item| std::cmp::Reverse(item.created_at_ms));
+        Ok(items)
+    }
+
+    async fn find_schedule(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        schedule_id: &str,
+    ) -> Result<Option<ScheduleRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let schedules = self
+            .schedules
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        Ok(schedules
+            .iter()
+            .find(|item| same_schedule(item, &ctx.tenant_id, project_id, schedule_id))
+            .cloned())
+    }
+
+    async fn create_schedule(
+        &self,
+        ctx: &TenantContext,
+        record: ScheduleRecord,
+    ) -> Result<ScheduleRecord, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let mut schedules = self
+            .schedules
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        if schedules.iter().any(|item| {
+            same_schedule(
+                item,
+                &record.tenant_id,
+                &record.project_id,
+                &record.schedule_id,
+            )
+        }) {
+            return Err(StorageError::conflict("schedule exists"));
+        }
+        schedules.push(record.clone());
+        Ok(record)
+    }
+
+    async fn update_schedule(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        schedule_id: &str,
+        update: ScheduleUpdate,
+    ) -> Result<Option<ScheduleRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let mut schedules = self
+            .schedules
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let Some(schedule) = schedules
+            .iter_mut()
+            .find(|item| same_schedule(item, &ctx.tenant_id, project_id, schedule_id))
+        else {
+            return Ok(None);
+        };
+        if let Some(name) = update.name {
+            schedule.name = name;
+        }
+        if let Some(enabled) = update.enabled {
+            schedule.enabled = enabled;
+        }
+        if let Some(timezone) = update.timezone {
+            schedule.timezone = timezone;
+        }
+        if let Some(spec) = update.spec {
+            schedule.spec = spec;
+        }
+        if let Some(policy) = update.missed_run_policy {
+            schedule.missed_run_policy = policy;
+        }
+        if let Some(evaluated_at_ms) = update.last_evaluated_at_ms {
+            schedule.last_evaluated_at_ms = evaluated_at_ms;
+        }
+        schedule.updated_at_ms = update.updated_at_ms;
+        Ok(Some(schedule.clone()))
+    }
+
+    async fn delete_schedule(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        schedule_id: &str,
+    ) -> Result<bool, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let mut schedules = self
+            .schedules
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let before = schedules.len();
+        schedules.retain(|item| !same_schedule(item, &ctx.tenant_id, project_id, schedule_id));
+        let deleted = schedules.len() != before;
+        if deleted {
+            let mut executions = self
+                .executions
+                .write()
+                .map_err(|_| StorageError::new("lock failed"))?;
+            executions.retain(|item| {
+                !(item.tenant_id == ctx.tenant_id
+                    && item.project_id == project_id
+                    && item.schedule_id == schedule_id)
+            });
+        }
+        Ok(deleted)
+    }
+
+    async fn list_enabled_schedules(&self) -> Result<Vec<ScheduleRecord>, StorageError> {
+        let schedules = self
+            .schedules
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        Ok(schedules
+            .iter()
+            .filter(|item| item.enabled)
+            .cloned()
+            .collect())
+    }
+
+    async fn mark_schedule_evaluated(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        schedule_id: &str,
+        evaluated_at_ms: i64,
+    ) -> Result<(), StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let mut schedules = self
+            .schedules
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        if let Some(schedule) = schedules
+            .iter_mut()
+            .find(|item| same_schedule(item, &ctx.tenant_id, project_id, schedule_id))
+        {
+            schedule.last_evaluated_at_ms = evaluated_at_ms;
+        }
+        Ok(())
+    }
+
+    async fn create_schedule_execution(
+        &self,
+        ctx: &TenantContext,
+        record: ScheduleExecutionRecord,
+    ) -> Result<ScheduleExecutionRecord, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let mut executions = self
+            .executions
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        executions.push(record.clone());
+        Ok(record)
+    }
+
+    async fn list_schedule_executions(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        schedule_id: &str,
+        limit: i64,
+    ) -> Result<Vec<ScheduleExecutionRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let executions = self
+            .executions
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let mut items: Vec<ScheduleExecutionRecord> = executions
+            .iter()
+            .filter(|item| {
+                item.tenant_id == ctx.tenant_id
+                    && item.project_id == project_id
+                    && item.schedule_id == schedule_id
+            })
+            .cloned()
+            .collect();
+        // 同一时刻执行的记录按计划时刻倒序，保证顺序稳定
+        items.sort_by(|a, b| {
+            b.executed_at_ms
+                .cmp(&a.executed_at_ms)
+                .then(b.scheduled_at_ms.cmp(&a.scheduled_at_ms))
+        });
+        if limit > 0 {
+            items.truncate(limit as usize);
+        }
+        Ok(items)
+    }
+}
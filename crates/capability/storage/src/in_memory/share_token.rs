@@ -0,0 +1,114 @@
+//! 只读数据分享令牌内存存储实现
+//!
+//! 仅用于本地测试和占位。
+
+use crate::error::StorageError;
+use crate::models::ShareTokenRecord;
+use crate::traits::ShareTokenStore;
+use crate::validation::ensure_project_scope;
+use domain::TenantContext;
+use std::sync::RwLock;
+
+/// 分享令牌内存存储
+pub struct InMemoryShareTokenStore {
+    tokens: RwLock<Vec<ShareTokenRecord>>,
+}
+
+impl InMemoryShareTokenStore {
+    /// 创建新的分享令牌存储
+    pub fn new() -> Self {
+        Self {
+            tokens: RwLock::new(Vec::new()),
+        }
+    }
+}
+
+impl Default for InMemoryShareTokenStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl ShareTokenStore for InMemoryShareTokenStore {
+    async fn create_share_token(
+        &self,
+        ctx: &TenantContext,
+        record: ShareTokenRecord,
+    ) -> Result<ShareTokenRecord, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let mut tokens = self
+            .tokens
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        if tokens
+            .iter()
+            .any(|item| item.token_id == record.token_id || item.token_hash == record.token_hash)
+        {
+            return Err(StorageError::conflict("share token exists"));
+        }
+        tokens.push(record.clone());
+        Ok(record)
+    }
+
+    async fn list_share_tokens(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<Vec<ShareTokenRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let tokens = self
+            .tokens
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let mut items: Vec<ShareTokenRecord> = tokens
+            .iter()
+            .filter(|item| item.tenant_id == ctx.tenant_id && item.project_id == project_id)
+            .cloned()
+            .collect();
+        items.sort_by_key(|item| std::cmp::Reverse(item.created_at_ms));
+        Ok(items)
+    }
+
+    async fn revoke_share_token(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        token_id: &str,
+        revoked_at_ms: i64,
+    ) -> Result<Option<ShareTokenRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let mut tokens = self
+            .tokens
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let Some(token) = tokens.iter_mut().find(|item| {
+            item.tenant_id == ctx.tenant_id
+                && item.project_id == project_id
+                && item.token_id == token_id
+        }) else {
+            return Ok(None);
+        };
+        if token.revoked_at_ms.is_none() {
+            token.revoked_at_ms = Some(revoked_at_ms);
+        }
+        Ok(Some(token.clone()))
+    }
+
+    async fn find_share_token_by_hash(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<ShareTokenRecord>, StorageError> {
+        let tokens = self
+            .tokens
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        Ok(tokens
+            .iter()
+            .find(|item| item.token_hash == token_hash)
+            .cloned())
+    }
+}
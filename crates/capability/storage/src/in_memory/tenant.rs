@@ -0,0 +1,46 @@
+//! 租户内存存储实现
+//!
+//! 仅用于本地测试和占位。
+
+use crate::error::StorageError;
+use crate::traits::TenantStore;
+use crate::validation::ensure_tenant;
+use domain::TenantContext;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// 租户内存存储（key 为 tenant_id，value 为租户名称）
+pub struct InMemoryTenantStore {
+    tenants: RwLock<HashMap<String, String>>,
+}
+
+impl InMemoryTenantStore {
+    /// 创建新的租户存储
+    pub fn new() -> Self {
+        Self {
+            tenants: RwLock::new(HashMap::new()),
+        }
+    }
+}
+
+impl Default for InMemoryTenantStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl TenantStore for InMemoryTenantStore {
+    async fn create_tenant(&self, ctx: &TenantContext, name: &str) -> Result<bool, StorageError> {
+        ensure_tenant(ctx)?;
+        let mut tenants = self
+            .tenants
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        if tenants.contains_key(&ctx.tenant_id) {
+            return Ok(false);
+        }
+        tenants.insert(ctx.tenant_id.clone(), name.to_string());
+        Ok(true)
+    }
+}
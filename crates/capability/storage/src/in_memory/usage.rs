@@ -0,0 +1,175 @@
+//! 用量计量与配额内存存储实现
+//!
+//! 仅用于本地测试和占位。
+
+use crate::error::StorageError;
+use crate::models::{QuotaRecord, UsageRecord};
+use crate::traits::UsageStore;
+use crate::validation::ensure_tenant;
+use domain::TenantContext;
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+/// 用量计量与配额内存存储
+///
+/// 用量 key 为 (tenant_id, period_start_ms, metric)，配额 key 为 (tenant_id, metric)。
+pub struct InMemoryUsageStore {
+    usage: RwLock<BTreeMap<(String, i64, String), i64>>,
+    quotas: RwLock<BTreeMap<(String, String), QuotaRecord>>,
+}
+
+impl InMemoryUsageStore {
+    /// 创建新的用量存储
+    pub fn new() -> Self {
+        Self {
+            usage: RwLock::new(BTreeMap::new()),
+            quotas: RwLock::new(BTreeMap::new()),
+        }
+    }
+}
+
+impl Default for InMemoryUsageStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl UsageStore for InMemoryUsageStore {
+    async fn consume_usage(
+        &self,
+        ctx: &TenantContext,
+        metric: &str,
+        period_start_ms: i64,
+        amount: i64,
+    ) -> Result<Option<i64>, StorageError> {
+        ensure_tenant(ctx)?;
+        let limit = self
+            .quotas
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?
+            .get(&(ctx.tenant_id.clone(), metric.to_string()))
+            .map(|quota| quota.limit);
+        let mut usage = self
+            .usage
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let count = usage
+            .entry((ctx.tenant_id.clone(), period_start_ms, metric.to_string()))
+            .or_insert(0);
+        let next = count.saturating_add(amount);
+        if limit.is_some_and(|limit| next > limit) {
+            return Ok(None);
+        }
+        *count = next;
+        Ok(Some(next))
+    }
+
+    async fn add_usage(
+        &self,
+        ctx: &TenantContext,
+        metric: &str,
+        period_start_ms: i64,
+        amount: i64,
+    ) -> Result<i64, StorageError> {
+        ensure_tenant(ctx)?;
+        let mut usage = self
+            .usage
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let count = usage
+            .entry((ctx.tenant_id.clone(), period_start_ms, metric.to_string()))
+            .or_insert(0);
+        *count = count.saturating_add(amount);
+        Ok(*count)
+    }
+
+    async fn list_usage(
+        &self,
+        ctx: &TenantContext,
+        from_ms: i64,
+        to_ms: i64,
+    ) -> Result<Vec<UsageRecord>, StorageError> {
+        ensure_tenant(ctx)?;
+        let usage = self
+            .usage
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        Ok(usage
+            .iter()
+            .filter(|((tenant_id, period_start_ms, _), count)| {
+                tenant_id == &ctx.tenant_id
+                    && *period_start_ms >= from_ms
+                    && *period_start_ms < to_ms
+                    && **count > 0
+            })
+            .map(
+                |((tenant_id, period_start_ms, metric), count)| UsageRecord {
+                    tenant_id: tenant_id.clone(),
+                    metric: metric.clone(),
+                    period_start_ms: *period_start_ms,
+                    count: *count,
+                },
+            )
+            .collect())
+    }
+
+    async fn list_quotas(&self, ctx: &TenantContext) -> Result<Vec<QuotaRecord>, StorageError> {
+        ensure_tenant(ctx)?;
+        let quotas = self
+            .quotas
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        Ok(quotas
+            .values()
+            .filter(|quota| quota.tenant_id == ctx.tenant_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_quota(
+        &self,
+        ctx: &TenantContext,
+        metric: &str,
+    ) -> Result<Option<QuotaRecord>, StorageError> {
+        ensure_tenant(ctx)?;
+        let quotas = self
+            .quotas
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        Ok(quotas
+            .get(&(ctx.tenant_id.clone(), metric.to_string()))
+            .cloned())
+    }
+
+    async fn upsert_quota(
+        &self,
+        ctx: &TenantContext,
+        record: QuotaRecord,
+    ) -> Result<QuotaRecord, StorageError> {
+        ensure_tenant(ctx)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let mut quotas = self
+            .quotas
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        quotas.insert(
+            (record.tenant_id.clone(), record.metric.clone()),
+            record.clone(),
+        );
+        Ok(record)
+    }
+
+    async fn delete_quota(&self, ctx: &TenantContext, metric: &str) -> Result<bool, StorageError> {
+        ensure_tenant(ctx)?;
+        let mut quotas = self
+            .quotas
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        Ok(quotas
+            .remove(&(ctx.tenant_id.clone(), metric.to_string()))
+            .is_some())
+    }
+}
@@ -215,7 +215,7 @@ impl RbacStore for InMemoryUserStore {
         let mut usernames =
             self.usernames.write().map_err(|_| StorageError::new("lock poisoned"))?;
         if usernames.contains_key(&record.username) {
-            return Err(StorageError::new("username already exists"));
+            return Err(StorageError::conflict("username already exists"));
         }
         let user = UserInternal {
             tenant_id: record.tenant_id.clone(),
@@ -313,7 +313,7 @@ impl RbacStore for InMemoryUserStore {
         let mut roles = self.roles.write().map_err(|_| StorageError::new("lock poisoned"))?;
         let key = tenant_role_key(&record.tenant_id, &record.role_code);
         if roles.contains_key(&key) {
-            return Err(StorageError::new("role already exists"));
+            return Err(StorageError::conflict("role already exists"));
         }
         roles.insert(
             key,
@@ -0,0 +1,166 @@
+//! Webhook 订阅与推送日志内存存储实现
+//!
+//! 仅用于本地测试和占位。
+
+use crate::error::StorageError;
+use crate::models::{WebhookDeliveryRecord, WebhookSubscriptionRecord};
+use crate::traits::{WebhookDeliveryQuery, WebhookSubscriptionStore};
+use crate::validation::ensure_project_scope;
+use domain::TenantContext;
+use std::sync::RwLock;
+
+/// Webhook 订阅内存存储
+pub struct InMemoryWebhookSubscriptionStore {
+    subscriptions: RwLock<Vec<WebhookSubscriptionRecord>>,
+    deliveries: RwLock<Vec<WebhookDeliveryRecord>>,
+}
+
+impl InMemoryWebhookSubscriptionStore {
+    /// 创建新的 Webhook 订阅存储
+    pub fn new() -> Self {
+        Self {
+            subscriptions: RwLock::new(Vec::new()),
+            deliveries: RwLock::new(Vec::new()),
+        }
+    }
+}
+
+impl Default for InMemoryWebhookSubscriptionStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl WebhookSubscriptionStore for InMemoryWebhookSubscriptionStore {
+    async fn create_webhook_subscription(
+        &self,
+        ctx: &TenantContext,
+        record: WebhookSubscriptionRecord,
+    ) -> Result<WebhookSubscriptionRecord, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let mut subscriptions = self
+            .subscriptions
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        subscriptions.push(record.clone());
+        Ok(record)
+    }
+
+    async fn list_webhook_subscriptions(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<Vec<WebhookSubscriptionRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let subscriptions = self
+            .subscriptions
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let mut items: Vec<WebhookSubscriptionRecord> = subscriptions
+            .iter()
+            .filter(|item| item.tenant_id == ctx.tenant_id && item.project_id == project_id)
+            .cloned()
+            .collect();
+        items.sort_by_key(|item| std::cmp::Reverse(item.created_at_ms));
+        Ok(items)
+    }
+
+    async fn delete_webhook_subscription(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        subscription_id: &str,
+    ) -> Result<bool, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let mut subscriptions = self
+            .subscriptions
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let before = subscriptions.len();
+        subscriptions.retain(|item| {
+            !(item.tenant_id == ctx.tenant_id
+                && item.project_id == project_id
+                && item.subscription_id == subscription_id)
+        });
+        Ok(subscriptions.len() != before)
+    }
+
+    async fn list_webhook_subscriptions_for_event(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        event_type: &str,
+    ) -> Result<Vec<WebhookSubscriptionRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let subscriptions = self
+            .subscriptions
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        Ok(subscriptions
+            .iter()
+            .filter(|item| {
+                item.tenant_id == ctx.tenant_id
+                    && item.project_id == project_id
+                    && item.enabled
+                    && item.event_types.iter().any(|value| value == event_type)
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn create_webhook_delivery(
+        &self,
+        ctx: &TenantContext,
+        record: WebhookDeliveryRecord,
+    ) -> Result<WebhookDeliveryRecord, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let mut deliveries = self
+            .deliveries
+            .write()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        deliveries.push(record.clone());
+        Ok(record)
+    }
+
+    async fn list_webhook_deliveries(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        options: WebhookDeliveryQuery,
+    ) -> Result<Vec<WebhookDeliveryRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let deliveries = self
+            .deliveries
+            .read()
+            .map_err(|_| StorageError::new("lock failed"))?;
+        let mut items: Vec<WebhookDeliveryRecord> = deliveries
+            .iter()
+            .filter(|item| item.tenant_id == ctx.tenant_id && item.project_id == project_id)
+            .filter(|item| {
+                options
+                    .subscription_id
+                    .as_deref()
+                    .is_none_or(|value| item.subscription_id == value)
+            })
+            .filter(|item| {
+                options
+                    .status
+                    .as_deref()
+                    .is_none_or(|value| item.status == value)
+            })
+            .cloned()
+            .collect();
+        items.sort_by_key(|item| std::cmp::Reverse(item.created_at_ms));
+        if options.limit > 0 {
+            items.truncate(options.limit as usize);
+        }
+        Ok(items)
+    }
+}
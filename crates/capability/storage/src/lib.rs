@@ -161,13 +161,21 @@ pub use validation::*;
 
 // 导出内存存储实现类型
 pub use in_memory::{
-    InMemoryAuditLogStore, InMemoryCommandReceiptStore, InMemoryCommandStore, InMemoryDeviceStore,
-    InMemoryGatewayStore, InMemoryMeasurementStore, InMemoryPointMappingStore, InMemoryPointStore,
-    InMemoryOnlineStore, InMemoryProjectStore, InMemoryRealtimeStore, InMemoryUserStore,
+    InMemoryAnomalyStore, InMemoryAuditLogStore, InMemoryCommandReceiptStore, InMemoryCommandStore,
+    InMemoryDemandResponseStore, InMemoryDeviceEventStore, InMemoryEmissionFactorStore,
+    InMemoryDeviceShadowStore, InMemoryDeviceStore, InMemoryDeviceTemplateStore,
+    InMemoryFeatureFlagStore, InMemoryFirmwareStore, InMemoryGatewayConfigStore, InMemoryGatewayStore,
+    InMemoryIdempotencyStore, InMemoryJobStore, InMemoryMaintenanceStore, InMemoryMeasurementStore, InMemoryPointMappingStore,
+    InMemoryPointStore, InMemoryOnlineStore, InMemoryPortfolioStore, InMemoryProjectCloneStore, InMemoryProjectStore, InMemoryRealtimeStore,
+    InMemoryRuleStore, InMemoryScheduleStore, InMemoryShareTokenStore, InMemoryTenantStore, InMemoryUsageStore, InMemoryUserStore,
+    InMemoryWebhookSubscriptionStore,
 };
 
 // 导出 PostgreSQL 存储实现类型
 pub use postgres::{
-    PgAuditLogStore, PgCommandReceiptStore, PgCommandStore, PgDeviceStore, PgGatewayStore,
-    PgMeasurementStore, PgPointMappingStore, PgPointStore, PgProjectStore, PgUserStore,
+    PgAnomalyStore, PgAuditLogStore, PgCommandReceiptStore, PgCommandStore, PgDemandResponseStore,
+    PgDeviceEventStore, PgDeviceShadowStore, PgDeviceStore, PgEmissionFactorStore,
+    PgDeviceTemplateStore, PgFeatureFlagStore, PgFirmwareStore, PgGatewayConfigStore, PgGatewayStore,
+    PgIdempotencyStore, PgJobStore, PgMaintenanceStore, PgMeasurementStore, PgPointMappingStore, PgPointStore, PgPortfolioStore, PgProjectCloneStore, PgProjectStore,
+    PgRuleStore, PgScheduleStore, PgShareTokenStore, PgTenantStore, PgUsageStore, PgUserStore, PgWebhookSubscriptionStore,
 };
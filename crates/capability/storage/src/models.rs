@@ -3,12 +3,29 @@
 //! 定义所有存储相关的数据模型和更新结构：
 //! - 用户模型：UserRecord
 //! - 项目模型：ProjectRecord, ProjectUpdate
+//! - 项目组合：PortfolioRecord, PortfolioUpdate
 //! - 楼宇层级：AreaRecord, BuildingRecord, FloorRecord, RoomRecord
 //! - 网关模型：GatewayRecord, GatewayUpdate（含协议配置）
 //! - 设备模型：DeviceRecord, DeviceUpdate（含地址配置）
 //! - 点位模型：PointRecord, PointUpdate
 //! - 点映射模型：PointMappingRecord, PointMappingUpdate（含协议细节）
-//! - 时序与实时模型：MeasurementRecord, RealtimeRecord
+//! - 设备模板：DeviceTemplateRecord, DeviceTemplatePoint, DeviceInstance
+//! - 项目克隆：ProjectClone
+//! - 网关配置下发：GatewayConfigRecord
+//! - 固件升级：FirmwarePackageRecord, FirmwareCampaignRecord, FirmwareRolloutRecord,
+//!   FirmwareRolloutUpdate
+//! - 维护模式：MaintenanceWindowRecord
+//! - 审计日志：AuditLogRecord, AuditChainEntry（租户级哈希链）
+//! - Webhook：WebhookSubscriptionRecord, WebhookDeliveryRecord
+//! - 数据分享：ShareTokenRecord
+//! - 自动化规则：RuleRecord, RuleUpdate, RuleExecutionRecord
+//! - 控制计划：ScheduleRecord, ScheduleUpdate, ScheduleExecutionRecord
+//! - 用能异常：AnomalyRecord
+//! - 碳排放因子：EmissionFactorRecord
+//! - 用量与配额：UsageRecord, QuotaRecord
+//! - 时序与实时模型：MeasurementRecord, MeasurementCoverage, RealtimeRecord
+
+use sha2::{Digest, Sha256};
 
 /// 用户记录（用于 M0 演示）。
 #[derive(Debug, Clone)]
@@ -87,6 +104,16 @@ pub struct PermissionRecord {
     pub description: String,
 }
 
+/// 资产集合版本（用于列表 ETag）。
+///
+/// `count` 为集合条数；`revision` 为最近变更标记（PG 为 `max(updated_at)` 微秒，
+/// 内存实现为变更计数）。两者都不变时视为集合未变更。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CollectionVersion {
+    pub count: i64,
+    pub revision: i64,
+}
+
 /// 项目记录（用于租户归属校验）。
 #[derive(Debug, Clone)]
 pub struct ProjectRecord {
@@ -103,6 +130,28 @@ pub struct ProjectUpdate {
     pub timezone: Option<String>,
 }
 
+/// 项目组合记录（多站点客户把同租户下的项目分组查看）。
+#[derive(Debug, Clone)]
+pub struct PortfolioRecord {
+    pub tenant_id: String,
+    pub portfolio_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    /// 成员项目 ID（按加入顺序，去重）
+    pub project_ids: Vec<String>,
+    pub created_at_ms: i64,
+    pub updated_at_ms: i64,
+}
+
+/// 项目组合更新输入（`project_ids` 为 Some 时整体替换成员列表）。
+#[derive(Debug, Clone)]
+pub struct PortfolioUpdate {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub project_ids: Option<Vec<String>>,
+    pub updated_at_ms: i64,
+}
+
 // ============================================================================
 // 楼宇层级模型（区域 → 楼宇 → 楼层 → 房间）
 // ============================================================================
@@ -191,6 +240,7 @@ pub struct RoomUpdate {
 /// - `modbus_tcp`: Modbus TCP 协议  
 /// - `tcp_server`: TCP 服务器模式
 /// - `tcp_client`: TCP 客户端模式
+/// - `modbus_server`: Modbus TCP 从站（只读，对外暴露点位最新值）
 #[derive(Debug, Clone)]
 pub struct GatewayRecord {
     pub gateway_id: String,
@@ -198,7 +248,7 @@ pub struct GatewayRecord {
     pub project_id: String,
     pub name: String,
     pub status: String,
-    /// 协议类型: mqtt | modbus_tcp | tcp_server | tcp_client
+    /// 协议类型: mqtt | modbus_tcp | tcp_server | tcp_client | modbus_server
     pub protocol_type: String,
     /// 协议配置（JSON 格式）
     pub protocol_config: Option<String>,
@@ -228,6 +278,8 @@ pub struct DeviceRecord {
     pub room_id: Option<String>,
     /// 协议地址配置（JSON 格式）
     pub address_config: Option<String>,
+    /// 离线判定阈值（秒，未设置时使用全局默认值）
+    pub offline_after_seconds: Option<i64>,
 }
 
 /// 设备更新输入。
@@ -237,6 +289,7 @@ pub struct DeviceUpdate {
     pub model: Option<String>,
     pub room_id: Option<String>,
     pub address_config: Option<String>,
+    pub offline_after_seconds: Option<i64>,
 }
 
 /// 点位记录。
@@ -249,6 +302,8 @@ pub struct PointRecord {
     pub key: String,
     pub data_type: String,
     pub unit: Option<String>,
+    /// 点位标签（用于按标签分组查询，如 `energy`、`hvac`）
+    pub tags: Vec<String>,
 }
 
 /// 点位更新输入。
@@ -257,6 +312,8 @@ pub struct PointUpdate {
     pub key: Option<String>,
     pub data_type: Option<String>,
     pub unit: Option<String>,
+    /// 提供时整体替换标签
+    pub tags: Option<Vec<String>>,
 }
 
 /// 点位映射记录。
@@ -290,6 +347,137 @@ pub struct PointMappingUpdate {
     pub protocol_detail: Option<String>,
 }
 
+// ============================================================================
+// 设备模板（产品模型）
+// ============================================================================
+
+/// 设备模板记录。
+///
+/// 描述某一型号设备的标准点位集合与映射提示，用于批量接入同型号设备。
+#[derive(Debug, Clone)]
+pub struct DeviceTemplateRecord {
+    pub template_id: String,
+    pub tenant_id: String,
+    pub project_id: String,
+    pub name: String,
+    pub model: Option<String>,
+    pub points: Vec<DeviceTemplatePoint>,
+}
+
+/// 设备模板中的标准点位。
+///
+/// `source_type` 与 `address` 同时存在时，实例化会一并生成点位映射。
+#[derive(Debug, Clone)]
+pub struct DeviceTemplatePoint {
+    pub key: String,
+    pub data_type: String,
+    pub unit: Option<String>,
+    pub source_type: Option<String>,
+    pub address: Option<String>,
+    pub scale: Option<f64>,
+    pub offset: Option<f64>,
+    /// 协议细节配置（JSON 格式）
+    pub protocol_detail: Option<String>,
+}
+
+/// 设备实例化结果（设备 + 点位 + 点位映射）。
+#[derive(Debug, Clone)]
+pub struct DeviceInstance {
+    pub device: DeviceRecord,
+    pub points: Vec<PointRecord>,
+    pub mappings: Vec<PointMappingRecord>,
+}
+
+/// 项目克隆内容（新项目 + 重新分配 ID 后的资产树与规则）。
+#[derive(Debug, Clone)]
+pub struct ProjectClone {
+    pub project: ProjectRecord,
+    pub gateways: Vec<GatewayRecord>,
+    /// 网关命令载荷格式（新网关 ID, 格式 JSON 文本），仅包含已设置格式的网关
+    pub gateway_command_formats: Vec<(String, String)>,
+    pub devices: Vec<DeviceRecord>,
+    pub points: Vec<PointRecord>,
+    pub mappings: Vec<PointMappingRecord>,
+    pub device_templates: Vec<DeviceTemplateRecord>,
+    pub rules: Vec<RuleRecord>,
+}
+
+impl ProjectClone {
+    /// 全部记录是否都属于新项目（租户与项目 ID 一致）
+    pub fn is_scoped_to_project(&self) -> bool {
+        let tenant_id = self.project.tenant_id.as_str();
+        let project_id = self.project.project_id.as_str();
+        let scoped = |tenant: &str, project: &str| tenant == tenant_id && project == project_id;
+        self.gateways
+            .iter()
+            .all(|item| scoped(&item.tenant_id, &item.project_id))
+            && self.gateway_command_formats.iter().all(|(gateway_id, _)| {
+                self.gateways
+                    .iter()
+                    .any(|item| &item.gateway_id == gateway_id)
+            })
+            && self
+                .devices
+                .iter()
+                .all(|item| scoped(&item.tenant_id, &item.project_id))
+            && self
+                .points
+                .iter()
+                .all(|item| scoped(&item.tenant_id, &item.project_id))
+            && self
+                .mappings
+                .iter()
+                .all(|item| scoped(&item.tenant_id, &item.project_id))
+            && self
+                .device_templates
+                .iter()
+                .all(|item| scoped(&item.tenant_id, &item.project_id))
+            && self
+                .rules
+                .iter()
+                .all(|item| scoped(&item.tenant_id, &item.project_id))
+    }
+}
+
+/// 网关配置下发记录。
+///
+/// 每次下发生成一个递增版本；`status` 流转：
+/// `pending` → `published`/`failed`（发布结果）→ `applied`/`failed`（网关回执）。
+#[derive(Debug, Clone)]
+pub struct GatewayConfigRecord {
+    pub tenant_id: String,
+    pub project_id: String,
+    pub gateway_id: String,
+    /// 配置版本（由存储层在创建时分配，按网关单调递增）
+    pub version: i64,
+    /// 配置文档（JSON 格式）
+    pub document: String,
+    pub status: String,
+    pub message: Option<String>,
+    pub pushed_by: String,
+    pub pushed_at_ms: i64,
+    pub updated_at_ms: i64,
+}
+
+/// 设备影子记录（期望状态）。
+///
+/// `desired` 为 `点位 key → 期望值` 的 JSON 对象；每次设置期望状态版本号递增，
+/// 上报状态由实时值推导，不落库。
+#[derive(Debug, Clone)]
+pub struct DeviceShadowRecord {
+    pub tenant_id: String,
+    pub project_id: String,
+    pub device_id: String,
+    /// 期望状态（JSON 对象）
+    pub desired: String,
+    /// 期望状态版本（由存储层分配，按设备单调递增）
+    pub version: i64,
+    /// 最近一次下发差量的命令 ID（差量为空时不下发）
+    pub last_command_id: Option<String>,
+    pub updated_by: String,
+    pub updated_at_ms: i64,
+}
+
 /// 时序测点记录。
 #[derive(Debug, Clone)]
 pub struct MeasurementRecord {
@@ -301,6 +489,26 @@ pub struct MeasurementRecord {
     pub quality: Option<String>,
 }
 
+/// 测点数据覆盖率（按期望上报间隔切桶统计）。
+#[derive(Debug, Clone)]
+pub struct MeasurementCoverage {
+    /// 窗口内期望的桶数量
+    pub expected_buckets: i64,
+    /// 至少有一条数据的桶数量
+    pub covered_buckets: i64,
+    /// 完整度百分比（0-100）
+    pub completeness_pct: f64,
+    /// 连续缺失区间（按时间升序）
+    pub gaps: Vec<MeasurementGap>,
+}
+
+/// 数据缺失区间，左闭右开 `[from_ms, to_ms)`。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MeasurementGap {
+    pub from_ms: i64,
+    pub to_ms: i64,
+}
+
 /// 实时测点记录（last_value）。
 #[derive(Debug, Clone)]
 pub struct RealtimeRecord {
@@ -325,6 +533,13 @@ pub struct CommandRecord {
     pub issued_at_ms: i64,
 }
 
+/// 控制命令按状态计数（用于控制面板统计）。
+#[derive(Debug, Clone)]
+pub struct CommandStatusCount {
+    pub status: String,
+    pub count: i64,
+}
+
 /// 控制命令回执记录。
 #[derive(Debug, Clone)]
 pub struct CommandReceiptRecord {
@@ -350,3 +565,497 @@ pub struct AuditLogRecord {
     pub detail: Option<String>,
     pub ts_ms: i64,
 }
+
+impl AuditLogRecord {
+    /// 计算哈希链节点摘要：SHA-256(JSON[seq, prev_hash, 记录各字段]) 的十六进制。
+    ///
+    /// 以 JSON 数组序列化保证字段边界无歧义；任一字段被修改都会导致摘要变化。
+    pub fn chain_hash(&self, seq: i64, prev_hash: &str) -> String {
+        let content = serde_json::json!([
+            seq,
+            prev_hash,
+            self.audit_id,
+            self.tenant_id,
+            self.project_id,
+            self.actor,
+            self.action,
+            self.resource,
+            self.result,
+            self.detail,
+            self.ts_ms,
+        ]);
+        hex::encode(Sha256::digest(content.to_string().as_bytes()))
+    }
+}
+
+/// 审计日志哈希链节点。
+///
+/// 每个租户的审计日志按写入顺序分配连续的 `seq`（从 1 开始），
+/// `prev_hash` 为上一节点的 `hash`（首个节点为空串），用于发现缺失或被篡改的记录。
+#[derive(Debug, Clone)]
+pub struct AuditChainEntry {
+    pub record: AuditLogRecord,
+    pub seq: i64,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+impl AuditChainEntry {
+    /// 校验节点与上一节点的衔接及自身摘要。
+    ///
+    /// 返回失败原因：`gap`（序号不连续，记录缺失）、`broken_link`（未指向上一节点摘要）、
+    /// `hash_mismatch`（记录内容或摘要被修改）；通过时返回 None。
+    pub fn check(&self, expected_seq: i64, expected_prev_hash: &str) -> Option<&'static str> {
+        if self.seq != expected_seq {
+            Some("gap")
+        } else if self.prev_hash != expected_prev_hash {
+            Some("broken_link")
+        } else if self.record.chain_hash(self.seq, &self.prev_hash) != self.hash {
+            Some("hash_mismatch")
+        } else {
+            None
+        }
+    }
+}
+
+/// 只读数据分享令牌记录。
+///
+/// 令牌绑定单个项目，`scopes` 为授予的只读范围（realtime / measurements）；
+/// 仅保存令牌明文的 SHA-256 摘要，明文只在创建时返回给调用方。
+#[derive(Debug, Clone)]
+pub struct ShareTokenRecord {
+    pub tenant_id: String,
+    pub project_id: String,
+    pub token_id: String,
+    pub token_hash: String,
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub created_by: String,
+    pub expires_at_ms: i64,
+    pub revoked_at_ms: Option<i64>,
+    pub created_at_ms: i64,
+}
+
+impl ShareTokenRecord {
+    /// 在指定时刻是否有效（未撤销且未过期）
+    pub fn is_active(&self, now_ms: i64) -> bool {
+        self.revoked_at_ms.is_none() && now_ms < self.expires_at_ms
+    }
+}
+
+/// Webhook 订阅记录。
+///
+/// `secret` 用于对推送内容做 HMAC-SHA256 签名，仅在创建时返回给调用方。
+#[derive(Debug, Clone)]
+pub struct WebhookSubscriptionRecord {
+    pub subscription_id: String,
+    pub tenant_id: String,
+    pub project_id: String,
+    pub url: String,
+    /// 订阅的事件类型（如 `device.offline`、`command.completed`）
+    pub event_types: Vec<String>,
+    pub secret: String,
+    pub enabled: bool,
+    pub created_by: String,
+    pub created_at_ms: i64,
+}
+
+/// Webhook 推送日志记录。
+///
+/// 每个事件对每个订阅生成一条记录；`status` 为 `success` 或 `failed`，
+/// `attempts` 为含重试在内的实际请求次数。
+#[derive(Debug, Clone)]
+pub struct WebhookDeliveryRecord {
+    pub delivery_id: String,
+    pub tenant_id: String,
+    pub project_id: String,
+    pub subscription_id: String,
+    pub event_id: String,
+    pub event_type: String,
+    pub status: String,
+    pub attempts: i32,
+    /// 最后一次请求的 HTTP 状态码（连接失败时为空）
+    pub response_status: Option<i32>,
+    pub error: Option<String>,
+    pub created_at_ms: i64,
+}
+
+/// 自动化规则记录。
+///
+/// `trigger` 为触发条件 JSON 对象，`actions` 为动作 JSON 数组，结构由规则引擎解析校验。
+#[derive(Debug, Clone)]
+pub struct RuleRecord {
+    pub tenant_id: String,
+    pub project_id: String,
+    pub rule_id: String,
+    pub name: String,
+    pub enabled: bool,
+    /// 触发条件（JSON 对象）
+    pub trigger: String,
+    /// 动作列表（JSON 数组）
+    pub actions: String,
+    pub created_by: String,
+    pub created_at_ms: i64,
+    pub updated_at_ms: i64,
+}
+
+/// 自动化规则更新（字段为 None 表示不修改）。
+#[derive(Debug, Clone, Default)]
+pub struct RuleUpdate {
+    pub name: Option<String>,
+    pub enabled: Option<bool>,
+    pub trigger: Option<String>,
+    pub actions: Option<String>,
+    pub updated_at_ms: i64,
+}
+
+/// 自动化规则执行记录。
+///
+/// 每次触发生成一条记录；`status` 为 `success`（全部动作成功）或 `failed`，
+/// `results` 为按动作顺序的执行结果 JSON 数组。
+#[derive(Debug, Clone)]
+pub struct RuleExecutionRecord {
+    pub execution_id: String,
+    pub tenant_id: String,
+    pub project_id: String,
+    pub rule_id: String,
+    /// 触发详情（JSON 对象，如触发时的点位值）
+    pub trigger_detail: String,
+    pub status: String,
+    pub results: String,
+    pub executed_at_ms: i64,
+}
+
+/// 控制计划记录（周期性下发预定义命令或设定值曲线）。
+///
+/// `spec` 为计划定义 JSON 对象，结构由计划执行器解析校验；
+/// `last_evaluated_at_ms` 为执行器游标，早于游标的计划时刻视为已处理。
+#[derive(Debug, Clone)]
+pub struct ScheduleRecord {
+    pub tenant_id: String,
+    pub project_id: String,
+    pub schedule_id: String,
+    pub name: String,
+    pub enabled: bool,
+    /// IANA 时区名（计划时刻按该时区解释）
+    pub timezone: String,
+    /// 计划定义（JSON 对象）
+    pub spec: String,
+    /// 错过执行时的策略：skip | run_once | run_all
+    pub missed_run_policy: String,
+    pub last_evaluated_at_ms: i64,
+    pub created_by: String,
+    pub created_at_ms: i64,
+    pub updated_at_ms: i64,
+}
+
+/// 控制计划更新（字段为 None 表示不修改）。
+#[derive(Debug, Clone, Default)]
+pub struct ScheduleUpdate {
+    pub name: Option<String>,
+    pub enabled: Option<bool>,
+    pub timezone: Option<String>,
+    pub spec: Option<String>,
+    pub missed_run_policy: Option<String>,
+    /// 重置执行器游标（启用或修改计划时刻时使用，避免补跑修改前的时刻）
+    pub last_evaluated_at_ms: Option<i64>,
+    pub updated_at_ms: i64,
+}
+
+/// 控制计划执行记录。
+///
+/// 每个计划时刻生成一条记录；`status` 为 `success`、`failed` 或 `skipped`（按策略跳过的错过时刻），
+/// `results` 为按命令顺序的执行结果 JSON 数组。
+#[derive(Debug, Clone)]
+pub struct ScheduleExecutionRecord {
+    pub execution_id: String,
+    pub tenant_id: String,
+    pub project_id: String,
+    pub schedule_id: String,
+    /// 计划时刻
+    pub scheduled_at_ms: i64,
+    pub status: String,
+    pub results: String,
+    pub executed_at_ms: i64,
+}
+
+/// 可削减负荷（需求响应中可被切除或降载的设备）。
+///
+/// `priority` 越小越先削减；`power_point_id` 为设备有功功率点位（单位 kW），
+/// 用于选择负荷与跟踪实际削减量；实时值缺失时按 `rated_kw` 估算。
+#[derive(Debug, Clone)]
+pub struct SheddableLoadRecord {
+    pub tenant_id: String,
+    pub project_id: String,
+    pub device_id: String,
+    pub priority: i32,
+    pub power_point_id: String,
+    pub rated_kw: f64,
+    /// 削减命令载荷（JSON 对象）
+    pub shed_payload: String,
+    /// 恢复命令载荷（JSON 对象）
+    pub restore_payload: String,
+    pub enabled: bool,
+    pub created_at_ms: i64,
+    pub updated_at_ms: i64,
+}
+
+/// 需求响应事件。
+///
+/// `status` 为 `scheduled`、`active`、`completed`、`cancelled` 或 `expired`（窗口结束前未能启动）；
+/// `loads` 为已选负荷及其削减 / 恢复命令的 JSON 数组。
+#[derive(Debug, Clone)]
+pub struct DemandResponseEventRecord {
+    pub tenant_id: String,
+    pub project_id: String,
+    pub event_id: String,
+    /// 目标削减量（kW）
+    pub target_kw: f64,
+    pub start_at_ms: i64,
+    pub end_at_ms: i64,
+    pub status: String,
+    /// 已请求取消（由编排器在下一次检查时恢复负荷并结束事件）
+    pub cancel_requested: bool,
+    /// 削减前已选负荷的功率合计（kW）
+    pub baseline_kw: Option<f64>,
+    /// 按实时功率计算的实际削减量（kW）
+    pub achieved_kw: Option<f64>,
+    pub loads: String,
+    pub created_by: String,
+    pub created_at_ms: i64,
+    pub updated_at_ms: i64,
+}
+
+/// 需求响应事件更新（字段为 None 表示不修改）。
+#[derive(Debug, Clone, Default)]
+pub struct DemandResponseEventUpdate {
+    pub status: Option<String>,
+    pub cancel_requested: Option<bool>,
+    pub baseline_kw: Option<f64>,
+    pub achieved_kw: Option<f64>,
+    pub loads: Option<String>,
+    pub updated_at_ms: i64,
+}
+
+/// 用能异常（点位小时值偏离周内同时段基线）。
+///
+/// 基线为过去若干周同一 hour-of-week（项目时区，周一 0 点为 0）的小时值平均；
+/// 同一点位的同一小时桶只记录一次。
+#[derive(Debug, Clone)]
+pub struct AnomalyRecord {
+    pub tenant_id: String,
+    pub project_id: String,
+    pub anomaly_id: String,
+    pub point_id: String,
+    pub device_id: String,
+    /// 小时桶起始时刻（项目时区整点）
+    pub bucket_start_ms: i64,
+    /// 周内小时（0–167）
+    pub hour_of_week: i32,
+    /// 该小时的实际值（瞬时量为小时平均，累计量为小时增量）
+    pub actual_value: f64,
+    /// 基线值
+    pub baseline_value: f64,
+    /// 参与基线计算的历史小时数
+    pub baseline_samples: i32,
+    /// 偏差百分比（(实际 − 基线) / |基线| × 100，正数为偏高）
+    pub deviation_pct: f64,
+    /// 检测时使用的偏差阈值（百分比）
+    pub threshold_pct: f64,
+    pub detected_at_ms: i64,
+}
+
+/// 设备时间线事件。
+///
+/// 由事件总线上与设备相关的领域事件（创建、配置变更、上下线、命令、告警）落库而来，
+/// `event_id` 与领域事件 ID 一致，同一设备重复写入同一事件时忽略。
+#[derive(Debug, Clone)]
+pub struct DeviceEventRecord {
+    pub tenant_id: String,
+    pub project_id: String,
+    pub device_id: String,
+    pub event_id: String,
+    pub event_type: String,
+    pub occurred_at_ms: i64,
+    /// 事件数据（JSON 对象，与领域事件 `data` 一致）
+    pub data: String,
+}
+
+/// 碳排放因子（每单位能源消耗折算的 CO₂e 千克数）。
+///
+/// `project_id` 为 None 时是租户默认值；项目级因子覆盖同一能源类型的租户默认值。
+#[derive(Debug, Clone)]
+pub struct EmissionFactorRecord {
+    pub tenant_id: String,
+    pub project_id: Option<String>,
+    /// 能源类型（electricity / gas / diesel）
+    pub energy_source: String,
+    /// kgCO₂e / 单位
+    pub kg_co2e_per_unit: f64,
+    /// 消耗量单位（如 kWh、m3、L），仅用于展示，不做换算
+    pub unit: String,
+    pub updated_at_ms: i64,
+}
+
+/// 固件包（网关 OTA 升级包元数据）。
+///
+/// 固件文件本身由对象存储等外部系统保存，这里只记录 `storage_url` 引用与 SHA-256 校验和；
+/// 同一项目下 (name, version) 唯一。
+#[derive(Debug, Clone)]
+pub struct FirmwarePackageRecord {
+    pub tenant_id: String,
+    pub project_id: String,
+    pub package_id: String,
+    pub name: String,
+    pub version: String,
+    /// 固件文件 SHA-256（64 位小写十六进制）
+    pub checksum_sha256: String,
+    pub size_bytes: i64,
+    /// 固件文件下载地址或存储引用（网关据此拉取固件）
+    pub storage_url: String,
+    /// 附加元数据（JSON 对象，如适用型号、发布说明）
+    pub metadata: Option<String>,
+    pub created_by: String,
+    pub created_at_ms: i64,
+}
+
+/// 固件升级批次（将一个固件包推送到一组网关）。
+///
+/// `status` 为 `in_progress`、`completed`（全部网关升级成功）或 `failed`（全部结束且存在失败）。
+#[derive(Debug, Clone)]
+pub struct FirmwareCampaignRecord {
+    pub tenant_id: String,
+    pub project_id: String,
+    pub campaign_id: String,
+    pub package_id: String,
+    pub name: String,
+    pub status: String,
+    pub created_by: String,
+    pub created_at_ms: i64,
+    pub updated_at_ms: i64,
+}
+
+/// 单个网关在升级批次中的进度。
+///
+/// `status` 为 `pending` → `published`/`failed` → `downloading`/`installing` → `succeeded`/`failed`；
+/// `progress` 为网关回执的百分比（0–100）。
+#[derive(Debug, Clone)]
+pub struct FirmwareRolloutRecord {
+    pub tenant_id: String,
+    pub project_id: String,
+    pub campaign_id: String,
+    pub gateway_id: String,
+    pub status: String,
+    pub progress: i32,
+    pub message: Option<String>,
+    pub updated_at_ms: i64,
+}
+
+/// 网关升级进度更新（`progress` 为 None 表示保留原值）。
+#[derive(Debug, Clone)]
+pub struct FirmwareRolloutUpdate {
+    pub status: String,
+    pub progress: Option<i32>,
+    pub message: Option<String>,
+    pub updated_at_ms: i64,
+}
+
+/// 设备 / 网关维护窗口（每个目标最多一条）。
+///
+/// 窗口内抑制离线告警、拦截自动化命令；人工命令仍可下发（记录覆盖审计）。
+/// 窗口到期后记录保留，直到被清除或覆盖。
+#[derive(Debug, Clone)]
+pub struct MaintenanceWindowRecord {
+    pub tenant_id: String,
+    pub project_id: String,
+    /// 目标类型：device | gateway
+    pub target_type: String,
+    pub target_id: String,
+    pub starts_at_ms: i64,
+    pub ends_at_ms: i64,
+    pub reason: Option<String>,
+    pub created_by: String,
+    pub created_at_ms: i64,
+}
+
+impl MaintenanceWindowRecord {
+    /// 指定时刻是否处于维护窗口内（含开始时刻，不含结束时刻）
+    pub fn is_active_at(&self, ts_ms: i64) -> bool {
+        self.starts_at_ms <= ts_ms && ts_ms < self.ends_at_ms
+    }
+}
+
+/// 幂等键记录。
+///
+/// 同一租户下的 `Idempotency-Key` 在有效期内只执行一次；
+/// `status_code` / `response_body` 为空表示首个请求仍在处理中。
+#[derive(Debug, Clone)]
+pub struct IdempotencyRecord {
+    pub tenant_id: String,
+    pub idempotency_key: String,
+    /// 请求摘要（方法 + 路径 + 请求体的 SHA-256），用于识别同键不同请求
+    pub request_hash: String,
+    pub status_code: Option<i32>,
+    /// 首次响应头（JSON 数组 `[[name, value], ...]`），重放时原样恢复
+    pub response_headers: Option<String>,
+    pub response_body: Option<String>,
+    pub created_at_ms: i64,
+    pub expires_at_ms: i64,
+}
+
+/// 租户功能开关记录。
+///
+/// `variant` 为可选的灰度变体（如 `v2`），由客户端或业务自行解释。
+#[derive(Debug, Clone)]
+pub struct FeatureFlagRecord {
+    pub tenant_id: String,
+    pub flag_key: String,
+    pub enabled: bool,
+    pub variant: Option<String>,
+    pub updated_at_ms: i64,
+}
+
+/// 租户用量记录（计数类指标按周期累计）。
+#[derive(Debug, Clone)]
+pub struct UsageRecord {
+    pub tenant_id: String,
+    /// 指标（见 `domain::usage`）
+    pub metric: String,
+    /// 周期起始时刻（UTC 自然日）
+    pub period_start_ms: i64,
+    pub count: i64,
+}
+
+/// 租户配额记录（计数类指标为每周期上限，`points` 为总数上限）。
+#[derive(Debug, Clone)]
+pub struct QuotaRecord {
+    pub tenant_id: String,
+    pub metric: String,
+    pub limit: i64,
+    pub updated_at_ms: i64,
+}
+
+/// 后台任务记录。
+///
+/// `project_id` 为 None 时是租户级任务；`params` / `result` 为 JSON 文本，由任务类型自行解释。
+/// 状态取值见 `ems-jobs`（queued / running / succeeded / failed / cancelled）。
+#[derive(Debug, Clone)]
+pub struct JobRecord {
+    pub job_id: String,
+    pub tenant_id: String,
+    pub project_id: Option<String>,
+    pub kind: String,
+    pub status: String,
+    pub params: String,
+    /// 进度（0-100）
+    pub progress: i32,
+    pub result: Option<String>,
+    pub error: Option<String>,
+    /// 已请求取消（运行中的任务在下一次进度上报时停止）
+    pub cancel_requested: bool,
+    pub created_by: String,
+    pub created_at_ms: i64,
+    pub started_at_ms: Option<i64>,
+    pub finished_at_ms: Option<i64>,
+    pub updated_at_ms: i64,
+}
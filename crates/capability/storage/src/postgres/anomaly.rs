@@ -0,0 +1,141 @@
+//! Postgres 用能异常实现
+
+use crate::error::StorageError;
+use crate::models::AnomalyRecord;
+use crate::traits::{AnomalyQuery, AnomalyStore};
+use crate::validation::ensure_project_scope;
+use domain::TenantContext;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+
+pub struct PgAnomalyStore {
+    pub pool: PgPool,
+}
+
+impl PgAnomalyStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+const ANOMALY_COLUMNS: &str = "tenant_id, project_id, anomaly_id, point_id, device_id, \
+     (extract(epoch from bucket_start) * 1000)::bigint as bucket_start_ms, \
+     hour_of_week, actual_value, baseline_value, baseline_samples, deviation_pct, threshold_pct, \
+     (extract(epoch from detected_at) * 1000)::bigint as detected_at_ms";
+
+fn anomaly_from_row(row: &PgRow) -> Result<AnomalyRecord, StorageError> {
+    Ok(AnomalyRecord {
+        tenant_id: row.try_get("tenant_id")?,
+        project_id: row.try_get("project_id")?,
+        anomaly_id: row.try_get("anomaly_id")?,
+        point_id: row.try_get("point_id")?,
+        device_id: row.try_get("device_id")?,
+        bucket_start_ms: row.try_get("bucket_start_ms")?,
+        hour_of_week: row.try_get("hour_of_week")?,
+        actual_value: row.try_get("actual_value")?,
+        baseline_value: row.try_get("baseline_value")?,
+        baseline_samples: row.try_get("baseline_samples")?,
+        deviation_pct: row.try_get("deviation_pct")?,
+        threshold_pct: row.try_get("threshold_pct")?,
+        detected_at_ms: row.try_get("detected_at_ms")?,
+    })
+}
+
+#[async_trait::async_trait]
+impl AnomalyStore for PgAnomalyStore {
+    async fn insert_anomaly(
+        &self,
+        ctx: &TenantContext,
+        record: AnomalyRecord,
+    ) -> Result<bool, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let result = sqlx::query(
+            "insert into anomalies \
+             (tenant_id, project_id, anomaly_id, point_id, device_id, bucket_start, hour_of_week, \
+             actual_value, baseline_value, baseline_samples, deviation_pct, threshold_pct, \
+             detected_at) \
+             values ($1, $2, $3, $4, $5, to_timestamp($6 / 1000.0), $7, $8, $9, $10, $11, $12, \
+             to_timestamp($13 / 1000.0)) \
+             on conflict (tenant_id, project_id, point_id, bucket_start) do nothing",
+        )
+        .bind(&record.tenant_id)
+        .bind(&record.project_id)
+        .bind(&record.anomaly_id)
+        .bind(&record.point_id)
+        .bind(&record.device_id)
+        .bind(record.bucket_start_ms as f64)
+        .bind(record.hour_of_week)
+        .bind(record.actual_value)
+        .bind(record.baseline_value)
+        .bind(record.baseline_samples)
+        .bind(record.deviation_pct)
+        .bind(record.threshold_pct)
+        .bind(record.detected_at_ms as f64)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn list_anomalies(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        options: AnomalyQuery,
+    ) -> Result<Vec<AnomalyRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let sql = format!(
+            "select {ANOMALY_COLUMNS} from anomalies \
+             where tenant_id = $1 and project_id = $2 \
+             and ($3::text is null or point_id = $3) \
+             and ($4::text is null or device_id = $4) \
+             and ($5::double precision is null or bucket_start >= to_timestamp($5 / 1000.0)) \
+             and ($6::double precision is null or bucket_start < to_timestamp($6 / 1000.0)) \
+             order by bucket_start desc, point_id \
+             limit $7"
+        );
+        let rows = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .bind(options.point_id)
+            .bind(options.device_id)
+            .bind(options.from_ms.map(|value| value as f64))
+            .bind(options.to_ms.map(|value| value as f64))
+            .bind(options.limit.max(0))
+            .fetch_all(&self.pool)
+            .await?;
+        let mut items = Vec::with_capacity(rows.len());
+        for row in rows {
+            items.push(anomaly_from_row(&row)?);
+        }
+        Ok(items)
+    }
+
+    async fn count_anomalies(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        options: AnomalyQuery,
+    ) -> Result<i64, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let count: i64 = sqlx::query_scalar(
+            "select count(*) from anomalies \
+             where tenant_id = $1 and project_id = $2 \
+             and ($3::text is null or point_id = $3) \
+             and ($4::text is null or device_id = $4) \
+             and ($5::double precision is null or bucket_start >= to_timestamp($5 / 1000.0)) \
+             and ($6::double precision is null or bucket_start < to_timestamp($6 / 1000.0))",
+        )
+        .bind(&ctx.tenant_id)
+        .bind(project_id)
+        .bind(options.point_id)
+        .bind(options.device_id)
+        .bind(options.from_ms.map(|value| value as f64))
+        .bind(options.to_ms.map(|value| value as f64))
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(count)
+    }
+}
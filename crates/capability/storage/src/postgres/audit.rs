@@ -1,10 +1,14 @@
 //! Postgres 审计日志实现
+//!
+//! 写入时以租户级事务锁（`pg_advisory_xact_lock`）串行分配哈希链序号，
+//! 迁移前已存在的记录 `seq` 为空，不参与哈希链。
 
 use crate::error::StorageError;
-use crate::models::AuditLogRecord;
+use crate::models::{AuditChainEntry, AuditLogRecord};
 use crate::traits::AuditLogStore;
 use crate::validation::ensure_project_scope;
 use domain::TenantContext;
+use sqlx::postgres::PgRow;
 use sqlx::{PgPool, Row};
 
 pub struct PgAuditLogStore {
@@ -25,15 +29,37 @@ impl AuditLogStore for PgAuditLogStore {
         record: AuditLogRecord,
     ) -> Result<AuditLogRecord, StorageError> {
         if record.tenant_id != ctx.tenant_id {
-            return Err(StorageError::new("tenant mismatch"));
+            return Err(StorageError::forbidden("tenant mismatch"));
         }
         if let Some(project_id) = record.project_id.as_deref() {
             ensure_project_scope(ctx, project_id)?;
         }
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("select pg_advisory_xact_lock(hashtext('audit_chain:' || $1))")
+            .bind(&record.tenant_id)
+            .execute(&mut *tx)
+            .await?;
+        let last = sqlx::query(
+            "select seq, hash from audit_logs \
+             where tenant_id = $1 and seq is not null \
+             order by seq desc limit 1",
+        )
+        .bind(&record.tenant_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let (seq, prev_hash) = match last {
+            Some(row) => (
+                row.try_get::<i64, _>("seq")? + 1,
+                row.try_get::<String, _>("hash")?,
+            ),
+            None => (1, String::new()),
+        };
+        let hash = record.chain_hash(seq, &prev_hash);
         sqlx::query(
             "insert into audit_logs \
-             (audit_id, tenant_id, project_id, actor, action, resource, result, detail, ts) \
-             values ($1, $2, $3, $4, $5, $6, $7, $8, to_timestamp($9 / 1000.0))",
+             (audit_id, tenant_id, project_id, actor, action, resource, result, detail, ts, \
+             seq, prev_hash, hash) \
+             values ($1, $2, $3, $4, $5, $6, $7, $8, to_timestamp($9 / 1000.0), $10, $11, $12)",
         )
         .bind(&record.audit_id)
         .bind(&record.tenant_id)
@@ -44,8 +70,12 @@ impl AuditLogStore for PgAuditLogStore {
         .bind(&record.result)
         .bind(&record.detail)
         .bind(record.ts_ms as f64)
-        .execute(&self.pool)
+        .bind(seq)
+        .bind(&prev_hash)
+        .bind(&hash)
+        .execute(&mut *tx)
         .await?;
+        tx.commit().await?;
         Ok(record)
     }
 
@@ -78,18 +108,53 @@ impl AuditLogStore for PgAuditLogStore {
         .await?;
         let mut items = Vec::with_capacity(rows.len());
         for row in rows {
-            items.push(AuditLogRecord {
-                audit_id: row.try_get("audit_id")?,
-                tenant_id: row.try_get("tenant_id")?,
-                project_id: row.try_get("project_id")?,
-                actor: row.try_get("actor")?,
-                action: row.try_get("action")?,
-                resource: row.try_get("resource")?,
-                result: row.try_get("result")?,
-                detail: row.try_get("detail")?,
-                ts_ms: row.try_get("ts_ms")?,
+            items.push(audit_log_from_row(&row)?);
+        }
+        Ok(items)
+    }
+
+    async fn list_audit_chain(
+        &self,
+        ctx: &TenantContext,
+        after_seq: i64,
+        limit: i64,
+    ) -> Result<Vec<AuditChainEntry>, StorageError> {
+        let rows = sqlx::query(
+            "select audit_id, tenant_id, project_id, actor, action, resource, result, detail, \
+             (extract(epoch from ts) * 1000)::bigint as ts_ms, seq, prev_hash, hash \
+             from audit_logs \
+             where tenant_id = $1 and seq > $2 \
+             order by seq asc \
+             limit $3",
+        )
+        .bind(&ctx.tenant_id)
+        .bind(after_seq)
+        .bind(limit.max(0))
+        .fetch_all(&self.pool)
+        .await?;
+        let mut items = Vec::with_capacity(rows.len());
+        for row in rows {
+            items.push(AuditChainEntry {
+                record: audit_log_from_row(&row)?,
+                seq: row.try_get("seq")?,
+                prev_hash: row.try_get("prev_hash")?,
+                hash: row.try_get("hash")?,
             });
         }
         Ok(items)
     }
 }
+
+fn audit_log_from_row(row: &PgRow) -> Result<AuditLogRecord, StorageError> {
+    Ok(AuditLogRecord {
+        audit_id: row.try_get("audit_id")?,
+        tenant_id: row.try_get("tenant_id")?,
+        project_id: row.try_get("project_id")?,
+        actor: row.try_get("actor")?,
+        action: row.try_get("action")?,
+        resource: row.try_get("resource")?,
+        result: row.try_get("result")?,
+        detail: row.try_get("detail")?,
+        ts_ms: row.try_get("ts_ms")?,
+    })
+}
@@ -1,8 +1,8 @@
 //! Postgres 控制命令实现
 
 use crate::error::StorageError;
-use crate::models::CommandRecord;
-use crate::traits::CommandStore;
+use crate::models::{CommandRecord, CommandStatusCount};
+use crate::traits::{CommandQueryOptions, CommandStore};
 use crate::validation::ensure_project_scope;
 use domain::TenantContext;
 use sqlx::{PgPool, Row};
@@ -26,7 +26,7 @@ impl CommandStore for PgCommandStore {
     ) -> Result<CommandRecord, StorageError> {
         ensure_project_scope(ctx, &record.project_id)?;
         if record.tenant_id != ctx.tenant_id {
-            return Err(StorageError::new("tenant mismatch"));
+            return Err(StorageError::forbidden("tenant mismatch"));
         }
         sqlx::query(
             "insert into commands \
@@ -46,6 +46,39 @@ impl CommandStore for PgCommandStore {
         Ok(record)
     }
 
+    async fn find_command(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        command_id: &str,
+    ) -> Result<Option<CommandRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let row = sqlx::query(
+            "select command_id, tenant_id, project_id, target, payload::text as payload, status, \
+             issued_by, (extract(epoch from issued_at) * 1000)::bigint as issued_at_ms \
+             from commands \
+             where tenant_id = $1 and project_id = $2 and command_id = $3",
+        )
+        .bind(&ctx.tenant_id)
+        .bind(project_id)
+        .bind(command_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        Ok(Some(CommandRecord {
+            command_id: row.try_get("command_id")?,
+            tenant_id: row.try_get("tenant_id")?,
+            project_id: row.try_get("project_id")?,
+            target: row.try_get("target")?,
+            payload: row.try_get("payload")?,
+            status: row.try_get("status")?,
+            issued_by: row.try_get("issued_by")?,
+            issued_at_ms: row.try_get("issued_at_ms")?,
+        }))
+    }
+
     async fn update_command_status(
         &self,
         ctx: &TenantContext,
@@ -108,20 +141,37 @@ impl CommandStore for PgCommandStore {
         &self,
         ctx: &TenantContext,
         project_id: &str,
-        limit: i64,
+        options: CommandQueryOptions,
     ) -> Result<Vec<CommandRecord>, StorageError> {
         ensure_project_scope(ctx, project_id)?;
+        // 游标仅给时间戳时按时间严格截断；同时给 command_id 时按 (issued_at, command_id) 比较
         let rows = sqlx::query(
             "select command_id, tenant_id, project_id, target, payload::text as payload, status, \
              issued_by, (extract(epoch from issued_at) * 1000)::bigint as issued_at_ms \
              from commands \
              where tenant_id = $1 and project_id = $2 \
-             order by issued_at desc \
-             limit $3",
+             and ($3::text is null or status = $3) \
+             and ($4::text is null or target = $4) \
+             and ($5::text is null or issued_by = $5) \
+             and ($6::double precision is null or issued_at >= to_timestamp($6 / 1000.0)) \
+             and ($7::double precision is null or issued_at <= to_timestamp($7 / 1000.0)) \
+             and ($8::double precision is null \
+                  or issued_at < to_timestamp($8 / 1000.0) \
+                  or ($9::text is not null and issued_at = to_timestamp($8 / 1000.0) \
+                      and command_id < $9)) \
+             order by issued_at desc, command_id desc \
+             limit $10",
         )
         .bind(&ctx.tenant_id)
         .bind(project_id)
-        .bind(limit.max(0))
+        .bind(options.status)
+        .bind(options.target)
+        .bind(options.issued_by)
+        .bind(options.from_ms.map(|value| value as f64))
+        .bind(options.to_ms.map(|value| value as f64))
+        .bind(options.cursor_ts_ms.map(|value| value as f64))
+        .bind(options.cursor_command_id)
+        .bind(options.limit.max(0))
         .fetch_all(&self.pool)
         .await?;
         let mut items = Vec::with_capacity(rows.len());
@@ -139,4 +189,37 @@ impl CommandStore for PgCommandStore {
         }
         Ok(items)
     }
+
+    async fn count_commands_by_status(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        from_ms: Option<i64>,
+        to_ms: Option<i64>,
+    ) -> Result<Vec<CommandStatusCount>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let rows = sqlx::query(
+            "select status, count(*)::bigint as count \
+             from commands \
+             where tenant_id = $1 and project_id = $2 \
+             and ($3::double precision is null or issued_at >= to_timestamp($3 / 1000.0)) \
+             and ($4::double precision is null or issued_at <= to_timestamp($4 / 1000.0)) \
+             group by status \
+             order by status",
+        )
+        .bind(&ctx.tenant_id)
+        .bind(project_id)
+        .bind(from_ms.map(|value| value as f64))
+        .bind(to_ms.map(|value| value as f64))
+        .fetch_all(&self.pool)
+        .await?;
+        let mut items = Vec::with_capacity(rows.len());
+        for row in rows {
+            items.push(CommandStatusCount {
+                status: row.try_get("status")?,
+                count: row.try_get("count")?,
+            });
+        }
+        Ok(items)
+    }
 }
@@ -26,7 +26,7 @@ impl CommandReceiptStore for PgCommandReceiptStore {
     ) -> Result<CommandReceiptWriteResult, StorageError> {
         ensure_project_scope(ctx, &record.project_id)?;
         if record.tenant_id != ctx.tenant_id {
-            return Err(StorageError::new("tenant mismatch"));
+            return Err(StorageError::forbidden("tenant mismatch"));
         }
         let result = sqlx::query(
             "insert into command_receipts \
@@ -0,0 +1,287 @@
+//! Postgres 需求响应可削减负荷与事件实现
+
+use crate::error::StorageError;
+use crate::models::{DemandResponseEventRecord, DemandResponseEventUpdate, SheddableLoadRecord};
+use crate::traits::DemandResponseStore;
+use crate::validation::ensure_project_scope;
+use domain::TenantContext;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+
+pub struct PgDemandResponseStore {
+    pub pool: PgPool,
+}
+
+impl PgDemandResponseStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+const LOAD_COLUMNS: &str = "tenant_id, project_id, device_id, priority, power_point_id, \
+     rated_kw, shed_payload::text as shed_payload, restore_payload::text as restore_payload, \
+     enabled, \
+     (extract(epoch from created_at) * 1000)::bigint as created_at_ms, \
+     (extract(epoch from updated_at) * 1000)::bigint as updated_at_ms";
+
+const EVENT_COLUMNS: &str = "tenant_id, project_id, event_id, target_kw, \
+     (extract(epoch from start_at) * 1000)::bigint as start_at_ms, \
+     (extract(epoch from end_at) * 1000)::bigint as end_at_ms, \
+     status, cancel_requested, baseline_kw, achieved_kw, loads::text as loads, created_by, \
+     (extract(epoch from created_at) * 1000)::bigint as created_at_ms, \
+     (extract(epoch from updated_at) * 1000)::bigint as updated_at_ms";
+
+fn load_from_row(row: &PgRow) -> Result<SheddableLoadRecord, StorageError> {
+    Ok(SheddableLoadRecord {
+        tenant_id: row.try_get("tenant_id")?,
+        project_id: row.try_get("project_id")?,
+        device_id: row.try_get("device_id")?,
+        priority: row.try_get("priority")?,
+        power_point_id: row.try_get("power_point_id")?,
+        rated_kw: row.try_get("rated_kw")?,
+        shed_payload: row.try_get("shed_payload")?,
+        restore_payload: row.try_get("restore_payload")?,
+        enabled: row.try_get("enabled")?,
+        created_at_ms: row.try_get("created_at_ms")?,
+        updated_at_ms: row.try_get("updated_at_ms")?,
+    })
+}
+
+fn event_from_row(row: &PgRow) -> Result<DemandResponseEventRecord, StorageError> {
+    Ok(DemandResponseEventRecord {
+        tenant_id: row.try_get("tenant_id")?,
+        project_id: row.try_get("project_id")?,
+        event_id: row.try_get("event_id")?,
+        target_kw: row.try_get("target_kw")?,
+        start_at_ms: row.try_get("start_at_ms")?,
+        end_at_ms: row.try_get("end_at_ms")?,
+        status: row.try_get("status")?,
+        cancel_requested: row.try_get("cancel_requested")?,
+        baseline_kw: row.try_get("baseline_kw")?,
+        achieved_kw: row.try_get("achieved_kw")?,
+        loads: row.try_get("loads")?,
+        created_by: row.try_get("created_by")?,
+        created_at_ms: row.try_get("created_at_ms")?,
+        updated_at_ms: row.try_get("updated_at_ms")?,
+    })
+}
+
+#[async_trait::async_trait]
+impl DemandResponseStore for PgDemandResponseStore {
+    async fn list_sheddable_loads(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<Vec<SheddableLoadRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let sql = format!(
+            "select {LOAD_COLUMNS} from demand_response_loads \
+             where tenant_id = $1 and project_id = $2 \
+             order by priority, device_id"
+        );
+        let rows = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .fetch_all(&self.pool)
+            .await?;
+        let mut items = Vec::with_capacity(rows.len());
+        for row in rows {
+            items.push(load_from_row(&row)?);
+        }
+        Ok(items)
+    }
+
+    async fn upsert_sheddable_load(
+        &self,
+        ctx: &TenantContext,
+        record: SheddableLoadRecord,
+    ) -> Result<SheddableLoadRecord, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let sql = format!(
+            "insert into demand_response_loads \
+             (tenant_id, project_id, device_id, priority, power_point_id, rated_kw, \
+             shed_payload, restore_payload, enabled, created_at, updated_at) \
+             values ($1, $2, $3, $4, $5, $6, $7::jsonb, $8::jsonb, $9, \
+             to_timestamp($10 / 1000.0), to_timestamp($11 / 1000.0)) \
+             on conflict (tenant_id, project_id, device_id) do update set \
+             priority = excluded.priority, \
+             power_point_id = excluded.power_point_id, \
+             rated_kw = excluded.rated_kw, \
+             shed_payload = excluded.shed_payload, \
+             restore_payload = excluded.restore_payload, \
+             enabled = excluded.enabled, \
+             updated_at = excluded.updated_at \
+             returning {LOAD_COLUMNS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&record.tenant_id)
+            .bind(&record.project_id)
+            .bind(&record.device_id)
+            .bind(record.priority)
+            .bind(&record.power_point_id)
+            .bind(record.rated_kw)
+            .bind(&record.shed_payload)
+            .bind(&record.restore_payload)
+            .bind(record.enabled)
+            .bind(record.created_at_ms as f64)
+            .bind(record.updated_at_ms as f64)
+            .fetch_one(&self.pool)
+            .await?;
+        load_from_row(&row)
+    }
+
+    async fn delete_sheddable_load(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        device_id: &str,
+    ) -> Result<bool, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let result = sqlx::query(
+            "delete from demand_response_loads \
+             where tenant_id = $1 and project_id = $2 and device_id = $3",
+        )
+        .bind(&ctx.tenant_id)
+        .bind(project_id)
+        .bind(device_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn list_demand_response_events(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        limit: i64,
+    ) -> Result<Vec<DemandResponseEventRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let sql = format!(
+            "select {EVENT_COLUMNS} from demand_response_events \
+             where tenant_id = $1 and project_id = $2 \
+             order by start_at desc \
+             limit $3"
+        );
+        let rows = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .bind(limit.max(0))
+            .fetch_all(&self.pool)
+            .await?;
+        let mut items = Vec::with_capacity(rows.len());
+        for row in rows {
+            items.push(event_from_row(&row)?);
+        }
+        Ok(items)
+    }
+
+    async fn find_demand_response_event(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        event_id: &str,
+    ) -> Result<Option<DemandResponseEventRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let sql = format!(
+            "select {EVENT_COLUMNS} from demand_response_events \
+             where tenant_id = $1 and project_id = $2 and event_id = $3"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .bind(event_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(event_from_row).transpose()
+    }
+
+    async fn create_demand_response_event(
+        &self,
+        ctx: &TenantContext,
+        record: DemandResponseEventRecord,
+    ) -> Result<DemandResponseEventRecord, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let sql = format!(
+            "insert into demand_response_events \
+             (tenant_id, project_id, event_id, target_kw, start_at, end_at, status, \
+             cancel_requested, baseline_kw, achieved_kw, loads, created_by, created_at, updated_at) \
+             values ($1, $2, $3, $4, to_timestamp($5 / 1000.0), to_timestamp($6 / 1000.0), $7, \
+             $8, $9, $10, $11::jsonb, $12, to_timestamp($13 / 1000.0), to_timestamp($14 / 1000.0)) \
+             returning {EVENT_COLUMNS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&record.tenant_id)
+            .bind(&record.project_id)
+            .bind(&record.event_id)
+            .bind(record.target_kw)
+            .bind(record.start_at_ms as f64)
+            .bind(record.end_at_ms as f64)
+            .bind(&record.status)
+            .bind(record.cancel_requested)
+            .bind(record.baseline_kw)
+            .bind(record.achieved_kw)
+            .bind(&record.loads)
+            .bind(&record.created_by)
+            .bind(record.created_at_ms as f64)
+            .bind(record.updated_at_ms as f64)
+            .fetch_one(&self.pool)
+            .await?;
+        event_from_row(&row)
+    }
+
+    async fn update_demand_response_event(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        event_id: &str,
+        update: DemandResponseEventUpdate,
+    ) -> Result<Option<DemandResponseEventRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let sql = format!(
+            "update demand_response_events set \
+             status = coalesce($1, status), \
+             cancel_requested = coalesce($2, cancel_requested), \
+             baseline_kw = coalesce($3, baseline_kw), \
+             achieved_kw = coalesce($4, achieved_kw), \
+             loads = coalesce($5::jsonb, loads), \
+             updated_at = to_timestamp($6 / 1000.0) \
+             where tenant_id = $7 and project_id = $8 and event_id = $9 \
+             returning {EVENT_COLUMNS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(update.status)
+            .bind(update.cancel_requested)
+            .bind(update.baseline_kw)
+            .bind(update.achieved_kw)
+            .bind(update.loads)
+            .bind(update.updated_at_ms as f64)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .bind(event_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(event_from_row).transpose()
+    }
+
+    async fn list_open_demand_response_events(
+        &self,
+    ) -> Result<Vec<DemandResponseEventRecord>, StorageError> {
+        let sql = format!(
+            "select {EVENT_COLUMNS} from demand_response_events \
+             where status in ('scheduled', 'active') \
+             order by start_at"
+        );
+        let rows = sqlx::query(&sql).fetch_all(&self.pool).await?;
+        let mut items = Vec::with_capacity(rows.len());
+        for row in rows {
+            items.push(event_from_row(&row)?);
+        }
+        Ok(items)
+    }
+}
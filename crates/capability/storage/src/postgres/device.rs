@@ -10,7 +10,7 @@
 //! - **返回更新后数据**：update/delete 操作返回完整记录或受影响行数
 
 use crate::error::StorageError;
-use crate::models::{DeviceRecord, DeviceUpdate};
+use crate::models::{CollectionVersion, DeviceRecord, DeviceUpdate};
 use crate::traits::DeviceStore;
 use crate::validation::ensure_project_scope;
 use domain::TenantContext;
@@ -81,7 +81,7 @@ impl DeviceStore for PgDeviceStore {
 
         // 查询指定租户和项目下的所有设备
         let rows = sqlx::query(
-            "select device_id, tenant_id, project_id, gateway_id, name, model, room_id, address_config \
+            "select device_id, tenant_id, project_id, gateway_id, name, model, room_id, address_config, offline_after_seconds \
              from devices where tenant_id = $1 and project_id = $2",
         )
         .bind(&ctx.tenant_id)
@@ -101,11 +101,34 @@ impl DeviceStore for PgDeviceStore {
                 model: row.try_get("model")?,
                 room_id: row.try_get("room_id")?,
                 address_config: row.try_get("address_config")?,
+                offline_after_seconds: row.try_get("offline_after_seconds")?,
             });
         }
         Ok(devices)
     }
 
+    /// 指定项目设备集合的版本
+    async fn devices_version(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<CollectionVersion, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let row = sqlx::query(
+            "select count(*) as count, \
+             coalesce((extract(epoch from max(updated_at)) * 1000000)::bigint, 0) as revision \
+             from devices where tenant_id = $1 and project_id = $2",
+        )
+        .bind(&ctx.tenant_id)
+        .bind(project_id)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(CollectionVersion {
+            count: row.try_get("count")?,
+            revision: row.try_get("revision")?,
+        })
+    }
+
     /// 查找指定设备
     ///
     /// # 安全
@@ -128,7 +151,7 @@ impl DeviceStore for PgDeviceStore {
 
         // 使用三重条件查询：租户 + 项目 + 设备 ID
         let row = sqlx::query(
-            "select device_id, tenant_id, project_id, gateway_id, name, model, room_id, address_config \
+            "select device_id, tenant_id, project_id, gateway_id, name, model, room_id, address_config, offline_after_seconds \
              from devices where tenant_id = $1 and project_id = $2 and device_id = $3",
         )
         .bind(&ctx.tenant_id)
@@ -151,6 +174,7 @@ impl DeviceStore for PgDeviceStore {
             model: row.try_get("model")?,
             room_id: row.try_get("room_id")?,
             address_config: row.try_get("address_config")?,
+            offline_after_seconds: row.try_get("offline_after_seconds")?,
         }))
     }
 
@@ -174,13 +198,13 @@ impl DeviceStore for PgDeviceStore {
 
         // 验证租户 ID 一致性：防止恶意用户创建跨租户数据
         if record.tenant_id != ctx.tenant_id {
-            return Err(StorageError::new("tenant mismatch"));
+            return Err(StorageError::forbidden("tenant mismatch"));
         }
 
         // 执行插入操作
         sqlx::query(
-            "insert into devices (device_id, tenant_id, project_id, gateway_id, name, model, room_id, address_config) \
-             values ($1, $2, $3, $4, $5, $6, $7, $8)",
+            "insert into devices (device_id, tenant_id, project_id, gateway_id, name, model, room_id, address_config, offline_after_seconds) \
+             values ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
         )
         .bind(&record.device_id)
         .bind(&record.tenant_id)
@@ -190,6 +214,7 @@ impl DeviceStore for PgDeviceStore {
         .bind(&record.model)
         .bind(&record.room_id)
         .bind(&record.address_config)
+        .bind(record.offline_after_seconds)
         .execute(&self.pool)
         .await?;
 
@@ -229,14 +254,17 @@ impl DeviceStore for PgDeviceStore {
              name = coalesce($1, name), \
              model = coalesce($2, model), \
              room_id = coalesce($3, room_id), \
-             address_config = coalesce($4, address_config) \
-             where tenant_id = $5 and project_id = $6 and device_id = $7 \
-             returning device_id, tenant_id, project_id, gateway_id, name, model, room_id, address_config",
+             address_config = coalesce($4, address_config), \
+             offline_after_seconds = coalesce($5, offline_after_seconds), \
+             updated_at = now() \
+             where tenant_id = $6 and project_id = $7 and device_id = $8 \
+             returning device_id, tenant_id, project_id, gateway_id, name, model, room_id, address_config, offline_after_seconds",
         )
         .bind(update.name)
         .bind(update.model)
         .bind(update.room_id)
         .bind(update.address_config)
+        .bind(update.offline_after_seconds)
         .bind(&ctx.tenant_id)
         .bind(project_id)
         .bind(device_id)
@@ -257,6 +285,7 @@ impl DeviceStore for PgDeviceStore {
             model: row.try_get("model")?,
             room_id: row.try_get("room_id")?,
             address_config: row.try_get("address_config")?,
+            offline_after_seconds: row.try_get("offline_after_seconds")?,
         }))
     }
 
@@ -326,4 +355,32 @@ impl DeviceStore for PgDeviceStore {
         // 根据受影响行数判断是否删除成功
         Ok(result.rows_affected() > 0)
     }
+
+    /// 查询全部租户的设备
+    ///
+    /// 不经过租户上下文，仅供离线检测后台任务使用。
+    async fn list_all_devices(&self) -> Result<Vec<DeviceRecord>, StorageError> {
+        let rows = sqlx::query(
+            "select device_id, tenant_id, project_id, gateway_id, name, model, room_id, address_config, offline_after_seconds \
+             from devices order by device_id",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut devices = Vec::with_capacity(rows.len());
+        for row in rows {
+            devices.push(DeviceRecord {
+                device_id: row.try_get("device_id")?,
+                tenant_id: row.try_get("tenant_id")?,
+                project_id: row.try_get("project_id")?,
+                gateway_id: row.try_get("gateway_id")?,
+                name: row.try_get("name")?,
+                model: row.try_get("model")?,
+                room_id: row.try_get("room_id")?,
+                address_config: row.try_get("address_config")?,
+                offline_after_seconds: row.try_get("offline_after_seconds")?,
+            });
+        }
+        Ok(devices)
+    }
 }
@@ -0,0 +1,99 @@
+//! Postgres 设备时间线实现
+
+use crate::error::StorageError;
+use crate::models::DeviceEventRecord;
+use crate::traits::{DeviceEventQuery, DeviceEventStore};
+use crate::validation::ensure_project_scope;
+use domain::TenantContext;
+use sqlx::{PgPool, Row};
+
+pub struct PgDeviceEventStore {
+    pub pool: PgPool,
+}
+
+impl PgDeviceEventStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl DeviceEventStore for PgDeviceEventStore {
+    async fn insert_device_event(
+        &self,
+        ctx: &TenantContext,
+        record: DeviceEventRecord,
+    ) -> Result<bool, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let result = sqlx::query(
+            "insert into device_events \
+             (tenant_id, project_id, device_id, event_id, event_type, occurred_at, data) \
+             values ($1, $2, $3, $4, $5, to_timestamp($6 / 1000.0), $7::jsonb) \
+             on conflict (tenant_id, device_id, event_id) do nothing",
+        )
+        .bind(&record.tenant_id)
+        .bind(&record.project_id)
+        .bind(&record.device_id)
+        .bind(&record.event_id)
+        .bind(&record.event_type)
+        .bind(record.occurred_at_ms as f64)
+        .bind(&record.data)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn list_device_events(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        device_id: &str,
+        options: DeviceEventQuery,
+    ) -> Result<Vec<DeviceEventRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        // 游标仅给时间戳时按时间严格截断；同时给 event_id 时按 (occurred_at, event_id) 比较
+        let rows = sqlx::query(
+            "select tenant_id, project_id, device_id, event_id, event_type, \
+             (extract(epoch from occurred_at) * 1000)::bigint as occurred_at_ms, \
+             data::text as data \
+             from device_events \
+             where tenant_id = $1 and project_id = $2 and device_id = $3 \
+             and ($4::text is null or event_type = $4) \
+             and ($5::double precision is null or occurred_at >= to_timestamp($5 / 1000.0)) \
+             and ($6::double precision is null or occurred_at <= to_timestamp($6 / 1000.0)) \
+             and ($7::double precision is null \
+                  or occurred_at < to_timestamp($7 / 1000.0) \
+                  or ($8::text is not null and occurred_at = to_timestamp($7 / 1000.0) \
+                      and event_id < $8)) \
+             order by occurred_at desc, event_id desc \
+             limit $9",
+        )
+        .bind(&ctx.tenant_id)
+        .bind(project_id)
+        .bind(device_id)
+        .bind(options.event_type)
+        .bind(options.from_ms.map(|value| value as f64))
+        .bind(options.to_ms.map(|value| value as f64))
+        .bind(options.cursor_ts_ms.map(|value| value as f64))
+        .bind(options.cursor_event_id)
+        .bind(options.limit.max(0))
+        .fetch_all(&self.pool)
+        .await?;
+        let mut items = Vec::with_capacity(rows.len());
+        for row in rows {
+            items.push(DeviceEventRecord {
+                tenant_id: row.try_get("tenant_id")?,
+                project_id: row.try_get("project_id")?,
+                device_id: row.try_get("device_id")?,
+                event_id: row.try_get("event_id")?,
+                event_type: row.try_get("event_type")?,
+                occurred_at_ms: row.try_get("occurred_at_ms")?,
+                data: row.try_get("data")?,
+            });
+        }
+        Ok(items)
+    }
+}
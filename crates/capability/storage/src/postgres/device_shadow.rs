@@ -0,0 +1,120 @@
+//! Postgres 设备影子实现
+
+use crate::error::StorageError;
+use crate::models::DeviceShadowRecord;
+use crate::traits::DeviceShadowStore;
+use crate::validation::ensure_project_scope;
+use domain::TenantContext;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+
+pub struct PgDeviceShadowStore {
+    pub pool: PgPool,
+}
+
+impl PgDeviceShadowStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+const DEVICE_SHADOW_COLUMNS: &str = "tenant_id, project_id, device_id, desired::text as desired, \
+     version, last_command_id, updated_by, \
+     (extract(epoch from updated_at) * 1000)::bigint as updated_at_ms";
+
+fn device_shadow_from_row(row: &PgRow) -> Result<DeviceShadowRecord, StorageError> {
+    Ok(DeviceShadowRecord {
+        tenant_id: row.try_get("tenant_id")?,
+        project_id: row.try_get("project_id")?,
+        device_id: row.try_get("device_id")?,
+        desired: row.try_get("desired")?,
+        version: row.try_get("version")?,
+        last_command_id: row.try_get("last_command_id")?,
+        updated_by: row.try_get("updated_by")?,
+        updated_at_ms: row.try_get("updated_at_ms")?,
+    })
+}
+
+#[async_trait::async_trait]
+impl DeviceShadowStore for PgDeviceShadowStore {
+    async fn get_device_shadow(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        device_id: &str,
+    ) -> Result<Option<DeviceShadowRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let sql = format!(
+            "select {DEVICE_SHADOW_COLUMNS} from device_shadows \
+             where tenant_id = $1 and project_id = $2 and device_id = $3"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .bind(device_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        Ok(Some(device_shadow_from_row(&row)?))
+    }
+
+    async fn put_desired_state(
+        &self,
+        ctx: &TenantContext,
+        record: DeviceShadowRecord,
+    ) -> Result<DeviceShadowRecord, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let sql = format!(
+            "insert into device_shadows \
+             (tenant_id, project_id, device_id, desired, version, last_command_id, updated_by, updated_at) \
+             values ($1, $2, $3, $4::jsonb, 1, null, $5, to_timestamp($6 / 1000.0)) \
+             on conflict (tenant_id, project_id, device_id) do update set \
+             desired = excluded.desired, version = device_shadows.version + 1, \
+             last_command_id = null, updated_by = excluded.updated_by, updated_at = excluded.updated_at \
+             returning {DEVICE_SHADOW_COLUMNS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&record.tenant_id)
+            .bind(&record.project_id)
+            .bind(&record.device_id)
+            .bind(&record.desired)
+            .bind(&record.updated_by)
+            .bind(record.updated_at_ms as f64)
+            .fetch_one(&self.pool)
+            .await?;
+        device_shadow_from_row(&row)
+    }
+
+    async fn set_shadow_command(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        device_id: &str,
+        version: i64,
+        command_id: &str,
+    ) -> Result<Option<DeviceShadowRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let sql = format!(
+            "update device_shadows set last_command_id = $1 \
+             where tenant_id = $2 and project_id = $3 and device_id = $4 and version = $5 \
+             returning {DEVICE_SHADOW_COLUMNS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(command_id)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .bind(device_id)
+            .bind(version)
+            .fetch_optional(&self.pool)
+            .await?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        Ok(Some(device_shadow_from_row(&row)?))
+    }
+}
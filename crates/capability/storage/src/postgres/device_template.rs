@@ -0,0 +1,279 @@
+//! Postgres 设备模板存储实现
+//!
+//! 通过 SQL 查询实现设备模板增删查与设备实例化，实现 [`DeviceTemplateStore`] trait。
+//!
+//! ## 设计要点
+//!
+//! - **多租户隔离**：所有 SQL 查询都包含 `tenant_id` 过滤条件
+//! - **模板点位**：标准点位存放在 `device_template_points`，按 `ordinal` 保持顺序
+//! - **事务实例化**：设备、点位、点位映射在同一事务内写入，任一失败整体回滚
+
+use crate::error::StorageError;
+use crate::models::{DeviceInstance, DeviceTemplatePoint, DeviceTemplateRecord};
+use crate::traits::DeviceTemplateStore;
+use crate::validation::ensure_project_scope;
+use domain::TenantContext;
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+
+/// PostgreSQL 设备模板存储实现
+pub struct PgDeviceTemplateStore {
+    /// PostgreSQL 连接池
+    pub pool: PgPool,
+}
+
+impl PgDeviceTemplateStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn connect(database_url: &str) -> Result<Self, StorageError> {
+        let pool = crate::connection::connect_pool(database_url).await?;
+        Ok(Self { pool })
+    }
+
+    /// 批量加载模板点位（按 template_id 分组，保持 ordinal 顺序）。
+    async fn load_points(
+        &self,
+        template_ids: &[String],
+    ) -> Result<HashMap<String, Vec<DeviceTemplatePoint>>, StorageError> {
+        let mut grouped: HashMap<String, Vec<DeviceTemplatePoint>> = HashMap::new();
+        if template_ids.is_empty() {
+            return Ok(grouped);
+        }
+        let rows = sqlx::query(
+            "select template_id, key, data_type, unit, source_type, address, scale, offset_value, protocol_detail \
+             from device_template_points where template_id = any($1) order by template_id, ordinal",
+        )
+        .bind(template_ids)
+        .fetch_all(&self.pool)
+        .await?;
+        for row in rows {
+            let template_id: String = row.try_get("template_id")?;
+            grouped
+                .entry(template_id)
+                .or_default()
+                .push(DeviceTemplatePoint {
+                    key: row.try_get("key")?,
+                    data_type: row.try_get("data_type")?,
+                    unit: row.try_get("unit")?,
+                    source_type: row.try_get("source_type")?,
+                    address: row.try_get("address")?,
+                    scale: row.try_get("scale")?,
+                    offset: row.try_get("offset_value")?,
+                    protocol_detail: row.try_get("protocol_detail")?,
+                });
+        }
+        Ok(grouped)
+    }
+}
+
+#[async_trait::async_trait]
+impl DeviceTemplateStore for PgDeviceTemplateStore {
+    async fn list_device_templates(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<Vec<DeviceTemplateRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let rows = sqlx::query(
+            "select template_id, tenant_id, project_id, name, model \
+             from device_templates where tenant_id = $1 and project_id = $2 order by created_at",
+        )
+        .bind(&ctx.tenant_id)
+        .bind(project_id)
+        .fetch_all(&self.pool)
+        .await?;
+        let mut templates = Vec::with_capacity(rows.len());
+        for row in rows {
+            templates.push(DeviceTemplateRecord {
+                template_id: row.try_get("template_id")?,
+                tenant_id: row.try_get("tenant_id")?,
+                project_id: row.try_get("project_id")?,
+                name: row.try_get("name")?,
+                model: row.try_get("model")?,
+                points: Vec::new(),
+            });
+        }
+        let template_ids: Vec<String> = templates
+            .iter()
+            .map(|item| item.template_id.clone())
+            .collect();
+        let mut points = self.load_points(&template_ids).await?;
+        for template in templates.iter_mut() {
+            template.points = points.remove(&template.template_id).unwrap_or_default();
+        }
+        Ok(templates)
+    }
+
+    async fn find_device_template(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        template_id: &str,
+    ) -> Result<Option<DeviceTemplateRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let row = sqlx::query(
+            "select template_id, tenant_id, project_id, name, model \
+             from device_templates where tenant_id = $1 and project_id = $2 and template_id = $3",
+        )
+        .bind(&ctx.tenant_id)
+        .bind(project_id)
+        .bind(template_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let mut points = self.load_points(&[template_id.to_string()]).await?;
+        Ok(Some(DeviceTemplateRecord {
+            template_id: row.try_get("template_id")?,
+            tenant_id: row.try_get("tenant_id")?,
+            project_id: row.try_get("project_id")?,
+            name: row.try_get("name")?,
+            model: row.try_get("model")?,
+            points: points.remove(template_id).unwrap_or_default(),
+        }))
+    }
+
+    async fn create_device_template(
+        &self,
+        ctx: &TenantContext,
+        record: DeviceTemplateRecord,
+    ) -> Result<DeviceTemplateRecord, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "insert into device_templates (template_id, tenant_id, project_id, name, model) \
+             values ($1, $2, $3, $4, $5)",
+        )
+        .bind(&record.template_id)
+        .bind(&record.tenant_id)
+        .bind(&record.project_id)
+        .bind(&record.name)
+        .bind(&record.model)
+        .execute(&mut *tx)
+        .await?;
+        for (ordinal, point) in record.points.iter().enumerate() {
+            sqlx::query(
+                "insert into device_template_points \
+                 (template_id, ordinal, key, data_type, unit, source_type, address, scale, offset_value, protocol_detail) \
+                 values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+            )
+            .bind(&record.template_id)
+            .bind(ordinal as i32)
+            .bind(&point.key)
+            .bind(&point.data_type)
+            .bind(&point.unit)
+            .bind(&point.source_type)
+            .bind(&point.address)
+            .bind(point.scale)
+            .bind(point.offset)
+            .bind(&point.protocol_detail)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(record)
+    }
+
+    async fn delete_device_template(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        template_id: &str,
+    ) -> Result<bool, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        // device_template_points 通过 ON DELETE CASCADE 随模板删除
+        let result = sqlx::query(
+            "delete from device_templates where tenant_id = $1 and project_id = $2 and template_id = $3",
+        )
+        .bind(&ctx.tenant_id)
+        .bind(project_id)
+        .bind(template_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn instantiate_device(
+        &self,
+        ctx: &TenantContext,
+        instance: DeviceInstance,
+    ) -> Result<DeviceInstance, StorageError> {
+        ensure_project_scope(ctx, &instance.device.project_id)?;
+        if instance.device.tenant_id != ctx.tenant_id
+            || instance
+                .points
+                .iter()
+                .any(|item| item.tenant_id != ctx.tenant_id)
+            || instance
+                .mappings
+                .iter()
+                .any(|item| item.tenant_id != ctx.tenant_id)
+        {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+
+        // 使用事务确保设备、点位、点位映射要么全部写入，要么全部回滚
+        let mut tx = self.pool.begin().await?;
+
+        let device = &instance.device;
+        sqlx::query(
+            "insert into devices (device_id, tenant_id, project_id, gateway_id, name, model, room_id, address_config, offline_after_seconds) \
+             values ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+        )
+        .bind(&device.device_id)
+        .bind(&device.tenant_id)
+        .bind(&device.project_id)
+        .bind(&device.gateway_id)
+        .bind(&device.name)
+        .bind(&device.model)
+        .bind(&device.room_id)
+        .bind(&device.address_config)
+        .bind(device.offline_after_seconds)
+        .execute(&mut *tx)
+        .await?;
+
+        for point in &instance.points {
+            sqlx::query(
+                "insert into points (point_id, tenant_id, project_id, device_id, key, data_type, unit, tags) \
+                 values ($1, $2, $3, $4, $5, $6, $7, $8)",
+            )
+            .bind(&point.point_id)
+            .bind(&point.tenant_id)
+            .bind(&point.project_id)
+            .bind(&point.device_id)
+            .bind(&point.key)
+            .bind(&point.data_type)
+            .bind(&point.unit)
+            .bind(&point.tags)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for mapping in &instance.mappings {
+            sqlx::query(
+                "insert into point_sources (source_id, tenant_id, project_id, point_id, source_type, address, scale, offset_value, protocol_detail) \
+                 values ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+            )
+            .bind(&mapping.source_id)
+            .bind(&mapping.tenant_id)
+            .bind(&mapping.project_id)
+            .bind(&mapping.point_id)
+            .bind(&mapping.source_type)
+            .bind(&mapping.address)
+            .bind(mapping.scale)
+            .bind(mapping.offset)
+            .bind(&mapping.protocol_detail)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(instance)
+    }
+}
@@ -0,0 +1,113 @@
+//! Postgres 碳排放因子实现
+//!
+//! 租户默认值以空串 `project_id` 保存，便于用主键做覆盖写入。
+
+use crate::error::StorageError;
+use crate::models::EmissionFactorRecord;
+use crate::traits::EmissionFactorStore;
+use crate::validation::{ensure_project_scope, ensure_tenant};
+use domain::TenantContext;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+
+pub struct PgEmissionFactorStore {
+    pub pool: PgPool,
+}
+
+impl PgEmissionFactorStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+const EMISSION_FACTOR_COLUMNS: &str = "tenant_id, project_id, energy_source, kg_co2e_per_unit, unit, \
+     (extract(epoch from updated_at) * 1000)::bigint as updated_at_ms";
+
+fn emission_factor_from_row(row: &PgRow) -> Result<EmissionFactorRecord, StorageError> {
+    let project_id: String = row.try_get("project_id")?;
+    Ok(EmissionFactorRecord {
+        tenant_id: row.try_get("tenant_id")?,
+        project_id: (!project_id.is_empty()).then_some(project_id),
+        energy_source: row.try_get("energy_source")?,
+        kg_co2e_per_unit: row.try_get("kg_co2e_per_unit")?,
+        unit: row.try_get("unit")?,
+        updated_at_ms: row.try_get("updated_at_ms")?,
+    })
+}
+
+fn ensure_scope(ctx: &TenantContext, project_id: Option<&str>) -> Result<(), StorageError> {
+    match project_id {
+        Some(project_id) => ensure_project_scope(ctx, project_id),
+        None => ensure_tenant(ctx),
+    }
+}
+
+#[async_trait::async_trait]
+impl EmissionFactorStore for PgEmissionFactorStore {
+    async fn list_emission_factors(
+        &self,
+        ctx: &TenantContext,
+        project_id: Option<&str>,
+    ) -> Result<Vec<EmissionFactorRecord>, StorageError> {
+        ensure_scope(ctx, project_id)?;
+        let sql = format!(
+            "select {EMISSION_FACTOR_COLUMNS} from emission_factors \
+             where tenant_id = $1 and project_id = $2 order by energy_source"
+        );
+        let rows = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(project_id.unwrap_or_default())
+            .fetch_all(&self.pool)
+            .await?;
+        rows.iter().map(emission_factor_from_row).collect()
+    }
+
+    async fn upsert_emission_factor(
+        &self,
+        ctx: &TenantContext,
+        record: EmissionFactorRecord,
+    ) -> Result<EmissionFactorRecord, StorageError> {
+        ensure_scope(ctx, record.project_id.as_deref())?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let sql = format!(
+            "insert into emission_factors \
+             (tenant_id, project_id, energy_source, kg_co2e_per_unit, unit, updated_at) \
+             values ($1, $2, $3, $4, $5, to_timestamp($6 / 1000.0)) \
+             on conflict (tenant_id, project_id, energy_source) do update set \
+             kg_co2e_per_unit = excluded.kg_co2e_per_unit, unit = excluded.unit, \
+             updated_at = excluded.updated_at \
+             returning {EMISSION_FACTOR_COLUMNS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&record.tenant_id)
+            .bind(record.project_id.as_deref().unwrap_or_default())
+            .bind(&record.energy_source)
+            .bind(record.kg_co2e_per_unit)
+            .bind(&record.unit)
+            .bind(record.updated_at_ms as f64)
+            .fetch_one(&self.pool)
+            .await?;
+        emission_factor_from_row(&row)
+    }
+
+    async fn delete_emission_factor(
+        &self,
+        ctx: &TenantContext,
+        project_id: Option<&str>,
+        energy_source: &str,
+    ) -> Result<bool, StorageError> {
+        ensure_scope(ctx, project_id)?;
+        let result = sqlx::query(
+            "delete from emission_factors \
+             where tenant_id = $1 and project_id = $2 and energy_source = $3",
+        )
+        .bind(&ctx.tenant_id)
+        .bind(project_id.unwrap_or_default())
+        .bind(energy_source)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,111 @@
+//! Postgres 功能开关实现
+
+use crate::error::StorageError;
+use crate::models::FeatureFlagRecord;
+use crate::traits::FeatureFlagStore;
+use crate::validation::ensure_tenant;
+use domain::TenantContext;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+
+pub struct PgFeatureFlagStore {
+    pub pool: PgPool,
+}
+
+impl PgFeatureFlagStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+const FEATURE_FLAG_COLUMNS: &str = "tenant_id, flag_key, enabled, variant, \
+     (extract(epoch from updated_at) * 1000)::bigint as updated_at_ms";
+
+fn feature_flag_from_row(row: &PgRow) -> Result<FeatureFlagRecord, StorageError> {
+    Ok(FeatureFlagRecord {
+        tenant_id: row.try_get("tenant_id")?,
+        flag_key: row.try_get("flag_key")?,
+        enabled: row.try_get("enabled")?,
+        variant: row.try_get("variant")?,
+        updated_at_ms: row.try_get("updated_at_ms")?,
+    })
+}
+
+#[async_trait::async_trait]
+impl FeatureFlagStore for PgFeatureFlagStore {
+    async fn list_feature_flags(
+        &self,
+        ctx: &TenantContext,
+    ) -> Result<Vec<FeatureFlagRecord>, StorageError> {
+        ensure_tenant(ctx)?;
+        let sql = format!(
+            "select {FEATURE_FLAG_COLUMNS} from tenant_feature_flags \
+             where tenant_id = $1 order by flag_key"
+        );
+        let rows = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .fetch_all(&self.pool)
+            .await?;
+        rows.iter().map(feature_flag_from_row).collect()
+    }
+
+    async fn get_feature_flag(
+        &self,
+        ctx: &TenantContext,
+        flag_key: &str,
+    ) -> Result<Option<FeatureFlagRecord>, StorageError> {
+        ensure_tenant(ctx)?;
+        let sql = format!(
+            "select {FEATURE_FLAG_COLUMNS} from tenant_feature_flags \
+             where tenant_id = $1 and flag_key = $2"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(flag_key)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(feature_flag_from_row).transpose()
+    }
+
+    async fn upsert_feature_flag(
+        &self,
+        ctx: &TenantContext,
+        record: FeatureFlagRecord,
+    ) -> Result<FeatureFlagRecord, StorageError> {
+        ensure_tenant(ctx)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let sql = format!(
+            "insert into tenant_feature_flags (tenant_id, flag_key, enabled, variant, updated_at) \
+             values ($1, $2, $3, $4, to_timestamp($5 / 1000.0)) \
+             on conflict (tenant_id, flag_key) do update set \
+             enabled = excluded.enabled, variant = excluded.variant, updated_at = excluded.updated_at \
+             returning {FEATURE_FLAG_COLUMNS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&record.tenant_id)
+            .bind(&record.flag_key)
+            .bind(record.enabled)
+            .bind(&record.variant)
+            .bind(record.updated_at_ms as f64)
+            .fetch_one(&self.pool)
+            .await?;
+        feature_flag_from_row(&row)
+    }
+
+    async fn delete_feature_flag(
+        &self,
+        ctx: &TenantContext,
+        flag_key: &str,
+    ) -> Result<bool, StorageError> {
+        ensure_tenant(ctx)?;
+        let result =
+            sqlx::query("delete from tenant_feature_flags where tenant_id = $1 and flag_key = $2")
+                .bind(&ctx.tenant_id)
+                .bind(flag_key)
+                .execute(&self.pool)
+                .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,342 @@
+//! Postgres 固件包、升级批次与网关升级进度实现
+
+use crate::error::StorageError;
+use crate::models::{
+    FirmwareCampaignRecord, FirmwarePackageRecord, FirmwareRolloutRecord, FirmwareRolloutUpdate,
+};
+use crate::traits::FirmwareStore;
+use crate::validation::ensure_project_scope;
+use domain::TenantContext;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+
+pub struct PgFirmwareStore {
+    pub pool: PgPool,
+}
+
+impl PgFirmwareStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+const PACKAGE_COLUMNS: &str = "tenant_id, project_id, package_id, name, version, \
+     checksum_sha256, size_bytes, storage_url, metadata::text as metadata, created_by, \
+     (extract(epoch from created_at) * 1000)::bigint as created_at_ms";
+
+const CAMPAIGN_COLUMNS: &str = "tenant_id, project_id, campaign_id, package_id, name, status, \
+     created_by, \
+     (extract(epoch from created_at) * 1000)::bigint as created_at_ms, \
+     (extract(epoch from updated_at) * 1000)::bigint as updated_at_ms";
+
+const ROLLOUT_COLUMNS: &str = "tenant_id, project_id, campaign_id, gateway_id, status, progress, \
+     message, (extract(epoch from updated_at) * 1000)::bigint as updated_at_ms";
+
+fn package_from_row(row: &PgRow) -> Result<FirmwarePackageRecord, StorageError> {
+    Ok(FirmwarePackageRecord {
+        tenant_id: row.try_get("tenant_id")?,
+        project_id: row.try_get("project_id")?,
+        package_id: row.try_get("package_id")?,
+        name: row.try_get("name")?,
+        version: row.try_get("version")?,
+        checksum_sha256: row.try_get("checksum_sha256")?,
+        size_bytes: row.try_get("size_bytes")?,
+        storage_url: row.try_get("storage_url")?,
+        metadata: row.try_get("metadata")?,
+        created_by: row.try_get("created_by")?,
+        created_at_ms: row.try_get("created_at_ms")?,
+    })
+}
+
+fn campaign_from_row(row: &PgRow) -> Result<FirmwareCampaignRecord, StorageError> {
+    Ok(FirmwareCampaignRecord {
+        tenant_id: row.try_get("tenant_id")?,
+        project_id: row.try_get("project_id")?,
+        campaign_id: row.try_get("campaign_id")?,
+        package_id: row.try_get("package_id")?,
+        name: row.try_get("name")?,
+        status: row.try_get("status")?,
+        created_by: row.try_get("created_by")?,
+        created_at_ms: row.try_get("created_at_ms")?,
+        updated_at_ms: row.try_get("updated_at_ms")?,
+    })
+}
+
+fn rollout_from_row(row: &PgRow) -> Result<FirmwareRolloutRecord, StorageError> {
+    Ok(FirmwareRolloutRecord {
+        tenant_id: row.try_get("tenant_id")?,
+        project_id: row.try_get("project_id")?,
+        campaign_id: row.try_get("campaign_id")?,
+        gateway_id: row.try_get("gateway_id")?,
+        status: row.try_get("status")?,
+        progress: row.try_get("progress")?,
+        message: row.try_get("message")?,
+        updated_at_ms: row.try_get("updated_at_ms")?,
+    })
+}
+
+#[async_trait::async_trait]
+impl FirmwareStore for PgFirmwareStore {
+    async fn list_firmware_packages(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<Vec<FirmwarePackageRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let sql = format!(
+            "select {PACKAGE_COLUMNS} from firmware_packages \
+             where tenant_id = $1 and project_id = $2 \
+             order by created_at desc"
+        );
+        let rows = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .fetch_all(&self.pool)
+            .await?;
+        let mut items = Vec::with_capacity(rows.len());
+        for row in rows {
+            items.push(package_from_row(&row)?);
+        }
+        Ok(items)
+    }
+
+    async fn find_firmware_package(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        package_id: &str,
+    ) -> Result<Option<FirmwarePackageRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let sql = format!(
+            "select {PACKAGE_COLUMNS} from firmware_packages \
+             where tenant_id = $1 and project_id = $2 and package_id = $3"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .bind(package_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(package_from_row).transpose()
+    }
+
+    async fn create_firmware_package(
+        &self,
+        ctx: &TenantContext,
+        record: FirmwarePackageRecord,
+    ) -> Result<FirmwarePackageRecord, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let sql = format!(
+            "insert into firmware_packages \
+             (tenant_id, project_id, package_id, name, version, checksum_sha256, size_bytes, \
+             storage_url, metadata, created_by, created_at) \
+             values ($1, $2, $3, $4, $5, $6, $7, $8, $9::jsonb, $10, to_timestamp($11 / 1000.0)) \
+             returning {PACKAGE_COLUMNS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&record.tenant_id)
+            .bind(&record.project_id)
+            .bind(&record.package_id)
+            .bind(&record.name)
+            .bind(&record.version)
+            .bind(&record.checksum_sha256)
+            .bind(record.size_bytes)
+            .bind(&record.storage_url)
+            .bind(&record.metadata)
+            .bind(&record.created_by)
+            .bind(record.created_at_ms as f64)
+            .fetch_one(&self.pool)
+            .await?;
+        package_from_row(&row)
+    }
+
+    async fn list_firmware_campaigns(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        limit: i64,
+    ) -> Result<Vec<FirmwareCampaignRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let sql = format!(
+            "select {CAMPAIGN_COLUMNS} from firmware_campaigns \
+             where tenant_id = $1 and project_id = $2 \
+             order by created_at desc \
+             limit $3"
+        );
+        let rows = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .bind(limit.max(0))
+            .fetch_all(&self.pool)
+            .await?;
+        let mut items = Vec::with_capacity(rows.len());
+        for row in rows {
+            items.push(campaign_from_row(&row)?);
+        }
+        Ok(items)
+    }
+
+    async fn find_firmware_campaign(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        campaign_id: &str,
+    ) -> Result<Option<FirmwareCampaignRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let sql = format!(
+            "select {CAMPAIGN_COLUMNS} from firmware_campaigns \
+             where tenant_id = $1 and project_id = $2 and campaign_id = $3"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .bind(campaign_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(campaign_from_row).transpose()
+    }
+
+    async fn create_firmware_campaign(
+        &self,
+        ctx: &TenantContext,
+        record: FirmwareCampaignRecord,
+        rollouts: Vec<FirmwareRolloutRecord>,
+    ) -> Result<FirmwareCampaignRecord, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id
+            || rollouts
+                .iter()
+                .any(|rollout| rollout.tenant_id != ctx.tenant_id)
+        {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let mut tx = self.pool.begin().await?;
+        let sql = format!(
+            "insert into firmware_campaigns \
+             (tenant_id, project_id, campaign_id, package_id, name, status, created_by, \
+             created_at, updated_at) \
+             values ($1, $2, $3, $4, $5, $6, $7, \
+             to_timestamp($8 / 1000.0), to_timestamp($9 / 1000.0)) \
+             returning {CAMPAIGN_COLUMNS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&record.tenant_id)
+            .bind(&record.project_id)
+            .bind(&record.campaign_id)
+            .bind(&record.package_id)
+            .bind(&record.name)
+            .bind(&record.status)
+            .bind(&record.created_by)
+            .bind(record.created_at_ms as f64)
+            .bind(record.updated_at_ms as f64)
+            .fetch_one(&mut *tx)
+            .await?;
+        let created = campaign_from_row(&row)?;
+        for rollout in rollouts {
+            sqlx::query(
+                "insert into firmware_rollouts \
+                 (tenant_id, project_id, campaign_id, gateway_id, status, progress, message, \
+                 updated_at) \
+                 values ($1, $2, $3, $4, $5, $6, $7, to_timestamp($8 / 1000.0))",
+            )
+            .bind(&rollout.tenant_id)
+            .bind(&rollout.project_id)
+            .bind(&rollout.campaign_id)
+            .bind(&rollout.gateway_id)
+            .bind(&rollout.status)
+            .bind(rollout.progress)
+            .bind(&rollout.message)
+            .bind(rollout.updated_at_ms as f64)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(created)
+    }
+
+    async fn update_firmware_campaign_status(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        campaign_id: &str,
+        status: &str,
+        updated_at_ms: i64,
+    ) -> Result<Option<FirmwareCampaignRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let sql = format!(
+            "update firmware_campaigns set \
+             status = $1, updated_at = to_timestamp($2 / 1000.0) \
+             where tenant_id = $3 and project_id = $4 and campaign_id = $5 \
+             returning {CAMPAIGN_COLUMNS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(status)
+            .bind(updated_at_ms as f64)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .bind(campaign_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(campaign_from_row).transpose()
+    }
+
+    async fn list_firmware_rollouts(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        campaign_id: &str,
+    ) -> Result<Vec<FirmwareRolloutRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let sql = format!(
+            "select {ROLLOUT_COLUMNS} from firmware_rollouts \
+             where tenant_id = $1 and project_id = $2 and campaign_id = $3 \
+             order by gateway_id"
+        );
+        let rows = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .bind(campaign_id)
+            .fetch_all(&self.pool)
+            .await?;
+        let mut items = Vec::with_capacity(rows.len());
+        for row in rows {
+            items.push(rollout_from_row(&row)?);
+        }
+        Ok(items)
+    }
+
+    async fn update_firmware_rollout(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        campaign_id: &str,
+        gateway_id: &str,
+        update: FirmwareRolloutUpdate,
+    ) -> Result<Option<FirmwareRolloutRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let sql = format!(
+            "update firmware_rollouts set \
+             status = $1, \
+             progress = coalesce($2, progress), \
+             message = $3, \
+             updated_at = to_timestamp($4 / 1000.0) \
+             where tenant_id = $5 and project_id = $6 and campaign_id = $7 and gateway_id = $8 \
+             returning {ROLLOUT_COLUMNS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&update.status)
+            .bind(update.progress)
+            .bind(&update.message)
+            .bind(update.updated_at_ms as f64)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .bind(campaign_id)
+            .bind(gateway_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(rollout_from_row).transpose()
+    }
+}
@@ -7,7 +7,7 @@
 //! - 使用参数化 SQL 防止注入
 
 use crate::error::StorageError;
-use crate::models::{GatewayRecord, GatewayUpdate};
+use crate::models::{CollectionVersion, GatewayRecord, GatewayUpdate};
 use crate::traits::GatewayStore;
 use crate::validation::ensure_project_scope;
 use domain::TenantContext;
@@ -67,6 +67,28 @@ impl GatewayStore for PgGatewayStore {
         Ok(gateways)
     }
 
+    /// 指定项目网关集合的版本
+    async fn gateways_version(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<CollectionVersion, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let row = sqlx::query(
+            "select count(*) as count, \
+             coalesce((extract(epoch from max(updated_at)) * 1000000)::bigint, 0) as revision \
+             from gateways where tenant_id = $1 and project_id = $2",
+        )
+        .bind(&ctx.tenant_id)
+        .bind(project_id)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(CollectionVersion {
+            count: row.try_get("count")?,
+            revision: row.try_get("revision")?,
+        })
+    }
+
     /// 查找指定网关
     async fn find_gateway(
         &self,
@@ -106,7 +128,7 @@ impl GatewayStore for PgGatewayStore {
     ) -> Result<GatewayRecord, StorageError> {
         ensure_project_scope(ctx, &record.project_id)?;
         if record.tenant_id != ctx.tenant_id {
-            return Err(StorageError::new("tenant mismatch"));
+            return Err(StorageError::forbidden("tenant mismatch"));
         }
         sqlx::query(
             "insert into gateways (gateway_id, tenant_id, project_id, name, status, protocol_type, protocol_config) \
@@ -138,7 +160,8 @@ impl GatewayStore for PgGatewayStore {
              name = coalesce($1, name), \
              status = coalesce($2, status), \
              protocol_type = coalesce($3, protocol_type), \
-             protocol_config = coalesce($4, protocol_config) \
+             protocol_config = coalesce($4, protocol_config), \
+             updated_at = now() \
              where tenant_id = $5 and project_id = $6 and gateway_id = $7 \
              returning gateway_id, tenant_id, project_id, name, status, protocol_type, protocol_config",
         )
@@ -233,4 +256,113 @@ impl GatewayStore for PgGatewayStore {
 
         Ok(result.rows_affected() > 0)
     }
+
+    /// 查询全部租户的网关（离线检测后台任务使用）
+    async fn list_all_gateways(&self) -> Result<Vec<GatewayRecord>, StorageError> {
+        let rows = sqlx::query(
+            "select gateway_id, tenant_id, project_id, name, status, protocol_type, protocol_config \
+             from gateways order by gateway_id",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let mut gateways = Vec::with_capacity(rows.len());
+        for row in rows {
+            gateways.push(GatewayRecord {
+                gateway_id: row.try_get("gateway_id")?,
+                tenant_id: row.try_get("tenant_id")?,
+                project_id: row.try_get("project_id")?,
+                name: row.try_get("name")?,
+                status: row.try_get("status")?,
+                protocol_type: row.try_get("protocol_type")?,
+                protocol_config: row.try_get("protocol_config")?,
+            });
+        }
+        Ok(gateways)
+    }
+
+    async fn set_gateway_token_hash(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        gateway_id: &str,
+        token_hash: Option<&str>,
+    ) -> Result<bool, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let result = sqlx::query(
+            "update gateways set token_hash = $1 \
+             where tenant_id = $2 and project_id = $3 and gateway_id = $4",
+        )
+        .bind(token_hash)
+        .bind(&ctx.tenant_id)
+        .bind(project_id)
+        .bind(gateway_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn find_gateway_by_token_hash(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<GatewayRecord>, StorageError> {
+        let row = sqlx::query(
+            "select gateway_id, tenant_id, project_id, name, status, protocol_type, protocol_config \
+             from gateways where token_hash = $1",
+        )
+        .bind(token_hash)
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        Ok(Some(GatewayRecord {
+            gateway_id: row.try_get("gateway_id")?,
+            tenant_id: row.try_get("tenant_id")?,
+            project_id: row.try_get("project_id")?,
+            name: row.try_get("name")?,
+            status: row.try_get("status")?,
+            protocol_type: row.try_get("protocol_type")?,
+            protocol_config: row.try_get("protocol_config")?,
+        }))
+    }
+
+    async fn set_gateway_command_format(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        gateway_id: &str,
+        format: Option<&str>,
+    ) -> Result<bool, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let result = sqlx::query(
+            "update gateways set command_payload_format = $1, updated_at = now() \
+             where tenant_id = $2 and project_id = $3 and gateway_id = $4",
+        )
+        .bind(format)
+        .bind(&ctx.tenant_id)
+        .bind(project_id)
+        .bind(gateway_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn find_gateway_command_format(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        gateway_id: &str,
+    ) -> Result<Option<String>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let format: Option<Option<String>> = sqlx::query_scalar(
+            "select command_payload_format from gateways \
+             where tenant_id = $1 and project_id = $2 and gateway_id = $3",
+        )
+        .bind(&ctx.tenant_id)
+        .bind(project_id)
+        .bind(gateway_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(format.flatten())
+    }
 }
@@ -0,0 +1,135 @@
+//! Postgres 网关配置下发记录实现
+
+use crate::error::StorageError;
+use crate::models::GatewayConfigRecord;
+use crate::traits::GatewayConfigStore;
+use crate::validation::ensure_project_scope;
+use domain::TenantContext;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+
+pub struct PgGatewayConfigStore {
+    pub pool: PgPool,
+}
+
+impl PgGatewayConfigStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+const GATEWAY_CONFIG_COLUMNS: &str = "tenant_id, project_id, gateway_id, version, \
+     document::text as document, status, message, pushed_by, \
+     (extract(epoch from pushed_at) * 1000)::bigint as pushed_at_ms, \
+     (extract(epoch from updated_at) * 1000)::bigint as updated_at_ms";
+
+fn gateway_config_from_row(row: &PgRow) -> Result<GatewayConfigRecord, StorageError> {
+    Ok(GatewayConfigRecord {
+        tenant_id: row.try_get("tenant_id")?,
+        project_id: row.try_get("project_id")?,
+        gateway_id: row.try_get("gateway_id")?,
+        version: row.try_get("version")?,
+        document: row.try_get("document")?,
+        status: row.try_get("status")?,
+        message: row.try_get("message")?,
+        pushed_by: row.try_get("pushed_by")?,
+        pushed_at_ms: row.try_get("pushed_at_ms")?,
+        updated_at_ms: row.try_get("updated_at_ms")?,
+    })
+}
+
+#[async_trait::async_trait]
+impl GatewayConfigStore for PgGatewayConfigStore {
+    async fn create_gateway_config(
+        &self,
+        ctx: &TenantContext,
+        record: GatewayConfigRecord,
+    ) -> Result<GatewayConfigRecord, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        // 版本号在插入时按网关取 max + 1；并发冲突由主键保证不会重复
+        let sql = format!(
+            "insert into gateway_configs \
+             (tenant_id, project_id, gateway_id, version, document, status, message, pushed_by, pushed_at, updated_at) \
+             select $1, $2, $3, coalesce(max(version), 0) + 1, $4::jsonb, $5, $6, $7, \
+             to_timestamp($8 / 1000.0), to_timestamp($9 / 1000.0) \
+             from gateway_configs where tenant_id = $1 and project_id = $2 and gateway_id = $3 \
+             returning {GATEWAY_CONFIG_COLUMNS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&record.tenant_id)
+            .bind(&record.project_id)
+            .bind(&record.gateway_id)
+            .bind(&record.document)
+            .bind(&record.status)
+            .bind(&record.message)
+            .bind(&record.pushed_by)
+            .bind(record.pushed_at_ms as f64)
+            .bind(record.updated_at_ms as f64)
+            .fetch_one(&self.pool)
+            .await?;
+        gateway_config_from_row(&row)
+    }
+
+    async fn list_gateway_configs(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        gateway_id: &str,
+        limit: i64,
+    ) -> Result<Vec<GatewayConfigRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let sql = format!(
+            "select {GATEWAY_CONFIG_COLUMNS} from gateway_configs \
+             where tenant_id = $1 and project_id = $2 and gateway_id = $3 \
+             order by version desc \
+             limit $4"
+        );
+        let rows = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .bind(gateway_id)
+            .bind(limit.max(0))
+            .fetch_all(&self.pool)
+            .await?;
+        let mut items = Vec::with_capacity(rows.len());
+        for row in rows {
+            items.push(gateway_config_from_row(&row)?);
+        }
+        Ok(items)
+    }
+
+    async fn update_gateway_config_status(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        gateway_id: &str,
+        version: i64,
+        status: &str,
+        message: Option<String>,
+        updated_at_ms: i64,
+    ) -> Result<Option<GatewayConfigRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let sql = format!(
+            "update gateway_configs set status = $1, message = $2, updated_at = to_timestamp($3 / 1000.0) \
+             where tenant_id = $4 and project_id = $5 and gateway_id = $6 and version = $7 \
+             returning {GATEWAY_CONFIG_COLUMNS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(status)
+            .bind(message)
+            .bind(updated_at_ms as f64)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .bind(gateway_id)
+            .bind(version)
+            .fetch_optional(&self.pool)
+            .await?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        Ok(Some(gateway_config_from_row(&row)?))
+    }
+}
@@ -0,0 +1,132 @@
+//! Postgres 幂等键实现
+
+use crate::error::StorageError;
+use crate::models::IdempotencyRecord;
+use crate::traits::IdempotencyStore;
+use crate::validation::ensure_tenant;
+use domain::TenantContext;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+
+pub struct PgIdempotencyStore {
+    pub pool: PgPool,
+}
+
+impl PgIdempotencyStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+const IDEMPOTENCY_COLUMNS: &str = "tenant_id, idempotency_key, request_hash, status_code, \
+     response_headers, response_body, \
+     (extract(epoch from created_at) * 1000)::bigint as created_at_ms, \
+     (extract(epoch from expires_at) * 1000)::bigint as expires_at_ms";
+
+fn idempotency_from_row(row: &PgRow) -> Result<IdempotencyRecord, StorageError> {
+    Ok(IdempotencyRecord {
+        tenant_id: row.try_get("tenant_id")?,
+        idempotency_key: row.try_get("idempotency_key")?,
+        request_hash: row.try_get("request_hash")?,
+        status_code: row.try_get("status_code")?,
+        response_headers: row.try_get("response_headers")?,
+        response_body: row.try_get("response_body")?,
+        created_at_ms: row.try_get("created_at_ms")?,
+        expires_at_ms: row.try_get("expires_at_ms")?,
+    })
+}
+
+#[async_trait::async_trait]
+impl IdempotencyStore for PgIdempotencyStore {
+    async fn reserve_idempotency_key(
+        &self,
+        ctx: &TenantContext,
+        record: IdempotencyRecord,
+    ) -> Result<Option<IdempotencyRecord>, StorageError> {
+        ensure_tenant(ctx)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        // 顺带清理该租户已过期的记录
+        sqlx::query(
+            "delete from idempotency_keys \
+             where tenant_id = $1 and expires_at <= to_timestamp($2 / 1000.0)",
+        )
+        .bind(&record.tenant_id)
+        .bind(record.created_at_ms as f64)
+        .execute(&self.pool)
+        .await?;
+
+        // 键不存在或已过期时预占成功（并发请求只有一个能写入）
+        let reserved = sqlx::query(
+            "insert into idempotency_keys \
+             (tenant_id, idempotency_key, request_hash, status_code, response_body, created_at, expires_at) \
+             values ($1, $2, $3, null, null, to_timestamp($4 / 1000.0), to_timestamp($5 / 1000.0)) \
+             on conflict (tenant_id, idempotency_key) do update set \
+             request_hash = excluded.request_hash, status_code = null, \
+             response_headers = null, response_body = null, \
+             created_at = excluded.created_at, expires_at = excluded.expires_at \
+             where idempotency_keys.expires_at <= excluded.created_at \
+             returning idempotency_key",
+        )
+        .bind(&record.tenant_id)
+        .bind(&record.idempotency_key)
+        .bind(&record.request_hash)
+        .bind(record.created_at_ms as f64)
+        .bind(record.expires_at_ms as f64)
+        .fetch_optional(&self.pool)
+        .await?;
+        if reserved.is_some() {
+            return Ok(None);
+        }
+
+        let sql = format!(
+            "select {IDEMPOTENCY_COLUMNS} from idempotency_keys \
+             where tenant_id = $1 and idempotency_key = $2"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&record.tenant_id)
+            .bind(&record.idempotency_key)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(idempotency_from_row).transpose()
+    }
+
+    async fn complete_idempotency_key(
+        &self,
+        ctx: &TenantContext,
+        idempotency_key: &str,
+        status_code: i32,
+        response_headers: String,
+        response_body: String,
+    ) -> Result<(), StorageError> {
+        ensure_tenant(ctx)?;
+        sqlx::query(
+            "update idempotency_keys \
+             set status_code = $3, response_headers = $4, response_body = $5 \
+             where tenant_id = $1 and idempotency_key = $2",
+        )
+        .bind(&ctx.tenant_id)
+        .bind(idempotency_key)
+        .bind(status_code)
+        .bind(response_headers)
+        .bind(response_body)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn release_idempotency_key(
+        &self,
+        ctx: &TenantContext,
+        idempotency_key: &str,
+    ) -> Result<(), StorageError> {
+        ensure_tenant(ctx)?;
+        sqlx::query("delete from idempotency_keys where tenant_id = $1 and idempotency_key = $2")
+            .bind(&ctx.tenant_id)
+            .bind(idempotency_key)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
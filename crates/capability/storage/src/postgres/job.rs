@@ -0,0 +1,254 @@
+//! Postgres 后台任务实现
+
+use crate::error::StorageError;
+use crate::models::JobRecord;
+use crate::traits::{JobFinish, JobQuery, JobStore};
+use crate::validation::{ensure_project_scope, ensure_tenant};
+use domain::TenantContext;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+
+pub struct PgJobStore {
+    pub pool: PgPool,
+}
+
+impl PgJobStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+const JOB_COLUMNS: &str = "job_id, tenant_id, project_id, kind, status, params::text as params, \
+     progress, result::text as result, error, cancel_requested, created_by, \
+     (extract(epoch from created_at) * 1000)::bigint as created_at_ms, \
+     (extract(epoch from started_at) * 1000)::bigint as started_at_ms, \
+     (extract(epoch from finished_at) * 1000)::bigint as finished_at_ms, \
+     (extract(epoch from updated_at) * 1000)::bigint as updated_at_ms";
+
+/// 单个任务的过滤条件：`$1` 租户、`$2` 任务 ID、`$3` 项目作用域（为空时不限）
+const JOB_SCOPE: &str = "tenant_id = $1 and job_id = $2 and ($3::text is null or project_id = $3)";
+
+fn job_from_row(row: &PgRow) -> Result<JobRecord, StorageError> {
+    Ok(JobRecord {
+        job_id: row.try_get("job_id")?,
+        tenant_id: row.try_get("tenant_id")?,
+        project_id: row.try_get("project_id")?,
+        kind: row.try_get("kind")?,
+        status: row.try_get("status")?,
+        params: row.try_get("params")?,
+        progress: row.try_get("progress")?,
+        result: row.try_get("result")?,
+        error: row.try_get("error")?,
+        cancel_requested: row.try_get("cancel_requested")?,
+        created_by: row.try_get("created_by")?,
+        created_at_ms: row.try_get("created_at_ms")?,
+        started_at_ms: row.try_get("started_at_ms")?,
+        finished_at_ms: row.try_get("finished_at_ms")?,
+        updated_at_ms: row.try_get("updated_at_ms")?,
+    })
+}
+
+#[async_trait::async_trait]
+impl JobStore for PgJobStore {
+    async fn create_job(
+        &self,
+        ctx: &TenantContext,
+        record: JobRecord,
+    ) -> Result<JobRecord, StorageError> {
+        match record.project_id.as_deref() {
+            Some(project_id) => ensure_project_scope(ctx, project_id)?,
+            None => ensure_tenant(ctx)?,
+        }
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let sql = format!(
+            "insert into jobs \
+             (job_id, tenant_id, project_id, kind, status, params, progress, created_by, \
+             created_at, updated_at) \
+             values ($1, $2, $3, $4, $5, $6::jsonb, $7, $8, to_timestamp($9 / 1000.0), \
+             to_timestamp($9 / 1000.0)) \
+             returning {JOB_COLUMNS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&record.job_id)
+            .bind(&record.tenant_id)
+            .bind(&record.project_id)
+            .bind(&record.kind)
+            .bind(&record.status)
+            .bind(&record.params)
+            .bind(record.progress)
+            .bind(&record.created_by)
+            .bind(record.created_at_ms as f64)
+            .fetch_one(&self.pool)
+            .await?;
+        job_from_row(&row)
+    }
+
+    async fn find_job(
+        &self,
+        ctx: &TenantContext,
+        job_id: &str,
+    ) -> Result<Option<JobRecord>, StorageError> {
+        ensure_tenant(ctx)?;
+        let sql = format!("select {JOB_COLUMNS} from jobs where {JOB_SCOPE}");
+        let row = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(job_id)
+            .bind(&ctx.project_scope)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(job_from_row).transpose()
+    }
+
+    async fn list_jobs(
+        &self,
+        ctx: &TenantContext,
+        options: JobQuery,
+    ) -> Result<Vec<JobRecord>, StorageError> {
+        ensure_tenant(ctx)?;
+        let sql = format!(
+            "select {JOB_COLUMNS} from jobs \
+             where tenant_id = $1 \
+             and ($2::text is null or project_id = $2) \
+             and ($3::text is null or project_id = $3) \
+             and ($4::text is null or kind = $4) \
+             and ($5::text is null or status = $5) \
+             order by created_at desc, job_id desc \
+             limit $6"
+        );
+        let rows = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(&ctx.project_scope)
+            .bind(options.project_id)
+            .bind(options.kind)
+            .bind(options.status)
+            .bind(options.limit.max(0))
+            .fetch_all(&self.pool)
+            .await?;
+        let mut items = Vec::with_capacity(rows.len());
+        for row in rows {
+            items.push(job_from_row(&row)?);
+        }
+        Ok(items)
+    }
+
+    async fn start_job(
+        &self,
+        ctx: &TenantContext,
+        job_id: &str,
+        started_at_ms: i64,
+    ) -> Result<Option<JobRecord>, StorageError> {
+        ensure_tenant(ctx)?;
+        let sql = format!(
+            "update jobs set status = 'running', \
+             started_at = to_timestamp($4 / 1000.0), updated_at = to_timestamp($4 / 1000.0) \
+             where {JOB_SCOPE} and status = 'queued' and not cancel_requested \
+             returning {JOB_COLUMNS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(job_id)
+            .bind(&ctx.project_scope)
+            .bind(started_at_ms as f64)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(job_from_row).transpose()
+    }
+
+    async fn update_job_progress(
+        &self,
+        ctx: &TenantContext,
+        job_id: &str,
+        progress: i32,
+        updated_at_ms: i64,
+    ) -> Result<Option<JobRecord>, StorageError> {
+        ensure_tenant(ctx)?;
+        let sql = format!(
+            "update jobs set progress = $4, updated_at = to_timestamp($5 / 1000.0) \
+             where {JOB_SCOPE} and status = 'running' \
+             returning {JOB_COLUMNS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(job_id)
+            .bind(&ctx.project_scope)
+            .bind(progress.clamp(0, 100))
+            .bind(updated_at_ms as f64)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(job_from_row).transpose()
+    }
+
+    async fn finish_job(
+        &self,
+        ctx: &TenantContext,
+        job_id: &str,
+        finish: JobFinish,
+    ) -> Result<Option<JobRecord>, StorageError> {
+        ensure_tenant(ctx)?;
+        let sql = format!(
+            "update jobs set status = $4, result = $5::jsonb, error = $6, \
+             progress = case when $4 = 'succeeded' then 100 else progress end, \
+             finished_at = to_timestamp($7 / 1000.0), updated_at = to_timestamp($7 / 1000.0) \
+             where {JOB_SCOPE} and status in ('queued', 'running') \
+             returning {JOB_COLUMNS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(job_id)
+            .bind(&ctx.project_scope)
+            .bind(&finish.status)
+            .bind(&finish.result)
+            .bind(&finish.error)
+            .bind(finish.finished_at_ms as f64)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(job_from_row).transpose()
+    }
+
+    async fn request_job_cancel(
+        &self,
+        ctx: &TenantContext,
+        job_id: &str,
+        requested_at_ms: i64,
+    ) -> Result<Option<JobRecord>, StorageError> {
+        ensure_tenant(ctx)?;
+        let sql = format!(
+            "update jobs set \
+             cancel_requested = cancel_requested or status in ('queued', 'running'), \
+             status = case when status = 'queued' then 'cancelled' else status end, \
+             finished_at = case when status = 'queued' then to_timestamp($4 / 1000.0) \
+                           else finished_at end, \
+             updated_at = case when status in ('queued', 'running') and not cancel_requested \
+                          then to_timestamp($4 / 1000.0) else updated_at end \
+             where {JOB_SCOPE} \
+             returning {JOB_COLUMNS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(job_id)
+            .bind(&ctx.project_scope)
+            .bind(requested_at_ms as f64)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(job_from_row).transpose()
+    }
+
+    async fn fail_unfinished_jobs(
+        &self,
+        error: &str,
+        finished_at_ms: i64,
+    ) -> Result<u64, StorageError> {
+        let result = sqlx::query(
+            "update jobs set status = 'failed', error = $1, \
+             finished_at = to_timestamp($2 / 1000.0), updated_at = to_timestamp($2 / 1000.0) \
+             where status in ('queued', 'running')",
+        )
+        .bind(error)
+        .bind(finished_at_ms as f64)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
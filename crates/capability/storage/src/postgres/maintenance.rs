@@ -0,0 +1,146 @@
+//! Postgres 维护窗口实现
+
+use crate::error::StorageError;
+use crate::models::MaintenanceWindowRecord;
+use crate::traits::MaintenanceStore;
+use crate::validation::ensure_project_scope;
+use domain::TenantContext;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+
+pub struct PgMaintenanceStore {
+    pub pool: PgPool,
+}
+
+impl PgMaintenanceStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+const WINDOW_COLUMNS: &str = "tenant_id, project_id, target_type, target_id, \
+     (extract(epoch from starts_at) * 1000)::bigint as starts_at_ms, \
+     (extract(epoch from ends_at) * 1000)::bigint as ends_at_ms, \
+     reason, created_by, \
+     (extract(epoch from created_at) * 1000)::bigint as created_at_ms";
+
+fn window_from_row(row: &PgRow) -> Result<MaintenanceWindowRecord, StorageError> {
+    Ok(MaintenanceWindowRecord {
+        tenant_id: row.try_get("tenant_id")?,
+        project_id: row.try_get("project_id")?,
+        target_type: row.try_get("target_type")?,
+        target_id: row.try_get("target_id")?,
+        starts_at_ms: row.try_get("starts_at_ms")?,
+        ends_at_ms: row.try_get("ends_at_ms")?,
+        reason: row.try_get("reason")?,
+        created_by: row.try_get("created_by")?,
+        created_at_ms: row.try_get("created_at_ms")?,
+    })
+}
+
+#[async_trait::async_trait]
+impl MaintenanceStore for PgMaintenanceStore {
+    async fn list_maintenance_windows(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<Vec<MaintenanceWindowRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let sql = format!(
+            "select {WINDOW_COLUMNS} from maintenance_windows \
+             where tenant_id = $1 and project_id = $2 \
+             order by target_type, target_id"
+        );
+        let rows = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .fetch_all(&self.pool)
+            .await?;
+        let mut items = Vec::with_capacity(rows.len());
+        for row in rows {
+            items.push(window_from_row(&row)?);
+        }
+        Ok(items)
+    }
+
+    async fn find_maintenance_window(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        target_type: &str,
+        target_id: &str,
+    ) -> Result<Option<MaintenanceWindowRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let sql = format!(
+            "select {WINDOW_COLUMNS} from maintenance_windows \
+             where tenant_id = $1 and project_id = $2 and target_type = $3 and target_id = $4"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .bind(target_type)
+            .bind(target_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(window_from_row).transpose()
+    }
+
+    async fn upsert_maintenance_window(
+        &self,
+        ctx: &TenantContext,
+        record: MaintenanceWindowRecord,
+    ) -> Result<MaintenanceWindowRecord, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let sql = format!(
+            "insert into maintenance_windows \
+             (tenant_id, project_id, target_type, target_id, starts_at, ends_at, reason, \
+             created_by, created_at) \
+             values ($1, $2, $3, $4, to_timestamp($5 / 1000.0), to_timestamp($6 / 1000.0), \
+             $7, $8, to_timestamp($9 / 1000.0)) \
+             on conflict (tenant_id, project_id, target_type, target_id) do update set \
+             starts_at = excluded.starts_at, \
+             ends_at = excluded.ends_at, \
+             reason = excluded.reason, \
+             created_by = excluded.created_by, \
+             created_at = excluded.created_at \
+             returning {WINDOW_COLUMNS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&record.tenant_id)
+            .bind(&record.project_id)
+            .bind(&record.target_type)
+            .bind(&record.target_id)
+            .bind(record.starts_at_ms as f64)
+            .bind(record.ends_at_ms as f64)
+            .bind(&record.reason)
+            .bind(&record.created_by)
+            .bind(record.created_at_ms as f64)
+            .fetch_one(&self.pool)
+            .await?;
+        window_from_row(&row)
+    }
+
+    async fn delete_maintenance_window(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        target_type: &str,
+        target_id: &str,
+    ) -> Result<bool, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let result = sqlx::query(
+            "delete from maintenance_windows \
+             where tenant_id = $1 and project_id = $2 and target_type = $3 and target_id = $4",
+        )
+        .bind(&ctx.tenant_id)
+        .bind(project_id)
+        .bind(target_type)
+        .bind(target_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -1,9 +1,10 @@
 //! Postgres 时序写入实现
 
 use crate::error::StorageError;
-use crate::models::MeasurementRecord;
+use crate::models::{MeasurementCoverage, MeasurementGap, MeasurementRecord};
 use crate::traits::{
-    MeasurementAggFn, MeasurementStore, MeasurementsQueryOptions, TimeOrder,
+    MeasurementAggFn, MeasurementCoverageOptions, MeasurementDedupStrategy, MeasurementStore,
+    MeasurementsQueryOptions, TimeOrder,
 };
 use crate::validation::ensure_project_scope;
 use domain::{PointValue, PointValueData, TenantContext};
@@ -11,16 +12,56 @@ use sqlx::{PgPool, Row};
 
 pub struct PgMeasurementStore {
     pub pool: PgPool,
+    dedup: MeasurementDedupStrategy,
 }
 
 impl PgMeasurementStore {
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            dedup: MeasurementDedupStrategy::default(),
+        }
     }
 
     pub async fn connect(database_url: &str) -> Result<Self, StorageError> {
         let pool = crate::connection::connect_pool(database_url).await?;
-        Ok(Self { pool })
+        Ok(Self::new(pool))
+    }
+
+    /// 设置重复写入（主键 tenant_id, project_id, point_id, ts 冲突）的处理策略，默认 `ignore`
+    pub fn with_dedup_strategy(mut self, dedup: MeasurementDedupStrategy) -> Self {
+        self.dedup = dedup;
+        self
+    }
+
+    fn insert_sql(&self) -> String {
+        format!(
+            "insert into measurement (tenant_id, project_id, point_id, ts, value, quality) \
+             values ($1, $2, $3, to_timestamp($4 / 1000.0), $5, $6) {}",
+            on_conflict_clause(self.dedup)
+        )
+    }
+}
+
+/// 质量等级（越大越好）：`good` 或未标注为 2，`bad` 为 0，其他为 1
+fn quality_rank_sql(column: &str) -> String {
+    format!(
+        "(case when coalesce(lower({column}), 'good') = 'good' then 2 \
+         when lower({column}) = 'bad' then 0 else 1 end)"
+    )
+}
+
+fn on_conflict_clause(dedup: MeasurementDedupStrategy) -> String {
+    let target = "on conflict (tenant_id, project_id, point_id, ts)";
+    let update = "do update set value = excluded.value, quality = excluded.quality";
+    match dedup {
+        MeasurementDedupStrategy::Ignore => format!("{target} do nothing"),
+        MeasurementDedupStrategy::Overwrite => format!("{target} {update}"),
+        MeasurementDedupStrategy::KeepBestQuality => format!(
+            "{target} {update} where {} >= {}",
+            quality_rank_sql("excluded.quality"),
+            quality_rank_sql("measurement.quality")
+        ),
     }
 }
 
@@ -42,21 +83,18 @@ impl MeasurementStore for PgMeasurementStore {
     ) -> Result<(), StorageError> {
         ensure_project_scope(ctx, &value.project_id)?;
         if value.tenant_id != ctx.tenant_id {
-            return Err(StorageError::new("tenant mismatch"));
+            return Err(StorageError::forbidden("tenant mismatch"));
         }
         let value_str = value_to_string(value);
-        sqlx::query(
-            "insert into measurement (tenant_id, project_id, point_id, ts, value, quality) \
-             values ($1, $2, $3, to_timestamp($4 / 1000.0), $5, $6)",
-        )
-        .bind(&value.tenant_id)
-        .bind(&value.project_id)
-        .bind(&value.point_id)
-        .bind(value.ts_ms as f64)
-        .bind(value_str)
-        .bind(&value.quality)
-        .execute(&self.pool)
-        .await?;
+        sqlx::query(&self.insert_sql())
+            .bind(&value.tenant_id)
+            .bind(&value.project_id)
+            .bind(&value.point_id)
+            .bind(value.ts_ms as f64)
+            .bind(value_str)
+            .bind(&value.quality)
+            .execute(&self.pool)
+            .await?;
         Ok(())
     }
 
@@ -68,28 +106,29 @@ impl MeasurementStore for PgMeasurementStore {
         if values.is_empty() {
             return Ok(0);
         }
+        let sql = self.insert_sql();
+        let mut written = 0;
         let mut tx = self.pool.begin().await?;
         for value in values {
             ensure_project_scope(ctx, &value.project_id)?;
             if value.tenant_id != ctx.tenant_id {
-                return Err(StorageError::new("tenant mismatch"));
+                return Err(StorageError::forbidden("tenant mismatch"));
             }
             let value_str = value_to_string(value);
-            sqlx::query(
-                "insert into measurement (tenant_id, project_id, point_id, ts, value, quality) \
-                 values ($1, $2, $3, to_timestamp($4 / 1000.0), $5, $6)",
-            )
-            .bind(&value.tenant_id)
-            .bind(&value.project_id)
-            .bind(&value.point_id)
-            .bind(value.ts_ms as f64)
-            .bind(value_str)
-            .bind(&value.quality)
-            .execute(&mut *tx)
-            .await?;
+            // 冲突被忽略（do nothing 或质量较差）时影响行数为 0
+            let result = sqlx::query(&sql)
+                .bind(&value.tenant_id)
+                .bind(&value.project_id)
+                .bind(&value.point_id)
+                .bind(value.ts_ms as f64)
+                .bind(value_str)
+                .bind(&value.quality)
+                .execute(&mut *tx)
+                .await?;
+            written += result.rows_affected() as usize;
         }
         tx.commit().await?;
-        Ok(values.len())
+        Ok(written)
     }
 
     async fn query_measurements(
@@ -112,8 +151,101 @@ impl MeasurementStore for PgMeasurementStore {
 
         query_measurements_raw(self, ctx, project_id, point_id, options).await
     }
+
+    async fn measurement_coverage(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        point_id: &str,
+        options: MeasurementCoverageOptions,
+    ) -> Result<MeasurementCoverage, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let expected_buckets = options.expected_buckets();
+        if expected_buckets == 0 {
+            return Ok(MeasurementCoverage {
+                expected_buckets: 0,
+                covered_buckets: 0,
+                completeness_pct: 0.0,
+                gaps: Vec::new(),
+            });
+        }
+
+        let sql = format!(
+            "{COVERAGE_BUCKETS_CTE} \
+             select count(*)::bigint as covered_buckets, \
+               round(count(*) * 100.0 / $7, 2)::float8 as completeness_pct \
+             from buckets"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .bind(point_id)
+            .bind(options.from_ms)
+            .bind(options.to_ms)
+            .bind(options.expected_interval_ms)
+            .bind(expected_buckets)
+            .fetch_one(&self.pool)
+            .await?;
+        let covered_buckets: i64 = row.try_get("covered_buckets")?;
+        let completeness_pct: f64 = row.try_get("completeness_pct")?;
+
+        // 以 -1 和 expected_buckets 作为哨兵，相邻已覆盖桶之间的空桶即缺失区间
+        let sql = format!(
+            "{COVERAGE_BUCKETS_CTE}, \
+             bounded as ( \
+               select idx from buckets \
+               union all select -1::bigint \
+               union all select $7::bigint \
+             ), \
+             ordered as ( \
+               select idx, lag(idx) over (order by idx) as prev_idx from bounded \
+             ) \
+             select $4 + (prev_idx + 1) * $6 as from_ms, \
+               least($4 + idx * $6, $5) as to_ms \
+             from ordered \
+             where idx - prev_idx > 1 \
+             order by idx \
+             limit $8"
+        );
+        let rows = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .bind(point_id)
+            .bind(options.from_ms)
+            .bind(options.to_ms)
+            .bind(options.expected_interval_ms)
+            .bind(expected_buckets)
+            .bind(options.gap_limit.max(0))
+            .fetch_all(&self.pool)
+            .await?;
+        let mut gaps = Vec::with_capacity(rows.len());
+        for row in rows {
+            gaps.push(MeasurementGap {
+                from_ms: row.try_get("from_ms")?,
+                to_ms: row.try_get("to_ms")?,
+            });
+        }
+
+        Ok(MeasurementCoverage {
+            expected_buckets,
+            covered_buckets,
+            completeness_pct,
+            gaps,
+        })
+    }
 }
 
+/// 按期望间隔把窗口切桶，去重后的桶号即已覆盖的桶。
+const COVERAGE_BUCKETS_CTE: &str = "with buckets as ( \
+    select distinct floor((extract(epoch from ts) * 1000 - $4) / $6)::bigint as idx \
+    from measurement \
+    where tenant_id = $1 \
+    and project_id = $2 \
+    and point_id = $3 \
+    and ts >= to_timestamp($4 / 1000.0) \
+    and ts < to_timestamp($5 / 1000.0) \
+ )";
+
 async fn query_measurements_raw(
     store: &PgMeasurementStore,
     ctx: &TenantContext,
@@ -174,7 +306,7 @@ async fn query_measurements_aggregated(
     aggregation: crate::traits::MeasurementAggregation,
 ) -> Result<Vec<MeasurementRecord>, StorageError> {
     let bucket_ms = aggregation.bucket_ms;
-    if bucket_ms <= 0 {
+    if aggregation.calendar.is_none() && bucket_ms <= 0 {
         return Ok(Vec::new());
     }
     let (cursor_op, order_by) = match options.order {
@@ -190,10 +322,20 @@ async fn query_measurements_aggregated(
         MeasurementAggFn::Count => "count(*)::text",
     };
 
+    // 日历桶：在项目时区的本地时间上 date_trunc，再换算回 timestamptz；
+    // 固定桶：按 epoch 对齐。$7 分别绑定时区名 / 桶大小。
+    let bucket_expr = match aggregation.calendar {
+        Some(calendar) => format!(
+            "date_trunc('{}', ts at time zone $7::text) at time zone $7::text",
+            calendar.trunc_unit()
+        ),
+        None => "to_timestamp(floor(extract(epoch from ts) * 1000 / $7) * $7 / 1000.0)".to_string(),
+    };
+
     let sql = format!(
         "with filtered as ( \
             select tenant_id, project_id, point_id, ts, \
-              to_timestamp(floor(extract(epoch from ts) * 1000 / $7) * $7 / 1000.0) as bucket_ts, \
+              {bucket_expr} as bucket_ts, \
               value \
             from measurement \
             where tenant_id = $1 \
@@ -213,17 +355,18 @@ async fn query_measurements_aggregated(
          limit $8"
     );
 
-    let rows = sqlx::query(&sql)
+    let query = sqlx::query(&sql)
         .bind(&ctx.tenant_id)
         .bind(project_id)
         .bind(point_id)
         .bind(options.from_ms)
         .bind(options.to_ms)
-        .bind(options.cursor_ts_ms)
-        .bind(bucket_ms)
-        .bind(options.limit)
-        .fetch_all(&store.pool)
-        .await?;
+        .bind(options.cursor_ts_ms);
+    let query = match aggregation.calendar {
+        Some(_) => query.bind(options.timezone.name()),
+        None => query.bind(bucket_ms),
+    };
+    let rows = query.bind(options.limit).fetch_all(&store.pool).await?;
 
     let mut items = Vec::with_capacity(rows.len());
     for row in rows {
@@ -238,3 +381,41 @@ async fn query_measurements_aggregated(
     }
     Ok(items)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quality_rank_sql_matches_rust_rank() {
+        assert_eq!(
+            quality_rank_sql("excluded.quality"),
+            "(case when coalesce(lower(excluded.quality), 'good') = 'good' then 2 \
+             when lower(excluded.quality) = 'bad' then 0 else 1 end)"
+        );
+    }
+
+    #[test]
+    fn on_conflict_clause_per_strategy() {
+        assert_eq!(
+            on_conflict_clause(MeasurementDedupStrategy::Ignore),
+            "on conflict (tenant_id, project_id, point_id, ts) do nothing"
+        );
+        assert_eq!(
+            on_conflict_clause(MeasurementDedupStrategy::Overwrite),
+            "on conflict (tenant_id, project_id, point_id, ts) \
+             do update set value = excluded.value, quality = excluded.quality"
+        );
+        let clause = on_conflict_clause(MeasurementDedupStrategy::KeepBestQuality);
+        assert_eq!(
+            clause,
+            format!(
+                "on conflict (tenant_id, project_id, point_id, ts) \
+                 do update set value = excluded.value, quality = excluded.quality \
+                 where {} >= {}",
+                quality_rank_sql("excluded.quality"),
+                quality_rank_sql("measurement.quality")
+            )
+        );
+    }
+}
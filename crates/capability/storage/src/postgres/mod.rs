@@ -12,16 +12,36 @@
 //!
 //! ## 包含的实现
 //!
+//! - **TenantStore** (`tenant.rs`)：租户存储（运维侧创建租户）
 //! - **UserStore** (`user.rs`)：用户存储，支持登录验证和权限查询
 //! - **ProjectStore** (`project.rs`)：项目存储，支持 CRUD 和租户归属校验
+//! - **PortfolioStore** (`portfolio.rs`)：项目组合（多站点分组）及成员项目
 //! - **GatewayStore** (`gateway.rs`)：网关存储，支持项目级资源管理
 //! - **DeviceStore** (`device.rs`)：设备存储，支持项目级资源管理
 //! - **PointStore** (`point.rs`)：点位存储，支持项目级资源管理
 //! - **PointMappingStore** (`point_mapping.rs`)：点位映射存储，支持项目级资源管理
+//! - **DeviceTemplateStore** (`device_template.rs`)：设备模板存储，支持事务化设备实例化
+//! - **ProjectCloneStore** (`project_clone.rs`)：项目克隆，事务化写入新项目及其资产树与规则
+//! - **GatewayConfigStore** (`gateway_config.rs`)：网关配置下发记录（版本 + 状态）
+//! - **DeviceShadowStore** (`device_shadow.rs`)：设备影子期望状态（版本 + 最近差量命令）
+//! - **FirmwareStore** (`firmware.rs`)：固件包、升级批次与网关升级进度
+//! - **MaintenanceStore** (`maintenance.rs`)：设备 / 网关维护窗口
 //! - **MeasurementStore** (`measurement.rs`)：时序写入支持
 //! - **CommandStore** (`command.rs`)：控制命令存储
 //! - **CommandReceiptStore** (`command_receipt.rs`)：命令回执存储
 //! - **AuditLogStore** (`audit.rs`)：审计日志存储
+//! - **WebhookSubscriptionStore** (`webhook.rs`)：Webhook 订阅与推送日志
+//! - **ShareTokenStore** (`share_token.rs`)：只读数据分享令牌（仅保存摘要，带过期与撤销）
+//! - **RuleStore** (`rule.rs`)：自动化规则与执行记录
+//! - **ScheduleStore** (`schedule.rs`)：控制计划与执行记录
+//! - **DemandResponseStore** (`demand_response.rs`)：需求响应可削减负荷与事件
+//! - **AnomalyStore** (`anomaly.rs`)：用能异常（点位小时值偏离周内同时段基线）
+//! - **DeviceEventStore** (`device_event.rs`)：设备时间线（创建、配置变更、上下线、命令、告警）
+//! - **EmissionFactorStore** (`emission_factor.rs`)：碳排放因子（租户默认值 + 项目覆盖）
+//! - **IdempotencyStore** (`idempotency.rs`)：POST 幂等键（请求摘要 + 响应，带过期时间）
+//! - **FeatureFlagStore** (`feature_flag.rs`)：租户功能开关（开关键 → 启用 + 变体）
+//! - **UsageStore** (`usage.rs`)：租户用量计量（按日计数）与配额
+//! - **JobStore** (`job.rs`)：后台任务（状态、进度、取消请求、结果）
 //!
 //! ## 数据库模式要求
 //!
@@ -45,7 +65,56 @@
 //! - `gateways`：网关表（gateway_id, tenant_id, project_id, name, status, last_seen_at）
 //! - `devices`：设备表（device_id, tenant_id, project_id, gateway_id, name, model）
 //! - `points`：点位表（point_id, tenant_id, project_id, device_id, key, data_type, unit）
-//! - `point_sources`：点位映射表（source_id, tenant_id, project_id, point_id, source_type, address, scale, offset_value）
+//! - `point_sources`：点位映射表（source_id, tenant_id, project_id, point_id, source_type, address, scale, offset_value；
+//!   (tenant_id, project_id, source_type, address) 唯一）
+//! - `device_templates` / `device_template_points`：设备模板与模板点位
+//! - `gateway_configs`：网关配置下发记录（tenant_id, project_id, gateway_id, version, document, status）
+//! - `device_shadows`：设备影子期望状态（tenant_id, project_id, device_id, desired, version, last_command_id）
+//! - `firmware_packages`：固件包（tenant_id, project_id, package_id, name, version, checksum_sha256, size_bytes, storage_url）
+//! - `firmware_campaigns`：升级批次（campaign_id, package_id, name, status）
+//! - `firmware_rollouts`：网关升级进度（campaign_id, gateway_id, status, progress, message）
+//! - `maintenance_windows`：维护窗口（tenant_id, project_id, target_type, target_id, starts_at, ends_at, reason）
+//!
+//! ### 事件推送表
+//! - `webhook_subscriptions`：Webhook 订阅（subscription_id, tenant_id, project_id, url, event_types, secret）
+//! - `webhook_deliveries`：推送日志（delivery_id, subscription_id, event_id, status, attempts）
+//!
+//! ### 自动化规则表
+//! - `automation_rules`：规则（tenant_id, project_id, rule_id, name, enabled, trigger, actions）
+//! - `automation_rule_executions`：执行记录（execution_id, rule_id, trigger_detail, status, results）
+//!
+//! ### 控制计划表
+//! - `control_schedules`：计划（tenant_id, project_id, schedule_id, timezone, spec, missed_run_policy, last_evaluated_at）
+//! - `control_schedule_executions`：执行记录（execution_id, schedule_id, scheduled_at, status, results）
+//!
+//! ### 需求响应表
+//! - `demand_response_loads`：可削减负荷（tenant_id, project_id, device_id, priority, power_point_id, rated_kw, shed_payload, restore_payload）
+//! - `demand_response_events`：事件（event_id, target_kw, start_at, end_at, status, baseline_kw, achieved_kw, loads）
+//!
+//! ### 分析表
+//! - `anomalies`：用能异常（anomaly_id, tenant_id, project_id, point_id, device_id, bucket_start, actual_value, baseline_value, deviation_pct）
+//! - `device_events`：设备时间线（tenant_id, project_id, device_id, event_id, event_type, occurred_at, data）
+//! - `emission_factors`：碳排放因子（tenant_id, project_id（空串表示租户默认值）, energy_source, kg_co2e_per_unit, unit）
+//!
+//! ### 幂等表
+//! - `idempotency_keys`：POST 幂等键（tenant_id, idempotency_key, request_hash, status_code, response_headers, response_body, expires_at）
+//!
+//! ### 功能开关表
+//! - `tenant_feature_flags`：租户功能开关（tenant_id, flag_key, enabled, variant）
+//!
+//! ### 用量与配额表
+//! - `tenant_usage`：租户用量（tenant_id, metric, period_start, count）
+//! - `tenant_quotas`：租户配额（tenant_id, metric, limit_value）
+//!
+//! ### 后台任务表
+//! - `jobs`：后台任务（job_id, tenant_id, project_id, kind, status, params, progress, result, error, cancel_requested）
+//!
+//! ### 数据分享表
+//! - `share_tokens`：只读数据分享令牌（token_id, tenant_id, project_id, token_hash, scopes, expires_at, revoked_at）
+//!
+//! ### 项目组合表
+//! - `portfolios`：项目组合（tenant_id, portfolio_id, name, description）
+//! - `portfolio_projects`：组合成员（tenant_id, portfolio_id, project_id, ordinal）
 //!
 //! ## 性能优化
 //!
@@ -146,25 +215,65 @@
 //! - **数据归档**：支持历史数据的归档和清理
 
 // 导出各个 PostgreSQL 存储实现
+pub mod anomaly;
 pub mod audit;
 pub mod command;
 pub mod command_receipt;
+pub mod demand_response;
 pub mod device;
+pub mod device_event;
+pub mod device_shadow;
+pub mod device_template;
+pub mod emission_factor;
+pub mod feature_flag;
+pub mod firmware;
 pub mod gateway;
+pub mod gateway_config;
+pub mod idempotency;
+pub mod job;
+pub mod maintenance;
 pub mod measurement;
 pub mod point;
 pub mod point_mapping;
+pub mod portfolio;
 pub mod project;
+pub mod project_clone;
+pub mod rule;
+pub mod schedule;
+pub mod share_token;
+pub mod tenant;
+pub mod usage;
 pub mod user;
+pub mod webhook;
 
 // 导出到 crate 根目录，方便外部引用
+pub use anomaly::*;
 pub use audit::*;
 pub use command::*;
 pub use command_receipt::*;
+pub use demand_response::*;
 pub use device::*;
+pub use device_event::*;
+pub use device_shadow::*;
+pub use device_template::*;
+pub use emission_factor::*;
+pub use feature_flag::*;
+pub use firmware::*;
 pub use gateway::*;
+pub use gateway_config::*;
+pub use idempotency::*;
+pub use job::*;
+pub use maintenance::*;
 pub use measurement::*;
 pub use point::*;
 pub use point_mapping::*;
+pub use portfolio::*;
 pub use project::*;
+pub use project_clone::*;
+pub use rule::*;
+pub use schedule::*;
+pub use share_token::*;
+pub use tenant::*;
+pub use usage::*;
 pub use user::*;
+pub use webhook::*;
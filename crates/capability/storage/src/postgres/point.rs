@@ -7,9 +7,9 @@
 //! - 使用参数化 SQL 防止注入
 
 use crate::error::StorageError;
-use crate::models::{PointRecord, PointUpdate};
+use crate::models::{CollectionVersion, PointRecord, PointUpdate};
 use crate::traits::PointStore;
-use crate::validation::ensure_project_scope;
+use crate::validation::{ensure_project_scope, ensure_tenant};
 use domain::TenantContext;
 use sqlx::{PgPool, Row};
 
@@ -37,7 +37,7 @@ impl PointStore for PgPointStore {
     ) -> Result<Vec<PointRecord>, StorageError> {
         ensure_project_scope(ctx, project_id)?;
         let rows = sqlx::query(
-            "select point_id, tenant_id, project_id, device_id, key, data_type, unit \
+            "select point_id, tenant_id, project_id, device_id, key, data_type, unit, tags \
              from points where tenant_id = $1 and project_id = $2",
         )
         .bind(&ctx.tenant_id)
@@ -54,11 +54,34 @@ impl PointStore for PgPointStore {
                 key: row.try_get("key")?,
                 data_type: row.try_get("data_type")?,
                 unit: row.try_get("unit")?,
+                tags: row.try_get("tags")?,
             });
         }
         Ok(points)
     }
 
+    /// 指定项目点集合的版本
+    async fn points_version(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<CollectionVersion, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let row = sqlx::query(
+            "select count(*) as count, \
+             coalesce((extract(epoch from max(updated_at)) * 1000000)::bigint, 0) as revision \
+             from points where tenant_id = $1 and project_id = $2",
+        )
+        .bind(&ctx.tenant_id)
+        .bind(project_id)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(CollectionVersion {
+            count: row.try_get("count")?,
+            revision: row.try_get("revision")?,
+        })
+    }
+
     async fn find_point(
         &self,
         ctx: &TenantContext,
@@ -67,7 +90,7 @@ impl PointStore for PgPointStore {
     ) -> Result<Option<PointRecord>, StorageError> {
         ensure_project_scope(ctx, project_id)?;
         let row = sqlx::query(
-            "select point_id, tenant_id, project_id, device_id, key, data_type, unit \
+            "select point_id, tenant_id, project_id, device_id, key, data_type, unit, tags \
              from points where tenant_id = $1 and project_id = $2 and point_id = $3",
         )
         .bind(&ctx.tenant_id)
@@ -86,6 +109,7 @@ impl PointStore for PgPointStore {
             key: row.try_get("key")?,
             data_type: row.try_get("data_type")?,
             unit: row.try_get("unit")?,
+            tags: row.try_get("tags")?,
         }))
     }
 
@@ -96,11 +120,12 @@ impl PointStore for PgPointStore {
     ) -> Result<PointRecord, StorageError> {
         ensure_project_scope(ctx, &record.project_id)?;
         if record.tenant_id != ctx.tenant_id {
-            return Err(StorageError::new("tenant mismatch"));
+            return Err(StorageError::forbidden("tenant mismatch"));
         }
         sqlx::query(
-            "insert into points (point_id, tenant_id, project_id, device_id, key, data_type, unit) \
-             values ($1, $2, $3, $4, $5, $6, $7)",
+            "insert into points \
+             (point_id, tenant_id, project_id, device_id, key, data_type, unit, tags) \
+             values ($1, $2, $3, $4, $5, $6, $7, $8)",
         )
         .bind(&record.point_id)
         .bind(&record.tenant_id)
@@ -109,6 +134,7 @@ impl PointStore for PgPointStore {
         .bind(&record.key)
         .bind(&record.data_type)
         .bind(&record.unit)
+        .bind(&record.tags)
         .execute(&self.pool)
         .await?;
         Ok(record)
@@ -126,13 +152,16 @@ impl PointStore for PgPointStore {
             "update points set \
              key = coalesce($1, key), \
              data_type = coalesce($2, data_type), \
-             unit = coalesce($3, unit) \
-             where tenant_id = $4 and project_id = $5 and point_id = $6 \
-             returning point_id, tenant_id, project_id, device_id, key, data_type, unit",
+             unit = coalesce($3, unit), \
+             tags = coalesce($4, tags), \
+             updated_at = now() \
+             where tenant_id = $5 and project_id = $6 and point_id = $7 \
+             returning point_id, tenant_id, project_id, device_id, key, data_type, unit, tags",
         )
         .bind(update.key)
         .bind(update.data_type)
         .bind(update.unit)
+        .bind(update.tags)
         .bind(&ctx.tenant_id)
         .bind(project_id)
         .bind(point_id)
@@ -149,6 +178,7 @@ impl PointStore for PgPointStore {
             key: row.try_get("key")?,
             data_type: row.try_get("data_type")?,
             unit: row.try_get("unit")?,
+            tags: row.try_get("tags")?,
         }))
     }
 
@@ -193,4 +223,37 @@ impl PointStore for PgPointStore {
 
         Ok(result.rows_affected() > 0)
     }
+
+    async fn list_points_with_tag(&self, tag: &str) -> Result<Vec<PointRecord>, StorageError> {
+        let rows = sqlx::query(
+            "select point_id, tenant_id, project_id, device_id, key, data_type, unit, tags \
+             from points where $1 = any(tags) order by point_id",
+        )
+        .bind(tag)
+        .fetch_all(&self.pool)
+        .await?;
+        let mut points = Vec::with_capacity(rows.len());
+        for row in rows {
+            points.push(PointRecord {
+                point_id: row.try_get("point_id")?,
+                tenant_id: row.try_get("tenant_id")?,
+                project_id: row.try_get("project_id")?,
+                device_id: row.try_get("device_id")?,
+                key: row.try_get("key")?,
+                data_type: row.try_get("data_type")?,
+                unit: row.try_get("unit")?,
+                tags: row.try_get("tags")?,
+            });
+        }
+        Ok(points)
+    }
+
+    async fn count_points(&self, ctx: &TenantContext) -> Result<i64, StorageError> {
+        ensure_tenant(ctx)?;
+        let count: i64 = sqlx::query_scalar("select count(*) from points where tenant_id = $1")
+            .bind(&ctx.tenant_id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count)
+    }
 }
@@ -7,7 +7,7 @@
 //! - 使用参数化 SQL 防止注入
 
 use crate::error::StorageError;
-use crate::models::{PointMappingRecord, PointMappingUpdate};
+use crate::models::{CollectionVersion, PointMappingRecord, PointMappingUpdate};
 use crate::traits::PointMappingStore;
 use crate::validation::ensure_project_scope;
 use domain::TenantContext;
@@ -60,6 +60,27 @@ impl PointMappingStore for PgPointMappingStore {
         Ok(mappings)
     }
 
+    /// 指定项目点映射集合的版本
+    async fn point_mappings_version(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<CollectionVersion, StorageError> {
+        let row = sqlx::query(
+            "select count(*) as count, \
+             coalesce((extract(epoch from max(updated_at)) * 1000000)::bigint, 0) as revision \
+             from point_sources where tenant_id = $1 and project_id = $2",
+        )
+        .bind(&ctx.tenant_id)
+        .bind(project_id)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(CollectionVersion {
+            count: row.try_get("count")?,
+            revision: row.try_get("revision")?,
+        })
+    }
+
     async fn find_point_mapping(
         &self,
         ctx: &TenantContext,
@@ -99,7 +120,7 @@ impl PointMappingStore for PgPointMappingStore {
     ) -> Result<PointMappingRecord, StorageError> {
         ensure_project_scope(ctx, &record.project_id)?;
         if record.tenant_id != ctx.tenant_id {
-            return Err(StorageError::new("tenant mismatch"));
+            return Err(StorageError::forbidden("tenant mismatch"));
         }
         sqlx::query(
             "insert into point_sources (source_id, tenant_id, project_id, point_id, source_type, address, scale, offset_value, protocol_detail) \
@@ -133,7 +154,8 @@ impl PointMappingStore for PgPointMappingStore {
              address = coalesce($2, address), \
              scale = coalesce($3, scale), \
              offset_value = coalesce($4, offset_value), \
-             protocol_detail = coalesce($5, protocol_detail) \
+             protocol_detail = coalesce($5, protocol_detail), \
+             updated_at = now() \
              where tenant_id = $6 and project_id = $7 and source_id = $8 \
              returning source_id, tenant_id, project_id, point_id, source_type, address, scale, offset_value, protocol_detail",
         )
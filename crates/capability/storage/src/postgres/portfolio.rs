@@ -0,0 +1,210 @@
+//! Postgres 项目组合实现
+//!
+//! 组合保存在 `portfolios`，成员项目保存在 `portfolio_projects`（按 ordinal 保持加入顺序）；
+//! 删除项目时成员关系通过外键级联删除。
+
+use crate::error::StorageError;
+use crate::models::{PortfolioRecord, PortfolioUpdate};
+use crate::traits::PortfolioStore;
+use crate::validation::ensure_tenant;
+use domain::TenantContext;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Postgres, Row, Transaction};
+
+pub struct PgPortfolioStore {
+    pub pool: PgPool,
+}
+
+impl PgPortfolioStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+const PORTFOLIO_COLUMNS: &str = "p.tenant_id, p.portfolio_id, p.name, p.description, \
+     array(select m.project_id from portfolio_projects m \
+     where m.tenant_id = p.tenant_id and m.portfolio_id = p.portfolio_id \
+     order by m.ordinal) as project_ids, \
+     (extract(epoch from p.created_at) * 1000)::bigint as created_at_ms, \
+     (extract(epoch from p.updated_at) * 1000)::bigint as updated_at_ms";
+
+fn portfolio_from_row(row: &PgRow) -> Result<PortfolioRecord, StorageError> {
+    Ok(PortfolioRecord {
+        tenant_id: row.try_get("tenant_id")?,
+        portfolio_id: row.try_get("portfolio_id")?,
+        name: row.try_get("name")?,
+        description: row.try_get("description")?,
+        project_ids: row.try_get("project_ids")?,
+        created_at_ms: row.try_get("created_at_ms")?,
+        updated_at_ms: row.try_get("updated_at_ms")?,
+    })
+}
+
+/// 整体替换组合成员
+async fn replace_members(
+    tx: &mut Transaction<'_, Postgres>,
+    tenant_id: &str,
+    portfolio_id: &str,
+    project_ids: &[String],
+) -> Result<(), StorageError> {
+    sqlx::query("delete from portfolio_projects where tenant_id = $1 and portfolio_id = $2")
+        .bind(tenant_id)
+        .bind(portfolio_id)
+        .execute(&mut **tx)
+        .await?;
+    for (ordinal, project_id) in project_ids.iter().enumerate() {
+        sqlx::query(
+            "insert into portfolio_projects (tenant_id, portfolio_id, project_id, ordinal) \
+             values ($1, $2, $3, $4)",
+        )
+        .bind(tenant_id)
+        .bind(portfolio_id)
+        .bind(project_id)
+        .bind(ordinal as i32)
+        .execute(&mut **tx)
+        .await?;
+    }
+    Ok(())
+}
+
+async fn fetch_portfolio(
+    tx: &mut Transaction<'_, Postgres>,
+    tenant_id: &str,
+    portfolio_id: &str,
+) -> Result<Option<PortfolioRecord>, StorageError> {
+    let sql = format!(
+        "select {PORTFOLIO_COLUMNS} from portfolios p \
+         where p.tenant_id = $1 and p.portfolio_id = $2"
+    );
+    let row = sqlx::query(&sql)
+        .bind(tenant_id)
+        .bind(portfolio_id)
+        .fetch_optional(&mut **tx)
+        .await?;
+    row.as_ref().map(portfolio_from_row).transpose()
+}
+
+#[async_trait::async_trait]
+impl PortfolioStore for PgPortfolioStore {
+    async fn list_portfolios(
+        &self,
+        ctx: &TenantContext,
+    ) -> Result<Vec<PortfolioRecord>, StorageError> {
+        ensure_tenant(ctx)?;
+        let sql = format!(
+            "select {PORTFOLIO_COLUMNS} from portfolios p \
+             where p.tenant_id = $1 \
+             order by p.created_at desc"
+        );
+        let rows = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .fetch_all(&self.pool)
+            .await?;
+        let mut items = Vec::with_capacity(rows.len());
+        for row in rows {
+            items.push(portfolio_from_row(&row)?);
+        }
+        Ok(items)
+    }
+
+    async fn find_portfolio(
+        &self,
+        ctx: &TenantContext,
+        portfolio_id: &str,
+    ) -> Result<Option<PortfolioRecord>, StorageError> {
+        ensure_tenant(ctx)?;
+        let sql = format!(
+            "select {PORTFOLIO_COLUMNS} from portfolios p \
+             where p.tenant_id = $1 and p.portfolio_id = $2"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(portfolio_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(portfolio_from_row).transpose()
+    }
+
+    async fn create_portfolio(
+        &self,
+        ctx: &TenantContext,
+        record: PortfolioRecord,
+    ) -> Result<PortfolioRecord, StorageError> {
+        ensure_tenant(ctx)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "insert into portfolios \
+             (tenant_id, portfolio_id, name, description, created_at, updated_at) \
+             values ($1, $2, $3, $4, to_timestamp($5 / 1000.0), to_timestamp($6 / 1000.0))",
+        )
+        .bind(&record.tenant_id)
+        .bind(&record.portfolio_id)
+        .bind(&record.name)
+        .bind(&record.description)
+        .bind(record.created_at_ms as f64)
+        .bind(record.updated_at_ms as f64)
+        .execute(&mut *tx)
+        .await?;
+        replace_members(
+            &mut tx,
+            &record.tenant_id,
+            &record.portfolio_id,
+            &record.project_ids,
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(record)
+    }
+
+    async fn update_portfolio(
+        &self,
+        ctx: &TenantContext,
+        portfolio_id: &str,
+        update: PortfolioUpdate,
+    ) -> Result<Option<PortfolioRecord>, StorageError> {
+        ensure_tenant(ctx)?;
+        let mut tx = self.pool.begin().await?;
+        let result = sqlx::query(
+            "update portfolios set \
+             name = coalesce($1, name), \
+             description = coalesce($2, description), \
+             updated_at = to_timestamp($3 / 1000.0) \
+             where tenant_id = $4 and portfolio_id = $5",
+        )
+        .bind(update.name)
+        .bind(update.description)
+        .bind(update.updated_at_ms as f64)
+        .bind(&ctx.tenant_id)
+        .bind(portfolio_id)
+        .execute(&mut *tx)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Ok(None);
+        }
+        if let Some(project_ids) = &update.project_ids {
+            replace_members(&mut tx, &ctx.tenant_id, portfolio_id, project_ids).await?;
+        }
+        let record = fetch_portfolio(&mut tx, &ctx.tenant_id, portfolio_id).await?;
+        tx.commit().await?;
+        Ok(record)
+    }
+
+    async fn delete_portfolio(
+        &self,
+        ctx: &TenantContext,
+        portfolio_id: &str,
+    ) -> Result<bool, StorageError> {
+        ensure_tenant(ctx)?;
+        // 成员关系通过外键级联删除
+        let result =
+            sqlx::query("delete from portfolios where tenant_id = $1 and portfolio_id = $2")
+                .bind(&ctx.tenant_id)
+                .bind(portfolio_id)
+                .execute(&self.pool)
+                .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -7,7 +7,7 @@
 //! - 使用参数化 SQL 防止注入
 
 use crate::error::StorageError;
-use crate::models::{ProjectRecord, ProjectUpdate};
+use crate::models::{CollectionVersion, ProjectRecord, ProjectUpdate};
 use crate::traits::ProjectStore;
 use crate::validation::ensure_tenant;
 use domain::TenantContext;
@@ -59,6 +59,26 @@ impl ProjectStore for PgProjectStore {
         Ok(projects)
     }
 
+    /// 当前租户项目集合的版本
+    async fn projects_version(
+        &self,
+        ctx: &TenantContext,
+    ) -> Result<CollectionVersion, StorageError> {
+        ensure_tenant(ctx)?;
+        let row = sqlx::query(
+            "select count(*) as count, \
+             coalesce((extract(epoch from max(updated_at)) * 1000000)::bigint, 0) as revision \
+             from projects where tenant_id = $1",
+        )
+        .bind(&ctx.tenant_id)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(CollectionVersion {
+            count: row.try_get("count")?,
+            revision: row.try_get("revision")?,
+        })
+    }
+
     /// 查找指定项目
     async fn find_project(
         &self,
@@ -93,7 +113,7 @@ impl ProjectStore for PgProjectStore {
     ) -> Result<ProjectRecord, StorageError> {
         ensure_tenant(ctx)?;
         if record.tenant_id != ctx.tenant_id {
-            return Err(StorageError::new("tenant mismatch"));
+            return Err(StorageError::forbidden("tenant mismatch"));
         }
         sqlx::query(
             "insert into projects (project_id, tenant_id, name, timezone) \
@@ -119,7 +139,8 @@ impl ProjectStore for PgProjectStore {
         let row = sqlx::query(
             "update projects set \
              name = coalesce($1, name), \
-             timezone = coalesce($2, timezone) \
+             timezone = coalesce($2, timezone), \
+             updated_at = now() \
              where tenant_id = $3 and project_id = $4 \
              returning project_id, tenant_id, name, timezone",
         )
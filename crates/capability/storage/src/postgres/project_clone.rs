@@ -0,0 +1,189 @@
+//! Postgres 项目克隆存储实现
+//!
+//! 在单个事务内写入新项目及其资产树，实现 [`ProjectCloneStore`] trait。
+//!
+//! ## 设计要点
+//!
+//! - **多租户隔离**：全部记录的 `tenant_id` / `project_id` 必须与新项目一致
+//! - **事务写入**：项目、网关（含命令载荷格式）、设备、点位、点位映射、设备模板与规则任一失败整体回滚
+
+use crate::error::StorageError;
+use crate::models::ProjectClone;
+use crate::traits::ProjectCloneStore;
+use crate::validation::ensure_tenant;
+use domain::TenantContext;
+use sqlx::PgPool;
+use std::collections::HashMap;
+
+/// PostgreSQL 项目克隆存储实现
+pub struct PgProjectCloneStore {
+    /// PostgreSQL 连接池
+    pub pool: PgPool,
+}
+
+impl PgProjectCloneStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl ProjectCloneStore for PgProjectCloneStore {
+    async fn create_project_clone(
+        &self,
+        ctx: &TenantContext,
+        clone: ProjectClone,
+    ) -> Result<ProjectClone, StorageError> {
+        ensure_tenant(ctx)?;
+        if clone.project.tenant_id != ctx.tenant_id || !clone.is_scoped_to_project() {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        let project = &clone.project;
+        sqlx::query(
+            "insert into projects (project_id, tenant_id, name, timezone) \
+             values ($1, $2, $3, $4)",
+        )
+        .bind(&project.project_id)
+        .bind(&project.tenant_id)
+        .bind(&project.name)
+        .bind(&project.timezone)
+        .execute(&mut *tx)
+        .await?;
+
+        let command_formats: HashMap<&str, &str> = clone
+            .gateway_command_formats
+            .iter()
+            .map(|(gateway_id, format)| (gateway_id.as_str(), format.as_str()))
+            .collect();
+        for gateway in &clone.gateways {
+            sqlx::query(
+                "insert into gateways (gateway_id, tenant_id, project_id, name, status, protocol_type, protocol_config, command_payload_format) \
+                 values ($1, $2, $3, $4, $5, $6, $7, $8)",
+            )
+            .bind(&gateway.gateway_id)
+            .bind(&gateway.tenant_id)
+            .bind(&gateway.project_id)
+            .bind(&gateway.name)
+            .bind(&gateway.status)
+            .bind(&gateway.protocol_type)
+            .bind(&gateway.protocol_config)
+            .bind(command_formats.get(gateway.gateway_id.as_str()).copied())
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for device in &clone.devices {
+            sqlx::query(
+                "insert into devices (device_id, tenant_id, project_id, gateway_id, name, model, room_id, address_config, offline_after_seconds) \
+                 values ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+            )
+            .bind(&device.device_id)
+            .bind(&device.tenant_id)
+            .bind(&device.project_id)
+            .bind(&device.gateway_id)
+            .bind(&device.name)
+            .bind(&device.model)
+            .bind(&device.room_id)
+            .bind(&device.address_config)
+            .bind(device.offline_after_seconds)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for point in &clone.points {
+            sqlx::query(
+                "insert into points (point_id, tenant_id, project_id, device_id, key, data_type, unit, tags) \
+                 values ($1, $2, $3, $4, $5, $6, $7, $8)",
+            )
+            .bind(&point.point_id)
+            .bind(&point.tenant_id)
+            .bind(&point.project_id)
+            .bind(&point.device_id)
+            .bind(&point.key)
+            .bind(&point.data_type)
+            .bind(&point.unit)
+            .bind(&point.tags)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for mapping in &clone.mappings {
+            sqlx::query(
+                "insert into point_sources (source_id, tenant_id, project_id, point_id, source_type, address, scale, offset_value, protocol_detail) \
+                 values ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+            )
+            .bind(&mapping.source_id)
+            .bind(&mapping.tenant_id)
+            .bind(&mapping.project_id)
+            .bind(&mapping.point_id)
+            .bind(&mapping.source_type)
+            .bind(&mapping.address)
+            .bind(mapping.scale)
+            .bind(mapping.offset)
+            .bind(&mapping.protocol_detail)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for template in &clone.device_templates {
+            sqlx::query(
+                "insert into device_templates (template_id, tenant_id, project_id, name, model) \
+                 values ($1, $2, $3, $4, $5)",
+            )
+            .bind(&template.template_id)
+            .bind(&template.tenant_id)
+            .bind(&template.project_id)
+            .bind(&template.name)
+            .bind(&template.model)
+            .execute(&mut *tx)
+            .await?;
+            for (ordinal, point) in template.points.iter().enumerate() {
+                sqlx::query(
+                    "insert into device_template_points \
+                     (template_id, ordinal, key, data_type, unit, source_type, address, scale, offset_value, protocol_detail) \
+                     values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+                )
+                .bind(&template.template_id)
+                .bind(ordinal as i32)
+                .bind(&point.key)
+                .bind(&point.data_type)
+                .bind(&point.unit)
+                .bind(&point.source_type)
+                .bind(&point.address)
+                .bind(point.scale)
+                .bind(point.offset)
+                .bind(&point.protocol_detail)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+
+        for rule in &clone.rules {
+            sqlx::query(
+                "insert into automation_rules \
+                 (tenant_id, project_id, rule_id, name, enabled, trigger, actions, created_by, \
+                 created_at, updated_at) \
+                 values ($1, $2, $3, $4, $5, $6::jsonb, $7::jsonb, $8, \
+                 to_timestamp($9 / 1000.0), to_timestamp($10 / 1000.0))",
+            )
+            .bind(&rule.tenant_id)
+            .bind(&rule.project_id)
+            .bind(&rule.rule_id)
+            .bind(&rule.name)
+            .bind(rule.enabled)
+            .bind(&rule.trigger)
+            .bind(&rule.actions)
+            .bind(&rule.created_by)
+            .bind(rule.created_at_ms as f64)
+            .bind(rule.updated_at_ms as f64)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(clone)
+    }
+}
@@ -0,0 +1,255 @@
+//! Postgres 自动化规则与执行记录实现
+
+use crate::error::StorageError;
+use crate::models::{RuleExecutionRecord, RuleRecord, RuleUpdate};
+use crate::traits::RuleStore;
+use crate::validation::ensure_project_scope;
+use domain::TenantContext;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+
+pub struct PgRuleStore {
+    pub pool: PgPool,
+}
+
+impl PgRuleStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+const RULE_COLUMNS: &str = "tenant_id, project_id, rule_id, name, enabled, \
+     trigger::text as trigger, actions::text as actions, created_by, \
+     (extract(epoch from created_at) * 1000)::bigint as created_at_ms, \
+     (extract(epoch from updated_at) * 1000)::bigint as updated_at_ms";
+
+const EXECUTION_COLUMNS: &str = "execution_id, tenant_id, project_id, rule_id, \
+     trigger_detail::text as trigger_detail, status, results::text as results, \
+     (extract(epoch from executed_at) * 1000)::bigint as executed_at_ms";
+
+fn rule_from_row(row: &PgRow) -> Result<RuleRecord, StorageError> {
+    Ok(RuleRecord {
+        tenant_id: row.try_get("tenant_id")?,
+        project_id: row.try_get("project_id")?,
+        rule_id: row.try_get("rule_id")?,
+        name: row.try_get("name")?,
+        enabled: row.try_get("enabled")?,
+        trigger: row.try_get("trigger")?,
+        actions: row.try_get("actions")?,
+        created_by: row.try_get("created_by")?,
+        created_at_ms: row.try_get("created_at_ms")?,
+        updated_at_ms: row.try_get("updated_at_ms")?,
+    })
+}
+
+fn execution_from_row(row: &PgRow) -> Result<RuleExecutionRecord, StorageError> {
+    Ok(RuleExecutionRecord {
+        execution_id: row.try_get("execution_id")?,
+        tenant_id: row.try_get("tenant_id")?,
+        project_id: row.try_get("project_id")?,
+        rule_id: row.try_get("rule_id")?,
+        trigger_detail: row.try_get("trigger_detail")?,
+        status: row.try_get("status")?,
+        results: row.try_get("results")?,
+        executed_at_ms: row.try_get("executed_at_ms")?,
+    })
+}
+
+#[async_trait::async_trait]
+impl RuleStore for PgRuleStore {
+    async fn list_rules(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<Vec<RuleRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let sql = format!(
+            "select {RULE_COLUMNS} from automation_rules \
+             where tenant_id = $1 and project_id = $2 \
+             order by created_at desc"
+        );
+        let rows = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .fetch_all(&self.pool)
+            .await?;
+        let mut items = Vec::with_capacity(rows.len());
+        for row in rows {
+            items.push(rule_from_row(&row)?);
+        }
+        Ok(items)
+    }
+
+    async fn find_rule(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        rule_id: &str,
+    ) -> Result<Option<RuleRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let sql = format!(
+            "select {RULE_COLUMNS} from automation_rules \
+             where tenant_id = $1 and project_id = $2 and rule_id = $3"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .bind(rule_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(rule_from_row).transpose()
+    }
+
+    async fn create_rule(
+        &self,
+        ctx: &TenantContext,
+        record: RuleRecord,
+    ) -> Result<RuleRecord, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let sql = format!(
+            "insert into automation_rules \
+             (tenant_id, project_id, rule_id, name, enabled, trigger, actions, created_by, \
+             created_at, updated_at) \
+             values ($1, $2, $3, $4, $5, $6::jsonb, $7::jsonb, $8, \
+             to_timestamp($9 / 1000.0), to_timestamp($10 / 1000.0)) \
+             returning {RULE_COLUMNS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&record.tenant_id)
+            .bind(&record.project_id)
+            .bind(&record.rule_id)
+            .bind(&record.name)
+            .bind(record.enabled)
+            .bind(&record.trigger)
+            .bind(&record.actions)
+            .bind(&record.created_by)
+            .bind(record.created_at_ms as f64)
+            .bind(record.updated_at_ms as f64)
+            .fetch_one(&self.pool)
+            .await?;
+        rule_from_row(&row)
+    }
+
+    async fn update_rule(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        rule_id: &str,
+        update: RuleUpdate,
+    ) -> Result<Option<RuleRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let sql = format!(
+            "update automation_rules set \
+             name = coalesce($1, name), \
+             enabled = coalesce($2, enabled), \
+             trigger = coalesce($3::jsonb, trigger), \
+             actions = coalesce($4::jsonb, actions), \
+             updated_at = to_timestamp($5 / 1000.0) \
+             where tenant_id = $6 and project_id = $7 and rule_id = $8 \
+             returning {RULE_COLUMNS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(update.name)
+            .bind(update.enabled)
+            .bind(update.trigger)
+            .bind(update.actions)
+            .bind(update.updated_at_ms as f64)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .bind(rule_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(rule_from_row).transpose()
+    }
+
+    async fn delete_rule(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        rule_id: &str,
+    ) -> Result<bool, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        // 执行记录通过外键级联删除
+        let result = sqlx::query(
+            "delete from automation_rules \
+             where tenant_id = $1 and project_id = $2 and rule_id = $3",
+        )
+        .bind(&ctx.tenant_id)
+        .bind(project_id)
+        .bind(rule_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn list_enabled_rules(&self) -> Result<Vec<RuleRecord>, StorageError> {
+        let sql = format!("select {RULE_COLUMNS} from automation_rules where enabled");
+        let rows = sqlx::query(&sql).fetch_all(&self.pool).await?;
+        let mut items = Vec::with_capacity(rows.len());
+        for row in rows {
+            items.push(rule_from_row(&row)?);
+        }
+        Ok(items)
+    }
+
+    async fn create_rule_execution(
+        &self,
+        ctx: &TenantContext,
+        record: RuleExecutionRecord,
+    ) -> Result<RuleExecutionRecord, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let sql = format!(
+            "insert into automation_rule_executions \
+             (execution_id, tenant_id, project_id, rule_id, trigger_detail, status, results, \
+             executed_at) \
+             values ($1, $2, $3, $4, $5::jsonb, $6, $7::jsonb, to_timestamp($8 / 1000.0)) \
+             returning {EXECUTION_COLUMNS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&record.execution_id)
+            .bind(&record.tenant_id)
+            .bind(&record.project_id)
+            .bind(&record.rule_id)
+            .bind(&record.trigger_detail)
+            .bind(&record.status)
+            .bind(&record.results)
+            .bind(record.executed_at_ms as f64)
+            .fetch_one(&self.pool)
+            .await?;
+        execution_from_row(&row)
+    }
+
+    async fn list_rule_executions(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        rule_id: &str,
+        limit: i64,
+    ) -> Result<Vec<RuleExecutionRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let sql = format!(
+            "select {EXECUTION_COLUMNS} from automation_rule_executions \
+             where tenant_id = $1 and project_id = $2 and rule_id = $3 \
+             order by executed_at desc \
+             limit $4"
+        );
+        let rows = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .bind(rule_id)
+            .bind(limit.max(0))
+            .fetch_all(&self.pool)
+            .await?;
+        let mut items = Vec::with_capacity(rows.len());
+        for row in rows {
+            items.push(execution_from_row(&row)?);
+        }
+        Ok(items)
+    }
+}
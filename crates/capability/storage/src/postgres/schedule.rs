@@ -0,0 +1,288 @@
+//! Postgres 控制计划与执行记录实现
+
+use crate::error::StorageError;
+use crate::models::{ScheduleExecutionRecord, ScheduleRecord, ScheduleUpdate};
+use crate::traits::ScheduleStore;
+use crate::validation::ensure_project_scope;
+use domain::TenantContext;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+
+pub struct PgScheduleStore {
+    pub pool: PgPool,
+}
+
+impl PgScheduleStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+const SCHEDULE_COLUMNS: &str = "tenant_id, project_id, schedule_id, name, enabled, timezone, \
+     spec::text as spec, missed_run_policy, \
+     (extract(epoch from last_evaluated_at) * 1000)::bigint as last_evaluated_at_ms, \
+     created_by, \
+     (extract(epoch from created_at) * 1000)::bigint as created_at_ms, \
+     (extract(epoch from updated_at) * 1000)::bigint as updated_at_ms";
+
+const EXECUTION_COLUMNS: &str = "execution_id, tenant_id, project_id, schedule_id, \
+     (extract(epoch from scheduled_at) * 1000)::bigint as scheduled_at_ms, \
+     status, results::text as results, \
+     (extract(epoch from executed_at) * 1000)::bigint as executed_at_ms";
+
+fn schedule_from_row(row: &PgRow) -> Result<ScheduleRecord, StorageError> {
+    Ok(ScheduleRecord {
+        tenant_id: row.try_get("tenant_id")?,
+        project_id: row.try_get("project_id")?,
+        schedule_id: row.try_get("schedule_id")?,
+        name: row.try_get("name")?,
+        enabled: row.try_get("enabled")?,
+        timezone: row.try_get("timezone")?,
+        spec: row.try_get("spec")?,
+        missed_run_policy: row.try_get("missed_run_policy")?,
+        last_evaluated_at_ms: row.try_get("last_evaluated_at_ms")?,
+        created_by: row.try_get("created_by")?,
+        created_at_ms: row.try_get("created_at_ms")?,
+        updated_at_ms: row.try_get("updated_at_ms")?,
+    })
+}
+
+fn execution_from_row(row: &PgRow) -> Result<ScheduleExecutionRecord, StorageError> {
+    Ok(ScheduleExecutionRecord {
+        execution_id: row.try_get("execution_id")?,
+        tenant_id: row.try_get("tenant_id")?,
+        project_id: row.try_get("project_id")?,
+        schedule_id: row.try_get("schedule_id")?,
+        scheduled_at_ms: row.try_get("scheduled_at_ms")?,
+        status: row.try_get("status")?,
+        results: row.try_get("results")?,
+        executed_at_ms: row.try_get("executed_at_ms")?,
+    })
+}
+
+#[async_trait::async_trait]
+impl ScheduleStore for PgScheduleStore {
+    async fn list_schedules(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<Vec<ScheduleRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let sql = format!(
+            "select {SCHEDULE_COLUMNS} from control_schedules \
+             where tenant_id = $1 and project_id = $2 \
+             order by created_at desc"
+        );
+        let rows = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .fetch_all(&self.pool)
+            .await?;
+        let mut items = Vec::with_capacity(rows.len());
+        for row in rows {
+            items.push(schedule_from_row(&row)?);
+        }
+        Ok(items)
+    }
+
+    async fn find_schedule(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        schedule_id: &str,
+    ) -> Result<Option<ScheduleRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let sql = format!(
+            "select {SCHEDULE_COLUMNS} from control_schedules \
+             where tenant_id = $1 and project_id = $2 and schedule_id = $3"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .bind(schedule_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(schedule_from_row).transpose()
+    }
+
+    async fn create_schedule(
+        &self,
+        ctx: &TenantContext,
+        record: ScheduleRecord,
+    ) -> Result<ScheduleRecord, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let sql = format!(
+            "insert into control_schedules \
+             (tenant_id, project_id, schedule_id, name, enabled, timezone, spec, \
+             missed_run_policy, last_evaluated_at, created_by, created_at, updated_at) \
+             values ($1, $2, $3, $4, $5, $6, $7::jsonb, $8, to_timestamp($9 / 1000.0), $10, \
+             to_timestamp($11 / 1000.0), to_timestamp($12 / 1000.0)) \
+             returning {SCHEDULE_COLUMNS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&record.tenant_id)
+            .bind(&record.project_id)
+            .bind(&record.schedule_id)
+            .bind(&record.name)
+            .bind(record.enabled)
+            .bind(&record.timezone)
+            .bind(&record.spec)
+            .bind(&record.missed_run_policy)
+            .bind(record.last_evaluated_at_ms as f64)
+            .bind(&record.created_by)
+            .bind(record.created_at_ms as f64)
+            .bind(record.updated_at_ms as f64)
+            .fetch_one(&self.pool)
+            .await?;
+        schedule_from_row(&row)
+    }
+
+    async fn update_schedule(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        schedule_id: &str,
+        update: ScheduleUpdate,
+    ) -> Result<Option<ScheduleRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let sql = format!(
+            "update control_schedules set \
+             name = coalesce($1, name), \
+             enabled = coalesce($2, enabled), \
+             timezone = coalesce($3, timezone), \
+             spec = coalesce($4::jsonb, spec), \
+             missed_run_policy = coalesce($5, missed_run_policy), \
+             last_evaluated_at = coalesce(to_timestamp($6 / 1000.0), last_evaluated_at), \
+             updated_at = to_timestamp($7 / 1000.0) \
+             where tenant_id = $8 and project_id = $9 and schedule_id = $10 \
+             returning {SCHEDULE_COLUMNS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(update.name)
+            .bind(update.enabled)
+            .bind(update.timezone)
+            .bind(update.spec)
+            .bind(update.missed_run_policy)
+            .bind(update.last_evaluated_at_ms.map(|value| value as f64))
+            .bind(update.updated_at_ms as f64)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .bind(schedule_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(schedule_from_row).transpose()
+    }
+
+    async fn delete_schedule(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        schedule_id: &str,
+    ) -> Result<bool, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        // 执行记录通过外键级联删除
+        let result = sqlx::query(
+            "delete from control_schedules \
+             where tenant_id = $1 and project_id = $2 and schedule_id = $3",
+        )
+        .bind(&ctx.tenant_id)
+        .bind(project_id)
+        .bind(schedule_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn list_enabled_schedules(&self) -> Result<Vec<ScheduleRecord>, StorageError> {
+        let sql = format!("select {SCHEDULE_COLUMNS} from control_schedules where enabled");
+        let rows = sqlx::query(&sql).fetch_all(&self.pool).await?;
+        let mut items = Vec::with_capacity(rows.len());
+        for row in rows {
+            items.push(schedule_from_row(&row)?);
+        }
+        Ok(items)
+    }
+
+    async fn mark_schedule_evaluated(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        schedule_id: &str,
+        evaluated_at_ms: i64,
+    ) -> Result<(), StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        sqlx::query(
+            "update control_schedules set last_evaluated_at = to_timestamp($1 / 1000.0) \
+             where tenant_id = $2 and project_id = $3 and schedule_id = $4",
+        )
+        .bind(evaluated_at_ms as f64)
+        .bind(&ctx.tenant_id)
+        .bind(project_id)
+        .bind(schedule_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn create_schedule_execution(
+        &self,
+        ctx: &TenantContext,
+        record: ScheduleExecutionRecord,
+    ) -> Result<ScheduleExecutionRecord, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let sql = format!(
+            "insert into control_schedule_executions \
+             (execution_id, tenant_id, project_id, schedule_id, scheduled_at, status, results, \
+             executed_at) \
+             values ($1, $2, $3, $4, to_timestamp($5 / 1000.0), $6, $7::jsonb, \
+             to_timestamp($8 / 1000.0)) \
+             returning {EXECUTION_COLUMNS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&record.execution_id)
+            .bind(&record.tenant_id)
+            .bind(&record.project_id)
+            .bind(&record.schedule_id)
+            .bind(record.scheduled_at_ms as f64)
+            .bind(&record.status)
+            .bind(&record.results)
+            .bind(record.executed_at_ms as f64)
+            .fetch_one(&self.pool)
+            .await?;
+        execution_from_row(&row)
+    }
+
+    async fn list_schedule_executions(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        schedule_id: &str,
+        limit: i64,
+    ) -> Result<Vec<ScheduleExecutionRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let sql = format!(
+            "select {EXECUTION_COLUMNS} from control_schedule_executions \
+             where tenant_id = $1 and project_id = $2 and schedule_id = $3 \
+             order by executed_at desc, scheduled_at desc \
+             limit $4"
+        );
+        let rows = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .bind(schedule_id)
+            .bind(limit.max(0))
+            .fetch_all(&self.pool)
+            .await?;
+        let mut items = Vec::with_capacity(rows.len());
+        for row in rows {
+            items.push(execution_from_row(&row)?);
+        }
+        Ok(items)
+    }
+}
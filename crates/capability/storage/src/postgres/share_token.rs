@@ -0,0 +1,134 @@
+//! Postgres 只读数据分享令牌实现
+
+use crate::error::StorageError;
+use crate::models::ShareTokenRecord;
+use crate::traits::ShareTokenStore;
+use crate::validation::ensure_project_scope;
+use domain::TenantContext;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+
+pub struct PgShareTokenStore {
+    pub pool: PgPool,
+}
+
+impl PgShareTokenStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+const SHARE_TOKEN_COLUMNS: &str = "token_id, tenant_id, project_id, token_hash, name, scopes, \
+     created_by, \
+     (extract(epoch from expires_at) * 1000)::bigint as expires_at_ms, \
+     (extract(epoch from revoked_at) * 1000)::bigint as revoked_at_ms, \
+     (extract(epoch from created_at) * 1000)::bigint as created_at_ms";
+
+fn share_token_from_row(row: &PgRow) -> Result<ShareTokenRecord, StorageError> {
+    Ok(ShareTokenRecord {
+        tenant_id: row.try_get("tenant_id")?,
+        project_id: row.try_get("project_id")?,
+        token_id: row.try_get("token_id")?,
+        token_hash: row.try_get("token_hash")?,
+        name: row.try_get("name")?,
+        scopes: row.try_get("scopes")?,
+        created_by: row.try_get("created_by")?,
+        expires_at_ms: row.try_get("expires_at_ms")?,
+        revoked_at_ms: row.try_get("revoked_at_ms")?,
+        created_at_ms: row.try_get("created_at_ms")?,
+    })
+}
+
+#[async_trait::async_trait]
+impl ShareTokenStore for PgShareTokenStore {
+    async fn create_share_token(
+        &self,
+        ctx: &TenantContext,
+        record: ShareTokenRecord,
+    ) -> Result<ShareTokenRecord, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let sql = format!(
+            "insert into share_tokens \
+             (token_id, tenant_id, project_id, token_hash, name, scopes, created_by, \
+             expires_at, created_at) \
+             values ($1, $2, $3, $4, $5, $6, $7, to_timestamp($8 / 1000.0), \
+             to_timestamp($9 / 1000.0)) \
+             returning {SHARE_TOKEN_COLUMNS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&record.token_id)
+            .bind(&record.tenant_id)
+            .bind(&record.project_id)
+            .bind(&record.token_hash)
+            .bind(&record.name)
+            .bind(&record.scopes)
+            .bind(&record.created_by)
+            .bind(record.expires_at_ms as f64)
+            .bind(record.created_at_ms as f64)
+            .fetch_one(&self.pool)
+            .await?;
+        share_token_from_row(&row)
+    }
+
+    async fn list_share_tokens(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<Vec<ShareTokenRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let sql = format!(
+            "select {SHARE_TOKEN_COLUMNS} from share_tokens \
+             where tenant_id = $1 and project_id = $2 \
+             order by created_at desc"
+        );
+        let rows = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .fetch_all(&self.pool)
+            .await?;
+        let mut items = Vec::with_capacity(rows.len());
+        for row in rows {
+            items.push(share_token_from_row(&row)?);
+        }
+        Ok(items)
+    }
+
+    async fn revoke_share_token(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        token_id: &str,
+        revoked_at_ms: i64,
+    ) -> Result<Option<ShareTokenRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let sql = format!(
+            "update share_tokens set \
+             revoked_at = coalesce(revoked_at, to_timestamp($1 / 1000.0)) \
+             where tenant_id = $2 and project_id = $3 and token_id = $4 \
+             returning {SHARE_TOKEN_COLUMNS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(revoked_at_ms as f64)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .bind(token_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(share_token_from_row).transpose()
+    }
+
+    async fn find_share_token_by_hash(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<ShareTokenRecord>, StorageError> {
+        let sql = format!("select {SHARE_TOKEN_COLUMNS} from share_tokens where token_hash = $1");
+        let row = sqlx::query(&sql)
+            .bind(token_hash)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(share_token_from_row).transpose()
+    }
+}
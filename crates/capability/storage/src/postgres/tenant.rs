@@ -0,0 +1,33 @@
+//! Postgres 租户实现
+
+use crate::error::StorageError;
+use crate::traits::TenantStore;
+use crate::validation::ensure_tenant;
+use domain::TenantContext;
+use sqlx::PgPool;
+
+pub struct PgTenantStore {
+    pub pool: PgPool,
+}
+
+impl PgTenantStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl TenantStore for PgTenantStore {
+    async fn create_tenant(&self, ctx: &TenantContext, name: &str) -> Result<bool, StorageError> {
+        ensure_tenant(ctx)?;
+        let result = sqlx::query(
+            "insert into tenants (tenant_id, name) values ($1, $2) \
+             on conflict (tenant_id) do nothing",
+        )
+        .bind(&ctx.tenant_id)
+        .bind(name)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,184 @@
+//! Postgres 用量计量与配额实现
+
+use crate::error::StorageError;
+use crate::models::{QuotaRecord, UsageRecord};
+use crate::traits::UsageStore;
+use crate::validation::ensure_tenant;
+use domain::TenantContext;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+
+pub struct PgUsageStore {
+    pub pool: PgPool,
+}
+
+impl PgUsageStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+const USAGE_COLUMNS: &str = "tenant_id, metric, \
+     (extract(epoch from period_start) * 1000)::bigint as period_start_ms, count";
+
+const QUOTA_COLUMNS: &str = "tenant_id, metric, limit_value, \
+     (extract(epoch from updated_at) * 1000)::bigint as updated_at_ms";
+
+fn usage_from_row(row: &PgRow) -> Result<UsageRecord, StorageError> {
+    Ok(UsageRecord {
+        tenant_id: row.try_get("tenant_id")?,
+        metric: row.try_get("metric")?,
+        period_start_ms: row.try_get("period_start_ms")?,
+        count: row.try_get("count")?,
+    })
+}
+
+fn quota_from_row(row: &PgRow) -> Result<QuotaRecord, StorageError> {
+    Ok(QuotaRecord {
+        tenant_id: row.try_get("tenant_id")?,
+        metric: row.try_get("metric")?,
+        limit: row.try_get("limit_value")?,
+        updated_at_ms: row.try_get("updated_at_ms")?,
+    })
+}
+
+#[async_trait::async_trait]
+impl UsageStore for PgUsageStore {
+    async fn consume_usage(
+        &self,
+        ctx: &TenantContext,
+        metric: &str,
+        period_start_ms: i64,
+        amount: i64,
+    ) -> Result<Option<i64>, StorageError> {
+        ensure_tenant(ctx)?;
+        // 配额判断与累加在同一条语句内完成，并发实例不会越过上限
+        let count: Option<i64> = sqlx::query_scalar(
+            "with quota as ( \
+                 select limit_value from tenant_quotas where tenant_id = $1 and metric = $2 \
+             ) \
+             insert into tenant_usage (tenant_id, metric, period_start, count) \
+             select $1, $2, to_timestamp($3 / 1000.0), $4 \
+             where $4 <= coalesce((select limit_value from quota), $4) \
+             on conflict (tenant_id, metric, period_start) do update set \
+             count = tenant_usage.count + excluded.count \
+             where tenant_usage.count + excluded.count \
+                 <= coalesce((select limit_value from quota), tenant_usage.count + excluded.count) \
+             returning count",
+        )
+        .bind(&ctx.tenant_id)
+        .bind(metric)
+        .bind(period_start_ms as f64)
+        .bind(amount)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(count)
+    }
+
+    async fn add_usage(
+        &self,
+        ctx: &TenantContext,
+        metric: &str,
+        period_start_ms: i64,
+        amount: i64,
+    ) -> Result<i64, StorageError> {
+        ensure_tenant(ctx)?;
+        let count: i64 = sqlx::query_scalar(
+            "insert into tenant_usage (tenant_id, metric, period_start, count)              values ($1, $2, to_timestamp($3 / 1000.0), $4)              on conflict (tenant_id, metric, period_start) do update set              count = tenant_usage.count + excluded.count              returning count",
+        )
+        .bind(&ctx.tenant_id)
+        .bind(metric)
+        .bind(period_start_ms as f64)
+        .bind(amount)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(count)
+    }
+
+    async fn list_usage(
+        &self,
+        ctx: &TenantContext,
+        from_ms: i64,
+        to_ms: i64,
+    ) -> Result<Vec<UsageRecord>, StorageError> {
+        ensure_tenant(ctx)?;
+        let sql = format!(
+            "select {USAGE_COLUMNS} from tenant_usage \
+             where tenant_id = $1 and period_start >= to_timestamp($2 / 1000.0) \
+             and period_start < to_timestamp($3 / 1000.0) \
+             order by period_start, metric"
+        );
+        let rows = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(from_ms as f64)
+            .bind(to_ms as f64)
+            .fetch_all(&self.pool)
+            .await?;
+        rows.iter().map(usage_from_row).collect()
+    }
+
+    async fn list_quotas(&self, ctx: &TenantContext) -> Result<Vec<QuotaRecord>, StorageError> {
+        ensure_tenant(ctx)?;
+        let sql = format!(
+            "select {QUOTA_COLUMNS} from tenant_quotas where tenant_id = $1 order by metric"
+        );
+        let rows = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .fetch_all(&self.pool)
+            .await?;
+        rows.iter().map(quota_from_row).collect()
+    }
+
+    async fn get_quota(
+        &self,
+        ctx: &TenantContext,
+        metric: &str,
+    ) -> Result<Option<QuotaRecord>, StorageError> {
+        ensure_tenant(ctx)?;
+        let sql = format!(
+            "select {QUOTA_COLUMNS} from tenant_quotas where tenant_id = $1 and metric = $2"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(metric)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(quota_from_row).transpose()
+    }
+
+    async fn upsert_quota(
+        &self,
+        ctx: &TenantContext,
+        record: QuotaRecord,
+    ) -> Result<QuotaRecord, StorageError> {
+        ensure_tenant(ctx)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let sql = format!(
+            "insert into tenant_quotas (tenant_id, metric, limit_value, updated_at) \
+             values ($1, $2, $3, to_timestamp($4 / 1000.0)) \
+             on conflict (tenant_id, metric) do update set \
+             limit_value = excluded.limit_value, updated_at = excluded.updated_at \
+             returning {QUOTA_COLUMNS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&record.tenant_id)
+            .bind(&record.metric)
+            .bind(record.limit)
+            .bind(record.updated_at_ms as f64)
+            .fetch_one(&self.pool)
+            .await?;
+        quota_from_row(&row)
+    }
+
+    async fn delete_quota(&self, ctx: &TenantContext, metric: &str) -> Result<bool, StorageError> {
+        ensure_tenant(ctx)?;
+        let result = sqlx::query("delete from tenant_quotas where tenant_id = $1 and metric = $2")
+            .bind(&ctx.tenant_id)
+            .bind(metric)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
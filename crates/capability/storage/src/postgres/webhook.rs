@@ -0,0 +1,219 @@
+//! Postgres Webhook 订阅与推送日志实现
+
+use crate::error::StorageError;
+use crate::models::{WebhookDeliveryRecord, WebhookSubscriptionRecord};
+use crate::traits::{WebhookDeliveryQuery, WebhookSubscriptionStore};
+use crate::validation::ensure_project_scope;
+use domain::TenantContext;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+
+pub struct PgWebhookSubscriptionStore {
+    pub pool: PgPool,
+}
+
+impl PgWebhookSubscriptionStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+const SUBSCRIPTION_COLUMNS: &str = "subscription_id, tenant_id, project_id, url, event_types, \
+     secret, enabled, created_by, \
+     (extract(epoch from created_at) * 1000)::bigint as created_at_ms";
+
+const DELIVERY_COLUMNS: &str = "delivery_id, tenant_id, project_id, subscription_id, event_id, \
+     event_type, status, attempts, response_status, error, \
+     (extract(epoch from created_at) * 1000)::bigint as created_at_ms";
+
+fn subscription_from_row(row: &PgRow) -> Result<WebhookSubscriptionRecord, StorageError> {
+    Ok(WebhookSubscriptionRecord {
+        subscription_id: row.try_get("subscription_id")?,
+        tenant_id: row.try_get("tenant_id")?,
+        project_id: row.try_get("project_id")?,
+        url: row.try_get("url")?,
+        event_types: row.try_get("event_types")?,
+        secret: row.try_get("secret")?,
+        enabled: row.try_get("enabled")?,
+        created_by: row.try_get("created_by")?,
+        created_at_ms: row.try_get("created_at_ms")?,
+    })
+}
+
+fn delivery_from_row(row: &PgRow) -> Result<WebhookDeliveryRecord, StorageError> {
+    Ok(WebhookDeliveryRecord {
+        delivery_id: row.try_get("delivery_id")?,
+        tenant_id: row.try_get("tenant_id")?,
+        project_id: row.try_get("project_id")?,
+        subscription_id: row.try_get("subscription_id")?,
+        event_id: row.try_get("event_id")?,
+        event_type: row.try_get("event_type")?,
+        status: row.try_get("status")?,
+        attempts: row.try_get("attempts")?,
+        response_status: row.try_get("response_status")?,
+        error: row.try_get("error")?,
+        created_at_ms: row.try_get("created_at_ms")?,
+    })
+}
+
+#[async_trait::async_trait]
+impl WebhookSubscriptionStore for PgWebhookSubscriptionStore {
+    async fn create_webhook_subscription(
+        &self,
+        ctx: &TenantContext,
+        record: WebhookSubscriptionRecord,
+    ) -> Result<WebhookSubscriptionRecord, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let sql = format!(
+            "insert into webhook_subscriptions \
+             (subscription_id, tenant_id, project_id, url, event_types, secret, enabled, created_by, created_at) \
+             values ($1, $2, $3, $4, $5, $6, $7, $8, to_timestamp($9 / 1000.0)) \
+             returning {SUBSCRIPTION_COLUMNS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&record.subscription_id)
+            .bind(&record.tenant_id)
+            .bind(&record.project_id)
+            .bind(&record.url)
+            .bind(&record.event_types)
+            .bind(&record.secret)
+            .bind(record.enabled)
+            .bind(&record.created_by)
+            .bind(record.created_at_ms as f64)
+            .fetch_one(&self.pool)
+            .await?;
+        subscription_from_row(&row)
+    }
+
+    async fn list_webhook_subscriptions(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<Vec<WebhookSubscriptionRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let sql = format!(
+            "select {SUBSCRIPTION_COLUMNS} from webhook_subscriptions \
+             where tenant_id = $1 and project_id = $2 \
+             order by created_at desc"
+        );
+        let rows = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .fetch_all(&self.pool)
+            .await?;
+        let mut items = Vec::with_capacity(rows.len());
+        for row in rows {
+            items.push(subscription_from_row(&row)?);
+        }
+        Ok(items)
+    }
+
+    async fn delete_webhook_subscription(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        subscription_id: &str,
+    ) -> Result<bool, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let result = sqlx::query(
+            "delete from webhook_subscriptions \
+             where tenant_id = $1 and project_id = $2 and subscription_id = $3",
+        )
+        .bind(&ctx.tenant_id)
+        .bind(project_id)
+        .bind(subscription_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn list_webhook_subscriptions_for_event(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        event_type: &str,
+    ) -> Result<Vec<WebhookSubscriptionRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let sql = format!(
+            "select {SUBSCRIPTION_COLUMNS} from webhook_subscriptions \
+             where tenant_id = $1 and project_id = $2 and enabled and $3 = any(event_types)"
+        );
+        let rows = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .bind(event_type)
+            .fetch_all(&self.pool)
+            .await?;
+        let mut items = Vec::with_capacity(rows.len());
+        for row in rows {
+            items.push(subscription_from_row(&row)?);
+        }
+        Ok(items)
+    }
+
+    async fn create_webhook_delivery(
+        &self,
+        ctx: &TenantContext,
+        record: WebhookDeliveryRecord,
+    ) -> Result<WebhookDeliveryRecord, StorageError> {
+        ensure_project_scope(ctx, &record.project_id)?;
+        if record.tenant_id != ctx.tenant_id {
+            return Err(StorageError::forbidden("tenant mismatch"));
+        }
+        let sql = format!(
+            "insert into webhook_deliveries \
+             (delivery_id, tenant_id, project_id, subscription_id, event_id, event_type, status, \
+             attempts, response_status, error, created_at) \
+             values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, to_timestamp($11 / 1000.0)) \
+             returning {DELIVERY_COLUMNS}"
+        );
+        let row = sqlx::query(&sql)
+            .bind(&record.delivery_id)
+            .bind(&record.tenant_id)
+            .bind(&record.project_id)
+            .bind(&record.subscription_id)
+            .bind(&record.event_id)
+            .bind(&record.event_type)
+            .bind(&record.status)
+            .bind(record.attempts)
+            .bind(record.response_status)
+            .bind(&record.error)
+            .bind(record.created_at_ms as f64)
+            .fetch_one(&self.pool)
+            .await?;
+        delivery_from_row(&row)
+    }
+
+    async fn list_webhook_deliveries(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        options: WebhookDeliveryQuery,
+    ) -> Result<Vec<WebhookDeliveryRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        let sql = format!(
+            "select {DELIVERY_COLUMNS} from webhook_deliveries \
+             where tenant_id = $1 and project_id = $2 \
+             and ($3::text is null or subscription_id = $3) \
+             and ($4::text is null or status = $4) \
+             order by created_at desc \
+             limit $5"
+        );
+        let rows = sqlx::query(&sql)
+            .bind(&ctx.tenant_id)
+            .bind(project_id)
+            .bind(options.subscription_id)
+            .bind(options.status)
+            .bind(options.limit.max(0))
+            .fetch_all(&self.pool)
+            .await?;
+        let mut items = Vec::with_capacity(rows.len());
+        for row in rows {
+            items.push(delivery_from_row(&row)?);
+        }
+        Ok(items)
+    }
+}
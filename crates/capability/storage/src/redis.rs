@@ -70,8 +70,7 @@ pub struct RedisOnlineStore {
 
 impl RedisOnlineStore {
     pub fn connect(redis_url: &str, ttl_seconds: u64) -> Result<Self, StorageError> {
-        let client =
-            redis::Client::open(redis_url).map_err(|err| StorageError::new(err.to_string()))?;
+        let client = redis::Client::open(redis_url)?;
         let ttl = ttl_seconds.max(1);
         Ok(Self {
             client,
@@ -96,8 +95,7 @@ impl RedisRealtimeStore {
     }
 
     pub fn connect(redis_url: &str) -> Result<Self, StorageError> {
-        let client =
-            redis::Client::open(redis_url).map_err(|err| StorageError::new(err.to_string()))?;
+        let client = redis::Client::open(redis_url)?;
         Ok(Self::new(client))
     }
 
@@ -105,8 +103,7 @@ impl RedisRealtimeStore {
         redis_url: &str,
         last_value_ttl_seconds: Option<u64>,
     ) -> Result<Self, StorageError> {
-        let client =
-            redis::Client::open(redis_url).map_err(|err| StorageError::new(err.to_string()))?;
+        let client = redis::Client::open(redis_url)?;
         let ttl = match last_value_ttl_seconds {
             Some(value) if value == 0 => None,
             Some(value) => Some(value),
@@ -125,13 +122,9 @@ impl RealtimeStore for RedisRealtimeStore {
     ) -> Result<(), StorageError> {
         ensure_project_scope(ctx, &value.project_id)?;
         if value.tenant_id != ctx.tenant_id {
-            return Err(StorageError::new("tenant mismatch"));
+            return Err(StorageError::forbidden("tenant mismatch"));
         }
-        let mut connection = self
-            .client
-            .get_multiplexed_tokio_connection()
-            .await
-            .map_err(|err| StorageError::new(err.to_string()))?;
+        let mut connection = self.client.get_multiplexed_tokio_connection().await?;
         let payload = LastValuePayload {
             ts_ms: value.ts_ms,
             value: value_to_string(value),
@@ -141,15 +134,9 @@ impl RealtimeStore for RedisRealtimeStore {
             serde_json::to_string(&payload).map_err(|err| StorageError::new(err.to_string()))?;
         let key = last_value_key(value);
         if let Some(ttl) = self.last_value_ttl_seconds {
-            connection
-                .set_ex::<_, _, ()>(key, data, ttl)
-                .await
-                .map_err(|err| StorageError::new(err.to_string()))?;
+            connection.set_ex::<_, _, ()>(key, data, ttl).await?;
         } else {
-            connection
-                .set::<_, _, ()>(key, data)
-                .await
-                .map_err(|err| StorageError::new(err.to_string()))?;
+            connection.set::<_, _, ()>(key, data).await?;
         }
         Ok(())
     }
@@ -161,19 +148,12 @@ impl RealtimeStore for RedisRealtimeStore {
         point_id: &str,
     ) -> Result<Option<RealtimeRecord>, StorageError> {
         ensure_project_scope(ctx, project_id)?;
-        let mut connection = self
-            .client
-            .get_multiplexed_tokio_connection()
-            .await
-            .map_err(|err| StorageError::new(err.to_string()))?;
+        let mut connection = self.client.get_multiplexed_tokio_connection().await?;
         let key = format!(
             "tenant:{}:project:{}:point:{}:last_value",
             ctx.tenant_id, project_id, point_id
         );
-        let data: Option<String> = connection
-            .get(key)
-            .await
-            .map_err(|err| StorageError::new(err.to_string()))?;
+        let data: Option<String> = connection.get(key).await?;
         let Some(data) = data else {
             return Ok(None);
         };
@@ -195,11 +175,7 @@ impl RealtimeStore for RedisRealtimeStore {
         project_id: &str,
     ) -> Result<Vec<RealtimeRecord>, StorageError> {
         ensure_project_scope(ctx, project_id)?;
-        let mut connection = self
-            .client
-            .get_multiplexed_tokio_connection()
-            .await
-            .map_err(|err| StorageError::new(err.to_string()))?;
+        let mut connection = self.client.get_multiplexed_tokio_connection().await?;
         let pattern = format!(
             "tenant:{}:project:{}:point:*:last_value",
             ctx.tenant_id, project_id
@@ -214,17 +190,13 @@ impl RealtimeStore for RedisRealtimeStore {
                 .arg("COUNT")
                 .arg(100)
                 .query_async(&mut connection)
-                .await
-                .map_err(|err| StorageError::new(err.to_string()))?;
+                .await?;
             for key in keys {
                 let point_id = match parse_point_id_from_key(&key) {
                     Some(value) => value.to_string(),
                     None => continue,
                 };
-                let data: Option<String> = connection
-                    .get(&key)
-                    .await
-                    .map_err(|err| StorageError::new(err.to_string()))?;
+                let data: Option<String> = connection.get(&key).await?;
                 let Some(data) = data else {
                     continue;
                 };
@@ -246,6 +218,50 @@ impl RealtimeStore for RedisRealtimeStore {
         }
         Ok(items)
     }
+
+    async fn get_last_values(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        point_ids: &[String],
+    ) -> Result<Vec<RealtimeRecord>, StorageError> {
+        ensure_project_scope(ctx, project_id)?;
+        if point_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut connection = self.client.get_multiplexed_tokio_connection().await?;
+        let keys: Vec<String> = point_ids
+            .iter()
+            .map(|point_id| {
+                format!(
+                    "tenant:{}:project:{}:point:{}:last_value",
+                    ctx.tenant_id, project_id, point_id
+                )
+            })
+            .collect();
+        // 显式使用 MGET，保证单个 key 时返回值仍为数组
+        let values: Vec<Option<String>> = redis::cmd("MGET")
+            .arg(&keys)
+            .query_async(&mut connection)
+            .await?;
+        let mut items = Vec::with_capacity(values.len());
+        for (point_id, data) in point_ids.iter().zip(values) {
+            let Some(data) = data else {
+                continue;
+            };
+            let payload: LastValuePayload = serde_json::from_str(&data)
+                .map_err(|err| StorageError::new(err.to_string()))?;
+            items.push(RealtimeRecord {
+                tenant_id: ctx.tenant_id.clone(),
+                project_id: project_id.to_string(),
+                point_id: point_id.clone(),
+                ts_ms: payload.ts_ms,
+                value: payload.value,
+                quality: payload.quality,
+            });
+        }
+        Ok(items)
+    }
 }
 
 #[async_trait::async_trait]
@@ -258,19 +274,14 @@ impl OnlineStore for RedisOnlineStore {
         ts_ms: i64,
     ) -> Result<(), StorageError> {
         ensure_project_scope(ctx, project_id)?;
-        let mut connection = self
-            .client
-            .get_multiplexed_tokio_connection()
-            .await
-            .map_err(|err| StorageError::new(err.to_string()))?;
+        let mut connection = self.client.get_multiplexed_tokio_connection().await?;
         let payload = OnlinePayload { ts_ms };
         let data =
             serde_json::to_string(&payload).map_err(|err| StorageError::new(err.to_string()))?;
         let key = gateway_online_key(&ctx.tenant_id, project_id, gateway_id);
         connection
             .set_ex::<_, _, ()>(key, data, self.ttl_seconds)
-            .await
-            .map_err(|err| StorageError::new(err.to_string()))?;
+            .await?;
         Ok(())
     }
 
@@ -282,19 +293,14 @@ impl OnlineStore for RedisOnlineStore {
         ts_ms: i64,
     ) -> Result<(), StorageError> {
         ensure_project_scope(ctx, project_id)?;
-        let mut connection = self
-            .client
-            .get_multiplexed_tokio_connection()
-            .await
-            .map_err(|err| StorageError::new(err.to_string()))?;
+        let mut connection = self.client.get_multiplexed_tokio_connection().await?;
         let payload = OnlinePayload { ts_ms };
         let data =
             serde_json::to_string(&payload).map_err(|err| StorageError::new(err.to_string()))?;
         let key = device_online_key(&ctx.tenant_id, project_id, device_id);
         connection
             .set_ex::<_, _, ()>(key, data, self.ttl_seconds)
-            .await
-            .map_err(|err| StorageError::new(err.to_string()))?;
+            .await?;
         Ok(())
     }
 
@@ -305,16 +311,9 @@ impl OnlineStore for RedisOnlineStore {
         gateway_id: &str,
     ) -> Result<Option<i64>, StorageError> {
         ensure_project_scope(ctx, project_id)?;
-        let mut connection = self
-            .client
-            .get_multiplexed_tokio_connection()
-            .await
-            .map_err(|err| StorageError::new(err.to_string()))?;
+        let mut connection = self.client.get_multiplexed_tokio_connection().await?;
         let key = gateway_online_key(&ctx.tenant_id, project_id, gateway_id);
-        let data: Option<String> = connection
-            .get(key)
-            .await
-            .map_err(|err| StorageError::new(err.to_string()))?;
+        let data: Option<String> = connection.get(key).await?;
         let Some(data) = data else {
             return Ok(None);
         };
@@ -330,16 +329,9 @@ impl OnlineStore for RedisOnlineStore {
         device_id: &str,
     ) -> Result<Option<i64>, StorageError> {
         ensure_project_scope(ctx, project_id)?;
-        let mut connection = self
-            .client
-            .get_multiplexed_tokio_connection()
-            .await
-            .map_err(|err| StorageError::new(err.to_string()))?;
+        let mut connection = self.client.get_multiplexed_tokio_connection().await?;
         let key = device_online_key(&ctx.tenant_id, project_id, device_id);
-        let data: Option<String> = connection
-            .get(key)
-            .await
-            .map_err(|err| StorageError::new(err.to_string()))?;
+        let data: Option<String> = connection.get(key).await?;
         let Some(data) = data else {
             return Ok(None);
         };
@@ -362,15 +354,8 @@ impl OnlineStore for RedisOnlineStore {
             .iter()
             .map(|id| gateway_online_key(&ctx.tenant_id, project_id, id))
             .collect();
-        let mut connection = self
-            .client
-            .get_multiplexed_tokio_connection()
-            .await
-            .map_err(|err| StorageError::new(err.to_string()))?;
-        let values: Vec<Option<String>> = connection
-            .mget(keys)
-            .await
-            .map_err(|err| StorageError::new(err.to_string()))?;
+        let mut connection = self.client.get_multiplexed_tokio_connection().await?;
+        let values: Vec<Option<String>> = connection.mget(keys).await?;
         let mut result = std::collections::HashMap::new();
         for (id, value) in gateway_ids.iter().zip(values.into_iter()) {
             let Some(value) = value else { continue };
@@ -397,15 +382,8 @@ impl OnlineStore for RedisOnlineStore {
             .iter()
             .map(|id| device_online_key(&ctx.tenant_id, project_id, id))
             .collect();
-        let mut connection = self
-            .client
-            .get_multiplexed_tokio_connection()
-            .await
-            .map_err(|err| StorageError::new(err.to_string()))?;
-        let values: Vec<Option<String>> = connection
-            .mget(keys)
-            .await
-            .map_err(|err| StorageError::new(err.to_string()))?;
+        let mut connection = self.client.get_multiplexed_tokio_connection().await?;
+        let values: Vec<Option<String>> = connection.mget(keys).await?;
         let mut result = std::collections::HashMap::new();
         for (id, value) in device_ids.iter().zip(values.into_iter()) {
             let Some(value) = value else { continue };
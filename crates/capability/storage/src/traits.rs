@@ -1,8 +1,10 @@
 //! 存储接口 Trait 定义
 //!
 //! 定义所有资源存储的异步接口：
+//! - TenantStore：租户存储
 //! - UserStore：用户存储
 //! - ProjectStore：项目存储
+//! - PortfolioStore：项目组合（多站点分组）存储
 //! - AreaStore：区域存储
 //! - BuildingStore：楼宇存储
 //! - FloorStore：楼层存储
@@ -11,6 +13,24 @@
 //! - DeviceStore：设备存储
 //! - PointStore：点存储
 //! - PointMappingStore：点映射存储
+//! - DeviceTemplateStore：设备模板存储
+//! - ProjectCloneStore：项目克隆（资产树整体写入新项目）
+//! - GatewayConfigStore：网关配置下发记录存储
+//! - DeviceShadowStore：设备影子（期望状态）存储
+//! - FirmwareStore：固件包、升级批次与网关升级进度存储
+//! - MaintenanceStore：设备 / 网关维护窗口存储
+//! - WebhookSubscriptionStore：Webhook 订阅与推送日志存储
+//! - ShareTokenStore：只读数据分享令牌存储
+//! - RuleStore：自动化规则与执行记录存储
+//! - ScheduleStore：控制计划与执行记录存储
+//! - DemandResponseStore：需求响应可削减负荷与事件存储
+//! - AnomalyStore：用能异常存储
+//! - DeviceEventStore：设备时间线事件存储
+//! - EmissionFactorStore：碳排放因子存储
+//! - IdempotencyStore：POST 幂等键存储
+//! - FeatureFlagStore：租户功能开关存储
+//! - UsageStore：租户用量计量与配额存储
+//! - JobStore：后台任务存储
 //!
 //! 设计原则：
 //! - 所有接口显式接收 TenantContext
@@ -19,16 +39,34 @@
 
 use crate::error::StorageError;
 use crate::models::{
-    AreaRecord, AreaUpdate, AuditLogRecord, BuildingRecord, BuildingUpdate, CommandReceiptRecord,
-    CommandRecord, DeviceRecord, DeviceUpdate, FloorRecord, FloorUpdate, GatewayRecord,
-    GatewayUpdate, MeasurementRecord, PermissionRecord, PointMappingRecord, PointMappingUpdate,
-    PointRecord, PointUpdate, ProjectRecord, ProjectUpdate, RbacRoleCreate, RbacRoleRecord,
-    RbacUserCreate, RbacUserRecord, RbacUserUpdate, RealtimeRecord, RoomRecord, RoomUpdate,
-    UserRecord,
+    AnomalyRecord, AreaRecord, AreaUpdate, AuditChainEntry, AuditLogRecord, BuildingRecord,
+    BuildingUpdate, CollectionVersion, CommandReceiptRecord, CommandRecord, CommandStatusCount,
+    DemandResponseEventRecord, DemandResponseEventUpdate, DeviceEventRecord, DeviceInstance,
+    DeviceRecord, DeviceShadowRecord, DeviceTemplateRecord, DeviceUpdate, EmissionFactorRecord,
+    FeatureFlagRecord, FirmwareCampaignRecord, FirmwarePackageRecord, FirmwareRolloutRecord,
+    FirmwareRolloutUpdate, FloorRecord, FloorUpdate, GatewayConfigRecord, GatewayRecord,
+    GatewayUpdate, IdempotencyRecord, JobRecord, MaintenanceWindowRecord, MeasurementCoverage,
+    MeasurementRecord, PermissionRecord, PointMappingRecord, PointMappingUpdate, PointRecord,
+    PointUpdate, PortfolioRecord, PortfolioUpdate, ProjectClone, ProjectRecord, ProjectUpdate,
+    QuotaRecord, RbacRoleCreate, RbacRoleRecord, RbacUserCreate, RbacUserRecord, RbacUserUpdate,
+    RealtimeRecord, RoomRecord, RoomUpdate, RuleExecutionRecord, RuleRecord, RuleUpdate,
+    ScheduleExecutionRecord, ScheduleRecord, ScheduleUpdate, ShareTokenRecord, SheddableLoadRecord,
+    UsageRecord, UserRecord, WebhookDeliveryRecord, WebhookSubscriptionRecord,
 };
 use async_trait::async_trait;
+use chrono::{Datelike, Offset, TimeZone, Timelike};
+use chrono_tz::Tz;
 use domain::{PointValue, TenantContext};
 
+/// 租户存储接口
+///
+/// 租户由运维侧创建（管理工具、演示数据），上下文中的 tenant_id 即要创建的租户。
+#[async_trait]
+pub trait TenantStore: Send + Sync {
+    /// 创建租户，返回是否新建（已存在时返回 false，不修改名称）
+    async fn create_tenant(&self, ctx: &TenantContext, name: &str) -> Result<bool, StorageError>;
+}
+
 /// 用户存储接口
 ///
 /// 提供用户查询功能（禁止在 handler 中直接连 SQL）。
@@ -122,6 +160,12 @@ pub trait ProjectStore: Send + Sync {
     /// 列出当前租户的所有项目
     async fn list_projects(&self, ctx: &TenantContext) -> Result<Vec<ProjectRecord>, StorageError>;
 
+    /// 当前租户项目集合的版本（用于列表 ETag）
+    async fn projects_version(
+        &self,
+        ctx: &TenantContext,
+    ) -> Result<CollectionVersion, StorageError>;
+
     /// 查找指定项目
     async fn find_project(
         &self,
@@ -159,6 +203,47 @@ pub trait ProjectStore: Send + Sync {
     ) -> Result<bool, StorageError>;
 }
 
+/// 项目组合存储接口
+///
+/// 组合按租户隔离；成员项目的租户归属由调用方校验，成员列表随组合整体读写。
+#[async_trait]
+pub trait PortfolioStore: Send + Sync {
+    /// 列出当前租户的项目组合（按创建时间倒序）
+    async fn list_portfolios(
+        &self,
+        ctx: &TenantContext,
+    ) -> Result<Vec<PortfolioRecord>, StorageError>;
+
+    /// 查找指定项目组合
+    async fn find_portfolio(
+        &self,
+        ctx: &TenantContext,
+        portfolio_id: &str,
+    ) -> Result<Option<PortfolioRecord>, StorageError>;
+
+    /// 创建项目组合
+    async fn create_portfolio(
+        &self,
+        ctx: &TenantContext,
+        record: PortfolioRecord,
+    ) -> Result<PortfolioRecord, StorageError>;
+
+    /// 更新项目组合（成员列表为 Some 时整体替换）
+    async fn update_portfolio(
+        &self,
+        ctx: &TenantContext,
+        portfolio_id: &str,
+        update: PortfolioUpdate,
+    ) -> Result<Option<PortfolioRecord>, StorageError>;
+
+    /// 删除项目组合（不影响成员项目），返回是否存在
+    async fn delete_portfolio(
+        &self,
+        ctx: &TenantContext,
+        portfolio_id: &str,
+    ) -> Result<bool, StorageError>;
+}
+
 // ============================================================================
 // 楼宇层级存储接口（区域 → 楼宇 → 楼层 → 房间）
 // ============================================================================
@@ -330,6 +415,13 @@ pub trait GatewayStore: Send + Sync {
         project_id: &str,
     ) -> Result<Vec<GatewayRecord>, StorageError>;
 
+    /// 指定项目网关集合的版本（用于列表 ETag）
+    async fn gateways_version(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<CollectionVersion, StorageError>;
+
     /// 查找指定网关
     async fn find_gateway(
         &self,
@@ -361,6 +453,45 @@ pub trait GatewayStore: Send + Sync {
         project_id: &str,
         gateway_id: &str,
     ) -> Result<bool, StorageError>;
+
+    /// 查询全部租户的网关
+    ///
+    /// 仅供离线检测后台任务使用（不经过租户上下文，调用方不得对外暴露）。
+    async fn list_all_gateways(&self) -> Result<Vec<GatewayRecord>, StorageError>;
+
+    /// 设置网关回调令牌摘要（`None` 表示吊销），网关不存在时返回 false
+    async fn set_gateway_token_hash(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        gateway_id: &str,
+        token_hash: Option<&str>,
+    ) -> Result<bool, StorageError>;
+
+    /// 按回调令牌摘要查找网关
+    ///
+    /// 仅供网关令牌鉴权使用（不经过租户上下文，调用方不得对外暴露）。
+    async fn find_gateway_by_token_hash(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<GatewayRecord>, StorageError>;
+
+    /// 设置网关命令载荷格式（JSON 文本，`None` 表示恢复默认），网关不存在时返回 false
+    async fn set_gateway_command_format(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        gateway_id: &str,
+        format: Option<&str>,
+    ) -> Result<bool, StorageError>;
+
+    /// 查询网关命令载荷格式（未设置或网关不存在时返回 None）
+    async fn find_gateway_command_format(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        gateway_id: &str,
+    ) -> Result<Option<String>, StorageError>;
 }
 
 /// 设备存储接口
@@ -375,6 +506,13 @@ pub trait DeviceStore: Send + Sync {
         project_id: &str,
     ) -> Result<Vec<DeviceRecord>, StorageError>;
 
+    /// 指定项目设备集合的版本（用于列表 ETag）
+    async fn devices_version(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<CollectionVersion, StorageError>;
+
     /// 查找指定设备
     async fn find_device(
         &self,
@@ -406,6 +544,11 @@ pub trait DeviceStore: Send + Sync {
         project_id: &str,
         device_id: &str,
     ) -> Result<bool, StorageError>;
+
+    /// 查询全部租户的设备
+    ///
+    /// 仅供离线检测后台任务使用（不经过租户上下文，调用方不得对外暴露）。
+    async fn list_all_devices(&self) -> Result<Vec<DeviceRecord>, StorageError>;
 }
 
 /// 点位存储接口
@@ -420,6 +563,13 @@ pub trait PointStore: Send + Sync {
         project_id: &str,
     ) -> Result<Vec<PointRecord>, StorageError>;
 
+    /// 指定项目点集合的版本（用于列表 ETag）
+    async fn points_version(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<CollectionVersion, StorageError>;
+
     /// 查找指定点
     async fn find_point(
         &self,
@@ -451,6 +601,14 @@ pub trait PointStore: Send + Sync {
         project_id: &str,
         point_id: &str,
     ) -> Result<bool, StorageError>;
+
+    /// 查询全部租户带指定标签的点位
+    ///
+    /// 仅供异常检测后台任务使用（不经过租户上下文，调用方不得对外暴露）。
+    async fn list_points_with_tag(&self, tag: &str) -> Result<Vec<PointRecord>, StorageError>;
+
+    /// 统计租户下全部项目的点位数（用于点位配额）
+    async fn count_points(&self, ctx: &TenantContext) -> Result<i64, StorageError>;
 }
 
 /// 点映射存储接口
@@ -465,6 +623,13 @@ pub trait PointMappingStore: Send + Sync {
         project_id: &str,
     ) -> Result<Vec<PointMappingRecord>, StorageError>;
 
+    /// 指定项目点映射集合的版本（用于列表 ETag）
+    async fn point_mappings_version(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<CollectionVersion, StorageError>;
+
     /// 查找指定点映射
     async fn find_point_mapping(
         &self,
@@ -474,13 +639,15 @@ pub trait PointMappingStore: Send + Sync {
     ) -> Result<Option<PointMappingRecord>, StorageError>;
 
     /// 创建新点映射
+    ///
+    /// 同一项目内 (source_type, address) 已被其他映射占用时返回 Conflict。
     async fn create_point_mapping(
         &self,
         ctx: &TenantContext,
         record: PointMappingRecord,
     ) -> Result<PointMappingRecord, StorageError>;
 
-    /// 更新点映射
+    /// 更新点映射（地址冲突规则同创建）
     async fn update_point_mapping(
         &self,
         ctx: &TenantContext,
@@ -498,6 +665,249 @@ pub trait PointMappingStore: Send + Sync {
     ) -> Result<bool, StorageError>;
 }
 
+/// 设备模板存储接口
+///
+/// 提供设备模板的增删查，以及按模板实例化设备。
+#[async_trait]
+pub trait DeviceTemplateStore: Send + Sync {
+    /// 列出指定项目的所有设备模板
+    async fn list_device_templates(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<Vec<DeviceTemplateRecord>, StorageError>;
+
+    /// 查找指定设备模板
+    async fn find_device_template(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        template_id: &str,
+    ) -> Result<Option<DeviceTemplateRecord>, StorageError>;
+
+    /// 创建新设备模板
+    async fn create_device_template(
+        &self,
+        ctx: &TenantContext,
+        record: DeviceTemplateRecord,
+    ) -> Result<DeviceTemplateRecord, StorageError>;
+
+    /// 删除设备模板（不影响已实例化的设备）
+    async fn delete_device_template(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        template_id: &str,
+    ) -> Result<bool, StorageError>;
+
+    /// 实例化设备：在同一事务内写入设备、点位与点位映射。
+    ///
+    /// 任一写入失败时整体回滚，不留下半成品设备。
+    async fn instantiate_device(
+        &self,
+        ctx: &TenantContext,
+        instance: DeviceInstance,
+    ) -> Result<DeviceInstance, StorageError>;
+}
+
+/// 项目克隆存储接口
+///
+/// 把重新分配 ID 后的资产树整体写入新项目（ID 重映射由调用方完成）。
+#[async_trait]
+pub trait ProjectCloneStore: Send + Sync {
+    /// 在同一事务内写入新项目及其网关、设备、点位、点位映射、设备模板与规则。
+    ///
+    /// 任一写入失败时整体回滚，不留下半成品项目。
+    async fn create_project_clone(
+        &self,
+        ctx: &TenantContext,
+        clone: ProjectClone,
+    ) -> Result<ProjectClone, StorageError>;
+}
+
+/// 网关配置下发记录存储接口
+///
+/// 记录每个网关的配置版本与下发/应用状态。
+#[async_trait]
+pub trait GatewayConfigStore: Send + Sync {
+    /// 创建下发记录（忽略入参 version，按网关分配下一个版本号）
+    async fn create_gateway_config(
+        &self,
+        ctx: &TenantContext,
+        record: GatewayConfigRecord,
+    ) -> Result<GatewayConfigRecord, StorageError>;
+
+    /// 查询网关的下发记录（按版本倒序）
+    async fn list_gateway_configs(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        gateway_id: &str,
+        limit: i64,
+    ) -> Result<Vec<GatewayConfigRecord>, StorageError>;
+
+    /// 更新指定版本的下发状态
+    async fn update_gateway_config_status(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        gateway_id: &str,
+        version: i64,
+        status: &str,
+        message: Option<String>,
+        updated_at_ms: i64,
+    ) -> Result<Option<GatewayConfigRecord>, StorageError>;
+}
+
+/// 固件升级存储接口
+///
+/// 固件包与升级批次按项目隔离；每个批次为每个目标网关保存一条升级进度。
+#[async_trait]
+pub trait FirmwareStore: Send + Sync {
+    /// 查询项目下的固件包（按创建时间倒序）
+    async fn list_firmware_packages(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<Vec<FirmwarePackageRecord>, StorageError>;
+
+    /// 查询单个固件包
+    async fn find_firmware_package(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        package_id: &str,
+    ) -> Result<Option<FirmwarePackageRecord>, StorageError>;
+
+    /// 创建固件包（同一项目下 name + version 重复时返回 Conflict）
+    async fn create_firmware_package(
+        &self,
+        ctx: &TenantContext,
+        record: FirmwarePackageRecord,
+    ) -> Result<FirmwarePackageRecord, StorageError>;
+
+    /// 查询项目下的升级批次（按创建时间倒序）
+    async fn list_firmware_campaigns(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        limit: i64,
+    ) -> Result<Vec<FirmwareCampaignRecord>, StorageError>;
+
+    /// 查询单个升级批次
+    async fn find_firmware_campaign(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        campaign_id: &str,
+    ) -> Result<Option<FirmwareCampaignRecord>, StorageError>;
+
+    /// 创建升级批次及其目标网关的升级进度（原子写入）
+    async fn create_firmware_campaign(
+        &self,
+        ctx: &TenantContext,
+        record: FirmwareCampaignRecord,
+        rollouts: Vec<FirmwareRolloutRecord>,
+    ) -> Result<FirmwareCampaignRecord, StorageError>;
+
+    /// 更新升级批次状态，不存在时返回 None
+    async fn update_firmware_campaign_status(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        campaign_id: &str,
+        status: &str,
+        updated_at_ms: i64,
+    ) -> Result<Option<FirmwareCampaignRecord>, StorageError>;
+
+    /// 查询升级批次下各网关的升级进度（按网关 ID 排序）
+    async fn list_firmware_rollouts(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        campaign_id: &str,
+    ) -> Result<Vec<FirmwareRolloutRecord>, StorageError>;
+
+    /// 更新网关升级进度，不存在时返回 None
+    async fn update_firmware_rollout(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        campaign_id: &str,
+        gateway_id: &str,
+        update: FirmwareRolloutUpdate,
+    ) -> Result<Option<FirmwareRolloutRecord>, StorageError>;
+}
+
+/// 维护窗口存储接口
+///
+/// 按 (项目, 目标类型, 目标 ID) 唯一；重复设置覆盖原窗口。
+#[async_trait]
+pub trait MaintenanceStore: Send + Sync {
+    /// 查询项目下的维护窗口（按目标类型、目标 ID 排序）
+    async fn list_maintenance_windows(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<Vec<MaintenanceWindowRecord>, StorageError>;
+
+    /// 查询单个目标的维护窗口
+    async fn find_maintenance_window(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        target_type: &str,
+        target_id: &str,
+    ) -> Result<Option<MaintenanceWindowRecord>, StorageError>;
+
+    /// 设置目标的维护窗口（已存在时覆盖）
+    async fn upsert_maintenance_window(
+        &self,
+        ctx: &TenantContext,
+        record: MaintenanceWindowRecord,
+    ) -> Result<MaintenanceWindowRecord, StorageError>;
+
+    /// 清除目标的维护窗口，返回是否存在
+    async fn delete_maintenance_window(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        target_type: &str,
+        target_id: &str,
+    ) -> Result<bool, StorageError>;
+}
+
+/// 设备影子存储接口
+///
+/// 每个设备一条期望状态记录；上报状态与差量由调用方根据实时值计算。
+#[async_trait]
+pub trait DeviceShadowStore: Send + Sync {
+    /// 查询设备影子（未设置过期望状态时返回 None）
+    async fn get_device_shadow(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        device_id: &str,
+    ) -> Result<Option<DeviceShadowRecord>, StorageError>;
+
+    /// 写入期望状态（忽略入参 version，在当前版本上加一；清空 last_command_id）
+    async fn put_desired_state(
+        &self,
+        ctx: &TenantContext,
+        record: DeviceShadowRecord,
+    ) -> Result<DeviceShadowRecord, StorageError>;
+
+    /// 记录指定版本下发差量所用的命令（版本已被覆盖时返回 None）
+    async fn set_shadow_command(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        device_id: &str,
+        version: i64,
+        command_id: &str,
+    ) -> Result<Option<DeviceShadowRecord>, StorageError>;
+}
+
 /// 时序写入接口
 ///
 /// 用于写入 Timescale measurement 数据。
@@ -526,6 +936,15 @@ pub trait MeasurementStore: Send + Sync {
         options: MeasurementsQueryOptions,
     ) -> Result<Vec<MeasurementRecord>, StorageError>;
 
+    /// 统计数据覆盖率与缺失区间（窗口为 `[from_ms, to_ms)`）。
+    async fn measurement_coverage(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        point_id: &str,
+        options: MeasurementCoverageOptions,
+    ) -> Result<MeasurementCoverage, StorageError>;
+
     /// 查询历史测点值
     async fn list_measurements(
         &self,
@@ -546,6 +965,27 @@ pub trait MeasurementStore: Send + Sync {
     }
 }
 
+/// 覆盖率统计参数。
+#[derive(Debug, Clone, Copy)]
+pub struct MeasurementCoverageOptions {
+    pub from_ms: i64,
+    pub to_ms: i64,
+    /// 期望上报间隔（毫秒），即切桶大小
+    pub expected_interval_ms: i64,
+    /// 最多返回的缺失区间数量
+    pub gap_limit: i64,
+}
+
+impl MeasurementCoverageOptions {
+    /// 窗口内期望的桶数量（向上取整）。
+    pub fn expected_buckets(&self) -> i64 {
+        if self.expected_interval_ms <= 0 || self.to_ms <= self.from_ms {
+            return 0;
+        }
+        (self.to_ms - self.from_ms + self.expected_interval_ms - 1) / self.expected_interval_ms
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TimeOrder {
     Asc,
@@ -567,10 +1007,69 @@ pub enum MeasurementAggFn {
     Count,
 }
 
+/// 日历聚合桶（按时区本地时间对齐，桶长随 DST / 月份天数变化）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalendarBucket {
+    Hour,
+    Day,
+    Month,
+}
+
+impl CalendarBucket {
+    /// 解析 `1h` / `1d` / `1mo`。
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "1h" => Some(Self::Hour),
+            "1d" => Some(Self::Day),
+            "1mo" => Some(Self::Month),
+            _ => None,
+        }
+    }
+
+    /// 对应的 `date_trunc` 字段名。
+    pub fn trunc_unit(&self) -> &'static str {
+        match self {
+            Self::Hour => "hour",
+            Self::Day => "day",
+            Self::Month => "month",
+        }
+    }
+
+    /// 本地时间所在桶的起始时刻（毫秒）。
+    pub fn bucket_start_ms(&self, ts_ms: i64, timezone: Tz) -> i64 {
+        let Some(utc) = chrono::DateTime::from_timestamp_millis(ts_ms) else {
+            return ts_ms;
+        };
+        let local = utc.with_timezone(&timezone).naive_local();
+        let date = match self {
+            Self::Month => local.date().with_day(1).unwrap_or(local.date()),
+            _ => local.date(),
+        };
+        let hour = match self {
+            Self::Hour => local.hour(),
+            _ => 0,
+        };
+        let Some(start) = date.and_hms_opt(hour, 0, 0) else {
+            return ts_ms;
+        };
+        match timezone.from_local_datetime(&start).earliest() {
+            Some(value) => value.timestamp_millis(),
+            // 本地起始时刻落在夏令时跳变的空档内：按跳变前的偏移换算
+            None => {
+                let offset = timezone.offset_from_utc_datetime(&utc.naive_utc()).fix();
+                start.and_utc().timestamp_millis() - i64::from(offset.local_minus_utc()) * 1000
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct MeasurementAggregation {
+    /// 固定桶大小（毫秒，按 epoch 对齐）；设置 `calendar` 时忽略
     pub bucket_ms: i64,
     pub func: MeasurementAggFn,
+    /// 日历桶（按 `MeasurementsQueryOptions.timezone` 本地时间对齐）
+    pub calendar: Option<CalendarBucket>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -581,6 +1080,8 @@ pub struct MeasurementsQueryOptions {
     pub order: TimeOrder,
     pub limit: i64,
     pub aggregation: Option<MeasurementAggregation>,
+    /// 日历聚合使用的时区（通常为项目时区，默认 UTC）
+    pub timezone: Tz,
 }
 
 impl MeasurementsQueryOptions {
@@ -592,10 +1093,65 @@ impl MeasurementsQueryOptions {
             order: TimeOrder::Asc,
             limit,
             aggregation: None,
+            timezone: Tz::UTC,
+        }
+    }
+}
+
+/// 测量值去重策略：同一点位同一时间戳重复写入时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MeasurementDedupStrategy {
+    /// 保留已有值，忽略重复写入
+    #[default]
+    Ignore,
+    /// 以新写入的值覆盖
+    Overwrite,
+    /// 新值质量不低于已有值时覆盖（`good` 或未标注 > 其他 > `bad`）
+    KeepBestQuality,
+}
+
+impl MeasurementDedupStrategy {
+    /// 解析配置值（`ignore` / `overwrite` / `keep_best_quality`）
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "ignore" => Some(Self::Ignore),
+            "overwrite" => Some(Self::Overwrite),
+            "keep_best_quality" => Some(Self::KeepBestQuality),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Ignore => "ignore",
+            Self::Overwrite => "overwrite",
+            Self::KeepBestQuality => "keep_best_quality",
+        }
+    }
+
+    /// 重复写入时是否以新值替换已有值
+    pub fn replaces(self, existing_quality: Option<&str>, incoming_quality: Option<&str>) -> bool {
+        match self {
+            Self::Ignore => false,
+            Self::Overwrite => true,
+            Self::KeepBestQuality => {
+                measurement_quality_rank(incoming_quality)
+                    >= measurement_quality_rank(existing_quality)
+            }
         }
     }
 }
 
+/// 测量值质量等级（越大越好，不区分大小写）：`good` 或未标注为 2，`bad` 为 0，其他为 1
+pub fn measurement_quality_rank(quality: Option<&str>) -> u8 {
+    match quality {
+        None => 2,
+        Some(quality) if quality.eq_ignore_ascii_case("good") => 2,
+        Some(quality) if quality.eq_ignore_ascii_case("bad") => 0,
+        Some(_) => 1,
+    }
+}
+
 /// 实时数据接口
 ///
 /// 用于维护 Redis last_value。
@@ -622,6 +1178,14 @@ pub trait RealtimeStore: Send + Sync {
         ctx: &TenantContext,
         project_id: &str,
     ) -> Result<Vec<RealtimeRecord>, StorageError>;
+
+    /// 批量查询指定点位的 last_value（无值的点位不返回）
+    async fn get_last_values(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        point_ids: &[String],
+    ) -> Result<Vec<RealtimeRecord>, StorageError>;
 }
 
 /// 控制命令存储接口
@@ -634,6 +1198,14 @@ pub trait CommandStore: Send + Sync {
         record: CommandRecord,
     ) -> Result<CommandRecord, StorageError>;
 
+    /// 查询命令（不存在或不属于当前租户 / 项目时返回 None）
+    async fn find_command(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        command_id: &str,
+    ) -> Result<Option<CommandRecord>, StorageError>;
+
     /// 更新命令状态
     async fn update_command_status(
         &self,
@@ -656,24 +1228,58 @@ pub trait CommandStore: Send + Sync {
         to_status: &str,
     ) -> Result<bool, StorageError>;
 
-    /// 查询命令列表
+    /// 查询命令列表（按下发时间倒序，支持过滤与 keyset 分页）
     async fn list_commands(
         &self,
         ctx: &TenantContext,
         project_id: &str,
-        limit: i64,
+        options: CommandQueryOptions,
     ) -> Result<Vec<CommandRecord>, StorageError>;
-}
 
-/// 命令回执存储接口
-#[async_trait]
-pub trait CommandReceiptStore: Send + Sync {
-    /// 写入命令回执
-    async fn create_receipt(
+    /// 按状态统计时间窗口内的命令数量
+    async fn count_commands_by_status(
         &self,
         ctx: &TenantContext,
-        record: CommandReceiptRecord,
-    ) -> Result<CommandReceiptWriteResult, StorageError>;
+        project_id: &str,
+        from_ms: Option<i64>,
+        to_ms: Option<i64>,
+    ) -> Result<Vec<CommandStatusCount>, StorageError>;
+}
+
+/// 命令列表查询参数。
+///
+/// 结果按 `(issued_at_ms, command_id)` 倒序；游标为上一页最后一条的
+/// `issued_at_ms` 与 `command_id`，仅返回排在其之后的记录。
+#[derive(Debug, Clone, Default)]
+pub struct CommandQueryOptions {
+    pub status: Option<String>,
+    pub target: Option<String>,
+    pub issued_by: Option<String>,
+    pub from_ms: Option<i64>,
+    pub to_ms: Option<i64>,
+    pub cursor_ts_ms: Option<i64>,
+    pub cursor_command_id: Option<String>,
+    pub limit: i64,
+}
+
+impl CommandQueryOptions {
+    pub fn simple(limit: i64) -> Self {
+        Self {
+            limit,
+            ..Self::default()
+        }
+    }
+}
+
+/// 命令回执存储接口
+#[async_trait]
+pub trait CommandReceiptStore: Send + Sync {
+    /// 写入命令回执
+    async fn create_receipt(
+        &self,
+        ctx: &TenantContext,
+        record: CommandReceiptRecord,
+    ) -> Result<CommandReceiptWriteResult, StorageError>;
 
     /// 查询命令回执
     async fn list_receipts(
@@ -692,9 +1298,11 @@ pub struct CommandReceiptWriteResult {
 }
 
 /// 审计日志存储接口
+///
+/// 写入时由存储按租户串行分配哈希链序号并计算摘要（见 `AuditChainEntry`）。
 #[async_trait]
 pub trait AuditLogStore: Send + Sync {
-    /// 写入审计日志
+    /// 写入审计日志（追加到租户哈希链末尾）
     async fn create_audit_log(
         &self,
         ctx: &TenantContext,
@@ -710,4 +1318,625 @@ pub trait AuditLogStore: Send + Sync {
         to_ms: Option<i64>,
         limit: i64,
     ) -> Result<Vec<AuditLogRecord>, StorageError>;
+
+    /// 按序号升序读取租户哈希链（`seq > after_seq`，最多 `limit` 条）
+    async fn list_audit_chain(
+        &self,
+        ctx: &TenantContext,
+        after_seq: i64,
+        limit: i64,
+    ) -> Result<Vec<AuditChainEntry>, StorageError>;
+}
+
+/// 只读数据分享令牌存储接口
+///
+/// 令牌按项目管理；按摘要查找用于免登录访问，不依赖租户上下文。
+#[async_trait]
+pub trait ShareTokenStore: Send + Sync {
+    /// 创建分享令牌
+    async fn create_share_token(
+        &self,
+        ctx: &TenantContext,
+        record: ShareTokenRecord,
+    ) -> Result<ShareTokenRecord, StorageError>;
+
+    /// 查询项目下的分享令牌（按创建时间倒序，含已撤销与已过期）
+    async fn list_share_tokens(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<Vec<ShareTokenRecord>, StorageError>;
+
+    /// 撤销分享令牌（已撤销的保持原撤销时间），返回撤销后的记录
+    async fn revoke_share_token(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        token_id: &str,
+        revoked_at_ms: i64,
+    ) -> Result<Option<ShareTokenRecord>, StorageError>;
+
+    /// 按令牌摘要查找（全部租户，调用方校验有效期与项目）
+    async fn find_share_token_by_hash(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<ShareTokenRecord>, StorageError>;
+}
+
+/// Webhook 订阅存储接口
+///
+/// 订阅按项目隔离；推送日志记录每次投递的最终结果（含重试次数）。
+#[async_trait]
+pub trait WebhookSubscriptionStore: Send + Sync {
+    /// 创建订阅
+    async fn create_webhook_subscription(
+        &self,
+        ctx: &TenantContext,
+        record: WebhookSubscriptionRecord,
+    ) -> Result<WebhookSubscriptionRecord, StorageError>;
+
+    /// 查询项目下的订阅（按创建时间倒序）
+    async fn list_webhook_subscriptions(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<Vec<WebhookSubscriptionRecord>, StorageError>;
+
+    /// 删除订阅，返回是否存在
+    async fn delete_webhook_subscription(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        subscription_id: &str,
+    ) -> Result<bool, StorageError>;
+
+    /// 查询订阅了指定事件类型的启用订阅
+    async fn list_webhook_subscriptions_for_event(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        event_type: &str,
+    ) -> Result<Vec<WebhookSubscriptionRecord>, StorageError>;
+
+    /// 写入推送日志
+    async fn create_webhook_delivery(
+        &self,
+        ctx: &TenantContext,
+        record: WebhookDeliveryRecord,
+    ) -> Result<WebhookDeliveryRecord, StorageError>;
+
+    /// 查询推送日志（按创建时间倒序）
+    async fn list_webhook_deliveries(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        options: WebhookDeliveryQuery,
+    ) -> Result<Vec<WebhookDeliveryRecord>, StorageError>;
+}
+
+/// 推送日志查询参数。
+#[derive(Debug, Clone, Default)]
+pub struct WebhookDeliveryQuery {
+    pub subscription_id: Option<String>,
+    pub status: Option<String>,
+    pub limit: i64,
+}
+
+/// 自动化规则存储接口
+///
+/// 规则按项目隔离；执行记录只追加，按执行时间倒序查询。
+#[async_trait]
+pub trait RuleStore: Send + Sync {
+    /// 查询项目下的规则（按创建时间倒序）
+    async fn list_rules(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<Vec<RuleRecord>, StorageError>;
+
+    /// 查询单条规则
+    async fn find_rule(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        rule_id: &str,
+    ) -> Result<Option<RuleRecord>, StorageError>;
+
+    /// 创建规则
+    async fn create_rule(
+        &self,
+        ctx: &TenantContext,
+        record: RuleRecord,
+    ) -> Result<RuleRecord, StorageError>;
+
+    /// 更新规则，不存在时返回 None
+    async fn update_rule(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        rule_id: &str,
+        update: RuleUpdate,
+    ) -> Result<Option<RuleRecord>, StorageError>;
+
+    /// 删除规则（连同执行记录），返回是否存在
+    async fn delete_rule(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        rule_id: &str,
+    ) -> Result<bool, StorageError>;
+
+    /// 查询全部租户的启用规则
+    ///
+    /// 仅供规则引擎后台任务使用（不经过租户上下文，调用方不得对外暴露）。
+    async fn list_enabled_rules(&self) -> Result<Vec<RuleRecord>, StorageError>;
+
+    /// 写入执行记录
+    async fn create_rule_execution(
+        &self,
+        ctx: &TenantContext,
+        record: RuleExecutionRecord,
+    ) -> Result<RuleExecutionRecord, StorageError>;
+
+    /// 查询规则的执行记录（按执行时间倒序）
+    async fn list_rule_executions(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        rule_id: &str,
+        limit: i64,
+    ) -> Result<Vec<RuleExecutionRecord>, StorageError>;
+}
+
+/// 控制计划存储接口
+///
+/// 计划按项目隔离；执行记录只追加，按执行时间倒序查询。
+#[async_trait]
+pub trait ScheduleStore: Send + Sync {
+    /// 查询项目下的计划（按创建时间倒序）
+    async fn list_schedules(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<Vec<ScheduleRecord>, StorageError>;
+
+    /// 查询单个计划
+    async fn find_schedule(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        schedule_id: &str,
+    ) -> Result<Option<ScheduleRecord>, StorageError>;
+
+    /// 创建计划
+    async fn create_schedule(
+        &self,
+        ctx: &TenantContext,
+        record: ScheduleRecord,
+    ) -> Result<ScheduleRecord, StorageError>;
+
+    /// 更新计划，不存在时返回 None
+    async fn update_schedule(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        schedule_id: &str,
+        update: ScheduleUpdate,
+    ) -> Result<Option<ScheduleRecord>, StorageError>;
+
+    /// 删除计划（连同执行记录），返回是否存在
+    async fn delete_schedule(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        schedule_id: &str,
+    ) -> Result<bool, StorageError>;
+
+    /// 查询全部租户的启用计划
+    ///
+    /// 仅供计划执行器后台任务使用（不经过租户上下文，调用方不得对外暴露）。
+    async fn list_enabled_schedules(&self) -> Result<Vec<ScheduleRecord>, StorageError>;
+
+    /// 推进执行器游标（不修改 `updated_at`）
+    async fn mark_schedule_evaluated(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        schedule_id: &str,
+        evaluated_at_ms: i64,
+    ) -> Result<(), StorageError>;
+
+    /// 写入执行记录
+    async fn create_schedule_execution(
+        &self,
+        ctx: &TenantContext,
+        record: ScheduleExecutionRecord,
+    ) -> Result<ScheduleExecutionRecord, StorageError>;
+
+    /// 查询计划的执行记录（按执行时间倒序）
+    async fn list_schedule_executions(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        schedule_id: &str,
+        limit: i64,
+    ) -> Result<Vec<ScheduleExecutionRecord>, StorageError>;
+}
+
+/// 需求响应存储接口
+///
+/// 可削减负荷按 (项目, 设备) 唯一；事件按项目隔离，按开始时间倒序查询。
+#[async_trait]
+pub trait DemandResponseStore: Send + Sync {
+    /// 查询项目下的可削减负荷（按优先级升序）
+    async fn list_sheddable_loads(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+    ) -> Result<Vec<SheddableLoadRecord>, StorageError>;
+
+    /// 创建或替换设备的可削减负荷配置（保留原创建时间）
+    async fn upsert_sheddable_load(
+        &self,
+        ctx: &TenantContext,
+        record: SheddableLoadRecord,
+    ) -> Result<SheddableLoadRecord, StorageError>;
+
+    /// 删除设备的可削减负荷配置，返回是否存在
+    async fn delete_sheddable_load(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        device_id: &str,
+    ) -> Result<bool, StorageError>;
+
+    /// 查询项目下的事件（按开始时间倒序）
+    async fn list_demand_response_events(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        limit: i64,
+    ) -> Result<Vec<DemandResponseEventRecord>, StorageError>;
+
+    /// 查询单个事件
+    async fn find_demand_response_event(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        event_id: &str,
+    ) -> Result<Option<DemandResponseEventRecord>, StorageError>;
+
+    /// 创建事件
+    async fn create_demand_response_event(
+        &self,
+        ctx: &TenantContext,
+        record: DemandResponseEventRecord,
+    ) -> Result<DemandResponseEventRecord, StorageError>;
+
+    /// 更新事件，不存在时返回 None
+    async fn update_demand_response_event(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        event_id: &str,
+        update: DemandResponseEventUpdate,
+    ) -> Result<Option<DemandResponseEventRecord>, StorageError>;
+
+    /// 查询全部租户未结束（scheduled / active）的事件
+    ///
+    /// 仅供需求响应编排器后台任务使用（不经过租户上下文，调用方不得对外暴露）。
+    async fn list_open_demand_response_events(
+        &self,
+    ) -> Result<Vec<DemandResponseEventRecord>, StorageError>;
+}
+
+/// 用能异常存储接口
+///
+/// 异常只追加，按 (项目, 点位, 小时桶) 去重，按小时桶倒序查询。
+#[async_trait]
+pub trait AnomalyStore: Send + Sync {
+    /// 记录异常，返回是否新增（同一点位同一小时桶已存在时返回 false）
+    async fn insert_anomaly(
+        &self,
+        ctx: &TenantContext,
+        record: AnomalyRecord,
+    ) -> Result<bool, StorageError>;
+
+    /// 查询项目下的异常（按小时桶倒序）
+    async fn list_anomalies(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        options: AnomalyQuery,
+    ) -> Result<Vec<AnomalyRecord>, StorageError>;
+
+    /// 统计项目下符合条件的异常数（忽略 `limit`）
+    async fn count_anomalies(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        options: AnomalyQuery,
+    ) -> Result<i64, StorageError>;
+}
+
+/// 异常查询参数（时间范围按小时桶起始时刻过滤，左闭右开）。
+#[derive(Debug, Clone, Default)]
+pub struct AnomalyQuery {
+    pub point_id: Option<String>,
+    pub device_id: Option<String>,
+    pub from_ms: Option<i64>,
+    pub to_ms: Option<i64>,
+    pub limit: i64,
+}
+
+/// 设备时间线事件存储接口
+///
+/// 事件只追加，按 (设备, 事件 ID) 去重；查询结果按 `(occurred_at_ms, event_id)` 倒序。
+#[async_trait]
+pub trait DeviceEventStore: Send + Sync {
+    /// 记录事件，返回是否新增（同一设备已记录该事件时返回 false）
+    async fn insert_device_event(
+        &self,
+        ctx: &TenantContext,
+        record: DeviceEventRecord,
+    ) -> Result<bool, StorageError>;
+
+    /// 查询设备时间线
+    async fn list_device_events(
+        &self,
+        ctx: &TenantContext,
+        project_id: &str,
+        device_id: &str,
+        options: DeviceEventQuery,
+    ) -> Result<Vec<DeviceEventRecord>, StorageError>;
+}
+
+/// 设备时间线查询参数。
+///
+/// 时间范围按发生时间过滤（闭区间）；游标为上一页最后一条的 `occurred_at_ms` 与
+/// `event_id`，仅返回排在其之后的记录。
+#[derive(Debug, Clone, Default)]
+pub struct DeviceEventQuery {
+    pub event_type: Option<String>,
+    pub from_ms: Option<i64>,
+    pub to_ms: Option<i64>,
+    pub cursor_ts_ms: Option<i64>,
+    pub cursor_event_id: Option<String>,
+    pub limit: i64,
+}
+
+/// 碳排放因子存储接口
+///
+/// 按 (租户, 项目, 能源类型) 保存因子；`project_id` 为 None 表示租户默认值。
+/// 各方法只操作指定作用域本身，租户默认值与项目覆盖的合并由调用方处理。
+#[async_trait]
+pub trait EmissionFactorStore: Send + Sync {
+    /// 列出指定作用域的因子（按能源类型排序）
+    async fn list_emission_factors(
+        &self,
+        ctx: &TenantContext,
+        project_id: Option<&str>,
+    ) -> Result<Vec<EmissionFactorRecord>, StorageError>;
+
+    /// 写入因子（同一作用域同一能源类型存在则覆盖）
+    async fn upsert_emission_factor(
+        &self,
+        ctx: &TenantContext,
+        record: EmissionFactorRecord,
+    ) -> Result<EmissionFactorRecord, StorageError>;
+
+    /// 删除因子，返回是否存在
+    async fn delete_emission_factor(
+        &self,
+        ctx: &TenantContext,
+        project_id: Option<&str>,
+        energy_source: &str,
+    ) -> Result<bool, StorageError>;
+}
+
+/// 幂等键存储接口
+///
+/// 按 (租户, 幂等键) 记录请求摘要与响应，过期记录视为不存在。
+#[async_trait]
+pub trait IdempotencyStore: Send + Sync {
+    /// 预占幂等键：预占成功返回 None；键已存在且未过期时返回已有记录
+    async fn reserve_idempotency_key(
+        &self,
+        ctx: &TenantContext,
+        record: IdempotencyRecord,
+    ) -> Result<Option<IdempotencyRecord>, StorageError>;
+
+    /// 记录首次请求的响应（状态码、响应头 JSON 与响应体）
+    async fn complete_idempotency_key(
+        &self,
+        ctx: &TenantContext,
+        idempotency_key: &str,
+        status_code: i32,
+        response_headers: String,
+        response_body: String,
+    ) -> Result<(), StorageError>;
+
+    /// 释放幂等键（首次请求失败时允许客户端重试）
+    async fn release_idempotency_key(
+        &self,
+        ctx: &TenantContext,
+        idempotency_key: &str,
+    ) -> Result<(), StorageError>;
+}
+
+/// 租户功能开关存储接口
+///
+/// 按 (租户, 开关键) 保存开关状态；未配置的开关由调用方按默认值处理。
+#[async_trait]
+pub trait FeatureFlagStore: Send + Sync {
+    /// 列出租户已配置的开关（按开关键排序）
+    async fn list_feature_flags(
+        &self,
+        ctx: &TenantContext,
+    ) -> Result<Vec<FeatureFlagRecord>, StorageError>;
+
+    /// 查询单个开关
+    async fn get_feature_flag(
+        &self,
+        ctx: &TenantContext,
+        flag_key: &str,
+    ) -> Result<Option<FeatureFlagRecord>, StorageError>;
+
+    /// 写入开关（存在则覆盖）
+    async fn upsert_feature_flag(
+        &self,
+        ctx: &TenantContext,
+        record: FeatureFlagRecord,
+    ) -> Result<FeatureFlagRecord, StorageError>;
+
+    /// 删除开关（恢复默认值），返回是否存在
+    async fn delete_feature_flag(
+        &self,
+        ctx: &TenantContext,
+        flag_key: &str,
+    ) -> Result<bool, StorageError>;
+}
+
+/// 租户用量计量与配额存储接口
+///
+/// 计数类指标按 (租户, 指标, 周期起始) 累计；配额按 (租户, 指标) 保存上限，未配置时不限制。
+#[async_trait]
+pub trait UsageStore: Send + Sync {
+    /// 累加用量：累加后不超过配额时写入并返回累加后的用量，超出配额时不累加并返回 None
+    async fn consume_usage(
+        &self,
+        ctx: &TenantContext,
+        metric: &str,
+        period_start_ms: i64,
+        amount: i64,
+    ) -> Result<Option<i64>, StorageError>;
+
+    /// 累加用量（不检查配额），返回累加后的用量
+    ///
+    /// 供已在调用方按缓存完成配额检查的批量计量刷盘使用。
+    async fn add_usage(
+        &self,
+        ctx: &TenantContext,
+        metric: &str,
+        period_start_ms: i64,
+        amount: i64,
+    ) -> Result<i64, StorageError>;
+
+    /// 列出周期起始时刻在 `[from_ms, to_ms)` 内的用量（按周期、指标排序）
+    async fn list_usage(
+        &self,
+        ctx: &TenantContext,
+        from_ms: i64,
+        to_ms: i64,
+    ) -> Result<Vec<UsageRecord>, StorageError>;
+
+    /// 列出租户已配置的配额（按指标排序）
+    async fn list_quotas(&self, ctx: &TenantContext) -> Result<Vec<QuotaRecord>, StorageError>;
+
+    /// 查询单个指标的配额
+    async fn get_quota(
+        &self,
+        ctx: &TenantContext,
+        metric: &str,
+    ) -> Result<Option<QuotaRecord>, StorageError>;
+
+    /// 写入配额（存在则覆盖）
+    async fn upsert_quota(
+        &self,
+        ctx: &TenantContext,
+        record: QuotaRecord,
+    ) -> Result<QuotaRecord, StorageError>;
+
+    /// 删除配额（恢复不限制），返回是否存在
+    async fn delete_quota(&self, ctx: &TenantContext, metric: &str) -> Result<bool, StorageError>;
+}
+
+/// 后台任务存储接口
+///
+/// 状态只能按 queued → running → succeeded / failed / cancelled 推进，
+/// 已结束的任务不再修改；查询结果按 `(created_at_ms, job_id)` 倒序。
+#[async_trait]
+pub trait JobStore: Send + Sync {
+    /// 创建任务
+    async fn create_job(
+        &self,
+        ctx: &TenantContext,
+        record: JobRecord,
+    ) -> Result<JobRecord, StorageError>;
+
+    /// 查询单个任务
+    async fn find_job(
+        &self,
+        ctx: &TenantContext,
+        job_id: &str,
+    ) -> Result<Option<JobRecord>, StorageError>;
+
+    /// 查询租户下的任务
+    async fn list_jobs(
+        &self,
+        ctx: &TenantContext,
+        options: JobQuery,
+    ) -> Result<Vec<JobRecord>, StorageError>;
+
+    /// 开始执行：仅 `queued` 且未请求取消的任务转为 `running`，否则返回 None
+    async fn start_job(
+        &self,
+        ctx: &TenantContext,
+        job_id: &str,
+        started_at_ms: i64,
+    ) -> Result<Option<JobRecord>, StorageError>;
+
+    /// 更新运行中任务的进度，返回更新后的任务（含取消标记）；任务不在运行时返回 None
+    async fn update_job_progress(
+        &self,
+        ctx: &TenantContext,
+        job_id: &str,
+        progress: i32,
+        updated_at_ms: i64,
+    ) -> Result<Option<JobRecord>, StorageError>;
+
+    /// 结束未完成的任务（写入终态、结果或错误），任务已结束时返回 None
+    async fn finish_job(
+        &self,
+        ctx: &TenantContext,
+        job_id: &str,
+        finish: JobFinish,
+    ) -> Result<Option<JobRecord>, StorageError>;
+
+    /// 请求取消：`queued` 直接转为 `cancelled`，`running` 标记取消请求，已结束的不变
+    async fn request_job_cancel(
+        &self,
+        ctx: &TenantContext,
+        job_id: &str,
+        requested_at_ms: i64,
+    ) -> Result<Option<JobRecord>, StorageError>;
+
+    /// 把全部租户未结束（queued / running）的任务标记为 `failed`，返回数量
+    ///
+    /// 仅供任务执行器启动时回收上次停机中断的任务（不经过租户上下文，调用方不得对外暴露）。
+    async fn fail_unfinished_jobs(
+        &self,
+        error: &str,
+        finished_at_ms: i64,
+    ) -> Result<u64, StorageError>;
+}
+
+/// 后台任务查询参数。
+#[derive(Debug, Clone, Default)]
+pub struct JobQuery {
+    pub project_id: Option<String>,
+    pub kind: Option<String>,
+    pub status: Option<String>,
+    pub limit: i64,
+}
+
+/// 任务终态。
+#[derive(Debug, Clone)]
+pub struct JobFinish {
+    /// succeeded / failed / cancelled
+    pub status: String,
+    /// 结果（JSON 文本）
+    pub result: Option<String>,
+    pub error: Option<String>,
+    pub finished_at_ms: i64,
 }
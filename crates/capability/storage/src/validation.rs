@@ -16,7 +16,7 @@ use domain::TenantContext;
 /// 确保所有数据访问都有有效的租户上下文。
 pub fn ensure_tenant(ctx: &TenantContext) -> Result<(), StorageError> {
     if ctx.tenant_id.is_empty() {
-        return Err(StorageError::new("tenant_id required"));
+        return Err(StorageError::forbidden("tenant_id required"));
     }
     Ok(())
 }
@@ -28,7 +28,7 @@ pub fn ensure_project_scope(ctx: &TenantContext, project_id: &str) -> Result<(),
     ensure_tenant(ctx)?;
     if let Some(scope) = ctx.project_scope.as_deref() {
         if scope != project_id {
-            return Err(StorageError::new("project scope mismatch"));
+            return Err(StorageError::forbidden("project scope mismatch"));
         }
     }
     Ok(())
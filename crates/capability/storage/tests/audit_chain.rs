@@ -0,0 +1,79 @@
+use domain::TenantContext;
+use ems_storage::{AuditLogRecord, AuditLogStore, InMemoryAuditLogStore};
+
+fn tenant_ctx(tenant_id: &str) -> TenantContext {
+    TenantContext::new(tenant_id, "user-1", vec![], vec![], None)
+}
+
+fn audit_log(tenant_id: &str, audit_id: &str, ts_ms: i64) -> AuditLogRecord {
+    AuditLogRecord {
+        audit_id: audit_id.to_string(),
+        tenant_id: tenant_id.to_string(),
+        project_id: None,
+        actor: "user-1".to_string(),
+        action: "CONTROL.COMMAND.ISSUE".to_string(),
+        resource: "command:cmd-1".to_string(),
+        result: "accepted".to_string(),
+        detail: Some("{}".to_string()),
+        ts_ms,
+    }
+}
+
+#[tokio::test]
+async fn audit_chain_links_records_per_tenant() {
+    let store = InMemoryAuditLogStore::new();
+    let ctx = tenant_ctx("tenant-1");
+    let other = tenant_ctx("tenant-2");
+    for (index, audit_id) in ["audit-1", "audit-2", "audit-3"].iter().enumerate() {
+        store
+            .create_audit_log(&ctx, audit_log("tenant-1", audit_id, index as i64))
+            .await
+            .expect("create");
+    }
+    store
+        .create_audit_log(&other, audit_log("tenant-2", "audit-x", 10))
+        .await
+        .expect("create other");
+
+    let chain = store.list_audit_chain(&ctx, 0, 100).await.expect("chain");
+    assert_eq!(chain.len(), 3);
+    let mut prev_hash = String::new();
+    for (index, entry) in chain.iter().enumerate() {
+        assert_eq!(entry.check(index as i64 + 1, &prev_hash), None);
+        prev_hash = entry.hash.clone();
+    }
+
+    // 其他租户独立从 1 开始编号
+    let other_chain = store.list_audit_chain(&other, 0, 100).await.expect("chain");
+    assert_eq!(other_chain.len(), 1);
+    assert_eq!(other_chain[0].seq, 1);
+    assert!(other_chain[0].prev_hash.is_empty());
+
+    let page = store.list_audit_chain(&ctx, 1, 1).await.expect("page");
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].seq, 2);
+}
+
+#[tokio::test]
+async fn audit_chain_check_detects_tampering_and_gaps() {
+    let store = InMemoryAuditLogStore::new();
+    let ctx = tenant_ctx("tenant-1");
+    for (index, audit_id) in ["audit-1", "audit-2", "audit-3"].iter().enumerate() {
+        store
+            .create_audit_log(&ctx, audit_log("tenant-1", audit_id, index as i64))
+            .await
+            .expect("create");
+    }
+    let chain = store.list_audit_chain(&ctx, 0, 100).await.expect("chain");
+
+    let mut modified = chain[1].clone();
+    modified.record.result = "rejected".to_string();
+    assert_eq!(modified.check(2, &chain[0].hash), Some("hash_mismatch"));
+
+    // 删除第 2 条后，第 3 条序号不连续
+    assert_eq!(chain[2].check(2, &chain[0].hash), Some("gap"));
+
+    let mut relinked = chain[2].clone();
+    relinked.seq = 2;
+    assert_eq!(relinked.check(2, &chain[0].hash), Some("broken_link"));
+}
@@ -0,0 +1,142 @@
+use domain::TenantContext;
+use ems_storage::{CommandQueryOptions, CommandRecord, CommandStore, InMemoryCommandStore};
+
+fn tenant_ctx(project_id: &str) -> TenantContext {
+    TenantContext::new(
+        "tenant-1",
+        "user-1",
+        vec![],
+        vec![],
+        Some(project_id.to_string()),
+    )
+}
+
+fn command(command_id: &str, target: &str, status: &str, issued_at_ms: i64) -> CommandRecord {
+    CommandRecord {
+        command_id: command_id.to_string(),
+        tenant_id: "tenant-1".to_string(),
+        project_id: "project-1".to_string(),
+        target: target.to_string(),
+        payload: "{}".to_string(),
+        status: status.to_string(),
+        issued_by: "user-1".to_string(),
+        issued_at_ms,
+    }
+}
+
+async fn seeded_store(ctx: &TenantContext) -> InMemoryCommandStore {
+    let store = InMemoryCommandStore::new();
+    for record in [
+        command("cmd-1", "dev-1", "success", 1_000),
+        command("cmd-2", "dev-1", "failed", 2_000),
+        command("cmd-3", "dev-2", "success", 2_000),
+        command("cmd-4", "dev-2", "accepted", 3_000),
+    ] {
+        store.create_command(ctx, record).await.expect("create");
+    }
+    store
+}
+
+#[tokio::test]
+async fn list_commands_applies_filters() {
+    let ctx = tenant_ctx("project-1");
+    let store = seeded_store(&ctx).await;
+
+    let items = store
+        .list_commands(
+            &ctx,
+            "project-1",
+            CommandQueryOptions {
+                status: Some("success".to_string()),
+                ..CommandQueryOptions::simple(10)
+            },
+        )
+        .await
+        .expect("list");
+    assert_eq!(items.len(), 2);
+
+    let items = store
+        .list_commands(
+            &ctx,
+            "project-1",
+            CommandQueryOptions {
+                target: Some("dev-2".to_string()),
+                from_ms: Some(2_500),
+                ..CommandQueryOptions::simple(10)
+            },
+        )
+        .await
+        .expect("list");
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].command_id, "cmd-4");
+}
+
+#[tokio::test]
+async fn list_commands_paginates_with_cursor() {
+    let ctx = tenant_ctx("project-1");
+    let store = seeded_store(&ctx).await;
+
+    let first = store
+        .list_commands(&ctx, "project-1", CommandQueryOptions::simple(2))
+        .await
+        .expect("list");
+    let ids: Vec<&str> = first.iter().map(|item| item.command_id.as_str()).collect();
+    assert_eq!(ids, vec!["cmd-4", "cmd-3"]);
+
+    let last = first.last().expect("last");
+    let second = store
+        .list_commands(
+            &ctx,
+            "project-1",
+            CommandQueryOptions {
+                cursor_ts_ms: Some(last.issued_at_ms),
+                cursor_command_id: Some(last.command_id.clone()),
+                ..CommandQueryOptions::simple(2)
+            },
+        )
+        .await
+        .expect("list");
+    let ids: Vec<&str> = second.iter().map(|item| item.command_id.as_str()).collect();
+    assert_eq!(ids, vec!["cmd-2", "cmd-1"]);
+}
+
+#[tokio::test]
+async fn count_commands_by_status_in_window() {
+    let ctx = tenant_ctx("project-1");
+    let store = seeded_store(&ctx).await;
+
+    let counts = store
+        .count_commands_by_status(&ctx, "project-1", Some(2_000), None)
+        .await
+        .expect("count");
+    let pairs: Vec<(&str, i64)> = counts
+        .iter()
+        .map(|item| (item.status.as_str(), item.count))
+        .collect();
+    assert_eq!(pairs, vec![("accepted", 1), ("failed", 1), ("success", 1)]);
+}
+
+#[tokio::test]
+async fn find_command_is_tenant_scoped() {
+    let ctx = tenant_ctx("project-1");
+    let store = seeded_store(&ctx).await;
+
+    let found = store
+        .find_command(&ctx, "project-1", "cmd-2")
+        .await
+        .expect("find");
+    assert_eq!(found.map(|item| item.target), Some("dev-1".to_string()));
+
+    let other_tenant = TenantContext::new(
+        "tenant-2",
+        "user-1",
+        vec![],
+        vec![],
+        Some("project-1".to_string()),
+    );
+    let found = store
+        .find_command(&other_tenant, "project-1", "cmd-2")
+        .await
+        .expect("find");
+    assert!(found.is_none());
+}
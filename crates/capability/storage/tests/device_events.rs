@@ -0,0 +1,108 @@
+use domain::TenantContext;
+use ems_storage::{
+    DeviceEventQuery, DeviceEventRecord, DeviceEventStore, InMemoryDeviceEventStore,
+};
+
+fn project_ctx(tenant_id: &str, project_id: &str) -> TenantContext {
+    TenantContext::new(
+        tenant_id,
+        "system",
+        vec![],
+        vec![],
+        Some(project_id.to_string()),
+    )
+}
+
+fn device_event(
+    tenant_id: &str,
+    device_id: &str,
+    event_id: &str,
+    occurred_at_ms: i64,
+) -> DeviceEventRecord {
+    DeviceEventRecord {
+        tenant_id: tenant_id.to_string(),
+        project_id: "project-1".to_string(),
+        device_id: device_id.to_string(),
+        event_id: event_id.to_string(),
+        event_type: "device.offline".to_string(),
+        occurred_at_ms,
+        data: "{}".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn device_events_are_deduplicated_and_paged_by_cursor() {
+    let store = InMemoryDeviceEventStore::new();
+    let ctx = project_ctx("tenant-1", "project-1");
+    for (event_id, occurred_at_ms) in [("e-1", 1_000), ("e-2", 2_000), ("e-3", 2_000)] {
+        let inserted = store
+            .insert_device_event(
+                &ctx,
+                device_event("tenant-1", "dev-1", event_id, occurred_at_ms),
+            )
+            .await
+            .expect("insert");
+        assert!(inserted);
+    }
+    let duplicate = store
+        .insert_device_event(&ctx, device_event("tenant-1", "dev-1", "e-1", 1_000))
+        .await
+        .expect("insert");
+    assert!(!duplicate);
+    store
+        .insert_device_event(&ctx, device_event("tenant-1", "dev-2", "e-4", 3_000))
+        .await
+        .expect("insert");
+
+    let page = store
+        .list_device_events(
+            &ctx,
+            "project-1",
+            "dev-1",
+            DeviceEventQuery {
+                limit: 2,
+                ..DeviceEventQuery::default()
+            },
+        )
+        .await
+        .expect("list");
+    let ids: Vec<&str> = page.iter().map(|item| item.event_id.as_str()).collect();
+    assert_eq!(ids, vec!["e-3", "e-2"]);
+
+    let next = store
+        .list_device_events(
+            &ctx,
+            "project-1",
+            "dev-1",
+            DeviceEventQuery {
+                cursor_ts_ms: Some(2_000),
+                cursor_event_id: Some("e-2".to_string()),
+                limit: 2,
+                ..DeviceEventQuery::default()
+            },
+        )
+        .await
+        .expect("list");
+    let ids: Vec<&str> = next.iter().map(|item| item.event_id.as_str()).collect();
+    assert_eq!(ids, vec!["e-1"]);
+}
+
+#[tokio::test]
+async fn device_events_are_tenant_scoped() {
+    let store = InMemoryDeviceEventStore::new();
+    let ctx = project_ctx("tenant-1", "project-1");
+    let err = store
+        .insert_device_event(&ctx, device_event("tenant-2", "dev-1", "e-1", 1_000))
+        .await;
+    assert!(err.is_err());
+    store
+        .insert_device_event(&ctx, device_event("tenant-1", "dev-1", "e-1", 1_000))
+        .await
+        .expect("insert");
+    let other = project_ctx("tenant-2", "project-1");
+    let items = store
+        .list_device_events(&other, "project-1", "dev-1", DeviceEventQuery::default())
+        .await
+        .expect("list");
+    assert!(items.is_empty());
+}
@@ -0,0 +1,184 @@
+use domain::TenantContext;
+use ems_storage::{
+    DeviceInstance, DeviceRecord, DeviceStore, DeviceTemplatePoint, DeviceTemplateRecord,
+    DeviceTemplateStore, InMemoryDeviceStore, InMemoryDeviceTemplateStore,
+    InMemoryPointMappingStore, InMemoryPointStore, PointMappingRecord, PointMappingStore,
+    PointRecord, PointStore,
+};
+use std::sync::Arc;
+
+fn tenant_ctx(project_id: &str) -> TenantContext {
+    TenantContext::new(
+        "tenant-1",
+        "user-1",
+        vec![],
+        vec![],
+        Some(project_id.to_string()),
+    )
+}
+
+fn device(device_id: &str) -> DeviceRecord {
+    DeviceRecord {
+        device_id: device_id.to_string(),
+        tenant_id: "tenant-1".to_string(),
+        project_id: "project-1".to_string(),
+        gateway_id: "gw-1".to_string(),
+        name: "Meter".to_string(),
+        model: None,
+        room_id: None,
+        address_config: None,
+        offline_after_seconds: None,
+    }
+}
+
+fn point(point_id: &str, device_id: &str) -> PointRecord {
+    PointRecord {
+        point_id: point_id.to_string(),
+        tenant_id: "tenant-1".to_string(),
+        project_id: "project-1".to_string(),
+        device_id: device_id.to_string(),
+        key: point_id.to_string(),
+        data_type: "f64".to_string(),
+        unit: None,
+        tags: Vec::new(),
+    }
+}
+
+fn mapping(source_id: &str, point_id: &str) -> PointMappingRecord {
+    PointMappingRecord {
+        source_id: source_id.to_string(),
+        tenant_id: "tenant-1".to_string(),
+        project_id: "project-1".to_string(),
+        point_id: point_id.to_string(),
+        source_type: "mqtt".to_string(),
+        address: point_id.to_string(),
+        scale: None,
+        offset: None,
+        protocol_detail: None,
+    }
+}
+
+#[tokio::test]
+async fn device_template_in_memory_crud() {
+    let store = InMemoryDeviceTemplateStore::new(
+        Arc::new(InMemoryDeviceStore::new()),
+        Arc::new(InMemoryPointStore::new()),
+        Arc::new(InMemoryPointMappingStore::new()),
+    );
+    let ctx = tenant_ctx("project-1");
+    let record = DeviceTemplateRecord {
+        template_id: "tpl-1".to_string(),
+        tenant_id: "tenant-1".to_string(),
+        project_id: "project-1".to_string(),
+        name: "Meter".to_string(),
+        model: Some("m1".to_string()),
+        points: vec![DeviceTemplatePoint {
+            key: "voltage".to_string(),
+            data_type: "f64".to_string(),
+            unit: Some("V".to_string()),
+            source_type: Some("mqtt".to_string()),
+            address: Some("voltage".to_string()),
+            scale: Some(0.1),
+            offset: None,
+            protocol_detail: None,
+        }],
+    };
+    store
+        .create_device_template(&ctx, record)
+        .await
+        .expect("create");
+
+    let list = store
+        .list_device_templates(&ctx, "project-1")
+        .await
+        .expect("list");
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].points.len(), 1);
+
+    let deleted = store
+        .delete_device_template(&ctx, "project-1", "tpl-1")
+        .await
+        .expect("delete");
+    assert!(deleted);
+    let got = store
+        .find_device_template(&ctx, "project-1", "tpl-1")
+        .await
+        .expect("find");
+    assert!(got.is_none());
+}
+
+#[tokio::test]
+async fn instantiate_device_writes_points_and_mappings() {
+    let device_store = Arc::new(InMemoryDeviceStore::new());
+    let point_store = Arc::new(InMemoryPointStore::new());
+    let mapping_store = Arc::new(InMemoryPointMappingStore::new());
+    let store = InMemoryDeviceTemplateStore::new(
+        device_store.clone(),
+        point_store.clone(),
+        mapping_store.clone(),
+    );
+    let ctx = tenant_ctx("project-1");
+    let instance = DeviceInstance {
+        device: device("dev-1"),
+        points: vec![point("p-1", "dev-1"), point("p-2", "dev-1")],
+        mappings: vec![mapping("s-1", "p-1")],
+    };
+    let created = store
+        .instantiate_device(&ctx, instance)
+        .await
+        .expect("instantiate");
+    assert_eq!(created.points.len(), 2);
+    assert_eq!(created.mappings.len(), 1);
+
+    let devices = device_store
+        .list_devices(&ctx, "project-1")
+        .await
+        .expect("devices");
+    assert_eq!(devices.len(), 1);
+    let points = point_store
+        .list_points(&ctx, "project-1")
+        .await
+        .expect("points");
+    assert_eq!(points.len(), 2);
+    let mappings = mapping_store
+        .list_point_mappings(&ctx, "project-1")
+        .await
+        .expect("mappings");
+    assert_eq!(mappings.len(), 1);
+}
+
+#[tokio::test]
+async fn instantiate_device_rolls_back_on_failure() {
+    let device_store = Arc::new(InMemoryDeviceStore::new());
+    let point_store = Arc::new(InMemoryPointStore::new());
+    let mapping_store = Arc::new(InMemoryPointMappingStore::new());
+    let store = InMemoryDeviceTemplateStore::new(
+        device_store.clone(),
+        point_store.clone(),
+        mapping_store.clone(),
+    );
+    let ctx = tenant_ctx("project-1");
+    let instance = DeviceInstance {
+        device: device("dev-1"),
+        points: vec![point("p-1", "dev-1")],
+        mappings: vec![mapping("s-1", "p-1"), mapping("s-1", "p-1")],
+    };
+    let result = store.instantiate_device(&ctx, instance).await;
+    assert!(result.is_err());
+
+    let devices = device_store
+        .list_devices(&ctx, "project-1")
+        .await
+        .expect("devices");
+    assert!(devices.is_empty());
+    let points = point_store
+        .list_points(&ctx, "project-1")
+        .await
+        .expect("points");
+    assert!(points.is_empty());
+    let mappings = mapping_store
+        .list_point_mappings(&ctx, "project-1")
+        .await
+        .expect("mappings");
+    assert!(mappings.is_empty());
+}
@@ -0,0 +1,80 @@
+use domain::TenantContext;
+use ems_storage::{FeatureFlagRecord, FeatureFlagStore, InMemoryFeatureFlagStore};
+
+fn tenant_ctx(tenant_id: &str) -> TenantContext {
+    TenantContext::new(tenant_id, "user-1", vec![], vec![], None)
+}
+
+fn flag(tenant_id: &str, flag_key: &str, enabled: bool) -> FeatureFlagRecord {
+    FeatureFlagRecord {
+        tenant_id: tenant_id.to_string(),
+        flag_key: flag_key.to_string(),
+        enabled,
+        variant: None,
+        updated_at_ms: 0,
+    }
+}
+
+#[tokio::test]
+async fn feature_flags_are_isolated_per_tenant() {
+    let store = InMemoryFeatureFlagStore::new();
+    let ctx = tenant_ctx("tenant-1");
+    let other = tenant_ctx("tenant-2");
+
+    store
+        .upsert_feature_flag(&ctx, flag("tenant-1", "control", false))
+        .await
+        .expect("upsert");
+    let mut beta = flag("tenant-1", "beta.dashboard", true);
+    beta.variant = Some("v2".to_string());
+    store.upsert_feature_flag(&ctx, beta).await.expect("upsert");
+
+    let flags = store.list_feature_flags(&ctx).await.expect("list");
+    let keys: Vec<_> = flags.iter().map(|flag| flag.flag_key.as_str()).collect();
+    assert_eq!(keys, vec!["beta.dashboard", "control"]);
+    assert!(
+        store
+            .list_feature_flags(&other)
+            .await
+            .expect("list")
+            .is_empty()
+    );
+    assert!(
+        store
+            .get_feature_flag(&other, "control")
+            .await
+            .expect("get")
+            .is_none()
+    );
+
+    // 覆盖写入
+    store
+        .upsert_feature_flag(&ctx, flag("tenant-1", "control", true))
+        .await
+        .expect("upsert");
+    let control = store
+        .get_feature_flag(&ctx, "control")
+        .await
+        .expect("get")
+        .expect("exists");
+    assert!(control.enabled);
+
+    assert!(
+        store
+            .delete_feature_flag(&ctx, "control")
+            .await
+            .expect("delete")
+    );
+    assert!(
+        !store
+            .delete_feature_flag(&ctx, "control")
+            .await
+            .expect("delete")
+    );
+
+    let err = store
+        .upsert_feature_flag(&ctx, flag("tenant-2", "control", false))
+        .await
+        .expect_err("tenant mismatch");
+    assert_eq!(err.kind(), ems_storage::StorageErrorKind::Forbidden);
+}
@@ -0,0 +1,86 @@
+use domain::TenantContext;
+use ems_storage::{GatewayConfigRecord, GatewayConfigStore, InMemoryGatewayConfigStore};
+
+fn tenant_ctx(project_id: &str) -> TenantContext {
+    TenantContext::new(
+        "tenant-1",
+        "user-1",
+        vec![],
+        vec![],
+        Some(project_id.to_string()),
+    )
+}
+
+fn config(gateway_id: &str) -> GatewayConfigRecord {
+    GatewayConfigRecord {
+        tenant_id: "tenant-1".to_string(),
+        project_id: "project-1".to_string(),
+        gateway_id: gateway_id.to_string(),
+        version: 0,
+        document: "{}".to_string(),
+        status: "pending".to_string(),
+        message: None,
+        pushed_by: "user-1".to_string(),
+        pushed_at_ms: 1,
+        updated_at_ms: 1,
+    }
+}
+
+#[tokio::test]
+async fn gateway_config_versions_increase_per_gateway() {
+    let store = InMemoryGatewayConfigStore::new();
+    let ctx = tenant_ctx("project-1");
+    let first = store
+        .create_gateway_config(&ctx, config("gw-1"))
+        .await
+        .expect("create");
+    let second = store
+        .create_gateway_config(&ctx, config("gw-1"))
+        .await
+        .expect("create");
+    let other = store
+        .create_gateway_config(&ctx, config("gw-2"))
+        .await
+        .expect("create");
+    assert_eq!(first.version, 1);
+    assert_eq!(second.version, 2);
+    assert_eq!(other.version, 1);
+
+    let list = store
+        .list_gateway_configs(&ctx, "project-1", "gw-1", 10)
+        .await
+        .expect("list");
+    assert_eq!(list.len(), 2);
+    assert_eq!(list[0].version, 2);
+}
+
+#[tokio::test]
+async fn gateway_config_status_updates_by_version() {
+    let store = InMemoryGatewayConfigStore::new();
+    let ctx = tenant_ctx("project-1");
+    store
+        .create_gateway_config(&ctx, config("gw-1"))
+        .await
+        .expect("create");
+    let updated = store
+        .update_gateway_config_status(
+            &ctx,
+            "project-1",
+            "gw-1",
+            1,
+            "applied",
+            Some("ok".to_string()),
+            2,
+        )
+        .await
+        .expect("update");
+    let updated = updated.expect("record");
+    assert_eq!(updated.status, "applied");
+    assert_eq!(updated.updated_at_ms, 2);
+
+    let missing = store
+        .update_gateway_config_status(&ctx, "project-1", "gw-1", 9, "applied", None, 3)
+        .await
+        .expect("update");
+    assert!(missing.is_none());
+}
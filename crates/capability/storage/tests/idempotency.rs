@@ -0,0 +1,105 @@
+use domain::TenantContext;
+use ems_storage::{IdempotencyRecord, IdempotencyStore, InMemoryIdempotencyStore};
+
+fn tenant_ctx(tenant_id: &str) -> TenantContext {
+    TenantContext::new(tenant_id, "user-1", vec![], vec![], None)
+}
+
+fn record(tenant_id: &str, key: &str, created_at_ms: i64) -> IdempotencyRecord {
+    IdempotencyRecord {
+        tenant_id: tenant_id.to_string(),
+        idempotency_key: key.to_string(),
+        request_hash: "hash-1".to_string(),
+        status_code: None,
+        response_headers: None,
+        response_body: None,
+        created_at_ms,
+        expires_at_ms: created_at_ms + 1_000,
+    }
+}
+
+#[tokio::test]
+async fn idempotency_key_reserved_once_per_tenant() {
+    let store = InMemoryIdempotencyStore::new();
+    let ctx = tenant_ctx("tenant-1");
+
+    let reserved = store
+        .reserve_idempotency_key(&ctx, record("tenant-1", "key-1", 0))
+        .await
+        .expect("reserve");
+    assert!(reserved.is_none());
+
+    // 处理中：返回未完成的已有记录
+    let existing = store
+        .reserve_idempotency_key(&ctx, record("tenant-1", "key-1", 10))
+        .await
+        .expect("reserve")
+        .expect("existing");
+    assert!(existing.status_code.is_none());
+
+    store
+        .complete_idempotency_key(
+            &ctx,
+            "key-1",
+            201,
+            r#"[["location","/api/v1/projects/p-1"]]"#.to_string(),
+            "{\"success\":true}".to_string(),
+        )
+        .await
+        .expect("complete");
+    let existing = store
+        .reserve_idempotency_key(&ctx, record("tenant-1", "key-1", 20))
+        .await
+        .expect("reserve")
+        .expect("existing");
+    assert_eq!(existing.status_code, Some(201));
+    assert_eq!(
+        existing.response_headers.as_deref(),
+        Some(r#"[["location","/api/v1/projects/p-1"]]"#)
+    );
+    assert_eq!(
+        existing.response_body.as_deref(),
+        Some("{\"success\":true}")
+    );
+
+    // 其他租户的同名键互不影响
+    let other = tenant_ctx("tenant-2");
+    let reserved = store
+        .reserve_idempotency_key(&other, record("tenant-2", "key-1", 20))
+        .await
+        .expect("reserve");
+    assert!(reserved.is_none());
+}
+
+#[tokio::test]
+async fn idempotency_key_expired_or_released_can_be_reused() {
+    let store = InMemoryIdempotencyStore::new();
+    let ctx = tenant_ctx("tenant-1");
+
+    store
+        .reserve_idempotency_key(&ctx, record("tenant-1", "key-1", 0))
+        .await
+        .expect("reserve");
+    // 超过有效期后重新预占
+    let reserved = store
+        .reserve_idempotency_key(&ctx, record("tenant-1", "key-1", 1_000))
+        .await
+        .expect("reserve");
+    assert!(reserved.is_none());
+
+    store
+        .release_idempotency_key(&ctx, "key-1")
+        .await
+        .expect("release");
+    let reserved = store
+        .reserve_idempotency_key(&ctx, record("tenant-1", "key-1", 1_010))
+        .await
+        .expect("reserve");
+    assert!(reserved.is_none());
+
+    let err = store
+        .reserve_idempotency_key(&ctx, record("tenant-2", "key-2", 0))
+        .await
+        .expect_err("tenant mismatch");
+    assert_eq!(err.to_string(), "tenant mismatch");
+}
@@ -1,8 +1,8 @@
 use domain::TenantContext;
 use ems_storage::{
-    DeviceRecord, DeviceStore, GatewayRecord, GatewayStore, InMemoryDeviceStore,
+    DeviceRecord, DeviceStore, GatewayRecord, GatewayStore, GatewayUpdate, InMemoryDeviceStore,
     InMemoryGatewayStore, InMemoryPointMappingStore, InMemoryPointStore, PointMappingRecord,
-    PointMappingStore, PointRecord, PointStore,
+    PointMappingStore, PointMappingUpdate, PointRecord, PointStore, StorageErrorKind,
 };
 
 fn tenant_ctx(project_id: &str) -> TenantContext {
@@ -41,6 +41,190 @@ async fn gateway_in_memory_crud() {
     assert!(got.is_some());
 }
 
+#[tokio::test]
+async fn gateway_collection_version_tracks_changes() {
+    let store = InMemoryGatewayStore::new();
+    let ctx = tenant_ctx("project-1");
+    let empty = store
+        .gateways_version(&ctx, "project-1")
+        .await
+        .expect("version");
+    assert_eq!(empty.count, 0);
+    let record = GatewayRecord {
+        gateway_id: "gw-1".to_string(),
+        tenant_id: "tenant-1".to_string(),
+        project_id: "project-1".to_string(),
+        name: "Gateway 1".to_string(),
+        status: "offline".to_string(),
+        protocol_type: "mqtt".to_string(),
+        protocol_config: None,
+    };
+    store.create_gateway(&ctx, record).await.expect("create");
+    let created = store
+        .gateways_version(&ctx, "project-1")
+        .await
+        .expect("version");
+    assert_eq!(created.count, 1);
+    // 读取不改变版本
+    store.list_gateways(&ctx, "project-1").await.expect("list");
+    assert_eq!(
+        store
+            .gateways_version(&ctx, "project-1")
+            .await
+            .expect("version"),
+        created
+    );
+
+    // 更新：条数不变，变更标记前进
+    store
+        .update_gateway(
+            &ctx,
+            "project-1",
+            "gw-1",
+            GatewayUpdate {
+                name: Some("Gateway 1b".to_string()),
+                status: None,
+                protocol_type: None,
+                protocol_config: None,
+            },
+        )
+        .await
+        .expect("update");
+    let updated = store
+        .gateways_version(&ctx, "project-1")
+        .await
+        .expect("version");
+    assert_eq!(updated.count, 1);
+    assert_ne!(updated, created);
+
+    store
+        .delete_gateway(&ctx, "project-1", "gw-1")
+        .await
+        .expect("delete");
+    let deleted = store
+        .gateways_version(&ctx, "project-1")
+        .await
+        .expect("version");
+    assert_eq!(deleted.count, 0);
+    assert_ne!(deleted, empty);
+}
+
+#[tokio::test]
+async fn gateway_token_hash_lookup_and_revoke() {
+    let store = InMemoryGatewayStore::new();
+    let ctx = tenant_ctx("project-1");
+    let record = GatewayRecord {
+        gateway_id: "gw-1".to_string(),
+        tenant_id: "tenant-1".to_string(),
+        project_id: "project-1".to_string(),
+        name: "Gateway 1".to_string(),
+        status: "offline".to_string(),
+        protocol_type: "mqtt".to_string(),
+        protocol_config: None,
+    };
+    store.create_gateway(&ctx, record).await.expect("create");
+
+    let missing = store
+        .set_gateway_token_hash(&ctx, "project-1", "gw-404", Some("hash-1"))
+        .await
+        .expect("set");
+    assert!(!missing);
+    let set = store
+        .set_gateway_token_hash(&ctx, "project-1", "gw-1", Some("hash-1"))
+        .await
+        .expect("set");
+    assert!(set);
+    let found = store
+        .find_gateway_by_token_hash("hash-1")
+        .await
+        .expect("find")
+        .expect("gateway");
+    assert_eq!(found.gateway_id, "gw-1");
+    assert_eq!(found.tenant_id, "tenant-1");
+
+    // 轮换后旧摘要失效
+    store
+        .set_gateway_token_hash(&ctx, "project-1", "gw-1", Some("hash-2"))
+        .await
+        .expect("rotate");
+    assert!(
+        store
+            .find_gateway_by_token_hash("hash-1")
+            .await
+            .expect("find")
+            .is_none()
+    );
+
+    store
+        .set_gateway_token_hash(&ctx, "project-1", "gw-1", None)
+        .await
+        .expect("revoke");
+    assert!(
+        store
+            .find_gateway_by_token_hash("hash-2")
+            .await
+            .expect("find")
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn gateway_command_format_set_and_clear() {
+    let store = InMemoryGatewayStore::new();
+    let ctx = tenant_ctx("project-1");
+    let record = GatewayRecord {
+        gateway_id: "gw-1".to_string(),
+        tenant_id: "tenant-1".to_string(),
+        project_id: "project-1".to_string(),
+        name: "Gateway 1".to_string(),
+        status: "offline".to_string(),
+        protocol_type: "mqtt".to_string(),
+        protocol_config: None,
+    };
+    store.create_gateway(&ctx, record).await.expect("create");
+
+    let missing = store
+        .set_gateway_command_format(&ctx, "project-1", "gw-404", Some(r#"{"format":"raw"}"#))
+        .await
+        .expect("set");
+    assert!(!missing);
+    let before = store
+        .gateways_version(&ctx, "project-1")
+        .await
+        .expect("version");
+    let set = store
+        .set_gateway_command_format(&ctx, "project-1", "gw-1", Some(r#"{"format":"raw"}"#))
+        .await
+        .expect("set");
+    assert!(set);
+    // 与其他网关变更一致，刷新集合版本（列表 ETag）
+    let after = store
+        .gateways_version(&ctx, "project-1")
+        .await
+        .expect("version");
+    assert_ne!(before.revision, after.revision);
+    assert_eq!(
+        store
+            .find_gateway_command_format(&ctx, "project-1", "gw-1")
+            .await
+            .expect("find")
+            .as_deref(),
+        Some(r#"{"format":"raw"}"#)
+    );
+
+    store
+        .set_gateway_command_format(&ctx, "project-1", "gw-1", None)
+        .await
+        .expect("clear");
+    assert!(
+        store
+            .find_gateway_command_format(&ctx, "project-1", "gw-1")
+            .await
+            .expect("find")
+            .is_none()
+    );
+}
+
 #[tokio::test]
 async fn device_in_memory_crud() {
     let store = InMemoryDeviceStore::new();
@@ -54,6 +238,7 @@ async fn device_in_memory_crud() {
         model: Some("m1".to_string()),
         room_id: None,
         address_config: None,
+        offline_after_seconds: None,
     };
     let created = store.create_device(&ctx, record).await.expect("create");
     assert_eq!(created.device_id, "dev-1");
@@ -68,6 +253,60 @@ async fn device_in_memory_crud() {
     assert!(got.is_some());
 }
 
+#[tokio::test]
+async fn list_all_spans_tenants() {
+    let gateway_store = InMemoryGatewayStore::new();
+    let device_store = InMemoryDeviceStore::new();
+    for tenant_id in ["tenant-2", "tenant-1"] {
+        let ctx = TenantContext::new(
+            tenant_id,
+            "user-1",
+            vec![],
+            vec![],
+            Some("project-1".to_string()),
+        );
+        gateway_store
+            .create_gateway(
+                &ctx,
+                GatewayRecord {
+                    gateway_id: format!("gw-{tenant_id}"),
+                    tenant_id: tenant_id.to_string(),
+                    project_id: "project-1".to_string(),
+                    name: "Gateway".to_string(),
+                    status: "offline".to_string(),
+                    protocol_type: "mqtt".to_string(),
+                    protocol_config: None,
+                },
+            )
+            .await
+            .expect("gateway");
+        device_store
+            .create_device(
+                &ctx,
+                DeviceRecord {
+                    device_id: format!("dev-{tenant_id}"),
+                    tenant_id: tenant_id.to_string(),
+                    project_id: "project-1".to_string(),
+                    gateway_id: format!("gw-{tenant_id}"),
+                    name: "Device".to_string(),
+                    model: None,
+                    room_id: None,
+                    address_config: None,
+                    offline_after_seconds: Some(60),
+                },
+            )
+            .await
+            .expect("device");
+    }
+
+    let gateways = gateway_store.list_all_gateways().await.expect("gateways");
+    assert_eq!(gateways.len(), 2);
+    let devices = device_store.list_all_devices().await.expect("devices");
+    let ids: Vec<&str> = devices.iter().map(|item| item.device_id.as_str()).collect();
+    assert_eq!(ids, vec!["dev-tenant-1", "dev-tenant-2"]);
+    assert_eq!(devices[0].offline_after_seconds, Some(60));
+}
+
 #[tokio::test]
 async fn point_in_memory_crud() {
     let store = InMemoryPointStore::new();
@@ -80,6 +319,7 @@ async fn point_in_memory_crud() {
         key: "temp".to_string(),
         data_type: "float".to_string(),
         unit: Some("C".to_string()),
+        tags: vec!["hvac".to_string()],
     };
     let created = store.create_point(&ctx, record).await.expect("create");
     assert_eq!(created.point_id, "pt-1");
@@ -127,3 +367,55 @@ async fn point_mapping_in_memory_crud() {
         .expect("find");
     assert!(got.is_some());
 }
+
+#[tokio::test]
+async fn point_mapping_rejects_duplicate_address() {
+    let store = InMemoryPointMappingStore::new();
+    let ctx = tenant_ctx("project-1");
+    let mapping = |source_id: &str, address: &str| PointMappingRecord {
+        source_id: source_id.to_string(),
+        tenant_id: "tenant-1".to_string(),
+        project_id: "project-1".to_string(),
+        point_id: format!("pt-{source_id}"),
+        source_type: "mqtt".to_string(),
+        address: address.to_string(),
+        scale: None,
+        offset: None,
+        protocol_detail: None,
+    };
+    store
+        .create_point_mapping(&ctx, mapping("src-1", "topic/1"))
+        .await
+        .expect("create");
+    store
+        .create_point_mapping(&ctx, mapping("src-2", "topic/2"))
+        .await
+        .expect("create");
+
+    let err = store
+        .create_point_mapping(&ctx, mapping("src-3", "topic/1"))
+        .await
+        .expect_err("duplicate address");
+    assert_eq!(err.kind(), StorageErrorKind::Conflict);
+
+    let update = |address: &str| PointMappingUpdate {
+        source_type: None,
+        address: Some(address.to_string()),
+        scale: None,
+        offset: None,
+        protocol_detail: None,
+    };
+    let err = store
+        .update_point_mapping(&ctx, "project-1", "src-2", update("topic/1"))
+        .await
+        .expect_err("duplicate address");
+    assert_eq!(err.kind(), StorageErrorKind::Conflict);
+
+    // 保持自身地址不算冲突
+    let updated = store
+        .update_point_mapping(&ctx, "project-1", "src-2", update("topic/2"))
+        .await
+        .expect("update")
+        .expect("mapping");
+    assert_eq!(updated.address, "topic/2");
+}
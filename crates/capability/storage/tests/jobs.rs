@@ -0,0 +1,196 @@
+use domain::TenantContext;
+use ems_storage::{InMemoryJobStore, JobFinish, JobQuery, JobRecord, JobStore};
+
+fn tenant_ctx(tenant_id: &str, project_scope: Option<&str>) -> TenantContext {
+    TenantContext::new(
+        tenant_id,
+        "system",
+        vec![],
+        vec![],
+        project_scope.map(str::to_string),
+    )
+}
+
+fn job(job_id: &str, project_id: Option<&str>, created_at_ms: i64) -> JobRecord {
+    JobRecord {
+        job_id: job_id.to_string(),
+        tenant_id: "tenant-1".to_string(),
+        project_id: project_id.map(str::to_string),
+        kind: "report.power_quality".to_string(),
+        status: "queued".to_string(),
+        params: "{}".to_string(),
+        progress: 0,
+        result: None,
+        error: None,
+        cancel_requested: false,
+        created_by: "user-1".to_string(),
+        created_at_ms,
+        started_at_ms: None,
+        finished_at_ms: None,
+        updated_at_ms: created_at_ms,
+    }
+}
+
+#[tokio::test]
+async fn job_lifecycle_transitions_only_from_unfinished_states() {
+    let store = InMemoryJobStore::new();
+    let ctx = tenant_ctx("tenant-1", None);
+    store
+        .create_job(&ctx, job("job-1", Some("project-1"), 1_000))
+        .await
+        .expect("create");
+
+    let started = store.start_job(&ctx, "job-1", 2_000).await.expect("start");
+    assert_eq!(started.map(|job| job.status), Some("running".to_string()));
+    assert!(
+        store
+            .start_job(&ctx, "job-1", 2_500)
+            .await
+            .expect("start")
+            .is_none()
+    );
+
+    let updated = store
+        .update_job_progress(&ctx, "job-1", 150, 3_000)
+        .await
+        .expect("progress")
+        .expect("running job");
+    assert_eq!(updated.progress, 100);
+
+    let finished = store
+        .finish_job(
+            &ctx,
+            "job-1",
+            JobFinish {
+                status: "succeeded".to_string(),
+                result: Some("{}".to_string()),
+                error: None,
+                finished_at_ms: 4_000,
+            },
+        )
+        .await
+        .expect("finish")
+        .expect("running job");
+    assert_eq!(finished.finished_at_ms, Some(4_000));
+
+    // 已结束的任务不再变化
+    let cancelled = store
+        .request_job_cancel(&ctx, "job-1", 5_000)
+        .await
+        .expect("cancel")
+        .expect("job");
+    assert_eq!(cancelled.status, "succeeded");
+    assert!(!cancelled.cancel_requested);
+    assert!(
+        store
+            .update_job_progress(&ctx, "job-1", 10, 6_000)
+            .await
+            .expect("progress")
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn queued_job_cancel_and_interrupted_recovery() {
+    let store = InMemoryJobStore::new();
+    let ctx = tenant_ctx("tenant-1", None);
+    for (job_id, created_at_ms) in [("job-1", 1_000), ("job-2", 2_000), ("job-3", 3_000)] {
+        store
+            .create_job(&ctx, job(job_id, Some("project-1"), created_at_ms))
+            .await
+            .expect("create");
+    }
+
+    let cancelled = store
+        .request_job_cancel(&ctx, "job-1", 4_000)
+        .await
+        .expect("cancel")
+        .expect("job");
+    assert_eq!(cancelled.status, "cancelled");
+    assert!(
+        store
+            .start_job(&ctx, "job-1", 4_500)
+            .await
+            .expect("start")
+            .is_none()
+    );
+
+    store.start_job(&ctx, "job-2", 5_000).await.expect("start");
+    let running = store
+        .request_job_cancel(&ctx, "job-2", 5_500)
+        .await
+        .expect("cancel")
+        .expect("job");
+    assert_eq!(running.status, "running");
+    assert!(running.cancel_requested);
+
+    let count = store
+        .fail_unfinished_jobs("interrupted", 6_000)
+        .await
+        .expect("recover");
+    assert_eq!(count, 2);
+    let failed = store
+        .list_jobs(
+            &ctx,
+            JobQuery {
+                status: Some("failed".to_string()),
+                limit: 10,
+                ..JobQuery::default()
+            },
+        )
+        .await
+        .expect("list");
+    let ids: Vec<&str> = failed.iter().map(|job| job.job_id.as_str()).collect();
+    assert_eq!(ids, vec!["job-3", "job-2"]);
+}
+
+#[tokio::test]
+async fn jobs_are_tenant_and_project_scoped() {
+    let store = InMemoryJobStore::new();
+    let ctx = tenant_ctx("tenant-1", None);
+    store
+        .create_job(&ctx, job("job-1", Some("project-1"), 1_000))
+        .await
+        .expect("create");
+    store
+        .create_job(&ctx, job("job-2", None, 2_000))
+        .await
+        .expect("create");
+
+    let other_tenant = tenant_ctx("tenant-2", None);
+    assert!(
+        store
+            .find_job(&other_tenant, "job-1")
+            .await
+            .expect("find")
+            .is_none()
+    );
+    assert!(
+        store
+            .create_job(&other_tenant, job("job-3", None, 3_000))
+            .await
+            .is_err()
+    );
+
+    // 项目作用域内只能看到该项目的任务
+    let scoped = tenant_ctx("tenant-1", Some("project-1"));
+    let items = store
+        .list_jobs(
+            &scoped,
+            JobQuery {
+                limit: 10,
+                ..JobQuery::default()
+            },
+        )
+        .await
+        .expect("list");
+    let ids: Vec<&str> = items.iter().map(|job| job.job_id.as_str()).collect();
+    assert_eq!(ids, vec!["job-1"]);
+    assert!(
+        store
+            .find_job(&scoped, "job-2")
+            .await
+            .expect("find")
+            .is_none()
+    );
+}
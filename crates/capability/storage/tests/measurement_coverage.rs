@@ -0,0 +1,95 @@
+use domain::{PointValue, PointValueData, TenantContext};
+use ems_storage::{
+    InMemoryMeasurementStore, MeasurementCoverageOptions, MeasurementGap, MeasurementStore,
+};
+
+fn tenant_ctx(project_id: &str) -> TenantContext {
+    TenantContext::new(
+        "tenant-1",
+        "user-1",
+        vec![],
+        vec![],
+        Some(project_id.to_string()),
+    )
+}
+
+fn sample_value(point_id: &str, ts_ms: i64) -> PointValue {
+    PointValue {
+        tenant_id: "tenant-1".to_string(),
+        project_id: "project-1".to_string(),
+        point_id: point_id.to_string(),
+        ts_ms,
+        value: PointValueData::F64(1.0),
+        quality: None,
+    }
+}
+
+fn options(from_ms: i64, to_ms: i64, expected_interval_ms: i64) -> MeasurementCoverageOptions {
+    MeasurementCoverageOptions {
+        from_ms,
+        to_ms,
+        expected_interval_ms,
+        gap_limit: 100,
+    }
+}
+
+#[tokio::test]
+async fn coverage_reports_gaps_and_completeness() {
+    let store = InMemoryMeasurementStore::new();
+    let ctx = tenant_ctx("project-1");
+    let values = vec![
+        sample_value("point-1", 0),
+        sample_value("point-1", 500),
+        sample_value("point-1", 1_000),
+        sample_value("point-1", 4_200),
+        sample_value("point-1", 9_999),
+        sample_value("point-1", 10_000),
+        sample_value("point-2", 2_000),
+    ];
+    store
+        .write_measurements(&ctx, &values)
+        .await
+        .expect("write");
+
+    let coverage = store
+        .measurement_coverage(&ctx, "project-1", "point-1", options(0, 10_000, 1_000))
+        .await
+        .expect("coverage");
+    assert_eq!(coverage.expected_buckets, 10);
+    assert_eq!(coverage.covered_buckets, 4);
+    assert_eq!(coverage.completeness_pct, 40.0);
+    assert_eq!(
+        coverage.gaps,
+        vec![
+            MeasurementGap {
+                from_ms: 2_000,
+                to_ms: 4_000,
+            },
+            MeasurementGap {
+                from_ms: 5_000,
+                to_ms: 9_000,
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn coverage_without_data_is_one_gap() {
+    let store = InMemoryMeasurementStore::new();
+    let ctx = tenant_ctx("project-1");
+
+    let coverage = store
+        .measurement_coverage(&ctx, "project-1", "point-1", options(0, 2_500, 1_000))
+        .await
+        .expect("coverage");
+    assert_eq!(coverage.expected_buckets, 3);
+    assert_eq!(coverage.covered_buckets, 0);
+    assert_eq!(coverage.completeness_pct, 0.0);
+    assert_eq!(
+        coverage.gaps,
+        vec![MeasurementGap {
+            from_ms: 0,
+            to_ms: 2_500,
+        }]
+    );
+}
@@ -1,7 +1,9 @@
+use chrono_tz::Tz;
 use domain::{PointValue, PointValueData, TenantContext};
 use ems_storage::{
-    InMemoryMeasurementStore, MeasurementAggFn, MeasurementAggregation, MeasurementStore,
-    MeasurementsQueryOptions, TimeOrder,
+    CalendarBucket, InMemoryMeasurementStore, MeasurementAggFn, MeasurementAggregation,
+    MeasurementDedupStrategy, MeasurementStore, MeasurementsQueryOptions, TimeOrder,
+    measurement_quality_rank,
 };
 
 fn sample_value(
@@ -193,6 +195,7 @@ async fn measurements_support_cursor_and_order() {
                 order: TimeOrder::Asc,
                 limit: 10,
                 aggregation: None,
+                timezone: Tz::UTC,
             },
         )
         .await
@@ -211,6 +214,7 @@ async fn measurements_support_cursor_and_order() {
                 order: TimeOrder::Desc,
                 limit: 10,
                 aggregation: None,
+                timezone: Tz::UTC,
             },
         )
         .await
@@ -274,7 +278,9 @@ async fn measurements_support_aggregation() {
                 aggregation: Some(MeasurementAggregation {
                     bucket_ms: 1000,
                     func: MeasurementAggFn::Avg,
+                    calendar: None,
                 }),
+                timezone: Tz::UTC,
             },
         )
         .await
@@ -282,3 +288,198 @@ async fn measurements_support_aggregation() {
     assert_eq!(items.iter().map(|i| i.ts_ms).collect::<Vec<_>>(), vec![1000, 2000]);
     assert_eq!(items[0].value.parse::<f64>().ok(), Some(2.0));
 }
+
+#[tokio::test]
+async fn measurements_calendar_buckets_follow_timezone() {
+    let store = InMemoryMeasurementStore::new();
+    let ctx = TenantContext::new(
+        "tenant-1",
+        "user-1",
+        vec![],
+        vec![],
+        Some("project-1".to_string()),
+    );
+    // 2024-01-01T15:00Z（上海 23:00）与 2024-01-01T17:00Z（上海次日 01:00）
+    let values = vec![
+        sample_value(
+            "tenant-1",
+            "project-1",
+            "point-1",
+            1_704_121_200_000,
+            PointValueData::F64(1.0),
+        ),
+        sample_value(
+            "tenant-1",
+            "project-1",
+            "point-1",
+            1_704_128_400_000,
+            PointValueData::F64(3.0),
+        ),
+    ];
+    store
+        .write_measurements(&ctx, &values)
+        .await
+        .expect("write measurements");
+
+    let daily = |timezone: Tz| MeasurementsQueryOptions {
+        from_ms: None,
+        to_ms: None,
+        cursor_ts_ms: None,
+        order: TimeOrder::Asc,
+        limit: 10,
+        aggregation: Some(MeasurementAggregation {
+            bucket_ms: 0,
+            func: MeasurementAggFn::Sum,
+            calendar: Some(CalendarBucket::Day),
+        }),
+        timezone,
+    };
+
+    // UTC：同一天
+    let items = store
+        .query_measurements(&ctx, "project-1", "point-1", daily(Tz::UTC))
+        .await
+        .expect("query measurements");
+    assert_eq!(
+        items.iter().map(|i| i.ts_ms).collect::<Vec<_>>(),
+        vec![1_704_067_200_000]
+    );
+    assert_eq!(items[0].value.parse::<f64>().ok(), Some(4.0));
+
+    // 上海：按本地午夜切分，桶起始为本地 00:00（UTC 前一日 16:00）
+    let items = store
+        .query_measurements(&ctx, "project-1", "point-1", daily(Tz::Asia__Shanghai))
+        .await
+        .expect("query measurements");
+    assert_eq!(
+        items.iter().map(|i| i.ts_ms).collect::<Vec<_>>(),
+        vec![1_704_038_400_000, 1_704_124_800_000]
+    );
+
+    // 月桶：2024-01-31T20:00Z 在上海已是 2 月
+    assert_eq!(
+        CalendarBucket::Month.bucket_start_ms(1_706_731_200_000, Tz::Asia__Shanghai),
+        1_706_716_800_000
+    );
+    // 夏令时切换日（纽约 2024-03-10）只有 23 小时
+    let day_start = CalendarBucket::Day.bucket_start_ms(1_710_072_000_000, Tz::America__New_York);
+    let next_day_start =
+        CalendarBucket::Day.bucket_start_ms(1_710_158_400_000, Tz::America__New_York);
+    assert_eq!(day_start, 1_710_046_800_000);
+    assert_eq!(next_day_start - day_start, 23 * 3_600_000);
+    assert_eq!(CalendarBucket::parse("1MO"), Some(CalendarBucket::Month));
+    assert_eq!(CalendarBucket::parse("2d"), None);
+}
+
+#[test]
+fn measurement_dedup_strategy_parses_config_values() {
+    for strategy in [
+        MeasurementDedupStrategy::Ignore,
+        MeasurementDedupStrategy::Overwrite,
+        MeasurementDedupStrategy::KeepBestQuality,
+    ] {
+        assert_eq!(
+            MeasurementDedupStrategy::parse(strategy.as_str()),
+            Some(strategy)
+        );
+    }
+    assert_eq!(
+        MeasurementDedupStrategy::default(),
+        MeasurementDedupStrategy::Ignore
+    );
+    assert_eq!(MeasurementDedupStrategy::parse("latest"), None);
+}
+
+async fn stored_values(
+    store: &InMemoryMeasurementStore,
+    ctx: &TenantContext,
+) -> Vec<(String, Option<String>)> {
+    store
+        .query_measurements(
+            ctx,
+            "project-1",
+            "point-1",
+            MeasurementsQueryOptions::simple(None, None, 10),
+        )
+        .await
+        .expect("query")
+        .into_iter()
+        .map(|record| (record.value, record.quality))
+        .collect()
+}
+
+#[tokio::test]
+async fn in_memory_store_applies_dedup_strategy() {
+    let ctx = TenantContext::new(
+        "tenant-1",
+        "user-1",
+        vec![],
+        vec![],
+        Some("project-1".to_string()),
+    );
+    let with_quality = |value: i64, quality: Option<&str>| PointValue {
+        quality: quality.map(str::to_string),
+        ..sample_value(
+            "tenant-1",
+            "project-1",
+            "point-1",
+            1000,
+            PointValueData::I64(value),
+        )
+    };
+    // ignore：保留已有值，重复写入不计入写入数
+    let store = InMemoryMeasurementStore::new();
+    let written = store
+        .write_measurements(&ctx, &[with_quality(1, None), with_quality(2, None)])
+        .await
+        .expect("write");
+    assert_eq!(written, 1);
+    assert_eq!(stored_values(&store, &ctx).await, vec![("1".to_string(), None)]);
+
+    // overwrite：新值覆盖
+    let store =
+        InMemoryMeasurementStore::new().with_dedup_strategy(MeasurementDedupStrategy::Overwrite);
+    let written = store
+        .write_measurements(&ctx, &[with_quality(1, Some("good")), with_quality(2, Some("bad"))])
+        .await
+        .expect("write");
+    assert_eq!(written, 2);
+    assert_eq!(
+        stored_values(&store, &ctx).await,
+        vec![("2".to_string(), Some("bad".to_string()))]
+    );
+
+    // keep_best_quality：质量更差的重放被忽略，同等或更好的覆盖
+    let store = InMemoryMeasurementStore::new()
+        .with_dedup_strategy(MeasurementDedupStrategy::KeepBestQuality);
+    let written = store
+        .write_measurements(
+            &ctx,
+            &[
+                with_quality(1, Some("uncertain")),
+                with_quality(2, Some("bad")),
+                with_quality(3, Some("GOOD")),
+                with_quality(4, Some("uncertain")),
+            ],
+        )
+        .await
+        .expect("write");
+    assert_eq!(written, 2);
+    assert_eq!(
+        stored_values(&store, &ctx).await,
+        vec![("3".to_string(), Some("GOOD".to_string()))]
+    );
+    assert_eq!(store.len(), 1);
+}
+
+#[test]
+fn measurement_quality_rank_orders_good_other_bad() {
+    assert_eq!(measurement_quality_rank(None), 2);
+    assert_eq!(measurement_quality_rank(Some("Good")), 2);
+    assert_eq!(measurement_quality_rank(Some("uncertain")), 1);
+    assert_eq!(measurement_quality_rank(Some("BAD")), 0);
+    assert!(MeasurementDedupStrategy::KeepBestQuality.replaces(Some("bad"), Some("uncertain")));
+    assert!(!MeasurementDedupStrategy::KeepBestQuality.replaces(None, Some("uncertain")));
+    assert!(!MeasurementDedupStrategy::Ignore.replaces(Some("bad"), None));
+    assert!(MeasurementDedupStrategy::Overwrite.replaces(None, Some("bad")));
+}
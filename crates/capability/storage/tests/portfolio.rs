@@ -0,0 +1,110 @@
+use domain::TenantContext;
+use ems_storage::{InMemoryPortfolioStore, PortfolioRecord, PortfolioStore, PortfolioUpdate};
+
+fn tenant_ctx(tenant_id: &str) -> TenantContext {
+    TenantContext::new(tenant_id, "user-1", vec![], vec![], None)
+}
+
+fn portfolio(tenant_id: &str, portfolio_id: &str, created_at_ms: i64) -> PortfolioRecord {
+    PortfolioRecord {
+        tenant_id: tenant_id.to_string(),
+        portfolio_id: portfolio_id.to_string(),
+        name: "East".to_string(),
+        description: None,
+        project_ids: vec!["project-1".to_string()],
+        created_at_ms,
+        updated_at_ms: created_at_ms,
+    }
+}
+
+#[tokio::test]
+async fn portfolios_are_tenant_scoped() {
+    let store = InMemoryPortfolioStore::new();
+    let ctx = tenant_ctx("tenant-1");
+    let other = tenant_ctx("tenant-2");
+
+    store
+        .create_portfolio(&ctx, portfolio("tenant-1", "portfolio-1", 1))
+        .await
+        .expect("create");
+    store
+        .create_portfolio(&ctx, portfolio("tenant-1", "portfolio-2", 2))
+        .await
+        .expect("create");
+    assert!(
+        store
+            .create_portfolio(&ctx, portfolio("tenant-1", "portfolio-1", 3))
+            .await
+            .is_err()
+    );
+    assert!(
+        store
+            .create_portfolio(&ctx, portfolio("tenant-2", "portfolio-3", 3))
+            .await
+            .is_err()
+    );
+
+    // 按创建时间倒序
+    let items = store.list_portfolios(&ctx).await.expect("list");
+    let ids: Vec<&str> = items
+        .iter()
+        .map(|item| item.portfolio_id.as_str())
+        .collect();
+    assert_eq!(ids, vec!["portfolio-2", "portfolio-1"]);
+
+    // 其他租户不可见、不可修改
+    assert!(
+        store
+            .list_portfolios(&other)
+            .await
+            .expect("list")
+            .is_empty()
+    );
+    assert!(
+        store
+            .find_portfolio(&other, "portfolio-1")
+            .await
+            .expect("find")
+            .is_none()
+    );
+    assert!(
+        !store
+            .delete_portfolio(&other, "portfolio-1")
+            .await
+            .expect("delete")
+    );
+
+    // 成员列表整体替换，未提供的字段保持不变
+    let updated = store
+        .update_portfolio(
+            &ctx,
+            "portfolio-1",
+            PortfolioUpdate {
+                name: None,
+                description: Some("East coast".to_string()),
+                project_ids: Some(vec!["project-2".to_string(), "project-3".to_string()]),
+                updated_at_ms: 10,
+            },
+        )
+        .await
+        .expect("update")
+        .expect("exists");
+    assert_eq!(updated.name, "East");
+    assert_eq!(updated.description.as_deref(), Some("East coast"));
+    assert_eq!(updated.project_ids, vec!["project-2", "project-3"]);
+    assert_eq!(updated.updated_at_ms, 10);
+
+    assert!(
+        store
+            .delete_portfolio(&ctx, "portfolio-1")
+            .await
+            .expect("delete")
+    );
+    assert!(
+        store
+            .find_portfolio(&ctx, "portfolio-1")
+            .await
+            .expect("find")
+            .is_none()
+    );
+}
@@ -1,5 +1,5 @@
 use domain::TenantContext;
-use ems_storage::{InMemoryProjectStore, ProjectRecord, ProjectStore};
+use ems_storage::{InMemoryProjectStore, ProjectRecord, ProjectStore, StorageErrorKind};
 
 #[tokio::test]
 async fn project_belongs_to_tenant() {
@@ -21,6 +21,7 @@ async fn project_rejects_empty_tenant() {
         .await
         .expect_err("tenant required");
     assert_eq!(err.to_string(), "tenant_id required");
+    assert_eq!(err.kind(), StorageErrorKind::Forbidden);
 }
 
 #[tokio::test]
@@ -37,3 +38,20 @@ async fn project_list_includes_created() {
     let list = store.list_projects(&ctx).await.expect("list");
     assert!(list.iter().any(|item| item.project_id == "project-2"));
 }
+
+#[tokio::test]
+async fn project_duplicate_is_conflict() {
+    let store = InMemoryProjectStore::with_default_project();
+    let ctx = TenantContext::new("tenant-1", "user-1", vec![], vec![], None);
+    let record = ProjectRecord {
+        project_id: "project-1".to_string(),
+        tenant_id: "tenant-1".to_string(),
+        name: "Project 1".to_string(),
+        timezone: "UTC".to_string(),
+    };
+    let err = store
+        .create_project(&ctx, record)
+        .await
+        .expect_err("duplicate");
+    assert_eq!(err.kind(), StorageErrorKind::Conflict);
+}
@@ -0,0 +1,94 @@
+use domain::TenantContext;
+use ems_storage::{InMemoryShareTokenStore, ShareTokenRecord, ShareTokenStore};
+
+fn project_ctx(tenant_id: &str, project_id: &str) -> TenantContext {
+    TenantContext::new(
+        tenant_id,
+        "user-1",
+        vec![],
+        vec![],
+        Some(project_id.to_string()),
+    )
+}
+
+fn share_token(token_id: &str, created_at_ms: i64) -> ShareTokenRecord {
+    ShareTokenRecord {
+        tenant_id: "tenant-1".to_string(),
+        project_id: "project-1".to_string(),
+        token_id: token_id.to_string(),
+        token_hash: format!("hash-{token_id}"),
+        name: "Lobby".to_string(),
+        scopes: vec!["realtime".to_string()],
+        created_by: "user-1".to_string(),
+        expires_at_ms: created_at_ms + 1_000,
+        revoked_at_ms: None,
+        created_at_ms,
+    }
+}
+
+#[tokio::test]
+async fn share_tokens_list_find_and_revoke() {
+    let store = InMemoryShareTokenStore::new();
+    let ctx = project_ctx("tenant-1", "project-1");
+
+    store
+        .create_share_token(&ctx, share_token("token-1", 1))
+        .await
+        .expect("create");
+    store
+        .create_share_token(&ctx, share_token("token-2", 2))
+        .await
+        .expect("create");
+    assert!(
+        store
+            .create_share_token(&ctx, share_token("token-1", 3))
+            .await
+            .is_err()
+    );
+    assert!(
+        store
+            .create_share_token(
+                &project_ctx("tenant-2", "project-1"),
+                share_token("token-3", 3)
+            )
+            .await
+            .is_err()
+    );
+
+    let items = store
+        .list_share_tokens(&ctx, "project-1")
+        .await
+        .expect("list");
+    let ids: Vec<&str> = items.iter().map(|item| item.token_id.as_str()).collect();
+    assert_eq!(ids, vec!["token-2", "token-1"]);
+
+    let found = store
+        .find_share_token_by_hash("hash-token-1")
+        .await
+        .expect("find")
+        .expect("token");
+    assert!(found.is_active(500));
+    assert!(!found.is_active(1_001));
+
+    let revoked = store
+        .revoke_share_token(&ctx, "project-1", "token-1", 100)
+        .await
+        .expect("revoke")
+        .expect("token");
+    assert_eq!(revoked.revoked_at_ms, Some(100));
+    assert!(!revoked.is_active(50));
+    // 重复撤销保留首次撤销时间
+    let revoked = store
+        .revoke_share_token(&ctx, "project-1", "token-1", 200)
+        .await
+        .expect("revoke")
+        .expect("token");
+    assert_eq!(revoked.revoked_at_ms, Some(100));
+    assert!(
+        store
+            .revoke_share_token(&ctx, "project-1", "missing", 200)
+            .await
+            .expect("revoke")
+            .is_none()
+    );
+}
@@ -0,0 +1,106 @@
+use domain::TenantContext;
+use ems_storage::{InMemoryUsageStore, QuotaRecord, UsageStore};
+
+fn tenant_ctx(tenant_id: &str) -> TenantContext {
+    TenantContext::new(tenant_id, "user-1", vec![], vec![], None)
+}
+
+fn quota(tenant_id: &str, metric: &str, limit: i64) -> QuotaRecord {
+    QuotaRecord {
+        tenant_id: tenant_id.to_string(),
+        metric: metric.to_string(),
+        limit,
+        updated_at_ms: 0,
+    }
+}
+
+#[tokio::test]
+async fn usage_is_rejected_beyond_quota() {
+    let store = InMemoryUsageStore::new();
+    let ctx = tenant_ctx("tenant-1");
+    let other = tenant_ctx("tenant-2");
+    let day_ms = 24 * 3_600_000;
+
+    // 未配置配额时不限制
+    assert_eq!(
+        store
+            .consume_usage(&ctx, "commands", 0, 5)
+            .await
+            .expect("consume"),
+        Some(5)
+    );
+    store
+        .upsert_quota(&ctx, quota("tenant-1", "commands", 6))
+        .await
+        .expect("quota");
+    assert_eq!(
+        store
+            .consume_usage(&ctx, "commands", 0, 1)
+            .await
+            .expect("consume"),
+        Some(6)
+    );
+    // 超出配额时不累加
+    assert_eq!(
+        store
+            .consume_usage(&ctx, "commands", 0, 1)
+            .await
+            .expect("consume"),
+        None
+    );
+    // 新周期重新计数；配额按租户隔离
+    assert_eq!(
+        store
+            .consume_usage(&ctx, "commands", day_ms, 1)
+            .await
+            .expect("consume"),
+        Some(1)
+    );
+    assert_eq!(
+        store
+            .consume_usage(&other, "commands", 0, 10)
+            .await
+            .expect("consume"),
+        Some(10)
+    );
+
+    let usage = store.list_usage(&ctx, 0, day_ms).await.expect("list");
+    assert_eq!(usage.len(), 1);
+    assert_eq!(usage[0].count, 6);
+    assert_eq!(
+        store
+            .list_usage(&ctx, 0, 2 * day_ms)
+            .await
+            .expect("list")
+            .len(),
+        2
+    );
+
+    assert_eq!(store.list_quotas(&ctx).await.expect("quotas").len(), 1);
+    assert!(store.list_quotas(&other).await.expect("quotas").is_empty());
+    assert!(store.delete_quota(&ctx, "commands").await.expect("delete"));
+    assert!(!store.delete_quota(&ctx, "commands").await.expect("delete"));
+    assert_eq!(
+        store
+            .consume_usage(&ctx, "commands", 0, 1)
+            .await
+            .expect("consume"),
+        Some(7)
+    );
+
+    // add_usage 不检查配额
+    store
+        .upsert_quota(&ctx, quota("tenant-1", "commands", 1))
+        .await
+        .expect("quota");
+    assert_eq!(
+        store.add_usage(&ctx, "commands", 0, 3).await.expect("add"),
+        10
+    );
+
+    let err = store
+        .upsert_quota(&ctx, quota("tenant-2", "commands", 1))
+        .await
+        .expect_err("tenant mismatch");
+    assert_eq!(err.kind(), ems_storage::StorageErrorKind::Forbidden);
+}
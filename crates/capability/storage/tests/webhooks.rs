@@ -0,0 +1,158 @@
+use domain::TenantContext;
+use ems_storage::{
+    InMemoryWebhookSubscriptionStore, WebhookDeliveryQuery, WebhookDeliveryRecord,
+    WebhookSubscriptionRecord, WebhookSubscriptionStore,
+};
+
+fn tenant_ctx(project_id: &str) -> TenantContext {
+    TenantContext::new(
+        "tenant-1",
+        "user-1",
+        vec![],
+        vec![],
+        Some(project_id.to_string()),
+    )
+}
+
+fn subscription(
+    subscription_id: &str,
+    event_types: &[&str],
+    enabled: bool,
+) -> WebhookSubscriptionRecord {
+    WebhookSubscriptionRecord {
+        subscription_id: subscription_id.to_string(),
+        tenant_id: "tenant-1".to_string(),
+        project_id: "project-1".to_string(),
+        url: "https://example.com/hook".to_string(),
+        event_types: event_types.iter().map(|value| value.to_string()).collect(),
+        secret: "secret".to_string(),
+        enabled,
+        created_by: "user-1".to_string(),
+        created_at_ms: 1_700_000_000_000,
+    }
+}
+
+fn delivery(
+    delivery_id: &str,
+    subscription_id: &str,
+    status: &str,
+    created_at_ms: i64,
+) -> WebhookDeliveryRecord {
+    WebhookDeliveryRecord {
+        delivery_id: delivery_id.to_string(),
+        tenant_id: "tenant-1".to_string(),
+        project_id: "project-1".to_string(),
+        subscription_id: subscription_id.to_string(),
+        event_id: format!("event-{}", delivery_id),
+        event_type: "gateway.created".to_string(),
+        status: status.to_string(),
+        attempts: 1,
+        response_status: Some(200),
+        error: None,
+        created_at_ms,
+    }
+}
+
+#[tokio::test]
+async fn subscriptions_match_enabled_event_types() {
+    let store = InMemoryWebhookSubscriptionStore::new();
+    let ctx = tenant_ctx("project-1");
+    store
+        .create_webhook_subscription(&ctx, subscription("sub-1", &["gateway.created"], true))
+        .await
+        .expect("create");
+    store
+        .create_webhook_subscription(
+            &ctx,
+            subscription("sub-2", &["gateway.created", "command.completed"], false),
+        )
+        .await
+        .expect("create");
+    store
+        .create_webhook_subscription(&ctx, subscription("sub-3", &["command.completed"], true))
+        .await
+        .expect("create");
+
+    let matched = store
+        .list_webhook_subscriptions_for_event(&ctx, "project-1", "gateway.created")
+        .await
+        .expect("match");
+    assert_eq!(matched.len(), 1);
+    assert_eq!(matched[0].subscription_id, "sub-1");
+
+    // 项目作用域外不可访问
+    let other = tenant_ctx("project-2");
+    assert!(
+        store
+            .list_webhook_subscriptions(&other, "project-1")
+            .await
+            .is_err()
+    );
+
+    assert!(
+        store
+            .delete_webhook_subscription(&ctx, "project-1", "sub-1")
+            .await
+            .expect("delete")
+    );
+    assert!(
+        !store
+            .delete_webhook_subscription(&ctx, "project-1", "sub-1")
+            .await
+            .expect("delete")
+    );
+    let items = store
+        .list_webhook_subscriptions(&ctx, "project-1")
+        .await
+        .expect("list");
+    assert_eq!(items.len(), 2);
+}
+
+#[tokio::test]
+async fn deliveries_filter_by_subscription_and_status() {
+    let store = InMemoryWebhookSubscriptionStore::new();
+    let ctx = tenant_ctx("project-1");
+    for record in [
+        delivery("d-1", "sub-1", "success", 1_000),
+        delivery("d-2", "sub-1", "failed", 2_000),
+        delivery("d-3", "sub-2", "failed", 3_000),
+    ] {
+        store
+            .create_webhook_delivery(&ctx, record)
+            .await
+            .expect("create");
+    }
+
+    let failed = store
+        .list_webhook_deliveries(
+            &ctx,
+            "project-1",
+            WebhookDeliveryQuery {
+                status: Some("failed".to_string()),
+                limit: 10,
+                ..WebhookDeliveryQuery::default()
+            },
+        )
+        .await
+        .expect("list");
+    let ids: Vec<&str> = failed
+        .iter()
+        .map(|item| item.delivery_id.as_str())
+        .collect();
+    assert_eq!(ids, vec!["d-3", "d-2"]);
+
+    let by_subscription = store
+        .list_webhook_deliveries(
+            &ctx,
+            "project-1",
+            WebhookDeliveryQuery {
+                subscription_id: Some("sub-1".to_string()),
+                limit: 1,
+                ..WebhookDeliveryQuery::default()
+            },
+        )
+        .await
+        .expect("list");
+    assert_eq!(by_subscription.len(), 1);
+    assert_eq!(by_subscription[0].delivery_id, "d-2");
+}
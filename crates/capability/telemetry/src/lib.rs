@@ -1,8 +1,11 @@
 //! 追踪与请求 ID 生成。
 
-use std::sync::OnceLock;
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
-use tracing_subscriber::{EnvFilter, fmt};
+use std::sync::{OnceLock, RwLock};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Registry, fmt, reload};
 
 /// 请求级追踪标识。
 #[derive(Debug, Clone)]
@@ -33,6 +36,67 @@ pub struct MetricsSnapshot {
     pub command_issue_latency_ms_total: u64,
     pub command_issue_latency_ms_count: u64,
     pub receipts_processed: u64,
+    pub receipts_rejected: u64,
+}
+
+impl MetricsSnapshot {
+    /// 渲染为 Prometheus 文本格式（仅全局计数，不含租户标记的指标）。
+    pub fn to_prometheus(&self) -> String {
+        let counters = [
+            ("ems_raw_events_total", self.raw_events),
+            ("ems_normalized_values_total", self.normalized_values),
+            ("ems_write_success_total", self.write_success),
+            ("ems_write_failure_total", self.write_failure),
+            ("ems_dropped_duplicate_total", self.dropped_duplicate),
+            ("ems_dropped_invalid_total", self.dropped_invalid),
+            ("ems_dropped_stale_total", self.dropped_stale),
+            ("ems_dropped_unmapped_total", self.dropped_unmapped),
+            ("ems_backpressure_total", self.backpressure),
+            ("ems_write_latency_ms_total", self.write_latency_ms_total),
+            ("ems_write_latency_ms_count", self.write_latency_ms_count),
+            (
+                "ems_end_to_end_latency_ms_total",
+                self.end_to_end_latency_ms_total,
+            ),
+            (
+                "ems_end_to_end_latency_ms_count",
+                self.end_to_end_latency_ms_count,
+            ),
+            ("ems_commands_issued_total", self.commands_issued),
+            (
+                "ems_command_dispatch_success_total",
+                self.command_dispatch_success,
+            ),
+            (
+                "ems_command_dispatch_failure_total",
+                self.command_dispatch_failure,
+            ),
+            (
+                "ems_command_issue_latency_ms_total",
+                self.command_issue_latency_ms_total,
+            ),
+            (
+                "ems_command_issue_latency_ms_count",
+                self.command_issue_latency_ms_count,
+            ),
+            ("ems_receipts_processed_total", self.receipts_processed),
+            ("ems_receipts_rejected_total", self.receipts_rejected),
+        ];
+        let mut output = String::from("# TYPE ems_up gauge\nems_up 1\n");
+        for (name, value) in counters {
+            output.push_str(&format!("# TYPE {name} counter\n{name} {value}\n"));
+        }
+        output
+    }
+}
+
+/// 按租户标记的指标快照（仅包含可归属到租户的计数）。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TenantMetricsSnapshot {
+    pub raw_events: u64,
+    pub write_success: u64,
+    pub commands_issued: u64,
+    pub receipts_processed: u64,
 }
 
 /// 基础指标（MVP）。
@@ -56,6 +120,8 @@ pub struct TelemetryMetrics {
     command_issue_latency_ms_total: AtomicU64,
     command_issue_latency_ms_count: AtomicU64,
     receipts_processed: AtomicU64,
+    receipts_rejected: AtomicU64,
+    tenants: RwLock<HashMap<String, TenantMetricsSnapshot>>,
 }
 
 impl TelemetryMetrics {
@@ -80,6 +146,8 @@ impl TelemetryMetrics {
             command_issue_latency_ms_total: AtomicU64::new(0),
             command_issue_latency_ms_count: AtomicU64::new(0),
             receipts_processed: AtomicU64::new(0),
+            receipts_rejected: AtomicU64::new(0),
+            tenants: RwLock::new(HashMap::new()),
         }
     }
 
@@ -108,10 +176,32 @@ impl TelemetryMetrics {
                 .command_issue_latency_ms_count
                 .load(Ordering::Relaxed),
             receipts_processed: self.receipts_processed.load(Ordering::Relaxed),
+            receipts_rejected: self.receipts_rejected.load(Ordering::Relaxed),
+        }
+    }
+
+    /// 指定租户的指标快照（未产生过指标的租户返回全 0）。
+    pub fn tenant_snapshot(&self, tenant_id: &str) -> TenantMetricsSnapshot {
+        self.tenants
+            .read()
+            .ok()
+            .and_then(|tenants| tenants.get(tenant_id).copied())
+            .unwrap_or_default()
+    }
+
+    fn update_tenant(&self, tenant_id: &str, update: impl FnOnce(&mut TenantMetricsSnapshot)) {
+        if let Ok(mut tenants) = self.tenants.write() {
+            update(tenants.entry(tenant_id.to_string()).or_default());
         }
     }
 }
 
+impl Default for TelemetryMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 static METRICS: OnceLock<TelemetryMetrics> = OnceLock::new();
 
 /// 获取全局指标实例（MVP）。
@@ -119,10 +209,36 @@ pub fn metrics() -> &'static TelemetryMetrics {
     METRICS.get_or_init(TelemetryMetrics::new)
 }
 
-/// 初始化 tracing（默认 info）。
+/// 运行时可替换的日志过滤器。
+static LOG_FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+fn default_filter() -> EnvFilter {
+    EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"))
+}
+
+/// 初始化 tracing（默认读取 RUST_LOG，未设置时为 info）。
 pub fn init_tracing() {
-    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
-    let _ = fmt().with_env_filter(filter).try_init();
+    let (filter, handle) = reload::Layer::new(default_filter());
+    if tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer())
+        .try_init()
+        .is_ok()
+    {
+        let _ = LOG_FILTER.set(handle);
+    }
+}
+
+/// 替换日志过滤器（如 `debug`、`info,ems.ingest=debug`）；`None` 恢复 RUST_LOG / info。
+pub fn set_log_filter(directives: Option<&str>) -> Result<(), String> {
+    let filter = match directives {
+        Some(directives) => EnvFilter::try_new(directives).map_err(|err| err.to_string())?,
+        None => default_filter(),
+    };
+    let Some(handle) = LOG_FILTER.get() else {
+        return Err("tracing not initialized".to_string());
+    };
+    handle.reload(filter).map_err(|err| err.to_string())
 }
 
 /// 生成新的 request_id 与 trace_id。
@@ -133,9 +249,11 @@ pub fn new_request_ids() -> RequestIds {
     }
 }
 
-/// 记录 RawEvent 接收次数。
-pub fn record_raw_event() {
-    metrics().raw_events.fetch_add(1, Ordering::Relaxed);
+/// 记录 RawEvent 接收次数（同时计入所属租户）。
+pub fn record_raw_event(tenant_id: &str) {
+    let metrics = metrics();
+    metrics.raw_events.fetch_add(1, Ordering::Relaxed);
+    metrics.update_tenant(tenant_id, |tenant| tenant.raw_events += 1);
 }
 
 /// 记录规范化输出次数。
@@ -143,9 +261,11 @@ pub fn record_normalized_value() {
     metrics().normalized_values.fetch_add(1, Ordering::Relaxed);
 }
 
-/// 记录写入成功次数。
-pub fn record_write_success() {
-    metrics().write_success.fetch_add(1, Ordering::Relaxed);
+/// 记录写入成功次数（同时计入所属租户）。
+pub fn record_write_success(tenant_id: &str) {
+    let metrics = metrics();
+    metrics.write_success.fetch_add(1, Ordering::Relaxed);
+    metrics.update_tenant(tenant_id, |tenant| tenant.write_success += 1);
 }
 
 /// 记录写入失败次数。
@@ -200,9 +320,11 @@ pub fn record_end_to_end_latency_ms(latency_ms: u64) {
         .fetch_add(1, Ordering::Relaxed);
 }
 
-/// 记录命令下发请求次数。
-pub fn record_command_issued() {
-    metrics().commands_issued.fetch_add(1, Ordering::Relaxed);
+/// 记录命令下发请求次数（同时计入所属租户）。
+pub fn record_command_issued(tenant_id: &str) {
+    let metrics = metrics();
+    metrics.commands_issued.fetch_add(1, Ordering::Relaxed);
+    metrics.update_tenant(tenant_id, |tenant| tenant.commands_issued += 1);
 }
 
 /// 记录命令下发成功次数（MQTT 发布成功）。
@@ -230,9 +352,18 @@ pub fn record_command_issue_latency_ms(latency_ms: u64) {
         .fetch_add(1, Ordering::Relaxed);
 }
 
-/// 记录回执处理次数（MQTT 回执成功写入）。
-pub fn record_receipt_processed() {
+/// 记录回执处理次数（MQTT 回执成功写入，同时计入所属租户）。
+pub fn record_receipt_processed(tenant_id: &str) {
+    let metrics = metrics();
+    metrics.receipts_processed.fetch_add(1, Ordering::Relaxed);
+    metrics.update_tenant(tenant_id, |tenant| tenant.receipts_processed += 1);
+}
+
+/// 记录被拒绝的回执次数（主题无法解析、载荷无效、严格模式下命令校验失败）。
+///
+/// 被拒绝的回执无法可靠归属到租户，只计入全局计数。
+pub fn record_receipt_rejected() {
     metrics()
-        .receipts_processed
+        .receipts_rejected
         .fetch_add(1, Ordering::Relaxed);
 }
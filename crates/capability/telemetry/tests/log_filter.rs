@@ -0,0 +1,10 @@
+use ems_telemetry::{init_tracing, set_log_filter};
+
+#[test]
+fn log_filter_can_be_replaced_at_runtime() {
+    init_tracing();
+    set_log_filter(Some("debug")).expect("debug filter");
+    set_log_filter(Some("info,ems.ingest=trace")).expect("per-target filter");
+    set_log_filter(None).expect("default filter");
+    assert!(set_log_filter(Some("ems=notalevel")).is_err());
+}
@@ -0,0 +1,33 @@
+use ems_telemetry::{metrics, record_command_issued, record_raw_event, record_write_success};
+
+#[test]
+fn tenant_metrics_are_isolated() {
+    record_raw_event("tenant-metrics-a");
+    record_raw_event("tenant-metrics-a");
+    record_write_success("tenant-metrics-a");
+    record_command_issued("tenant-metrics-b");
+
+    let tenant_a = metrics().tenant_snapshot("tenant-metrics-a");
+    assert_eq!(tenant_a.raw_events, 2);
+    assert_eq!(tenant_a.write_success, 1);
+    assert_eq!(tenant_a.commands_issued, 0);
+
+    let tenant_b = metrics().tenant_snapshot("tenant-metrics-b");
+    assert_eq!(tenant_b.raw_events, 0);
+    assert_eq!(tenant_b.commands_issued, 1);
+
+    // 全局计数包含所有租户
+    let global = metrics().snapshot();
+    assert!(global.raw_events >= 2);
+    assert_eq!(metrics().tenant_snapshot("tenant-unknown").raw_events, 0);
+}
+
+#[test]
+fn prometheus_text_contains_global_counters() {
+    let text = metrics().snapshot().to_prometheus();
+    assert!(text.starts_with("# TYPE ems_up gauge\nems_up 1\n"));
+    assert!(text.contains("# TYPE ems_raw_events_total counter\n"));
+    assert!(text.contains("ems_receipts_processed_total "));
+    assert!(text.contains("ems_receipts_rejected_total "));
+    assert!(!text.contains("tenant"));
+}
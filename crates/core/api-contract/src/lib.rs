@@ -1,4 +1,6 @@
 //! 稳定的 DTO 与 API 响应契约。
+//!
+//! 请求与响应类型均同时实现 Serialize / Deserialize，服务端与 `ems-client` 共用同一套定义。
 
 use serde::{Deserialize, Serialize};
 
@@ -8,11 +10,18 @@ pub mod error_codes {
     pub const AUTH_FORBIDDEN: &str = "AUTH.FORBIDDEN";
     pub const INVALID_REQUEST: &str = "INVALID.REQUEST";
     pub const RESOURCE_NOT_FOUND: &str = "RESOURCE.NOT_FOUND";
+    pub const RESOURCE_CONFLICT: &str = "RESOURCE.CONFLICT";
+    pub const SERVICE_UNAVAILABLE: &str = "SERVICE.UNAVAILABLE";
     pub const INTERNAL_ERROR: &str = "INTERNAL.ERROR";
+    pub const API_VERSION_UNSUPPORTED: &str = "API.VERSION_UNSUPPORTED";
+    pub const IDEMPOTENCY_IN_PROGRESS: &str = "IDEMPOTENCY.IN_PROGRESS";
+    pub const IDEMPOTENCY_KEY_REUSED: &str = "IDEMPOTENCY.KEY_REUSED";
+    pub const FEATURE_DISABLED: &str = "FEATURE.DISABLED";
+    pub const QUOTA_EXCEEDED: &str = "QUOTA.EXCEEDED";
 }
 
 /// 标准 API 响应封装。
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ApiResponse<T> {
     pub success: bool,
     pub data: Option<T>,
@@ -20,7 +29,7 @@ pub struct ApiResponse<T> {
 }
 
 /// 失败响应的错误体。
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ApiError {
     pub code: String,
     pub message: String,
@@ -47,8 +56,17 @@ impl<T> ApiResponse<T> {
     }
 }
 
+/// 字段级校验错误（400 响应的 `data`）。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldErrorDto {
+    /// 请求体中的字段路径，如 `protocolConfig.port`
+    pub field: String,
+    pub message: String,
+}
+
 /// 登录请求体。
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LoginRequest {
     pub username: String,
@@ -56,7 +74,7 @@ pub struct LoginRequest {
 }
 
 /// 登录响应体。
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LoginResponse {
     pub access_token: String,
@@ -70,7 +88,7 @@ pub struct LoginResponse {
 }
 
 /// 刷新 token 请求体。
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RefreshTokenRequest {
     #[serde(alias = "refresh_token")]
@@ -78,7 +96,7 @@ pub struct RefreshTokenRequest {
 }
 
 /// 刷新 token 响应体。
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RefreshTokenResponse {
     pub access_token: String,
@@ -87,18 +105,18 @@ pub struct RefreshTokenResponse {
 }
 
 /// 动态路由返回结构（兼容 pure-admin-thin）。
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct AsyncRoute {
     pub path: String,
     pub name: String,
     pub component: String,
     pub meta: RouteMeta,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub children: Vec<AsyncRoute>,
 }
 
 /// 路由元数据。
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct RouteMeta {
     pub title: String,
     pub icon: String,
@@ -108,7 +126,7 @@ pub struct RouteMeta {
 }
 
 /// RBAC 用户返回结构（tenant 级）。
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RbacUserDto {
     pub user_id: String,
@@ -118,7 +136,7 @@ pub struct RbacUserDto {
 }
 
 /// RBAC 创建用户请求体（tenant 级）。
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateRbacUserRequest {
     pub username: String,
@@ -128,7 +146,7 @@ pub struct CreateRbacUserRequest {
 }
 
 /// RBAC 更新用户请求体（tenant 级）。
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdateRbacUserRequest {
     pub password: Option<String>,
@@ -136,14 +154,14 @@ pub struct UpdateRbacUserRequest {
 }
 
 /// RBAC 设置用户角色请求体（tenant 级，替换模式）。
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SetUserRolesRequest {
     pub roles: Vec<String>,
 }
 
 /// RBAC 角色返回结构（tenant 级）。
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RbacRoleDto {
     pub role_code: String,
@@ -152,7 +170,7 @@ pub struct RbacRoleDto {
 }
 
 /// RBAC 创建角色请求体（tenant 级）。
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateRbacRoleRequest {
     pub role_code: String,
@@ -161,14 +179,14 @@ pub struct CreateRbacRoleRequest {
 }
 
 /// RBAC 设置角色权限请求体（tenant 级，替换模式）。
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SetRolePermissionsRequest {
     pub permissions: Vec<String>,
 }
 
 /// 权限码返回结构。
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PermissionDto {
     pub permission_code: String,
@@ -176,7 +194,7 @@ pub struct PermissionDto {
 }
 
 /// 项目创建请求体。
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateProjectRequest {
     pub name: String,
@@ -184,7 +202,7 @@ pub struct CreateProjectRequest {
 }
 
 /// 项目更新请求体。
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdateProjectRequest {
     pub name: Option<String>,
@@ -192,7 +210,7 @@ pub struct UpdateProjectRequest {
 }
 
 /// 项目返回结构。
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ProjectDto {
     pub project_id: String,
@@ -200,8 +218,116 @@ pub struct ProjectDto {
     pub timezone: String,
 }
 
+/// 项目克隆请求体。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloneProjectRequest {
+    pub name: String,
+    /// 默认沿用源项目时区
+    pub timezone: Option<String>,
+    /// 是否复制自动化规则（默认 true；复制出的规则均为停用状态）
+    pub include_rules: Option<bool>,
+}
+
+/// 项目克隆结果（新项目 + 各类资源复制数量）。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectCloneDto {
+    pub project: ProjectDto,
+    pub source_project_id: String,
+    pub gateways: usize,
+    /// 随网关复制的命令载荷格式数量
+    pub gateway_command_formats: usize,
+    pub devices: usize,
+    pub points: usize,
+    pub point_mappings: usize,
+    pub device_templates: usize,
+    pub rules: usize,
+    /// 未复制的内容（如 `dashboards`），需在新项目中人工补齐
+    pub not_copied: Vec<String>,
+}
+
+/// 项目组合创建请求体。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatePortfolioRequest {
+    pub name: String,
+    pub description: Option<String>,
+    /// 成员项目 ID（需归属当前租户，重复项自动去除）
+    #[serde(default)]
+    pub project_ids: Vec<String>,
+}
+
+/// 项目组合更新请求体（`projectIds` 存在时整体替换成员列表）。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdatePortfolioRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub project_ids: Option<Vec<String>>,
+}
+
+/// 项目组合返回结构。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortfolioDto {
+    pub portfolio_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub project_ids: Vec<String>,
+    pub created_at_ms: i64,
+    pub updated_at_ms: i64,
+}
+
+/// 项目组合概览查询参数（`from` / `to` 为毫秒时间戳，左闭右开）。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortfolioOverviewQuery {
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+/// 单个能源类型的消耗量。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnergyConsumptionDto {
+    pub energy_source: String,
+    pub consumption: f64,
+    pub unit: String,
+}
+
+/// 单个成员项目的概览。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortfolioProjectOverviewDto {
+    pub project_id: String,
+    pub name: String,
+    pub consumption: Vec<EnergyConsumptionDto>,
+    /// 窗口内的异常告警数
+    pub alarm_count: i64,
+    pub gateways: usize,
+    pub gateways_online: usize,
+    /// 网关在线率（0–1，无网关时为 null）
+    pub online_rate: Option<f64>,
+}
+
+/// 项目组合概览（成员项目明细 + 汇总）。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortfolioOverviewDto {
+    pub portfolio_id: String,
+    pub from: i64,
+    pub to: i64,
+    pub projects: Vec<PortfolioProjectOverviewDto>,
+    pub consumption: Vec<EnergyConsumptionDto>,
+    pub alarm_count: i64,
+    pub gateways: usize,
+    pub gateways_online: usize,
+    pub online_rate: Option<f64>,
+}
+
 /// 网关创建请求体。
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateGatewayRequest {
     pub name: String,
@@ -213,7 +339,7 @@ pub struct CreateGatewayRequest {
 }
 
 /// 网关更新请求体。
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdateGatewayRequest {
     pub name: Option<String>,
@@ -223,7 +349,7 @@ pub struct UpdateGatewayRequest {
 }
 
 /// 网关返回结构。
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GatewayDto {
     pub gateway_id: String,
@@ -236,8 +362,168 @@ pub struct GatewayDto {
     pub protocol_config: Option<String>,
 }
 
+/// 网关回调令牌返回结构（令牌明文仅签发时返回一次）。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GatewayTokenDto {
+    pub gateway_id: String,
+    pub project_id: String,
+    pub token: String,
+}
+
+/// 网关命令载荷格式。
+///
+/// `format` 为 `envelope`（默认）/ `raw` / `template` / `hex`；`template` 用于 template 与 hex，
+/// `checksum`（`crc16_modbus` / `sum8`）仅用于 hex。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GatewayCommandFormatDto {
+    pub gateway_id: String,
+    pub format: String,
+    pub template: Option<String>,
+    pub checksum: Option<String>,
+}
+
+/// 设置网关命令载荷格式请求。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetGatewayCommandFormatRequest {
+    pub format: String,
+    pub template: Option<String>,
+    pub checksum: Option<String>,
+}
+
+/// 网关配置下发记录查询参数。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GatewayConfigPushQuery {
+    pub limit: Option<i64>,
+}
+
+/// 网关配置下发记录返回结构。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GatewayConfigPushDto {
+    pub project_id: String,
+    pub gateway_id: String,
+    pub version: i64,
+    /// 下发的配置文档（网关 + 设备 + 点位 + 映射）
+    pub document: serde_json::Value,
+    /// pending | published | applied | failed
+    pub status: String,
+    pub message: Option<String>,
+    pub pushed_by: String,
+    pub pushed_at_ms: i64,
+    pub updated_at_ms: i64,
+}
+
+/// 固件包登记请求体（固件文件由外部存储保存，这里只登记元数据与引用）。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateFirmwarePackageRequest {
+    pub name: String,
+    pub version: String,
+    /// 固件文件 SHA-256（64 位十六进制）
+    pub checksum_sha256: String,
+    pub size_bytes: i64,
+    /// 固件文件下载地址或存储引用
+    pub storage_url: String,
+    /// 附加元数据（JSON 对象）
+    pub metadata: Option<serde_json::Value>,
+}
+
+/// 固件包返回结构。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FirmwarePackageDto {
+    pub package_id: String,
+    pub project_id: String,
+    pub name: String,
+    pub version: String,
+    pub checksum_sha256: String,
+    pub size_bytes: i64,
+    pub storage_url: String,
+    pub metadata: Option<serde_json::Value>,
+    pub created_by: String,
+    pub created_at_ms: i64,
+}
+
+/// 固件升级批次创建请求体。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateFirmwareCampaignRequest {
+    pub package_id: String,
+    /// 批次名称，缺省为 `{name} {version}`
+    pub name: Option<String>,
+    /// 目标网关；缺省为项目下全部网关
+    pub gateway_ids: Option<Vec<String>>,
+}
+
+/// 固件升级批次查询参数。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FirmwareCampaignQuery {
+    pub limit: Option<i64>,
+}
+
+/// 固件升级批次返回结构。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FirmwareCampaignDto {
+    pub campaign_id: String,
+    pub project_id: String,
+    pub package_id: String,
+    pub name: String,
+    /// in_progress | completed | failed
+    pub status: String,
+    pub created_by: String,
+    pub created_at_ms: i64,
+    pub updated_at_ms: i64,
+}
+
+/// 网关升级进度返回结构。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FirmwareRolloutDto {
+    pub campaign_id: String,
+    pub gateway_id: String,
+    /// pending | published | downloading | installing | succeeded | failed
+    pub status: String,
+    /// 升级进度百分比（0–100）
+    pub progress: i32,
+    pub message: Option<String>,
+    pub updated_at_ms: i64,
+}
+
+/// 设置维护窗口请求体（设备 / 网关）。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetMaintenanceWindowRequest {
+    /// 开始时间（缺省为当前时间）
+    pub starts_at_ms: Option<i64>,
+    pub ends_at_ms: i64,
+    pub reason: Option<String>,
+}
+
+/// 维护窗口返回结构。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceWindowDto {
+    pub project_id: String,
+    /// device | gateway
+    pub target_type: String,
+    pub target_id: String,
+    pub starts_at_ms: i64,
+    pub ends_at_ms: i64,
+    pub reason: Option<String>,
+    pub created_by: String,
+    pub created_at_ms: i64,
+    /// 当前是否处于窗口内
+    pub active: bool,
+}
+
 /// 设备创建请求体。
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateDeviceRequest {
     pub gateway_id: String,
@@ -247,20 +533,23 @@ pub struct CreateDeviceRequest {
     pub room_id: Option<String>,
     /// 协议地址配置（JSON 字符串）
     pub address_config: Option<String>,
+    /// 离线判定阈值（秒，未设置时使用全局默认值）
+    pub offline_after_seconds: Option<i64>,
 }
 
 /// 设备更新请求体。
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdateDeviceRequest {
     pub name: Option<String>,
     pub model: Option<String>,
     pub room_id: Option<String>,
     pub address_config: Option<String>,
+    pub offline_after_seconds: Option<i64>,
 }
 
 /// 设备返回结构。
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DeviceDto {
     pub device_id: String,
@@ -272,29 +561,161 @@ pub struct DeviceDto {
     pub last_seen_at_ms: Option<i64>,
     pub room_id: Option<String>,
     pub address_config: Option<String>,
+    /// 离线判定阈值（秒，为空表示使用全局默认值）
+    pub offline_after_seconds: Option<i64>,
+}
+
+/// 设备影子期望状态设置请求体（整体替换）。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateDeviceShadowRequest {
+    /// 点位 key → 期望值（数值 / 布尔 / 字符串）
+    pub desired: serde_json::Value,
+}
+
+/// 设备时间线查询参数。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceEventQuery {
+    pub limit: Option<i64>,
+    /// 按事件类型精确过滤（如 `device.offline`、`command.completed`）。
+    pub event_type: Option<String>,
+    /// 发生时间范围（毫秒，闭区间）。
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    /// 可选游标：上一页最后一条的 `occurredAtMs`，结果按发生时间倒序。
+    pub cursor_ts_ms: Option<i64>,
+    /// 可选游标：上一页最后一条的 `eventId`（与 `cursorTsMs` 配合处理同一时间戳）。
+    pub cursor_event_id: Option<String>,
+}
+
+/// 设备时间线事件返回结构。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceEventDto {
+    pub event_id: String,
+    pub project_id: String,
+    pub device_id: String,
+    pub event_type: String,
+    pub occurred_at_ms: i64,
+    /// 事件数据（与领域事件 `data` 一致）
+    pub data: serde_json::Value,
+}
+
+/// 设备影子返回结构。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceShadowDto {
+    pub project_id: String,
+    pub device_id: String,
+    /// 期望状态版本（未设置过为 0）
+    pub version: i64,
+    pub desired: serde_json::Value,
+    /// 由实时值（及差量命令的成功回执）推导的上报状态
+    pub reported: serde_json::Value,
+    pub reported_at_ms: Option<i64>,
+    /// 期望值与上报值不一致的点位（空对象表示已同步）
+    pub delta: serde_json::Value,
+    pub in_sync: bool,
+    pub last_command_id: Option<String>,
+    /// 最近一次差量命令的最新回执状态（设置期望状态的响应中为下发结果）
+    pub last_command_status: Option<String>,
+    pub updated_by: Option<String>,
+    pub updated_at_ms: Option<i64>,
+}
+
+/// 设备创建查询参数。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateDeviceQuery {
+    /// 设备模板 ID（提供时按模板同时创建点位与点位映射）
+    pub template_id: Option<String>,
+}
+
+/// 设备模板点位请求体。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceTemplatePointRequest {
+    pub key: String,
+    pub data_type: String,
+    pub unit: Option<String>,
+    /// 映射提示：数据源类型（与 address 同时提供）
+    pub source_type: Option<String>,
+    /// 映射提示：数据源地址（与 sourceType 同时提供）
+    pub address: Option<String>,
+    pub scale: Option<f64>,
+    pub offset: Option<f64>,
+    /// 协议细节配置（JSON 字符串）
+    pub protocol_detail: Option<String>,
+}
+
+/// 设备模板创建请求体。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateDeviceTemplateRequest {
+    pub name: String,
+    pub model: Option<String>,
+    pub points: Vec<DeviceTemplatePointRequest>,
+}
+
+/// 设备模板点位返回结构。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceTemplatePointDto {
+    pub key: String,
+    pub data_type: String,
+    pub unit: Option<String>,
+    pub source_type: Option<String>,
+    pub address: Option<String>,
+    pub scale: Option<f64>,
+    pub offset: Option<f64>,
+    pub protocol_detail: Option<String>,
+}
+
+/// 设备模板返回结构。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceTemplateDto {
+    pub template_id: String,
+    pub project_id: String,
+    pub name: String,
+    pub model: Option<String>,
+    pub points: Vec<DeviceTemplatePointDto>,
+}
+
+/// 按模板实例化设备的返回结构。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceInstanceDto {
+    pub device: DeviceDto,
+    pub points: Vec<PointDto>,
+    pub mappings: Vec<PointMappingDto>,
 }
 
 /// 点位创建请求体。
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CreatePointRequest {
     pub device_id: String,
     pub key: String,
     pub data_type: String,
     pub unit: Option<String>,
+    pub tags: Option<Vec<String>>,
 }
 
 /// 点位更新请求体。
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdatePointRequest {
     pub key: Option<String>,
     pub data_type: Option<String>,
     pub unit: Option<String>,
+    /// 提供时整体替换标签（空数组表示清空）。
+    pub tags: Option<Vec<String>>,
 }
 
 /// 点位返回结构。
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PointDto {
     pub point_id: String,
@@ -303,10 +724,58 @@ pub struct PointDto {
     pub key: String,
     pub data_type: String,
     pub unit: Option<String>,
+    pub tags: Vec<String>,
+}
+
+/// 点位值写入项。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PointValueInput {
+    pub point_id: String,
+    /// 毫秒时间戳；不传时取服务端当前时间
+    pub ts_ms: Option<i64>,
+    /// 标量值：数字 / 布尔 / 字符串
+    pub value: serde_json::Value,
+    pub quality: Option<String>,
+}
+
+/// 点位值批量写入请求体（`POST /projects/{id}/points/values`）。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WritePointValuesRequest {
+    pub values: Vec<PointValueInput>,
+}
+
+/// 被流水线拒绝的点位值。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RejectedPointValueDto {
+    pub point_id: String,
+    pub ts_ms: i64,
+    /// invalid_ts | invalid_value | stale | duplicate
+    pub reason: String,
+}
+
+/// 点位值批量写入结果。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WritePointValuesDto {
+    /// 通过校验与去重并已写入的数量
+    pub accepted: usize,
+    pub rejected: Vec<RejectedPointValueDto>,
+}
+
+/// InfluxDB 行协议写入查询参数（`POST /projects/{id}/influx/write`）。
+///
+/// 1.x 的 `db` / `rp` 与 2.x 的 `org` / `bucket` 参数接受但忽略。
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct InfluxWriteQuery {
+    /// 时间戳精度：`ns`（默认）/ `us` / `ms` / `s`（兼容 `n` / `u`）
+    pub precision: Option<String>,
 }
 
 /// 点位映射创建请求体。
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CreatePointMappingRequest {
     pub point_id: String,
@@ -319,7 +788,7 @@ pub struct CreatePointMappingRequest {
 }
 
 /// 点位映射更新请求体。
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdatePointMappingRequest {
     pub source_type: Option<String>,
@@ -330,7 +799,7 @@ pub struct UpdatePointMappingRequest {
 }
 
 /// 点位映射返回结构。
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PointMappingDto {
     pub source_id: String,
@@ -343,15 +812,125 @@ pub struct PointMappingDto {
     pub protocol_detail: Option<String>,
 }
 
+/// 点位映射冲突预检请求体。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatePointMappingsRequest {
+    pub mappings: Vec<PointMappingCandidate>,
+}
+
+/// 待创建 / 待更新的点位映射地址。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PointMappingCandidate {
+    /// 更新已有映射时传入，预检时排除自身
+    pub source_id: Option<String>,
+    pub source_type: String,
+    pub address: String,
+}
+
+/// 点位映射冲突预检结果。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PointMappingValidationDto {
+    pub valid: bool,
+    pub conflicts: Vec<PointMappingConflictDto>,
+}
+
+/// 点位映射地址冲突。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PointMappingConflictDto {
+    /// 请求中候选项的下标
+    pub index: usize,
+    pub source_type: String,
+    pub address: String,
+    /// existing（已被项目内映射占用）| duplicate_in_request（与请求中更早的候选项重复）
+    pub reason: String,
+    pub existing_source_id: Option<String>,
+    pub existing_point_id: Option<String>,
+    pub duplicate_of_index: Option<usize>,
+}
+
+/// 重复点位映射报告项：同一 (sourceType, address) 下的全部映射。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PointMappingDuplicateDto {
+    pub source_type: String,
+    pub address: String,
+    pub mappings: Vec<PointMappingDto>,
+}
+
+/// 点位映射试运行请求体。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestPointMappingRequest {
+    /// 采集 topic 中的映射 ID（可选，同采集链路：优先按 ID 匹配，再按地址匹配）
+    pub source_id: Option<String>,
+    pub address: String,
+    /// 原始报文文本
+    pub payload: String,
+    /// 接收时间（毫秒），默认服务端当前时间
+    pub received_at_ms: Option<i64>,
+}
+
+/// 点位映射试运行结果（不写入任何数据）。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PointMappingTestDto {
+    pub matched: bool,
+    pub mapping: Option<PointMappingDto>,
+    /// 报文解析出的原始数值
+    pub raw_value: Option<f64>,
+    /// 应用 scale 后的数值
+    pub scaled_value: Option<f64>,
+    /// 规范化结果（应用 scale 与 offset 后）
+    pub point_value: Option<RealtimeValueDto>,
+    /// 报文无法解析时的原因
+    pub error: Option<String>,
+}
+
 /// 实时查询参数。
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RealtimeQuery {
     pub point_id: Option<String>,
+    /// 按设备过滤：返回该设备下全部点位的最新值。
+    pub device_id: Option<String>,
+    /// 按标签过滤：返回带有该标签的点位的最新值（可与 deviceId 组合）。
+    pub tag: Option<String>,
+}
+
+/// 实时订阅参数（WebSocket：`GET /projects/{id}/realtime/ws`）。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RealtimeStreamQuery {
+    /// 逗号分隔的点位 ID 列表（与 deviceId/tag 互斥）；均未提供时订阅项目内全部点位。
+    pub point_ids: Option<String>,
+    pub device_id: Option<String>,
+    pub tag: Option<String>,
+    /// 轮询间隔（毫秒），默认 1000，最小 200。
+    pub interval_ms: Option<u64>,
+}
+
+/// 字段选择查询参数（列表 / 历史查询接口）。
+///
+/// `fields` 为逗号分隔的响应字段名（camelCase），只返回所列字段，如 `fields=pointId,tsMs,value`。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldsQuery {
+    pub fields: Option<String>,
+}
+
+/// 分享令牌查询参数（实时/历史只读接口，免登录访问）。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareTokenQuery {
+    pub share_token: Option<String>,
 }
 
 /// 实时返回结构。
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RealtimeValueDto {
     pub project_id: String,
@@ -362,7 +941,7 @@ pub struct RealtimeValueDto {
 }
 
 /// 历史查询参数。
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MeasurementsQuery {
     pub point_id: String,
@@ -375,12 +954,25 @@ pub struct MeasurementsQuery {
     pub order: Option<String>,
     /// 聚合桶大小（毫秒）。提供该字段将返回聚合结果（value 为聚合值字符串，tsMs 为桶起始）。
     pub bucket_ms: Option<i64>,
+    /// 日历聚合桶：`1h`/`1d`/`1mo`，按项目时区的本地时间对齐（与 `bucketMs` 互斥）。
+    pub bucket: Option<String>,
     /// 聚合函数：`avg`/`min`/`max`/`sum`/`count`。默认 `avg`。
     pub agg: Option<String>,
 }
 
+/// 历史数据导出查询参数（流式 CSV / NDJSON）。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MeasurementExportQuery {
+    pub point_id: String,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    /// 导出格式：`csv`（默认）/ `ndjson`。
+    pub format: Option<String>,
+}
+
 /// 历史返回结构。
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MeasurementValueDto {
     pub project_id: String,
@@ -390,23 +982,341 @@ pub struct MeasurementValueDto {
     pub quality: Option<String>,
 }
 
+/// 数据覆盖率查询参数。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PointCoverageQuery {
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    /// 期望上报间隔（毫秒）。
+    pub expected_interval_ms: Option<i64>,
+}
+
+/// 数据缺失区间（左闭右开）。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoverageGapDto {
+    pub from_ms: i64,
+    pub to_ms: i64,
+}
+
+/// 数据覆盖率返回结构。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PointCoverageDto {
+    pub project_id: String,
+    pub point_id: String,
+    pub from: i64,
+    pub to: i64,
+    pub expected_interval_ms: i64,
+    pub expected_buckets: i64,
+    pub covered_buckets: i64,
+    pub completeness_pct: f64,
+    pub gaps: Vec<CoverageGapDto>,
+}
+
+/// 用能异常查询参数（`from` / `to` 按小时桶起始时刻过滤）。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnomalyQuery {
+    pub point_id: Option<String>,
+    pub device_id: Option<String>,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+/// 用能异常返回结构。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnomalyDto {
+    pub anomaly_id: String,
+    pub project_id: String,
+    pub point_id: String,
+    pub device_id: String,
+    /// 小时桶起始时刻（项目时区整点）
+    pub bucket_start_ms: i64,
+    /// 周内小时（0–167，周一 0 点为 0）
+    pub hour_of_week: i32,
+    pub actual_value: f64,
+    pub baseline_value: f64,
+    pub baseline_samples: i32,
+    /// 偏差百分比（正数为偏高）
+    pub deviation_pct: f64,
+    pub threshold_pct: f64,
+    pub detected_at_ms: i64,
+}
+
+/// 碳排放因子（kgCO₂e / 单位）。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmissionFactorDto {
+    /// electricity | gas | diesel
+    pub energy_source: String,
+    pub kg_co2e_per_unit: f64,
+    pub unit: String,
+    /// tenant（租户默认值）| project（项目覆盖）
+    pub scope: String,
+    pub project_id: Option<String>,
+    pub updated_at_ms: i64,
+}
+
+/// 排放因子写入请求体。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateEmissionFactorRequest {
+    pub kg_co2e_per_unit: f64,
+    /// 消耗量单位，默认取能源类型的默认单位（kWh / m3 / L）
+    pub unit: Option<String>,
+}
+
+/// 碳排放报表查询参数（`from` / `to` 为毫秒时间戳，`bucket` 为 1h|1d|1mo，默认 1mo）。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CarbonReportQuery {
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    pub bucket: Option<String>,
+}
+
+/// 单个能源类型的消耗与排放。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CarbonSourceUsageDto {
+    pub energy_source: String,
+    pub consumption: f64,
+    pub unit: String,
+    /// 未配置排放因子时为 null
+    pub kg_co2e_per_unit: Option<f64>,
+    pub co2e_kg: f64,
+}
+
+/// 单个周期的排放。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CarbonPeriodDto {
+    /// 周期起始时刻（项目时区日历桶）
+    pub period_start_ms: i64,
+    pub sources: Vec<CarbonSourceUsageDto>,
+    pub co2e_kg: f64,
+}
+
+/// 碳排放报表。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CarbonReportDto {
+    pub project_id: String,
+    pub from: i64,
+    pub to: i64,
+    pub bucket: String,
+    pub timezone: String,
+    pub periods: Vec<CarbonPeriodDto>,
+    /// 窗口内各能源类型合计
+    pub totals: Vec<CarbonSourceUsageDto>,
+    pub total_co2e_kg: f64,
+    /// 有消耗但未配置排放因子的能源类型
+    pub missing_factors: Vec<String>,
+}
+
+/// 电能质量报表查询参数（`from` / `to` 为毫秒时间戳；需量窗口默认 15 分钟，子区间默认 5 分钟）。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PowerQualityQuery {
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    pub device_id: Option<String>,
+    pub window_minutes: Option<i64>,
+    pub subinterval_minutes: Option<i64>,
+}
+
+/// 单台设备的电能质量指标（无法计算的指标为 null）。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DevicePowerQualityDto {
+    pub device_id: String,
+    pub kw_point_id: Option<String>,
+    pub kva_point_id: Option<String>,
+    pub kwh_point_id: Option<String>,
+    /// 参与计算的功率子区间数
+    pub samples: usize,
+    pub energy_kwh: Option<f64>,
+    pub average_demand_kw: Option<f64>,
+    pub peak_demand_kw: Option<f64>,
+    /// 峰值需量窗口起始时刻
+    pub peak_demand_at_ms: Option<i64>,
+    /// 负荷率（平均需量 / 峰值需量）
+    pub load_factor: Option<f64>,
+    pub power_factor: Option<f64>,
+    pub min_power_factor: Option<f64>,
+}
+
+/// 电能质量报表。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PowerQualityReportDto {
+    pub project_id: String,
+    pub from: i64,
+    pub to: i64,
+    pub window_minutes: i64,
+    pub subinterval_minutes: i64,
+    pub devices: Vec<DevicePowerQualityDto>,
+}
+
+/// 后台任务查询参数。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobQuery {
+    pub project_id: Option<String>,
+    /// 任务类型（如 `report.power_quality`）。
+    pub kind: Option<String>,
+    /// 任务状态：queued / running / succeeded / failed / cancelled。
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// 后台任务返回结构。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobDto {
+    pub job_id: String,
+    /// 租户级任务为 null。
+    pub project_id: Option<String>,
+    pub kind: String,
+    /// queued / running / succeeded / failed / cancelled
+    pub status: String,
+    pub params: serde_json::Value,
+    /// 进度（0-100）
+    pub progress: i32,
+    /// 执行结果（仅 succeeded）
+    pub result: Option<serde_json::Value>,
+    /// 失败原因（仅 failed）
+    pub error: Option<String>,
+    pub cancel_requested: bool,
+    pub created_by: String,
+    pub created_at_ms: i64,
+    pub started_at_ms: Option<i64>,
+    pub finished_at_ms: Option<i64>,
+    pub updated_at_ms: i64,
+}
+
+/// 用量报表查询参数（`from` / `to` 为毫秒时间戳，默认当日 UTC）。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageQuery {
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+/// 单日用量。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageDailyDto {
+    /// 自然日起始时刻（UTC）
+    pub period_start_ms: i64,
+    pub count: i64,
+}
+
+/// 单个指标的用量与配额。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageMetricDto {
+    /// points | measurements | api_calls | commands
+    pub metric: String,
+    /// 查询窗口内累计用量（points 为当前点位数）
+    pub total: i64,
+    /// 与配额比较的当前值：points 为当前点位数，其余为当日用量
+    pub current: i64,
+    /// 未配置配额时为 null（不限制）
+    pub limit: Option<i64>,
+    /// 按日明细（points 为空）
+    pub daily: Vec<UsageDailyDto>,
+}
+
+/// 租户用量报表。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageReportDto {
+    pub tenant_id: String,
+    pub from: i64,
+    pub to: i64,
+    pub metrics: Vec<UsageMetricDto>,
+}
+
+/// 租户配额。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaDto {
+    pub metric: String,
+    pub limit: i64,
+    pub updated_at_ms: i64,
+}
+
+/// 配额写入请求体。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateQuotaRequest {
+    pub limit: i64,
+}
+
 /// 命令创建请求体。
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateCommandRequest {
     pub target: String,
     pub payload: serde_json::Value,
+    /// 仅校验并渲染将要发布的主题与载荷，不创建命令、不发布。
+    #[serde(default)]
+    pub dry_run: bool,
 }
 
 /// 命令查询参数。
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CommandQuery {
     pub limit: Option<i64>,
+    /// 按状态过滤：issued/accepted/success/failed/timeout。
+    pub status: Option<String>,
+    /// 按下发目标精确过滤。
+    pub target: Option<String>,
+    /// 按下发人（用户 ID）过滤。
+    pub issued_by: Option<String>,
+    /// 下发时间范围（毫秒，闭区间）。
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    /// 可选游标：上一页最后一条的 `issuedAtMs`，结果按下发时间倒序。
+    pub cursor_ts_ms: Option<i64>,
+    /// 可选游标：上一页最后一条的 `commandId`（与 `cursorTsMs` 配合处理同一时间戳）。
+    pub cursor_command_id: Option<String>,
+}
+
+/// 命令统计查询参数。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandStatsQuery {
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+/// 命令状态计数。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandStatusCountDto {
+    pub status: String,
+    pub count: i64,
+}
+
+/// 命令统计返回结构（时间窗口内按状态计数）。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandStatsDto {
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    pub total: i64,
+    pub by_status: Vec<CommandStatusCountDto>,
 }
 
 /// 命令返回结构。
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CommandDto {
     pub command_id: String,
@@ -416,10 +1326,39 @@ pub struct CommandDto {
     pub status: String,
     pub issued_by: String,
     pub issued_at_ms: i64,
+    /// 提示信息（如人工命令覆盖目标的维护窗口）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+}
+
+/// 命令 dry-run 返回结构（未创建命令、未发布）。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandDryRunDto {
+    pub dry_run: bool,
+    /// 本次预览生成的命令 ID（未保存）
+    pub command_id: String,
+    pub project_id: String,
+    pub target: String,
+    /// 目标类型：gateway / device
+    pub target_type: String,
+    /// 目标所属网关（目标为网关时即其自身）
+    pub gateway_id: String,
+    /// 发布主题；未启用 MQTT 下发时为 null
+    pub topic: Option<String>,
+    /// 载荷格式：envelope / raw / template / hex
+    pub payload_format: String,
+    /// 载荷文本（非 UTF-8 的二进制载荷为 null）
+    pub payload: Option<String>,
+    /// 载荷十六进制
+    pub payload_hex: String,
+    pub issued_at_ms: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
 }
 
 /// 命令回执返回结构。
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CommandReceiptDto {
     pub receipt_id: String,
@@ -431,7 +1370,7 @@ pub struct CommandReceiptDto {
 }
 
 /// 审计查询参数。
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AuditLogQuery {
     pub from: Option<i64>,
@@ -440,7 +1379,7 @@ pub struct AuditLogQuery {
 }
 
 /// 审计日志返回结构。
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AuditLogDto {
     pub audit_id: String,
@@ -453,27 +1392,359 @@ pub struct AuditLogDto {
     pub ts_ms: i64,
 }
 
-/// Telemetry 指标快照（MVP，聚合计数）。
-#[derive(Debug, Serialize)]
+/// 审计哈希链校验结果。
+///
+/// `lastSeq` / `lastHash` 可由调用方留存作为锚点：下次校验时若末尾序号回退或摘要变化，
+/// 说明链尾记录被截断或替换。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditChainVerificationDto {
+    pub verified: bool,
+    /// 已校验通过的节点数
+    pub checked: i64,
+    pub last_seq: i64,
+    pub last_hash: Option<String>,
+    pub failure: Option<AuditChainFailureDto>,
+}
+
+/// 审计哈希链校验失败位置。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditChainFailureDto {
+    /// 期望的序号
+    pub seq: i64,
+    /// 实际读到的记录 ID
+    pub audit_id: String,
+    /// gap | broken_link | hash_mismatch
+    pub reason: String,
+}
+
+/// 分享令牌创建请求体。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateShareTokenRequest {
+    pub name: String,
+    /// 授权范围：realtime | measurements；不传时授予全部
+    pub scopes: Option<Vec<String>>,
+    /// 有效期（秒），默认 30 天，最长 365 天
+    pub expires_in_seconds: Option<i64>,
+}
+
+/// 分享令牌返回结构。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareTokenDto {
+    pub token_id: String,
+    pub project_id: String,
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub expires_at_ms: i64,
+    pub revoked_at_ms: Option<i64>,
+    /// 令牌明文（仅创建时返回）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    pub created_by: String,
+    pub created_at_ms: i64,
+}
+
+/// Webhook 订阅创建请求体。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateWebhookRequest {
+    /// 推送地址（http/https）
+    pub url: String,
+    /// 订阅的事件类型：device.offline | command.completed | alarm.raised | gateway.created
+    pub event_types: Vec<String>,
+    /// 签名密钥；不传则由服务端生成
+    pub secret: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+/// Webhook 订阅返回结构。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookSubscriptionDto {
+    pub subscription_id: String,
+    pub project_id: String,
+    pub url: String,
+    pub event_types: Vec<String>,
+    pub enabled: bool,
+    /// 签名密钥（仅创建时返回）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    pub created_by: String,
+    pub created_at_ms: i64,
+}
+
+/// Webhook 推送日志查询参数。
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct MetricsSnapshotDto {
+pub struct WebhookDeliveryQuery {
+    pub subscription_id: Option<String>,
+    /// success | failed
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// Webhook 推送日志返回结构。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookDeliveryDto {
+    pub delivery_id: String,
+    pub subscription_id: String,
+    pub event_id: String,
+    pub event_type: String,
+    /// success | failed
+    pub status: String,
+    pub attempts: i32,
+    pub response_status: Option<i32>,
+    pub error: Option<String>,
+    pub created_at_ms: i64,
+}
+
+/// 自动化规则创建请求体。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateRuleRequest {
+    pub name: String,
+    /// 触发条件：`{"type": "point" | "schedule" | "alarm" | "device_offline", ...}`
+    pub trigger: serde_json::Value,
+    /// 动作列表：`[{"type": "command" | "alarm" | "webhook", ...}]`
+    pub actions: Vec<serde_json::Value>,
+    pub enabled: Option<bool>,
+}
+
+/// 自动化规则更新请求体（未传字段保持不变）。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateRuleRequest {
+    pub name: Option<String>,
+    pub trigger: Option<serde_json::Value>,
+    pub actions: Option<Vec<serde_json::Value>>,
+    pub enabled: Option<bool>,
+}
+
+/// 自动化规则返回结构。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleDto {
+    pub rule_id: String,
+    pub project_id: String,
+    pub name: String,
+    pub enabled: bool,
+    pub trigger: serde_json::Value,
+    pub actions: serde_json::Value,
+    pub created_by: String,
+    pub created_at_ms: i64,
+    pub updated_at_ms: i64,
+}
+
+/// 自动化规则执行记录查询参数。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleExecutionQuery {
+    pub limit: Option<i64>,
+}
+
+/// 自动化规则执行记录返回结构。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleExecutionDto {
+    pub execution_id: String,
+    pub rule_id: String,
+    /// 触发详情（如触发时的点位值、告警事件 ID）
+    pub trigger: serde_json::Value,
+    /// success | failed
+    pub status: String,
+    /// 按动作顺序的执行结果（`type`、`status`，以及 `commandId` / `eventId` / `error` 等）
+    pub results: serde_json::Value,
+    pub executed_at_ms: i64,
+}
+
+/// 控制计划创建请求体。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateScheduleRequest {
+    pub name: String,
+    /// 计划定义：`{"type": "commands" | "setpoint_profile", ...}`
+    pub spec: serde_json::Value,
+    /// IANA 时区名（默认取项目时区）
+    pub timezone: Option<String>,
+    /// 错过执行策略：skip（默认）| run_once | run_all
+    pub missed_run_policy: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+/// 控制计划更新请求体（未传字段保持不变）。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateScheduleRequest {
+    pub name: Option<String>,
+    pub spec: Option<serde_json::Value>,
+    pub timezone: Option<String>,
+    pub missed_run_policy: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+/// 控制计划返回结构。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleDto {
+    pub schedule_id: String,
+    pub project_id: String,
+    pub name: String,
+    pub enabled: bool,
+    pub timezone: String,
+    pub spec: serde_json::Value,
+    pub missed_run_policy: String,
+    /// 下一个计划时刻（停用时为空）
+    pub next_run_at_ms: Option<i64>,
+    pub last_evaluated_at_ms: i64,
+    pub created_by: String,
+    pub created_at_ms: i64,
+    pub updated_at_ms: i64,
+}
+
+/// 控制计划执行记录查询参数。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleExecutionQuery {
+    pub limit: Option<i64>,
+}
+
+/// 控制计划执行记录返回结构。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleExecutionDto {
+    pub execution_id: String,
+    pub schedule_id: String,
+    pub scheduled_at_ms: i64,
+    /// success | failed | skipped
+    pub status: String,
+    /// 按命令顺序的执行结果（`target`、`status`，以及 `commandId` / `error`；跳过时为 `missedRuns`）
+    pub results: serde_json::Value,
+    pub executed_at_ms: i64,
+}
+
+/// 可削减负荷配置请求体（PUT 整体替换）。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpsertSheddableLoadRequest {
+    /// 数值越小越先削减
+    pub priority: i32,
+    /// 设备有功功率点位（kW），未传时取设备下带 `power` 标签的点位
+    pub power_point_id: Option<String>,
+    /// 实时功率缺失时的估算值（kW）
+    pub rated_kw: f64,
+    /// 削减命令载荷（JSON 对象）
+    pub shed_payload: serde_json::Value,
+    /// 恢复命令载荷（JSON 对象）
+    pub restore_payload: serde_json::Value,
+    pub enabled: Option<bool>,
+}
+
+/// 可削减负荷返回结构。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SheddableLoadDto {
+    pub device_id: String,
+    pub project_id: String,
+    pub priority: i32,
+    pub power_point_id: String,
+    pub rated_kw: f64,
+    pub shed_payload: serde_json::Value,
+    pub restore_payload: serde_json::Value,
+    pub enabled: bool,
+    pub created_at_ms: i64,
+    pub updated_at_ms: i64,
+}
+
+/// 需求响应事件创建请求体。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateDemandResponseEventRequest {
+    /// 目标削减量（kW）
+    pub target_kw: f64,
+    /// 开始时间（默认立即开始）
+    pub start_at_ms: Option<i64>,
+    pub end_at_ms: i64,
+}
+
+/// 需求响应事件查询参数。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DemandResponseEventQuery {
+    pub limit: Option<i64>,
+}
+
+/// 需求响应事件返回结构。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DemandResponseEventDto {
+    pub event_id: String,
+    pub project_id: String,
+    pub target_kw: f64,
+    pub start_at_ms: i64,
+    pub end_at_ms: i64,
+    /// scheduled | active | completed | cancelled | expired
+    pub status: String,
+    pub cancel_requested: bool,
+    /// 削减前已选负荷的功率合计（kW）
+    pub baseline_kw: Option<f64>,
+    /// 按实时功率计算的实际削减量（kW）
+    pub achieved_kw: Option<f64>,
+    /// 已选负荷（`deviceId`、`priority`、`baselineKw`、`currentKw`、`shedCommandId`、`restoreCommandId`，失败时含 `error`）
+    pub loads: serde_json::Value,
+    pub created_by: String,
+    pub created_at_ms: i64,
+    pub updated_at_ms: i64,
+}
+
+/// 当前租户的指标计数（`GET /metrics`；仅包含带租户标记的指标，全局计数见运维端口）。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TenantMetricsDto {
+    pub tenant_id: String,
     pub raw_events: u64,
-    pub normalized_values: u64,
     pub write_success: u64,
-    pub write_failure: u64,
-    pub dropped_duplicate: u64,
-    pub dropped_invalid: u64,
-    pub dropped_stale: u64,
-    pub dropped_unmapped: u64,
-    pub backpressure: u64,
-    pub write_latency_ms_total: u64,
-    pub write_latency_ms_count: u64,
-    pub end_to_end_latency_ms_total: u64,
-    pub end_to_end_latency_ms_count: u64,
     pub commands_issued: u64,
-    pub command_dispatch_success: u64,
-    pub command_dispatch_failure: u64,
-    pub command_issue_latency_ms_total: u64,
-    pub command_issue_latency_ms_count: u64,
     pub receipts_processed: u64,
 }
+
+/// 生效配置项（已脱敏，标注来源）。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigEntryDto {
+    /// 配置字段名（如 `mqtt_host`）
+    pub field: String,
+    /// 对应的环境变量名（如 `EMS_MQTT_HOST`）
+    pub env: String,
+    pub value: serde_json::Value,
+    /// 来源：env / secret_file / secret_provider / file / default
+    pub source: String,
+    /// 是否使用默认值
+    pub is_default: bool,
+}
+
+/// 租户功能开关（已合并默认值）。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeatureFlagDto {
+    pub flag_key: String,
+    pub enabled: bool,
+    pub variant: Option<String>,
+    /// 是否为内置开关（control / graphql / webhooks）
+    pub builtin: bool,
+    /// 未配置、取默认值
+    pub is_default: bool,
+    pub updated_at: Option<i64>,
+}
+
+/// 功能开关写入请求体。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateFeatureFlagRequest {
+    pub enabled: bool,
+    /// 可选灰度变体（如 `v2`）
+    pub variant: Option<String>,
+}
@@ -0,0 +1,23 @@
+/// 租户级功能开关（feature flag）键。
+///
+/// 内置开关默认开启（未配置时保持现有行为），可按租户关闭；
+/// 其余键视为灰度/测试功能，默认关闭，需按租户显式开启。
+pub const CONTROL: &str = "control";
+pub const GRAPHQL: &str = "graphql";
+pub const WEBHOOKS: &str = "webhooks";
+
+pub const BUILTIN_FLAGS: [&str; 3] = [CONTROL, GRAPHQL, WEBHOOKS];
+
+/// 开关未配置时的默认取值。
+pub fn default_enabled(flag_key: &str) -> bool {
+    BUILTIN_FLAGS.contains(&flag_key)
+}
+
+/// 开关键格式：小写字母开头，仅含小写字母、数字、`.`、`_`、`-`，长度不超过 64。
+pub fn is_valid_flag_key(flag_key: &str) -> bool {
+    flag_key.len() <= 64
+        && flag_key.starts_with(|ch: char| ch.is_ascii_lowercase())
+        && flag_key.chars().all(|ch| {
+            ch.is_ascii_lowercase() || ch.is_ascii_digit() || matches!(ch, '.' | '_' | '-')
+        })
+}
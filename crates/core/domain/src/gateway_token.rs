@@ -0,0 +1,9 @@
+/// 网关回调令牌（只能经 HTTP 回调的网关 / 设备使用）。
+///
+/// 令牌绑定单个网关，只能为该网关及其下设备的命令写入回执，不具备任何用户权限；
+/// 令牌明文以固定前缀开头，便于与登录 access_token、分享令牌区分。
+pub const GATEWAY_TOKEN_PREFIX: &str = "ems_gw_";
+
+pub fn is_gateway_token(token: &str) -> bool {
+    token.starts_with(GATEWAY_TOKEN_PREFIX)
+}
@@ -1,5 +1,9 @@
 pub mod data;
+pub mod features;
+pub mod gateway_token;
 pub mod permissions;
+pub mod share;
+pub mod usage;
 
 pub use data::{PointValue, PointValueData, RawEvent};
 
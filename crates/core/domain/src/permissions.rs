@@ -5,12 +5,15 @@ pub const PROJECT_READ: &str = "PROJECT.READ";
 pub const PROJECT_WRITE: &str = "PROJECT.WRITE";
 pub const ASSET_GATEWAY_READ: &str = "ASSET.GATEWAY.READ";
 pub const ASSET_GATEWAY_WRITE: &str = "ASSET.GATEWAY.WRITE";
+pub const ASSET_FIRMWARE_READ: &str = "ASSET.FIRMWARE.READ";
+pub const ASSET_FIRMWARE_WRITE: &str = "ASSET.FIRMWARE.WRITE";
 pub const ASSET_DEVICE_READ: &str = "ASSET.DEVICE.READ";
 pub const ASSET_DEVICE_WRITE: &str = "ASSET.DEVICE.WRITE";
 pub const ASSET_POINT_READ: &str = "ASSET.POINT.READ";
 pub const ASSET_POINT_WRITE: &str = "ASSET.POINT.WRITE";
 pub const DATA_REALTIME_READ: &str = "DATA.REALTIME.READ";
 pub const DATA_MEASUREMENTS_READ: &str = "DATA.MEASUREMENTS.READ";
+pub const DATA_WRITE: &str = "DATA.WRITE";
 pub const CONTROL_COMMAND_ISSUE: &str = "CONTROL.COMMAND.ISSUE";
 pub const CONTROL_COMMAND_READ: &str = "CONTROL.COMMAND.READ";
 pub const ALARM_RULE_READ: &str = "ALARM.RULE.READ";
@@ -22,19 +25,46 @@ pub const RBAC_USER_WRITE: &str = "RBAC.USER.WRITE";
 pub const RBAC_ROLE_READ: &str = "RBAC.ROLE.READ";
 pub const RBAC_ROLE_WRITE: &str = "RBAC.ROLE.WRITE";
 
-pub const SYSTEM_METRICS_READ: &str = "SYSTEM.METRICS.READ";
+pub const OPS_METRICS_READ: &str = "OPS.METRICS.READ";
 
-pub const PERMISSION_CODES: [&str; 20] = [
+pub const FEATURE_FLAG_READ: &str = "FEATURE.FLAG.READ";
+
+pub const AUTOMATION_RULE_READ: &str = "AUTOMATION.RULE.READ";
+pub const AUTOMATION_RULE_WRITE: &str = "AUTOMATION.RULE.WRITE";
+pub const AUTOMATION_SCHEDULE_READ: &str = "AUTOMATION.SCHEDULE.READ";
+pub const AUTOMATION_SCHEDULE_WRITE: &str = "AUTOMATION.SCHEDULE.WRITE";
+
+pub const CONTROL_DEMAND_RESPONSE_READ: &str = "CONTROL.DEMAND_RESPONSE.READ";
+pub const CONTROL_DEMAND_RESPONSE_WRITE: &str = "CONTROL.DEMAND_RESPONSE.WRITE";
+
+pub const CARBON_FACTOR_READ: &str = "CARBON.FACTOR.READ";
+pub const CARBON_FACTOR_WRITE: &str = "CARBON.FACTOR.WRITE";
+
+pub const USAGE_QUOTA_READ: &str = "USAGE.QUOTA.READ";
+
+pub const PORTFOLIO_READ: &str = "PORTFOLIO.READ";
+pub const PORTFOLIO_WRITE: &str = "PORTFOLIO.WRITE";
+
+pub const SHARE_TOKEN_READ: &str = "SHARE.TOKEN.READ";
+pub const SHARE_TOKEN_WRITE: &str = "SHARE.TOKEN.WRITE";
+
+pub const JOB_READ: &str = "JOB.READ";
+pub const JOB_WRITE: &str = "JOB.WRITE";
+
+pub const PERMISSION_CODES: [&str; 39] = [
     PROJECT_READ,
     PROJECT_WRITE,
     ASSET_GATEWAY_READ,
     ASSET_GATEWAY_WRITE,
+    ASSET_FIRMWARE_READ,
+    ASSET_FIRMWARE_WRITE,
     ASSET_DEVICE_READ,
     ASSET_DEVICE_WRITE,
     ASSET_POINT_READ,
     ASSET_POINT_WRITE,
     DATA_REALTIME_READ,
     DATA_MEASUREMENTS_READ,
+    DATA_WRITE,
     CONTROL_COMMAND_ISSUE,
     CONTROL_COMMAND_READ,
     ALARM_RULE_READ,
@@ -44,5 +74,21 @@ pub const PERMISSION_CODES: [&str; 20] = [
     RBAC_USER_WRITE,
     RBAC_ROLE_READ,
     RBAC_ROLE_WRITE,
-    SYSTEM_METRICS_READ,
+    OPS_METRICS_READ,
+    FEATURE_FLAG_READ,
+    AUTOMATION_RULE_READ,
+    AUTOMATION_RULE_WRITE,
+    AUTOMATION_SCHEDULE_READ,
+    AUTOMATION_SCHEDULE_WRITE,
+    CONTROL_DEMAND_RESPONSE_READ,
+    CONTROL_DEMAND_RESPONSE_WRITE,
+    CARBON_FACTOR_READ,
+    CARBON_FACTOR_WRITE,
+    USAGE_QUOTA_READ,
+    PORTFOLIO_READ,
+    PORTFOLIO_WRITE,
+    SHARE_TOKEN_READ,
+    SHARE_TOKEN_WRITE,
+    JOB_READ,
+    JOB_WRITE,
 ];
@@ -0,0 +1,27 @@
+use crate::permissions;
+
+/// 只读数据分享令牌（看板嵌入、大屏展示）。
+///
+/// 分享令牌绑定单个项目，按范围授予只读数据权限，无需登录；
+/// 令牌明文以固定前缀开头，便于与登录 access_token 区分。
+pub const SHARE_TOKEN_PREFIX: &str = "ems_share_";
+
+/// 实时数据（最新值查询与 WebSocket 订阅）
+pub const REALTIME: &str = "realtime";
+/// 历史测量数据
+pub const MEASUREMENTS: &str = "measurements";
+
+pub const SCOPES: [&str; 2] = [REALTIME, MEASUREMENTS];
+
+pub fn is_share_token(token: &str) -> bool {
+    token.starts_with(SHARE_TOKEN_PREFIX)
+}
+
+/// 分享范围对应的权限码
+pub fn scope_permission(scope: &str) -> Option<&'static str> {
+    match scope {
+        REALTIME => Some(permissions::DATA_REALTIME_READ),
+        MEASUREMENTS => Some(permissions::DATA_MEASUREMENTS_READ),
+        _ => None,
+    }
+}
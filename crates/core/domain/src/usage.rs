@@ -0,0 +1,25 @@
+/// 租户用量计量指标与配额。
+///
+/// 计数类指标按 UTC 自然日累计（配额为每日上限）；`points` 为当前点位总数（配额为总数上限）。
+/// 未配置配额的指标不限制。
+pub const API_CALLS: &str = "api_calls";
+pub const COMMANDS: &str = "commands";
+pub const MEASUREMENTS: &str = "measurements";
+pub const POINTS: &str = "points";
+
+pub const METRICS: [&str; 4] = [API_CALLS, COMMANDS, MEASUREMENTS, POINTS];
+
+/// 按日累计的计数类指标。
+pub const DAILY_METRICS: [&str; 3] = [API_CALLS, COMMANDS, MEASUREMENTS];
+
+/// 一天（毫秒）。
+pub const DAY_MS: i64 = 24 * 3600 * 1000;
+
+pub fn is_valid_metric(metric: &str) -> bool {
+    METRICS.contains(&metric)
+}
+
+/// 时刻所在 UTC 自然日的起始时刻。
+pub fn day_start_ms(ts_ms: i64) -> i64 {
+    ts_ms.div_euclid(DAY_MS) * DAY_MS
+}
@@ -0,0 +1,43 @@
+//! 客户端错误类型。
+
+/// ems-client 统一错误。
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    /// 网络或 HTTP 层错误（连接失败、超时等）
+    #[error("http error: {0}")]
+    Http(#[from] reqwest::Error),
+    /// 服务端返回失败响应（`success: false` 或非 2xx）
+    #[error("api error {status} {code}: {message}")]
+    Api {
+        status: u16,
+        code: String,
+        message: String,
+    },
+    /// 响应体无法解析为预期 DTO
+    #[error("decode error: {0}")]
+    Decode(String),
+    /// WebSocket 握手或传输错误，以及服务端异常关闭
+    #[error("websocket error: {0}")]
+    WebSocket(String),
+    /// 需要 token 的操作在登录前调用
+    #[error("not logged in")]
+    NotLoggedIn,
+}
+
+impl ClientError {
+    /// HTTP 状态码（仅 `Api` 错误）
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            ClientError::Api { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// 稳定错误码（见 `api_contract::error_codes`，仅 `Api` 错误）
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            ClientError::Api { code, .. } => Some(code),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,360 @@
+//! # EMS Rust 客户端（ems-client）
+//!
+//! 封装 EMS HTTP API，供内部服务与客户脚本直接调用，无需手写 reqwest 请求：
+//! - 登录与 token 管理：请求返回 401 时自动用 refresh token 换取新 token 并重试一次
+//! - 请求 / 响应类型直接复用 `api_contract` 中的 DTO
+//! - 分页：历史数据与命令列表按 keyset 游标逐页拉取（[`MeasurementPager`] / [`CommandPager`]）
+//! - 实时订阅：WebSocket `realtime/ws`（[`RealtimeSubscription`]）
+//!
+//! 所有请求使用 `/api/v1` 版本前缀。
+
+mod error;
+mod pager;
+mod realtime;
+
+use std::sync::{Arc, Mutex};
+
+use reqwest::{Method, RequestBuilder, Response, StatusCode};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use api_contract::{
+    ApiResponse, CommandDto, CommandQuery, CreateCommandRequest, CreateProjectRequest, DeviceDto,
+    GatewayDto, LoginRequest, LoginResponse, MeasurementsQuery, PointDto, ProjectDto,
+    RealtimeQuery, RealtimeStreamQuery, RealtimeValueDto, RefreshTokenRequest,
+    RefreshTokenResponse,
+};
+
+pub use api_contract;
+pub use error::ClientError;
+pub use pager::{CommandPager, MeasurementPager, Pager};
+pub use realtime::RealtimeSubscription;
+
+/// API 版本前缀
+const API_PREFIX: &str = "/api/v1";
+
+/// 当前会话 token
+#[derive(Debug, Clone)]
+struct Tokens {
+    access_token: String,
+    refresh_token: Option<String>,
+}
+
+/// EMS API 客户端（`Clone` 后共享同一会话）。
+#[derive(Clone)]
+pub struct EmsClient {
+    http: reqwest::Client,
+    base_url: String,
+    tokens: Arc<Mutex<Option<Tokens>>>,
+    /// 串行化 token 刷新（refresh token 轮换后旧值即失效）
+    refresh_lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl EmsClient {
+    /// 创建客户端，`base_url` 如 `http://127.0.0.1:8080`
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self::with_http_client(base_url, reqwest::Client::new())
+    }
+
+    /// 使用自定义 reqwest 客户端（超时、代理、证书等）
+    pub fn with_http_client(base_url: impl Into<String>, http: reqwest::Client) -> Self {
+        Self {
+            http,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            tokens: Arc::new(Mutex::new(None)),
+            refresh_lock: Arc::new(tokio::sync::Mutex::new(())),
+        }
+    }
+
+    /// 使用已有 token（如服务账号签发的 token），无需登录
+    pub fn with_tokens(
+        self,
+        access_token: impl Into<String>,
+        refresh_token: Option<String>,
+    ) -> Self {
+        self.store_tokens(access_token.into(), refresh_token);
+        self
+    }
+
+    /// 当前 access token
+    pub fn access_token(&self) -> Option<String> {
+        self.lock_tokens()
+            .as_ref()
+            .map(|tokens| tokens.access_token.clone())
+    }
+
+    /// 登录并保存 token
+    pub async fn login(
+        &self,
+        username: &str,
+        password: &str,
+    ) -> Result<LoginResponse, ClientError> {
+        let request = LoginRequest {
+            username: username.to_string(),
+            password: password.to_string(),
+        };
+        let response = self
+            .http
+            .post(self.url("/login"))
+            .json(&request)
+            .send()
+            .await?;
+        let login: LoginResponse = decode(response).await?;
+        self.store_tokens(
+            login.access_token.clone(),
+            Some(login.refresh_token.clone()),
+        );
+        Ok(login)
+    }
+
+    /// 用 refresh token 换取新 token（服务端轮换 refresh token）
+    pub async fn refresh(&self) -> Result<(), ClientError> {
+        let _guard = self.refresh_lock.lock().await;
+        self.refresh_locked().await
+    }
+
+    // ------------------------------------------------------------------
+    // 通用请求（path 不含 /api/v1 前缀，如 `/projects`）
+    // ------------------------------------------------------------------
+
+    pub async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, ClientError> {
+        self.execute(|| self.request(Method::GET, path)).await
+    }
+
+    pub async fn get_with_query<Q, T>(&self, path: &str, query: &Q) -> Result<T, ClientError>
+    where
+        Q: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        self.execute(|| self.request(Method::GET, path).query(query))
+            .await
+    }
+
+    pub async fn post<B, T>(&self, path: &str, body: &B) -> Result<T, ClientError>
+    where
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        self.execute(|| self.request(Method::POST, path).json(body))
+            .await
+    }
+
+    pub async fn put<B, T>(&self, path: &str, body: &B) -> Result<T, ClientError>
+    where
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        self.execute(|| self.request(Method::PUT, path).json(body))
+            .await
+    }
+
+    pub async fn delete(&self, path: &str) -> Result<(), ClientError> {
+        self.execute(|| self.request(Method::DELETE, path)).await
+    }
+
+    // ------------------------------------------------------------------
+    // 常用接口
+    // ------------------------------------------------------------------
+
+    pub async fn list_projects(&self) -> Result<Vec<ProjectDto>, ClientError> {
+        self.get("/projects").await
+    }
+
+    pub async fn create_project(
+        &self,
+        request: &CreateProjectRequest,
+    ) -> Result<ProjectDto, ClientError> {
+        self.post("/projects", request).await
+    }
+
+    pub async fn list_gateways(&self, project_id: &str) -> Result<Vec<GatewayDto>, ClientError> {
+        self.get(&format!("/projects/{project_id}/gateways")).await
+    }
+
+    pub async fn list_devices(&self, project_id: &str) -> Result<Vec<DeviceDto>, ClientError> {
+        self.get(&format!("/projects/{project_id}/devices")).await
+    }
+
+    pub async fn list_points(&self, project_id: &str) -> Result<Vec<PointDto>, ClientError> {
+        self.get(&format!("/projects/{project_id}/points")).await
+    }
+
+    /// 最新值快照
+    pub async fn realtime(
+        &self,
+        project_id: &str,
+        query: &RealtimeQuery,
+    ) -> Result<Vec<RealtimeValueDto>, ClientError> {
+        self.get_with_query(&format!("/projects/{project_id}/realtime"), query)
+            .await
+    }
+
+    /// 历史数据分页器（`query.limit` 为页大小，默认 1000）
+    pub fn measurements(&self, project_id: &str, query: MeasurementsQuery) -> MeasurementPager {
+        MeasurementPager::measurements(self.clone(), project_id, query)
+    }
+
+    /// 下发命令
+    pub async fn create_command(
+        &self,
+        project_id: &str,
+        request: &CreateCommandRequest,
+    ) -> Result<CommandDto, ClientError> {
+        self.post(&format!("/projects/{project_id}/commands"), request)
+            .await
+    }
+
+    /// 命令分页器（按下发时间倒序，`query.limit` 为页大小，默认 100）
+    pub fn commands(&self, project_id: &str, query: CommandQuery) -> CommandPager {
+        CommandPager::commands(self.clone(), project_id, query)
+    }
+
+    /// 订阅实时值（WebSocket）；握手返回 401 时刷新 token 后重连一次
+    pub async fn subscribe_realtime(
+        &self,
+        project_id: &str,
+        query: &RealtimeStreamQuery,
+    ) -> Result<RealtimeSubscription, ClientError> {
+        let mut url = self
+            .http
+            .get(self.url(&format!("/projects/{project_id}/realtime/ws")))
+            .query(query)
+            .build()?
+            .url()
+            .clone();
+        let scheme = if url.scheme() == "https" { "wss" } else { "ws" };
+        url.set_scheme(scheme)
+            .map_err(|_| ClientError::WebSocket(format!("invalid base url: {}", self.base_url)))?;
+
+        let token = self.access_token();
+        match RealtimeSubscription::connect(url.as_str(), token.as_deref()).await {
+            Ok(subscription) => Ok(subscription),
+            Err(tokio_tungstenite::tungstenite::Error::Http(response))
+                if response.status() == StatusCode::UNAUTHORIZED.as_u16()
+                    && self.has_refresh_token() =>
+            {
+                self.refresh_after(token.as_deref()).await?;
+                let token = self.access_token();
+                RealtimeSubscription::connect(url.as_str(), token.as_deref())
+                    .await
+                    .map_err(|err| ClientError::WebSocket(err.to_string()))
+            }
+            Err(err) => Err(ClientError::WebSocket(err.to_string())),
+        }
+    }
+
+    // ------------------------------------------------------------------
+    // 内部实现
+    // ------------------------------------------------------------------
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{API_PREFIX}{path}", self.base_url)
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.http.request(method, self.url(path))
+    }
+
+    fn lock_tokens(&self) -> std::sync::MutexGuard<'_, Option<Tokens>> {
+        self.tokens
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn store_tokens(&self, access_token: String, refresh_token: Option<String>) {
+        *self.lock_tokens() = Some(Tokens {
+            access_token,
+            refresh_token,
+        });
+    }
+
+    fn has_refresh_token(&self) -> bool {
+        self.lock_tokens()
+            .as_ref()
+            .is_some_and(|tokens| tokens.refresh_token.is_some())
+    }
+
+    /// 发送请求（附带 access token）；401 时刷新 token 并重试一次
+    async fn execute<T, F>(&self, build: F) -> Result<T, ClientError>
+    where
+        T: DeserializeOwned,
+        F: Fn() -> RequestBuilder,
+    {
+        let token = self.access_token();
+        let response = with_bearer(build(), token.as_deref()).send().await?;
+        if response.status() != StatusCode::UNAUTHORIZED || !self.has_refresh_token() {
+            return decode(response).await;
+        }
+        self.refresh_after(token.as_deref()).await?;
+        let token = self.access_token();
+        let response = with_bearer(build(), token.as_deref()).send().await?;
+        decode(response).await
+    }
+
+    /// 刷新 token；若等待期间其他请求已完成刷新（access token 已变化）则直接返回
+    async fn refresh_after(&self, stale_token: Option<&str>) -> Result<(), ClientError> {
+        let _guard = self.refresh_lock.lock().await;
+        if self.access_token().as_deref() != stale_token {
+            return Ok(());
+        }
+        self.refresh_locked().await
+    }
+
+    async fn refresh_locked(&self) -> Result<(), ClientError> {
+        let refresh_token = self
+            .lock_tokens()
+            .as_ref()
+            .and_then(|tokens| tokens.refresh_token.clone())
+            .ok_or(ClientError::NotLoggedIn)?;
+        let response = self
+            .http
+            .post(self.url("/refresh-token"))
+            .json(&RefreshTokenRequest { refresh_token })
+            .send()
+            .await?;
+        let refreshed: RefreshTokenResponse = decode(response).await?;
+        self.store_tokens(refreshed.access_token, Some(refreshed.refresh_token));
+        Ok(())
+    }
+}
+
+fn with_bearer(builder: RequestBuilder, token: Option<&str>) -> RequestBuilder {
+    match token {
+        Some(token) => builder.bearer_auth(token),
+        None => builder,
+    }
+}
+
+/// 解析 `ApiResponse<T>` 信封；失败响应转换为 `ClientError::Api`
+async fn decode<T: DeserializeOwned>(response: Response) -> Result<T, ClientError> {
+    let status = response.status();
+    let bytes = response.bytes().await?;
+    match serde_json::from_slice::<ApiResponse<T>>(&bytes) {
+        Ok(ApiResponse {
+            success: true,
+            data,
+            ..
+        }) => match data {
+            Some(data) => Ok(data),
+            // `ApiResponse::success(())` 序列化为 `data: null`
+            None => serde_json::from_value(serde_json::Value::Null)
+                .map_err(|err| ClientError::Decode(err.to_string())),
+        },
+        Ok(ApiResponse {
+            error: Some(error), ..
+        }) => Err(ClientError::Api {
+            status: status.as_u16(),
+            code: error.code,
+            message: error.message,
+        }),
+        Ok(_) => Err(ClientError::Decode(
+            "response without data or error".to_string(),
+        )),
+        Err(err) if status.is_success() => Err(ClientError::Decode(err.to_string())),
+        Err(_) => Err(ClientError::Api {
+            status: status.as_u16(),
+            code: String::new(),
+            message: String::from_utf8_lossy(&bytes).into_owned(),
+        }),
+    }
+}
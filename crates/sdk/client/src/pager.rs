@@ -0,0 +1,92 @@
+//! keyset 分页：按上一页最后一条记录推进游标，直到返回条数少于 limit。
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use api_contract::{CommandDto, CommandQuery, MeasurementValueDto, MeasurementsQuery};
+
+use crate::{ClientError, EmsClient};
+
+/// 历史数据默认页大小（与服务端默认一致）
+const DEFAULT_MEASUREMENT_LIMIT: i64 = 1000;
+/// 命令列表默认页大小（与服务端默认一致）
+const DEFAULT_COMMAND_LIMIT: i64 = 100;
+
+/// 历史数据分页（`GET /projects/{id}/measurements`，游标为 `cursorTsMs`）
+pub type MeasurementPager = Pager<MeasurementsQuery, MeasurementValueDto>;
+/// 命令分页（`GET /projects/{id}/commands`，游标为 `cursorTsMs` + `cursorCommandId`）
+pub type CommandPager = Pager<CommandQuery, CommandDto>;
+
+/// 通用分页器：`Q` 为查询参数，`T` 为单条记录。
+pub struct Pager<Q, T> {
+    client: EmsClient,
+    path: String,
+    query: Q,
+    limit: i64,
+    done: bool,
+    /// 以上一页最后一条记录更新查询游标
+    advance: fn(&mut Q, &T),
+}
+
+impl<Q: Serialize, T: DeserializeOwned> Pager<Q, T> {
+    /// 拉取下一页；没有更多数据时返回 `None`
+    pub async fn next_page(&mut self) -> Result<Option<Vec<T>>, ClientError> {
+        if self.done {
+            return Ok(None);
+        }
+        let page: Vec<T> = self.client.get_with_query(&self.path, &self.query).await?;
+        let Some(last) = page.last() else {
+            self.done = true;
+            return Ok(None);
+        };
+        (self.advance)(&mut self.query, last);
+        if (page.len() as i64) < self.limit {
+            self.done = true;
+        }
+        Ok(Some(page))
+    }
+
+    /// 拉取剩余全部页并合并
+    pub async fn collect_all(mut self) -> Result<Vec<T>, ClientError> {
+        let mut items = Vec::new();
+        while let Some(page) = self.next_page().await? {
+            items.extend(page);
+        }
+        Ok(items)
+    }
+}
+
+impl MeasurementPager {
+    pub(crate) fn measurements(
+        client: EmsClient,
+        project_id: &str,
+        mut query: MeasurementsQuery,
+    ) -> Self {
+        let limit = *query.limit.get_or_insert(DEFAULT_MEASUREMENT_LIMIT);
+        Self {
+            client,
+            path: format!("/projects/{project_id}/measurements"),
+            query,
+            limit,
+            done: false,
+            advance: |query, last| query.cursor_ts_ms = Some(last.ts_ms),
+        }
+    }
+}
+
+impl CommandPager {
+    pub(crate) fn commands(client: EmsClient, project_id: &str, mut query: CommandQuery) -> Self {
+        let limit = *query.limit.get_or_insert(DEFAULT_COMMAND_LIMIT);
+        Self {
+            client,
+            path: format!("/projects/{project_id}/commands"),
+            query,
+            limit,
+            done: false,
+            advance: |query, last| {
+                query.cursor_ts_ms = Some(last.issued_at_ms);
+                query.cursor_command_id = Some(last.command_id.clone());
+            },
+        }
+    }
+}
@@ -0,0 +1,74 @@
+//! 实时订阅：WebSocket `GET /projects/{id}/realtime/ws`。
+
+use futures_util::StreamExt;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::http::header::AUTHORIZATION;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use api_contract::RealtimeValueDto;
+
+use crate::ClientError;
+
+/// 实时订阅连接：服务端按间隔推送 tsMs 发生变化的点位最新值。
+pub struct RealtimeSubscription {
+    socket: WebSocketStream<MaybeTlsStream<TcpStream>>,
+}
+
+impl RealtimeSubscription {
+    /// 建立连接（`token` 以 `Authorization: Bearer` 头发送）
+    pub(crate) async fn connect(
+        url: &str,
+        token: Option<&str>,
+    ) -> Result<Self, tokio_tungstenite::tungstenite::Error> {
+        let mut request = url.into_client_request()?;
+        if let Some(token) = token {
+            let value = HeaderValue::from_str(&format!("Bearer {token}"))
+                .map_err(|err| tokio_tungstenite::tungstenite::Error::HttpFormat(err.into()))?;
+            request.headers_mut().insert(AUTHORIZATION, value);
+        }
+        let (socket, _) = tokio_tungstenite::connect_async(request).await?;
+        Ok(Self { socket })
+    }
+
+    /// 等待下一条实时值；连接正常关闭时返回 `None`，服务端异常关闭时返回错误
+    pub async fn next(&mut self) -> Option<Result<RealtimeValueDto, ClientError>> {
+        loop {
+            let message = match self.socket.next().await? {
+                Ok(message) => message,
+                Err(err) => return Some(Err(ClientError::WebSocket(err.to_string()))),
+            };
+            match message {
+                Message::Text(text) => {
+                    return Some(
+                        serde_json::from_str(&text)
+                            .map_err(|err| ClientError::Decode(err.to_string())),
+                    );
+                }
+                Message::Close(Some(frame)) if frame.code != CloseCode::Normal => {
+                    return Some(Err(ClientError::WebSocket(format!(
+                        "closed by server ({}): {}",
+                        u16::from(frame.code),
+                        frame.reason
+                    ))));
+                }
+                Message::Close(_) => return None,
+                _ => continue,
+            }
+        }
+    }
+
+    /// 主动关闭订阅
+    pub async fn close(mut self) -> Result<(), ClientError> {
+        self.socket
+            .close(None)
+            .await
+            .map_err(|err| ClientError::WebSocket(err.to_string()))?;
+        // 等待服务端确认关闭
+        while let Some(Ok(_)) = self.socket.next().await {}
+        Ok(())
+    }
+}
@@ -0,0 +1,264 @@
+use std::sync::{Arc, Mutex};
+
+use axum::extract::ws::{CloseFrame, Message, WebSocketUpgrade, close_code};
+use axum::extract::{Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use ems_client::api_contract::{
+    ApiResponse, CommandDto, CommandQuery, LoginRequest, LoginResponse, ProjectDto,
+    RealtimeStreamQuery, RealtimeValueDto, RefreshTokenRequest, RefreshTokenResponse,
+};
+use ems_client::{ClientError, EmsClient};
+
+#[derive(Default)]
+struct Mock {
+    valid_access: String,
+    refresh_calls: usize,
+}
+
+type Shared = Arc<Mutex<Mock>>;
+
+fn authorized(state: &Shared, headers: &HeaderMap) -> bool {
+    let expected = format!("Bearer {}", state.lock().unwrap().valid_access);
+    headers
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        == Some(expected.as_str())
+}
+
+fn unauthorized() -> Response {
+    (
+        StatusCode::UNAUTHORIZED,
+        Json(ApiResponse::<()>::error(
+            "AUTH.UNAUTHORIZED",
+            "token expired",
+        )),
+    )
+        .into_response()
+}
+
+async fn login(State(state): State<Shared>, Json(req): Json<LoginRequest>) -> Response {
+    if req.password != "secret" {
+        return unauthorized();
+    }
+    state.lock().unwrap().valid_access = "access-1".to_string();
+    Json(ApiResponse::success(LoginResponse {
+        access_token: "access-1".to_string(),
+        refresh_token: "refresh-1".to_string(),
+        expires: 0,
+        username: req.username.clone(),
+        nickname: req.username,
+        avatar: String::new(),
+        roles: vec!["admin".to_string()],
+        permissions: Vec::new(),
+    }))
+    .into_response()
+}
+
+async fn refresh(State(state): State<Shared>, Json(req): Json<RefreshTokenRequest>) -> Response {
+    let mut state = state.lock().unwrap();
+    state.refresh_calls += 1;
+    let access_token = format!("access-{}", state.refresh_calls + 1);
+    state.valid_access = access_token.clone();
+    Json(ApiResponse::success(RefreshTokenResponse {
+        access_token,
+        refresh_token: format!("{}-rotated", req.refresh_token),
+        expires: 0,
+    }))
+    .into_response()
+}
+
+async fn projects(State(state): State<Shared>, headers: HeaderMap) -> Response {
+    if !authorized(&state, &headers) {
+        return unauthorized();
+    }
+    Json(ApiResponse::success(vec![ProjectDto {
+        project_id: "project-1".to_string(),
+        name: "Default Project".to_string(),
+        timezone: "UTC".to_string(),
+    }]))
+    .into_response()
+}
+
+/// 5 条命令，按下发时间倒序，支持 limit + cursorTsMs 游标
+async fn commands(
+    State(state): State<Shared>,
+    headers: HeaderMap,
+    Query(query): Query<CommandQuery>,
+) -> Response {
+    if !authorized(&state, &headers) {
+        return unauthorized();
+    }
+    let data: Vec<CommandDto> = (1..=5)
+        .rev()
+        .map(|index| CommandDto {
+            command_id: format!("command-{index}"),
+            project_id: "project-1".to_string(),
+            target: "device-1".to_string(),
+            payload: serde_json::json!({"index": index}),
+            status: "issued".to_string(),
+            issued_by: "user-1".to_string(),
+            issued_at_ms: index * 100,
+            warning: None,
+        })
+        .filter(|command| {
+            query
+                .cursor_ts_ms
+                .is_none_or(|cursor| command.issued_at_ms < cursor)
+        })
+        .take(query.limit.unwrap_or(100) as usize)
+        .collect();
+    Json(ApiResponse::success(data)).into_response()
+}
+
+async fn realtime_ws(
+    State(state): State<Shared>,
+    headers: HeaderMap,
+    Query(query): Query<RealtimeStreamQuery>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    if !authorized(&state, &headers) {
+        return unauthorized();
+    }
+    let point_ids = query.point_ids.unwrap_or_default();
+    ws.on_upgrade(move |mut socket| async move {
+        for (index, point_id) in point_ids.split(',').enumerate() {
+            let value = RealtimeValueDto {
+                project_id: "project-1".to_string(),
+                point_id: point_id.to_string(),
+                ts_ms: index as i64,
+                value: "1.5".to_string(),
+                quality: None,
+            };
+            let text = serde_json::to_string(&value).unwrap();
+            let _ = socket.send(Message::Text(text)).await;
+        }
+        let _ = socket
+            .send(Message::Close(Some(CloseFrame {
+                code: close_code::ERROR,
+                reason: "realtime store unavailable".into(),
+            })))
+            .await;
+    })
+}
+
+async fn spawn_mock() -> (String, Shared) {
+    let state = Shared::default();
+    let app = Router::new()
+        .route("/api/v1/login", post(login))
+        .route("/api/v1/refresh-token", post(refresh))
+        .route("/api/v1/projects", get(projects))
+        .route("/api/v1/projects/project-1/commands", get(commands))
+        .route("/api/v1/projects/project-1/realtime/ws", get(realtime_ws))
+        .with_state(state.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind");
+    let addr = listener.local_addr().expect("addr");
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.expect("serve");
+    });
+    (format!("http://{addr}/"), state)
+}
+
+#[tokio::test]
+async fn login_and_refresh_on_unauthorized() {
+    let (base_url, state) = spawn_mock().await;
+    let client = EmsClient::new(base_url);
+
+    let err = client
+        .login("admin", "wrong")
+        .await
+        .expect_err("bad password");
+    assert_eq!(err.status(), Some(401));
+    assert_eq!(err.code(), Some("AUTH.UNAUTHORIZED"));
+
+    client.login("admin", "secret").await.expect("login");
+    assert_eq!(client.access_token().as_deref(), Some("access-1"));
+    let projects = client.list_projects().await.expect("projects");
+    assert_eq!(projects.len(), 1);
+
+    // 模拟 access token 过期：首次请求 401，刷新后重试成功
+    state.lock().unwrap().valid_access = "expired".to_string();
+    let clone = client.clone();
+    let (first, second) = tokio::join!(client.list_projects(), clone.list_projects());
+    assert!(first.is_ok() && second.is_ok());
+    // 并发 401 只触发一次刷新
+    assert_eq!(state.lock().unwrap().refresh_calls, 1);
+    assert_eq!(client.access_token().as_deref(), Some("access-2"));
+}
+
+#[tokio::test]
+async fn command_pager_follows_cursor() {
+    let (base_url, _state) = spawn_mock().await;
+    let client = EmsClient::new(base_url);
+    client.login("admin", "secret").await.expect("login");
+
+    let mut pager = client.commands(
+        "project-1",
+        CommandQuery {
+            limit: Some(2),
+            status: None,
+            target: None,
+            issued_by: None,
+            from: None,
+            to: None,
+            cursor_ts_ms: None,
+            cursor_command_id: None,
+        },
+    );
+    let mut pages = Vec::new();
+    while let Some(page) = pager.next_page().await.expect("page") {
+        pages.push(
+            page.into_iter()
+                .map(|command| command.command_id)
+                .collect::<Vec<_>>(),
+        );
+    }
+    assert_eq!(
+        pages,
+        vec![
+            vec!["command-5", "command-4"],
+            vec!["command-3", "command-2"],
+            vec!["command-1"],
+        ]
+    );
+}
+
+#[tokio::test]
+async fn realtime_subscription_reads_values_until_close() {
+    let (base_url, _state) = spawn_mock().await;
+    let client = EmsClient::new(base_url);
+
+    let err = client
+        .subscribe_realtime("project-1", &stream_query("point-1"))
+        .await
+        .err()
+        .expect("not logged in");
+    assert!(matches!(err, ClientError::WebSocket(_)));
+
+    client.login("admin", "secret").await.expect("login");
+    let mut subscription = client
+        .subscribe_realtime("project-1", &stream_query("point-1,point-2"))
+        .await
+        .expect("subscribe");
+    let first = subscription.next().await.expect("first").expect("value");
+    let second = subscription.next().await.expect("second").expect("value");
+    assert_eq!(first.point_id, "point-1");
+    assert_eq!(second.point_id, "point-2");
+    let closed = subscription.next().await.expect("close frame");
+    assert!(
+        matches!(closed, Err(ClientError::WebSocket(message)) if message.contains("unavailable"))
+    );
+}
+
+fn stream_query(point_ids: &str) -> RealtimeStreamQuery {
+    RealtimeStreamQuery {
+        point_ids: Some(point_ids.to_string()),
+        device_id: None,
+        tag: None,
+        interval_ms: None,
+    }
+}
@@ -37,6 +37,7 @@ VALUES ('PROJECT.READ', 'Read projects'),
        ('ASSET.POINT.WRITE', 'Write points'),
        ('DATA.REALTIME.READ', 'Read realtime data'),
        ('DATA.MEASUREMENTS.READ', 'Read measurements'),
+       ('DATA.WRITE', 'Write point values via API'),
        ('CONTROL.COMMAND.ISSUE', 'Issue commands'),
        ('CONTROL.COMMAND.READ', 'Read commands'),
        ('ALARM.RULE.READ', 'Read alarm rules'),
@@ -46,7 +47,25 @@ VALUES ('PROJECT.READ', 'Read projects'),
        ('RBAC.USER.WRITE', 'Write users'),
        ('RBAC.ROLE.READ', 'Read roles'),
        ('RBAC.ROLE.WRITE', 'Write roles'),
-       ('SYSTEM.METRICS.READ', 'Read metrics snapshot')
+       ('OPS.METRICS.READ', 'Read operational metrics'),
+       ('FEATURE.FLAG.READ', 'Read feature flags'),
+       ('AUTOMATION.RULE.READ', 'Read automation rules and executions'),
+       ('AUTOMATION.RULE.WRITE', 'Write automation rules'),
+       ('AUTOMATION.SCHEDULE.READ', 'Read control schedules and executions'),
+       ('AUTOMATION.SCHEDULE.WRITE', 'Write control schedules'),
+       ('CONTROL.DEMAND_RESPONSE.READ', 'Read sheddable loads and demand response events'),
+       ('CONTROL.DEMAND_RESPONSE.WRITE', 'Manage sheddable loads and demand response events'),
+       ('ASSET.FIRMWARE.READ', 'Read firmware packages and rollout campaigns'),
+       ('ASSET.FIRMWARE.WRITE', 'Upload firmware packages and start rollout campaigns'),
+       ('CARBON.FACTOR.READ', 'Read carbon emission factors'),
+       ('CARBON.FACTOR.WRITE', 'Write carbon emission factors'),
+       ('USAGE.QUOTA.READ', 'Read tenant usage and quotas'),
+       ('PORTFOLIO.READ', 'Read project portfolios and fleet overview'),
+       ('PORTFOLIO.WRITE', 'Write project portfolios'),
+       ('SHARE.TOKEN.READ', 'Read project data share tokens'),
+       ('SHARE.TOKEN.WRITE', 'Create and revoke project data share tokens'),
+       ('JOB.READ', 'Read background jobs'),
+       ('JOB.WRITE', 'Cancel background jobs')
 ON CONFLICT (permission_code) DO NOTHING;
 
 INSERT INTO user_roles (user_id, role_code)
@@ -68,6 +87,7 @@ VALUES ('admin', 'PROJECT.READ'),
        ('admin', 'ASSET.POINT.WRITE'),
        ('admin', 'DATA.REALTIME.READ'),
        ('admin', 'DATA.MEASUREMENTS.READ'),
+       ('admin', 'DATA.WRITE'),
        ('admin', 'CONTROL.COMMAND.ISSUE'),
        ('admin', 'CONTROL.COMMAND.READ'),
        ('admin', 'ALARM.RULE.READ'),
@@ -77,7 +97,25 @@ VALUES ('admin', 'PROJECT.READ'),
        ('admin', 'RBAC.USER.WRITE'),
        ('admin', 'RBAC.ROLE.READ'),
        ('admin', 'RBAC.ROLE.WRITE'),
-       ('admin', 'SYSTEM.METRICS.READ')
+       ('admin', 'OPS.METRICS.READ'),
+       ('admin', 'FEATURE.FLAG.READ'),
+       ('admin', 'AUTOMATION.RULE.READ'),
+       ('admin', 'AUTOMATION.RULE.WRITE'),
+       ('admin', 'AUTOMATION.SCHEDULE.READ'),
+       ('admin', 'AUTOMATION.SCHEDULE.WRITE'),
+       ('admin', 'CONTROL.DEMAND_RESPONSE.READ'),
+       ('admin', 'CONTROL.DEMAND_RESPONSE.WRITE'),
+       ('admin', 'ASSET.FIRMWARE.READ'),
+       ('admin', 'ASSET.FIRMWARE.WRITE'),
+       ('admin', 'CARBON.FACTOR.READ'),
+       ('admin', 'CARBON.FACTOR.WRITE'),
+       ('admin', 'USAGE.QUOTA.READ'),
+       ('admin', 'PORTFOLIO.READ'),
+       ('admin', 'PORTFOLIO.WRITE'),
+       ('admin', 'SHARE.TOKEN.READ'),
+       ('admin', 'SHARE.TOKEN.WRITE'),
+       ('admin', 'JOB.READ'),
+       ('admin', 'JOB.WRITE')
 ON CONFLICT (role_code, permission_code) DO NOTHING;
 
 -- Tenant-scoped RBAC (new tables)
@@ -106,6 +144,7 @@ CROSS JOIN (
     ('ASSET.POINT.WRITE'),
     ('DATA.REALTIME.READ'),
     ('DATA.MEASUREMENTS.READ'),
+    ('DATA.WRITE'),
     ('CONTROL.COMMAND.ISSUE'),
     ('CONTROL.COMMAND.READ'),
     ('ALARM.RULE.READ'),
@@ -115,7 +154,25 @@ CROSS JOIN (
     ('RBAC.USER.WRITE'),
     ('RBAC.ROLE.READ'),
     ('RBAC.ROLE.WRITE'),
-    ('SYSTEM.METRICS.READ')
+    ('OPS.METRICS.READ'),
+    ('FEATURE.FLAG.READ'),
+    ('AUTOMATION.RULE.READ'),
+    ('AUTOMATION.RULE.WRITE'),
+    ('AUTOMATION.SCHEDULE.READ'),
+    ('AUTOMATION.SCHEDULE.WRITE'),
+    ('CONTROL.DEMAND_RESPONSE.READ'),
+    ('CONTROL.DEMAND_RESPONSE.WRITE'),
+    ('ASSET.FIRMWARE.READ'),
+    ('ASSET.FIRMWARE.WRITE'),
+    ('CARBON.FACTOR.READ'),
+    ('CARBON.FACTOR.WRITE'),
+    ('USAGE.QUOTA.READ'),
+    ('PORTFOLIO.READ'),
+    ('PORTFOLIO.WRITE'),
+    ('SHARE.TOKEN.READ'),
+    ('SHARE.TOKEN.WRITE'),
+    ('JOB.READ'),
+    ('JOB.WRITE')
 ) p(permission_code)
 ON CONFLICT (tenant_id, role_code, permission_code) DO NOTHING;
 
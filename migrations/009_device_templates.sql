@@ -0,0 +1,36 @@
+-- EMS 设备模板（产品模型）
+-- 迁移版本：009
+-- 描述：添加设备模板及其标准点位，用于同型号设备批量接入
+
+-- ============================================================================
+-- 1. 设备模板表 (device_templates)
+-- ============================================================================
+CREATE TABLE IF NOT EXISTS device_templates (
+    template_id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL REFERENCES tenants(tenant_id),
+    project_id TEXT NOT NULL REFERENCES projects(project_id),
+    name TEXT NOT NULL,
+    model TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_device_templates_tenant_project
+    ON device_templates (tenant_id, project_id);
+
+-- ============================================================================
+-- 2. 模板点位表 (device_template_points)
+-- ============================================================================
+-- source_type/address 为映射提示：同时存在时实例化会生成 point_sources 记录
+CREATE TABLE IF NOT EXISTS device_template_points (
+    template_id TEXT NOT NULL REFERENCES device_templates(template_id) ON DELETE CASCADE,
+    ordinal INTEGER NOT NULL,
+    key TEXT NOT NULL,
+    data_type TEXT NOT NULL,
+    unit TEXT,
+    source_type TEXT,
+    address TEXT,
+    scale DOUBLE PRECISION,
+    offset_value DOUBLE PRECISION,
+    protocol_detail TEXT,
+    PRIMARY KEY (template_id, ordinal)
+);
@@ -0,0 +1,18 @@
+-- EMS 网关配置下发
+-- 迁移版本：010
+-- 描述：记录网关配置文档的版本与下发/应用状态
+
+CREATE TABLE IF NOT EXISTS gateway_configs (
+    tenant_id TEXT NOT NULL,
+    project_id TEXT NOT NULL,
+    gateway_id TEXT NOT NULL,
+    version BIGINT NOT NULL,
+    document JSONB NOT NULL,
+    -- pending | published | applied | failed
+    status TEXT NOT NULL,
+    message TEXT,
+    pushed_by TEXT NOT NULL,
+    pushed_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (tenant_id, project_id, gateway_id, version)
+);
@@ -0,0 +1,8 @@
+-- EMS 点位标签
+-- 迁移版本：011
+-- 描述：为点位增加标签，用于按标签分组查询实时数据
+
+ALTER TABLE points ADD COLUMN IF NOT EXISTS tags TEXT[] NOT NULL DEFAULT '{}';
+
+CREATE INDEX IF NOT EXISTS idx_points_tags
+    ON points USING GIN (tags);
@@ -0,0 +1,38 @@
+-- EMS Webhook 事件订阅
+-- 迁移版本：012
+-- 描述：租户按项目订阅领域事件（device.offline / command.completed / alarm.raised / gateway.created），
+--       记录每次推送的结果与重试次数
+
+CREATE TABLE IF NOT EXISTS webhook_subscriptions (
+    subscription_id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    project_id TEXT NOT NULL,
+    url TEXT NOT NULL,
+    event_types TEXT[] NOT NULL DEFAULT '{}',
+    -- HMAC-SHA256 签名密钥
+    secret TEXT NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_by TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_subscriptions_tenant_project
+    ON webhook_subscriptions (tenant_id, project_id);
+
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    delivery_id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    project_id TEXT NOT NULL,
+    subscription_id TEXT NOT NULL,
+    event_id TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+    -- success | failed
+    status TEXT NOT NULL,
+    attempts INTEGER NOT NULL,
+    response_status INTEGER,
+    error TEXT,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_tenant_project_created
+    ON webhook_deliveries (tenant_id, project_id, created_at DESC);
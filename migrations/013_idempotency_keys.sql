@@ -0,0 +1,20 @@
+-- EMS POST 幂等键
+-- 迁移版本：013
+-- 描述：按 (租户, Idempotency-Key) 记录请求摘要与首次响应，
+--       有效期内的重试直接返回首次结果，避免重复创建资源或重复下发命令
+
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    tenant_id TEXT NOT NULL,
+    idempotency_key TEXT NOT NULL,
+    -- 方法 + 路径 + 请求体的 SHA-256
+    request_hash TEXT NOT NULL,
+    -- 为空表示首个请求仍在处理中
+    status_code INTEGER,
+    response_body TEXT,
+    created_at TIMESTAMPTZ NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (tenant_id, idempotency_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_idempotency_keys_tenant_expires
+    ON idempotency_keys (tenant_id, expires_at);
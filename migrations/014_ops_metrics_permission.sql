@@ -0,0 +1,23 @@
+-- EMS 运维指标权限
+-- 迁移版本：014
+-- 描述：指标快照改由 OPS.METRICS.READ 控制，原 SYSTEM.METRICS.READ 的授权平移到新权限后移除
+
+INSERT INTO permissions (permission_code, description)
+VALUES ('OPS.METRICS.READ', 'Read operational metrics')
+ON CONFLICT (permission_code) DO NOTHING;
+
+INSERT INTO role_permissions (role_code, permission_code)
+SELECT role_code, 'OPS.METRICS.READ'
+FROM role_permissions
+WHERE permission_code = 'SYSTEM.METRICS.READ'
+ON CONFLICT (role_code, permission_code) DO NOTHING;
+
+INSERT INTO tenant_role_permissions (tenant_id, role_code, permission_code)
+SELECT tenant_id, role_code, 'OPS.METRICS.READ'
+FROM tenant_role_permissions
+WHERE permission_code = 'SYSTEM.METRICS.READ'
+ON CONFLICT (tenant_id, role_code, permission_code) DO NOTHING;
+
+DELETE FROM role_permissions WHERE permission_code = 'SYSTEM.METRICS.READ';
+DELETE FROM tenant_role_permissions WHERE permission_code = 'SYSTEM.METRICS.READ';
+DELETE FROM permissions WHERE permission_code = 'SYSTEM.METRICS.READ';
@@ -0,0 +1,19 @@
+-- EMS 运维配置快照权限
+-- 迁移版本：015
+-- 描述：新增 OPS.CONFIG.READ（读取脱敏后的生效配置），授予已拥有 OPS.METRICS.READ 的角色
+
+INSERT INTO permissions (permission_code, description)
+VALUES ('OPS.CONFIG.READ', 'Read effective configuration')
+ON CONFLICT (permission_code) DO NOTHING;
+
+INSERT INTO role_permissions (role_code, permission_code)
+SELECT role_code, 'OPS.CONFIG.READ'
+FROM role_permissions
+WHERE permission_code = 'OPS.METRICS.READ'
+ON CONFLICT (role_code, permission_code) DO NOTHING;
+
+INSERT INTO tenant_role_permissions (tenant_id, role_code, permission_code)
+SELECT tenant_id, role_code, 'OPS.CONFIG.READ'
+FROM tenant_role_permissions
+WHERE permission_code = 'OPS.METRICS.READ'
+ON CONFLICT (tenant_id, role_code, permission_code) DO NOTHING;
@@ -0,0 +1,33 @@
+-- EMS 租户功能开关
+-- 迁移版本：016
+-- 描述：按 (租户, 开关键) 保存功能开关（启用 + 可选灰度变体），
+--       未配置的开关取默认值；新增 FEATURE.FLAG.READ / FEATURE.FLAG.WRITE，授予已拥有 RBAC.ROLE.WRITE 的角色
+
+CREATE TABLE IF NOT EXISTS tenant_feature_flags (
+    tenant_id TEXT NOT NULL,
+    flag_key TEXT NOT NULL,
+    enabled BOOLEAN NOT NULL,
+    -- 可选灰度变体（如 v2），由业务自行解释
+    variant TEXT,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (tenant_id, flag_key)
+);
+
+INSERT INTO permissions (permission_code, description)
+VALUES ('FEATURE.FLAG.READ', 'Read feature flags'),
+       ('FEATURE.FLAG.WRITE', 'Write feature flags')
+ON CONFLICT (permission_code) DO NOTHING;
+
+INSERT INTO role_permissions (role_code, permission_code)
+SELECT role_code, p.permission_code
+FROM role_permissions
+CROSS JOIN (VALUES ('FEATURE.FLAG.READ'), ('FEATURE.FLAG.WRITE')) p(permission_code)
+WHERE role_permissions.permission_code = 'RBAC.ROLE.WRITE'
+ON CONFLICT (role_code, permission_code) DO NOTHING;
+
+INSERT INTO tenant_role_permissions (tenant_id, role_code, permission_code)
+SELECT tenant_id, role_code, p.permission_code
+FROM tenant_role_permissions
+CROSS JOIN (VALUES ('FEATURE.FLAG.READ'), ('FEATURE.FLAG.WRITE')) p(permission_code)
+WHERE tenant_role_permissions.permission_code = 'RBAC.ROLE.WRITE'
+ON CONFLICT (tenant_id, role_code, permission_code) DO NOTHING;
@@ -0,0 +1,16 @@
+-- EMS 设备影子
+-- 迁移版本：017
+-- 描述：按设备保存期望状态（点位 key → 期望值）与版本；上报状态由实时值推导，不落库
+
+CREATE TABLE IF NOT EXISTS device_shadows (
+    tenant_id TEXT NOT NULL,
+    project_id TEXT NOT NULL,
+    device_id TEXT NOT NULL REFERENCES devices(device_id) ON DELETE CASCADE,
+    desired JSONB NOT NULL,
+    version BIGINT NOT NULL,
+    -- 最近一次下发差量的命令（差量为空时为 NULL）
+    last_command_id TEXT,
+    updated_by TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (tenant_id, project_id, device_id)
+);
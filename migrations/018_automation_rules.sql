@@ -0,0 +1,71 @@
+-- EMS 自动化规则
+-- 迁移版本：018
+-- 描述：租户按项目定义 if-this-then-that 规则（触发条件 + 动作列表），记录每次执行结果；
+--       新增 AUTOMATION.RULE.READ / AUTOMATION.RULE.WRITE，
+--       分别授予已拥有 CONTROL.COMMAND.READ / CONTROL.COMMAND.ISSUE 的角色
+
+CREATE TABLE IF NOT EXISTS automation_rules (
+    tenant_id TEXT NOT NULL,
+    project_id TEXT NOT NULL,
+    rule_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    -- 触发条件：{"type": "point" | "schedule" | "alarm" | "device_offline", ...}
+    trigger JSONB NOT NULL,
+    -- 动作列表：[{"type": "command" | "alarm" | "webhook", ...}]
+    actions JSONB NOT NULL,
+    created_by TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (tenant_id, project_id, rule_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_automation_rules_enabled
+    ON automation_rules (enabled);
+
+CREATE TABLE IF NOT EXISTS automation_rule_executions (
+    execution_id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    project_id TEXT NOT NULL,
+    rule_id TEXT NOT NULL,
+    trigger_detail JSONB NOT NULL,
+    -- success | failed
+    status TEXT NOT NULL,
+    -- 按动作顺序的执行结果
+    results JSONB NOT NULL,
+    executed_at TIMESTAMPTZ NOT NULL,
+    FOREIGN KEY (tenant_id, project_id, rule_id)
+        REFERENCES automation_rules (tenant_id, project_id, rule_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_automation_rule_executions_rule_executed
+    ON automation_rule_executions (tenant_id, project_id, rule_id, executed_at DESC);
+
+INSERT INTO permissions (permission_code, description)
+VALUES ('AUTOMATION.RULE.READ', 'Read automation rules and executions'),
+       ('AUTOMATION.RULE.WRITE', 'Write automation rules')
+ON CONFLICT (permission_code) DO NOTHING;
+
+INSERT INTO role_permissions (role_code, permission_code)
+SELECT role_code, 'AUTOMATION.RULE.READ'
+FROM role_permissions
+WHERE permission_code = 'CONTROL.COMMAND.READ'
+ON CONFLICT (role_code, permission_code) DO NOTHING;
+
+INSERT INTO role_permissions (role_code, permission_code)
+SELECT role_code, 'AUTOMATION.RULE.WRITE'
+FROM role_permissions
+WHERE permission_code = 'CONTROL.COMMAND.ISSUE'
+ON CONFLICT (role_code, permission_code) DO NOTHING;
+
+INSERT INTO tenant_role_permissions (tenant_id, role_code, permission_code)
+SELECT tenant_id, role_code, 'AUTOMATION.RULE.READ'
+FROM tenant_role_permissions
+WHERE permission_code = 'CONTROL.COMMAND.READ'
+ON CONFLICT (tenant_id, role_code, permission_code) DO NOTHING;
+
+INSERT INTO tenant_role_permissions (tenant_id, role_code, permission_code)
+SELECT tenant_id, role_code, 'AUTOMATION.RULE.WRITE'
+FROM tenant_role_permissions
+WHERE permission_code = 'CONTROL.COMMAND.ISSUE'
+ON CONFLICT (tenant_id, role_code, permission_code) DO NOTHING;
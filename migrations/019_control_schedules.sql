@@ -0,0 +1,75 @@
+-- EMS 控制计划
+-- 迁移版本：019
+-- 描述：按 cron 表达式（项目时区）周期性下发预定义命令或设定值曲线，记录每个计划时刻的执行结果；
+--       新增 AUTOMATION.SCHEDULE.READ / AUTOMATION.SCHEDULE.WRITE，
+--       分别授予已拥有 CONTROL.COMMAND.READ / CONTROL.COMMAND.ISSUE 的角色
+
+CREATE TABLE IF NOT EXISTS control_schedules (
+    tenant_id TEXT NOT NULL,
+    project_id TEXT NOT NULL,
+    schedule_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    -- IANA 时区名，计划时刻按该时区解释（默认取项目时区）
+    timezone TEXT NOT NULL,
+    -- 计划定义：{"type": "commands" | "setpoint_profile", ...}
+    spec JSONB NOT NULL,
+    -- 错过执行时的策略：skip | run_once | run_all
+    missed_run_policy TEXT NOT NULL DEFAULT 'skip',
+    -- 执行器游标：早于该时间的计划时刻视为已处理
+    last_evaluated_at TIMESTAMPTZ NOT NULL,
+    created_by TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (tenant_id, project_id, schedule_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_control_schedules_enabled
+    ON control_schedules (enabled);
+
+CREATE TABLE IF NOT EXISTS control_schedule_executions (
+    execution_id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    project_id TEXT NOT NULL,
+    schedule_id TEXT NOT NULL,
+    scheduled_at TIMESTAMPTZ NOT NULL,
+    -- success | failed | skipped
+    status TEXT NOT NULL,
+    -- 按命令顺序的执行结果
+    results JSONB NOT NULL,
+    executed_at TIMESTAMPTZ NOT NULL,
+    FOREIGN KEY (tenant_id, project_id, schedule_id)
+        REFERENCES control_schedules (tenant_id, project_id, schedule_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_control_schedule_executions_schedule_executed
+    ON control_schedule_executions (tenant_id, project_id, schedule_id, executed_at DESC);
+
+INSERT INTO permissions (permission_code, description)
+VALUES ('AUTOMATION.SCHEDULE.READ', 'Read control schedules and executions'),
+       ('AUTOMATION.SCHEDULE.WRITE', 'Write control schedules')
+ON CONFLICT (permission_code) DO NOTHING;
+
+INSERT INTO role_permissions (role_code, permission_code)
+SELECT role_code, 'AUTOMATION.SCHEDULE.READ'
+FROM role_permissions
+WHERE permission_code = 'CONTROL.COMMAND.READ'
+ON CONFLICT (role_code, permission_code) DO NOTHING;
+
+INSERT INTO role_permissions (role_code, permission_code)
+SELECT role_code, 'AUTOMATION.SCHEDULE.WRITE'
+FROM role_permissions
+WHERE permission_code = 'CONTROL.COMMAND.ISSUE'
+ON CONFLICT (role_code, permission_code) DO NOTHING;
+
+INSERT INTO tenant_role_permissions (tenant_id, role_code, permission_code)
+SELECT tenant_id, role_code, 'AUTOMATION.SCHEDULE.READ'
+FROM tenant_role_permissions
+WHERE permission_code = 'CONTROL.COMMAND.READ'
+ON CONFLICT (tenant_id, role_code, permission_code) DO NOTHING;
+
+INSERT INTO tenant_role_permissions (tenant_id, role_code, permission_code)
+SELECT tenant_id, role_code, 'AUTOMATION.SCHEDULE.WRITE'
+FROM tenant_role_permissions
+WHERE permission_code = 'CONTROL.COMMAND.ISSUE'
+ON CONFLICT (tenant_id, role_code, permission_code) DO NOTHING;
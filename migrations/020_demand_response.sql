@@ -0,0 +1,80 @@
+-- EMS 需求响应（负荷削减）
+-- 迁移版本：020
+-- 描述：登记可削减负荷（设备 + 优先级 + 功率点位 + 削减/恢复命令），记录需求响应事件的
+--       目标削减量、时间窗口、已选负荷与实际削减量；
+--       新增 CONTROL.DEMAND_RESPONSE.READ / CONTROL.DEMAND_RESPONSE.WRITE，
+--       分别授予已拥有 CONTROL.COMMAND.READ / CONTROL.COMMAND.ISSUE 的角色
+
+CREATE TABLE IF NOT EXISTS demand_response_loads (
+    tenant_id TEXT NOT NULL,
+    project_id TEXT NOT NULL,
+    device_id TEXT NOT NULL,
+    -- 数值越小越先削减
+    priority INTEGER NOT NULL,
+    -- 设备有功功率点位（kW）
+    power_point_id TEXT NOT NULL,
+    -- 实时功率缺失时的估算值（kW）
+    rated_kw DOUBLE PRECISION NOT NULL,
+    shed_payload JSONB NOT NULL,
+    restore_payload JSONB NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (tenant_id, project_id, device_id)
+);
+
+CREATE TABLE IF NOT EXISTS demand_response_events (
+    tenant_id TEXT NOT NULL,
+    project_id TEXT NOT NULL,
+    event_id TEXT NOT NULL,
+    target_kw DOUBLE PRECISION NOT NULL,
+    start_at TIMESTAMPTZ NOT NULL,
+    end_at TIMESTAMPTZ NOT NULL,
+    -- scheduled | active | completed | cancelled | expired
+    status TEXT NOT NULL,
+    cancel_requested BOOLEAN NOT NULL DEFAULT FALSE,
+    baseline_kw DOUBLE PRECISION,
+    achieved_kw DOUBLE PRECISION,
+    -- 已选负荷：[{deviceId, priority, baselineKw, currentKw, shedCommandId, restoreCommandId, ...}]
+    loads JSONB NOT NULL DEFAULT '[]'::jsonb,
+    created_by TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (tenant_id, project_id, event_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_demand_response_events_project_start
+    ON demand_response_events (tenant_id, project_id, start_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_demand_response_events_open
+    ON demand_response_events (status)
+    WHERE status IN ('scheduled', 'active');
+
+INSERT INTO permissions (permission_code, description)
+VALUES ('CONTROL.DEMAND_RESPONSE.READ', 'Read sheddable loads and demand response events'),
+       ('CONTROL.DEMAND_RESPONSE.WRITE', 'Manage sheddable loads and demand response events')
+ON CONFLICT (permission_code) DO NOTHING;
+
+INSERT INTO role_permissions (role_code, permission_code)
+SELECT role_code, 'CONTROL.DEMAND_RESPONSE.READ'
+FROM role_permissions
+WHERE permission_code = 'CONTROL.COMMAND.READ'
+ON CONFLICT (role_code, permission_code) DO NOTHING;
+
+INSERT INTO role_permissions (role_code, permission_code)
+SELECT role_code, 'CONTROL.DEMAND_RESPONSE.WRITE'
+FROM role_permissions
+WHERE permission_code = 'CONTROL.COMMAND.ISSUE'
+ON CONFLICT (role_code, permission_code) DO NOTHING;
+
+INSERT INTO tenant_role_permissions (tenant_id, role_code, permission_code)
+SELECT tenant_id, role_code, 'CONTROL.DEMAND_RESPONSE.READ'
+FROM tenant_role_permissions
+WHERE permission_code = 'CONTROL.COMMAND.READ'
+ON CONFLICT (tenant_id, role_code, permission_code) DO NOTHING;
+
+INSERT INTO tenant_role_permissions (tenant_id, role_code, permission_code)
+SELECT tenant_id, role_code, 'CONTROL.DEMAND_RESPONSE.WRITE'
+FROM tenant_role_permissions
+WHERE permission_code = 'CONTROL.COMMAND.ISSUE'
+ON CONFLICT (tenant_id, role_code, permission_code) DO NOTHING;
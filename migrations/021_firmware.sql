@@ -0,0 +1,87 @@
+-- EMS 网关固件升级（OTA）
+-- 迁移版本：021
+-- 描述：登记固件包（元数据 + SHA-256 校验和 + 存储引用），记录升级批次及每个目标网关的
+--       升级状态与进度；
+--       新增 ASSET.FIRMWARE.READ / ASSET.FIRMWARE.WRITE，
+--       分别授予已拥有 ASSET.GATEWAY.READ / ASSET.GATEWAY.WRITE 的角色
+
+CREATE TABLE IF NOT EXISTS firmware_packages (
+    tenant_id TEXT NOT NULL,
+    project_id TEXT NOT NULL,
+    package_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    version TEXT NOT NULL,
+    -- 固件文件 SHA-256（小写十六进制）
+    checksum_sha256 TEXT NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    -- 固件文件下载地址或对象存储引用
+    storage_url TEXT NOT NULL,
+    metadata JSONB,
+    created_by TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (tenant_id, project_id, package_id),
+    UNIQUE (tenant_id, project_id, name, version)
+);
+
+CREATE TABLE IF NOT EXISTS firmware_campaigns (
+    tenant_id TEXT NOT NULL,
+    project_id TEXT NOT NULL,
+    campaign_id TEXT NOT NULL,
+    package_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    -- in_progress | completed | failed
+    status TEXT NOT NULL,
+    created_by TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (tenant_id, project_id, campaign_id),
+    FOREIGN KEY (tenant_id, project_id, package_id)
+        REFERENCES firmware_packages (tenant_id, project_id, package_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_firmware_campaigns_project_created
+    ON firmware_campaigns (tenant_id, project_id, created_at DESC);
+
+CREATE TABLE IF NOT EXISTS firmware_rollouts (
+    tenant_id TEXT NOT NULL,
+    project_id TEXT NOT NULL,
+    campaign_id TEXT NOT NULL,
+    gateway_id TEXT NOT NULL,
+    -- pending | published | downloading | installing | succeeded | failed
+    status TEXT NOT NULL,
+    progress INTEGER NOT NULL DEFAULT 0,
+    message TEXT,
+    updated_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (tenant_id, project_id, campaign_id, gateway_id),
+    FOREIGN KEY (tenant_id, project_id, campaign_id)
+        REFERENCES firmware_campaigns (tenant_id, project_id, campaign_id) ON DELETE CASCADE
+);
+
+INSERT INTO permissions (permission_code, description)
+VALUES ('ASSET.FIRMWARE.READ', 'Read firmware packages and rollout campaigns'),
+       ('ASSET.FIRMWARE.WRITE', 'Upload firmware packages and start rollout campaigns')
+ON CONFLICT (permission_code) DO NOTHING;
+
+INSERT INTO role_permissions (role_code, permission_code)
+SELECT role_code, 'ASSET.FIRMWARE.READ'
+FROM role_permissions
+WHERE permission_code = 'ASSET.GATEWAY.READ'
+ON CONFLICT (role_code, permission_code) DO NOTHING;
+
+INSERT INTO role_permissions (role_code, permission_code)
+SELECT role_code, 'ASSET.FIRMWARE.WRITE'
+FROM role_permissions
+WHERE permission_code = 'ASSET.GATEWAY.WRITE'
+ON CONFLICT (role_code, permission_code) DO NOTHING;
+
+INSERT INTO tenant_role_permissions (tenant_id, role_code, permission_code)
+SELECT tenant_id, role_code, 'ASSET.FIRMWARE.READ'
+FROM tenant_role_permissions
+WHERE permission_code = 'ASSET.GATEWAY.READ'
+ON CONFLICT (tenant_id, role_code, permission_code) DO NOTHING;
+
+INSERT INTO tenant_role_permissions (tenant_id, role_code, permission_code)
+SELECT tenant_id, role_code, 'ASSET.FIRMWARE.WRITE'
+FROM tenant_role_permissions
+WHERE permission_code = 'ASSET.GATEWAY.WRITE'
+ON CONFLICT (tenant_id, role_code, permission_code) DO NOTHING;
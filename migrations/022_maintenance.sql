@@ -0,0 +1,19 @@
+-- EMS 设备 / 网关维护模式
+-- 迁移版本：022
+-- 描述：按目标（设备或网关）保存维护窗口；窗口内抑制离线告警、拦截自动化命令，
+--       人工命令仍可下发并记录覆盖审计
+
+CREATE TABLE IF NOT EXISTS maintenance_windows (
+    tenant_id TEXT NOT NULL,
+    project_id TEXT NOT NULL,
+    -- device | gateway
+    target_type TEXT NOT NULL,
+    target_id TEXT NOT NULL,
+    starts_at TIMESTAMPTZ NOT NULL,
+    ends_at TIMESTAMPTZ NOT NULL,
+    reason TEXT,
+    created_by TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (tenant_id, project_id, target_type, target_id),
+    CHECK (ends_at > starts_at)
+);
@@ -0,0 +1,25 @@
+-- EMS 用能基线与异常检测
+-- 迁移版本：023
+-- 描述：记录点位小时值偏离周内同时段（hour-of-week）基线的异常；
+--       同一点位同一小时桶只记录一次
+
+CREATE TABLE IF NOT EXISTS anomalies (
+    anomaly_id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL,
+    project_id TEXT NOT NULL,
+    point_id TEXT NOT NULL,
+    device_id TEXT NOT NULL,
+    bucket_start TIMESTAMPTZ NOT NULL,
+    -- 0–167，项目时区周一 0 点为 0
+    hour_of_week INTEGER NOT NULL,
+    actual_value DOUBLE PRECISION NOT NULL,
+    baseline_value DOUBLE PRECISION NOT NULL,
+    baseline_samples INTEGER NOT NULL,
+    deviation_pct DOUBLE PRECISION NOT NULL,
+    threshold_pct DOUBLE PRECISION NOT NULL,
+    detected_at TIMESTAMPTZ NOT NULL,
+    UNIQUE (tenant_id, project_id, point_id, bucket_start)
+);
+
+CREATE INDEX IF NOT EXISTS idx_anomalies_project_bucket
+    ON anomalies (tenant_id, project_id, bucket_start DESC);
@@ -0,0 +1,45 @@
+-- EMS 碳排放因子
+-- 迁移版本：024
+-- 描述：按 (租户, 项目, 能源类型) 保存碳排放因子（kgCO₂e / 单位），project_id 为空串表示租户默认值；
+--       新增 CARBON.FACTOR.READ / CARBON.FACTOR.WRITE，授予已拥有 PROJECT.READ / PROJECT.WRITE 的角色
+
+CREATE TABLE IF NOT EXISTS emission_factors (
+    tenant_id TEXT NOT NULL,
+    -- 空串表示租户默认值，非空为项目覆盖
+    project_id TEXT NOT NULL DEFAULT '',
+    -- electricity | gas | diesel
+    energy_source TEXT NOT NULL,
+    kg_co2e_per_unit DOUBLE PRECISION NOT NULL CHECK (kg_co2e_per_unit >= 0),
+    unit TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (tenant_id, project_id, energy_source)
+);
+
+INSERT INTO permissions (permission_code, description)
+VALUES ('CARBON.FACTOR.READ', 'Read carbon emission factors'),
+       ('CARBON.FACTOR.WRITE', 'Write carbon emission factors')
+ON CONFLICT (permission_code) DO NOTHING;
+
+INSERT INTO role_permissions (role_code, permission_code)
+SELECT role_code, 'CARBON.FACTOR.READ'
+FROM role_permissions
+WHERE permission_code = 'PROJECT.READ'
+ON CONFLICT (role_code, permission_code) DO NOTHING;
+
+INSERT INTO role_permissions (role_code, permission_code)
+SELECT role_code, 'CARBON.FACTOR.WRITE'
+FROM role_permissions
+WHERE permission_code = 'PROJECT.WRITE'
+ON CONFLICT (role_code, permission_code) DO NOTHING;
+
+INSERT INTO tenant_role_permissions (tenant_id, role_code, permission_code)
+SELECT tenant_id, role_code, 'CARBON.FACTOR.READ'
+FROM tenant_role_permissions
+WHERE permission_code = 'PROJECT.READ'
+ON CONFLICT (tenant_id, role_code, permission_code) DO NOTHING;
+
+INSERT INTO tenant_role_permissions (tenant_id, role_code, permission_code)
+SELECT tenant_id, role_code, 'CARBON.FACTOR.WRITE'
+FROM tenant_role_permissions
+WHERE permission_code = 'PROJECT.WRITE'
+ON CONFLICT (tenant_id, role_code, permission_code) DO NOTHING;
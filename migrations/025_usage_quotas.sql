@@ -0,0 +1,42 @@
+-- EMS 租户用量计量与配额
+-- 迁移版本：025
+-- 描述：按 (租户, 指标, 自然日) 累计用量（API 调用、命令、测量值写入），按 (租户, 指标) 保存配额上限，
+--       未配置的指标不限制；新增 USAGE.QUOTA.READ / USAGE.QUOTA.WRITE，授予已拥有 RBAC.ROLE.WRITE 的角色
+
+CREATE TABLE IF NOT EXISTS tenant_usage (
+    tenant_id TEXT NOT NULL,
+    -- api_calls | commands | measurements
+    metric TEXT NOT NULL,
+    -- UTC 自然日起始时刻
+    period_start TIMESTAMPTZ NOT NULL,
+    count BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (tenant_id, metric, period_start)
+);
+
+CREATE TABLE IF NOT EXISTS tenant_quotas (
+    tenant_id TEXT NOT NULL,
+    -- api_calls | commands | measurements（每日上限）| points（总数上限）
+    metric TEXT NOT NULL,
+    limit_value BIGINT NOT NULL CHECK (limit_value >= 0),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (tenant_id, metric)
+);
+
+INSERT INTO permissions (permission_code, description)
+VALUES ('USAGE.QUOTA.READ', 'Read tenant usage and quotas'),
+       ('USAGE.QUOTA.WRITE', 'Write tenant quotas')
+ON CONFLICT (permission_code) DO NOTHING;
+
+INSERT INTO role_permissions (role_code, permission_code)
+SELECT role_code, p.permission_code
+FROM role_permissions
+CROSS JOIN (VALUES ('USAGE.QUOTA.READ'), ('USAGE.QUOTA.WRITE')) p(permission_code)
+WHERE role_permissions.permission_code = 'RBAC.ROLE.WRITE'
+ON CONFLICT (role_code, permission_code) DO NOTHING;
+
+INSERT INTO tenant_role_permissions (tenant_id, role_code, permission_code)
+SELECT tenant_id, role_code, p.permission_code
+FROM tenant_role_permissions
+CROSS JOIN (VALUES ('USAGE.QUOTA.READ'), ('USAGE.QUOTA.WRITE')) p(permission_code)
+WHERE tenant_role_permissions.permission_code = 'RBAC.ROLE.WRITE'
+ON CONFLICT (tenant_id, role_code, permission_code) DO NOTHING;
@@ -0,0 +1,57 @@
+-- EMS 项目组合（多站点分组）
+-- 迁移版本：026
+-- 描述：按租户保存项目组合及其成员项目（保持加入顺序），删除组合或项目时成员关系级联删除；
+--       新增 PORTFOLIO.READ / PORTFOLIO.WRITE，授予已拥有 PROJECT.READ / PROJECT.WRITE 的角色
+
+CREATE TABLE IF NOT EXISTS portfolios (
+    tenant_id TEXT NOT NULL REFERENCES tenants(tenant_id),
+    portfolio_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (tenant_id, portfolio_id)
+);
+
+CREATE TABLE IF NOT EXISTS portfolio_projects (
+    tenant_id TEXT NOT NULL,
+    portfolio_id TEXT NOT NULL,
+    project_id TEXT NOT NULL REFERENCES projects(project_id) ON DELETE CASCADE,
+    -- 加入顺序
+    ordinal INT NOT NULL,
+    PRIMARY KEY (tenant_id, portfolio_id, project_id),
+    FOREIGN KEY (tenant_id, portfolio_id)
+        REFERENCES portfolios(tenant_id, portfolio_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_portfolio_projects_project
+    ON portfolio_projects (project_id);
+
+INSERT INTO permissions (permission_code, description)
+VALUES ('PORTFOLIO.READ', 'Read project portfolios and fleet overview'),
+       ('PORTFOLIO.WRITE', 'Write project portfolios')
+ON CONFLICT (permission_code) DO NOTHING;
+
+INSERT INTO role_permissions (role_code, permission_code)
+SELECT role_code, 'PORTFOLIO.READ'
+FROM role_permissions
+WHERE permission_code = 'PROJECT.READ'
+ON CONFLICT (role_code, permission_code) DO NOTHING;
+
+INSERT INTO role_permissions (role_code, permission_code)
+SELECT role_code, 'PORTFOLIO.WRITE'
+FROM role_permissions
+WHERE permission_code = 'PROJECT.WRITE'
+ON CONFLICT (role_code, permission_code) DO NOTHING;
+
+INSERT INTO tenant_role_permissions (tenant_id, role_code, permission_code)
+SELECT tenant_id, role_code, 'PORTFOLIO.READ'
+FROM tenant_role_permissions
+WHERE permission_code = 'PROJECT.READ'
+ON CONFLICT (tenant_id, role_code, permission_code) DO NOTHING;
+
+INSERT INTO tenant_role_permissions (tenant_id, role_code, permission_code)
+SELECT tenant_id, role_code, 'PORTFOLIO.WRITE'
+FROM tenant_role_permissions
+WHERE permission_code = 'PROJECT.WRITE'
+ON CONFLICT (tenant_id, role_code, permission_code) DO NOTHING;
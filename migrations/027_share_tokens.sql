@@ -0,0 +1,51 @@
+-- EMS 只读数据分享令牌
+-- 迁移版本：027
+-- 描述：分享令牌绑定单个项目，按范围（realtime / measurements）授予只读数据访问，带过期时间、可撤销；
+--       仅保存令牌 SHA-256 摘要；新增 SHARE.TOKEN.READ / SHARE.TOKEN.WRITE，授予已拥有 PROJECT.WRITE 的角色
+
+CREATE TABLE IF NOT EXISTS share_tokens (
+    token_id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL REFERENCES tenants(tenant_id),
+    project_id TEXT NOT NULL REFERENCES projects(project_id) ON DELETE CASCADE,
+    -- 令牌明文的 SHA-256（十六进制）
+    token_hash TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    -- realtime | measurements
+    scopes TEXT[] NOT NULL,
+    created_by TEXT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_share_tokens_project
+    ON share_tokens (tenant_id, project_id, created_at DESC);
+
+INSERT INTO permissions (permission_code, description)
+VALUES ('SHARE.TOKEN.READ', 'Read project data share tokens'),
+       ('SHARE.TOKEN.WRITE', 'Create and revoke project data share tokens')
+ON CONFLICT (permission_code) DO NOTHING;
+
+INSERT INTO role_permissions (role_code, permission_code)
+SELECT role_code, 'SHARE.TOKEN.READ'
+FROM role_permissions
+WHERE permission_code = 'PROJECT.WRITE'
+ON CONFLICT (role_code, permission_code) DO NOTHING;
+
+INSERT INTO role_permissions (role_code, permission_code)
+SELECT role_code, 'SHARE.TOKEN.WRITE'
+FROM role_permissions
+WHERE permission_code = 'PROJECT.WRITE'
+ON CONFLICT (role_code, permission_code) DO NOTHING;
+
+INSERT INTO tenant_role_permissions (tenant_id, role_code, permission_code)
+SELECT tenant_id, role_code, 'SHARE.TOKEN.READ'
+FROM tenant_role_permissions
+WHERE permission_code = 'PROJECT.WRITE'
+ON CONFLICT (tenant_id, role_code, permission_code) DO NOTHING;
+
+INSERT INTO tenant_role_permissions (tenant_id, role_code, permission_code)
+SELECT tenant_id, role_code, 'SHARE.TOKEN.WRITE'
+FROM tenant_role_permissions
+WHERE permission_code = 'PROJECT.WRITE'
+ON CONFLICT (tenant_id, role_code, permission_code) DO NOTHING;
@@ -0,0 +1,20 @@
+-- EMS 点位值写入权限
+-- 迁移版本：028
+-- 描述：新增 DATA.WRITE（POST /projects/{id}/points/values，供可信集成直接写入点位值），
+--       授予已拥有 ASSET.POINT.WRITE 的角色
+
+INSERT INTO permissions (permission_code, description)
+VALUES ('DATA.WRITE', 'Write point values via API')
+ON CONFLICT (permission_code) DO NOTHING;
+
+INSERT INTO role_permissions (role_code, permission_code)
+SELECT role_code, 'DATA.WRITE'
+FROM role_permissions
+WHERE permission_code = 'ASSET.POINT.WRITE'
+ON CONFLICT (role_code, permission_code) DO NOTHING;
+
+INSERT INTO tenant_role_permissions (tenant_id, role_code, permission_code)
+SELECT tenant_id, role_code, 'DATA.WRITE'
+FROM tenant_role_permissions
+WHERE permission_code = 'ASSET.POINT.WRITE'
+ON CONFLICT (tenant_id, role_code, permission_code) DO NOTHING;
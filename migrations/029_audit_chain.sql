@@ -0,0 +1,12 @@
+-- EMS 审计日志哈希链
+-- 迁移版本：029
+-- 描述：审计日志按租户分配连续序号 seq，hash = SHA-256(JSON[seq, prev_hash, 记录字段])，
+--       prev_hash 指向同租户上一条记录的 hash；迁移前已有记录 seq 为空，不参与校验
+
+ALTER TABLE audit_logs ADD COLUMN IF NOT EXISTS seq BIGINT;
+ALTER TABLE audit_logs ADD COLUMN IF NOT EXISTS prev_hash TEXT;
+ALTER TABLE audit_logs ADD COLUMN IF NOT EXISTS hash TEXT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_audit_logs_tenant_seq
+    ON audit_logs (tenant_id, seq)
+    WHERE seq IS NOT NULL;
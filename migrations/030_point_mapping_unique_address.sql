@@ -0,0 +1,25 @@
+-- EMS 点位映射地址唯一约束
+-- 迁移版本：030
+-- 描述：同一项目内 (source_type, address) 只能对应一个点位映射，避免规范化结果不确定。
+--       若已存在重复映射则跳过建索引并给出提示：先通过
+--       GET /projects/{project_id}/point-mappings/duplicates 查出重复项并清理，再重新执行迁移。
+
+DO $$
+DECLARE
+    duplicate_count BIGINT;
+BEGIN
+    SELECT count(*) INTO duplicate_count
+    FROM (
+        SELECT 1
+        FROM point_sources
+        GROUP BY tenant_id, project_id, source_type, address
+        HAVING count(*) > 1
+    ) duplicates;
+
+    IF duplicate_count = 0 THEN
+        CREATE UNIQUE INDEX IF NOT EXISTS uq_point_sources_project_address
+            ON point_sources (tenant_id, project_id, source_type, address);
+    ELSE
+        RAISE NOTICE 'point_sources has % duplicated (source_type, address) groups; unique index skipped', duplicate_count;
+    END IF;
+END$$;
@@ -0,0 +1,17 @@
+-- EMS 设备离线判定阈值
+-- 迁移版本：031
+-- 描述：离线检测任务按设备阈值判定离线（超过阈值未上报即发布 device.offline），
+--       为空时使用全局默认值（EMS_PRESENCE_OFFLINE_AFTER_SECONDS）。
+
+ALTER TABLE devices ADD COLUMN IF NOT EXISTS offline_after_seconds BIGINT;
+
+DO $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_constraint WHERE conname = 'ck_devices_offline_after_seconds'
+    ) THEN
+        ALTER TABLE devices
+            ADD CONSTRAINT ck_devices_offline_after_seconds
+            CHECK (offline_after_seconds IS NULL OR offline_after_seconds > 0);
+    END IF;
+END $$;
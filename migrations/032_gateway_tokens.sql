@@ -0,0 +1,10 @@
+-- EMS 网关回调令牌
+-- 迁移版本：032
+-- 描述：只能经 HTTPS 回调的网关 / 设备使用网关令牌写入命令回执；
+--       只保存令牌摘要（SHA-256），为空表示未签发或已吊销。
+
+ALTER TABLE gateways ADD COLUMN IF NOT EXISTS token_hash TEXT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS uq_gateways_token_hash
+    ON gateways (token_hash)
+    WHERE token_hash IS NOT NULL;
@@ -0,0 +1,20 @@
+-- EMS 设备时间线
+-- 迁移版本：033
+-- 描述：记录与设备相关的领域事件（创建、配置变更、上下线、命令下发 / 完成、告警），
+--       事件 ID 与领域事件一致，同一设备同一事件只记录一次；按发生时间倒序分页查询
+
+CREATE TABLE IF NOT EXISTS device_events (
+    tenant_id TEXT NOT NULL,
+    project_id TEXT NOT NULL,
+    device_id TEXT NOT NULL,
+    event_id TEXT NOT NULL,
+    -- device.created | device.updated | device.online | device.offline
+    -- | command.issued | command.completed | alarm.raised
+    event_type TEXT NOT NULL,
+    occurred_at TIMESTAMPTZ NOT NULL,
+    data JSONB NOT NULL DEFAULT '{}'::jsonb,
+    PRIMARY KEY (tenant_id, device_id, event_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_device_events_device_time
+    ON device_events (tenant_id, project_id, device_id, occurred_at DESC, event_id DESC);
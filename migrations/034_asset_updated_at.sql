@@ -0,0 +1,10 @@
+-- EMS 资产变更时间
+-- 迁移版本：034
+-- 描述：资产列表按 count + max(updated_at) 计算弱 ETag，未变更时返回 304；
+--       新建行取默认值，更新语句显式刷新 updated_at，删除由条数变化体现。
+
+ALTER TABLE projects ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ NOT NULL DEFAULT now();
+ALTER TABLE gateways ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ NOT NULL DEFAULT now();
+ALTER TABLE devices ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ NOT NULL DEFAULT now();
+ALTER TABLE points ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ NOT NULL DEFAULT now();
+ALTER TABLE point_sources ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ NOT NULL DEFAULT now();
@@ -0,0 +1,63 @@
+-- EMS 后台任务
+-- 迁移版本：035
+-- 描述：长耗时操作（报表、清理、导入、汇总等）以后台任务异步执行，记录状态
+--       （queued / running / succeeded / failed / cancelled）、进度、结果与错误；
+--       新增 JOB.READ（授予已拥有 PROJECT.READ 的角色）/ JOB.WRITE（授予已拥有 PROJECT.WRITE 的角色）
+
+CREATE TABLE IF NOT EXISTS jobs (
+    job_id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL REFERENCES tenants(tenant_id),
+    -- 租户级任务为空
+    project_id TEXT REFERENCES projects(project_id) ON DELETE CASCADE,
+    -- 任务类型（如 report.power_quality）
+    kind TEXT NOT NULL,
+    -- queued | running | succeeded | failed | cancelled
+    status TEXT NOT NULL,
+    params JSONB NOT NULL DEFAULT '{}'::jsonb,
+    -- 0-100
+    progress INTEGER NOT NULL DEFAULT 0,
+    result JSONB,
+    error TEXT,
+    cancel_requested BOOLEAN NOT NULL DEFAULT false,
+    created_by TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    started_at TIMESTAMPTZ,
+    finished_at TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_jobs_tenant_created
+    ON jobs (tenant_id, created_at DESC, job_id DESC);
+
+CREATE INDEX IF NOT EXISTS idx_jobs_unfinished
+    ON jobs (status)
+    WHERE status IN ('queued', 'running');
+
+INSERT INTO permissions (permission_code, description)
+VALUES ('JOB.READ', 'Read background jobs'),
+       ('JOB.WRITE', 'Cancel background jobs')
+ON CONFLICT (permission_code) DO NOTHING;
+
+INSERT INTO role_permissions (role_code, permission_code)
+SELECT role_code, 'JOB.READ'
+FROM role_permissions
+WHERE permission_code = 'PROJECT.READ'
+ON CONFLICT (role_code, permission_code) DO NOTHING;
+
+INSERT INTO role_permissions (role_code, permission_code)
+SELECT role_code, 'JOB.WRITE'
+FROM role_permissions
+WHERE permission_code = 'PROJECT.WRITE'
+ON CONFLICT (role_code, permission_code) DO NOTHING;
+
+INSERT INTO tenant_role_permissions (tenant_id, role_code, permission_code)
+SELECT tenant_id, role_code, 'JOB.READ'
+FROM tenant_role_permissions
+WHERE permission_code = 'PROJECT.READ'
+ON CONFLICT (tenant_id, role_code, permission_code) DO NOTHING;
+
+INSERT INTO tenant_role_permissions (tenant_id, role_code, permission_code)
+SELECT tenant_id, role_code, 'JOB.WRITE'
+FROM tenant_role_permissions
+WHERE permission_code = 'PROJECT.WRITE'
+ON CONFLICT (tenant_id, role_code, permission_code) DO NOTHING;
@@ -0,0 +1,7 @@
+-- EMS 网关命令载荷格式
+-- 迁移版本：036
+-- 描述：按网关配置命令下发的载荷格式（envelope / raw / template / hex），
+--       JSON 文本如 {"format":"hex","template":"01 06 {payload.register:u16} {payload.value:u16}","checksum":"crc16_modbus"}；
+--       为空表示默认 JSON 信封。
+
+ALTER TABLE gateways ADD COLUMN IF NOT EXISTS command_payload_format TEXT;
@@ -0,0 +1,34 @@
+-- EMS 测量值去重主键
+-- 迁移版本：037
+-- 描述：measurement 以 (tenant_id, project_id, point_id, ts) 为主键，重复写入按 EMS_MEASUREMENT_DEDUP 策略
+--       （ignore / overwrite / keep_best_quality）经 ON CONFLICT 处理；
+--       加主键前清理已有重复行，每组保留质量最好的一行（good 或未标注 > 其他 > bad）。
+
+DELETE FROM measurement m
+USING (
+    SELECT tableoid, ctid,
+        row_number() OVER (
+            PARTITION BY tenant_id, project_id, point_id, ts
+            ORDER BY
+                CASE
+                    WHEN coalesce(lower(quality), 'good') = 'good' THEN 2
+                    WHEN lower(quality) = 'bad' THEN 0
+                    ELSE 1
+                END DESC
+        ) AS rn
+    FROM measurement
+) duplicates
+WHERE m.tableoid = duplicates.tableoid
+    AND m.ctid = duplicates.ctid
+    AND duplicates.rn > 1;
+
+DO $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_constraint
+        WHERE conrelid = 'measurement'::regclass AND contype = 'p'
+    ) THEN
+        ALTER TABLE measurement
+            ADD CONSTRAINT measurement_pkey PRIMARY KEY (tenant_id, project_id, point_id, ts);
+    END IF;
+END $$;
@@ -0,0 +1,6 @@
+-- EMS 幂等键响应头
+-- 迁移版本：038
+-- 描述：记录首次响应的响应头（JSON 数组 [[name, value], ...]），
+--       重放时恢复 Location / ETag / Retry-After 等头，与首次响应一致
+
+ALTER TABLE idempotency_keys ADD COLUMN IF NOT EXISTS response_headers TEXT;
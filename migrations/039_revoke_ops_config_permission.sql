@@ -0,0 +1,7 @@
+-- EMS 运维配置快照权限回收
+-- 迁移版本：039
+-- 描述：生效配置快照改为只在运维监听（EMS_OPS_ADDR）提供，移除租户角色上的 OPS.CONFIG.READ
+
+DELETE FROM role_permissions WHERE permission_code = 'OPS.CONFIG.READ';
+DELETE FROM tenant_role_permissions WHERE permission_code = 'OPS.CONFIG.READ';
+DELETE FROM permissions WHERE permission_code = 'OPS.CONFIG.READ';
@@ -0,0 +1,7 @@
+-- EMS 功能开关写权限回收
+-- 迁移版本：040
+-- 描述：功能开关改由平台运维在运维监听（EMS_OPS_ADDR）按租户设置，租户角色只保留 FEATURE.FLAG.READ
+
+DELETE FROM role_permissions WHERE permission_code = 'FEATURE.FLAG.WRITE';
+DELETE FROM tenant_role_permissions WHERE permission_code = 'FEATURE.FLAG.WRITE';
+DELETE FROM permissions WHERE permission_code = 'FEATURE.FLAG.WRITE';
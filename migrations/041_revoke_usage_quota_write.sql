@@ -0,0 +1,7 @@
+-- EMS 配额写权限回收
+-- 迁移版本：041
+-- 描述：租户配额改由平台运维在运维监听（EMS_OPS_ADDR）按租户设置，租户角色只保留 USAGE.QUOTA.READ
+
+DELETE FROM role_permissions WHERE permission_code = 'USAGE.QUOTA.WRITE';
+DELETE FROM tenant_role_permissions WHERE permission_code = 'USAGE.QUOTA.WRITE';
+DELETE FROM permissions WHERE permission_code = 'USAGE.QUOTA.WRITE';
@@ -12,6 +12,40 @@ psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/004_timescale.sql
 psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/005_control.sql
 psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/006_rbac.sql
 psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/007_auth_sessions.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/008_building_hierarchy_protocol.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/009_device_templates.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/010_gateway_configs.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/011_point_tags.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/012_webhooks.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/013_idempotency_keys.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/014_ops_metrics_permission.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/015_ops_config_permission.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/016_feature_flags.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/017_device_shadows.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/018_automation_rules.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/019_control_schedules.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/020_demand_response.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/021_firmware.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/022_maintenance.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/023_anomalies.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/024_emission_factors.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/025_usage_quotas.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/026_portfolios.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/027_share_tokens.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/028_data_write_permission.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/029_audit_chain.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/030_point_mapping_unique_address.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/031_device_offline_threshold.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/032_gateway_tokens.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/033_device_events.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/034_asset_updated_at.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/035_jobs.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/036_gateway_command_format.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/037_measurement_primary_key.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/038_idempotency_response_headers.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/039_revoke_ops_config_permission.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/040_revoke_feature_flag_write.sql
+psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/041_revoke_usage_quota_write.sql
 psql "$EMS_DATABASE_URL" -v ON_ERROR_STOP=1 -f migrations/002_seed.sql
 
 require_timescale="${EMS_REQUIRE_TIMESCALE:-}"